@@ -13,6 +13,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 /// Firebase public keys cache
@@ -24,6 +25,8 @@ pub struct FirebaseAuth {
     client: Client,
     /// Firebase project ID
     project_id: String,
+    /// Key for admin impersonation tokens (impersonation tokens rejected when unset)
+    impersonation_secret: Option<String>,
 }
 
 /// JWT Claims from Firebase ID token
@@ -86,16 +89,31 @@ impl FirebaseAuth {
             keys: Arc::new(RwLock::new(HashMap::new())),
             client: Client::new(),
             project_id,
+            impersonation_secret: None,
         }
     }
 
+    /// Also accept admin impersonation tokens signed with `secret`
+    pub fn with_impersonation_secret(mut self, secret: Option<String>) -> Self {
+        self.impersonation_secret = secret;
+        self
+    }
+
     /// Fetch public keys from Google
     /// URL: https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com
     /// Or JWK: https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com
-    pub async fn refresh_keys(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Returns how long the keys may be cached (Cache-Control max-age, default 1 hour).
+    pub async fn refresh_keys(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
         let url = "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
 
-        let response: GoogleKeys = self.client.get(url).send().await?.json().await?;
+        let http_response = self.client.get(url).send().await?.error_for_status()?;
+        let max_age = http_response
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_max_age)
+            .unwrap_or(Duration::from_secs(3600));
+        let response: GoogleKeys = http_response.json().await?;
 
         let mut keys = self.keys.write().await;
         keys.clear();
@@ -108,8 +126,8 @@ impl FirebaseAuth {
             }
         }
 
-        tracing::info!("Refreshed {} Firebase public keys", keys.len());
-        Ok(())
+        tracing::info!("Refreshed {} Firebase public keys (max-age {}s)", keys.len(), max_age.as_secs());
+        Ok(max_age)
     }
 
     /// Verify a Firebase ID token and extract the user ID and name
@@ -152,6 +170,15 @@ impl FirebaseAuth {
     }
 }
 
+/// Parse `max-age` from a Cache-Control header value
+fn parse_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|secs| secs.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
 /// Authenticated user extractor for Axum
 /// Usage: async fn handler(user: AuthUser) -> impl IntoResponse { ... }
 #[derive(Debug, Clone)]
@@ -159,6 +186,8 @@ pub struct AuthUser {
     pub uid: String,
     pub name: Option<String>,
     pub email: Option<String>,
+    /// Admin uid when the request uses an impersonation token for `uid`
+    pub impersonated_by: Option<String>,
 }
 
 /// Extension to store Firebase auth in request
@@ -200,10 +229,25 @@ where
                 message: "Firebase auth not configured".to_string(),
             })?;
 
+        // Admin impersonation token (see crate::impersonation)
+        if crate::impersonation::is_impersonation_token(token) {
+            let secret = firebase_auth.0.impersonation_secret.as_deref().ok_or_else(|| AuthError {
+                error: "invalid_token".to_string(),
+                message: "Impersonation is not enabled".to_string(),
+            })?;
+            let claims = crate::impersonation::verify_token(secret, token)?;
+            return Ok(AuthUser {
+                uid: claims.sub,
+                name: None,
+                email: None,
+                impersonated_by: Some(claims.act),
+            });
+        }
+
         // Verify token
         let (uid, name, email) = firebase_auth.0.verify_token(token).await?;
 
-        Ok(AuthUser { uid, name, email })
+        Ok(AuthUser { uid, name, email, impersonated_by: None })
     }
 }
 
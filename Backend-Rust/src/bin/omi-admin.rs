@@ -0,0 +1,429 @@
+// OMI admin CLI - operational tasks against Firestore, using the backend's own services
+// Usage: omi-admin <command> [args] [--dry-run]
+// Reads the same environment (.env) as the API server.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use omi_desktop_backend::config::Config;
+use omi_desktop_backend::llm::{LlmClient, TaskKind};
+use omi_desktop_backend::models::{ConversationEditField, TranscriptSegment};
+use omi_desktop_backend::routes::updates::ReleaseInfo;
+use omi_desktop_backend::services::firestore::{
+    ACTION_ITEMS_SUBCOLLECTION, ADVICE_SUBCOLLECTION, CHAT_SESSIONS_SUBCOLLECTION,
+    CONVERSATIONS_SUBCOLLECTION, DEVICES_SUBCOLLECTION, FOCUS_SESSIONS_SUBCOLLECTION,
+    FOLDERS_SUBCOLLECTION, GOALS_SUBCOLLECTION, MEMORIES_SUBCOLLECTION, MEMOS_SUBCOLLECTION,
+    MESSAGES_SUBCOLLECTION, PEOPLE_SUBCOLLECTION, STAGED_TASKS_SUBCOLLECTION,
+    USER_WEBHOOKS_SUBCOLLECTION,
+};
+use omi_desktop_backend::services::migrations::{self, RunOptions, MIGRATIONS};
+use omi_desktop_backend::services::{conversation_analytics, conversation_history, language, FirestoreService};
+
+type AdminResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+const USAGE: &str = "omi-admin - operational tasks for the OMI backend
+
+USAGE:
+    omi-admin <command> [args] [--dry-run]
+
+COMMANDS:
+    counts <uid>
+        Show document counts for a user's collections
+    backfill-conversations <uid> [--limit N]
+        Fill missing detected_languages and analytics on a user's conversations
+    restructure <uid> <conversation_id>
+        Re-run LLM structuring (title, overview, emoji, category) for a conversation
+    publish-release <version> <build_number> <download_url> <ed_signature>
+                    [--changelog \"a;b\"] [--channel staging|beta|stable] [--critical] [--not-live]
+        Publish a desktop release to the appcast
+    grant-entitlement <uid> <entitlement>
+        Grant an entitlement to a user
+    migrate list
+        Show schema migrations and their progress
+    migrate run <migration_id> [--limit N] [--restart]
+        Run or resume a schema migration
+
+FLAGS:
+    --dry-run    Show what would change without writing anything";
+
+/// Subcollections reported by `counts`
+const COUNTED_COLLECTIONS: &[&str] = &[
+    CONVERSATIONS_SUBCOLLECTION,
+    ACTION_ITEMS_SUBCOLLECTION,
+    STAGED_TASKS_SUBCOLLECTION,
+    MEMORIES_SUBCOLLECTION,
+    MEMOS_SUBCOLLECTION,
+    GOALS_SUBCOLLECTION,
+    FOLDERS_SUBCOLLECTION,
+    PEOPLE_SUBCOLLECTION,
+    CHAT_SESSIONS_SUBCOLLECTION,
+    MESSAGES_SUBCOLLECTION,
+    FOCUS_SESSIONS_SUBCOLLECTION,
+    ADVICE_SUBCOLLECTION,
+    DEVICES_SUBCOLLECTION,
+    USER_WEBHOOKS_SUBCOLLECTION,
+];
+
+/// Conversations fetched per page while backfilling
+const BACKFILL_PAGE_SIZE: usize = 100;
+
+/// Parsed command line: positional args, `--flag value` options and bare `--switches`
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+/// Flags that take a value
+const VALUE_FLAGS: &[&str] = &["--limit", "--changelog", "--channel"];
+
+impl Args {
+    fn parse(raw: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = Args {
+            positional: vec![],
+            options: HashMap::new(),
+            switches: vec![],
+        };
+        let mut raw = raw.peekable();
+        while let Some(arg) = raw.next() {
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                let value = raw.next().ok_or(format!("{} needs a value", arg))?;
+                args.options.insert(arg, value);
+            } else if arg.starts_with("--") {
+                args.switches.push(arg);
+            } else {
+                args.positional.push(arg);
+            }
+        }
+        Ok(args)
+    }
+
+    fn has(&self, switch: &str) -> bool {
+        self.switches.iter().any(|s| s == switch)
+    }
+
+    fn dry_run(&self) -> bool {
+        self.has("--dry-run")
+    }
+
+    /// Positional argument `index` (0 = command)
+    fn arg(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or(format!("missing <{}>\n\n{}", name, USAGE))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "omi_desktop_backend=warn".into()),
+        )
+        .with_target(false)
+        .init();
+    dotenvy::dotenv().ok();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => exit_with(&e),
+    };
+    let Some(command) = args.positional.first().cloned() else {
+        println!("{}", USAGE);
+        return;
+    };
+
+    let config = Config::from_env();
+    let firestore = match FirestoreService::new(
+        config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string()),
+        config.encryption_secret.clone(),
+    )
+    .await
+    {
+        Ok(fs) => fs,
+        Err(e) => exit_with(&format!("Failed to initialize Firestore: {}", e)),
+    };
+
+    if args.dry_run() {
+        println!("(dry run - nothing will be written)");
+    }
+
+    let result = match command.as_str() {
+        "counts" => counts(&firestore, &args).await,
+        "backfill-conversations" => backfill_conversations(&firestore, &args).await,
+        "restructure" => restructure(&firestore, &config, &args).await,
+        "publish-release" => publish_release(&firestore, &args).await,
+        "grant-entitlement" => grant_entitlement(&firestore, &args).await,
+        "migrate" => migrate(&firestore, &args).await,
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command '{}'\n\n{}", other, USAGE).into()),
+    };
+
+    if let Err(e) = result {
+        exit_with(&e.to_string());
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
+}
+
+/// counts <uid>
+async fn counts(firestore: &FirestoreService, args: &Args) -> AdminResult {
+    let uid = args.arg(1, "uid")?;
+    println!("Document counts for user {}:", uid);
+    for collection in COUNTED_COLLECTIONS {
+        match firestore.count_user_documents(uid, collection).await {
+            Ok(count) => println!("  {:<16} {}", collection, count),
+            Err(e) => println!("  {:<16} error: {}", collection, e),
+        }
+    }
+    Ok(())
+}
+
+/// backfill-conversations <uid> [--limit N]
+async fn backfill_conversations(firestore: &FirestoreService, args: &Args) -> AdminResult {
+    let uid = args.arg(1, "uid")?;
+    let limit: usize = match args.options.get("--limit") {
+        Some(n) => n.parse().map_err(|_| format!("invalid --limit '{}'", n))?,
+        None => usize::MAX,
+    };
+
+    let (mut scanned, mut updated) = (0, 0);
+    let mut offset = 0;
+    while scanned < limit {
+        let page = firestore
+            .get_conversations(uid, BACKFILL_PAGE_SIZE, offset, true, &[], None, None, None, None)
+            .await?;
+        if page.is_empty() {
+            break;
+        }
+        offset += page.len();
+
+        for mut conversation in page.into_iter().take(limit - scanned) {
+            scanned += 1;
+            let missing_languages = conversation.detected_languages.is_empty();
+            let missing_analytics = conversation.analytics.is_none();
+            if !missing_languages && !missing_analytics {
+                continue;
+            }
+
+            language::tag_segment_languages(&mut conversation.transcript_segments);
+            let languages = if missing_languages {
+                language::conversation_languages(&conversation.transcript_segments)
+            } else {
+                conversation.detected_languages.clone()
+            };
+            let analytics = conversation
+                .analytics
+                .clone()
+                .or_else(|| conversation_analytics::compute_analytics(&conversation.transcript_segments));
+            if languages.is_empty() && analytics.is_none() {
+                continue;
+            }
+
+            println!(
+                "  {} languages={:?} analytics={}",
+                conversation.id,
+                languages,
+                if analytics.is_some() { "yes" } else { "no" }
+            );
+            if !args.dry_run() {
+                firestore
+                    .set_conversation_derived_fields(uid, &conversation.id, &languages, analytics.as_ref())
+                    .await?;
+            }
+            updated += 1;
+        }
+    }
+
+    println!(
+        "Scanned {} conversations, {} {}",
+        scanned,
+        updated,
+        if args.dry_run() { "would be updated" } else { "updated" }
+    );
+    Ok(())
+}
+
+/// restructure <uid> <conversation_id>
+async fn restructure(firestore: &FirestoreService, config: &Config, args: &Args) -> AdminResult {
+    let uid = args.arg(1, "uid")?;
+    let conversation_id = args.arg(2, "conversation_id")?;
+    let api_key = config
+        .gemini_api_key
+        .clone()
+        .ok_or("GEMINI_API_KEY not configured")?;
+
+    let conversation = firestore
+        .get_conversation(uid, conversation_id)
+        .await?
+        .ok_or("Conversation not found")?;
+    if conversation.transcript_segments.is_empty() {
+        return Err("Conversation has no transcript".into());
+    }
+
+    let settings = firestore.get_processing_settings(uid).await.unwrap_or_default();
+    let llm = LlmClient::new(api_key)
+        .with_task_models(firestore.llm_task_models(&config.llm_models).await)
+        .with_custom_processing_prompt(Some(&settings.prompt))
+        .with_plain_titles(settings.plain_titles);
+    let summary_language =
+        language::summary_language(&conversation.language, &conversation.detected_languages);
+    let transcript = TranscriptSegment::to_transcript_text(&conversation.transcript_segments);
+
+    let structured = llm
+        .extract_structure(
+            &transcript,
+            &conversation.started_at.to_rfc3339(),
+            "UTC",
+            &summary_language,
+            None,
+        )
+        .await?;
+
+    println!("Old title: {}", conversation.structured.title);
+    println!("New title: {}", structured.title);
+    println!("New overview: {}", structured.overview);
+    println!("New emoji/category: {} {:?}", structured.emoji, structured.category);
+
+    if !args.dry_run() {
+        firestore
+            .update_conversation_summary(uid, conversation_id, &structured, llm.model_for(TaskKind::Summary))
+            .await?;
+        let previous = conversation_history::current_value(&conversation, ConversationEditField::Summary);
+        let edit = conversation_history::new_edit(ConversationEditField::Summary, previous, "admin", "summary_regeneration");
+        conversation_history::record_edit(firestore, uid, conversation_id, &edit).await;
+        println!("Updated conversation {}", conversation_id);
+    }
+    Ok(())
+}
+
+/// publish-release <version> <build_number> <download_url> <ed_signature> [options]
+async fn publish_release(firestore: &FirestoreService, args: &Args) -> AdminResult {
+    let version = args.arg(1, "version")?;
+    let build_number: u32 = args
+        .arg(2, "build_number")?
+        .parse()
+        .map_err(|_| "build_number must be a positive integer")?;
+    let download_url = args.arg(3, "download_url")?;
+    let ed_signature = args.arg(4, "ed_signature")?;
+
+    let channel = args.options.get("--channel").cloned();
+    if let Some(ch) = &channel {
+        if !matches!(ch.as_str(), "staging" | "beta" | "stable") {
+            return Err(format!("invalid --channel '{}'", ch).into());
+        }
+    }
+
+    let release = ReleaseInfo {
+        version: version.to_string(),
+        build_number,
+        download_url: download_url.to_string(),
+        ed_signature: ed_signature.to_string(),
+        published_at: Utc::now().to_rfc3339(),
+        changelog: args
+            .options
+            .get("--changelog")
+            .map(|c| {
+                c.split(';')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        is_live: !args.has("--not-live"),
+        is_critical: args.has("--critical"),
+        channel,
+    };
+
+    println!(
+        "Release v{}+{} channel={} live={} critical={} changelog={:?}",
+        release.version,
+        release.build_number,
+        release.channel.as_deref().unwrap_or("staging"),
+        release.is_live,
+        release.is_critical,
+        release.changelog
+    );
+
+    if !args.dry_run() {
+        let doc_id = firestore.create_desktop_release(&release).await?;
+        println!("Published {}", doc_id);
+    }
+    Ok(())
+}
+
+/// grant-entitlement <uid> <entitlement>
+async fn grant_entitlement(firestore: &FirestoreService, args: &Args) -> AdminResult {
+    let uid = args.arg(1, "uid")?;
+    let entitlement = args.arg(2, "entitlement")?.trim();
+    if entitlement.is_empty() {
+        return Err("entitlement can't be empty".into());
+    }
+
+    let current = firestore.get_user_entitlements(uid).await?;
+    if current.iter().any(|e| e == entitlement) {
+        println!("User {} already has {}", uid, entitlement);
+        return Ok(());
+    }
+
+    println!("Granting {} to user {} (current: {:?})", entitlement, uid, current);
+    if !args.dry_run() {
+        firestore.grant_user_entitlement(uid, entitlement).await?;
+        println!("Done");
+    }
+    Ok(())
+}
+
+/// migrate list | migrate run <migration_id> [--limit N] [--restart]
+async fn migrate(firestore: &FirestoreService, args: &Args) -> AdminResult {
+    match args.arg(1, "list|run")? {
+        "list" => {
+            for migration in MIGRATIONS {
+                let progress = match firestore.get_migration_record(migration.id).await? {
+                    Some(r) => format!("{:?}, scanned {}, updated {}", r.status, r.scanned, r.updated),
+                    None => "never run".to_string(),
+                };
+                println!("{}\n    {}\n    {}", migration.id, migration.description, progress);
+            }
+            Ok(())
+        }
+        "run" => {
+            let id = args.arg(2, "migration_id")?;
+            let migration =
+                migrations::find_migration(id).ok_or(format!("unknown migration '{}'", id))?;
+            let limit = match args.options.get("--limit") {
+                Some(n) => Some(n.parse().map_err(|_| format!("invalid --limit '{}'", n))?),
+                None => None,
+            };
+            let options = RunOptions {
+                dry_run: args.dry_run(),
+                limit,
+                restart: args.has("--restart"),
+            };
+
+            let record = migrations::run_migration(firestore, migration, options, |r| {
+                println!("  scanned {} / updated {}", r.scanned, r.updated);
+            })
+            .await?;
+            println!(
+                "{}: {:?} - scanned {}, {} {}",
+                migration.id,
+                record.status,
+                record.scanned,
+                record.updated,
+                if options.dry_run { "would be updated" } else { "updated" }
+            );
+            Ok(())
+        }
+        other => Err(format!("unknown migrate command '{}'", other).into()),
+    }
+}
@@ -0,0 +1,104 @@
+// Request body size limits - Small default for JSON, per-route overrides for large payloads
+// Oversized requests get a 413 that states the limit instead of axum's generic rejection.
+
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use std::convert::Infallible;
+
+/// Default limit for JSON endpoints
+pub const DEFAULT_BODY_LIMIT_BYTES: usize = 1024 * 1024;
+/// Limit for endpoints that accept whole transcripts or activity batches
+pub const LARGE_JSON_BODY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Marks a 413 response that already explains the limit
+#[derive(Clone, Copy)]
+struct LimitExplained;
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+/// 413 response stating the limit
+pub fn payload_too_large(max_bytes: usize) -> Response {
+    let mut response = (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "Request body too large: the limit for this endpoint is {}",
+            format_bytes(max_bytes)
+        ),
+    )
+        .into_response();
+    response.extensions_mut().insert(LimitExplained);
+    response
+}
+
+/// Reject by Content-Length up front (when `precheck`); replace bare 413s from
+/// extractors or handlers with one that states the limit
+async fn enforce_limit(max_bytes: usize, precheck: bool, request: Request, next: Next) -> Response {
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if precheck && declared.is_some_and(|len| len > max_bytes) {
+        tracing::warn!(
+            "Rejected {} {}: body of {} bytes exceeds {}",
+            request.method(),
+            request.uri().path(),
+            declared.unwrap_or_default(),
+            max_bytes
+        );
+        return payload_too_large(max_bytes);
+    }
+
+    let response = next.run(request).await;
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE
+        && response.extensions().get::<LimitExplained>().is_none()
+    {
+        return payload_too_large(max_bytes);
+    }
+    response
+}
+
+/// Apply a body limit to one route, overriding the app-wide default
+pub fn with_body_limit<S>(route: MethodRouter<S>, max_bytes: usize) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route
+        .layer::<_, Infallible>(middleware::from_fn(move |request: Request, next: Next| {
+            enforce_limit(max_bytes, true, request, next)
+        }))
+        .layer(DefaultBodyLimit::max(max_bytes))
+}
+
+/// App-wide default limit, layered on the whole router together with
+/// `DefaultBodyLimit::max(DEFAULT_BODY_LIMIT_BYTES)`. Doesn't check Content-Length
+/// itself since routes with `with_body_limit` may allow more.
+pub async fn default_body_limit(request: Request, next: Next) -> Response {
+    enforce_limit(DEFAULT_BODY_LIMIT_BYTES, false, request, next).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_too_large_states_limit() {
+        assert_eq!(format_bytes(DEFAULT_BODY_LIMIT_BYTES), "1.0 MB");
+        assert_eq!(format_bytes(64 * 1024), "64 KB");
+
+        let response = payload_too_large(LARGE_JSON_BODY_LIMIT_BYTES);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(response.extensions().get::<LimitExplained>().is_some());
+    }
+}
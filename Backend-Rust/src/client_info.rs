@@ -0,0 +1,226 @@
+// Client info - The real client address behind load balancers and proxies
+// The forwarding header the trusted proxies write (FORWARDED_HEADER: X-Forwarded-For or
+// Forwarded) is only believed when the connecting peer is a trusted proxy (TRUSTED_PROXIES);
+// the other header is ignored, since a proxy that doesn't write it passes a client's copy
+// through. The result is attached to every request as ClientInfo.
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Who sent the request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    /// Client IP; None when the connection address is unknown
+    pub ip: Option<IpAddr>,
+}
+
+impl ClientInfo {
+    pub fn ip_string(&self) -> Option<String> {
+        self.ip.map(|ip| ip.to_string())
+    }
+}
+
+impl std::fmt::Display for ClientInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.ip {
+            Some(ip) => write!(f, "{}", ip),
+            None => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Reads the extension set by `resolve_client_info`; unknown when it didn't run
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<ClientInfo>().cloned().unwrap_or_default())
+    }
+}
+
+/// Header the trusted proxies record the client address in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// `X-Forwarded-For` (GCP load balancers, Cloud Run, most proxies)
+    #[default]
+    XForwardedFor,
+    /// RFC 7239 `Forwarded`, its `for=` parameters
+    Forwarded,
+}
+
+impl std::str::FromStr for ForwardedHeader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            "forwarded" => Ok(Self::Forwarded),
+            other => Err(format!("unknown forwarding header '{}'", other)),
+        }
+    }
+}
+
+/// Networks allowed to report the client address, and the header they report it in
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardedHeader,
+}
+
+impl TrustedProxies {
+    /// Parse addresses and CIDR ranges ("10.0.0.0/8", "127.0.0.1"); invalid entries are
+    /// logged and skipped
+    pub fn parse(entries: &[String], header: ForwardedHeader) -> Self {
+        let networks = entries
+            .iter()
+            .filter_map(|entry| {
+                let parsed = Self::parse_entry(entry);
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring invalid TRUSTED_PROXIES entry: {}", entry);
+                }
+                parsed
+            })
+            .collect();
+        Self { networks, header }
+    }
+
+    /// One address or CIDR range
+    pub fn parse_entry(entry: &str) -> Option<IpNet> {
+        entry
+            .parse::<IpNet>()
+            .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+            .ok()
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Client address of a request that arrived from `peer`. The configured forwarding
+    /// header is walked from the nearest hop; the first address that isn't a trusted proxy is the client.
+    /// Entries further left are client-supplied and never believed over a closer one.
+    pub fn client_ip(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer = peer?;
+        if !self.contains(peer) {
+            return Some(peer);
+        }
+        let mut client = peer;
+        for hop in forwarded_chain(headers, self.header).into_iter().rev() {
+            // An unparseable hop ("unknown", obfuscated) ends what can be trusted
+            let Some(ip) = hop else { break };
+            client = ip;
+            if !self.contains(ip) {
+                break;
+            }
+        }
+        Some(client)
+    }
+}
+
+/// Hops recorded in `header`, client first
+fn forwarded_chain(headers: &HeaderMap, header: ForwardedHeader) -> Vec<Option<IpAddr>> {
+    let values = |name: &str| -> Vec<String> {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    };
+
+    match header {
+        ForwardedHeader::Forwarded => values("forwarded")
+            .iter()
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.trim().split_once('='))
+                    .find(|(key, _)| key.eq_ignore_ascii_case("for"))
+                    .and_then(|(_, value)| parse_hop(value))
+            })
+            .collect(),
+        ForwardedHeader::XForwardedFor => values("x-forwarded-for").iter().map(|hop| parse_hop(hop)).collect(),
+    }
+}
+
+/// An address as it appears in a forwarding header, optionally quoted, bracketed or with a port
+fn parse_hop(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    value
+        .parse::<IpAddr>()
+        .or_else(|_| value.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .or_else(|_| value.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>())
+        .ok()
+}
+
+/// Middleware that attaches the request's ClientInfo. Needs the server to be started with
+/// connect info (`into_make_service_with_connect_info::<SocketAddr>`).
+pub async fn resolve_client_info(
+    State(trusted): State<Arc<TrustedProxies>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    let ip = trusted.client_ip(peer, request.headers());
+    request.extensions_mut().insert(ClientInfo { ip });
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_client_ip_behind_trusted_proxies() {
+        let proxies = ["10.0.0.0/8".to_string(), "not-an-ip".to_string()];
+        let trusted = TrustedProxies::parse(&proxies, ForwardedHeader::XForwardedFor);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("1.1.1.1, 203.0.113.7, 10.0.0.5"));
+
+        // An untrusted peer's headers are ignored
+        assert_eq!(trusted.client_ip(Some(ip("198.51.100.1")), &headers), Some(ip("198.51.100.1")));
+        // Behind trusted proxies, the nearest untrusted hop wins over a spoofed leftmost entry
+        assert_eq!(trusted.client_ip(Some(ip("10.0.0.1")), &headers), Some(ip("203.0.113.7")));
+        assert_eq!(trusted.client_ip(None, &headers), None);
+
+        // A client-written Forwarded is ignored when the proxies write X-Forwarded-For
+        headers.insert("forwarded", HeaderValue::from_static("for=192.0.2.99"));
+        assert_eq!(trusted.client_ip(Some(ip("10.0.0.1")), &headers), Some(ip("203.0.113.7")));
+
+        // Proxies that write Forwarded: quoted IPv6 and ports, and X-Forwarded-For is ignored
+        let trusted = TrustedProxies::parse(&proxies, ForwardedHeader::Forwarded);
+        headers.insert(
+            "forwarded",
+            HeaderValue::from_static("for=192.0.2.60;proto=https, for=\"[2001:db8::17]:4711\";by=10.0.0.5"),
+        );
+        assert_eq!(trusted.client_ip(Some(ip("10.0.0.1")), &headers), Some(ip("2001:db8::17")));
+
+        // An unparseable hop stops the walk at the last trusted address
+        headers.insert("forwarded", HeaderValue::from_static("for=192.0.2.60, for=unknown"));
+        assert_eq!(trusted.client_ip(Some(ip("10.0.0.1")), &headers), Some(ip("10.0.0.1")));
+        assert_eq!("Forwarded".parse(), Ok(ForwardedHeader::Forwarded));
+    }
+}
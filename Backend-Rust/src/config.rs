@@ -1,8 +1,14 @@
 // Configuration - Environment variables
 // Copied from Python backend .env
 
+use std::collections::HashMap;
 use std::env;
 
+use crate::client_info::ForwardedHeader;
+use crate::llm::TaskModels;
+use crate::models::TruncationStrategy;
+use crate::services::storage::StorageKind;
+
 /// Application configuration loaded from environment
 #[derive(Clone)]
 pub struct Config {
@@ -58,6 +64,108 @@ pub struct Config {
     pub pinecone_api_key: Option<String>,
     /// Pinecone host URL (e.g. https://index-name-xxx.svc.environment.pinecone.io)
     pub pinecone_host: Option<String>,
+    /// Whether the X-Omi-Environment header may reroute Firestore calls (dev/QA only)
+    pub allow_environment_override: bool,
+    /// Environment name -> Firestore project ID (e.g. "staging" -> "based-hardware-dev")
+    pub firestore_environments: HashMap<String, String>,
+    /// How often the background action item scorer runs (0 = disabled)
+    pub action_item_scoring_interval_minutes: u64,
+    /// Timeout for a single outbound integration webhook call
+    pub integration_webhook_timeout_secs: u64,
+    /// Retries for integration webhooks that fail with 5xx/network errors
+    pub integration_webhook_max_retries: u32,
+    /// Max in-flight webhook calls per app
+    pub integration_webhook_max_concurrency: usize,
+    /// Consecutive failures before delivery to an app is paused for a user
+    pub integration_webhook_failure_threshold: u32,
+    /// GCS bucket for chat attachments (uploads disabled when unset)
+    pub chat_attachments_bucket: Option<String>,
+    /// Latest released OMI device firmware (for update checks)
+    pub omi_latest_firmware_version: Option<String>,
+    /// Resend API key for outbound email (sending disabled when unset)
+    pub mailer_api_key: Option<String>,
+    /// Sender address for outbound email
+    pub mailer_from_address: String,
+    /// Notion public integration OAuth credentials (Notion sync disabled when unset)
+    pub notion_client_id: Option<String>,
+    pub notion_client_secret: Option<String>,
+    pub notion_redirect_uri: Option<String>,
+    /// Slack app OAuth credentials and request signing secret (Slack disabled when unset)
+    pub slack_client_id: Option<String>,
+    pub slack_client_secret: Option<String>,
+    pub slack_signing_secret: Option<String>,
+    pub slack_redirect_uri: Option<String>,
+    /// UIDs allowed to call /v1/admin endpoints (comma-separated ADMIN_UIDS)
+    pub admin_uids: Vec<String>,
+    /// HMAC key for admin impersonation tokens (impersonation disabled when unset)
+    pub impersonation_secret: Option<String>,
+    /// Origins allowed for browser requests (CORS_ALLOWED_ORIGINS, comma-separated;
+    /// "https://*.example.com" matches subdomains)
+    pub cors_allowed_origins: Vec<String>,
+    /// Whether browsers may send cookies/credentials cross-origin
+    pub cors_allow_credentials: bool,
+    /// Allow every origin and skip HSTS/frame headers (local dev and Swift webviews)
+    pub cors_dev_mode: bool,
+    /// Strict-Transport-Security max-age (0 = header disabled)
+    pub hsts_max_age_secs: u64,
+    /// Proxies/load balancers whose forwarding headers give the client IP
+    /// (TRUSTED_PROXIES, comma-separated addresses or CIDR ranges)
+    pub trusted_proxies: Vec<String>,
+    /// Header the trusted proxies write the client address to (FORWARDED_HEADER:
+    /// x-forwarded-for or forwarded)
+    pub forwarded_header: ForwardedHeader,
+    /// Max LLM-backed requests running at once per user (0 = unlimited)
+    pub llm_max_concurrent_per_user: usize,
+    /// Max LLM-backed requests waiting for a slot per user before a 429
+    pub llm_max_queued_per_user: usize,
+    /// Background jobs (reprocessing, rescoring, exports) running at once on this instance
+    pub job_queue_workers: usize,
+    /// Max background jobs running at once per user (0 = unlimited)
+    pub job_queue_max_running_per_user: usize,
+    /// Max background jobs waiting per user before new ones are rejected (0 = unlimited)
+    pub job_queue_max_queued_per_user: usize,
+    /// Model per LLM task (LLM_MODEL, LLM_MODEL_<TASK>); admin overrides apply on top
+    pub llm_models: TaskModels,
+    /// Most segments accepted in one uploaded transcript
+    pub transcript_max_segments: usize,
+    /// Most (estimated) tokens accepted in one uploaded transcript
+    pub transcript_max_tokens: usize,
+    /// What to do with transcripts over the limits when the request doesn't say
+    pub transcript_truncation: TruncationStrategy,
+    /// Percent of from-segments requests that also run the shadow candidate (0 = off)
+    pub shadow_traffic_percent: f64,
+    /// Model the shadow candidate uses for every task (SHADOW_MODEL)
+    pub shadow_model: Option<String>,
+    /// Candidate structure prompt, read from SHADOW_STRUCTURE_PROMPT_FILE
+    pub shadow_structure_prompt: Option<String>,
+    /// Idle Firestore connections kept open per host
+    pub firestore_pool_max_idle_per_host: usize,
+    /// How long an idle Firestore connection stays pooled
+    pub firestore_pool_idle_timeout_secs: u64,
+    /// TCP keepalive interval for Firestore connections
+    pub firestore_tcp_keepalive_secs: u64,
+    /// Re-send slow critical reads (get_conversation, get_memories) after their p95 latency
+    pub firestore_hedged_reads: bool,
+    /// Shortest wait before a hedged read is re-sent
+    pub firestore_hedge_min_delay_ms: u64,
+    /// Translate chat responses that came back in a language other than the user's
+    pub chat_enforce_language: bool,
+    /// Convert markdown in chat responses to plain text for clients that can't render it
+    pub chat_strip_markdown: bool,
+    /// Store redacted LLM prompt/response pairs per request (debugging only)
+    pub llm_debug_logging: bool,
+    /// How long stored LLM debug entries are kept
+    pub llm_debug_log_ttl_hours: i64,
+    /// GCS bucket for data export archives (exports disabled when unset)
+    pub exports_bucket: Option<String>,
+    /// HMAC secret for signed export download links
+    pub export_signing_secret: Option<String>,
+    /// Days a finished export archive is kept before it's deleted
+    pub export_retention_days: i64,
+    /// Where conversations, memories, action items, messages and settings live (STORAGE)
+    pub storage: StorageKind,
+    /// SQLite file used when STORAGE=local
+    pub local_storage_path: String,
 }
 
 impl Config {
@@ -100,12 +208,167 @@ impl Config {
             crisp_website_id: env::var("CRISP_WEBSITE_ID").ok(),
             pinecone_api_key: env::var("PINECONE_API_KEY").ok(),
             pinecone_host: env::var("PINECONE_HOST").ok(),
+            allow_environment_override: env::var("ALLOW_ENVIRONMENT_OVERRIDE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            firestore_environments: env::var("FIRESTORE_ENVIRONMENTS")
+                .map(|v| parse_environments(&v))
+                .unwrap_or_default(),
+            action_item_scoring_interval_minutes: env::var("ACTION_ITEM_SCORING_INTERVAL_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(360),
+            integration_webhook_timeout_secs: env::var("INTEGRATION_WEBHOOK_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+            integration_webhook_max_retries: env::var("INTEGRATION_WEBHOOK_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+            integration_webhook_max_concurrency: env::var("INTEGRATION_WEBHOOK_MAX_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+            integration_webhook_failure_threshold: env::var("INTEGRATION_WEBHOOK_FAILURE_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            chat_attachments_bucket: env::var("CHAT_ATTACHMENTS_BUCKET").ok(),
+            omi_latest_firmware_version: env::var("OMI_LATEST_FIRMWARE_VERSION").ok(),
+            mailer_api_key: env::var("RESEND_API_KEY").ok(),
+            mailer_from_address: env::var("MAILER_FROM_ADDRESS")
+                .unwrap_or_else(|_| "Omi <noreply@omi.me>".to_string()),
+            notion_client_id: env::var("NOTION_CLIENT_ID").ok(),
+            notion_client_secret: env::var("NOTION_CLIENT_SECRET").ok(),
+            notion_redirect_uri: env::var("NOTION_REDIRECT_URI").ok(),
+            slack_client_id: env::var("SLACK_CLIENT_ID").ok(),
+            slack_client_secret: env::var("SLACK_CLIENT_SECRET").ok(),
+            slack_signing_secret: env::var("SLACK_SIGNING_SECRET").ok(),
+            slack_redirect_uri: env::var("SLACK_REDIRECT_URI").ok(),
+            admin_uids: env::var("ADMIN_UIDS")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            impersonation_secret: env::var("IMPERSONATION_SECRET").ok().filter(|s| !s.is_empty()),
+            cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            cors_allow_credentials: env::var("CORS_ALLOW_CREDENTIALS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            cors_dev_mode: env::var("CORS_DEV_MODE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            hsts_max_age_secs: env::var("HSTS_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(31_536_000),
+            trusted_proxies: env::var("TRUSTED_PROXIES")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            forwarded_header: env::var("FORWARDED_HEADER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            llm_max_concurrent_per_user: env::var("LLM_MAX_CONCURRENT_PER_USER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+            llm_max_queued_per_user: env::var("LLM_MAX_QUEUED_PER_USER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+            job_queue_workers: env::var("JOB_QUEUE_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+            job_queue_max_running_per_user: env::var("JOB_QUEUE_MAX_RUNNING_PER_USER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+            job_queue_max_queued_per_user: env::var("JOB_QUEUE_MAX_QUEUED_PER_USER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+            llm_models: TaskModels::from_env(),
+            transcript_max_segments: env::var("TRANSCRIPT_MAX_SEGMENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20_000),
+            transcript_max_tokens: env::var("TRANSCRIPT_MAX_TOKENS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(400_000),
+            transcript_truncation: env::var("TRANSCRIPT_TRUNCATION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(TruncationStrategy::Summarize),
+            shadow_traffic_percent: env::var("SHADOW_TRAFFIC_PERCENT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .map(|p| p.clamp(0.0, 100.0))
+                .unwrap_or(0.0),
+            shadow_model: env::var("SHADOW_MODEL").ok().filter(|m| !m.is_empty()),
+            shadow_structure_prompt: env::var("SHADOW_STRUCTURE_PROMPT_FILE").ok().and_then(|path| {
+                std::fs::read_to_string(&path)
+                    .map_err(|e| tracing::warn!("Failed to read SHADOW_STRUCTURE_PROMPT_FILE {}: {}", path, e))
+                    .ok()
+            }),
+            firestore_pool_max_idle_per_host: env::var("FIRESTORE_POOL_MAX_IDLE_PER_HOST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(32),
+            firestore_pool_idle_timeout_secs: env::var("FIRESTORE_POOL_IDLE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(90),
+            firestore_tcp_keepalive_secs: env::var("FIRESTORE_TCP_KEEPALIVE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+            firestore_hedged_reads: env::var("FIRESTORE_HEDGED_READS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            firestore_hedge_min_delay_ms: env::var("FIRESTORE_HEDGE_MIN_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+            chat_enforce_language: env::var("CHAT_ENFORCE_LANGUAGE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            chat_strip_markdown: env::var("CHAT_STRIP_MARKDOWN")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            llm_debug_logging: env::var("LLM_DEBUG_LOGGING")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            llm_debug_log_ttl_hours: env::var("LLM_DEBUG_LOG_TTL_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(24),
+            exports_bucket: env::var("EXPORTS_BUCKET").ok(),
+            export_signing_secret: env::var("EXPORT_SIGNING_SECRET").ok(),
+            export_retention_days: env::var("EXPORT_RETENTION_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(7),
+            storage: env::var("STORAGE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            local_storage_path: env::var("LOCAL_STORAGE_PATH")
+                .unwrap_or_else(|_| "omi-local.db".to_string()),
         }
     }
 
     /// Validate that required configuration is present
     pub fn validate(&self) -> Result<(), String> {
-        if self.google_application_credentials.is_none() {
+        if self.storage == StorageKind::Local {
+            tracing::warn!(
+                "STORAGE=local - user data is stored in {} and Firestore is not used; apps, integrations and background jobs are unavailable",
+                self.local_storage_path
+            );
+        } else if self.google_application_credentials.is_none() {
             tracing::warn!("GOOGLE_APPLICATION_CREDENTIALS not set - Firestore will use default credentials");
         }
         if self.gemini_api_key.is_none() {
@@ -117,6 +380,17 @@ impl Config {
         if self.encryption_secret.is_none() {
             tracing::warn!("ENCRYPTION_SECRET not set — encrypted user data will not be decryptable");
         }
+        if self.cors_dev_mode {
+            tracing::warn!("CORS_DEV_MODE enabled - any origin may call the API");
+        } else if self.cors_allowed_origins.is_empty() {
+            tracing::warn!("CORS_ALLOWED_ORIGINS not set - cross-origin browser requests will be rejected");
+        }
+        if self.allow_environment_override {
+            tracing::warn!(
+                "ALLOW_ENVIRONMENT_OVERRIDE enabled - X-Omi-Environment can route to {:?}",
+                self.firestore_environments.keys().collect::<Vec<_>>()
+            );
+        }
         Ok(())
     }
 
@@ -133,3 +407,27 @@ impl Config {
         })
     }
 }
+
+/// Parse a comma-separated list, dropping empty entries
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Parse FIRESTORE_ENVIRONMENTS ("staging=project-a,production=project-b")
+fn parse_environments(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (name, project) = pair.split_once('=')?;
+            let (name, project) = (name.trim(), project.trim());
+            if name.is_empty() || project.is_empty() {
+                return None;
+            }
+            Some((name.to_lowercase(), project.to_string()))
+        })
+        .collect()
+}
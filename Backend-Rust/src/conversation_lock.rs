@@ -0,0 +1,354 @@
+// Conversation locks - Keeps edits off a conversation while a processing job rewrites it
+// Jobs (reprocess, reprocess-all, merge) set locked_by/locked_until on the conversation and
+// mutation endpoints answer 423 Locked with Retry-After until the job releases it. Locks
+// lapse after LOCK_TTL_SECS, so a job that dies without releasing can't hold one for good.
+// A held lock is a LockGuard: dropping it (a handler whose client disconnected, a job that
+// panicked) releases the lock on a spawned task instead of leaving it until it lapses.
+
+use axum::{
+    extract::{Extension, Path, Request},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::auth::AuthUser;
+use crate::models::ConversationLock;
+use crate::services::firestore::{carry_project_override, FirestoreError};
+use crate::services::FirestoreService;
+
+/// How long a lock holds without being released
+pub const LOCK_TTL_SECS: i64 = 15 * 60;
+/// Tries when a concurrent write changes the conversation between reading and locking it
+const ACQUIRE_ATTEMPTS: usize = 3;
+/// Path parameters that name the conversation in locked routes
+const CONVERSATION_ID_PARAMS: &[&str] = &["id", "conversation_id"];
+
+/// Rejection for a change to a conversation a job is processing
+#[derive(Debug, Serialize)]
+pub struct ConversationLocked {
+    pub error: String,
+    pub message: String,
+    pub conversation_id: String,
+    pub locked_by: String,
+    pub locked_until: DateTime<Utc>,
+    pub retry_after_secs: u64,
+}
+
+impl ConversationLocked {
+    fn new(conversation_id: &str, lock: &ConversationLock, now: DateTime<Utc>) -> Self {
+        let retry_after_secs = lock.retry_after_secs(now);
+        Self {
+            error: "conversation_locked".to_string(),
+            message: format!(
+                "Conversation {} is being processed ({}); retry in {}s",
+                conversation_id, lock.locked_by, retry_after_secs
+            ),
+            conversation_id: conversation_id.to_string(),
+            locked_by: lock.locked_by.clone(),
+            locked_until: lock.locked_until,
+            retry_after_secs,
+        }
+    }
+}
+
+impl IntoResponse for ConversationLocked {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after_secs;
+        let mut response = (StatusCode::LOCKED, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        response
+    }
+}
+
+/// Why a conversation couldn't be locked
+#[derive(Debug)]
+pub enum LockError {
+    Locked(ConversationLocked),
+    NotFound,
+    Failed(String),
+}
+
+impl IntoResponse for LockError {
+    fn into_response(self) -> Response {
+        match self {
+            LockError::Locked(locked) => locked.into_response(),
+            other => <(StatusCode, String)>::from(other).into_response(),
+        }
+    }
+}
+
+/// For handlers with plain-text errors; loses the Retry-After header
+impl From<LockError> for (StatusCode, String) {
+    fn from(error: LockError) -> Self {
+        match error {
+            LockError::Locked(locked) => (StatusCode::LOCKED, locked.message),
+            LockError::NotFound => (StatusCode::NOT_FOUND, "Conversation not found".to_string()),
+            LockError::Failed(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    }
+}
+
+/// The lock a job takes now
+fn new_lock(job: &str, now: DateTime<Utc>) -> ConversationLock {
+    ConversationLock {
+        locked_by: job.to_string(),
+        locked_until: now + Duration::seconds(LOCK_TTL_SECS),
+    }
+}
+
+/// Fails with `Locked` while a job holds the conversation. A missing conversation passes,
+/// so the handler reports it the way it always has.
+pub async fn check(firestore: &FirestoreService, uid: &str, conversation_id: &str) -> Result<(), LockError> {
+    let now = Utc::now();
+    match firestore.get_conversation_lock(uid, conversation_id).await {
+        Ok(Some((Some(lock), _))) if lock.is_active(now) => {
+            Err(LockError::Locked(ConversationLocked::new(conversation_id, &lock, now)))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(LockError::Failed(format!("Failed to read conversation lock: {}", e))),
+    }
+}
+
+/// A held conversation lock. Call [`LockGuard::release`] when the job ends; if the guard
+/// is dropped first, the lock is released on a spawned task.
+pub struct LockGuard {
+    release: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+impl LockGuard {
+    fn new(release: impl Future<Output = ()> + Send + 'static) -> Self {
+        Self {
+            // Released in the project the lock was taken in, even from a spawned task
+            release: Some(Box::pin(carry_project_override(release))),
+        }
+    }
+
+    /// Release the lock now
+    pub async fn release(mut self) {
+        if let Some(release) = self.release.take() {
+            release.await;
+        }
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let Some(release) = self.release.take() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(release);
+            }
+            Err(_) => tracing::warn!("Conversation lock dropped outside a runtime; it lapses on its own"),
+        }
+    }
+}
+
+/// Lock the conversation for `job`, unless another job holds it. The lock is held until
+/// the returned guard is released or dropped.
+pub async fn acquire(
+    firestore: &Arc<FirestoreService>,
+    uid: &str,
+    conversation_id: &str,
+    job: &str,
+) -> Result<LockGuard, LockError> {
+    let lock = lock_conversation(firestore, uid, conversation_id, job).await?;
+    let (firestore, uid, conversation_id) = (firestore.clone(), uid.to_string(), conversation_id.to_string());
+    Ok(LockGuard::new(async move {
+        release(&firestore, &uid, &conversation_id, &lock).await;
+    }))
+}
+
+async fn lock_conversation(
+    firestore: &FirestoreService,
+    uid: &str,
+    conversation_id: &str,
+    job: &str,
+) -> Result<ConversationLock, LockError> {
+    for _ in 0..ACQUIRE_ATTEMPTS {
+        let now = Utc::now();
+        let (current, update_time) = match firestore.get_conversation_lock(uid, conversation_id).await {
+            Ok(Some(found)) => found,
+            Ok(None) => return Err(LockError::NotFound),
+            Err(e) => return Err(LockError::Failed(format!("Failed to read conversation lock: {}", e))),
+        };
+        if let Some(lock) = current.filter(|lock| lock.is_active(now)) {
+            return Err(LockError::Locked(ConversationLocked::new(conversation_id, &lock, now)));
+        }
+
+        let lock = new_lock(job, now);
+        match firestore
+            .set_conversation_lock(uid, conversation_id, Some(&lock), &update_time)
+            .await
+        {
+            Ok(()) => return Ok(lock),
+            Err(FirestoreError::FailedPrecondition(_)) => continue,
+            Err(e) => return Err(LockError::Failed(format!("Failed to lock conversation: {}", e))),
+        }
+    }
+    Err(LockError::Failed(format!(
+        "Conversation {} kept changing while being locked",
+        conversation_id
+    )))
+}
+
+/// Release `lock` if it's still the conversation's lock; failures are logged, since the
+/// lock lapses on its own
+async fn release(firestore: &FirestoreService, uid: &str, conversation_id: &str, lock: &ConversationLock) {
+    for _ in 0..ACQUIRE_ATTEMPTS {
+        let update_time = match firestore.get_conversation_lock(uid, conversation_id).await {
+            Ok(Some((Some(current), update_time))) if current == *lock => update_time,
+            // Gone, expired and retaken, or already released
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("Failed to read lock of conversation {}: {}", conversation_id, e);
+                return;
+            }
+        };
+        match firestore.set_conversation_lock(uid, conversation_id, None, &update_time).await {
+            Ok(()) => return,
+            Err(FirestoreError::FailedPrecondition(_)) => continue,
+            Err(e) => {
+                tracing::warn!("Failed to release lock of conversation {}: {}", conversation_id, e);
+                return;
+            }
+        }
+    }
+}
+
+/// The conversation a route is about: its `:id` or `:conversation_id` parameter
+fn conversation_id_param(params: &HashMap<String, String>) -> Option<&str> {
+    CONVERSATION_ID_PARAMS
+        .iter()
+        .find_map(|name| params.get(*name))
+        .map(String::as_str)
+}
+
+/// Reject writes to a locked conversation (the route's `:id` or `:conversation_id`) with
+/// 423 Locked. The Firestore service comes from a request extension layered on in main.
+async fn reject_locked_conversation(
+    Extension(firestore): Extension<Arc<FirestoreService>>,
+    user: AuthUser,
+    Path(params): Path<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+    if let Some(conversation_id) = conversation_id_param(&params) {
+        if let Err(error) = check(&firestore, &user.uid, conversation_id).await {
+            if let LockError::Locked(locked) = &error {
+                tracing::info!(
+                    "Rejected {} {} for user {}: locked by {}",
+                    request.method(),
+                    request.uri().path(),
+                    user.uid,
+                    locked.locked_by
+                );
+            }
+            return error.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Refuse changes to the route's conversation while a processing job holds it
+pub fn with_conversation_lock<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.layer::<_, Infallible>(middleware::from_fn(reject_locked_conversation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_response_and_expiry() {
+        let now = Utc::now();
+        let lock = new_lock("reprocess-all", now);
+        assert!(lock.is_active(now));
+        assert!(!lock.is_active(now + Duration::seconds(LOCK_TTL_SECS)));
+
+        let locked = ConversationLocked::new("c1", &lock, now + Duration::milliseconds(1500));
+        assert_eq!(locked.retry_after_secs, LOCK_TTL_SECS as u64 - 1);
+        let response = locked.into_response();
+        assert_eq!(response.status(), StatusCode::LOCKED);
+        assert_eq!(
+            response.headers()[header::RETRY_AFTER],
+            (LOCK_TTL_SECS - 1).to_string().as_str()
+        );
+
+        // About to lapse still asks for a second
+        assert_eq!(lock.retry_after_secs(lock.locked_until - Duration::milliseconds(10)), 1);
+        let (status, message) = <(StatusCode, String)>::from(LockError::NotFound);
+        assert_eq!((status, message.as_str()), (StatusCode::NOT_FOUND, "Conversation not found"));
+    }
+
+    #[test]
+    fn test_conversation_id_param_names() {
+        let params = |name: &str| HashMap::from([(name.to_string(), "c1".to_string())]);
+        assert_eq!(conversation_id_param(&params("id")), Some("c1"));
+        assert_eq!(conversation_id_param(&params("conversation_id")), Some("c1"));
+        assert_eq!(conversation_id_param(&params("person_id")), None);
+    }
+
+    #[tokio::test]
+    async fn test_guard_releases_when_dropped() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let released = Arc::new(AtomicUsize::new(0));
+        let guard = |released: &Arc<AtomicUsize>| {
+            let released = released.clone();
+            LockGuard::new(async move {
+                released.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+
+        // A handler dropped mid-request (client disconnected) while holding the lock
+        let held = guard(&released);
+        let handler = tokio::spawn(async move {
+            let _held = held;
+            std::future::pending::<()>().await;
+        });
+        handler.abort();
+        let _ = handler.await;
+        tokio::task::yield_now().await;
+        assert_eq!(released.load(Ordering::SeqCst), 1);
+
+        // An explicit release runs once, not again on drop
+        guard(&released).release().await;
+        tokio::task::yield_now().await;
+        assert_eq!(released.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_guard_releases_in_its_project() {
+        use crate::services::firestore::{current_project_override, with_project_override};
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let held = with_project_override("staging".to_string(), async {
+            LockGuard::new(async move {
+                let _ = tx.send(current_project_override());
+            })
+        })
+        .await;
+        drop(held);
+        assert_eq!(rx.await.unwrap().as_deref(), Some("staging"));
+    }
+}
@@ -0,0 +1,59 @@
+// Per-request Firestore environment routing (dev/QA only)
+// Lets the same binary read staging or production data based on a request header.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::services::firestore::with_project_override;
+
+/// Header carrying the environment name (e.g. "staging", "production")
+pub const ENVIRONMENT_HEADER: &str = "X-Omi-Environment";
+
+/// Middleware that routes all Firestore calls made while handling the request
+/// to the project configured for the `X-Omi-Environment` header.
+/// The header is ignored unless ALLOW_ENVIRONMENT_OVERRIDE is enabled.
+pub async fn firestore_environment(
+    State(config): State<Arc<Config>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let environment = request
+        .headers()
+        .get(ENVIRONMENT_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty());
+
+    let Some(environment) = environment else {
+        return next.run(request).await;
+    };
+
+    if !config.allow_environment_override {
+        tracing::debug!("Ignoring {} header ({}) - override disabled", ENVIRONMENT_HEADER, environment);
+        return next.run(request).await;
+    }
+
+    let Some(project_id) = config.firestore_environments.get(&environment).cloned() else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown environment '{}'", environment),
+        )
+            .into_response();
+    };
+
+    tracing::info!(
+        "Routing {} {} to Firestore project {} (environment={})",
+        request.method(),
+        request.uri().path(),
+        project_id,
+        environment
+    );
+
+    with_project_override(project_id, next.run(request)).await
+}
@@ -0,0 +1,121 @@
+// Conditional GETs - ETags for polled list endpoints
+// The ETag is a hash of the response body; a matching If-None-Match gets an empty 304.
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+
+/// Responses larger than this, or of unknown length, are passed through without an ETag
+const MAX_ETAG_BODY_BYTES: usize = 32 * 1024 * 1024;
+const CACHE_CONTROL: &str = "private, no-cache";
+
+/// Strong ETag for a response body
+pub fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Whether an If-None-Match header value matches `etag` (weak comparison, per RFC 9110)
+pub fn if_none_match_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    })
+}
+
+/// Whether a body is known to be small enough to buffer for hashing. Streams report
+/// no upper bound and are never buffered.
+fn fits_etag_buffer<B: HttpBody>(body: &B) -> bool {
+    body.size_hint().upper().is_some_and(|upper| upper <= MAX_ETAG_BODY_BYTES as u64)
+}
+
+/// Middleware: hash successful GET responses into an ETag and answer 304
+/// when the client already has that version
+async fn conditional_get(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK || !fits_etag_buffer(response.body()) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ETAG_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for ETag: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let etag = etag_for(&bytes);
+    let Ok(etag_value) = HeaderValue::from_str(&etag) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let not_modified = if_none_match.is_some_and(|v| if_none_match_matches(&v, &etag));
+    let (mut parts, body) = if not_modified {
+        let (parts, _) = StatusCode::NOT_MODIFIED.into_response().into_parts();
+        (parts, Body::empty())
+    } else {
+        (parts, Body::from(bytes))
+    };
+
+    // Clients may cache but must revalidate on every poll
+    parts
+        .headers
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static(CACHE_CONTROL));
+    parts.headers.insert(header::ETAG, etag_value);
+    Response::from_parts(parts, body)
+}
+
+/// Add ETag / If-None-Match handling to a route's GET handler
+pub fn with_etag<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.layer::<_, Infallible>(middleware::from_fn(conditional_get))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_is_stable() {
+        let etag = etag_for(b"[{\"id\":\"a\"}]");
+        assert_eq!(etag, etag_for(b"[{\"id\":\"a\"}]"));
+        assert_ne!(etag, etag_for(b"[{\"id\":\"b\"}]"));
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+    }
+
+    #[test]
+    fn test_only_bounded_bodies_are_buffered() {
+        assert!(fits_etag_buffer(&Body::from("[]")));
+        assert!(!fits_etag_buffer(&Body::from(vec![0u8; MAX_ETAG_BODY_BYTES + 1])));
+        let stream = futures::stream::iter([Ok::<_, std::io::Error>("chunk")]);
+        assert!(!fits_etag_buffer(&Body::from_stream(stream)));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = etag_for(b"body");
+        assert!(if_none_match_matches(&etag, &etag));
+        assert!(if_none_match_matches(&format!("W/{}", etag), &etag));
+        assert!(if_none_match_matches(&format!("\"other\", {}", etag), &etag));
+        assert!(if_none_match_matches("*", &etag));
+        assert!(!if_none_match_matches("\"other\"", &etag));
+    }
+}
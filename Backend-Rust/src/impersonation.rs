@@ -0,0 +1,171 @@
+// Admin impersonation - Short-lived tokens that let a support engineer act as a user
+// Tokens are HS256 JWTs signed with IMPERSONATION_SECRET; every request made with one
+// is written to the impersonation_audit collection.
+
+use axum::{
+    extract::{Request, State},
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthError;
+use crate::client_info::ClientInfo;
+use crate::models::ImpersonationAuditEntry;
+use crate::services::FirestoreService;
+use crate::AppState;
+
+/// How long a minted token stays valid
+pub const IMPERSONATION_TOKEN_MINUTES: i64 = 15;
+/// Issuer of impersonation tokens (Firebase ID tokens use securetoken.google.com)
+const IMPERSONATION_ISSUER: &str = "omi-impersonation";
+
+/// Claims of an impersonation token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpersonationClaims {
+    /// Impersonated uid
+    pub sub: String,
+    /// Admin uid acting as the user
+    pub act: String,
+    pub reason: String,
+    /// Token id, shared by all audit entries of the session
+    pub jti: String,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl ImpersonationClaims {
+    pub fn new(admin_uid: &str, target_uid: &str, reason: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            sub: target_uid.to_string(),
+            act: admin_uid.to_string(),
+            reason: reason.to_string(),
+            jti: uuid::Uuid::new_v4().to_string(),
+            iss: IMPERSONATION_ISSUER.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::minutes(IMPERSONATION_TOKEN_MINUTES)).timestamp(),
+        }
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_else(Utc::now)
+    }
+
+    /// An audit entry for one request made under these claims
+    pub fn audit_entry(&self, method: &str, path: &str, status: u16, ip: Option<String>) -> ImpersonationAuditEntry {
+        ImpersonationAuditEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            admin_uid: self.act.clone(),
+            target_uid: self.sub.clone(),
+            reason: self.reason.clone(),
+            session_id: self.jti.clone(),
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            ip,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Sign claims into a bearer token
+pub fn mint_token(secret: &str, claims: &ImpersonationClaims) -> Result<String, jsonwebtoken::errors::Error> {
+    encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(secret.as_bytes()))
+}
+
+/// Whether a bearer token is an impersonation token rather than a Firebase ID token
+/// (Firebase tokens are RS256); says nothing about its validity
+pub fn is_impersonation_token(token: &str) -> bool {
+    decode_header(token).is_ok_and(|header| header.alg == Algorithm::HS256)
+}
+
+/// Check an impersonation token's signature, issuer and expiry
+pub fn verify_token(secret: &str, token: &str) -> Result<ImpersonationClaims, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[IMPERSONATION_ISSUER]);
+    decode::<ImpersonationClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .map(|data| data.claims)
+        .map_err(|e| AuthError {
+            error: "invalid_token".to_string(),
+            message: format!("Impersonation token validation failed: {}", e),
+        })
+}
+
+/// Store an audit entry; a failure is logged rather than failing the request
+pub async fn record_audit(firestore: &FirestoreService, entry: &ImpersonationAuditEntry) {
+    if let Err(e) = firestore.add_impersonation_audit(entry).await {
+        tracing::error!(
+            "Failed to audit impersonated {} {} by {} as {}: {}",
+            entry.method,
+            entry.path,
+            entry.admin_uid,
+            entry.target_uid,
+            e
+        );
+    }
+}
+
+/// Middleware that writes an audit entry for every request carrying a valid
+/// impersonation token. Other requests pass through untouched; invalid tokens are
+/// rejected by the AuthUser extractor.
+pub async fn audit_impersonated_requests(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let claims = state.config.impersonation_secret.as_deref().and_then(|secret| {
+        let token = request
+            .headers()
+            .get(AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")?;
+        if !is_impersonation_token(token) {
+            return None;
+        }
+        verify_token(secret, token).ok()
+    });
+    let Some(claims) = claims else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let client = request.extensions().get::<ClientInfo>().cloned().unwrap_or_default();
+    let response = next.run(request).await;
+    tracing::info!(
+        "Impersonated request {} {} by {} as {} from {} ({})",
+        method,
+        path,
+        claims.act,
+        claims.sub,
+        client,
+        response.status()
+    );
+    let entry = claims.audit_entry(&method, &path, response.status().as_u16(), client.ip_string());
+    record_audit(&state.firestore, &entry).await;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_round_trip() {
+        let claims = ImpersonationClaims::new("admin-1", "user-1", "Ticket 4512: missing memories", Utc::now());
+        let token = mint_token("secret", &claims).unwrap();
+        assert!(is_impersonation_token(&token));
+        assert_eq!(verify_token("secret", &token).unwrap(), claims);
+        assert!(verify_token("other-secret", &token).is_err());
+
+        let expired = ImpersonationClaims::new("admin-1", "user-1", "Ticket 4512", Utc::now() - Duration::hours(1));
+        assert!(verify_token("secret", &mint_token("secret", &expired).unwrap()).is_err());
+
+        assert!(!is_impersonation_token("not-a-jwt"));
+    }
+}
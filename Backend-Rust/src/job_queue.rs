@@ -0,0 +1,379 @@
+// Background job queue - Fair scheduling of per-user background work
+// Jobs run on a fixed pool of workers (JOB_QUEUE_WORKERS). Interactive jobs (work a user is
+// waiting on, like regenerating a conversation) always go before background ones (the
+// periodic per-user passes: rescoring, rollover, retention, reviews, digests; exports). Within a class, users take turns round-robin, and no user runs more
+// than JOB_QUEUE_MAX_RUNNING_PER_USER jobs at once, so one heavy user can't starve the rest.
+// A user with JOB_QUEUE_MAX_QUEUED_PER_USER jobs waiting gets their next submission rejected.
+// Jobs run with the submitting request's task-locals: its Firestore project override and
+// its LLM debug logging context.
+
+use axum::http::StatusCode;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, Semaphore};
+
+use crate::llm_debug::carry_debug_context;
+use crate::services::firestore::carry_project_override;
+
+/// Busiest users listed in the stats
+const MAX_USERS_IN_STATS: usize = 20;
+
+/// Scheduling class; every queued interactive job starts before any background one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// A user is waiting on the result
+    Interactive,
+    /// Periodic or bulk work nobody is watching
+    Background,
+}
+
+impl JobPriority {
+    const ALL: [JobPriority; 2] = [JobPriority::Interactive, JobPriority::Background];
+
+    fn index(self) -> usize {
+        match self {
+            JobPriority::Interactive => 0,
+            JobPriority::Background => 1,
+        }
+    }
+}
+
+type JobFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct Job {
+    name: &'static str,
+    future: JobFuture,
+}
+
+#[derive(Default)]
+struct UserJobs {
+    pending: [VecDeque<Job>; 2],
+    running: usize,
+}
+
+impl UserJobs {
+    fn queued(&self) -> usize {
+        self.pending.iter().map(VecDeque::len).sum()
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    users: HashMap<String, UserJobs>,
+    /// Per class, users with pending jobs in the order they get their next turn
+    turns: [VecDeque<String>; 2],
+    completed: u64,
+}
+
+impl QueueState {
+    /// Next job to start: the highest class with a runnable job, and within it the first
+    /// user in turn order who is under the per-user cap. That user moves to the back.
+    fn next_job(&mut self, max_running_per_user: usize) -> Option<(String, Job)> {
+        for priority in JobPriority::ALL {
+            let class = priority.index();
+            for _ in 0..self.turns[class].len() {
+                let uid = self.turns[class].pop_front()?;
+                let Some(user) = self.users.get_mut(&uid) else {
+                    continue;
+                };
+                if user.pending[class].is_empty() {
+                    continue;
+                }
+                if max_running_per_user > 0 && user.running >= max_running_per_user {
+                    self.turns[class].push_back(uid);
+                    continue;
+                }
+
+                let job = user.pending[class].pop_front().expect("checked non-empty");
+                user.running += 1;
+                if !user.pending[class].is_empty() {
+                    self.turns[class].push_back(uid.clone());
+                }
+                return Some((uid, job));
+            }
+        }
+        None
+    }
+}
+
+/// Rejection when a user already has the maximum jobs waiting
+#[derive(Debug)]
+pub struct JobQueueFull {
+    pub max_queued_per_user: usize,
+}
+
+impl std::fmt::Display for JobQueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Too many jobs waiting: at most {} per user", self.max_queued_per_user)
+    }
+}
+
+impl std::error::Error for JobQueueFull {}
+
+/// Handler error: 429 with the rejection message
+impl From<JobQueueFull> for (StatusCode, String) {
+    fn from(e: JobQueueFull) -> Self {
+        (StatusCode::TOO_MANY_REQUESTS, e.to_string())
+    }
+}
+
+/// Queued and running jobs for one user
+#[derive(Debug, Clone, Serialize)]
+pub struct UserJobLoad {
+    pub uid: String,
+    pub running: usize,
+    pub queued_interactive: usize,
+    pub queued_background: usize,
+}
+
+/// Current queue depth, for metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct JobQueueStats {
+    pub workers: usize,
+    pub max_running_per_user: usize,
+    pub max_queued_per_user: usize,
+    pub running: usize,
+    pub queued_interactive: usize,
+    pub queued_background: usize,
+    pub active_users: usize,
+    /// Jobs finished since startup
+    pub completed: u64,
+    /// Busiest users first
+    pub users: Vec<UserJobLoad>,
+}
+
+/// Shared queue; clones submit to the same workers
+#[derive(Clone)]
+pub struct JobQueue {
+    workers: usize,
+    max_running_per_user: usize,
+    max_queued_per_user: usize,
+    state: Arc<Mutex<QueueState>>,
+    /// Wakes the dispatcher when a job is submitted or a user's running job finishes
+    wake: Arc<Notify>,
+}
+
+impl JobQueue {
+    /// Queue with its dispatcher running; needs a Tokio runtime
+    pub fn start(workers: usize, max_running_per_user: usize, max_queued_per_user: usize) -> Self {
+        let queue = Self::new(workers, max_running_per_user, max_queued_per_user);
+        tokio::spawn(queue.clone().dispatch());
+        tracing::info!(
+            "Job queue started with {} workers ({} running, {} queued per user)",
+            queue.workers,
+            max_running_per_user,
+            max_queued_per_user
+        );
+        queue
+    }
+
+    fn new(workers: usize, max_running_per_user: usize, max_queued_per_user: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            max_running_per_user,
+            max_queued_per_user,
+            state: Arc::new(Mutex::new(QueueState::default())),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Queue a job for `uid`. `name` labels it in logs.
+    pub fn submit<F>(&self, uid: &str, priority: JobPriority, name: &'static str, job: F) -> Result<(), JobQueueFull>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        {
+            let mut state = self.state.lock().unwrap();
+            let user = state.users.entry(uid.to_string()).or_default();
+            if self.max_queued_per_user > 0 && user.queued() >= self.max_queued_per_user {
+                tracing::warn!("Job queue full for user {}; rejected {}", uid, name);
+                return Err(JobQueueFull {
+                    max_queued_per_user: self.max_queued_per_user,
+                });
+            }
+
+            let class = priority.index();
+            let first_in_class = user.pending[class].is_empty();
+            user.pending[class].push_back(Job {
+                name,
+                future: Box::pin(carry_project_override(carry_debug_context(job))),
+            });
+            if first_in_class {
+                state.turns[class].push_back(uid.to_string());
+            }
+        }
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// Queue one user's share of a periodic pass as a background job. `job` gets the uid
+    /// and its error is logged; a user whose queue is full is picked up on a later pass.
+    pub fn submit_for_user<F, Fut, T, E>(&self, uid: &str, name: &'static str, job: F)
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        let job_uid = uid.to_string();
+        let run = job(job_uid.clone());
+        let job = async move {
+            if let Err(e) = run.await {
+                tracing::error!("Job {} failed for user {}: {}", name, job_uid, e);
+            }
+        };
+        if let Err(e) = self.submit(uid, JobPriority::Background, name, job) {
+            tracing::warn!("Job {} skipped for user {}: {}", name, uid, e);
+        }
+    }
+
+    /// Start jobs as workers free up, in fair order
+    async fn dispatch(self) {
+        let workers = Arc::new(Semaphore::new(self.workers));
+        loop {
+            let permit = workers
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("job worker semaphore is never closed");
+
+            // A single dispatcher waits here, so notify_one never loses a wakeup
+            let (uid, job) = loop {
+                let next = self.state.lock().unwrap().next_job(self.max_running_per_user);
+                match next {
+                    Some(next) => break next,
+                    None => self.wake.notified().await,
+                }
+            };
+
+            let queue = self.clone();
+            tokio::spawn(async move {
+                // Run on its own task so a panicking job still frees its slot
+                if let Err(e) = tokio::spawn(job.future).await {
+                    tracing::error!("Job {} for user {} panicked: {}", job.name, uid, e);
+                }
+                queue.finish(&uid);
+                drop(permit);
+            });
+        }
+    }
+
+    /// Mark one of the user's jobs finished and drop the entry once idle
+    fn finish(&self, uid: &str) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.completed += 1;
+            if let Some(user) = state.users.get_mut(uid) {
+                user.running -= 1;
+                if user.running == 0 && user.queued() == 0 {
+                    state.users.remove(uid);
+                }
+            }
+        }
+        // The user may have been held back by the per-user cap
+        self.wake.notify_one();
+    }
+
+    pub fn stats(&self) -> JobQueueStats {
+        let state = self.state.lock().unwrap();
+        let mut loads: Vec<UserJobLoad> = state
+            .users
+            .iter()
+            .map(|(uid, user)| UserJobLoad {
+                uid: uid.clone(),
+                running: user.running,
+                queued_interactive: user.pending[JobPriority::Interactive.index()].len(),
+                queued_background: user.pending[JobPriority::Background.index()].len(),
+            })
+            .collect();
+        loads.sort_by_key(|l| std::cmp::Reverse(l.running + l.queued_interactive + l.queued_background));
+
+        JobQueueStats {
+            workers: self.workers,
+            max_running_per_user: self.max_running_per_user,
+            max_queued_per_user: self.max_queued_per_user,
+            running: loads.iter().map(|l| l.running).sum(),
+            queued_interactive: loads.iter().map(|l| l.queued_interactive).sum(),
+            queued_background: loads.iter().map(|l| l.queued_background).sum(),
+            active_users: loads.len(),
+            completed: state.completed,
+            users: loads.into_iter().take(MAX_USERS_IN_STATS).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fair_order_across_users_and_classes() {
+        let queue = JobQueue::new(1, 1, 3);
+        for uid in ["heavy", "heavy", "heavy", "light"] {
+            queue.submit(uid, JobPriority::Background, "rescore", async {}).unwrap();
+        }
+        queue.submit("light", JobPriority::Interactive, "regenerate", async {}).unwrap();
+        assert!(queue.submit("heavy", JobPriority::Background, "rescore", async {}).is_err());
+
+        let mut state = queue.state.lock().unwrap();
+        let mut order = Vec::new();
+        while let Some((uid, job)) = state.next_job(queue.max_running_per_user) {
+            order.push(format!("{}:{}", uid, job.name));
+            // Finish it right away so the per-user cap doesn't hold the user back
+            state.users.get_mut(&uid).unwrap().running -= 1;
+        }
+        assert_eq!(
+            order,
+            vec!["light:regenerate", "heavy:rescore", "light:rescore", "heavy:rescore", "heavy:rescore"]
+        );
+        drop(state);
+
+        // A user at the cap waits while others run
+        let queue = JobQueue::new(2, 1, 3);
+        for uid in ["a", "a", "b"] {
+            queue.submit(uid, JobPriority::Background, "rescore", async {}).unwrap();
+        }
+        let mut state = queue.state.lock().unwrap();
+        assert_eq!(state.next_job(1).unwrap().0, "a");
+        assert_eq!(state.next_job(1).unwrap().0, "b");
+        assert!(state.next_job(1).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_submit_for_user_runs_the_job_with_its_uid() {
+        let queue = JobQueue::start(1, 1, 3);
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        queue.submit_for_user("u1", "test", |uid| async move {
+            let _ = tx.send(uid);
+            Err::<(), _>("logged, not propagated")
+        });
+        assert_eq!(rx.await.unwrap(), "u1");
+
+        // A full queue skips the user instead of failing the pass
+        let queue = JobQueue::new(1, 1, 1);
+        for _ in 0..2 {
+            queue.submit_for_user("u1", "test", |_| async { Ok::<_, String>(()) });
+        }
+        assert_eq!(queue.state.lock().unwrap().users["u1"].queued(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_in_the_submitting_project() {
+        use crate::services::firestore::{current_project_override, with_project_override};
+
+        let queue = JobQueue::start(1, 1, 3);
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        with_project_override("staging".to_string(), async {
+            queue
+                .submit("u1", JobPriority::Background, "test", async move {
+                    let _ = tx.send(current_project_override());
+                })
+                .unwrap();
+        })
+        .await;
+        assert_eq!(rx.await.unwrap().as_deref(), Some("staging"));
+    }
+}
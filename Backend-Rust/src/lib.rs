@@ -0,0 +1,85 @@
+// OMI Desktop Backend - library crate
+// Shared by the API server (main.rs) and operational binaries in src/bin
+
+use std::sync::Arc;
+
+pub mod auth;
+pub mod body_limit;
+pub mod client_info;
+pub mod config;
+pub mod conversation_lock;
+pub mod encryption;
+pub mod environment;
+pub mod etag;
+pub mod impersonation;
+pub mod job_queue;
+pub mod llm;
+pub mod llm_debug;
+pub mod llm_limit;
+pub mod models;
+pub mod routes;
+pub mod security;
+pub mod services;
+
+use config::Config;
+use services::{FirestoreService, IntegrationService, RedisService};
+
+/// Application state shared across handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub firestore: Arc<FirestoreService>,
+    /// Conversations, memories, action items, messages and settings (Firestore or local SQLite)
+    pub storage: Arc<dyn services::storage::Storage>,
+    pub integrations: Arc<IntegrationService>,
+    pub redis: Option<Arc<RedisService>>,
+    pub config: Arc<Config>,
+    pub crisp_session_cache: routes::crisp::SessionCache,
+    pub screen_context: services::screen_context::ScreenContextBuffer,
+    pub presence: services::presence::PresenceStore,
+    pub search_index: services::universal_search::SearchIndexCache,
+    pub mailer: Option<Arc<services::mailer::Mailer>>,
+    pub notion: Arc<services::notion::NotionService>,
+    pub slack: Arc<services::slack::SlackService>,
+    pub llm_limiter: llm_limit::LlmLimiter,
+    pub jobs: job_queue::JobQueue,
+    pub events: services::events::EventBus,
+}
+
+impl AppState {
+    /// Model per LLM task: configured models with admin overrides applied
+    pub async fn llm_task_models(&self) -> llm::TaskModels {
+        self.firestore.llm_task_models(&self.config.llm_models).await
+    }
+
+    /// LLM client for a user's data: their own key (BYOK) if configured, else the
+    /// server key. None when neither is available; an error when the user's saved
+    /// key can't be read.
+    pub async fn llm_client(&self, uid: &str) -> Result<Option<llm::LlmClient>, services::llm_keys::UserKeyError> {
+        let Some(api_key) = self.llm_api_key(uid).await? else {
+            return Ok(None);
+        };
+        Ok(Some(llm::LlmClient::new(api_key).with_task_models(self.llm_task_models().await)))
+    }
+
+    /// LLM client on the server key, for platform checks that shouldn't run on a user's key
+    pub async fn server_llm_client(&self) -> Option<llm::LlmClient> {
+        let api_key = self.config.gemini_api_key.clone()?;
+        Some(llm::LlmClient::new(api_key).with_task_models(self.llm_task_models().await))
+    }
+
+    /// Whether the app's prompts are blocked by moderation (checked on the server key)
+    pub async fn app_blocked(&self, app: &models::App) -> bool {
+        let llm = self.server_llm_client().await;
+        let moderation = services::app_moderation::ensure_moderated(&self.firestore, llm.as_ref(), app).await;
+        if moderation.blocks_execution() {
+            tracing::warn!("Refusing to run prompts of app {} (flagged as {})", app.id, moderation.verdict.as_str());
+            return true;
+        }
+        false
+    }
+
+    /// API key for LLM calls on a user's data (see `llm_client`)
+    pub async fn llm_api_key(&self, uid: &str) -> Result<Option<String>, services::llm_keys::UserKeyError> {
+        services::llm_keys::api_key_for_user(&self.firestore, uid, self.config.gemini_api_key.as_deref()).await
+    }
+}
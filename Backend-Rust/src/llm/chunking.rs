@@ -0,0 +1,125 @@
+// Long-transcript summarization - Map-reduce over transcript chunks
+// Chunks are condensed into notes in parallel; the combined notes then stand in
+// for the transcript in the structure, action item and memory prompts.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::estimate::estimate_tokens;
+use crate::models::TranscriptSegment;
+
+/// Transcripts above this many tokens are condensed chunk by chunk first
+pub const LONG_TRANSCRIPT_TOKENS: usize = 60_000;
+/// Target size of one chunk
+pub const CHUNK_TOKEN_BUDGET: usize = 15_000;
+/// Chunk summaries running at once
+pub const MAX_CONCURRENT_CHUNKS: usize = 4;
+/// Output budget for one chunk's notes
+pub const CHUNK_NOTES_MAX_OUTPUT_TOKENS: usize = 2000;
+/// Allowance for the speaker label and separators of each segment
+const SEGMENT_OVERHEAD_TOKENS: usize = 4;
+
+/// Progress of a long-transcript processing run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingProgress {
+    /// "summarizing_chunks" or "synthesizing"
+    pub stage: String,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProcessingProgress {
+    pub fn new(stage: &str, chunks_done: usize, chunks_total: usize) -> Self {
+        Self {
+            stage: stage.to_string(),
+            chunks_done,
+            chunks_total,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Called as chunks complete (see LlmClient::with_progress)
+pub type ProgressCallback = Arc<dyn Fn(ProcessingProgress) + Send + Sync>;
+
+pub fn needs_chunking(transcript: &str) -> bool {
+    estimate_tokens(transcript) > LONG_TRANSCRIPT_TOKENS
+}
+
+/// Split segments into consecutive chunks of about `budget` tokens.
+/// A single segment larger than the budget gets a chunk of its own.
+pub fn chunk_segments(segments: &[TranscriptSegment], budget: usize) -> Vec<&[TranscriptSegment]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (i, segment) in segments.iter().enumerate() {
+        let segment_tokens = estimate_tokens(&segment.text) + SEGMENT_OVERHEAD_TOKENS;
+        if i > start && tokens + segment_tokens > budget {
+            chunks.push(&segments[start..i]);
+            start = i;
+            tokens = 0;
+        }
+        tokens += segment_tokens;
+    }
+    if start < segments.len() {
+        chunks.push(&segments[start..]);
+    }
+    chunks
+}
+
+/// Join chunk notes, in order, into the text used in place of the transcript
+pub fn combine_notes(notes: &[String]) -> String {
+    let total = notes.len();
+    let parts = notes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| format!("[Part {} of {}]\n{}", i + 1, total, n.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!(
+        "(This conversation was too long to include verbatim. Below are detailed notes on each part, in order.)\n\n{}",
+        parts
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(words: usize) -> TranscriptSegment {
+        TranscriptSegment {
+            text: "word ".repeat(words),
+            speaker: "SPEAKER_00".to_string(),
+            speaker_id: 0,
+            is_user: false,
+            person_id: None,
+            start: 0.0,
+            end: 0.0,
+            language: None,
+            words: None,
+        }
+    }
+
+    #[test]
+    fn test_chunk_segments_by_budget() {
+        let segments: Vec<_> = (0..10).map(|_| segment(96)).collect();
+        let chunks = chunk_segments(&segments, 300);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![3, 3, 3, 1]);
+
+        // Oversized segments are never split or dropped
+        let segments = vec![segment(500), segment(10)];
+        let chunks = chunk_segments(&segments, 100);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![1, 1]);
+        assert!(chunk_segments(&[], 100).is_empty());
+    }
+
+    #[test]
+    fn test_combine_notes_keeps_order() {
+        let combined = combine_notes(&["first".to_string(), " second ".to_string()]);
+        let first = combined.find("[Part 1 of 2]\nfirst").unwrap();
+        let second = combined.find("[Part 2 of 2]\nsecond").unwrap();
+        assert!(first < second);
+    }
+}
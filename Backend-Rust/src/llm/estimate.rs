@@ -0,0 +1,273 @@
+// LLM cost estimates - Projected tokens, cost and latency for conversation processing
+// Mirrors the calls made by LlmClient without contacting the model.
+
+use serde::Serialize;
+
+use super::chunking::{
+    CHUNK_NOTES_MAX_OUTPUT_TOKENS, CHUNK_TOKEN_BUDGET, LONG_TRANSCRIPT_TOKENS,
+};
+use super::prompts::{
+    ACTION_ITEMS_PROMPT, BRIEF_SUMMARY_PROMPT, BRIEF_TRANSCRIPT_THRESHOLD, CHUNK_NOTES_PROMPT,
+    MEMORIES_PROMPT, STRUCTURE_PROMPT,
+};
+
+/// Price and throughput assumptions for a model
+#[derive(Debug, Clone, Copy)]
+pub struct ModelProfile {
+    pub model: &'static str,
+    /// USD per million input tokens
+    pub input_per_million: f64,
+    /// USD per million output tokens
+    pub output_per_million: f64,
+    /// Input tokens processed per second
+    pub prefill_tokens_per_second: f64,
+    /// Output tokens generated per second
+    pub output_tokens_per_second: f64,
+    /// Fixed per-call overhead (network, queueing, thinking)
+    pub call_overhead_seconds: f64,
+}
+
+/// Known models; the first entry must be client::DEFAULT_MODEL (used for unknown models)
+const MODEL_PROFILES: &[ModelProfile] = &[
+    ModelProfile {
+        model: "gemini-3-pro-preview",
+        input_per_million: 2.0,
+        output_per_million: 12.0,
+        prefill_tokens_per_second: 8000.0,
+        output_tokens_per_second: 60.0,
+        call_overhead_seconds: 4.0,
+    },
+    ModelProfile {
+        model: "gemini-2.5-pro",
+        input_per_million: 1.25,
+        output_per_million: 10.0,
+        prefill_tokens_per_second: 8000.0,
+        output_tokens_per_second: 80.0,
+        call_overhead_seconds: 3.0,
+    },
+    ModelProfile {
+        model: "gemini-2.5-flash",
+        input_per_million: 0.3,
+        output_per_million: 2.5,
+        prefill_tokens_per_second: 20000.0,
+        output_tokens_per_second: 200.0,
+        call_overhead_seconds: 1.0,
+    },
+];
+
+/// Share of the output budget a call typically uses
+const EXPECTED_OUTPUT_RATIO: f64 = 0.4;
+/// Allowance for the existing action items / memories lists sent for deduplication
+const EXISTING_ACTION_ITEMS_TOKENS: usize = 400;
+const EXISTING_MEMORIES_TOKENS: usize = 2000;
+
+/// Profile for a model; unknown models are priced like the default model
+pub fn model_profile(model: &str) -> ModelProfile {
+    MODEL_PROFILES
+        .iter()
+        .find(|p| p.model == model)
+        .copied()
+        .unwrap_or(MODEL_PROFILES[0])
+}
+
+/// Approximate token count: ~4 characters per token for alphabetic scripts,
+/// one token per CJK character, at least one per word
+pub fn estimate_tokens(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|word| {
+            let (cjk, other) = word.chars().fold((0usize, 0usize), |(cjk, other), c| {
+                if is_cjk(c) {
+                    (cjk + 1, other)
+                } else {
+                    (cjk, other + 1)
+                }
+            });
+            cjk + if other > 0 { other.div_ceil(4) } else { 0 }
+        })
+        .sum()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF       // Hiragana, Katakana
+        | 0x3400..=0x4DBF     // CJK Extension A
+        | 0x4E00..=0x9FFF     // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF     // Hangul
+    )
+}
+
+/// One planned LLM call
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmCallEstimate {
+    pub step: String,
+    pub input_tokens: usize,
+    pub max_output_tokens: usize,
+}
+
+impl LlmCallEstimate {
+    fn new(step: &str, prompt_tokens: usize, max_output_tokens: usize) -> Self {
+        Self {
+            step: step.to_string(),
+            input_tokens: prompt_tokens,
+            max_output_tokens,
+        }
+    }
+}
+
+/// Calls made by full conversation processing (see process_conversation_with_calendar)
+pub fn processing_calls(
+    transcript: &str,
+    custom_prompt: Option<&str>,
+    extract_memories: bool,
+) -> Vec<LlmCallEstimate> {
+    let mut transcript_tokens = estimate_tokens(transcript);
+    let custom_tokens = custom_prompt.map(estimate_tokens).unwrap_or(0);
+
+    if transcript.split_whitespace().count() < BRIEF_TRANSCRIPT_THRESHOLD {
+        return vec![LlmCallEstimate::new(
+            "brief_summary",
+            estimate_tokens(BRIEF_SUMMARY_PROMPT) + transcript_tokens + custom_tokens,
+            500,
+        )];
+    }
+
+    // Long transcripts are condensed chunk by chunk; later calls see the notes instead
+    let mut calls = Vec::new();
+    if transcript_tokens > LONG_TRANSCRIPT_TOKENS {
+        let chunk_count = transcript_tokens.div_ceil(CHUNK_TOKEN_BUDGET);
+        let prompt_tokens = estimate_tokens(CHUNK_NOTES_PROMPT);
+        for _ in 0..chunk_count {
+            calls.push(LlmCallEstimate::new(
+                "chunk_notes",
+                prompt_tokens + transcript_tokens / chunk_count,
+                CHUNK_NOTES_MAX_OUTPUT_TOKENS,
+            ));
+        }
+        transcript_tokens =
+            (chunk_count as f64 * CHUNK_NOTES_MAX_OUTPUT_TOKENS as f64 * EXPECTED_OUTPUT_RATIO) as usize;
+    }
+
+    calls.extend([
+        LlmCallEstimate::new(
+            "structure",
+            estimate_tokens(STRUCTURE_PROMPT) + transcript_tokens + custom_tokens,
+            1500,
+        ),
+        LlmCallEstimate::new(
+            "action_items",
+            estimate_tokens(ACTION_ITEMS_PROMPT) + transcript_tokens + EXISTING_ACTION_ITEMS_TOKENS,
+            1500,
+        ),
+    ]);
+    if extract_memories {
+        calls.push(LlmCallEstimate::new(
+            "memories",
+            estimate_tokens(MEMORIES_PROMPT) + transcript_tokens + EXISTING_MEMORIES_TOKENS,
+            500,
+        ));
+    }
+    calls
+}
+
+/// Call made when regenerating only the summary (extract_structure)
+pub fn summary_calls(transcript: &str, custom_prompt: Option<&str>) -> Vec<LlmCallEstimate> {
+    vec![LlmCallEstimate::new(
+        "structure",
+        estimate_tokens(STRUCTURE_PROMPT)
+            + estimate_tokens(transcript)
+            + custom_prompt.map(estimate_tokens).unwrap_or(0),
+        1500,
+    )]
+}
+
+/// Call made when reprocessing with an app's memory prompt (run_memory_prompt)
+pub fn memory_prompt_calls(prompt_template: &str, context: &str) -> Vec<LlmCallEstimate> {
+    vec![LlmCallEstimate::new(
+        "app_memory_prompt",
+        estimate_tokens(prompt_template) + estimate_tokens(context),
+        2000,
+    )]
+}
+
+/// Projected cost and latency of a set of calls (run one after another)
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessingEstimate {
+    pub model: String,
+    pub transcript_tokens: usize,
+    pub input_tokens: usize,
+    /// Output tokens at typical usage
+    pub expected_output_tokens: usize,
+    /// Output token budget across calls
+    pub max_output_tokens: usize,
+    pub expected_cost_usd: f64,
+    /// Cost if every call used its full output budget
+    pub max_cost_usd: f64,
+    pub expected_latency_seconds: f64,
+    pub calls: Vec<LlmCallEstimate>,
+}
+
+pub fn estimate(model: &str, transcript: &str, calls: Vec<LlmCallEstimate>) -> ProcessingEstimate {
+    let profile = model_profile(model);
+    let input_tokens: usize = calls.iter().map(|c| c.input_tokens).sum();
+    let max_output_tokens: usize = calls.iter().map(|c| c.max_output_tokens).sum();
+    let expected_output_tokens = (max_output_tokens as f64 * EXPECTED_OUTPUT_RATIO).round() as usize;
+
+    let cost = |output: usize| {
+        let usd = input_tokens as f64 / 1e6 * profile.input_per_million
+            + output as f64 / 1e6 * profile.output_per_million;
+        (usd * 1e5).round() / 1e5
+    };
+    let latency = calls.len() as f64 * profile.call_overhead_seconds
+        + input_tokens as f64 / profile.prefill_tokens_per_second
+        + expected_output_tokens as f64 / profile.output_tokens_per_second;
+
+    ProcessingEstimate {
+        model: profile.model.to_string(),
+        transcript_tokens: estimate_tokens(transcript),
+        input_tokens,
+        expected_output_tokens,
+        max_output_tokens,
+        expected_cost_usd: cost(expected_output_tokens),
+        max_cost_usd: cost(max_output_tokens),
+        expected_latency_seconds: (latency * 10.0).round() / 10.0,
+        calls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hi there"), 3);
+        assert_eq!(estimate_tokens("internationalization"), 5);
+        assert_eq!(estimate_tokens("会議は三時です"), 7);
+    }
+
+    #[test]
+    fn test_processing_estimate_follows_pipeline() {
+        let brief = processing_calls("just a few words", None, true);
+        assert_eq!(brief.len(), 1);
+        assert_eq!(brief[0].step, "brief_summary");
+
+        let long = "word ".repeat(1000);
+        let full = processing_calls(&long, None, false);
+        let steps: Vec<&str> = full.iter().map(|c| c.step.as_str()).collect();
+        assert_eq!(steps, vec!["structure", "action_items"]);
+
+        let est = estimate("unknown-model", &long, full);
+        assert_eq!(est.model, "gemini-3-pro-preview");
+        assert_eq!(est.transcript_tokens, 1000);
+        assert_eq!(est.max_output_tokens, 3000);
+        assert!(est.max_cost_usd > est.expected_cost_usd);
+        assert!(est.expected_latency_seconds > 0.0);
+
+        let very_long = "word ".repeat(70_000);
+        let chunked = processing_calls(&very_long, None, false);
+        let chunk_calls = chunked.iter().filter(|c| c.step == "chunk_notes").count();
+        assert_eq!(chunk_calls, 5);
+        assert!(chunked.last().unwrap().input_tokens < 70_000);
+    }
+}
@@ -1,7 +1,13 @@
 // LLM module
 
+pub mod chunking;
 pub mod client;
+pub mod estimate;
 pub mod persona;
 pub mod prompts;
+pub mod routing;
+pub mod titles;
+pub mod topics;
 
 pub use client::LlmClient;
+pub use routing::{TaskKind, TaskModels};
@@ -2,6 +2,7 @@
 // Port from Python backend persona functionality
 
 use super::client::LlmClient;
+use super::routing::TaskKind;
 use crate::models::MemoryDB;
 use serde::Deserialize;
 
@@ -148,6 +149,7 @@ impl LlmClient {
         // Step 2: Condense memories into personality profile
         let condensation_prompt = MEMORY_CONDENSATION_PROMPT.replace("{memories}", &memories_text);
         let profile_json = self.call_with_schema(
+            TaskKind::Persona,
             &condensation_prompt,
             Some(0.7),
             Some(2000),
@@ -172,11 +174,11 @@ impl LlmClient {
 
         // Step 3: Generate the persona system prompt
         let system_prompt_request = PERSONA_SYSTEM_PROMPT_TEMPLATE.replace("{profile}", &profile_text);
-        let persona_prompt = self.call_text(&system_prompt_request, Some(0.7), Some(1500)).await?;
+        let persona_prompt = self.call_text(TaskKind::Persona, &system_prompt_request, Some(0.7), Some(1500)).await?;
 
         // Step 4: Generate short description
         let description_request = DESCRIPTION_PROMPT.replace("{profile}", &profile_text);
-        let description = self.call_text(&description_request, Some(0.7), Some(300)).await?;
+        let description = self.call_text(TaskKind::Persona, &description_request, Some(0.7), Some(300)).await?;
 
         // Ensure description is within limits
         let description = if description.len() > 250 {
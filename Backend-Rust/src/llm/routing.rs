@@ -0,0 +1,179 @@
+// LLM model routing - Which model handles each kind of task
+// Defaults come from LLM_MODEL / LLM_MODEL_<TASK>; admins can override per task at runtime.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use super::client::DEFAULT_MODEL;
+
+/// How long admin overrides are cached before being reloaded from Firestore
+const OVERRIDES_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Kind of work an LLM call does, used to pick its model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Conversation title, overview and structure, chunk notes, app memory prompts
+    Summary,
+    /// Short titles for chat sessions and memos
+    Title,
+    /// Action item extraction and scoring
+    ActionItems,
+    /// Memory extraction
+    Memories,
+    /// Chat helpers: context checks, date ranges, question rewriting, attachments
+    Chat,
+    /// Knowledge graph entity extraction
+    KnowledgeGraph,
+    /// Goal progress evaluation
+    Goals,
+    /// Follow-up email drafts
+    Email,
+    /// Persona profiles and prompts
+    Persona,
+    /// Quick yes/no checks, such as whether a recording is worth keeping
+    Classification,
+    /// Relationship notes for the people view
+    People,
+}
+
+impl TaskKind {
+    pub const ALL: [TaskKind; 11] = [
+        TaskKind::Summary,
+        TaskKind::Title,
+        TaskKind::ActionItems,
+        TaskKind::Memories,
+        TaskKind::Chat,
+        TaskKind::KnowledgeGraph,
+        TaskKind::Goals,
+        TaskKind::Email,
+        TaskKind::Persona,
+        TaskKind::Classification,
+        TaskKind::People,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Summary => "summary",
+            TaskKind::Title => "title",
+            TaskKind::ActionItems => "action_items",
+            TaskKind::Memories => "memories",
+            TaskKind::Chat => "chat",
+            TaskKind::KnowledgeGraph => "knowledge_graph",
+            TaskKind::Goals => "goals",
+            TaskKind::Email => "email",
+            TaskKind::Persona => "persona",
+            TaskKind::Classification => "classification",
+            TaskKind::People => "people",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<TaskKind> {
+        TaskKind::ALL.into_iter().find(|t| t.as_str() == value)
+    }
+
+    /// Environment variable holding this task's configured model
+    pub fn env_var(&self) -> String {
+        format!("LLM_MODEL_{}", self.as_str().to_uppercase())
+    }
+}
+
+/// Model per task, falling back to a default model
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskModels {
+    pub default_model: String,
+    pub tasks: HashMap<TaskKind, String>,
+}
+
+impl Default for TaskModels {
+    fn default() -> Self {
+        Self {
+            default_model: DEFAULT_MODEL.to_string(),
+            tasks: HashMap::new(),
+        }
+    }
+}
+
+impl TaskModels {
+    /// Read LLM_MODEL (default model) and LLM_MODEL_<TASK> (per-task models)
+    pub fn from_env() -> Self {
+        let non_empty = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            default_model: non_empty("LLM_MODEL").unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            tasks: TaskKind::ALL
+                .into_iter()
+                .filter_map(|task| non_empty(&task.env_var()).map(|model| (task, model)))
+                .collect(),
+        }
+    }
+
+    pub fn model_for(&self, task: TaskKind) -> &str {
+        self.tasks.get(&task).unwrap_or(&self.default_model)
+    }
+
+    /// These models with admin overrides applied on top
+    pub fn with_overrides(&self, overrides: &HashMap<TaskKind, String>) -> TaskModels {
+        let mut models = self.clone();
+        models.tasks.extend(overrides.iter().map(|(task, model)| (*task, model.clone())));
+        models
+    }
+}
+
+struct OverridesSnapshot {
+    overrides: HashMap<TaskKind, String>,
+    fetched_at: Instant,
+}
+
+/// Admin per-task model overrides, cached process-wide
+#[derive(Clone, Default)]
+pub struct ModelOverrides {
+    inner: Arc<RwLock<Option<OverridesSnapshot>>>,
+}
+
+impl ModelOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cached overrides, unless missing or stale
+    pub async fn get(&self) -> Option<HashMap<TaskKind, String>> {
+        let cached = self.inner.read().await;
+        cached
+            .as_ref()
+            .filter(|s| s.fetched_at.elapsed() < OVERRIDES_MAX_AGE)
+            .map(|s| s.overrides.clone())
+    }
+
+    pub async fn set(&self, overrides: HashMap<TaskKind, String>) {
+        *self.inner.write().await = Some(OverridesSnapshot {
+            overrides,
+            fetched_at: Instant::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_models_fall_back_and_override() {
+        let mut models = TaskModels::default();
+        models.tasks.insert(TaskKind::Title, "gemini-2.5-flash".to_string());
+        assert_eq!(models.model_for(TaskKind::Title), "gemini-2.5-flash");
+        assert_eq!(models.model_for(TaskKind::Summary), DEFAULT_MODEL);
+
+        let overrides = HashMap::from([(TaskKind::Summary, "gemini-2.5-pro".to_string())]);
+        let models = models.with_overrides(&overrides);
+        assert_eq!(models.model_for(TaskKind::Summary), "gemini-2.5-pro");
+        assert_eq!(models.model_for(TaskKind::Title), "gemini-2.5-flash");
+
+        assert_eq!(TaskKind::parse("action_items"), Some(TaskKind::ActionItems));
+        assert_eq!(TaskKind::ActionItems.env_var(), "LLM_MODEL_ACTION_ITEMS");
+        assert_eq!(TaskKind::parse("nope"), None);
+    }
+}
@@ -0,0 +1,82 @@
+// Conversation titles - Disambiguation against recent titles and the plain-title (no emoji) policy
+
+use chrono::NaiveDateTime;
+
+/// Recent titles sent to the model as "already used"
+pub const MAX_RECENT_TITLES: usize = 20;
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF     // Emoticons, symbols & pictographs, transport, flags
+        | 0x2600..=0x27BF     // Misc symbols, dingbats
+        | 0x2B00..=0x2BFF     // Arrows and stars (⭐)
+        | 0xFE0F              // Variation selector-16
+        | 0x200D              // Zero-width joiner
+    )
+}
+
+/// Remove emoji from a title and tidy the leftover whitespace
+pub fn strip_emojis(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| !is_emoji(*c))
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize(title: &str) -> String {
+    strip_emojis(title).to_lowercase()
+}
+
+/// If the model still produced a title the user already has, qualify it with the date
+/// (e.g. "Chat with Alex" -> "Chat with Alex (Mar 4)"). `started_at` is in the user's local time.
+pub fn disambiguate_title(title: &str, recent_titles: &[String], started_at: NaiveDateTime) -> String {
+    let taken = |candidate: &str| {
+        let candidate = normalize(candidate);
+        recent_titles.iter().any(|t| normalize(t) == candidate)
+    };
+    if title.trim().is_empty() || !taken(title) {
+        return title.to_string();
+    }
+
+    let dated = format!("{} ({})", title, started_at.format("%b %-d"));
+    if !taken(&dated) {
+        return dated;
+    }
+    format!("{} ({})", title, started_at.format("%b %-d, %H:%M"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_strip_emojis() {
+        assert_eq!(strip_emojis("🎉 Team Finalizes Q2 Budget"), "Team Finalizes Q2 Budget");
+        assert_eq!(strip_emojis("Launch ⭐️ Review 👨‍👩‍👧"), "Launch Review");
+        assert_eq!(strip_emojis("Café Plans — 2024"), "Café Plans — 2024");
+    }
+
+    #[test]
+    fn test_disambiguate_title() {
+        let started_at = NaiveDate::from_ymd_opt(2024, 3, 4)
+            .and_then(|d| d.and_hms_opt(15, 30, 0))
+            .unwrap();
+        let recent = vec!["Chat with Alex".to_string(), "Budget Review".to_string()];
+
+        assert_eq!(disambiguate_title("Roadmap Sync", &recent, started_at), "Roadmap Sync");
+        assert_eq!(
+            disambiguate_title("chat with alex", &recent, started_at),
+            "chat with alex (Mar 4)"
+        );
+
+        let recent = vec!["Chat with Alex".to_string(), "Chat with Alex (Mar 4)".to_string()];
+        assert_eq!(
+            disambiguate_title("Chat with Alex", &recent, started_at),
+            "Chat with Alex (Mar 4, 15:30)"
+        );
+    }
+}
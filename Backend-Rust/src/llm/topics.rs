@@ -0,0 +1,106 @@
+// Blocked memory topics - Topics the user never wants memorized
+// Added to the memory extraction prompt as hard constraints, then enforced again on the output.
+
+use super::prompts::sanitize_custom_prompt;
+
+/// Most topics a user can block
+pub const MAX_BLOCKED_TOPICS: usize = 50;
+/// Longest accepted topic, in characters
+pub const MAX_TOPIC_CHARS: usize = 60;
+/// Topic words shorter than this must match whole words (no prefix matching)
+const MIN_STEM_CHARS: usize = 4;
+
+/// Clean up a user's topic list: single-line, lowercase, de-duplicated and capped
+pub fn normalize_blocked_topics(topics: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for topic in topics {
+        let topic = sanitize_custom_prompt(topic)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let topic: String = topic.chars().take(MAX_TOPIC_CHARS).collect();
+        if !topic.is_empty() && !normalized.contains(&topic) {
+            normalized.push(topic);
+        }
+        if normalized.len() == MAX_BLOCKED_TOPICS {
+            break;
+        }
+    }
+    normalized
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Strip a plural ending so "finances" also matches "financial"
+fn stem(word: &str) -> &str {
+    let stemmed = word
+        .strip_suffix("es")
+        .filter(|s| s.chars().count() >= MIN_STEM_CHARS)
+        .or_else(|| word.strip_suffix('s'))
+        .unwrap_or(word);
+    if stemmed.chars().count() >= MIN_STEM_CHARS {
+        stemmed
+    } else {
+        word
+    }
+}
+
+fn word_matches(text_word: &str, topic_word: &str) -> bool {
+    let stemmed = stem(topic_word);
+    if stemmed.chars().count() >= MIN_STEM_CHARS {
+        text_word.starts_with(stemmed)
+    } else {
+        text_word == topic_word
+    }
+}
+
+/// The first blocked topic the text mentions, if any. Topic words match the start
+/// of words in the text ("health" matches "healthcare"), multi-word topics in order.
+pub fn matching_blocked_topic<'a>(text: &str, topics: &'a [String]) -> Option<&'a str> {
+    let text_words = words(text);
+    topics
+        .iter()
+        .find(|topic| {
+            let topic_words = words(topic);
+            !topic_words.is_empty()
+                && text_words.windows(topic_words.len()).any(|window| {
+                    window
+                        .iter()
+                        .zip(&topic_words)
+                        .all(|(text_word, topic_word)| word_matches(text_word, topic_word))
+                })
+        })
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_blocked_topic() {
+        let topics = normalize_blocked_topics(&[
+            " Finances ".to_string(),
+            "health".to_string(),
+            "HEALTH".to_string(),
+            "my ex".to_string(),
+            "{}".to_string(),
+        ]);
+        assert_eq!(topics, vec!["finances", "health", "my ex"]);
+
+        assert_eq!(
+            matching_blocked_topic("Sam is worried about their financial situation", &topics),
+            Some("finances")
+        );
+        assert_eq!(matching_blocked_topic("Sam switched healthcare providers", &topics), Some("health"));
+        assert_eq!(matching_blocked_topic("Sam ran into my ex at the cafe", &topics), Some("my ex"));
+        assert_eq!(matching_blocked_topic("Sam prefers the express train", &topics), None);
+        assert_eq!(matching_blocked_topic("Sam learned Rust", &[]), None);
+    }
+}
@@ -0,0 +1,180 @@
+// LLM debug logging - Redacted prompt/response pairs per request, for debugging quality issues
+// Opt-in via LLM_DEBUG_LOGGING. Each request gets an X-Request-Id (the client's, or a new
+// one) echoed on the response; LLM calls made while handling it are scrubbed of PII,
+// truncated and stored in llm_debug_logs for LLM_DEBUG_LOG_TTL_HOURS. Admins read them back
+// with GET /v1/admin/llm-debug/:request_id.
+// Jobs a request queues on the job queue are captured under its request ID; other
+// `tokio::spawn`ed work isn't.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{Duration, Utc};
+use regex::Regex;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+
+use crate::llm::TaskKind;
+use crate::models::LlmDebugEntry;
+use crate::services::firestore::carry_project_override;
+use crate::services::FirestoreService;
+use crate::AppState;
+
+/// Header carrying the request ID, in both directions
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+/// Longest client-supplied request ID accepted; longer ones are replaced
+const MAX_REQUEST_ID_CHARS: usize = 64;
+/// Prompts and responses are cut to this many characters after redaction
+const MAX_LOGGED_CHARS: usize = 4000;
+
+tokio::task_local! {
+    /// Where the current request's LLM calls are logged. Unset when logging is off.
+    static DEBUG_CONTEXT: DebugContext;
+}
+
+#[derive(Clone)]
+struct DebugContext {
+    request_id: String,
+    route: String,
+    firestore: Arc<FirestoreService>,
+    ttl: Duration,
+}
+
+/// Middleware that tags the request with an ID and, when debug logging is on, captures
+/// the LLM calls made while handling it
+pub async fn capture_llm_calls(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.config.llm_debug_logging {
+        return next.run(request).await;
+    }
+
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .filter(|id| valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let context = DebugContext {
+        request_id: request_id.clone(),
+        route: format!("{} {}", request.method(), request.uri().path()),
+        firestore: state.firestore.clone(),
+        ttl: Duration::hours(state.config.llm_debug_log_ttl_hours),
+    };
+
+    let mut response = DEBUG_CONTEXT.scope(context, next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Capture the current request's debug context now and re-apply it wherever `fut` is
+/// polled later, so LLM calls on work it hands off are logged under its request ID
+pub fn carry_debug_context<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let context = DEBUG_CONTEXT.try_with(|context| context.clone()).ok();
+    async move {
+        match context {
+            Some(context) => DEBUG_CONTEXT.scope(context, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_CHARS
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Log one LLM call of the current request, if debug logging captures it. The write
+/// happens in the background and never fails the call.
+pub fn record(task: TaskKind, model: &str, prompt: &str, outcome: Result<&str, &str>, duration: std::time::Duration) {
+    let Ok(context) = DEBUG_CONTEXT.try_with(|context| context.clone()) else {
+        return;
+    };
+
+    let now = Utc::now();
+    let (response, error) = match outcome {
+        Ok(response) => (Some(response), None),
+        Err(error) => (None, Some(error)),
+    };
+    let entry = LlmDebugEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        request_id: context.request_id,
+        route: context.route,
+        task: task.as_str().to_string(),
+        model: model.to_string(),
+        prompt: redact(prompt),
+        response: response.map(redact),
+        error: error.map(redact),
+        prompt_chars: prompt.chars().count(),
+        response_chars: response.map(|r| r.chars().count()).unwrap_or(0),
+        duration_ms: duration.as_millis() as u64,
+        created_at: now,
+        expire_at: now + context.ttl,
+    };
+
+    let firestore = context.firestore;
+    tokio::spawn(carry_project_override(async move {
+        if let Err(e) = firestore.save_llm_debug_entry(&entry).await {
+            tracing::warn!("Failed to save LLM debug entry for request {}: {}", entry.request_id, e);
+        }
+    }));
+}
+
+/// Patterns replaced before anything is stored, most specific first
+fn pii_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (r"(?i)[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}", "[EMAIL]"),
+            (r"(?i)\bhttps?://\S+", "[URL]"),
+            (r"\b(?:\d[ -]?){13,19}\b", "[CARD]"),
+            (r"\+?\(?\d{1,4}\)?[ .-]?\(?\d{2,4}\)?[ .-]?\d{3,4}[ .-]?\d{3,4}\b", "[PHONE]"),
+            (r"\b\d{6,}\b", "[NUMBER]"),
+        ]
+        .into_iter()
+        .map(|(pattern, label)| (Regex::new(pattern).unwrap(), label))
+        .collect()
+    })
+}
+
+/// Scrub emails, URLs, card/phone numbers and long digit runs, then truncate
+pub fn redact(text: &str) -> String {
+    let scrubbed = pii_patterns()
+        .iter()
+        .fold(text.to_string(), |text, (pattern, label)| {
+            pattern.replace_all(&text, *label).into_owned()
+        });
+
+    let total = scrubbed.chars().count();
+    if total <= MAX_LOGGED_CHARS {
+        return scrubbed;
+    }
+    let kept: String = scrubbed.chars().take(MAX_LOGGED_CHARS).collect();
+    format!("{}…[{} more chars]", kept, total - MAX_LOGGED_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_scrubs_pii_and_truncates() {
+        let text = "Mail jane.doe@example.com or call +1 (415) 555-0134, see https://x.io/a?b=1. \
+                    Card 4111 1111 1111 1111, order 12345678, meeting at 10:30 on 2024-03-10.";
+        assert_eq!(
+            redact(text),
+            "Mail [EMAIL] or call [PHONE], see [URL] \
+             Card [CARD], order [NUMBER], meeting at 10:30 on 2024-03-10."
+        );
+
+        let long = "a".repeat(MAX_LOGGED_CHARS + 5);
+        assert!(redact(&long).ends_with("…[5 more chars]"));
+        assert!(valid_request_id("req-1_a"));
+        assert!(!valid_request_id("bad id"));
+    }
+}
@@ -0,0 +1,277 @@
+// Per-user LLM request limiting - Caps in-flight and queued LLM requests per uid
+// Configured via LLM_MAX_CONCURRENT_PER_USER and LLM_MAX_QUEUED_PER_USER (0 = unlimited).
+
+use axum::{
+    extract::{Extension, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+    Json,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::auth::AuthUser;
+use crate::client_info::ClientInfo;
+
+/// Suggested client backoff for a rejected request
+const RETRY_AFTER_SECS: u64 = 2;
+/// Busiest users listed in the stats
+const MAX_USERS_IN_STATS: usize = 20;
+
+struct UserSlots {
+    semaphore: Arc<Semaphore>,
+    in_flight: usize,
+    queued: usize,
+}
+
+/// Shared limiter; clones share the same per-user slots
+#[derive(Clone)]
+pub struct LlmLimiter {
+    max_concurrent: usize,
+    max_queued: usize,
+    users: Arc<Mutex<HashMap<String, UserSlots>>>,
+}
+
+/// Rejection when a user already has the maximum requests running and queued
+#[derive(Debug, Serialize)]
+pub struct LlmLimitExceeded {
+    pub error: String,
+    pub message: String,
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+    pub retry_after_secs: u64,
+}
+
+impl IntoResponse for LlmLimitExceeded {
+    fn into_response(self) -> Response {
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+        response
+    }
+}
+
+/// Held for the duration of an LLM request
+pub struct LlmPermit {
+    limiter: LlmLimiter,
+    uid: String,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for LlmPermit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.uid, |slots| slots.in_flight -= 1);
+    }
+}
+
+/// Counts a waiting request; undone if the request is dropped while queued
+struct QueueGuard<'a> {
+    limiter: &'a LlmLimiter,
+    uid: &'a str,
+    promoted: bool,
+}
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        if !self.promoted {
+            self.limiter.release(self.uid, |slots| slots.queued -= 1);
+        }
+    }
+}
+
+/// In-flight and queued requests for one user
+#[derive(Debug, Clone, Serialize)]
+pub struct UserLlmLoad {
+    pub uid: String,
+    pub in_flight: usize,
+    pub queued: usize,
+}
+
+/// Current limiter load, for metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmLimiterStats {
+    pub max_concurrent_per_user: usize,
+    pub max_queued_per_user: usize,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub active_users: usize,
+    /// Busiest users first
+    pub users: Vec<UserLlmLoad>,
+}
+
+impl LlmLimiter {
+    pub fn new(max_concurrent: usize, max_queued: usize) -> Self {
+        Self {
+            max_concurrent,
+            max_queued,
+            users: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Wait for a slot, or fail immediately when the user's queue is full
+    pub async fn acquire(&self, uid: &str) -> Result<LlmPermit, LlmLimitExceeded> {
+        if self.max_concurrent == 0 {
+            return Ok(LlmPermit {
+                limiter: self.clone(),
+                uid: uid.to_string(),
+                _permit: None,
+            });
+        }
+
+        let semaphore = {
+            let mut users = self.users.lock().unwrap();
+            let slots = users.entry(uid.to_string()).or_insert_with(|| UserSlots {
+                semaphore: Arc::new(Semaphore::new(self.max_concurrent)),
+                in_flight: 0,
+                queued: 0,
+            });
+            if slots.in_flight >= self.max_concurrent && slots.queued >= self.max_queued {
+                let in_flight = slots.in_flight;
+                let queued = slots.queued;
+                drop(users);
+                tracing::warn!(
+                    "LLM request limit hit for user {} ({} in flight, {} queued)",
+                    uid,
+                    in_flight,
+                    queued
+                );
+                return Err(LlmLimitExceeded {
+                    error: "llm_rate_limited".to_string(),
+                    message: format!(
+                        "Too many AI requests in progress: at most {} running and {} waiting per user",
+                        self.max_concurrent, self.max_queued
+                    ),
+                    max_concurrent: self.max_concurrent,
+                    max_queued: self.max_queued,
+                    retry_after_secs: RETRY_AFTER_SECS,
+                });
+            }
+            slots.queued += 1;
+            slots.semaphore.clone()
+        };
+
+        let mut guard = QueueGuard {
+            limiter: self,
+            uid,
+            promoted: false,
+        };
+        let permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("LLM limiter semaphore is never closed");
+
+        // Move from queued to in flight in one step so the entry isn't pruned in between
+        if let Some(slots) = self.users.lock().unwrap().get_mut(uid) {
+            slots.queued -= 1;
+            slots.in_flight += 1;
+        }
+        guard.promoted = true;
+
+        Ok(LlmPermit {
+            limiter: self.clone(),
+            uid: uid.to_string(),
+            _permit: Some(permit),
+        })
+    }
+
+    /// Update a user's counts and drop the entry once idle
+    fn release(&self, uid: &str, update: impl FnOnce(&mut UserSlots)) {
+        if self.max_concurrent == 0 {
+            return;
+        }
+        let mut users = self.users.lock().unwrap();
+        if let Some(slots) = users.get_mut(uid) {
+            update(slots);
+            if slots.in_flight == 0 && slots.queued == 0 {
+                users.remove(uid);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> LlmLimiterStats {
+        let users = self.users.lock().unwrap();
+        let mut loads: Vec<UserLlmLoad> = users
+            .iter()
+            .map(|(uid, slots)| UserLlmLoad {
+                uid: uid.clone(),
+                in_flight: slots.in_flight,
+                queued: slots.queued,
+            })
+            .collect();
+        loads.sort_by_key(|l| std::cmp::Reverse(l.in_flight + l.queued));
+
+        LlmLimiterStats {
+            max_concurrent_per_user: self.max_concurrent,
+            max_queued_per_user: self.max_queued,
+            in_flight: loads.iter().map(|l| l.in_flight).sum(),
+            queued: loads.iter().map(|l| l.queued).sum(),
+            active_users: loads.len(),
+            users: loads.into_iter().take(MAX_USERS_IN_STATS).collect(),
+        }
+    }
+}
+
+/// Hold an LLM slot for the authenticated user while the request runs.
+/// The limiter comes from a request extension layered on in main.
+async fn limit_llm_requests(
+    Extension(limiter): Extension<LlmLimiter>,
+    user: AuthUser,
+    client: ClientInfo,
+    request: Request,
+    next: Next,
+) -> Response {
+    match limiter.acquire(&user.uid).await {
+        Ok(_permit) => next.run(request).await,
+        Err(rejection) => {
+            tracing::warn!("Rejected LLM request from user {} at {}", user.uid, client);
+            rejection.into_response()
+        }
+    }
+}
+
+/// Apply the per-user LLM limit to a route that calls the model
+pub fn with_llm_limit<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.layer::<_, Infallible>(middleware::from_fn(limit_llm_requests))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_rejects_when_queue_full() {
+        let limiter = LlmLimiter::new(1, 0);
+        let first = block_on(limiter.acquire("u1")).unwrap();
+        let rejected = block_on(limiter.acquire("u1")).err().expect("queue is full");
+        assert_eq!(rejected.error, "llm_rate_limited");
+        // Other users are unaffected
+        let other = block_on(limiter.acquire("u2")).unwrap();
+
+        let stats = limiter.stats();
+        assert_eq!(stats.in_flight, 2);
+        assert_eq!(stats.active_users, 2);
+
+        drop(first);
+        drop(other);
+        assert_eq!(limiter.stats().active_users, 0);
+        assert!(block_on(limiter.acquire("u1")).is_ok());
+    }
+
+    #[test]
+    fn test_zero_concurrency_is_unlimited() {
+        let limiter = LlmLimiter::new(0, 0);
+        let permits: Vec<_> = (0..5).map(|_| block_on(limiter.acquire("u1")).unwrap()).collect();
+        assert_eq!(permits.len(), 5);
+        assert_eq!(limiter.stats().in_flight, 0);
+    }
+}
@@ -4,8 +4,8 @@
 use axum::Router;
 use std::fs::OpenOptions;
 use std::io::LineWriter;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::fmt::format::Writer;
 use tracing_subscriber::fmt::time::FormatTime;
@@ -22,31 +22,20 @@ impl FormatTime for BackendTimer {
     }
 }
 
-mod auth;
-mod config;
-mod encryption;
-mod llm;
-mod models;
-mod routes;
-mod services;
+use omi_desktop_backend::{auth, body_limit, client_info, config, environment, impersonation, job_queue, llm_debug, llm_limit, routes, security, services, AppState};
 
 use auth::{firebase_auth_extension, FirebaseAuth};
 use config::Config;
-use routes::{action_items_routes, advice_routes, agent_routes, apps_routes, auth_routes, chat_routes, chat_sessions_routes, conversations_routes, crisp_routes, daily_score_routes, focus_sessions_routes, folder_routes, goals_routes, health_routes, knowledge_graph_routes, llm_usage_routes, memories_routes, messages_routes, people_routes, personas_routes, screen_activity_routes, staged_tasks_routes, stats_routes, updates_routes, users_routes, webhook_routes};
+use routes::{action_items_routes, admin_routes, advice_routes, agent_routes, apps_routes, auth_routes, badges_routes, chat_routes, chat_sessions_routes, conversation_templates_routes, conversations_routes, crisp_routes, daily_score_routes, devices_routes, exports_routes, focus_sessions_routes, folder_routes, folder_rules_routes, goals_routes, groups_routes, health_routes, knowledge_graph_routes, llm_usage_routes, memories_routes, memos_routes, messages_routes, notifications_routes, notion_routes, people_routes, personas_routes, presence_routes, reviews_routes, screen_activity_routes, search_routes, slack_routes, staged_tasks_routes, stats_routes, sync_routes, timeline_routes, update_stream_routes, updates_routes, users_routes, webhook_routes};
 use services::{FirestoreService, IntegrationService, RedisService};
 
-/// Application state shared across handlers
-#[derive(Clone)]
-pub struct AppState {
-    pub firestore: Arc<FirestoreService>,
-    pub integrations: Arc<IntegrationService>,
-    pub redis: Option<Arc<RedisService>>,
-    pub config: Arc<Config>,
-    pub crisp_session_cache: routes::crisp::SessionCache,
-}
-
 #[tokio::main]
 async fn main() {
+    // `--check`: validate the deployment and exit instead of serving
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        std::process::exit(run_self_check().await);
+    }
+
     // Open log file (same as Swift dev app: /tmp/omi-dev.log)
     // Wrap in LineWriter to flush after each line (ensures logs appear immediately)
     let log_file = OpenOptions::new()
@@ -95,20 +84,26 @@ async fn main() {
     }
 
     // Initialize Firebase Auth
-    let firebase_auth = Arc::new(FirebaseAuth::new(
-        config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string()),
-    ));
+    let firebase_auth = Arc::new(
+        FirebaseAuth::new(
+            config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string()),
+        )
+        .with_impersonation_secret(config.impersonation_secret.clone()),
+    );
 
     // Refresh Firebase keys with retry (transient network failures at startup)
+    // Afterwards a background task re-fetches them on their Cache-Control schedule
+    let mut key_max_age = std::time::Duration::from_secs(60);
     {
         let max_attempts = 3u32;
         let mut last_err = None;
         for attempt in 1..=max_attempts {
             match firebase_auth.refresh_keys().await {
-                Ok(_) => {
+                Ok(max_age) => {
                     if attempt > 1 {
                         tracing::info!("Firebase keys fetched on attempt {}", attempt);
                     }
+                    key_max_age = max_age;
                     last_err = None;
                     break;
                 }
@@ -126,20 +121,48 @@ async fn main() {
         }
     }
 
-    // Initialize Firestore
-    let firestore = match FirestoreService::new(
-        config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string()),
-        config.encryption_secret.clone(),
-    ).await {
-        Ok(fs) => Arc::new(fs),
-        Err(e) => {
-            tracing::warn!("Failed to initialize Firestore: {} - using placeholder", e);
-            Arc::new(FirestoreService::new("based-hardware".to_string(), config.encryption_secret.clone()).await.unwrap())
+    services::token_refresh::spawn_firebase_key_refresh(firebase_auth.clone(), key_max_age);
+
+    // User data store: Firestore unless STORAGE=local, which runs without Firestore at all
+    let http_tuning = services::firestore_http::HttpTuning::from_config(&config);
+    let (firestore, storage): (Arc<FirestoreService>, Arc<dyn services::storage::Storage>) = match config.storage {
+        services::storage::StorageKind::Local => match services::local_storage::LocalStorage::open(&config.local_storage_path) {
+            Ok(local) => {
+                tracing::info!("Local storage at {} - Firestore disabled", config.local_storage_path);
+                (Arc::new(FirestoreService::disabled(config.encryption_secret.clone())), Arc::new(local))
+            }
+            Err(e) => {
+                tracing::error!("Failed to open local storage {}: {}", config.local_storage_path, e);
+                std::process::exit(1);
+            }
+        },
+        services::storage::StorageKind::Firestore => {
+            let firestore = match FirestoreService::new(
+                config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string()),
+                config.encryption_secret.clone(),
+            ).await {
+                Ok(fs) => Arc::new(fs.with_http_tuning(&http_tuning)),
+                Err(e) => {
+                    tracing::warn!("Failed to initialize Firestore: {} - using placeholder", e);
+                    Arc::new(
+                        FirestoreService::new("based-hardware".to_string(), config.encryption_secret.clone())
+                            .await
+                            .unwrap()
+                            .with_http_tuning(&http_tuning),
+                    )
+                }
+            };
+
+            // Renew the Firestore access token before it expires
+            services::token_refresh::spawn_firestore_token_refresh(firestore.clone());
+            (firestore.clone(), firestore)
         }
     };
 
     // Initialize Integration Service
-    let integrations = Arc::new(IntegrationService::new());
+    let integrations = Arc::new(IntegrationService::with_policy(
+        services::integrations::WebhookPolicy::from_config(&config),
+    ));
 
     // Initialize Redis (optional - for conversation visibility/sharing)
     // Use explicit connection params to avoid URL encoding issues with special characters in password
@@ -159,33 +182,122 @@ async fn main() {
         None
     };
 
+    // Initialize mailer (optional - for user-confirmed outbound email)
+    let mailer = services::mailer::Mailer::from_config(&config).map(Arc::new);
+    if mailer.is_none() {
+        tracing::warn!("Mailer not configured - follow-up emails can be drafted but not sent");
+    }
+
     // Create app state
     let state = AppState {
         firestore,
+        storage,
         integrations,
         redis,
         config: Arc::new(config.clone()),
         crisp_session_cache: routes::crisp::new_session_cache(),
+        screen_context: services::screen_context::ScreenContextBuffer::new(),
+        presence: services::presence::PresenceStore::new(),
+        search_index: services::universal_search::SearchIndexCache::new(),
+        mailer,
+        notion: Arc::new(services::notion::NotionService::from_config(&config)),
+        slack: Arc::new(services::slack::SlackService::from_config(&config)),
+        llm_limiter: llm_limit::LlmLimiter::new(
+            config.llm_max_concurrent_per_user,
+            config.llm_max_queued_per_user,
+        ),
+        jobs: job_queue::JobQueue::start(
+            config.job_queue_workers,
+            config.job_queue_max_running_per_user,
+            config.job_queue_max_queued_per_user,
+        ),
+        events: services::events::EventBus::new(),
     };
 
-    // Build CORS layer
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    services::screen_context::spawn_screen_context_pruner(state.screen_context.clone());
+    services::presence::spawn_presence_pruner(state.presence.clone());
+
+    // Everything below reads or writes Firestore directly, so none of it runs with STORAGE=local
+    if !state.firestore.is_disabled() {
+        // Event consumers: webhook/app integrations and Notion auto-sync
+        services::integrations::spawn_integration_dispatcher(
+            &state.events,
+            state.firestore.clone(),
+            state.integrations.clone(),
+        );
+        services::notion::spawn_notion_auto_sync(&state.events, state.firestore.clone(), state.notion.clone());
+
+        // Background action item scoring (relevance + priority)
+        services::prioritization::spawn_action_item_scorer(
+            state.firestore.clone(),
+            state.config.clone(),
+            state.jobs.clone(),
+        );
+
+        // Marketplace listings are served from a periodically refreshed snapshot
+        services::apps_cache::spawn_apps_cache_refresh(state.firestore.clone());
+
+        // Opt-in daily rollover of overdue action items
+        services::rollover::spawn_action_item_rollover(state.firestore.clone(), state.jobs.clone());
+
+        // Opt-in approval of memories left pending review
+        services::memory_review::spawn_memory_auto_approve(state.firestore.clone(), state.jobs.clone());
+
+        // Opt-in daily digest DMs for users who connected Slack
+        services::slack::spawn_slack_digest(state.firestore.clone(), state.slack.clone(), state.jobs.clone());
+
+        // Opt-in conversation auto-archival and deletion
+        services::retention::spawn_retention_enforcement(state.firestore.clone(), state.jobs.clone());
+
+        // Daily LLM-assisted goal progress from the previous day's activity
+        services::goal_progress::spawn_goal_progress_updater(
+            state.firestore.clone(),
+            state.config.clone(),
+            state.jobs.clone(),
+        );
+
+        // Sunday-evening weekly reviews for users who opted in
+        services::weekly_review::spawn_weekly_review_job(
+            state.firestore.clone(),
+            state.config.clone(),
+            state.jobs.clone(),
+        );
+
+        // Daily report of thumbs ratings on LLM output
+        services::llm_quality::spawn_quality_report_job(state.firestore.clone());
+
+        // Deletion of data export archives past their retention
+        services::data_export::spawn_export_cleanup(state.firestore.clone(), state.config.clone());
+    }
+
+    // Build CORS layer from the configured origins
+    let cors = security::cors_layer(&state.config);
 
     // Build auth router (has its own state)
     let auth_router = auth_routes(state.config.clone());
+    let state_config = state.config.clone();
+    let security_config = state.config.clone();
+    let llm_limiter = state.llm_limiter.clone();
+    let lock_firestore = state.firestore.clone();
+    let audit_state = state.clone();
+    let llm_debug_state = state.clone();
+    let trusted_proxies = Arc::new(client_info::TrustedProxies::parse(
+        &state.config.trusted_proxies,
+        state.config.forwarded_header,
+    ));
 
     // Build main app router with AppState
     let main_router = Router::new()
         .merge(health_routes())
         .merge(memories_routes())
+        .merge(memos_routes())
         .merge(messages_routes())
         .merge(chat_routes())
         .merge(chat_sessions_routes())
         .merge(conversations_routes())
         .merge(action_items_routes())
+        .merge(admin_routes())
+        .merge(sync_routes())
         .merge(agent_routes())
         .merge(staged_tasks_routes())
         .merge(focus_sessions_routes())
@@ -194,22 +306,64 @@ async fn main() {
         .merge(advice_routes())
         .merge(updates_routes())
         .merge(folder_routes())
+        .merge(folder_rules_routes())
+        .merge(conversation_templates_routes())
         .merge(goals_routes())
+        .merge(reviews_routes())
+        .merge(notifications_routes())
+        .merge(update_stream_routes())
+        .merge(groups_routes())
         .merge(daily_score_routes())
+        .merge(devices_routes())
+        .merge(notion_routes())
+        .merge(slack_routes())
         .merge(people_routes())
         .merge(personas_routes())
+        .merge(presence_routes())
         .merge(knowledge_graph_routes())
         .merge(llm_usage_routes())
         .merge(stats_routes())
         .merge(webhook_routes())
         .merge(crisp_routes())
         .merge(screen_activity_routes())
+        .merge(search_routes())
+        .merge(badges_routes())
+        .merge(timeline_routes())
+        .merge(exports_routes())
         .with_state(state);
 
     // Merge both (now both are Router<()>), then add layers
     let app = main_router
         .merge(auth_router)
+        .layer(axum::middleware::from_fn_with_state(
+            state_config,
+            environment::firestore_environment,
+        ))
+        // Outside the environment override, so audit entries always land in the main project
+        .layer(axum::middleware::from_fn_with_state(
+            audit_state,
+            impersonation::audit_impersonated_requests,
+        ))
+        // Request IDs cover the whole request, including impersonated ones
+        .layer(axum::middleware::from_fn_with_state(
+            llm_debug_state,
+            llm_debug::capture_llm_calls,
+        ))
+        // Outside the audit and every route, so all of them see the real client IP
+        .layer(axum::middleware::from_fn_with_state(
+            trusted_proxies,
+            client_info::resolve_client_info,
+        ))
         .layer(firebase_auth_extension(firebase_auth))
+        .layer(axum::Extension(llm_limiter))
+        // For conversation_lock's route middleware
+        .layer(axum::Extension(lock_firestore))
+        .layer(axum::extract::DefaultBodyLimit::max(body_limit::DEFAULT_BODY_LIMIT_BYTES))
+        .layer(axum::middleware::from_fn(body_limit::default_body_limit))
+        .layer(axum::middleware::from_fn_with_state(
+            security_config,
+            security::security_headers,
+        ))
         .layer(cors)
         .layer(TraceLayer::new_for_http());
 
@@ -218,5 +372,28 @@ async fn main() {
     tracing::info!("Starting OMI Desktop Backend on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
+}
+
+/// Print the self-check report; exit code 1 when any check failed
+async fn run_self_check() -> i32 {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "omi_desktop_backend=error".into()),
+        )
+        .with_target(false)
+        .init();
+    dotenvy::dotenv().ok();
+
+    let config = Config::from_env();
+    let report = services::self_check::run(&config).await;
+    println!("{}", report.render());
+    if report.passed() {
+        0
+    } else {
+        1
+    }
 }
@@ -0,0 +1,100 @@
+// Accountability group models - Opt-in groups that share daily focus scores
+// Path: accountability_groups/{group_id}, accountability_groups/{group_id}/members/{uid}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Max members per group (groups are meant to be small)
+pub const MAX_GROUP_MEMBERS: usize = 12;
+
+/// Max days a leaderboard can cover
+pub const MAX_LEADERBOARD_DAYS: u32 = 14;
+
+/// An accountability group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountabilityGroup {
+    pub id: String,
+    pub name: String,
+    pub owner_uid: String,
+    /// Code other users join with
+    pub invite_code: String,
+    /// Member uids, kept on the group doc for "my groups" queries; not exposed
+    #[serde(skip_serializing, default)]
+    pub member_uids: Vec<String>,
+    pub member_count: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A member's settings within a group. Only focus scores are ever shared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMember {
+    #[serde(skip_serializing, default)]
+    pub uid: String,
+    pub display_name: String,
+    /// Whether this member's focus scores appear on the leaderboard
+    pub share_focus: bool,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Request to create a group
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    /// Name shown to other members (defaults to the account name)
+    pub display_name: Option<String>,
+}
+
+/// Request to join a group by invite code
+#[derive(Debug, Clone, Deserialize)]
+pub struct JoinGroupRequest {
+    pub invite_code: String,
+    pub display_name: Option<String>,
+}
+
+/// Request to change the caller's membership settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateGroupMembershipRequest {
+    pub share_focus: Option<bool>,
+    pub display_name: Option<String>,
+}
+
+/// Query for GET /v1/groups/:id/leaderboard
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeaderboardQuery {
+    #[serde(default = "default_leaderboard_days")]
+    pub days: u32,
+}
+
+fn default_leaderboard_days() -> u32 {
+    7
+}
+
+/// Focus score for one day; None when the member had no focus sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyFocusScore {
+    pub date: String,
+    pub focus_score: Option<f64>,
+}
+
+/// One member's row on the leaderboard
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    /// 1-based rank among sharing members with focus data
+    pub rank: Option<usize>,
+    pub display_name: String,
+    pub is_you: bool,
+    pub sharing: bool,
+    /// Focused share of tracked time over the period (0-100)
+    pub focus_score: Option<f64>,
+    pub focused_minutes: Option<i64>,
+    pub daily: Vec<DailyFocusScore>,
+}
+
+/// Response for GET /v1/groups/:id/leaderboard
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupLeaderboard {
+    pub group_id: String,
+    pub name: String,
+    pub days: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
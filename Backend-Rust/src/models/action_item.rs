@@ -11,11 +11,13 @@ pub struct ActionItemDB {
     /// Document ID
     pub id: String,
     /// The action item description
+    #[serde(default)]
     pub description: String,
     /// Whether the action item has been completed
     #[serde(default)]
     pub completed: bool,
     /// When the action item was created
+    #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
     /// When the action item was last updated
     pub updated_at: Option<DateTime<Utc>>,
@@ -73,6 +75,24 @@ pub struct ActionItemDB {
     /// ID of original parent task in recurrence chain
     #[serde(default)]
     pub recurrence_parent_id: Option<String>,
+    /// How many times the daily rollover moved this task's due date to "today"
+    #[serde(default)]
+    pub rollover_count: Option<i32>,
+    /// Whether this task is skipped by the daily rollover
+    #[serde(default)]
+    pub rollover_excluded: Option<bool>,
+    /// Preview of the most recent progress note
+    #[serde(default)]
+    pub latest_note: Option<String>,
+    /// When the most recent progress note was added
+    #[serde(default)]
+    pub latest_note_at: Option<DateTime<Utc>>,
+    /// Number of progress notes on this task
+    #[serde(default)]
+    pub notes_count: Option<i32>,
+    /// Hidden from lists until this time
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
 }
 
 /// Request body for updating an action item
@@ -84,6 +104,13 @@ pub struct UpdateActionItemRequest {
     pub description: Option<String>,
     /// New due date
     pub due_at: Option<DateTime<Utc>>,
+    /// New due date as a phrase ("next Tuesday morning"), resolved in the user's
+    /// timezone. Mutually exclusive with `due_at`; the resolved time comes back as `due_at`.
+    #[serde(default)]
+    pub due_text: Option<String>,
+    /// IANA timezone for `due_text`; defaults to the profile's timezone
+    #[serde(default)]
+    pub timezone: Option<String>,
     /// New priority: "high", "medium", "low"
     pub priority: Option<String>,
     /// New category: "work", "personal", "health", etc.
@@ -98,6 +125,18 @@ pub struct UpdateActionItemRequest {
     pub indent_level: Option<i32>,
     /// Recurrence rule: "daily", "weekdays", "weekly", "biweekly", "monthly" (empty string = clear)
     pub recurrence_rule: Option<String>,
+    /// Exclude this task from (or re-include it in) the daily rollover
+    pub rollover_excluded: Option<bool>,
+    /// The item's `updated_at` as this client last saw it. If the item has changed
+    /// since, the update is rejected with 409 and the current copy (unless `?force=true`).
+    #[serde(default)]
+    pub expected_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Whether the item's `updated_at` still matches the one a client last saw.
+/// Compared at millisecond precision, which is what clients round-trip.
+pub fn is_same_version(current: Option<DateTime<Utc>>, expected: DateTime<Utc>) -> bool {
+    current.is_some_and(|current| current.timestamp_millis() == expected.timestamp_millis())
 }
 
 /// Response for action item status operations
@@ -142,6 +181,29 @@ pub struct CreateActionItemRequest {
     pub recurrence_parent_id: Option<String>,
 }
 
+/// Response for reconciling a conversation's structured action items
+/// with top-level action items
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileActionItemsResponse {
+    /// Existing action items newly linked to the conversation
+    pub linked: usize,
+    /// Action items created from structured items with no match
+    pub created: usize,
+    /// Top-level action items for every structured item, in structured order
+    pub items: Vec<ActionItemDB>,
+}
+
+/// Normalize an action item description for matching
+/// (case, surrounding whitespace/punctuation, and repeated spaces are ignored)
+pub fn normalize_action_item_description(description: &str) -> String {
+    description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
 /// Request body for sharing tasks
 #[derive(Debug, Clone, Deserialize)]
 pub struct ShareTasksRequest {
@@ -219,3 +281,78 @@ pub struct SortOrderUpdate {
     pub sort_order: i32,
     pub indent_level: i32,
 }
+
+// ============================================================================
+// Notes - users/{uid}/action_items/{item_id}/notes/{note_id}
+// ============================================================================
+
+/// Max length of a single progress note
+pub const MAX_ACTION_ITEM_NOTE_CHARS: usize = 5000;
+/// Length of the latest-note preview stored on the action item
+const NOTE_PREVIEW_CHARS: usize = 200;
+
+/// Progress note on an action item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItemNote {
+    pub id: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for adding a note to an action item
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateActionItemNoteRequest {
+    pub content: String,
+}
+
+/// Response for GET /v1/action-items/next
+#[derive(Debug, Clone, Serialize)]
+pub struct NextActionItemResponse {
+    /// The task to do now; None when nothing is open
+    pub action_item: Option<ActionItemDB>,
+    /// Higher is more pressing; only comparable within one response
+    pub score: f64,
+    /// Why this task was picked, for the UI
+    pub reasoning: String,
+    /// Open tasks considered
+    pub candidates: usize,
+    /// What the user is doing now, as used for the pick (e.g. "Xcode — ContentView.swift")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_context: Option<String>,
+}
+
+/// Preview of a note for list responses: whitespace collapsed, cut at NOTE_PREVIEW_CHARS
+pub fn note_preview(content: &str) -> String {
+    let collapsed = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= NOTE_PREVIEW_CHARS {
+        return collapsed;
+    }
+    let mut preview: String = collapsed.chars().take(NOTE_PREVIEW_CHARS - 1).collect();
+    preview.push('…');
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_action_item_description() {
+        assert_eq!(
+            normalize_action_item_description("  Send the   Q3 report to Sarah. "),
+            normalize_action_item_description("send the Q3 report to sarah")
+        );
+        assert_ne!(
+            normalize_action_item_description("Send the report"),
+            normalize_action_item_description("Review the report")
+        );
+    }
+
+    #[test]
+    fn test_note_preview() {
+        assert_eq!(note_preview("  called the\n vendor  "), "called the vendor");
+        let preview = note_preview(&"a".repeat(500));
+        assert_eq!(preview.chars().count(), NOTE_PREVIEW_CHARS);
+        assert!(preview.ends_with('…'));
+    }
+}
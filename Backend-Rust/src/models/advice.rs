@@ -3,6 +3,9 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::screen_activity::ScreenContextSnapshot;
 
 /// Advice category enum matching the Swift AdviceCategory
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -21,6 +24,19 @@ impl Default for AdviceCategory {
     }
 }
 
+impl AdviceCategory {
+    /// Firestore / wire representation of the category
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdviceCategory::Productivity => "productivity",
+            AdviceCategory::Health => "health",
+            AdviceCategory::Communication => "communication",
+            AdviceCategory::Learning => "learning",
+            AdviceCategory::Other => "other",
+        }
+    }
+}
+
 /// Advice stored in Firestore subcollection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdviceDB {
@@ -52,6 +68,27 @@ pub struct AdviceDB {
     /// Whether the advice has been dismissed/archived
     #[serde(default)]
     pub is_dismissed: bool,
+    /// User feedback: true = helpful, false = not helpful, None = no feedback yet
+    #[serde(default)]
+    pub helpful: Option<bool>,
+    /// Optional free-text feedback from the user
+    #[serde(default)]
+    pub feedback_text: Option<String>,
+    /// When feedback was last submitted
+    #[serde(default)]
+    pub feedback_at: Option<DateTime<Utc>>,
+    /// Hidden from lists until this time
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// When the client pushed the advice as a notification
+    #[serde(default)]
+    pub pushed_at: Option<DateTime<Utc>>,
+    /// When the user first saw the advice
+    #[serde(default)]
+    pub seen_at: Option<DateTime<Utc>>,
+    /// When the user acted on the advice
+    #[serde(default)]
+    pub acted_at: Option<DateTime<Utc>>,
 }
 
 /// Request body for creating new advice
@@ -80,6 +117,45 @@ pub struct UpdateAdviceRequest {
     pub is_read: Option<bool>,
     /// Mark as dismissed/archived
     pub is_dismissed: Option<bool>,
+    /// Delivery tracking, reported by the client. Setting `seen_at` also marks the advice read.
+    pub pushed_at: Option<DateTime<Utc>>,
+    pub seen_at: Option<DateTime<Utc>>,
+    pub acted_at: Option<DateTime<Utc>>,
+}
+
+/// Most advice IDs accepted by one bulk request
+pub const MAX_ADVICE_BULK_IDS: usize = 500;
+
+/// Operation applied by POST /v1/advice/bulk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdviceBulkAction {
+    MarkRead,
+    Dismiss,
+    Delete,
+}
+
+/// Request body for POST /v1/advice/bulk
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdviceBulkRequest {
+    pub ids: Vec<String>,
+    pub action: AdviceBulkAction,
+}
+
+/// Result of a bulk operation
+#[derive(Debug, Clone, Serialize)]
+pub struct AdviceBulkResponse {
+    /// Number of advice items changed
+    pub updated: usize,
+    /// Requested IDs that don't exist (ignored)
+    pub not_found: Vec<String>,
+}
+
+/// Response for GET /v1/advice/unread-count
+#[derive(Debug, Clone, Serialize)]
+pub struct AdviceUnreadCountResponse {
+    /// Advice that is neither read nor dismissed
+    pub unread: i64,
 }
 
 /// Response for advice status operations
@@ -100,8 +176,172 @@ pub struct GetAdviceQuery {
     /// Include dismissed advice (default: false)
     #[serde(default)]
     pub include_dismissed: bool,
+    /// Include advice that is still snoozed (default: false)
+    #[serde(default)]
+    pub include_snoozed: bool,
 }
 
 fn default_limit() -> usize {
     100
 }
+
+/// Request body for submitting feedback on advice
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdviceFeedbackRequest {
+    /// Whether the advice was helpful
+    pub helpful: bool,
+    /// Optional free-text explanation
+    pub text: Option<String>,
+}
+
+/// Per-category feedback counts
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct AdviceCategoryFeedback {
+    pub category: String,
+    pub helpful: usize,
+    pub not_helpful: usize,
+}
+
+/// Aggregated advice feedback used to steer advice generation
+#[derive(Debug, Clone, Serialize)]
+pub struct AdviceFeedbackSummary {
+    /// Total advice items with feedback
+    pub total_feedback: usize,
+    /// Feedback counts grouped by category
+    pub categories: Vec<AdviceCategoryFeedback>,
+    /// Categories the user consistently rejects (generation should skip these)
+    pub suppressed_categories: Vec<String>,
+    /// Recent free-text comments on advice marked not helpful
+    pub recent_negative_comments: Vec<String>,
+    /// Ready-to-append section for the advice generation prompt (empty if no signal yet)
+    pub prompt_guidance: String,
+    /// Latest screen snapshot, when requested (also appended to prompt_guidance)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screen_context: Option<ScreenContextSnapshot>,
+}
+
+/// Query parameters for the feedback summary
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdviceFeedbackSummaryQuery {
+    /// Include the latest screen-context snapshot
+    #[serde(default)]
+    pub include_screen_context: bool,
+}
+
+/// Minimum number of "not helpful" votes before a category can be suppressed
+const SUPPRESS_MIN_NEGATIVE: usize = 3;
+/// Share of "not helpful" votes at which a category is suppressed
+const SUPPRESS_NEGATIVE_RATIO: f64 = 0.7;
+/// Number of recent negative comments surfaced to the prompt
+const MAX_NEGATIVE_COMMENTS: usize = 5;
+
+impl AdviceFeedbackSummary {
+    /// Aggregate feedback from advice items (expected newest first)
+    pub fn from_advice(advice: &[AdviceDB]) -> Self {
+        let mut by_category: BTreeMap<&'static str, AdviceCategoryFeedback> = BTreeMap::new();
+        let mut recent_negative_comments = Vec::new();
+        let mut total_feedback = 0;
+
+        for item in advice {
+            let Some(helpful) = item.helpful else { continue };
+            total_feedback += 1;
+
+            let category = item.category.as_str();
+            let entry = by_category.entry(category).or_insert_with(|| AdviceCategoryFeedback {
+                category: category.to_string(),
+                ..Default::default()
+            });
+
+            if helpful {
+                entry.helpful += 1;
+            } else {
+                entry.not_helpful += 1;
+                if let Some(text) = item.feedback_text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+                    if recent_negative_comments.len() < MAX_NEGATIVE_COMMENTS {
+                        recent_negative_comments.push(text.to_string());
+                    }
+                }
+            }
+        }
+        let categories: Vec<AdviceCategoryFeedback> = by_category.into_values().collect();
+
+        let suppressed_categories: Vec<String> = categories
+            .iter()
+            .filter(|c| {
+                let total = c.helpful + c.not_helpful;
+                c.not_helpful >= SUPPRESS_MIN_NEGATIVE
+                    && c.not_helpful as f64 / total as f64 >= SUPPRESS_NEGATIVE_RATIO
+            })
+            .map(|c| c.category.clone())
+            .collect();
+
+        let prompt_guidance = crate::llm::prompts::build_advice_feedback_guidance(
+            &suppressed_categories,
+            &recent_negative_comments,
+        );
+
+        Self {
+            total_feedback,
+            categories,
+            suppressed_categories,
+            recent_negative_comments,
+            prompt_guidance,
+            screen_context: None,
+        }
+    }
+
+    /// Attach the user's current screen so generated advice reflects it
+    pub fn with_screen_context(mut self, snapshot: ScreenContextSnapshot) -> Self {
+        if !self.prompt_guidance.is_empty() {
+            self.prompt_guidance.push_str("\n\n");
+        }
+        self.prompt_guidance.push_str(&snapshot.to_context_string());
+        self.screen_context = Some(snapshot);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advice(category: &str, helpful: Option<bool>, feedback_text: Option<&str>) -> AdviceDB {
+        serde_json::from_value(serde_json::json!({
+            "id": "a1",
+            "content": "Take a break",
+            "category": category,
+            "created_at": "2026-03-02T09:00:00Z",
+            "helpful": helpful,
+            "feedback_text": feedback_text
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feedback_summary() {
+        let mut items = vec![
+            advice("health", Some(false), Some("  Too preachy  ")),
+            advice("health", Some(false), Some("   ")),
+            advice("health", Some(false), None),
+            advice("health", Some(true), None),
+            advice("productivity", Some(false), None),
+            advice("productivity", Some(true), None),
+            advice("learning", None, Some("ignored")),
+        ];
+        let summary = AdviceFeedbackSummary::from_advice(&items);
+        assert_eq!(summary.total_feedback, 6);
+        assert_eq!(summary.categories.len(), 2);
+        // 3 of 4 health votes are negative; productivity has too few
+        assert_eq!(summary.suppressed_categories, vec!["health"]);
+        assert_eq!(summary.recent_negative_comments, vec!["Too preachy"]);
+        assert!(summary.prompt_guidance.contains("health"));
+
+        // Only the newest negative comments are kept
+        items.extend((0..6).map(|i| advice("other", Some(false), Some(&format!("comment {}", i)))));
+        let summary = AdviceFeedbackSummary::from_advice(&items);
+        assert_eq!(summary.recent_negative_comments.len(), 5);
+        assert_eq!(summary.recent_negative_comments[1], "comment 0");
+
+        assert!(AdviceFeedbackSummary::from_advice(&[]).prompt_guidance.is_empty());
+    }
+}
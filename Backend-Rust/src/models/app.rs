@@ -3,6 +3,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// App capability types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -22,6 +23,8 @@ pub enum TriggerEvent {
     MemoryCreation,
     TranscriptProcessed,
     AudioBytes,
+    #[serde(rename = "action_item.completed")]
+    ActionItemCompleted,
 }
 
 /// Actions that apps can perform
@@ -45,6 +48,52 @@ pub enum NotificationScope {
     UserChat,
 }
 
+/// User data and actions an app may be granted. Users consent to an app's scopes when
+/// enabling it, and each data access checks the grant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AppScope {
+    /// Conversations: webhook payloads, memory prompts and chat context
+    #[serde(rename = "read:conversations")]
+    ReadConversations,
+    /// Memories in chat context
+    #[serde(rename = "read:memories")]
+    ReadMemories,
+    /// Action item sync, including action_item.completed events
+    #[serde(rename = "write:action_items")]
+    WriteActionItems,
+    /// Proactive notifications to the user
+    #[serde(rename = "notifications")]
+    Notifications,
+}
+
+impl AppScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppScope::ReadConversations => "read:conversations",
+            AppScope::ReadMemories => "read:memories",
+            AppScope::WriteActionItems => "write:action_items",
+            AppScope::Notifications => "notifications",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<AppScope> {
+        match value {
+            "read:conversations" => Some(AppScope::ReadConversations),
+            "read:memories" => Some(AppScope::ReadMemories),
+            "write:action_items" => Some(AppScope::WriteActionItems),
+            "notifications" => Some(AppScope::Notifications),
+            _ => None,
+        }
+    }
+}
+
+/// An auth step the user completes before the integration works (e.g. OAuth)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthStep {
+    pub name: String,
+    pub url: String,
+}
+
 /// External integration configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExternalIntegration {
@@ -54,6 +103,17 @@ pub struct ExternalIntegration {
     pub webhook_url: String,
     /// URL to verify setup completion
     pub setup_completed_url: Option<String>,
+    /// Setup instructions (file path, or URL if `is_instructions_url`)
+    #[serde(default)]
+    pub setup_instructions_file_path: Option<String>,
+    #[serde(default)]
+    pub is_instructions_url: bool,
+    /// Auth steps the user must complete
+    #[serde(default)]
+    pub auth_steps: Vec<AuthStep>,
+    /// App's own home page
+    #[serde(default)]
+    pub app_home_url: Option<String>,
     /// Actions the app can perform
     #[serde(default)]
     pub actions: Vec<ActionType>,
@@ -113,6 +173,11 @@ pub struct App {
     #[serde(default)]
     pub capabilities: Vec<String>,
 
+    /// Scopes the app declares; apps without any get scopes inferred from their
+    /// capabilities (see `requested_scopes`)
+    #[serde(default)]
+    pub scopes: Vec<AppScope>,
+
     /// Owner user ID
     pub uid: Option<String>,
     /// Whether the app is approved for public listing
@@ -162,13 +227,282 @@ pub struct App {
     // Runtime field (not stored in DB)
     #[serde(default)]
     pub enabled: bool,
+
+    // Runtime field: webhook delivery paused after repeated failures (from enabled_plugins doc)
+    #[serde(default)]
+    pub integration_delivery_disabled: bool,
+
+    // Runtime field: scopes the user consented to when enabling the app (from enabled_plugins doc)
+    #[serde(default)]
+    pub granted_scopes: Vec<AppScope>,
+
+    // Runtime field: the user finished the app's auth steps (from enabled_plugins doc)
+    #[serde(default)]
+    pub setup_completed: bool,
+
+    /// Latest moderation of the app's prompts
+    #[serde(default)]
+    pub moderation: Option<AppModeration>,
+
+    /// Developer-supplied name/description per language tag (lowercase, e.g. "pt-br")
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub translations: HashMap<String, AppTranslation>,
 }
 
 fn default_status() -> String {
     "under-review".to_string()
 }
 
+/// Language of the base name/description on the app document
+pub const BASE_APP_LANGUAGE: &str = "en";
+/// Most languages an app may be translated into
+pub const MAX_APP_TRANSLATIONS: usize = 50;
+/// Longest translated app name, in characters
+pub const MAX_APP_NAME_CHARS: usize = 100;
+/// Longest translated app description, in characters
+pub const MAX_APP_DESCRIPTION_CHARS: usize = 5_000;
+
+/// An app's name and description in one language; missing fields fall back to the base text
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AppTranslation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Request body for PUT /v1/apps/:app_id/translations (replaces all translations)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetAppTranslationsRequest {
+    pub translations: HashMap<String, AppTranslation>,
+}
+
+/// Lowercase a language tag ("pt_BR" -> "pt-br"); None unless it looks like
+/// language[-region], e.g. "es", "zh-hant" or "es-419"
+pub fn normalize_language_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-").to_lowercase();
+    let mut parts = tag.split('-');
+    let primary = parts.next()?;
+    if !(2..=3).contains(&primary.len()) || !primary.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let subtags_ok = parts.all(|p| (2..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+    subtags_ok.then_some(tag)
+}
+
+/// Language tags from an Accept-Language header, most preferred first.
+/// Wildcards, malformed tags and q=0 entries are dropped.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut languages: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = normalize_language_tag(parts.next()?)?;
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (q > 0.0).then_some((tag, q))
+        })
+        .collect();
+    // Stable sort keeps header order between equal weights
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Translation to show for the preferred languages. Each language matches exactly, then by
+/// its primary subtag ("pt-br" falls back to "pt"); reaching the base language stops the search.
+fn select_translation<'a>(
+    translations: &'a HashMap<String, AppTranslation>,
+    languages: &[String],
+) -> Option<&'a AppTranslation> {
+    for language in languages {
+        if let Some(translation) = translations.get(language) {
+            return Some(translation);
+        }
+        let primary = language.split('-').next().unwrap_or(language);
+        if let Some(translation) = translations.get(primary) {
+            return Some(translation);
+        }
+        if primary == BASE_APP_LANGUAGE {
+            return None;
+        }
+    }
+    None
+}
+
+/// Swap in the best translation of the name and description, if any
+fn localize_metadata(
+    name: &mut String,
+    description: &mut String,
+    translations: &HashMap<String, AppTranslation>,
+    languages: &[String],
+) {
+    let Some(translation) = select_translation(translations, languages) else { return };
+    if let Some(translated) = translation.name.as_ref().filter(|n| !n.trim().is_empty()) {
+        *name = translated.clone();
+    }
+    if let Some(translated) = translation.description.as_ref().filter(|d| !d.trim().is_empty()) {
+        *description = translated.clone();
+    }
+}
+
+/// Outcome of moderating an app's prompts, most severe last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationVerdict {
+    Clean,
+    /// Prompt-injection patterns or odd instructions; still runs
+    Suspicious,
+    /// Tries to send user data outside the app; refused unless an admin allows it
+    Exfiltration,
+}
+
+impl ModerationVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationVerdict::Clean => "clean",
+            ModerationVerdict::Suspicious => "suspicious",
+            ModerationVerdict::Exfiltration => "exfiltration",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ModerationVerdict> {
+        match value {
+            "clean" => Some(ModerationVerdict::Clean),
+            "suspicious" => Some(ModerationVerdict::Suspicious),
+            "exfiltration" => Some(ModerationVerdict::Exfiltration),
+            _ => None,
+        }
+    }
+}
+
+/// Moderation verdict stored on an app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppModeration {
+    pub verdict: ModerationVerdict,
+    #[serde(default)]
+    pub reasons: Vec<String>,
+    /// Hash of the prompts that were checked; a changed app is moderated again
+    pub prompt_hash: String,
+    pub checked_at: DateTime<Utc>,
+    /// An admin allowed the app to run despite the verdict
+    #[serde(default)]
+    pub admin_override: bool,
+    #[serde(default)]
+    pub overridden_by: Option<String>,
+}
+
+impl AppModeration {
+    /// Whether the app's prompts must not run against user data
+    pub fn blocks_execution(&self) -> bool {
+        self.verdict == ModerationVerdict::Exfiltration && !self.admin_override
+    }
+}
+
+/// User data an app's chat context may draw on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatDataScope {
+    pub conversations: bool,
+    pub memories: bool,
+    pub user_name: bool,
+}
+
+impl ChatDataScope {
+    /// No restrictions (the main Omi chat, and apps without declared scopes)
+    pub const ALL: Self = Self {
+        conversations: true,
+        memories: true,
+        user_name: true,
+    };
+}
+
 impl App {
+    /// Data the app's chat context may include, from its notification scopes and
+    /// read actions. Apps that declare neither get everything.
+    pub fn chat_data_scope(&self) -> ChatDataScope {
+        let scopes = self
+            .proactive_notification
+            .as_ref()
+            .map(|pn| pn.scopes.as_slice())
+            .unwrap_or_default();
+        let actions = self
+            .external_integration
+            .as_ref()
+            .map(|ei| ei.actions.as_slice())
+            .unwrap_or_default();
+        let reads_conversations = actions.contains(&ActionType::ReadConversations);
+        let reads_memories = actions.contains(&ActionType::ReadMemories);
+
+        if scopes.is_empty() && !reads_conversations && !reads_memories {
+            return ChatDataScope::ALL;
+        }
+        ChatDataScope {
+            conversations: reads_conversations || scopes.contains(&NotificationScope::UserContext),
+            memories: reads_memories || scopes.contains(&NotificationScope::UserFacts),
+            user_name: scopes.contains(&NotificationScope::UserName),
+        }
+    }
+
+    /// Chat data scope limited to what the user granted
+    pub fn permitted_chat_data_scope(&self) -> ChatDataScope {
+        let requested = self.chat_data_scope();
+        ChatDataScope {
+            conversations: requested.conversations && self.has_scope(AppScope::ReadConversations),
+            memories: requested.memories && self.has_scope(AppScope::ReadMemories),
+            user_name: requested.user_name,
+        }
+    }
+
+    /// Scopes the user is asked to grant: the declared ones, or for apps that predate
+    /// scopes, what their capabilities already gave them access to
+    pub fn requested_scopes(&self) -> Vec<AppScope> {
+        if !self.scopes.is_empty() {
+            return self.scopes.clone();
+        }
+        let triggers_on = self.external_integration.as_ref().map(|ei| &ei.triggers_on);
+        let data = self.chat_data_scope();
+        let mut scopes = vec![];
+        if self.works_with_memories()
+            || (self.works_with_chat() && data.conversations)
+            || matches!(triggers_on, Some(TriggerEvent::MemoryCreation | TriggerEvent::TranscriptProcessed))
+        {
+            scopes.push(AppScope::ReadConversations);
+        }
+        if self.works_with_chat() && data.memories {
+            scopes.push(AppScope::ReadMemories);
+        }
+        if triggers_on == Some(&TriggerEvent::ActionItemCompleted) {
+            scopes.push(AppScope::WriteActionItems);
+        }
+        if self.has_proactive_notifications() {
+            scopes.push(AppScope::Notifications);
+        }
+        scopes
+    }
+
+    /// Whether the user granted `scope`
+    pub fn has_scope(&self, scope: AppScope) -> bool {
+        self.granted_scopes.contains(&scope)
+    }
+
+    /// Whether the user has to complete auth steps (e.g. OAuth) before the integration works
+    pub fn requires_setup(&self) -> bool {
+        self.external_integration
+            .as_ref()
+            .is_some_and(|ei| !ei.auth_steps.is_empty())
+    }
+
+    /// Enabled, but integration triggers are held until setup completes
+    pub fn awaiting_setup(&self) -> bool {
+        self.requires_setup() && !self.setup_completed
+    }
+
+    /// Show the name and description in the first preferred language the app supports
+    pub fn localize(&mut self, languages: &[String]) {
+        localize_metadata(&mut self.name, &mut self.description, &self.translations, languages);
+    }
+
     /// Check if app works with chat
     pub fn works_with_chat(&self) -> bool {
         self.capabilities.contains(&"chat".to_string())
@@ -220,6 +554,28 @@ pub struct AppSummary {
     /// Used for categorizing apps into Integrations vs Notifications sections
     #[serde(default, skip_serializing)]
     pub has_auth_steps: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Used for localizing list views (see `localize`); not sent to clients
+    #[serde(default, skip_serializing)]
+    pub translations: HashMap<String, AppTranslation>,
+}
+
+impl AppSummary {
+    /// Show the name and description in the first preferred language the app supports
+    pub fn localize(&mut self, languages: &[String]) {
+        localize_metadata(&mut self.name, &mut self.description, &self.translations, languages);
+    }
+
+    /// Whether the name or description, in any language, contains the lowercase query
+    pub fn matches_query(&self, query_lower: &str) -> bool {
+        std::iter::once((Some(&self.name), Some(&self.description)))
+            .chain(self.translations.values().map(|t| (t.name.as_ref(), t.description.as_ref())))
+            .any(|(name, description)| {
+                name.is_some_and(|n| n.to_lowercase().contains(query_lower))
+                    || description.is_some_and(|d| d.to_lowercase().contains(query_lower))
+            })
+    }
 }
 
 impl From<App> for AppSummary {
@@ -241,6 +597,8 @@ impl From<App> for AppSummary {
             price: app.price,
             enabled: app.enabled,
             has_auth_steps: false, // Would need to be set from external_integration if available
+            created_at: app.created_at,
+            translations: app.translations,
         }
     }
 }
@@ -292,6 +650,9 @@ pub struct UserEnabledApp {
 #[derive(Debug, Deserialize)]
 pub struct ToggleAppRequest {
     pub app_id: String,
+    /// Scopes the user consented to; enabling requires every scope the app requests
+    #[serde(default)]
+    pub granted_scopes: Vec<AppScope>,
 }
 
 /// Response for enable/disable
@@ -301,6 +662,89 @@ pub struct ToggleAppResponse {
     pub message: String,
 }
 
+/// How long a setup `state` stays valid
+pub const APP_SETUP_STATE_TTL_MINUTES: i64 = 30;
+
+/// A started setup flow, stored under its hashed `state` until the callback uses it
+/// Path: app_setup_states/{sha256(state)}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSetupState {
+    pub uid: String,
+    pub app_id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Response for POST /v1/apps/:app_id/setup
+#[derive(Debug, Serialize)]
+pub struct AppSetupStartResponse {
+    pub app_id: String,
+    /// The app's auth page, with `uid` and `state` appended
+    pub auth_url: String,
+    pub state: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Query for GET /v1/apps/setup/callback
+#[derive(Debug, Deserialize)]
+pub struct AppSetupCallbackQuery {
+    pub state: String,
+}
+
+/// Setup status of an enabled app
+#[derive(Debug, Serialize)]
+pub struct AppSetupStatusResponse {
+    pub app_id: String,
+    /// Whether the app has auth steps at all
+    pub required: bool,
+    pub completed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Longest prompt accepted by the prompt sandbox, in characters
+pub const MAX_TEST_PROMPT_CHARS: usize = 10_000;
+/// Longest sample transcript accepted by the prompt sandbox, in characters
+pub const MAX_TEST_TRANSCRIPT_CHARS: usize = 50_000;
+/// Prompt sandbox runs allowed per developer per hour
+pub const MAX_PROMPT_TESTS_PER_HOUR: u32 = 30;
+
+/// Which app prompt is being tested
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTestKind {
+    /// memory_prompt, run over a conversation
+    Memory,
+    /// chat_prompt, answering a question about a conversation
+    Chat,
+}
+
+/// Request body for POST /v1/apps/test-prompt
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestPromptRequest {
+    pub kind: PromptTestKind,
+    pub prompt: String,
+    /// Sample transcript ("Speaker: text" lines); a built-in synthetic one when omitted
+    #[serde(default)]
+    pub transcript: Option<String>,
+    /// Question for chat prompts; a built-in one when omitted
+    #[serde(default)]
+    pub question: Option<String>,
+}
+
+/// Output of a prompt sandbox run
+#[derive(Debug, Clone, Serialize)]
+pub struct TestPromptResponse {
+    pub kind: PromptTestKind,
+    pub result: String,
+    /// "synthetic" or "provided"
+    pub sample: String,
+    pub model: String,
+    pub usage: crate::llm::client::TokenUsage,
+    /// Runs left this hour; None when no limit could be applied
+    pub remaining_this_hour: Option<u32>,
+}
+
 /// Request to submit a review
 #[derive(Debug, Deserialize)]
 pub struct SubmitReviewRequest {
@@ -309,6 +753,19 @@ pub struct SubmitReviewRequest {
     pub review: String,
 }
 
+/// Request to edit the caller's own review
+#[derive(Debug, Deserialize)]
+pub struct UpdateReviewRequest {
+    pub score: Option<i32>,
+    pub review: Option<String>,
+}
+
+/// Developer response to a review
+#[derive(Debug, Deserialize)]
+pub struct RespondToReviewRequest {
+    pub response: String,
+}
+
 /// Query parameters for listing apps
 #[derive(Debug, Deserialize)]
 pub struct ListAppsQuery {
@@ -470,6 +927,191 @@ fn default_offset() -> usize {
     0
 }
 
+// ============================================================================
+// Apps Home Feed Types
+// ============================================================================
+
+/// Apps per home section when the client doesn't ask
+pub const DEFAULT_HOME_SECTION_LIMIT: usize = 10;
+/// Most apps returned per home section
+pub const MAX_HOME_SECTION_LIMIT: usize = 50;
+/// Category sections shown when admins haven't picked the spotlight categories
+pub const DEFAULT_SPOTLIGHT_CATEGORIES: usize = 3;
+
+/// A kind of section on the marketplace home
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeSectionKind {
+    /// Apps the user has enabled
+    YourApps,
+    /// Most installed apps
+    Popular,
+    /// Apps created in the last 7 days, newest first
+    NewThisWeek,
+    /// One section per spotlighted category
+    CategorySpotlights,
+}
+
+/// Admin-managed layout of the marketplace home (admin_settings/apps_home)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppsHomeLayout {
+    /// Sections in display order; kinds left out are hidden
+    pub sections: Vec<HomeSectionKind>,
+    /// Category ids to spotlight, in order; empty picks the most installed categories
+    #[serde(default)]
+    pub spotlight_categories: Vec<String>,
+}
+
+impl Default for AppsHomeLayout {
+    fn default() -> Self {
+        Self {
+            sections: vec![
+                HomeSectionKind::YourApps,
+                HomeSectionKind::Popular,
+                HomeSectionKind::NewThisWeek,
+                HomeSectionKind::CategorySpotlights,
+            ],
+            spotlight_categories: vec![],
+        }
+    }
+}
+
+/// One section of the marketplace home
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppsHomeSection {
+    /// Stable id: the kind, or "category:<id>" for spotlights
+    pub id: String,
+    pub kind: HomeSectionKind,
+    pub title: String,
+    pub apps: Vec<AppSummary>,
+}
+
+/// Response for GET /v2/apps/home
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppsHomeResponse {
+    pub sections: Vec<AppsHomeSection>,
+}
+
+/// Query parameters for GET /v2/apps/home
+#[derive(Debug, Deserialize)]
+pub struct AppsHomeQuery {
+    /// Apps per section (capped at MAX_HOME_SECTION_LIMIT)
+    #[serde(default = "default_home_section_limit")]
+    pub limit: usize,
+}
+
+fn default_home_section_limit() -> usize {
+    DEFAULT_HOME_SECTION_LIMIT
+}
+
 fn default_v2_limit() -> usize {
     20
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(extra: serde_json::Value) -> App {
+        let mut value = serde_json::json!({
+            "id": "app1",
+            "name": "Coach",
+            "description": "",
+            "image": "",
+            "category": "productivity",
+            "author": "omi",
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_localize_app() {
+        assert_eq!(parse_accept_language("pt_BR, es;q=0.5, *;q=0.1, fr;q=0"), ["pt-br", "es"]);
+        assert_eq!(parse_accept_language("de;q=0.3, ja"), ["ja", "de"]);
+        assert_eq!(normalize_language_tag("english"), None);
+
+        let mut translated = app(serde_json::json!({
+            "translations": {
+                "pt": {"name": "Treinador"},
+                "es": {"name": "Entrenador", "description": "Te ayuda"}
+            }
+        }));
+        translated.localize(&parse_accept_language("pt-BR,es;q=0.9"));
+        assert_eq!((translated.name.as_str(), translated.description.as_str()), ("Treinador", ""));
+
+        // English comes first, so the base text wins over Spanish
+        let mut summary = AppSummary::from(app(serde_json::json!({
+            "translations": {"es": {"name": "Entrenador"}}
+        })));
+        summary.localize(&parse_accept_language("en-US,es;q=0.8"));
+        assert_eq!(summary.name, "Coach");
+        assert!(summary.matches_query("entrena"));
+        summary.localize(&parse_accept_language("es-MX"));
+        assert_eq!(summary.name, "Entrenador");
+    }
+
+    #[test]
+    fn test_chat_data_scope() {
+        let unscoped = app(serde_json::json!({}));
+        assert_eq!(unscoped.chat_data_scope(), ChatDataScope::ALL);
+
+        let facts_only = app(serde_json::json!({
+            "proactive_notification": {"scopes": ["user_name", "user_facts"]}
+        }));
+        assert_eq!(
+            facts_only.chat_data_scope(),
+            ChatDataScope { conversations: false, memories: true, user_name: true }
+        );
+
+        let reads_conversations = app(serde_json::json!({
+            "external_integration": {
+                "triggers_on": "memory_creation",
+                "webhook_url": "https://example.com",
+                "setup_completed_url": null,
+                "actions": ["read_conversations"]
+            }
+        }));
+        assert_eq!(
+            reads_conversations.chat_data_scope(),
+            ChatDataScope { conversations: true, memories: false, user_name: false }
+        );
+    }
+
+    #[test]
+    fn test_requested_and_permitted_scopes() {
+        let mut legacy_chat = app(serde_json::json!({"capabilities": ["chat"]}));
+        assert_eq!(
+            legacy_chat.requested_scopes(),
+            [AppScope::ReadConversations, AppScope::ReadMemories]
+        );
+        // Nothing granted, nothing readable
+        assert_eq!(
+            legacy_chat.permitted_chat_data_scope(),
+            ChatDataScope { conversations: false, memories: false, user_name: true }
+        );
+        legacy_chat.granted_scopes = vec![AppScope::ReadMemories];
+        assert_eq!(
+            legacy_chat.permitted_chat_data_scope(),
+            ChatDataScope { conversations: false, memories: true, user_name: true }
+        );
+
+        let declared = app(serde_json::json!({
+            "capabilities": ["chat", "memories"],
+            "scopes": ["read:memories", "notifications"]
+        }));
+        assert_eq!(declared.requested_scopes(), [AppScope::ReadMemories, AppScope::Notifications]);
+
+        let task_sync = app(serde_json::json!({
+            "capabilities": ["external_integration"],
+            "external_integration": {
+                "triggers_on": "action_item.completed",
+                "webhook_url": "https://example.com",
+                "setup_completed_url": null
+            }
+        }));
+        assert_eq!(task_sync.requested_scopes(), [AppScope::WriteActionItems]);
+        assert_eq!(AppScope::parse("write:action_items"), Some(AppScope::WriteActionItems));
+        assert_eq!(AppScope::parse("read:everything"), None);
+    }
+}
@@ -0,0 +1,25 @@
+// Badge models - Counts shown on the menu bar icon, fetched in one request
+// Counts only (no timestamps), so the response's ETag changes only when a count does.
+
+use serde::{Deserialize, Serialize};
+
+/// Query for GET /v1/badges
+#[derive(Debug, Clone, Deserialize)]
+pub struct BadgesQuery {
+    /// IANA timezone that decides "today" (defaults to the profile's, then UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Response for GET /v1/badges
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BadgeCounts {
+    /// Advice not yet read or dismissed
+    pub unread_advice: u32,
+    /// Extracted memories waiting for review
+    pub unreviewed_memories: u32,
+    /// Open action items due later today
+    pub action_items_due_today: u32,
+    /// Open action items due before now
+    pub action_items_overdue: u32,
+}
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::category::Category;
 
@@ -22,6 +23,49 @@ pub struct TranscriptSegment {
     pub start: f64,
     #[serde(default)]
     pub end: f64,
+    /// Detected spoken language (ISO 639-1), when the text was long enough to classify
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Word-level timing, when the STT provider supplies it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<WordTiming>>,
+}
+
+/// Timing of one spoken word, in seconds from the start of the recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl WordTiming {
+    /// Compact storage form: [[word, start, end], ...] with millisecond precision
+    pub fn to_compact(words: &[WordTiming]) -> serde_json::Value {
+        let round_ms = |t: f64| (t * 1000.0).round() / 1000.0;
+        serde_json::Value::Array(
+            words
+                .iter()
+                .map(|w| serde_json::json!([w.word, round_ms(w.start), round_ms(w.end)]))
+                .collect(),
+        )
+    }
+
+    /// Parse the compact storage form (None if absent or malformed)
+    pub fn from_compact(value: &serde_json::Value) -> Option<Vec<WordTiming>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|entry| {
+                let entry = entry.as_array()?;
+                Some(WordTiming {
+                    word: entry.first()?.as_str()?.to_string(),
+                    start: entry.get(1)?.as_f64()?,
+                    end: entry.get(2)?.as_f64()?,
+                })
+            })
+            .collect()
+    }
 }
 
 fn default_speaker() -> String {
@@ -63,6 +107,29 @@ pub struct ActionItem {
     /// Priority classification: "high", "medium", "low"
     #[serde(default)]
     pub priority: Option<String>,
+    /// ID of the top-level action item (users/{uid}/action_items) this was reconciled into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_item_id: Option<String>,
+    /// How sure the extraction is about due_at (0.0-1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at_confidence: Option<f64>,
+    /// The spoken phrase due_at was inferred from, e.g. "by Friday"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_phrase: Option<String>,
+}
+
+impl ActionItem {
+    /// Staged task metadata carrying the due date inference, if there is one
+    pub fn due_metadata(&self) -> Option<String> {
+        let confidence = self.due_at_confidence.filter(|_| self.due_at.is_some())?;
+        Some(
+            serde_json::json!({
+                "due_at_confidence": confidence,
+                "due_phrase": self.due_phrase,
+            })
+            .to_string(),
+        )
+    }
 }
 
 /// An event extracted from conversation
@@ -79,6 +146,36 @@ pub struct Event {
     /// The duration of the event in minutes
     #[serde(default = "default_duration")]
     pub duration: i32,
+    /// Whether the user already added this event to their calendar
+    #[serde(default)]
+    pub created: bool,
+}
+
+/// Request body for marking conversation events as created
+/// Copied from Python SetConversationEventsStateRequest
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetConversationEventsStateRequest {
+    /// Indexes into structured.events
+    pub events_idx: Vec<usize>,
+    /// New `created` value for each index
+    pub values: Vec<bool>,
+}
+
+impl SetConversationEventsStateRequest {
+    /// Set `created` on the indexed events. Leaves the events unchanged if the lists differ
+    /// in length or an index is out of range.
+    pub fn apply(&self, events: &mut [Event]) -> Result<(), String> {
+        if self.events_idx.len() != self.values.len() {
+            return Err("events_idx and values must have the same length".to_string());
+        }
+        if let Some(idx) = self.events_idx.iter().find(|&&idx| idx >= events.len()) {
+            return Err(format!("Event index {} out of range", idx));
+        }
+        for (&idx, &created) in self.events_idx.iter().zip(&self.values) {
+            events[idx].created = created;
+        }
+        Ok(())
+    }
 }
 
 fn default_duration() -> i32 {
@@ -113,6 +210,40 @@ fn default_emoji() -> String {
     "🧠".to_string()
 }
 
+/// Overview characters kept in a conversation's context line
+const CONTEXT_LINE_OVERVIEW_CHARS: usize = 160;
+
+/// Compact one-line description for chat context: emoji, title and the first
+/// sentence of the overview
+pub fn conversation_context_line(emoji: &str, title: &str, overview: &str) -> String {
+    let overview = overview.trim();
+    let first_sentence = overview
+        .find(". ")
+        .map(|i| &overview[..=i])
+        .unwrap_or(overview);
+    let summary = if first_sentence.chars().count() > CONTEXT_LINE_OVERVIEW_CHARS {
+        let truncated: String = first_sentence.chars().take(CONTEXT_LINE_OVERVIEW_CHARS).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        first_sentence.to_string()
+    };
+
+    let title = title.trim();
+    let line = match (title.is_empty(), summary.is_empty()) {
+        (false, false) => format!("{} {} - {}", emoji, title, summary),
+        (false, true) => format!("{} {}", emoji, title),
+        _ => format!("{} {}", emoji, summary),
+    };
+    line.trim().to_string()
+}
+
+impl Structured {
+    /// See [`conversation_context_line`]
+    pub fn context_line(&self) -> String {
+        conversation_context_line(&self.emoji, &self.title, &self.overview)
+    }
+}
+
 /// Conversation status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -130,6 +261,37 @@ impl Default for ConversationStatus {
     }
 }
 
+/// Why a conversation was discarded automatically (see services::auto_discard)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscardReason {
+    /// Too few transcript segments to be a real conversation
+    TooFewSegments,
+    /// Too little speech time or too few words
+    TooLittleSpeech,
+    /// The LLM judged the content trivial (noise, a few filler words)
+    TrivialContent,
+}
+
+impl DiscardReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscardReason::TooFewSegments => "too_few_segments",
+            DiscardReason::TooLittleSpeech => "too_little_speech",
+            DiscardReason::TrivialContent => "trivial_content",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<DiscardReason> {
+        match value {
+            "too_few_segments" => Some(DiscardReason::TooFewSegments),
+            "too_little_speech" => Some(DiscardReason::TooLittleSpeech),
+            "trivial_content" => Some(DiscardReason::TrivialContent),
+            _ => None,
+        }
+    }
+}
+
 /// Conversation source (what device/app created it)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -206,6 +368,9 @@ pub struct Conversation {
     pub status: ConversationStatus,
     #[serde(default)]
     pub discarded: bool,
+    /// Set when the conversation was discarded automatically rather than by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discard_reason: Option<DiscardReason>,
     #[serde(default)]
     pub deleted: bool,
     #[serde(default)]
@@ -228,4 +393,158 @@ pub struct Conversation {
     /// Name of input device (microphone) used for recording
     #[serde(default)]
     pub input_device_name: Option<String>,
+    /// User opted out of learning from this conversation (no memory extraction)
+    #[serde(default)]
+    pub memory_extraction_disabled: bool,
+    /// Latest push to the user's Notion database, if any
+    #[serde(default)]
+    pub notion_sync: Option<super::notion::NotionSyncStatus>,
+    /// Languages detected in the transcript (ISO 639-1), most-used first
+    #[serde(default)]
+    pub detected_languages: Vec<String>,
+    /// Talk-time and interruption metrics computed from the transcript
+    #[serde(default)]
+    pub analytics: Option<ConversationAnalytics>,
+    /// Precomputed one-line description used by chat context (see Structured::context_line)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_line: Option<String>,
+    /// LLM model that produced each generated artifact, keyed by task
+    /// (summary, action_items, memories), for comparing output quality across models
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub generated_by: HashMap<String, String>,
+    /// Conversation template the summary followed, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// When the raw transcript was removed under the user's transcript retention setting;
+    /// the summary and other structured data are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_deleted_at: Option<DateTime<Utc>>,
+    /// Focus sessions that overlapped the conversation, attached when it was processed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub focus_sessions: Vec<super::focus_session::ConversationFocusSession>,
+    /// Version of the processing prompts the summary was generated with
+    /// (see llm::prompts::prompt_version)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// The user's thumbs rating of the summary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<super::llm_quality::ConversationFeedback>,
+}
+
+/// Claim a processing job holds on a conversation (the locked_by/locked_until fields)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationLock {
+    /// Job holding the lock, e.g. "reprocess:<app_id>" or "merge"
+    pub locked_by: String,
+    /// When the lock lapses if the job never releases it
+    pub locked_until: DateTime<Utc>,
+}
+
+impl ConversationLock {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until > now
+    }
+
+    /// Whole seconds until the lock lapses (at least 1 while active)
+    pub fn retry_after_secs(&self, now: DateTime<Utc>) -> u64 {
+        let millis = (self.locked_until - now).num_milliseconds().max(0) as u64;
+        millis.div_ceil(1000).max(1)
+    }
+}
+
+/// Per-conversation speaking metrics for the coaching view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationAnalytics {
+    /// Sum of all speakers' talk time in seconds
+    pub total_talk_time_seconds: f64,
+    /// Words per minute across all speakers
+    pub words_per_minute: f64,
+    /// Times a speaker started talking before the previous speaker finished
+    pub interruptions: i32,
+    /// Longest uninterrupted run of speech by one speaker, in seconds
+    pub longest_monologue_seconds: f64,
+    /// Speaker key (see SpeakerAnalytics) of the longest monologue
+    pub longest_monologue_speaker: Option<String>,
+    /// Sorted by talk time, most first
+    pub speakers: Vec<SpeakerAnalytics>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Query for GET /v1/conversations/calendar
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversationCalendarQuery {
+    pub year: i32,
+    /// 1-12
+    pub month: u32,
+    /// IANA timezone that decides which day a conversation falls on (defaults to the
+    /// profile's, then UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Activity on one day of the conversations calendar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationCalendarDay {
+    /// YYYY-MM-DD in the requested timezone
+    pub date: chrono::NaiveDate,
+    /// Conversations started that day (discarded ones excluded)
+    pub count: i64,
+    /// Talk time from conversation analytics; conversations without analytics add nothing
+    pub talk_minutes: f64,
+}
+
+/// Response for GET /v1/conversations/calendar: every day of the month, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationCalendarResponse {
+    pub year: i32,
+    pub month: u32,
+    pub timezone: String,
+    pub days: Vec<ConversationCalendarDay>,
+    pub total_count: i64,
+    pub total_talk_minutes: f64,
+}
+
+/// Speaking metrics for one speaker in a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerAnalytics {
+    /// "user", the person_id, or "speaker_{n}"
+    pub speaker: String,
+    /// Display name, resolved when served (not stored)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub is_user: bool,
+    #[serde(default)]
+    pub person_id: Option<String>,
+    pub talk_time_seconds: f64,
+    /// Share of total talk time (0-1)
+    pub talk_time_ratio: f64,
+    pub word_count: i32,
+    pub words_per_minute: f64,
+    pub longest_monologue_seconds: f64,
+    /// Times this speaker cut someone else off
+    pub interruptions: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str) -> Event {
+        serde_json::from_value(serde_json::json!({"title": title, "start": "2026-03-02T09:00:00Z"})).unwrap()
+    }
+
+    #[test]
+    fn test_set_events_state() {
+        let mut events = vec![event("Standup"), event("Review")];
+        let request = |events_idx: Vec<usize>, values: Vec<bool>| SetConversationEventsStateRequest { events_idx, values };
+
+        request(vec![1], vec![true]).apply(&mut events).unwrap();
+        assert_eq!((events[0].created, events[1].created, events[1].duration), (false, true, 30));
+
+        // Invalid requests change nothing
+        assert!(request(vec![0, 2], vec![true, true]).apply(&mut events).is_err());
+        assert!(request(vec![0], vec![]).apply(&mut events).is_err());
+        assert!(!events[0].created);
+    }
 }
@@ -0,0 +1,66 @@
+// Conversation edit models - History of overwritten conversation fields
+// Path: users/{uid}/conversations/{conversation_id}/edits/{edit_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Edits returned by the history endpoint
+pub const MAX_CONVERSATION_EDITS: usize = 100;
+
+/// Which part of the conversation an edit replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationEditField {
+    /// structured.title
+    Title,
+    /// structured title, overview, emoji and category as generated together
+    Summary,
+    /// transcript_segments
+    Transcript,
+}
+
+impl ConversationEditField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConversationEditField::Title => "title",
+            ConversationEditField::Summary => "summary",
+            ConversationEditField::Transcript => "transcript",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "title" => Some(ConversationEditField::Title),
+            "summary" => Some(ConversationEditField::Summary),
+            "transcript" => Some(ConversationEditField::Transcript),
+            _ => None,
+        }
+    }
+}
+
+/// One overwrite of a conversation field, keeping the value it replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationEdit {
+    pub id: String,
+    pub field: ConversationEditField,
+    /// The field's value before the edit (a string for titles, an object for
+    /// summaries, a segment array for transcripts)
+    pub previous_value: Value,
+    /// uid of the user, or "admin" for maintenance tooling
+    pub edited_by: String,
+    /// What made the change: "user", "speaker_assignment", "summary_regeneration" or "restore"
+    pub source: String,
+    /// Edit whose previous value was restored, for source "restore"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response for GET /v1/conversations/:id/history
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationHistoryResponse {
+    pub conversation_id: String,
+    /// Newest first
+    pub edits: Vec<ConversationEdit>,
+}
@@ -0,0 +1,60 @@
+// Conversation template models - Consistent summaries for recurring meeting types
+// Path: users/{uid}/conversation_templates/{template_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most templates a user can keep
+pub const MAX_TEMPLATES: usize = 50;
+/// Most summary sections per template
+pub const MAX_TEMPLATE_SECTIONS: usize = 10;
+/// Longest template name, section heading or title pattern, in characters
+pub const MAX_TEMPLATE_LABEL_CHARS: usize = 80;
+/// Longest free-form instructions, in characters
+pub const MAX_TEMPLATE_INSTRUCTIONS_CHARS: usize = 1000;
+/// Most folders or title patterns a template can be assigned to
+pub const MAX_TEMPLATE_MATCHERS: usize = 20;
+
+/// How to summarize one kind of meeting, and which conversations it applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTemplate {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// Headings the overview is organized under, in order (e.g. "Updates", "Blockers")
+    #[serde(default)]
+    pub summary_sections: Vec<String>,
+    /// Extra summary instructions
+    #[serde(default)]
+    pub summary_instructions: String,
+    /// How action items should be phrased or assigned (e.g. "prefix with the owner's name")
+    #[serde(default)]
+    pub action_item_conventions: String,
+    /// Conversations filed in these folders use the template
+    #[serde(default)]
+    pub folder_ids: Vec<String>,
+    /// Conversations whose title hint matches one of these use the template.
+    /// Case-insensitive; `*` matches any run of characters, otherwise a substring match.
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create or replace a template
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveConversationTemplateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub summary_sections: Vec<String>,
+    #[serde(default)]
+    pub summary_instructions: String,
+    #[serde(default)]
+    pub action_item_conventions: String,
+    #[serde(default)]
+    pub folder_ids: Vec<String>,
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+}
@@ -0,0 +1,61 @@
+// Data export models - Archives of a user's data, built in the background
+// Stored in data_exports/{id}; the archive itself is a gzipped JSON-lines file in GCS,
+// downloaded through a signed link that supports Range requests.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long a signed download link works (capped at the export's expiry)
+pub const EXPORT_LINK_TTL_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataExportStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// One export archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExport {
+    pub id: String,
+    pub uid: String,
+    pub status: DataExportStatus,
+    /// Object path in the exports bucket, once uploaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Records in the archive, by type
+    #[serde(default)]
+    pub conversations: usize,
+    #[serde(default)]
+    pub memories: usize,
+    #[serde(default)]
+    pub action_items: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// When the archive is deleted
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Response for POST /v1/exports/:id/link
+#[derive(Debug, Serialize)]
+pub struct ExportLinkResponse {
+    /// Path of the download endpoint with its signature; needs no Authorization header
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+    pub size_bytes: Option<u64>,
+}
+
+/// Query of a signed download link
+#[derive(Debug, Deserialize)]
+pub struct ExportDownloadQuery {
+    /// Unix seconds
+    pub expires: i64,
+    pub sig: String,
+}
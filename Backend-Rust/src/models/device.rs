@@ -0,0 +1,184 @@
+// Device models - OMI wearable pairing and health
+// Path: users/{uid}/devices/{device_id}
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A device is considered online if it sent a heartbeat within this window
+const ONLINE_WINDOW_MINUTES: i64 = 5;
+/// Battery level (percent) at or below which the device is flagged as low
+const LOW_BATTERY_PERCENT: i32 = 15;
+
+/// Paired OMI device as stored in Firestore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDB {
+    /// Stable ID derived from the serial number
+    pub id: String,
+    #[serde(default)]
+    pub serial_number: String,
+    /// User-facing name (e.g. "Omi DevKit 2")
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Hardware model identifier
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    #[serde(default)]
+    pub hardware_revision: Option<String>,
+    /// Battery level 0-100 from the latest heartbeat
+    #[serde(default)]
+    pub battery_level: Option<i32>,
+    #[serde(default)]
+    pub is_charging: bool,
+    /// Latest heartbeat time
+    #[serde(default)]
+    pub last_seen_at: Option<DateTime<Utc>>,
+    #[serde(default = "Utc::now")]
+    pub paired_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DeviceDB {
+    /// Whether the device has sent a heartbeat recently
+    pub fn is_online(&self, now: DateTime<Utc>) -> bool {
+        self.last_seen_at
+            .is_some_and(|seen| now - seen <= Duration::minutes(ONLINE_WINDOW_MINUTES))
+    }
+
+    pub fn is_battery_low(&self) -> bool {
+        !self.is_charging && self.battery_level.is_some_and(|b| b <= LOW_BATTERY_PERCENT)
+    }
+}
+
+/// Request to register (pair) a device. Re-registering the same serial updates it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisterDeviceRequest {
+    pub serial_number: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    #[serde(default)]
+    pub hardware_revision: Option<String>,
+}
+
+/// Periodic status report from the desktop app while connected to the device
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceHeartbeatRequest {
+    #[serde(default)]
+    pub battery_level: Option<i32>,
+    #[serde(default)]
+    pub is_charging: Option<bool>,
+    /// Reported when the firmware changed since registration
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+}
+
+/// Device with derived health info, as returned to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStatusResponse {
+    #[serde(flatten)]
+    pub device: DeviceDB,
+    pub online: bool,
+    pub battery_low: bool,
+    pub firmware: DeviceFirmwareInfo,
+}
+
+impl DeviceStatusResponse {
+    pub fn new(device: DeviceDB, latest_firmware: Option<&str>, now: DateTime<Utc>) -> Self {
+        Self {
+            online: device.is_online(now),
+            battery_low: device.is_battery_low(),
+            firmware: DeviceFirmwareInfo::new(device.firmware_version.as_deref(), latest_firmware),
+            device,
+        }
+    }
+}
+
+/// Firmware version info for a device
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceFirmwareInfo {
+    pub current_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub update_available: bool,
+}
+
+impl DeviceFirmwareInfo {
+    pub fn new(current: Option<&str>, latest: Option<&str>) -> Self {
+        let update_available = match (current, latest) {
+            (Some(current), Some(latest)) => is_newer_version(latest, current),
+            _ => false,
+        };
+        Self {
+            current_version: current.map(|s| s.to_string()),
+            latest_version: latest.map(|s| s.to_string()),
+            update_available,
+        }
+    }
+}
+
+/// Compare dotted version strings ("2.0.10" > "2.0.9"); a leading "v" is ignored
+pub fn is_newer_version(candidate: &str, current: &str) -> bool {
+    fn parts(v: &str) -> Vec<u64> {
+        v.trim()
+            .trim_start_matches(['v', 'V'])
+            .split('.')
+            .map(|p| {
+                p.chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    let (a, b) = (parts(candidate), parts(current));
+    let len = a.len().max(b.len());
+    for i in 0..len {
+        let (x, y) = (a.get(i).copied().unwrap_or(0), b.get(i).copied().unwrap_or(0));
+        if x != y {
+            return x > y;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("2.0.10", "2.0.9"));
+        assert!(is_newer_version("v3.0", "2.9.9"));
+        assert!(!is_newer_version("2.0", "2.0.0"));
+        assert!(!is_newer_version("1.0.5", "1.0.6-beta"));
+    }
+
+    #[test]
+    fn test_device_online_and_battery() {
+        let now = Utc::now();
+        let device = DeviceDB {
+            id: "d1".to_string(),
+            serial_number: "OMI-123".to_string(),
+            name: None,
+            model: None,
+            firmware_version: Some("2.0.1".to_string()),
+            hardware_revision: None,
+            battery_level: Some(10),
+            is_charging: false,
+            last_seen_at: Some(now - Duration::minutes(2)),
+            paired_at: now,
+            updated_at: now,
+        };
+        assert!(device.is_online(now));
+        assert!(!device.is_online(now + Duration::minutes(10)));
+        assert!(device.is_battery_low());
+        assert!(!DeviceDB { is_charging: true, ..device }.is_battery_low());
+    }
+}
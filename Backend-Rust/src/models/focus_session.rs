@@ -56,6 +56,142 @@ pub struct CreateFocusSessionRequest {
     pub message: Option<String>,
 }
 
+/// A single foreground-app sample reported by the desktop agent
+#[derive(Debug, Clone, Deserialize)]
+pub struct FocusObservation {
+    /// Focus status at sample time
+    pub status: FocusStatus,
+    /// The app or website in the foreground
+    pub app_or_site: String,
+    /// Optional description of what's on screen
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Client-side sample timestamp
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Request body for ingesting a batch of focus observations
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchFocusObservationsRequest {
+    pub observations: Vec<FocusObservation>,
+    /// How often the agent samples, in seconds (credited to the last sample of each session)
+    #[serde(default = "default_sample_interval_seconds")]
+    pub sample_interval_seconds: i64,
+}
+
+fn default_sample_interval_seconds() -> i64 {
+    5
+}
+
+/// Response for a batch ingestion
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchFocusObservationsResponse {
+    /// Number of observations accepted after de-duplication
+    pub observations_accepted: usize,
+    /// Sessions written (re-sent batches produce the same session IDs)
+    pub sessions: Vec<FocusSessionDB>,
+}
+
+/// A run of consecutive same-app observations, ready to be stored as one session
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoalescedFocusSession {
+    pub status: FocusStatus,
+    pub app_or_site: String,
+    pub description: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_seconds: i64,
+}
+
+/// Coalesce observations into sessions.
+/// Observations are sorted by timestamp and exact duplicates dropped; consecutive
+/// samples with the same app and status are merged unless the gap between them
+/// exceeds `max_gap_seconds` (e.g. the machine slept). Each session is credited
+/// with one extra `sample_interval_seconds` for its final sample.
+/// Returns the sessions and the number of observations used.
+pub fn coalesce_focus_observations(
+    observations: &[FocusObservation],
+    sample_interval_seconds: i64,
+    max_gap_seconds: i64,
+) -> (Vec<CoalescedFocusSession>, usize) {
+    let mut sorted: Vec<&FocusObservation> = observations
+        .iter()
+        .filter(|o| !o.app_or_site.trim().is_empty())
+        .collect();
+    sorted.sort_by_key(|o| o.timestamp);
+    sorted.dedup_by(|a, b| a.timestamp == b.timestamp && a.app_or_site == b.app_or_site);
+
+    let mut sessions: Vec<CoalescedFocusSession> = Vec::new();
+    let mut last_seen: Option<DateTime<Utc>> = None;
+
+    for obs in &sorted {
+        let continues = match (sessions.last(), last_seen) {
+            (Some(current), Some(prev)) => {
+                current.app_or_site == obs.app_or_site
+                    && current.status == obs.status
+                    && (obs.timestamp - prev).num_seconds() <= max_gap_seconds
+            }
+            _ => false,
+        };
+
+        if continues {
+            let current = sessions.last_mut().unwrap();
+            current.duration_seconds =
+                (obs.timestamp - current.started_at).num_seconds() + sample_interval_seconds;
+            if current.description.is_empty() {
+                current.description = obs.description.clone().unwrap_or_default();
+            }
+        } else {
+            sessions.push(CoalescedFocusSession {
+                status: obs.status.clone(),
+                app_or_site: obs.app_or_site.clone(),
+                description: obs.description.clone().unwrap_or_default(),
+                started_at: obs.timestamp,
+                duration_seconds: sample_interval_seconds,
+            });
+        }
+        last_seen = Some(obs.timestamp);
+    }
+
+    (sessions, sorted.len())
+}
+
+/// Join the first coalesced session onto the stored session it continues (same app
+/// and status, within `max_gap_seconds` of that session's last sample), so the
+/// session keeps its first event's start time, and with it its ID, across batches.
+/// Only batch-ingested sessions (which have a duration) are continued.
+/// Returns whether the first session was joined.
+pub fn continue_focus_session(
+    sessions: &mut [CoalescedFocusSession],
+    previous: &FocusSessionDB,
+    sample_interval_seconds: i64,
+    max_gap_seconds: i64,
+) -> bool {
+    let (Some(first), Some(previous_duration)) = (sessions.first_mut(), previous.duration_seconds) else {
+        return false;
+    };
+    if first.app_or_site != previous.app_or_site
+        || first.status != previous.status
+        || first.started_at < previous.created_at
+    {
+        return false;
+    }
+
+    let previous_end = previous.created_at + chrono::Duration::seconds(previous_duration);
+    let previous_last_sample = previous_end - chrono::Duration::seconds(sample_interval_seconds);
+    if (first.started_at - previous_last_sample).num_seconds() > max_gap_seconds {
+        return false;
+    }
+
+    // A re-sent batch can end before what's already stored
+    let end = previous_end.max(first.started_at + chrono::Duration::seconds(first.duration_seconds));
+    first.started_at = previous.created_at;
+    first.duration_seconds = (end - previous.created_at).num_seconds();
+    if first.description.is_empty() {
+        first.description = previous.description.clone();
+    }
+    true
+}
+
 /// Query params for getting focus sessions
 #[derive(Debug, Clone, Deserialize)]
 pub struct GetFocusSessionsQuery {
@@ -110,6 +246,35 @@ pub struct FocusStats {
     pub top_distractions: Vec<DistractionEntry>,
 }
 
+/// A focus session that overlapped a conversation, stored on the conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationFocusSession {
+    pub session_id: String,
+    pub status: FocusStatus,
+    pub app_or_site: String,
+    pub started_at: DateTime<Utc>,
+    /// Seconds the session overlapped the conversation
+    pub overlap_seconds: i64,
+}
+
+/// What was being said during a focus session, from one overlapping conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusConversationSnippet {
+    pub conversation_id: String,
+    pub title: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Transcript lines spoken during the session (the overview when none are timed)
+    pub snippet: String,
+}
+
+/// Response for GET /v1/focus-sessions/:id/context
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusSessionContextResponse {
+    pub session: FocusSessionDB,
+    pub conversations: Vec<FocusConversationSnippet>,
+}
+
 /// Query params for getting focus stats
 #[derive(Debug, Clone, Deserialize)]
 pub struct GetFocusStatsQuery {
@@ -117,3 +282,104 @@ pub struct GetFocusStatsQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub date: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn obs(app: &str, status: FocusStatus, secs: i64) -> FocusObservation {
+        FocusObservation {
+            status,
+            app_or_site: app.to_string(),
+            description: None,
+            timestamp: Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_coalesces_consecutive_same_app() {
+        let observations = vec![
+            obs("Xcode", FocusStatus::Focused, 0),
+            obs("Xcode", FocusStatus::Focused, 5),
+            obs("Xcode", FocusStatus::Focused, 10),
+            obs("Twitter", FocusStatus::Distracted, 15),
+            obs("Xcode", FocusStatus::Focused, 20),
+        ];
+        let (sessions, used) = coalesce_focus_observations(&observations, 5, 60);
+        assert_eq!(used, 5);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].duration_seconds, 15);
+        assert_eq!(sessions[1].app_or_site, "Twitter");
+        assert_eq!(sessions[1].duration_seconds, 5);
+    }
+
+    #[test]
+    fn test_resent_and_unordered_observations_are_deduplicated() {
+        let observations = vec![
+            obs("Xcode", FocusStatus::Focused, 5),
+            obs("Xcode", FocusStatus::Focused, 0),
+            obs("Xcode", FocusStatus::Focused, 5),
+        ];
+        let (sessions, used) = coalesce_focus_observations(&observations, 5, 60);
+        assert_eq!(used, 2);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].duration_seconds, 10);
+    }
+
+    fn stored(app: &str, status: FocusStatus, secs: i64, duration_seconds: Option<i64>) -> FocusSessionDB {
+        FocusSessionDB {
+            id: "stored".to_string(),
+            status,
+            app_or_site: app.to_string(),
+            description: "Editing".to_string(),
+            message: None,
+            created_at: Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap(),
+            duration_seconds,
+        }
+    }
+
+    #[test]
+    fn test_session_continues_across_batches() {
+        // Previous batch stored Xcode from 0s with samples up to 10s
+        let previous = stored("Xcode", FocusStatus::Focused, 0, Some(15));
+        let observations = vec![
+            obs("Xcode", FocusStatus::Focused, 15),
+            obs("Xcode", FocusStatus::Focused, 20),
+            obs("Twitter", FocusStatus::Distracted, 25),
+        ];
+        let (mut sessions, _) = coalesce_focus_observations(&observations, 5, 60);
+        assert!(continue_focus_session(&mut sessions, &previous, 5, 60));
+        assert_eq!(sessions[0].started_at, previous.created_at);
+        assert_eq!(sessions[0].duration_seconds, 25);
+        assert_eq!(sessions[0].description, "Editing");
+        assert_eq!(sessions[1].app_or_site, "Twitter");
+
+        // Re-sending the batch joins the same stored session without growing it
+        let extended = stored("Xcode", FocusStatus::Focused, 0, Some(25));
+        let (mut resent, _) = coalesce_focus_observations(&observations, 5, 60);
+        assert!(continue_focus_session(&mut resent, &extended, 5, 60));
+        assert_eq!(resent, sessions);
+    }
+
+    #[test]
+    fn test_session_not_continued_after_gap_or_app_change() {
+        let observations = vec![obs("Xcode", FocusStatus::Focused, 600)];
+        let (mut sessions, _) = coalesce_focus_observations(&observations, 5, 60);
+        let original = sessions.clone();
+        assert!(!continue_focus_session(&mut sessions, &stored("Xcode", FocusStatus::Focused, 0, Some(15)), 5, 60));
+        assert!(!continue_focus_session(&mut sessions, &stored("Slack", FocusStatus::Focused, 590, Some(15)), 5, 60));
+        assert!(!continue_focus_session(&mut sessions, &stored("Xcode", FocusStatus::Focused, 590, None), 5, 60));
+        assert_eq!(sessions, original);
+    }
+
+    #[test]
+    fn test_large_gap_splits_session() {
+        let observations = vec![
+            obs("Xcode", FocusStatus::Focused, 0),
+            obs("Xcode", FocusStatus::Focused, 600),
+        ];
+        let (sessions, _) = coalesce_focus_observations(&observations, 5, 60);
+        assert_eq!(sessions.len(), 2);
+    }
+}
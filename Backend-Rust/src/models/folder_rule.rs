@@ -0,0 +1,113 @@
+// Folder rule models - User-defined rules that file new conversations into folders
+// Path: users/{uid}/folder_rules/{rule_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most rules a user can keep
+pub const MAX_FOLDER_RULES: usize = 50;
+/// Most conditions per rule
+pub const MAX_RULE_CONDITIONS: usize = 5;
+/// Longest rule name or condition value, in characters
+pub const MAX_RULE_LABEL_CHARS: usize = 80;
+/// Most conversations one backfill request looks at
+pub const MAX_BACKFILL_CONVERSATIONS: usize = 2000;
+
+/// Conversation field a condition looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderRuleField {
+    Title,
+    Overview,
+    /// Category name, e.g. "work"
+    Category,
+    /// Source name, e.g. "desktop", "phone", "omi"
+    Source,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderRuleOperator {
+    /// Substring match; `*` matches any run of characters
+    Contains,
+    Equals,
+}
+
+/// One test on a conversation field. Comparisons ignore case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderRuleCondition {
+    pub field: FolderRuleField,
+    pub operator: FolderRuleOperator,
+    pub value: String,
+}
+
+/// Files conversations matching all its conditions into `folder_id`.
+/// Rules are tried in `order` and the first match wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderRule {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub conditions: Vec<FolderRuleCondition>,
+    #[serde(default)]
+    pub folder_id: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub order: i32,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request to create or replace a rule
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveFolderRuleRequest {
+    pub name: String,
+    pub conditions: Vec<FolderRuleCondition>,
+    pub folder_id: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Request to reorder rules; rules not listed keep their relative order after these
+#[derive(Debug, Deserialize)]
+pub struct ReorderFolderRulesRequest {
+    pub rule_ids: Vec<String>,
+}
+
+/// Request to apply the rules to existing conversations
+#[derive(Debug, Default, Deserialize)]
+pub struct FolderRuleBackfillRequest {
+    /// Report what would move without moving anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Also refile conversations that are already in a folder
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Most recent conversations looked at (capped at MAX_BACKFILL_CONVERSATIONS)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// A conversation the backfill moved (or would move)
+#[derive(Debug, Serialize)]
+pub struct FolderRuleMove {
+    pub conversation_id: String,
+    pub rule_id: String,
+    pub folder_id: String,
+}
+
+/// Response for POST /v1/folder-rules/backfill
+#[derive(Debug, Serialize)]
+pub struct FolderRuleBackfillResponse {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub moved: Vec<FolderRuleMove>,
+}
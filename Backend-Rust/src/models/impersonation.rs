@@ -0,0 +1,60 @@
+// Impersonation models - Admin sessions acting as another user, and their audit trail
+// Audit entries: impersonation_audit/{entry_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Shortest accepted impersonation reason, in characters
+pub const MIN_IMPERSONATION_REASON_CHARS: usize = 10;
+/// Longest accepted impersonation reason, in characters
+pub const MAX_IMPERSONATION_REASON_CHARS: usize = 500;
+/// Audit entries returned by the admin listing
+pub const MAX_IMPERSONATION_AUDIT_ENTRIES: usize = 500;
+
+/// Request body for POST /v1/admin/impersonate
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImpersonateRequest {
+    /// The user to act as
+    pub uid: String,
+    /// Why the session is needed, e.g. a support ticket reference
+    pub reason: String,
+}
+
+/// A minted impersonation token
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpersonateResponse {
+    /// Bearer token accepted in place of the user's Firebase ID token
+    pub token: String,
+    pub uid: String,
+    /// Token id, recorded on every audit entry made with it
+    pub session_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// One request made (or token minted) while impersonating a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationAuditEntry {
+    pub id: String,
+    pub admin_uid: String,
+    pub target_uid: String,
+    pub reason: String,
+    pub session_id: String,
+    pub method: String,
+    pub path: String,
+    /// Response status
+    pub status: u16,
+    /// Client IP the request came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query for GET /v1/admin/impersonation-audit
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImpersonationAuditQuery {
+    /// Only entries for this impersonated user
+    #[serde(default)]
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
@@ -86,12 +86,17 @@ impl KnowledgeGraphNode {
 /// An edge in the knowledge graph representing a relationship
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeGraphEdge {
+    #[serde(default)]
     pub id: String,
+    #[serde(default)]
     pub source_id: String,
+    #[serde(default)]
     pub target_id: String,
+    #[serde(default)]
     pub label: String,
     #[serde(default)]
     pub memory_ids: Vec<String>,
+    #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -0,0 +1,123 @@
+// LLM credential models - A user's own API key (BYOK) for LLM calls on their data
+// Path: users/{uid}/integrations/llm (key encrypted at rest)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// LLM provider a user key belongs to. Conversation processing and chat run on Gemini.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProvider {
+    Gemini,
+}
+
+impl LlmProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmProvider::Gemini => "gemini",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<LlmProvider> {
+        match value {
+            "gemini" => Some(LlmProvider::Gemini),
+            _ => None,
+        }
+    }
+}
+
+/// Longest accepted API key, in characters
+pub const MAX_LLM_API_KEY_CHARS: usize = 256;
+
+/// A user's stored LLM credentials (key decrypted)
+#[derive(Debug, Clone)]
+pub struct LlmCredentials {
+    pub provider: LlmProvider,
+    pub api_key: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LlmCredentials {
+    /// Last four characters of the key, for display
+    pub fn key_hint(&self) -> String {
+        let chars: Vec<char> = self.api_key.chars().collect();
+        let tail: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+        format!("…{}", tail)
+    }
+}
+
+/// Request to set (and validate) the user's own key
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetLlmCredentialsRequest {
+    pub provider: LlmProvider,
+    pub api_key: String,
+}
+
+/// Request to validate a key without saving it; omit the key to check the stored one
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ValidateLlmCredentialsRequest {
+    #[serde(default)]
+    pub provider: Option<LlmProvider>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// What the user has configured; the key itself is never returned
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmCredentialsStatus {
+    pub configured: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<LlmProvider>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Option<&LlmCredentials>> for LlmCredentialsStatus {
+    fn from(credentials: Option<&LlmCredentials>) -> Self {
+        Self {
+            configured: credentials.is_some(),
+            provider: credentials.map(|c| c.provider),
+            key_hint: credentials.map(LlmCredentials::key_hint),
+            updated_at: credentials.map(|c| c.updated_at),
+        }
+    }
+}
+
+/// Result of checking a key against the provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidateLlmCredentialsResponse {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(api_key: &str) -> LlmCredentials {
+        LlmCredentials {
+            provider: LlmProvider::Gemini,
+            api_key: api_key.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_key_hint() {
+        assert_eq!(credentials("AIzaSyExample1234").key_hint(), "…1234");
+        assert_eq!(credentials("abc").key_hint(), "…abc");
+        assert_eq!(credentials("").key_hint(), "…");
+        assert_eq!(credentials("ключ-éüöä").key_hint(), "…éüöä");
+    }
+
+    #[test]
+    fn test_status_never_includes_the_key() {
+        let stored = credentials("AIzaSyExample1234");
+        let status = serde_json::to_value(LlmCredentialsStatus::from(Some(&stored))).unwrap();
+        assert_eq!(status["key_hint"], "…1234");
+        assert!(!status.to_string().contains("AIzaSyExample1234"));
+    }
+}
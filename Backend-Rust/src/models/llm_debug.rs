@@ -0,0 +1,42 @@
+// LLM debug log models - Redacted prompt/response pairs kept briefly for debugging
+// Stored in llm_debug_logs/{id} only while LLM_DEBUG_LOGGING is on; a Firestore TTL policy
+// on `expire_at` deletes them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most entries returned for one request
+pub const MAX_LLM_DEBUG_ENTRIES: usize = 100;
+
+/// One LLM call made while handling a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmDebugEntry {
+    pub id: String,
+    /// The X-Request-Id of the request that made the call
+    pub request_id: String,
+    /// "POST /v2/chat/initial-message"
+    pub route: String,
+    pub task: String,
+    pub model: String,
+    /// Redacted and truncated
+    pub prompt: String,
+    /// Redacted and truncated; None when the call failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Lengths before truncation
+    pub prompt_chars: usize,
+    #[serde(default)]
+    pub response_chars: usize,
+    pub duration_ms: u64,
+    pub created_at: DateTime<Utc>,
+    pub expire_at: DateTime<Utc>,
+}
+
+/// Response for GET /v1/admin/llm-debug/:request_id, oldest call first
+#[derive(Debug, Serialize)]
+pub struct LlmDebugLogResponse {
+    pub request_id: String,
+    pub entries: Vec<LlmDebugEntry>,
+}
@@ -0,0 +1,112 @@
+// LLM quality models - Thumbs ratings on LLM output and the daily report built from them
+// Ratings: llm_quality_ratings/{kind}_{uid}_{item_id}; reports: llm_quality_reports/{date}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted conversation feedback comment, in characters
+pub const MAX_FEEDBACK_COMMENT_CHARS: usize = 1000;
+
+/// What produced a piece of LLM output; each part is a report dimension
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmProvenance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Fingerprint of the prompt templates (see llm::prompts::prompt_version)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// Feature that produced the output, e.g. "chat" or "conversation_processing"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+}
+
+/// What was rated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityRatingKind {
+    Message,
+    Conversation,
+}
+
+impl QualityRatingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QualityRatingKind::Message => "message",
+            QualityRatingKind::Conversation => "conversation",
+        }
+    }
+}
+
+/// One user's current rating of one message or conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityRating {
+    pub kind: QualityRatingKind,
+    /// 1 (thumbs up) or -1 (thumbs down)
+    pub rating: i32,
+    #[serde(flatten)]
+    pub provenance: LlmProvenance,
+    pub rated_at: DateTime<Utc>,
+}
+
+/// Ratings for one value of a report dimension
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityBucket {
+    pub key: String,
+    pub thumbs_up: u32,
+    pub thumbs_down: u32,
+    /// thumbs_up / (thumbs_up + thumbs_down)
+    pub approval_rate: f64,
+}
+
+impl QualityBucket {
+    pub fn new(key: &str, thumbs_up: u32, thumbs_down: u32) -> Self {
+        Self {
+            key: key.to_string(),
+            thumbs_up,
+            thumbs_down,
+            approval_rate: approval_rate(thumbs_up, thumbs_down),
+        }
+    }
+}
+
+/// Share of thumbs up, rounded to 3 decimals (0 when nothing was rated)
+pub fn approval_rate(thumbs_up: u32, thumbs_down: u32) -> f64 {
+    let total = thumbs_up + thumbs_down;
+    if total == 0 {
+        return 0.0;
+    }
+    (thumbs_up as f64 / total as f64 * 1000.0).round() / 1000.0
+}
+
+/// Ratings given on one UTC day, overall and broken down by model, prompt version and route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityReport {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub thumbs_up: u32,
+    pub thumbs_down: u32,
+    pub approval_rate: f64,
+    pub by_model: Vec<QualityBucket>,
+    pub by_prompt_version: Vec<QualityBucket>,
+    pub by_route: Vec<QualityBucket>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// A user's rating of a conversation's summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationFeedback {
+    /// 1 (thumbs up) or -1 (thumbs down)
+    pub rating: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub rated_at: DateTime<Utc>,
+}
+
+/// Request body for PUT /v1/conversations/:id/feedback
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversationFeedbackRequest {
+    /// 1 (thumbs up), -1 (thumbs down), null (clear feedback)
+    pub rating: Option<i32>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
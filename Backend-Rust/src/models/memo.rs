@@ -0,0 +1,60 @@
+// Memo models - Quick voice-note style memos kept apart from conversations
+// Path: users/{uid}/memos/{memo_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Max characters accepted for a memo
+pub const MAX_MEMO_CHARS: usize = 5000;
+
+/// A short memo with an LLM one-liner title
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Memo {
+    pub id: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub title: String,
+    /// Action item descriptions extracted from the memo (also staged as tasks)
+    #[serde(default)]
+    pub action_items: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to create a memo from a short transcript or typed text
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMemoRequest {
+    pub text: String,
+    /// Also extract action items and stage them as tasks
+    #[serde(default)]
+    pub extract_action_items: bool,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// IANA timezone used to resolve relative due dates
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Query for GET /v1/memos
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetMemosQuery {
+    #[serde(default = "default_memos_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// Query for GET /v1/memos/search
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchMemosQuery {
+    pub q: String,
+    #[serde(default = "default_memos_limit")]
+    pub limit: usize,
+}
+
+fn default_memos_limit() -> usize {
+    50
+}
@@ -5,6 +5,13 @@ use serde::{Deserialize, Serialize};
 
 use super::category::MemoryCategory;
 
+/// Most memories listed by GET /v3/memories/pending-review
+pub const MAX_PENDING_REVIEW_LIMIT: usize = 200;
+/// Most memory IDs in one review batch (approve and reject combined)
+pub const MAX_MEMORY_REVIEW_BATCH_IDS: usize = 500;
+/// Longest accepted auto-approve delay
+pub const MAX_MEMORY_AUTO_APPROVE_DAYS: i32 = 365;
+
 // =========================================================================
 // REQUEST TYPES
 // =========================================================================
@@ -54,6 +61,26 @@ pub struct ReviewMemoryRequest {
     pub value: bool,
 }
 
+/// Request to approve and reject several memories at once
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MemoryReviewBatchRequest {
+    #[serde(default)]
+    pub approve: Vec<String>,
+    #[serde(default)]
+    pub reject: Vec<String>,
+}
+
+/// Query parameters for GET /v3/memories/pending-review
+#[derive(Debug, Clone, Deserialize)]
+pub struct PendingReviewQuery {
+    #[serde(default = "default_pending_review_limit")]
+    pub limit: usize,
+}
+
+fn default_pending_review_limit() -> usize {
+    50
+}
+
 /// Request to update memory read/dismissed status
 #[derive(Debug, Clone, Deserialize)]
 pub struct UpdateMemoryReadRequest {
@@ -100,6 +127,28 @@ pub struct MemoryStatusResponse {
     pub status: String,
 }
 
+/// Memories waiting for the user's review, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingReviewResponse {
+    pub memories: Vec<MemoryDB>,
+    /// All memories awaiting review
+    pub pending: usize,
+    /// Pending memories per category
+    pub by_category: std::collections::HashMap<String, usize>,
+    /// When the oldest pending memory was created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_pending_at: Option<DateTime<Utc>>,
+}
+
+/// Result of a review batch
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryReviewBatchResponse {
+    pub approved: usize,
+    pub rejected: usize,
+    /// IDs that don't exist (already deleted)
+    pub not_found: Vec<String>,
+}
+
 /// A memory extracted from conversation - long-term knowledge about the user
 /// Copied from Python Memory model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,8 +170,12 @@ pub struct Memory {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryDB {
     pub id: String,
+    /// Not stored in the document
+    #[serde(default)]
     pub uid: String,
+    #[serde(default)]
     pub content: String,
+    #[serde(default)]
     pub category: MemoryCategory,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -4,6 +4,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::LlmProvenance;
+
 // =========================================================================
 // REQUEST TYPES
 // =========================================================================
@@ -24,6 +26,19 @@ pub struct SaveMessageRequest {
     /// Optional JSON metadata (e.g. tool calls, screenshot context)
     #[serde(default)]
     pub metadata: Option<String>,
+    /// IDs of attachments previously uploaded via POST /v2/messages/attachments
+    #[serde(default)]
+    pub attachment_ids: Vec<String>,
+    /// Model, prompt version and route that generated an AI message (for quality reports)
+    #[serde(flatten)]
+    pub provenance: LlmProvenance,
+    /// Whether the client renders markdown; AI messages are stored as plain text if not
+    #[serde(default = "default_true")]
+    pub supports_markdown: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Query params for getting messages
@@ -51,6 +66,30 @@ pub struct DeleteMessagesQuery {
     pub app_id: Option<String>,
 }
 
+/// Query params for GET /v2/messages/search
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchMessagesQuery {
+    /// Words that must all appear in a message
+    pub q: String,
+    /// Only messages in this app's chat
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// Only messages in this chat session
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+/// Longest accepted search query, in characters
+pub const MAX_MESSAGE_SEARCH_QUERY_CHARS: usize = 200;
+/// Most results returned by one search
+pub const MAX_MESSAGE_SEARCH_LIMIT: usize = 50;
+
+fn default_search_limit() -> usize {
+    20
+}
+
 /// Request to rate a message
 #[derive(Debug, Clone, Deserialize)]
 pub struct RateMessageRequest {
@@ -71,6 +110,44 @@ fn default_limit() -> usize {
 pub struct SaveMessageResponse {
     pub id: String,
     pub created_at: DateTime<Utc>,
+    /// The stored text, when response guardrails rewrote an AI message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Response for attachment upload
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadAttachmentsResponse {
+    pub attachments: Vec<MessageAttachment>,
+}
+
+/// A message next to a search hit in its session
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageContext {
+    pub id: String,
+    pub sender: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One message matching a search
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageSearchResult {
+    pub message: MessageDB,
+    /// Text around the first matched word
+    pub snippet: String,
+    /// Session to open to show the message in its chat
+    pub session_id: Option<String>,
+    pub previous: Option<MessageContext>,
+    pub next: Option<MessageContext>,
+}
+
+/// Response for GET /v2/messages/search
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMessagesResponse {
+    pub query: String,
+    /// Newest first
+    pub results: Vec<MessageSearchResult>,
 }
 
 /// Simple status response
@@ -92,10 +169,13 @@ pub struct MessageDB {
     /// Unique message ID
     pub id: String,
     /// Message text content
+    #[serde(default)]
     pub text: String,
     /// When the message was created
+    #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
     /// Sender: "human" or "ai"
+    #[serde(default = "default_sender")]
     pub sender: String,
     /// App ID for app-specific chats (null = main Omi chat)
     #[serde(default)]
@@ -112,6 +192,46 @@ pub struct MessageDB {
     /// Optional JSON metadata (tool calls, screenshot context, etc.)
     #[serde(default)]
     pub metadata: Option<String>,
+    /// Files and images attached to the message
+    #[serde(default)]
+    pub attachments: Vec<MessageAttachment>,
+    /// What generated an AI message
+    #[serde(flatten)]
+    pub provenance: LlmProvenance,
+}
+
+/// File or image attached to a chat message
+/// Path: users/{uid}/files/{id} (bytes stored in GCS)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageAttachment {
+    pub id: String,
+    /// Original file name
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_mime_type")]
+    pub mime_type: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: i64,
+    /// Object path within the attachments bucket
+    pub storage_path: String,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_sender() -> String {
+    "human".to_string()
+}
+
+fn default_mime_type() -> String {
+    "application/octet-stream".to_string()
+}
+
+impl MessageAttachment {
+    /// Whether the attachment can be passed to a vision model
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
 }
 
 impl MessageDB {
@@ -132,6 +252,8 @@ impl MessageDB {
             rating: None,
             reported: false,
             metadata: None,
+            attachments: vec![],
+            provenance: LlmProvenance::default(),
         }
     }
 }
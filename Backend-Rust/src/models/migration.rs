@@ -0,0 +1,56 @@
+// Schema migration models - Progress of versioned Firestore backfills
+// Path: schema_migrations/{migration_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    #[default]
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Tracking record for a migration. Runs resume from `cursor` until completed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationRecord {
+    pub id: String,
+    pub status: MigrationStatus,
+    /// Documents examined so far
+    pub scanned: u64,
+    /// Documents changed so far (or that would change, for dry runs)
+    pub updated: u64,
+    /// Full name of the last processed document
+    pub cursor: Option<String>,
+    pub dry_run: bool,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A registered migration with its tracking record (if it ever ran)
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationInfo {
+    pub id: String,
+    pub description: String,
+    /// Collection group the migration scans
+    pub collection: String,
+    pub record: Option<MigrationRecord>,
+}
+
+/// Request for POST /v1/admin/migrations/:id/run
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunMigrationRequest {
+    /// Count what would change without writing
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Stop after scanning this many documents
+    pub limit: Option<u64>,
+    /// Start over even if a previous run completed
+    #[serde(default)]
+    pub restart: bool,
+}
@@ -1,63 +1,142 @@
 // Models module
 
+pub mod accountability;
 pub mod action_item;
 pub mod advice;
 pub mod agent;
 pub mod app;
+pub mod badges;
 pub mod category;
 pub mod chat_session;
 pub mod conversation;
+pub mod conversation_edit;
+pub mod conversation_template;
+pub mod data_export;
+pub mod device;
 pub mod focus_session;
 pub mod folder;
+pub mod folder_rule;
 pub mod goal;
+pub mod impersonation;
 pub mod knowledge_graph;
+pub mod llm_credentials;
+pub mod llm_debug;
+pub mod llm_quality;
 pub mod llm_usage;
+pub mod memo;
 pub mod memory;
 pub mod message;
+pub mod migration;
+pub mod notification;
+pub mod notion;
 pub mod person;
 pub mod persona;
+pub mod presence;
 pub mod request;
+pub mod review;
 pub mod screen_activity;
+pub mod search;
+pub mod shadow;
+pub mod slack;
+pub mod snooze;
+pub mod sync;
+pub mod timeline;
 pub mod user_settings;
+pub mod user_webhook;
 
-pub use action_item::{AcceptTasksRequest, AcceptTasksResponse, ActionItemDB, ActionItemsListResponse, ActionItemStatusResponse, BatchCreateActionItemsRequest, BatchUpdateScoresRequest, BatchUpdateSortOrdersRequest, CreateActionItemRequest, PromoteResponse, ShareTasksRequest, ShareTasksResponse, SharedTaskInfo, SharedTasksResponse, UpdateActionItemRequest};
-pub use advice::{AdviceCategory, AdviceDB, AdviceStatusResponse, CreateAdviceRequest, GetAdviceQuery, UpdateAdviceRequest};
+pub use accountability::{
+    AccountabilityGroup, CreateGroupRequest, DailyFocusScore, GroupLeaderboard, GroupMember,
+    JoinGroupRequest, LeaderboardEntry, LeaderboardQuery, UpdateGroupMembershipRequest,
+    MAX_GROUP_MEMBERS, MAX_LEADERBOARD_DAYS,
+};
+pub use action_item::{is_same_version, note_preview, normalize_action_item_description, AcceptTasksRequest, AcceptTasksResponse, ActionItemDB, ActionItemNote, ActionItemsListResponse, ActionItemStatusResponse, BatchCreateActionItemsRequest, BatchUpdateScoresRequest, BatchUpdateSortOrdersRequest, CreateActionItemNoteRequest, CreateActionItemRequest, NextActionItemResponse, PromoteResponse, ReconcileActionItemsResponse, ShareTasksRequest, ShareTasksResponse, SharedTaskInfo, SharedTasksResponse, UpdateActionItemRequest, MAX_ACTION_ITEM_NOTE_CHARS};
+pub use advice::{AdviceBulkAction, AdviceBulkRequest, AdviceBulkResponse, AdviceCategory, AdviceDB, AdviceFeedbackRequest, AdviceFeedbackSummary, AdviceFeedbackSummaryQuery, AdviceStatusResponse, CreateAdviceRequest, GetAdviceQuery, UpdateAdviceRequest, AdviceUnreadCountResponse, MAX_ADVICE_BULK_IDS};
 pub use app::{
-    App, AppCapabilityDef, AppCategory, AppGroup, AppReview, AppSummary, AppsV2Meta, AppsV2Query,
-    AppsV2Response, CapabilityInfo, ListAppsQuery, PaginationMeta, SearchAppsQuery,
-    SubmitReviewRequest, ToggleAppRequest, ToggleAppResponse, TriggerEvent, get_app_capabilities,
+    ActionType, AuthStep, ExternalIntegration, NotificationScope, ProactiveNotification,
+    App, AppCapabilityDef, AppScope, AppCategory, AppSetupCallbackQuery, AppSetupStartResponse, AppSetupState,
+    AppSetupStatusResponse, APP_SETUP_STATE_TTL_MINUTES, AppGroup, AppsHomeLayout, AppsHomeQuery,
+    AppsHomeResponse, AppsHomeSection, AppTranslation, HomeSectionKind, SetAppTranslationsRequest,
+    DEFAULT_SPOTLIGHT_CATEGORIES, MAX_APP_DESCRIPTION_CHARS, MAX_APP_NAME_CHARS, MAX_APP_TRANSLATIONS,
+    MAX_HOME_SECTION_LIMIT, normalize_language_tag, parse_accept_language, AppModeration, ChatDataScope, ModerationVerdict, AppReview, AppSummary, AppsV2Meta, AppsV2Query,
+    AppsV2Response, CapabilityInfo, ListAppsQuery, PaginationMeta, RespondToReviewRequest,
+    PromptTestKind, SearchAppsQuery, SubmitReviewRequest, TestPromptRequest, TestPromptResponse, ToggleAppRequest,
+    ToggleAppResponse, TriggerEvent, MAX_PROMPT_TESTS_PER_HOUR, MAX_TEST_PROMPT_CHARS, MAX_TEST_TRANSCRIPT_CHARS,
+    UpdateReviewRequest, get_app_capabilities,
     get_app_categories, get_v2_capabilities,
 };
+pub use badges::{BadgeCounts, BadgesQuery};
 pub use category::{Category, MemoryCategory};
 pub use conversation::{
-    ActionItem, AppResult, Conversation, ConversationPhoto, ConversationSource, ConversationStatus,
-    Event, Geolocation, Structured, TranscriptSegment,
+    conversation_context_line, ActionItem, AppResult, Conversation, ConversationAnalytics,
+    ConversationCalendarDay, ConversationCalendarQuery, ConversationCalendarResponse, ConversationLock, ConversationPhoto,
+    ConversationSource, ConversationStatus, DiscardReason, Event, Geolocation, SetConversationEventsStateRequest,
+    SpeakerAnalytics, Structured, TranscriptSegment, WordTiming,
+};
+pub use conversation_edit::{
+    ConversationEdit, ConversationEditField, ConversationHistoryResponse, MAX_CONVERSATION_EDITS,
+};
+pub use conversation_template::{
+    ConversationTemplate, SaveConversationTemplateRequest, MAX_TEMPLATES,
+    MAX_TEMPLATE_INSTRUCTIONS_CHARS, MAX_TEMPLATE_LABEL_CHARS, MAX_TEMPLATE_MATCHERS,
+    MAX_TEMPLATE_SECTIONS,
 };
 pub use folder::{
     BulkMoveRequest, BulkMoveResponse, CreateFolderRequest, DeleteFolderQuery, Folder,
     MoveToFolderRequest, ReorderFoldersRequest, UpdateFolderRequest,
 };
+pub use folder_rule::{
+    FolderRule, FolderRuleBackfillRequest, FolderRuleBackfillResponse, FolderRuleCondition,
+    FolderRuleField, FolderRuleMove, FolderRuleOperator, ReorderFolderRulesRequest,
+    SaveFolderRuleRequest, MAX_BACKFILL_CONVERSATIONS, MAX_FOLDER_RULES, MAX_RULE_CONDITIONS,
+    MAX_RULE_LABEL_CHARS,
+};
+pub use impersonation::{
+    ImpersonateRequest, ImpersonateResponse, ImpersonationAuditEntry, ImpersonationAuditQuery,
+    MAX_IMPERSONATION_AUDIT_ENTRIES, MAX_IMPERSONATION_REASON_CHARS, MIN_IMPERSONATION_REASON_CHARS,
+};
+pub use llm_quality::{
+    approval_rate, ConversationFeedback, ConversationFeedbackRequest, LlmProvenance, QualityBucket, QualityRating,
+    QualityRatingKind, QualityReport, MAX_FEEDBACK_COMMENT_CHARS,
+};
 pub use memory::{
     CreateMemoryRequest, CreateMemoryResponse, EditMemoryRequest, GetMemoriesQuery, Memory,
-    MemoryDB, MemoryStatusResponse, ReviewMemoryRequest, UpdateMemoryReadRequest,
-    UpdateVisibilityRequest,
+    MemoryDB, MemoryReviewBatchRequest, MemoryReviewBatchResponse, MemoryStatusResponse,
+    PendingReviewQuery, PendingReviewResponse, ReviewMemoryRequest, UpdateMemoryReadRequest,
+    UpdateVisibilityRequest, MAX_MEMORY_AUTO_APPROVE_DAYS, MAX_MEMORY_REVIEW_BATCH_IDS,
+    MAX_PENDING_REVIEW_LIMIT,
 };
 pub use message::{
-    DeleteMessagesQuery, GetMessagesQuery, MessageDB, MessageStatusResponse, RateMessageRequest,
-    SaveMessageRequest, SaveMessageResponse,
+    DeleteMessagesQuery, GetMessagesQuery, MessageAttachment, MessageContext, MessageDB,
+    MessageSearchResult, MessageStatusResponse, RateMessageRequest, SaveMessageRequest,
+    SaveMessageResponse, SearchMessagesQuery, SearchMessagesResponse, UploadAttachmentsResponse,
+    MAX_MESSAGE_SEARCH_LIMIT, MAX_MESSAGE_SEARCH_QUERY_CHARS,
+};
+pub use request::{
+    CreateConversationRequest, CreateConversationResponse, DraftFollowUpEmailRequest,
+    DraftFollowUpEmailResponse, EmailQuotaEntry, EmailSendQuota, EmailSendRecord, FollowUpEmailDraft, SetMemoryExtractionRequest, StoredEmailDraft,
+    SetMemoryExtractionResponse, TranscriptTruncation, TruncationStrategy,
+};
+pub use review::{
+    GenerateWeeklyReviewRequest, ReviewItem, WeeklyReview, WeeklyReviewsQuery,
+    MAX_WEEKLY_REVIEWS_LIMIT,
 };
-pub use request::{CreateConversationRequest, CreateConversationResponse};
 pub use focus_session::{
-    CreateFocusSessionRequest, DistractionEntry, FocusSessionDB, FocusSessionStatusResponse,
+    coalesce_focus_observations, continue_focus_session, BatchFocusObservationsRequest, BatchFocusObservationsResponse,
+    CoalescedFocusSession, ConversationFocusSession, CreateFocusSessionRequest, DistractionEntry,
+    FocusConversationSnippet, FocusSessionContextResponse, FocusSessionDB, FocusSessionStatusResponse,
     FocusStats, FocusStatus, GetFocusSessionsQuery, GetFocusStatsQuery,
 };
 pub use user_settings::{
-    DailySummarySettings, NotificationSettings, PrivateCloudSync, RecordingPermission,
-    AIUserProfile, TranscriptionPreferences, UpdateAIUserProfileRequest, UpdateDailySummaryRequest,
-    UpdateLanguageRequest, UpdateNotificationSettingsRequest, UpdateTranscriptionPreferencesRequest,
+    DailySummarySettings, NotificationSettings, PrivateCloudSync, ProcessingPromptSettings,
+    RecordingPermission, AIUserProfile, TranscriptionPreferences, UpdateAIUserProfileRequest,
+    UpdateDailySummaryRequest, UpdateLanguageRequest, UpdateNotificationSettingsRequest,
+    UpdateProcessingPromptRequest, UpdateTranscriptionPreferencesRequest,
     UpdateUserProfileRequest, UserLanguage, UserProfile, UserSettingsStatusResponse,
     AssistantSettingsData, SharedAssistantSettingsData, FocusSettingsData, TaskSettingsData,
-    AdviceSettingsData, MemorySettingsData,
+    AdviceSettingsData, MemorySettingsData, ActionItemRolloverSettings,
+    UpdateActionItemRolloverRequest, WeeklyReviewSettings, UpdateWeeklyReviewSettingsRequest,
+    RetentionCandidate, RetentionPolicy, RetentionPreview,
+    UpdateRetentionPolicyRequest, AutoDiscardLevel,
 };
 pub use chat_session::{
     ChatSessionDB, ChatSessionStatusResponse, CreateChatSessionRequest, GetChatSessionsQuery,
@@ -68,13 +147,60 @@ pub use goal::{
     GoalHistoryResponse, GoalStatusResponse, GoalType, GoalsListResponse, ScoreData, ScoreResponse,
     UpdateGoalProgressQuery, UpdateGoalRequest,
 };
-pub use person::{BulkAssignSegmentsRequest, CreatePersonRequest, Person};
+pub use person::{
+    BulkAssignSegmentsRequest, CreatePersonRequest, Person, PersonConversation, PersonOverview,
+    RelationshipNotes,
+};
 pub use persona::{
     CheckUsernameQuery, CreatePersonaRequest, GeneratePromptRequest, GeneratePromptResponse,
-    PersonaDB, PersonaResponse, PersonaStatusResponse, UpdatePersonaRequest,
-    UsernameAvailableResponse,
+    PersonaDB, PersonaResponse, PersonaStatusResponse, PublicPersonaMemory, PublicPersonaProfile,
+    UpdatePersonaRequest, UsernameAvailableResponse, MAX_PUBLIC_PERSONA_MEMORIES,
+};
+pub use presence::{
+    DevicePresence, PresenceHeartbeatRequest, PresenceResponse, PresenceState,
+    MAX_PRESENCE_FIELD_CHARS,
+};
+pub use device::{
+    DeviceDB, DeviceFirmwareInfo, DeviceHeartbeatRequest, DeviceStatusResponse,
+    RegisterDeviceRequest,
+};
+pub use notion::{
+    NotionConnectRequest, NotionConnection, NotionDatabase, NotionDatabaseProperty,
+    NotionPropertyMapping, NotionStatusResponse, NotionSyncStatus, UpdateNotionSettingsRequest,
+};
+pub use search::{
+    SearchResultKind, UniversalSearchRequest, UniversalSearchResponse, UniversalSearchResult,
+    MAX_UNIVERSAL_SEARCH_LIMIT, MAX_UNIVERSAL_SEARCH_QUERY_CHARS,
+};
+pub use shadow::{ShadowComparison, ShadowOutput};
+pub use snooze::{
+    BusyBlock, SnoozeBasis, SnoozeRequest, SnoozeResponse, MAX_SNOOZE_BUSY_BLOCKS, MAX_SNOOZE_DAYS,
+};
+pub use slack::{
+    SlackCommandPayload, SlackConnectRequest, SlackConnection, SlackEventEnvelope,
+    SlackStatusResponse, UpdateSlackSettingsRequest,
+};
+pub use timeline::{TimelineCounts, TimelineEntry, TimelineItem, TimelineQuery, TimelineResponse};
+pub use user_webhook::{
+    CreateUserWebhookRequest, CreateUserWebhookResponse, UserWebhook, ACTION_ITEM_COMPLETED_EVENT,
+    MAX_USER_WEBHOOKS, USER_WEBHOOK_EVENTS,
+};
+pub use llm_credentials::{
+    LlmCredentials, LlmCredentialsStatus, LlmProvider, SetLlmCredentialsRequest,
+    ValidateLlmCredentialsRequest, ValidateLlmCredentialsResponse, MAX_LLM_API_KEY_CHARS,
 };
 pub use llm_usage::{RecordLlmUsageRequest, RecordLlmUsageResponse};
+pub use data_export::{
+    DataExport, DataExportStatus, ExportDownloadQuery, ExportLinkResponse, EXPORT_LINK_TTL_HOURS,
+};
+pub use llm_debug::{LlmDebugEntry, LlmDebugLogResponse, MAX_LLM_DEBUG_ENTRIES};
+pub use migration::{MigrationInfo, MigrationRecord, MigrationStatus, RunMigrationRequest};
+pub use sync::{CollectionDelta, SettingsDelta, SyncPage, SyncPosition, SyncQuery, SyncResponse};
+pub use memo::{CreateMemoRequest, GetMemosQuery, Memo, SearchMemosQuery, MAX_MEMO_CHARS};
+pub use notification::{
+    GetNotificationsQuery, MarkNotificationsReadRequest, MarkNotificationsReadResponse, Notification,
+    NotificationKind, NotificationsResponse, MAX_NOTIFICATIONS_LIMIT, MAX_NOTIFICATION_READ_IDS,
+};
 pub use knowledge_graph::{
     ExtractedKnowledge, KnowledgeGraphEdge,
     KnowledgeGraphNode, KnowledgeGraphResponse, KnowledgeGraphStatusResponse, NodeType,
@@ -0,0 +1,79 @@
+// Notification models - In-app notification inbox, kept alongside ephemeral pushes
+// Path: users/{uid}/notifications/{notification_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most notifications returned by one list request
+pub const MAX_NOTIFICATIONS_LIMIT: usize = 200;
+/// Most IDs accepted by one mark-read request
+pub const MAX_NOTIFICATION_READ_IDS: usize = 500;
+
+/// What produced a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// Goal milestones and other proactive nudges
+    Nudge,
+    /// Generated summaries and reviews
+    Summary,
+    /// A connected integration needs attention
+    IntegrationAlert,
+}
+
+/// A notification as stored in the inbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub kind: NotificationKind,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// In-app destination (e.g. "/reviews/weekly/2026-03-02")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<DateTime<Utc>>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for GET /v1/notifications
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetNotificationsQuery {
+    #[serde(default = "default_notifications_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+fn default_notifications_limit() -> usize {
+    50
+}
+
+/// Response for GET /v1/notifications
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationsResponse {
+    pub notifications: Vec<Notification>,
+    /// Unread notifications in the whole inbox, not just this page
+    pub unread: i64,
+}
+
+/// Request body for POST /v1/notifications/read
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MarkNotificationsReadRequest {
+    /// Notifications to mark read; empty marks the whole inbox read
+    #[serde(default)]
+    pub ids: Vec<String>,
+}
+
+/// Response for POST /v1/notifications/read
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkNotificationsReadResponse {
+    pub updated: usize,
+    /// Requested IDs that don't exist
+    pub not_found: Vec<String>,
+}
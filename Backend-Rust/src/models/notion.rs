@@ -0,0 +1,166 @@
+// Notion integration models
+// Path: users/{uid}/integrations/notion
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which Notion database properties conversation fields are written to.
+/// Only the title is required; unmapped fields are skipped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotionPropertyMapping {
+    /// Title property (every Notion database has exactly one)
+    #[serde(default = "default_title_property")]
+    pub title: String,
+    /// Date property for the conversation start time
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Rich text property for the overview
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Select property for the conversation category
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Number property for the count of open action items
+    #[serde(default)]
+    pub open_action_items: Option<String>,
+}
+
+fn default_title_property() -> String {
+    "Name".to_string()
+}
+
+impl Default for NotionPropertyMapping {
+    fn default() -> Self {
+        Self {
+            title: default_title_property(),
+            date: None,
+            summary: None,
+            category: None,
+            open_action_items: None,
+        }
+    }
+}
+
+/// A user's Notion connection as stored in Firestore
+#[derive(Debug, Clone)]
+pub struct NotionConnection {
+    pub access_token: String,
+    pub workspace_id: String,
+    pub workspace_name: Option<String>,
+    pub bot_id: String,
+    /// Target database for conversation pages (chosen after connecting)
+    pub database_id: Option<String>,
+    pub property_mapping: NotionPropertyMapping,
+    /// Push every processed conversation automatically
+    pub auto_sync: bool,
+    pub connected_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Result of the latest push of a conversation to Notion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotionSyncStatus {
+    /// "synced" or "failed"
+    pub status: String,
+    #[serde(default)]
+    pub page_id: Option<String>,
+    #[serde(default)]
+    pub page_url: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+impl NotionSyncStatus {
+    pub fn synced(page_id: String, page_url: Option<String>) -> Self {
+        Self {
+            status: "synced".to_string(),
+            page_id: Some(page_id),
+            page_url,
+            error: None,
+            synced_at: Utc::now(),
+        }
+    }
+
+    /// Keeps the previous page reference so a later retry can replace it
+    pub fn failed(error: String, previous: Option<&NotionSyncStatus>) -> Self {
+        Self {
+            status: "failed".to_string(),
+            page_id: previous.and_then(|p| p.page_id.clone()),
+            page_url: previous.and_then(|p| p.page_url.clone()),
+            error: Some(error),
+            synced_at: Utc::now(),
+        }
+    }
+}
+
+/// Request to connect Notion with an OAuth authorization code
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotionConnectRequest {
+    pub code: String,
+    /// Must match the redirect URI used to obtain the code (defaults to the configured one)
+    #[serde(default)]
+    pub redirect_uri: Option<String>,
+}
+
+/// Request to update Notion sync settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateNotionSettingsRequest {
+    #[serde(default)]
+    pub database_id: Option<String>,
+    #[serde(default)]
+    pub property_mapping: Option<NotionPropertyMapping>,
+    #[serde(default)]
+    pub auto_sync: Option<bool>,
+}
+
+/// Notion connection status (never includes the access token)
+#[derive(Debug, Clone, Serialize)]
+pub struct NotionStatusResponse {
+    pub connected: bool,
+    pub workspace_name: Option<String>,
+    pub database_id: Option<String>,
+    pub property_mapping: Option<NotionPropertyMapping>,
+    pub auto_sync: bool,
+    pub connected_at: Option<DateTime<Utc>>,
+}
+
+impl From<Option<&NotionConnection>> for NotionStatusResponse {
+    fn from(connection: Option<&NotionConnection>) -> Self {
+        match connection {
+            Some(c) => Self {
+                connected: true,
+                workspace_name: c.workspace_name.clone(),
+                database_id: c.database_id.clone(),
+                property_mapping: Some(c.property_mapping.clone()),
+                auto_sync: c.auto_sync,
+                connected_at: Some(c.connected_at),
+            },
+            None => Self {
+                connected: false,
+                workspace_name: None,
+                database_id: None,
+                property_mapping: None,
+                auto_sync: false,
+                connected_at: None,
+            },
+        }
+    }
+}
+
+/// A Notion database the integration has been given access to
+#[derive(Debug, Clone, Serialize)]
+pub struct NotionDatabase {
+    pub id: String,
+    pub title: String,
+    pub url: Option<String>,
+    /// Database schema, for building a property mapping
+    pub properties: Vec<NotionDatabaseProperty>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotionDatabaseProperty {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub property_type: String,
+}
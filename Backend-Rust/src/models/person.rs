@@ -3,6 +3,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::action_item::ActionItemDB;
+
 /// A person (speaker profile) associated with the user
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Person {
@@ -10,6 +12,18 @@ pub struct Person {
     pub name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Cached LLM relationship notes for the people overview
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationship_notes: Option<RelationshipNotes>,
+}
+
+/// Short LLM-written notes about the user's relationship with a person
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationshipNotes {
+    pub notes: Vec<String>,
+    /// Newest conversation the notes were written from; newer ones trigger a rewrite
+    pub latest_conversation_id: String,
+    pub generated_at: DateTime<Utc>,
 }
 
 /// Request to create a new person
@@ -25,3 +39,30 @@ pub struct BulkAssignSegmentsRequest {
     pub assign_type: String,
     pub value: Option<String>,
 }
+
+/// A conversation the person spoke in
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonConversation {
+    pub id: String,
+    pub title: String,
+    pub emoji: String,
+    pub overview: String,
+    pub started_at: DateTime<Utc>,
+    /// Seconds of speech attributed to the person
+    pub talk_time_seconds: f64,
+}
+
+/// Everything known about one person: conversations, shared tasks and relationship notes
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonOverview {
+    pub person: Person,
+    pub conversation_count: usize,
+    pub first_seen_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub total_talk_time_seconds: f64,
+    /// Newest first
+    pub conversations: Vec<PersonConversation>,
+    /// Action items from those conversations or naming the person
+    pub action_items: Vec<ActionItemDB>,
+    pub relationship_notes: Vec<String>,
+}
@@ -4,6 +4,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::category::MemoryCategory;
+
 // =========================================================================
 // REQUEST TYPES
 // =========================================================================
@@ -28,6 +30,8 @@ pub struct UpdatePersonaRequest {
     pub persona_prompt: Option<String>,
     /// Avatar image URL
     pub image: Option<String>,
+    /// Ask search engines not to index the public page (/u/:username)
+    pub noindex: Option<bool>,
 }
 
 /// Request to regenerate persona prompt from current memories
@@ -70,6 +74,9 @@ pub struct PersonaResponse {
     pub updated_at: DateTime<Utc>,
     /// Number of public memories used to build the persona
     pub public_memories_count: Option<i32>,
+    /// Public page asks search engines not to index it
+    #[serde(default)]
+    pub noindex: bool,
 }
 
 /// Simple status response
@@ -94,6 +101,31 @@ pub struct GeneratePromptResponse {
     pub memories_used: i32,
 }
 
+/// Most public memories shown on a persona page
+pub const MAX_PUBLIC_PERSONA_MEMORIES: usize = 50;
+
+/// A public memory as shown on a persona page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicPersonaMemory {
+    pub content: String,
+    pub category: MemoryCategory,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Public profile served at GET /u/:username (no owner ids, email or prompt)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicPersonaProfile {
+    pub username: String,
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    pub author: String,
+    pub memories: Vec<PublicPersonaMemory>,
+    /// Search engines are asked not to index the page
+    pub noindex: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
 // =========================================================================
 // DATABASE MODEL
 // =========================================================================
@@ -134,6 +166,9 @@ pub struct PersonaDB {
     pub email: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Public page asks search engines not to index it
+    #[serde(default)]
+    pub noindex: bool,
 }
 
 impl PersonaDB {
@@ -157,6 +192,7 @@ impl PersonaDB {
             created_at: self.created_at,
             updated_at: self.updated_at,
             public_memories_count,
+            noindex: self.noindex,
         }
     }
 }
@@ -0,0 +1,58 @@
+// Presence models - What each of a user's devices is doing right now
+// Lets devices see whether another one is already recording.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a device is currently doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceState {
+    Idle,
+    Recording,
+    InMeeting,
+}
+
+/// Longest accepted device ID or name
+pub const MAX_PRESENCE_FIELD_CHARS: usize = 128;
+
+/// Heartbeat sent periodically by each running app
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresenceHeartbeatRequest {
+    /// Stable per-install identifier chosen by the client
+    pub device_id: String,
+    pub state: PresenceState,
+    /// Shown to other devices, e.g. "Work MacBook"
+    #[serde(default)]
+    pub device_name: Option<String>,
+    /// "macos", "ios", "omi", ...
+    #[serde(default)]
+    pub platform: Option<String>,
+}
+
+/// Last reported state of one device
+#[derive(Debug, Clone, Serialize)]
+pub struct DevicePresence {
+    pub device_id: String,
+    pub state: PresenceState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Client IP of the last heartbeat
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    /// Time of the last heartbeat
+    pub updated_at: DateTime<Utc>,
+    /// The device counts as gone after this without a new heartbeat
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Devices with a recent heartbeat
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceResponse {
+    /// Most recently seen first
+    pub devices: Vec<DevicePresence>,
+    /// Whether any device is recording or in a meeting
+    pub any_recording: bool,
+}
@@ -21,6 +21,60 @@ pub struct CreateConversationRequest {
     pub source: ConversationSource,
     /// Name of the input device (microphone) used for recording
     pub input_device_name: Option<String>,
+    /// Don't extract memories from this conversation
+    #[serde(default)]
+    pub memory_extraction_disabled: bool,
+    /// File the conversation in this folder (its template, if any, applies)
+    #[serde(default)]
+    pub folder_id: Option<String>,
+    /// Summarize with this conversation template instead of a matched one
+    #[serde(default)]
+    pub template_id: Option<String>,
+    /// Known meeting title (e.g. the calendar event), matched against template title patterns
+    #[serde(default)]
+    pub title_hint: Option<String>,
+    /// What to do if the transcript is over the server's length limits; defaults to the
+    /// server's configured strategy
+    #[serde(default)]
+    pub truncation: Option<TruncationStrategy>,
+}
+
+/// How an over-long transcript is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Fail with 413 Payload Too Large
+    Reject,
+    /// Keep the beginning of the transcript
+    Head,
+    /// Keep the end of the transcript
+    Tail,
+    /// Keep the whole transcript and summarize it chunk by chunk
+    Summarize,
+}
+
+impl std::str::FromStr for TruncationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "head" => Ok(Self::Head),
+            "tail" => Ok(Self::Tail),
+            "summarize" => Ok(Self::Summarize),
+            other => Err(format!("Unknown truncation strategy: {}", other)),
+        }
+    }
+}
+
+/// How a transcript over the length limits was handled
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptTruncation {
+    pub strategy: TruncationStrategy,
+    pub original_segments: usize,
+    pub kept_segments: usize,
+    pub original_tokens: usize,
+    pub kept_tokens: usize,
 }
 
 fn default_language() -> String {
@@ -31,6 +85,125 @@ fn default_timezone() -> String {
     "UTC".to_string()
 }
 
+/// Request to toggle memory extraction for a conversation
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetMemoryExtractionRequest {
+    /// True = don't learn from this conversation
+    pub disabled: bool,
+    /// When disabling, also delete memories already extracted from it
+    #[serde(default = "default_true")]
+    pub delete_existing_memories: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Response after toggling memory extraction
+#[derive(Debug, Clone, Serialize)]
+pub struct SetMemoryExtractionResponse {
+    pub memory_extraction_disabled: bool,
+    pub deleted_memories: usize,
+}
+
+/// Request to draft (and optionally send) a follow-up email for a conversation.
+/// Send only after the user has reviewed the draft: pass `send` with the draft's ID and
+/// the final recipients, plus `subject`/`body` if the user edited them; otherwise the
+/// stored draft is sent as-is. To have the wording regenerated, draft again with `instructions`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DraftFollowUpEmailRequest {
+    /// Extra guidance for the draft (e.g. "keep it short", "formal tone")
+    #[serde(default)]
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub send: bool,
+    /// Recipient email addresses (required when sending)
+    #[serde(default)]
+    pub to: Vec<String>,
+    /// The confirmed draft (required when sending)
+    #[serde(default)]
+    pub draft_id: Option<String>,
+    /// The user's edited subject, sent instead of the draft's
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// The user's edited body, sent instead of the draft's
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// Editable follow-up email draft
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUpEmailDraft {
+    pub subject: String,
+    pub body: String,
+    /// Names of attendees the email is likely meant for (from speaker names)
+    #[serde(default)]
+    pub suggested_recipients: Vec<String>,
+}
+
+/// Response for the follow-up email endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftFollowUpEmailResponse {
+    /// Pass back to send this draft
+    pub draft_id: String,
+    pub draft: FollowUpEmailDraft,
+    pub sent: bool,
+}
+
+/// A generated follow-up email, kept so only drafts the server wrote can be sent
+/// (users/{uid}/email_drafts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEmailDraft {
+    pub id: String,
+    pub conversation_id: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Audit record of one sent follow-up email (users/{uid}/email_sends)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSendRecord {
+    pub id: String,
+    pub conversation_id: String,
+    pub draft_id: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Rolling tally behind the daily follow-up email limit (users/{uid}/email_send_quota/daily).
+/// Reserved before each send with a conditional write, so concurrent sends can't both
+/// slip under the limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmailSendQuota {
+    #[serde(default)]
+    pub sends: Vec<EmailQuotaEntry>,
+}
+
+/// Recipients of one send, as counted by the quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailQuotaEntry {
+    pub sent_at: DateTime<Utc>,
+    pub recipients: usize,
+}
+
+impl EmailSendQuota {
+    /// Count `recipients` sent at `now` if the last 24 hours stay within `max_per_day`.
+    /// Sends older than that are dropped either way.
+    pub fn reserve(&mut self, now: DateTime<Utc>, recipients: usize, max_per_day: usize) -> bool {
+        self.sends.retain(|s| s.sent_at > now - chrono::Duration::hours(24));
+        let sent: usize = self.sends.iter().map(|s| s.recipients).sum();
+        if sent + recipients > max_per_day {
+            return false;
+        }
+        self.sends.push(EmailQuotaEntry { sent_at: now, recipients });
+        true
+    }
+}
+
 /// Response after creating a conversation
 /// Copied from Python CreateConversationResponse
 #[derive(Debug, Clone, Serialize)]
@@ -38,4 +211,42 @@ pub struct CreateConversationResponse {
     pub id: String,
     pub status: String,
     pub discarded: bool,
+    /// Whether segments were dropped to fit the length limits
+    pub truncated: bool,
+    /// Set when the transcript was over the length limits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<TranscriptTruncation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_email_send_quota_rolls_over_after_a_day() {
+        let now = Utc::now();
+        let mut quota = EmailSendQuota::default();
+        assert!(quota.reserve(now - Duration::hours(30), 40, 50));
+        assert!(quota.reserve(now - Duration::hours(2), 30, 50));
+        // The 40 from yesterday no longer count; 30 + 25 would exceed 50
+        assert!(!quota.reserve(now, 25, 50));
+        assert_eq!(quota.sends.len(), 1);
+        assert!(quota.reserve(now, 20, 50));
+        assert!(!quota.reserve(now, 1, 50));
+    }
+
+    #[test]
+    fn test_send_request_accepts_edits() {
+        let request: DraftFollowUpEmailRequest = serde_json::from_value(serde_json::json!({
+            "send": true,
+            "draft_id": "d1",
+            "to": ["a@example.com"],
+            "subject": "Next steps",
+            "body": "Thanks for today"
+        }))
+        .unwrap();
+        assert_eq!(request.subject.as_deref(), Some("Next steps"));
+        assert_eq!(request.body.as_deref(), Some("Thanks for today"));
+    }
 }
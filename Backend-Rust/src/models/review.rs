@@ -0,0 +1,77 @@
+// Weekly review models - LLM-written look back over a week of tasks, goals and focus
+// Path: users/{uid}/weekly_reviews/{week_start}
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most past reviews returned by one list request
+pub const MAX_WEEKLY_REVIEWS_LIMIT: usize = 52;
+
+/// An action item as it appears in a review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub action_item_id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Review of one Monday-to-Sunday week in the user's timezone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyReview {
+    /// Monday the week starts on; also the document ID
+    pub week_start: NaiveDate,
+    /// IANA timezone the week boundaries were computed in
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Action items completed during the week
+    #[serde(default)]
+    pub accomplished: Vec<ReviewItem>,
+    /// Action items that came due during the week and are still open
+    #[serde(default)]
+    pub slipped: Vec<ReviewItem>,
+    #[serde(default)]
+    pub focused_minutes: i64,
+    #[serde(default)]
+    pub distracted_minutes: i64,
+    /// LLM-written summary of how the week went
+    #[serde(default)]
+    pub focus_summary: String,
+    /// LLM-suggested priorities for the following week
+    #[serde(default)]
+    pub suggested_priorities: Vec<String>,
+    /// Whether the review was produced by the weekly schedule rather than on request
+    #[serde(default)]
+    pub scheduled: bool,
+    #[serde(default = "Utc::now")]
+    pub generated_at: DateTime<Utc>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+/// Request body for POST /v1/reviews/weekly
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GenerateWeeklyReviewRequest {
+    /// Any day in the week to review; defaults to the most recent completed week
+    #[serde(default)]
+    pub week_of: Option<NaiveDate>,
+    /// Defaults to the user's profile timezone
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Query parameters for GET /v1/reviews/weekly
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeeklyReviewsQuery {
+    #[serde(default = "default_weekly_reviews_limit")]
+    pub limit: usize,
+}
+
+fn default_weekly_reviews_limit() -> usize {
+    12
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -24,3 +25,68 @@ pub struct ScreenActivitySyncResponse {
     pub synced: usize,
     pub last_id: i64,
 }
+
+/// Snapshot of what the user is looking at (active window + OCR text)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenContextSnapshot {
+    #[serde(default)]
+    pub app_name: String,
+    #[serde(default)]
+    pub window_title: String,
+    #[serde(default)]
+    pub ocr_text: String,
+    pub captured_at: DateTime<Utc>,
+}
+
+impl ScreenContextSnapshot {
+    /// One-line description, e.g. "Xcode — ContentView.swift"
+    pub fn summary(&self) -> String {
+        match (self.app_name.is_empty(), self.window_title.is_empty()) {
+            (false, false) => format!("{} — {}", self.app_name, self.window_title),
+            (false, true) => self.app_name.clone(),
+            (true, false) => self.window_title.clone(),
+            (true, true) => String::new(),
+        }
+    }
+
+    /// Prompt section describing the snapshot
+    pub fn to_context_string(&self) -> String {
+        let mut lines = vec!["<screen_context>".to_string()];
+        lines.push(format!(
+            "Captured at: {}",
+            self.captured_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        if !self.app_name.is_empty() {
+            lines.push(format!("Active app: {}", self.app_name));
+        }
+        if !self.window_title.is_empty() {
+            lines.push(format!("Window title: {}", self.window_title));
+        }
+        if !self.ocr_text.is_empty() {
+            lines.push(format!("Visible text:\n{}", self.ocr_text));
+        }
+        lines.push("</screen_context>".to_string());
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScreenContextRequest {
+    #[serde(default)]
+    pub app_name: String,
+    #[serde(default)]
+    pub window_title: String,
+    #[serde(default)]
+    pub ocr_text: String,
+    /// When the snapshot was taken (defaults to now)
+    #[serde(default)]
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenContextResponse {
+    /// False when the snapshot was dropped by throttling
+    pub accepted: bool,
+    /// Snapshots currently buffered for the user
+    pub buffered: usize,
+}
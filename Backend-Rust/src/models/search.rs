@@ -0,0 +1,74 @@
+// Universal search models - Command palette search across the user's data
+// One ranked list mixing conversations, memories, action items, folders and apps.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most results returned by one search
+pub const MAX_UNIVERSAL_SEARCH_LIMIT: usize = 50;
+/// Longest accepted query, in characters
+pub const MAX_UNIVERSAL_SEARCH_QUERY_CHARS: usize = 200;
+
+/// What a search result points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchResultKind {
+    Conversation,
+    Memory,
+    ActionItem,
+    Folder,
+    App,
+}
+
+impl SearchResultKind {
+    /// Desktop deep link opening the item
+    pub fn deep_link(&self, id: &str) -> String {
+        let path = match self {
+            SearchResultKind::Conversation => "conversation",
+            SearchResultKind::Memory => "memory",
+            SearchResultKind::ActionItem => "task",
+            SearchResultKind::Folder => "folder",
+            SearchResultKind::App => "app",
+        };
+        format!("omi-computer://{}/{}", path, id)
+    }
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+/// Request body for POST /v2/search/universal
+#[derive(Debug, Clone, Deserialize)]
+pub struct UniversalSearchRequest {
+    pub query: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Only these kinds; all when empty
+    #[serde(default)]
+    pub types: Vec<SearchResultKind>,
+}
+
+/// One result of a universal search
+#[derive(Debug, Clone, Serialize)]
+pub struct UniversalSearchResult {
+    #[serde(rename = "type")]
+    pub kind: SearchResultKind,
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    pub deep_link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    pub score: f64,
+}
+
+/// Response for POST /v2/search/universal
+#[derive(Debug, Clone, Serialize)]
+pub struct UniversalSearchResponse {
+    pub query: String,
+    pub results: Vec<UniversalSearchResult>,
+    /// Server time spent on the search
+    pub took_ms: u64,
+}
@@ -0,0 +1,40 @@
+// Shadow comparison models - Baseline and candidate processing output for one conversation
+// Stored in shadow_comparisons/{conversation_id} for offline review; never returned to users.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What one processing run produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShadowOutput {
+    /// Model used for the summary
+    pub model: String,
+    /// Fingerprint of the prompt templates (see llm::prompts::prompt_version)
+    pub prompt_version: String,
+    pub title: String,
+    pub overview: String,
+    pub emoji: String,
+    pub category: String,
+    #[serde(default)]
+    pub action_items: Vec<String>,
+    #[serde(default)]
+    pub memories: Vec<String>,
+    /// Wall-clock processing time
+    pub duration_ms: u64,
+}
+
+/// Production output next to the candidate's for the same transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowComparison {
+    /// The conversation ID
+    pub id: String,
+    pub uid: String,
+    pub baseline: ShadowOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<ShadowOutput>,
+    /// Why the candidate run failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_error: Option<String>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
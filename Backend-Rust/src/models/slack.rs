@@ -0,0 +1,143 @@
+// Slack integration models
+// Path: users/{uid}/integrations/slack, lookup: slack_users/{team_id}_{slack_user_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default local hour for the daily digest DM
+pub const DEFAULT_SLACK_DIGEST_HOUR: i32 = 18;
+
+/// A user's Slack connection as stored in Firestore
+#[derive(Debug, Clone)]
+pub struct SlackConnection {
+    /// Bot token for the installing workspace
+    pub access_token: String,
+    pub team_id: String,
+    pub team_name: Option<String>,
+    pub bot_user_id: Option<String>,
+    /// The user's own Slack ID; maps inbound commands/messages back to them
+    pub slack_user_id: String,
+    /// Create action items from slash commands and DMs to the bot
+    pub action_item_capture_enabled: bool,
+    /// Deliver the daily digest as a Slack DM
+    pub digest_enabled: bool,
+    /// Local hour (0-23) the digest is sent at
+    pub digest_hour: i32,
+    /// Channel to post the digest to (defaults to a DM with the user)
+    pub digest_channel_id: Option<String>,
+    /// Local date (YYYY-MM-DD) of the last digest sent
+    pub last_digest_date: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to connect Slack with an OAuth authorization code
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackConnectRequest {
+    pub code: String,
+    /// Must match the redirect URI used to obtain the code (defaults to the configured one)
+    #[serde(default)]
+    pub redirect_uri: Option<String>,
+}
+
+/// Request to update Slack integration settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSlackSettingsRequest {
+    #[serde(default)]
+    pub action_item_capture_enabled: Option<bool>,
+    #[serde(default)]
+    pub digest_enabled: Option<bool>,
+    #[serde(default)]
+    pub digest_hour: Option<i32>,
+    /// Empty string resets to the default DM
+    #[serde(default)]
+    pub digest_channel_id: Option<String>,
+}
+
+/// Slack connection status (never includes the access token)
+#[derive(Debug, Clone, Serialize)]
+pub struct SlackStatusResponse {
+    pub connected: bool,
+    pub team_name: Option<String>,
+    pub action_item_capture_enabled: bool,
+    pub digest_enabled: bool,
+    pub digest_hour: i32,
+    pub digest_channel_id: Option<String>,
+    pub last_digest_date: Option<String>,
+    pub connected_at: Option<DateTime<Utc>>,
+}
+
+impl From<Option<&SlackConnection>> for SlackStatusResponse {
+    fn from(connection: Option<&SlackConnection>) -> Self {
+        match connection {
+            Some(c) => Self {
+                connected: true,
+                team_name: c.team_name.clone(),
+                action_item_capture_enabled: c.action_item_capture_enabled,
+                digest_enabled: c.digest_enabled,
+                digest_hour: c.digest_hour,
+                digest_channel_id: c.digest_channel_id.clone(),
+                last_digest_date: c.last_digest_date.clone(),
+                connected_at: Some(c.connected_at),
+            },
+            None => Self {
+                connected: false,
+                team_name: None,
+                action_item_capture_enabled: false,
+                digest_enabled: false,
+                digest_hour: DEFAULT_SLACK_DIGEST_HOUR,
+                digest_channel_id: None,
+                last_digest_date: None,
+                connected_at: None,
+            },
+        }
+    }
+}
+
+/// Slash command payload (application/x-www-form-urlencoded)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackCommandPayload {
+    pub team_id: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub channel_id: Option<String>,
+}
+
+/// Events API envelope
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackEventEnvelope {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Set for url_verification
+    #[serde(default)]
+    pub challenge: Option<String>,
+    #[serde(default)]
+    pub team_id: Option<String>,
+    #[serde(default)]
+    pub event: Option<SlackMessageEvent>,
+}
+
+/// Inner event; only direct messages to the bot are handled
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackMessageEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub channel_type: Option<String>,
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Present for edits, joins, etc.
+    #[serde(default)]
+    pub subtype: Option<String>,
+    /// Present when the message was sent by a bot (including ours)
+    #[serde(default)]
+    pub bot_id: Option<String>,
+}
@@ -0,0 +1,54 @@
+// Snooze models - Hide an action item or advice until a resurface time
+// Stored as `snoozed_until` on the item; list queries skip items still snoozed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest snooze, in days
+pub const MAX_SNOOZE_DAYS: i64 = 90;
+/// Most calendar blocks accepted with a smart snooze
+pub const MAX_SNOOZE_BUSY_BLOCKS: usize = 100;
+
+/// A busy block from the client's calendar
+#[derive(Debug, Clone, Deserialize)]
+pub struct BusyBlock {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Request to snooze: exactly one of `minutes`, `until` or `smart`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SnoozeRequest {
+    #[serde(default)]
+    pub minutes: Option<i64>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    /// Let the backend pick the time from the due date, calendar and focus schedule
+    #[serde(default)]
+    pub smart: bool,
+    /// Upcoming calendar events, so a smart snooze doesn't resurface mid-meeting
+    #[serde(default)]
+    pub busy: Vec<BusyBlock>,
+    /// IANA timezone for a smart snooze; defaults to the profile's timezone
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// What a snooze time was based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnoozeBasis {
+    /// The duration or time in the request
+    Requested,
+    /// Ahead of the item's due date
+    DueDate,
+    /// The next morning (no upcoming due date)
+    NextMorning,
+}
+
+/// Result of a snooze
+#[derive(Debug, Clone, Serialize)]
+pub struct SnoozeResponse {
+    pub snoozed_until: DateTime<Utc>,
+    pub basis: SnoozeBasis,
+}
@@ -0,0 +1,219 @@
+// Sync models - Delta responses for GET /v2/sync
+// Clients pass the previous response's `cursor` as `since` to fetch only what changed.
+// Changes are read in (updated_at, document ID) order; a truncated response also carries
+// `cursor_id`, passed back as `after_id` to continue right after the last document sent.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Query for GET /v2/sync
+#[derive(Debug, Deserialize)]
+pub struct SyncQuery {
+    /// Cursor from the previous sync; omit for a full sync
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Include full documents alongside the IDs
+    #[serde(default)]
+    pub include_bodies: bool,
+    /// Max documents read per collection
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `cursor_id` from the previous truncated sync; resumes after that document at `since`
+    #[serde(default)]
+    pub after_id: Option<String>,
+}
+
+/// Where a document falls in sync order: by `updated_at` (`deleted_at` for
+/// deletions), then document ID
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SyncPosition {
+    pub updated_at: DateTime<Utc>,
+    pub id: String,
+}
+
+/// Changes read from one collection in sync order
+#[derive(Debug)]
+pub struct SyncPage<T> {
+    pub docs: Vec<(SyncPosition, T)>,
+    /// Position of the last document read when the read limit was hit (more may follow)
+    pub end: Option<SyncPosition>,
+}
+
+impl<T> SyncPage<T> {
+    /// Keep the documents up to `boundary`, the earliest `end` across every page of the
+    /// sync, so the next sync can resume from one position for all collections.
+    /// Also returns whether this collection has changes left for the next sync.
+    pub fn take_until(self, boundary: Option<&SyncPosition>) -> (Vec<T>, bool) {
+        let mut remaining = self.end.is_some();
+        let docs = self
+            .docs
+            .into_iter()
+            .filter_map(|(position, doc)| match boundary {
+                Some(boundary) if position > *boundary => {
+                    remaining = true;
+                    None
+                }
+                _ => Some(doc),
+            })
+            .collect();
+        (docs, remaining)
+    }
+}
+
+/// Changes to one collection since the cursor
+#[derive(Debug, Serialize)]
+pub struct CollectionDelta<T> {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Changed documents (only with include_bodies)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<T>>,
+    /// More changes remain; sync again with the returned `cursor` and `cursor_id`
+    pub truncated: bool,
+}
+
+impl<T> CollectionDelta<T> {
+    /// Sort changed documents into created/updated/deleted. `key` returns
+    /// (id, created_at, soft_deleted); `tombstones` are IDs of hard-deleted documents.
+    pub fn build<F>(
+        docs: Vec<T>,
+        tombstones: Vec<String>,
+        since: Option<DateTime<Utc>>,
+        include_bodies: bool,
+        truncated: bool,
+        key: F,
+    ) -> Self
+    where
+        F: Fn(&T) -> (String, DateTime<Utc>, bool),
+    {
+        let mut delta = CollectionDelta {
+            created: Vec::new(),
+            updated: Vec::new(),
+            deleted: tombstones,
+            items: None,
+            truncated,
+        };
+
+        let mut items = Vec::new();
+        for doc in docs {
+            let (id, created_at, soft_deleted) = key(&doc);
+            if soft_deleted {
+                // A full sync has nothing to delete locally
+                if since.is_some() {
+                    delta.deleted.push(id);
+                }
+                continue;
+            }
+            if since.is_none_or(|since| created_at >= since) {
+                delta.created.push(id);
+            } else {
+                delta.updated.push(id);
+            }
+            items.push(doc);
+        }
+        if include_bodies {
+            delta.items = Some(items);
+        }
+        delta
+    }
+}
+
+/// Whether the user's settings changed since the cursor
+#[derive(Debug, Serialize)]
+pub struct SettingsDelta {
+    pub changed: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Response for GET /v2/sync
+#[derive(Debug, Serialize)]
+pub struct SyncResponse<C, M, A, F> {
+    /// Pass as `since` on the next sync
+    pub cursor: DateTime<Utc>,
+    /// Set when the response was truncated; pass as `after_id` with `since = cursor`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_id: Option<String>,
+    /// The cursor is older than deletion history; discard local state and sync without `since`
+    pub full_resync_required: bool,
+    pub conversations: CollectionDelta<C>,
+    pub memories: CollectionDelta<M>,
+    pub action_items: CollectionDelta<A>,
+    pub folders: CollectionDelta<F>,
+    pub settings: SettingsDelta,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_delta_classification() {
+        let since = Utc::now() - Duration::hours(1);
+        let docs = vec![
+            ("new", since + Duration::minutes(5), false),
+            ("old", since - Duration::days(1), false),
+            ("gone", since - Duration::days(2), true),
+        ];
+        let delta = CollectionDelta::build(
+            docs,
+            vec!["hard".to_string()],
+            Some(since),
+            true,
+            false,
+            |d| (d.0.to_string(), d.1, d.2),
+        );
+        assert_eq!(delta.created, vec!["new"]);
+        assert_eq!(delta.updated, vec!["old"]);
+        assert_eq!(delta.deleted, vec!["hard", "gone"]);
+        assert_eq!(delta.items.map(|i| i.len()), Some(2));
+        assert!(!delta.truncated);
+    }
+
+    #[test]
+    fn test_full_sync_skips_deleted() {
+        let now = Utc::now();
+        let docs = vec![("a", now, false), ("b", now, true)];
+        let delta = CollectionDelta::build(docs, Vec::new(), None, false, true, |d| {
+            (d.0.to_string(), d.1, d.2)
+        });
+        assert_eq!(delta.created, vec!["a"]);
+        assert!(delta.deleted.is_empty());
+        assert!(delta.items.is_none());
+        assert!(delta.truncated);
+    }
+
+    fn position(minutes: i64, id: &str) -> SyncPosition {
+        let base = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        SyncPosition { updated_at: base + Duration::minutes(minutes), id: id.to_string() }
+    }
+
+    fn page(docs: &[(i64, &'static str)], full: bool) -> SyncPage<&'static str> {
+        let docs: Vec<_> = docs.iter().map(|&(m, id)| (position(m, id), id)).collect();
+        let end = if full { docs.last().map(|(p, _)| p.clone()) } else { None };
+        SyncPage { docs, end }
+    }
+
+    #[test]
+    fn test_pages_stop_at_the_earliest_boundary() {
+        // Conversations hit the limit at minute 2; memories go on to minute 5
+        let conversations = page(&[(1, "c1"), (2, "c2")], true);
+        let memories = page(&[(1, "m1"), (2, "a-tie"), (2, "z-tie"), (5, "m5")], false);
+        let boundary = [&conversations.end, &memories.end].into_iter().flatten().min().cloned();
+        assert_eq!(boundary, Some(position(2, "c2")));
+
+        let (conversations, more_conversations) = conversations.take_until(boundary.as_ref());
+        let (memories, more_memories) = memories.take_until(boundary.as_ref());
+        assert_eq!(conversations, vec!["c1", "c2"]);
+        assert!(more_conversations);
+        // Same timestamp as the boundary but a later ID: left for the next sync
+        assert_eq!(memories, vec!["m1", "a-tie"]);
+        assert!(more_memories);
+
+        // The next sync resumes strictly after the boundary and finishes
+        let next = page(&[(2, "z-tie"), (5, "m5")], false);
+        assert!(next.docs.iter().all(|(p, _)| *p > position(2, "c2")));
+        assert_eq!(next.take_until(None), (vec!["z-tie", "m5"], false));
+    }
+}
@@ -0,0 +1,85 @@
+// Timeline models - One chronological feed of a day for the Swift "Today" view
+// Assembled on request from conversations, focus sessions, advice and action items.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AdviceCategory, Category, FocusStatus};
+
+/// Query params for GET /v1/timeline
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimelineQuery {
+    /// Calendar day in the user's timezone; the last 24 hours when omitted
+    pub date: Option<NaiveDate>,
+    /// IANA timezone; defaults to the profile's, then UTC
+    pub timezone: Option<String>,
+}
+
+/// What happened, by kind
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineItem {
+    Conversation {
+        id: String,
+        title: String,
+        overview: String,
+        emoji: String,
+        category: Category,
+        finished_at: DateTime<Utc>,
+    },
+    FocusSession {
+        id: String,
+        status: FocusStatus,
+        app_or_site: String,
+        description: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duration_seconds: Option<i64>,
+    },
+    Advice {
+        id: String,
+        content: String,
+        category: AdviceCategory,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_app: Option<String>,
+    },
+    ActionItemCreated {
+        id: String,
+        description: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        conversation_id: Option<String>,
+    },
+    ActionItemCompleted {
+        id: String,
+        description: String,
+    },
+}
+
+/// One feed entry; `at` is when it started or happened
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub item: TimelineItem,
+}
+
+/// Entries per kind, for the view's header
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TimelineCounts {
+    pub conversations: usize,
+    pub focus_sessions: usize,
+    pub advice: usize,
+    pub action_items_created: usize,
+    pub action_items_completed: usize,
+}
+
+/// Response for GET /v1/timeline, oldest entry first
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineResponse {
+    /// The requested day; None for the rolling last 24 hours
+    pub date: Option<NaiveDate>,
+    pub timezone: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub counts: TimelineCounts,
+    pub entries: Vec<TimelineEntry>,
+}
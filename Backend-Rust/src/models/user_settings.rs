@@ -30,6 +30,133 @@ pub struct UpdateDailySummaryRequest {
     pub hour: Option<i32>,
 }
 
+/// Daily rollover of overdue action items (opt-in)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItemRolloverSettings {
+    /// Whether overdue, incomplete action items are moved to today each morning
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local hour (0-23) after which the rollover runs
+    #[serde(default = "default_rollover_hour")]
+    pub hour: i32,
+    /// Local date (YYYY-MM-DD) of the last rollover run
+    #[serde(default)]
+    pub last_rolled_over_on: Option<String>,
+}
+
+impl Default for ActionItemRolloverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: default_rollover_hour(),
+            last_rolled_over_on: None,
+        }
+    }
+}
+
+fn default_rollover_hour() -> i32 {
+    6 // 6 AM
+}
+
+/// Request to update action item rollover settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateActionItemRolloverRequest {
+    pub enabled: Option<bool>,
+    pub hour: Option<i32>,
+}
+
+/// Automatic weekly review, generated on Sunday evening in the user's timezone (opt-in)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyReviewSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local hour (0-23) on Sunday after which the review is generated
+    #[serde(default = "default_weekly_review_hour")]
+    pub hour: i32,
+    /// Monday (YYYY-MM-DD) of the last week reviewed by the schedule
+    #[serde(default)]
+    pub last_reviewed_week: Option<String>,
+}
+
+impl Default for WeeklyReviewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: default_weekly_review_hour(),
+            last_reviewed_week: None,
+        }
+    }
+}
+
+fn default_weekly_review_hour() -> i32 {
+    18 // 6 PM
+}
+
+/// Request to update weekly review settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateWeeklyReviewSettingsRequest {
+    pub enabled: Option<bool>,
+    pub hour: Option<i32>,
+}
+
+/// Conversation retention policy. A value of None (or 0 in requests) disables that step.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetentionPolicy {
+    /// Move conversations older than this many days into the Archive folder
+    #[serde(default)]
+    pub archive_after_days: Option<i32>,
+    /// Permanently delete conversations older than this many days
+    #[serde(default)]
+    pub delete_after_days: Option<i32>,
+    /// Remove raw transcripts (keeping summaries) of conversations older than this many days
+    #[serde(default)]
+    pub transcript_delete_after_days: Option<i32>,
+    /// UTC date (YYYY-MM-DD) the policy was last enforced
+    #[serde(default)]
+    pub last_enforced_on: Option<String>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.archive_after_days.is_some()
+            || self.delete_after_days.is_some()
+            || self.transcript_delete_after_days.is_some()
+    }
+}
+
+/// Request to update the retention policy (0 disables a step)
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateRetentionPolicyRequest {
+    pub archive_after_days: Option<i32>,
+    pub delete_after_days: Option<i32>,
+    pub transcript_delete_after_days: Option<i32>,
+    /// Required when transcript deletion is turned on or shortened, since it can't be undone
+    #[serde(default)]
+    pub confirm_transcript_deletion: bool,
+}
+
+/// Conversation affected by the retention policy
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionCandidate {
+    pub id: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Dry run of the retention policy: what the next enforcement would do
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPreview {
+    pub archive_after_days: Option<i32>,
+    pub delete_after_days: Option<i32>,
+    pub transcript_delete_after_days: Option<i32>,
+    pub archive: Vec<RetentionCandidate>,
+    pub delete: Vec<RetentionCandidate>,
+    /// Conversations whose transcript would be removed (summaries are kept)
+    pub strip_transcripts: Vec<RetentionCandidate>,
+    /// True when more conversations qualify than a single run processes
+    pub truncated: bool,
+}
+
 /// Transcription preferences
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TranscriptionPreferences {
@@ -106,6 +233,66 @@ pub struct UpdateNotificationSettingsRequest {
     pub frequency: Option<i32>,
 }
 
+/// How eagerly short or trivial recordings are discarded automatically
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoDiscardLevel {
+    Off,
+    /// Only obvious accidental recordings
+    #[default]
+    Conservative,
+    /// Also short snippets with little substance
+    Aggressive,
+}
+
+impl AutoDiscardLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoDiscardLevel::Off => "off",
+            AutoDiscardLevel::Conservative => "conservative",
+            AutoDiscardLevel::Aggressive => "aggressive",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<AutoDiscardLevel> {
+        match value {
+            "off" => Some(AutoDiscardLevel::Off),
+            "conservative" => Some(AutoDiscardLevel::Conservative),
+            "aggressive" => Some(AutoDiscardLevel::Aggressive),
+            _ => None,
+        }
+    }
+}
+
+/// User-defined instructions for how conversations are summarized
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProcessingPromptSettings {
+    /// Custom instructions appended to the summary prompt (empty = none)
+    #[serde(default)]
+    pub prompt: String,
+    /// Maximum allowed length of `prompt` in characters
+    #[serde(default)]
+    pub max_length: usize,
+    /// Keep emoji out of generated conversation titles
+    #[serde(default)]
+    pub plain_titles: bool,
+    /// How eagerly accidental recordings are discarded
+    #[serde(default)]
+    pub auto_discard: AutoDiscardLevel,
+}
+
+/// Request to update the custom processing prompt (empty string clears it).
+/// Omitted fields are left unchanged.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateProcessingPromptRequest {
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub plain_titles: Option<bool>,
+    #[serde(default)]
+    pub auto_discard: Option<AutoDiscardLevel>,
+}
+
 /// User profile from Firestore
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
@@ -233,6 +420,11 @@ pub struct MemorySettingsData {
     pub min_confidence: Option<f64>,
     pub notifications_enabled: Option<bool>,
     pub excluded_apps: Option<Vec<String>>,
+    /// Topics never to memorize (e.g. "health", "finances"); applied to conversation
+    /// memory extraction on the server
+    pub blocked_topics: Option<Vec<String>>,
+    /// Approve memories still pending review after this many days (0 = never)
+    pub auto_approve_after_days: Option<i32>,
 }
 
 /// All assistant settings (response and request — all fields optional for partial updates)
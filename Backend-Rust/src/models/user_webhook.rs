@@ -0,0 +1,57 @@
+// User webhook models - Developer automation endpoints registered by the user
+// Path: users/{uid}/webhooks/{webhook_id}
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Fired when an action item is marked completed
+pub const ACTION_ITEM_COMPLETED_EVENT: &str = "action_item.completed";
+
+/// Events a user webhook can subscribe to
+pub const USER_WEBHOOK_EVENTS: &[&str] = &[ACTION_ITEM_COMPLETED_EVENT];
+
+/// Max webhooks a user can register
+pub const MAX_USER_WEBHOOKS: usize = 10;
+
+/// A user-registered webhook as stored in Firestore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserWebhook {
+    pub id: String,
+    pub url: String,
+    /// Subscribed event names (see USER_WEBHOOK_EVENTS)
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// HMAC-SHA256 signing secret; only returned when the webhook is created
+    #[serde(default, skip_serializing)]
+    pub secret: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl UserWebhook {
+    /// Whether this webhook should receive the given event
+    pub fn subscribes_to(&self, event: &str) -> bool {
+        self.enabled && self.events.iter().any(|e| e == event)
+    }
+}
+
+/// Request to register a webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateUserWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+/// Response for a newly registered webhook, including its signing secret
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateUserWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: UserWebhook,
+    pub secret: String,
+}
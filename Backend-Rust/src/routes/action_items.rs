@@ -1,16 +1,30 @@
 // Action Items routes
-// Endpoints: GET /v1/action-items, PATCH/DELETE /v1/action-items/{id}
+// Endpoints: GET /v1/action-items, GET /v1/action-items/export.csv, PATCH/DELETE /v1/action-items/{id},
+// GET/POST /v1/action-items/{id}/notes, DELETE /v1/action-items/{id}/notes/{note_id},
+// POST/DELETE /v1/action-items/{id}/snooze, GET /v1/action-items/next
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use futures::StreamExt;
 use serde::Deserialize;
 
 use crate::auth::AuthUser;
-use crate::models::{AcceptTasksRequest, AcceptTasksResponse, ActionItemDB, ActionItemsListResponse, ActionItemStatusResponse, BatchCreateActionItemsRequest, BatchUpdateScoresRequest, BatchUpdateSortOrdersRequest, CreateActionItemRequest, ShareTasksRequest, ShareTasksResponse, SharedTaskInfo, SharedTasksResponse, UpdateActionItemRequest};
+use crate::llm_limit::with_llm_limit;
+use crate::models::{is_same_version, AcceptTasksRequest, AcceptTasksResponse, ActionItemDB, ActionItemNote, ActionItemsListResponse, ActionItemStatusResponse, BatchCreateActionItemsRequest, BatchUpdateScoresRequest, BatchUpdateSortOrdersRequest, CreateActionItemNoteRequest, CreateActionItemRequest, ShareTasksRequest, ShareTasksResponse, SharedTaskInfo, SharedTasksResponse, UpdateActionItemRequest, MAX_ACTION_ITEM_NOTE_CHARS};
+use crate::models::{NextActionItemResponse, SnoozeRequest, SnoozeResponse};
+use crate::services::action_item_export::{self, CSV_HEADER};
+use crate::services::due_dates::{self, MAX_DUE_TEXT_CHARS};
+use crate::services::{next_action, snooze};
+use crate::services::events::AppEvent;
+use crate::services::firestore::{FirestoreError, Precondition};
 use crate::AppState;
 
 #[derive(Deserialize)]
@@ -47,12 +61,37 @@ pub struct GetActionItemsQuery {
     pub sort_by: Option<String>,
     /// If true, return ONLY soft-deleted items. Default: exclude deleted items.
     pub deleted: Option<bool>,
+    /// Include items that are still snoozed. Default: hide them until they resurface.
+    #[serde(default)]
+    pub include_snoozed: bool,
 }
 
 fn default_limit() -> usize {
     100
 }
 
+/// Filters for the CSV export: the list filters (without paging) plus category
+#[derive(Deserialize)]
+pub struct ExportActionItemsQuery {
+    pub completed: Option<bool>,
+    pub conversation_id: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub due_start_date: Option<String>,
+    pub due_end_date: Option<String>,
+    pub sort_by: Option<String>,
+    pub deleted: Option<bool>,
+    #[serde(default)]
+    pub include_snoozed: bool,
+    /// Only items in this category
+    pub category: Option<String>,
+}
+
+/// Approximate size of each chunk of the export body
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+/// Most action items in one export
+const MAX_EXPORT_ITEMS: usize = 20_000;
+
 /// POST /v1/action-items - Create a new action item
 async fn create_action_item(
     State(state): State<AppState>,
@@ -67,7 +106,7 @@ async fn create_action_item(
     );
 
     match state
-        .firestore
+        .storage
         .create_action_item(
             &user.uid,
             &request.description,
@@ -86,7 +125,7 @@ async fn create_action_item(
         Ok(item) => Ok(Json(item)),
         Err(e) => {
             tracing::error!("Failed to create action item: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -112,7 +151,7 @@ async fn get_action_items(
     let fetch_limit = query.limit + 1;
 
     match state
-        .firestore
+        .storage
         .get_action_items(
             &user.uid,
             fetch_limit,
@@ -125,6 +164,7 @@ async fn get_action_items(
             query.due_end_date.as_deref(),
             query.sort_by.as_deref(),
             query.deleted,
+            query.include_snoozed,
         )
         .await
     {
@@ -145,6 +185,73 @@ async fn get_action_items(
     }
 }
 
+/// GET /v1/action-items/export.csv - Stream the filtered action items as CSV.
+/// Items are streamed from Firestore page by page as the response is written.
+async fn export_action_items_csv(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<ExportActionItemsQuery>,
+) -> Response {
+    tracing::info!(
+        "Exporting action items for user {} with completed={:?}, category={:?}, deleted={:?}",
+        user.uid,
+        query.completed,
+        query.category,
+        query.deleted
+    );
+
+    let header_chunk = futures::stream::once(async { Ok::<_, std::io::Error>(CSV_HEADER.to_string()) });
+    let firestore = state.firestore.clone();
+    let rows = async_stream::stream! {
+        let mut items = firestore
+            .stream_action_items(
+                &user.uid,
+                query.completed,
+                query.conversation_id.as_deref(),
+                query.start_date.as_deref(),
+                query.end_date.as_deref(),
+                query.due_start_date.as_deref(),
+                query.due_end_date.as_deref(),
+                query.sort_by.as_deref(),
+                query.deleted,
+                query.include_snoozed,
+            )
+            .take(MAX_EXPORT_ITEMS);
+
+        let mut chunk = String::new();
+        while let Some(item) = items.next().await {
+            match item {
+                Ok(item) => {
+                    if query.category.as_deref().is_none_or(|c| item.category.as_deref() == Some(c)) {
+                        chunk.push_str(&action_item_export::csv_row(&item));
+                    }
+                    if chunk.len() >= EXPORT_CHUNK_BYTES {
+                        yield Ok(std::mem::take(&mut chunk));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to read action items for export: {}", e);
+                    yield Err(std::io::Error::other(e.to_string()));
+                    return;
+                }
+            }
+        }
+        if !chunk.is_empty() {
+            yield Ok(chunk);
+        }
+    };
+
+    let filename = format!("action-items-{}.csv", Utc::now().format("%Y-%m-%d"));
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(header_chunk.chain(rows)),
+    )
+        .into_response()
+}
+
 /// GET /v1/action-items/{id} - Get a single action item
 async fn get_action_item_by_id(
     State(state): State<AppState>,
@@ -153,33 +260,141 @@ async fn get_action_item_by_id(
 ) -> Result<Json<ActionItemDB>, StatusCode> {
     tracing::info!("Getting action item {} for user {}", item_id, user.uid);
 
-    match state.firestore.get_action_item_by_id(&user.uid, &item_id).await {
+    match state.storage.get_action_item_by_id(&user.uid, &item_id).await {
         Ok(Some(item)) => Ok(Json(item)),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to get action item: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UpdateActionItemQuery {
+    /// Apply the update even if the item changed since `expected_updated_at`
+    #[serde(default)]
+    force: bool,
+}
+
+/// 409 carrying the server's current copy, so the client can merge and retry
+fn conflict_response(current: ActionItemDB) -> Response {
+    (StatusCode::CONFLICT, Json(current)).into_response()
+}
+
+/// Resolve a `due_text` phrase in the request's or the profile's timezone: common
+/// phrases directly, anything else with the LLM. 422 if it can't be understood.
+async fn resolve_due_text(
+    state: &AppState,
+    uid: &str,
+    text: &str,
+    timezone: Option<&str>,
+) -> Result<DateTime<Utc>, Response> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > MAX_DUE_TEXT_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("due_text must be 1-{} characters", MAX_DUE_TEXT_CHARS),
+        )
+            .into_response());
+    }
+
+    let timezone = match timezone {
+        Some(tz) => Some(tz.to_string()),
+        None => state.firestore.get_user_profile(uid).await.ok().and_then(|p| p.time_zone),
+    };
+    let tz: Tz = timezone.and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC);
+    let now = Utc::now().with_timezone(&tz);
+
+    if let Some(due_at) = due_dates::parse_due_text(text, now) {
+        return Ok(due_at);
+    }
+    let llm = state
+        .llm_client(uid)
+        .await
+        .map_err(|e| <(StatusCode, String)>::from(e).into_response())?;
+    if let Some(llm) = llm {
+        match llm.resolve_due_text(text, &now.to_rfc3339(), tz.name()).await {
+            Ok(Some(due_at)) => return Ok(due_at),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to resolve due text with the LLM: {}", e),
         }
     }
+    Err((
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("Could not understand \"{}\" as a due date", text),
+    )
+        .into_response())
 }
 
 /// PATCH /v1/action-items/{id} - Update an action item
+///
+/// With `expected_updated_at`, the write only applies if nobody else changed the
+/// item since; otherwise 409 with the current copy. `?force=true` skips the check.
+/// `due_text` sets the due date from a phrase; the updated item carries the resolved time.
 async fn update_action_item(
     State(state): State<AppState>,
     user: AuthUser,
     Path(item_id): Path<String>,
+    Query(query): Query<UpdateActionItemQuery>,
     Json(request): Json<UpdateActionItemRequest>,
-) -> Result<Json<ActionItemDB>, StatusCode> {
+) -> Result<Json<ActionItemDB>, Response> {
     tracing::info!("Updating action item {} for user {}", item_id, user.uid);
 
+    let due_at = match (&request.due_text, request.due_at) {
+        (Some(_), Some(_)) => {
+            return Err((StatusCode::BAD_REQUEST, "Send either due_at or due_text, not both").into_response())
+        }
+        (Some(text), None) => {
+            Some(resolve_due_text(&state, &user.uid, text, request.timezone.as_deref()).await?)
+        }
+        (None, due_at) => due_at,
+    };
+
+    let expected_updated_at = request.expected_updated_at.filter(|_| !query.force);
+    let current = if expected_updated_at.is_some() || request.completed == Some(true) {
+        match state.storage.get_action_item_versioned(&user.uid, &item_id).await {
+            Ok(current) => current,
+            Err(e) if expected_updated_at.is_some() => {
+                tracing::error!("Failed to load action item {} before update: {}", item_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load action item {} before update: {}", item_id, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Only a pending -> completed transition fires action_item.completed
+    let was_completed = match (&current, request.completed) {
+        (Some((item, _)), Some(true)) => item.completed,
+        _ => true,
+    };
+
+    // Pin the write to the version that was checked, so a concurrent edit in
+    // between still conflicts
+    let precondition = match (expected_updated_at, current) {
+        (None, _) => None,
+        (Some(_), None) => return Err(StatusCode::NOT_FOUND.into_response()),
+        (Some(expected), Some((item, update_time))) => {
+            if !is_same_version(item.updated_at, expected) {
+                return Err(conflict_response(item));
+            }
+            Some(Precondition::UpdateTime(update_time))
+        }
+    };
+
     match state
-        .firestore
+        .storage
         .update_action_item(
             &user.uid,
             &item_id,
             request.completed,
             request.description.as_deref(),
-            request.due_at,
+            due_at,
             request.priority.as_deref(),
             request.category.as_deref(),
             request.goal_id.as_deref(),
@@ -187,13 +402,33 @@ async fn update_action_item(
             request.sort_order,
             request.indent_level,
             request.recurrence_rule.as_deref(),
+            request.rollover_excluded,
+            precondition.as_ref(),
         )
         .await
     {
-        Ok(item) => Ok(Json(item)),
+        Ok(item) => {
+            if !was_completed && item.completed {
+                state.events.publish(AppEvent::ActionItemCompleted {
+                    uid: user.uid.clone(),
+                    item: std::sync::Arc::new(item.clone()),
+                });
+            }
+            Ok(Json(item))
+        }
+        Err(FirestoreError::FailedPrecondition(_)) => {
+            match state.storage.get_action_item_by_id(&user.uid, &item_id).await {
+                Ok(Some(item)) => Err(conflict_response(item)),
+                Ok(None) => Err(StatusCode::NOT_FOUND.into_response()),
+                Err(e) => {
+                    tracing::error!("Failed to load action item {} after conflict: {}", item_id, e);
+                    Err(StatusCode::CONFLICT.into_response())
+                }
+            }
+        }
         Err(e) => {
             tracing::error!("Failed to update action item: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
 }
@@ -214,7 +449,7 @@ async fn batch_create_action_items(
 
     for item_request in request.items {
         match state
-            .firestore
+            .storage
             .create_action_item(
                 &user.uid,
                 &item_request.description,
@@ -265,7 +500,7 @@ async fn batch_update_sort_orders(
         })),
         Err(e) => {
             tracing::error!("Failed to batch update sort orders: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -278,13 +513,13 @@ async fn delete_action_item(
 ) -> Result<Json<ActionItemStatusResponse>, StatusCode> {
     tracing::info!("Deleting action item {} for user {}", item_id, user.uid);
 
-    match state.firestore.delete_action_item(&user.uid, &item_id).await {
+    match state.storage.delete_action_item(&user.uid, &item_id).await {
         Ok(()) => Ok(Json(ActionItemStatusResponse {
             status: "ok".to_string(),
         })),
         Err(e) => {
             tracing::error!("Failed to delete action item: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -321,7 +556,7 @@ async fn soft_delete_action_item(
         Ok(item) => Ok(Json(item)),
         Err(e) => {
             tracing::error!("Failed to soft-delete action item: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -350,7 +585,7 @@ async fn batch_update_scores(
         })),
         Err(e) => {
             tracing::error!("Failed to batch update scores: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -369,7 +604,7 @@ async fn share_tasks(
 
     // Verify ownership of each task
     for id in &request.task_ids {
-        match state.firestore.get_action_item_by_id(&user.uid, id).await {
+        match state.storage.get_action_item_by_id(&user.uid, id).await {
             Ok(Some(_)) => {}
             Ok(None) => {
                 tracing::warn!("Task {} not found for user {}", id, user.uid);
@@ -377,7 +612,7 @@ async fn share_tasks(
             }
             Err(e) => {
                 tracing::error!("Failed to verify task ownership: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(e.http_status());
             }
         }
     }
@@ -423,7 +658,7 @@ async fn get_shared_tasks(
     // Fetch each task from sender's Firestore (only expose description + due_at)
     let mut tasks = Vec::new();
     for id in &task_ids {
-        match state.firestore.get_action_item_by_id(&sender_uid, id).await {
+        match state.storage.get_action_item_by_id(&sender_uid, id).await {
             Ok(Some(item)) => {
                 tasks.push(SharedTaskInfo {
                     description: item.description,
@@ -479,7 +714,7 @@ async fn accept_tasks(
     // Create tasks in recipient's Firestore
     let mut created_ids = Vec::new();
     for id in &task_ids {
-        match state.firestore.get_action_item_by_id(&sender_uid, id).await {
+        match state.storage.get_action_item_by_id(&sender_uid, id).await {
             Ok(Some(item)) => {
                 let metadata = serde_json::json!({
                     "shared_from": sender_uid,
@@ -488,7 +723,7 @@ async fn accept_tasks(
                 .to_string();
 
                 match state
-                    .firestore
+                    .storage
                     .create_action_item(
                         &user.uid,
                         &item.description,
@@ -528,20 +763,206 @@ async fn accept_tasks(
     }))
 }
 
+// ============================================================================
+// Notes
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct GetActionItemNotesQuery {
+    #[serde(default = "default_notes_limit")]
+    pub limit: usize,
+}
+
+fn default_notes_limit() -> usize {
+    50
+}
+
+/// 404 unless the action item exists
+async fn require_action_item(state: &AppState, uid: &str, item_id: &str) -> Result<(), StatusCode> {
+    match state.storage.get_action_item_by_id(uid, item_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get action item: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/action-items/{id}/notes - Progress notes, newest first
+async fn get_action_item_notes(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(item_id): Path<String>,
+    Query(query): Query<GetActionItemNotesQuery>,
+) -> Result<Json<Vec<ActionItemNote>>, StatusCode> {
+    require_action_item(&state, &user.uid, &item_id).await?;
+
+    match state
+        .firestore
+        .get_action_item_notes(&user.uid, &item_id, query.limit.min(500))
+        .await
+    {
+        Ok(notes) => Ok(Json(notes)),
+        Err(e) => {
+            tracing::error!("Failed to get action item notes: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/action-items/{id}/notes - Add a progress note
+async fn create_action_item_note(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(item_id): Path<String>,
+    Json(request): Json<CreateActionItemNoteRequest>,
+) -> Result<Json<ActionItemNote>, (StatusCode, String)> {
+    let content = request.content.trim();
+    if content.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Note content is required".to_string()));
+    }
+    if content.chars().count() > MAX_ACTION_ITEM_NOTE_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Notes are limited to {} characters", MAX_ACTION_ITEM_NOTE_CHARS),
+        ));
+    }
+    require_action_item(&state, &user.uid, &item_id)
+        .await
+        .map_err(|status| (status, "Action item not found".to_string()))?;
+
+    match state
+        .firestore
+        .create_action_item_note(&user.uid, &item_id, content)
+        .await
+    {
+        Ok(note) => Ok(Json(note)),
+        Err(e) => {
+            tracing::error!("Failed to create action item note: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// DELETE /v1/action-items/{id}/notes/{note_id} - Delete a progress note
+async fn delete_action_item_note(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((item_id, note_id)): Path<(String, String)>,
+) -> Result<Json<ActionItemStatusResponse>, StatusCode> {
+    require_action_item(&state, &user.uid, &item_id).await?;
+
+    match state
+        .firestore
+        .delete_action_item_note(&user.uid, &item_id, &note_id)
+        .await
+    {
+        Ok(()) => Ok(Json(ActionItemStatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to delete action item note: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/action-items/{id}/snooze - Hide the item from lists until a resurface time,
+/// given as `minutes`, `until`, or `smart` (picked from the due date, calendar and focus schedule)
+async fn snooze_action_item(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(item_id): Path<String>,
+    Json(request): Json<SnoozeRequest>,
+) -> Result<Json<SnoozeResponse>, (StatusCode, String)> {
+    let item = state
+        .storage
+        .get_action_item_by_id(&user.uid, &item_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get action item: {}", e);
+            (e.http_status(), "Failed to get action item".to_string())
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Action item not found".to_string()))?;
+
+    let (snoozed_until, basis) = snooze::resolve(&state.firestore, &user.uid, &request, item.due_at)
+        .await
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    if let Err(e) = state
+        .firestore
+        .snooze_action_item(&user.uid, &item_id, Some(snoozed_until))
+        .await
+    {
+        tracing::error!("Failed to snooze action item: {}", e);
+        return Err((e.http_status(), "Failed to snooze action item".to_string()));
+    }
+    Ok(Json(SnoozeResponse { snoozed_until, basis }))
+}
+
+/// DELETE /v1/action-items/{id}/snooze - Resurface the item now
+async fn unsnooze_action_item(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(item_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    match state.firestore.snooze_action_item(&user.uid, &item_id, None).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(FirestoreError::NotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to unsnooze action item: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/action-items/next - The one open task to do now, with the reasoning for the pick
+async fn get_next_action_item(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<NextActionItemResponse>, (StatusCode, String)> {
+    let screen = state.screen_context.latest(&user.uid).await;
+    match next_action::recommend_next(&state.firestore, &user.uid, screen).await {
+        Ok(next) => Ok(Json(next)),
+        Err(e) => {
+            tracing::error!("Failed to pick next action item: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to pick next action item".to_string()))
+        }
+    }
+}
+
 pub fn action_items_routes() -> Router<AppState> {
     Router::new()
         .route("/v1/action-items", get(get_action_items).post(create_action_item))
         .route("/v1/action-items/batch", axum::routing::post(batch_create_action_items).patch(batch_update_sort_orders))
+        .route("/v1/action-items/export.csv", get(export_action_items_csv))
+        .route("/v1/action-items/next", get(get_next_action_item))
         .route("/v1/action-items/batch-scores", axum::routing::patch(batch_update_scores))
         .route("/v1/action-items/share", axum::routing::post(share_tasks))
         .route("/v1/action-items/shared/:token", get(get_shared_tasks))
         .route("/v1/action-items/accept", axum::routing::post(accept_tasks))
         .route(
             "/v1/action-items/:id",
-            get(get_action_item_by_id).patch(update_action_item).delete(delete_action_item),
+            get(get_action_item_by_id)
+                .delete(delete_action_item)
+                // `due_text` may be resolved by the LLM
+                .merge(with_llm_limit(axum::routing::patch(update_action_item))),
         )
         .route(
             "/v1/action-items/:id/soft-delete",
             axum::routing::post(soft_delete_action_item),
         )
+        .route(
+            "/v1/action-items/:id/snooze",
+            axum::routing::post(snooze_action_item).delete(unsnooze_action_item),
+        )
+        .route(
+            "/v1/action-items/:id/notes",
+            get(get_action_item_notes).post(create_action_item_note),
+        )
+        .route(
+            "/v1/action-items/:id/notes/:note_id",
+            axum::routing::delete(delete_action_item_note),
+        )
 }
@@ -0,0 +1,547 @@
+// Admin routes - Operational endpoints restricted to ADMIN_UIDS
+// Endpoints: GET /v1/admin/migrations, POST /v1/admin/migrations/:id/run,
+//            POST /v1/admin/apps/:app_id/approve, POST /v1/admin/apps/:app_id/reject,
+//            POST /v1/admin/apps/:app_id/moderate, PUT /v1/admin/apps/:app_id/moderation-override,
+//            GET /v1/admin/metrics, GET /v1/admin/llm-models, PUT /v1/admin/llm-models/:task,
+//            GET /v1/admin/llm-quality/:date, POST /v1/admin/impersonate,
+//            GET /v1/admin/impersonation-audit, GET /v1/admin/apps-home, PUT /v1/admin/apps-home,
+//            GET /v1/admin/llm-debug/:request_id
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthUser;
+use crate::client_info::ClientInfo;
+use crate::impersonation::{self, ImpersonationClaims};
+use crate::llm::TaskKind;
+use crate::job_queue::JobQueueStats;
+use crate::llm_limit::LlmLimiterStats;
+use crate::models::{
+    App, AppModeration, AppsHomeLayout, ImpersonateRequest, ImpersonateResponse, ImpersonationAuditEntry,
+    ImpersonationAuditQuery, LlmDebugLogResponse, MigrationInfo, MigrationRecord, MigrationStatus, QualityReport,
+    RunMigrationRequest, MAX_IMPERSONATION_AUDIT_ENTRIES, MAX_IMPERSONATION_REASON_CHARS,
+    MIN_IMPERSONATION_REASON_CHARS, MAX_LLM_DEBUG_ENTRIES, get_app_categories,
+};
+use crate::services::firestore::{carry_project_override, FirestoreError};
+use crate::services::{app_moderation, llm_quality};
+use crate::services::migrations::{self, RunOptions, MIGRATIONS};
+use crate::AppState;
+
+/// A running migration that hasn't reported progress for this long is treated as dead
+const STALE_MIGRATION_MINUTES: i64 = 10;
+/// Default scan limit for dry runs, which run inline
+const DEFAULT_DRY_RUN_LIMIT: u64 = 1000;
+
+/// Status response for app review operations
+#[derive(Serialize)]
+struct StatusResponse {
+    status: String,
+}
+
+/// Request to allow (or stop allowing) an app's flagged prompts to run
+#[derive(Deserialize)]
+struct ModerationOverrideRequest {
+    allow: bool,
+}
+
+/// Process-level load metrics
+#[derive(Serialize)]
+struct MetricsResponse {
+    llm: LlmLimiterStats,
+    jobs: JobQueueStats,
+}
+
+/// Effective model for one LLM task
+#[derive(Serialize)]
+struct TaskModelInfo {
+    task: TaskKind,
+    model: String,
+    /// From LLM_MODEL / LLM_MODEL_<TASK>
+    configured_model: String,
+    /// Admin override, if set
+    override_model: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LlmModelsResponse {
+    default_model: String,
+    tasks: Vec<TaskModelInfo>,
+}
+
+/// Body for PUT /v1/admin/llm-models/:task; null clears the override
+#[derive(Deserialize)]
+struct SetLlmModelRequest {
+    model: Option<String>,
+}
+
+fn require_admin(state: &AppState, user: &AuthUser) -> Result<(), (StatusCode, String)> {
+    if user.impersonated_by.is_some() {
+        return Err((StatusCode::FORBIDDEN, "Admin endpoints are unavailable while impersonating".to_string()));
+    }
+    if state.config.admin_uids.iter().any(|uid| uid == &user.uid) {
+        Ok(())
+    } else {
+        Err((StatusCode::FORBIDDEN, "Admin access required".to_string()))
+    }
+}
+
+/// GET /v1/admin/migrations - Registered migrations and their progress
+async fn list_migrations(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<MigrationInfo>>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+
+    let mut infos = Vec::with_capacity(MIGRATIONS.len());
+    for migration in MIGRATIONS {
+        let record = state
+            .firestore
+            .get_migration_record(migration.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get migration record: {}", e);
+                e.public_error()
+            })?;
+        infos.push(MigrationInfo {
+            id: migration.id.to_string(),
+            description: migration.description.to_string(),
+            collection: migration.collection_id.to_string(),
+            record,
+        });
+    }
+    Ok(Json(infos))
+}
+
+/// POST /v1/admin/migrations/:id/run - Start or resume a migration in the background.
+/// Dry runs execute inline and return the counts. Poll GET /v1/admin/migrations for progress.
+async fn run_migration(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(migration_id): Path<String>,
+    Json(request): Json<RunMigrationRequest>,
+) -> Result<(StatusCode, Json<MigrationRecord>), (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let migration = migrations::find_migration(&migration_id)
+        .ok_or((StatusCode::NOT_FOUND, "Unknown migration".to_string()))?;
+
+    if request.dry_run {
+        let options = RunOptions {
+            dry_run: true,
+            limit: Some(request.limit.unwrap_or(DEFAULT_DRY_RUN_LIMIT)),
+            restart: true,
+        };
+        let record = migrations::run_migration(&state.firestore, migration, options, |_| {})
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        return Ok((StatusCode::OK, Json(record)));
+    }
+
+    let existing = state
+        .firestore
+        .get_migration_record(migration.id)
+        .await?;
+    if let Some(record) = &existing {
+        let stale_before = Utc::now() - Duration::minutes(STALE_MIGRATION_MINUTES);
+        if record.status == MigrationStatus::Running && record.updated_at > stale_before {
+            return Err((StatusCode::CONFLICT, "Migration is already running".to_string()));
+        }
+        if record.status == MigrationStatus::Completed && !request.restart {
+            return Ok((StatusCode::OK, Json(record.clone())));
+        }
+    }
+
+    tracing::info!("Admin {} started migration {}", user.uid, migration.id);
+    let firestore = state.firestore.clone();
+    let options = RunOptions {
+        dry_run: false,
+        limit: request.limit,
+        restart: request.restart,
+    };
+    tokio::spawn(carry_project_override(async move {
+        if let Err(e) = migrations::run_migration(&firestore, migration, options, |_| {}).await {
+            tracing::error!("Migration {} stopped: {}", migration.id, e);
+        }
+    }));
+
+    let now = Utc::now();
+    let accepted = MigrationRecord {
+        id: migration.id.to_string(),
+        status: MigrationStatus::Running,
+        scanned: 0,
+        updated: 0,
+        cursor: None,
+        dry_run: false,
+        error: None,
+        started_at: now,
+        updated_at: now,
+        completed_at: None,
+    };
+    let record = match existing {
+        Some(r) if !request.restart => MigrationRecord {
+            status: MigrationStatus::Running,
+            error: None,
+            ..r
+        },
+        _ => accepted,
+    };
+    Ok((StatusCode::ACCEPTED, Json(record)))
+}
+
+/// Approve or reject an app; the marketplace cache is invalidated on success
+async fn set_app_approval(
+    state: &AppState,
+    user: &AuthUser,
+    app_id: &str,
+    approved: bool,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    require_admin(state, user)?;
+    let app = fetch_app(state, user, app_id).await?;
+    if approved && state.app_blocked(&app).await {
+        return Err((
+            StatusCode::CONFLICT,
+            "App is flagged by moderation; override the verdict before approving".to_string(),
+        ));
+    }
+
+    state
+        .firestore
+        .set_app_approved(app_id, approved)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update approval for app {}: {}", app_id, e);
+            e.public_error()
+        })?;
+    tracing::info!("Admin {} set app {} approved={}", user.uid, app_id, approved);
+    Ok(Json(StatusResponse {
+        status: "ok".to_string(),
+    }))
+}
+
+async fn fetch_app(state: &AppState, user: &AuthUser, app_id: &str) -> Result<App, (StatusCode, String)> {
+    state
+        .firestore
+        .get_app(&user.uid, app_id)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "App not found".to_string()))
+}
+
+async fn store_moderation(
+    state: &AppState,
+    app_id: &str,
+    moderation: AppModeration,
+) -> Result<Json<AppModeration>, (StatusCode, String)> {
+    state
+        .firestore
+        .set_app_moderation(app_id, &moderation)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to store moderation for app {}: {}", app_id, e);
+            e.public_error()
+        })?;
+    Ok(Json(moderation))
+}
+
+/// POST /v1/admin/apps/:app_id/moderate - Re-run moderation on the app's current prompts
+async fn moderate_app(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+) -> Result<Json<AppModeration>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let app = fetch_app(&state, &user, &app_id).await?;
+    let llm = state.server_llm_client().await;
+    let moderation = app_moderation::moderate(llm.as_ref(), &app).await;
+    tracing::info!("Admin {} re-moderated app {}: {}", user.uid, app_id, moderation.verdict.as_str());
+    store_moderation(&state, &app_id, moderation).await
+}
+
+/// PUT /v1/admin/apps/:app_id/moderation-override - Let a flagged app's prompts run
+/// (until they change) or revoke that
+async fn set_moderation_override(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+    Json(request): Json<ModerationOverrideRequest>,
+) -> Result<Json<AppModeration>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let app = fetch_app(&state, &user, &app_id).await?;
+    let llm = state.server_llm_client().await;
+    let mut moderation = app_moderation::ensure_moderated(&state.firestore, llm.as_ref(), &app).await;
+    moderation.admin_override = request.allow;
+    moderation.overridden_by = request.allow.then(|| user.uid.clone());
+    tracing::info!("Admin {} set moderation override for app {} to {}", user.uid, app_id, request.allow);
+    store_moderation(&state, &app_id, moderation).await
+}
+
+/// POST /v1/admin/apps/:app_id/approve - Publish an app to the marketplace
+async fn approve_app(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    set_app_approval(&state, &user, &app_id, true).await
+}
+
+/// POST /v1/admin/apps/:app_id/reject - Remove an app from the marketplace
+async fn reject_app(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    set_app_approval(&state, &user, &app_id, false).await
+}
+
+/// GET /v1/admin/metrics - In-flight and queued LLM requests and background jobs on this instance
+async fn get_metrics(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<MetricsResponse>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    Ok(Json(MetricsResponse {
+        llm: state.llm_limiter.stats(),
+        jobs: state.jobs.stats(),
+    }))
+}
+
+fn llm_models_response(state: &AppState, overrides: &HashMap<TaskKind, String>) -> LlmModelsResponse {
+    let configured = &state.config.llm_models;
+    let effective = configured.with_overrides(overrides);
+    LlmModelsResponse {
+        default_model: configured.default_model.clone(),
+        tasks: TaskKind::ALL
+            .into_iter()
+            .map(|task| TaskModelInfo {
+                task,
+                model: effective.model_for(task).to_string(),
+                configured_model: configured.model_for(task).to_string(),
+                override_model: overrides.get(&task).cloned(),
+            })
+            .collect(),
+    }
+}
+
+/// GET /v1/admin/llm-models - Model used for each LLM task
+async fn get_llm_models(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<LlmModelsResponse>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let overrides = state
+        .firestore
+        .get_llm_model_overrides()
+        .await?;
+    Ok(Json(llm_models_response(&state, &overrides)))
+}
+
+/// PUT /v1/admin/llm-models/:task - Override (or clear) the model for a task.
+/// Other instances pick up the change within a few minutes.
+async fn set_llm_model(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(task): Path<String>,
+    Json(request): Json<SetLlmModelRequest>,
+) -> Result<Json<LlmModelsResponse>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let task = TaskKind::parse(&task)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Unknown LLM task: {}", task)))?;
+    let model = request.model.as_deref().map(str::trim).filter(|m| !m.is_empty());
+
+    let overrides = state
+        .firestore
+        .set_llm_model_override(task, model)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to set model override for {}: {}", task.as_str(), e);
+            e.public_error()
+        })?;
+    tracing::info!("Admin {} set {} model override to {:?}", user.uid, task.as_str(), model);
+    Ok(Json(llm_models_response(&state, &overrides)))
+}
+
+#[derive(Deserialize)]
+struct QualityReportQuery {
+    /// Rebuild the report from the current ratings instead of returning the stored one
+    #[serde(default)]
+    refresh: bool,
+}
+
+/// GET /v1/admin/llm-quality/:date - Thumbs ratings given on a UTC day, by model,
+/// prompt version and route. Built on demand when the daily job hasn't stored it yet.
+async fn get_llm_quality_report(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(date): Path<String>,
+    Query(query): Query<QualityReportQuery>,
+) -> Result<Json<QualityReport>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        return Err((StatusCode::BAD_REQUEST, "date must be YYYY-MM-DD".to_string()));
+    }
+
+    if !query.refresh {
+        match state.firestore.get_quality_report(&date).await {
+            Ok(Some(report)) => return Ok(Json(report)),
+            Ok(None) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let report = llm_quality::generate_report(&state.firestore, &date).await.map_err(|e| {
+        tracing::error!("Failed to build LLM quality report for {}: {}", date, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build LLM quality report".to_string())
+    })?;
+    Ok(Json(report))
+}
+
+/// POST /v1/admin/impersonate - Mint a short-lived token for acting as a user
+async fn impersonate_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(request): Json<ImpersonateRequest>,
+) -> Result<Json<ImpersonateResponse>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let Some(secret) = state.config.impersonation_secret.as_deref() else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "Impersonation is not configured".to_string()));
+    };
+    let reason = request.reason.trim();
+    let reason_chars = reason.chars().count();
+    if !(MIN_IMPERSONATION_REASON_CHARS..=MAX_IMPERSONATION_REASON_CHARS).contains(&reason_chars) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "reason must be {}-{} characters",
+                MIN_IMPERSONATION_REASON_CHARS, MAX_IMPERSONATION_REASON_CHARS
+            ),
+        ));
+    }
+    if request.uid == user.uid {
+        return Err((StatusCode::BAD_REQUEST, "Cannot impersonate yourself".to_string()));
+    }
+    if let Err(e) = state.firestore.get_user_profile(&request.uid).await {
+        return Err(if let FirestoreError::NotFound(_) = e {
+            (StatusCode::NOT_FOUND, "User not found".to_string())
+        } else {
+            e.into()
+        });
+    }
+
+    let claims = ImpersonationClaims::new(&user.uid, &request.uid, reason, Utc::now());
+    let token = impersonation::mint_token(secret, &claims)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // The minting itself is the first entry of the session
+    impersonation::record_audit(
+        &state.firestore,
+        &claims.audit_entry("POST", "/v1/admin/impersonate", StatusCode::OK.as_u16(), client.ip_string()),
+    )
+    .await;
+    tracing::warn!("Admin {} started impersonating {} from {}: {}", user.uid, request.uid, client, reason);
+
+    Ok(Json(ImpersonateResponse {
+        token,
+        uid: claims.sub.clone(),
+        session_id: claims.jti.clone(),
+        expires_at: claims.expires_at(),
+    }))
+}
+
+/// GET /v1/admin/impersonation-audit - Newest impersonation audit entries
+async fn list_impersonation_audit(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<ImpersonationAuditQuery>,
+) -> Result<Json<Vec<ImpersonationAuditEntry>>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_IMPERSONATION_AUDIT_ENTRIES);
+    let entries = state
+        .firestore
+        .get_impersonation_audit(query.uid.as_deref(), limit)
+        .await?;
+    Ok(Json(entries))
+}
+
+/// GET /v1/admin/llm-debug/:request_id - Redacted LLM calls a request made, while
+/// LLM_DEBUG_LOGGING is on and the entries haven't expired
+async fn get_llm_debug_log(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(request_id): Path<String>,
+) -> Result<Json<LlmDebugLogResponse>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let entries = state
+        .firestore
+        .get_llm_debug_entries(&request_id, MAX_LLM_DEBUG_ENTRIES)
+        .await?;
+    Ok(Json(LlmDebugLogResponse { request_id, entries }))
+}
+
+/// GET /v1/admin/apps-home - Section order and spotlight categories of the marketplace home
+async fn get_apps_home_layout(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<AppsHomeLayout>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+    let layout = state
+        .firestore
+        .get_apps_home_layout()
+        .await?;
+    Ok(Json(layout))
+}
+
+/// PUT /v1/admin/apps-home - Replace the marketplace home layout
+async fn set_apps_home_layout(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(mut layout): Json<AppsHomeLayout>,
+) -> Result<Json<AppsHomeLayout>, (StatusCode, String)> {
+    require_admin(&state, &user)?;
+
+    let mut seen = Vec::with_capacity(layout.sections.len());
+    layout.sections.retain(|kind| {
+        let first = !seen.contains(kind);
+        seen.push(*kind);
+        first
+    });
+    if layout.sections.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "At least one section is required".to_string()));
+    }
+    let categories: Vec<String> = get_app_categories().into_iter().map(|c| c.id).collect();
+    if let Some(unknown) = layout.spotlight_categories.iter().find(|c| !categories.contains(c)) {
+        return Err((StatusCode::BAD_REQUEST, format!("Unknown app category: {}", unknown)));
+    }
+
+    state
+        .firestore
+        .set_apps_home_layout(&layout)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to set apps home layout: {}", e);
+            e.public_error()
+        })?;
+    tracing::info!("Admin {} set apps home layout to {:?}", user.uid, layout);
+    Ok(Json(layout))
+}
+
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/admin/migrations", get(list_migrations))
+        .route("/v1/admin/migrations/:id/run", post(run_migration))
+        .route("/v1/admin/apps/:app_id/approve", post(approve_app))
+        .route("/v1/admin/apps/:app_id/reject", post(reject_app))
+        .route("/v1/admin/apps/:app_id/moderate", post(moderate_app))
+        .route("/v1/admin/apps/:app_id/moderation-override", put(set_moderation_override))
+        .route("/v1/admin/metrics", get(get_metrics))
+        .route("/v1/admin/llm-models", get(get_llm_models))
+        .route("/v1/admin/llm-models/:task", put(set_llm_model))
+        .route("/v1/admin/llm-quality/:date", get(get_llm_quality_report))
+        .route("/v1/admin/impersonate", post(impersonate_user))
+        .route("/v1/admin/impersonation-audit", get(list_impersonation_audit))
+        .route("/v1/admin/llm-debug/:request_id", get(get_llm_debug_log))
+        .route("/v1/admin/apps-home", get(get_apps_home_layout).put(set_apps_home_layout))
+}
@@ -1,23 +1,44 @@
 // Advice routes
-// Endpoints: GET/POST /v1/advice, PATCH/DELETE /v1/advice/{id}
+// Endpoints: GET/POST /v1/advice, PATCH/DELETE /v1/advice/{id}, POST /v1/advice/{id}/feedback,
+// POST/DELETE /v1/advice/{id}/snooze, GET /v1/advice/unread-count, POST /v1/advice/bulk
+// Advice creation and the feedback summary can draw on the latest screen-context snapshot.
 
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    routing::{get, patch},
+    routing::{get, patch, post},
     Json, Router,
 };
 
 use crate::auth::AuthUser;
-use crate::models::{AdviceDB, AdviceStatusResponse, CreateAdviceRequest, GetAdviceQuery, UpdateAdviceRequest};
+use crate::models::{
+    AdviceBulkRequest, AdviceBulkResponse, AdviceDB, AdviceFeedbackRequest, AdviceFeedbackSummary, AdviceFeedbackSummaryQuery,
+    AdviceStatusResponse,
+    AdviceUnreadCountResponse, CreateAdviceRequest, GetAdviceQuery, SnoozeRequest, SnoozeResponse,
+    UpdateAdviceRequest, MAX_ADVICE_BULK_IDS,
+};
+use crate::services::firestore::{FirestoreError, ADVICE_SUBCOLLECTION};
+use crate::services::snooze;
 use crate::AppState;
 
 /// POST /v1/advice - Create new advice
 async fn create_advice(
     State(state): State<AppState>,
     user: AuthUser,
-    Json(request): Json<CreateAdviceRequest>,
+    Json(mut request): Json<CreateAdviceRequest>,
 ) -> Result<Json<AdviceDB>, StatusCode> {
+    // Fill in missing activity from the latest screen snapshot
+    if request.source_app.is_none() || request.current_activity.is_none() {
+        if let Some(snapshot) = state.screen_context.latest(&user.uid).await {
+            if request.source_app.is_none() && !snapshot.app_name.is_empty() {
+                request.source_app = Some(snapshot.app_name.clone());
+            }
+            if request.current_activity.is_none() {
+                request.current_activity = Some(snapshot.summary()).filter(|s| !s.is_empty());
+            }
+        }
+    }
+
     tracing::info!(
         "Creating advice for user {} with category={:?}, source_app={:?}",
         user.uid,
@@ -42,7 +63,7 @@ async fn create_advice(
         Ok(advice) => Ok(Json(advice)),
         Err(e) => {
             tracing::error!("Failed to create advice: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -70,6 +91,7 @@ async fn get_advice(
             query.offset,
             query.category.as_deref(),
             query.include_dismissed,
+            query.include_snoozed,
         )
         .await
     {
@@ -81,7 +103,7 @@ async fn get_advice(
     }
 }
 
-/// PATCH /v1/advice/{id} - Update advice (mark as read/dismissed)
+/// PATCH /v1/advice/{id} - Update advice (read/dismissed state, delivery tracking)
 async fn update_advice(
     State(state): State<AppState>,
     user: AuthUser,
@@ -92,13 +114,13 @@ async fn update_advice(
 
     match state
         .firestore
-        .update_advice(&user.uid, &advice_id, request.is_read, request.is_dismissed)
+        .update_advice(&user.uid, &advice_id, &request)
         .await
     {
         Ok(advice) => Ok(Json(advice)),
         Err(e) => {
             tracing::error!("Failed to update advice: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -117,7 +139,7 @@ async fn delete_advice(
         })),
         Err(e) => {
             tracing::error!("Failed to delete advice: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -138,7 +160,181 @@ async fn mark_all_read(
         }
         Err(e) => {
             tracing::error!("Failed to mark all advice as read: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/advice/unread-count - Advice that is neither read nor dismissed
+async fn get_unread_count(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<AdviceUnreadCountResponse>, StatusCode> {
+    match state
+        .firestore
+        .count_user_documents_where(&user.uid, ADVICE_SUBCOLLECTION, &[("is_read", false), ("is_dismissed", false)])
+        .await
+    {
+        Ok(unread) => Ok(Json(AdviceUnreadCountResponse { unread })),
+        Err(e) => {
+            tracing::error!("Failed to count unread advice: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/advice/bulk - Mark read, dismiss or delete several advice items
+async fn bulk_update_advice(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(mut request): Json<AdviceBulkRequest>,
+) -> Result<Json<AdviceBulkResponse>, (StatusCode, String)> {
+    request.ids.retain(|id| !id.trim().is_empty());
+    request.ids.sort();
+    request.ids.dedup();
+    if request.ids.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "ids must not be empty".to_string()));
+    }
+    if request.ids.len() > MAX_ADVICE_BULK_IDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} ids per request", MAX_ADVICE_BULK_IDS),
+        ));
+    }
+
+    tracing::info!(
+        "Bulk {:?} on {} advice for user {}",
+        request.action,
+        request.ids.len(),
+        user.uid
+    );
+
+    match state
+        .firestore
+        .bulk_update_advice(&user.uid, &request.ids, request.action)
+        .await
+    {
+        Ok((updated, not_found)) => Ok(Json(AdviceBulkResponse { updated, not_found })),
+        Err(e) => {
+            tracing::error!("Failed to bulk update advice: {}", e);
+            Err((e.http_status(), "Failed to update advice".to_string()))
+        }
+    }
+}
+
+/// Maximum length of free-text advice feedback
+const MAX_FEEDBACK_TEXT_CHARS: usize = 500;
+
+/// Number of recent advice items considered when aggregating feedback
+const FEEDBACK_WINDOW: usize = 300;
+
+/// POST /v1/advice/{id}/feedback - Mark advice as helpful / not helpful
+async fn submit_feedback(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(advice_id): Path<String>,
+    Json(request): Json<AdviceFeedbackRequest>,
+) -> Result<Json<AdviceDB>, StatusCode> {
+    tracing::info!(
+        "Submitting advice feedback helpful={} for advice {} user {}",
+        request.helpful,
+        advice_id,
+        user.uid
+    );
+
+    let text: Option<String> = request
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| t.chars().take(MAX_FEEDBACK_TEXT_CHARS).collect());
+
+    match state
+        .firestore
+        .set_advice_feedback(&user.uid, &advice_id, request.helpful, text.as_deref())
+        .await
+    {
+        Ok(advice) => Ok(Json(advice)),
+        Err(e) => {
+            tracing::error!("Failed to submit advice feedback: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/advice/feedback-summary - Aggregated feedback for steering advice generation
+async fn get_feedback_summary(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<AdviceFeedbackSummaryQuery>,
+) -> Result<Json<AdviceFeedbackSummary>, StatusCode> {
+    tracing::info!("Getting advice feedback summary for user {}", user.uid);
+
+    let screen_context = if query.include_screen_context {
+        state.screen_context.latest(&user.uid).await
+    } else {
+        None
+    };
+
+    match state
+        .firestore
+        .get_advice(&user.uid, FEEDBACK_WINDOW, 0, None, true, true)
+        .await
+    {
+        Ok(advice) => {
+            let summary = AdviceFeedbackSummary::from_advice(&advice);
+            Ok(Json(match screen_context {
+                Some(snapshot) => summary.with_screen_context(snapshot),
+                None => summary,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to get advice for feedback summary: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/advice/{id}/snooze - Hide advice until a resurface time, given as
+/// `minutes`, `until`, or `smart` (picked from the calendar and focus schedule)
+async fn snooze_advice(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(advice_id): Path<String>,
+    Json(request): Json<SnoozeRequest>,
+) -> Result<Json<SnoozeResponse>, (StatusCode, String)> {
+    let (snoozed_until, basis) = snooze::resolve(&state.firestore, &user.uid, &request, None)
+        .await
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    match state
+        .firestore
+        .snooze_advice(&user.uid, &advice_id, Some(snoozed_until))
+        .await
+    {
+        Ok(()) => Ok(Json(SnoozeResponse { snoozed_until, basis })),
+        Err(FirestoreError::NotFound(_)) => {
+            Err((StatusCode::NOT_FOUND, "Advice not found".to_string()))
+        }
+        Err(e) => {
+            tracing::error!("Failed to snooze advice: {}", e);
+            Err((e.http_status(), "Failed to snooze advice".to_string()))
+        }
+    }
+}
+
+/// DELETE /v1/advice/{id}/snooze - Resurface advice now
+async fn unsnooze_advice(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(advice_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    match state.firestore.snooze_advice(&user.uid, &advice_id, None).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(FirestoreError::NotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to unsnooze advice: {}", e);
+            Err(e.http_status())
         }
     }
 }
@@ -147,6 +343,11 @@ pub fn advice_routes() -> Router<AppState> {
     Router::new()
         .route("/v1/advice", get(get_advice).post(create_advice))
         .route("/v1/advice/mark-all-read", axum::routing::post(mark_all_read))
+        .route("/v1/advice/unread-count", get(get_unread_count))
+        .route("/v1/advice/bulk", post(bulk_update_advice))
+        .route("/v1/advice/feedback-summary", get(get_feedback_summary))
+        .route("/v1/advice/:id/feedback", post(submit_feedback))
+        .route("/v1/advice/:id/snooze", post(snooze_advice).delete(unsnooze_advice))
         .route(
             "/v1/advice/:id",
             patch(update_advice).delete(delete_advice),
@@ -10,6 +10,7 @@ use axum::{
 
 use crate::auth::AuthUser;
 use crate::models::agent::{AgentStatusResponse, AgentVmStatus, ProvisionAgentResponse};
+use crate::services::firestore::carry_project_override;
 use crate::AppState;
 
 /// POST /v2/agent/provision
@@ -43,7 +44,7 @@ async fn provision_agent_vm(
         }
         Err(e) => {
             tracing::error!("Failed to check existing agent VM: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(e.http_status());
         }
     }
 
@@ -83,7 +84,7 @@ async fn provision_agent_vm(
     let uid = user.uid.clone();
     let vm_name_clone = vm_name.clone();
     let auth_token_clone = auth_token.clone();
-    tokio::spawn(async move {
+    tokio::spawn(carry_project_override(async move {
         tracing::info!("Starting GCE VM creation: {}", vm_name_clone);
 
         match create_gce_vm(
@@ -130,7 +131,7 @@ async fn provision_agent_vm(
                 }
             }
         }
-    });
+    }));
 
     Ok(Json(ProvisionAgentResponse {
         status: "provisioning".to_string(),
@@ -187,7 +188,7 @@ async fn get_agent_status(
                         let zone = vm.zone.clone();
                         let auth_token = vm.auth_token.clone();
 
-                        tokio::spawn(async move {
+                        tokio::spawn(carry_project_override(async move {
                             match start_stopped_vm(&firestore, &vm_name, &zone).await {
                                 Ok(ip) => {
                                     tracing::info!("VM {} restarted with IP {}", vm_name, ip);
@@ -220,7 +221,7 @@ async fn get_agent_status(
                                         .await;
                                 }
                             }
-                        });
+                        }));
 
                         // Return "provisioning" so the client polls
                         return Ok(Json(Some(AgentStatusResponse {
@@ -250,7 +251,7 @@ async fn get_agent_status(
                         let zone = vm.zone.clone();
                         let auth_token = vm.auth_token.clone();
 
-                        tokio::spawn(async move {
+                        tokio::spawn(carry_project_override(async move {
                             let project = "based-hardware";
                             let instance_url = format!(
                                 "https://compute.googleapis.com/compute/v1/projects/{}/zones/{}/instances/{}",
@@ -278,7 +279,7 @@ async fn get_agent_status(
                                     }
                                 }
                             }
-                        });
+                        }));
 
                         // Return provisioning so client polls
                         return Ok(Json(Some(AgentStatusResponse {
@@ -318,7 +319,7 @@ async fn get_agent_status(
         Ok(None) => Ok(Json(None)),
         Err(e) => {
             tracing::error!("Failed to get agent VM status: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -1,26 +1,72 @@
 // Apps routes - OMI Apps/Plugins system
 // Endpoints for app discovery, management, and usage
+// Apps with auth steps are set up via POST /v1/apps/:app_id/setup, which hands out the
+// app's auth URL with a one-time state; GET /v1/app-setup/callback?state= marks setup
+// complete. Integration triggers aren't delivered until it is.
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    routing::{get, post},
+    async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, StatusCode},
+    routing::{get, post, put},
     Json, Router,
 };
 
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
 use crate::auth::AuthUser;
+use crate::etag::with_etag;
 use crate::models::{
-    App, AppCapabilityDef, AppCategory, AppGroup, AppReview, AppSummary, AppsV2Meta, AppsV2Query,
-    AppsV2Response, CapabilityInfo, ListAppsQuery, PaginationMeta, SearchAppsQuery,
-    SubmitReviewRequest, ToggleAppRequest, ToggleAppResponse, get_app_capabilities,
-    get_app_categories, get_v2_capabilities,
+    App, AppCapabilityDef, AppSetupCallbackQuery, AppSetupStartResponse, AppSetupState,
+    AppSetupStatusResponse, APP_SETUP_STATE_TTL_MINUTES, AppCategory, AppGroup, AppReview, AppSummary, AppsHomeQuery,
+    AppsHomeResponse, AppsV2Meta, AppsV2Query,
+    AppsV2Response, CapabilityInfo, ListAppsQuery, PaginationMeta, RespondToReviewRequest,
+    SearchAppsQuery, SubmitReviewRequest, TestPromptRequest, TestPromptResponse, ToggleAppRequest,
+    ToggleAppResponse, UpdateReviewRequest, get_app_capabilities, get_app_categories, get_v2_capabilities,
+    MAX_HOME_SECTION_LIMIT, MAX_PROMPT_TESTS_PER_HOUR, MAX_TEST_PROMPT_CHARS, MAX_TEST_TRANSCRIPT_CHARS,
+    AppTranslation, SetAppTranslationsRequest, MAX_APP_DESCRIPTION_CHARS, MAX_APP_NAME_CHARS,
+    MAX_APP_TRANSLATIONS, normalize_language_tag, parse_accept_language,
 };
+use crate::llm_limit::with_llm_limit;
+use crate::services::firestore::FirestoreError;
+use crate::services::{apps_home, prompt_sandbox};
 use crate::AppState;
 use crate::services::redis::RedisService;
 use std::sync::Arc;
 
+// ============================================================================
+// Localization
+// ============================================================================
+
+/// Preferred languages from the Accept-Language header, most preferred first
+struct AcceptLanguage(Vec<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AcceptLanguage
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let languages = parts
+            .headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|h| h.to_str().ok())
+            .map(parse_accept_language)
+            .unwrap_or_default();
+        Ok(Self(languages))
+    }
+}
+
+fn localize_apps(apps: &mut [AppSummary], languages: &[String]) {
+    for app in apps {
+        app.localize(languages);
+    }
+}
+
 // ============================================================================
 // Helper function to enrich apps with Redis data
 // ============================================================================
@@ -66,6 +112,7 @@ async fn enrich_apps_from_redis(apps: &mut [AppSummary], redis: Option<&Arc<Redi
 async fn list_apps(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
     Query(query): Query<ListAppsQuery>,
 ) -> Result<Json<Vec<AppSummary>>, (StatusCode, String)> {
     tracing::info!(
@@ -85,11 +132,12 @@ async fn list_apps(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to get apps: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
     enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
     Ok(Json(apps))
 }
 
@@ -97,6 +145,7 @@ async fn list_apps(
 async fn list_approved_apps(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
     Query(query): Query<ListAppsQuery>,
 ) -> Result<Json<Vec<AppSummary>>, (StatusCode, String)> {
     tracing::info!("Listing approved apps for user {}", user.uid);
@@ -109,11 +158,12 @@ async fn list_approved_apps(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to get approved apps: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get approved apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
     enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
     Ok(Json(apps))
 }
 
@@ -121,6 +171,7 @@ async fn list_approved_apps(
 async fn list_popular_apps(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
 ) -> Result<Json<Vec<AppSummary>>, (StatusCode, String)> {
     tracing::info!("Listing popular apps for user {}", user.uid);
 
@@ -128,11 +179,12 @@ async fn list_popular_apps(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to get popular apps: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get popular apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
     enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
     Ok(Json(apps))
 }
 
@@ -140,6 +192,7 @@ async fn list_popular_apps(
 async fn search_apps(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
     Query(query): Query<SearchAppsQuery>,
 ) -> Result<Json<Vec<AppSummary>>, (StatusCode, String)> {
     tracing::info!(
@@ -168,11 +221,12 @@ async fn search_apps(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to search apps: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to search apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
     enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
     Ok(Json(apps))
 }
 
@@ -181,6 +235,7 @@ async fn search_apps(
 async fn get_apps_v2(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
     Query(query): Query<AppsV2Query>,
 ) -> Result<Json<AppsV2Response>, (StatusCode, String)> {
     tracing::info!(
@@ -200,7 +255,7 @@ async fn get_apps_v2(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to get apps for v2: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
@@ -282,6 +337,9 @@ async fn get_apps_v2(
         tracing::debug!("Redis not configured - using installs from Firestore");
     }
 
+    localize_apps(&mut all_apps, &languages);
+    localize_apps(&mut popular_apps, &languages);
+
     let popular_ids: std::collections::HashSet<_> = popular_apps.iter().map(|a| a.id.clone()).collect();
 
     // Get capabilities for grouping
@@ -399,6 +457,37 @@ async fn get_apps_v2(
     Ok(Json(response))
 }
 
+/// GET /v2/apps/home - Marketplace home: your apps, popular, new this week and category
+/// spotlights in one response, ordered by the admin-managed layout
+async fn get_apps_home(
+    State(state): State<AppState>,
+    user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
+    Query(query): Query<AppsHomeQuery>,
+) -> Result<Json<AppsHomeResponse>, (StatusCode, String)> {
+    let limit = query.limit.clamp(1, MAX_HOME_SECTION_LIMIT);
+    tracing::info!("Getting apps home for user {} with limit={}", user.uid, limit);
+
+    let mut apps = match state.firestore.get_apps(&user.uid, 5000, 0, None, None).await {
+        Ok(apps) => apps,
+        Err(e) => {
+            tracing::error!("Failed to get apps for home: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
+
+    let layout = state.firestore.get_apps_home_layout().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load apps home layout: {} - using default", e);
+        Default::default()
+    });
+
+    Ok(Json(AppsHomeResponse {
+        sections: apps_home::build_home_sections(&apps, &layout, limit, chrono::Utc::now()),
+    }))
+}
+
 /// Compute app ranking score (matching Python backend formula)
 /// Formula: ((rating_avg / 5) ** 2) * log(1 + rating_count) * sqrt(log(1 + installs))
 /// - Power of 2 on rating makes ratings below 3.0 fall steeply
@@ -476,6 +565,7 @@ async fn get_app_details(
     State(state): State<AppState>,
     user: AuthUser,
     Path(app_id): Path<String>,
+    AcceptLanguage(languages): AcceptLanguage,
 ) -> Result<Json<App>, (StatusCode, String)> {
     tracing::info!("Getting app details for {} by user {}", app_id, user.uid);
 
@@ -484,7 +574,7 @@ async fn get_app_details(
         Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
         Err(e) => {
             tracing::error!("Failed to get app: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get app: {}", e)));
+            return Err(e.public_error());
         }
     };
 
@@ -512,9 +602,69 @@ async fn get_app_details(
         }
     }
 
+    app.localize(&languages);
     Ok(Json(app))
 }
 
+/// PUT /v1/apps/:app_id/translations - Replace the app's translated name and description
+/// per language. Owner only; languages are BCP 47 tags ("es", "pt-BR").
+async fn set_app_translations(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+    Json(request): Json<SetAppTranslationsRequest>,
+) -> Result<Json<HashMap<String, AppTranslation>>, (StatusCode, String)> {
+    if request.translations.len() > MAX_APP_TRANSLATIONS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} translations are allowed", MAX_APP_TRANSLATIONS),
+        ));
+    }
+
+    let mut translations = HashMap::with_capacity(request.translations.len());
+    for (language, translation) in request.translations {
+        let Some(tag) = normalize_language_tag(&language) else {
+            return Err((StatusCode::BAD_REQUEST, format!("Invalid language tag: {}", language)));
+        };
+        let clean = |text: Option<String>, max_chars: usize, field: &str| {
+            let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+            match text {
+                Some(t) if t.chars().count() > max_chars => Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("{} {} must be at most {} characters", tag, field, max_chars),
+                )),
+                text => Ok(text),
+            }
+        };
+        let translation = AppTranslation {
+            name: clean(translation.name, MAX_APP_NAME_CHARS, "name")?,
+            description: clean(translation.description, MAX_APP_DESCRIPTION_CHARS, "description")?,
+        };
+        if translation != AppTranslation::default() {
+            translations.insert(tag, translation);
+        }
+    }
+
+    let app = match state.firestore.get_app(&user.uid, &app_id).await {
+        Ok(Some(app)) => app,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    if app.uid.as_deref() != Some(user.uid.as_str()) {
+        return Err((StatusCode::FORBIDDEN, "Only the app owner can edit translations".to_string()));
+    }
+
+    if let Err(e) = state.firestore.set_app_translations(&app_id, &translations).await {
+        tracing::error!("Failed to set translations for app {}: {}", app_id, e);
+        return Err(e.public_error());
+    }
+    tracing::info!("User {} set {} translations for app {}", user.uid, translations.len(), app_id);
+    Ok(Json(translations))
+}
+
 /// GET /v1/apps/:app_id/reviews - Get app reviews
 async fn get_app_reviews(
     State(state): State<AppState>,
@@ -527,7 +677,7 @@ async fn get_app_reviews(
         Ok(reviews) => Ok(Json(reviews)),
         Err(e) => {
             tracing::error!("Failed to get reviews: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get reviews: {}", e)))
+            Err(e.public_error())
         }
     }
 }
@@ -537,6 +687,7 @@ async fn get_app_reviews(
 // ============================================================================
 
 /// POST /v1/apps/enable - Enable an app for the user
+/// The request must grant every scope the app requests (428 lists the missing ones)
 async fn enable_app(
     State(state): State<AppState>,
     user: AuthUser,
@@ -544,14 +695,39 @@ async fn enable_app(
 ) -> Result<Json<ToggleAppResponse>, (StatusCode, String)> {
     tracing::info!("Enabling app {} for user {}", request.app_id, user.uid);
 
-    match state.firestore.enable_app(&user.uid, &request.app_id).await {
+    let app = match state.firestore.get_app(&user.uid, &request.app_id).await {
+        Ok(Some(app)) => app,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    let requested = app.requested_scopes();
+    let missing: Vec<&str> = requested
+        .iter()
+        .filter(|scope| !request.granted_scopes.contains(scope))
+        .map(|scope| scope.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err((
+            StatusCode::PRECONDITION_REQUIRED,
+            format!("Consent required for scopes: {}", missing.join(", ")),
+        ));
+    }
+
+    match state
+        .firestore
+        .enable_app(&user.uid, &request.app_id, &requested, app.requires_setup())
+        .await
+    {
         Ok(_) => Ok(Json(ToggleAppResponse {
             success: true,
             message: "App enabled successfully".to_string(),
         })),
         Err(e) => {
             tracing::error!("Failed to enable app: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to enable app: {}", e)))
+            Err(e.public_error())
         }
     }
 }
@@ -571,7 +747,7 @@ async fn disable_app(
         })),
         Err(e) => {
             tracing::error!("Failed to disable app: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to disable app: {}", e)))
+            Err(e.public_error())
         }
     }
 }
@@ -580,6 +756,7 @@ async fn disable_app(
 async fn get_enabled_apps(
     State(state): State<AppState>,
     user: AuthUser,
+    AcceptLanguage(languages): AcceptLanguage,
 ) -> Result<Json<Vec<AppSummary>>, (StatusCode, String)> {
     tracing::info!("Getting enabled apps for user {}", user.uid);
 
@@ -587,11 +764,12 @@ async fn get_enabled_apps(
         Ok(apps) => apps,
         Err(e) => {
             tracing::error!("Failed to get enabled apps: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get enabled apps: {}", e)));
+            return Err(e.public_error());
         }
     };
 
     enrich_apps_from_redis(&mut apps, state.redis.as_ref()).await;
+    localize_apps(&mut apps, &languages);
     Ok(Json(apps))
 }
 
@@ -612,9 +790,16 @@ async fn submit_review(
         request.score
     );
 
-    // Validate score
-    if request.score < 1 || request.score > 5 {
-        return Err((StatusCode::BAD_REQUEST, "Score must be between 1 and 5".to_string()));
+    validate_review(Some(request.score), Some(&request.review))?;
+
+    // One review per user; edits go through PATCH /v1/apps/:app_id/reviews/:uid
+    match state.firestore.get_app_review(&request.app_id, &user.uid).await {
+        Ok(Some(_)) => return Err(review_exists_error(&request.app_id, &user.uid)),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Failed to check existing review: {}", e);
+            return Err(e.public_error());
+        }
     }
 
     match state
@@ -623,13 +808,134 @@ async fn submit_review(
         .await
     {
         Ok(review) => Ok(Json(review)),
+        Err(FirestoreError::AlreadyExists(_)) => {
+            Err(review_exists_error(&request.app_id, &user.uid))
+        }
         Err(e) => {
             tracing::error!("Failed to submit review: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to submit review: {}", e)))
+            Err(e.public_error())
         }
     }
 }
 
+/// Max length of a review or developer response
+const MAX_REVIEW_CHARS: usize = 2000;
+
+fn validate_review(score: Option<i32>, review: Option<&str>) -> Result<(), (StatusCode, String)> {
+    if score.is_some_and(|s| !(1..=5).contains(&s)) {
+        return Err((StatusCode::BAD_REQUEST, "Score must be between 1 and 5".to_string()));
+    }
+    if review.is_some_and(|r| r.chars().count() > MAX_REVIEW_CHARS) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Review must be at most {} characters", MAX_REVIEW_CHARS),
+        ));
+    }
+    Ok(())
+}
+
+fn review_exists_error(app_id: &str, uid: &str) -> (StatusCode, String) {
+    (
+        StatusCode::CONFLICT,
+        format!(
+            "You have already reviewed this app; edit it with PATCH /v1/apps/{}/reviews/{}",
+            app_id, uid
+        ),
+    )
+}
+
+/// Map Firestore errors for a missing review to 404
+fn review_error(action: &str, e: FirestoreError) -> (StatusCode, String) {
+    if let FirestoreError::NotFound(_) = e {
+        return (StatusCode::NOT_FOUND, "Review not found".to_string());
+    }
+    tracing::error!("Failed to {} review: {}", action, e);
+    e.public_error()
+}
+
+/// PATCH /v1/apps/:app_id/reviews/:uid - Edit the caller's own review
+async fn update_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((app_id, reviewer_uid)): Path<(String, String)>,
+    Json(request): Json<UpdateReviewRequest>,
+) -> Result<Json<AppReview>, (StatusCode, String)> {
+    if reviewer_uid != user.uid {
+        return Err((StatusCode::FORBIDDEN, "You can only edit your own review".to_string()));
+    }
+    if request.score.is_none() && request.review.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "Nothing to update".to_string()));
+    }
+    validate_review(request.score, request.review.as_deref())?;
+
+    tracing::info!("User {} editing review for app {}", user.uid, app_id);
+
+    state
+        .firestore
+        .update_app_review(&user.uid, &app_id, request.score, request.review.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| review_error("update", e))
+}
+
+/// DELETE /v1/apps/:app_id/reviews/:uid - Delete the caller's own review
+async fn delete_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((app_id, reviewer_uid)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if reviewer_uid != user.uid {
+        return Err((StatusCode::FORBIDDEN, "You can only delete your own review".to_string()));
+    }
+
+    tracing::info!("User {} deleting review for app {}", user.uid, app_id);
+
+    state
+        .firestore
+        .delete_app_review(&user.uid, &app_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| review_error("delete", e))
+}
+
+/// POST /v1/apps/:app_id/reviews/:uid/respond - App owner responds to a review
+async fn respond_to_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((app_id, reviewer_uid)): Path<(String, String)>,
+    Json(request): Json<RespondToReviewRequest>,
+) -> Result<Json<AppReview>, (StatusCode, String)> {
+    let response = request.response.trim();
+    if response.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Response cannot be empty".to_string()));
+    }
+    validate_review(None, Some(response))?;
+
+    let app = match state.firestore.get_app(&user.uid, &app_id).await {
+        Ok(Some(app)) => app,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    if app.uid.as_deref() != Some(user.uid.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the app's developer can respond to reviews".to_string(),
+        ));
+    }
+
+    tracing::info!("Developer {} responding to review by {} on app {}", user.uid, reviewer_uid, app_id);
+
+    state
+        .firestore
+        .respond_to_app_review(&app_id, &reviewer_uid, response)
+        .await
+        .map(Json)
+        .map_err(|e| review_error("respond to", e))
+}
+
 // ============================================================================
 // Metadata Endpoints
 // ============================================================================
@@ -648,6 +954,217 @@ async fn list_capabilities(
     Ok(Json(get_app_capabilities()))
 }
 
+// ============================================================================
+// Developer Sandbox
+// ============================================================================
+
+/// POST /v1/apps/test-prompt - Run a memory or chat prompt against a sample transcript
+/// Only synthetic or developer-provided samples are used; limited to
+/// MAX_PROMPT_TESTS_PER_HOUR runs per developer.
+async fn test_prompt(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<TestPromptRequest>,
+) -> Result<Json<TestPromptResponse>, (StatusCode, String)> {
+    let prompt_chars = request.prompt.trim().chars().count();
+    if prompt_chars == 0 || prompt_chars > MAX_TEST_PROMPT_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("prompt must be 1-{} characters", MAX_TEST_PROMPT_CHARS),
+        ));
+    }
+    if request.transcript.as_ref().is_some_and(|t| t.chars().count() > MAX_TEST_TRANSCRIPT_CHARS) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("transcript must be at most {} characters", MAX_TEST_TRANSCRIPT_CHARS),
+        ));
+    }
+
+    let remaining_this_hour = match &state.redis {
+        Some(redis) => {
+            let hour = chrono::Utc::now().format("%Y%m%d%H").to_string();
+            match redis.increment_prompt_tests(&user.uid, &hour).await {
+                Ok(count) if count > MAX_PROMPT_TESTS_PER_HOUR => {
+                    return Err((
+                        StatusCode::TOO_MANY_REQUESTS,
+                        format!("Limit of {} prompt tests per hour reached", MAX_PROMPT_TESTS_PER_HOUR),
+                    ));
+                }
+                Ok(count) => Some(MAX_PROMPT_TESTS_PER_HOUR - count),
+                Err(e) => {
+                    tracing::warn!("Failed to count prompt tests for {}: {}", user.uid, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let Some(llm) = state.llm_client(&user.uid).await? else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "No LLM key configured".to_string()));
+    };
+    let (task, input, synthetic) = prompt_sandbox::build_input(&request);
+    let (result, model, usage) = prompt_sandbox::run(&llm, task, &input).await.map_err(|e| {
+        tracing::error!("Prompt test failed for {}: {}", user.uid, e);
+        (StatusCode::BAD_GATEWAY, format!("Prompt test failed: {}", e))
+    })?;
+
+    tracing::info!(
+        "Prompt test ({:?}) by {}: {} tokens",
+        request.kind,
+        user.uid,
+        usage.total_tokens
+    );
+    Ok(Json(TestPromptResponse {
+        kind: request.kind,
+        result,
+        sample: if synthetic { "synthetic" } else { "provided" }.to_string(),
+        model,
+        usage,
+        remaining_this_hour,
+    }))
+}
+
+// ============================================================================
+// App Setup Endpoints
+// ============================================================================
+
+/// Firestore key of a setup state; the raw state is only ever in the auth URL
+fn setup_state_hash(state: &str) -> String {
+    hex::encode(Sha256::digest(state.as_bytes()))
+}
+
+/// The app's first auth step URL with `uid` and `state` appended
+fn setup_auth_url(step_url: &str, uid: &str, state: &str) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(step_url).map_err(|e| e.to_string())?;
+    url.query_pairs_mut().append_pair("uid", uid).append_pair("state", state);
+    Ok(url.into())
+}
+
+/// POST /v1/apps/:app_id/setup - Start setting up an enabled app
+/// Returns the auth URL to open; the app's backend calls the callback with the state.
+async fn start_app_setup(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+) -> Result<Json<AppSetupStartResponse>, (StatusCode, String)> {
+    let app = match state.firestore.get_app(&user.uid, &app_id).await {
+        Ok(Some(app)) => app,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    let Some(step) = app
+        .external_integration
+        .as_ref()
+        .and_then(|integration| integration.auth_steps.first())
+    else {
+        return Err((StatusCode::BAD_REQUEST, "App has no setup steps".to_string()));
+    };
+    match state.firestore.get_app_setup(&user.uid, &app_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err((StatusCode::CONFLICT, "Enable the app before setting it up".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app setup: {}", e);
+            return Err(e.public_error());
+        }
+    }
+
+    let setup_state = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let auth_url = setup_auth_url(&step.url, &user.uid, &setup_state).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("App has an invalid auth URL: {}", e),
+        )
+    })?;
+    let now = Utc::now();
+    let setup = AppSetupState {
+        uid: user.uid.clone(),
+        app_id: app_id.clone(),
+        created_at: now,
+        expires_at: now + Duration::minutes(APP_SETUP_STATE_TTL_MINUTES),
+    };
+    if let Err(e) = state
+        .firestore
+        .save_app_setup_state(&setup_state_hash(&setup_state), &setup)
+        .await
+    {
+        tracing::error!("Failed to save app setup state: {}", e);
+        return Err(e.public_error());
+    }
+
+    tracing::info!("Started setup of app {} for user {}", app_id, user.uid);
+    Ok(Json(AppSetupStartResponse {
+        app_id,
+        auth_url,
+        state: setup_state,
+        expires_at: setup.expires_at,
+    }))
+}
+
+/// GET /v1/apps/:app_id/setup - Whether an enabled app's setup is complete
+async fn get_app_setup_status(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(app_id): Path<String>,
+) -> Result<Json<AppSetupStatusResponse>, (StatusCode, String)> {
+    let app = match state.firestore.get_app(&user.uid, &app_id).await {
+        Ok(Some(app)) => app,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "App not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    match state.firestore.get_app_setup(&user.uid, &app_id).await {
+        Ok(Some((completed, completed_at))) => {
+            let required = app.requires_setup();
+            Ok(Json(AppSetupStatusResponse {
+                app_id,
+                required,
+                completed: completed || !required,
+                completed_at,
+            }))
+        }
+        Ok(None) => Err((StatusCode::NOT_FOUND, "App is not enabled".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get app setup: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// GET /v1/app-setup/callback?state= - Called once the user finished the app's auth flow
+/// Unauthenticated: the one-time state identifies the user and app.
+async fn app_setup_callback(
+    State(state): State<AppState>,
+    Query(query): Query<AppSetupCallbackQuery>,
+) -> Result<Json<AppSetupStatusResponse>, (StatusCode, String)> {
+    let setup = match state.firestore.take_app_setup_state(&setup_state_hash(&query.state)).await {
+        Ok(Some(setup)) if setup.expires_at > Utc::now() => setup,
+        Ok(_) => return Err((StatusCode::BAD_REQUEST, "Invalid or expired state".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to read app setup state: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    // Fails if the app was disabled since setup started
+    if let Err(e) = state.firestore.complete_app_setup(&setup.uid, &setup.app_id).await {
+        tracing::warn!("Failed to complete setup of app {} for {}: {}", setup.app_id, setup.uid, e);
+        return Err((StatusCode::CONFLICT, "App is no longer enabled".to_string()));
+    }
+
+    Ok(Json(AppSetupStatusResponse {
+        app_id: setup.app_id,
+        required: true,
+        completed: true,
+        completed_at: Some(Utc::now()),
+    }))
+}
+
 // ============================================================================
 // Router
 // ============================================================================
@@ -655,21 +1172,61 @@ async fn list_capabilities(
 pub fn apps_routes() -> Router<AppState> {
     Router::new()
         // Discovery
-        .route("/v1/apps", get(list_apps))
+        .route("/v1/apps", with_etag(get(list_apps)))
         .route("/v1/approved-apps", get(list_approved_apps))
         .route("/v1/apps/popular", get(list_popular_apps))
-        .route("/v2/apps", get(get_apps_v2))
+        .route("/v2/apps", with_etag(get(get_apps_v2)))
+        .route("/v2/apps/home", with_etag(get(get_apps_home)))
         .route("/v2/apps/search", get(search_apps))
         // Details
         .route("/v1/apps/:app_id", get(get_app_details))
         .route("/v1/apps/:app_id/reviews", get(get_app_reviews))
+        .route("/v1/apps/:app_id/translations", put(set_app_translations))
         // Management
         .route("/v1/apps/enable", post(enable_app))
         .route("/v1/apps/disable", post(disable_app))
         .route("/v1/apps/enabled", get(get_enabled_apps))
+        // Setup
+        .route("/v1/apps/:app_id/setup", get(get_app_setup_status).post(start_app_setup))
+        .route("/v1/app-setup/callback", get(app_setup_callback))
+        // Developer sandbox
+        .route("/v1/apps/test-prompt", with_llm_limit(post(test_prompt)))
         // Reviews
         .route("/v1/apps/review", post(submit_review))
+        .route(
+            "/v1/apps/:app_id/reviews/:uid",
+            axum::routing::patch(update_review).delete(delete_review),
+        )
+        .route("/v1/apps/:app_id/reviews/:uid/respond", post(respond_to_review))
         // Metadata
         .route("/v1/app-categories", get(list_categories))
         .route("/v1/app-capabilities", get(list_capabilities))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_auth_url_appends_state() {
+        assert_eq!(
+            setup_auth_url("https://example.com/auth", "u1", "s1").unwrap(),
+            "https://example.com/auth?uid=u1&state=s1"
+        );
+        assert_eq!(
+            setup_auth_url("https://example.com/auth?app=notes", "u 1", "s1").unwrap(),
+            "https://example.com/auth?app=notes&uid=u+1&state=s1"
+        );
+        assert!(setup_auth_url("not a url", "u1", "s1").is_err());
+        assert_ne!(setup_state_hash("s1"), "s1");
+    }
+
+    #[test]
+    fn test_validate_review() {
+        assert!(validate_review(None, None).is_ok());
+        assert!(validate_review(Some(5), Some(&"a".repeat(MAX_REVIEW_CHARS))).is_ok());
+        assert_eq!(validate_review(Some(0), None).unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert!(validate_review(Some(6), None).is_err());
+        assert!(validate_review(None, Some(&"a".repeat(MAX_REVIEW_CHARS + 1))).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+// Badge routes - Menu bar counts in one polled request
+// Endpoints: GET /v1/badges
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+
+use crate::auth::AuthUser;
+use crate::etag::with_etag;
+use crate::models::{BadgeCounts, BadgesQuery};
+use crate::services::badges;
+use crate::AppState;
+
+/// GET /v1/badges - Unread advice, unreviewed memories, and due today / overdue action items
+async fn get_badges(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<BadgesQuery>,
+) -> Result<Json<BadgeCounts>, (StatusCode, String)> {
+    let timezone = query.timezone.as_deref().map(str::trim).filter(|tz| !tz.is_empty());
+    let badges = badges::get_badges(&state, &user.uid, timezone).await.map_err(|e| {
+        tracing::error!("Failed to get badges for user {}: {}", user.uid, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to get badges".to_string())
+    })?;
+    Ok(Json(badges))
+}
+
+pub fn badges_routes() -> Router<AppState> {
+    Router::new().route("/v1/badges", with_etag(get(get_badges)))
+}
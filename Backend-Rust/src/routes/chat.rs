@@ -2,7 +2,7 @@
 // Ported from Python backend: utils/retrieval/graph.py, utils/llm/chat.py
 //
 // Endpoints:
-// - POST /v2/chat-context - Get context for building chat prompts
+// - POST /v2/chat-context - Get context for building chat prompts (optionally app-scoped)
 
 use axum::{
     extract::State,
@@ -17,7 +17,12 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::auth::AuthUser;
+use crate::llm_limit::with_llm_limit;
+use crate::llm::client::InlineImage;
+use crate::models::screen_activity::ScreenContextSnapshot;
 use crate::llm::LlmClient;
+use crate::models::{App, ChatDataScope};
+use crate::services::response_guardrails::ResponseGuardrails;
 use crate::services::FirestoreService;
 use crate::AppState;
 
@@ -36,9 +41,18 @@ pub struct ChatContextRequest {
     /// Optional app ID for app-specific context
     #[serde(default)]
     pub app_id: Option<String>,
+    /// Chat session the question belongs to; its app is used when app_id is omitted
+    #[serde(default)]
+    pub session_id: Option<String>,
     /// Previous messages for conversation history context
     #[serde(default)]
     pub messages: Vec<ChatMessageInput>,
+    /// Attachments on the current message (from POST /v2/messages/attachments)
+    #[serde(default)]
+    pub attachment_ids: Vec<String>,
+    /// Include the latest screen-context snapshot (POST /v1/context/screen)
+    #[serde(default)]
+    pub include_screen_context: bool,
 }
 
 fn default_timezone() -> String {
@@ -66,6 +80,15 @@ pub struct ChatContextResponse {
     pub context_string: String,
     /// Citation sources for tracking which conversations/memories are cited
     pub citation_sources: Vec<CitationSource>,
+    /// Vision model's reading of attached images (also included in context_string)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachment_context: Option<String>,
+    /// Latest screen snapshot used (also included in context_string)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screen_context: Option<ScreenContextSnapshot>,
+    /// App whose persona and data scopes shaped the context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
 }
 
 /// Request for initial message generation
@@ -76,6 +99,13 @@ pub struct InitialMessageRequest {
     /// Optional app ID for app-specific persona
     #[serde(default)]
     pub app_id: Option<String>,
+    /// Whether the client renders markdown; the greeting is plain text if not
+    #[serde(default = "default_supports_markdown")]
+    pub supports_markdown: bool,
+}
+
+fn default_supports_markdown() -> bool {
+    true
 }
 
 /// Response with generated initial message
@@ -123,6 +153,8 @@ pub struct ConversationSummary {
     pub emoji: String,
     pub category: String,
     pub created_at: DateTime<Utc>,
+    /// Compact one-line description used in context_string
+    pub context_line: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -184,7 +216,10 @@ Extract the date range from the user's question that would help find relevant co
 Current date/time in UTC: {current_datetime}
 User's timezone: {timezone}
 
+{conversation_history}
+
 Rules:
+- Resolve relative references against the conversation above (e.g. "the day before that")
 - "today" = start of today to end of today in user's timezone
 - "yesterday" = start of yesterday to end of yesterday
 - "this week" = start of this week (Monday) to now
@@ -199,6 +234,21 @@ User's Question:
 Return the date range in UTC, or null if no date reference found.
 "#;
 
+const STANDALONE_QUESTION_PROMPT: &str = r#"
+Rewrite the user's latest question so it can be understood without the conversation.
+
+{conversation_history}
+
+Latest Question:
+{question}
+
+Rules:
+- Replace pronouns and vague references ("he", "she", "they", "it", "that meeting") with the people, topics or events they refer to in the conversation above
+- Keep the user's wording otherwise; don't answer the question
+- If nothing needs resolving, return the question unchanged
+- Also list the key people, topics and places the question is about (short keywords)
+"#;
+
 // ============================================================================
 // HANDLERS
 // ============================================================================
@@ -210,7 +260,7 @@ async fn get_chat_context(
     Json(request): Json<ChatContextRequest>,
 ) -> Result<Json<ChatContextResponse>, StatusCode> {
     let question = request.question.trim();
-    if question.is_empty() {
+    if question.is_empty() && request.attachment_ids.is_empty() {
         return Ok(Json(ChatContextResponse {
             requires_context: false,
             date_range: None,
@@ -218,6 +268,9 @@ async fn get_chat_context(
             memories: vec![],
             context_string: String::new(),
             citation_sources: vec![],
+            attachment_context: None,
+            screen_context: None,
+            app_id: None,
         }));
     }
 
@@ -229,39 +282,48 @@ async fn get_chat_context(
         request.app_id
     );
 
-    // Fetch app details if app_id provided
-    let app_context = if let Some(app_id) = &request.app_id {
-        match state.firestore.get_app(&user.uid, app_id).await {
-            Ok(Some(app)) => {
-                tracing::info!("Loaded app: {} ({})", app.name, app_id);
-                Some((app.name, app.chat_prompt, app.persona_prompt))
-            }
-            Ok(None) => {
-                tracing::warn!("App not found: {}", app_id);
-                None
-            }
-            Err(e) => {
-                tracing::warn!("Failed to fetch app {}: {}", app_id, e);
-                None
-            }
-        }
+    let screen_context = if request.include_screen_context {
+        state.screen_context.latest(&user.uid).await
     } else {
         None
     };
 
-    // Get API key for LLM calls
-    let api_key = match &state.config.gemini_api_key {
-        Some(key) => key.clone(),
+    // App persona and data scopes (from app_id, or the chat session's app)
+    let app = load_chat_app(&state, &user.uid, &request).await;
+    let scope = app.as_ref().map_or(ChatDataScope::ALL, App::permitted_chat_data_scope);
+    let user_name = if scope.user_name {
+        user.name.as_deref().unwrap_or("User")
+    } else {
+        "User"
+    };
+
+    // Get API key for LLM calls (the user's own key if they brought one)
+    let api_key = match state.llm_api_key(&user.uid).await? {
+        Some(key) => key,
         None => {
             tracing::warn!("No Gemini API key configured, returning basic context");
-            return get_basic_context(&state.firestore, &user.uid, user.name.as_deref().unwrap_or("User"), &request).await;
+            return get_basic_context(
+                &state.firestore,
+                &user.uid,
+                user_name,
+                &request,
+                app.as_ref(),
+                screen_context,
+            )
+            .await;
         }
     };
 
-    let llm = LlmClient::new(api_key);
+    let llm = LlmClient::new(api_key).with_task_models(state.llm_task_models().await);
+
+    // Read attached images with the vision model so the answer can use them
+    let attachment_context = if request.attachment_ids.is_empty() {
+        None
+    } else {
+        describe_attachments(&state, &llm, &user.uid, question, &request.attachment_ids).await
+    };
 
     // Format conversation history for context-aware decisions
-    let user_name = user.name.as_deref().unwrap_or("User");
     let conversation_history = format_conversation_history(&request.messages, user_name);
 
     // Step 1: Determine if context is needed (considering conversation history)
@@ -272,8 +334,18 @@ async fn get_chat_context(
     if !requires_context {
         tracing::info!("Question does not require context");
         // Still return memories for personalization
-        let memories = get_user_memories(&state.firestore, &user.uid).await;
-        let context_string = format_memories_context(&memories);
+        let memories = if scope.memories {
+            get_user_memories(&state.firestore, &user.uid).await
+        } else {
+            vec![]
+        };
+        let context_string = with_screen_context(
+            with_attachment_context(
+                with_app_context(format_memories_context(&memories), app.as_ref()),
+                attachment_context.as_deref(),
+            ),
+            screen_context.as_ref(),
+        );
 
         return Ok(Json(ChatContextResponse {
             requires_context: false,
@@ -282,44 +354,53 @@ async fn get_chat_context(
             memories,
             context_string,
             citation_sources: vec![],
+            attachment_context,
+            screen_context,
+            app_id: app.map(|a| a.id),
         }));
     }
 
-    // Step 2: Extract date range from question
-    let date_range = extract_date_range(&llm, question, &request.timezone).await;
+    // Step 2: Resolve references to earlier messages ("what did he say about it?")
+    let (standalone_question, keywords) = if request.messages.is_empty() {
+        (question.to_string(), Vec::new())
+    } else {
+        resolve_question(&llm, question, &conversation_history).await
+    };
+
+    // Step 3: Extract date range from question
+    let date_range = extract_date_range(
+        &llm,
+        &standalone_question,
+        &conversation_history,
+        &request.timezone,
+    )
+    .await;
     tracing::info!("Extracted date range: {:?}", date_range);
 
-    // Step 3: Fetch conversations (with date filter if available)
-    let conversations = get_relevant_conversations(
-        &state.firestore,
-        &user.uid,
-        date_range.as_ref(),
-    ).await;
+    // Step 4: Fetch conversations (with date filter if available), most relevant first
+    let conversations = if scope.conversations {
+        get_relevant_conversations(
+            &state.firestore,
+            &user.uid,
+            date_range.as_ref(),
+            &keywords,
+        ).await
+    } else {
+        vec![]
+    };
 
-    // Step 4: Fetch user memories
-    let memories = get_user_memories(&state.firestore, &user.uid).await;
+    // Step 5: Fetch user memories
+    let memories = if scope.memories {
+        get_user_memories(&state.firestore, &user.uid).await
+    } else {
+        vec![]
+    };
 
-    // Step 5: Build context string for prompt (including conversation history and app context)
+    // Step 6: Build context string for prompt (including conversation history and app context)
     let (base_context, citation_sources) = build_context_string(&conversations, &memories, &request.timezone);
 
     // Add app-specific context if available
-    let context_with_app = if let Some((app_name, chat_prompt, persona_prompt)) = &app_context {
-        let mut app_section = format!("<app_context>\nYou are chatting as the \"{}\" assistant.\n", app_name);
-        if let Some(persona) = persona_prompt {
-            if !persona.is_empty() {
-                app_section.push_str(&format!("Persona: {}\n", persona));
-            }
-        }
-        if let Some(prompt) = chat_prompt {
-            if !prompt.is_empty() {
-                app_section.push_str(&format!("Instructions: {}\n", prompt));
-            }
-        }
-        app_section.push_str("</app_context>\n\n");
-        format!("{}{}", app_section, base_context)
-    } else {
-        base_context
-    };
+    let context_with_app = with_app_context(base_context, app.as_ref());
 
     let context_string = if request.messages.is_empty() {
         context_with_app
@@ -329,6 +410,10 @@ async fn get_chat_context(
             conversation_history, context_with_app
         )
     };
+    let context_string = with_screen_context(
+        with_attachment_context(context_string, attachment_context.as_deref()),
+        screen_context.as_ref(),
+    );
 
     tracing::info!(
         "Chat context: {} conversations, {} memories, {} prior messages, {} citation sources",
@@ -345,6 +430,9 @@ async fn get_chat_context(
         memories,
         context_string,
         citation_sources,
+        attachment_context,
+        screen_context,
+        app_id: app.map(|a| a.id),
     }))
 }
 
@@ -361,9 +449,9 @@ async fn generate_initial_message(
         request.app_id
     );
 
-    // Get API key for LLM calls
-    let api_key = match &state.config.gemini_api_key {
-        Some(key) => key.clone(),
+    // Get API key for LLM calls (the user's own key if they brought one)
+    let api_key = match state.llm_api_key(&user.uid).await? {
+        Some(key) => key,
         None => {
             tracing::warn!("No Gemini API key configured, returning default greeting");
             // Save and return default greeting
@@ -378,7 +466,7 @@ async fn generate_initial_message(
         }
     };
 
-    let llm = LlmClient::new(api_key);
+    let llm = LlmClient::new(api_key).with_task_models(state.llm_task_models().await);
 
     // Fetch user memories (top 10)
     let memories: Vec<String> = match state.firestore.get_memories(&user.uid, 10).await {
@@ -392,6 +480,7 @@ async fn generate_initial_message(
     // Get app persona if app_id provided
     let (app_name, app_persona) = if let Some(app_id) = &request.app_id {
         match state.firestore.get_app(&user.uid, app_id).await {
+            Ok(Some(app)) if state.app_blocked(&app).await => (None, None),
             Ok(Some(app)) => (Some(app.name), app.chat_prompt),
             Ok(None) => {
                 tracing::warn!("App not found: {}", app_id);
@@ -411,7 +500,11 @@ async fn generate_initial_message(
         .generate_initial_message(&memories, app_name.as_deref(), app_persona.as_deref())
         .await
     {
-        Ok(msg) => msg.trim().to_string(),
+        Ok(msg) => {
+            ResponseGuardrails::from_config(&state.config)
+                .apply(&state, &user.uid, msg.trim(), request.supports_markdown)
+                .await
+        }
         Err(e) => {
             tracing::error!("Failed to generate initial message: {}", e);
             "Hello! I'm here to help. What's on your mind?".to_string()
@@ -438,7 +531,7 @@ async fn save_and_return_greeting(
     greeting: &str,
 ) -> Result<Json<InitialMessageResponse>, StatusCode> {
     match state
-        .firestore
+        .storage
         .save_message(uid, greeting, "ai", app_id, Some(session_id), None)
         .await
     {
@@ -459,7 +552,7 @@ async fn save_and_return_greeting(
         }
         Err(e) => {
             tracing::error!("Failed to save initial message: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -477,9 +570,9 @@ async fn generate_session_title(
         request.messages.len()
     );
 
-    // Get API key for LLM calls
-    let api_key = match &state.config.gemini_api_key {
-        Some(key) => key.clone(),
+    // Get API key for LLM calls (the user's own key if they brought one)
+    let api_key = match state.llm_api_key(&user.uid).await? {
+        Some(key) => key,
         None => {
             tracing::warn!("No Gemini API key configured, returning default title");
             return Ok(Json(GenerateTitleResponse {
@@ -488,7 +581,7 @@ async fn generate_session_title(
         }
     };
 
-    let llm = LlmClient::new(api_key);
+    let llm = LlmClient::new(api_key).with_task_models(state.llm_task_models().await);
 
     // Convert messages to the format expected by the LLM
     let messages: Vec<(String, String)> = request
@@ -577,15 +670,43 @@ async fn check_requires_context(
     }
 }
 
+/// Rewrite the question as a standalone one using the chat history.
+/// Returns the question and its keywords; falls back to the original question.
+async fn resolve_question(
+    llm: &LlmClient,
+    question: &str,
+    conversation_history: &str,
+) -> (String, Vec<String>) {
+    let prompt = STANDALONE_QUESTION_PROMPT
+        .replace("{question}", question)
+        .replace("{conversation_history}", conversation_history);
+
+    match llm.rewrite_standalone_question(&prompt).await {
+        Ok((rewritten, keywords)) if !rewritten.trim().is_empty() => {
+            if rewritten != question {
+                tracing::info!("Resolved question: {}", truncate_str(&rewritten, 100));
+            }
+            (rewritten, keywords)
+        }
+        Ok(_) => (question.to_string(), Vec::new()),
+        Err(e) => {
+            tracing::warn!("Failed to resolve question references: {}", e);
+            (question.to_string(), Vec::new())
+        }
+    }
+}
+
 /// Extract date range from question using LLM
 async fn extract_date_range(
     llm: &LlmClient,
     question: &str,
+    conversation_history: &str,
     timezone: &str,
 ) -> Option<DateRange> {
     let now = Utc::now();
     let prompt = DATE_EXTRACTION_PROMPT
         .replace("{question}", question)
+        .replace("{conversation_history}", conversation_history)
         .replace("{current_datetime}", &now.to_rfc3339())
         .replace("{timezone}", timezone);
 
@@ -609,11 +730,13 @@ async fn extract_date_range(
     }
 }
 
-/// Get relevant conversations from Firestore
+/// Get relevant conversations from Firestore.
+/// Conversations mentioning more of `keywords` come first; otherwise newest first.
 async fn get_relevant_conversations(
     firestore: &Arc<FirestoreService>,
     uid: &str,
     date_range: Option<&DateRange>,
+    keywords: &[String],
 ) -> Vec<ConversationSummary> {
     // Fetch recent completed conversations
     let statuses = vec!["completed".to_string()];
@@ -622,21 +745,28 @@ async fn get_relevant_conversations(
         .get_conversations(uid, 50, 0, false, &statuses, None, None, None, None)
         .await
     {
-        Ok(conversations) => {
+        Ok(mut conversations) => {
             // Filter by date range if provided
-            let filtered: Vec<_> = if let Some(range) = date_range {
-                conversations
-                    .into_iter()
-                    .filter(|c| c.created_at >= range.start && c.created_at <= range.end)
-                    .take(20)
-                    .collect()
-            } else {
-                conversations.into_iter().take(20).collect()
-            };
+            if let Some(range) = date_range {
+                conversations.retain(|c| c.created_at >= range.start && c.created_at <= range.end);
+            }
+            // Stable sort keeps recency order among equally relevant conversations
+            conversations.sort_by_cached_key(|c| {
+                std::cmp::Reverse(keyword_matches(
+                    &format!("{} {}", c.structured.title, c.structured.overview),
+                    keywords,
+                ))
+            });
 
-            filtered
+            conversations
                 .into_iter()
+                .take(20)
                 .map(|c| ConversationSummary {
+                    // Stored at processing time; older conversations fall back until backfilled
+                    context_line: c
+                        .context_line
+                        .clone()
+                        .unwrap_or_else(|| c.structured.context_line()),
                     id: c.id,
                     title: c.structured.title,
                     overview: c.structured.overview,
@@ -653,6 +783,15 @@ async fn get_relevant_conversations(
     }
 }
 
+/// Number of keywords that appear in `text` (case-insensitive)
+fn keyword_matches(text: &str, keywords: &[String]) -> usize {
+    let text = text.to_lowercase();
+    keywords
+        .iter()
+        .filter(|k| !k.trim().is_empty() && text.contains(&k.trim().to_lowercase()))
+        .count()
+}
+
 /// Get user memories from Firestore
 async fn get_user_memories(firestore: &Arc<FirestoreService>, uid: &str) -> Vec<MemorySummary> {
     match firestore.get_memories(uid, 50).await {
@@ -704,14 +843,7 @@ fn build_context_string(
             } else {
                 conv.created_at.format("%Y-%m-%d").to_string()
             };
-            conv_lines.push(format!(
-                "[{}] {} {} - {} ({})",
-                index,
-                conv.emoji,
-                conv.title,
-                conv.overview,
-                date_str
-            ));
+            conv_lines.push(format!("[{}] {} ({})", index, conv.context_line, date_str));
 
             // Track citation source
             citation_sources.push(CitationSource {
@@ -762,6 +894,8 @@ async fn get_basic_context(
     uid: &str,
     user_name: &str,
     request: &ChatContextRequest,
+    app: Option<&App>,
+    screen_context: Option<ScreenContextSnapshot>,
 ) -> Result<Json<ChatContextResponse>, StatusCode> {
     let now = Utc::now();
     let date_range = DateRange {
@@ -769,47 +903,31 @@ async fn get_basic_context(
         end: now,
     };
 
-    // Fetch app context if app_id provided
-    let app_context = if let Some(app_id) = &request.app_id {
-        match firestore.get_app(uid, app_id).await {
-            Ok(Some(app)) => Some((app.name, app.chat_prompt, app.persona_prompt)),
-            _ => None,
-        }
+    let scope = app.map_or(ChatDataScope::ALL, App::permitted_chat_data_scope);
+    let conversations = if scope.conversations {
+        get_relevant_conversations(firestore, uid, Some(&date_range), &[]).await
     } else {
-        None
+        vec![]
+    };
+    let memories = if scope.memories {
+        get_user_memories(firestore, uid).await
+    } else {
+        vec![]
     };
-
-    let conversations = get_relevant_conversations(firestore, uid, Some(&date_range)).await;
-    let memories = get_user_memories(firestore, uid).await;
 
     // Include conversation history in context string
     let conversation_history = format_conversation_history(&request.messages, user_name);
     let (base_context, citation_sources) = build_context_string(&conversations, &memories, &request.timezone);
 
     // Add app-specific context if available
-    let context_with_app = if let Some((app_name, chat_prompt, persona_prompt)) = &app_context {
-        let mut app_section = format!("<app_context>\nYou are chatting as the \"{}\" assistant.\n", app_name);
-        if let Some(persona) = persona_prompt {
-            if !persona.is_empty() {
-                app_section.push_str(&format!("Persona: {}\n", persona));
-            }
-        }
-        if let Some(prompt) = chat_prompt {
-            if !prompt.is_empty() {
-                app_section.push_str(&format!("Instructions: {}\n", prompt));
-            }
-        }
-        app_section.push_str("</app_context>\n\n");
-        format!("{}{}", app_section, base_context)
-    } else {
-        base_context
-    };
+    let context_with_app = with_app_context(base_context, app);
 
     let context_string = if request.messages.is_empty() {
         context_with_app
     } else {
         format!("<current_conversation>\n{}\n</current_conversation>\n\n{}", conversation_history, context_with_app)
     };
+    let context_string = with_screen_context(context_string, screen_context.as_ref());
 
     Ok(Json(ChatContextResponse {
         requires_context: true,
@@ -818,16 +936,156 @@ async fn get_basic_context(
         memories,
         context_string,
         citation_sources,
+        attachment_context: None,
+        screen_context,
+        app_id: app.map(|a| a.id.clone()),
     }))
 }
 
+/// Max total image bytes sent inline to the vision model
+const MAX_VISION_BYTES: i64 = 15 * 1024 * 1024;
+
+/// Load the message's image attachments and describe them with the vision model
+async fn describe_attachments(
+    state: &AppState,
+    llm: &LlmClient,
+    uid: &str,
+    question: &str,
+    attachment_ids: &[String],
+) -> Option<String> {
+    let bucket = state.config.chat_attachments_bucket.as_deref()?;
+
+    let attachments = match state.firestore.get_chat_attachments(uid, attachment_ids).await {
+        Ok(attachments) => attachments,
+        Err(e) => {
+            tracing::warn!("Failed to load chat attachments: {}", e);
+            return None;
+        }
+    };
+
+    let mut images = vec![];
+    let mut total_bytes = 0;
+    for attachment in attachments.iter().filter(|a| a.is_image()) {
+        total_bytes += attachment.size;
+        if total_bytes > MAX_VISION_BYTES {
+            tracing::warn!("Skipping attachment {}: vision input limit reached", attachment.id);
+            break;
+        }
+        match state.firestore.download_chat_attachment(bucket, attachment).await {
+            Ok(data) => images.push(InlineImage {
+                name: attachment.name.clone(),
+                mime_type: attachment.mime_type.clone(),
+                data,
+            }),
+            Err(e) => tracing::warn!("Failed to download attachment {}: {}", attachment.id, e),
+        }
+    }
+
+    // Non-image files are listed by name so the assistant knows they exist
+    let others: Vec<&str> = attachments
+        .iter()
+        .filter(|a| !a.is_image())
+        .map(|a| a.name.as_str())
+        .collect();
+
+    let mut sections = vec![];
+    if !images.is_empty() {
+        match llm.describe_chat_attachments(question, &images).await {
+            Ok(description) if !description.is_empty() => sections.push(description),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to describe chat attachments: {}", e),
+        }
+    }
+    if !others.is_empty() {
+        sections.push(format!("Other attached files: {}", others.join(", ")));
+    }
+
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections.join("\n\n"))
+    }
+}
+
+/// Prepend the user's current screen to the prompt context string
+/// The chat app for this request: `app_id`, else the chat session's app.
+/// Apps that don't support chat are ignored.
+async fn load_chat_app(state: &AppState, uid: &str, request: &ChatContextRequest) -> Option<App> {
+    let firestore = &state.firestore;
+    let app_id = match (&request.app_id, &request.session_id) {
+        (Some(app_id), _) => app_id.clone(),
+        (None, Some(session_id)) => match firestore.get_chat_session(uid, session_id).await {
+            Ok(Some(session)) => session.app_id?,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!("Failed to fetch chat session {}: {}", session_id, e);
+                return None;
+            }
+        },
+        (None, None) => return None,
+    };
+
+    match firestore.get_app(uid, &app_id).await {
+        Ok(Some(app)) if app.works_with_chat() => {
+            if state.app_blocked(&app).await {
+                return None;
+            }
+            tracing::info!("Loaded app: {} ({})", app.name, app_id);
+            Some(app)
+        }
+        Ok(Some(_)) => {
+            tracing::warn!("App {} does not support chat", app_id);
+            None
+        }
+        Ok(None) => {
+            tracing::warn!("App not found: {}", app_id);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Failed to fetch app {}: {}", app_id, e);
+            None
+        }
+    }
+}
+
+/// Prepend the app persona block so app chats get consistent prompts
+fn with_app_context(context: String, app: Option<&App>) -> String {
+    let Some(app) = app else {
+        return context;
+    };
+    let mut app_section = format!("<app_context>\nYou are chatting as the \"{}\" assistant.\n", app.name);
+    if let Some(persona) = app.persona_prompt.as_deref().filter(|p| !p.is_empty()) {
+        app_section.push_str(&format!("Persona: {}\n", persona));
+    }
+    if let Some(prompt) = app.chat_prompt.as_deref().filter(|p| !p.is_empty()) {
+        app_section.push_str(&format!("Instructions: {}\n", prompt));
+    }
+    app_section.push_str("</app_context>\n\n");
+    format!("{}{}", app_section, context)
+}
+
+fn with_screen_context(context: String, screen_context: Option<&ScreenContextSnapshot>) -> String {
+    match screen_context {
+        Some(snapshot) => format!("{}\n\n{}", snapshot.to_context_string(), context),
+        None => context,
+    }
+}
+
+/// Prepend attachment context to the prompt context string
+fn with_attachment_context(context: String, attachment_context: Option<&str>) -> String {
+    match attachment_context {
+        Some(attachments) => format!("<attachments>\n{}\n</attachments>\n\n{}", attachments, context),
+        None => context,
+    }
+}
+
 // ============================================================================
 // ROUTER
 // ============================================================================
 
 pub fn chat_routes() -> Router<AppState> {
     Router::new()
-        .route("/v2/chat-context", post(get_chat_context))
-        .route("/v2/chat/initial-message", post(generate_initial_message))
-        .route("/v2/chat/generate-title", post(generate_session_title))
+        .route("/v2/chat-context", with_llm_limit(post(get_chat_context)))
+        .route("/v2/chat/initial-message", with_llm_limit(post(generate_initial_message)))
+        .route("/v2/chat/generate-title", with_llm_limit(post(generate_session_title)))
 }
@@ -36,7 +36,7 @@ async fn create_chat_session(
         Ok(session) => Ok(Json(session)),
         Err(e) => {
             tracing::error!("Failed to create chat session: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -96,7 +96,7 @@ async fn get_chat_session(
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to get chat session: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -129,11 +129,7 @@ async fn update_chat_session(
         Ok(session) => Ok(Json(session)),
         Err(e) => {
             tracing::error!("Failed to update chat session: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(e.http_status())
         }
     }
 }
@@ -161,7 +157,7 @@ async fn delete_chat_session(
         })),
         Err(e) => {
             tracing::error!("Failed to delete chat session: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -0,0 +1,155 @@
+// Conversation template routes - Summary structure for recurring meeting types
+// Endpoints: GET/POST /v1/conversation-templates, PUT/DELETE /v1/conversation-templates/:id
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::Utc;
+
+use crate::auth::AuthUser;
+use crate::models::{ConversationTemplate, SaveConversationTemplateRequest, MAX_TEMPLATES};
+use crate::services::conversation_templates;
+use crate::AppState;
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    tracing::error!("Conversation template error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to access templates".to_string())
+}
+
+/// Validate the request, including that every assigned folder exists
+async fn validated(
+    state: &AppState,
+    uid: &str,
+    request: SaveConversationTemplateRequest,
+) -> Result<SaveConversationTemplateRequest, (StatusCode, String)> {
+    let request = conversation_templates::clean_request(request)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    if !request.folder_ids.is_empty() {
+        let folders = state.firestore.get_folders(uid).await.map_err(internal_error)?;
+        if let Some(missing) = request
+            .folder_ids
+            .iter()
+            .find(|id| !folders.iter().any(|f| &f.id == *id))
+        {
+            return Err((StatusCode::BAD_REQUEST, format!("Folder {} not found", missing)));
+        }
+    }
+    Ok(request)
+}
+
+/// GET /v1/conversation-templates - List the user's templates
+async fn list_templates(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<ConversationTemplate>>, (StatusCode, String)> {
+    state
+        .firestore
+        .get_conversation_templates(&user.uid)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// POST /v1/conversation-templates - Create a template
+async fn create_template(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<SaveConversationTemplateRequest>,
+) -> Result<Json<ConversationTemplate>, (StatusCode, String)> {
+    let request = validated(&state, &user.uid, request).await?;
+    let existing = state
+        .firestore
+        .get_conversation_templates(&user.uid)
+        .await
+        .map_err(internal_error)?;
+    if existing.len() >= MAX_TEMPLATES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} templates are allowed", MAX_TEMPLATES),
+        ));
+    }
+
+    let now = Utc::now();
+    let template = ConversationTemplate {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name,
+        summary_sections: request.summary_sections,
+        summary_instructions: request.summary_instructions,
+        action_item_conventions: request.action_item_conventions,
+        folder_ids: request.folder_ids,
+        title_patterns: request.title_patterns,
+        created_at: now,
+        updated_at: now,
+    };
+    state
+        .firestore
+        .save_conversation_template(&user.uid, &template)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(template))
+}
+
+/// PUT /v1/conversation-templates/:id - Replace a template
+async fn update_template(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(template_id): Path<String>,
+    Json(request): Json<SaveConversationTemplateRequest>,
+) -> Result<Json<ConversationTemplate>, (StatusCode, String)> {
+    let request = validated(&state, &user.uid, request).await?;
+    let existing = state
+        .firestore
+        .get_conversation_templates(&user.uid)
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .find(|t| t.id == template_id)
+        .ok_or((StatusCode::NOT_FOUND, "Template not found".to_string()))?;
+
+    let template = ConversationTemplate {
+        id: existing.id,
+        name: request.name,
+        summary_sections: request.summary_sections,
+        summary_instructions: request.summary_instructions,
+        action_item_conventions: request.action_item_conventions,
+        folder_ids: request.folder_ids,
+        title_patterns: request.title_patterns,
+        created_at: existing.created_at,
+        updated_at: Utc::now(),
+    };
+    state
+        .firestore
+        .save_conversation_template(&user.uid, &template)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(template))
+}
+
+/// DELETE /v1/conversation-templates/:id - Delete a template
+async fn delete_template(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(template_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .firestore
+        .delete_conversation_template(&user.uid, &template_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn conversation_templates_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/v1/conversation-templates",
+            get(list_templates).post(create_template),
+        )
+        .route(
+            "/v1/conversation-templates/:template_id",
+            put(update_template).delete(delete_template),
+        )
+}
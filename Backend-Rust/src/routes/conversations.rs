@@ -1,20 +1,48 @@
 // Conversations routes - Port from Python backend
-// Endpoints: GET /v1/conversations, POST /v1/conversations/from-segments, POST /v1/conversations/:id/reprocess
+// Endpoints: GET /v1/conversations, POST /v1/conversations/from-segments, POST /v1/conversations/:id/reprocess,
+//            PATCH /v1/conversations/:id/events, POST /v1/conversations/:id/action-items/reconcile,
+//            PATCH /v1/conversations/:id/memory-extraction, GET /v1/conversations/:id/export,
+//            POST /v1/conversations/:id/draft-email, GET /v1/conversations/:id/analytics,
+//            POST /v1/conversations/:id/estimate, GET /v1/conversations/processing-status,
+//            GET /v1/conversations/:id/transcript/search, GET /v1/conversations/auto-discarded,
+//            GET /v1/conversations/:id/history, POST /v1/conversations/:id/history/:edit_id/restore,
+//            POST /v1/conversations/:id/reprocess-all, PUT /v1/conversations/:id/feedback,
+//            GET /v1/conversations/calendar, GET /v1/conversations/:id/app-results
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    routing::{get, patch, post},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post, put},
     Json, Router,
 };
 
 use serde::{Deserialize, Serialize};
 
 use crate::auth::AuthUser;
-use crate::llm::LlmClient;
+use crate::client_info::ClientInfo;
+use crate::conversation_lock::{self, with_conversation_lock, LockError};
+use crate::llm_limit::with_llm_limit;
+use crate::body_limit::{with_body_limit, LARGE_JSON_BODY_LIMIT_BYTES};
+use crate::etag::with_etag;
+use crate::job_queue::JobPriority;
+use crate::llm::chunking::{ProcessingProgress, ProgressCallback};
+use crate::llm::estimate::{self, ProcessingEstimate};
+use crate::llm::{prompts, titles, LlmClient, TaskKind};
+use crate::services::firestore::FirestoreError;
+use crate::services::conversation_export::{self, ExportFormat, SpeakerNames};
+use crate::services::events::AppEvent;
+use crate::services::reprocess_all::{self, AppReprocessState, AppReprocessStatus, ReprocessAllProgress};
+use crate::services::shadow::{self, ShadowCandidate, ShadowInput};
+use crate::services::transcript_limits::TranscriptLimits;
+use crate::services::transcript_search::{self, TranscriptHit};
+use crate::services::{auto_discard, conversation_analytics, conversation_calendar, conversation_history, conversation_processing, conversation_templates, focus_context, folder_rules, language, llm_quality, mailer};
 use crate::models::{
-    Conversation, ConversationSource, ConversationStatus, CreateConversationRequest,
-    CreateConversationResponse, Structured, TranscriptSegment,
+    normalize_action_item_description, ActionItemDB, AppResult, AppScope, ReconcileActionItemsResponse, Conversation, ConversationAnalytics, ConversationCalendarQuery, ConversationCalendarResponse, ConversationEdit, ConversationEditField, ConversationFeedback, ConversationFeedbackRequest, ConversationHistoryResponse, ConversationSource, ConversationStatus, CreateConversationRequest,
+    CreateConversationResponse, DiscardReason, DraftFollowUpEmailRequest, DraftFollowUpEmailResponse,
+    EmailSendRecord, FollowUpEmailDraft, StoredEmailDraft, LlmProvenance, MAX_CONVERSATION_EDITS, MAX_FEEDBACK_COMMENT_CHARS, QualityRatingKind,
+    SetConversationEventsStateRequest, SetMemoryExtractionRequest,
+    SetMemoryExtractionResponse, ProcessingPromptSettings, Structured, TranscriptSegment, TruncationStrategy,
 };
 use crate::AppState;
 
@@ -77,7 +105,7 @@ async fn get_conversations(
     );
 
     match state
-        .firestore
+        .storage
         .get_conversations(
             &user.uid,
             query.limit,
@@ -106,7 +134,7 @@ async fn get_conversations(
         }
         Err(e) => {
             tracing::error!("Failed to get conversations: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get conversations: {}", e)))
+            Err(e.public_error())
         }
     }
 }
@@ -124,6 +152,89 @@ pub struct ConversationsCountResponse {
     pub count: i64,
 }
 
+#[derive(Serialize)]
+struct ProcessingStatusResponse {
+    /// Progress of the user's in-flight long-transcript processing (null when idle)
+    processing: Option<ProcessingProgress>,
+    /// Per-app status of reprocess-all runs from the last hour, newest first
+    reprocess: Vec<ReprocessAllProgress>,
+}
+
+/// GET /v1/conversations/processing-status - Chunk progress while a long transcript is processed
+async fn get_processing_status(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<ProcessingStatusResponse>, (StatusCode, String)> {
+    let Some(redis) = &state.redis else {
+        return Ok(Json(ProcessingStatusResponse { processing: None, reprocess: Vec::new() }));
+    };
+    let (processing, reprocess) =
+        tokio::join!(redis.get_processing_progress(&user.uid), redis.get_reprocess_progress(&user.uid));
+    let map_err = |e: redis::RedisError| {
+        tracing::error!("Failed to get processing progress: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    };
+    Ok(Json(ProcessingStatusResponse {
+        processing: processing.map_err(map_err)?,
+        reprocess: reprocess.map_err(map_err)?,
+    }))
+}
+
+/// Max transcript characters in an auto-discarded conversation preview
+const DISCARD_PREVIEW_CHARS: usize = 200;
+
+#[derive(Deserialize)]
+pub struct AutoDiscardedQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+/// Review queue entry for an automatically discarded conversation
+#[derive(Serialize)]
+struct AutoDiscardedConversation {
+    id: String,
+    started_at: chrono::DateTime<chrono::Utc>,
+    finished_at: chrono::DateTime<chrono::Utc>,
+    discard_reason: DiscardReason,
+    segment_count: usize,
+    /// Start of the transcript, so the user can tell what was recorded
+    preview: String,
+}
+
+/// GET /v1/conversations/auto-discarded - Review queue of conversations discarded
+/// by the auto-discard heuristic, newest first
+async fn get_auto_discarded_conversations(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<AutoDiscardedQuery>,
+) -> Result<Json<Vec<AutoDiscardedConversation>>, (StatusCode, String)> {
+    let conversations = state
+        .storage
+        .get_auto_discarded_conversations(&user.uid, query.limit.clamp(1, 200))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get auto-discarded conversations: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(
+        conversations
+            .into_iter()
+            .filter_map(|c| {
+                let transcript = TranscriptSegment::to_transcript_text(&c.transcript_segments);
+                Some(AutoDiscardedConversation {
+                    discard_reason: c.discard_reason?,
+                    preview: transcript.chars().take(DISCARD_PREVIEW_CHARS).collect(),
+                    segment_count: c.transcript_segments.len(),
+                    id: c.id,
+                    started_at: c.started_at,
+                    finished_at: c.finished_at,
+                })
+            })
+            .collect(),
+    ))
+}
+
 /// GET /v1/conversations/count - Get count of user conversations
 async fn get_conversations_count(
     State(state): State<AppState>,
@@ -144,14 +255,37 @@ async fn get_conversations_count(
     );
 
     match state
-        .firestore
+        .storage
         .get_conversations_count(&user.uid, query.include_discarded, &statuses)
         .await
     {
         Ok(count) => Ok(Json(ConversationsCountResponse { count })),
         Err(e) => {
             tracing::error!("Failed to get conversations count: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get conversations count: {}", e)))
+            Err(e.public_error())
+        }
+    }
+}
+
+/// GET /v1/conversations/calendar?year=&month= - Per-day conversation counts and talk
+/// minutes for a month (activity heatmap), without fetching conversation bodies
+async fn get_conversations_calendar(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<ConversationCalendarQuery>,
+) -> Result<Json<ConversationCalendarResponse>, (StatusCode, String)> {
+    if !(2000..=2100).contains(&query.year) {
+        return Err((StatusCode::BAD_REQUEST, "year must be between 2000 and 2100".to_string()));
+    }
+    let timezone = query.timezone.as_deref().map(str::trim).filter(|tz| !tz.is_empty());
+    tracing::info!("Getting conversations calendar {}-{:02} for user {}", query.year, query.month, user.uid);
+
+    match conversation_calendar::get_calendar(&state, &user.uid, query.year, query.month, timezone).await {
+        Ok(Some(calendar)) => Ok(Json(calendar)),
+        Ok(None) => Err((StatusCode::BAD_REQUEST, "month must be between 1 and 12".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversations calendar: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to get conversations calendar".to_string()))
         }
     }
 }
@@ -169,14 +303,169 @@ async fn create_conversation_from_segments(
         request.transcript_segments.len()
     );
 
+    // Bring the transcript within the configured limits
+    let limits = TranscriptLimits {
+        max_segments: state.config.transcript_max_segments,
+        max_tokens: state.config.transcript_max_tokens,
+    };
+    let strategy = request.truncation.unwrap_or(state.config.transcript_truncation);
+    let (mut transcript_segments, truncation) = limits
+        .apply(request.transcript_segments.clone(), strategy)
+        .map_err(|e| {
+            tracing::warn!("Rejecting transcript for user {}: {}", user.uid, e);
+            (StatusCode::PAYLOAD_TOO_LARGE, e.to_string())
+        })?;
+    if let Some(truncation) = &truncation {
+        tracing::info!(
+            "Transcript over limits for user {} ({:?}): kept {}/{} segments",
+            user.uid,
+            truncation.strategy,
+            truncation.kept_segments,
+            truncation.original_segments
+        );
+    }
+    let truncated = truncation.as_ref().is_some_and(|t| t.kept_segments < t.original_segments);
+    let always_condense = truncation.as_ref().is_some_and(|t| t.strategy == TruncationStrategy::Summarize);
+
+    // Tag each segment with its spoken language; mixed-language users get per-segment tags
+    language::tag_segment_languages(&mut transcript_segments);
+    let detected_languages = language::conversation_languages(&transcript_segments);
+    let summary_language = language::summary_language(&request.language, &detected_languages);
+    if summary_language != request.language {
+        tracing::info!(
+            "Detected languages {:?} differ from requested '{}', summarizing in '{}'",
+            detected_languages,
+            request.language,
+            summary_language
+        );
+    }
+
     // Only process desktop-originated conversations with LLM.
     // Non-desktop sources (omi, bee, etc.) are fully handled by the Python backend.
     let is_desktop = request.source == ConversationSource::Desktop;
 
-    let processed = if is_desktop {
+    // User's custom summary instructions, title and auto-discard preferences (optional)
+    let settings = if is_desktop {
+        state
+            .storage
+            .get_processing_settings(&user.uid)
+            .await
+            .unwrap_or_default()
+    } else {
+        ProcessingPromptSettings::default()
+    };
+    let task_models = state.llm_task_models().await;
+
+    // Folder the client filed the conversation in; an unknown one is ignored rather than
+    // failing the upload
+    let folder_id = match request.folder_id.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        Some(folder_id) => match state.storage.get_folders(&user.uid).await {
+            Ok(folders) if folders.iter().any(|f| f.id == folder_id) => Some(folder_id.to_string()),
+            Ok(_) => {
+                tracing::warn!("Ignoring unknown folder {} for new conversation", folder_id);
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Failed to check folder {}: {}", folder_id, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Template for recurring meeting types: explicit, by folder, or by title hint
+    let template = if is_desktop {
+        let templates = state
+            .storage
+            .get_conversation_templates(&user.uid)
+            .await
+            .unwrap_or_default();
+        conversation_templates::resolve(
+            &templates,
+            request.template_id.as_deref(),
+            folder_id.as_deref(),
+            request.title_hint.as_deref(),
+        )
+        .cloned()
+    } else {
+        None
+    };
+
+    // The user's own key if they brought one, else the server key
+    let llm_api_key = if is_desktop { state.llm_api_key(&user.uid).await? } else { None };
+
+    // Accidental desktop recordings are saved as discarded for review, without processing
+    let discard_reason = if is_desktop {
+        let classifier = llm_api_key
+            .as_ref()
+            .map(|key| LlmClient::new(key.clone()).with_task_models(task_models.clone()));
+        auto_discard::discard_reason(classifier.as_ref(), &transcript_segments, settings.auto_discard).await
+    } else {
+        None
+    };
+
+    // Generate conversation ID
+    let conversation_id = uuid::Uuid::new_v4().to_string();
+
+    let processed = if let Some(reason) = discard_reason {
+        tracing::info!("Auto-discarding conversation for user {} ({})", user.uid, reason.as_str());
+        LlmClient::skip_extraction()
+    } else if is_desktop {
+        let recent_titles = state
+            .storage
+            .get_recent_conversation_titles(&user.uid, titles::MAX_RECENT_TITLES)
+            .await
+            .unwrap_or_default();
+        let blocked_topics = state
+            .storage
+            .get_blocked_memory_topics(&user.uid)
+            .await
+            .unwrap_or_default();
+
+        // Long transcripts publish chunk progress for GET /v1/conversations/processing-status.
+        // A single writer task keeps updates in order and finishes before the status is cleared.
+        let mut progress_writer = None;
+        let mut progress_callback: Option<ProgressCallback> = None;
+        if let Some(redis) = state.redis.clone() {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let uid = user.uid.clone();
+            progress_writer = Some(tokio::spawn(async move {
+                while let Some(progress) = rx.recv().await {
+                    if let Err(e) = redis.store_processing_progress(&uid, &progress).await {
+                        tracing::warn!("Failed to store processing progress: {}", e);
+                    }
+                }
+                let _ = redis.clear_processing_progress(&uid).await;
+            }));
+            progress_callback = Some(std::sync::Arc::new(move |progress| {
+                let _ = tx.send(progress);
+            }));
+        }
+
         // Get LLM client (Gemini)
-        let llm_client = if let Some(api_key) = &state.config.gemini_api_key {
-            LlmClient::new(api_key.clone())
+        let (llm_client, shadow_run) = if let Some(api_key) = &llm_api_key {
+            let llm = LlmClient::new(api_key.clone())
+                .with_task_models(task_models)
+                .with_custom_processing_prompt(Some(&settings.prompt))
+                .with_template(template.as_ref())
+                .with_memory_extraction(!request.memory_extraction_disabled)
+                .with_blocked_topics(&blocked_topics)
+                .with_recent_titles(recent_titles)
+                .with_plain_titles(settings.plain_titles)
+                .with_always_condense(always_condense);
+            // A sample of requests on the server key also runs the shadow candidate.
+            // Cloned before the progress callback so only production reports progress.
+            let shadow_run = ShadowCandidate::from_config(&state.config)
+                .filter(|c| c.sampled(&conversation_id) && llm_api_key == state.config.gemini_api_key)
+                .map(|candidate| {
+                    let client = candidate.client(&llm);
+                    (candidate, client)
+                });
+            let llm = match progress_callback {
+                Some(callback) => llm.with_progress(callback),
+                None => llm,
+            };
+            (llm, shadow_run)
         } else {
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -186,7 +475,7 @@ async fn create_conversation_from_segments(
 
         // Get existing data for deduplication
         let existing_memories = state
-            .firestore
+            .storage
             .get_memories(&user.uid, 500)
             .await
             .unwrap_or_default();
@@ -194,8 +483,8 @@ async fn create_conversation_from_segments(
         // Fetch recent action items + staged tasks for dedup context
         let two_days_ago = (chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339();
         let mut existing_action_items: Vec<crate::models::ActionItem> = state
-            .firestore
-            .get_action_items(&user.uid, 50, 0, None, None, Some(&two_days_ago), None, None, None, None, None)
+            .storage
+            .get_action_items(&user.uid, 50, 0, None, None, Some(&two_days_ago), None, None, None, None, None, true)
             .await
             .unwrap_or_default()
             .into_iter()
@@ -205,17 +494,23 @@ async fn create_conversation_from_segments(
                 due_at: db_item.due_at,
                 confidence: None,
                 priority: db_item.priority,
+                action_item_id: Some(db_item.id),
+                due_at_confidence: None,
+                due_phrase: None,
             })
             .collect();
 
         // Also include staged tasks (recent extractions not yet promoted)
-        if let Ok(staged) = state.firestore.get_staged_tasks(&user.uid, 50, 0).await {
+        if let Ok(staged) = state.storage.get_staged_tasks(&user.uid, 50, 0).await {
             existing_action_items.extend(staged.into_iter().map(|s| crate::models::ActionItem {
                 description: s.description,
                 completed: false,
                 due_at: s.due_at,
                 confidence: None,
                 priority: s.priority,
+                action_item_id: None,
+                due_at_confidence: None,
+                due_phrase: None,
             }));
         }
 
@@ -223,40 +518,79 @@ async fn create_conversation_from_segments(
         let started_at = request.started_at.to_rfc3339();
         let user_name = user.name.as_deref().unwrap_or("User");
 
-        llm_client
+        let processing_started = std::time::Instant::now();
+        let processed = llm_client
             .process_conversation(
-                &request.transcript_segments,
+                &transcript_segments,
                 &started_at,
                 &request.timezone,
-                &request.language,
+                &summary_language,
                 user_name,
                 &existing_action_items,
                 &existing_memories,
             )
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to process conversation: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            })?
+            .await;
+        let baseline_model = llm_client.model_for(TaskKind::Summary).to_string();
+        // Dropping the client closes the progress channel; the writer then clears the status
+        drop(llm_client);
+        if let Some(writer) = progress_writer {
+            let _ = writer.await;
+        }
+        let processed = processed.map_err(|e| {
+            tracing::error!("Failed to process conversation: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+
+        if let Some((candidate, client)) = shadow_run.filter(|_| !processed.discarded) {
+            let baseline = shadow::output(
+                &processed,
+                &baseline_model,
+                prompts::prompt_version().to_string(),
+                processing_started,
+            );
+            let input = ShadowInput {
+                uid: user.uid.clone(),
+                conversation_id: conversation_id.clone(),
+                segments: transcript_segments.clone(),
+                started_at,
+                timezone: request.timezone.clone(),
+                language: summary_language.clone(),
+                user_name: user_name.to_string(),
+                existing_action_items,
+                existing_memories,
+            };
+            shadow::spawn_shadow_run(state.firestore.clone(), candidate, client, input, baseline);
+        }
+        processed
     } else {
         // Non-desktop: skip all LLM extraction (Python backend handles it)
         tracing::info!("Skipping LLM extraction for non-desktop source {:?}", request.source);
         LlmClient::skip_extraction()
     };
 
-    // Generate conversation ID
-    let conversation_id = uuid::Uuid::new_v4().to_string();
-
     if processed.discarded {
         return Ok(Json(CreateConversationResponse {
             id: conversation_id,
             status: "completed".to_string(),
             discarded: true,
+            truncated,
+            truncation: truncation.clone(),
         }));
     }
 
+    let analytics = conversation_analytics::compute_analytics(&transcript_segments);
+    let focus_sessions = if discard_reason.is_none() {
+        focus_context::sessions_for_conversation(state.storage.as_ref(), &user.uid, request.started_at, request.finished_at).await
+    } else {
+        vec![]
+    };
+
+    // Skipped extraction generates nothing, so there's no prompt version to record
+    let prompt_version =
+        (!processed.generated_by.is_empty()).then(|| prompts::prompt_version().to_string());
+
     // Create conversation object
-    let conversation = Conversation {
+    let mut conversation = Conversation {
         id: conversation_id.clone(),
         created_at: request.started_at,
         started_at: request.started_at,
@@ -264,98 +598,74 @@ async fn create_conversation_from_segments(
         source: request.source.clone(),
         language: request.language.clone(),
         status: ConversationStatus::Completed,
-        discarded: false,
+        discarded: discard_reason.is_some(),
+        discard_reason,
         deleted: false,
         starred: false,
         is_locked: false,
         structured: processed.structured,
-        transcript_segments: request.transcript_segments.clone(),
+        transcript_segments,
         apps_results: vec![],
-        folder_id: None,
+        folder_id,
         geolocation: None,
         photos: vec![],
         input_device_name: request.input_device_name.clone(),
+        memory_extraction_disabled: request.memory_extraction_disabled,
+        notion_sync: None,
+        detected_languages,
+        analytics,
+        context_line: None,
+        generated_by: processed.generated_by,
+        template_id: template.filter(|_| discard_reason.is_none()).map(|t| t.id),
+        transcript_deleted_at: None,
+        focus_sessions,
+        prompt_version,
+        feedback: None,
     };
 
-    // Save conversation
-    if let Err(e) = state.firestore.save_conversation(&user.uid, &conversation).await {
-        tracing::error!("Failed to save conversation: {}", e);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+    // The user's folder rules file conversations saved without an explicit folder
+    if conversation.folder_id.is_none() && !conversation.discarded {
+        conversation.folder_id =
+            folder_rules::folder_for_new_conversation(state.storage.as_ref(), &user.uid, &conversation).await;
     }
 
-    // Save action items as staged tasks (go through ranking/promotion pipeline)
-    if !processed.action_items.is_empty() {
-        let source_str = format!("transcription:{:?}", request.source).to_lowercase();
-        for item in &processed.action_items {
-            if let Err(e) = state
-                .firestore
-                .create_staged_task(
-                    &user.uid,
-                    &item.description,
-                    item.due_at,
-                    Some(&source_str),
-                    item.priority.as_deref(),
-                    None, // metadata
-                    None, // category
-                    None, // relevance_score - will be ranked by prioritization service
-                )
-                .await
-            {
-                tracing::error!("Failed to save staged task: {}", e);
-            }
-        }
-        tracing::info!(
-            "Saved {} action items as staged tasks for conversation {}",
-            processed.action_items.len(),
-            conversation_id
-        );
+    // Save the conversation, its action items (as staged tasks) and its memories
+    if let Err(e) = conversation_processing::save_processed_conversation(
+        state.storage.as_ref(),
+        &user.uid,
+        &conversation,
+        &processed.action_items,
+        &processed.memories,
+    )
+    .await
+    {
+        tracing::error!("Failed to save conversation: {}", e);
+        return Err(e.public_error());
     }
 
-    // Save memories
-    if !processed.memories.is_empty() {
-        if let Err(e) = state
-            .firestore
-            .save_memories(&user.uid, &conversation_id, &processed.memories)
-            .await
-        {
-            tracing::error!("Failed to save memories: {}", e);
-        }
+    // Auto-discarded: kept only for the review queue (GET /v1/conversations/auto-discarded)
+    if conversation.discarded {
+        return Ok(Json(CreateConversationResponse {
+            id: conversation_id,
+            status: "completed".to_string(),
+            discarded: true,
+            truncated,
+            truncation: truncation.clone(),
+        }));
     }
 
-    // Trigger external integrations (async, don't block response)
-    let integrations = state.integrations.clone();
-    let firestore = state.firestore.clone();
-    let uid = user.uid.clone();
-    let conv_for_trigger = conversation.clone();
-
-    tokio::spawn(async move {
-        // Get user's enabled apps with full details
-        match firestore.get_enabled_apps_full(&uid).await {
-            Ok(enabled_apps) => {
-                let results = integrations
-                    .trigger_conversation_created(&uid, &conv_for_trigger, &enabled_apps)
-                    .await;
-
-                if !results.is_empty() {
-                    let successful = results.iter().filter(|r| r.success).count();
-                    let failed = results.len() - successful;
-                    tracing::info!(
-                        "Integration triggers completed: {} successful, {} failed",
-                        successful,
-                        failed
-                    );
-                }
-            }
-            Err(e) => {
-                tracing::error!("Failed to get enabled apps for integration triggers: {}", e);
-            }
-        }
+    // Integrations and Notion sync consume this in the background
+    state.events.publish(AppEvent::ConversationCreated {
+        uid: user.uid.clone(),
+        conversation: std::sync::Arc::new(conversation),
     });
 
     Ok(Json(CreateConversationResponse {
         id: conversation_id,
         status: "completed".to_string(),
         discarded: false,
+        truncated,
+        truncation,
     }))
 }
 
@@ -387,7 +697,7 @@ async fn reprocess_conversation(
 
     // Fetch the conversation
     let conversation = state
-        .firestore
+        .storage
         .get_conversation(&user.uid, &conversation_id)
         .await
         .map_err(|e| {
@@ -411,6 +721,10 @@ async fn reprocess_conversation(
             (StatusCode::NOT_FOUND, "App not found".to_string())
         })?;
 
+    if state.app_blocked(&app).await {
+        return Err((StatusCode::FORBIDDEN, "App is blocked by moderation".to_string()));
+    }
+
     // Check if app has memories capability
     if !app.capabilities.contains(&"memories".to_string()) {
         return Err((
@@ -418,15 +732,28 @@ async fn reprocess_conversation(
             "App does not have memories capability".to_string(),
         ));
     }
+    if !app.has_scope(AppScope::ReadConversations) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "App has not been granted read:conversations".to_string(),
+        ));
+    }
+
+    if conversation.memory_extraction_disabled {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Memory extraction is disabled for this conversation".to_string(),
+        ));
+    }
 
     // Get the app's memory prompt
-    let memory_prompt = app.memory_prompt.unwrap_or_else(|| {
-        "Analyze this conversation and provide insights.".to_string()
-    });
+    let memory_prompt = app
+        .memory_prompt
+        .unwrap_or_else(|| reprocess_all::DEFAULT_MEMORY_PROMPT.to_string());
 
     // Get LLM client (Gemini)
-    let llm_client = if let Some(api_key) = &state.config.gemini_api_key {
-        LlmClient::new(api_key.clone())
+    let llm_client = if let Some(llm) = state.llm_client(&user.uid).await? {
+        llm
     } else {
         return Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -435,34 +762,40 @@ async fn reprocess_conversation(
     };
 
     // Build transcript text
-    let transcript_text: String = conversation
-        .transcript_segments
-        .iter()
-        .map(|s| {
-            let speaker = if s.is_user { user.name.clone().unwrap_or_else(|| "User".to_string()) } else { format!("Speaker {}", s.speaker_id) };
-            format!("{}: {}", speaker, s.text)
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+    let transcript_text = reprocess_all::memory_prompt_transcript(&conversation, user.name.as_deref());
+
+    // Hold off edits until the result is saved
+    let lock = conversation_lock::acquire(
+        &state.firestore,
+        &user.uid,
+        &conversation_id,
+        &format!("reprocess:{}", request.app_id),
+    )
+    .await?;
 
     // Run the app's memory prompt against the conversation
     let result = llm_client
         .run_memory_prompt(&memory_prompt, &transcript_text, &conversation.structured)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to run memory prompt: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to process: {}", e))
-        })?;
+        .await;
 
     // Save the app result to the conversation
-    if let Err(e) = state
-        .firestore
-        .add_app_result(&user.uid, &conversation_id, &request.app_id, &result)
-        .await
-    {
-        tracing::error!("Failed to save app result: {}", e);
-        // Continue anyway, just log the error
+    if let Ok(result) = &result {
+        match state
+            .firestore
+            .add_app_result(&user.uid, &conversation_id, &request.app_id, result)
+            .await
+        {
+            Ok(()) => reprocess_all::publish_result(&state, &user.uid, &conversation_id, &request.app_id, result),
+            // Continue anyway, just log the error
+            Err(e) => tracing::error!("Failed to save app result: {}", e),
+        }
     }
+    lock.release().await;
+
+    let result = result.map_err(|e| {
+        tracing::error!("Failed to run memory prompt: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to process: {}", e))
+    })?;
 
     Ok(Json(ReprocessResponse {
         success: true,
@@ -471,6 +804,153 @@ async fn reprocess_conversation(
     }))
 }
 
+#[derive(Serialize)]
+pub struct AppResultsResponse {
+    conversation_id: String,
+    apps_results: Vec<AppResult>,
+    /// Apps still queued or running in a reprocess-all run of this conversation
+    pending: Vec<AppReprocessStatus>,
+}
+
+/// GET /v1/conversations/:id/app-results - App results saved so far, plus the apps still
+/// to finish. Each result is also announced on GET /v1/updates/stream as it's saved.
+async fn get_app_results(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<AppResultsResponse>, (StatusCode, String)> {
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    let mut pending = Vec::new();
+    if let Some(redis) = &state.redis {
+        match redis.get_reprocess_progress(&user.uid).await {
+            Ok(runs) => {
+                pending = runs
+                    .into_iter()
+                    .filter(|r| r.conversation_id == conversation_id)
+                    .flat_map(|r| r.apps)
+                    .filter(|a| matches!(a.state, AppReprocessState::Queued | AppReprocessState::Running))
+                    .collect();
+            }
+            Err(e) => tracing::warn!("Failed to read reprocess progress: {}", e),
+        }
+    }
+
+    Ok(Json(AppResultsResponse {
+        conversation_id,
+        apps_results: conversation.apps_results,
+        pending,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ReprocessAllResponse {
+    conversation_id: String,
+    /// Apps queued to run, plus those skipped up front
+    apps: Vec<AppReprocessStatus>,
+}
+
+/// POST /v1/conversations/:id/reprocess-all - Queue reprocessing with every enabled
+/// memories app; progress is reported by GET /v1/conversations/processing-status
+async fn reprocess_conversation_all_apps(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<(StatusCode, Json<ReprocessAllResponse>), (StatusCode, String)> {
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    if conversation.memory_extraction_disabled {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Memory extraction is disabled for this conversation".to_string(),
+        ));
+    }
+
+    // One run per conversation at a time
+    if let Some(redis) = &state.redis {
+        match redis.get_reprocess_progress(&user.uid).await {
+            Ok(runs) => {
+                if runs.iter().any(|r| r.conversation_id == conversation_id && !r.is_finished()) {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        "Conversation is already being reprocessed".to_string(),
+                    ));
+                }
+            }
+            Err(e) => tracing::warn!("Failed to read reprocess progress: {}", e),
+        }
+    }
+
+    let apps = state.firestore.get_enabled_apps_full(&user.uid).await.map_err(|e| {
+        tracing::error!("Failed to get enabled apps: {}", e);
+        e.public_error()
+    })?;
+    let mut progress = ReprocessAllProgress {
+        conversation_id: conversation_id.clone(),
+        apps: reprocess_all::plan(&apps, &conversation.apps_results),
+        started_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+    let response = ReprocessAllResponse {
+        conversation_id: conversation_id.clone(),
+        apps: progress.apps.clone(),
+    };
+    if progress.is_finished() {
+        return Ok((StatusCode::OK, Json(response)));
+    }
+    let lock = conversation_lock::acquire(&state.firestore, &user.uid, &conversation_id, "reprocess-all").await?;
+
+    tracing::info!(
+        "Queued reprocessing of conversation {} with {} apps for user {}",
+        conversation_id,
+        progress.apps.iter().filter(|a| a.state == AppReprocessState::Queued).count(),
+        user.uid
+    );
+    if let Some(redis) = &state.redis {
+        progress.updated_at = chrono::Utc::now();
+        if let Err(e) = redis.store_reprocess_progress(&user.uid, &progress).await {
+            tracing::warn!("Failed to store reprocess progress: {}", e);
+        }
+    }
+    let job_state = state.clone();
+    let uid = user.uid.clone();
+    let mut rejected = progress.clone();
+    let queued = state.jobs.submit(&user.uid, JobPriority::Interactive, "reprocess-all", async move {
+        reprocess_all::run(job_state, uid, user.name, conversation, apps, progress).await;
+        lock.release().await;
+    });
+    if let Err(e) = queued {
+        // Don't leave the run looking queued, which would block a retry
+        for app in rejected.apps.iter_mut().filter(|a| a.state == AppReprocessState::Queued) {
+            app.state = AppReprocessState::Failed;
+            app.detail = Some(e.to_string());
+        }
+        if let Some(redis) = &state.redis {
+            rejected.updated_at = chrono::Utc::now();
+            if let Err(e) = redis.store_reprocess_progress(&user.uid, &rejected).await {
+                tracing::warn!("Failed to store reprocess progress: {}", e);
+            }
+        }
+        // The rejected job held the lock; dropping it released it
+        return Err(e.into());
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
 // Search request/response models
 #[derive(Deserialize)]
 pub struct SearchConversationsRequest {
@@ -515,14 +995,14 @@ async fn search_conversations(
 
     // Fetch all conversations (we'll filter in memory since Firestore doesn't support full-text search)
     let all_conversations = match state
-        .firestore
+        .storage
         .get_conversations(&user.uid, 500, 0, request.include_discarded, &["completed".to_string()], None, None, None, None)
         .await
     {
         Ok(convs) => convs,
         Err(e) => {
             tracing::error!("Failed to get conversations for search: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to search: {}", e)));
+            return Err(e.public_error());
         }
     };
 
@@ -558,73 +1038,763 @@ async fn search_conversations(
 }
 
 #[derive(Deserialize)]
-pub struct StarredParams {
-    starred: bool,
+pub struct StarredParams {
+    starred: bool,
+}
+
+#[derive(Serialize)]
+pub struct StatusResponse {
+    status: String,
+}
+
+/// PATCH /v1/conversations/:id/starred - Set conversation starred status
+async fn set_conversation_starred(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    Query(params): Query<StarredParams>,
+) -> Result<Json<StatusResponse>, StatusCode> {
+    tracing::info!(
+        "Setting conversation {} starred={} for user {}",
+        conversation_id,
+        params.starred,
+        user.uid
+    );
+
+    match state
+        .storage
+        .set_conversation_starred(&user.uid, &conversation_id, params.starred)
+        .await
+    {
+        Ok(()) => Ok(Json(StatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to set starred: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// PATCH /v1/conversations/:id/memory-extraction - Exclude/include a conversation in learning.
+/// Disabling also deletes memories previously extracted from it (unless told not to).
+async fn set_conversation_memory_extraction(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    Json(request): Json<SetMemoryExtractionRequest>,
+) -> Result<Json<SetMemoryExtractionResponse>, StatusCode> {
+    tracing::info!(
+        "Setting conversation {} memory_extraction_disabled={} for user {}",
+        conversation_id,
+        request.disabled,
+        user.uid
+    );
+
+    if let Err(e) = state
+        .storage
+        .set_conversation_memory_extraction_disabled(&user.uid, &conversation_id, request.disabled)
+        .await
+    {
+        tracing::error!("Failed to set memory extraction flag: {}", e);
+        return Err(e.http_status());
+    }
+
+    let deleted_memories = if request.disabled && request.delete_existing_memories {
+        match state
+            .storage
+            .delete_memories_for_conversation(&user.uid, &conversation_id)
+            .await
+        {
+            Ok(count) => count,
+            Err(e) => {
+                tracing::error!("Failed to delete memories for conversation: {}", e);
+                return Err(e.http_status());
+            }
+        }
+    } else {
+        0
+    };
+
+    Ok(Json(SetMemoryExtractionResponse {
+        memory_extraction_disabled: request.disabled,
+        deleted_memories,
+    }))
+}
+
+/// PATCH /v1/conversations/:id/events - Mark structured events as created (added to calendar)
+async fn set_conversation_events_state(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    Json(request): Json<SetConversationEventsStateRequest>,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Setting {} event states on conversation {} for user {}",
+        request.events_idx.len(),
+        conversation_id,
+        user.uid
+    );
+
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    let mut events = conversation.structured.events;
+    request
+        .apply(&mut events)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    match state
+        .storage
+        .update_conversation_events(&user.uid, &conversation_id, &events)
+        .await
+    {
+        Ok(()) => Ok(Json(StatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to update conversation events: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// POST /v1/conversations/:id/action-items/reconcile
+/// Link each structured action item to a top-level action item (matched by
+/// description), creating missing ones. Back-references are stored both ways:
+/// ActionItemDB.conversation_id and structured action_item_id.
+async fn reconcile_conversation_action_items(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<ReconcileActionItemsResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Reconciling action items for conversation {} for user {}",
+        conversation_id,
+        user.uid
+    );
+
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    // Candidates: items already linked to this conversation, plus items created
+    // around it (e.g. promoted staged tasks that never got a conversation_id)
+    let internal_error = |e: FirestoreError| {
+        tracing::error!("Failed to reconcile action items: {}", e);
+        e.public_error()
+    };
+    let mut candidates: Vec<ActionItemDB> = state
+        .storage
+        .get_action_items(&user.uid, 200, 0, None, Some(&conversation_id), None, None, None, None, None, None, true)
+        .await
+        .map_err(internal_error)?;
+    let window_start = (conversation.created_at - chrono::Duration::days(1)).to_rfc3339();
+    let window_end = (conversation.created_at + chrono::Duration::days(3)).to_rfc3339();
+    for item in state
+        .storage
+        .get_action_items(&user.uid, 200, 0, None, None, Some(&window_start), Some(&window_end), None, None, None, None, true)
+        .await
+        .map_err(internal_error)?
+    {
+        if item.conversation_id.is_none() && !candidates.iter().any(|c| c.id == item.id) {
+            candidates.push(item);
+        }
+    }
+    candidates.retain(|c| !c.deleted.unwrap_or(false));
+
+    let source = format!("transcription:{:?}", conversation.source).to_lowercase();
+    let mut structured_items = conversation.structured.action_items;
+    let mut claimed: Vec<String> = Vec::new();
+    let mut items: Vec<ActionItemDB> = Vec::new();
+    let (mut linked, mut created) = (0, 0);
+
+    for structured in structured_items.iter_mut() {
+        let wanted = normalize_action_item_description(&structured.description);
+        let matched = candidates
+            .iter()
+            .filter(|c| !claimed.contains(&c.id))
+            .find(|c| structured.action_item_id.as_deref() == Some(c.id.as_str()))
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .filter(|c| !claimed.contains(&c.id))
+                    .find(|c| normalize_action_item_description(&c.description) == wanted)
+            })
+            .cloned();
+
+        let item = match matched {
+            Some(item) if item.conversation_id.as_deref() == Some(conversation_id.as_str()) => item,
+            Some(item) => {
+                linked += 1;
+                state
+                    .storage
+                    .link_action_item_to_conversation(&user.uid, &item.id, &conversation_id)
+                    .await
+                    .map_err(internal_error)?
+            }
+            None => {
+                created += 1;
+                let new_item = state
+                    .storage
+                    .create_action_item(
+                        &user.uid,
+                        &structured.description,
+                        structured.due_at,
+                        Some(&source),
+                        structured.priority.as_deref(),
+                        None, // metadata
+                        None, // category
+                        None, // relevance_score
+                        None, // from_staged
+                        None, // recurrence_rule
+                        None, // recurrence_parent_id
+                    )
+                    .await
+                    .map_err(internal_error)?;
+                state
+                    .storage
+                    .link_action_item_to_conversation(&user.uid, &new_item.id, &conversation_id)
+                    .await
+                    .map_err(internal_error)?
+            }
+        };
+
+        structured.action_item_id = Some(item.id.clone());
+        claimed.push(item.id.clone());
+        items.push(item);
+    }
+
+    state
+        .storage
+        .update_conversation_action_items(&user.uid, &conversation_id, &structured_items)
+        .await
+        .map_err(internal_error)?;
+
+    tracing::info!(
+        "Reconciled {} action items for conversation {} ({} linked, {} created)",
+        items.len(),
+        conversation_id,
+        linked,
+        created
+    );
+
+    Ok(Json(ReconcileActionItemsResponse { linked, created, items }))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateConversationRequest {
+    title: Option<String>,
+}
+
+/// GET /v1/conversations/:id - Get a single conversation by ID
+async fn get_conversation_by_id(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<Conversation>, (StatusCode, String)> {
+    tracing::info!(
+        "Getting conversation {} for user {}",
+        conversation_id,
+        user.uid
+    );
+
+    match state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+    {
+        Ok(Some(conversation)) => Ok(Json(conversation)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// Query for GET /v1/conversations/:id/export
+#[derive(Deserialize)]
+pub struct ExportConversationQuery {
+    #[serde(default = "default_export_format")]
+    pub format: ExportFormat,
+}
+
+fn default_export_format() -> ExportFormat {
+    ExportFormat::Markdown
+}
+
+/// Build a safe download filename from the conversation title
+fn export_filename(title: &str, extension: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+    let stem = if stem.is_empty() { "conversation".to_string() } else { stem };
+    format!("{}.{}", stem.chars().take(80).collect::<String>(), extension)
+}
+
+/// GET /v1/conversations/:id/export - Download a conversation as Markdown, text or PDF
+async fn export_conversation(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    Query(query): Query<ExportConversationQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    tracing::info!(
+        "Exporting conversation {} for user {} as {:?}",
+        conversation_id,
+        user.uid,
+        query.format
+    );
+
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    let (names, tz) = SpeakerNames::load(&state.firestore, &user.uid).await;
+
+    let body = match query.format {
+        ExportFormat::Markdown => {
+            conversation_export::render_markdown(&conversation, &names, tz).into_bytes()
+        }
+        ExportFormat::Txt => conversation_export::render_text(&conversation, &names, tz).into_bytes(),
+        ExportFormat::Pdf => conversation_export::render_pdf(&conversation, &names, tz),
+    };
+
+    let filename = export_filename(&conversation.structured.title, query.format.extension());
+    Ok((
+        [
+            (header::CONTENT_TYPE, query.format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Which LLM operation to estimate
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateOperation {
+    /// Full processing: summary, action items and memories
+    #[default]
+    Process,
+    /// Regenerate the summary only
+    Summary,
+    /// Reprocess with an app's memory prompt (requires app_id)
+    Reprocess,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EstimateProcessingRequest {
+    #[serde(default)]
+    operation: EstimateOperation,
+    app_id: Option<String>,
+}
+
+/// POST /v1/conversations/:id/estimate - Projected tokens, cost and latency of
+/// (re)processing a conversation. Never calls the LLM.
+async fn estimate_conversation_processing(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    request: Option<Json<EstimateProcessingRequest>>,
+) -> Result<Json<ProcessingEstimate>, (StatusCode, String)> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    if conversation.transcript_segments.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Conversation has no transcript".to_string()));
+    }
+    let transcript = TranscriptSegment::to_transcript_text(&conversation.transcript_segments);
+
+    let calls = match request.operation {
+        EstimateOperation::Process | EstimateOperation::Summary => {
+            let custom_prompt = state
+                .storage
+                .get_processing_prompt(&user.uid)
+                .await
+                .unwrap_or_default();
+            if request.operation == EstimateOperation::Summary {
+                estimate::summary_calls(&transcript, custom_prompt.as_deref())
+            } else {
+                estimate::processing_calls(
+                    &transcript,
+                    custom_prompt.as_deref(),
+                    !conversation.memory_extraction_disabled,
+                )
+            }
+        }
+        EstimateOperation::Reprocess => {
+            let app_id = request.app_id.as_deref().ok_or((
+                StatusCode::BAD_REQUEST,
+                "app_id is required for reprocess estimates".to_string(),
+            ))?;
+            let app = state
+                .firestore
+                .get_app(&user.uid, app_id)
+                .await?
+                .ok_or((StatusCode::NOT_FOUND, "App not found".to_string()))?;
+            let context = format!(
+                "{}\n{}\n{}",
+                conversation.structured.title, conversation.structured.overview, transcript
+            );
+            estimate::memory_prompt_calls(app.memory_prompt.as_deref().unwrap_or_default(), &context)
+        }
+    };
+
+    // Priced with the summary model, which handles the largest calls
+    let models = state.llm_task_models().await;
+    Ok(Json(estimate::estimate(models.model_for(TaskKind::Summary), &transcript, calls)))
+}
+
+/// GET /v1/conversations/:id/analytics - Talk-time, monologue and interruption metrics
+/// Conversations processed before analytics existed are computed on the fly.
+async fn get_conversation_analytics(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<ConversationAnalytics>, (StatusCode, String)> {
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    let mut analytics = conversation
+        .analytics
+        .or_else(|| conversation_analytics::compute_analytics(&conversation.transcript_segments))
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            "Conversation has no timed transcript to analyze".to_string(),
+        ))?;
+
+    let (names, _) = SpeakerNames::load(&state.firestore, &user.uid).await;
+    conversation_analytics::resolve_speaker_names(&mut analytics, &names);
+
+    Ok(Json(analytics))
+}
+
+#[derive(Deserialize)]
+struct TranscriptSearchQuery {
+    q: String,
+    #[serde(default = "default_transcript_search_limit")]
+    limit: usize,
+}
+
+fn default_transcript_search_limit() -> usize {
+    20
 }
 
 #[derive(Serialize)]
-pub struct StatusResponse {
-    status: String,
+struct TranscriptSearchHit {
+    #[serde(flatten)]
+    hit: TranscriptHit,
+    /// Opens the desktop player at the spoken moment
+    deep_link: String,
 }
 
-/// PATCH /v1/conversations/:id/starred - Set conversation starred status
-async fn set_conversation_starred(
+#[derive(Serialize)]
+struct TranscriptSearchResponse {
+    conversation_id: String,
+    query: String,
+    hits: Vec<TranscriptSearchHit>,
+}
+
+/// GET /v1/conversations/:id/transcript/search?q= - Text matches with audio timestamps
+async fn search_conversation_transcript(
     State(state): State<AppState>,
     user: AuthUser,
     Path(conversation_id): Path<String>,
-    Query(params): Query<StarredParams>,
-) -> Result<Json<StatusResponse>, StatusCode> {
-    tracing::info!(
-        "Setting conversation {} starred={} for user {}",
+    Query(query): Query<TranscriptSearchQuery>,
+) -> Result<Json<TranscriptSearchResponse>, (StatusCode, String)> {
+    if query.q.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "q is required".to_string()));
+    }
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    let hits = transcript_search::search_transcript(
+        &conversation.transcript_segments,
+        &query.q,
+        query.limit.clamp(1, 100),
+    )
+    .into_iter()
+    .map(|hit| TranscriptSearchHit {
+        deep_link: format!(
+            "omi-computer://conversation/{}?t={:.2}",
+            conversation_id, hit.start
+        ),
+        hit,
+    })
+    .collect();
+
+    Ok(Json(TranscriptSearchResponse {
         conversation_id,
-        params.starred,
-        user.uid
-    );
+        query: query.q,
+        hits,
+    }))
+}
 
-    match state
-        .firestore
-        .set_conversation_starred(&user.uid, &conversation_id, params.starred)
+/// Max recipients for a follow-up email
+const MAX_EMAIL_RECIPIENTS: usize = 20;
+/// Max follow-up email recipients per user over any 24 hours, across all sends
+const MAX_EMAIL_RECIPIENTS_PER_DAY: usize = 50;
+/// Limits on a user-edited subject and body
+const MAX_EMAIL_SUBJECT_CHARS: usize = 200;
+const MAX_EMAIL_BODY_CHARS: usize = 20_000;
+/// Drafts older than this can't be sent
+const EMAIL_DRAFT_TTL_HOURS: i64 = 24;
+
+/// POST /v1/conversations/:id/draft-email - Draft a follow-up email, or send a confirmed draft
+async fn draft_follow_up_email(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(conversation_id): Path<String>,
+    Json(request): Json<DraftFollowUpEmailRequest>,
+) -> Result<Json<DraftFollowUpEmailResponse>, (StatusCode, String)> {
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
         .await
-    {
-        Ok(()) => Ok(Json(StatusResponse {
-            status: "ok".to_string(),
-        })),
-        Err(e) => {
-            tracing::error!("Failed to set starred: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    if request.send {
+        return send_follow_up_email(&state, &user.uid, &client, &conversation_id, request).await;
+    }
+
+    let llm_client = match state.llm_client(&user.uid).await? {
+        Some(llm) => llm,
+        None => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "GEMINI_API_KEY not configured".to_string(),
+            ))
         }
+    };
+
+    if conversation.transcript_segments.is_empty() && conversation.structured.overview.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Conversation has no content to summarize".to_string()));
     }
-}
 
-#[derive(Deserialize)]
-pub struct UpdateConversationRequest {
-    title: Option<String>,
+    tracing::info!("Drafting follow-up email for conversation {} (user {})", conversation_id, user.uid);
+
+    let (names, _) = SpeakerNames::load(&state.firestore, &user.uid).await;
+    let participants = conversation_export::participants(&conversation.transcript_segments, &names);
+    let transcript = conversation_export::named_transcript(&conversation.transcript_segments, &names);
+
+    let draft = llm_client
+        .draft_follow_up_email(
+            &names.user_name,
+            &conversation.structured,
+            &participants,
+            &transcript,
+            request.instructions.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to draft follow-up email: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to draft email: {}", e))
+        })?;
+
+    let stored = StoredEmailDraft {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: conversation_id.clone(),
+        subject: draft.subject.clone(),
+        body: draft.body.clone(),
+        created_at: chrono::Utc::now(),
+    };
+    state.firestore.save_email_draft(&user.uid, &stored).await.map_err(|e| {
+        tracing::error!("Failed to save email draft: {}", e);
+        (e.http_status(), "Failed to save email draft".to_string())
+    })?;
+
+    Ok(Json(DraftFollowUpEmailResponse {
+        draft_id: stored.id,
+        draft,
+        sent: false,
+    }))
 }
 
-/// GET /v1/conversations/:id - Get a single conversation by ID
-async fn get_conversation_by_id(
-    State(state): State<AppState>,
-    user: AuthUser,
-    Path(conversation_id): Path<String>,
-) -> Result<Json<Conversation>, (StatusCode, String)> {
+/// Send a user-confirmed, server-generated draft, with the user's edits if any. Recipients
+/// count against a daily limit before sending, and every send is recorded.
+async fn send_follow_up_email(
+    state: &AppState,
+    uid: &str,
+    client: &ClientInfo,
+    conversation_id: &str,
+    request: DraftFollowUpEmailRequest,
+) -> Result<Json<DraftFollowUpEmailResponse>, (StatusCode, String)> {
+    let mailer = state.mailer.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Email sending is not configured".to_string(),
+    ))?;
+
+    let draft_id = request
+        .draft_id
+        .as_deref()
+        .ok_or((StatusCode::BAD_REQUEST, "draft_id is required to send".to_string()))?;
+    let draft = state
+        .firestore
+        .get_email_draft(uid, draft_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get email draft: {}", e);
+            (e.http_status(), "Failed to get email draft".to_string())
+        })?
+        .filter(|d| d.conversation_id == conversation_id)
+        .ok_or((StatusCode::NOT_FOUND, "Draft not found".to_string()))?;
+    let now = chrono::Utc::now();
+    if draft.created_at < now - chrono::Duration::hours(EMAIL_DRAFT_TTL_HOURS) {
+        return Err((StatusCode::GONE, "Draft expired; draft the email again".to_string()));
+    }
+
+    // The user's edits replace the draft's wording
+    let subject = request.subject.as_deref().map(str::trim).unwrap_or(&draft.subject).to_string();
+    let body = request.body.as_deref().map(str::trim).unwrap_or(&draft.body).to_string();
+    if subject.is_empty() || body.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Subject and body can't be empty".to_string()));
+    }
+    if subject.chars().count() > MAX_EMAIL_SUBJECT_CHARS || body.chars().count() > MAX_EMAIL_BODY_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Subject and body are limited to {} and {} characters",
+                MAX_EMAIL_SUBJECT_CHARS, MAX_EMAIL_BODY_CHARS
+            ),
+        ));
+    }
+
+    let to: Vec<String> = request.to.iter().map(|a| a.trim().to_string()).collect();
+    if to.is_empty() || to.len() > MAX_EMAIL_RECIPIENTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Between 1 and {} recipients are required", MAX_EMAIL_RECIPIENTS),
+        ));
+    }
+    if let Some(invalid) = to.iter().find(|a| !mailer::is_valid_email(a)) {
+        return Err((StatusCode::BAD_REQUEST, format!("Invalid email address: {}", invalid)));
+    }
+
+    // Counted before sending, so a failed write can't lift the limit
+    let reserved = state
+        .firestore
+        .reserve_email_recipients(uid, to.len(), MAX_EMAIL_RECIPIENTS_PER_DAY)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reserve email send quota: {}", e);
+            (e.http_status(), "Failed to check the email send limit".to_string())
+        })?;
+    if !reserved {
+        tracing::warn!("Follow-up email send limit reached for user {}", uid);
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Limit of {} email recipients per day reached", MAX_EMAIL_RECIPIENTS_PER_DAY),
+        ));
+    }
+
+    // Replies go straight to the user
+    let reply_to = state
+        .firestore
+        .get_user_profile(uid)
+        .await
+        .ok()
+        .and_then(|p| p.email)
+        .filter(|e| mailer::is_valid_email(e));
+
     tracing::info!(
-        "Getting conversation {} for user {}",
+        "Sending follow-up email for conversation {} (user {}, {}) to {} recipients",
         conversation_id,
-        user.uid
+        uid,
+        client,
+        to.len()
     );
 
-    match state
-        .firestore
-        .get_conversation(&user.uid, &conversation_id)
+    let record = EmailSendRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: conversation_id.to_string(),
+        draft_id: draft.id.clone(),
+        recipients: to.clone(),
+        subject: subject.clone(),
+        ip: client.ip_string(),
+        sent_at: now,
+    };
+    state.firestore.add_email_send(uid, &record).await.map_err(|e| {
+        tracing::error!("Failed to record email send: {}", e);
+        (e.http_status(), "Failed to send email".to_string())
+    })?;
+
+    mailer
+        .send(&to, &subject, &body, reply_to.as_deref())
         .await
-    {
-        Ok(Some(conversation)) => Ok(Json(conversation)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
-        Err(e) => {
-            tracing::error!("Failed to get conversation: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get conversation: {}", e)))
-        }
+        .map_err(|e| {
+            tracing::error!("Failed to send follow-up email: {}", e);
+            (StatusCode::BAD_GATEWAY, "Failed to send email".to_string())
+        })?;
+
+    // Each draft is sent once
+    if let Err(e) = state.firestore.delete_email_draft(uid, &draft.id).await {
+        tracing::warn!("Failed to delete sent email draft {}: {}", draft.id, e);
     }
+
+    Ok(Json(DraftFollowUpEmailResponse {
+        draft_id: draft.id,
+        draft: FollowUpEmailDraft {
+            subject,
+            body,
+            suggested_recipients: vec![],
+        },
+        sent: true,
+    }))
 }
 
 /// DELETE /v1/conversations/:id - Delete a conversation
@@ -640,14 +1810,14 @@ async fn delete_conversation(
     );
 
     match state
-        .firestore
+        .storage
         .delete_conversation(&user.uid, &conversation_id)
         .await
     {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(e) => {
             tracing::error!("Failed to delete conversation: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -667,15 +1837,38 @@ async fn update_conversation(
 
     // Update title if provided
     if let Some(title) = &request.title {
+        // Keep the title being replaced in the edit history
+        let previous = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+            Ok(Some(conversation)) => conversation.structured.title,
+            Ok(None) => return Err(StatusCode::NOT_FOUND),
+            Err(e) => {
+                tracing::error!("Failed to get conversation: {}", e);
+                return Err(e.http_status());
+            }
+        };
+        if previous == *title {
+            return Ok(Json(StatusResponse {
+                status: "ok".to_string(),
+            }));
+        }
+
         match state
-            .firestore
+            .storage
             .update_conversation_title(&user.uid, &conversation_id, title)
             .await
         {
-            Ok(()) => {},
+            Ok(()) => {
+                let edit = conversation_history::new_edit(
+                    ConversationEditField::Title,
+                    serde_json::json!(previous),
+                    &user.uid,
+                    "user",
+                );
+                conversation_history::record_edit(&state.firestore, &user.uid, &conversation_id, &edit).await;
+            }
             Err(e) => {
                 tracing::error!("Failed to update conversation title: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(e.http_status());
             }
         }
     }
@@ -685,6 +1878,136 @@ async fn update_conversation(
     }))
 }
 
+/// PUT /v1/conversations/:id/feedback - Thumbs up/down (and an optional comment) on the
+/// conversation's summary; a null rating clears it
+async fn set_conversation_feedback(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+    Json(request): Json<ConversationFeedbackRequest>,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    if request.rating.is_some_and(|r| r != 1 && r != -1) {
+        return Err((StatusCode::BAD_REQUEST, "rating must be 1, -1 or null".to_string()));
+    }
+    let comment = request.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_FEEDBACK_COMMENT_CHARS) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("comment must be at most {} characters", MAX_FEEDBACK_COMMENT_CHARS),
+        ));
+    }
+
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    let feedback = request.rating.map(|rating| ConversationFeedback {
+        rating,
+        comment: comment.map(str::to_string),
+        rated_at: chrono::Utc::now(),
+    });
+    if let Err(e) = state
+        .firestore
+        .set_conversation_feedback(&user.uid, &conversation_id, feedback.as_ref())
+        .await
+    {
+        if let FirestoreError::NotFound(_) = e {
+            return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string()));
+        }
+        tracing::error!("Failed to set conversation feedback: {}", e);
+        return Err(e.public_error());
+    }
+
+    llm_quality::record_rating(
+        &state.firestore,
+        &user.uid,
+        QualityRatingKind::Conversation,
+        &conversation_id,
+        request.rating,
+        LlmProvenance {
+            model: conversation.generated_by.get("summary").cloned(),
+            prompt_version: conversation.prompt_version,
+            route: Some("conversation_processing".to_string()),
+        },
+    )
+    .await;
+
+    Ok(Json(StatusResponse {
+        status: "ok".to_string(),
+    }))
+}
+
+/// GET /v1/conversations/:id/history - Edits made to the conversation, newest first
+async fn get_conversation_history(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<ConversationHistoryResponse>, (StatusCode, String)> {
+    match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    }
+
+    let edits = state
+        .firestore
+        .get_conversation_edits(&user.uid, &conversation_id, MAX_CONVERSATION_EDITS)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation history: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(ConversationHistoryResponse { conversation_id, edits }))
+}
+
+/// POST /v1/conversations/:id/history/:edit_id/restore - Put back the value an edit replaced
+async fn restore_conversation_edit(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((conversation_id, edit_id)): Path<(String, String)>,
+) -> Result<Json<ConversationEdit>, (StatusCode, String)> {
+    tracing::info!(
+        "Restoring edit {} of conversation {} for user {}",
+        edit_id,
+        conversation_id,
+        user.uid
+    );
+
+    let conversation = match state.storage.get_conversation(&user.uid, &conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation: {}", e);
+            return Err(e.public_error());
+        }
+    };
+    let edit = match state.firestore.get_conversation_edit(&user.uid, &conversation_id, &edit_id).await {
+        Ok(Some(edit)) => edit,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Edit not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get conversation edit: {}", e);
+            return Err(e.public_error());
+        }
+    };
+
+    let restore = conversation_history::restore_edit(&state.firestore, &user.uid, &conversation, &edit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to restore conversation edit: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to restore".to_string())
+        })?;
+    Ok(Json(restore))
+}
+
 // ============================================================================
 // MERGE CONVERSATIONS
 // ============================================================================
@@ -713,11 +2036,45 @@ pub struct MergeConversationsResponse {
     new_conversation_id: Option<String>,
 }
 
-/// POST /v1/conversations/merge - Merge multiple conversations into one
+/// POST /v1/conversations/merge - Merge multiple conversations into one. The sources are
+/// locked while merging, so nothing changes them before they're replaced.
 async fn merge_conversations(
     State(state): State<AppState>,
     user: AuthUser,
     Json(request): Json<MergeConversationsRequest>,
+) -> Result<Json<MergeConversationsResponse>, Response> {
+    let mut source_ids = request.conversation_ids.clone();
+    source_ids.sort();
+    source_ids.dedup();
+
+    let mut locks = Vec::new();
+    let mut lock_error = None;
+    for conv_id in source_ids {
+        match conversation_lock::acquire(&state.firestore, &user.uid, &conv_id, "merge").await {
+            Ok(lock) => locks.push(lock),
+            // Reported by the merge itself
+            Err(LockError::NotFound) => {}
+            Err(e) => {
+                lock_error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let result = match lock_error {
+        Some(e) => Err(e.into_response()),
+        None => merge_sources(&state, &user, request).await.map_err(IntoResponse::into_response),
+    };
+    for lock in locks {
+        lock.release().await;
+    }
+    result
+}
+
+async fn merge_sources(
+    state: &AppState,
+    user: &AuthUser,
+    request: MergeConversationsRequest,
 ) -> Result<Json<MergeConversationsResponse>, (StatusCode, String)> {
     tracing::info!(
         "Merging {} conversations for user {}",
@@ -736,7 +2093,7 @@ async fn merge_conversations(
     // Fetch all conversations
     let mut conversations = Vec::new();
     for conv_id in &request.conversation_ids {
-        match state.firestore.get_conversation(&user.uid, conv_id).await {
+        match state.storage.get_conversation(&user.uid, conv_id).await {
             Ok(Some(conv)) => conversations.push(conv),
             Ok(None) => {
                 return Err((
@@ -745,10 +2102,7 @@ async fn merge_conversations(
                 ));
             }
             Err(e) => {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to fetch conversation {}: {}", conv_id, e),
-                ));
+                return Err(e.into());
             }
         }
     }
@@ -805,6 +2159,7 @@ async fn merge_conversations(
         language: first.language.clone(),
         status: ConversationStatus::Processing,
         discarded: false,
+        discard_reason: None,
         deleted: false,
         starred: false,
         is_locked: false,
@@ -822,16 +2177,81 @@ async fn merge_conversations(
         geolocation: first.geolocation.clone(),
         photos: vec![],
         input_device_name: first.input_device_name.clone(),
+        // Excluding any source conversation excludes the merged one
+        memory_extraction_disabled: conversations.iter().any(|c| c.memory_extraction_disabled),
+        notion_sync: None,
+        detected_languages: vec![],
+        analytics: None,
+        context_line: None,
+        generated_by: Default::default(),
+        template_id: None,
+        transcript_deleted_at: None,
+        focus_sessions: vec![],
+        prompt_version: None,
+        feedback: None,
     };
+    language::tag_segment_languages(&mut merged_conversation.transcript_segments);
+    merged_conversation.detected_languages =
+        language::conversation_languages(&merged_conversation.transcript_segments);
+    merged_conversation.analytics =
+        conversation_analytics::compute_analytics(&merged_conversation.transcript_segments);
+    merged_conversation.focus_sessions = focus_context::sessions_for_conversation(
+        state.storage.as_ref(),
+        &user.uid,
+        merged_conversation.started_at,
+        merged_conversation.finished_at,
+    )
+    .await;
 
     // If reprocessing is requested and we have an LLM client, process the merged conversation
+    let mut extracted = (Vec::new(), Vec::new());
     if request.reprocess {
-        if let Some(api_key) = &state.config.gemini_api_key {
-            let llm = LlmClient::new(api_key.clone());
+        let llm = state.llm_client(&user.uid).await.unwrap_or_else(|e| {
+            tracing::warn!("Skipping reprocessing of merged conversation {}: {}", new_conversation_id, e);
+            None
+        });
+        if let Some(llm) = llm {
+            let settings = state
+                .storage
+                .get_processing_settings(&user.uid)
+                .await
+                .unwrap_or_default();
+            let recent_titles: Vec<String> = state
+                .storage
+                .get_recent_conversation_titles(&user.uid, titles::MAX_RECENT_TITLES)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                // The merged conversation replaces its sources, so their titles are free
+                .filter(|t| !conversations.iter().any(|c| &c.structured.title == t))
+                .collect();
+            let blocked_topics = state
+                .storage
+                .get_blocked_memory_topics(&user.uid)
+                .await
+                .unwrap_or_default();
+            let templates = state
+                .storage
+                .get_conversation_templates(&user.uid)
+                .await
+                .unwrap_or_default();
+            let template = conversation_templates::resolve(
+                &templates,
+                first.template_id.as_deref(),
+                first.folder_id.as_deref(),
+                Some(&first.structured.title),
+            );
+            let llm = llm
+                .with_custom_processing_prompt(Some(&settings.prompt))
+                .with_template(template)
+                .with_memory_extraction(!merged_conversation.memory_extraction_disabled)
+                .with_blocked_topics(&blocked_topics)
+                .with_recent_titles(recent_titles)
+                .with_plain_titles(settings.plain_titles);
 
             // Get existing data for deduplication
             let existing_memories = state
-                .firestore
+                .storage
                 .get_memories(&user.uid, 500)
                 .await
                 .unwrap_or_default();
@@ -844,7 +2264,10 @@ async fn merge_conversations(
                     &merged_conversation.transcript_segments,
                     &started_at_str,
                     "UTC",
-                    &merged_conversation.language,
+                    &language::summary_language(
+                        &merged_conversation.language,
+                        &merged_conversation.detected_languages,
+                    ),
                     user.name.as_deref().unwrap_or("User"),
                     &[],
                     &existing_memories,
@@ -853,37 +2276,14 @@ async fn merge_conversations(
             {
                 Ok(processed) => {
                     merged_conversation.structured = processed.structured;
+                    merged_conversation.generated_by = processed.generated_by;
+                    merged_conversation.prompt_version = Some(prompts::prompt_version().to_string());
+                    merged_conversation.template_id = template.map(|t| t.id.clone());
                     // Append "(merged)" to title to indicate this is a merged conversation
                     merged_conversation.structured.title = format!("{} (merged)", merged_conversation.structured.title);
                     merged_conversation.status = ConversationStatus::Completed;
 
-                    // Save action items as staged tasks
-                    if !processed.action_items.is_empty() {
-                        let source_str = format!("transcription:{:?}", merged_conversation.source).to_lowercase();
-                        for item in &processed.action_items {
-                            let _ = state
-                                .firestore
-                                .create_staged_task(
-                                    &user.uid,
-                                    &item.description,
-                                    item.due_at,
-                                    Some(&source_str),
-                                    item.priority.as_deref(),
-                                    None,
-                                    None,
-                                    None,
-                                )
-                                .await;
-                        }
-                    }
-
-                    // Save memories if any
-                    if !processed.memories.is_empty() {
-                        let _ = state
-                            .firestore
-                            .save_memories(&user.uid, &new_conversation_id, &processed.memories)
-                            .await;
-                    }
+                    extracted = (processed.action_items, processed.memories);
                 }
                 Err(e) => {
                     tracing::error!("Failed to process merged conversation: {}", e);
@@ -899,22 +2299,23 @@ async fn merge_conversations(
         merged_conversation.status = ConversationStatus::Completed;
     }
 
-    // Save the merged conversation
-    if let Err(e) = state
-        .firestore
-        .save_conversation(&user.uid, &merged_conversation)
-        .await
+    // Save the merged conversation with what reprocessing extracted from it
+    if let Err(e) = conversation_processing::save_processed_conversation(
+        state.storage.as_ref(),
+        &user.uid,
+        &merged_conversation,
+        &extracted.0,
+        &extracted.1,
+    )
+    .await
     {
         tracing::error!("Failed to save merged conversation: {}", e);
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to save merged conversation: {}", e),
-        ));
+        return Err(e.public_error());
     }
 
     // Delete source conversations
     for conv_id in &request.conversation_ids {
-        if let Err(e) = state.firestore.delete_conversation(&user.uid, conv_id).await {
+        if let Err(e) = state.storage.delete_conversation(&user.uid, conv_id).await {
             tracing::warn!("Failed to delete source conversation {}: {}", conv_id, e);
             // Continue anyway - merged conversation is already saved
         }
@@ -967,6 +2368,10 @@ fn merge_transcript_segments(conversations: &[Conversation]) -> Vec<TranscriptSe
                 let mut seg_copy = seg.clone();
                 seg_copy.start += offset;
                 seg_copy.end += offset;
+                for word in seg_copy.words.iter_mut().flatten() {
+                    word.start += offset;
+                    word.end += offset;
+                }
                 merged.push(seg_copy);
             }
 
@@ -1021,10 +2426,9 @@ async fn set_conversation_visibility(
 
     // Verify conversation exists and belongs to user
     let _conversation = state
-        .firestore
+        .storage
         .get_conversation(&user.uid, &conversation_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .await?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
 
     // Update visibility in Firestore
@@ -1034,7 +2438,7 @@ async fn set_conversation_visibility(
         .await
     {
         tracing::error!("Failed to set visibility in Firestore: {}", e);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+        return Err(e.public_error());
     }
 
     // Update Redis for fast lookup
@@ -1103,12 +2507,12 @@ async fn get_shared_conversation(
 
     // Fetch the conversation from Firestore
     let conversation = state
-        .firestore
+        .storage
         .get_conversation(&uid, &conversation_id)
         .await
         .map_err(|e| {
             tracing::error!("Failed to get conversation from Firestore: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            e.public_error()
         })?
         .ok_or_else(|| {
             tracing::info!("Conversation {} not found in Firestore", conversation_id);
@@ -1128,22 +2532,66 @@ async fn get_shared_conversation(
 
 pub fn conversations_routes() -> Router<AppState> {
     Router::new()
-        .route("/v1/conversations", get(get_conversations))
+        .route("/v1/conversations", with_etag(get(get_conversations)))
         .route("/v1/conversations/count", get(get_conversations_count))
+        .route("/v1/conversations/calendar", with_etag(get(get_conversations_calendar)))
+        .route("/v1/conversations/processing-status", get(get_processing_status))
+        .route("/v1/conversations/auto-discarded", get(get_auto_discarded_conversations))
         .route("/v1/conversations/search", post(search_conversations))
-        .route("/v1/conversations/merge", post(merge_conversations))
+        .route("/v1/conversations/merge", with_llm_limit(post(merge_conversations)))
         .route(
             "/v1/conversations/from-segments",
-            post(create_conversation_from_segments),
+            // Whole transcripts can be well past the default JSON limit
+            with_body_limit(
+                with_llm_limit(post(create_conversation_from_segments)),
+                LARGE_JSON_BODY_LIMIT_BYTES,
+            ),
         )
         .route(
             "/v1/conversations/:id/reprocess",
-            post(reprocess_conversation),
+            with_conversation_lock(with_llm_limit(post(reprocess_conversation))),
         )
+        .route(
+            "/v1/conversations/:id/reprocess-all",
+            with_conversation_lock(with_llm_limit(post(reprocess_conversation_all_apps))),
+        )
+        .route("/v1/conversations/:id/app-results", get(get_app_results))
         .route(
             "/v1/conversations/:id/starred",
             patch(set_conversation_starred),
         )
+        .route(
+            "/v1/conversations/:id/action-items/reconcile",
+            with_conversation_lock(post(reconcile_conversation_action_items)),
+        )
+        .route(
+            "/v1/conversations/:id/events",
+            with_conversation_lock(patch(set_conversation_events_state)),
+        )
+        .route(
+            "/v1/conversations/:id/memory-extraction",
+            patch(set_conversation_memory_extraction),
+        )
+        .route(
+            "/v1/conversations/:id/export",
+            get(export_conversation),
+        )
+        .route(
+            "/v1/conversations/:id/estimate",
+            post(estimate_conversation_processing),
+        )
+        .route(
+            "/v1/conversations/:id/analytics",
+            get(get_conversation_analytics),
+        )
+        .route(
+            "/v1/conversations/:id/transcript/search",
+            get(search_conversation_transcript),
+        )
+        .route(
+            "/v1/conversations/:id/draft-email",
+            with_llm_limit(post(draft_follow_up_email)),
+        )
         .route(
             "/v1/conversations/:id/visibility",
             patch(set_conversation_visibility),
@@ -1152,8 +2600,23 @@ pub fn conversations_routes() -> Router<AppState> {
             "/v1/conversations/:id/shared",
             get(get_shared_conversation),
         )
+        .route(
+            "/v1/conversations/:id/feedback",
+            put(set_conversation_feedback),
+        )
+        .route(
+            "/v1/conversations/:id/history",
+            get(get_conversation_history),
+        )
+        .route(
+            "/v1/conversations/:id/history/:edit_id/restore",
+            with_conversation_lock(post(restore_conversation_edit)),
+        )
         .route(
             "/v1/conversations/:id",
-            get(get_conversation_by_id).patch(update_conversation).delete(delete_conversation),
+            // GETs pass through the lock check
+            with_conversation_lock(
+                get(get_conversation_by_id).patch(update_conversation).delete(delete_conversation),
+            ),
         )
 }
@@ -60,7 +60,7 @@ async fn get_daily_score(
         }
         Err(e) => {
             tracing::error!("Failed to calculate daily score: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -0,0 +1,197 @@
+// Device routes - OMI wearable pairing and health
+// Endpoints: GET/POST /v1/devices, GET/DELETE /v1/devices/{id},
+//            POST /v1/devices/{id}/heartbeat, GET /v1/devices/{id}/firmware
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::auth::AuthUser;
+use crate::models::{
+    DeviceFirmwareInfo, DeviceHeartbeatRequest, DeviceStatusResponse, RegisterDeviceRequest,
+};
+use crate::AppState;
+
+/// Status response for operations
+#[derive(Serialize)]
+struct StatusResponse {
+    status: String,
+}
+
+/// Trim optional strings and drop empty ones
+fn clean(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// GET /v1/devices - List paired devices with health info
+async fn get_devices(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<DeviceStatusResponse>>, StatusCode> {
+    tracing::info!("Getting devices for user {}", user.uid);
+
+    let latest_firmware = state.config.omi_latest_firmware_version.as_deref();
+    let now = Utc::now();
+
+    match state.firestore.get_devices(&user.uid).await {
+        Ok(devices) => Ok(Json(
+            devices
+                .into_iter()
+                .map(|d| DeviceStatusResponse::new(d, latest_firmware, now))
+                .collect(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to get devices: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/devices - Register (pair) a device
+async fn register_device(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<RegisterDeviceRequest>,
+) -> Result<Json<DeviceStatusResponse>, StatusCode> {
+    let serial_number = request.serial_number.trim();
+    if serial_number.is_empty() {
+        tracing::warn!("Empty device serial number");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    tracing::info!("Registering device {} for user {}", serial_number, user.uid);
+
+    match state
+        .firestore
+        .register_device(
+            &user.uid,
+            serial_number,
+            clean(&request.name),
+            clean(&request.model),
+            clean(&request.firmware_version),
+            clean(&request.hardware_revision),
+        )
+        .await
+    {
+        Ok(device) => Ok(Json(DeviceStatusResponse::new(
+            device,
+            state.config.omi_latest_firmware_version.as_deref(),
+            Utc::now(),
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to register device: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/devices/{id} - Get a paired device with health info
+async fn get_device(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(device_id): Path<String>,
+) -> Result<Json<DeviceStatusResponse>, StatusCode> {
+    match state.firestore.get_device(&user.uid, &device_id).await {
+        Ok(Some(device)) => Ok(Json(DeviceStatusResponse::new(
+            device,
+            state.config.omi_latest_firmware_version.as_deref(),
+            Utc::now(),
+        ))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get device: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// DELETE /v1/devices/{id} - Unpair a device
+async fn unpair_device(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(device_id): Path<String>,
+) -> Result<Json<StatusResponse>, StatusCode> {
+    tracing::info!("Unpairing device {} for user {}", device_id, user.uid);
+
+    match state.firestore.delete_device(&user.uid, &device_id).await {
+        Ok(()) => Ok(Json(StatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to unpair device: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/devices/{id}/heartbeat - Record battery/status from a connected device
+async fn device_heartbeat(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(device_id): Path<String>,
+    Json(request): Json<DeviceHeartbeatRequest>,
+) -> Result<Json<DeviceStatusResponse>, StatusCode> {
+    if let Some(level) = request.battery_level {
+        if !(0..=100).contains(&level) {
+            tracing::warn!("Invalid battery level: {}", level);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    match state
+        .firestore
+        .record_device_heartbeat(
+            &user.uid,
+            &device_id,
+            request.battery_level,
+            request.is_charging,
+            clean(&request.firmware_version),
+        )
+        .await
+    {
+        Ok(device) => Ok(Json(DeviceStatusResponse::new(
+            device,
+            state.config.omi_latest_firmware_version.as_deref(),
+            Utc::now(),
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to record device heartbeat: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/devices/{id}/firmware - Current vs latest firmware for a device
+async fn get_device_firmware(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(device_id): Path<String>,
+) -> Result<Json<DeviceFirmwareInfo>, StatusCode> {
+    match state.firestore.get_device(&user.uid, &device_id).await {
+        Ok(Some(device)) => Ok(Json(DeviceFirmwareInfo::new(
+            device.firmware_version.as_deref(),
+            state.config.omi_latest_firmware_version.as_deref(),
+        ))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get device: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+pub fn devices_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/devices", get(get_devices).post(register_device))
+        .route(
+            "/v1/devices/:id",
+            get(get_device).delete(unpair_device),
+        )
+        .route("/v1/devices/:id/heartbeat", post(device_heartbeat))
+        .route("/v1/devices/:id/firmware", get(get_device_firmware))
+}
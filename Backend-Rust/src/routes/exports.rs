@@ -0,0 +1,209 @@
+// Data export routes
+// Endpoints: POST/GET /v1/exports, GET /v1/exports/{id}, POST /v1/exports/{id}/link,
+// GET /v1/exports/{id}/download (signed link, no auth; supports Range/If-Range)
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+
+use crate::auth::AuthUser;
+use crate::job_queue::JobQueueFull;
+use crate::models::{DataExport, DataExportStatus, ExportDownloadQuery, ExportLinkResponse};
+use crate::services::data_export;
+use crate::services::firestore::FirestoreError;
+use crate::AppState;
+
+/// Exports listed per user
+const MAX_LISTED_EXPORTS: usize = 20;
+
+/// Headers of the GCS response passed on to the client
+const FORWARDED_HEADERS: &[header::HeaderName] = &[
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::ETAG,
+    header::LAST_MODIFIED,
+];
+
+/// POST /v1/exports - Start building an archive of the user's data
+async fn create_export(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<(StatusCode, Json<DataExport>), (StatusCode, String)> {
+    let Some(bucket) = state.config.exports_bucket.clone() else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "Data exports are not configured".to_string()));
+    };
+
+    let existing = state
+        .firestore
+        .get_user_data_exports(&user.uid, MAX_LISTED_EXPORTS)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get data exports: {}", e);
+            e.public_error()
+        })?;
+    let now = Utc::now();
+    for stale in existing.iter().filter(|e| data_export::is_stale(e, now)) {
+        tracing::warn!("Data export {} for user {} timed out", stale.id, user.uid);
+        let failed = DataExport {
+            status: DataExportStatus::Failed,
+            error: Some("Export timed out".to_string()),
+            ..stale.clone()
+        };
+        if let Err(e) = state.firestore.save_data_export(&failed).await {
+            tracing::error!("Failed to save data export {}: {}", failed.id, e);
+        }
+    }
+    if existing
+        .iter()
+        .any(|e| e.status == DataExportStatus::Running && !data_export::is_stale(e, now))
+    {
+        return Err((StatusCode::CONFLICT, "An export is already running".to_string()));
+    }
+
+    let export = data_export::start_export(state.firestore.clone(), &state.config, &state.jobs, bucket, &user.uid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to start data export: {}", e);
+            let status = if e.is::<JobQueueFull>() {
+                StatusCode::TOO_MANY_REQUESTS
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, format!("Failed to start data export: {}", e))
+        })?;
+    tracing::info!("Started data export {} for user {}", export.id, user.uid);
+    Ok((StatusCode::ACCEPTED, Json(export)))
+}
+
+/// GET /v1/exports - The user's exports, newest first
+async fn list_exports(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<DataExport>>, (StatusCode, String)> {
+    state
+        .firestore
+        .get_user_data_exports(&user.uid, MAX_LISTED_EXPORTS)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to get data exports: {}", e);
+            e.public_error()
+        })
+}
+
+/// An export owned by `uid`; another user's export is reported as missing
+async fn owned_export(state: &AppState, uid: &str, export_id: &str) -> Result<DataExport, (StatusCode, String)> {
+    match state.firestore.get_data_export(export_id).await {
+        Ok(Some(export)) if export.uid == uid => Ok(export),
+        Ok(_) => Err((StatusCode::NOT_FOUND, "Export not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get data export {}: {}", export_id, e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// GET /v1/exports/{id} - One export, for polling its status
+async fn get_export(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(export_id): Path<String>,
+) -> Result<Json<DataExport>, (StatusCode, String)> {
+    owned_export(&state, &user.uid, &export_id).await.map(Json)
+}
+
+/// POST /v1/exports/{id}/link - Signed download link for a completed export
+async fn create_download_link(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(export_id): Path<String>,
+) -> Result<Json<ExportLinkResponse>, (StatusCode, String)> {
+    let Some(secret) = state.config.export_signing_secret.as_deref() else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "Export downloads are not configured".to_string()));
+    };
+    let export = owned_export(&state, &user.uid, &export_id).await?;
+    let now = Utc::now();
+    data_export::downloadable_archive(&export, now)?;
+
+    let (url, expires_at) = data_export::download_link(secret, &export, now);
+    Ok(Json(ExportLinkResponse {
+        url,
+        expires_at,
+        size_bytes: export.size_bytes,
+    }))
+}
+
+/// GET /v1/exports/{id}/download - Stream the archive. Range and If-Range are forwarded to
+/// storage, so interrupted downloads resume with 206 Partial Content.
+async fn download_export(
+    State(state): State<AppState>,
+    Path(export_id): Path<String>,
+    Query(query): Query<ExportDownloadQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let (Some(bucket), Some(secret)) = (
+        state.config.exports_bucket.as_deref(),
+        state.config.export_signing_secret.as_deref(),
+    ) else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "Export downloads are not configured".to_string()));
+    };
+    let now = Utc::now();
+    if !data_export::verify_download(secret, &export_id, query.expires, &query.sig, now) {
+        return Err((StatusCode::FORBIDDEN, "Invalid or expired download link".to_string()));
+    }
+
+    let export = match state.firestore.get_data_export(&export_id).await {
+        Ok(Some(export)) => export,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Export not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get data export {}: {}", export_id, e);
+            return Err(e.public_error());
+        }
+    };
+    let storage_path = data_export::downloadable_archive(&export, now)?;
+
+    let header_str = |name: header::HeaderName| headers.get(name).and_then(|v| v.to_str().ok());
+    let upstream = state
+        .firestore
+        .open_export_archive(bucket, storage_path, header_str(header::RANGE), header_str(header::IF_RANGE))
+        .await
+        .map_err(|e| match e {
+            // Deleted by the cleanup job
+            FirestoreError::NotFound(_) => (StatusCode::GONE, "Export has expired".to_string()),
+            e => {
+                tracing::error!("Failed to open archive of data export {}: {}", export_id, e);
+                (StatusCode::BAD_GATEWAY, "Failed to read export archive".to_string())
+            }
+        })?;
+
+    let status = StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let filename = storage_path.rsplit('/').next().unwrap_or("omi-export.jsonl.gz");
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_TYPE, "application/gzip")
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename));
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = upstream.headers().get(name.as_str()) {
+            builder = builder.header(name, value.as_bytes());
+        }
+    }
+
+    builder
+        .body(Body::from_stream(upstream.bytes_stream()))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub fn exports_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/exports", post(create_export).get(list_exports))
+        .route("/v1/exports/:id", get(get_export))
+        .route("/v1/exports/:id/link", post(create_download_link))
+        .route("/v1/exports/:id/download", get(download_export))
+}
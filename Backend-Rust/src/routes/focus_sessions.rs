@@ -1,5 +1,6 @@
 // Focus Sessions routes
-// Endpoints: POST /v1/focus-sessions, GET /v1/focus-sessions, DELETE /v1/focus-sessions/{id}, GET /v1/focus-stats
+// Endpoints: POST /v1/focus-sessions, POST /v1/focus-sessions/batch, GET /v1/focus-sessions,
+//            DELETE /v1/focus-sessions/{id}, GET /v1/focus-sessions/{id}/context, GET /v1/focus-stats
 
 use axum::{
     extract::{Path, Query, State},
@@ -10,9 +11,12 @@ use axum::{
 
 use crate::auth::AuthUser;
 use crate::models::{
-    CreateFocusSessionRequest, FocusSessionDB, FocusSessionStatusResponse, FocusStats,
-    GetFocusSessionsQuery, GetFocusStatsQuery,
+    coalesce_focus_observations, continue_focus_session, BatchFocusObservationsRequest, BatchFocusObservationsResponse,
+    CreateFocusSessionRequest, FocusSessionContextResponse, FocusSessionDB, FocusSessionStatusResponse,
+    FocusStats, GetFocusSessionsQuery, GetFocusStatsQuery,
 };
+use crate::services::events::AppEvent;
+use crate::services::focus_context;
 use crate::AppState;
 
 /// POST /v1/focus-sessions - Create a new focus session
@@ -39,10 +43,81 @@ async fn create_focus_session(
         )
         .await
     {
-        Ok(session) => Ok(Json(session)),
+        Ok(session) => {
+            state.events.publish(AppEvent::FocusSessionCreated {
+                uid: user.uid.clone(),
+                session: std::sync::Arc::new(session.clone()),
+            });
+            Ok(Json(session))
+        }
         Err(e) => {
             tracing::error!("Failed to create focus session: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
+        }
+    }
+}
+
+/// Maximum observations accepted per batch
+const MAX_BATCH_OBSERVATIONS: usize = 2000;
+
+/// Samples further apart than this start a new session even for the same app
+const MAX_OBSERVATION_GAP_SECONDS: i64 = 120;
+
+/// POST /v1/focus-sessions/batch - Ingest sampled foreground-app observations
+/// Consecutive same-app samples are coalesced into sessions with durations.
+/// Re-sending the same batch is idempotent (session IDs are deterministic).
+async fn create_focus_sessions_batch(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<BatchFocusObservationsRequest>,
+) -> Result<Json<BatchFocusObservationsResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Ingesting {} focus observations for user {}",
+        request.observations.len(),
+        user.uid
+    );
+
+    if request.observations.len() > MAX_BATCH_OBSERVATIONS {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("At most {} observations per batch", MAX_BATCH_OBSERVATIONS),
+        ));
+    }
+    if !(1..=300).contains(&request.sample_interval_seconds) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "sample_interval_seconds must be between 1 and 300".to_string(),
+        ));
+    }
+
+    let max_gap = MAX_OBSERVATION_GAP_SECONDS.max(request.sample_interval_seconds * 2);
+    let (mut sessions, observations_accepted) =
+        coalesce_focus_observations(&request.observations, request.sample_interval_seconds, max_gap);
+
+    // A session still running when the previous batch was sent keeps its first event's
+    // start (and ID) rather than starting over at this batch's first sample
+    if let Some(first) = sessions.first() {
+        let previous = state
+            .firestore
+            .get_focus_session_started_before(&user.uid, first.started_at)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get previous focus session: {}", e);
+                (e.http_status(), "Failed to store focus sessions".to_string())
+            })?;
+        if let Some(previous) = previous {
+            continue_focus_session(&mut sessions, &previous, request.sample_interval_seconds, max_gap);
+        }
+    }
+
+    match state.firestore.upsert_focus_sessions(&user.uid, &sessions).await {
+        Ok(sessions) => Ok(Json(BatchFocusObservationsResponse {
+            observations_accepted,
+            sessions,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to store focus session batch: {}", e);
+            Err((e.http_status(), "Failed to store focus sessions".to_string()))
         }
     }
 }
@@ -92,6 +167,33 @@ async fn delete_focus_session(
         })),
         Err(e) => {
             tracing::error!("Failed to delete focus session: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/focus-sessions/{id}/context - Conversations overlapping the session and
+/// what was said during it
+async fn get_focus_session_context(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(session_id): Path<String>,
+) -> Result<Json<FocusSessionContextResponse>, StatusCode> {
+    tracing::info!("Getting context of focus session {} for user {}", session_id, user.uid);
+
+    let session = match state.firestore.get_focus_session(&user.uid, &session_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get focus session: {}", e);
+            return Err(e.http_status());
+        }
+    };
+
+    match focus_context::conversations_for_session(&state.firestore, &user.uid, &session).await {
+        Ok(conversations) => Ok(Json(FocusSessionContextResponse { session, conversations })),
+        Err(e) => {
+            tracing::error!("Failed to get conversations for focus session: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
@@ -113,7 +215,7 @@ async fn get_focus_stats(
         Ok(stats) => Ok(Json(stats)),
         Err(e) => {
             tracing::error!("Failed to get focus stats: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -124,6 +226,8 @@ pub fn focus_sessions_routes() -> Router<AppState> {
             "/v1/focus-sessions",
             get(get_focus_sessions).post(create_focus_session),
         )
+        .route("/v1/focus-sessions/batch", axum::routing::post(create_focus_sessions_batch))
         .route("/v1/focus-sessions/:id", axum::routing::delete(delete_focus_session))
+        .route("/v1/focus-sessions/:id/context", get(get_focus_session_context))
         .route("/v1/focus-stats", get(get_focus_stats))
 }
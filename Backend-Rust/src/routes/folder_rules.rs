@@ -0,0 +1,183 @@
+// Folder rule routes - Automatic filing of conversations into folders
+// Endpoints: GET/POST /v1/folder-rules, PUT/DELETE /v1/folder-rules/:id,
+// POST /v1/folder-rules/reorder, POST /v1/folder-rules/backfill
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::Utc;
+
+use crate::auth::AuthUser;
+use crate::models::{
+    FolderRule, FolderRuleBackfillRequest, FolderRuleBackfillResponse, ReorderFolderRulesRequest,
+    SaveFolderRuleRequest, MAX_FOLDER_RULES,
+};
+use crate::services::folder_rules;
+use crate::AppState;
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    tracing::error!("Folder rule error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to access folder rules".to_string())
+}
+
+/// Validate the request, including that the target folder exists
+async fn validated(
+    state: &AppState,
+    uid: &str,
+    request: SaveFolderRuleRequest,
+) -> Result<SaveFolderRuleRequest, (StatusCode, String)> {
+    let request = folder_rules::clean_request(request).map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    let folders = state.firestore.get_folders(uid).await.map_err(internal_error)?;
+    if !folders.iter().any(|f| f.id == request.folder_id) {
+        return Err((StatusCode::BAD_REQUEST, format!("Folder {} not found", request.folder_id)));
+    }
+    Ok(request)
+}
+
+/// GET /v1/folder-rules - List the user's rules in evaluation order
+async fn list_rules(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<FolderRule>>, (StatusCode, String)> {
+    state
+        .firestore
+        .get_folder_rules(&user.uid)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// POST /v1/folder-rules - Create a rule, evaluated after the existing ones
+async fn create_rule(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<SaveFolderRuleRequest>,
+) -> Result<Json<FolderRule>, (StatusCode, String)> {
+    let request = validated(&state, &user.uid, request).await?;
+    let existing = state.firestore.get_folder_rules(&user.uid).await.map_err(internal_error)?;
+    if existing.len() >= MAX_FOLDER_RULES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} folder rules are allowed", MAX_FOLDER_RULES),
+        ));
+    }
+
+    let now = Utc::now();
+    let rule = FolderRule {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name,
+        conditions: request.conditions,
+        folder_id: request.folder_id,
+        enabled: request.enabled,
+        order: existing.iter().map(|r| r.order + 1).max().unwrap_or(0),
+        created_at: now,
+        updated_at: now,
+    };
+    state
+        .firestore
+        .save_folder_rule(&user.uid, &rule)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(rule))
+}
+
+/// PUT /v1/folder-rules/:id - Replace a rule, keeping its place in the order
+async fn update_rule(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(rule_id): Path<String>,
+    Json(request): Json<SaveFolderRuleRequest>,
+) -> Result<Json<FolderRule>, (StatusCode, String)> {
+    let request = validated(&state, &user.uid, request).await?;
+    let existing = state
+        .firestore
+        .get_folder_rules(&user.uid)
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .find(|r| r.id == rule_id)
+        .ok_or((StatusCode::NOT_FOUND, "Folder rule not found".to_string()))?;
+
+    let rule = FolderRule {
+        id: existing.id,
+        name: request.name,
+        conditions: request.conditions,
+        folder_id: request.folder_id,
+        enabled: request.enabled,
+        order: existing.order,
+        created_at: existing.created_at,
+        updated_at: Utc::now(),
+    };
+    state
+        .firestore
+        .save_folder_rule(&user.uid, &rule)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(rule))
+}
+
+/// DELETE /v1/folder-rules/:id - Delete a rule
+async fn delete_rule(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(rule_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .firestore
+        .delete_folder_rule(&user.uid, &rule_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /v1/folder-rules/reorder - Set the evaluation order; the first matching rule wins
+async fn reorder_rules(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<ReorderFolderRulesRequest>,
+) -> Result<Json<Vec<FolderRule>>, (StatusCode, String)> {
+    let rules = state.firestore.get_folder_rules(&user.uid).await.map_err(internal_error)?;
+    let order = folder_rules::reordered(&rules, &request.rule_ids);
+
+    let mut reordered = Vec::with_capacity(rules.len());
+    for (index, rule_id) in order.iter().enumerate() {
+        let Some(rule) = rules.iter().find(|r| &r.id == rule_id) else {
+            continue;
+        };
+        let mut rule = rule.clone();
+        if rule.order != index as i32 {
+            rule.order = index as i32;
+            state
+                .firestore
+                .save_folder_rule(&user.uid, &rule)
+                .await
+                .map_err(internal_error)?;
+        }
+        reordered.push(rule);
+    }
+    Ok(Json(reordered))
+}
+
+/// POST /v1/folder-rules/backfill - Apply the rules to existing conversations
+async fn backfill_rules(
+    State(state): State<AppState>,
+    user: AuthUser,
+    request: Option<Json<FolderRuleBackfillRequest>>,
+) -> Result<Json<FolderRuleBackfillResponse>, (StatusCode, String)> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    folder_rules::backfill(&state.firestore, &user.uid, &request)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+pub fn folder_rules_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/folder-rules", get(list_rules).post(create_rule))
+        .route("/v1/folder-rules/reorder", post(reorder_rules))
+        .route("/v1/folder-rules/backfill", post(backfill_rules))
+        .route("/v1/folder-rules/:rule_id", put(update_rule).delete(delete_rule))
+}
@@ -51,7 +51,7 @@ async fn get_folders(
         Ok(folders) => Ok(Json(folders)),
         Err(e) => {
             tracing::error!("Failed to get folders: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -77,7 +77,7 @@ async fn create_folder(
         Ok(folder) => Ok(Json(folder)),
         Err(e) => {
             tracing::error!("Failed to create folder: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -106,7 +106,7 @@ async fn update_folder(
         Ok(folder) => Ok(Json(folder)),
         Err(e) => {
             tracing::error!("Failed to update folder: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -151,7 +151,7 @@ async fn reorder_folders(
         })),
         Err(e) => {
             tracing::error!("Failed to reorder folders: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -178,7 +178,7 @@ async fn bulk_move_conversations(
         Ok(moved_count) => Ok(Json(BulkMoveResponse { moved_count })),
         Err(e) => {
             tracing::error!("Failed to bulk move conversations: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -207,7 +207,7 @@ async fn move_conversation_to_folder(
         })),
         Err(e) => {
             tracing::error!("Failed to move conversation to folder: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
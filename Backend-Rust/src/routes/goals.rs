@@ -26,7 +26,7 @@ async fn get_completed_goals(
         Ok(goals) => Ok(Json(GoalsListResponse { goals })),
         Err(e) => {
             tracing::error!("Failed to get completed goals: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -42,7 +42,7 @@ async fn get_all_goals(
         Ok(goals) => Ok(Json(GoalsListResponse { goals })),
         Err(e) => {
             tracing::error!("Failed to get goals: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -86,7 +86,7 @@ async fn create_goal(
         Ok(goal) => Ok(Json(goal)),
         Err(e) => {
             tracing::error!("Failed to create goal: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -124,7 +124,7 @@ async fn update_goal(
         Ok(goal) => Ok(Json(goal)),
         Err(e) => {
             tracing::error!("Failed to update goal: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -151,7 +151,7 @@ async fn update_goal_progress(
         Ok(goal) => Ok(Json(goal)),
         Err(e) => {
             tracing::error!("Failed to update goal progress: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -178,7 +178,7 @@ async fn get_goal_history(
         Ok(history) => Ok(Json(GoalHistoryResponse { history })),
         Err(e) => {
             tracing::error!("Failed to get goal history: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -213,7 +213,7 @@ async fn delete_goal(
         })),
         Err(e) => {
             tracing::error!("Failed to soft-delete goal: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -0,0 +1,272 @@
+// Accountability group routes - Opt-in focus score sharing
+// Endpoints: GET/POST /v1/groups, POST /v1/groups/join, DELETE /v1/groups/:id,
+//            POST /v1/groups/:id/leave, PATCH /v1/groups/:id/membership, GET /v1/groups/:id/leaderboard
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use chrono::Utc;
+
+use crate::auth::AuthUser;
+use crate::models::{
+    AccountabilityGroup, CreateGroupRequest, GroupLeaderboard, GroupMember, JoinGroupRequest,
+    LeaderboardQuery, UpdateGroupMembershipRequest, MAX_GROUP_MEMBERS, MAX_LEADERBOARD_DAYS,
+};
+use crate::services::accountability;
+use crate::AppState;
+
+const MAX_GROUP_NAME_CHARS: usize = 60;
+const MAX_DISPLAY_NAME_CHARS: usize = 40;
+
+fn internal_error(context: &str, e: impl std::fmt::Display) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+/// Requested display name, falling back to the account name
+fn resolve_display_name(
+    requested: Option<&str>,
+    user: &AuthUser,
+) -> Result<String, (StatusCode, String)> {
+    let name = requested
+        .or(user.name.as_deref())
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or("Member");
+    if name.chars().count() > MAX_DISPLAY_NAME_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Display name is limited to {} characters", MAX_DISPLAY_NAME_CHARS),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Load a group the caller belongs to. Non-members get 404 so group IDs don't leak.
+async fn get_member_group(
+    state: &AppState,
+    group_id: &str,
+    uid: &str,
+) -> Result<AccountabilityGroup, (StatusCode, String)> {
+    state
+        .firestore
+        .get_accountability_group(group_id)
+        .await
+        .map_err(|e| internal_error("Failed to get group", e))?
+        .filter(|g| g.member_uids.iter().any(|m| m == uid))
+        .ok_or((StatusCode::NOT_FOUND, "Group not found".to_string()))
+}
+
+/// GET /v1/groups - Groups the user belongs to
+async fn get_groups(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<AccountabilityGroup>>, (StatusCode, String)> {
+    state
+        .firestore
+        .get_user_accountability_groups(&user.uid)
+        .await
+        .map(Json)
+        .map_err(|e| internal_error("Failed to get groups", e))
+}
+
+/// POST /v1/groups - Create a group; the creator joins as owner and shares focus scores
+async fn create_group(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<CreateGroupRequest>,
+) -> Result<Json<AccountabilityGroup>, (StatusCode, String)> {
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_GROUP_NAME_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Group name must be 1-{} characters", MAX_GROUP_NAME_CHARS),
+        ));
+    }
+    let display_name = resolve_display_name(request.display_name.as_deref(), &user)?;
+
+    let now = Utc::now();
+    let group = AccountabilityGroup {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        owner_uid: user.uid.clone(),
+        invite_code: accountability::generate_invite_code(),
+        member_uids: vec![user.uid.clone()],
+        member_count: 1,
+        created_at: now,
+    };
+    let owner = GroupMember {
+        uid: user.uid.clone(),
+        display_name,
+        share_focus: true,
+        joined_at: now,
+    };
+
+    state
+        .firestore
+        .create_accountability_group(&group, &owner)
+        .await
+        .map_err(|e| internal_error("Failed to create group", e))?;
+
+    Ok(Json(group))
+}
+
+/// POST /v1/groups/join - Join a group by invite code
+async fn join_group(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<JoinGroupRequest>,
+) -> Result<Json<AccountabilityGroup>, (StatusCode, String)> {
+    let code = accountability::normalize_invite_code(&request.invite_code);
+    let display_name = resolve_display_name(request.display_name.as_deref(), &user)?;
+
+    let mut group = state
+        .firestore
+        .get_accountability_group_by_invite_code(&code)
+        .await
+        .map_err(|e| internal_error("Failed to look up invite code", e))?
+        .ok_or((StatusCode::NOT_FOUND, "Invalid invite code".to_string()))?;
+
+    if group.member_uids.contains(&user.uid) {
+        return Err((StatusCode::CONFLICT, "Already a member of this group".to_string()));
+    }
+    if group.member_uids.len() >= MAX_GROUP_MEMBERS {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Groups are limited to {} members", MAX_GROUP_MEMBERS),
+        ));
+    }
+
+    let member = GroupMember {
+        uid: user.uid.clone(),
+        display_name,
+        share_focus: true,
+        joined_at: Utc::now(),
+    };
+    state
+        .firestore
+        .add_group_member(&group.id, &member)
+        .await
+        .map_err(|e| internal_error("Failed to join group", e))?;
+
+    tracing::info!("User {} joined accountability group {}", user.uid, group.id);
+    group.member_uids.push(user.uid);
+    group.member_count = group.member_uids.len();
+    Ok(Json(group))
+}
+
+/// POST /v1/groups/:id/leave - Leave a group. An owner can only leave once alone,
+/// which deletes the group.
+async fn leave_group(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(group_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let group = get_member_group(&state, &group_id, &user.uid).await?;
+
+    if group.owner_uid == user.uid {
+        if group.member_uids.len() > 1 {
+            return Err((
+                StatusCode::CONFLICT,
+                "The owner can't leave while others are in the group; delete it instead".to_string(),
+            ));
+        }
+        state
+            .firestore
+            .delete_accountability_group(&group_id)
+            .await
+            .map_err(|e| internal_error("Failed to delete group", e))?;
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    state
+        .firestore
+        .remove_group_member(&group_id, &user.uid)
+        .await
+        .map_err(|e| internal_error("Failed to leave group", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /v1/groups/:id - Delete a group (owner only)
+async fn delete_group(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(group_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let group = get_member_group(&state, &group_id, &user.uid).await?;
+    if group.owner_uid != user.uid {
+        return Err((StatusCode::FORBIDDEN, "Only the owner can delete the group".to_string()));
+    }
+
+    state
+        .firestore
+        .delete_accountability_group(&group_id)
+        .await
+        .map_err(|e| internal_error("Failed to delete group", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /v1/groups/:id/membership - Toggle focus sharing or change display name
+async fn update_membership(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(group_id): Path<String>,
+    Json(request): Json<UpdateGroupMembershipRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    get_member_group(&state, &group_id, &user.uid).await?;
+
+    let display_name = match request.display_name.as_deref() {
+        Some(name) if name.trim().is_empty() => {
+            return Err((StatusCode::BAD_REQUEST, "Display name can't be empty".to_string()))
+        }
+        Some(name) => Some(resolve_display_name(Some(name), &user)?),
+        None => None,
+    };
+
+    state
+        .firestore
+        .update_group_member(&group_id, &user.uid, request.share_focus, display_name.as_deref())
+        .await
+        .map_err(|e| internal_error("Failed to update membership", e))?;
+    Ok(StatusCode::OK)
+}
+
+/// GET /v1/groups/:id/leaderboard?days=7 - Focus scores of members who share them
+async fn get_leaderboard(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(group_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<GroupLeaderboard>, (StatusCode, String)> {
+    if query.days == 0 || query.days > MAX_LEADERBOARD_DAYS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("days must be between 1 and {}", MAX_LEADERBOARD_DAYS),
+        ));
+    }
+
+    let group = get_member_group(&state, &group_id, &user.uid).await?;
+    let members = state
+        .firestore
+        .get_group_members(&group_id)
+        .await
+        .map_err(|e| internal_error("Failed to get group members", e))?;
+
+    let leaderboard =
+        accountability::build_leaderboard(&state.firestore, &group, &members, &user.uid, query.days)
+            .await;
+    Ok(Json(leaderboard))
+}
+
+pub fn groups_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/groups", get(get_groups).post(create_group))
+        .route("/v1/groups/join", post(join_group))
+        .route("/v1/groups/:id", delete(delete_group))
+        .route("/v1/groups/:id/leave", post(leave_group))
+        .route("/v1/groups/:id/membership", patch(update_membership))
+        .route("/v1/groups/:id/leaderboard", get(get_leaderboard))
+}
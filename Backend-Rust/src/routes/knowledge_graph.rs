@@ -11,7 +11,7 @@ use serde::Deserialize;
 use std::collections::HashMap;
 
 use crate::auth::AuthUser;
-use crate::llm::LlmClient;
+use crate::llm_limit::with_llm_limit;
 use crate::models::{
     KnowledgeGraphEdge, KnowledgeGraphNode, KnowledgeGraphResponse, KnowledgeGraphStatusResponse,
     NodeType, RebuildGraphResponse,
@@ -62,8 +62,8 @@ async fn rebuild_knowledge_graph(
 
     let limit = query.limit.unwrap_or(500);
 
-    // Check for Gemini API key
-    let api_key = state.config.gemini_api_key.clone().ok_or_else(|| {
+    // The user's own key if they brought one, else the server key
+    let llm = state.llm_client(&user.uid).await?.ok_or_else(|| {
         tracing::error!("Gemini API key not configured");
         StatusCode::SERVICE_UNAVAILABLE
     })?;
@@ -93,9 +93,6 @@ async fn rebuild_knowledge_graph(
 
     tracing::info!("Processing {} memories for knowledge graph", memories.len());
 
-    // Create LLM client
-    let llm = LlmClient::new(api_key);
-
     // Track nodes by lowercase label for deduplication
     let mut node_map: HashMap<String, KnowledgeGraphNode> = HashMap::new();
     let mut edges: Vec<KnowledgeGraphEdge> = Vec::new();
@@ -256,6 +253,6 @@ async fn delete_knowledge_graph(
 pub fn knowledge_graph_routes() -> Router<AppState> {
     Router::new()
         .route("/v1/knowledge-graph", get(get_knowledge_graph))
-        .route("/v1/knowledge-graph/rebuild", post(rebuild_knowledge_graph))
+        .route("/v1/knowledge-graph/rebuild", with_llm_limit(post(rebuild_knowledge_graph)))
         .route("/v1/knowledge-graph", delete(delete_knowledge_graph))
 }
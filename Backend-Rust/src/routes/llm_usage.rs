@@ -31,7 +31,7 @@ async fn record_llm_usage(
         })),
         Err(e) => {
             tracing::error!("LLM usage write failed for {}: {}", user.uid, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -47,7 +47,7 @@ async fn get_total_llm_cost(
         }
         Err(e) => {
             tracing::error!("LLM total cost fetch failed for {}: {}", user.uid, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
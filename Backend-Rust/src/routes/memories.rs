@@ -1,5 +1,6 @@
 // Memories routes - Port from Python backend
 // Endpoints: GET, POST, DELETE, PATCH /v3/memories
+// Review queue: GET /v3/memories/pending-review, POST /v3/memories/review-batch
 
 use axum::{
     extract::{Path, Query, State},
@@ -9,10 +10,16 @@ use axum::{
 };
 
 use crate::auth::AuthUser;
+use crate::etag::with_etag;
 use crate::models::{
     CreateMemoryRequest, CreateMemoryResponse, EditMemoryRequest, GetMemoriesQuery, MemoryDB,
-    MemoryStatusResponse, ReviewMemoryRequest, UpdateMemoryReadRequest, UpdateVisibilityRequest,
+    MemoryReviewBatchRequest, MemoryReviewBatchResponse, MemoryStatusResponse, PendingReviewQuery,
+    PendingReviewResponse, ReviewMemoryRequest, UpdateMemoryReadRequest, UpdateVisibilityRequest,
+    MAX_MEMORY_REVIEW_BATCH_IDS, MAX_PENDING_REVIEW_LIMIT,
 };
+use crate::services::firestore::MEMORIES_SUBCOLLECTION;
+use crate::services::memory_review::{self, MAX_PENDING_SCAN};
+use crate::services::persona_pages;
 use crate::AppState;
 
 /// GET /v3/memories - Fetch user memories with optional filtering
@@ -41,7 +48,7 @@ async fn get_memories(
     );
 
     match state
-        .firestore
+        .storage
         .get_memories_filtered(
             &user.uid,
             query.limit,
@@ -75,7 +82,7 @@ async fn create_memory(
     );
 
     match state
-        .firestore
+        .storage
         .create_memory(
             &user.uid,
             &request.content,
@@ -98,7 +105,7 @@ async fn create_memory(
         })),
         Err(e) => {
             tracing::error!("Failed to create memory: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -111,13 +118,16 @@ async fn delete_memory(
 ) -> Result<Json<MemoryStatusResponse>, StatusCode> {
     tracing::info!("Deleting memory {} for user {}", memory_id, user.uid);
 
-    match state.firestore.delete_memory(&user.uid, &memory_id).await {
-        Ok(()) => Ok(Json(MemoryStatusResponse {
-            status: "ok".to_string(),
-        })),
+    match state.storage.delete_memory(&user.uid, &memory_id).await {
+        Ok(()) => {
+            persona_pages::invalidate_for_user(&state.firestore, state.redis.as_deref(), &user.uid).await;
+            Ok(Json(MemoryStatusResponse {
+                status: "ok".to_string(),
+            }))
+        }
         Err(e) => {
             tracing::error!("Failed to delete memory: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -132,16 +142,19 @@ async fn edit_memory(
     tracing::info!("Editing memory {} for user {}", memory_id, user.uid);
 
     match state
-        .firestore
+        .storage
         .update_memory_content(&user.uid, &memory_id, &request.value)
         .await
     {
-        Ok(()) => Ok(Json(MemoryStatusResponse {
-            status: "ok".to_string(),
-        })),
+        Ok(()) => {
+            persona_pages::invalidate_for_user(&state.firestore, state.redis.as_deref(), &user.uid).await;
+            Ok(Json(MemoryStatusResponse {
+                status: "ok".to_string(),
+            }))
+        }
         Err(e) => {
             tracing::error!("Failed to edit memory: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -160,16 +173,19 @@ async fn update_visibility(
     );
 
     match state
-        .firestore
+        .storage
         .update_memory_visibility(&user.uid, &memory_id, &request.value)
         .await
     {
-        Ok(()) => Ok(Json(MemoryStatusResponse {
-            status: "ok".to_string(),
-        })),
+        Ok(()) => {
+            persona_pages::invalidate_for_user(&state.firestore, state.redis.as_deref(), &user.uid).await;
+            Ok(Json(MemoryStatusResponse {
+                status: "ok".to_string(),
+            }))
+        }
         Err(e) => {
             tracing::error!("Failed to update memory visibility: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -198,11 +214,84 @@ async fn review_memory(
         })),
         Err(e) => {
             tracing::error!("Failed to review memory: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
 
+/// GET /v3/memories/pending-review - Memories awaiting review, newest first, with counts
+async fn get_pending_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<PendingReviewQuery>,
+) -> Result<Json<PendingReviewResponse>, (StatusCode, String)> {
+    let limit = query.limit.clamp(1, MAX_PENDING_REVIEW_LIMIT);
+
+    let (pending, total) = tokio::try_join!(
+        state.firestore.get_pending_review_memories(&user.uid, MAX_PENDING_SCAN),
+        state
+            .firestore
+            .count_user_documents_where(&user.uid, MEMORIES_SUBCOLLECTION, &[("reviewed", false)]),
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to load pending memories for {}: {}", user.uid, e);
+        e.public_error()
+    })?;
+
+    // Category counts cover the scanned memories; the total covers the whole queue
+    let mut response = memory_review::pending_review(pending, limit);
+    response.pending = response.pending.max(total.max(0) as usize);
+    Ok(Json(response))
+}
+
+/// POST /v3/memories/review-batch - Approve and reject several memories at once
+async fn review_memories_batch(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<MemoryReviewBatchRequest>,
+) -> Result<Json<MemoryReviewBatchResponse>, (StatusCode, String)> {
+    let MemoryReviewBatchRequest { mut approve, mut reject } = request;
+    if approve.len() + reject.len() > MAX_MEMORY_REVIEW_BATCH_IDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} memory IDs per batch", MAX_MEMORY_REVIEW_BATCH_IDS),
+        ));
+    }
+    for ids in [&mut approve, &mut reject] {
+        ids.sort();
+        ids.dedup();
+    }
+    if let Some(id) = approve.iter().find(|id| reject.binary_search(id).is_ok()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Memory {} is both approved and rejected", id),
+        ));
+    }
+
+    let review = |ids: Vec<String>, value: bool| {
+        let state = &state;
+        let uid = &user.uid;
+        async move {
+            if ids.is_empty() {
+                return Ok((0, Vec::new()));
+            }
+            state.firestore.review_memories(uid, &ids, value).await
+        }
+    };
+    let ((approved, mut not_found), (rejected, rejected_not_found)) =
+        tokio::try_join!(review(approve, true), review(reject, false)).map_err(|e| {
+            tracing::error!("Failed to review memories for {}: {}", user.uid, e);
+            e.public_error()
+        })?;
+    not_found.extend(rejected_not_found);
+
+    Ok(Json(MemoryReviewBatchResponse {
+        approved,
+        rejected,
+        not_found,
+    }))
+}
+
 /// PATCH /v3/memories/:id/read - Update memory read/dismissed status
 async fn update_memory_read(
     State(state): State<AppState>,
@@ -220,7 +309,7 @@ async fn update_memory_read(
         Ok(memory) => Ok(Json(memory)),
         Err(e) => {
             tracing::error!("Failed to update memory read status: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -241,7 +330,7 @@ async fn mark_all_read(
         }
         Err(e) => {
             tracing::error!("Failed to mark all memories as read: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -269,13 +358,14 @@ async fn update_all_visibility(
                 count,
                 user.uid
             );
+            persona_pages::invalidate_for_user(&state.firestore, state.redis.as_deref(), &user.uid).await;
             Ok(Json(MemoryStatusResponse {
                 status: format!("updated {} memories", count),
             }))
         }
         Err(e) => {
             tracing::error!("Failed to update all memories visibility: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -290,13 +380,14 @@ async fn delete_all_memories(
     match state.firestore.delete_all_memories(&user.uid).await {
         Ok(count) => {
             tracing::info!("Deleted {} memories for user {}", count, user.uid);
+            persona_pages::invalidate_for_user(&state.firestore, state.redis.as_deref(), &user.uid).await;
             Ok(Json(MemoryStatusResponse {
                 status: format!("deleted {} memories", count),
             }))
         }
         Err(e) => {
             tracing::error!("Failed to delete all memories: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -305,10 +396,14 @@ pub fn memories_routes() -> Router<AppState> {
     Router::new()
         .route(
             "/v3/memories",
-            get(get_memories)
-                .post(create_memory)
-                .delete(delete_all_memories),
+            with_etag(
+                get(get_memories)
+                    .post(create_memory)
+                    .delete(delete_all_memories),
+            ),
         )
+        .route("/v3/memories/pending-review", with_etag(get(get_pending_review)))
+        .route("/v3/memories/review-batch", post(review_memories_batch))
         .route("/v3/memories/mark-all-read", post(mark_all_read))
         .route("/v3/memories/visibility", patch(update_all_visibility))
         .route("/v3/memories/:id", delete(delete_memory).patch(edit_memory))
@@ -0,0 +1,197 @@
+// Memos routes - Quick voice-note style memos, outside the conversation pipeline
+// Endpoints: POST /v1/memos, GET /v1/memos, GET /v1/memos/search, DELETE /v1/memos/:id
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::Utc;
+
+use crate::auth::AuthUser;
+use crate::llm_limit::with_llm_limit;
+use crate::models::{CreateMemoRequest, GetMemosQuery, Memo, SearchMemosQuery, MAX_MEMO_CHARS};
+use crate::services::language;
+use crate::AppState;
+
+/// Memos scanned by search (Firestore has no full-text search)
+const MAX_SEARCH_MEMOS: usize = 500;
+
+/// First few words of the memo, used when the LLM title is unavailable
+fn fallback_title(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut title = words.iter().take(8).copied().collect::<Vec<_>>().join(" ");
+    if words.len() > 8 {
+        title.push_str("...");
+    }
+    title
+}
+
+/// POST /v1/memos - Create a memo with an LLM title and optional action items
+async fn create_memo(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<CreateMemoRequest>,
+) -> Result<Json<Memo>, (StatusCode, String)> {
+    let text = request.text.trim();
+    if text.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Memo text is required".to_string()));
+    }
+    if text.chars().count() > MAX_MEMO_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Memo text is limited to {} characters", MAX_MEMO_CHARS),
+        ));
+    }
+
+    let llm = match state.llm_client(&user.uid).await? {
+        Some(llm) => llm,
+        None => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "GEMINI_API_KEY not configured".to_string(),
+            ))
+        }
+    };
+
+    let language = request
+        .language
+        .clone()
+        .filter(|l| !l.trim().is_empty())
+        .or_else(|| language::detect_language(text).map(|l| l.to_string()));
+
+    let title = match llm.generate_memo_title(text).await {
+        Ok(title) if !title.is_empty() => title,
+        Ok(_) => fallback_title(text),
+        Err(e) => {
+            tracing::warn!("Failed to generate memo title: {}", e);
+            fallback_title(text)
+        }
+    };
+
+    let mut action_items = vec![];
+    if request.extract_action_items {
+        let now = Utc::now();
+        let extracted = llm
+            .extract_action_items(
+                text,
+                &now.to_rfc3339(),
+                request.timezone.as_deref().unwrap_or("UTC"),
+                language.as_deref().unwrap_or("en"),
+                &[],
+                None,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to extract memo action items: {}", e);
+                vec![]
+            });
+
+        for item in extracted {
+            match state
+                .firestore
+                .create_staged_task(
+                    &user.uid,
+                    &item.description,
+                    item.due_at,
+                    Some("memo"),
+                    item.priority.as_deref(),
+                    item.due_metadata().as_deref(),
+                    None,
+                    None,
+                )
+                .await
+            {
+                Ok(_) => action_items.push(item.description),
+                Err(e) => tracing::error!("Failed to stage memo action item: {}", e),
+            }
+        }
+    }
+
+    let memo = Memo {
+        id: uuid::Uuid::new_v4().to_string(),
+        text: text.to_string(),
+        title,
+        action_items,
+        language,
+        created_at: Utc::now(),
+    };
+
+    state.firestore.create_memo(&user.uid, &memo).await.map_err(|e| {
+        tracing::error!("Failed to save memo: {}", e);
+        e.public_error()
+    })?;
+
+    Ok(Json(memo))
+}
+
+/// GET /v1/memos - List memos, newest first
+async fn get_memos(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<GetMemosQuery>,
+) -> Result<Json<Vec<Memo>>, (StatusCode, String)> {
+    state
+        .firestore
+        .get_memos(&user.uid, query.limit.min(500), query.offset)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to get memos: {}", e);
+            e.public_error()
+        })
+}
+
+/// GET /v1/memos/search?q= - Case-insensitive search over memo titles and text
+async fn search_memos(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<SearchMemosQuery>,
+) -> Result<Json<Vec<Memo>>, (StatusCode, String)> {
+    let needle = query.q.trim().to_lowercase();
+    if needle.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Query is required".to_string()));
+    }
+
+    let memos = state
+        .firestore
+        .get_memos(&user.uid, MAX_SEARCH_MEMOS, 0)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get memos for search: {}", e);
+            e.public_error()
+        })?;
+
+    let matches = memos
+        .into_iter()
+        .filter(|m| {
+            m.title.to_lowercase().contains(&needle) || m.text.to_lowercase().contains(&needle)
+        })
+        .take(query.limit)
+        .collect();
+
+    Ok(Json(matches))
+}
+
+/// DELETE /v1/memos/:id - Delete a memo
+async fn delete_memo(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(memo_id): Path<String>,
+) -> StatusCode {
+    match state.firestore.delete_memo(&user.uid, &memo_id).await {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(e) => {
+            tracing::error!("Failed to delete memo: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub fn memos_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/memos", get(get_memos).merge(with_llm_limit(post(create_memo))))
+        .route("/v1/memos/search", get(search_memos))
+        .route("/v1/memos/:id", delete(delete_memo))
+}
@@ -1,20 +1,36 @@
 // Chat Messages routes - For chat persistence
-// Endpoints: POST, GET, DELETE /v2/messages, PATCH /v2/messages/{id}/rating
+// Endpoints: POST, GET, DELETE /v2/messages, PATCH /v2/messages/{id}/rating,
+// POST /v2/messages/attachments, GET /v2/messages/attachments/{id}, GET /v2/messages/search
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    routing::{get, patch},
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
     Json, Router,
 };
 
 use crate::auth::AuthUser;
+use crate::body_limit::{payload_too_large, with_body_limit};
 use crate::models::{
-    DeleteMessagesQuery, GetMessagesQuery, MessageDB, MessageStatusResponse, RateMessageRequest,
-    SaveMessageRequest, SaveMessageResponse,
+    DeleteMessagesQuery, GetMessagesQuery, MessageDB, MessageStatusResponse, QualityRatingKind,
+    RateMessageRequest, SaveMessageRequest, SaveMessageResponse, SearchMessagesQuery,
+    SearchMessagesResponse, UploadAttachmentsResponse, MAX_MESSAGE_SEARCH_LIMIT,
+    MAX_MESSAGE_SEARCH_QUERY_CHARS,
 };
+use crate::services::firestore::FirestoreError;
+use crate::services::response_guardrails::ResponseGuardrails;
+use crate::services::{llm_quality, message_search};
+use crate::services::uploads::{self, SpoolError};
 use crate::AppState;
 
+/// Max size of a single attachment
+const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Max attachments per upload request / message
+const MAX_ATTACHMENTS: usize = 5;
+/// Max upload request size: MAX_ATTACHMENTS files plus multipart overhead
+const MAX_UPLOAD_BODY_BYTES: usize = MAX_ATTACHMENTS * MAX_ATTACHMENT_BYTES + 64 * 1024;
+
 /// POST /v2/messages - Save a chat message
 async fn save_message(
     State(state): State<AppState>,
@@ -34,31 +50,66 @@ async fn save_message(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    // Validate text is not empty
-    if request.text.trim().is_empty() {
+    // Validate text is not empty (attachment-only messages are allowed)
+    if request.text.trim().is_empty() && request.attachment_ids.is_empty() {
         tracing::warn!("Empty message text");
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    if request.attachment_ids.len() > MAX_ATTACHMENTS {
+        tracing::warn!("Too many attachments: {}", request.attachment_ids.len());
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let attachments = match state
+        .firestore
+        .get_chat_attachments(&user.uid, &request.attachment_ids)
+        .await
+    {
+        Ok(attachments) => attachments,
+        Err(e) => {
+            tracing::error!("Failed to load attachments: {}", e);
+            return Err(e.http_status());
+        }
+    };
+
+    if attachments.len() != request.attachment_ids.len() {
+        tracing::warn!("Unknown attachment IDs in {:?}", request.attachment_ids);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // AI responses get the language and format guardrails before they're stored
+    let guarded = if request.sender == "ai" && !request.text.trim().is_empty() {
+        let text = ResponseGuardrails::from_config(&state.config)
+            .apply(&state, &user.uid, &request.text, request.supports_markdown)
+            .await;
+        Some(text).filter(|text| *text != request.text)
+    } else {
+        None
+    };
+
     match state
         .firestore
-        .save_message(
+        .save_message_with_attachments(
             &user.uid,
-            &request.text,
+            guarded.as_deref().unwrap_or(&request.text),
             &request.sender,
             request.app_id.as_deref(),
             request.session_id.as_deref(),
             request.metadata.as_deref(),
+            &attachments,
+            &request.provenance,
         )
         .await
     {
         Ok(message) => Ok(Json(SaveMessageResponse {
             id: message.id,
             created_at: message.created_at,
+            text: guarded,
         })),
         Err(e) => {
             tracing::error!("Failed to save message: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -79,7 +130,7 @@ async fn get_messages(
     );
 
     match state
-        .firestore
+        .storage
         .get_messages(
             &user.uid,
             query.app_id.as_deref(),
@@ -110,7 +161,7 @@ async fn delete_messages(
     );
 
     match state
-        .firestore
+        .storage
         .delete_messages(&user.uid, query.app_id.as_deref())
         .await
     {
@@ -120,7 +171,7 @@ async fn delete_messages(
         })),
         Err(e) => {
             tracing::error!("Failed to delete messages: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -152,19 +203,195 @@ async fn rate_message(
         .update_message_rating(&user.uid, &message_id, request.rating)
         .await
     {
-        Ok(()) => Ok(Json(MessageStatusResponse {
-            status: "ok".to_string(),
-            deleted_count: None,
-        })),
+        Ok(message) => {
+            // Chat answers are generated by the client, which reports what produced them
+            let mut provenance = message.provenance;
+            if provenance.route.is_none() {
+                let route = if message.app_id.is_some() { "app_chat" } else { "chat" };
+                provenance.route = Some(route.to_string());
+            }
+            llm_quality::record_rating(
+                &state.firestore,
+                &user.uid,
+                QualityRatingKind::Message,
+                &message_id,
+                request.rating,
+                provenance,
+            )
+            .await;
+            Ok(Json(MessageStatusResponse {
+                status: "ok".to_string(),
+                deleted_count: None,
+            }))
+        }
         Err(e) => {
             tracing::error!("Failed to rate message: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v2/messages/attachments - Upload files/images to attach to a chat message
+/// Multipart form; every file field is stored. Returns IDs to pass as `attachment_ids`.
+/// Files are streamed to temp files (never held in memory) and then on to GCS.
+async fn upload_attachments(
+    State(state): State<AppState>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<UploadAttachmentsResponse>, Response> {
+    let bucket = match &state.config.chat_attachments_bucket {
+        Some(bucket) => bucket.clone(),
+        None => {
+            tracing::warn!("CHAT_ATTACHMENTS_BUCKET not configured");
+            return Err(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+    };
+
+    let mut attachments = vec![];
+
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
+        tracing::warn!("Invalid multipart body: {}", e);
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            payload_too_large(MAX_UPLOAD_BODY_BYTES)
+        } else {
+            (StatusCode::BAD_REQUEST, e.body_text()).into_response()
+        }
+    })? {
+        // Skip non-file form fields
+        let Some(name) = field.file_name().map(|s| s.to_string()) else {
+            continue;
+        };
+
+        if attachments.len() >= MAX_ATTACHMENTS {
+            tracing::warn!("Too many attachments in upload");
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("At most {} attachments per upload", MAX_ATTACHMENTS),
+            )
+                .into_response());
+        }
+
+        let mime_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        let spooled = match uploads::spool_field(&mut field, MAX_ATTACHMENT_BYTES as u64).await {
+            Ok(spooled) => spooled,
+            Err(SpoolError::TooLarge) => {
+                tracing::warn!("Attachment {} exceeds {} bytes", name, MAX_ATTACHMENT_BYTES);
+                return Err(payload_too_large(MAX_ATTACHMENT_BYTES));
+            }
+            Err(e @ SpoolError::Read(_)) => {
+                tracing::warn!("Failed to read attachment {}: {}", name, e);
+                return Err((StatusCode::BAD_REQUEST, e.to_string()).into_response());
+            }
+            Err(e) => {
+                tracing::error!("Failed to spool attachment {}: {}", name, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            }
+        };
+
+        if spooled.size == 0 {
+            return Err((StatusCode::BAD_REQUEST, format!("Attachment {} is empty", name)).into_response());
+        }
+
+        let upload = match spooled.body().await {
+            Ok(body) => {
+                state
+                    .firestore
+                    .upload_chat_attachment(&user.uid, &bucket, &name, &mime_type, body, spooled.size as i64)
+                    .await
+            }
+            Err(e) => Err(FirestoreError::Internal(e.to_string())),
+        };
+        match upload {
+            Ok(attachment) => attachments.push(attachment),
+            Err(e) => {
+                tracing::error!("Failed to upload attachment: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
             }
         }
     }
+
+    if attachments.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No files in upload".to_string()).into_response());
+    }
+
+    Ok(Json(UploadAttachmentsResponse { attachments }))
+}
+
+/// GET /v2/messages/attachments/{id} - Download an attachment's contents
+async fn download_attachment(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(attachment_id): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let bucket = state
+        .config
+        .chat_attachments_bucket
+        .clone()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let attachment = match state
+        .firestore
+        .get_chat_attachment(&user.uid, &attachment_id)
+        .await
+    {
+        Ok(Some(attachment)) => attachment,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get attachment: {}", e);
+            return Err(e.http_status());
+        }
+    };
+
+    match state
+        .firestore
+        .download_chat_attachment(&bucket, &attachment)
+        .await
+    {
+        Ok(data) => Ok(([(header::CONTENT_TYPE, attachment.mime_type)], data)),
+        Err(e) => {
+            tracing::error!("Failed to download attachment: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v2/messages/search - Messages containing every word of `q`, newest first,
+/// with the messages before and after each one in its session
+async fn search_messages(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<SearchMessagesQuery>,
+) -> Result<Json<SearchMessagesResponse>, (StatusCode, String)> {
+    let q = query.q.trim();
+    if q.is_empty() || q.chars().count() > MAX_MESSAGE_SEARCH_QUERY_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("q must be 1-{} characters", MAX_MESSAGE_SEARCH_QUERY_CHARS),
+        ));
+    }
+
+    let results = message_search::search(
+        &state.firestore,
+        &user.uid,
+        q,
+        query.app_id.as_deref(),
+        query.session_id.as_deref(),
+        query.limit.clamp(1, MAX_MESSAGE_SEARCH_LIMIT),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to search messages for user {}: {}", user.uid, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+
+    Ok(Json(SearchMessagesResponse {
+        query: q.to_string(),
+        results,
+    }))
 }
 
 pub fn messages_routes() -> Router<AppState> {
@@ -173,5 +400,11 @@ pub fn messages_routes() -> Router<AppState> {
             "/v2/messages",
             get(get_messages).post(save_message).delete(delete_messages),
         )
+        .route("/v2/messages/search", get(search_messages))
         .route("/v2/messages/:id/rating", patch(rate_message))
+        .route(
+            "/v2/messages/attachments",
+            with_body_limit(post(upload_attachments), MAX_UPLOAD_BODY_BYTES),
+        )
+        .route("/v2/messages/attachments/:id", get(download_attachment))
 }
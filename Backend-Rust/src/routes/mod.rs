@@ -1,55 +1,89 @@
 // Routes module
 
 pub mod action_items;
+pub mod admin;
 pub mod advice;
 pub mod agent;
 pub mod apps;
 pub mod auth;
+pub mod badges;
 pub mod chat;
 pub mod chat_sessions;
+pub mod conversation_templates;
 pub mod conversations;
 pub mod crisp;
 pub mod daily_score;
+pub mod devices;
+pub mod exports;
 pub mod focus_sessions;
+pub mod folder_rules;
 pub mod folders;
 pub mod goals;
+pub mod groups;
 pub mod health;
 pub mod knowledge_graph;
 pub mod llm_usage;
 pub mod memories;
+pub mod memos;
 pub mod messages;
+pub mod notifications;
+pub mod notion;
 pub mod people;
 pub mod personas;
+pub mod presence;
+pub mod update_stream;
 pub mod updates;
+pub mod reviews;
 pub mod staged_tasks;
 pub mod stats;
 pub mod users;
 pub mod webhooks;
 pub mod screen_activity;
+pub mod search;
+pub mod slack;
+pub mod sync;
+pub mod timeline;
 
 pub use action_items::action_items_routes;
+pub use admin::admin_routes;
 pub use advice::advice_routes;
 pub use agent::agent_routes;
 pub use apps::apps_routes;
 pub use auth::auth_routes;
+pub use badges::badges_routes;
 pub use chat::chat_routes;
 pub use chat_sessions::chat_sessions_routes;
+pub use conversation_templates::conversation_templates_routes;
 pub use conversations::conversations_routes;
 pub use crisp::crisp_routes;
 pub use daily_score::daily_score_routes;
+pub use devices::devices_routes;
+pub use exports::exports_routes;
+pub use notion::notion_routes;
+pub use slack::slack_routes;
+pub use sync::sync_routes;
+pub use timeline::timeline_routes;
 pub use focus_sessions::focus_sessions_routes;
+pub use folder_rules::folder_rules_routes;
 pub use folders::folder_routes;
 pub use goals::goals_routes;
+pub use groups::groups_routes;
 pub use health::health_routes;
 pub use knowledge_graph::knowledge_graph_routes;
 pub use llm_usage::llm_usage_routes;
 pub use memories::memories_routes;
+pub use memos::memos_routes;
 pub use messages::messages_routes;
+pub use notifications::notifications_routes;
 pub use people::people_routes;
 pub use personas::personas_routes;
+pub use presence::presence_routes;
+pub use reviews::reviews_routes;
 pub use staged_tasks::staged_tasks_routes;
 pub use stats::stats_routes;
+pub use update_stream::update_stream_routes;
 pub use updates::updates_routes;
 pub use users::users_routes;
 pub use webhooks::webhook_routes;
 pub use screen_activity::screen_activity_routes;
+pub use search::search_routes;
@@ -0,0 +1,103 @@
+// Notification inbox routes
+// Endpoints: GET /v1/notifications, POST /v1/notifications/read, DELETE /v1/notifications/:id
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+
+use crate::auth::AuthUser;
+use crate::etag::with_etag;
+use crate::models::{
+    GetNotificationsQuery, MarkNotificationsReadRequest, MarkNotificationsReadResponse, NotificationsResponse,
+    MAX_NOTIFICATIONS_LIMIT, MAX_NOTIFICATION_READ_IDS,
+};
+use crate::services::firestore::NOTIFICATIONS_SUBCOLLECTION;
+use crate::AppState;
+
+/// GET /v1/notifications - Inbox page, newest first, with the inbox's unread count
+async fn list_notifications(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<GetNotificationsQuery>,
+) -> Result<Json<NotificationsResponse>, (StatusCode, String)> {
+    let limit = query.limit.clamp(1, MAX_NOTIFICATIONS_LIMIT);
+
+    let (notifications, unread) = tokio::try_join!(
+        state.firestore.get_notifications(&user.uid, limit, query.unread_only),
+        state
+            .firestore
+            .count_user_documents_where(&user.uid, NOTIFICATIONS_SUBCOLLECTION, &[("read", false)]),
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to list notifications for {}: {}", user.uid, e);
+        e.public_error()
+    })?;
+
+    Ok(Json(NotificationsResponse { notifications, unread }))
+}
+
+/// POST /v1/notifications/read - Mark the given notifications read, or all of them when
+/// no IDs are given
+async fn mark_notifications_read(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<MarkNotificationsReadRequest>,
+) -> Result<Json<MarkNotificationsReadResponse>, (StatusCode, String)> {
+    if request.ids.len() > MAX_NOTIFICATION_READ_IDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} notification IDs per request", MAX_NOTIFICATION_READ_IDS),
+        ));
+    }
+
+    let result = if request.ids.is_empty() {
+        state
+            .firestore
+            .mark_all_notifications_read(&user.uid)
+            .await
+            .map(|updated| (updated, Vec::new()))
+    } else {
+        let mut ids = request.ids;
+        ids.sort();
+        ids.dedup();
+        state.firestore.mark_notifications_read(&user.uid, &ids).await
+    };
+
+    let (updated, not_found) = result.map_err(|e| {
+        tracing::error!("Failed to mark notifications read for {}: {}", user.uid, e);
+        e.public_error()
+    })?;
+
+    Ok(Json(MarkNotificationsReadResponse { updated, not_found }))
+}
+
+/// DELETE /v1/notifications/:id - Remove one notification from the inbox
+async fn delete_notification(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(notification_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let deleted = state
+        .firestore
+        .delete_notification(&user.uid, &notification_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete notification {} for {}: {}", notification_id, user.uid, e);
+            e.public_error()
+        })?;
+
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Notification not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn notifications_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/notifications", with_etag(get(list_notifications)))
+        .route("/v1/notifications/read", post(mark_notifications_read))
+        .route("/v1/notifications/:id", delete(delete_notification))
+}
@@ -0,0 +1,240 @@
+// Notion integration routes - Connect a workspace and push conversations
+// Endpoints: POST /v1/integrations/notion/connect, GET/PATCH/DELETE /v1/integrations/notion,
+//            GET /v1/integrations/notion/databases, POST /v1/conversations/:id/notion
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::auth::AuthUser;
+use crate::models::{
+    NotionConnectRequest, NotionConnection, NotionDatabase, NotionStatusResponse, NotionSyncStatus,
+    UpdateNotionSettingsRequest,
+};
+use crate::services::notion;
+use crate::AppState;
+
+/// Status response for operations
+#[derive(Serialize)]
+struct StatusResponse {
+    status: String,
+}
+
+/// Load the user's connection or 404 when Notion isn't connected
+async fn require_connection(
+    state: &AppState,
+    uid: &str,
+) -> Result<NotionConnection, (StatusCode, String)> {
+    match state.firestore.get_notion_connection(uid).await {
+        Ok(Some(connection)) => Ok(connection),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Notion is not connected".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get Notion connection: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// POST /v1/integrations/notion/connect - Exchange an OAuth code and store the connection
+async fn connect_notion(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<NotionConnectRequest>,
+) -> Result<Json<NotionStatusResponse>, (StatusCode, String)> {
+    if !state.notion.is_configured() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Notion integration is not configured".to_string(),
+        ));
+    }
+    if request.code.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "code is required".to_string()));
+    }
+
+    tracing::info!("Connecting Notion for user {}", user.uid);
+
+    let token = state
+        .notion
+        .exchange_code(request.code.trim(), request.redirect_uri.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!("Notion OAuth exchange failed: {}", e);
+            (StatusCode::BAD_REQUEST, e.to_string())
+        })?;
+
+    // Reconnecting the same workspace keeps the chosen database and mapping
+    let existing = state
+        .firestore
+        .get_notion_connection(&user.uid)
+        .await
+        .ok()
+        .flatten()
+        .filter(|c| c.workspace_id == token.workspace_id);
+
+    let now = Utc::now();
+    let connection = NotionConnection {
+        access_token: token.access_token,
+        workspace_id: token.workspace_id,
+        workspace_name: token.workspace_name,
+        bot_id: token.bot_id,
+        database_id: existing.as_ref().and_then(|c| c.database_id.clone()),
+        property_mapping: existing
+            .as_ref()
+            .map(|c| c.property_mapping.clone())
+            .unwrap_or_default(),
+        auto_sync: existing.as_ref().is_some_and(|c| c.auto_sync),
+        connected_at: existing.as_ref().map_or(now, |c| c.connected_at),
+        updated_at: now,
+    };
+
+    state
+        .firestore
+        .save_notion_connection(&user.uid, &connection)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save Notion connection: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(NotionStatusResponse::from(Some(&connection))))
+}
+
+/// GET /v1/integrations/notion - Connection status and sync settings
+async fn get_notion_status(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<NotionStatusResponse>, (StatusCode, String)> {
+    match state.firestore.get_notion_connection(&user.uid).await {
+        Ok(connection) => Ok(Json(NotionStatusResponse::from(connection.as_ref()))),
+        Err(e) => {
+            tracing::error!("Failed to get Notion connection: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// PATCH /v1/integrations/notion - Choose the database, property mapping and auto-sync
+async fn update_notion_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateNotionSettingsRequest>,
+) -> Result<Json<NotionStatusResponse>, (StatusCode, String)> {
+    let mut connection = require_connection(&state, &user.uid).await?;
+
+    if let Some(database_id) = request.database_id {
+        let database_id = database_id.trim().to_string();
+        connection.database_id = if database_id.is_empty() { None } else { Some(database_id) };
+    }
+    if let Some(mapping) = request.property_mapping {
+        if mapping.title.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "property_mapping.title is required".to_string(),
+            ));
+        }
+        connection.property_mapping = mapping;
+    }
+    if let Some(auto_sync) = request.auto_sync {
+        connection.auto_sync = auto_sync;
+    }
+    if connection.auto_sync && connection.database_id.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Select a database before enabling auto-sync".to_string(),
+        ));
+    }
+    connection.updated_at = Utc::now();
+
+    state
+        .firestore
+        .save_notion_connection(&user.uid, &connection)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update Notion settings: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(NotionStatusResponse::from(Some(&connection))))
+}
+
+/// DELETE /v1/integrations/notion - Disconnect Notion
+async fn disconnect_notion(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<StatusResponse>, StatusCode> {
+    tracing::info!("Disconnecting Notion for user {}", user.uid);
+
+    match state.firestore.delete_notion_connection(&user.uid).await {
+        Ok(()) => Ok(Json(StatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to disconnect Notion: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/integrations/notion/databases - Databases shared with the integration
+async fn get_notion_databases(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<NotionDatabase>>, (StatusCode, String)> {
+    let connection = require_connection(&state, &user.uid).await?;
+
+    match state.notion.list_databases(&connection.access_token).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            tracing::error!("Failed to list Notion databases: {}", e);
+            Err((StatusCode::BAD_GATEWAY, e.to_string()))
+        }
+    }
+}
+
+/// POST /v1/conversations/:id/notion - Push a conversation to Notion now
+async fn sync_conversation_to_notion(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<NotionSyncStatus>, (StatusCode, String)> {
+    let connection = require_connection(&state, &user.uid).await?;
+    if connection.database_id.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "No Notion database selected".to_string()));
+    }
+
+    let conversation = state
+        .storage
+        .get_conversation(&user.uid, &conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get conversation: {}", e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Conversation not found".to_string()))?;
+
+    match notion::sync_conversation(&state.firestore, &state.notion, &user.uid, &conversation).await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => {
+            tracing::error!("Failed to sync conversation to Notion: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to sync conversation to Notion".to_string()))
+        }
+    }
+}
+
+pub fn notion_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/integrations/notion/connect", post(connect_notion))
+        .route(
+            "/v1/integrations/notion",
+            get(get_notion_status)
+                .patch(update_notion_settings)
+                .delete(disconnect_notion),
+        )
+        .route("/v1/integrations/notion/databases", get(get_notion_databases))
+        .route("/v1/conversations/:id/notion", post(sync_conversation_to_notion))
+}
@@ -1,16 +1,31 @@
 // People routes - Speaker voice profiles for transcript naming
+// Plus a per-person overview: shared conversations, tasks and relationship notes.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::get,
     Json, Router,
 };
+use serde::Deserialize;
 
 use crate::auth::AuthUser;
-use crate::models::{BulkAssignSegmentsRequest, CreatePersonRequest, Person};
+use crate::conversation_lock::with_conversation_lock;
+use crate::llm_limit::with_llm_limit;
+use crate::models::{
+    BulkAssignSegmentsRequest, ConversationEditField, CreatePersonRequest, Person, PersonConversation, PersonOverview,
+    RelationshipNotes,
+};
+use crate::services::{conversation_history, people_overview};
 use crate::AppState;
 
+/// Most recent conversations scanned for the overview
+const OVERVIEW_CONVERSATION_SCAN: usize = 500;
+/// Most recent action items scanned for the overview
+const OVERVIEW_ACTION_ITEM_SCAN: usize = 500;
+/// Conversations summarized into relationship notes
+const NOTES_CONVERSATION_LIMIT: usize = 10;
+
 /// Create people routes
 pub fn people_routes() -> Router<AppState> {
     Router::new()
@@ -23,22 +38,39 @@ pub fn people_routes() -> Router<AppState> {
             "/v1/users/people/:person_id/name",
             axum::routing::patch(update_person_name),
         )
+        .route("/v1/people/:person_id/overview", with_llm_limit(get(get_person_overview)))
         .route(
             "/v1/conversations/:conversation_id/segments/assign-bulk",
-            axum::routing::patch(assign_segments_bulk),
+            with_conversation_lock(axum::routing::patch(assign_segments_bulk)),
         )
 }
 
-/// GET /v1/users/people - Get all people for the user
+#[derive(Deserialize)]
+struct PeopleQuery {
+    /// Case-insensitive name filter
+    q: Option<String>,
+}
+
+/// GET /v1/users/people?q= - Get all people for the user, optionally filtered by name
 async fn get_people(
     State(state): State<AppState>,
     user: AuthUser,
+    Query(query): Query<PeopleQuery>,
 ) -> Result<Json<Vec<Person>>, StatusCode> {
+    let search = query
+        .q
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
     match state.firestore.get_people(&user.uid).await {
-        Ok(people) => Ok(Json(people)),
+        Ok(mut people) => {
+            if let Some(search) = search {
+                people.retain(|p| p.name.to_lowercase().contains(&search));
+            }
+            Ok(Json(people))
+        }
         Err(e) => {
             tracing::error!("Failed to get people: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -57,7 +89,7 @@ async fn create_person(
         Ok(person) => Ok(Json(person)),
         Err(e) => {
             tracing::error!("Failed to create person: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -67,7 +99,7 @@ async fn update_person_name(
     State(state): State<AppState>,
     user: AuthUser,
     Path(person_id): Path<String>,
-    axum::extract::Query(query): axum::extract::Query<NameQuery>,
+    Query(query): Query<NameQuery>,
 ) -> StatusCode {
     if query.value.trim().is_empty() {
         return StatusCode::BAD_REQUEST;
@@ -86,6 +118,124 @@ async fn update_person_name(
     }
 }
 
+#[derive(Deserialize)]
+struct OverviewQuery {
+    /// Regenerate relationship notes even if the cached ones are current
+    #[serde(default)]
+    refresh_notes: bool,
+}
+
+/// GET /v1/people/:person_id/overview - Conversations, shared action items and
+/// relationship notes for one person
+async fn get_person_overview(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(person_id): Path<String>,
+    Query(query): Query<OverviewQuery>,
+) -> Result<Json<PersonOverview>, StatusCode> {
+    let person = match state.firestore.get_person(&user.uid, &person_id).await {
+        Ok(Some(person)) => person,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get person: {}", e);
+            return Err(e.http_status());
+        }
+    };
+
+    let statuses = vec!["completed".to_string()];
+    let (conversations, action_items) = tokio::join!(
+        state.storage.get_conversations(
+            &user.uid,
+            OVERVIEW_CONVERSATION_SCAN,
+            0,
+            false,
+            &statuses,
+            None,
+            None,
+            None,
+            None,
+        ),
+        state.storage.get_action_items(
+            &user.uid,
+            OVERVIEW_ACTION_ITEM_SCAN,
+            0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            true,
+        ),
+    );
+    let conversations = conversations.map_err(|e| {
+        tracing::error!("Failed to get conversations for person overview: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let action_items = action_items.map_err(|e| {
+        tracing::error!("Failed to get action items for person overview: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let conversations = people_overview::person_conversations(&person_id, &conversations);
+    let notes = relationship_notes(&state, &user, &person, &conversations, query.refresh_notes).await;
+
+    Ok(Json(people_overview::build_overview(person, conversations, action_items, notes)))
+}
+
+/// Cached notes when they cover the newest conversation, otherwise freshly generated
+/// ones. Falls back to the cached notes if generation is unavailable or fails.
+async fn relationship_notes(
+    state: &AppState,
+    user: &AuthUser,
+    person: &Person,
+    conversations: &[PersonConversation],
+    refresh: bool,
+) -> Vec<String> {
+    let cached = person.relationship_notes.as_ref();
+    let Some(latest) = conversations.first() else {
+        return Vec::new();
+    };
+    if let Some(cached) = cached.filter(|c| !refresh && c.latest_conversation_id == latest.id) {
+        return cached.notes.clone();
+    }
+    let fallback = || cached.map(|c| c.notes.clone()).unwrap_or_default();
+
+    let llm = state.llm_client(&user.uid).await.unwrap_or_else(|e| {
+        tracing::warn!("Can't refresh person notes for user {}: {}", user.uid, e);
+        None
+    });
+    let Some(llm) = llm else {
+        return fallback();
+    };
+    let user_name = user.name.as_deref().unwrap_or("User");
+    let recent = &conversations[..conversations.len().min(NOTES_CONVERSATION_LIMIT)];
+
+    match llm.generate_relationship_notes(user_name, &person.name, recent).await {
+        Ok(notes) => {
+            let record = RelationshipNotes {
+                notes: notes.clone(),
+                latest_conversation_id: latest.id.clone(),
+                generated_at: chrono::Utc::now(),
+            };
+            if let Err(e) = state
+                .firestore
+                .save_relationship_notes(&user.uid, &person.id, &record)
+                .await
+            {
+                tracing::warn!("Failed to cache relationship notes: {}", e);
+            }
+            notes
+        }
+        Err(e) => {
+            tracing::warn!("Failed to generate relationship notes: {}", e);
+            fallback()
+        }
+    }
+}
+
 /// DELETE /v1/users/people/:person_id - Delete a person
 async fn delete_person(
     State(state): State<AppState>,
@@ -123,7 +273,16 @@ async fn assign_segments_bulk(
         )
         .await
     {
-        Ok(()) => StatusCode::OK,
+        Ok(previous) => {
+            let edit = conversation_history::new_edit(
+                ConversationEditField::Transcript,
+                serde_json::json!(previous),
+                &user.uid,
+                "speaker_assignment",
+            );
+            conversation_history::record_edit(&state.firestore, &user.uid, &conversation_id, &edit).await;
+            StatusCode::OK
+        }
         Err(e) => {
             tracing::error!("Failed to assign segments: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
@@ -131,7 +290,7 @@ async fn assign_segments_bulk(
     }
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Deserialize)]
 struct NameQuery {
     value: String,
 }
@@ -1,21 +1,29 @@
 // Personas routes - AI persona/clone feature
-// Endpoints for persona CRUD and prompt generation
+// Endpoints for persona CRUD and prompt generation, plus public pages at /u/:username
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Json, Router,
 };
+use serde::Deserialize;
 
 use crate::auth::AuthUser;
-use crate::llm::LlmClient;
+use crate::llm_limit::with_llm_limit;
 use crate::models::{
     CheckUsernameQuery, CreatePersonaRequest, GeneratePromptRequest, GeneratePromptResponse,
-    PersonaResponse, PersonaStatusResponse, UpdatePersonaRequest, UsernameAvailableResponse,
+    PersonaResponse, PersonaStatusResponse, PublicPersonaProfile, UpdatePersonaRequest,
+    UsernameAvailableResponse, MAX_PUBLIC_PERSONA_MEMORIES,
 };
+use crate::services::persona_pages;
 use crate::AppState;
 
+/// Browsers may briefly reuse a public persona page; shared caches may not, so a
+/// memory made private stops showing once the Redis copy is invalidated
+const PUBLIC_PERSONA_CACHE_CONTROL: &str = "private, max-age=60";
+
 // ============================================================================
 // Persona CRUD Endpoints
 // ============================================================================
@@ -41,10 +49,7 @@ async fn get_persona(
         Ok(None) => Ok(Json(None)),
         Err(e) => {
             tracing::error!("Failed to get persona: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get persona: {}", e),
-            ))
+            Err(e.public_error())
         }
     }
 }
@@ -85,7 +90,7 @@ async fn create_persona(
             Err(e) => {
                 tracing::error!("Failed to check username: {}", e);
                 return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    e.http_status(),
                     "Failed to check username availability.".to_string(),
                 ));
             }
@@ -98,17 +103,13 @@ async fn create_persona(
         Ok(m) => m,
         Err(e) => {
             tracing::error!("Failed to get memories: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get memories: {}", e),
-            ));
+            return Err(e.public_error());
         }
     };
 
     // Generate persona prompt if we have memories
     let (description, persona_prompt) = if !memories.is_empty() {
-        if let Some(api_key) = &state.config.gemini_api_key {
-            let llm = LlmClient::new(api_key.clone());
+        if let Some(llm) = state.llm_client(&user.uid).await? {
             match llm.generate_persona_from_memories(&request.name, &memories).await {
                 Ok(result) => (result.description, Some(result.persona_prompt)),
                 Err(e) => {
@@ -144,10 +145,7 @@ async fn create_persona(
         }
         Err(e) => {
             tracing::error!("Failed to create persona: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create persona: {}", e),
-            ))
+            Err(e.public_error())
         }
     }
 }
@@ -168,10 +166,7 @@ async fn update_persona(
         }
         Err(e) => {
             tracing::error!("Failed to get persona: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get persona: {}", e),
-            ));
+            return Err(e.public_error());
         }
     };
 
@@ -184,10 +179,12 @@ async fn update_persona(
             request.description.as_deref(),
             request.persona_prompt.as_deref(),
             request.image.as_deref(),
+            request.noindex,
         )
         .await
     {
         Ok(()) => {
+            persona_pages::invalidate(state.redis.as_deref(), &persona).await;
             // Fetch updated persona
             match state.firestore.get_user_persona(&user.uid).await {
                 Ok(Some(updated)) => {
@@ -206,10 +203,7 @@ async fn update_persona(
         }
         Err(e) => {
             tracing::error!("Failed to update persona: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to update persona: {}", e),
-            ))
+            Err(e.public_error())
         }
     }
 }
@@ -229,25 +223,22 @@ async fn delete_persona(
         }
         Err(e) => {
             tracing::error!("Failed to get persona: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get persona: {}", e),
-            ));
+            return Err(e.public_error());
         }
     };
 
     // Delete persona
     match state.firestore.delete_persona(&persona.id).await {
-        Ok(()) => Ok(Json(PersonaStatusResponse {
-            status: "ok".to_string(),
-            message: Some("Persona deleted successfully".to_string()),
-        })),
+        Ok(()) => {
+            persona_pages::invalidate(state.redis.as_deref(), &persona).await;
+            Ok(Json(PersonaStatusResponse {
+                status: "ok".to_string(),
+                message: Some("Persona deleted successfully".to_string()),
+            }))
+        }
         Err(e) => {
             tracing::error!("Failed to delete persona: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to delete persona: {}", e),
-            ))
+            Err(e.public_error())
         }
     }
 }
@@ -272,10 +263,7 @@ async fn generate_prompt(
         }
         Err(e) => {
             tracing::error!("Failed to get persona: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get persona: {}", e),
-            ));
+            return Err(e.public_error());
         }
     };
 
@@ -284,10 +272,7 @@ async fn generate_prompt(
         Ok(m) => m,
         Err(e) => {
             tracing::error!("Failed to get memories: {}", e);
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get memories: {}", e),
-            ));
+            return Err(e.public_error());
         }
     };
 
@@ -299,12 +284,11 @@ async fn generate_prompt(
     }
 
     // Generate new prompt
-    let api_key = state.config.gemini_api_key.as_ref().ok_or((
+    let llm = state.llm_client(&user.uid).await?.ok_or((
         StatusCode::INTERNAL_SERVER_ERROR,
         "Gemini API key not configured".to_string(),
     ))?;
 
-    let llm = LlmClient::new(api_key.clone());
     let result = llm
         .generate_persona_from_memories(&persona.name, &memories)
         .await
@@ -325,15 +309,14 @@ async fn generate_prompt(
             Some(&result.description),
             Some(&result.persona_prompt),
             None,
+            None,
         )
         .await
         .map_err(|e| {
             tracing::error!("Failed to update persona: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to update persona: {}", e),
-            )
+            e.public_error()
         })?;
+    persona_pages::invalidate(state.redis.as_deref(), &persona).await;
 
     Ok(Json(GeneratePromptResponse {
         persona_prompt: result.persona_prompt,
@@ -342,6 +325,95 @@ async fn generate_prompt(
     }))
 }
 
+// ============================================================================
+// Public Persona Pages
+// ============================================================================
+
+#[derive(Deserialize)]
+struct PublicPersonaQuery {
+    /// "html" for the minimal HTML page; otherwise decided by the Accept header
+    format: Option<String>,
+}
+
+/// Public profile for a username, from the cache when fresh
+async fn load_public_persona(
+    state: &AppState,
+    username: &str,
+) -> Result<Option<PublicPersonaProfile>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(redis) = &state.redis {
+        match redis.get_public_persona(username).await {
+            Ok(Some(profile)) => return Ok(Some(profile)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read cached public persona {}: {}", username, e),
+        }
+    }
+
+    let Some(persona) = state.firestore.get_persona_by_username(username).await? else {
+        return Ok(None);
+    };
+    let memories = state
+        .firestore
+        .get_public_memories(&persona.uid, MAX_PUBLIC_PERSONA_MEMORIES)
+        .await?;
+    let Some(profile) = persona_pages::build_profile(persona, memories) else {
+        return Ok(None);
+    };
+
+    if let Some(redis) = &state.redis {
+        if let Err(e) = redis.store_public_persona(&profile).await {
+            tracing::warn!("Failed to cache public persona {}: {}", username, e);
+        }
+    }
+    Ok(Some(profile))
+}
+
+/// GET /u/:username - Public persona profile (no auth). JSON by default; minimal HTML
+/// with ?format=html or an Accept header asking for text/html.
+async fn get_public_persona(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    Query(query): Query<PublicPersonaQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let username = username.to_lowercase();
+    if !is_valid_username(&username) {
+        return Err((StatusCode::NOT_FOUND, "Persona not found".to_string()));
+    }
+
+    let profile = match load_public_persona(&state, &username).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Persona not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to load public persona {}: {}", username, e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load persona".to_string()));
+        }
+    };
+
+    let wants_html = match query.format.as_deref() {
+        Some(format) => format == "html",
+        None => headers
+            .get(header::ACCEPT)
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|accept| accept.trim_start().starts_with("text/html")),
+    };
+    let robots = persona_pages::robots_directive(&profile);
+
+    let mut response = if wants_html {
+        (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            persona_pages::render_html(&profile),
+        )
+            .into_response()
+    } else {
+        Json(profile).into_response()
+    };
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, header::HeaderValue::from_static(PUBLIC_PERSONA_CACHE_CONTROL));
+    response_headers.insert("x-robots-tag", header::HeaderValue::from_static(robots));
+    response_headers.insert(header::VARY, header::HeaderValue::from_static("accept"));
+    Ok(response)
+}
+
 // ============================================================================
 // Username Validation
 // ============================================================================
@@ -369,10 +441,7 @@ async fn check_username(
         })),
         Err(e) => {
             tracing::error!("Failed to check username: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to check username: {}", e),
-            ))
+            Err(e.public_error())
         }
     }
 }
@@ -395,9 +464,10 @@ fn is_valid_username(username: &str) -> bool {
 pub fn personas_routes() -> Router<AppState> {
     Router::new()
         .route("/v1/personas", get(get_persona))
-        .route("/v1/personas", post(create_persona))
+        .route("/v1/personas", with_llm_limit(post(create_persona)))
         .route("/v1/personas", patch(update_persona))
         .route("/v1/personas", delete(delete_persona))
-        .route("/v1/personas/generate-prompt", post(generate_prompt))
+        .route("/v1/personas/generate-prompt", with_llm_limit(post(generate_prompt)))
         .route("/v1/personas/check-username", get(check_username))
+        .route("/u/:username", get(get_public_persona))
 }
@@ -0,0 +1,62 @@
+// Presence routes - Which of the user's devices are recording right now
+// Endpoints: POST /v1/presence (heartbeat), GET /v1/presence
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+
+use crate::auth::AuthUser;
+use crate::client_info::ClientInfo;
+use crate::models::{
+    DevicePresence, PresenceHeartbeatRequest, PresenceResponse, PresenceState,
+    MAX_PRESENCE_FIELD_CHARS,
+};
+use crate::AppState;
+
+/// Trim an optional label, drop it if empty and cap its length
+fn clean_label(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().chars().take(MAX_PRESENCE_FIELD_CHARS).collect::<String>())
+        .filter(|v| !v.is_empty())
+}
+
+/// POST /v1/presence - Report this device's state; repeat well within the TTL
+async fn post_presence(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(request): Json<PresenceHeartbeatRequest>,
+) -> Result<Json<DevicePresence>, (StatusCode, String)> {
+    let device_id = request.device_id.trim();
+    if device_id.is_empty() || device_id.chars().count() > MAX_PRESENCE_FIELD_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("device_id must be 1-{} characters", MAX_PRESENCE_FIELD_CHARS),
+        ));
+    }
+
+    let presence = state
+        .presence
+        .heartbeat(
+            &user.uid,
+            device_id,
+            request.state,
+            clean_label(request.device_name),
+            clean_label(request.platform),
+            client.ip_string(),
+        )
+        .await;
+    Ok(Json(presence))
+}
+
+/// GET /v1/presence - Devices with a recent heartbeat
+async fn get_presence(State(state): State<AppState>, user: AuthUser) -> Json<PresenceResponse> {
+    let devices = state.presence.devices(&user.uid).await;
+    let any_recording = devices.iter().any(|d| d.state != PresenceState::Idle);
+    Json(PresenceResponse {
+        devices,
+        any_recording,
+    })
+}
+
+pub fn presence_routes() -> Router<AppState> {
+    Router::new().route("/v1/presence", get(get_presence).post(post_presence))
+}
@@ -0,0 +1,114 @@
+// Weekly review routes
+// Endpoints: POST /v1/reviews/weekly, GET /v1/reviews/weekly, GET /v1/reviews/weekly/:week_start
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::auth::AuthUser;
+use crate::llm_limit::with_llm_limit;
+use crate::models::{GenerateWeeklyReviewRequest, WeeklyReview, WeeklyReviewsQuery, MAX_WEEKLY_REVIEWS_LIMIT};
+use crate::services::weekly_review::{self, default_week_start, week_start_of};
+use crate::AppState;
+
+/// POST /v1/reviews/weekly - Generate (or regenerate) the review for a week
+async fn generate_weekly_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<GenerateWeeklyReviewRequest>,
+) -> Result<Json<WeeklyReview>, (StatusCode, String)> {
+    let tz_name = match request.timezone {
+        Some(tz) => Some(tz),
+        None => state
+            .firestore
+            .get_user_profile(&user.uid)
+            .await
+            .ok()
+            .and_then(|profile| profile.time_zone),
+    };
+    let tz: Tz = match tz_name {
+        Some(name) => name
+            .parse()
+            .map_err(|_| (StatusCode::BAD_REQUEST, format!("Unknown timezone: {}", name)))?,
+        None => Tz::UTC,
+    };
+
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    let week_start = match request.week_of {
+        Some(date) => week_start_of(date),
+        None => default_week_start(today),
+    };
+    if week_start > today {
+        return Err((StatusCode::BAD_REQUEST, "Cannot review a future week".to_string()));
+    }
+
+    let Some(llm) = state.llm_client(&user.uid).await? else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "No LLM key configured".to_string()));
+    };
+
+    tracing::info!("Generating weekly review for user {} (week of {})", user.uid, week_start);
+
+    let review = weekly_review::generate_weekly_review(&state.firestore, &llm, &user.uid, week_start, tz, false)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to generate weekly review for {}: {}", user.uid, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to generate weekly review: {}", e))
+        })?;
+
+    Ok(Json(review))
+}
+
+/// GET /v1/reviews/weekly - Past reviews, most recent week first
+async fn list_weekly_reviews(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<WeeklyReviewsQuery>,
+) -> Result<Json<Vec<WeeklyReview>>, (StatusCode, String)> {
+    let limit = query.limit.clamp(1, MAX_WEEKLY_REVIEWS_LIMIT);
+
+    let reviews = state
+        .firestore
+        .get_weekly_reviews(&user.uid, limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list weekly reviews for {}: {}", user.uid, e);
+            e.public_error()
+        })?;
+
+    Ok(Json(reviews))
+}
+
+/// GET /v1/reviews/weekly/:week_start - One review; any day of the week is accepted
+async fn get_weekly_review(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(week_of): Path<NaiveDate>,
+) -> Result<Json<WeeklyReview>, (StatusCode, String)> {
+    let week_start = week_start_of(week_of).format("%Y-%m-%d").to_string();
+
+    let review = state
+        .firestore
+        .get_weekly_review(&user.uid, &week_start)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get weekly review for {}: {}", user.uid, e);
+            e.public_error()
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Weekly review not found".to_string()))?;
+
+    Ok(Json(review))
+}
+
+pub fn reviews_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/v1/reviews/weekly",
+            with_llm_limit(post(generate_weekly_review)).get(list_weekly_reviews),
+        )
+        .route("/v1/reviews/weekly/:week_start", get(get_weekly_review))
+}
@@ -1,16 +1,23 @@
 // Screen Activity sync route
 // Receives screenshot metadata + embeddings from the desktop app,
 // writes metadata to Firestore and embeddings to Pinecone ns3.
+// Also ingests live screen-context snapshots (POST/GET /v1/context/screen).
 
 use axum::{
     extract::State,
     http::StatusCode,
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
+use chrono::Utc;
 
 use crate::auth::AuthUser;
-use crate::models::screen_activity::{ScreenActivitySyncRequest, ScreenActivitySyncResponse};
+use crate::body_limit::{with_body_limit, LARGE_JSON_BODY_LIMIT_BYTES};
+use crate::models::screen_activity::{
+    ScreenActivitySyncRequest, ScreenActivitySyncResponse, ScreenContextRequest,
+    ScreenContextResponse, ScreenContextSnapshot,
+};
+use crate::services::screen_context::MAX_OCR_CHARS;
 use crate::AppState;
 
 /// POST /v1/screen-activity/sync
@@ -44,7 +51,7 @@ async fn sync_screen_activity(
 
     if let Err(e) = &firestore_result {
         tracing::error!("Screen activity Firestore write failed: {}", e);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Firestore write failed: {}", e)));
+        return Err(e.public_error());
     }
 
     let written = firestore_result.unwrap();
@@ -147,6 +154,60 @@ async fn upsert_pinecone_vectors(
     Ok(())
 }
 
+/// POST /v1/context/screen - Record what the user is currently looking at
+async fn ingest_screen_context(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<ScreenContextRequest>,
+) -> Result<Json<ScreenContextResponse>, (StatusCode, String)> {
+    if request.app_name.trim().is_empty()
+        && request.window_title.trim().is_empty()
+        && request.ocr_text.trim().is_empty()
+    {
+        return Err((StatusCode::BAD_REQUEST, "Empty screen context".to_string()));
+    }
+
+    let now = Utc::now();
+    let snapshot = ScreenContextSnapshot {
+        app_name: request.app_name.trim().to_string(),
+        window_title: request.window_title.trim().to_string(),
+        ocr_text: request.ocr_text.trim().chars().take(MAX_OCR_CHARS).collect(),
+        // Clamp future timestamps from skewed clocks
+        captured_at: request.captured_at.map_or(now, |t| t.min(now)),
+    };
+
+    let (accepted, buffered) = state.screen_context.push(&user.uid, snapshot).await;
+    tracing::debug!(
+        "Screen context for user {}: accepted={} buffered={}",
+        user.uid,
+        accepted,
+        buffered
+    );
+
+    Ok(Json(ScreenContextResponse { accepted, buffered }))
+}
+
+/// GET /v1/context/screen - Latest unexpired snapshot (404 if none)
+async fn get_screen_context(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<ScreenContextSnapshot>, StatusCode> {
+    state
+        .screen_context
+        .latest(&user.uid)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 pub fn screen_activity_routes() -> Router<AppState> {
-    Router::new().route("/v1/screen-activity/sync", post(sync_screen_activity))
+    Router::new()
+        .route(
+            "/v1/screen-activity/sync",
+            with_body_limit(post(sync_screen_activity), LARGE_JSON_BODY_LIMIT_BYTES),
+        )
+        .route(
+            "/v1/context/screen",
+            get(get_screen_context).post(ingest_screen_context),
+        )
 }
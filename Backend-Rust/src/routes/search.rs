@@ -0,0 +1,56 @@
+// Search routes - Command palette search across conversations, memories, action items, folders and apps
+// Endpoints: POST /v2/search/universal
+
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use std::time::Instant;
+
+use crate::auth::AuthUser;
+use crate::models::{
+    UniversalSearchRequest, UniversalSearchResponse, MAX_UNIVERSAL_SEARCH_LIMIT,
+    MAX_UNIVERSAL_SEARCH_QUERY_CHARS,
+};
+use crate::AppState;
+
+/// POST /v2/search/universal - Ranked, mixed results for a (possibly partial) query.
+/// Served from a per-user in-memory index, so typing a prefix doesn't hit Firestore.
+async fn universal_search(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UniversalSearchRequest>,
+) -> Result<Json<UniversalSearchResponse>, (StatusCode, String)> {
+    let started = Instant::now();
+    if request.query.chars().count() > MAX_UNIVERSAL_SEARCH_QUERY_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("query must be at most {} characters", MAX_UNIVERSAL_SEARCH_QUERY_CHARS),
+        ));
+    }
+
+    let results = state
+        .search_index
+        .search(
+            &state.firestore,
+            &user.uid,
+            &request.query,
+            &request.types,
+            request.limit.clamp(1, MAX_UNIVERSAL_SEARCH_LIMIT),
+        )
+        .await;
+    let took_ms = started.elapsed().as_millis() as u64;
+    tracing::debug!(
+        "Universal search for user {} returned {} results in {}ms",
+        user.uid,
+        results.len(),
+        took_ms
+    );
+
+    Ok(Json(UniversalSearchResponse {
+        query: request.query,
+        results,
+        took_ms,
+    }))
+}
+
+pub fn search_routes() -> Router<AppState> {
+    Router::new().route("/v2/search/universal", post(universal_search))
+}
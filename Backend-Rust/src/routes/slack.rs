@@ -0,0 +1,423 @@
+// Slack integration routes - Connect a workspace, capture action items, daily digest DMs
+// Endpoints: POST /v1/integrations/slack/connect, GET/PATCH/DELETE /v1/integrations/slack,
+//            POST /v1/integrations/slack/digest,
+//            POST /v1/integrations/slack/commands, POST /v1/integrations/slack/events (signed by Slack)
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::auth::AuthUser;
+use crate::models::{
+    SlackCommandPayload, SlackConnectRequest, SlackConnection, SlackEventEnvelope,
+    SlackStatusResponse, UpdateSlackSettingsRequest,
+};
+use crate::services::slack;
+use crate::AppState;
+
+/// Status response for operations
+#[derive(Serialize)]
+struct StatusResponse {
+    status: String,
+}
+
+/// Max length of an action item captured from Slack
+const MAX_CAPTURED_ITEM_CHARS: usize = 500;
+
+/// Load the user's connection or 404 when Slack isn't connected
+async fn require_connection(
+    state: &AppState,
+    uid: &str,
+) -> Result<SlackConnection, (StatusCode, String)> {
+    match state.firestore.get_slack_connection(uid).await {
+        Ok(Some(connection)) => Ok(connection),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Slack is not connected".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get Slack connection: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// POST /v1/integrations/slack/connect - Exchange an OAuth code and store the connection
+async fn connect_slack(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<SlackConnectRequest>,
+) -> Result<Json<SlackStatusResponse>, (StatusCode, String)> {
+    if !state.slack.is_configured() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Slack integration is not configured".to_string(),
+        ));
+    }
+    if request.code.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "code is required".to_string()));
+    }
+
+    tracing::info!("Connecting Slack for user {}", user.uid);
+
+    let token = state
+        .slack
+        .exchange_code(request.code.trim(), request.redirect_uri.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!("Slack OAuth exchange failed: {}", e);
+            (StatusCode::BAD_REQUEST, e.to_string())
+        })?;
+
+    let existing = state
+        .firestore
+        .get_slack_connection(&user.uid)
+        .await?;
+
+    // Connecting a different Slack account drops the old one (and its inbound lookup)
+    let existing = match existing {
+        Some(c) if c.team_id == token.team_id && c.slack_user_id == token.slack_user_id => Some(c),
+        Some(_) => {
+            state
+                .firestore
+                .delete_slack_connection(&user.uid)
+                .await?;
+            None
+        }
+        None => None,
+    };
+
+    let now = Utc::now();
+    let connection = SlackConnection {
+        access_token: token.access_token,
+        team_id: token.team_id,
+        team_name: token.team_name,
+        bot_user_id: token.bot_user_id,
+        slack_user_id: token.slack_user_id,
+        action_item_capture_enabled: existing.as_ref().is_none_or(|c| c.action_item_capture_enabled),
+        digest_enabled: existing.as_ref().is_some_and(|c| c.digest_enabled),
+        digest_hour: existing
+            .as_ref()
+            .map_or(crate::models::slack::DEFAULT_SLACK_DIGEST_HOUR, |c| c.digest_hour),
+        digest_channel_id: existing.as_ref().and_then(|c| c.digest_channel_id.clone()),
+        last_digest_date: existing.as_ref().and_then(|c| c.last_digest_date.clone()),
+        connected_at: existing.as_ref().map_or(now, |c| c.connected_at),
+        updated_at: now,
+    };
+
+    state
+        .firestore
+        .save_slack_connection(&user.uid, &connection)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save Slack connection: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(SlackStatusResponse::from(Some(&connection))))
+}
+
+/// GET /v1/integrations/slack - Connection status and settings
+async fn get_slack_status(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<SlackStatusResponse>, (StatusCode, String)> {
+    match state.firestore.get_slack_connection(&user.uid).await {
+        Ok(connection) => Ok(Json(SlackStatusResponse::from(connection.as_ref()))),
+        Err(e) => {
+            tracing::error!("Failed to get Slack connection: {}", e);
+            Err(e.public_error())
+        }
+    }
+}
+
+/// PATCH /v1/integrations/slack - Update capture and digest settings
+async fn update_slack_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateSlackSettingsRequest>,
+) -> Result<Json<SlackStatusResponse>, (StatusCode, String)> {
+    let mut connection = require_connection(&state, &user.uid).await?;
+
+    if let Some(hour) = request.digest_hour {
+        if !(0..=23).contains(&hour) {
+            return Err((StatusCode::BAD_REQUEST, "digest_hour must be 0-23".to_string()));
+        }
+        connection.digest_hour = hour;
+    }
+    if let Some(enabled) = request.action_item_capture_enabled {
+        connection.action_item_capture_enabled = enabled;
+    }
+    if let Some(enabled) = request.digest_enabled {
+        connection.digest_enabled = enabled;
+    }
+    if let Some(channel) = request.digest_channel_id {
+        let channel = channel.trim().to_string();
+        connection.digest_channel_id = if channel.is_empty() { None } else { Some(channel) };
+    }
+    connection.updated_at = Utc::now();
+
+    state
+        .firestore
+        .save_slack_connection(&user.uid, &connection)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update Slack settings: {}", e);
+            e.public_error()
+        })?;
+
+    Ok(Json(SlackStatusResponse::from(Some(&connection))))
+}
+
+/// DELETE /v1/integrations/slack - Disconnect Slack
+async fn disconnect_slack(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<StatusResponse>, StatusCode> {
+    tracing::info!("Disconnecting Slack for user {}", user.uid);
+
+    match state.firestore.delete_slack_connection(&user.uid).await {
+        Ok(()) => Ok(Json(StatusResponse {
+            status: "ok".to_string(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to disconnect Slack: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/integrations/slack/digest - Send today's digest now (e.g. to preview it)
+async fn send_slack_digest_now(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    let connection = require_connection(&state, &user.uid).await?;
+
+    let tz: Tz = state
+        .firestore
+        .get_user_profile(&user.uid)
+        .await
+        .ok()
+        .and_then(|p| p.time_zone)
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(Tz::UTC);
+    let today = Utc::now().with_timezone(&tz).date_naive();
+
+    slack::send_digest(&state.firestore, &state.slack, &user.uid, &connection, tz, today)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to send Slack digest: {}", e);
+            (StatusCode::BAD_GATEWAY, e.to_string())
+        })?;
+
+    Ok(Json(StatusResponse {
+        status: "sent".to_string(),
+    }))
+}
+
+// =========================================================================
+// INBOUND (called by Slack, authenticated by request signature)
+// =========================================================================
+
+/// Reject requests not signed by our Slack app
+fn verify_request(state: &AppState, headers: &HeaderMap, body: &[u8]) -> Result<(), StatusCode> {
+    if state.config.slack_signing_secret.is_none() {
+        tracing::error!("Slack request received but SLACK_SIGNING_SECRET is not configured");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or("");
+    if !state.slack.verify_signature(
+        header("x-slack-request-timestamp"),
+        header("x-slack-signature"),
+        body,
+    ) {
+        tracing::warn!("Slack request with invalid signature");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+/// Create an action item for the Slack user if they connected Omi and allow capture.
+/// Returns a user-facing reply, plus the connection when the user was resolved.
+async fn capture_action_item(
+    state: &AppState,
+    team_id: &str,
+    slack_user_id: &str,
+    channel_id: Option<&str>,
+    text: &str,
+    via: &str,
+) -> (String, Option<SlackConnection>) {
+    const RETRY: &str = "Something went wrong, please try again.";
+
+    let uid = match state.firestore.find_uid_by_slack_user(team_id, slack_user_id).await {
+        Ok(Some(uid)) => uid,
+        Ok(None) => return ("Connect Slack in the Omi app to capture tasks here.".to_string(), None),
+        Err(e) => {
+            tracing::error!("Failed to resolve Slack user: {}", e);
+            return (RETRY.to_string(), None);
+        }
+    };
+
+    let connection = match state.firestore.get_slack_connection(&uid).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return ("Connect Slack in the Omi app to capture tasks here.".to_string(), None),
+        Err(e) => {
+            tracing::error!("Failed to get Slack connection: {}", e);
+            return (RETRY.to_string(), None);
+        }
+    };
+    if !connection.action_item_capture_enabled {
+        return (
+            "Task capture from Slack is turned off in your Omi settings.".to_string(),
+            Some(connection),
+        );
+    }
+
+    let description: String = text.trim().chars().take(MAX_CAPTURED_ITEM_CHARS).collect();
+    let metadata = slack::action_item_metadata(team_id, channel_id, via);
+
+    let reply = match state
+        .storage
+        .create_action_item(
+            &uid,
+            &description,
+            None,
+            Some("slack"),
+            None,
+            Some(&metadata),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+    {
+        Ok(_) => {
+            tracing::info!("Captured action item from Slack ({}) for user {}", via, uid);
+            format!("Added to your Omi tasks: {}", description)
+        }
+        Err(e) => {
+            tracing::error!("Failed to create action item from Slack: {}", e);
+            "Couldn't save that task, please try again.".to_string()
+        }
+    };
+    (reply, Some(connection))
+}
+
+/// POST /v1/integrations/slack/commands - Slash command, e.g. `/omi follow up with Dana`
+async fn handle_slack_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, StatusCode> {
+    verify_request(&state, &headers, &body)?;
+
+    let payload: SlackCommandPayload = serde_urlencoded::from_bytes(&body).map_err(|e| {
+        tracing::warn!("Invalid Slack command payload: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let text = payload.text.trim();
+    let reply = if text.is_empty() || text.eq_ignore_ascii_case("help") {
+        format!("Usage: `{} <task>` adds a task to Omi. You can also DM me a task.", payload.command)
+    } else {
+        capture_action_item(
+            &state,
+            &payload.team_id,
+            &payload.user_id,
+            payload.channel_id.as_deref(),
+            text,
+            "slash_command",
+        )
+        .await
+        .0
+    };
+
+    Ok(Json(json!({"response_type": "ephemeral", "text": reply})))
+}
+
+/// POST /v1/integrations/slack/events - Events API; DMs to the bot become action items
+async fn handle_slack_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, StatusCode> {
+    verify_request(&state, &headers, &body)?;
+
+    let envelope: SlackEventEnvelope = serde_json::from_slice(&body).map_err(|e| {
+        tracing::warn!("Invalid Slack event payload: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if envelope.event_type == "url_verification" {
+        return Ok(Json(json!({"challenge": envelope.challenge.unwrap_or_default()})));
+    }
+
+    // Slack retries when we're slow; the first delivery already captured the item
+    if headers.contains_key("x-slack-retry-num") {
+        return Ok(Json(json!({"ok": true})));
+    }
+
+    let (Some(team_id), Some(event)) = (envelope.team_id, envelope.event) else {
+        return Ok(Json(json!({"ok": true})));
+    };
+
+    let is_user_dm = event.event_type == "message"
+        && event.channel_type.as_deref() == Some("im")
+        && event.subtype.is_none()
+        && event.bot_id.is_none();
+    let (Some(user_id), Some(text)) = (event.user.clone(), event.text.clone()) else {
+        return Ok(Json(json!({"ok": true})));
+    };
+    if !is_user_dm || text.trim().is_empty() {
+        return Ok(Json(json!({"ok": true})));
+    }
+
+    // Acknowledge within Slack's 3s deadline; capture and reply in the background
+    tokio::spawn(async move {
+        let (reply, connection) = capture_action_item(
+            &state,
+            &team_id,
+            &user_id,
+            event.channel.as_deref(),
+            &text,
+            "direct_message",
+        )
+        .await;
+
+        // Unknown users get no reply: the bot token belongs to someone else's connection
+        if let (Some(connection), Some(channel)) = (connection, event.channel.as_deref()) {
+            if let Err(e) = state
+                .slack
+                .post_message(&connection.access_token, channel, &reply)
+                .await
+            {
+                tracing::warn!("Failed to reply to Slack DM: {}", e);
+            }
+        }
+    });
+
+    Ok(Json(json!({"ok": true})))
+}
+
+pub fn slack_routes() -> Router<AppState> {
+    Router::new()
+        .route("/v1/integrations/slack/connect", post(connect_slack))
+        .route(
+            "/v1/integrations/slack",
+            get(get_slack_status)
+                .patch(update_slack_settings)
+                .delete(disconnect_slack),
+        )
+        .route("/v1/integrations/slack/digest", post(send_slack_digest_now))
+        .route("/v1/integrations/slack/commands", post(handle_slack_command))
+        .route("/v1/integrations/slack/events", post(handle_slack_event))
+}
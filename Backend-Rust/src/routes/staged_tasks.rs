@@ -42,7 +42,7 @@ async fn create_staged_task(
     );
 
     match state
-        .firestore
+        .storage
         .create_staged_task(
             &user.uid,
             &request.description,
@@ -58,7 +58,7 @@ async fn create_staged_task(
         Ok(item) => Ok(Json(item)),
         Err(e) => {
             tracing::error!("Failed to create staged task: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -79,7 +79,7 @@ async fn get_staged_tasks(
     let fetch_limit = query.limit + 1;
 
     match state
-        .firestore
+        .storage
         .get_staged_tasks(&user.uid, fetch_limit, query.offset)
         .await
     {
@@ -109,7 +109,7 @@ async fn delete_staged_task(
     tracing::info!("Deleting staged task {} for user {}", item_id, user.uid);
 
     match state
-        .firestore
+        .storage
         .delete_staged_task(&user.uid, &item_id)
         .await
     {
@@ -118,7 +118,7 @@ async fn delete_staged_task(
         })),
         Err(e) => {
             tracing::error!("Failed to delete staged task: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -151,7 +151,7 @@ async fn batch_update_staged_scores(
         })),
         Err(e) => {
             tracing::error!("Failed to batch update staged scores: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -180,7 +180,7 @@ async fn promote_staged_task(
         Ok(items) => items,
         Err(e) => {
             tracing::error!("Failed to get active AI items: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(e.http_status());
         }
     };
 
@@ -210,11 +210,11 @@ async fn promote_staged_task(
         .collect();
 
     // Step 2: Get top-ranked staged tasks (fetch a batch for dedup skipping)
-    let staged_tasks = match state.firestore.get_staged_tasks(&user.uid, 20, 0).await {
+    let staged_tasks = match state.storage.get_staged_tasks(&user.uid, 20, 0).await {
         Ok(tasks) => tasks,
         Err(e) => {
             tracing::error!("Failed to get staged tasks: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(e.http_status());
         }
     };
 
@@ -262,7 +262,7 @@ async fn promote_staged_task(
     // Clean up all duplicates in the background
     for dup_id in &duplicate_ids {
         let _ = state
-            .firestore
+            .storage
             .delete_staged_task(&user.uid, dup_id)
             .await;
     }
@@ -287,7 +287,7 @@ async fn promote_staged_task(
 
     // Step 4: Create in action_items (from_staged=true marks it as promoted)
     let promoted_item = match state
-        .firestore
+        .storage
         .create_action_item(
             &user.uid,
             &top_task.description,
@@ -306,13 +306,13 @@ async fn promote_staged_task(
         Ok(item) => item,
         Err(e) => {
             tracing::error!("Failed to create promoted action item: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(e.http_status());
         }
     };
 
     // Step 5: Hard-delete from staged_tasks
     if let Err(e) = state
-        .firestore
+        .storage
         .delete_staged_task(&user.uid, &top_task.id)
         .await
     {
@@ -353,7 +353,7 @@ async fn migrate_ai_tasks(
     let batch_size = 500;
     loop {
         match state
-            .firestore
+            .storage
             .get_action_items(
                 &user.uid,
                 batch_size,
@@ -366,6 +366,7 @@ async fn migrate_ai_tasks(
                 None,
                 None,
                 None, // not deleted (default)
+                true, // include snoozed
             )
             .await
         {
@@ -379,7 +380,7 @@ async fn migrate_ai_tasks(
             }
             Err(e) => {
                 tracing::error!("Failed to get action items for migration: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(e.http_status());
             }
         }
     }
@@ -424,7 +425,7 @@ async fn migrate_ai_tasks(
         if !task.description.ends_with(" [screen]") && !task.description.starts_with("[screen] ") {
             let prefixed = format!("{} [screen]", task.description);
             if let Err(e) = state
-                .firestore
+                .storage
                 .update_action_item(
                     &user.uid,
                     &task.id,
@@ -438,6 +439,8 @@ async fn migrate_ai_tasks(
                     None,
                     None,
                     None, // recurrence_rule
+                    None, // rollover_excluded
+                    None, // precondition
                 )
                 .await
             {
@@ -465,7 +468,7 @@ async fn migrate_ai_tasks(
         Ok(count) => count,
         Err(e) => {
             tracing::error!("Migration batch commit failed: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(e.http_status());
         }
     };
 
@@ -500,7 +503,7 @@ async fn migrate_conversation_items(
         }))),
         Err(e) => {
             tracing::error!("Failed to migrate conversation items: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -0,0 +1,152 @@
+// Sync routes - Delta sync for desktop and mobile clients
+// Endpoints: GET /v2/sync
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+
+use crate::auth::AuthUser;
+use crate::models::{
+    ActionItemDB, CollectionDelta, Conversation, Folder, MemoryDB, SettingsDelta, SyncPage,
+    SyncQuery, SyncResponse,
+};
+use crate::services::firestore::{
+    FirestoreError, ACTION_ITEMS_SUBCOLLECTION, CONVERSATIONS_SUBCOLLECTION, FOLDERS_SUBCOLLECTION,
+    MEMORIES_SUBCOLLECTION,
+};
+use crate::AppState;
+
+/// Deletion history older than this may be incomplete; older cursors need a full resync
+const TOMBSTONE_RETENTION_DAYS: i64 = 30;
+const DEFAULT_SYNC_LIMIT: usize = 500;
+const MAX_SYNC_LIMIT: usize = 1000;
+const MAX_TOMBSTONES: usize = MAX_SYNC_LIMIT * 4;
+
+type SyncResult = SyncResponse<Conversation, MemoryDB, ActionItemDB, Folder>;
+
+fn internal_error(e: FirestoreError) -> (StatusCode, String) {
+    tracing::error!("Sync failed: {}", e);
+    e.public_error()
+}
+
+/// GET /v2/sync - IDs created/updated/deleted since `since`, with optional bodies
+async fn sync(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<SyncQuery>,
+) -> Result<Json<SyncResult>, (StatusCode, String)> {
+    // Taken before reading so writes during the sync are picked up next time
+    let cursor = Utc::now();
+    let limit = query.limit.unwrap_or(DEFAULT_SYNC_LIMIT).clamp(1, MAX_SYNC_LIMIT);
+    let since = query.since;
+    let full_resync_required =
+        since.is_some_and(|s| s < cursor - Duration::days(TOMBSTONE_RETENTION_DAYS));
+
+    let firestore = &state.firestore;
+    let uid = user.uid.as_str();
+    let after_id = query.after_id.as_deref();
+    let tombstones = async {
+        match since {
+            Some(since) if !full_resync_required => {
+                firestore.get_sync_tombstones(uid, since, after_id, MAX_TOMBSTONES).await
+            }
+            _ => Ok(SyncPage { docs: Vec::new(), end: None }),
+        }
+    };
+    let (conversations, memories, action_items, folders, settings_updated_at, tombstones) =
+        tokio::try_join!(
+            firestore.get_conversations_changed_since(uid, since, after_id, limit),
+            firestore.get_memories_changed_since(uid, since, after_id, limit),
+            firestore.get_action_items_changed_since(uid, since, after_id, limit),
+            firestore.get_folders_changed_since(uid, since, after_id, limit),
+            firestore.get_user_settings_updated_at(uid),
+            tombstones,
+        )
+        .map_err(internal_error)?;
+
+    // Every collection stops at the earliest point any read was cut off, so the next sync
+    // resumes from one position without skipping anything
+    let boundary = [
+        &conversations.end,
+        &memories.end,
+        &action_items.end,
+        &folders.end,
+        &tombstones.end,
+    ]
+    .into_iter()
+    .flatten()
+    .min()
+    .cloned();
+    let (conversations, conversations_truncated) = conversations.take_until(boundary.as_ref());
+    let (memories, memories_truncated) = memories.take_until(boundary.as_ref());
+    let (action_items, action_items_truncated) = action_items.take_until(boundary.as_ref());
+    let (folders, folders_truncated) = folders.take_until(boundary.as_ref());
+    // Deletions left for the next sync may belong to any collection
+    let (tombstones, tombstones_truncated) = tombstones.take_until(boundary.as_ref());
+
+    let deleted_ids = |collection: &str| -> Vec<String> {
+        tombstones
+            .iter()
+            .filter(|(c, _)| c == collection)
+            .map(|(_, id)| id.clone())
+            .collect()
+    };
+    let include_bodies = query.include_bodies;
+    let (cursor, cursor_id) = match boundary {
+        Some(boundary) => (boundary.updated_at, Some(boundary.id)),
+        None => (cursor, None),
+    };
+
+    Ok(Json(SyncResponse {
+        cursor,
+        cursor_id,
+        full_resync_required,
+        conversations: CollectionDelta::build(
+            conversations,
+            deleted_ids(CONVERSATIONS_SUBCOLLECTION),
+            since,
+            include_bodies,
+            conversations_truncated || tombstones_truncated,
+            |c| (c.id.clone(), c.created_at, c.deleted),
+        ),
+        memories: CollectionDelta::build(
+            memories,
+            deleted_ids(MEMORIES_SUBCOLLECTION),
+            since,
+            include_bodies,
+            memories_truncated || tombstones_truncated,
+            |m| (m.id.clone(), m.created_at, false),
+        ),
+        action_items: CollectionDelta::build(
+            action_items,
+            deleted_ids(ACTION_ITEMS_SUBCOLLECTION),
+            since,
+            include_bodies,
+            action_items_truncated || tombstones_truncated,
+            |a| (a.id.clone(), a.created_at, a.deleted == Some(true)),
+        ),
+        folders: CollectionDelta::build(
+            folders,
+            deleted_ids(FOLDERS_SUBCOLLECTION),
+            since,
+            include_bodies,
+            folders_truncated || tombstones_truncated,
+            |f| (f.id.clone(), f.created_at, false),
+        ),
+        settings: SettingsDelta {
+            changed: match (since, settings_updated_at) {
+                (Some(since), Some(updated_at)) => updated_at >= since,
+                _ => true,
+            },
+            updated_at: settings_updated_at,
+        },
+    }))
+}
+
+pub fn sync_routes() -> Router<AppState> {
+    Router::new().route("/v2/sync", get(sync))
+}
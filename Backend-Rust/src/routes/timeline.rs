@@ -0,0 +1,60 @@
+// Timeline routes - "What happened today" feed for the Swift Today view
+// Endpoints: GET /v1/timeline
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+
+use crate::auth::AuthUser;
+use crate::etag::with_etag;
+use crate::models::{TimelineQuery, TimelineResponse};
+use crate::services::timeline;
+use crate::AppState;
+
+/// GET /v1/timeline?date=YYYY-MM-DD - Conversations, focus sessions, advice and action items
+/// of a day in the user's timezone, oldest first; the last 24 hours without a date
+async fn get_timeline(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<TimelineResponse>, (StatusCode, String)> {
+    let tz_name = match query.timezone.filter(|tz| !tz.trim().is_empty()) {
+        Some(tz) => Some(tz),
+        None => state
+            .firestore
+            .get_user_profile(&user.uid)
+            .await
+            .ok()
+            .and_then(|profile| profile.time_zone),
+    };
+    let tz: Tz = match tz_name {
+        Some(name) => name
+            .trim()
+            .parse()
+            .map_err(|_| (StatusCode::BAD_REQUEST, format!("Unknown timezone: {}", name)))?,
+        None => Tz::UTC,
+    };
+    if let Some(date) = query.date {
+        if date > Utc::now().with_timezone(&tz).date_naive() {
+            return Err((StatusCode::BAD_REQUEST, "Cannot show a future day".to_string()));
+        }
+    }
+
+    let timeline = timeline::build_timeline(&state.firestore, &user.uid, tz, query.date)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to build timeline for {}: {}", user.uid, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build timeline".to_string())
+        })?;
+
+    Ok(Json(timeline))
+}
+
+pub fn timeline_routes() -> Router<AppState> {
+    Router::new().route("/v1/timeline", with_etag(get(get_timeline)))
+}
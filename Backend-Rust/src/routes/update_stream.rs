@@ -0,0 +1,60 @@
+// Update stream routes - Server-sent events for the signed-in user's domain events
+// Endpoints: GET /v1/updates/stream
+// Each event is named after the AppEvent (e.g. "app_result_completed") with a JSON payload.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Router,
+};
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::auth::AuthUser;
+use crate::services::events::PublishedEvent;
+use crate::services::firestore::current_project_override;
+use crate::AppState;
+
+/// Keep-alive comment interval, so proxies don't close an idle stream
+const KEEP_ALIVE_SECS: u64 = 15;
+
+/// GET /v1/updates/stream - Follow the user's conversation and app result events
+async fn stream_updates(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut receiver = state.events.subscribe();
+    let uid = user.uid;
+    // The stream is polled after the handler returns, outside the request's project scope
+    let project = current_project_override();
+    tracing::info!("Update stream opened for user {}", uid);
+
+    let stream = async_stream::stream! {
+        loop {
+            match receiver.recv().await {
+                Ok(PublishedEvent { event, project: published_in }) if event.uid() == uid && published_in == project => {
+                    if let Some(payload) = event.client_payload() {
+                        yield Ok(Event::default().event(event.name()).data(payload.to_string()));
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    // The client should refetch what it's showing
+                    tracing::warn!("Update stream for user {} missed {} events", uid, missed);
+                    yield Ok(Event::default().event("lagged").data(missed.to_string()));
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(KEEP_ALIVE_SECS)))
+}
+
+pub fn update_stream_routes() -> Router<AppState> {
+    Router::new().route("/v1/updates/stream", get(stream_updates))
+}
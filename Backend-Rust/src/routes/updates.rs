@@ -215,10 +215,7 @@ async fn download_redirect(State(state): State<AppState>) -> impl IntoResponse {
         }
         Err(e) => {
             tracing::error!("Failed to fetch releases for download redirect: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to fetch releases: {}", e),
-            )
+            e.public_error()
                 .into_response()
         }
     }
@@ -303,7 +300,7 @@ async fn create_release(
         Err(e) => {
             tracing::error!("Failed to create release: {}", e);
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                e.http_status(),
                 Json(CreateReleaseResponse {
                     success: false,
                     doc_id: String::new(),
@@ -2,7 +2,7 @@
 // Endpoints: /v1/users/*
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::get,
     Json, Router,
@@ -10,13 +10,24 @@ use axum::{
 use serde::Deserialize;
 
 use crate::auth::AuthUser;
+use crate::llm::prompts::{sanitize_custom_prompt, MAX_CUSTOM_PROCESSING_PROMPT_CHARS};
+use crate::llm::topics::{normalize_blocked_topics, MAX_BLOCKED_TOPICS};
 use crate::models::{
     DailySummarySettings, NotificationSettings, PrivateCloudSync, RecordingPermission,
     TranscriptionPreferences, UpdateDailySummaryRequest, UpdateLanguageRequest,
     AIUserProfile, UpdateAIUserProfileRequest, UpdateNotificationSettingsRequest,
     UpdateTranscriptionPreferencesRequest, UpdateUserProfileRequest, UserLanguage, UserProfile,
-    UserSettingsStatusResponse, AssistantSettingsData,
+    UserSettingsStatusResponse, AssistantSettingsData, ProcessingPromptSettings,
+    UpdateProcessingPromptRequest, ActionItemRolloverSettings, UpdateActionItemRolloverRequest,
+    WeeklyReviewSettings, UpdateWeeklyReviewSettingsRequest,
+    CreateUserWebhookRequest, CreateUserWebhookResponse, UserWebhook, MAX_MEMORY_AUTO_APPROVE_DAYS, MAX_USER_WEBHOOKS,
+    USER_WEBHOOK_EVENTS, RetentionPolicy, RetentionPreview, UpdateRetentionPolicyRequest,
+    LlmCredentials, LlmCredentialsStatus, SetLlmCredentialsRequest, ValidateLlmCredentialsRequest,
+    ValidateLlmCredentialsResponse, MAX_LLM_API_KEY_CHARS,
 };
+use crate::llm::LlmClient;
+use crate::services::firestore::FirestoreError;
+use crate::services::retention::preview_retention;
 use crate::AppState;
 
 // ============================================================================
@@ -63,7 +74,104 @@ async fn update_daily_summary_settings(
         Ok(settings) => Ok(Json(settings)),
         Err(e) => {
             tracing::error!("Failed to update daily summary settings: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
+        }
+    }
+}
+
+// ============================================================================
+// Action Item Rollover Settings
+// ============================================================================
+
+/// GET /v1/users/action-item-rollover-settings
+async fn get_action_item_rollover_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<ActionItemRolloverSettings>, StatusCode> {
+    tracing::info!("Getting action item rollover settings for user {}", user.uid);
+
+    match state.firestore.get_action_item_rollover_settings(&user.uid).await {
+        Ok(settings) => Ok(Json(settings)),
+        Err(e) => {
+            tracing::error!("Failed to get action item rollover settings: {}", e);
+            // Return defaults on error
+            Ok(Json(ActionItemRolloverSettings::default()))
+        }
+    }
+}
+
+/// PATCH /v1/users/action-item-rollover-settings
+async fn update_action_item_rollover_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateActionItemRolloverRequest>,
+) -> Result<Json<ActionItemRolloverSettings>, StatusCode> {
+    tracing::info!("Updating action item rollover settings for user {}", user.uid);
+
+    // Validate hour if provided
+    if let Some(hour) = request.hour {
+        if !(0..=23).contains(&hour) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    match state
+        .firestore
+        .update_action_item_rollover_settings(&user.uid, request.enabled, request.hour)
+        .await
+    {
+        Ok(settings) => Ok(Json(settings)),
+        Err(e) => {
+            tracing::error!("Failed to update action item rollover settings: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+// ============================================================================
+// Weekly Review Settings
+// ============================================================================
+
+/// GET /v1/users/weekly-review-settings
+async fn get_weekly_review_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<WeeklyReviewSettings>, StatusCode> {
+    tracing::info!("Getting weekly review settings for user {}", user.uid);
+
+    match state.firestore.get_weekly_review_settings(&user.uid).await {
+        Ok(settings) => Ok(Json(settings)),
+        Err(e) => {
+            tracing::error!("Failed to get weekly review settings: {}", e);
+            // Return defaults on error
+            Ok(Json(WeeklyReviewSettings::default()))
+        }
+    }
+}
+
+/// PATCH /v1/users/weekly-review-settings
+async fn update_weekly_review_settings(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateWeeklyReviewSettingsRequest>,
+) -> Result<Json<WeeklyReviewSettings>, StatusCode> {
+    tracing::info!("Updating weekly review settings for user {}", user.uid);
+
+    if let Some(hour) = request.hour {
+        if !(0..=23).contains(&hour) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    match state
+        .firestore
+        .update_weekly_review_settings(&user.uid, request.enabled, request.hour)
+        .await
+    {
+        Ok(settings) => Ok(Json(settings)),
+        Err(e) => {
+            tracing::error!("Failed to update weekly review settings: {}", e);
+            Err(e.http_status())
         }
     }
 }
@@ -116,7 +224,7 @@ async fn update_transcription_preferences(
         Ok(prefs) => Ok(Json(prefs)),
         Err(e) => {
             tracing::error!("Failed to update transcription preferences: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -132,7 +240,7 @@ async fn get_language(
 ) -> Result<Json<UserLanguage>, StatusCode> {
     tracing::info!("Getting language for user {}", user.uid);
 
-    match state.firestore.get_user_language(&user.uid).await {
+    match state.storage.get_user_language(&user.uid).await {
         Ok(lang) => Ok(Json(UserLanguage { language: lang })),
         Err(e) => {
             tracing::error!("Failed to get language: {}", e);
@@ -156,7 +264,7 @@ async fn update_language(
     );
 
     match state
-        .firestore
+        .storage
         .update_user_language(&user.uid, &request.language)
         .await
     {
@@ -165,7 +273,7 @@ async fn update_language(
         })),
         Err(e) => {
             tracing::error!("Failed to update language: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -217,7 +325,7 @@ async fn set_recording_permission(
         })),
         Err(e) => {
             tracing::error!("Failed to set recording permission: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -269,7 +377,7 @@ async fn set_private_cloud_sync(
         })),
         Err(e) => {
             tracing::error!("Failed to set private cloud sync: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -285,7 +393,7 @@ async fn get_notification_settings(
 ) -> Result<Json<NotificationSettings>, StatusCode> {
     tracing::info!("Getting notification settings for user {}", user.uid);
 
-    match state.firestore.get_notification_settings(&user.uid).await {
+    match state.storage.get_notification_settings(&user.uid).await {
         Ok(settings) => Ok(Json(settings)),
         Err(e) => {
             tracing::error!("Failed to get notification settings: {}", e);
@@ -313,14 +421,14 @@ async fn update_notification_settings(
     }
 
     match state
-        .firestore
+        .storage
         .update_notification_settings(&user.uid, request.enabled, request.frequency)
         .await
     {
         Ok(settings) => Ok(Json(settings)),
         Err(e) => {
             tracing::error!("Failed to update notification settings: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -395,7 +503,7 @@ async fn update_profile(
         Ok(profile) => Ok(Json(profile)),
         Err(e) => {
             tracing::error!("Failed to update profile: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -453,11 +561,83 @@ async fn update_ai_profile(
         Ok(profile) => Ok(Json(profile)),
         Err(e) => {
             tracing::error!("Failed to update AI user profile: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
 
+// ============================================================================
+// Custom Processing Prompt
+// ============================================================================
+
+/// GET /v1/users/processing-prompt
+async fn get_processing_prompt(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<ProcessingPromptSettings>, StatusCode> {
+    tracing::info!("Getting processing prompt for user {}", user.uid);
+
+    match state.storage.get_processing_settings(&user.uid).await {
+        Ok(settings) => Ok(Json(ProcessingPromptSettings {
+            max_length: MAX_CUSTOM_PROCESSING_PROMPT_CHARS,
+            ..settings
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get processing prompt: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// PATCH /v1/users/processing-prompt
+/// Stores the sanitized prompt (an empty prompt clears the customization) and the
+/// plain-title preference.
+async fn update_processing_prompt(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateProcessingPromptRequest>,
+) -> Result<Json<ProcessingPromptSettings>, (StatusCode, String)> {
+    tracing::info!("Updating processing prompt for user {}", user.uid);
+
+    if request
+        .prompt
+        .as_ref()
+        .is_some_and(|p| p.chars().count() > MAX_CUSTOM_PROCESSING_PROMPT_CHARS)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Prompt exceeds {} characters", MAX_CUSTOM_PROCESSING_PROMPT_CHARS),
+        ));
+    }
+
+    let prompt = request.prompt.as_deref().map(sanitize_custom_prompt);
+
+    let internal_error = |e: FirestoreError| {
+        tracing::error!("Failed to update processing prompt: {}", e);
+        (e.http_status(), "Failed to update processing prompt".to_string())
+    };
+    state
+        .storage
+        .update_processing_settings(
+            &user.uid,
+            prompt.as_deref(),
+            request.plain_titles,
+            request.auto_discard,
+        )
+        .await
+        .map_err(internal_error)?;
+    let settings = state
+        .storage
+        .get_processing_settings(&user.uid)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ProcessingPromptSettings {
+        max_length: MAX_CUSTOM_PROCESSING_PROMPT_CHARS,
+        ..settings
+    }))
+}
+
 // ============================================================================
 // Assistant Settings
 // ============================================================================
@@ -482,7 +662,7 @@ async fn get_assistant_settings(
 async fn update_assistant_settings(
     State(state): State<AppState>,
     user: AuthUser,
-    Json(request): Json<AssistantSettingsData>,
+    Json(mut request): Json<AssistantSettingsData>,
 ) -> Result<Json<AssistantSettingsData>, StatusCode> {
     tracing::info!("Updating assistant settings for user {}", user.uid);
 
@@ -530,7 +710,7 @@ async fn update_assistant_settings(
             }
         }
     }
-    if let Some(ref memory) = request.memory {
+    if let Some(ref mut memory) = request.memory {
         check_prompt(&memory.analysis_prompt, "Memory")?;
         if let Some(c) = memory.min_confidence {
             if !(0.0..=1.0).contains(&c) {
@@ -538,6 +718,19 @@ async fn update_assistant_settings(
                 return Err(StatusCode::BAD_REQUEST);
             }
         }
+        if let Some(ref topics) = memory.blocked_topics {
+            if topics.len() > MAX_BLOCKED_TOPICS {
+                tracing::warn!("Memory blocked_topics too long: {} (max {})", topics.len(), MAX_BLOCKED_TOPICS);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            memory.blocked_topics = Some(normalize_blocked_topics(topics));
+        }
+        if let Some(days) = memory.auto_approve_after_days {
+            if !(0..=MAX_MEMORY_AUTO_APPROVE_DAYS).contains(&days) {
+                tracing::warn!("Memory auto_approve_after_days out of range: {}", days);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
     }
 
     match state
@@ -548,11 +741,346 @@ async fn update_assistant_settings(
         Ok(settings) => Ok(Json(settings)),
         Err(e) => {
             tracing::error!("Failed to update assistant settings: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+// ============================================================================
+// Conversation Retention Policy
+// ============================================================================
+
+/// Longest supported retention threshold (10 years)
+const MAX_RETENTION_DAYS: i32 = 3650;
+
+/// Query params for previewing an unsaved policy; omitted fields use the saved policy
+#[derive(Deserialize)]
+struct RetentionPreviewQuery {
+    archive_after_days: Option<i32>,
+    delete_after_days: Option<i32>,
+    transcript_delete_after_days: Option<i32>,
+}
+
+/// Normalize requested thresholds (0 disables a step) and check they're consistent
+fn resolve_retention_policy(
+    current: &RetentionPolicy,
+    archive_after_days: Option<i32>,
+    delete_after_days: Option<i32>,
+    transcript_delete_after_days: Option<i32>,
+) -> Result<RetentionPolicy, StatusCode> {
+    let resolve = |requested: Option<i32>, current: Option<i32>| match requested {
+        Some(0) => Ok(None),
+        Some(d) if (1..=MAX_RETENTION_DAYS).contains(&d) => Ok(Some(d)),
+        Some(d) => {
+            tracing::warn!("Invalid retention threshold: {} days", d);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        None => Ok(current),
+    };
+    let archive = resolve(archive_after_days, current.archive_after_days)?;
+    let delete = resolve(delete_after_days, current.delete_after_days)?;
+    let transcript = resolve(transcript_delete_after_days, current.transcript_delete_after_days)?;
+
+    if let (Some(a), Some(d)) = (archive, delete) {
+        if d <= a {
+            tracing::warn!("delete_after_days ({}) must exceed archive_after_days ({})", d, a);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    if let (Some(t), Some(d)) = (transcript, delete) {
+        if d <= t {
+            tracing::warn!("delete_after_days ({}) must exceed transcript_delete_after_days ({})", d, t);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    Ok(RetentionPolicy {
+        archive_after_days: archive,
+        delete_after_days: delete,
+        transcript_delete_after_days: transcript,
+        last_enforced_on: current.last_enforced_on.clone(),
+    })
+}
+
+/// GET /v1/users/retention-policy
+async fn get_retention_policy(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<RetentionPolicy>, StatusCode> {
+    match state.firestore.get_retention_policy(&user.uid).await {
+        Ok(policy) => Ok(Json(policy)),
+        Err(e) => {
+            tracing::error!("Failed to get retention policy: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// PATCH /v1/users/retention-policy
+/// Turning on or shortening transcript deletion removes transcripts for good, so it returns
+/// 428 unless `confirm_transcript_deletion` is set; clients show the preview first.
+async fn update_retention_policy(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<UpdateRetentionPolicyRequest>,
+) -> Result<Json<RetentionPolicy>, StatusCode> {
+    let current = state.firestore.get_retention_policy(&user.uid).await.map_err(|e| {
+        tracing::error!("Failed to get retention policy: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let policy = resolve_retention_policy(
+        &current,
+        request.archive_after_days,
+        request.delete_after_days,
+        request.transcript_delete_after_days,
+    )?;
+
+    let removes_more_transcripts = match (policy.transcript_delete_after_days, current.transcript_delete_after_days) {
+        (Some(new), Some(old)) => new < old,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    if removes_more_transcripts && !request.confirm_transcript_deletion {
+        tracing::warn!("Transcript deletion change for user {} not confirmed", user.uid);
+        return Err(StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    match state.firestore.update_retention_policy(&user.uid, &policy).await {
+        Ok(()) => {
+            if removes_more_transcripts {
+                tracing::info!(
+                    "User {} confirmed transcript deletion after {:?} days",
+                    user.uid,
+                    policy.transcript_delete_after_days
+                );
+            }
+            Ok(Json(policy))
+        }
+        Err(e) => {
+            tracing::error!("Failed to update retention policy: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// GET /v1/users/retention-policy/preview - Dry run: what the next enforcement would do
+async fn preview_retention_policy(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<RetentionPreviewQuery>,
+) -> Result<Json<RetentionPreview>, StatusCode> {
+    let current = state.firestore.get_retention_policy(&user.uid).await.map_err(|e| {
+        tracing::error!("Failed to get retention policy: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let policy = resolve_retention_policy(
+        &current,
+        query.archive_after_days,
+        query.delete_after_days,
+        query.transcript_delete_after_days,
+    )?;
+
+    match preview_retention(&state.firestore, &user.uid, &policy).await {
+        Ok(preview) => Ok(Json(preview)),
+        Err(e) => {
+            tracing::error!("Failed to preview retention policy: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+// ============================================================================
+// User Webhooks
+// ============================================================================
+
+/// GET /v1/users/webhooks
+async fn get_user_webhooks(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<UserWebhook>>, StatusCode> {
+    match state.firestore.get_user_webhooks(&user.uid).await {
+        Ok(webhooks) => Ok(Json(webhooks)),
+        Err(e) => {
+            tracing::error!("Failed to get user webhooks: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/users/webhooks - Register a webhook; the signing secret is only returned here
+async fn create_user_webhook(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<CreateUserWebhookRequest>,
+) -> Result<Json<CreateUserWebhookResponse>, StatusCode> {
+    let url = request.url.trim();
+    if !url.starts_with("https://") || url.len() > 2048 {
+        tracing::warn!("Invalid webhook url for user {}", user.uid);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if request.events.is_empty()
+        || request
+            .events
+            .iter()
+            .any(|e| !USER_WEBHOOK_EVENTS.contains(&e.as_str()))
+    {
+        tracing::warn!("Invalid webhook events: {:?}", request.events);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let existing = state.firestore.get_user_webhooks(&user.uid).await.map_err(|e| {
+        tracing::error!("Failed to get user webhooks: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if existing.len() >= MAX_USER_WEBHOOKS {
+        tracing::warn!("User {} already has {} webhooks", user.uid, existing.len());
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let mut events = request.events;
+    events.sort();
+    events.dedup();
+
+    match state
+        .firestore
+        .create_user_webhook(&user.uid, url, &events)
+        .await
+    {
+        Ok(webhook) => Ok(Json(CreateUserWebhookResponse {
+            secret: webhook.secret.clone(),
+            webhook,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to create user webhook: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// DELETE /v1/users/webhooks/:id
+async fn delete_user_webhook(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(webhook_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    match state.firestore.delete_user_webhook(&user.uid, &webhook_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(FirestoreError::NotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to delete user webhook: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+// ============================================================================
+// LLM Credentials (bring your own key)
+// ============================================================================
+
+/// GET /v1/users/llm-credentials - Whether the user has their own key; never returns it
+async fn get_llm_credentials(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<LlmCredentialsStatus>, StatusCode> {
+    match state.firestore.get_llm_credentials(&user.uid).await {
+        Ok(credentials) => Ok(Json(LlmCredentialsStatus::from(credentials.as_ref()))),
+        Err(e) => {
+            tracing::error!("Failed to get LLM credentials: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// PUT /v1/users/llm-credentials - Validate the key with the provider, then store it encrypted
+async fn set_llm_credentials(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<SetLlmCredentialsRequest>,
+) -> Result<Json<LlmCredentialsStatus>, (StatusCode, String)> {
+    let api_key = request.api_key.trim().to_string();
+    if api_key.is_empty() || api_key.chars().count() > MAX_LLM_API_KEY_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("API key must be 1-{} characters", MAX_LLM_API_KEY_CHARS),
+        ));
+    }
+    if !state.firestore.can_encrypt() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Storing API keys is not available on this server".to_string(),
+        ));
+    }
+
+    let client = LlmClient::new(api_key.clone()).with_task_models(state.llm_task_models().await);
+    if let Err(reason) = client.check_api_key().await {
+        tracing::info!("Rejected LLM key for user {}: {}", user.uid, reason);
+        return Err((StatusCode::BAD_REQUEST, format!("API key was rejected: {}", reason)));
+    }
+
+    let credentials = LlmCredentials {
+        provider: request.provider,
+        api_key,
+        updated_at: chrono::Utc::now(),
+    };
+    if let Err(e) = state.firestore.save_llm_credentials(&user.uid, &credentials).await {
+        tracing::error!("Failed to save LLM credentials: {}", e);
+        return Err((e.http_status(), "Failed to save API key".to_string()));
+    }
+    tracing::info!("User {} configured their own {} key", user.uid, credentials.provider.as_str());
+    Ok(Json(LlmCredentialsStatus::from(Some(&credentials))))
+}
+
+/// DELETE /v1/users/llm-credentials - Go back to the server key
+async fn delete_llm_credentials(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<StatusCode, StatusCode> {
+    match state.firestore.delete_llm_credentials(&user.uid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            tracing::error!("Failed to delete LLM credentials: {}", e);
+            Err(e.http_status())
+        }
+    }
+}
+
+/// POST /v1/users/llm-credentials/validate - Check a key without saving it.
+/// Without a key in the body, checks the key the user's requests currently run with.
+async fn validate_llm_credentials(
+    State(state): State<AppState>,
+    user: AuthUser,
+    request: Option<Json<ValidateLlmCredentialsRequest>>,
+) -> Result<Json<ValidateLlmCredentialsResponse>, StatusCode> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let api_key = match request.api_key.map(|k| k.trim().to_string()) {
+        Some(key) if key.is_empty() || key.chars().count() > MAX_LLM_API_KEY_CHARS => {
+            return Err(StatusCode::BAD_REQUEST)
+        }
+        Some(key) => key,
+        None => match state.llm_api_key(&user.uid).await {
+            Ok(Some(key)) => key,
+            Ok(None) => {
+                return Ok(Json(ValidateLlmCredentialsResponse {
+                    valid: false,
+                    error: Some("No API key is configured".to_string()),
+                }))
+            }
+            Err(e) => {
+                return Ok(Json(ValidateLlmCredentialsResponse {
+                    valid: false,
+                    error: Some(e.to_string()),
+                }))
+            }
+        },
+    };
+
+    let client = LlmClient::new(api_key).with_task_models(state.llm_task_models().await);
+    let result = client.check_api_key().await;
+    Ok(Json(ValidateLlmCredentialsResponse {
+        valid: result.is_ok(),
+        error: result.err(),
+    }))
+}
+
 // ============================================================================
 // Router
 // ============================================================================
@@ -564,6 +1092,16 @@ pub fn users_routes() -> Router<AppState> {
             "/v1/users/daily-summary-settings",
             get(get_daily_summary_settings).patch(update_daily_summary_settings),
         )
+        // Action item rollover
+        .route(
+            "/v1/users/action-item-rollover-settings",
+            get(get_action_item_rollover_settings).patch(update_action_item_rollover_settings),
+        )
+        // Weekly review
+        .route(
+            "/v1/users/weekly-review-settings",
+            get(get_weekly_review_settings).patch(update_weekly_review_settings),
+        )
         // Transcription
         .route(
             "/v1/users/transcription-preferences",
@@ -596,9 +1134,43 @@ pub fn users_routes() -> Router<AppState> {
             "/v1/users/ai-profile",
             get(get_ai_profile).patch(update_ai_profile),
         )
+        // Custom conversation processing prompt
+        .route(
+            "/v1/users/processing-prompt",
+            get(get_processing_prompt).patch(update_processing_prompt),
+        )
         // Assistant settings (proactive assistants)
         .route(
             "/v1/users/assistant-settings",
             get(get_assistant_settings).patch(update_assistant_settings),
         )
+        // Conversation retention
+        .route(
+            "/v1/users/retention-policy",
+            get(get_retention_policy).patch(update_retention_policy),
+        )
+        .route(
+            "/v1/users/retention-policy/preview",
+            get(preview_retention_policy),
+        )
+        // Developer webhooks
+        .route(
+            "/v1/users/webhooks",
+            get(get_user_webhooks).post(create_user_webhook),
+        )
+        .route(
+            "/v1/users/webhooks/:id",
+            axum::routing::delete(delete_user_webhook),
+        )
+        // Bring your own LLM key
+        .route(
+            "/v1/users/llm-credentials",
+            get(get_llm_credentials)
+                .put(set_llm_credentials)
+                .delete(delete_llm_credentials),
+        )
+        .route(
+            "/v1/users/llm-credentials/validate",
+            axum::routing::post(validate_llm_credentials),
+        )
 }
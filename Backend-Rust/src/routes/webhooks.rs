@@ -153,8 +153,8 @@ async fn handle_sentry_webhook(
 
     // Dedup + relevance score: fetch existing items once for both checks
     let existing_items = state
-        .firestore
-        .get_action_items(admin_uid, 500, 0, None, None, None, None, None, None, None, None)
+        .storage
+        .get_action_items(admin_uid, 500, 0, None, None, None, None, None, None, None, None, true)
         .await
         .unwrap_or_default();
 
@@ -227,7 +227,7 @@ async fn handle_sentry_webhook(
 
     // Create action item
     match state
-        .firestore
+        .storage
         .create_action_item(
             admin_uid,
             &description,
@@ -259,7 +259,7 @@ async fn handle_sentry_webhook(
                 issue_id,
                 e
             );
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.http_status())
         }
     }
 }
@@ -446,7 +446,7 @@ async fn poll_sentry_feedback(
 
     // 1. Fetch existing sentry_feedback action items to get already-processed issue IDs
     let existing_items = state
-        .firestore
+        .storage
         .get_action_items(
             admin_uid,
             500,   // limit — must be large enough to include all sentry_feedback items
@@ -459,6 +459,7 @@ async fn poll_sentry_feedback(
             None,  // due_end_date
             None,  // sort_by
             None,  // include_deleted
+            true,  // include_snoozed
         )
         .await
         .unwrap_or_default();
@@ -582,7 +583,7 @@ async fn poll_sentry_feedback(
         let metadata_str = serde_json::to_string(&meta).unwrap_or_default();
 
         match state
-            .firestore
+            .storage
             .create_action_item(
                 admin_uid,
                 &description,
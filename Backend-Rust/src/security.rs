@@ -0,0 +1,121 @@
+// HTTP security - CORS policy and standard security response headers
+// Configured via CORS_ALLOWED_ORIGINS, CORS_ALLOW_CREDENTIALS, CORS_DEV_MODE and HSTS_MAX_AGE_SECS.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+use crate::config::Config;
+
+/// How long browsers may cache preflight responses
+const CORS_MAX_AGE_SECS: u64 = 3600;
+
+/// Whether `origin` matches an allowlist entry. Entries are exact origins
+/// ("https://app.omi.me") or subdomain wildcards ("https://*.omi.me").
+pub fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    let origin = origin.trim_end_matches('/');
+    allowed.iter().any(|entry| {
+        let entry = entry.trim_end_matches('/');
+        match entry.split_once("://*.") {
+            Some((scheme, domain)) => origin
+                .strip_prefix(scheme)
+                .and_then(|rest| rest.strip_prefix("://"))
+                .is_some_and(|host| {
+                    host.strip_suffix(domain)
+                        .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1)
+                }),
+            None => entry.eq_ignore_ascii_case(origin),
+        }
+    })
+}
+
+/// CORS layer for the configured origins. Dev mode mirrors any origin back.
+pub fn cors_layer(config: &Config) -> CorsLayer {
+    let origin = if config.cors_dev_mode {
+        AllowOrigin::mirror_request()
+    } else {
+        let allowed = config.cors_allowed_origins.clone();
+        AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+            origin.to_str().is_ok_and(|o| origin_allowed(o, &allowed))
+        })
+    };
+
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(AllowMethods::list([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ]))
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(config.cors_allow_credentials)
+        .max_age(Duration::from_secs(CORS_MAX_AGE_SECS))
+}
+
+/// Middleware adding nosniff, frame-deny, referrer and HSTS headers.
+/// Headers a handler already set are kept. Dev mode only adds nosniff.
+pub async fn security_headers(
+    State(config): State<Arc<Config>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    headers
+        .entry(header::X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    if config.cors_dev_mode {
+        return response;
+    }
+
+    headers
+        .entry(header::X_FRAME_OPTIONS)
+        .or_insert(HeaderValue::from_static("DENY"));
+    headers
+        .entry(header::REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static("no-referrer"));
+    if config.hsts_max_age_secs > 0 {
+        if let Ok(value) = HeaderValue::from_str(&format!(
+            "max-age={}; includeSubDomains",
+            config.hsts_max_age_secs
+        )) {
+            headers.entry(header::STRICT_TRANSPORT_SECURITY).or_insert(value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_origins() {
+        let allowed = vec!["https://app.omi.me".to_string(), "http://localhost:3000/".to_string()];
+        assert!(origin_allowed("https://app.omi.me", &allowed));
+        assert!(origin_allowed("http://localhost:3000", &allowed));
+        assert!(!origin_allowed("http://app.omi.me", &allowed));
+        assert!(!origin_allowed("https://app.omi.me.evil.com", &allowed));
+        assert!(!origin_allowed("null", &allowed));
+    }
+
+    #[test]
+    fn test_wildcard_origins() {
+        let allowed = vec!["https://*.omi.me".to_string()];
+        assert!(origin_allowed("https://app.omi.me", &allowed));
+        assert!(origin_allowed("https://a.b.omi.me", &allowed));
+        assert!(!origin_allowed("https://omi.me", &allowed));
+        assert!(!origin_allowed("https://evilomi.me", &allowed));
+        assert!(!origin_allowed("http://app.omi.me", &allowed));
+    }
+}
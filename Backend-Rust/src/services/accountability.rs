@@ -0,0 +1,173 @@
+// Accountability service - Leaderboards for opt-in focus sharing groups
+// Only focus minutes and scores leave a member's account; never sessions or distractions
+
+use chrono::{Duration, Utc};
+use futures::future::join_all;
+
+use crate::models::{
+    AccountabilityGroup, DailyFocusScore, GroupLeaderboard, GroupMember, LeaderboardEntry,
+};
+use crate::services::FirestoreService;
+
+/// Invite code alphabet without look-alike characters (0/O, 1/I/L)
+const INVITE_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const INVITE_CODE_LEN: usize = 8;
+
+/// Random invite code, e.g. "K7QW2MXD"
+pub fn generate_invite_code() -> String {
+    uuid::Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(INVITE_CODE_LEN)
+        .map(|b| INVITE_CODE_ALPHABET[*b as usize % INVITE_CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+/// Normalize a user-typed invite code
+pub fn normalize_invite_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Focused share of tracked time (0-100, one decimal); None without tracked time
+pub fn focus_score(focused_minutes: i64, distracted_minutes: i64) -> Option<f64> {
+    let total = focused_minutes + distracted_minutes;
+    if total <= 0 {
+        return None;
+    }
+    Some((focused_minutes as f64 / total as f64 * 1000.0).round() / 10.0)
+}
+
+/// Order entries by score (then focused minutes) and assign ranks to scored entries.
+/// Members who don't share or have no data are listed after, unranked.
+pub fn rank_entries(entries: &mut [LeaderboardEntry]) {
+    entries.sort_by(|a, b| {
+        b.focus_score
+            .unwrap_or(-1.0)
+            .total_cmp(&a.focus_score.unwrap_or(-1.0))
+            .then(b.focused_minutes.unwrap_or(0).cmp(&a.focused_minutes.unwrap_or(0)))
+            .then(a.display_name.cmp(&b.display_name))
+    });
+    let mut rank = 0;
+    for entry in entries.iter_mut() {
+        entry.rank = entry.focus_score.map(|_| {
+            rank += 1;
+            rank
+        });
+    }
+}
+
+/// Build the leaderboard for the last `days` UTC days (including today)
+pub async fn build_leaderboard(
+    firestore: &FirestoreService,
+    group: &AccountabilityGroup,
+    members: &[GroupMember],
+    viewer_uid: &str,
+    days: u32,
+) -> GroupLeaderboard {
+    let today = Utc::now().date_naive();
+    let dates: Vec<String> = (0..days as i64)
+        .rev()
+        .map(|offset| (today - Duration::days(offset)).format("%Y-%m-%d").to_string())
+        .collect();
+
+    let mut entries = join_all(members.iter().map(|member| {
+        let dates = &dates;
+        async move {
+            let mut entry = LeaderboardEntry {
+                rank: None,
+                display_name: member.display_name.clone(),
+                is_you: member.uid == viewer_uid,
+                sharing: member.share_focus,
+                focus_score: None,
+                focused_minutes: None,
+                daily: vec![],
+            };
+            if !member.share_focus {
+                return entry;
+            }
+
+            let (mut focused, mut distracted) = (0, 0);
+            for date in dates {
+                let stats = firestore.get_focus_stats(&member.uid, date).await;
+                let (f, d) = match stats {
+                    Ok(s) => (s.focused_minutes, s.distracted_minutes),
+                    Err(e) => {
+                        tracing::warn!("Leaderboard: focus stats failed for {}: {}", member.uid, e);
+                        (0, 0)
+                    }
+                };
+                focused += f;
+                distracted += d;
+                entry.daily.push(DailyFocusScore {
+                    date: date.clone(),
+                    focus_score: focus_score(f, d),
+                });
+            }
+            entry.focus_score = focus_score(focused, distracted);
+            entry.focused_minutes = Some(focused);
+            entry
+        }
+    }))
+    .await;
+
+    rank_entries(&mut entries);
+
+    GroupLeaderboard {
+        group_id: group.id.clone(),
+        name: group.name.clone(),
+        days,
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: Option<f64>, minutes: i64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank: None,
+            display_name: name.to_string(),
+            is_you: false,
+            sharing: score.is_some(),
+            focus_score: score,
+            focused_minutes: score.map(|_| minutes),
+            daily: vec![],
+        }
+    }
+
+    #[test]
+    fn test_rank_entries_skips_private_members() {
+        let mut entries = vec![
+            entry("Private", None, 0),
+            entry("Ana", Some(62.5), 120),
+            entry("Ben", Some(80.0), 90),
+            entry("Cy", Some(62.5), 300),
+        ];
+        rank_entries(&mut entries);
+
+        let order: Vec<(&str, Option<usize>)> = entries
+            .iter()
+            .map(|e| (e.display_name.as_str(), e.rank))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("Ben", Some(1)), ("Cy", Some(2)), ("Ana", Some(3)), ("Private", None)]
+        );
+    }
+
+    #[test]
+    fn test_focus_score_and_invite_codes() {
+        assert_eq!(focus_score(45, 15), Some(75.0));
+        assert_eq!(focus_score(1, 2), Some(33.3));
+        assert_eq!(focus_score(0, 0), None);
+
+        let code = generate_invite_code();
+        assert_eq!(code.len(), INVITE_CODE_LEN);
+        assert_eq!(normalize_invite_code(&code.to_lowercase()), code);
+        assert_eq!(normalize_invite_code(" k7qw-2mxd "), "K7QW2MXD");
+    }
+}
@@ -0,0 +1,76 @@
+// Action item export - CSV rows for dumping tasks into a spreadsheet
+// RFC 4180 quoting, plus a guard so cells can't be read as spreadsheet formulas.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::models::ActionItemDB;
+
+/// Header row of the export
+pub const CSV_HEADER: &str = "id,description,completed,priority,category,due_at,created_at,completed_at,source,conversation_id,goal_id,deleted\r\n";
+
+/// Quote a field when it holds a delimiter, quote or line break. Text a spreadsheet
+/// would evaluate (leading =, +, -, @, tab or CR) is prefixed with an apostrophe.
+pub fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn timestamp(value: Option<DateTime<Utc>>) -> String {
+    value.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true)).unwrap_or_default()
+}
+
+/// One CSV line (with CRLF) for an action item
+pub fn csv_row(item: &ActionItemDB) -> String {
+    let fields = [
+        csv_field(&item.id),
+        csv_field(&item.description),
+        item.completed.to_string(),
+        csv_field(item.priority.as_deref().unwrap_or_default()),
+        csv_field(item.category.as_deref().unwrap_or_default()),
+        timestamp(item.due_at),
+        timestamp(Some(item.created_at)),
+        timestamp(item.completed_at),
+        csv_field(item.source.as_deref().unwrap_or_default()),
+        csv_field(item.conversation_id.as_deref().unwrap_or_default()),
+        csv_field(item.goal_id.as_deref().unwrap_or_default()),
+        item.deleted.unwrap_or(false).to_string(),
+    ];
+    format!("{}\r\n", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_row_escaping() {
+        assert_eq!(csv_field("Buy milk"), "Buy milk");
+        assert_eq!(csv_field("Call Ana, then Ben"), "\"Call Ana, then Ben\"");
+        assert_eq!(csv_field("Read \"Dune\"\nagain"), "\"Read \"\"Dune\"\"\nagain\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("-5 pounds"), "'-5 pounds");
+
+        let item: ActionItemDB = serde_json::from_value(serde_json::json!({
+            "id": "a1",
+            "description": "Send deck, v2",
+            "completed": true,
+            "created_at": "2026-10-14T09:00:00Z",
+            "due_at": "2026-10-16T21:00:00Z",
+            "priority": "high"
+        }))
+        .unwrap();
+        assert_eq!(
+            csv_row(&item),
+            "a1,\"Send deck, v2\",true,high,,2026-10-16T21:00:00Z,2026-10-14T09:00:00Z,,,,,false\r\n"
+        );
+        assert_eq!(CSV_HEADER.matches(',').count(), csv_row(&item).matches(',').count() - 1);
+    }
+}
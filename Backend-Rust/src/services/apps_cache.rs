@@ -0,0 +1,121 @@
+// Apps cache - Process-wide snapshot of approved public apps for marketplace listings
+// Refreshed in the background; admin approve/reject invalidates it.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::models::AppSummary;
+use crate::services::FirestoreService;
+
+/// How often the background task reloads the snapshot
+const APPS_CACHE_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Snapshots older than this are reloaded on read (covers a stalled refresh task)
+const APPS_CACHE_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+struct AppsSnapshot {
+    apps: Arc<Vec<AppSummary>>,
+    fetched_at: Instant,
+}
+
+/// Approved, public apps sorted by installs; per-user `enabled` is never set here
+#[derive(Clone, Default)]
+pub struct AppsCache {
+    inner: Arc<RwLock<Option<AppsSnapshot>>>,
+}
+
+impl AppsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current snapshot, unless missing or older than the max age
+    pub async fn get(&self) -> Option<Arc<Vec<AppSummary>>> {
+        let snapshot = self.inner.read().await;
+        snapshot
+            .as_ref()
+            .filter(|s| s.fetched_at.elapsed() < APPS_CACHE_MAX_AGE)
+            .map(|s| s.apps.clone())
+    }
+
+    /// Replace the snapshot; returns it for the caller to use
+    pub async fn set(&self, mut apps: Vec<AppSummary>) -> Arc<Vec<AppSummary>> {
+        apps.sort_by_key(|app| std::cmp::Reverse(app.installs));
+        let apps = Arc::new(apps);
+        *self.inner.write().await = Some(AppsSnapshot {
+            apps: apps.clone(),
+            fetched_at: Instant::now(),
+        });
+        apps
+    }
+
+    /// Drop the snapshot so the next read reloads from Firestore
+    pub async fn invalidate(&self) {
+        *self.inner.write().await = None;
+    }
+}
+
+/// Apps matching the optional capability and category filters
+pub fn filter_apps(
+    apps: &[AppSummary],
+    capability: Option<&str>,
+    category: Option<&str>,
+) -> Vec<AppSummary> {
+    apps.iter()
+        .filter(|app| capability.is_none_or(|cap| app.capabilities.iter().any(|c| c == cap)))
+        .filter(|app| category.is_none_or(|cat| app.category == cat))
+        .cloned()
+        .collect()
+}
+
+/// Spawn a task that reloads the apps snapshot every few minutes
+pub fn spawn_apps_cache_refresh(firestore: Arc<FirestoreService>) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = firestore.refresh_apps_cache().await {
+                tracing::warn!("Apps cache refresh failed: {}", e);
+            }
+            tokio::time::sleep(APPS_CACHE_REFRESH_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(id: &str, category: &str, capabilities: &[&str], installs: i32) -> AppSummary {
+        AppSummary {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            image: String::new(),
+            category: category.to_string(),
+            author: String::new(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            approved: true,
+            private: false,
+            installs,
+            rating_avg: None,
+            rating_count: 0,
+            is_paid: false,
+            price: None,
+            enabled: false,
+            has_auth_steps: false,
+            created_at: None,
+            translations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_filter_apps() {
+        let apps = vec![
+            app("a", "productivity", &["chat"], 1),
+            app("b", "social", &["memories", "chat"], 9),
+        ];
+        assert_eq!(filter_apps(&apps, None, None).len(), 2);
+        assert_eq!(filter_apps(&apps, Some("memories"), None)[0].id, "b");
+        assert_eq!(filter_apps(&apps, Some("chat"), Some("productivity"))[0].id, "a");
+        assert!(filter_apps(&apps, Some("chat"), Some("health")).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+// Apps home - Assembles the marketplace home sections from the apps snapshot
+// Section order and spotlight categories come from the admin-managed layout.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{
+    get_app_categories, AppSummary, AppsHomeLayout, AppsHomeSection, HomeSectionKind,
+    DEFAULT_SPOTLIGHT_CATEGORIES,
+};
+
+/// How far back "new this week" looks
+const NEW_APPS_WINDOW_DAYS: i64 = 7;
+
+/// Build the home sections in layout order; `apps` is the snapshot with `enabled` set for
+/// the user. Empty sections are left out.
+pub fn build_home_sections(
+    apps: &[AppSummary],
+    layout: &AppsHomeLayout,
+    limit: usize,
+    now: DateTime<Utc>,
+) -> Vec<AppsHomeSection> {
+    let mut by_installs: Vec<&AppSummary> = apps.iter().collect();
+    by_installs.sort_by_key(|app| std::cmp::Reverse(app.installs));
+
+    let section = |kind: HomeSectionKind, id: String, title: String, apps: Vec<&AppSummary>| {
+        AppsHomeSection {
+            id,
+            kind,
+            title,
+            apps: apps.into_iter().take(limit).cloned().collect(),
+        }
+    };
+
+    let mut sections = Vec::new();
+    for &kind in &layout.sections {
+        match kind {
+            HomeSectionKind::YourApps => {
+                let apps = by_installs.iter().copied().filter(|app| app.enabled).collect();
+                sections.push(section(kind, "your_apps".to_string(), "Your Apps".to_string(), apps));
+            }
+            HomeSectionKind::Popular => {
+                sections.push(section(
+                    kind,
+                    "popular".to_string(),
+                    "Popular".to_string(),
+                    by_installs.clone(),
+                ));
+            }
+            HomeSectionKind::NewThisWeek => {
+                let cutoff = now - Duration::days(NEW_APPS_WINDOW_DAYS);
+                let mut apps: Vec<&AppSummary> = by_installs
+                    .iter()
+                    .copied()
+                    .filter(|app| app.created_at.is_some_and(|created| created >= cutoff))
+                    .collect();
+                apps.sort_by_key(|app| std::cmp::Reverse(app.created_at));
+                sections.push(section(
+                    kind,
+                    "new_this_week".to_string(),
+                    "New This Week".to_string(),
+                    apps,
+                ));
+            }
+            HomeSectionKind::CategorySpotlights => {
+                let titles: HashMap<String, String> = get_app_categories()
+                    .into_iter()
+                    .map(|c| (c.id, c.title))
+                    .collect();
+                for category in spotlight_categories(&by_installs, layout) {
+                    let apps = by_installs
+                        .iter()
+                        .copied()
+                        .filter(|app| app.category == category)
+                        .collect();
+                    let title = titles.get(&category).cloned().unwrap_or_else(|| category.clone());
+                    sections.push(section(kind, format!("category:{}", category), title, apps));
+                }
+            }
+        }
+    }
+
+    sections.retain(|s| !s.apps.is_empty());
+    sections
+}
+
+/// The layout's spotlight categories, or the most installed ones when none are set
+fn spotlight_categories(apps: &[&AppSummary], layout: &AppsHomeLayout) -> Vec<String> {
+    if !layout.spotlight_categories.is_empty() {
+        return layout.spotlight_categories.clone();
+    }
+    let mut installs: HashMap<&str, i64> = HashMap::new();
+    for app in apps {
+        *installs.entry(app.category.as_str()).or_default() += app.installs as i64;
+    }
+    let mut categories: Vec<(&str, i64)> = installs.into_iter().collect();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    categories
+        .into_iter()
+        .take(DEFAULT_SPOTLIGHT_CATEGORIES)
+        .map(|(category, _)| category.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(id: &str, category: &str, installs: i32, age_days: Option<i64>, now: DateTime<Utc>) -> AppSummary {
+        AppSummary {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            image: String::new(),
+            category: category.to_string(),
+            author: String::new(),
+            capabilities: vec!["chat".to_string()],
+            approved: true,
+            private: false,
+            installs,
+            rating_avg: None,
+            rating_count: 0,
+            is_paid: false,
+            price: None,
+            enabled: false,
+            has_auth_steps: false,
+            created_at: age_days.map(|d| now - Duration::days(d)),
+            translations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_build_home_sections() {
+        let now = Utc::now();
+        let mut apps = vec![
+            app("a", "productivity", 50, Some(30), now),
+            app("b", "social", 900, Some(2), now),
+            app("c", "productivity", 10, Some(1), now),
+            app("d", "health", 5, None, now),
+        ];
+        apps[0].enabled = true;
+
+        fn ids(section: &AppsHomeSection) -> Vec<&str> {
+            section.apps.iter().map(|a| a.id.as_str()).collect()
+        }
+
+        let sections = build_home_sections(&apps, &AppsHomeLayout::default(), 10, now);
+        let section_ids: Vec<&str> = sections.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            section_ids,
+            ["your_apps", "popular", "new_this_week", "category:social", "category:productivity", "category:health"]
+        );
+        assert_eq!(ids(&sections[0]), ["a"]);
+        assert_eq!(ids(&sections[1]), ["b", "a", "c", "d"]);
+        assert_eq!(ids(&sections[2]), ["c", "b"]);
+        assert_eq!(ids(&sections[4]), ["a", "c"]);
+
+        // Admin order, hidden sections, chosen spotlights and the per-section limit
+        let layout = AppsHomeLayout {
+            sections: vec![HomeSectionKind::CategorySpotlights, HomeSectionKind::Popular],
+            spotlight_categories: vec!["health".to_string(), "education".to_string()],
+        };
+        let sections = build_home_sections(&apps, &layout, 1, now);
+        let section_ids: Vec<&str> = sections.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(section_ids, ["category:health", "popular"]);
+        assert_eq!(ids(&sections[1]), ["b"]);
+    }
+}
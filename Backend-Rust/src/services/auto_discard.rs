@@ -0,0 +1,143 @@
+// Auto-discard - Flags accidental recordings before they are processed
+// Cheap transcript heuristics first; borderline cases get a time-boxed LLM triviality check.
+
+use std::time::Duration;
+
+use crate::llm::LlmClient;
+use crate::models::{AutoDiscardLevel, DiscardReason, TranscriptSegment};
+
+/// Give up on the triviality check after this long and keep the conversation
+const TRIVIALITY_CHECK_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Limits for one aggressiveness level
+struct Thresholds {
+    min_segments: usize,
+    min_speech_seconds: f64,
+    min_words: usize,
+    /// Transcripts below this many words get the LLM triviality check
+    check_content_below_words: usize,
+}
+
+fn thresholds(level: AutoDiscardLevel) -> Option<Thresholds> {
+    match level {
+        AutoDiscardLevel::Off => None,
+        AutoDiscardLevel::Conservative => Some(Thresholds {
+            min_segments: 2,
+            min_speech_seconds: 5.0,
+            min_words: 5,
+            check_content_below_words: 30,
+        }),
+        AutoDiscardLevel::Aggressive => Some(Thresholds {
+            min_segments: 3,
+            min_speech_seconds: 15.0,
+            min_words: 15,
+            check_content_below_words: 80,
+        }),
+    }
+}
+
+/// Outcome of the transcript heuristics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Keep,
+    Discard(DiscardReason),
+    /// Short enough that the content decides
+    CheckContent,
+}
+
+/// Total time covered by speech, in seconds
+fn speech_seconds(segments: &[TranscriptSegment]) -> f64 {
+    segments.iter().map(|s| (s.end - s.start).max(0.0)).sum()
+}
+
+/// Judge a transcript by segment count, speech duration and word count
+pub fn evaluate(segments: &[TranscriptSegment], level: AutoDiscardLevel) -> Verdict {
+    let Some(limits) = thresholds(level) else {
+        return Verdict::Keep;
+    };
+    let words: usize = segments.iter().map(|s| s.text.split_whitespace().count()).sum();
+
+    if segments.len() < limits.min_segments && words < limits.check_content_below_words {
+        return Verdict::Discard(DiscardReason::TooFewSegments);
+    }
+    if speech_seconds(segments) < limits.min_speech_seconds || words < limits.min_words {
+        return Verdict::Discard(DiscardReason::TooLittleSpeech);
+    }
+    if words < limits.check_content_below_words {
+        return Verdict::CheckContent;
+    }
+    Verdict::Keep
+}
+
+/// Reason to discard the conversation, if any. Without an LLM client, or when the
+/// check fails or times out, borderline conversations are kept.
+pub async fn discard_reason(
+    llm: Option<&LlmClient>,
+    segments: &[TranscriptSegment],
+    level: AutoDiscardLevel,
+) -> Option<DiscardReason> {
+    match evaluate(segments, level) {
+        Verdict::Keep => None,
+        Verdict::Discard(reason) => Some(reason),
+        Verdict::CheckContent => {
+            let llm = llm?;
+            let transcript = TranscriptSegment::to_transcript_text(segments);
+            match tokio::time::timeout(TRIVIALITY_CHECK_TIMEOUT, llm.is_trivial_conversation(&transcript)).await {
+                Ok(Ok(true)) => Some(DiscardReason::TrivialContent),
+                Ok(Ok(false)) => None,
+                Ok(Err(e)) => {
+                    tracing::warn!("Triviality check failed, keeping conversation: {}", e);
+                    None
+                }
+                Err(_) => {
+                    tracing::warn!("Triviality check timed out, keeping conversation");
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start: f64, end: f64) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            speaker: "SPEAKER_00".to_string(),
+            speaker_id: 0,
+            is_user: true,
+            person_id: None,
+            start,
+            end,
+            language: None,
+            words: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_by_level() {
+        let blip = vec![segment("hmm okay", 0.0, 1.0)];
+        assert_eq!(evaluate(&blip, AutoDiscardLevel::Off), Verdict::Keep);
+        assert_eq!(
+            evaluate(&blip, AutoDiscardLevel::Conservative),
+            Verdict::Discard(DiscardReason::TooFewSegments)
+        );
+
+        let short = vec![
+            segment("remind me to call the dentist tomorrow", 0.0, 4.0),
+            segment("about moving the appointment to friday", 4.0, 8.0),
+        ];
+        assert_eq!(evaluate(&short, AutoDiscardLevel::Conservative), Verdict::CheckContent);
+        assert_eq!(
+            evaluate(&short, AutoDiscardLevel::Aggressive),
+            Verdict::Discard(DiscardReason::TooFewSegments)
+        );
+
+        let long: Vec<_> = (0..10)
+            .map(|i| segment("we agreed to ship the new onboarding flow next week", i as f64 * 5.0, i as f64 * 5.0 + 5.0))
+            .collect();
+        assert_eq!(evaluate(&long, AutoDiscardLevel::Aggressive), Verdict::Keep);
+    }
+}
@@ -0,0 +1,99 @@
+// Badges - Menu bar counts read in one pass and cached briefly
+// Unread advice and unreviewed memories are aggregation counts; due and overdue action
+// items are split from one query in the user's timezone.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::models::BadgeCounts;
+use crate::services::firestore::{ADVICE_SUBCOLLECTION, MEMORIES_SUBCOLLECTION};
+use crate::AppState;
+
+/// Open action items with a due date read per request
+const MAX_DUE_ACTION_ITEMS: usize = 1000;
+
+/// Start of the day after `now` in `tz`
+pub fn end_of_today(now: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+    let tomorrow = now.with_timezone(&tz).date_naive() + Duration::days(1);
+    let midnight = tomorrow.and_hms_opt(0, 0, 0).unwrap_or_default();
+    // Some zones skip midnight on DST changes; the hour after always exists
+    [midnight, midnight + Duration::hours(1)]
+        .iter()
+        .find_map(|t| tz.from_local_datetime(t).earliest())
+        .map_or(now + Duration::days(1), |t| t.with_timezone(&Utc))
+}
+
+/// (due later today, overdue) among due dates that are all before the end of today
+pub fn split_due(due_dates: &[DateTime<Utc>], now: DateTime<Utc>) -> (u32, u32) {
+    let overdue = due_dates.iter().filter(|d| **d < now).count() as u32;
+    (due_dates.len() as u32 - overdue, overdue)
+}
+
+/// The user's badge counts, from the cache when fresh
+pub async fn get_badges(
+    state: &AppState,
+    uid: &str,
+    timezone: Option<&str>,
+) -> Result<BadgeCounts, Box<dyn std::error::Error + Send + Sync>> {
+    let cache_key = timezone.unwrap_or("profile");
+    if let Some(redis) = &state.redis {
+        match redis.get_badges(uid, cache_key).await {
+            Ok(Some(badges)) => return Ok(badges),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read cached badges: {}", e),
+        }
+    }
+
+    let timezone = match timezone {
+        Some(tz) => Some(tz.to_string()),
+        None => state.firestore.get_user_profile(uid).await.ok().and_then(|p| p.time_zone),
+    };
+    let tz: Tz = timezone.and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC);
+    let now = Utc::now();
+
+    let advice = state
+        .firestore
+        .count_user_documents_where(uid, ADVICE_SUBCOLLECTION, &[("is_read", false), ("is_dismissed", false)]);
+    let memories = state
+        .firestore
+        .count_user_documents_where(uid, MEMORIES_SUBCOLLECTION, &[("reviewed", false)]);
+    let due = state
+        .firestore
+        .get_open_action_item_due_dates(uid, end_of_today(now, tz), MAX_DUE_ACTION_ITEMS);
+    let (advice, memories, due) = tokio::join!(advice, memories, due);
+    let (action_items_due_today, action_items_overdue) = split_due(&due?, now);
+
+    let badges = BadgeCounts {
+        unread_advice: advice?.max(0) as u32,
+        unreviewed_memories: memories?.max(0) as u32,
+        action_items_due_today,
+        action_items_overdue,
+    };
+    if let Some(redis) = &state.redis {
+        if let Err(e) = redis.store_badges(uid, cache_key, &badges).await {
+            tracing::warn!("Failed to cache badges: {}", e);
+        }
+    }
+    Ok(badges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_of_today_and_split() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 22, 30, 0).unwrap();
+        assert_eq!(end_of_today(now, Tz::UTC), Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 0).unwrap());
+        // 15:30 in Los Angeles (UTC-7): the day ends at 07:00 UTC
+        let la: Tz = "America/Los_Angeles".parse().unwrap();
+        assert_eq!(end_of_today(now, la), Utc.with_ymd_and_hms(2026, 3, 11, 7, 0, 0).unwrap());
+        // Already the next day in Tokyo
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+        assert_eq!(end_of_today(now, tokyo), Utc.with_ymd_and_hms(2026, 3, 11, 15, 0, 0).unwrap());
+
+        let due = [now - Duration::days(3), now - Duration::minutes(5), now + Duration::hours(1)];
+        assert_eq!(split_due(&due, now), (1, 2));
+        assert_eq!(split_due(&[], now), (0, 0));
+    }
+}
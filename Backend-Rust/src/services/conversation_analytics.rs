@@ -0,0 +1,198 @@
+// Conversation analytics - Talk-time, monologue and interruption metrics from transcript segments
+// Computed during processing and stored on the conversation as `analytics`
+
+use chrono::Utc;
+use std::collections::HashMap;
+
+use crate::models::{ConversationAnalytics, SpeakerAnalytics, TranscriptSegment};
+use crate::services::conversation_export::SpeakerNames;
+
+/// Same-speaker segments separated by less than this are one continuous run of speech
+const MONOLOGUE_GAP_SECONDS: f64 = 2.0;
+/// Overlap needed before a speaker change counts as an interruption (absorbs timestamp jitter)
+const INTERRUPTION_OVERLAP_SECONDS: f64 = 0.3;
+
+/// Stable key for a segment's speaker: "user", the person_id, or "speaker_{n}"
+fn speaker_key(segment: &TranscriptSegment) -> String {
+    if segment.is_user {
+        "user".to_string()
+    } else if let Some(person_id) = &segment.person_id {
+        person_id.clone()
+    } else {
+        format!("speaker_{}", segment.speaker_id)
+    }
+}
+
+fn per_minute(words: i32, seconds: f64) -> f64 {
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    round2(words as f64 / (seconds / 60.0))
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Compute analytics from segments. Returns None when there is no timed speech.
+pub fn compute_analytics(segments: &[TranscriptSegment]) -> Option<ConversationAnalytics> {
+    let mut ordered: Vec<&TranscriptSegment> = segments
+        .iter()
+        .filter(|s| !s.text.trim().is_empty() && s.end > s.start)
+        .collect();
+    if ordered.is_empty() {
+        return None;
+    }
+    ordered.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut speakers: Vec<SpeakerAnalytics> = vec![];
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+
+    // Current run of speech: (speaker index, run start, last end)
+    let mut run: Option<(usize, f64, f64)> = None;
+    // Previous segment: (speaker index, end)
+    let mut previous: Option<(usize, f64)> = None;
+
+    for segment in ordered {
+        let key = speaker_key(segment);
+        let idx = *index_by_key.entry(key.clone()).or_insert_with(|| {
+            speakers.push(SpeakerAnalytics {
+                speaker: key,
+                name: None,
+                is_user: segment.is_user,
+                person_id: if segment.is_user { None } else { segment.person_id.clone() },
+                talk_time_seconds: 0.0,
+                talk_time_ratio: 0.0,
+                word_count: 0,
+                words_per_minute: 0.0,
+                longest_monologue_seconds: 0.0,
+                interruptions: 0,
+            });
+            speakers.len() - 1
+        });
+
+        let stats = &mut speakers[idx];
+        stats.talk_time_seconds += segment.end - segment.start;
+        stats.word_count += segment.text.split_whitespace().count() as i32;
+
+        if let Some((prev_idx, prev_end)) = previous {
+            if prev_idx != idx && segment.start < prev_end - INTERRUPTION_OVERLAP_SECONDS {
+                speakers[idx].interruptions += 1;
+            }
+        }
+
+        run = match run {
+            Some((run_idx, run_start, run_end))
+                if run_idx == idx && segment.start - run_end < MONOLOGUE_GAP_SECONDS =>
+            {
+                Some((idx, run_start, run_end.max(segment.end)))
+            }
+            Some((run_idx, run_start, run_end)) => {
+                let longest = &mut speakers[run_idx].longest_monologue_seconds;
+                *longest = longest.max(run_end - run_start);
+                Some((idx, segment.start, segment.end))
+            }
+            None => Some((idx, segment.start, segment.end)),
+        };
+
+        previous = Some((idx, segment.end));
+    }
+
+    if let Some((run_idx, run_start, run_end)) = run {
+        let longest = &mut speakers[run_idx].longest_monologue_seconds;
+        *longest = longest.max(run_end - run_start);
+    }
+
+    let total_talk_time: f64 = speakers.iter().map(|s| s.talk_time_seconds).sum();
+    let total_words: i32 = speakers.iter().map(|s| s.word_count).sum();
+    for stats in speakers.iter_mut() {
+        stats.talk_time_ratio = round2(stats.talk_time_seconds / total_talk_time);
+        stats.words_per_minute = per_minute(stats.word_count, stats.talk_time_seconds);
+        stats.talk_time_seconds = round2(stats.talk_time_seconds);
+        stats.longest_monologue_seconds = round2(stats.longest_monologue_seconds);
+    }
+    speakers.sort_by(|a, b| b.talk_time_seconds.total_cmp(&a.talk_time_seconds));
+
+    let longest = speakers
+        .iter()
+        .max_by(|a, b| a.longest_monologue_seconds.total_cmp(&b.longest_monologue_seconds));
+
+    Some(ConversationAnalytics {
+        total_talk_time_seconds: round2(total_talk_time),
+        words_per_minute: per_minute(total_words, total_talk_time),
+        interruptions: speakers.iter().map(|s| s.interruptions).sum(),
+        longest_monologue_seconds: longest.map_or(0.0, |s| s.longest_monologue_seconds),
+        longest_monologue_speaker: longest.map(|s| s.speaker.clone()),
+        speakers,
+        computed_at: Utc::now(),
+    })
+}
+
+/// Fill in display names for the response
+pub fn resolve_speaker_names(analytics: &mut ConversationAnalytics, names: &SpeakerNames) {
+    for stats in analytics.speakers.iter_mut() {
+        stats.name = Some(if stats.is_user {
+            names.user_name.clone()
+        } else if let Some(name) = stats.person_id.as_ref().and_then(|id| names.people.get(id)) {
+            name.clone()
+        } else {
+            match stats.speaker.strip_prefix("speaker_") {
+                Some(n) => format!("Speaker {}", n),
+                None => "Unknown speaker".to_string(),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, speaker_id: i32, is_user: bool, start: f64, end: f64) -> TranscriptSegment {
+        serde_json::from_value(serde_json::json!({
+            "text": text,
+            "speaker_id": speaker_id,
+            "is_user": is_user,
+            "start": start,
+            "end": end
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_talk_time_and_monologues() {
+        let segments = vec![
+            segment("one two three four five six", 0, true, 0.0, 6.0),
+            segment("seven eight nine", 0, true, 6.5, 9.0),
+            segment("ten eleven", 1, false, 10.0, 12.0),
+        ];
+        let analytics = compute_analytics(&segments).unwrap();
+
+        assert_eq!(analytics.total_talk_time_seconds, 10.5);
+        assert_eq!(analytics.interruptions, 0);
+        assert_eq!(analytics.longest_monologue_speaker.as_deref(), Some("user"));
+        assert_eq!(analytics.longest_monologue_seconds, 9.0);
+
+        let user = &analytics.speakers[0];
+        assert_eq!(user.speaker, "user");
+        assert_eq!(user.word_count, 9);
+        assert_eq!(user.talk_time_ratio, 0.81);
+        assert_eq!(user.words_per_minute, 63.53);
+    }
+
+    #[test]
+    fn test_overlapping_turn_counts_as_interruption() {
+        let segments = vec![
+            segment("I think we should", 0, true, 0.0, 5.0),
+            segment("no wait", 1, false, 3.0, 4.0),
+            segment("as I was saying", 0, true, 4.1, 7.0),
+            segment("sure", 1, false, 7.1, 8.0),
+        ];
+        let analytics = compute_analytics(&segments).unwrap();
+
+        assert_eq!(analytics.interruptions, 1);
+        let other = analytics.speakers.iter().find(|s| s.speaker == "speaker_1").unwrap();
+        assert_eq!(other.interruptions, 1);
+        assert!(compute_analytics(&[]).is_none());
+    }
+}
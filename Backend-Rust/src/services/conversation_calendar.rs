@@ -0,0 +1,106 @@
+// Conversation calendar - Per-day conversation counts and talk time for a month
+// One aggregation query per day (bounded concurrency), so no conversation bodies are read.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use futures::StreamExt;
+
+use crate::models::{ConversationCalendarDay, ConversationCalendarResponse};
+use crate::AppState;
+
+/// Day queries in flight at once
+const MAX_CONCURRENT_DAY_QUERIES: usize = 8;
+
+/// A calendar day and its [start, end) range in UTC
+type DayRange = (NaiveDate, DateTime<Utc>, DateTime<Utc>);
+
+/// Start of `date` in `tz`, as UTC
+fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    // Some zones skip midnight on DST changes; the hour after always exists
+    [midnight, midnight + Duration::hours(1)]
+        .iter()
+        .find_map(|t| tz.from_local_datetime(t).earliest())
+        .map_or_else(|| Utc.from_utc_datetime(&midnight), |t| t.with_timezone(&Utc))
+}
+
+/// Each day of the month with its range in `tz`; None for an invalid month
+pub fn month_days(year: i32, month: u32, tz: Tz) -> Option<Vec<DayRange>> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    Some(
+        first
+            .iter_days()
+            .take_while(|d| d.month() == month)
+            .map(|date| (date, start_of_day(date, tz), start_of_day(date + Duration::days(1), tz)))
+            .collect(),
+    )
+}
+
+/// Calendar for a month; days that haven't started yet are zero without a query.
+/// None for an invalid month.
+pub async fn get_calendar(
+    state: &AppState,
+    uid: &str,
+    year: i32,
+    month: u32,
+    timezone: Option<&str>,
+) -> Result<Option<ConversationCalendarResponse>, Box<dyn std::error::Error + Send + Sync>> {
+    let timezone = match timezone {
+        Some(tz) => Some(tz.to_string()),
+        None => state.firestore.get_user_profile(uid).await.ok().and_then(|p| p.time_zone),
+    };
+    let tz: Tz = timezone.and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC);
+    let Some(days) = month_days(year, month, tz) else {
+        return Ok(None);
+    };
+    let now = Utc::now();
+
+    let days: Vec<ConversationCalendarDay> = futures::stream::iter(days)
+        .map(|(date, start, end)| async move {
+            let (count, talk_seconds) = if start > now {
+                (0, 0.0)
+            } else {
+                state.firestore.get_conversation_totals_between(uid, start, end).await?
+            };
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(ConversationCalendarDay {
+                date,
+                count,
+                talk_minutes: (talk_seconds / 6.0).round() / 10.0,
+            })
+        })
+        .buffered(MAX_CONCURRENT_DAY_QUERIES)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()?;
+
+    let total_count = days.iter().map(|d| d.count).sum();
+    let total_talk_minutes = (days.iter().map(|d| d.talk_minutes).sum::<f64>() * 10.0).round() / 10.0;
+    Ok(Some(ConversationCalendarResponse {
+        year,
+        month,
+        timezone: tz.name().to_string(),
+        days,
+        total_count,
+        total_talk_minutes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_days() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let days = month_days(2026, 11, tz).unwrap();
+        assert_eq!(days.len(), 30);
+        assert_eq!(days[0].1.to_rfc3339(), "2026-11-01T04:00:00+00:00");
+        // DST ends on Nov 1: that day is 25 hours long
+        assert_eq!(days[0].2 - days[0].1, Duration::hours(25));
+        assert_eq!(days[29].2.to_rfc3339(), "2026-12-01T05:00:00+00:00");
+
+        assert_eq!(month_days(2028, 2, Tz::UTC).unwrap().len(), 29);
+        assert!(month_days(2026, 13, Tz::UTC).is_none());
+    }
+}
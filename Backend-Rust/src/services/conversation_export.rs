@@ -0,0 +1,471 @@
+// Conversation export - Render a single conversation as a shareable document
+// Formats: Markdown, plain text, PDF (minimal built-in writer, Helvetica only)
+
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::models::{Conversation, TranscriptSegment};
+use crate::services::FirestoreService;
+
+/// Silence between segments that starts a new transcript chapter
+const CHAPTER_GAP_SECS: f64 = 120.0;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[serde(alias = "md")]
+    Markdown,
+    Txt,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Txt => "text/plain; charset=utf-8",
+            ExportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Txt => "txt",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Speaker naming for the rendered transcript
+pub struct SpeakerNames {
+    pub user_name: String,
+    /// person_id -> name
+    pub people: HashMap<String, String>,
+}
+
+impl SpeakerNames {
+    /// Load the user's name, timezone and people. These only improve naming,
+    /// so failures fall back to "You" / UTC / generic speaker labels.
+    pub async fn load(firestore: &FirestoreService, uid: &str) -> (Self, Tz) {
+        let profile = firestore.get_user_profile(uid).await.ok();
+        let tz = profile
+            .as_ref()
+            .and_then(|p| p.time_zone.as_deref())
+            .and_then(|tz| tz.parse().ok())
+            .unwrap_or(Tz::UTC);
+        let people = firestore.get_people(uid).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to get people for speaker names: {}", e);
+            vec![]
+        });
+        let names = Self {
+            user_name: profile
+                .and_then(|p| p.name)
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| "You".to_string()),
+            people: people.into_iter().map(|p| (p.id, p.name)).collect(),
+        };
+        (names, tz)
+    }
+
+    pub fn name_for(&self, segment: &TranscriptSegment) -> String {
+        if segment.is_user {
+            return self.user_name.clone();
+        }
+        segment
+            .person_id
+            .as_ref()
+            .and_then(|id| self.people.get(id))
+            .cloned()
+            .unwrap_or_else(|| format!("Speaker {}", segment.speaker_id))
+    }
+}
+
+/// A run of transcript with no long pauses; consecutive segments from the
+/// same speaker are merged into one turn
+struct Chapter {
+    start: f64,
+    turns: Vec<(String, String)>,
+}
+
+fn chapters(segments: &[TranscriptSegment], names: &SpeakerNames) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = vec![];
+    let mut last_end: Option<f64> = None;
+
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let new_chapter = match last_end {
+            None => true,
+            Some(end) => segment.start - end > CHAPTER_GAP_SECS,
+        };
+        if new_chapter {
+            chapters.push(Chapter {
+                start: segment.start,
+                turns: vec![],
+            });
+        }
+        last_end = Some(segment.end.max(segment.start));
+
+        let speaker = names.name_for(segment);
+        let chapter = chapters.last_mut().expect("chapter pushed above");
+        match chapter.turns.last_mut() {
+            Some((last_speaker, last_text)) if *last_speaker == speaker => {
+                last_text.push(' ');
+                last_text.push_str(text);
+            }
+            _ => chapter.turns.push((speaker, text.to_string())),
+        }
+    }
+
+    chapters
+}
+
+/// Distinct speaker names in order of first appearance
+pub fn participants(segments: &[TranscriptSegment], names: &SpeakerNames) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    for segment in segments {
+        let name = names.name_for(segment);
+        if !out.contains(&name) {
+            out.push(name);
+        }
+    }
+    out
+}
+
+/// Transcript as "Name: text" turns, for prompts
+pub fn named_transcript(segments: &[TranscriptSegment], names: &SpeakerNames) -> String {
+    chapters(segments, names)
+        .iter()
+        .flat_map(|c| c.turns.iter())
+        .map(|(speaker, text)| format!("{}: {}", speaker, text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_offset(secs: f64) -> String {
+    let total = secs.max(0.0) as u64;
+    format!("{:02}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+}
+
+fn format_date(conversation: &Conversation, tz: Tz) -> String {
+    let start = conversation.started_at.with_timezone(&tz);
+    let end = conversation.finished_at.with_timezone(&tz);
+    format!(
+        "{} – {} ({})",
+        start.format("%A, %B %-d, %Y %H:%M"),
+        end.format("%H:%M"),
+        tz.name()
+    )
+}
+
+fn title(conversation: &Conversation) -> String {
+    let title = conversation.structured.title.trim();
+    if title.is_empty() {
+        "Untitled conversation".to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+/// Render a conversation as Markdown
+pub fn render_markdown(conversation: &Conversation, names: &SpeakerNames, tz: Tz) -> String {
+    let s = &conversation.structured;
+    let mut out = String::new();
+
+    let emoji = s.emoji.trim();
+    if emoji.is_empty() {
+        out.push_str(&format!("# {}\n\n", title(conversation)));
+    } else {
+        out.push_str(&format!("# {} {}\n\n", emoji, title(conversation)));
+    }
+    out.push_str(&format!("_{}_\n\n", format_date(conversation, tz)));
+
+    if !s.overview.trim().is_empty() {
+        out.push_str("## Overview\n\n");
+        out.push_str(s.overview.trim());
+        out.push_str("\n\n");
+    }
+
+    if !s.action_items.is_empty() {
+        out.push_str("## Action Items\n\n");
+        for item in &s.action_items {
+            let check = if item.completed { "x" } else { " " };
+            out.push_str(&format!("- [{}] {}", check, item.description.trim()));
+            if let Some(due) = item.due_at {
+                out.push_str(&format!(" (due {})", due.with_timezone(&tz).format("%Y-%m-%d")));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    let chapters = chapters(&conversation.transcript_segments, names);
+    if !chapters.is_empty() {
+        out.push_str("## Transcript\n\n");
+        for chapter in &chapters {
+            if chapters.len() > 1 {
+                out.push_str(&format!("### {}\n\n", format_offset(chapter.start)));
+            }
+            for (speaker, text) in &chapter.turns {
+                out.push_str(&format!("**{}:** {}\n\n", speaker, text));
+            }
+        }
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+/// Render a conversation as plain text
+pub fn render_text(conversation: &Conversation, names: &SpeakerNames, tz: Tz) -> String {
+    let s = &conversation.structured;
+    let title = title(conversation);
+    let mut out = String::new();
+
+    out.push_str(&title);
+    out.push('\n');
+    out.push_str(&"=".repeat(title.chars().count()));
+    out.push_str(&format!("\n{}\n\n", format_date(conversation, tz)));
+
+    if !s.overview.trim().is_empty() {
+        out.push_str("OVERVIEW\n\n");
+        out.push_str(s.overview.trim());
+        out.push_str("\n\n");
+    }
+
+    if !s.action_items.is_empty() {
+        out.push_str("ACTION ITEMS\n\n");
+        for item in &s.action_items {
+            let check = if item.completed { "x" } else { " " };
+            out.push_str(&format!("[{}] {}", check, item.description.trim()));
+            if let Some(due) = item.due_at {
+                out.push_str(&format!(" (due {})", due.with_timezone(&tz).format("%Y-%m-%d")));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    let chapters = chapters(&conversation.transcript_segments, names);
+    if !chapters.is_empty() {
+        out.push_str("TRANSCRIPT\n\n");
+        for chapter in &chapters {
+            if chapters.len() > 1 {
+                out.push_str(&format!("[{}]\n\n", format_offset(chapter.start)));
+            }
+            for (speaker, text) in &chapter.turns {
+                out.push_str(&format!("{}: {}\n\n", speaker, text));
+            }
+        }
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+/// Render a conversation as a PDF (text layout of the plain-text export)
+pub fn render_pdf(conversation: &Conversation, names: &SpeakerNames, tz: Tz) -> Vec<u8> {
+    text_to_pdf(&render_text(conversation, names, tz))
+}
+
+// =========================================================================
+// Minimal PDF writer
+// =========================================================================
+
+const PAGE_WIDTH: f32 = 612.0; // US Letter
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 54.0;
+const FONT_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 13.0;
+/// Conservative wrap width for 10pt Helvetica on a 504pt line
+const WRAP_CHARS: usize = 95;
+
+/// Wrap text into lines of at most `width` characters, breaking on spaces
+fn wrap_lines(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        if paragraph.trim().is_empty() {
+            lines.push(String::new());
+            continue;
+        }
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            // Hard-split words longer than a line
+            while word.chars().count() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let head: String = word.chars().take(width).collect();
+                word = word.chars().skip(width).collect();
+                lines.push(head);
+            }
+            let needed = if line.is_empty() { 0 } else { 1 } + word.chars().count();
+            if line.chars().count() + needed > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Encode a line as a PDF string literal (WinAnsi; unsupported chars become '?')
+fn pdf_string(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 2);
+    out.push('(');
+    for c in line.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '–' | '—' => out.push('-'),
+            '‘' | '’' => out.push('\''),
+            '“' | '”' => out.push('"'),
+            ' '..='~' => out.push(c),
+            c if (c as u32) >= 0xA0 && (c as u32) <= 0xFF => {
+                out.push_str(&format!("\\{:03o}", c as u32));
+            }
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Lay out text onto as many pages as needed and serialize a PDF document
+fn text_to_pdf(text: &str) -> Vec<u8> {
+    let lines = wrap_lines(text, WRAP_CHARS);
+    let lines_per_page = ((PAGE_HEIGHT - 2.0 * MARGIN) / LINE_HEIGHT) as usize;
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(lines_per_page).collect()
+    };
+
+    // Object layout: 1 catalog, 2 pages, 3 font, then (page, content) pairs
+    let mut objects: Vec<String> = vec![];
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 4 + i * 2))
+        .collect();
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    ));
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+
+    for (i, page_lines) in pages.iter().enumerate() {
+        let mut stream = format!(
+            "BT /F1 {} Tf {} TL {} {} Td\n",
+            FONT_SIZE,
+            LINE_HEIGHT,
+            MARGIN,
+            PAGE_HEIGHT - MARGIN - FONT_SIZE
+        );
+        for line in page_lines.iter() {
+            stream.push_str(&pdf_string(line));
+            stream.push_str(" Tj T*\n");
+        }
+        stream.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            5 + i * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            stream.len(),
+            stream
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, speaker_id: i32, is_user: bool, start: f64, end: f64) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            speaker: format!("SPEAKER_{:02}", speaker_id),
+            speaker_id,
+            is_user,
+            person_id: None,
+            start,
+            end,
+            language: None,
+            words: None,
+        }
+    }
+
+    #[test]
+    fn test_chapters_split_on_long_pauses_and_merge_turns() {
+        let names = SpeakerNames {
+            user_name: "Ana".to_string(),
+            people: HashMap::new(),
+        };
+        let segments = vec![
+            segment("Hi.", 0, true, 0.0, 1.0),
+            segment("How are you?", 0, true, 1.0, 2.0),
+            segment("Good.", 1, false, 2.5, 3.0),
+            segment("Back after lunch.", 1, false, 400.0, 402.0),
+        ];
+        let chapters = chapters(&segments, &names);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(
+            chapters[0].turns,
+            vec![
+                ("Ana".to_string(), "Hi. How are you?".to_string()),
+                ("Speaker 1".to_string(), "Good.".to_string()),
+            ]
+        );
+        assert_eq!(format_offset(chapters[1].start), "00:06:40");
+    }
+
+    #[test]
+    fn test_pdf_xref_offsets_point_at_objects() {
+        let pdf = text_to_pdf("Title (draft)\n\nSome text – with a dash");
+        let s = String::from_utf8(pdf).unwrap();
+        assert!(s.starts_with("%PDF-1.4\n"));
+        assert!(s.contains("(Title \\(draft\\)) Tj"));
+
+        let xref_at: usize = s.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(s[xref_at..].starts_with("xref"));
+        let first_obj: usize = s[xref_at..].lines().nth(3).unwrap()[..10].parse().unwrap();
+        assert!(s[first_obj..].starts_with("1 0 obj"));
+    }
+}
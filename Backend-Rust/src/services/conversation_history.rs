@@ -0,0 +1,144 @@
+// Conversation history - Records overwritten conversation fields and restores them
+// Each title, summary or transcript overwrite stores the value it replaced in the
+// conversation's edits subcollection; restoring writes that value back as a new edit.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use crate::models::{Conversation, ConversationEdit, ConversationEditField, Structured, TranscriptSegment};
+use crate::services::FirestoreService;
+
+/// A new edit of `field` that replaced `previous_value`
+pub fn new_edit(field: ConversationEditField, previous_value: Value, edited_by: &str, source: &str) -> ConversationEdit {
+    ConversationEdit {
+        id: uuid::Uuid::new_v4().to_string(),
+        field,
+        previous_value,
+        edited_by: edited_by.to_string(),
+        source: source.to_string(),
+        restored_from: None,
+        created_at: Utc::now(),
+    }
+}
+
+/// The summary fields regenerated together, plus the model that generated them
+pub fn summary_snapshot(structured: &Structured, model: Option<&str>) -> Value {
+    json!({
+        "title": structured.title,
+        "overview": structured.overview,
+        "emoji": structured.emoji,
+        "category": structured.category,
+        "model": model,
+    })
+}
+
+/// `current` with a summary snapshot's fields put back (action items and events are
+/// kept), and the model to record; None if the snapshot isn't a summary
+pub fn restored_summary(current: &Structured, snapshot: &Value) -> Option<(Structured, String)> {
+    let mut structured = current.clone();
+    structured.title = snapshot.get("title")?.as_str()?.to_string();
+    structured.overview = snapshot.get("overview")?.as_str()?.to_string();
+    if let Some(emoji) = snapshot.get("emoji").and_then(|e| e.as_str()) {
+        structured.emoji = emoji.to_string();
+    }
+    if let Some(category) = snapshot.get("category").and_then(|c| serde_json::from_value(c.clone()).ok()) {
+        structured.category = category;
+    }
+    let model = snapshot.get("model").and_then(|m| m.as_str()).unwrap_or("restored");
+    Some((structured, model.to_string()))
+}
+
+/// The conversation's current value of `field`, in the shape edits store it
+pub fn current_value(conversation: &Conversation, field: ConversationEditField) -> Value {
+    match field {
+        ConversationEditField::Title => json!(conversation.structured.title),
+        ConversationEditField::Summary => summary_snapshot(
+            &conversation.structured,
+            conversation.generated_by.get("summary").map(|m| m.as_str()),
+        ),
+        ConversationEditField::Transcript => json!(conversation.transcript_segments),
+    }
+}
+
+/// Store an edit; a failure is logged rather than failing the change it describes
+pub async fn record_edit(firestore: &FirestoreService, uid: &str, conversation_id: &str, edit: &ConversationEdit) {
+    if let Err(e) = firestore.add_conversation_edit(uid, conversation_id, edit).await {
+        tracing::error!(
+            "Failed to record {} edit on conversation {} for user {}: {}",
+            edit.field.as_str(),
+            conversation_id,
+            uid,
+            e
+        );
+    }
+}
+
+/// Write an edit's previous value back onto the conversation, recording the value it
+/// replaces as a "restore" edit
+pub async fn restore_edit(
+    firestore: &FirestoreService,
+    uid: &str,
+    conversation: &Conversation,
+    edit: &ConversationEdit,
+) -> Result<ConversationEdit, Box<dyn std::error::Error + Send + Sync>> {
+    match edit.field {
+        ConversationEditField::Title => {
+            let title = edit.previous_value.as_str().ok_or("Edit has no previous title")?;
+            firestore.update_conversation_title(uid, &conversation.id, title).await?;
+        }
+        ConversationEditField::Summary => {
+            let (structured, model) = restored_summary(&conversation.structured, &edit.previous_value)
+                .ok_or("Edit has no previous summary")?;
+            firestore
+                .update_conversation_summary(uid, &conversation.id, &structured, &model)
+                .await?;
+        }
+        ConversationEditField::Transcript => {
+            let segments: Vec<TranscriptSegment> = serde_json::from_value(edit.previous_value.clone())?;
+            firestore
+                .update_conversation_transcript(uid, &conversation.id, &segments)
+                .await?;
+        }
+    }
+
+    let mut restore = new_edit(edit.field, current_value(conversation, edit.field), uid, "restore");
+    restore.restored_from = Some(edit.id.clone());
+    record_edit(firestore, uid, &conversation.id, &restore).await;
+    Ok(restore)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Category;
+
+    #[test]
+    fn test_summary_snapshot_round_trip() {
+        let old: Structured = serde_json::from_value(json!({
+            "title": "Standup",
+            "overview": "Talked about the release",
+            "emoji": "🚀",
+            "category": "work",
+            "action_items": [{"description": "Tag the build"}]
+        }))
+        .unwrap();
+        let snapshot = summary_snapshot(&old, Some("gemini-2.5-flash"));
+
+        let mut current = old.clone();
+        current.title = "Regenerated".to_string();
+        current.overview = "Something else".to_string();
+        current.category = Category::Other;
+        current.action_items.clear();
+
+        let (restored, model) = restored_summary(&current, &snapshot).unwrap();
+        assert_eq!(restored.title, "Standup");
+        assert_eq!(restored.overview, "Talked about the release");
+        assert_eq!(restored.emoji, "🚀");
+        assert_eq!(restored.category, Category::Work);
+        // Action items aren't part of the summary, so the current ones stay
+        assert!(restored.action_items.is_empty());
+        assert_eq!(model, "gemini-2.5-flash");
+
+        assert!(restored_summary(&current, &json!("Standup")).is_none());
+    }
+}
@@ -0,0 +1,128 @@
+// Conversation processing - Saving what processing produced
+// The conversation itself, then its extractions: action items become staged tasks (ranked
+// and promoted later) and memories are saved pending review. Goes through Storage, so a
+// conversation processed with STORAGE=local lands entirely in the local store.
+
+use crate::models::{ActionItem, Conversation, Memory};
+use crate::services::firestore::FirestoreError;
+use crate::services::storage::Storage;
+
+/// Save a processed conversation and its extractions. Discarded conversations keep
+/// none, and memories are skipped for conversations excluded from learning.
+/// Fails only if the conversation can't be saved; extraction failures are logged.
+pub async fn save_processed_conversation(
+    storage: &dyn Storage,
+    uid: &str,
+    conversation: &Conversation,
+    action_items: &[ActionItem],
+    memories: &[Memory],
+) -> Result<(), FirestoreError> {
+    storage.save_conversation(uid, conversation).await?;
+    if conversation.discarded {
+        return Ok(());
+    }
+
+    if !action_items.is_empty() {
+        let source = format!("transcription:{:?}", conversation.source).to_lowercase();
+        for item in action_items {
+            if let Err(e) = storage
+                .create_staged_task(
+                    uid,
+                    &item.description,
+                    item.due_at,
+                    Some(&source),
+                    item.priority.as_deref(),
+                    item.due_metadata().as_deref(),
+                    None, // category
+                    None, // relevance_score - will be ranked by prioritization service
+                )
+                .await
+            {
+                tracing::error!("Failed to save staged task: {}", e);
+            }
+        }
+        tracing::info!(
+            "Saved {} action items as staged tasks for conversation {}",
+            action_items.len(),
+            conversation.id
+        );
+    }
+
+    if !memories.is_empty() && !conversation.memory_extraction_disabled {
+        if let Err(e) = storage.save_memories(uid, &conversation.id, memories).await {
+            tracing::error!("Failed to save memories: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MemoryCategory;
+    use crate::services::local_storage::LocalStorage;
+    use chrono::Utc;
+
+    fn conversation(id: &str) -> Conversation {
+        let now = Utc::now();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "created_at": now,
+            "started_at": now,
+            "finished_at": now,
+            "status": "completed",
+            "structured": {"title": "Planning", "overview": "Roadmap review"}
+        }))
+        .unwrap()
+    }
+
+    fn memory(content: &str) -> Memory {
+        Memory {
+            content: content.to_string(),
+            category: MemoryCategory::System,
+            tags: vec![],
+        }
+    }
+
+    fn action_item(description: &str) -> ActionItem {
+        serde_json::from_value(serde_json::json!({"description": description})).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_processed_conversation_lands_in_local_storage() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let conversation = conversation("c1");
+        let memories = [memory("Prefers async standups"), memory("Works on the roadmap")];
+        save_processed_conversation(&storage, "u1", &conversation, &[action_item("Send the roadmap")], &memories)
+            .await
+            .unwrap();
+
+        assert!(storage.get_conversation("u1", "c1").await.unwrap().is_some());
+        let saved = storage.get_memories("u1", 10).await.unwrap();
+        assert_eq!(saved.len(), 2);
+        assert!(saved.iter().all(|m| m.conversation_id.as_deref() == Some("c1") && m.user_review.is_none()));
+        let staged = storage.get_staged_tasks("u1", 10, 0).await.unwrap();
+        assert_eq!(staged.len(), 1);
+        assert_eq!(staged[0].description, "Send the roadmap");
+        assert_eq!(staged[0].source.as_deref(), Some("transcription:desktop"));
+
+        assert_eq!(storage.delete_memories_for_conversation("u1", "c1").await.unwrap(), 2);
+        assert!(storage.get_memories("u1", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_excluded_and_discarded_conversations_keep_no_extractions() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let mut excluded = conversation("excluded");
+        excluded.memory_extraction_disabled = true;
+        let mut discarded = conversation("discarded");
+        discarded.discarded = true;
+        for conversation in [&excluded, &discarded] {
+            save_processed_conversation(&storage, "u1", conversation, &[], &[memory("Likes tea")])
+                .await
+                .unwrap();
+        }
+        assert!(storage.get_memories("u1", 10).await.unwrap().is_empty());
+        assert!(storage.get_conversation("u1", "discarded").await.unwrap().is_some());
+    }
+}
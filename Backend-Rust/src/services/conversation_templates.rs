@@ -0,0 +1,137 @@
+// Conversation templates - Picks the template a new conversation is summarized with
+// An explicit template wins, then one assigned to the conversation's folder, then a title pattern.
+
+use crate::models::{
+    ConversationTemplate, SaveConversationTemplateRequest, MAX_TEMPLATE_INSTRUCTIONS_CHARS,
+    MAX_TEMPLATE_LABEL_CHARS, MAX_TEMPLATE_MATCHERS, MAX_TEMPLATE_SECTIONS,
+};
+
+/// Whether a title matches a pattern: case-insensitive, `*` matches any run of
+/// characters, and a pattern without `*` matches anywhere in the title
+pub fn title_matches(pattern: &str, title: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let title = title.trim().to_lowercase();
+    if pattern.is_empty() || title.is_empty() {
+        return false;
+    }
+    if !pattern.contains('*') {
+        return title.contains(&pattern);
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !title.starts_with(first) || !title[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &title[first.len()..title.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The template to apply, if any
+pub fn resolve<'a>(
+    templates: &'a [ConversationTemplate],
+    template_id: Option<&str>,
+    folder_id: Option<&str>,
+    title_hint: Option<&str>,
+) -> Option<&'a ConversationTemplate> {
+    // An unknown ID (e.g. a template deleted since) falls back to matching
+    if let Some(template) = template_id.and_then(|id| templates.iter().find(|t| t.id == id)) {
+        return Some(template);
+    }
+    if let Some(folder_id) = folder_id {
+        if let Some(template) = templates.iter().find(|t| t.folder_ids.iter().any(|f| f == folder_id)) {
+            return Some(template);
+        }
+    }
+    let title = title_hint?;
+    templates
+        .iter()
+        .find(|t| t.title_patterns.iter().any(|p| title_matches(p, title)))
+}
+
+fn clean_labels(values: Vec<String>, max: usize, what: &str) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for value in values {
+        let value = value.trim().to_string();
+        if value.chars().count() > MAX_TEMPLATE_LABEL_CHARS {
+            return Err(format!("Each {} is limited to {} characters", what, MAX_TEMPLATE_LABEL_CHARS));
+        }
+        if !value.is_empty() && !cleaned.contains(&value) {
+            cleaned.push(value);
+        }
+    }
+    if cleaned.len() > max {
+        return Err(format!("At most {} {}s are allowed", max, what));
+    }
+    Ok(cleaned)
+}
+
+/// Trim and validate a save request
+pub fn clean_request(
+    request: SaveConversationTemplateRequest,
+) -> Result<SaveConversationTemplateRequest, String> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_TEMPLATE_LABEL_CHARS {
+        return Err(format!("Template name must be 1-{} characters", MAX_TEMPLATE_LABEL_CHARS));
+    }
+    let summary_instructions = request.summary_instructions.trim().to_string();
+    let action_item_conventions = request.action_item_conventions.trim().to_string();
+    if summary_instructions.chars().count() > MAX_TEMPLATE_INSTRUCTIONS_CHARS
+        || action_item_conventions.chars().count() > MAX_TEMPLATE_INSTRUCTIONS_CHARS
+    {
+        return Err(format!(
+            "Template instructions are limited to {} characters",
+            MAX_TEMPLATE_INSTRUCTIONS_CHARS
+        ));
+    }
+
+    Ok(SaveConversationTemplateRequest {
+        name,
+        summary_sections: clean_labels(request.summary_sections, MAX_TEMPLATE_SECTIONS, "summary section")?,
+        summary_instructions,
+        action_item_conventions,
+        folder_ids: clean_labels(request.folder_ids, MAX_TEMPLATE_MATCHERS, "folder")?,
+        title_patterns: clean_labels(request.title_patterns, MAX_TEMPLATE_MATCHERS, "title pattern")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn template(id: &str, folder: &str, pattern: &str) -> ConversationTemplate {
+        ConversationTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            summary_sections: vec![],
+            summary_instructions: String::new(),
+            action_item_conventions: String::new(),
+            folder_ids: vec![folder.to_string()],
+            title_patterns: vec![pattern.to_string()],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_and_title_patterns() {
+        assert!(title_matches("1:1", "Weekly 1:1 with Dana"));
+        assert!(title_matches("weekly * with *", "Weekly sync with Dana"));
+        assert!(!title_matches("weekly * with", "Weekly sync with Dana"));
+        assert!(!title_matches("standup", "Quarterly planning"));
+
+        let templates = vec![template("one-on-one", "f-1on1", "1:1"), template("standup", "f-standup", "standup")];
+        let id = |t: Option<&ConversationTemplate>| t.map(|t| t.id.clone());
+        assert_eq!(id(resolve(&templates, Some("standup"), Some("f-1on1"), None)), Some("standup".to_string()));
+        assert_eq!(id(resolve(&templates, None, Some("f-1on1"), Some("Daily standup"))), Some("one-on-one".to_string()));
+        assert_eq!(id(resolve(&templates, None, Some("other"), Some("Daily Standup"))), Some("standup".to_string()));
+        assert_eq!(id(resolve(&templates, Some("deleted"), None, Some("Lunch"))), None);
+    }
+}
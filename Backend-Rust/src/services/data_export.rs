@@ -0,0 +1,367 @@
+// Data export - Builds archives of a user's data and serves them for resumable download
+// An export writes conversations, memories and action items as gzipped JSON lines to a temp
+// file, uploads it to EXPORTS_BUCKET and records it in data_exports. Downloads go through
+// signed links (no Authorization header, so download managers can resume them) and support
+// Range requests. Archives are deleted EXPORT_RETENTION_DAYS after they were requested.
+// An export still running after STALE_EXPORT_HOURS is marked failed when the user starts
+// another, so a worker that died mid-export doesn't block exports for good.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Config;
+use crate::job_queue::{JobPriority, JobQueue};
+use crate::models::{DataExport, DataExportStatus, EXPORT_LINK_TTL_HOURS};
+use crate::services::uploads::{self, SpooledFile};
+use crate::services::FirestoreService;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Conversations read per page while exporting
+const CONVERSATION_PAGE_SIZE: usize = 100;
+/// A running export older than this is taken to have died with its worker
+const STALE_EXPORT_HOURS: i64 = 6;
+/// Upper bounds per record type, so one export can't run forever
+const MAX_EXPORT_CONVERSATIONS: usize = 50_000;
+const MAX_EXPORT_MEMORIES: usize = 50_000;
+const MAX_EXPORT_ACTION_ITEMS: usize = 100_000;
+/// Compressed bytes buffered before they're written to the temp file
+const FLUSH_BYTES: usize = 256 * 1024;
+/// How often expired archives are deleted
+const CLEANUP_INTERVAL_HOURS: u64 = 6;
+/// Expired exports deleted per cleanup pass
+const MAX_CLEANUP_PER_PASS: usize = 500;
+
+/// Signature of a download link for `export_id` valid until `expires` (unix seconds)
+pub fn sign_download(secret: &str, export_id: &str, expires: i64) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{}:{}", export_id, expires).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Whether a download link's signature is valid and it hasn't expired
+pub fn verify_download(secret: &str, export_id: &str, expires: i64, sig: &str, now: DateTime<Utc>) -> bool {
+    if expires < now.timestamp() {
+        return false;
+    }
+    let Ok(sig) = hex::decode(sig) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{}:{}", export_id, expires).as_bytes());
+    mac.verify_slice(&sig).is_ok()
+}
+
+/// Signed download path for a completed export, and when it stops working
+pub fn download_link(secret: &str, export: &DataExport, now: DateTime<Utc>) -> (String, DateTime<Utc>) {
+    let expires_at = (now + Duration::hours(EXPORT_LINK_TTL_HOURS)).min(export.expires_at);
+    let expires = expires_at.timestamp();
+    let url = format!(
+        "/v1/exports/{}/download?expires={}&sig={}",
+        export.id,
+        expires,
+        sign_download(secret, &export.id, expires)
+    );
+    (url, expires_at)
+}
+
+/// Archive path of an export that can be downloaded now, or the status to refuse with:
+/// 410 once it has expired (its archive is deleted or about to be), 409 while it isn't
+/// complete and 404 if it has no archive
+pub fn downloadable_archive(export: &DataExport, now: DateTime<Utc>) -> Result<&str, (StatusCode, String)> {
+    if export.expires_at <= now {
+        return Err((StatusCode::GONE, "Export has expired".to_string()));
+    }
+    if export.status != DataExportStatus::Completed {
+        return Err((StatusCode::CONFLICT, "Export is not complete".to_string()));
+    }
+    export
+        .storage_path
+        .as_deref()
+        .ok_or((StatusCode::NOT_FOUND, "Export archive not found".to_string()))
+}
+
+/// Gzipped JSON lines written to a temp file as they're produced
+struct ArchiveWriter {
+    encoder: GzEncoder<Vec<u8>>,
+    file: tokio::fs::File,
+    spooled: SpooledFile,
+}
+
+impl ArchiveWriter {
+    async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (spooled, file) = uploads::spool_writer("omi-export").await?;
+        Ok(Self {
+            encoder: GzEncoder::new(Vec::new(), Compression::default()),
+            file,
+            spooled,
+        })
+    }
+
+    /// One `{"type": kind, "data": record}` line
+    async fn write<T: Serialize>(&mut self, kind: &str, record: &T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        serde_json::to_writer(&mut self.encoder, &serde_json::json!({"type": kind, "data": record}))?;
+        self.encoder.write_all(b"\n")?;
+        if self.encoder.get_ref().len() >= FLUSH_BYTES {
+            let compressed = std::mem::take(self.encoder.get_mut());
+            self.append(&compressed).await?;
+        }
+        Ok(())
+    }
+
+    async fn append(&mut self, bytes: &[u8]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.file.write_all(bytes).await?;
+        self.spooled.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<SpooledFile, Box<dyn std::error::Error + Send + Sync>> {
+        let encoder = std::mem::replace(&mut self.encoder, GzEncoder::new(Vec::new(), Compression::default()));
+        let rest = encoder.finish()?;
+        self.append(&rest).await?;
+        self.file.flush().await?;
+        Ok(self.spooled)
+    }
+}
+
+/// Record a new export and build it in the background
+pub async fn start_export(
+    firestore: Arc<FirestoreService>,
+    config: &Config,
+    jobs: &JobQueue,
+    bucket: String,
+    uid: &str,
+) -> Result<DataExport, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now();
+    let export = DataExport {
+        id: uuid::Uuid::new_v4().to_string(),
+        uid: uid.to_string(),
+        status: DataExportStatus::Running,
+        storage_path: None,
+        size_bytes: None,
+        conversations: 0,
+        memories: 0,
+        action_items: 0,
+        error: None,
+        created_at: now,
+        completed_at: None,
+        expires_at: now + Duration::days(config.export_retention_days),
+    };
+    firestore.save_data_export(&export).await?;
+
+    let mut running = export.clone();
+    let job_firestore = firestore.clone();
+    let queued = jobs.submit(uid, JobPriority::Background, "data-export", async move {
+        if let Err(e) = build_export(&job_firestore, &bucket, &mut running).await {
+            tracing::error!("Data export {} for user {} failed: {}", running.id, running.uid, e);
+            running.status = DataExportStatus::Failed;
+            running.error = Some(e.to_string());
+        }
+        if let Err(e) = job_firestore.save_data_export(&running).await {
+            tracing::error!("Failed to save data export {}: {}", running.id, e);
+        }
+    });
+    if let Err(e) = queued {
+        let failed = DataExport {
+            status: DataExportStatus::Failed,
+            error: Some(e.to_string()),
+            ..export
+        };
+        firestore.save_data_export(&failed).await?;
+        return Err(e.into());
+    }
+    Ok(export)
+}
+
+/// Write the archive, upload it and fill in `export`
+async fn build_export(
+    firestore: &FirestoreService,
+    bucket: &str,
+    export: &mut DataExport,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let uid = export.uid.clone();
+    let mut archive = ArchiveWriter::new().await?;
+
+    // Conversations as of when the export was requested
+    let requested_at = export.created_at.to_rfc3339();
+    let mut conversations = firestore
+        .stream_conversations_before(&uid, &requested_at, CONVERSATION_PAGE_SIZE)
+        .take(MAX_EXPORT_CONVERSATIONS);
+    while let Some(conversation) = conversations.next().await {
+        archive.write("conversation", &conversation?).await?;
+        export.conversations += 1;
+    }
+
+    let mut memories = firestore.stream_memories(&uid).take(MAX_EXPORT_MEMORIES);
+    while let Some(memory) = memories.next().await {
+        archive.write("memory", &memory?).await?;
+        export.memories += 1;
+    }
+
+    let mut items = firestore
+        .stream_action_items(&uid, None, None, None, None, None, None, None, None, true)
+        .take(MAX_EXPORT_ACTION_ITEMS);
+    while let Some(item) = items.next().await {
+        archive.write("action_item", &item?).await?;
+        export.action_items += 1;
+    }
+
+    let spooled = archive.finish().await?;
+    let storage_path = format!(
+        "exports/{}/{}/omi-export-{}.jsonl.gz",
+        uid,
+        export.id,
+        export.created_at.format("%Y-%m-%d")
+    );
+    firestore
+        .upload_export_archive(bucket, &storage_path, spooled.body().await?, spooled.size)
+        .await?;
+
+    export.status = DataExportStatus::Completed;
+    export.storage_path = Some(storage_path);
+    export.size_bytes = Some(spooled.size);
+    export.completed_at = Some(Utc::now());
+    tracing::info!(
+        "Data export {} for user {}: {} bytes ({} conversations, {} memories, {} action items)",
+        export.id,
+        uid,
+        spooled.size,
+        export.conversations,
+        export.memories,
+        export.action_items
+    );
+    Ok(())
+}
+
+/// Whether a running export has gone on so long its worker must have died (a restart or
+/// crash), so it no longer blocks new exports
+pub fn is_stale(export: &DataExport, now: DateTime<Utc>) -> bool {
+    export.status == DataExportStatus::Running && export.created_at < now - Duration::hours(STALE_EXPORT_HOURS)
+}
+
+/// Spawn the periodic deletion of expired archives. No-op without an exports bucket.
+pub fn spawn_export_cleanup(firestore: Arc<FirestoreService>, config: Arc<Config>) {
+    let Some(bucket) = config.exports_bucket.clone() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(CLEANUP_INTERVAL_HOURS * 3600));
+        loop {
+            interval.tick().await;
+            run_cleanup_pass(&firestore, &bucket).await;
+        }
+    });
+
+    tracing::info!("Data export cleanup scheduled every {} hours", CLEANUP_INTERVAL_HOURS);
+}
+
+async fn run_cleanup_pass(firestore: &FirestoreService, bucket: &str) {
+    let expired = match firestore.get_expired_data_exports(Utc::now(), MAX_CLEANUP_PER_PASS).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            tracing::error!("Data export cleanup: failed to list expired exports: {}", e);
+            return;
+        }
+    };
+
+    let mut deleted = 0;
+    for export in expired {
+        if let Some(path) = &export.storage_path {
+            if let Err(e) = firestore.delete_gcs_object(bucket, path).await {
+                tracing::warn!("Failed to delete archive of data export {}: {}", export.id, e);
+                continue;
+            }
+        }
+        match firestore.delete_data_export(&export.id).await {
+            Ok(()) => deleted += 1,
+            Err(e) => tracing::warn!("Failed to delete data export {}: {}", export.id, e),
+        }
+    }
+    if deleted > 0 {
+        tracing::info!("Data export cleanup: deleted {} expired exports", deleted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_download_links() {
+        let now = Utc::now();
+        let expires = (now + Duration::hours(1)).timestamp();
+        let sig = sign_download("secret", "e1", expires);
+
+        assert!(verify_download("secret", "e1", expires, &sig, now));
+        assert!(!verify_download("secret", "e2", expires, &sig, now));
+        assert!(!verify_download("other", "e1", expires, &sig, now));
+        assert!(!verify_download("secret", "e1", expires + 1, &sig, now));
+        assert!(!verify_download("secret", "e1", expires, &sig, now + Duration::hours(2)));
+        assert!(!verify_download("secret", "e1", expires, "not-hex", now));
+    }
+
+    #[test]
+    fn test_only_completed_unexpired_exports_are_downloadable() {
+        let now = Utc::now();
+        let export: DataExport = serde_json::from_value(serde_json::json!({
+            "id": "e1",
+            "uid": "u1",
+            "status": "completed",
+            "storage_path": "exports/u1/e1.jsonl.gz",
+            "created_at": now - Duration::days(1),
+            "expires_at": now + Duration::days(6),
+        }))
+        .unwrap();
+        assert_eq!(downloadable_archive(&export, now), Ok("exports/u1/e1.jsonl.gz"));
+
+        let status = |export: &DataExport, at| downloadable_archive(export, at).unwrap_err().0;
+        assert_eq!(status(&export, now + Duration::days(6)), StatusCode::GONE);
+        let running = DataExport {
+            status: DataExportStatus::Running,
+            storage_path: None,
+            ..export.clone()
+        };
+        assert_eq!(status(&running, now), StatusCode::CONFLICT);
+        let failed = DataExport {
+            status: DataExportStatus::Failed,
+            ..export.clone()
+        };
+        assert_eq!(status(&failed, now), StatusCode::CONFLICT);
+        let missing = DataExport {
+            storage_path: None,
+            ..export
+        };
+        assert_eq!(status(&missing, now), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_stale_running_exports() {
+        let now = Utc::now();
+        let export: DataExport = serde_json::from_value(serde_json::json!({
+            "id": "e1",
+            "uid": "u1",
+            "status": "running",
+            "created_at": now - Duration::hours(STALE_EXPORT_HOURS + 1),
+            "expires_at": now + Duration::days(7),
+        }))
+        .unwrap();
+        assert!(is_stale(&export, now));
+        assert!(!is_stale(&export, now - Duration::hours(2)));
+
+        let completed = DataExport {
+            status: DataExportStatus::Completed,
+            ..export
+        };
+        assert!(!is_stale(&completed, now));
+    }
+}
@@ -0,0 +1,266 @@
+// Due dates - Resolves phrases like "next Tuesday morning" to a timestamp
+// Common phrases are parsed here; anything else falls back to the LLM (in the route, or the
+// extraction model's own guess for spoken action items).
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Longest accepted due date phrase, in characters
+pub const MAX_DUE_TEXT_CHARS: usize = 200;
+/// Time of day used when a phrase names only a day ("friday" = friday 17:00)
+const DEFAULT_DUE_HOUR: u32 = 17;
+
+/// Confidence given to a due date the phrase parser resolved itself
+pub const PARSED_DUE_CONFIDENCE: f64 = 0.9;
+/// Confidence assumed when the model gives a due date without one
+const DEFAULT_INFERRED_DUE_CONFIDENCE: f64 = 0.5;
+
+/// Words that don't change the meaning ("due by friday at 5pm")
+const FILLER_WORDS: &[&str] = &["due", "by", "on", "at", "before", "the"];
+
+fn hm(hour: u32, minute: u32) -> Option<NaiveTime> {
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The next `weekday` after `today` (a week out when today is that weekday)
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(if ahead == 0 { 7 } else { ahead as i64 })
+}
+
+/// Friday of the current week (today on weekends)
+fn end_of_week(today: NaiveDate) -> NaiveDate {
+    today + Duration::days(4 - today.weekday().num_days_from_monday().min(4) as i64)
+}
+
+fn count(word: &str) -> Option<i64> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        _ => word.parse().ok().filter(|n| (1..=365).contains(n)),
+    }
+}
+
+/// "5pm", "5:30pm", "17:30", or "5" / "5:30" followed by "am"/"pm".
+/// Returns the time and how many words it used.
+fn clock(word: &str, next: Option<&str>) -> Option<(NaiveTime, usize)> {
+    let (digits, meridiem, used) = if let Some(d) = word.strip_suffix("am") {
+        (d, Some(false), 1)
+    } else if let Some(d) = word.strip_suffix("pm") {
+        (d, Some(true), 1)
+    } else if matches!(next, Some("am")) {
+        (word, Some(false), 2)
+    } else if matches!(next, Some("pm")) {
+        (word, Some(true), 2)
+    } else if word.contains(':') {
+        (word, None, 1)
+    } else {
+        return None;
+    };
+
+    let (hour, minute) = match digits.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (digits.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    Some((hm(hour, minute)?, used))
+}
+
+/// Resolve a due date phrase relative to `now` in the user's timezone.
+/// None when the phrase isn't one of the forms understood here.
+pub fn parse_due_text(text: &str, now: DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let text = text.trim().to_lowercase().replace([',', '.'], " ");
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|w| !FILLER_WORDS.contains(w))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let today = now.date_naive();
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut i = 0;
+    while i < words.len() {
+        let next = words.get(i + 1).copied();
+        let mut used = 1;
+        match words[i] {
+            "today" => date = Some(today),
+            "tonight" => {
+                date = Some(today);
+                time = time.or(hm(20, 0));
+            }
+            "tomorrow" | "tmrw" | "tmr" => date = Some(today + Duration::days(1)),
+            "eod" => {
+                date = Some(today);
+                time = hm(DEFAULT_DUE_HOUR, 0);
+            }
+            "eow" => {
+                date = Some(end_of_week(today));
+                time = hm(DEFAULT_DUE_HOUR, 0);
+            }
+            "end" if next == Some("of") => {
+                match words.get(i + 2).copied() {
+                    Some("day") => date = Some(today),
+                    Some("week") => date = Some(end_of_week(today)),
+                    _ => return None,
+                }
+                time = hm(DEFAULT_DUE_HOUR, 0);
+                used = 3;
+            }
+            "in" if next.and_then(count).is_some() => {
+                let n = count(next?)?;
+                let unit = words.get(i + 2)?.trim_end_matches('s');
+                match unit {
+                    "minute" | "min" => return Some((now + Duration::minutes(n)).with_timezone(&Utc)),
+                    "hour" | "hr" => return Some((now + Duration::hours(n)).with_timezone(&Utc)),
+                    "day" => date = Some(today + Duration::days(n)),
+                    "week" => date = Some(today + Duration::weeks(n)),
+                    _ => return None,
+                }
+                used = 3;
+            }
+            "next" if next == Some("week") => {
+                date = Some(next_weekday(today, Weekday::Mon));
+                used = 2;
+            }
+            // "next tuesday" and "this tuesday" both mean the coming tuesday
+            "next" | "this" | "in" => {}
+            "morning" => time = hm(9, 0),
+            "noon" | "midday" => time = hm(12, 0),
+            "afternoon" => time = hm(15, 0),
+            "evening" => time = hm(18, 0),
+            "night" => time = hm(20, 0),
+            word => {
+                if let Some(day) = weekday(word) {
+                    date = Some(next_weekday(today, day));
+                } else {
+                    let (parsed, clock_words) = clock(word, next)?;
+                    time = Some(parsed);
+                    used = clock_words;
+                }
+            }
+        }
+        i += used;
+    }
+
+    let (date, time) = match (date, time) {
+        (None, None) => return None,
+        (Some(date), time) => (date, time.or(hm(DEFAULT_DUE_HOUR, 0))?),
+        // A bare time is the next time the clock shows it
+        (None, Some(time)) if today.and_time(time) > now.naive_local() => (today, time),
+        (None, Some(time)) => (today + Duration::days(1), time),
+    };
+    now.timezone()
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Resolve the due date of an extracted action item against the conversation start.
+/// A spoken `phrase` the parser understands wins over the model's `due_at` (keeping the
+/// model's time when both land on the same day); otherwise the model's date is used with
+/// its own confidence. Dates before the conversation started are dropped.
+pub fn resolve_extracted_due(
+    phrase: Option<&str>,
+    due_at: Option<&str>,
+    confidence: Option<f64>,
+    started_at: &str,
+    timezone: &str,
+) -> Option<(DateTime<Utc>, f64)> {
+    let tz: Tz = timezone.parse().unwrap_or(Tz::UTC);
+    let started_at = DateTime::parse_from_rfc3339(started_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    let inferred = due_at
+        .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+    let parsed = phrase.and_then(|p| parse_due_text(p, started_at.with_timezone(&tz)));
+    let confidence = confidence.unwrap_or(DEFAULT_INFERRED_DUE_CONFIDENCE).clamp(0.0, 1.0);
+
+    let resolved = match (parsed, inferred) {
+        (Some(parsed), Some(inferred))
+            if parsed.with_timezone(&tz).date_naive() == inferred.with_timezone(&tz).date_naive() =>
+        {
+            (inferred, confidence.max(PARSED_DUE_CONFIDENCE))
+        }
+        (Some(parsed), _) => (parsed, PARSED_DUE_CONFIDENCE),
+        (None, Some(inferred)) => (inferred, confidence),
+        (None, None) => return None,
+    };
+    Some(resolved).filter(|(due_at, _)| *due_at >= started_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_due_text() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        // Wednesday 2026-10-14 10:00 EDT (14:00 UTC)
+        let now = tz.with_ymd_and_hms(2026, 10, 14, 10, 0, 0).unwrap();
+        let parse = |text: &str| parse_due_text(text, now).map(|dt| dt.to_rfc3339());
+
+        assert_eq!(parse("next Tuesday morning"), Some("2026-10-20T13:00:00+00:00".to_string()));
+        assert_eq!(parse("tomorrow at 5:30 pm"), Some("2026-10-15T21:30:00+00:00".to_string()));
+        assert_eq!(parse("by Wednesday"), Some("2026-10-21T21:00:00+00:00".to_string()));
+        assert_eq!(parse("9am"), Some("2026-10-15T13:00:00+00:00".to_string()));
+        assert_eq!(parse("end of week"), Some("2026-10-16T21:00:00+00:00".to_string()));
+        assert_eq!(parse("in 2 hours"), Some("2026-10-14T16:00:00+00:00".to_string()));
+        assert_eq!(parse("Friday in the afternoon"), Some("2026-10-16T19:00:00+00:00".to_string()));
+        assert_eq!(parse("in 3 days"), Some("2026-10-17T21:00:00+00:00".to_string()));
+        assert_eq!(parse("whenever the report lands"), None);
+        assert_eq!(parse("13pm"), None);
+    }
+
+    #[test]
+    fn test_resolve_extracted_due() {
+        // Wednesday 2026-10-14 10:00 EDT
+        let started_at = "2026-10-14T14:00:00Z";
+        let tz = "America/New_York";
+        let resolve = |phrase, due_at, confidence| {
+            resolve_extracted_due(phrase, due_at, confidence, started_at, tz)
+                .map(|(dt, c)| (dt.to_rfc3339(), c))
+        };
+
+        // The parser overrides a model date on another day
+        assert_eq!(
+            resolve(Some("by Friday"), Some("2026-10-23T21:00:00Z"), Some(0.6)),
+            Some(("2026-10-16T21:00:00+00:00".to_string(), PARSED_DUE_CONFIDENCE))
+        );
+        // Same day: the model's time is kept
+        assert_eq!(
+            resolve(Some("by Friday"), Some("2026-10-16T20:00:00Z"), Some(0.95)),
+            Some(("2026-10-16T20:00:00+00:00".to_string(), 0.95))
+        );
+        // Event-relative phrases keep the model's guess and confidence
+        assert_eq!(
+            resolve(Some("before the offsite"), Some("2026-10-22T21:00:00Z"), Some(0.4)),
+            Some(("2026-10-22T21:00:00+00:00".to_string(), 0.4))
+        );
+        assert_eq!(resolve(Some("before the offsite"), None, None), None);
+        // Dates in the past are dropped
+        assert_eq!(resolve(None, Some("2026-10-13T21:00:00Z"), Some(0.9)), None);
+    }
+}
@@ -0,0 +1,232 @@
+// Event bus - In-process pub/sub for domain events
+// Routes publish after a write succeeds; consumers (integrations, Notion sync) subscribe in main,
+// and clients follow their own events over GET /v1/updates/stream. Each event carries the
+// Firestore project it was published under, and consumers handle it in that project.
+
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::models::{ActionItemDB, Conversation, FocusSessionDB};
+use crate::services::firestore::{current_project_override, scoped_to_project};
+
+/// Events buffered per subscriber before slow consumers start missing them
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// A conversation finished processing and was saved
+    ConversationCreated {
+        uid: String,
+        conversation: Arc<Conversation>,
+    },
+    /// An action item moved from open to completed
+    ActionItemCompleted { uid: String, item: Arc<ActionItemDB> },
+    /// A focus session was recorded by the client
+    FocusSessionCreated {
+        uid: String,
+        session: Arc<FocusSessionDB>,
+    },
+    /// An app's result for a conversation was saved (one per app, as each finishes)
+    AppResultCompleted {
+        uid: String,
+        conversation_id: String,
+        app_id: String,
+        /// Start of the result content
+        preview: String,
+    },
+}
+
+impl AppEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::ConversationCreated { .. } => "conversation_created",
+            AppEvent::ActionItemCompleted { .. } => "action_item_completed",
+            AppEvent::FocusSessionCreated { .. } => "focus_session_created",
+            AppEvent::AppResultCompleted { .. } => "app_result_completed",
+        }
+    }
+
+    pub fn uid(&self) -> &str {
+        match self {
+            AppEvent::ConversationCreated { uid, .. }
+            | AppEvent::ActionItemCompleted { uid, .. }
+            | AppEvent::FocusSessionCreated { uid, .. }
+            | AppEvent::AppResultCompleted { uid, .. } => uid,
+        }
+    }
+
+    /// What the event's owner is sent over the updates stream; None for events clients
+    /// don't follow
+    pub fn client_payload(&self) -> Option<Value> {
+        match self {
+            AppEvent::ConversationCreated { conversation, .. } => {
+                Some(json!({"conversation_id": conversation.id}))
+            }
+            AppEvent::AppResultCompleted {
+                conversation_id,
+                app_id,
+                preview,
+                ..
+            } => Some(json!({
+                "conversation_id": conversation_id,
+                "app_id": app_id,
+                "preview": preview,
+            })),
+            AppEvent::ActionItemCompleted { .. } | AppEvent::FocusSessionCreated { .. } => None,
+        }
+    }
+}
+
+/// An event as delivered to subscribers
+#[derive(Debug, Clone)]
+pub struct PublishedEvent {
+    pub event: AppEvent,
+    /// Project override of the publishing request; None for the default project
+    pub project: Option<String>,
+}
+
+/// Broadcast bus; clones publish to the same subscribers
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<PublishedEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publish an event; returns how many consumers will see it
+    pub fn publish(&self, event: AppEvent) -> usize {
+        let name = event.name();
+        let published = PublishedEvent {
+            event,
+            project: current_project_override(),
+        };
+        match self.sender.send(published) {
+            Ok(receivers) => receivers,
+            Err(_) => {
+                tracing::debug!("No consumers for {} event", name);
+                0
+            }
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PublishedEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Spawn a consumer that handles each event in its own task, so one slow
+/// delivery doesn't hold up the events behind it. The task runs in the event's project.
+pub fn spawn_consumer<F, Fut>(bus: &EventBus, name: &'static str, handler: F)
+where
+    F: Fn(AppEvent) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut receiver = bus.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(published) => {
+                    tokio::spawn(scoped_to_project(published.project, handler(published.event)));
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Event consumer {} fell behind and missed {} events", name, missed);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FocusStatus;
+
+    #[test]
+    fn test_publish_reaches_every_subscriber() {
+        let bus = EventBus::new();
+        let session = FocusSessionDB {
+            id: "f1".to_string(),
+            status: FocusStatus::Focused,
+            app_or_site: "Xcode".to_string(),
+            description: "Editing Swift".to_string(),
+            message: None,
+            created_at: chrono::Utc::now(),
+            duration_seconds: Some(60),
+        };
+        let event = AppEvent::FocusSessionCreated {
+            uid: "u1".to_string(),
+            session: Arc::new(session),
+        };
+        assert_eq!(bus.publish(event.clone()), 0);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        assert_eq!(bus.publish(event), 2);
+        for receiver in [&mut first, &mut second] {
+            let received = receiver.try_recv().unwrap();
+            assert_eq!(received.event.name(), "focus_session_created");
+            assert_eq!(received.event.uid(), "u1");
+            assert_eq!(received.project, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consumer_runs_in_the_publishing_project() {
+        let bus = EventBus::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        spawn_consumer(&bus, "test", move |event| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send((event.uid().to_string(), current_project_override()));
+            }
+        });
+        let event = |uid: &str| AppEvent::ActionItemCompleted {
+            uid: uid.to_string(),
+            item: Arc::new(
+                serde_json::from_value(json!({"id": "a1", "description": "Ship", "created_at": chrono::Utc::now()}))
+                    .unwrap(),
+            ),
+        };
+
+        crate::services::firestore::with_project_override("staging".to_string(), async {
+            bus.publish(event("u1"));
+        })
+        .await;
+        bus.publish(event("u2"));
+
+        let mut received = vec![rx.recv().await.unwrap(), rx.recv().await.unwrap()];
+        received.sort();
+        assert_eq!(
+            received,
+            vec![("u1".to_string(), Some("staging".to_string())), ("u2".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_client_payload() {
+        let event = AppEvent::AppResultCompleted {
+            uid: "u1".to_string(),
+            conversation_id: "c1".to_string(),
+            app_id: "notes".to_string(),
+            preview: "Key points...".to_string(),
+        };
+        assert_eq!(
+            event.client_payload(),
+            Some(json!({"conversation_id": "c1", "app_id": "notes", "preview": "Key points..."}))
+        );
+        assert_eq!(event.name(), "app_result_completed");
+    }
+}
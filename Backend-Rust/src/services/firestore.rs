@@ -8,20 +8,77 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::encryption;
 
 use crate::models::{
-    ActionItemDB, AdviceCategory, AdviceDB, App, AppReview, AppSummary, Category,
-    ChatSessionDB, Conversation, DailySummarySettings, DistractionEntry, Folder, FocusSessionDB,
-    FocusStats, FocusStatus, GoalDB, GoalHistoryEntry, GoalType, Memory, MemoryCategory, MemoryDB, MessageDB,
-    NotificationSettings, PersonaDB, Structured, TranscriptSegment, TranscriptionPreferences,
-    AIUserProfile, UserProfile,
+    ActionType, AuthStep, ExternalIntegration, NotificationScope, ProactiveNotification, TriggerEvent,
+    ActionItemDB, ActionItemNote, AdviceCategory, AdviceDB, App, AppModeration, AppScope, AppReview, AppSetupState, AppSummary, DataExport, LlmDebugEntry, AppsHomeLayout, AppTranslation, Category,
+    ChatSessionDB, CoalescedFocusSession, ConversationFeedback, Conversation, ConversationAnalytics, ConversationEdit, ConversationEditField, ConversationFocusSession, ConversationLock, ConversationTemplate, DailyScore, DeviceDB, DailySummarySettings, DistractionEntry, EmailSendQuota, EmailSendRecord, Folder, FolderRule, FocusSessionDB,
+    FocusStats, FocusStatus, GoalDB, GoalHistoryEntry, GoalType, Memo, Memory, MemoryCategory, MemoryDB, MessageAttachment, MessageDB, ModerationVerdict,
+    NotificationSettings, NotionConnection, NotionPropertyMapping, NotionSyncStatus, PersonaDB, SlackConnection, SpeakerAnalytics, StoredEmailDraft, Structured, TranscriptSegment, TranscriptionPreferences, WordTiming,
+    AIUserProfile, AutoDiscardLevel, DiscardReason, ProcessingPromptSettings, UserProfile, UserWebhook, AccountabilityGroup, GroupMember,
     AssistantSettingsData, SharedAssistantSettingsData, FocusSettingsData, TaskSettingsData,
-    AdviceSettingsData, MemorySettingsData,
+    AdviceSettingsData, MemorySettingsData, ActionItemRolloverSettings, RetentionPolicy,
+    ImpersonationAuditEntry, LlmCredentials, LlmProvenance, LlmProvider, QualityBucket, QualityRating, QualityRatingKind, QualityReport,
+    MigrationRecord, Notification, ShadowComparison, SyncPage, SyncPosition, WeeklyReview, MAX_NOTIFICATION_READ_IDS, WeeklyReviewSettings,
 };
+use crate::llm::routing::{ModelOverrides, TaskKind, TaskModels};
+use crate::services::apps_cache::{self, AppsCache};
+pub use crate::services::firestore_error::FirestoreError;
+use crate::services::firestore_serde;
+use crate::services::firestore_http::{HedgedReads, HttpTuning};
+use crate::services::message_search;
+use crate::services::transcript_chunks::{chunk_doc_id, chunk_fields, TranscriptPayload, TRANSCRIPT_CHUNK_BYTES};
+
+/// OAuth scopes requested for Firestore access
+const FIRESTORE_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/datastore",
+    "https://www.googleapis.com/auth/cloud-platform",
+];
+
+/// Google credentials file, selected by its `type` field
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GoogleCredentials {
+    /// JSON key file (JWT bearer flow)
+    ServiceAccount(ServiceAccountCredentials),
+    /// Workload identity federation (STS token exchange)
+    ExternalAccount(ExternalAccountCredentials),
+    /// Service account impersonation on top of other source credentials
+    ImpersonatedServiceAccount(ImpersonatedServiceAccountCredentials),
+    /// User credentials from `gcloud auth application-default login`
+    AuthorizedUser(AuthorizedUserCredentials),
+}
+
+impl GoogleCredentials {
+    /// Short description for logs
+    fn describe(&self) -> String {
+        match self {
+            Self::ServiceAccount(c) => format!("service account {}", c.client_email),
+            Self::ExternalAccount(c) => format!("external account (audience {})", c.audience),
+            Self::ImpersonatedServiceAccount(c) => format!(
+                "impersonated service account via {} credentials",
+                c.source_credentials.describe()
+            ),
+            Self::AuthorizedUser(_) => "authorized user".to_string(),
+        }
+    }
+
+    /// Check that embedded keys decode, so a bad key fails before the first token exchange
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::ServiceAccount(c) => EncodingKey::from_rsa_pem(c.private_key.as_bytes())
+                .map(|_| ())
+                .map_err(|e| format!("Invalid private key for {}: {}", c.client_email, e)),
+            Self::ImpersonatedServiceAccount(c) => c.source_credentials.validate(),
+            Self::ExternalAccount(_) | Self::AuthorizedUser(_) => Ok(()),
+        }
+    }
+}
 
 /// Service account credentials from JSON file
 #[derive(Debug, Clone, Deserialize)]
@@ -31,6 +88,52 @@ struct ServiceAccountCredentials {
     token_uri: Option<String>,
 }
 
+/// Workload identity federation credentials (`type: external_account`)
+#[derive(Debug, Clone, Deserialize)]
+struct ExternalAccountCredentials {
+    audience: String,
+    subject_token_type: String,
+    token_url: String,
+    credential_source: CredentialSource,
+    /// If set, the federated token is exchanged for a service account token
+    service_account_impersonation_url: Option<String>,
+}
+
+/// Where an external account reads its subject token from
+#[derive(Debug, Clone, Deserialize)]
+struct CredentialSource {
+    file: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    format: Option<CredentialSourceFormat>,
+}
+
+/// Subject token format: "text" (default) or "json" with a field name
+#[derive(Debug, Clone, Deserialize)]
+struct CredentialSourceFormat {
+    #[serde(rename = "type")]
+    format_type: String,
+    subject_token_field_name: Option<String>,
+}
+
+/// Impersonation credentials (`type: impersonated_service_account`)
+#[derive(Debug, Clone, Deserialize)]
+struct ImpersonatedServiceAccountCredentials {
+    service_account_impersonation_url: String,
+    source_credentials: Box<GoogleCredentials>,
+    #[serde(default)]
+    delegates: Vec<String>,
+}
+
+/// User refresh-token credentials (`type: authorized_user`)
+#[derive(Debug, Clone, Deserialize)]
+struct AuthorizedUserCredentials {
+    client_id: String,
+    client_secret: String,
+    refresh_token: String,
+}
+
 /// JWT claims for Google OAuth2
 #[derive(Debug, Serialize)]
 struct GoogleJwtClaims {
@@ -47,18 +150,82 @@ struct CachedToken {
     expires_at: i64,
 }
 
+/// Boxed future resolving to (access token, lifetime in seconds)
+type TokenFuture<'a> = std::pin::Pin<
+    Box<dyn std::future::Future<Output = Result<(String, i64), FirestoreError>> + Send + 'a>,
+>;
+
+/// Documents (or values parsed from them) yielded page by page by the streaming queries
+pub type DocumentStream<'a, T> =
+    futures::stream::BoxStream<'a, Result<T, FirestoreError>>;
+
+/// Google OAuth access token lifetime when the response omits expires_in
+fn default_token_lifetime() -> i64 {
+    3600
+}
+
+tokio::task_local! {
+    /// Firestore project selected for the current request (via `X-Omi-Environment`).
+    /// Unset outside a request scope, in which case the default project is used.
+    static PROJECT_OVERRIDE: String;
+}
+
+/// Run a future with all Firestore calls inside it routed to `project_id`.
+/// Work moved onto `tokio::spawn` doesn't inherit the override; wrap it in
+/// `carry_project_override` first.
+pub async fn with_project_override<F: std::future::Future>(project_id: String, fut: F) -> F::Output {
+    PROJECT_OVERRIDE.scope(project_id, fut).await
+}
+
+/// The override active for the current task, if any
+pub fn current_project_override() -> Option<String> {
+    PROJECT_OVERRIDE.try_with(|p| p.clone()).ok()
+}
+
+/// Capture the current override now and re-apply it wherever `fut` is polled later, for
+/// work handed to `tokio::spawn`, the job queue or the event bus
+pub fn carry_project_override<F: std::future::Future>(fut: F) -> impl std::future::Future<Output = F::Output> {
+    scoped_to_project(current_project_override(), fut)
+}
+
+/// Run `fut` routed to `project_id`, or to the default project for None
+pub async fn scoped_to_project<F: std::future::Future>(project_id: Option<String>, fut: F) -> F::Output {
+    match project_id {
+        Some(project_id) => PROJECT_OVERRIDE.scope(project_id, fut).await,
+        None => fut.await,
+    }
+}
+
 /// Firestore collection paths
 /// Copied from Python database.py
 pub const USERS_COLLECTION: &str = "users";
 pub const CONVERSATIONS_SUBCOLLECTION: &str = "conversations";
 pub const ACTION_ITEMS_SUBCOLLECTION: &str = "action_items";
+/// Progress notes, nested under each action item
+pub const ACTION_ITEM_NOTES_SUBCOLLECTION: &str = "notes";
+/// Upper bound when reading every note of one action item
+const MAX_NOTES_PER_ACTION_ITEM: usize = 1000;
+/// Edit history, nested under each conversation
+pub const CONVERSATION_EDITS_SUBCOLLECTION: &str = "edits";
+/// Parts of transcripts too large for the conversation document (see services::transcript_chunks)
+pub const TRANSCRIPT_CHUNKS_SUBCOLLECTION: &str = "transcript_chunks";
+/// Upper bound when reading every edit of one conversation
+const MAX_EDITS_PER_CONVERSATION: usize = 1000;
 pub const MEMORIES_SUBCOLLECTION: &str = "memories";
 pub const APPS_COLLECTION: &str = "plugins_data";
 pub const ENABLED_APPS_SUBCOLLECTION: &str = "enabled_plugins";
 pub const FOCUS_SESSIONS_SUBCOLLECTION: &str = "focus_sessions";
 pub const ADVICE_SUBCOLLECTION: &str = "advice";
 pub const MESSAGES_SUBCOLLECTION: &str = "messages";
+pub const FILES_SUBCOLLECTION: &str = "files";
 pub const FOLDERS_SUBCOLLECTION: &str = "folders";
+pub const FOLDER_RULES_SUBCOLLECTION: &str = "folder_rules";
+pub const EMAIL_DRAFTS_SUBCOLLECTION: &str = "email_drafts";
+pub const EMAIL_SENDS_SUBCOLLECTION: &str = "email_sends";
+pub const EMAIL_SEND_QUOTA_SUBCOLLECTION: &str = "email_send_quota";
+const EMAIL_SEND_QUOTA_DOC: &str = "daily";
+/// Attempts at reserving email quota before giving up on contention
+const EMAIL_QUOTA_ATTEMPTS: usize = 5;
 pub const CHAT_SESSIONS_SUBCOLLECTION: &str = "chat_sessions";
 pub const GOALS_SUBCOLLECTION: &str = "goals";
 pub const KG_NODES_SUBCOLLECTION: &str = "knowledge_nodes";
@@ -67,6 +234,48 @@ pub const STAGED_TASKS_SUBCOLLECTION: &str = "staged_tasks";
 pub const PEOPLE_SUBCOLLECTION: &str = "people";
 pub const LLM_USAGE_SUBCOLLECTION: &str = "llm_usage";
 pub const SCREEN_ACTIVITY_SUBCOLLECTION: &str = "screen_activity";
+pub const DEVICES_SUBCOLLECTION: &str = "devices";
+pub const INTEGRATIONS_SUBCOLLECTION: &str = "integrations";
+pub const USER_WEBHOOKS_SUBCOLLECTION: &str = "webhooks";
+pub const MEMOS_SUBCOLLECTION: &str = "memos";
+pub const DAILY_SCORES_SUBCOLLECTION: &str = "daily_scores";
+pub const CONVERSATION_TEMPLATES_SUBCOLLECTION: &str = "conversation_templates";
+pub const WEEKLY_REVIEWS_SUBCOLLECTION: &str = "weekly_reviews";
+pub const NOTIFICATIONS_SUBCOLLECTION: &str = "notifications";
+/// Fixed ID of the system folder that retention archiving moves conversations into
+pub const ARCHIVE_FOLDER_ID: &str = "archive";
+/// Document IDs of connections under users/{uid}/integrations
+const NOTION_INTEGRATION_DOC: &str = "notion";
+const SLACK_INTEGRATION_DOC: &str = "slack";
+const LLM_CREDENTIALS_DOC: &str = "llm";
+/// Top-level lookup from a Slack (team, user) to an Omi uid, for inbound Slack requests
+pub const SLACK_USERS_COLLECTION: &str = "slack_users";
+/// Top-level opt-in accountability groups; members live in a subcollection
+pub const ACCOUNTABILITY_GROUPS_COLLECTION: &str = "accountability_groups";
+pub const GROUP_MEMBERS_SUBCOLLECTION: &str = "members";
+/// Records of hard-deleted documents, so /v2/sync can report deletions
+pub const SYNC_TOMBSTONES_SUBCOLLECTION: &str = "sync_tombstones";
+/// Top-level progress records for schema migrations (see services::migrations)
+pub const SCHEMA_MIGRATIONS_COLLECTION: &str = "schema_migrations";
+/// Top-level admin-managed settings; llm_models holds per-task model overrides
+pub const ADMIN_SETTINGS_COLLECTION: &str = "admin_settings";
+const LLM_MODELS_DOC: &str = "llm_models";
+/// Marketplace home layout (section order and spotlight categories)
+const APPS_HOME_DOC: &str = "apps_home";
+/// Top-level thumbs ratings on LLM output, one per rated message or conversation
+pub const LLM_QUALITY_RATINGS_COLLECTION: &str = "llm_quality_ratings";
+/// Top-level daily quality reports, keyed by YYYY-MM-DD
+pub const LLM_QUALITY_REPORTS_COLLECTION: &str = "llm_quality_reports";
+/// Top-level audit trail of requests made with admin impersonation tokens
+pub const IMPERSONATION_AUDIT_COLLECTION: &str = "impersonation_audit";
+/// Top-level baseline vs candidate outputs from shadow prompt runs, keyed by conversation
+pub const SHADOW_COMPARISONS_COLLECTION: &str = "shadow_comparisons";
+/// Top-level app setup flows awaiting their callback, keyed by the hashed state
+pub const APP_SETUP_STATES_COLLECTION: &str = "app_setup_states";
+/// Top-level redacted LLM calls, deleted by a TTL policy on `expire_at`
+pub const LLM_DEBUG_LOGS_COLLECTION: &str = "llm_debug_logs";
+/// Top-level data export archives (see services::data_export)
+pub const DATA_EXPORTS_COLLECTION: &str = "data_exports";
 
 /// Generate a document ID from a seed string using SHA256 hash
 /// Copied from Python document_id_from_seed
@@ -77,14 +286,167 @@ pub fn document_id_from_seed(seed: &str) -> String {
     hex::encode(&result[..10]) // First 20 hex chars (10 bytes)
 }
 
+/// A composite index as reported by the Firestore admin API
+#[derive(Debug, Clone)]
+pub struct CompositeIndex {
+    pub collection_group: String,
+    /// "COLLECTION" or "COLLECTION_GROUP"
+    pub query_scope: String,
+    /// (field path, "ASCENDING" | "DESCENDING" | "CONTAINS"), in index order
+    pub fields: Vec<(String, String)>,
+    /// "READY", "CREATING" or "NEEDS_REPAIR"
+    pub state: String,
+}
+
+/// Condition a document must meet for a write to apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Precondition {
+    /// The document must (true) or must not (false) exist
+    Exists(bool),
+    /// The document's last write must be exactly this `updateTime` (RFC 3339, as Firestore returns it)
+    UpdateTime(String),
+}
+
+impl Precondition {
+    /// Query parameter for REST writes
+    pub fn query_param(&self) -> String {
+        match self {
+            Precondition::Exists(exists) => format!("currentDocument.exists={}", exists),
+            Precondition::UpdateTime(time) => format!("currentDocument.updateTime={}", time),
+        }
+    }
+}
+
+/// Whether a failed write response means its precondition did not hold
+fn is_precondition_failure(status: reqwest::StatusCode, error_text: &str) -> bool {
+    (status == reqwest::StatusCode::BAD_REQUEST || status == reqwest::StatusCode::CONFLICT)
+        && error_text.contains("FAILED_PRECONDITION")
+}
+
+/// Fields written by a snooze; None clears it
+fn snooze_fields(until: Option<DateTime<Utc>>) -> Value {
+    json!({
+        "snoozed_until": match until {
+            Some(until) => json!({"timestampValue": until.to_rfc3339()}),
+            None => json!({"nullValue": null}),
+        },
+        "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+    })
+}
+
+/// Fields and update mask of an advice update. Seeing advice is a read receipt unless the
+/// client says otherwise.
+fn advice_update_fields(
+    update: &crate::models::UpdateAdviceRequest,
+    now: DateTime<Utc>,
+) -> (Value, Vec<&'static str>) {
+    let mut field_paths = vec!["updated_at"];
+    let mut fields = json!({
+        "updated_at": {"timestampValue": now.to_rfc3339()}
+    });
+
+    if let Some(read) = update.is_read.or(update.seen_at.map(|_| true)) {
+        field_paths.push("is_read");
+        fields["is_read"] = json!({"booleanValue": read});
+    }
+
+    if let Some(dismissed) = update.is_dismissed {
+        field_paths.push("is_dismissed");
+        fields["is_dismissed"] = json!({"booleanValue": dismissed});
+    }
+
+    for (path, at) in [
+        ("pushed_at", update.pushed_at),
+        ("seen_at", update.seen_at),
+        ("acted_at", update.acted_at),
+    ] {
+        if let Some(at) = at {
+            field_paths.push(path);
+            fields[path] = json!({"timestampValue": at.to_rfc3339()});
+        }
+    }
+    (fields, field_paths)
+}
+
+/// batchWrite entry applying a bulk action to one advice document. The document must
+/// exist, so a missing ID fails on its own with NOT_FOUND.
+fn advice_bulk_write(doc_name: String, action: crate::models::AdviceBulkAction, now: &str) -> Value {
+    use crate::models::AdviceBulkAction;
+
+    let field = match action {
+        AdviceBulkAction::Delete => {
+            return json!({"delete": doc_name, "currentDocument": {"exists": true}});
+        }
+        AdviceBulkAction::MarkRead => "is_read",
+        AdviceBulkAction::Dismiss => "is_dismissed",
+    };
+    json!({
+        "update": {
+            "name": doc_name,
+            "fields": {
+                field: {"booleanValue": true},
+                "updated_at": {"timestampValue": now}
+            }
+        },
+        "updateMask": {"fieldPaths": [field, "updated_at"]},
+        "currentDocument": {"exists": true}
+    })
+}
+
+/// Error of a failed review submission. A failed exists=false precondition means the user
+/// already has a review, which the caller answers with 409.
+fn submit_review_error(status: reqwest::StatusCode, error_text: &str) -> FirestoreError {
+    if is_precondition_failure(status, error_text) {
+        return FirestoreError::AlreadyExists(format!("Review already submitted: {}", error_text));
+    }
+    FirestoreError::from_response(status, "Failed to submit review", error_text)
+}
+
+/// Fields and update mask of an edit to a review; edited_at is always set
+fn review_edit_fields(score: Option<i32>, review: Option<&str>, now: DateTime<Utc>) -> (Value, Vec<&'static str>) {
+    let mut fields = json!({
+        "edited_at": {"timestampValue": now.to_rfc3339()}
+    });
+    let mut mask = vec!["edited_at"];
+    if let Some(s) = score {
+        fields["score"] = json!({"integerValue": s.to_string()});
+        mask.push("score");
+    }
+    if let Some(r) = review {
+        fields["review"] = json!({"stringValue": r});
+        mask.push("review");
+    }
+    (fields, mask)
+}
+
+/// A user's enabled_plugins document
+struct EnabledAppEntry {
+    app_id: String,
+    /// Webhook delivery paused after repeated failures
+    delivery_disabled: bool,
+    /// Scopes consented to; None for apps enabled before consent was recorded
+    granted_scopes: Option<Vec<AppScope>>,
+    /// Auth steps done; None for apps enabled before setup was tracked
+    setup_completed: Option<bool>,
+}
+
 /// Firestore REST API client
 pub struct FirestoreService {
     client: Client,
     project_id: String,
-    credentials: Option<ServiceAccountCredentials>,
-    cached_token: Arc<RwLock<Option<CachedToken>>>,
+    credentials: Option<GoogleCredentials>,
+    /// Access tokens keyed by project ID (one cache per routed environment)
+    cached_tokens: Arc<RwLock<HashMap<String, CachedToken>>>,
     /// Encryption secret for decrypting user data with enhanced protection level
     encryption_secret: Option<Vec<u8>>,
+    /// Approved public apps for marketplace listings
+    apps_cache: AppsCache,
+    /// Admin per-task LLM model overrides
+    model_overrides: ModelOverrides,
+    /// Latency tracking for hedged critical reads; None when hedging is off
+    hedged_reads: Option<HedgedReads>,
+    /// Set when running without Firestore (STORAGE=local); every call fails as unavailable
+    disabled: bool,
 }
 
 impl FirestoreService {
@@ -92,7 +454,7 @@ impl FirestoreService {
     pub async fn new(
         project_id: String,
         encryption_secret: Option<Vec<u8>>,
-    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Self, FirestoreError> {
         let client = Client::new();
 
         // Load service account credentials from GOOGLE_APPLICATION_CREDENTIALS
@@ -102,8 +464,12 @@ impl FirestoreService {
             client,
             project_id,
             credentials,
-            cached_token: Arc::new(RwLock::new(None)),
+            cached_tokens: Arc::new(RwLock::new(HashMap::new())),
             encryption_secret,
+            apps_cache: AppsCache::new(),
+            model_overrides: ModelOverrides::new(),
+            hedged_reads: None,
+            disabled: false,
         };
 
         // Pre-fetch an access token
@@ -114,8 +480,53 @@ impl FirestoreService {
         Ok(service)
     }
 
-    /// Load service account credentials from JSON file
-    fn load_credentials() -> Result<Option<ServiceAccountCredentials>, Box<dyn std::error::Error + Send + Sync>> {
+    /// A service that never reaches Firestore, for STORAGE=local. Loads no credentials,
+    /// and every call fails with `Unavailable`.
+    pub fn disabled(encryption_secret: Option<Vec<u8>>) -> Self {
+        Self {
+            client: Client::new(),
+            project_id: String::new(),
+            credentials: None,
+            cached_tokens: Arc::new(RwLock::new(HashMap::new())),
+            encryption_secret,
+            apps_cache: AppsCache::new(),
+            model_overrides: ModelOverrides::new(),
+            hedged_reads: None,
+            disabled: true,
+        }
+    }
+
+    /// Whether this service was built with `disabled` and never reaches Firestore
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Use a client with tuned pooling and keepalives, and hedge critical reads if enabled.
+    /// Keeps the default client if the tuned one can't be built.
+    pub fn with_http_tuning(mut self, tuning: &HttpTuning) -> Self {
+        match tuning.build_client() {
+            Ok(client) => self.client = client,
+            Err(e) => tracing::warn!("Failed to build tuned Firestore client, using defaults: {}", e),
+        }
+        self.hedged_reads = tuning.hedged_reads();
+        self
+    }
+
+    /// Send a latency-critical read, hedged when enabled
+    async fn send_critical_read(
+        &self,
+        path: &'static str,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        match &self.hedged_reads {
+            Some(hedged_reads) => hedged_reads.send(path, request).await,
+            None => request.send().await,
+        }
+    }
+
+    /// Load credentials from JSON file (service account, external account,
+    /// impersonated service account, or authorized user)
+    fn load_credentials() -> Result<Option<GoogleCredentials>, FirestoreError> {
         // Check GOOGLE_APPLICATION_CREDENTIALS environment variable
         let creds_path = match std::env::var("GOOGLE_APPLICATION_CREDENTIALS") {
             Ok(path) => path,
@@ -130,25 +541,27 @@ impl FirestoreService {
             }
         };
 
-        tracing::info!("Loading service account credentials from: {}", creds_path);
+        tracing::info!("Loading Google credentials from: {}", creds_path);
 
         let creds_json = std::fs::read_to_string(&creds_path)
             .map_err(|e| format!("Failed to read credentials file {}: {}", creds_path, e))?;
 
-        let credentials: ServiceAccountCredentials = serde_json::from_str(&creds_json)
+        let credentials: GoogleCredentials = serde_json::from_str(&creds_json)
             .map_err(|e| format!("Failed to parse credentials JSON: {}", e))?;
 
-        tracing::info!("Loaded credentials for service account: {}", credentials.client_email);
+        tracing::info!("Loaded credentials for {}", credentials.describe());
 
         Ok(Some(credentials))
     }
 
-    /// Get access token, using cache if valid or refreshing if needed
-    async fn get_access_token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    /// Get access token for the active project, using cache if valid or refreshing if needed
+    async fn get_access_token(&self) -> Result<String, FirestoreError> {
+        let project_id = self.project_id();
+
         // Check cached token
         {
-            let cache = self.cached_token.read().await;
-            if let Some(cached) = cache.as_ref() {
+            let cache = self.cached_tokens.read().await;
+            if let Some(cached) = cache.get(&project_id) {
                 let now = Utc::now().timestamp();
                 // Use token if it has at least 60 seconds left
                 if cached.expires_at > now + 60 {
@@ -158,26 +571,72 @@ impl FirestoreService {
         }
 
         // Need to refresh token
-        let token = self.fetch_new_access_token().await?;
+        let (token, expires_in) = self.fetch_new_access_token().await?;
 
-        // Cache it (tokens are valid for 1 hour, we'll refresh after 55 minutes)
+        // Cache it (treat as expired 5 minutes early)
         {
-            let mut cache = self.cached_token.write().await;
-            *cache = Some(CachedToken {
+            let mut cache = self.cached_tokens.write().await;
+            cache.insert(project_id, CachedToken {
                 token: token.clone(),
-                expires_at: Utc::now().timestamp() + 3300, // 55 minutes
+                expires_at: Utc::now().timestamp() + expires_in - 300,
             });
         }
 
         Ok(token)
     }
 
-    /// Fetch a new access token from Google OAuth
-    async fn fetch_new_access_token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Use service account credentials first (has full permissions)
+    /// Proactively renew cached access tokens that expire within `margin_secs`.
+    /// Returns the number of seconds until the next token enters that margin.
+    pub async fn refresh_expiring_tokens(
+        &self,
+        margin_secs: i64,
+    ) -> Result<i64, FirestoreError> {
+        let now = Utc::now().timestamp();
+
+        let expiring: Vec<String> = {
+            let cache = self.cached_tokens.read().await;
+            if cache.is_empty() {
+                // Startup fetch failed; seed the default project's token
+                vec![self.project_id.clone()]
+            } else {
+                cache
+                    .iter()
+                    .filter(|(_, t)| t.expires_at <= now + margin_secs)
+                    .map(|(project, _)| project.clone())
+                    .collect()
+            }
+        };
+
+        if !expiring.is_empty() {
+            // Tokens are per service account, not per project: one fetch covers all
+            let (token, expires_in) = self.fetch_new_access_token().await?;
+            let expires_at = Utc::now().timestamp() + expires_in - 300;
+            let mut cache = self.cached_tokens.write().await;
+            for project in &expiring {
+                cache.insert(project.clone(), CachedToken {
+                    token: token.clone(),
+                    expires_at,
+                });
+            }
+            tracing::info!("Proactively refreshed Firestore access token for {} project(s)", expiring.len());
+        }
+
+        let cache = self.cached_tokens.read().await;
+        let next_expiry = cache.values().map(|t| t.expires_at).min().unwrap_or(now);
+        Ok((next_expiry - margin_secs - Utc::now().timestamp()).max(0))
+    }
+
+    /// Fetch a new access token from Google OAuth.
+    /// Returns the token and its lifetime in seconds.
+    async fn fetch_new_access_token(&self) -> Result<(String, i64), FirestoreError> {
+        if self.disabled {
+            return Err(FirestoreError::Unavailable("Firestore is disabled (STORAGE=local)".to_string()));
+        }
+
+        // Use credentials file first (has full permissions)
         if let Some(creds) = &self.credentials {
-            let token = self.get_token_from_service_account(creds).await?;
-            tracing::info!("Got access token from service account");
+            let token = self.get_token_from_credentials(creds).await?;
+            tracing::info!("Got access token from {}", creds.describe());
             return Ok(token);
         }
 
@@ -191,7 +650,7 @@ impl FirestoreService {
     }
 
     /// Try to get token from GCP metadata server
-    async fn try_metadata_server(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    async fn try_metadata_server(&self) -> Result<(String, i64), FirestoreError> {
         let metadata_url =
             "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
 
@@ -206,26 +665,201 @@ impl FirestoreService {
             #[derive(Deserialize)]
             struct TokenResponse {
                 access_token: String,
+                #[serde(default = "default_token_lifetime")]
+                expires_in: i64,
             }
             let token: TokenResponse = response.json().await?;
-            return Ok(token.access_token);
+            return Ok((token.access_token, token.expires_in));
         }
 
         Err("Metadata server not available".into())
     }
 
+    /// Get access token for any supported credentials type.
+    /// Boxed because impersonation recurses into its source credentials.
+    fn get_token_from_credentials<'a>(
+        &'a self,
+        creds: &'a GoogleCredentials,
+    ) -> TokenFuture<'a> {
+        Box::pin(async move {
+            match creds {
+                GoogleCredentials::ServiceAccount(c) => self.get_token_from_service_account(c).await,
+                GoogleCredentials::ExternalAccount(c) => self.get_token_from_external_account(c).await,
+                GoogleCredentials::AuthorizedUser(c) => self.get_token_from_authorized_user(c).await,
+                GoogleCredentials::ImpersonatedServiceAccount(c) => {
+                    let (source_token, _) = self.get_token_from_credentials(&c.source_credentials).await?;
+                    self.impersonate_service_account(&source_token, &c.service_account_impersonation_url, &c.delegates)
+                        .await
+                }
+            }
+        })
+    }
+
+    /// Workload identity federation: exchange the external subject token at STS,
+    /// then optionally impersonate a service account with the federated token
+    async fn get_token_from_external_account(
+        &self,
+        creds: &ExternalAccountCredentials,
+    ) -> Result<(String, i64), FirestoreError> {
+        let subject_token = self.read_subject_token(&creds.credential_source).await?;
+        let scope = FIRESTORE_SCOPES.join(" ");
+
+        let response = self.client
+            .post(&creds.token_url)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:token-exchange"),
+                ("audience", creds.audience.as_str()),
+                ("scope", scope.as_str()),
+                ("requested_token_type", "urn:ietf:params:oauth:token-type:access_token"),
+                ("subject_token", subject_token.as_str()),
+                ("subject_token_type", creds.subject_token_type.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("STS token request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("STS token exchange failed: {}", error_text).into());
+        }
+
+        #[derive(Deserialize)]
+        struct StsResponse {
+            access_token: String,
+            #[serde(default = "default_token_lifetime")]
+            expires_in: i64,
+        }
+
+        let sts: StsResponse = response.json().await
+            .map_err(|e| format!("Failed to parse STS response: {}", e))?;
+
+        match &creds.service_account_impersonation_url {
+            Some(url) => self.impersonate_service_account(&sts.access_token, url, &[]).await,
+            None => Ok((sts.access_token, sts.expires_in)),
+        }
+    }
+
+    /// Read the external subject token from a file or URL credential source
+    async fn read_subject_token(
+        &self,
+        source: &CredentialSource,
+    ) -> Result<String, FirestoreError> {
+        let raw = if let Some(path) = &source.file {
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read subject token file {}: {}", path, e))?
+        } else if let Some(url) = &source.url {
+            let mut request = self.client.get(url);
+            for (name, value) in &source.headers {
+                request = request.header(name, value);
+            }
+            request.send().await?.error_for_status()?.text().await?
+        } else {
+            return Err("Unsupported credential_source (only file and url are supported)".into());
+        };
+
+        match &source.format {
+            Some(format) if format.format_type == "json" => {
+                let field = format.subject_token_field_name.as_deref()
+                    .ok_or("credential_source.format is json but subject_token_field_name is missing")?;
+                let value: Value = serde_json::from_str(&raw)?;
+                value.get(field)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .ok_or_else(|| format!("Subject token field '{}' not found", field).into())
+            }
+            _ => Ok(raw.trim().to_string()),
+        }
+    }
+
+    /// Exchange a source token for a service account token via the IAM credentials API
+    async fn impersonate_service_account(
+        &self,
+        source_token: &str,
+        impersonation_url: &str,
+        delegates: &[String],
+    ) -> Result<(String, i64), FirestoreError> {
+        let mut body = json!({
+            "scope": FIRESTORE_SCOPES,
+            "lifetime": "3600s"
+        });
+        if !delegates.is_empty() {
+            body["delegates"] = json!(delegates);
+        }
+
+        let response = self.client
+            .post(impersonation_url)
+            .bearer_auth(source_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Impersonation request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Service account impersonation failed: {}", error_text).into());
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GenerateAccessTokenResponse {
+            access_token: String,
+            expire_time: DateTime<Utc>,
+        }
+
+        let token: GenerateAccessTokenResponse = response.json().await
+            .map_err(|e| format!("Failed to parse impersonation response: {}", e))?;
+
+        let expires_in = (token.expire_time - Utc::now()).num_seconds();
+        Ok((token.access_token, expires_in))
+    }
+
+    /// Get access token from user credentials (OAuth2 refresh token flow)
+    async fn get_token_from_authorized_user(
+        &self,
+        creds: &AuthorizedUserCredentials,
+    ) -> Result<(String, i64), FirestoreError> {
+        let response = self.client
+            .post("https://oauth2.googleapis.com/token")
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", creds.client_id.as_str()),
+                ("client_secret", creds.client_secret.as_str()),
+                ("refresh_token", creds.refresh_token.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Refresh token exchange failed: {}", error_text).into());
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            #[serde(default = "default_token_lifetime")]
+            expires_in: i64,
+        }
+
+        let token: TokenResponse = response.json().await
+            .map_err(|e| format!("Failed to parse token response: {}", e))?;
+
+        Ok((token.access_token, token.expires_in))
+    }
+
     /// Get access token using service account credentials (OAuth2 JWT flow)
     async fn get_token_from_service_account(
         &self,
         creds: &ServiceAccountCredentials,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(String, i64), FirestoreError> {
         let now = Utc::now().timestamp();
         let token_uri = creds.token_uri.as_deref().unwrap_or("https://oauth2.googleapis.com/token");
 
         // Create JWT claims
         let claims = GoogleJwtClaims {
             iss: creds.client_email.clone(),
-            scope: "https://www.googleapis.com/auth/datastore https://www.googleapis.com/auth/cloud-platform".to_string(),
+            scope: FIRESTORE_SCOPES.join(" "),
             aud: token_uri.to_string(),
             iat: now,
             exp: now + 3600, // 1 hour
@@ -257,43 +891,134 @@ impl FirestoreService {
         #[derive(Deserialize)]
         struct TokenResponse {
             access_token: String,
+            #[serde(default = "default_token_lifetime")]
+            expires_in: i64,
         }
 
         let token_response: TokenResponse = response.json().await
             .map_err(|e| format!("Failed to parse token response: {}", e))?;
 
-        Ok(token_response.access_token)
+        Ok((token_response.access_token, token_response.expires_in))
     }
 
     /// Refresh access token (for manual refresh if needed)
-    pub async fn refresh_token(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn refresh_token(&self) -> Result<(), FirestoreError> {
         // Clear cache to force refresh
         {
-            let mut cache = self.cached_token.write().await;
-            *cache = None;
+            let mut cache = self.cached_tokens.write().await;
+            cache.remove(&self.project_id());
         }
         self.get_access_token().await?;
         Ok(())
     }
 
+    /// Project ID for the current call: the per-request override if one is
+    /// active, otherwise the project this service was created with
+    fn project_id(&self) -> String {
+        current_project_override().unwrap_or_else(|| self.project_id.clone())
+    }
+
     /// Build Firestore REST API base URL
     fn base_url(&self) -> String {
         format!(
             "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents",
-            self.project_id
+            self.project_id()
         )
     }
 
     /// Build request with auth header
-    async fn build_request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
+    async fn build_request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, FirestoreError> {
         let mut req = self.client.request(method, url);
         let token = self.get_access_token().await?;
         req = req.bearer_auth(token);
         Ok(req)
     }
 
+    // =========================================================================
+    // SELF-CHECK - Used by `--check` to gate deploys
+
+    /// Load and decode the configured Google credentials without using them.
+    /// Ok(None) when none are configured.
+    pub fn check_credentials() -> Result<Option<String>, FirestoreError> {
+        let Some(credentials) = Self::load_credentials()? else {
+            return Ok(None);
+        };
+        credentials.validate()?;
+        Ok(Some(credentials.describe()))
+    }
+
+    /// Fetch an access token and read one document, proving Firestore is reachable
+    pub async fn ping(&self) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}?pageSize=1&mask.fieldPaths=__name__", self.base_url(), USERS_COLLECTION);
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore ping failed", &error_text));
+        }
+        Ok(())
+    }
+
+    /// All composite indexes of the database
+    pub async fn list_composite_indexes(&self) -> Result<Vec<CompositeIndex>, FirestoreError> {
+        let base = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/collectionGroups/-/indexes",
+            self.project_id()
+        );
+        let mut indexes = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let url = match &page_token {
+                Some(token) => format!("{}?pageToken={}", base, urlencoding::encode(token)),
+                None => base.clone(),
+            };
+            let response = self.build_request(reqwest::Method::GET, &url).await?.send().await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Failed to list indexes", &error_text));
+            }
+            let body: Value = response.json().await?;
+
+            for index in body["indexes"].as_array().into_iter().flatten() {
+                // .../collectionGroups/{group}/indexes/{id}
+                let Some(collection_group) = index["name"].as_str().and_then(|n| n.split('/').rev().nth(2)) else {
+                    continue;
+                };
+                let fields = index["fields"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|f| {
+                        let path = f["fieldPath"].as_str()?;
+                        let mode = f["order"].as_str().or(f["arrayConfig"].as_str())?;
+                        Some((path.to_string(), mode.to_string()))
+                    })
+                    .collect();
+                indexes.push(CompositeIndex {
+                    collection_group: collection_group.to_string(),
+                    query_scope: index["queryScope"].as_str().unwrap_or("COLLECTION").to_string(),
+                    fields,
+                    state: index["state"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+
+            page_token = body["nextPageToken"].as_str().filter(|t| !t.is_empty()).map(String::from);
+            if page_token.is_none() {
+                break;
+            }
+        }
+        Ok(indexes)
+    }
+
     /// Build authenticated request for GCE Compute Engine API (public for agent routes)
-    pub async fn build_compute_request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn build_compute_request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, FirestoreError> {
         self.build_request(method, url).await
     }
 
@@ -312,15 +1037,15 @@ impl FirestoreService {
         total: i64,
         cost: f64,
         account: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
         let date_key = Utc::now().format("%Y-%m-%d").to_string();
         let doc_path = format!(
             "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-            self.project_id, USERS_COLLECTION, uid, LLM_USAGE_SUBCOLLECTION, date_key
+            self.project_id(), USERS_COLLECTION, uid, LLM_USAGE_SUBCOLLECTION, date_key
         );
         let commit_url = format!(
             "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-            self.project_id
+            self.project_id()
         );
         // Write to account-specific prefix (e.g. "desktop_chat_omi" or "desktop_chat_personal")
         // Also continue writing to "desktop_chat" for backward compat with existing queries
@@ -364,7 +1089,7 @@ impl FirestoreService {
     pub async fn get_total_llm_cost(
         &self,
         uid: &str,
-    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<f64, FirestoreError> {
         let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
         let query = json!({
             "structuredQuery": {
@@ -380,8 +1105,9 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore query failed: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query failed", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
@@ -420,7 +1146,7 @@ impl FirestoreService {
         folder_id: Option<&str>,
         start_date: Option<&str>,
         end_date: Option<&str>,
-    ) -> Result<Vec<Conversation>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Vec<Conversation>, FirestoreError> {
         // Build filters array (match Python behavior)
         let mut filters: Vec<Value> = Vec::new();
 
@@ -548,17 +1274,18 @@ impl FirestoreService {
         }
 
         let results: Vec<Value> = response.json().await?;
-        let conversations: Vec<Conversation> = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| match self.parse_conversation(d, uid) {
-                        Ok(conv) => Some(conv),
-                        Err(e) => {
-                            tracing::warn!("Failed to parse conversation: {}", e);
-                            None
-                        }
-                    })
+        let mut docs: Vec<Value> = results.into_iter().filter_map(|r| r.get("document").cloned()).collect();
+        for doc in docs.iter_mut() {
+            self.load_transcript_chunks(doc).await;
+        }
+        let conversations: Vec<Conversation> = docs
+            .iter()
+            .filter_map(|d| match self.parse_conversation(d, uid) {
+                Ok(conv) => Some(conv),
+                Err(e) => {
+                    tracing::warn!("Failed to parse conversation: {}", e);
+                    None
+                }
             })
             .collect();
 
@@ -566,32 +1293,113 @@ impl FirestoreService {
         Ok(conversations)
     }
 
-    /// Get count of conversations for a user using Firestore aggregation query
-    pub async fn get_conversations_count(
+    /// Every conversation (discarded included) created before `end_date`, oldest first,
+    /// streamed a page at a time with transcripts loaded
+    pub fn stream_conversations_before(
         &self,
         uid: &str,
-        include_discarded: bool,
-        statuses: &[String],
-    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!(
-            "{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid
-        );
-
-        // Build filters (same as get_conversations)
-        let mut filters: Vec<Value> = Vec::new();
-
-        if !include_discarded {
-            filters.push(json!({
+        end_date: &str,
+        page_size: usize,
+    ) -> DocumentStream<'_, Conversation> {
+        let uid = uid.to_string();
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "from": [{"collectionId": CONVERSATIONS_SUBCOLLECTION}],
+            "where": {
                 "fieldFilter": {
-                    "field": {"fieldPath": "discarded"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": false}
+                    "field": {"fieldPath": "created_at"},
+                    "op": "LESS_THAN",
+                    "value": {"timestampValue": end_date}
                 }
-            }));
-        }
+            },
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "ASCENDING"}]
+        });
+
+        Box::pin(async_stream::try_stream! {
+            let mut docs = self.stream_query(&parent, query, page_size);
+            while let Some(mut doc) = futures::TryStreamExt::try_next(&mut docs).await? {
+                self.load_transcript_chunks(&mut doc).await;
+                match self.parse_conversation(&doc, &uid) {
+                    Ok(conversation) => yield conversation,
+                    Err(e) => tracing::warn!("Failed to parse conversation: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Discarded conversations that carry a discard_reason (auto-discarded), newest first
+    pub async fn get_auto_discarded_conversations(
+        &self,
+        uid: &str,
+        limit: usize,
+    ) -> Result<Vec<Conversation>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": CONVERSATIONS_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "discarded"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query failed", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut docs: Vec<Value> = results.into_iter().filter_map(|r| r.get("document").cloned()).collect();
+        for doc in docs.iter_mut() {
+            self.load_transcript_chunks(doc).await;
+        }
+        Ok(docs
+            .iter()
+            .filter_map(|d| self.parse_conversation(d, uid).ok())
+            .filter(|c| c.discard_reason.is_some())
+            .collect())
+    }
+
+    /// Get count of conversations for a user using Firestore aggregation query
+    pub async fn get_conversations_count(
+        &self,
+        uid: &str,
+        include_discarded: bool,
+        statuses: &[String],
+    ) -> Result<i64, FirestoreError> {
+        let parent = format!(
+            "{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid
+        );
+
+        // Build filters (same as get_conversations)
+        let mut filters: Vec<Value> = Vec::new();
+
+        if !include_discarded {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "discarded"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": false}
+                }
+            }));
+        }
 
         if !statuses.is_empty() {
             filters.push(json!({
@@ -669,12 +1477,92 @@ impl FirestoreService {
         Ok(count)
     }
 
+    /// Number of non-discarded conversations started in [start, end) and their summed
+    /// talk time in seconds, from one aggregation query
+    pub async fn get_conversation_totals_between(
+        &self,
+        uid: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(i64, f64), FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let query = json!({
+            "structuredAggregationQuery": {
+                "structuredQuery": {
+                    "from": [{"collectionId": CONVERSATIONS_SUBCOLLECTION}],
+                    "where": {
+                        "compositeFilter": {
+                            "op": "AND",
+                            "filters": [
+                                {"fieldFilter": {
+                                    "field": {"fieldPath": "discarded"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": false}
+                                }},
+                                {"fieldFilter": {
+                                    "field": {"fieldPath": "started_at"},
+                                    "op": "GREATER_THAN_OR_EQUAL",
+                                    "value": {"timestampValue": start.to_rfc3339()}
+                                }},
+                                {"fieldFilter": {
+                                    "field": {"fieldPath": "started_at"},
+                                    "op": "LESS_THAN",
+                                    "value": {"timestampValue": end.to_rfc3339()}
+                                }}
+                            ]
+                        }
+                    }
+                },
+                "aggregations": [
+                    {"alias": "count", "count": {}},
+                    {"alias": "talk_seconds", "sum": {"field": {"fieldPath": "analytics.total_talk_time_seconds"}}}
+                ]
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runAggregationQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore aggregation query failed", &error_text));
+        }
+
+        // Response format: [{"result": {"aggregateFields": {"count": {"integerValue": "3"},
+        //                   "talk_seconds": {"doubleValue": 512.5}}}}]
+        let results: Vec<Value> = response.json().await?;
+        let fields = results
+            .first()
+            .and_then(|r| r.get("result"))
+            .and_then(|r| r.get("aggregateFields"))
+            .cloned()
+            .unwrap_or_default();
+        let count = fields
+            .get("count")
+            .and_then(|c| c.get("integerValue"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or(0);
+        // A sum over only integers comes back as integerValue
+        let talk_seconds = self
+            .parse_float(&fields, "talk_seconds")
+            .or_else(|| self.parse_int(&fields, "talk_seconds").map(f64::from))
+            .unwrap_or(0.0);
+        Ok((count, talk_seconds))
+    }
+
     /// Get a single conversation
     pub async fn get_conversation(
         &self,
         uid: &str,
         conversation_id: &str,
-    ) -> Result<Option<Conversation>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<Conversation>, FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
@@ -684,22 +1572,21 @@ impl FirestoreService {
             conversation_id
         );
 
-        let response = self
-            .build_request(reqwest::Method::GET, &url)
-            .await?
-            .send()
-            .await?;
+        let request = self.build_request(reqwest::Method::GET, &url).await?;
+        let response = self.send_critical_read("get_conversation", request).await?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Ok(None);
         }
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
+        let mut doc: Value = response.json().await?;
+        self.load_transcript_chunks(&mut doc).await;
         let conversation = self.parse_conversation(&doc, uid)?;
         Ok(Some(conversation))
     }
@@ -709,7 +1596,7 @@ impl FirestoreService {
         &self,
         uid: &str,
         conversation: &Conversation,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
@@ -719,7 +1606,11 @@ impl FirestoreService {
             conversation.id
         );
 
-        let doc = self.conversation_to_firestore(conversation, uid);
+        let (doc, chunks) = self.conversation_to_firestore(conversation, uid);
+        // Chunks go first so the document never counts chunks that don't exist yet
+        if !chunks.is_empty() {
+            self.write_transcript_chunks(uid, &conversation.id, &chunks).await?;
+        }
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -729,8 +1620,9 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore save error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore save error", &error_text));
         }
 
         tracing::info!("Saved conversation {} for user {}", conversation.id, uid);
@@ -744,7 +1636,7 @@ impl FirestoreService {
         conversation_id: &str,
         app_id: &str,
         content: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
         // First get the current conversation to append to apps_results
         let current = self.get_conversation(uid, conversation_id).await?;
         let mut apps_results = current
@@ -760,7 +1652,7 @@ impl FirestoreService {
 
         // Build the update document
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=apps_results",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=apps_results&updateMask.fieldPaths=updated_at",
             self.base_url(),
             USERS_COLLECTION,
             uid,
@@ -784,6 +1676,7 @@ impl FirestoreService {
 
         let doc = json!({
             "fields": {
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
                 "apps_results": {
                     "arrayValue": {
                         "values": apps_results_value
@@ -800,23 +1693,24 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
         tracing::info!("Added app result for app {} to conversation {}", app_id, conversation_id);
         Ok(())
     }
 
-    /// Set the starred status of a conversation
-    pub async fn set_conversation_starred(
+    /// Replace the structured events of a conversation
+    pub async fn update_conversation_events(
         &self,
         uid: &str,
         conversation_id: &str,
-        starred: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        events: &[crate::models::Event],
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=starred",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=structured.events&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
             self.base_url(),
             USERS_COLLECTION,
             uid,
@@ -824,9 +1718,17 @@ impl FirestoreService {
             conversation_id
         );
 
+        let events_values: Vec<Value> = events.iter().map(Self::event_to_value).collect();
         let doc = json!({
             "fields": {
-                "starred": {"booleanValue": starred}
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "structured": {
+                    "mapValue": {
+                        "fields": {
+                            "events": {"arrayValue": {"values": events_values}}
+                        }
+                    }
+                }
             }
         });
 
@@ -838,28 +1740,31 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
         tracing::info!(
-            "Set conversation {} starred={} for user {}",
+            "Updated {} events on conversation {} for user {}",
+            events.len(),
             conversation_id,
-            starred,
             uid
         );
         Ok(())
     }
 
-    /// Set the visibility of a conversation (for sharing)
-    pub async fn set_conversation_visibility(
+    /// Overwrite the LLM summary fields (title, overview, emoji, category), keeping
+    /// action items and events; `model` is recorded as generated_by.summary
+    pub async fn update_conversation_summary(
         &self,
         uid: &str,
         conversation_id: &str,
-        visibility: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        structured: &Structured,
+        model: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=structured.title&updateMask.fieldPaths=structured.overview&updateMask.fieldPaths=structured.emoji&updateMask.fieldPaths=structured.category&updateMask.fieldPaths=context_line&updateMask.fieldPaths=generated_by.summary&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
             self.base_url(),
             USERS_COLLECTION,
             uid,
@@ -867,9 +1772,22 @@ impl FirestoreService {
             conversation_id
         );
 
+        let category = format!("{:?}", structured.category).to_lowercase();
         let doc = json!({
             "fields": {
-                "visibility": {"stringValue": visibility}
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "context_line": {"stringValue": structured.context_line()},
+                "generated_by": {"mapValue": {"fields": {"summary": {"stringValue": model}}}},
+                "structured": {
+                    "mapValue": {
+                        "fields": {
+                            "title": {"stringValue": structured.title},
+                            "overview": {"stringValue": structured.overview},
+                            "emoji": {"stringValue": structured.emoji},
+                            "category": {"stringValue": category}
+                        }
+                    }
+                }
             }
         });
 
@@ -881,27 +1799,24 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        tracing::info!(
-            "Set conversation {} visibility='{}' for user {}",
-            conversation_id,
-            visibility,
-            uid
-        );
         Ok(())
     }
 
-    /// Delete a conversation
-    pub async fn delete_conversation(
+    /// Write fields derived from the transcript (detected languages and analytics)
+    pub async fn set_conversation_derived_fields(
         &self,
         uid: &str,
         conversation_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        detected_languages: &[String],
+        analytics: Option<&ConversationAnalytics>,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=detected_languages&updateMask.fieldPaths=analytics&currentDocument.exists=true",
             self.base_url(),
             USERS_COLLECTION,
             uid,
@@ -909,30 +1824,42 @@ impl FirestoreService {
             conversation_id
         );
 
+        let languages: Vec<Value> = detected_languages
+            .iter()
+            .map(|l| json!({"stringValue": l}))
+            .collect();
+        let mut fields = json!({
+            "detected_languages": {"arrayValue": {"values": languages}}
+        });
+        if let Some(analytics) = analytics {
+            fields["analytics"] = Self::conversation_analytics_to_value(analytics);
+        }
+
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        tracing::info!("Deleted conversation {} for user {}", conversation_id, uid);
         Ok(())
     }
 
-    /// Update a conversation's title
-    pub async fn update_conversation_title(
+    /// Replace the structured action items of a conversation
+    pub async fn update_conversation_action_items(
         &self,
         uid: &str,
         conversation_id: &str,
-        title: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        action_items: &[crate::models::ActionItem],
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=structured.title",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=structured.action_items&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
             self.base_url(),
             USERS_COLLECTION,
             uid,
@@ -940,12 +1867,14 @@ impl FirestoreService {
             conversation_id
         );
 
+        let values: Vec<Value> = action_items.iter().map(Self::structured_action_item_to_value).collect();
         let doc = json!({
             "fields": {
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
                 "structured": {
                     "mapValue": {
                         "fields": {
-                            "title": {"stringValue": title}
+                            "action_items": {"arrayValue": {"values": values}}
                         }
                     }
                 }
@@ -960,274 +1889,162 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        Ok(())
+    }
+
+    /// Set the starred status of a conversation
+    pub async fn set_conversation_starred(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        starred: bool,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=starred&updateMask.fieldPaths=updated_at",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "starred": {"booleanValue": starred}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
         tracing::info!(
-            "Updated conversation {} title for user {}",
+            "Set conversation {} starred={} for user {}",
             conversation_id,
+            starred,
             uid
         );
         Ok(())
     }
 
-    // =========================================================================
-    // MEMORIES
-    // =========================================================================
-
-    /// Get memories for a user with optional filtering
-    /// Copied from Python get_memories
-    /// Enriches memories with source from linked conversations
-    pub async fn get_memories_filtered(
+    /// Set whether memories may be extracted from a conversation
+    pub async fn set_conversation_memory_extraction_disabled(
         &self,
         uid: &str,
-        limit: usize,
-        offset: usize,
-        category: Option<&str>,
-        tags: Option<&[String]>,
-        include_dismissed: bool,
-    ) -> Result<Vec<MemoryDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        conversation_id: &str,
+        disabled: bool,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=memory_extraction_disabled&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
+        );
 
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
+        let doc = json!({
+            "fields": {
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "memory_extraction_disabled": {"booleanValue": disabled}
+            }
+        });
 
-        // Filter by category if specified
-        if let Some(cat) = category {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "category"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": cat}
-                }
-            }));
-        }
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-        // Filter by first tag in Firestore (ARRAY_CONTAINS supports one tag per query).
-        // Additional tags (if any) are still filtered in-memory below.
-        if let Some(filter_tags) = tags {
-            if let Some(first_tag) = filter_tags.first() {
-                filters.push(json!({
-                    "fieldFilter": {
-                        "field": {"fieldPath": "tags"},
-                        "op": "ARRAY_CONTAINS",
-                        "value": {"stringValue": first_tag}
-                    }
-                }));
-            }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        // NOTE: We do NOT filter is_dismissed in Firestore query because existing memories
-        // don't have this field. Firestore only returns documents where the field EXISTS and
-        // matches the value. Instead, we filter in-memory below (matching Python behavior).
-
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
-
-        // Fetch from Firestore in a loop to handle post-query filtering (rejected, dismissed, tags).
-        // These can't be reliably filtered in Firestore (fields may not exist on all docs),
-        // so we filter in Rust. Keep fetching until we have enough or Firestore is exhausted.
-        let order_by = json!([
-            {"field": {"fieldPath": "scoring"}, "direction": "DESCENDING"},
-            {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
-        ]);
-        let mut memories: Vec<MemoryDB> = Vec::new();
-        let mut current_offset = offset;
-        let fetch_batch = limit.max(500);
-
-        loop {
-            let mut structured_query = json!({
-                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
-                "orderBy": order_by.clone(),
-                "limit": fetch_batch,
-                "offset": current_offset
-            });
-
-            if let Some(ref where_filter) = where_clause {
-                structured_query["where"] = where_filter.clone();
-            }
-
-            let query = json!({
-                "structuredQuery": structured_query
-            });
-
-            let response = self
-                .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-                .await?
-                .json(&query)
-                .send()
-                .await?;
-
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                tracing::error!("Firestore query error: {}", error_text);
-                break;
-            }
-
-            let results: Vec<Value> = response.json().await?;
-            let fetched_count = results.iter().filter(|doc| doc.get("document").is_some()).count();
-
-            let batch: Vec<MemoryDB> = results
-                .into_iter()
-                .filter_map(|doc| {
-                    doc.get("document")
-                        .and_then(|d| self.parse_memory(d, uid).ok())
-                })
-                // Filter out rejected memories (matches Python behavior)
-                .filter(|m| m.user_review != Some(false))
-                // Filter out dismissed memories in-memory (not in Firestore query, since existing
-                // memories don't have is_dismissed field - Firestore requires field to exist for filters)
-                .filter(|m| include_dismissed || !m.is_dismissed)
-                // Filter by remaining tags in-memory (first tag is already filtered by Firestore ARRAY_CONTAINS)
-                .filter(|m| {
-                    match tags {
-                        Some(filter_tags) if filter_tags.len() > 1 => {
-                            filter_tags[1..].iter().all(|tag| m.tags.contains(tag))
-                        }
-                        _ => true,
-                    }
-                })
-                .collect();
-
-            memories.extend(batch);
-            current_offset += fetched_count;
-
-            // Stop if Firestore returned fewer than requested (no more data)
-            if fetched_count < fetch_batch {
-                break;
-            }
-
-            // Stop if we have enough items
-            if memories.len() >= limit {
-                memories.truncate(limit);
-                break;
-            }
-        }
-
-        // Enrich memories with source from linked conversations
-        self.enrich_memories_with_source(uid, &mut memories).await;
-
-        Ok(memories)
-    }
-
-    /// Get memories for a user (simple version for backward compatibility)
-    /// Copied from Python get_memories
-    /// Enriches memories with source from linked conversations
-    pub async fn get_memories(
-        &self,
-        uid: &str,
-        limit: usize,
-    ) -> Result<Vec<MemoryDB>, Box<dyn std::error::Error + Send + Sync>> {
-        self.get_memories_filtered(uid, limit, 0, None, None, false).await
-    }
-
-    /// Batch fetch conversations and populate source and input_device_name fields on memories
-    async fn enrich_memories_with_source(&self, uid: &str, memories: &mut [MemoryDB]) {
-        use std::collections::{HashMap, HashSet};
-
-        // Collect unique conversation IDs
-        let conversation_ids: HashSet<&str> = memories
-            .iter()
-            .filter_map(|m| m.conversation_id.as_deref())
-            .collect();
-
-        if conversation_ids.is_empty() {
-            return;
-        }
-
-        // Fetch conversations in parallel (limit to avoid too many concurrent requests)
-        // Store both source and input_device_name
-        let mut source_map: HashMap<String, (String, Option<String>)> = HashMap::new();
-
-        // Batch fetch - fetch up to 10 at a time
-        let ids: Vec<&str> = conversation_ids.into_iter().collect();
-        for chunk in ids.chunks(10) {
-            let futures: Vec<_> = chunk
-                .iter()
-                .map(|id| self.get_conversation(uid, id))
-                .collect();
-
-            let results = futures::future::join_all(futures).await;
-
-            for (id, result) in chunk.iter().zip(results) {
-                if let Ok(Some(conv)) = result {
-                    let source_str = format!("{:?}", conv.source).to_lowercase();
-                    source_map.insert(id.to_string(), (source_str, conv.input_device_name.clone()));
-                }
-            }
-        }
-
-        // Populate source and input_device_name fields on memories
-        for memory in memories.iter_mut() {
-            if let Some(conv_id) = &memory.conversation_id {
-                if let Some((source, device_name)) = source_map.get(conv_id) {
-                    memory.source = Some(source.clone());
-                    memory.input_device_name = device_name.clone();
-                }
-            }
-        }
+        tracing::info!(
+            "Set conversation {} memory_extraction_disabled={} for user {}",
+            conversation_id,
+            disabled,
+            uid
+        );
+        Ok(())
     }
 
-    /// Get a single memory by ID
-    pub async fn get_memory(
+    /// Set the visibility of a conversation (for sharing)
+    pub async fn set_conversation_visibility(
         &self,
         uid: &str,
-        memory_id: &str,
-    ) -> Result<Option<MemoryDB>, Box<dyn std::error::Error + Send + Sync>> {
+        conversation_id: &str,
+        visibility: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility&updateMask.fieldPaths=updated_at",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
 
+        let doc = json!({
+            "fields": {
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "visibility": {"stringValue": visibility}
+            }
+        });
+
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&doc)
             .send()
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Ok(None);
-        }
-
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        let memory = self.parse_memory(&doc, uid)?;
-        Ok(Some(memory))
+        tracing::info!(
+            "Set conversation {} visibility='{}' for user {}",
+            conversation_id,
+            visibility,
+            uid
+        );
+        Ok(())
     }
 
-    /// Delete a memory by ID
-    pub async fn delete_memory(
+    /// Delete a conversation
+    pub async fn delete_conversation(
         &self,
         uid: &str,
-        memory_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        conversation_id: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
 
         let response = self
@@ -1237,116 +2054,134 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        tracing::info!("Deleted memory {} for user {}", memory_id, uid);
+        // Firestore doesn't cascade deletes to subcollections
+        if let Err(e) = self.delete_conversation_edits(uid, conversation_id, None).await {
+            tracing::warn!("Failed to delete edit history of conversation {}: {}", conversation_id, e);
+        }
+        if let Err(e) = self.delete_transcript_chunks(uid, conversation_id).await {
+            tracing::warn!("Failed to delete transcript chunks of conversation {}: {}", conversation_id, e);
+        }
+        self.record_deletion(uid, CONVERSATIONS_SUBCOLLECTION, conversation_id).await;
+        tracing::info!("Deleted conversation {} for user {}", conversation_id, uid);
         Ok(())
     }
 
-    /// Update memory content
-    pub async fn update_memory_content(
+    /// A conversation's processing lock (expired or not) and the document's `updateTime`,
+    /// for use as a [`Precondition::UpdateTime`] when changing it. None if the conversation
+    /// doesn't exist.
+    pub async fn get_conversation_lock(
         &self,
         uid: &str,
-        memory_id: &str,
-        content: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        conversation_id: &str,
+    ) -> Result<Option<(Option<ConversationLock>, String)>, FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=content&updateMask.fieldPaths=updated_at",
+            "{}/{}/{}/{}/{}?mask.fieldPaths=locked_by&mask.fieldPaths=locked_until",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
 
-        let now = Utc::now();
-        let doc = json!({
-            "fields": {
-                "content": {"stringValue": content},
-                "updated_at": {"timestampValue": now.to_rfc3339()}
-            }
-        });
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        tracing::info!("Updated memory content {} for user {}", memory_id, uid);
-        Ok(())
+        let doc: Value = response.json().await?;
+        let update_time = doc
+            .get("updateTime")
+            .and_then(|t| t.as_str())
+            .ok_or("Missing updateTime")?
+            .to_string();
+        // A masked read omits `fields` entirely when none of the fields are set
+        let lock = doc.get("fields").and_then(|fields| {
+            Some(ConversationLock {
+                locked_by: self.parse_string(fields, "locked_by")?,
+                locked_until: self.parse_timestamp_optional(fields, "locked_until")?,
+            })
+        });
+        Ok(Some((lock, update_time)))
     }
 
-    /// Update memory visibility
-    pub async fn update_memory_visibility(
+    /// Set (Some) or clear (None) a conversation's processing lock, only if the document
+    /// is unchanged since `update_time`. Fails with [`FirestoreError::FailedPrecondition`] otherwise.
+    pub async fn set_conversation_lock(
         &self,
         uid: &str,
-        memory_id: &str,
-        visibility: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility&updateMask.fieldPaths=updated_at",
-            self.base_url(),
+        conversation_id: &str,
+        lock: Option<&ConversationLock>,
+        update_time: &str,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
             USERS_COLLECTION,
             uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
-
-        let now = Utc::now();
-        let doc = json!({
-            "fields": {
-                "visibility": {"stringValue": visibility},
-                "updated_at": {"timestampValue": now.to_rfc3339()}
-            }
-        });
-
-        let response = self
-            .build_request(reqwest::Method::PATCH, &url)
-            .await?
-            .json(&doc)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
-        }
-
-        tracing::info!("Updated memory visibility {} for user {}", memory_id, uid);
-        Ok(())
+        // Masked fields missing from the body are removed
+        let fields = match lock {
+            Some(lock) => json!({
+                "locked_by": {"stringValue": lock.locked_by},
+                "locked_until": {"timestampValue": lock.locked_until.to_rfc3339()}
+            }),
+            None => json!({}),
+        };
+        self.patch_document_fields_if(
+            &doc_name,
+            fields,
+            &["locked_by", "locked_until"],
+            &Precondition::UpdateTime(update_time.to_string()),
+        )
+        .await
     }
 
-    /// Review a memory (approve/reject)
-    pub async fn review_memory(
+    /// Update a conversation's title
+    pub async fn update_conversation_title(
         &self,
         uid: &str,
-        memory_id: &str,
-        value: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        conversation_id: &str,
+        title: &str,
+    ) -> Result<(), FirestoreError> {
+        // context_line is in the mask but not the body, so it's cleared (chat context
+        // falls back to building it from structured until the next full write)
         let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=reviewed&updateMask.fieldPaths=user_review&updateMask.fieldPaths=updated_at",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=structured.title&updateMask.fieldPaths=context_line&updateMask.fieldPaths=updated_at",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
 
-        let now = Utc::now();
         let doc = json!({
             "fields": {
-                "reviewed": {"booleanValue": true},
-                "user_review": {"booleanValue": value},
-                "updated_at": {"timestampValue": now.to_rfc3339()}
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()},
+                "structured": {
+                    "mapValue": {
+                        "fields": {
+                            "title": {"stringValue": title}
+                        }
+                    }
+                }
             }
         });
 
@@ -1358,907 +2193,883 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        tracing::info!("Reviewed memory {} for user {} with value {}", memory_id, uid, value);
+        tracing::info!(
+            "Updated conversation {} title for user {}",
+            conversation_id,
+            uid
+        );
         Ok(())
     }
 
-    /// Create a memory (manual or extracted)
-    pub async fn create_memory(
-        &self,
-        uid: &str,
-        content: &str,
-        visibility: &str,
-        category: Option<MemoryCategory>,
-        confidence: Option<f64>,
-        source_app: Option<&str>,
-        context_summary: Option<&str>,
-        tags: &[String],
-        reasoning: Option<&str>,
-        current_activity: Option<&str>,
-        source: Option<&str>,
-        window_title: Option<&str>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let memory_id = document_id_from_seed(content);
-        let now = Utc::now();
+    // =========================================================================
+    // CONVERSATION EDITS - Previous values of overwritten conversation fields
+    // =========================================================================
 
-        // Determine if this is a manual memory
-        let is_manual = category.is_none() || matches!(category, Some(MemoryCategory::Manual));
-        let actual_category = category.unwrap_or(MemoryCategory::Manual);
-        let scoring = MemoryDB::calculate_scoring(&actual_category, &now, is_manual);
+    fn conversation_edit_doc_name(&self, uid: &str, conversation_id: &str, edit_id: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.conversation_doc_name(uid, conversation_id),
+            CONVERSATION_EDITS_SUBCOLLECTION,
+            edit_id
+        )
+    }
 
-        let category_str = match actual_category {
-            MemoryCategory::System => "system",
-            MemoryCategory::Interesting => "interesting",
-            MemoryCategory::Manual => "manual",
-            // Legacy categories - preserve original value
-            MemoryCategory::Core => "core",
-            MemoryCategory::Hobbies => "hobbies",
-            MemoryCategory::Lifestyle => "lifestyle",
-            MemoryCategory::Interests => "interests",
+    /// Compress an edit's previous value like transcripts are: encrypted for enhanced
+    /// protection, bytes otherwise
+    fn encode_edit_value(&self, uid: &str, value: &Value) -> Value {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let _ = encoder.write_all(value.to_string().as_bytes());
+        let compressed_bytes = encoder.finish().unwrap_or_default();
+        if let Some(ref secret) = self.encryption_secret {
+            match encryption::encrypt(&hex::encode(&compressed_bytes), uid, secret) {
+                Ok(encrypted) => return json!({"stringValue": encrypted}),
+                Err(e) => tracing::warn!("Failed to encrypt conversation edit: {}, storing compressed bytes", e),
+            }
+        }
+        json!({"bytesValue": base64::engine::general_purpose::STANDARD.encode(&compressed_bytes)})
+    }
+
+    fn decode_edit_value(&self, uid: &str, field: &Value) -> Option<Value> {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let compressed_bytes = if let Some(encrypted) = field.get("stringValue").and_then(|v| v.as_str()) {
+            let secret = self.encryption_secret.as_ref()?;
+            hex::decode(encryption::decrypt(encrypted, uid, secret).ok()?).ok()?
+        } else {
+            let b64 = field.get("bytesValue").and_then(|v| v.as_str())?;
+            base64::engine::general_purpose::STANDARD.decode(b64).ok()?
         };
+        let mut decompressed = String::new();
+        ZlibDecoder::new(&compressed_bytes[..]).read_to_string(&mut decompressed).ok()?;
+        serde_json::from_str(&decompressed).ok()
+    }
 
+    /// Store one entry of a conversation's edit history
+    pub async fn add_conversation_edit(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        edit: &ConversationEdit,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id
+            "https://firestore.googleapis.com/v1/{}",
+            self.conversation_edit_doc_name(uid, conversation_id, &edit.id)
         );
-
-        // Build tags array for Firestore
-        let tags_values: Vec<Value> = tags
-            .iter()
-            .map(|t| json!({"stringValue": t}))
-            .collect();
-
-        // Build fields - always include base fields
-        // CRITICAL: Include all fields that Python expects (matching save_memories)
         let mut fields = json!({
-            // CRITICAL: id field required - Python model requires this
-            "id": {"stringValue": &memory_id},
-            // CRITICAL: uid field required - Python model requires this
-            "uid": {"stringValue": uid},
-            "content": {"stringValue": content},
-            "category": {"stringValue": category_str},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()},
-            "reviewed": {"booleanValue": is_manual},
-            "user_review": {"booleanValue": is_manual},
-            "visibility": {"stringValue": visibility},
-            "manually_added": {"booleanValue": is_manual},
-            "scoring": {"stringValue": scoring},
-            "is_read": {"booleanValue": false},
-            "is_dismissed": {"booleanValue": false},
-            // Additional fields for Python compatibility
-            "edited": {"booleanValue": false},
-            "is_locked": {"booleanValue": false},
-            "kg_extracted": {"booleanValue": false},
-            "tags": {"arrayValue": {"values": tags_values}}
+            "field": {"stringValue": edit.field.as_str()},
+            "previous_value": self.encode_edit_value(uid, &edit.previous_value),
+            "edited_by": {"stringValue": edit.edited_by},
+            "source": {"stringValue": edit.source},
+            "created_at": {"timestampValue": edit.created_at.to_rfc3339()}
         });
-
-        // Add optional fields if present
-        if let Some(conf) = confidence {
-            fields["confidence"] = json!({"doubleValue": conf});
-        }
-        if let Some(app) = source_app {
-            fields["source_app"] = json!({"stringValue": app});
-        }
-        if let Some(summary) = context_summary {
-            fields["context_summary"] = json!({"stringValue": summary});
-        }
-        if let Some(reason) = reasoning {
-            fields["reasoning"] = json!({"stringValue": reason});
-        }
-        if let Some(activity) = current_activity {
-            fields["current_activity"] = json!({"stringValue": activity});
-        }
-        if let Some(src) = source {
-            fields["source"] = json!({"stringValue": src});
-        }
-        if let Some(wt) = window_title {
-            fields["window_title"] = json!({"stringValue": wt});
+        if let Some(restored_from) = &edit.restored_from {
+            fields["restored_from"] = json!({"stringValue": restored_from});
         }
 
-        let doc = json!({ "fields": fields });
-
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        tracing::info!("Created memory {} for user {} (category: {})", memory_id, uid, category_str);
-        Ok(memory_id)
+        tracing::info!(
+            "Recorded {} edit {} on conversation {} for user {}",
+            edit.field.as_str(),
+            edit.id,
+            conversation_id,
+            uid
+        );
+        Ok(())
     }
 
-    /// Create a manual memory (convenience wrapper)
-    pub async fn create_manual_memory(
+    /// A conversation's edit history, newest first
+    pub async fn get_conversation_edits(
         &self,
         uid: &str,
-        content: &str,
-        visibility: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        self.create_memory(uid, content, visibility, None, None, None, None, &[], None, None, None, None).await
+        conversation_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ConversationEdit>, FirestoreError> {
+        let parent = format!(
+            "https://firestore.googleapis.com/v1/{}",
+            self.conversation_doc_name(uid, conversation_id)
+        );
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": CONVERSATION_EDITS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit
+            }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs.iter().filter_map(|doc| self.parse_conversation_edit(uid, doc)).collect())
     }
 
-    /// Update memory read/dismissed status
-    pub async fn update_memory_read_status(
+    /// One edit of a conversation, or None if it doesn't exist
+    pub async fn get_conversation_edit(
         &self,
         uid: &str,
-        memory_id: &str,
-        is_read: Option<bool>,
-        is_dismissed: Option<bool>,
-    ) -> Result<MemoryDB, Box<dyn std::error::Error + Send + Sync>> {
-        let mut update_fields = vec!["updated_at"];
-        let mut fields = json!({
-            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
-        });
-
-        if let Some(read) = is_read {
-            update_fields.push("is_read");
-            fields["is_read"] = json!({"booleanValue": read});
-        }
-        if let Some(dismissed) = is_dismissed {
-            update_fields.push("is_dismissed");
-            fields["is_dismissed"] = json!({"booleanValue": dismissed});
-        }
-
-        let update_mask = update_fields
-            .iter()
-            .map(|f| format!("updateMask.fieldPaths={}", f))
-            .collect::<Vec<_>>()
-            .join("&");
-
+        conversation_id: &str,
+        edit_id: &str,
+    ) -> Result<Option<ConversationEdit>, FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            MEMORIES_SUBCOLLECTION,
-            memory_id,
-            update_mask
+            "https://firestore.googleapis.com/v1/{}",
+            self.conversation_edit_doc_name(uid, conversation_id, edit_id)
         );
 
-        let doc = json!({ "fields": fields });
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        tracing::info!("Updated memory {} read status for user {}", memory_id, uid);
-
-        // Fetch and return the updated memory
-        self.get_memory(uid, memory_id)
-            .await?
-            .ok_or_else(|| "Memory not found after update".into())
+        let doc: Value = response.json().await?;
+        Ok(self.parse_conversation_edit(uid, &doc))
     }
 
-    /// Mark all memories as read
-    pub async fn mark_all_memories_read(
+    /// Delete a conversation's edit history (used when the conversation is deleted), or
+    /// with `field`, only the edits of that field
+    async fn delete_conversation_edits(
         &self,
         uid: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // First get all unread memories
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        let query = json!({
+        conversation_id: &str,
+        field: Option<ConversationEditField>,
+    ) -> Result<(), FirestoreError> {
+        let parent = format!(
+            "https://firestore.googleapis.com/v1/{}",
+            self.conversation_doc_name(uid, conversation_id)
+        );
+        let mut query = json!({
             "structuredQuery": {
-                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
-                "where": {
-                    "fieldFilter": {
-                        "field": {"fieldPath": "is_read"},
-                        "op": "EQUAL",
-                        "value": {"booleanValue": false}
-                    }
-                },
-                "limit": 500
+                "from": [{"collectionId": CONVERSATION_EDITS_SUBCOLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "limit": MAX_EDITS_PER_CONVERSATION
             }
         });
-
-        let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-            .await?
-            .json(&query)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
+        if let Some(field) = field {
+            query["structuredQuery"]["where"] = json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "field"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": field.as_str()}
+                }
+            });
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let memory_ids: Vec<String> = results
+        let docs = self.run_user_query(&parent, &query).await?;
+        let writes: Vec<Value> = docs
             .iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| d.get("name"))
-                    .and_then(|n| n.as_str())
-                    .map(|s| s.split('/').last().unwrap_or("").to_string())
-            })
-            .filter(|id| !id.is_empty())
+            .filter_map(|doc| doc.get("name")?.as_str())
+            .map(|name| json!({"delete": name}))
             .collect();
+        self.commit_batched_writes(writes).await
+    }
 
-        let count = memory_ids.len();
+    /// Parse an edit document; None if it's malformed or its value can't be decoded
+    fn parse_conversation_edit(&self, uid: &str, doc: &Value) -> Option<ConversationEdit> {
+        let fields = doc.get("fields")?;
+        let previous_value = fields
+            .get("previous_value")
+            .and_then(|v| self.decode_edit_value(uid, v))?;
+        Some(ConversationEdit {
+            id: firestore_serde::document_id(doc)?.to_string(),
+            field: ConversationEditField::parse(&self.parse_string(fields, "field")?)?,
+            previous_value,
+            edited_by: self.parse_string(fields, "edited_by").unwrap_or_default(),
+            source: self.parse_string(fields, "source").unwrap_or_default(),
+            restored_from: self.parse_string(fields, "restored_from"),
+            created_at: self.parse_timestamp_optional(fields, "created_at")?,
+        })
+    }
 
-        // Update each memory
-        for memory_id in memory_ids {
-            let url = format!(
-                "{}/{}/{}/{}/{}?updateMask.fieldPaths=is_read&updateMask.fieldPaths=updated_at",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                MEMORIES_SUBCOLLECTION,
-                memory_id
-            );
+    // =========================================================================
+    // MEMORIES
+    // =========================================================================
 
-            let doc = json!({
-                "fields": {
-                    "is_read": {"booleanValue": true},
-                    "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+    /// Get memories for a user with optional filtering
+    /// Copied from Python get_memories
+    /// Enriches memories with source from linked conversations
+    pub async fn get_memories_filtered(
+        &self,
+        uid: &str,
+        limit: usize,
+        offset: usize,
+        category: Option<&str>,
+        tags: Option<&[String]>,
+        include_dismissed: bool,
+    ) -> Result<Vec<MemoryDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        // Filter by category if specified
+        if let Some(cat) = category {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "category"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": cat}
                 }
-            });
+            }));
+        }
 
-            let _ = self
-                .build_request(reqwest::Method::PATCH, &url)
-                .await?
-                .json(&doc)
-                .send()
-                .await;
+        // Filter by first tag in Firestore (ARRAY_CONTAINS supports one tag per query).
+        // Additional tags (if any) are still filtered in-memory below.
+        if let Some(filter_tags) = tags {
+            if let Some(first_tag) = filter_tags.first() {
+                filters.push(json!({
+                    "fieldFilter": {
+                        "field": {"fieldPath": "tags"},
+                        "op": "ARRAY_CONTAINS",
+                        "value": {"stringValue": first_tag}
+                    }
+                }));
+            }
         }
 
-        tracing::info!("Marked {} memories as read for user {}", count, uid);
-        Ok(count)
-    }
+        // NOTE: We do NOT filter is_dismissed in Firestore query because existing memories
+        // don't have this field. Firestore only returns documents where the field EXISTS and
+        // matches the value. Instead, we filter in-memory below (matching Python behavior).
 
-    /// Update visibility of all memories for a user
-    pub async fn update_all_memories_visibility(
-        &self,
-        uid: &str,
-        visibility: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // Get all memories
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
 
-        let query = json!({
-            "structuredQuery": {
+        // Fetch from Firestore in a loop to handle post-query filtering (rejected, dismissed, tags).
+        // These can't be reliably filtered in Firestore (fields may not exist on all docs),
+        // so we filter in Rust. Keep fetching until we have enough or Firestore is exhausted.
+        let order_by = json!([
+            {"field": {"fieldPath": "scoring"}, "direction": "DESCENDING"},
+            {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
+        ]);
+        let mut memories: Vec<MemoryDB> = Vec::new();
+        let mut current_offset = offset;
+        let fetch_batch = limit.max(500);
+
+        loop {
+            let mut structured_query = json!({
                 "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
-                "limit": 1000
+                "orderBy": order_by.clone(),
+                "limit": fetch_batch,
+                "offset": current_offset
+            });
+
+            if let Some(ref where_filter) = where_clause {
+                structured_query["where"] = where_filter.clone();
             }
-        });
 
-        let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-            .await?
-            .json(&query)
-            .send()
-            .await?;
+            let query = json!({
+                "structuredQuery": structured_query
+            });
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
-        }
+            let request = self
+                .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+                .await?
+                .json(&query);
+            let response = self.send_critical_read("get_memories", request).await?;
 
-        let results: Vec<Value> = response.json().await?;
-        let memory_ids: Vec<String> = results
-            .iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| d.get("name"))
-                    .and_then(|n| n.as_str())
-                    .map(|s| s.split('/').last().unwrap_or("").to_string())
-            })
-            .filter(|id| !id.is_empty())
-            .collect();
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                tracing::error!("Firestore query error: {}", error_text);
+                break;
+            }
 
-        let count = memory_ids.len();
+            let results: Vec<Value> = response.json().await?;
+            let fetched_count = results.iter().filter(|doc| doc.get("document").is_some()).count();
 
-        // Update each memory's visibility
-        for memory_id in memory_ids {
-            let url = format!(
-                "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility&updateMask.fieldPaths=updated_at",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                MEMORIES_SUBCOLLECTION,
-                memory_id
-            );
+            let batch: Vec<MemoryDB> = results
+                .into_iter()
+                .filter_map(|doc| {
+                    doc.get("document")
+                        .and_then(|d| self.parse_memory(d, uid).ok())
+                })
+                // Filter out rejected memories (matches Python behavior)
+                .filter(|m| m.user_review != Some(false))
+                // Filter out dismissed memories in-memory (not in Firestore query, since existing
+                // memories don't have is_dismissed field - Firestore requires field to exist for filters)
+                .filter(|m| include_dismissed || !m.is_dismissed)
+                // Filter by remaining tags in-memory (first tag is already filtered by Firestore ARRAY_CONTAINS)
+                .filter(|m| {
+                    match tags {
+                        Some(filter_tags) if filter_tags.len() > 1 => {
+                            filter_tags[1..].iter().all(|tag| m.tags.contains(tag))
+                        }
+                        _ => true,
+                    }
+                })
+                .collect();
 
-            let doc = json!({
-                "fields": {
-                    "visibility": {"stringValue": visibility},
-                    "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
-                }
-            });
+            memories.extend(batch);
+            current_offset += fetched_count;
 
-            let _ = self
-                .build_request(reqwest::Method::PATCH, &url)
-                .await?
-                .json(&doc)
-                .send()
-                .await;
+            // Stop if Firestore returned fewer than requested (no more data)
+            if fetched_count < fetch_batch {
+                break;
+            }
+
+            // Stop if we have enough items
+            if memories.len() >= limit {
+                memories.truncate(limit);
+                break;
+            }
         }
 
-        tracing::info!(
-            "Updated visibility to '{}' for {} memories for user {}",
-            visibility,
-            count,
-            uid
-        );
-        Ok(count)
+        // Enrich memories with source from linked conversations
+        self.enrich_memories_with_source(uid, &mut memories).await;
+
+        Ok(memories)
     }
 
-    /// Delete all memories for a user
-    pub async fn delete_all_memories(
+    /// Get memories for a user (simple version for backward compatibility)
+    /// Copied from Python get_memories
+    /// Enriches memories with source from linked conversations
+    pub async fn get_memories(
         &self,
         uid: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // Get all memories
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        limit: usize,
+    ) -> Result<Vec<MemoryDB>, FirestoreError> {
+        self.get_memories_filtered(uid, limit, 0, None, None, false).await
+    }
 
+    /// Every memory of a user, newest first, streamed a page at a time (rejected and
+    /// dismissed ones are left out, as in `get_memories`)
+    pub fn stream_memories(&self, uid: &str) -> DocumentStream<'_, MemoryDB> {
+        const PAGE_SIZE: usize = 500;
+        let uid = uid.to_string();
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
         let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
-                "limit": 1000
-            }
+            "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}]
         });
 
-        let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-            .await?
-            .json(&query)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
-        }
-
-        let results: Vec<Value> = response.json().await?;
-        let memory_ids: Vec<String> = results
-            .iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| d.get("name"))
-                    .and_then(|n| n.as_str())
-                    .map(|s| s.split('/').last().unwrap_or("").to_string())
-            })
-            .filter(|id| !id.is_empty())
-            .collect();
+        Box::pin(async_stream::try_stream! {
+            let mut docs = self.stream_query(&parent, query, PAGE_SIZE);
+            let mut page: Vec<MemoryDB> = Vec::with_capacity(PAGE_SIZE);
+            loop {
+                let doc = futures::TryStreamExt::try_next(&mut docs).await?;
+                let done = doc.is_none();
+                if let Some(memory) = doc.and_then(|d| self.parse_memory(&d, &uid).ok()) {
+                    if memory.user_review != Some(false) && !memory.is_dismissed {
+                        page.push(memory);
+                    }
+                }
+                // Source enrichment is batched per page
+                if page.len() == PAGE_SIZE || (done && !page.is_empty()) {
+                    self.enrich_memories_with_source(&uid, &mut page).await;
+                    for memory in page.drain(..) {
+                        yield memory;
+                    }
+                }
+                if done {
+                    break;
+                }
+            }
+        })
+    }
 
-        let count = memory_ids.len();
+    /// Batch fetch conversations and populate source and input_device_name fields on memories
+    async fn enrich_memories_with_source(&self, uid: &str, memories: &mut [MemoryDB]) {
+        use std::collections::{HashMap, HashSet};
 
-        // Delete each memory
-        for memory_id in memory_ids {
-            let url = format!(
-                "{}/{}/{}/{}/{}",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                MEMORIES_SUBCOLLECTION,
-                memory_id
-            );
+        // Collect unique conversation IDs
+        let conversation_ids: HashSet<&str> = memories
+            .iter()
+            .filter_map(|m| m.conversation_id.as_deref())
+            .collect();
 
-            let _ = self
-                .build_request(reqwest::Method::DELETE, &url)
-                .await?
-                .send()
-                .await;
+        if conversation_ids.is_empty() {
+            return;
         }
 
-        tracing::info!("Deleted {} memories for user {}", count, uid);
-        Ok(count)
-    }
-
-    /// Save memories to Firestore
-    /// Memory IDs are generated from content hash to enable deduplication
-    /// Copied from Python save_memories
-    pub async fn save_memories(
-        &self,
-        uid: &str,
-        conversation_id: &str,
-        memories: &[Memory],
-    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut saved_ids = Vec::new();
-        let now = Utc::now();
+        // Fetch conversations in parallel (limit to avoid too many concurrent requests)
+        // Store both source and input_device_name
+        let mut source_map: HashMap<String, (String, Option<String>)> = HashMap::new();
 
-        for memory in memories {
-            let memory_id = document_id_from_seed(&memory.content);
-            let scoring = MemoryDB::calculate_scoring(&memory.category, &now, false);
+        // Batch fetch - fetch up to 10 at a time
+        let ids: Vec<&str> = conversation_ids.into_iter().collect();
+        for chunk in ids.chunks(10) {
+            let futures: Vec<_> = chunk
+                .iter()
+                .map(|id| self.get_conversation(uid, id))
+                .collect();
 
-            let url = format!(
-                "{}/{}/{}/{}/{}",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                MEMORIES_SUBCOLLECTION,
-                memory_id
-            );
+            let results = futures::future::join_all(futures).await;
 
-            let doc = json!({
-                "fields": {
-                    // CRITICAL: Include id field - Python model requires this
-                    "id": {"stringValue": memory_id},
-                    // Include uid - Python model requires this
-                    "uid": {"stringValue": uid},
-                    "content": {"stringValue": memory.content},
-                    "category": {"stringValue": format!("{:?}", memory.category).to_lowercase()},
-                    "created_at": {"timestampValue": now.to_rfc3339()},
-                    "updated_at": {"timestampValue": now.to_rfc3339()},
-                    "conversation_id": {"stringValue": conversation_id},
-                    // Legacy field - same as conversation_id, used by get_memory_ids_for_conversation
-                    "memory_id": {"stringValue": conversation_id},
-                    "reviewed": {"booleanValue": false},
-                    // CRITICAL: user_review must exist - Python filters on memory['user_review'] is not False
-                    // None/null means not yet reviewed by user (different from False which means rejected)
-                    "user_review": {"nullValue": null},
-                    "visibility": {"stringValue": "private"},
-                    "manually_added": {"booleanValue": false},
-                    "edited": {"booleanValue": false},
-                    "is_locked": {"booleanValue": false},
-                    "kg_extracted": {"booleanValue": false},
-                    "scoring": {"stringValue": scoring},
-                    // Empty tags array
-                    "tags": {"arrayValue": {"values": []}}
+            for (id, result) in chunk.iter().zip(results) {
+                if let Ok(Some(conv)) = result {
+                    let source_str = format!("{:?}", conv.source).to_lowercase();
+                    source_map.insert(id.to_string(), (source_str, conv.input_device_name.clone()));
                 }
-            });
-
-            let response = self
-                .build_request(reqwest::Method::PATCH, &url)
-                .await?
-                .json(&doc)
-                .send()
-                .await?;
-
-            if response.status().is_success() {
-                saved_ids.push(memory_id);
-            } else {
-                tracing::warn!("Failed to save memory: {}", response.text().await?);
             }
         }
 
-        tracing::info!(
-            "Saved {} memories for conversation {}",
-            saved_ids.len(),
-            conversation_id
-        );
-        Ok(saved_ids)
+        // Populate source and input_device_name fields on memories
+        for memory in memories.iter_mut() {
+            if let Some(conv_id) = &memory.conversation_id {
+                if let Some((source, device_name)) = source_map.get(conv_id) {
+                    memory.source = Some(source.clone());
+                    memory.input_device_name = device_name.clone();
+                }
+            }
+        }
     }
 
-    // =========================================================================
-    // ACTION ITEMS
-    // =========================================================================
-
-    /// Get action items for a user
-    /// Path: users/{uid}/action_items
-    #[allow(clippy::too_many_arguments)]
-    pub async fn get_action_items(
+    /// Get a single memory by ID
+    pub async fn get_memory(
         &self,
         uid: &str,
-        limit: usize,
-        offset: usize,
-        completed_filter: Option<bool>,
-        conversation_id: Option<&str>,
-        start_date: Option<&str>,
-        end_date: Option<&str>,
-        due_start_date: Option<&str>,
-        due_end_date: Option<&str>,
-        sort_by: Option<&str>,
-        include_deleted: Option<bool>,
-    ) -> Result<Vec<ActionItemDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        memory_id: &str,
+    ) -> Result<Option<MemoryDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            MEMORIES_SUBCOLLECTION,
+            memory_id
+        );
 
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
 
-        if let Some(completed) = completed_filter {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "completed"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": completed}
-                }
-            }));
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
         }
 
-        // Conversation ID filter
-        if let Some(conv_id) = conversation_id {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "conversation_id"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": conv_id}
-                }
-            }));
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore error", &error_text));
         }
 
-        // Date range filters for created_at
-        if let Some(start) = start_date {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "created_at"},
-                    "op": "GREATER_THAN_OR_EQUAL",
-                    "value": {"timestampValue": start}
-                }
-            }));
-        }
+        let doc: Value = response.json().await?;
+        let memory = self.parse_memory(&doc, uid)?;
+        Ok(Some(memory))
+    }
 
-        if let Some(end) = end_date {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "created_at"},
-                    "op": "LESS_THAN_OR_EQUAL",
-                    "value": {"timestampValue": end}
-                }
-            }));
-        }
+    /// Delete a memory by ID
+    pub async fn delete_memory(
+        &self,
+        uid: &str,
+        memory_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            MEMORIES_SUBCOLLECTION,
+            memory_id
+        );
 
-        // Date range filters for due_at
-        if let Some(due_start) = due_start_date {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "due_at"},
-                    "op": "GREATER_THAN_OR_EQUAL",
-                    "value": {"timestampValue": due_start}
-                }
-            }));
-        }
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
 
-        if let Some(due_end) = due_end_date {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "due_at"},
-                    "op": "LESS_THAN_OR_EQUAL",
-                    "value": {"timestampValue": due_end}
-                }
-            }));
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
-
-        // Build order by clause based on sort_by parameter
-        let order_by = match sort_by {
-            Some("due_at") => json!([
-                {"field": {"fieldPath": "due_at"}, "direction": "ASCENDING"},
-                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
-            ]),
-            Some("priority") => json!([
-                {"field": {"fieldPath": "priority"}, "direction": "DESCENDING"},
-                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
-            ]),
-            _ => json!([
-                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
-            ]),
-        };
-
-        // Fetch from Firestore in a loop to handle post-query deleted filtering.
-        // Since `deleted` can't be reliably filtered in Firestore (most docs lack the field),
-        // we filter in Rust. But this means a single Firestore page may yield fewer items
-        // than requested after filtering, so we keep fetching until we have enough or Firestore
-        // is exhausted.
-        let mut action_items: Vec<ActionItemDB> = Vec::new();
-        let mut current_offset = offset;
-        let fetch_batch = limit.max(500); // fetch in large batches to minimize round-trips
-
-        loop {
-            let mut structured_query = json!({
-                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
-                "orderBy": order_by.clone(),
-                "limit": fetch_batch,
-                "offset": current_offset
-            });
-
-            if let Some(ref where_filter) = where_clause {
-                structured_query["where"] = where_filter.clone();
-            }
-
-            let query = json!({
-                "structuredQuery": structured_query
-            });
+        self.record_deletion(uid, MEMORIES_SUBCOLLECTION, memory_id).await;
+        tracing::info!("Deleted memory {} for user {}", memory_id, uid);
+        Ok(())
+    }
 
-            let response = self
-                .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-                .await?
-                .json(&query)
-                .send()
-                .await?;
+    /// Update memory content
+    pub async fn update_memory_content(
+        &self,
+        uid: &str,
+        memory_id: &str,
+        content: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=content&updateMask.fieldPaths=updated_at",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            MEMORIES_SUBCOLLECTION,
+            memory_id
+        );
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                tracing::error!("Firestore query error: {}", error_text);
-                break;
+        let now = Utc::now();
+        let doc = json!({
+            "fields": {
+                "content": {"stringValue": content},
+                "updated_at": {"timestampValue": now.to_rfc3339()}
             }
+        });
 
-            let results: Vec<Value> = response.json().await?;
-            let fetched_count = results.iter().filter(|doc| doc.get("document").is_some()).count();
-
-            let batch: Vec<ActionItemDB> = results
-                .into_iter()
-                .filter_map(|doc| {
-                    doc.get("document")
-                        .and_then(|d| self.parse_action_item(d).ok())
-                })
-                // Filter based on deleted status
-                .filter(|item| {
-                    if include_deleted == Some(true) {
-                        item.deleted == Some(true)
-                    } else {
-                        item.deleted != Some(true)
-                    }
-                })
-                .collect();
-
-            action_items.extend(batch);
-            current_offset += fetched_count;
-
-            // Stop if Firestore returned fewer than requested (no more data)
-            if fetched_count < fetch_batch {
-                break;
-            }
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-            // Stop if we have enough items
-            if action_items.len() >= limit {
-                action_items.truncate(limit);
-                break;
-            }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        // Enrich action items that have conversation_id but no source
-        self.enrich_action_items_with_source(uid, &mut action_items).await;
-
-        // Post-query sort matching Python backend behavior (used by iOS/Flutter app):
-        // 1. Items WITH due_at come first (sorted by due_at ascending)
-        // 2. Items WITHOUT due_at come last
-        // 3. Tie-breaker: created_at descending (newest first)
-        action_items.sort_by(|a, b| {
-            match (&a.due_at, &b.due_at) {
-                (Some(due_a), Some(due_b)) => {
-                    due_a.cmp(due_b).then_with(|| b.created_at.cmp(&a.created_at))
-                }
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                (None, None) => b.created_at.cmp(&a.created_at),
-            }
-        });
-
-        Ok(action_items)
+        tracing::info!("Updated memory content {} for user {}", memory_id, uid);
+        Ok(())
     }
 
-    /// Batch fetch conversations and populate source field on action items
-    /// For items with conversation_id but no source, derives source as "transcription:{conversation.source}"
-    async fn enrich_action_items_with_source(&self, uid: &str, action_items: &mut [ActionItemDB]) {
-        use std::collections::{HashMap, HashSet};
-
-        // Collect unique conversation IDs from items that need enrichment
-        // (have conversation_id but no source)
-        let conversation_ids: HashSet<&str> = action_items
-            .iter()
-            .filter(|item| item.source.is_none() && item.conversation_id.is_some())
-            .filter_map(|item| item.conversation_id.as_deref())
-            .collect();
-
-        if conversation_ids.is_empty() {
-            return;
-        }
-
-        tracing::debug!(
-            "Enriching {} action items with source from {} conversations",
-            action_items.iter().filter(|i| i.source.is_none()).count(),
-            conversation_ids.len()
+    /// Update memory visibility
+    pub async fn update_memory_visibility(
+        &self,
+        uid: &str,
+        memory_id: &str,
+        visibility: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility&updateMask.fieldPaths=updated_at",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            MEMORIES_SUBCOLLECTION,
+            memory_id
         );
 
-        // Fetch conversations in parallel (limit to avoid too many concurrent requests)
-        let mut source_map: HashMap<String, String> = HashMap::new();
-
-        // Batch fetch - fetch up to 10 at a time
-        let ids: Vec<&str> = conversation_ids.into_iter().collect();
-        for chunk in ids.chunks(10) {
-            let futures: Vec<_> = chunk
-                .iter()
-                .map(|id| self.get_conversation(uid, id))
-                .collect();
+        let now = Utc::now();
+        let doc = json!({
+            "fields": {
+                "visibility": {"stringValue": visibility},
+                "updated_at": {"timestampValue": now.to_rfc3339()}
+            }
+        });
 
-            let results = futures::future::join_all(futures).await;
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-            for (id, result) in chunk.iter().zip(results) {
-                if let Ok(Some(conv)) = result {
-                    // Format as "transcription:{source}" to match expected values
-                    // e.g., "transcription:omi", "transcription:desktop"
-                    let source_str = format!("transcription:{:?}", conv.source).to_lowercase();
-                    source_map.insert(id.to_string(), source_str);
-                }
-            }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        // Populate source field on action items that don't have one
-        for item in action_items.iter_mut() {
-            if item.source.is_none() {
-                if let Some(conv_id) = &item.conversation_id {
-                    item.source = source_map.get(conv_id).cloned();
-                }
-            }
-        }
+        tracing::info!("Updated memory visibility {} for user {}", memory_id, uid);
+        Ok(())
     }
 
-    /// Get a single action item by ID
-    pub async fn get_action_item_by_id(
+    /// Review a memory (approve/reject)
+    pub async fn review_memory(
         &self,
         uid: &str,
-        item_id: &str,
-    ) -> Result<Option<ActionItemDB>, Box<dyn std::error::Error + Send + Sync>> {
+        memory_id: &str,
+        value: bool,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=reviewed&updateMask.fieldPaths=user_review&updateMask.fieldPaths=updated_at",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ACTION_ITEMS_SUBCOLLECTION,
-            item_id
+            MEMORIES_SUBCOLLECTION,
+            memory_id
         );
 
+        let now = Utc::now();
+        let doc = json!({
+            "fields": {
+                "reviewed": {"booleanValue": true},
+                "user_review": {"booleanValue": value},
+                "updated_at": {"timestampValue": now.to_rfc3339()}
+            }
+        });
+
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&doc)
             .send()
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Ok(None);
-        }
-
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore get error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        let mut action_item = self.parse_action_item(&doc)?;
+        tracing::info!("Reviewed memory {} for user {} with value {}", memory_id, uid, value);
+        Ok(())
+    }
 
-        // Enrich with source from conversation if needed
-        if action_item.source.is_none() {
-            if let Some(conv_id) = &action_item.conversation_id {
-                if let Ok(Some(conv)) = self.get_conversation(uid, conv_id).await {
-                    action_item.source = Some(format!("transcription:{:?}", conv.source).to_lowercase());
-                }
+    /// Memories still awaiting review (reviewed == false), newest first, up to `max`.
+    /// Sorted here rather than in the query, so no composite index is needed.
+    pub async fn get_pending_review_memories(
+        &self,
+        uid: &str,
+        max: usize,
+    ) -> Result<Vec<MemoryDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "reviewed"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": false}
+                    }
+                },
+                "limit": max
             }
-        }
+        });
 
-        Ok(Some(action_item))
+        let docs = self.run_user_query(&parent, &query).await?;
+        let mut memories: Vec<MemoryDB> = docs
+            .iter()
+            .filter_map(|doc| self.parse_memory(doc, uid).ok())
+            .collect();
+        memories.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+        Ok(memories)
     }
 
-    /// Update an action item
-    pub async fn update_action_item(
+    /// Approve (value = true) or reject several memories in batched writes.
+    /// Returns how many were reviewed and the IDs that don't exist.
+    pub async fn review_memories(
         &self,
         uid: &str,
-        item_id: &str,
-        completed: Option<bool>,
-        description: Option<&str>,
-        due_at: Option<DateTime<Utc>>,
-        priority: Option<&str>,
-        category: Option<&str>,
-        goal_id: Option<&str>,
-        relevance_score: Option<i32>,
-        sort_order: Option<i32>,
-        indent_level: Option<i32>,
-        recurrence_rule: Option<&str>,
-    ) -> Result<ActionItemDB, Box<dyn std::error::Error + Send + Sync>> {
-        // Build update mask and fields
-        let mut field_paths: Vec<&str> = vec!["updated_at"];
-        let mut fields = json!({
-            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
-        });
-
-        if let Some(c) = completed {
-            field_paths.push("completed");
-            fields["completed"] = json!({"booleanValue": c});
-
-            // Set or clear completed_at based on completion status
-            field_paths.push("completed_at");
-            if c {
-                fields["completed_at"] = json!({"timestampValue": Utc::now().to_rfc3339()});
-            } else {
-                // Clear completed_at when marking as incomplete (matches Python backend behavior)
-                fields["completed_at"] = json!({"nullValue": null});
-            }
-        }
+        memory_ids: &[String],
+        value: bool,
+    ) -> Result<(usize, Vec<String>), FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        let url = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:batchWrite",
+            self.project_id()
+        );
+        let mut reviewed = 0;
+        let mut not_found = Vec::new();
 
-        if let Some(d) = description {
-            field_paths.push("description");
-            fields["description"] = json!({"stringValue": d});
-        }
+        for chunk in memory_ids.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|id| {
+                    json!({
+                        "update": {
+                            "name": format!(
+                                "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                                self.project_id(), USERS_COLLECTION, uid, MEMORIES_SUBCOLLECTION, id
+                            ),
+                            "fields": {
+                                "reviewed": {"booleanValue": true},
+                                "user_review": {"booleanValue": value},
+                                "updated_at": {"timestampValue": now}
+                            }
+                        },
+                        "updateMask": {"fieldPaths": ["reviewed", "user_review", "updated_at"]},
+                        "currentDocument": {"exists": true}
+                    })
+                })
+                .collect();
 
-        if let Some(due) = due_at {
-            field_paths.push("due_at");
-            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
-        }
+            let response = self
+                .build_request(reqwest::Method::POST, &url)
+                .await?
+                .json(&json!({ "writes": writes }))
+                .send()
+                .await?;
 
-        if let Some(pri) = priority {
-            field_paths.push("priority");
-            fields["priority"] = json!({"stringValue": pri});
-        }
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch write error", &error_text));
+            }
 
-        if let Some(cat) = category {
-            field_paths.push("category");
-            fields["category"] = json!({"stringValue": cat});
+            // One status per write; 5 = NOT_FOUND (precondition on a missing document)
+            let result: Value = response.json().await?;
+            let statuses = result.get("status").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+            for (id, status) in chunk.iter().zip(statuses.iter()) {
+                match status.get("code").and_then(|c| c.as_i64()).unwrap_or(0) {
+                    0 => reviewed += 1,
+                    5 => not_found.push(id.clone()),
+                    code => {
+                        let message = status.get("message").and_then(|m| m.as_str()).unwrap_or("");
+                        return Err(format!("Firestore batch write failed for memory {} ({}): {}", id, code, message).into());
+                    }
+                }
+            }
         }
 
-        if let Some(gid) = goal_id {
-            field_paths.push("goal_id");
-            fields["goal_id"] = json!({"stringValue": gid});
-        }
+        tracing::info!("Reviewed {} memories for user {} with value {}", reviewed, uid, value);
+        Ok((reviewed, not_found))
+    }
 
-        if let Some(score) = relevance_score {
-            field_paths.push("relevance_score");
-            fields["relevance_score"] = json!({"integerValue": score.to_string()});
-        }
+    /// Users with a memory auto-approve delay set
+    pub async fn get_users_with_memory_auto_approve(
+        &self,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": USERS_COLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "assistant_settings.memory.auto_approve_after_days"},
+                        "op": "GREATER_THAN",
+                        "value": {"integerValue": "0"}
+                    }
+                }
+            }
+        });
 
-        if let Some(order) = sort_order {
-            field_paths.push("sort_order");
-            fields["sort_order"] = json!({"integerValue": order.to_string()});
-        }
+        let docs = self.run_user_query(&self.base_url(), &query).await?;
+        Ok(docs
+            .iter()
+            .filter_map(firestore_serde::document_id)
+            .map(|s| s.to_string())
+            .collect())
+    }
 
-        if let Some(indent) = indent_level {
-            field_paths.push("indent_level");
-            fields["indent_level"] = json!({"integerValue": indent.to_string()});
-        }
+    /// Create a memory (manual or extracted)
+    pub async fn create_memory(
+        &self,
+        uid: &str,
+        content: &str,
+        visibility: &str,
+        category: Option<MemoryCategory>,
+        confidence: Option<f64>,
+        source_app: Option<&str>,
+        context_summary: Option<&str>,
+        tags: &[String],
+        reasoning: Option<&str>,
+        current_activity: Option<&str>,
+        source: Option<&str>,
+        window_title: Option<&str>,
+    ) -> Result<String, FirestoreError> {
+        let memory_id = document_id_from_seed(content);
+        let now = Utc::now();
 
-        if let Some(rule) = recurrence_rule {
-            field_paths.push("recurrence_rule");
-            if rule.is_empty() {
-                fields["recurrence_rule"] = json!({"nullValue": null});
-            } else {
-                fields["recurrence_rule"] = json!({"stringValue": rule});
-            }
-        }
+        // Determine if this is a manual memory
+        let is_manual = category.is_none() || matches!(category, Some(MemoryCategory::Manual));
+        let actual_category = category.unwrap_or(MemoryCategory::Manual);
+        let scoring = MemoryDB::calculate_scoring(&actual_category, &now, is_manual);
 
-        let update_mask = field_paths
-            .iter()
-            .map(|p| format!("updateMask.fieldPaths={}", p))
-            .collect::<Vec<_>>()
-            .join("&");
+        let category_str = match actual_category {
+            MemoryCategory::System => "system",
+            MemoryCategory::Interesting => "interesting",
+            MemoryCategory::Manual => "manual",
+            // Legacy categories - preserve original value
+            MemoryCategory::Core => "core",
+            MemoryCategory::Hobbies => "hobbies",
+            MemoryCategory::Lifestyle => "lifestyle",
+            MemoryCategory::Interests => "interests",
+        };
 
         let url = format!(
-            "{}/{}/{}/{}/{}?{}",
+            "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ACTION_ITEMS_SUBCOLLECTION,
-            item_id,
-            update_mask
+            MEMORIES_SUBCOLLECTION,
+            memory_id
         );
 
-        let doc = json!({"fields": fields});
+        // Build tags array for Firestore
+        let tags_values: Vec<Value> = tags
+            .iter()
+            .map(|t| json!({"stringValue": t}))
+            .collect();
+
+        // Build fields - always include base fields
+        // CRITICAL: Include all fields that Python expects (matching save_memories)
+        let mut fields = json!({
+            // CRITICAL: id field required - Python model requires this
+            "id": {"stringValue": &memory_id},
+            // CRITICAL: uid field required - Python model requires this
+            "uid": {"stringValue": uid},
+            "content": {"stringValue": content},
+            "category": {"stringValue": category_str},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()},
+            "reviewed": {"booleanValue": is_manual},
+            "user_review": {"booleanValue": is_manual},
+            "visibility": {"stringValue": visibility},
+            "manually_added": {"booleanValue": is_manual},
+            "scoring": {"stringValue": scoring},
+            "is_read": {"booleanValue": false},
+            "is_dismissed": {"booleanValue": false},
+            // Additional fields for Python compatibility
+            "edited": {"booleanValue": false},
+            "is_locked": {"booleanValue": false},
+            "kg_extracted": {"booleanValue": false},
+            "tags": {"arrayValue": {"values": tags_values}}
+        });
+
+        // Add optional fields if present
+        if let Some(conf) = confidence {
+            fields["confidence"] = json!({"doubleValue": conf});
+        }
+        if let Some(app) = source_app {
+            fields["source_app"] = json!({"stringValue": app});
+        }
+        if let Some(summary) = context_summary {
+            fields["context_summary"] = json!({"stringValue": summary});
+        }
+        if let Some(reason) = reasoning {
+            fields["reasoning"] = json!({"stringValue": reason});
+        }
+        if let Some(activity) = current_activity {
+            fields["current_activity"] = json!({"stringValue": activity});
+        }
+        if let Some(src) = source {
+            fields["source"] = json!({"stringValue": src});
+        }
+        if let Some(wt) = window_title {
+            fields["window_title"] = json!({"stringValue": wt});
+        }
+
+        let doc = json!({ "fields": fields });
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -2268,82 +3079,50 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        // Parse and return the updated document
-        let updated_doc: Value = response.json().await?;
-        let mut action_item = self.parse_action_item(&updated_doc)?;
-
-        // Enrich with source from conversation if needed
-        if action_item.source.is_none() {
-            if let Some(conv_id) = &action_item.conversation_id {
-                if let Ok(Some(conv)) = self.get_conversation(uid, conv_id).await {
-                    action_item.source = Some(format!("transcription:{:?}", conv.source).to_lowercase());
-                }
-            }
-        }
+        tracing::info!("Created memory {} for user {} (category: {})", memory_id, uid, category_str);
+        Ok(memory_id)
+    }
 
-        tracing::info!("Updated action item {} for user {}", item_id, uid);
-        Ok(action_item)
+    /// Create a manual memory (convenience wrapper)
+    pub async fn create_manual_memory(
+        &self,
+        uid: &str,
+        content: &str,
+        visibility: &str,
+    ) -> Result<String, FirestoreError> {
+        self.create_memory(uid, content, visibility, None, None, None, None, &[], None, None, None, None).await
     }
 
-    /// Delete an action item
-    pub async fn delete_action_item(
+    /// Update memory read/dismissed status
+    pub async fn update_memory_read_status(
         &self,
         uid: &str,
-        item_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            ACTION_ITEMS_SUBCOLLECTION,
-            item_id
-        );
-
-        let response = self
-            .build_request(reqwest::Method::DELETE, &url)
-            .await?
-            .send()
-            .await?;
-
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
-        }
-
-        tracing::info!("Deleted action item {} for user {}", item_id, uid);
-        Ok(())
-    }
-
-    /// Soft-delete an action item (mark as deleted without removing from Firestore)
-    pub async fn soft_delete_action_item(
-        &self,
-        uid: &str,
-        item_id: &str,
-        deleted_by: &str,
-        reason: &str,
-        kept_task_id: &str,
-    ) -> Result<ActionItemDB, Box<dyn std::error::Error + Send + Sync>> {
-        let field_paths = vec![
-            "deleted", "deleted_by", "deleted_at", "deleted_reason", "kept_task_id", "updated_at",
-        ];
-
-        let fields = json!({
-            "deleted": {"booleanValue": true},
-            "deleted_by": {"stringValue": deleted_by},
-            "deleted_at": {"timestampValue": Utc::now().to_rfc3339()},
-            "deleted_reason": {"stringValue": reason},
-            "kept_task_id": {"stringValue": kept_task_id},
+        memory_id: &str,
+        is_read: Option<bool>,
+        is_dismissed: Option<bool>,
+    ) -> Result<MemoryDB, FirestoreError> {
+        let mut update_fields = vec!["updated_at"];
+        let mut fields = json!({
             "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
         });
 
-        let update_mask = field_paths
+        if let Some(read) = is_read {
+            update_fields.push("is_read");
+            fields["is_read"] = json!({"booleanValue": read});
+        }
+        if let Some(dismissed) = is_dismissed {
+            update_fields.push("is_dismissed");
+            fields["is_dismissed"] = json!({"booleanValue": dismissed});
+        }
+
+        let update_mask = update_fields
             .iter()
-            .map(|p| format!("updateMask.fieldPaths={}", p))
+            .map(|f| format!("updateMask.fieldPaths={}", f))
             .collect::<Vec<_>>()
             .join("&");
 
@@ -2352,12 +3131,12 @@ impl FirestoreService {
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ACTION_ITEMS_SUBCOLLECTION,
-            item_id,
+            MEMORIES_SUBCOLLECTION,
+            memory_id,
             update_mask
         );
 
-        let doc = json!({"fields": fields});
+        let doc = json!({ "fields": fields });
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -2367,142 +3146,410 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore soft-delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let updated_doc: Value = response.json().await?;
-        let action_item = self.parse_action_item(&updated_doc)?;
+        tracing::info!("Updated memory {} read status for user {}", memory_id, uid);
 
-        tracing::info!("Soft-deleted action item {} for user {} (by: {}, reason: {})", item_id, uid, deleted_by, reason);
-        Ok(action_item)
+        // Fetch and return the updated memory
+        self.get_memory(uid, memory_id)
+            .await?
+            .ok_or_else(|| "Memory not found after update".into())
     }
 
-    /// Save action items to Firestore
-    /// Create a single action item (for API/desktop creation)
-    pub async fn create_action_item(
+    /// Mark all memories as read
+    pub async fn mark_all_memories_read(
         &self,
         uid: &str,
-        description: &str,
-        due_at: Option<DateTime<Utc>>,
-        source: Option<&str>,
-        priority: Option<&str>,
-        metadata: Option<&str>,
-        category: Option<&str>,
-        relevance_score: Option<i32>,
-        from_staged: Option<bool>,
-        recurrence_rule: Option<&str>,
-        recurrence_parent_id: Option<&str>,
-    ) -> Result<ActionItemDB, Box<dyn std::error::Error + Send + Sync>> {
-        let item_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
-
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            ACTION_ITEMS_SUBCOLLECTION,
-            item_id
-        );
+    ) -> Result<usize, FirestoreError> {
+        // First get all unread memories
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let mut fields = json!({
-            "description": {"stringValue": description},
-            "completed": {"booleanValue": false},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "is_read"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": false}
+                    }
+                },
+                "limit": 500
+            }
         });
 
-        if let Some(due) = due_at {
-            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
-        }
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-        if let Some(src) = source {
-            fields["source"] = json!({"stringValue": src});
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        if let Some(pri) = priority {
-            fields["priority"] = json!({"stringValue": pri});
-        }
+        let results: Vec<Value> = response.json().await?;
+        let memory_ids: Vec<String> = results
+            .iter()
+            .filter_map(|doc| firestore_serde::document_id(doc.get("document")?))
+            .map(|s| s.to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
 
-        if let Some(meta) = metadata {
-            fields["metadata"] = json!({"stringValue": meta});
-        }
+        let count = memory_ids.len();
 
-        if let Some(cat) = category {
-            fields["category"] = json!({"stringValue": cat});
-        }
+        // Update each memory
+        for memory_id in memory_ids {
+            let url = format!(
+                "{}/{}/{}/{}/{}?updateMask.fieldPaths=is_read&updateMask.fieldPaths=updated_at",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                MEMORIES_SUBCOLLECTION,
+                memory_id
+            );
 
-        if let Some(score) = relevance_score {
-            fields["relevance_score"] = json!({"integerValue": score.to_string()});
-        }
+            let doc = json!({
+                "fields": {
+                    "is_read": {"booleanValue": true},
+                    "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+                }
+            });
 
-        if let Some(staged) = from_staged {
-            fields["from_staged"] = json!({"booleanValue": staged});
+            let _ = self
+                .build_request(reqwest::Method::PATCH, &url)
+                .await?
+                .json(&doc)
+                .send()
+                .await;
         }
 
-        if let Some(rule) = recurrence_rule {
-            fields["recurrence_rule"] = json!({"stringValue": rule});
-        }
+        tracing::info!("Marked {} memories as read for user {}", count, uid);
+        Ok(count)
+    }
 
-        if let Some(pid) = recurrence_parent_id {
-            fields["recurrence_parent_id"] = json!({"stringValue": pid});
-        }
+    /// Update visibility of all memories for a user
+    pub async fn update_all_memories_visibility(
+        &self,
+        uid: &str,
+        visibility: &str,
+    ) -> Result<usize, FirestoreError> {
+        // Get all memories
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let doc = json!({"fields": fields});
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
+                "limit": 1000
+            }
+        });
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        // Parse and return the created document
-        let created_doc: Value = response.json().await?;
-        let action_item = self.parse_action_item(&created_doc)?;
+        let results: Vec<Value> = response.json().await?;
+        let memory_ids: Vec<String> = results
+            .iter()
+            .filter_map(|doc| firestore_serde::document_id(doc.get("document")?))
+            .map(|s| s.to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        let count = memory_ids.len();
+
+        // Update each memory's visibility
+        for memory_id in memory_ids {
+            let url = format!(
+                "{}/{}/{}/{}/{}?updateMask.fieldPaths=visibility&updateMask.fieldPaths=updated_at",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                MEMORIES_SUBCOLLECTION,
+                memory_id
+            );
+
+            let doc = json!({
+                "fields": {
+                    "visibility": {"stringValue": visibility},
+                    "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+                }
+            });
+
+            let _ = self
+                .build_request(reqwest::Method::PATCH, &url)
+                .await?
+                .json(&doc)
+                .send()
+                .await;
+        }
 
         tracing::info!(
-            "Created action item {} for user {} with source={:?}",
-            item_id,
-            uid,
-            source
+            "Updated visibility to '{}' for {} memories for user {}",
+            visibility,
+            count,
+            uid
         );
-        Ok(action_item)
+        Ok(count)
     }
 
-    /// Batch update relevance scores for multiple action items using Firestore commit API.
-    /// Processes up to 500 writes per commit (Firestore limit).
-    pub async fn batch_update_scores(
+    /// Delete all memories for a user
+    pub async fn delete_all_memories(
         &self,
         uid: &str,
-        scores: &[(String, i32)],
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-
-        for chunk in scores.chunks(500) {
+    ) -> Result<usize, FirestoreError> {
+        let count = self.delete_memories_where(uid, None, |_| true).await?;
+        tracing::info!("Deleted {} memories for user {}", count, uid);
+        Ok(count)
+    }
+
+    /// Delete memories extracted from a conversation (manually added ones are kept)
+    /// Returns the number of memories deleted
+    pub async fn delete_memories_for_conversation(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+    ) -> Result<usize, FirestoreError> {
+        let filter = json!({
+            "fieldFilter": {
+                "field": {"fieldPath": "conversation_id"},
+                "op": "EQUAL",
+                "value": {"stringValue": conversation_id}
+            }
+        });
+        let count = self
+            .delete_memories_where(uid, Some(filter), |fields| {
+                !self.parse_bool(fields, "manually_added").unwrap_or(false)
+            })
+            .await?;
+
+        tracing::info!(
+            "Deleted {} memories from conversation {} for user {}",
+            count,
+            conversation_id,
+            uid
+        );
+        Ok(count)
+    }
+
+    /// Hard-delete the user's memories matching `filter` (all when None) whose fields pass
+    /// `delete`, paging through every match. Each delete is committed with its sync tombstone.
+    async fn delete_memories_where(
+        &self,
+        uid: &str,
+        filter: Option<Value>,
+        delete: impl Fn(&Value) -> bool,
+    ) -> Result<usize, FirestoreError> {
+        // Delete and tombstone pairs; an even batch keeps each pair in one commit
+        const IDS_PER_COMMIT: usize = 250;
+
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let mut query = json!({"from": [{"collectionId": MEMORIES_SUBCOLLECTION}]});
+        if let Some(filter) = filter {
+            query["where"] = filter;
+        }
+
+        let mut docs = self.stream_query(&parent, query, IDS_PER_COMMIT);
+        let mut ids: Vec<String> = Vec::with_capacity(IDS_PER_COMMIT);
+        let mut count = 0;
+        loop {
+            let doc = futures::TryStreamExt::try_next(&mut docs).await?;
+            let done = doc.is_none();
+            if let Some(doc) = doc {
+                let fields = doc.get("fields").cloned().unwrap_or_else(|| json!({}));
+                let id = firestore_serde::document_id(&doc).unwrap_or_default();
+                if !id.is_empty() && delete(&fields) {
+                    ids.push(id.to_string());
+                }
+            }
+            if ids.len() == IDS_PER_COMMIT || (done && !ids.is_empty()) {
+                let writes: Vec<Value> = ids
+                    .drain(..)
+                    .flat_map(|id| {
+                        [
+                            json!({"delete": self.memory_doc_name(uid, &id)}),
+                            self.sync_tombstone_write(uid, MEMORIES_SUBCOLLECTION, &id),
+                        ]
+                    })
+                    .collect();
+                count += writes.len() / 2;
+                self.commit_batched_writes(writes).await?;
+            }
+            if done {
+                break;
+            }
+        }
+        Ok(count)
+    }
+
+    fn memory_doc_name(&self, uid: &str, memory_id: &str) -> String {
+        format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            MEMORIES_SUBCOLLECTION,
+            memory_id
+        )
+    }
+
+    /// Save memories to Firestore
+    /// Memory IDs are generated from content hash to enable deduplication
+    /// Copied from Python save_memories
+    pub async fn save_memories(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        memories: &[Memory],
+    ) -> Result<Vec<String>, FirestoreError> {
+        let mut saved_ids = Vec::new();
+        let now = Utc::now();
+
+        for memory in memories {
+            let memory_id = document_id_from_seed(&memory.content);
+            let scoring = MemoryDB::calculate_scoring(&memory.category, &now, false);
+
+            let url = format!(
+                "{}/{}/{}/{}/{}",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                MEMORIES_SUBCOLLECTION,
+                memory_id
+            );
+
+            let doc = json!({
+                "fields": {
+                    // CRITICAL: Include id field - Python model requires this
+                    "id": {"stringValue": memory_id},
+                    // Include uid - Python model requires this
+                    "uid": {"stringValue": uid},
+                    "content": {"stringValue": memory.content},
+                    "category": {"stringValue": format!("{:?}", memory.category).to_lowercase()},
+                    "created_at": {"timestampValue": now.to_rfc3339()},
+                    "updated_at": {"timestampValue": now.to_rfc3339()},
+                    "conversation_id": {"stringValue": conversation_id},
+                    // Legacy field - same as conversation_id, used by get_memory_ids_for_conversation
+                    "memory_id": {"stringValue": conversation_id},
+                    "reviewed": {"booleanValue": false},
+                    // CRITICAL: user_review must exist - Python filters on memory['user_review'] is not False
+                    // None/null means not yet reviewed by user (different from False which means rejected)
+                    "user_review": {"nullValue": null},
+                    "visibility": {"stringValue": "private"},
+                    "manually_added": {"booleanValue": false},
+                    "edited": {"booleanValue": false},
+                    "is_locked": {"booleanValue": false},
+                    "kg_extracted": {"booleanValue": false},
+                    "scoring": {"stringValue": scoring},
+                    // Empty tags array
+                    "tags": {"arrayValue": {"values": []}}
+                }
+            });
+
+            let response = self
+                .build_request(reqwest::Method::PATCH, &url)
+                .await?
+                .json(&doc)
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                saved_ids.push(memory_id);
+            } else {
+                tracing::warn!("Failed to save memory: {}", response.text().await?);
+            }
+        }
+
+        tracing::info!(
+            "Saved {} memories for conversation {}",
+            saved_ids.len(),
+            conversation_id
+        );
+        Ok(saved_ids)
+    }
+
+    /// Link a top-level action item to the conversation it came from
+    pub async fn link_action_item_to_conversation(
+        &self,
+        uid: &str,
+        item_id: &str,
+        conversation_id: &str,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=conversation_id&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ACTION_ITEMS_SUBCOLLECTION,
+            item_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "conversation_id": {"stringValue": conversation_id},
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        let updated_doc: Value = response.json().await?;
+        self.parse_action_item(&updated_doc)
+    }
+
+    /// Batch update priorities for multiple action items using Firestore commit API.
+    /// Processes up to 500 writes per commit (Firestore limit).
+    pub async fn batch_update_priorities(
+        &self,
+        uid: &str,
+        priorities: &[(String, String)],
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
+
+        for chunk in priorities.chunks(500) {
             let writes: Vec<Value> = chunk
                 .iter()
-                .map(|(item_id, score)| {
+                .map(|(item_id, priority)| {
                     let doc_name = format!(
                         "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                        self.project_id, USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
+                        self.project_id(), USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
                     );
                     json!({
                         "update": {
                             "name": doc_name,
                             "fields": {
-                                "relevance_score": {"integerValue": score.to_string()},
+                                "priority": {"stringValue": priority},
                                 "updated_at": {"timestampValue": now.to_rfc3339()}
                             }
                         },
                         "updateMask": {
-                            "fieldPaths": ["relevance_score", "updated_at"]
+                            "fieldPaths": ["priority", "updated_at"]
                         }
                     })
                 })
@@ -2510,1034 +3557,1259 @@ impl FirestoreService {
 
             let commit_url = format!(
                 "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-                self.project_id
+                self.project_id()
             );
 
-            let body = json!({ "writes": writes });
-
             let response = self
                 .build_request(reqwest::Method::POST, &commit_url)
                 .await?
-                .json(&body)
+                .json(&json!({ "writes": writes }))
                 .send()
                 .await?;
 
             if !response.status().is_success() {
+                let status = response.status();
                 let error_text = response.text().await?;
-                return Err(format!("Firestore batch commit error: {}", error_text).into());
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
             }
         }
 
         tracing::info!(
-            "Batch updated {} relevance scores for user {}",
-            scores.len(),
+            "Batch updated {} priorities for user {}",
+            priorities.len(),
             uid
         );
         Ok(())
     }
 
-    /// Batch update sort orders and indent levels for multiple action items using Firestore commit API.
-    pub async fn batch_update_sort_orders(
+    /// Roll overdue action items forward using Firestore commit API.
+    /// `updates` are (item_id, new_due_at, new_rollover_count).
+    pub async fn batch_rollover_action_items(
         &self,
         uid: &str,
-        items: &[(String, i32, i32)], // (item_id, sort_order, indent_level)
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        updates: &[(String, DateTime<Utc>, i32)],
+    ) -> Result<(), FirestoreError> {
         let now = Utc::now();
 
-        for chunk in items.chunks(500) {
+        for chunk in updates.chunks(500) {
             let writes: Vec<Value> = chunk
                 .iter()
-                .map(|(item_id, sort_order, indent_level)| {
+                .map(|(item_id, due_at, count)| {
                     let doc_name = format!(
                         "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                        self.project_id, USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
+                        self.project_id(), USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
                     );
                     json!({
                         "update": {
                             "name": doc_name,
                             "fields": {
-                                "sort_order": {"integerValue": sort_order.to_string()},
-                                "indent_level": {"integerValue": indent_level.to_string()},
+                                "due_at": {"timestampValue": due_at.to_rfc3339()},
+                                "rollover_count": {"integerValue": count.to_string()},
                                 "updated_at": {"timestampValue": now.to_rfc3339()}
                             }
                         },
                         "updateMask": {
-                            "fieldPaths": ["sort_order", "indent_level", "updated_at"]
-                        }
+                            "fieldPaths": ["due_at", "rollover_count", "updated_at"]
+                        },
+                        "currentDocument": {"exists": true}
                     })
                 })
                 .collect();
 
             let commit_url = format!(
                 "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-                self.project_id
+                self.project_id()
             );
 
-            let body = json!({ "writes": writes });
-
             let response = self
                 .build_request(reqwest::Method::POST, &commit_url)
                 .await?
-                .json(&body)
+                .json(&json!({ "writes": writes }))
                 .send()
                 .await?;
 
             if !response.status().is_success() {
+                let status = response.status();
                 let error_text = response.text().await?;
-                return Err(format!("Firestore batch commit error: {}", error_text).into());
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
             }
         }
 
-        tracing::info!(
-            "Batch updated {} sort orders for user {}",
-            items.len(),
-            uid
-        );
+        tracing::info!("Rolled over {} action items for user {}", updates.len(), uid);
         Ok(())
     }
 
-    // =========================================================================
-    // STAGED TASKS
-    // =========================================================================
-
-    /// Create a staged task in the staged_tasks subcollection.
-    /// Same schema as action_items but stored separately for promotion workflow.
-    pub async fn create_staged_task(
+    /// Get IDs of users who created action items since `since`.
+    /// Uses a collection group query over all users' action_items subcollections.
+    pub async fn get_users_with_recent_action_items(
         &self,
-        uid: &str,
-        description: &str,
-        due_at: Option<DateTime<Utc>>,
-        source: Option<&str>,
-        priority: Option<&str>,
-        metadata: Option<&str>,
-        category: Option<&str>,
-        relevance_score: Option<i32>,
-    ) -> Result<ActionItemDB, Box<dyn std::error::Error + Send + Sync>> {
-        // Reject empty descriptions
-        let description = description.trim();
-        if description.is_empty() {
-            return Err("Cannot create staged task with empty description".into());
-        }
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
 
-        // Check for exact-match duplicate (case-insensitive)
-        let existing = self.get_staged_tasks(uid, 200, 0).await.unwrap_or_default();
-        let desc_lower = description.to_lowercase();
-        if existing.iter().any(|t| t.description.trim().to_lowercase() == desc_lower) {
-            tracing::info!(
-                "Skipping duplicate staged task for user {}: {}",
-                uid,
-                &description[..description.len().min(80)]
-            );
-            // Return the existing item instead of creating a duplicate
-            let existing_item = existing
-                .into_iter()
-                .find(|t| t.description.trim().to_lowercase() == desc_lower)
-                .unwrap();
-            return Ok(existing_item);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION, "allDescendants": true}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "created_at"},
+                        "op": "GREATER_THAN_OR_EQUAL",
+                        "value": {"timestampValue": since.to_rfc3339()}
+                    }
+                },
+                "limit": limit
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        let item_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
+        let results: Vec<Value> = response.json().await?;
+        let mut uids: Vec<String> = Vec::new();
 
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            STAGED_TASKS_SUBCOLLECTION,
-            item_id
-        );
+        for result in results {
+            // name: .../documents/users/{uid}/action_items/{item_id}
+            let uid = result
+                .get("document")
+                .and_then(|d| d.get("name"))
+                .and_then(|n| n.as_str())
+                .and_then(|name| name.split('/').rev().nth(2))
+                .map(|s| s.to_string());
+            if let Some(uid) = uid {
+                if !uids.contains(&uid) {
+                    uids.push(uid);
+                }
+            }
+        }
 
-        let mut fields = json!({
-            "description": {"stringValue": description},
-            "completed": {"booleanValue": false},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
-        });
+        Ok(uids)
+    }
 
-        if let Some(due) = due_at {
-            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
+    // =========================================================================
+    // ACTION ITEMS
+    // =========================================================================
+
+    /// Structured query (collection, filters and order) behind the action item list
+    /// and export. `deleted` and snooze filtering happen after the query.
+    fn action_items_query(
+        completed_filter: Option<bool>,
+        conversation_id: Option<&str>,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        due_start_date: Option<&str>,
+        due_end_date: Option<&str>,
+        sort_by: Option<&str>,
+    ) -> Value {
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        if let Some(completed) = completed_filter {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "completed"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": completed}
+                }
+            }));
         }
-        if let Some(src) = source {
-            fields["source"] = json!({"stringValue": src});
+
+        // Conversation ID filter
+        if let Some(conv_id) = conversation_id {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "conversation_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": conv_id}
+                }
+            }));
         }
-        if let Some(pri) = priority {
-            fields["priority"] = json!({"stringValue": pri});
+
+        // Date range filters for created_at
+        if let Some(start) = start_date {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "created_at"},
+                    "op": "GREATER_THAN_OR_EQUAL",
+                    "value": {"timestampValue": start}
+                }
+            }));
         }
-        if let Some(meta) = metadata {
-            fields["metadata"] = json!({"stringValue": meta});
+
+        if let Some(end) = end_date {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "created_at"},
+                    "op": "LESS_THAN_OR_EQUAL",
+                    "value": {"timestampValue": end}
+                }
+            }));
         }
-        if let Some(cat) = category {
-            fields["category"] = json!({"stringValue": cat});
+
+        // Date range filters for due_at
+        if let Some(due_start) = due_start_date {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "due_at"},
+                    "op": "GREATER_THAN_OR_EQUAL",
+                    "value": {"timestampValue": due_start}
+                }
+            }));
         }
-        if let Some(score) = relevance_score {
-            fields["relevance_score"] = json!({"integerValue": score.to_string()});
+
+        if let Some(due_end) = due_end_date {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "due_at"},
+                    "op": "LESS_THAN_OR_EQUAL",
+                    "value": {"timestampValue": due_end}
+                }
+            }));
         }
 
-        let doc = json!({"fields": fields});
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
 
-        let response = self
-            .build_request(reqwest::Method::PATCH, &url)
-            .await?
-            .json(&doc)
-            .send()
-            .await?;
+        // Build order by clause based on sort_by parameter
+        let order_by = match sort_by {
+            Some("due_at") => json!([
+                {"field": {"fieldPath": "due_at"}, "direction": "ASCENDING"},
+                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
+            ]),
+            Some("priority") => json!([
+                {"field": {"fieldPath": "priority"}, "direction": "DESCENDING"},
+                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
+            ]),
+            _ => json!([
+                {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
+            ]),
+        };
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore create staged task error: {}", error_text).into());
+        let mut structured_query = json!({
+            "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+            "orderBy": order_by
+        });
+        if let Some(where_filter) = where_clause {
+            structured_query["where"] = where_filter;
         }
-
-        let created_doc: Value = response.json().await?;
-        let item = self.parse_action_item(&created_doc)?;
-
-        tracing::info!(
-            "Created staged task {} for user {} with source={:?}",
-            item_id,
-            uid,
-            source
-        );
-        Ok(item)
+        structured_query
     }
 
-    /// Migrate action items that were created by the old conversation extraction path
-    /// (have conversation_id but no source field) to staged_tasks.
-    /// Returns (migrated_count, deleted_count).
-    pub async fn migrate_conversation_action_items_to_staged(
+    /// Stream action items matching the list filters in query order, a page at a time,
+    /// for exports too large to hold in memory
+    #[allow(clippy::too_many_arguments)]
+    pub fn stream_action_items(
         &self,
         uid: &str,
-    ) -> Result<(usize, usize), Box<dyn std::error::Error + Send + Sync>> {
-        // Fetch all incomplete, non-deleted action items.
-        // NOTE: get_action_items runs enrich_action_items_with_source which populates
-        // the source field from the conversation. So we can't check source.is_none()
-        // after that. Instead, we filter by conversation_id.is_some() — all items with
-        // a conversation_id were created by the old save_action_items path (confirmed
-        // 0 false positives: no items have both conversation_id AND a real source in Firestore).
-        let all_items = self
-            .get_action_items(uid, 10000, 0, Some(false), None, None, None, None, None, None, None)
-            .await?;
-
-        // Filter: has conversation_id → created by old save_action_items path
-        let bad_items: Vec<ActionItemDB> = all_items
-            .into_iter()
-            .filter(|item| item.conversation_id.is_some())
-            .collect();
-
-        if bad_items.is_empty() {
-            tracing::info!("No conversation action items to migrate for user {}", uid);
-            return Ok((0, 0));
-        }
-
-        tracing::info!(
-            "Found {} conversation action items to migrate for user {}",
-            bad_items.len(),
-            uid
-        );
-
-        // Use batch_migrate_to_staged for fast batch commits (250 items per batch)
-        let migrated = self.batch_migrate_to_staged(uid, &bad_items).await?;
-
-        tracing::info!(
-            "Migration complete for user {}: {} migrated out of {} candidates",
-            uid,
-            migrated,
-            bad_items.len()
+        completed_filter: Option<bool>,
+        conversation_id: Option<&str>,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        due_start_date: Option<&str>,
+        due_end_date: Option<&str>,
+        sort_by: Option<&str>,
+        include_deleted: Option<bool>,
+        include_snoozed: bool,
+    ) -> DocumentStream<'_, ActionItemDB> {
+        const PAGE_SIZE: usize = 500;
+        let uid = uid.to_string();
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = Self::action_items_query(
+            completed_filter,
+            conversation_id,
+            start_date,
+            end_date,
+            due_start_date,
+            due_end_date,
+            sort_by,
         );
 
-        Ok((migrated, migrated))
+        Box::pin(async_stream::try_stream! {
+            let now = Utc::now();
+            let mut docs = self.stream_query(&parent, query, PAGE_SIZE);
+            let mut page: Vec<ActionItemDB> = Vec::with_capacity(PAGE_SIZE);
+            loop {
+                let doc = futures::TryStreamExt::try_next(&mut docs).await?;
+                let done = doc.is_none();
+                if let Some(item) = doc.and_then(|d| self.parse_action_item(&d).ok()) {
+                    let deleted_matches = if include_deleted == Some(true) {
+                        item.deleted == Some(true)
+                    } else {
+                        item.deleted != Some(true)
+                    };
+                    if deleted_matches && (include_snoozed || item.snoozed_until.is_none_or(|until| until <= now)) {
+                        page.push(item);
+                    }
+                }
+                // Source enrichment is batched per page
+                if page.len() == PAGE_SIZE || (done && !page.is_empty()) {
+                    self.enrich_action_items_with_source(&uid, &mut page).await;
+                    for item in page.drain(..) {
+                        yield item;
+                    }
+                }
+                if done {
+                    break;
+                }
+            }
+        })
     }
 
-    /// Get staged tasks ordered by relevance_score ASC (best ranked first).
-    /// Filters out deleted and completed tasks.
-    pub async fn get_staged_tasks(
+    /// Get action items for a user
+    /// Path: users/{uid}/action_items
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_action_items(
         &self,
         uid: &str,
         limit: usize,
         offset: usize,
-    ) -> Result<Vec<ActionItemDB>, Box<dyn std::error::Error + Send + Sync>> {
+        completed_filter: Option<bool>,
+        conversation_id: Option<&str>,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        due_start_date: Option<&str>,
+        due_end_date: Option<&str>,
+        sort_by: Option<&str>,
+        include_deleted: Option<bool>,
+        include_snoozed: bool,
+    ) -> Result<Vec<ActionItemDB>, FirestoreError> {
         let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let now = Utc::now();
 
-        // Query non-completed staged tasks ordered by relevance_score ASC
-        let filters = vec![
-            json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "completed"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": false}
-                }
-            }),
-        ];
+        let base_query = Self::action_items_query(
+            completed_filter,
+            conversation_id,
+            start_date,
+            end_date,
+            due_start_date,
+            due_end_date,
+            sort_by,
+        );
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": STAGED_TASKS_SUBCOLLECTION}],
-                "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": filters
-                    }
-                },
-                "orderBy": [
-                    {"field": {"fieldPath": "relevance_score"}, "direction": "ASCENDING"},
-                    {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
-                ],
-                "limit": limit + offset
-            }
-        });
+        // Fetch from Firestore in a loop to handle post-query deleted filtering.
+        // Since `deleted` can't be reliably filtered in Firestore (most docs lack the field),
+        // we filter in Rust. But this means a single Firestore page may yield fewer items
+        // than requested after filtering, so we keep fetching until we have enough or Firestore
+        // is exhausted.
+        let mut action_items: Vec<ActionItemDB> = Vec::new();
+        let mut current_offset = offset;
+        let fetch_batch = limit.max(500); // fetch in large batches to minimize round-trips
 
-        let query_url = format!("{}:runQuery", parent);
-        let response = self
-            .build_request(reqwest::Method::POST, &query_url)
-            .await?
-            .json(&query)
-            .send()
-            .await?;
+        loop {
+            let mut structured_query = base_query.clone();
+            structured_query["limit"] = json!(fetch_batch);
+            structured_query["offset"] = json!(current_offset);
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query staged tasks error: {}", error_text).into());
+            let query = json!({
+                "structuredQuery": structured_query
+            });
+
+            let response = self
+                .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+                .await?
+                .json(&query)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                tracing::error!("Firestore query error: {}", error_text);
+                break;
+            }
+
+            let results: Vec<Value> = response.json().await?;
+            let fetched_count = results.iter().filter(|doc| doc.get("document").is_some()).count();
+
+            let batch: Vec<ActionItemDB> = results
+                .into_iter()
+                .filter_map(|doc| {
+                    doc.get("document")
+                        .and_then(|d| self.parse_action_item(d).ok())
+                })
+                // Filter based on deleted status
+                .filter(|item| {
+                    if include_deleted == Some(true) {
+                        item.deleted == Some(true)
+                    } else {
+                        item.deleted != Some(true)
+                    }
+                })
+                // Snoozed items stay hidden until their resurface time
+                .filter(|item| include_snoozed || item.snoozed_until.is_none_or(|until| until <= now))
+                .collect();
+
+            action_items.extend(batch);
+            current_offset += fetched_count;
+
+            // Stop if Firestore returned fewer than requested (no more data)
+            if fetched_count < fetch_batch {
+                break;
+            }
+
+            // Stop if we have enough items
+            if action_items.len() >= limit {
+                action_items.truncate(limit);
+                break;
+            }
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let items: Vec<ActionItemDB> = results
+        // Enrich action items that have conversation_id but no source
+        self.enrich_action_items_with_source(uid, &mut action_items).await;
+
+        // Post-query sort matching Python backend behavior (used by iOS/Flutter app):
+        // 1. Items WITH due_at come first (sorted by due_at ascending)
+        // 2. Items WITHOUT due_at come last
+        // 3. Tie-breaker: created_at descending (newest first)
+        action_items.sort_by(|a, b| {
+            match (&a.due_at, &b.due_at) {
+                (Some(due_a), Some(due_b)) => {
+                    due_a.cmp(due_b).then_with(|| b.created_at.cmp(&a.created_at))
+                }
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => b.created_at.cmp(&a.created_at),
+            }
+        });
+
+        Ok(action_items)
+    }
+
+    /// Batch fetch conversations and populate source field on action items
+    /// For items with conversation_id but no source, derives source as "transcription:{conversation.source}"
+    async fn enrich_action_items_with_source(&self, uid: &str, action_items: &mut [ActionItemDB]) {
+        use std::collections::{HashMap, HashSet};
+
+        // Collect unique conversation IDs from items that need enrichment
+        // (have conversation_id but no source)
+        let conversation_ids: HashSet<&str> = action_items
             .iter()
-            .filter_map(|r| r.get("document"))
-            .filter_map(|doc| self.parse_action_item(doc).ok())
-            .filter(|item| item.deleted != Some(true))
-            .skip(offset)
+            .filter(|item| item.source.is_none() && item.conversation_id.is_some())
+            .filter_map(|item| item.conversation_id.as_deref())
             .collect();
 
-        Ok(items)
+        if conversation_ids.is_empty() {
+            return;
+        }
+
+        tracing::debug!(
+            "Enriching {} action items with source from {} conversations",
+            action_items.iter().filter(|i| i.source.is_none()).count(),
+            conversation_ids.len()
+        );
+
+        // Fetch conversations in parallel (limit to avoid too many concurrent requests)
+        let mut source_map: HashMap<String, String> = HashMap::new();
+
+        // Batch fetch - fetch up to 10 at a time
+        let ids: Vec<&str> = conversation_ids.into_iter().collect();
+        for chunk in ids.chunks(10) {
+            let futures: Vec<_> = chunk
+                .iter()
+                .map(|id| self.get_conversation(uid, id))
+                .collect();
+
+            let results = futures::future::join_all(futures).await;
+
+            for (id, result) in chunk.iter().zip(results) {
+                if let Ok(Some(conv)) = result {
+                    // Format as "transcription:{source}" to match expected values
+                    // e.g., "transcription:omi", "transcription:desktop"
+                    let source_str = format!("transcription:{:?}", conv.source).to_lowercase();
+                    source_map.insert(id.to_string(), source_str);
+                }
+            }
+        }
+
+        // Populate source field on action items that don't have one
+        for item in action_items.iter_mut() {
+            if item.source.is_none() {
+                if let Some(conv_id) = &item.conversation_id {
+                    item.source = source_map.get(conv_id).cloned();
+                }
+            }
+        }
     }
 
-    /// Hard-delete a staged task (permanently remove from Firestore).
-    pub async fn delete_staged_task(
+    /// Get a single action item by ID
+    pub async fn get_action_item_by_id(
+        &self,
+        uid: &str,
+        item_id: &str,
+    ) -> Result<Option<ActionItemDB>, FirestoreError> {
+        Ok(self
+            .get_action_item_versioned(uid, item_id)
+            .await?
+            .map(|(item, _)| item))
+    }
+
+    /// An action item with its document `updateTime`, for use as a
+    /// [`Precondition::UpdateTime`] on a following write
+    pub async fn get_action_item_versioned(
         &self,
         uid: &str,
         item_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<(ActionItemDB, String)>, FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            STAGED_TASKS_SUBCOLLECTION,
+            ACTION_ITEMS_SUBCOLLECTION,
             item_id
         );
 
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete staged task error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        tracing::info!("Deleted staged task {} for user {}", item_id, uid);
-        Ok(())
+        let doc: Value = response.json().await?;
+        let mut action_item = self.parse_action_item(&doc)?;
+        let update_time = doc
+            .get("updateTime")
+            .and_then(|t| t.as_str())
+            .ok_or("Missing updateTime")?
+            .to_string();
+
+        // Enrich with source from conversation if needed
+        if action_item.source.is_none() {
+            if let Some(conv_id) = &action_item.conversation_id {
+                if let Ok(Some(conv)) = self.get_conversation(uid, conv_id).await {
+                    action_item.source = Some(format!("transcription:{:?}", conv.source).to_lowercase());
+                }
+            }
+        }
+
+        Ok(Some((action_item, update_time)))
     }
 
-    /// Batch update relevance scores for staged tasks.
-    pub async fn batch_update_staged_scores(
+    /// Update an action item
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_action_item(
         &self,
         uid: &str,
-        scores: &[(String, i32)],
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
+        item_id: &str,
+        completed: Option<bool>,
+        description: Option<&str>,
+        due_at: Option<DateTime<Utc>>,
+        priority: Option<&str>,
+        category: Option<&str>,
+        goal_id: Option<&str>,
+        relevance_score: Option<i32>,
+        sort_order: Option<i32>,
+        indent_level: Option<i32>,
+        recurrence_rule: Option<&str>,
+        rollover_excluded: Option<bool>,
+        precondition: Option<&Precondition>,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        // Build update mask and fields
+        let mut field_paths: Vec<&str> = vec!["updated_at"];
+        let mut fields = json!({
+            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+        });
 
-        for chunk in scores.chunks(500) {
-            let writes: Vec<Value> = chunk
-                .iter()
-                .map(|(item_id, score)| {
-                    let doc_name = format!(
-                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                        self.project_id, USERS_COLLECTION, uid, STAGED_TASKS_SUBCOLLECTION, item_id
-                    );
-                    json!({
-                        "update": {
-                            "name": doc_name,
-                            "fields": {
-                                "relevance_score": {"integerValue": score.to_string()},
-                                "updated_at": {"timestampValue": now.to_rfc3339()}
-                            }
-                        },
-                        "updateMask": {
-                            "fieldPaths": ["relevance_score", "updated_at"]
-                        }
-                    })
-                })
-                .collect();
+        if let Some(c) = completed {
+            field_paths.push("completed");
+            fields["completed"] = json!({"booleanValue": c});
 
-            let commit_url = format!(
-                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-                self.project_id
-            );
+            // Set or clear completed_at based on completion status
+            field_paths.push("completed_at");
+            if c {
+                fields["completed_at"] = json!({"timestampValue": Utc::now().to_rfc3339()});
+            } else {
+                // Clear completed_at when marking as incomplete (matches Python backend behavior)
+                fields["completed_at"] = json!({"nullValue": null});
+            }
+        }
 
-            let body = json!({ "writes": writes });
+        if let Some(d) = description {
+            field_paths.push("description");
+            fields["description"] = json!({"stringValue": d});
+        }
 
-            let response = self
-                .build_request(reqwest::Method::POST, &commit_url)
-                .await?
-                .json(&body)
-                .send()
-                .await?;
+        if let Some(due) = due_at {
+            field_paths.push("due_at");
+            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
+        }
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(format!("Firestore batch commit staged scores error: {}", error_text).into());
-            }
+        if let Some(pri) = priority {
+            field_paths.push("priority");
+            fields["priority"] = json!({"stringValue": pri});
         }
 
-        tracing::info!(
-            "Batch updated {} staged task scores for user {}",
-            scores.len(),
-            uid
-        );
-        Ok(())
-    }
-
-    /// Batch migrate tasks from action_items to staged_tasks using Firestore commit API.
-    /// Each task is created in staged_tasks and deleted from action_items atomically.
-    /// Processes 250 tasks per commit (each needs 2 writes, Firestore limit is 500).
-    pub async fn batch_migrate_to_staged(
-        &self,
-        uid: &str,
-        tasks: &[ActionItemDB],
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-        let mut migrated_count = 0;
-
-        // 250 tasks per batch (each task = 2 writes: create + delete, limit 500)
-        for chunk in tasks.chunks(250) {
-            let mut writes: Vec<Value> = Vec::new();
-
-            for task in chunk {
-                let staged_id = uuid::Uuid::new_v4().to_string();
-                let staged_doc_name = format!(
-                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                    self.project_id, USERS_COLLECTION, uid, STAGED_TASKS_SUBCOLLECTION, staged_id
-                );
-
-                let mut fields = json!({
-                    "description": {"stringValue": &task.description},
-                    "completed": {"booleanValue": false},
-                    "created_at": {"timestampValue": now.to_rfc3339()},
-                    "updated_at": {"timestampValue": now.to_rfc3339()}
-                });
-                if let Some(ref due) = task.due_at {
-                    fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
-                }
-                if let Some(ref src) = task.source {
-                    fields["source"] = json!({"stringValue": src});
-                }
-                if let Some(ref pri) = task.priority {
-                    fields["priority"] = json!({"stringValue": pri});
-                }
-                if let Some(ref meta) = task.metadata {
-                    fields["metadata"] = json!({"stringValue": meta});
-                }
-                if let Some(ref cat) = task.category {
-                    fields["category"] = json!({"stringValue": cat});
-                }
-                if let Some(score) = task.relevance_score {
-                    fields["relevance_score"] = json!({"integerValue": score.to_string()});
-                }
-
-                // Write 1: Create in staged_tasks
-                writes.push(json!({
-                    "update": {
-                        "name": staged_doc_name,
-                        "fields": fields
-                    }
-                }));
+        if let Some(cat) = category {
+            field_paths.push("category");
+            fields["category"] = json!({"stringValue": cat});
+        }
 
-                // Write 2: Delete from action_items
-                let action_doc_name = format!(
-                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                    self.project_id, USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, task.id
-                );
-                writes.push(json!({
-                    "delete": action_doc_name
-                }));
-            }
+        if let Some(gid) = goal_id {
+            field_paths.push("goal_id");
+            fields["goal_id"] = json!({"stringValue": gid});
+        }
 
-            let commit_url = format!(
-                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-                self.project_id
-            );
+        if let Some(score) = relevance_score {
+            field_paths.push("relevance_score");
+            fields["relevance_score"] = json!({"integerValue": score.to_string()});
+        }
 
-            let body = json!({ "writes": writes });
+        if let Some(order) = sort_order {
+            field_paths.push("sort_order");
+            fields["sort_order"] = json!({"integerValue": order.to_string()});
+        }
 
-            let response = self
-                .build_request(reqwest::Method::POST, &commit_url)
-                .await?
-                .json(&body)
-                .send()
-                .await?;
+        if let Some(indent) = indent_level {
+            field_paths.push("indent_level");
+            fields["indent_level"] = json!({"integerValue": indent.to_string()});
+        }
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(format!("Firestore batch migrate commit error: {}", error_text).into());
+        if let Some(rule) = recurrence_rule {
+            field_paths.push("recurrence_rule");
+            if rule.is_empty() {
+                fields["recurrence_rule"] = json!({"nullValue": null});
+            } else {
+                fields["recurrence_rule"] = json!({"stringValue": rule});
             }
+        }
 
-            migrated_count += chunk.len();
-            tracing::info!(
-                "Batch migrated {} tasks ({} total so far) for user {}",
-                chunk.len(),
-                migrated_count,
-                uid
-            );
+        if let Some(excluded) = rollover_excluded {
+            field_paths.push("rollover_excluded");
+            fields["rollover_excluded"] = json!({"booleanValue": excluded});
         }
 
-        Ok(migrated_count)
-    }
+        let update_mask = field_paths
+            .iter()
+            .map(|p| format!("updateMask.fieldPaths={}", p))
+            .collect::<Vec<_>>()
+            .join("&");
 
-    /// Count active AI action items promoted from staged_tasks (from_staged=true, not completed, not deleted).
-    /// Used by the promotion system to determine if more tasks should be promoted.
-    pub async fn count_active_ai_action_items(
-        &self,
-        uid: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ACTION_ITEMS_SUBCOLLECTION,
+            item_id,
+            update_mask
+        );
+        let url = match precondition {
+            Some(precondition) => format!("{}&{}", url, precondition.query_param()),
+            None => url,
+        };
 
-        // Composite filter: from_staged=true AND completed=false at Firestore level
-        // so we don't miss items when users have thousands of action_items
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
-                "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": [
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "completed"},
-                                    "op": "EQUAL",
-                                    "value": {"booleanValue": false}
-                                }
-                            },
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "from_staged"},
-                                    "op": "EQUAL",
-                                    "value": {"booleanValue": true}
-                                }
-                            }
-                        ]
-                    }
-                },
-                "limit": 100
-            }
-        });
+        let doc = json!({"fields": fields});
 
-        let query_url = format!("{}:runQuery", parent);
         let response = self
-            .build_request(reqwest::Method::POST, &query_url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await?;
-            return Err(format!("Firestore count AI items error: {}", error_text).into());
+            if is_precondition_failure(status, &error_text) {
+                return Err(FirestoreError::FailedPrecondition(error_text));
+            }
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let count = results
-            .iter()
-            .filter_map(|r| r.get("document"))
-            .filter_map(|doc| self.parse_action_item(doc).ok())
-            .filter(|item| {
-                item.deleted != Some(true)
-                    && item.from_staged == Some(true)
-            })
-            .count();
-
-        Ok(count)
-    }
-
-    /// Get active AI action items promoted from staged_tasks (from_staged=true, not completed, not deleted).
-    /// Returns the actual items for dedup comparison during promotion.
-    /// Uses a composite filter to query from_staged=true AND completed=false at the Firestore level.
-    pub async fn get_active_ai_action_items(
-        &self,
-        uid: &str,
-    ) -> Result<Vec<ActionItemDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        // Parse and return the updated document
+        let updated_doc: Value = response.json().await?;
+        let mut action_item = self.parse_action_item(&updated_doc)?;
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
-                "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": [
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "completed"},
-                                    "op": "EQUAL",
-                                    "value": {"booleanValue": false}
-                                }
-                            },
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "from_staged"},
-                                    "op": "EQUAL",
-                                    "value": {"booleanValue": true}
-                                }
-                            }
-                        ]
-                    }
-                },
-                "limit": 100
+        // Enrich with source from conversation if needed
+        if action_item.source.is_none() {
+            if let Some(conv_id) = &action_item.conversation_id {
+                if let Ok(Some(conv)) = self.get_conversation(uid, conv_id).await {
+                    action_item.source = Some(format!("transcription:{:?}", conv.source).to_lowercase());
+                }
             }
-        });
-
-        let query_url = format!("{}:runQuery", parent);
-        let response = self
-            .build_request(reqwest::Method::POST, &query_url)
-            .await?
-            .json(&query)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore get active AI items error: {}", error_text).into());
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let items: Vec<ActionItemDB> = results
-            .iter()
-            .filter_map(|r| r.get("document"))
-            .filter_map(|doc| self.parse_action_item(doc).ok())
-            .filter(|item| {
-                item.deleted != Some(true)
-                    && item.from_staged == Some(true)
-            })
-            .collect();
-
-        Ok(items)
+        tracing::info!("Updated action item {} for user {}", item_id, uid);
+        Ok(action_item)
     }
 
-    /// Get a single staged task by ID.
-    pub async fn get_staged_task_by_id(
+    /// Delete an action item
+    pub async fn delete_action_item(
         &self,
         uid: &str,
         item_id: &str,
-    ) -> Result<Option<ActionItemDB>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            STAGED_TASKS_SUBCOLLECTION,
+            ACTION_ITEMS_SUBCOLLECTION,
             item_id
         );
 
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::DELETE, &url)
             .await?
             .send()
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Ok(None);
-        }
-
-        if !response.status().is_success() {
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore get staged task error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        let item = self.parse_action_item(&doc)?;
-        Ok(Some(item))
+        // Firestore doesn't cascade deletes to subcollections
+        if let Err(e) = self.delete_action_item_notes(uid, item_id).await {
+            tracing::warn!("Failed to delete notes of action item {}: {}", item_id, e);
+        }
+        self.record_deletion(uid, ACTION_ITEMS_SUBCOLLECTION, item_id).await;
+        tracing::info!("Deleted action item {} for user {}", item_id, uid);
+        Ok(())
     }
 
-    // =========================================================================
-    // APPS
-    // =========================================================================
-
-    /// Get all apps with optional filters
-    pub async fn get_apps(
+    /// Soft-delete an action item (mark as deleted without removing from Firestore)
+    pub async fn soft_delete_action_item(
         &self,
         uid: &str,
-        limit: usize,
-        offset: usize,
-        capability: Option<&str>,
-        category: Option<&str>,
-    ) -> Result<Vec<AppSummary>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = self.base_url();
-
-        // Build filters (matching Python backend: approved=True AND private=False)
-        let mut filters: Vec<Value> = vec![
-            // Only approved apps
-            json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "approved"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": true}
-                }
-            }),
-            // Only public apps (not private)
-            json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "private"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": false}
-                }
-            }),
+        item_id: &str,
+        deleted_by: &str,
+        reason: &str,
+        kept_task_id: &str,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        let field_paths = vec![
+            "deleted", "deleted_by", "deleted_at", "deleted_reason", "kept_task_id", "updated_at",
         ];
 
-        if let Some(cat) = category {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "category"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": cat}
-                }
-            }));
-        }
-
-        // Build where clause
-        let where_clause = if filters.len() == 1 {
-            filters.into_iter().next()
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
-
-        // Note: We don't use orderBy in the query because it would require a composite index
-        // Instead, we fetch all matching apps and sort in memory (matching Python backend behavior)
-        let mut structured_query = json!({
-            "from": [{"collectionId": APPS_COLLECTION}]
+        let fields = json!({
+            "deleted": {"booleanValue": true},
+            "deleted_by": {"stringValue": deleted_by},
+            "deleted_at": {"timestampValue": Utc::now().to_rfc3339()},
+            "deleted_reason": {"stringValue": reason},
+            "kept_task_id": {"stringValue": kept_task_id},
+            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
         });
 
-        if let Some(where_filter) = where_clause {
-            structured_query["where"] = where_filter;
-        }
+        let update_mask = field_paths
+            .iter()
+            .map(|p| format!("updateMask.fieldPaths={}", p))
+            .collect::<Vec<_>>()
+            .join("&");
 
-        let query = json!({
-            "structuredQuery": structured_query
-        });
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ACTION_ITEMS_SUBCOLLECTION,
+            item_id,
+            update_mask
+        );
+
+        let doc = json!({"fields": fields});
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore soft-delete error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
+        let updated_doc: Value = response.json().await?;
+        let action_item = self.parse_action_item(&updated_doc)?;
 
-        // Get user's enabled apps to mark them
-        let enabled_app_ids = self.get_enabled_app_ids(uid).await.unwrap_or_default();
+        tracing::info!("Soft-deleted action item {} for user {} (by: {}, reason: {})", item_id, uid, deleted_by, reason);
+        Ok(action_item)
+    }
 
-        let mut apps: Vec<AppSummary> = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_app_summary(d).ok())
-            })
-            .collect();
+    /// Save action items to Firestore
+    /// Create a single action item (for API/desktop creation)
+    pub async fn create_action_item(
+        &self,
+        uid: &str,
+        description: &str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&str>,
+        priority: Option<&str>,
+        metadata: Option<&str>,
+        category: Option<&str>,
+        relevance_score: Option<i32>,
+        from_staged: Option<bool>,
+        recurrence_rule: Option<&str>,
+        recurrence_parent_id: Option<&str>,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        let item_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ACTION_ITEMS_SUBCOLLECTION,
+            item_id
+        );
+
+        let mut fields = json!({
+            "description": {"stringValue": description},
+            "completed": {"booleanValue": false},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-        // Filter by capability if specified
-        if let Some(cap) = capability {
-            apps.retain(|app| app.capabilities.contains(&cap.to_string()));
+        if let Some(due) = due_at {
+            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
         }
 
-        // Mark enabled apps
-        for app in &mut apps {
-            app.enabled = enabled_app_ids.contains(&app.id);
+        if let Some(src) = source {
+            fields["source"] = json!({"stringValue": src});
         }
 
-        // Sort by installs descending (in memory, to avoid needing composite index)
-        apps.sort_by(|a, b| b.installs.cmp(&a.installs));
+        if let Some(pri) = priority {
+            fields["priority"] = json!({"stringValue": pri});
+        }
 
-        // Apply pagination
-        let start = offset.min(apps.len());
-        let end = (offset + limit).min(apps.len());
-        Ok(apps[start..end].to_vec())
-    }
+        if let Some(meta) = metadata {
+            fields["metadata"] = json!({"stringValue": meta});
+        }
 
-    /// Get approved public apps
-    pub async fn get_approved_apps(
-        &self,
-        uid: &str,
-        limit: usize,
-        offset: usize,
-    ) -> Result<Vec<AppSummary>, Box<dyn std::error::Error + Send + Sync>> {
-        self.get_apps(uid, limit, offset, None, None).await
-    }
+        if let Some(cat) = category {
+            fields["category"] = json!({"stringValue": cat});
+        }
 
-    /// Get popular apps (apps marked with is_popular=true, matching Python backend behavior)
-    pub async fn get_popular_apps(
-        &self,
-        uid: &str,
-        limit: usize,
-    ) -> Result<Vec<AppSummary>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = self.base_url();
+        if let Some(score) = relevance_score {
+            fields["relevance_score"] = json!({"integerValue": score.to_string()});
+        }
 
-        // Query for apps where approved=true AND is_popular=true (matching Python backend)
-        let filters = vec![
-            json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "approved"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": true}
-                }
-            }),
-            json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "is_popular"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": true}
-                }
-            }),
-        ];
+        if let Some(staged) = from_staged {
+            fields["from_staged"] = json!({"booleanValue": staged});
+        }
 
-        let where_clause = json!({
-            "compositeFilter": {
-                "op": "AND",
-                "filters": filters
-            }
-        });
+        if let Some(rule) = recurrence_rule {
+            fields["recurrence_rule"] = json!({"stringValue": rule});
+        }
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": APPS_COLLECTION}],
-                "where": where_clause
-            }
-        });
+        if let Some(pid) = recurrence_parent_id {
+            fields["recurrence_parent_id"] = json!({"stringValue": pid});
+        }
+
+        let doc = json!({"fields": fields});
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error for popular apps: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
+        // Parse and return the created document
+        let created_doc: Value = response.json().await?;
+        let action_item = self.parse_action_item(&created_doc)?;
 
-        // Get user's enabled apps to mark them
-        let enabled_app_ids = self.get_enabled_app_ids(uid).await.unwrap_or_default();
+        tracing::info!(
+            "Created action item {} for user {} with source={:?}",
+            item_id,
+            uid,
+            source
+        );
+        Ok(action_item)
+    }
 
-        let mut apps: Vec<AppSummary> = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_app_summary(d).ok())
-            })
-            .collect();
+    /// Batch update relevance scores for multiple action items using Firestore commit API.
+    /// Processes up to 500 writes per commit (Firestore limit).
+    pub async fn batch_update_scores(
+        &self,
+        uid: &str,
+        scores: &[(String, i32)],
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
 
-        // Mark enabled apps
-        for app in &mut apps {
-            app.enabled = enabled_app_ids.contains(&app.id);
-        }
+        for chunk in scores.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|(item_id, score)| {
+                    let doc_name = format!(
+                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                        self.project_id(), USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
+                    );
+                    json!({
+                        "update": {
+                            "name": doc_name,
+                            "fields": {
+                                "relevance_score": {"integerValue": score.to_string()},
+                                "updated_at": {"timestampValue": now.to_rfc3339()}
+                            }
+                        },
+                        "updateMask": {
+                            "fieldPaths": ["relevance_score", "updated_at"]
+                        }
+                    })
+                })
+                .collect();
 
-        // Sort by installs descending (matching Python backend behavior)
-        apps.sort_by(|a, b| b.installs.cmp(&a.installs));
+            let commit_url = format!(
+                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+                self.project_id()
+            );
 
-        apps.truncate(limit);
-        Ok(apps)
+            let body = json!({ "writes": writes });
+
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&body)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
+            }
+        }
+
+        tracing::info!(
+            "Batch updated {} relevance scores for user {}",
+            scores.len(),
+            uid
+        );
+        Ok(())
     }
 
-    /// Search apps with filters
-    pub async fn search_apps(
+    /// Batch update sort orders and indent levels for multiple action items using Firestore commit API.
+    pub async fn batch_update_sort_orders(
         &self,
         uid: &str,
-        query: Option<&str>,
-        category: Option<&str>,
-        capability: Option<&str>,
-        min_rating: Option<i32>,
-        my_apps: bool,
-        installed_only: bool,
-        limit: usize,
-        offset: usize,
-    ) -> Result<Vec<AppSummary>, Box<dyn std::error::Error + Send + Sync>> {
-        // Start with all apps
-        let mut apps = self.get_apps(uid, 500, 0, capability, category).await?;
+        items: &[(String, i32, i32)], // (item_id, sort_order, indent_level)
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
 
-        // Filter by query (name/description)
-        if let Some(q) = query {
-            let q_lower = q.to_lowercase();
-            apps.retain(|app| {
-                app.name.to_lowercase().contains(&q_lower)
-                    || app.description.to_lowercase().contains(&q_lower)
-            });
-        }
+        for chunk in items.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|(item_id, sort_order, indent_level)| {
+                    let doc_name = format!(
+                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                        self.project_id(), USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, item_id
+                    );
+                    json!({
+                        "update": {
+                            "name": doc_name,
+                            "fields": {
+                                "sort_order": {"integerValue": sort_order.to_string()},
+                                "indent_level": {"integerValue": indent_level.to_string()},
+                                "updated_at": {"timestampValue": now.to_rfc3339()}
+                            }
+                        },
+                        "updateMask": {
+                            "fieldPaths": ["sort_order", "indent_level", "updated_at"]
+                        }
+                    })
+                })
+                .collect();
 
-        // Filter by minimum rating
-        if let Some(min) = min_rating {
-            apps.retain(|app| app.rating_avg.unwrap_or(0.0) >= min as f64);
-        }
+            let commit_url = format!(
+                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+                self.project_id()
+            );
 
-        // Filter by my apps (apps owned by the user)
-        if my_apps {
-            // For now, we don't have uid in AppSummary, so skip this filter
-            // In a full implementation, we'd need to check app.uid == uid
-        }
+            let body = json!({ "writes": writes });
 
-        // Filter by installed only
-        if installed_only {
-            apps.retain(|app| app.enabled);
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&body)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
+            }
         }
 
-        // Apply pagination
-        let start = offset.min(apps.len());
-        let end = (offset + limit).min(apps.len());
-        Ok(apps[start..end].to_vec())
+        tracing::info!(
+            "Batch updated {} sort orders for user {}",
+            items.len(),
+            uid
+        );
+        Ok(())
     }
 
-    /// Get a single app by ID
-    pub async fn get_app(
+    // =========================================================================
+    // STAGED TASKS
+    // =========================================================================
+
+    /// Create a staged task in the staged_tasks subcollection.
+    /// Same schema as action_items but stored separately for promotion workflow.
+    pub async fn create_staged_task(
         &self,
         uid: &str,
-        app_id: &str,
-    ) -> Result<Option<App>, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("{}/{}/{}", self.base_url(), APPS_COLLECTION, app_id);
-
-        let response = self
-            .build_request(reqwest::Method::GET, &url)
-            .await?
-            .send()
-            .await?;
-
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Ok(None);
+        description: &str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&str>,
+        priority: Option<&str>,
+        metadata: Option<&str>,
+        category: Option<&str>,
+        relevance_score: Option<i32>,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        // Reject empty descriptions
+        let description = description.trim();
+        if description.is_empty() {
+            return Err("Cannot create staged task with empty description".into());
         }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore error: {}", error_text).into());
+        // Check for exact-match duplicate (case-insensitive)
+        let existing = self.get_staged_tasks(uid, 200, 0).await.unwrap_or_default();
+        let desc_lower = description.to_lowercase();
+        if existing.iter().any(|t| t.description.trim().to_lowercase() == desc_lower) {
+            tracing::info!(
+                "Skipping duplicate staged task for user {}: {}",
+                uid,
+                &description[..description.len().min(80)]
+            );
+            // Return the existing item instead of creating a duplicate
+            let existing_item = existing
+                .into_iter()
+                .find(|t| t.description.trim().to_lowercase() == desc_lower)
+                .unwrap();
+            return Ok(existing_item);
         }
 
-        let doc: Value = response.json().await?;
-        let mut app = self.parse_app(&doc)?;
+        let item_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
 
-        // Check if enabled for user
-        let enabled_ids = self.get_enabled_app_ids(uid).await.unwrap_or_default();
-        app.enabled = enabled_ids.contains(&app.id);
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            STAGED_TASKS_SUBCOLLECTION,
+            item_id
+        );
 
-        Ok(Some(app))
-    }
+        let mut fields = json!({
+            "description": {"stringValue": description},
+            "completed": {"booleanValue": false},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-    /// Get reviews for an app
-    pub async fn get_app_reviews(
-        &self,
-        app_id: &str,
-    ) -> Result<Vec<AppReview>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), APPS_COLLECTION, app_id);
+        if let Some(due) = due_at {
+            fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
+        }
+        if let Some(src) = source {
+            fields["source"] = json!({"stringValue": src});
+        }
+        if let Some(pri) = priority {
+            fields["priority"] = json!({"stringValue": pri});
+        }
+        if let Some(meta) = metadata {
+            fields["metadata"] = json!({"stringValue": meta});
+        }
+        if let Some(cat) = category {
+            fields["category"] = json!({"stringValue": cat});
+        }
+        if let Some(score) = relevance_score {
+            fields["relevance_score"] = json!({"integerValue": score.to_string()});
+        }
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": "reviews"}],
-                "orderBy": [{"field": {"fieldPath": "rated_at"}, "direction": "DESCENDING"}],
-                "limit": 100
-            }
-        });
+        let doc = json!({"fields": fields});
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore create staged task error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let reviews = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_app_review(d).ok())
-            })
-            .collect();
-
-        Ok(reviews)
-    }
+        let created_doc: Value = response.json().await?;
+        let item = self.parse_action_item(&created_doc)?;
 
-    /// Enable an app for a user
-    pub async fn enable_app(
+        tracing::info!(
+            "Created staged task {} for user {} with source={:?}",
+            item_id,
+            uid,
+            source
+        );
+        Ok(item)
+    }
+
+    /// Migrate action items that were created by the old conversation extraction path
+    /// (have conversation_id but no source field) to staged_tasks.
+    /// Returns (migrated_count, deleted_count).
+    pub async fn migrate_conversation_action_items_to_staged(
         &self,
         uid: &str,
-        app_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
+    ) -> Result<(usize, usize), FirestoreError> {
+        // Fetch all incomplete, non-deleted action items.
+        // NOTE: get_action_items runs enrich_action_items_with_source which populates
+        // the source field from the conversation. So we can't check source.is_none()
+        // after that. Instead, we filter by conversation_id.is_some() — all items with
+        // a conversation_id were created by the old save_action_items path (confirmed
+        // 0 false positives: no items have both conversation_id AND a real source in Firestore).
+        let all_items = self
+            .get_action_items(uid, 10000, 0, Some(false), None, None, None, None, None, None, None, true)
+            .await?;
+
+        // Filter: has conversation_id → created by old save_action_items path
+        let bad_items: Vec<ActionItemDB> = all_items
+            .into_iter()
+            .filter(|item| item.conversation_id.is_some())
+            .collect();
+
+        if bad_items.is_empty() {
+            tracing::info!("No conversation action items to migrate for user {}", uid);
+            return Ok((0, 0));
+        }
+
+        tracing::info!(
+            "Found {} conversation action items to migrate for user {}",
+            bad_items.len(),
+            uid
+        );
+
+        // Use batch_migrate_to_staged for fast batch commits (250 items per batch)
+        let migrated = self.batch_migrate_to_staged(uid, &bad_items).await?;
+
+        tracing::info!(
+            "Migration complete for user {}: {} migrated out of {} candidates",
             uid,
-            ENABLED_APPS_SUBCOLLECTION,
-            app_id
+            migrated,
+            bad_items.len()
         );
 
-        let now = Utc::now();
-        let doc = json!({
-            "fields": {
-                "app_id": {"stringValue": app_id},
-                "enabled_at": {"timestampValue": now.to_rfc3339()}
+        Ok((migrated, migrated))
+    }
+
+    /// Get staged tasks ordered by relevance_score ASC (best ranked first).
+    /// Filters out deleted and completed tasks.
+    pub async fn get_staged_tasks(
+        &self,
+        uid: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ActionItemDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Query non-completed staged tasks ordered by relevance_score ASC
+        let filters = vec![
+            json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "completed"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": false}
+                }
+            }),
+        ];
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": STAGED_TASKS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": filters
+                    }
+                },
+                "orderBy": [
+                    {"field": {"fieldPath": "relevance_score"}, "direction": "ASCENDING"},
+                    {"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}
+                ],
+                "limit": limit + offset
             }
         });
 
+        let query_url = format!("{}:runQuery", parent);
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &query_url)
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to enable app: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query staged tasks error", &error_text));
         }
 
-        // Increment install count on the app
-        self.increment_app_installs(app_id).await?;
+        let results: Vec<Value> = response.json().await?;
+        let items: Vec<ActionItemDB> = results
+            .iter()
+            .filter_map(|r| r.get("document"))
+            .filter_map(|doc| self.parse_action_item(doc).ok())
+            .filter(|item| item.deleted != Some(true))
+            .skip(offset)
+            .collect();
 
-        tracing::info!("Enabled app {} for user {}", app_id, uid);
-        Ok(())
+        Ok(items)
     }
 
-    /// Disable an app for a user
-    pub async fn disable_app(
+    /// Hard-delete a staged task (permanently remove from Firestore).
+    pub async fn delete_staged_task(
         &self,
         uid: &str,
-        app_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        item_id: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ENABLED_APPS_SUBCOLLECTION,
-            app_id
+            STAGED_TASKS_SUBCOLLECTION,
+            item_id
         );
 
         let response = self
@@ -3547,1148 +4819,7062 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to disable app: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete staged task error", &error_text));
         }
 
-        tracing::info!("Disabled app {} for user {}", app_id, uid);
+        tracing::info!("Deleted staged task {} for user {}", item_id, uid);
         Ok(())
     }
 
-    /// Get user's enabled app IDs
-    async fn get_enabled_app_ids(
+    /// Batch update relevance scores for staged tasks.
+    pub async fn batch_update_staged_scores(
         &self,
         uid: &str,
-    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": ENABLED_APPS_SUBCOLLECTION}],
-                "limit": 500
-            }
-        });
-
-        let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
-            .await?
-            .json(&query)
-            .send()
-            .await?;
+        scores: &[(String, i32)],
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
 
-        if !response.status().is_success() {
-            return Ok(vec![]);
-        }
+        for chunk in scores.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|(item_id, score)| {
+                    let doc_name = format!(
+                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                        self.project_id(), USERS_COLLECTION, uid, STAGED_TASKS_SUBCOLLECTION, item_id
+                    );
+                    json!({
+                        "update": {
+                            "name": doc_name,
+                            "fields": {
+                                "relevance_score": {"integerValue": score.to_string()},
+                                "updated_at": {"timestampValue": now.to_rfc3339()}
+                            }
+                        },
+                        "updateMask": {
+                            "fieldPaths": ["relevance_score", "updated_at"]
+                        }
+                    })
+                })
+                .collect();
 
-        let results: Vec<Value> = response.json().await?;
-        let ids = results
-            .into_iter()
-            .filter_map(|doc| {
-                let d = doc.get("document")?;
-                let name = d.get("name")?.as_str()?;
-                Some(name.split('/').last()?.to_string())
-            })
-            .collect();
+            let commit_url = format!(
+                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+                self.project_id()
+            );
 
-        Ok(ids)
-    }
+            let body = json!({ "writes": writes });
 
-    /// Get user's enabled apps as summaries
-    pub async fn get_enabled_apps(
-        &self,
-        uid: &str,
-    ) -> Result<Vec<AppSummary>, Box<dyn std::error::Error + Send + Sync>> {
-        let enabled_ids = self.get_enabled_app_ids(uid).await?;
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&body)
+                .send()
+                .await?;
 
-        let mut apps = Vec::new();
-        for app_id in enabled_ids {
-            if let Ok(Some(app)) = self.get_app(uid, &app_id).await {
-                let mut summary = AppSummary::from(app);
-                summary.enabled = true;
-                apps.push(summary);
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch commit staged scores error", &error_text));
             }
         }
 
-        Ok(apps)
+        tracing::info!(
+            "Batch updated {} staged task scores for user {}",
+            scores.len(),
+            uid
+        );
+        Ok(())
     }
 
-    /// Get user's enabled apps with full App details (for integration triggers)
-    pub async fn get_enabled_apps_full(
+    /// Batch migrate tasks from action_items to staged_tasks using Firestore commit API.
+    /// Each task is created in staged_tasks and deleted from action_items atomically.
+    /// Processes 250 tasks per commit (each needs 2 writes, Firestore limit is 500).
+    pub async fn batch_migrate_to_staged(
         &self,
         uid: &str,
-    ) -> Result<Vec<App>, Box<dyn std::error::Error + Send + Sync>> {
-        let enabled_ids = self.get_enabled_app_ids(uid).await?;
-
-        let mut apps = Vec::new();
-        for app_id in enabled_ids {
-            if let Ok(Some(mut app)) = self.get_app(uid, &app_id).await {
-                app.enabled = true;
-                apps.push(app);
-            }
-        }
-
-        Ok(apps)
-    }
-
-    /// Increment app install count
-    async fn increment_app_installs(
-        &self,
-        app_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // First get current installs
-        let app = match self.get_app("", app_id).await? {
-            Some(a) => a,
-            None => return Ok(()),
-        };
+        tasks: &[ActionItemDB],
+    ) -> Result<usize, FirestoreError> {
+        let now = Utc::now();
+        let mut migrated_count = 0;
 
-        let new_installs = app.installs + 1;
+        // 250 tasks per batch (each task = 2 writes: create + delete, limit 500)
+        for chunk in tasks.chunks(250) {
+            let mut writes: Vec<Value> = Vec::new();
 
-        let url = format!(
-            "{}/{}/{}?updateMask.fieldPaths=installs",
-            self.base_url(),
-            APPS_COLLECTION,
-            app_id
-        );
+            for task in chunk {
+                let staged_id = uuid::Uuid::new_v4().to_string();
+                let staged_doc_name = format!(
+                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                    self.project_id(), USERS_COLLECTION, uid, STAGED_TASKS_SUBCOLLECTION, staged_id
+                );
 
-        let doc = json!({
-            "fields": {
-                "installs": {"integerValue": new_installs.to_string()}
-            }
-        });
+                let mut fields = json!({
+                    "description": {"stringValue": &task.description},
+                    "completed": {"booleanValue": false},
+                    "created_at": {"timestampValue": now.to_rfc3339()},
+                    "updated_at": {"timestampValue": now.to_rfc3339()}
+                });
+                if let Some(ref due) = task.due_at {
+                    fields["due_at"] = json!({"timestampValue": due.to_rfc3339()});
+                }
+                if let Some(ref src) = task.source {
+                    fields["source"] = json!({"stringValue": src});
+                }
+                if let Some(ref pri) = task.priority {
+                    fields["priority"] = json!({"stringValue": pri});
+                }
+                if let Some(ref meta) = task.metadata {
+                    fields["metadata"] = json!({"stringValue": meta});
+                }
+                if let Some(ref cat) = task.category {
+                    fields["category"] = json!({"stringValue": cat});
+                }
+                if let Some(score) = task.relevance_score {
+                    fields["relevance_score"] = json!({"integerValue": score.to_string()});
+                }
 
-        let response = self
-            .build_request(reqwest::Method::PATCH, &url)
-            .await?
-            .json(&doc)
-            .send()
-            .await?;
+                // Write 1: Create in staged_tasks
+                writes.push(json!({
+                    "update": {
+                        "name": staged_doc_name,
+                        "fields": fields
+                    }
+                }));
 
-        if !response.status().is_success() {
-            tracing::warn!("Failed to increment app installs: {}", response.text().await?);
+                // Write 2: Delete from action_items
+                let action_doc_name = format!(
+                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                    self.project_id(), USERS_COLLECTION, uid, ACTION_ITEMS_SUBCOLLECTION, task.id
+                );
+                writes.push(json!({
+                    "delete": action_doc_name
+                }));
+            }
+
+            let commit_url = format!(
+                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+                self.project_id()
+            );
+
+            let body = json!({ "writes": writes });
+
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&body)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch migrate commit error", &error_text));
+            }
+
+            migrated_count += chunk.len();
+            tracing::info!(
+                "Batch migrated {} tasks ({} total so far) for user {}",
+                chunk.len(),
+                migrated_count,
+                uid
+            );
         }
 
-        Ok(())
+        Ok(migrated_count)
     }
 
-    /// Submit a review for an app
-    pub async fn submit_app_review(
+    /// Count active AI action items promoted from staged_tasks (from_staged=true, not completed, not deleted).
+    /// Used by the promotion system to determine if more tasks should be promoted.
+    pub async fn count_active_ai_action_items(
         &self,
         uid: &str,
-        app_id: &str,
-        score: i32,
-        review: &str,
-    ) -> Result<AppReview, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/reviews/{}",
-            self.base_url(),
-            APPS_COLLECTION,
-            app_id,
-            uid
-        );
+    ) -> Result<usize, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let now = Utc::now();
-        let doc = json!({
-            "fields": {
-                "uid": {"stringValue": uid},
-                "score": {"integerValue": score.to_string()},
-                "review": {"stringValue": review},
-                "rated_at": {"timestampValue": now.to_rfc3339()}
+        // Composite filter: from_staged=true AND completed=false at Firestore level
+        // so we don't miss items when users have thousands of action_items
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "completed"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": false}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "from_staged"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": true}
+                                }
+                            }
+                        ]
+                    }
+                },
+                "limit": 100
             }
         });
 
+        let query_url = format!("{}:runQuery", parent);
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &query_url)
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to submit review: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore count AI items error", &error_text));
         }
 
-        // Update app's rating average and count
-        self.update_app_rating(app_id).await?;
+        let results: Vec<Value> = response.json().await?;
+        let count = results
+            .iter()
+            .filter_map(|r| r.get("document"))
+            .filter_map(|doc| self.parse_action_item(doc).ok())
+            .filter(|item| {
+                item.deleted != Some(true)
+                    && item.from_staged == Some(true)
+            })
+            .count();
 
-        Ok(AppReview {
-            uid: uid.to_string(),
-            score,
-            review: review.to_string(),
-            response: None,
-            rated_at: now,
-            edited_at: None,
-        })
+        Ok(count)
     }
 
-    /// Update app's rating average and count
-    async fn update_app_rating(
+    /// Due dates of the user's open (not completed or deleted) action items due before `before`
+    pub async fn get_open_action_item_due_dates(
         &self,
-        app_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let reviews = self.get_app_reviews(app_id).await?;
-
-        if reviews.is_empty() {
-            return Ok(());
-        }
+        uid: &str,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<DateTime<Utc>>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "select": {"fields": [{"fieldPath": "due_at"}, {"fieldPath": "deleted"}]},
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "completed"},
+                                "op": "EQUAL",
+                                "value": {"booleanValue": false}
+                            }},
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "due_at"},
+                                "op": "LESS_THAN",
+                                "value": {"timestampValue": before.to_rfc3339()}
+                            }}
+                        ]
+                    }
+                },
+                "limit": limit
+            }
+        });
 
-        let total: i32 = reviews.iter().map(|r| r.score).sum();
-        let count = reviews.len() as i32;
-        let avg = total as f64 / count as f64;
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs
+            .iter()
+            .filter_map(|doc| doc.get("fields"))
+            // deleted is only written on soft-delete, so it's filtered here
+            .filter(|fields| !self.parse_bool(fields, "deleted").unwrap_or(false))
+            .filter_map(|fields| self.parse_timestamp_optional(fields, "due_at"))
+            .collect())
+    }
 
-        let url = format!(
-            "{}/{}/{}?updateMask.fieldPaths=rating_avg&updateMask.fieldPaths=rating_count",
-            self.base_url(),
-            APPS_COLLECTION,
-            app_id
-        );
+    /// Get active AI action items promoted from staged_tasks (from_staged=true, not completed, not deleted).
+    /// Returns the actual items for dedup comparison during promotion.
+    /// Uses a composite filter to query from_staged=true AND completed=false at the Firestore level.
+    pub async fn get_active_ai_action_items(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<ActionItemDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let doc = json!({
-            "fields": {
-                "rating_avg": {"doubleValue": avg},
-                "rating_count": {"integerValue": count.to_string()}
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "completed"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": false}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "from_staged"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": true}
+                                }
+                            }
+                        ]
+                    }
+                },
+                "limit": 100
             }
         });
 
+        let query_url = format!("{}:runQuery", parent);
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &query_url)
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
-            tracing::warn!("Failed to update app rating: {}", response.text().await?);
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get active AI items error", &error_text));
         }
 
-        Ok(())
+        let results: Vec<Value> = response.json().await?;
+        let items: Vec<ActionItemDB> = results
+            .iter()
+            .filter_map(|r| r.get("document"))
+            .filter_map(|doc| self.parse_action_item(doc).ok())
+            .filter(|item| {
+                item.deleted != Some(true)
+                    && item.from_staged == Some(true)
+            })
+            .collect();
+
+        Ok(items)
     }
 
-    /// Parse Firestore document to App
-    fn parse_app(
+    /// Get a single staged task by ID.
+    pub async fn get_staged_task_by_id(
         &self,
-        doc: &Value,
-    ) -> Result<App, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields in document")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
+        uid: &str,
+        item_id: &str,
+    ) -> Result<Option<ActionItemDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            STAGED_TASKS_SUBCOLLECTION,
+            item_id
+        );
 
-        Ok(App {
-            id,
-            name: self.parse_string(fields, "name").unwrap_or_default(),
-            description: self.parse_string(fields, "description").unwrap_or_default(),
-            image: self.parse_string(fields, "image").unwrap_or_default(),
-            category: self.parse_string(fields, "category").unwrap_or_else(|| "other".to_string()),
-            author: self.parse_string(fields, "author").unwrap_or_default(),
-            email: self.parse_string(fields, "email"),
-            capabilities: self.parse_string_array(fields, "capabilities"),
-            uid: self.parse_string(fields, "uid"),
-            approved: self.parse_bool(fields, "approved").unwrap_or(false),
-            private: self.parse_bool(fields, "private").unwrap_or(false),
-            status: self.parse_string(fields, "status").unwrap_or_else(|| "under-review".to_string()),
-            chat_prompt: self.parse_string(fields, "chat_prompt"),
-            memory_prompt: self.parse_string(fields, "memory_prompt"),
-            persona_prompt: self.parse_string(fields, "persona_prompt"),
-            external_integration: None, // TODO: Parse nested object
-            proactive_notification: None, // TODO: Parse nested object
-            chat_tools: vec![], // TODO: Parse array of nested objects
-            installs: self.parse_int(fields, "installs").unwrap_or(0),
-            rating_avg: self.parse_float(fields, "rating_avg"),
-            rating_count: self.parse_int(fields, "rating_count").unwrap_or(0),
-            is_paid: self.parse_bool(fields, "is_paid").unwrap_or(false),
-            price: self.parse_float(fields, "price"),
-            payment_plan: self.parse_string(fields, "payment_plan"),
-            username: self.parse_string(fields, "username"),
-            twitter: self.parse_string(fields, "twitter"),
-            created_at: self.parse_timestamp_optional(fields, "created_at"),
-            enabled: false, // Will be set by caller
-        })
-    }
-
-    /// Parse Firestore document to AppSummary
-    fn parse_app_summary(
-        &self,
-        doc: &Value,
-    ) -> Result<AppSummary, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields in document")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
-
-        // Parse has_auth_steps from external_integration.auth_steps
-        // Structure: external_integration: { mapValue: { fields: { auth_steps: { arrayValue: { values: [...] } } } } }
-        let has_auth_steps = fields
-            .get("external_integration")
-            .and_then(|ei| ei.get("mapValue"))
-            .and_then(|mv| mv.get("fields"))
-            .and_then(|f| f.get("auth_steps"))
-            .and_then(|as_| as_.get("arrayValue"))
-            .and_then(|av| av.get("values"))
-            .and_then(|v| v.as_array())
-            .map(|arr| !arr.is_empty())
-            .unwrap_or(false);
-
-        Ok(AppSummary {
-            id,
-            name: self.parse_string(fields, "name").unwrap_or_default(),
-            description: self.parse_string(fields, "description").unwrap_or_default(),
-            image: self.parse_string(fields, "image").unwrap_or_default(),
-            category: self.parse_string(fields, "category").unwrap_or_else(|| "other".to_string()),
-            author: self.parse_string(fields, "author").unwrap_or_default(),
-            capabilities: self.parse_string_array(fields, "capabilities"),
-            approved: self.parse_bool(fields, "approved").unwrap_or(false),
-            private: self.parse_bool(fields, "private").unwrap_or(false),
-            installs: self.parse_int(fields, "installs").unwrap_or(0),
-            rating_avg: self.parse_float(fields, "rating_avg"),
-            rating_count: self.parse_int(fields, "rating_count").unwrap_or(0),
-            is_paid: self.parse_bool(fields, "is_paid").unwrap_or(false),
-            price: self.parse_float(fields, "price"),
-            enabled: false, // Will be set by caller
-            has_auth_steps,
-        })
-    }
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
 
-    /// Parse Firestore document to AppReview
-    fn parse_app_review(
-        &self,
-        doc: &Value,
-    ) -> Result<AppReview, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let uid = name.split('/').last().unwrap_or("").to_string();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
 
-        Ok(AppReview {
-            uid,
-            score: self.parse_int(fields, "score").unwrap_or(0),
-            review: self.parse_string(fields, "review").unwrap_or_default(),
-            response: self.parse_string(fields, "response"),
-            rated_at: self.parse_timestamp_optional(fields, "rated_at").unwrap_or_else(Utc::now),
-            edited_at: self.parse_timestamp_optional(fields, "edited_at"),
-        })
-    }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get staged task error", &error_text));
+        }
 
-    /// Parse string array from Firestore
-    fn parse_string_array(&self, fields: &Value, key: &str) -> Vec<String> {
-        fields
-            .get(key)
-            .and_then(|v| v.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.get("stringValue")?.as_str().map(|s| s.to_string()))
-                    .collect()
-            })
-            .unwrap_or_default()
+        let doc: Value = response.json().await?;
+        let item = self.parse_action_item(&doc)?;
+        Ok(Some(item))
     }
 
     // =========================================================================
-    // PARSING HELPERS
+    // APPS
     // =========================================================================
 
-    /// Parse Firestore document to Conversation
-    /// Decrypts transcript_segments and photos if data_protection_level is "enhanced"
-    fn parse_conversation(
+    /// Get all apps with optional filters (served from the apps cache)
+    pub async fn get_apps(
         &self,
-        doc: &Value,
         uid: &str,
-    ) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc
-            .get("fields")
-            .ok_or("Missing fields in document")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
-
-        // Use created_at as fallback for missing timestamps
-        let created_at = self.parse_timestamp_optional(fields, "created_at")
-            .unwrap_or_else(Utc::now);
-        let started_at = self.parse_timestamp_optional(fields, "started_at")
-            .unwrap_or(created_at);
-        let finished_at = self.parse_timestamp_optional(fields, "finished_at")
-            .unwrap_or(created_at);
+        limit: usize,
+        offset: usize,
+        capability: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        let snapshot = self.approved_apps_snapshot().await?;
+        let mut apps = apps_cache::filter_apps(&snapshot, capability, category);
 
-        // Parse apps_results
-        let apps_results = self.parse_apps_results(fields);
+        // Mark enabled apps
+        let enabled_app_ids = self.get_enabled_app_ids(uid).await.unwrap_or_default();
+        for app in &mut apps {
+            app.enabled = enabled_app_ids.contains(&app.id);
+        }
 
-        Ok(Conversation {
-            id,
-            created_at,
-            started_at,
-            finished_at,
-            source: self.parse_string(fields, "source")
-                .and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok())
-                .unwrap_or_default(),
-            language: self.parse_string(fields, "language").unwrap_or_default(),
-            status: self.parse_string(fields, "status")
-                .and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok())
-                .unwrap_or_default(),
-            discarded: self.parse_bool(fields, "discarded").unwrap_or(false),
-            deleted: self.parse_bool(fields, "deleted").unwrap_or(false),
-            starred: self.parse_bool(fields, "starred").unwrap_or(false),
-            is_locked: self.parse_bool(fields, "is_locked").unwrap_or(false),
-            folder_id: self.parse_string(fields, "folder_id"),
-            structured: self.parse_structured(fields)?,
-            transcript_segments: self.parse_transcript_segments(fields, uid)?,
-            apps_results,
-            geolocation: self.parse_geolocation(fields),
-            photos: self.parse_photos(fields, uid),
-            input_device_name: self.parse_string(fields, "input_device_name"),
-        })
+        // Apply pagination
+        let start = offset.min(apps.len());
+        let end = (offset + limit).min(apps.len());
+        Ok(apps[start..end].to_vec())
     }
 
-    /// Parse apps_results array from Firestore fields
-    fn parse_apps_results(&self, fields: &Value) -> Vec<crate::models::AppResult> {
-        let array = match fields.get("apps_results")
-            .and_then(|a| a.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|a| a.as_array())
-        {
-            Some(arr) => arr,
-            None => return vec![],
-        };
-
-        array.iter().filter_map(|item| {
-            let map_fields = item.get("mapValue")?.get("fields")?;
-            let app_id = self.parse_string(map_fields, "app_id");
-            let content = self.parse_string(map_fields, "content").unwrap_or_default();
-            Some(crate::models::AppResult { app_id, content })
-        }).collect()
+    /// Approved public apps from the cache, loading them on a miss
+    pub async fn approved_apps_snapshot(
+        &self,
+    ) -> Result<Arc<Vec<AppSummary>>, FirestoreError> {
+        if let Some(apps) = self.apps_cache.get().await {
+            return Ok(apps);
+        }
+        self.refresh_apps_cache().await
     }
 
-    /// Parse Firestore document to ActionItemDB
-    fn parse_action_item(
+    /// Reload the apps cache from Firestore
+    pub async fn refresh_apps_cache(
         &self,
-        doc: &Value,
-    ) -> Result<ActionItemDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
+    ) -> Result<Arc<Vec<AppSummary>>, FirestoreError> {
+        let apps = self.query_approved_apps().await?;
+        tracing::debug!("Apps cache refreshed with {} apps", apps.len());
+        Ok(self.apps_cache.set(apps).await)
+    }
 
-        Ok(ActionItemDB {
-            id,
-            description: self.parse_string(fields, "description").unwrap_or_default(),
-            completed: self.parse_bool(fields, "completed").unwrap_or(false),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at"),
-            due_at: self.parse_timestamp_optional(fields, "due_at"),
-            completed_at: self.parse_timestamp_optional(fields, "completed_at"),
-            conversation_id: self.parse_string(fields, "conversation_id"),
-            source: self.parse_string(fields, "source"),
-            priority: self.parse_string(fields, "priority"),
-            metadata: self.parse_string(fields, "metadata"),
-            deleted: self.parse_bool(fields, "deleted").ok(),
-            deleted_by: self.parse_string(fields, "deleted_by"),
-            deleted_at: self.parse_timestamp_optional(fields, "deleted_at"),
-            deleted_reason: self.parse_string(fields, "deleted_reason"),
-            kept_task_id: self.parse_string(fields, "kept_task_id"),
-            category: self.parse_string(fields, "category"),
-            goal_id: self.parse_string(fields, "goal_id"),
-            relevance_score: self.parse_int(fields, "relevance_score"),
-            sort_order: self.parse_int(fields, "sort_order"),
-            indent_level: self.parse_int(fields, "indent_level"),
-            from_staged: self.parse_bool(fields, "from_staged").ok(),
-            recurrence_rule: self.parse_string(fields, "recurrence_rule"),
-            recurrence_parent_id: self.parse_string(fields, "recurrence_parent_id"),
-        })
+    /// Drop the apps cache after an app's visibility changes
+    pub async fn invalidate_apps_cache(&self) {
+        self.apps_cache.invalidate().await;
     }
 
-    /// Parse Firestore document to MemoryDB
-    /// Decrypts content if data_protection_level is "enhanced" and encryption secret is available.
-    fn parse_memory(
+    /// Query all approved, public apps (matching Python backend: approved=True AND private=False)
+    async fn query_approved_apps(
         &self,
-        doc: &Value,
-        uid: &str,
-    ) -> Result<MemoryDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
-
-        // Get raw content
-        let mut content = self.parse_string(fields, "content").unwrap_or_default();
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        let parent = self.base_url();
 
-        // Check if content is encrypted (data_protection_level = "enhanced")
-        let data_protection_level = self.parse_string(fields, "data_protection_level");
-        if data_protection_level.as_deref() == Some("enhanced") {
-            if let Some(ref secret) = self.encryption_secret {
-                match encryption::decrypt(&content, uid, secret) {
-                    Ok(decrypted) => content = decrypted,
-                    Err(e) => {
-                        tracing::warn!("Failed to decrypt memory {}: {}", id, e);
-                        content = "[Encrypted content — decryption failed]".to_string();
+        // Note: We don't use orderBy in the query because it would require a composite index
+        // Instead, we fetch all matching apps and sort in memory (matching Python backend behavior)
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": APPS_COLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "approved"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": true}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "private"},
+                                    "op": "EQUAL",
+                                    "value": {"booleanValue": false}
+                                }
+                            }
+                        ]
                     }
                 }
-            } else {
-                tracing::warn!(
-                    "Memory {} has enhanced protection but no encryption secret configured",
-                    id
-                );
-                content = "[Encrypted content — decryption failed]".to_string();
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore apps query error", &error_text));
         }
 
-        Ok(MemoryDB {
-            id: id.clone(),
-            uid: "".to_string(), // Not stored in document
-            content,
-            category: self.parse_string(fields, "category")
-                .and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok())
-                .unwrap_or_default(),
-            created_at: self.parse_timestamp(fields, "created_at")?,
-            updated_at: self.parse_timestamp(fields, "updated_at")?,
-            conversation_id: self.parse_string(fields, "conversation_id"),
-            reviewed: self.parse_bool(fields, "reviewed").unwrap_or(false),
-            user_review: self.parse_bool(fields, "user_review").ok(),
-            visibility: self.parse_string(fields, "visibility").unwrap_or_else(|| "private".to_string()),
-            manually_added: self.parse_bool(fields, "manually_added").unwrap_or(false),
-            scoring: self.parse_string(fields, "scoring"),
-            source: self.parse_string(fields, "source"), // Can be stored directly for tips, or enriched from conversation
-            input_device_name: None, // Enriched later from linked conversation
-            confidence: self.parse_float(fields, "confidence"),
-            source_app: self.parse_string(fields, "source_app"),
-            context_summary: self.parse_string(fields, "context_summary"),
-            is_read: self.parse_bool(fields, "is_read").unwrap_or(false),
-            is_dismissed: self.parse_bool(fields, "is_dismissed").unwrap_or(false),
-            tags: self.parse_string_array(fields, "tags"),
-            reasoning: self.parse_string(fields, "reasoning"),
-            current_activity: self.parse_string(fields, "current_activity"),
-            window_title: self.parse_string(fields, "window_title"),
-        })
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_app_summary(d).ok())
+            })
+            .collect())
     }
 
-    /// Parse structured data from conversation
-    fn parse_structured(
+    /// Set an app's approval; approved apps show up in the marketplace
+    pub async fn set_app_approved(
         &self,
-        fields: &Value,
-    ) -> Result<Structured, Box<dyn std::error::Error + Send + Sync>> {
-        let structured = fields.get("structured").and_then(|s| s.get("mapValue")).and_then(|m| m.get("fields"));
-
-        if let Some(s) = structured {
-            let title = self.parse_string(s, "title").unwrap_or_default();
-            if title.is_empty() {
-                tracing::warn!(
-                    "DEBUG parse_structured: title is empty! structured fields: {}",
-                    serde_json::to_string_pretty(s).unwrap_or_default()
-                );
-            }
-            Ok(Structured {
-                title,
-                overview: self.parse_string(s, "overview").unwrap_or_default(),
-                emoji: self.parse_string(s, "emoji").unwrap_or_else(|| "🧠".to_string()),
-                category: self.parse_string(s, "category")
-                    .and_then(|c| serde_json::from_str(&format!("\"{}\"", c)).ok())
-                    .unwrap_or_default(),
-                action_items: self.parse_action_items_from_structured(s),
-                events: self.parse_events_from_structured(s),
-            })
-        } else {
-            tracing::warn!(
-                "DEBUG parse_structured: no structured field found! fields: {}",
-                serde_json::to_string_pretty(fields).unwrap_or_default()
-            );
-            Ok(Structured::default())
-        }
+        app_id: &str,
+        approved: bool,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}",
+            self.project_id(),
+            APPS_COLLECTION,
+            app_id
+        );
+        self.patch_document_fields(
+            &doc_name,
+            json!({"approved": {"booleanValue": approved}}),
+            &["approved"],
+        )
+        .await?;
+        self.invalidate_apps_cache().await;
+        Ok(())
     }
 
-    /// Parse action_items array from structured field
-    fn parse_action_items_from_structured(&self, structured_fields: &Value) -> Vec<crate::models::ActionItem> {
-        let array = match structured_fields.get("action_items")
-            .and_then(|a| a.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|a| a.as_array())
-        {
-            Some(arr) => arr,
-            None => return vec![],
+    /// Store the moderation verdict for an app's prompts
+    pub async fn set_app_moderation(
+        &self,
+        app_id: &str,
+        moderation: &AppModeration,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}",
+            self.project_id(),
+            APPS_COLLECTION,
+            app_id
+        );
+        let overridden_by = match &moderation.overridden_by {
+            Some(uid) => json!({"stringValue": uid}),
+            None => json!({"nullValue": null}),
         };
-
-        array.iter().filter_map(|item| {
-            let map_fields = item.get("mapValue")?.get("fields")?;
-            let description = self.parse_string(map_fields, "description").unwrap_or_default();
-            let completed = self.parse_bool(map_fields, "completed").unwrap_or(false);
-            let due_at = self.parse_timestamp_optional(map_fields, "due_at");
-            Some(crate::models::ActionItem { description, completed, due_at, confidence: None, priority: None })
-        }).collect()
+        let value = json!({"mapValue": {"fields": {
+            "verdict": {"stringValue": moderation.verdict.as_str()},
+            "reasons": self.build_string_array_value(&moderation.reasons),
+            "prompt_hash": {"stringValue": moderation.prompt_hash},
+            "checked_at": {"timestampValue": moderation.checked_at.to_rfc3339()},
+            "admin_override": {"booleanValue": moderation.admin_override},
+            "overridden_by": overridden_by,
+        }}});
+        self.patch_document_fields(&doc_name, json!({"moderation": value}), &["moderation"])
+            .await?;
+        self.invalidate_apps_cache().await;
+        Ok(())
     }
 
-    /// Parse events array from structured field
-    fn parse_events_from_structured(&self, structured_fields: &Value) -> Vec<crate::models::Event> {
-        let array = match structured_fields.get("events")
-            .and_then(|a| a.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|a| a.as_array())
-        {
-            Some(arr) => arr,
-            None => return vec![],
-        };
-
-        array.iter().filter_map(|item| {
-            let map_fields = item.get("mapValue")?.get("fields")?;
-            let title = self.parse_string(map_fields, "title").unwrap_or_default();
-            let description = self.parse_string(map_fields, "description").unwrap_or_default();
-            let start = self.parse_timestamp_optional(map_fields, "start")?;
-            let duration = self.parse_int(map_fields, "duration").unwrap_or(30);
-            Some(crate::models::Event { title, description, start, duration })
-        }).collect()
+    /// Replace an app's translated names and descriptions
+    pub async fn set_app_translations(
+        &self,
+        app_id: &str,
+        translations: &HashMap<String, AppTranslation>,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}",
+            self.project_id(),
+            APPS_COLLECTION,
+            app_id
+        );
+        let languages: serde_json::Map<String, Value> = translations
+            .iter()
+            .map(|(language, t)| {
+                let mut fields = serde_json::Map::new();
+                if let Some(name) = &t.name {
+                    fields.insert("name".to_string(), json!({"stringValue": name}));
+                }
+                if let Some(description) = &t.description {
+                    fields.insert("description".to_string(), json!({"stringValue": description}));
+                }
+                (language.clone(), self.build_sub_map_value(fields))
+            })
+            .collect();
+        self.patch_document_fields(
+            &doc_name,
+            json!({"translations": self.build_sub_map_value(languages)}),
+            &["translations"],
+        )
+        .await?;
+        self.invalidate_apps_cache().await;
+        Ok(())
     }
 
-    /// Parse geolocation from conversation fields
-    fn parse_geolocation(&self, fields: &Value) -> Option<crate::models::Geolocation> {
-        let geo = fields.get("geolocation")?.get("mapValue")?.get("fields")?;
-
-        Some(crate::models::Geolocation {
-            google_place_id: self.parse_string(geo, "google_place_id"),
-            latitude: self.parse_float(geo, "latitude").unwrap_or(0.0),
-            longitude: self.parse_float(geo, "longitude").unwrap_or(0.0),
-            address: self.parse_string(geo, "address"),
-            location_type: self.parse_string(geo, "location_type"),
-        })
+    /// Get approved public apps
+    pub async fn get_approved_apps(
+        &self,
+        uid: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        self.get_apps(uid, limit, offset, None, None).await
     }
 
-    /// Parse photos array from conversation fields
-    /// Decrypts base64 field if data_protection_level is "enhanced"
-    fn parse_photos(&self, fields: &Value, uid: &str) -> Vec<crate::models::ConversationPhoto> {
-        let array = match fields.get("photos")
-            .and_then(|a| a.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|a| a.as_array())
-        {
-            Some(arr) => arr,
-            None => return vec![],
-        };
-
-        array.iter().filter_map(|item| {
-            let map_fields = item.get("mapValue")?.get("fields")?;
-            let id = self.parse_string(map_fields, "id");
-            let mut base64 = self.parse_string(map_fields, "base64").unwrap_or_default();
-            let description = self.parse_string(map_fields, "description");
-            let created_at = self.parse_timestamp_optional(map_fields, "created_at").unwrap_or_else(Utc::now);
-            let discarded = self.parse_bool(map_fields, "discarded").unwrap_or(false);
+    /// Get popular apps (apps marked with is_popular=true, matching Python backend behavior)
+    pub async fn get_popular_apps(
+        &self,
+        uid: &str,
+        limit: usize,
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        let parent = self.base_url();
 
-            // Check if photo is encrypted (data_protection_level = "enhanced")
-            let data_protection_level = self.parse_string(map_fields, "data_protection_level");
-            if data_protection_level.as_deref() == Some("enhanced") {
-                if let Some(ref secret) = self.encryption_secret {
-                    match encryption::decrypt(&base64, uid, secret) {
-                        Ok(decrypted) => base64 = decrypted,
-                        Err(e) => {
-                            tracing::warn!("Failed to decrypt photo {:?}: {} — skipping", id, e);
-                            return None;
-                        }
-                    }
-                } else {
-                    tracing::warn!("Photo {:?} has enhanced protection but no encryption secret — skipping", id);
-                    return None;
+        // Query for apps where approved=true AND is_popular=true (matching Python backend)
+        let filters = vec![
+            json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "approved"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": true}
+                }
+            }),
+            json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "is_popular"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": true}
                 }
+            }),
+        ];
+
+        let where_clause = json!({
+            "compositeFilter": {
+                "op": "AND",
+                "filters": filters
             }
+        });
 
-            Some(crate::models::ConversationPhoto { id, base64, description, created_at, discarded })
-        }).collect()
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": APPS_COLLECTION}],
+                "where": where_clause
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error for popular apps: {}", error_text);
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+
+        // Get user's enabled apps to mark them
+        let enabled_app_ids = self.get_enabled_app_ids(uid).await.unwrap_or_default();
+
+        let mut apps: Vec<AppSummary> = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_app_summary(d).ok())
+            })
+            .collect();
+
+        // Mark enabled apps
+        for app in &mut apps {
+            app.enabled = enabled_app_ids.contains(&app.id);
+        }
+
+        // Sort by installs descending (matching Python backend behavior)
+        apps.sort_by(|a, b| b.installs.cmp(&a.installs));
+
+        apps.truncate(limit);
+        Ok(apps)
     }
 
-    /// Parse transcript segments
-    /// Handles plain arrays, zlib-compressed bytes (from OMI device), and encrypted segments.
-    /// For encrypted segments (data_protection_level = "enhanced"):
-    ///   - Decrypts the base64 string → hex string
-    ///   - Converts hex to bytes
-    ///   - Decompresses with zlib
-    ///   - Parses JSON array
-    fn parse_transcript_segments(
+    /// Search apps with filters
+    pub async fn search_apps(
         &self,
-        fields: &Value,
         uid: &str,
-    ) -> Result<Vec<TranscriptSegment>, Box<dyn std::error::Error + Send + Sync>> {
-        use flate2::read::ZlibDecoder;
-        use std::io::Read;
-
-        let transcript_field = fields.get("transcript_segments");
+        query: Option<&str>,
+        category: Option<&str>,
+        capability: Option<&str>,
+        min_rating: Option<i32>,
+        my_apps: bool,
+        installed_only: bool,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        // Start with all apps
+        let mut apps = self.get_apps(uid, 500, 0, capability, category).await?;
 
-        // Check if transcript is a string (encrypted for enhanced protection)
-        if let Some(string_val) = transcript_field.and_then(|t| t.get("stringValue")).and_then(|s| s.as_str()) {
-            let data_protection_level = self.parse_string(fields, "data_protection_level");
-            if data_protection_level.as_deref() == Some("enhanced") {
-                if let Some(ref secret) = self.encryption_secret {
-                    // Decrypt the encrypted string
-                    let decrypted_payload = match encryption::decrypt(string_val, uid, secret) {
-                        Ok(decrypted) => decrypted,
-                        Err(e) => {
-                            tracing::warn!("Failed to decrypt transcript segments: {}", e);
-                            return Ok(vec![]);
-                        }
-                    };
+        // Filter by query (name/description)
+        if let Some(q) = query {
+            let q_lower = q.to_lowercase();
+            apps.retain(|app| app.matches_query(&q_lower));
+        }
 
-                    // Check if compression is used (should always be true for enhanced)
-                    let is_compressed = self.parse_bool(fields, "transcript_segments_compressed").unwrap_or(false);
+        // Filter by minimum rating
+        if let Some(min) = min_rating {
+            apps.retain(|app| app.rating_avg.unwrap_or(0.0) >= min as f64);
+        }
 
-                    if is_compressed {
-                        // Decrypted payload is a hex string, convert to bytes
-                        match hex::decode(&decrypted_payload) {
-                            Ok(compressed_bytes) => {
-                                // Decompress with zlib
-                                let mut decoder = ZlibDecoder::new(&compressed_bytes[..]);
-                                let mut decompressed = String::new();
-                                if let Err(e) = decoder.read_to_string(&mut decompressed) {
-                                    tracing::warn!("Failed to decompress encrypted transcript segments: {}", e);
-                                    return Ok(vec![]);
-                                }
+        // Filter by my apps (apps owned by the user)
+        if my_apps {
+            // For now, we don't have uid in AppSummary, so skip this filter
+            // In a full implementation, we'd need to check app.uid == uid
+        }
 
-                                // Parse JSON array of segments
-                                match serde_json::from_str::<Vec<serde_json::Value>>(&decompressed) {
-                                    Ok(segments) => {
-                                        let result: Vec<TranscriptSegment> = segments
-                                            .iter()
-                                            .filter_map(|seg| {
-                                                Some(TranscriptSegment {
-                                                    text: seg.get("text")?.as_str()?.to_string(),
-                                                    speaker: seg.get("speaker")
-                                                        .and_then(|s| s.as_str())
-                                                        .unwrap_or("SPEAKER_00")
-                                                        .to_string(),
-                                                    speaker_id: seg.get("speaker_id")
-                                                        .and_then(|s| s.as_i64())
-                                                        .unwrap_or(0) as i32,
-                                                    is_user: seg.get("is_user")
-                                                        .and_then(|s| s.as_bool())
-                                                        .unwrap_or(false),
-                                                    person_id: seg.get("person_id")
-                                                        .and_then(|s| s.as_str())
-                                                        .map(|s| s.to_string()),
-                                                    start: seg.get("start")
-                                                        .and_then(|s| s.as_f64())
-                                                        .unwrap_or(0.0),
-                                                    end: seg.get("end")
-                                                        .and_then(|s| s.as_f64())
-                                                        .unwrap_or(0.0),
-                                                })
-                                            })
-                                            .collect();
-                                        tracing::debug!("Decrypted and decompressed {} transcript segments for user {}", result.len(), uid);
-                                        return Ok(result);
-                                    }
-                                    Err(e) => {
-                                        tracing::warn!("Failed to parse decrypted transcript segments JSON: {}", e);
-                                        return Ok(vec![]);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to decode hex from decrypted transcript: {}", e);
-                                return Ok(vec![]);
-                            }
-                        }
-                    } else {
-                        // Old format: decrypted payload is JSON directly (backward compatibility)
-                        match serde_json::from_str::<Vec<serde_json::Value>>(&decrypted_payload) {
-                            Ok(segments) => {
-                                let result: Vec<TranscriptSegment> = segments
-                                    .iter()
-                                    .filter_map(|seg| {
-                                        Some(TranscriptSegment {
-                                            text: seg.get("text")?.as_str()?.to_string(),
-                                            speaker: seg.get("speaker")
-                                                .and_then(|s| s.as_str())
-                                                .unwrap_or("SPEAKER_00")
-                                                .to_string(),
-                                            speaker_id: seg.get("speaker_id")
-                                                .and_then(|s| s.as_i64())
-                                                .unwrap_or(0) as i32,
-                                            is_user: seg.get("is_user")
-                                                .and_then(|s| s.as_bool())
-                                                .unwrap_or(false),
-                                            person_id: seg.get("person_id")
-                                                .and_then(|s| s.as_str())
-                                                .map(|s| s.to_string()),
-                                            start: seg.get("start")
-                                                .and_then(|s| s.as_f64())
-                                                .unwrap_or(0.0),
-                                            end: seg.get("end")
-                                                .and_then(|s| s.as_f64())
-                                                .unwrap_or(0.0),
-                                        })
-                                    })
-                                    .collect();
-                                tracing::debug!("Decrypted {} transcript segments (uncompressed) for user {}", result.len(), uid);
-                                return Ok(result);
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to parse decrypted transcript segments JSON: {}", e);
-                                return Ok(vec![]);
-                            }
-                        }
-                    }
-                } else {
-                    tracing::debug!(
-                        "Transcript segments have enhanced protection but no encryption secret configured"
-                    );
-                    return Ok(vec![]);
-                }
-            } else {
-                // String but not enhanced - shouldn't happen, but return empty
-                tracing::debug!("Transcript segments are string format but not enhanced protection");
-                return Ok(vec![]);
+        // Filter by installed only
+        if installed_only {
+            apps.retain(|app| app.enabled);
+        }
+
+        // Apply pagination
+        let start = offset.min(apps.len());
+        let end = (offset + limit).min(apps.len());
+        Ok(apps[start..end].to_vec())
+    }
+
+    /// Get a single app by ID
+    pub async fn get_app(
+        &self,
+        uid: &str,
+        app_id: &str,
+    ) -> Result<Option<App>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), APPS_COLLECTION, app_id);
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        let mut app = self.parse_app(&doc)?;
+
+        // Check if enabled for user, and what they granted
+        let entries = self.get_enabled_app_entries(uid).await.unwrap_or_default();
+        if let Some(entry) = entries.into_iter().find(|e| e.app_id == app.id) {
+            app.enabled = true;
+            app.granted_scopes = entry.granted_scopes.unwrap_or_else(|| app.requested_scopes());
+        }
+
+        Ok(Some(app))
+    }
+
+    /// Get reviews for an app
+    pub async fn get_app_reviews(
+        &self,
+        app_id: &str,
+    ) -> Result<Vec<AppReview>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), APPS_COLLECTION, app_id);
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": "reviews"}],
+                "orderBy": [{"field": {"fieldPath": "rated_at"}, "direction": "DESCENDING"}],
+                "limit": 100
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
         }
 
-        // Check if transcript is bytes (zlib compressed) - decompress it
-        if let Some(bytes_val) = transcript_field.and_then(|t| t.get("bytesValue")) {
-            if let Some(b64_str) = bytes_val.as_str() {
-                match self.decompress_transcript_segments(b64_str) {
-                    Ok(segments) => {
-                        tracing::debug!("Decompressed {} transcript segments", segments.len());
-                        return Ok(segments);
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to decompress transcript segments: {}", e);
-                        return Ok(vec![]);
-                    }
-                }
+        let results: Vec<Value> = response.json().await?;
+        let reviews = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_app_review(d).ok())
+            })
+            .collect();
+
+        Ok(reviews)
+    }
+
+    /// Enable an app for a user, recording the scopes they consented to
+    pub async fn enable_app(
+        &self,
+        uid: &str,
+        app_id: &str,
+        granted_scopes: &[AppScope],
+        setup_required: bool,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ENABLED_APPS_SUBCOLLECTION,
+            app_id
+        );
+
+        let now = Utc::now();
+        let doc = json!({
+            "fields": {
+                "app_id": {"stringValue": app_id},
+                "enabled_at": {"timestampValue": now.to_rfc3339()},
+                "granted_scopes": {"arrayValue": {"values": granted_scopes
+                    .iter()
+                    .map(|scope| json!({"stringValue": scope.as_str()}))
+                    .collect::<Vec<_>>()}},
+                "scopes_granted_at": {"timestampValue": now.to_rfc3339()},
+                "setup_completed": {"booleanValue": !setup_required}
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to enable app", &error_text));
+        }
+
+        // Increment install count on the app
+        self.increment_app_installs(app_id).await?;
+
+        tracing::info!("Enabled app {} for user {}", app_id, uid);
+        Ok(())
+    }
+
+    /// Disable an app for a user
+    pub async fn disable_app(
+        &self,
+        uid: &str,
+        app_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ENABLED_APPS_SUBCOLLECTION,
+            app_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to disable app", &error_text));
+        }
+
+        tracing::info!("Disabled app {} for user {}", app_id, uid);
+        Ok(())
+    }
+
+    /// Setup progress of an enabled app: (completed, completed_at). None if the app isn't
+    /// enabled. Apps enabled before setup was tracked count as completed.
+    pub async fn get_app_setup(
+        &self,
+        uid: &str,
+        app_id: &str,
+    ) -> Result<Option<(bool, Option<DateTime<Utc>>)>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ENABLED_APPS_SUBCOLLECTION,
+            app_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to get enabled app", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").cloned().unwrap_or_else(|| json!({}));
+        Ok(Some((
+            self.parse_bool(&fields, "setup_completed").unwrap_or(true),
+            self.parse_timestamp_optional(&fields, "setup_completed_at"),
+        )))
+    }
+
+    /// Mark an enabled app's setup complete. Fails with NOT_FOUND if the app was disabled.
+    pub async fn complete_app_setup(
+        &self,
+        uid: &str,
+        app_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=setup_completed&updateMask.fieldPaths=setup_completed_at&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ENABLED_APPS_SUBCOLLECTION,
+            app_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "setup_completed": {"booleanValue": true},
+                "setup_completed_at": {"timestampValue": Utc::now().to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to complete app setup", &error_text));
+        }
+
+        tracing::info!("Completed setup of app {} for user {}", app_id, uid);
+        Ok(())
+    }
+
+    /// Store a started setup flow under its hashed state
+    pub async fn save_app_setup_state(
+        &self,
+        state_hash: &str,
+        setup: &AppSetupState,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), APP_SETUP_STATES_COLLECTION, state_hash);
+        let fields = firestore_serde::to_fields(setup, &[])?;
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to save app setup state", &error_text));
+        }
+        Ok(())
+    }
+
+    /// Read and delete a setup flow, so each state is used once. None if unknown or
+    /// already used; expiry is left to the caller.
+    pub async fn take_app_setup_state(
+        &self,
+        state_hash: &str,
+    ) -> Result<Option<AppSetupState>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), APP_SETUP_STATES_COLLECTION, state_hash);
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to get app setup state", &error_text));
+        }
+        let doc: Value = response.json().await?;
+        let setup: AppSetupState = firestore_serde::from_document(&doc)?;
+        let update_time = doc.get("updateTime").and_then(|t| t.as_str()).unwrap_or_default();
+
+        // Conditional on the version read, so two callbacks racing can't both use it
+        let delete_url = format!("{}?currentDocument.updateTime={}", url, update_time);
+        let response = self
+            .build_request(reqwest::Method::DELETE, &delete_url)
+            .await?
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            if status == reqwest::StatusCode::NOT_FOUND || is_precondition_failure(status, &error_text) {
+                return Ok(None);
+            }
+            return Err(format!("Failed to delete app setup state: {}", error_text).into());
+        }
+        Ok(Some(setup))
+    }
+
+    /// Get user's enabled app IDs
+    async fn get_enabled_app_ids(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<String>, FirestoreError> {
+        Ok(self
+            .get_enabled_app_entries(uid)
+            .await?
+            .into_iter()
+            .map(|entry| entry.app_id)
+            .collect())
+    }
+
+    /// Get user's enabled_plugins documents
+    async fn get_enabled_app_entries(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<EnabledAppEntry>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ENABLED_APPS_SUBCOLLECTION}],
+                "limit": 500
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let entries = results
+            .into_iter()
+            .filter_map(|doc| {
+                let d = doc.get("document")?;
+                let fields = d.get("fields");
+                Some(EnabledAppEntry {
+                    app_id: firestore_serde::document_id(d)?.to_string(),
+                    delivery_disabled: fields
+                        .and_then(|f| self.parse_bool(f, "integration_delivery_disabled").ok())
+                        .unwrap_or(false),
+                    granted_scopes: fields.and_then(|f| self.parse_app_scopes(f, "granted_scopes")),
+                    setup_completed: fields.and_then(|f| self.parse_bool(f, "setup_completed").ok()),
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Get user's enabled apps as summaries
+    pub async fn get_enabled_apps(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<AppSummary>, FirestoreError> {
+        let enabled_ids = self.get_enabled_app_ids(uid).await?;
+
+        let mut apps = Vec::new();
+        for app_id in enabled_ids {
+            if let Ok(Some(app)) = self.get_app(uid, &app_id).await {
+                let mut summary = AppSummary::from(app);
+                summary.enabled = true;
+                apps.push(summary);
+            }
+        }
+
+        Ok(apps)
+    }
+
+    /// Get user's enabled apps with full App details (for integration triggers)
+    pub async fn get_enabled_apps_full(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<App>, FirestoreError> {
+        let enabled = self.get_enabled_app_entries(uid).await?;
+
+        let mut apps = Vec::new();
+        for entry in enabled {
+            if let Ok(Some(mut app)) = self.get_app(uid, &entry.app_id).await {
+                app.enabled = true;
+                app.integration_delivery_disabled = entry.delivery_disabled;
+                app.granted_scopes = entry.granted_scopes.unwrap_or_else(|| app.requested_scopes());
+                // Apps enabled before setup was tracked keep working
+                app.setup_completed = entry.setup_completed.unwrap_or(true);
+                apps.push(app);
+            }
+        }
+
+        Ok(apps)
+    }
+
+    /// Pause webhook delivery for an enabled app after repeated failures.
+    /// Re-enabling the app (enable_app rewrites the doc) clears the flag.
+    pub async fn disable_integration_delivery(
+        &self,
+        uid: &str,
+        app_id: &str,
+        reason: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=integration_delivery_disabled&updateMask.fieldPaths=integration_delivery_disabled_reason&updateMask.fieldPaths=integration_delivery_disabled_at&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ENABLED_APPS_SUBCOLLECTION,
+            app_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "integration_delivery_disabled": {"booleanValue": true},
+                "integration_delivery_disabled_reason": {"stringValue": reason},
+                "integration_delivery_disabled_at": {"timestampValue": Utc::now().to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to disable integration delivery", &error_text));
+        }
+
+        tracing::info!("Disabled integration delivery for app {} (user {})", app_id, uid);
+        Ok(())
+    }
+
+    /// Increment app install count
+    async fn increment_app_installs(
+        &self,
+        app_id: &str,
+    ) -> Result<(), FirestoreError> {
+        // First get current installs
+        let app = match self.get_app("", app_id).await? {
+            Some(a) => a,
+            None => return Ok(()),
+        };
+
+        let new_installs = app.installs + 1;
+
+        let url = format!(
+            "{}/{}/{}?updateMask.fieldPaths=installs",
+            self.base_url(),
+            APPS_COLLECTION,
+            app_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "installs": {"integerValue": new_installs.to_string()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            tracing::warn!("Failed to increment app installs: {}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
+    /// plugins_data/{app_id}/reviews/{uid}
+    fn app_review_url(&self, app_id: &str, uid: &str) -> String {
+        format!(
+            "{}/{}/{}/reviews/{}",
+            self.base_url(),
+            APPS_COLLECTION,
+            app_id,
+            uid
+        )
+    }
+
+    /// Get a single user's review of an app
+    pub async fn get_app_review(
+        &self,
+        app_id: &str,
+        uid: &str,
+    ) -> Result<Option<AppReview>, FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::GET, &self.app_review_url(app_id, uid))
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        Ok(Some(self.parse_app_review(&doc)?))
+    }
+
+    /// Submit a review for an app. Fails with ALREADY_EXISTS if the user has already reviewed it.
+    pub async fn submit_app_review(
+        &self,
+        uid: &str,
+        app_id: &str,
+        score: i32,
+        review: &str,
+    ) -> Result<AppReview, FirestoreError> {
+        let url = format!(
+            "{}?currentDocument.exists=false",
+            self.app_review_url(app_id, uid)
+        );
+
+        let now = Utc::now();
+        let doc = json!({
+            "fields": {
+                "uid": {"stringValue": uid},
+                "score": {"integerValue": score.to_string()},
+                "review": {"stringValue": review},
+                "rated_at": {"timestampValue": now.to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(submit_review_error(status, &error_text));
+        }
+
+        // Update app's rating average and count
+        self.update_app_rating(app_id).await?;
+
+        Ok(AppReview {
+            uid: uid.to_string(),
+            score,
+            review: review.to_string(),
+            response: None,
+            rated_at: now,
+            edited_at: None,
+        })
+    }
+
+    /// Edit the user's own review; sets edited_at
+    pub async fn update_app_review(
+        &self,
+        uid: &str,
+        app_id: &str,
+        score: Option<i32>,
+        review: Option<&str>,
+    ) -> Result<AppReview, FirestoreError> {
+        let (fields, mask) = review_edit_fields(score, review, Utc::now());
+
+        let mask_params: String = mask
+            .iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
+        let url = format!(
+            "{}?{}&currentDocument.exists=true",
+            self.app_review_url(app_id, uid),
+            mask_params
+        );
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to update review", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        if score.is_some() {
+            self.update_app_rating(app_id).await?;
+        }
+        self.parse_app_review(&doc)
+    }
+
+    /// Delete the user's own review and recompute the app's rating
+    pub async fn delete_app_review(
+        &self,
+        uid: &str,
+        app_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}?currentDocument.exists=true", self.app_review_url(app_id, uid));
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to delete review", &error_text));
+        }
+
+        self.update_app_rating(app_id).await
+    }
+
+    /// Store the developer's response on a review
+    pub async fn respond_to_app_review(
+        &self,
+        app_id: &str,
+        reviewer_uid: &str,
+        response_text: &str,
+    ) -> Result<AppReview, FirestoreError> {
+        let url = format!(
+            "{}?updateMask.fieldPaths=response&updateMask.fieldPaths=responded_at&currentDocument.exists=true",
+            self.app_review_url(app_id, reviewer_uid)
+        );
+
+        let doc = json!({
+            "fields": {
+                "response": {"stringValue": response_text},
+                "responded_at": {"timestampValue": Utc::now().to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to respond to review", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        self.parse_app_review(&doc)
+    }
+
+    /// Update app's rating average and count
+    async fn update_app_rating(
+        &self,
+        app_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let reviews = self.get_app_reviews(app_id).await?;
+
+        // No reviews left (e.g. the last one was deleted) resets the rating
+        let total: i32 = reviews.iter().map(|r| r.score).sum();
+        let count = reviews.len() as i32;
+        let avg = if count > 0 { total as f64 / count as f64 } else { 0.0 };
+
+        let url = format!(
+            "{}/{}/{}?updateMask.fieldPaths=rating_avg&updateMask.fieldPaths=rating_count",
+            self.base_url(),
+            APPS_COLLECTION,
+            app_id
+        );
+
+        let doc = json!({
+            "fields": {
+                "rating_avg": {"doubleValue": avg},
+                "rating_count": {"integerValue": count.to_string()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            tracing::warn!("Failed to update app rating: {}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
+    /// Parse Firestore document to App
+    fn parse_app(
+        &self,
+        doc: &Value,
+    ) -> Result<App, FirestoreError> {
+        let fields = doc.get("fields").ok_or("Missing fields in document")?;
+        let id = firestore_serde::document_id(doc).unwrap_or_default().to_string();
+
+        Ok(App {
+            id,
+            name: self.parse_string(fields, "name").unwrap_or_default(),
+            description: self.parse_string(fields, "description").unwrap_or_default(),
+            image: self.parse_string(fields, "image").unwrap_or_default(),
+            category: self.parse_string(fields, "category").unwrap_or_else(|| "other".to_string()),
+            author: self.parse_string(fields, "author").unwrap_or_default(),
+            email: self.parse_string(fields, "email"),
+            capabilities: self.parse_string_array(fields, "capabilities"),
+            scopes: self.parse_app_scopes(fields, "scopes").unwrap_or_default(),
+            uid: self.parse_string(fields, "uid"),
+            approved: self.parse_bool(fields, "approved").unwrap_or(false),
+            private: self.parse_bool(fields, "private").unwrap_or(false),
+            status: self.parse_string(fields, "status").unwrap_or_else(|| "under-review".to_string()),
+            chat_prompt: self.parse_string(fields, "chat_prompt"),
+            memory_prompt: self.parse_string(fields, "memory_prompt"),
+            persona_prompt: self.parse_string(fields, "persona_prompt"),
+            external_integration: self.parse_external_integration(fields),
+            proactive_notification: self.parse_proactive_notification(fields),
+            chat_tools: vec![], // TODO: Parse array of nested objects
+            installs: self.parse_int(fields, "installs").unwrap_or(0),
+            rating_avg: self.parse_float(fields, "rating_avg"),
+            rating_count: self.parse_int(fields, "rating_count").unwrap_or(0),
+            is_paid: self.parse_bool(fields, "is_paid").unwrap_or(false),
+            price: self.parse_float(fields, "price"),
+            payment_plan: self.parse_string(fields, "payment_plan"),
+            username: self.parse_string(fields, "username"),
+            twitter: self.parse_string(fields, "twitter"),
+            created_at: self.parse_timestamp_optional(fields, "created_at"),
+            enabled: false, // Will be set by caller
+            integration_delivery_disabled: false, // Will be set by caller
+            granted_scopes: vec![], // Will be set by caller
+            setup_completed: false, // Will be set by caller
+            moderation: self.parse_app_moderation(fields),
+            translations: self.parse_app_translations(fields),
+        })
+    }
+
+    /// Parse the translations map of an app ({"es": {"name": ..., "description": ...}})
+    fn parse_app_translations(&self, fields: &Value) -> HashMap<String, AppTranslation> {
+        self.parse_sub_map(fields, "translations")
+            .and_then(|m| m.as_object())
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(language, v)| {
+                        let t = v.get("mapValue")?.get("fields")?;
+                        Some((
+                            language.clone(),
+                            AppTranslation {
+                                name: self.parse_string(t, "name"),
+                                description: self.parse_string(t, "description"),
+                            },
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn parse_app_moderation(&self, fields: &Value) -> Option<AppModeration> {
+        let m = self.parse_sub_map(fields, "moderation")?;
+        Some(AppModeration {
+            verdict: ModerationVerdict::parse(&self.parse_string(m, "verdict")?)?,
+            reasons: self.parse_string_array(m, "reasons"),
+            prompt_hash: self.parse_string(m, "prompt_hash").unwrap_or_default(),
+            checked_at: self.parse_timestamp_optional(m, "checked_at").unwrap_or_else(Utc::now),
+            admin_override: self.parse_bool(m, "admin_override").unwrap_or(false),
+            overridden_by: self.parse_string(m, "overridden_by"),
+        })
+    }
+
+    /// Parse the nested external_integration map of an app.
+    /// Returns None if absent or if `triggers_on` is missing/unknown.
+    fn parse_external_integration(&self, fields: &Value) -> Option<ExternalIntegration> {
+        let ei = fields.get("external_integration")?.get("mapValue")?.get("fields")?;
+
+        let trigger = self.parse_string(ei, "triggers_on")?;
+        let triggers_on: TriggerEvent = match serde_json::from_value(json!(trigger)) {
+            Ok(t) => t,
+            Err(_) => {
+                tracing::warn!("Unknown external_integration trigger: {}", trigger);
+                return None;
+            }
+        };
+
+        let auth_steps = Self::array_values(ei, "auth_steps")
+            .iter()
+            .filter_map(|v| {
+                let step = v.get("mapValue")?.get("fields")?;
+                Some(AuthStep {
+                    name: self.parse_string(step, "name").unwrap_or_default(),
+                    url: self.parse_string(step, "url")?,
+                })
+            })
+            .collect();
+
+        // Python stores actions as [{"action": "create_conversation"}]; accept bare strings too
+        let actions = Self::array_values(ei, "actions")
+            .iter()
+            .filter_map(|v| {
+                let action = v.get("stringValue").or_else(|| {
+                    v.get("mapValue")?.get("fields")?.get("action")?.get("stringValue")
+                })?;
+                serde_json::from_value::<ActionType>(action.clone()).ok()
+            })
+            .collect();
+
+        Some(ExternalIntegration {
+            triggers_on,
+            webhook_url: self.parse_string(ei, "webhook_url").unwrap_or_default(),
+            setup_completed_url: self.parse_string(ei, "setup_completed_url"),
+            setup_instructions_file_path: self.parse_string(ei, "setup_instructions_file_path"),
+            is_instructions_url: self.parse_bool(ei, "is_instructions_url").unwrap_or(false),
+            auth_steps,
+            app_home_url: self.parse_string(ei, "app_home_url"),
+            actions,
+        })
+    }
+
+    /// Parse an array of app scopes (unknown ones are skipped); None if the field is missing
+    fn parse_app_scopes(&self, fields: &Value, key: &str) -> Option<Vec<AppScope>> {
+        fields.get(key)?;
+        Some(
+            self.parse_string_array(fields, key)
+                .iter()
+                .filter_map(|s| AppScope::parse(s))
+                .collect(),
+        )
+    }
+
+    /// Parse the nested proactive_notification map of an app (unknown scopes are skipped)
+    fn parse_proactive_notification(&self, fields: &Value) -> Option<ProactiveNotification> {
+        let pn = fields.get("proactive_notification")?.get("mapValue")?.get("fields")?;
+
+        let scopes = self
+            .parse_string_array(pn, "scopes")
+            .into_iter()
+            .filter_map(|s| serde_json::from_value::<NotificationScope>(json!(s)).ok())
+            .collect();
+
+        Some(ProactiveNotification { scopes })
+    }
+
+    /// Values of an arrayValue field (empty if missing)
+    fn array_values<'a>(fields: &'a Value, key: &str) -> &'a [Value] {
+        fields
+            .get(key)
+            .and_then(|v| v.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|v| v.as_array())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Build the Firestore map value for an app's external_integration
+    #[allow(dead_code)] // Used by the app submission API
+    fn external_integration_to_value(ei: &ExternalIntegration) -> Value {
+        let mut fields = serde_json::Map::new();
+        fields.insert("triggers_on".to_string(), json!({"stringValue": Self::enum_str(&ei.triggers_on)}));
+        fields.insert("webhook_url".to_string(), json!({"stringValue": ei.webhook_url}));
+        if let Some(url) = &ei.setup_completed_url {
+            fields.insert("setup_completed_url".to_string(), json!({"stringValue": url}));
+        }
+        if let Some(path) = &ei.setup_instructions_file_path {
+            fields.insert("setup_instructions_file_path".to_string(), json!({"stringValue": path}));
+        }
+        fields.insert("is_instructions_url".to_string(), json!({"booleanValue": ei.is_instructions_url}));
+        if let Some(url) = &ei.app_home_url {
+            fields.insert("app_home_url".to_string(), json!({"stringValue": url}));
+        }
+
+        let auth_steps: Vec<Value> = ei.auth_steps.iter().map(|step| {
+            json!({"mapValue": {"fields": {
+                "name": {"stringValue": step.name},
+                "url": {"stringValue": step.url}
+            }}})
+        }).collect();
+        fields.insert("auth_steps".to_string(), json!({"arrayValue": {"values": auth_steps}}));
+
+        let actions: Vec<Value> = ei.actions.iter().map(|action| {
+            json!({"mapValue": {"fields": {
+                "action": {"stringValue": Self::enum_str(action)}
+            }}})
+        }).collect();
+        fields.insert("actions".to_string(), json!({"arrayValue": {"values": actions}}));
+
+        json!({"mapValue": {"fields": fields}})
+    }
+
+    /// Build the Firestore map value for an app's proactive_notification
+    #[allow(dead_code)] // Used by the app submission API
+    fn proactive_notification_to_value(pn: &ProactiveNotification) -> Value {
+        let scopes: Vec<Value> = pn.scopes.iter()
+            .map(|scope| json!({"stringValue": Self::enum_str(scope)}))
+            .collect();
+        json!({"mapValue": {"fields": {
+            "scopes": {"arrayValue": {"values": scopes}}
+        }}})
+    }
+
+    /// Serialized snake_case name of a unit enum variant
+    #[allow(dead_code)]
+    fn enum_str<T: Serialize>(value: &T) -> String {
+        serde_json::to_value(value)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+
+    /// Parse Firestore document to AppSummary
+    fn parse_app_summary(
+        &self,
+        doc: &Value,
+    ) -> Result<AppSummary, FirestoreError> {
+        let fields = doc.get("fields").ok_or("Missing fields in document")?;
+        let id = firestore_serde::document_id(doc).unwrap_or_default().to_string();
+
+        // Parse has_auth_steps from external_integration.auth_steps
+        // Structure: external_integration: { mapValue: { fields: { auth_steps: { arrayValue: { values: [...] } } } } }
+        let has_auth_steps = fields
+            .get("external_integration")
+            .and_then(|ei| ei.get("mapValue"))
+            .and_then(|mv| mv.get("fields"))
+            .and_then(|f| f.get("auth_steps"))
+            .and_then(|as_| as_.get("arrayValue"))
+            .and_then(|av| av.get("values"))
+            .and_then(|v| v.as_array())
+            .map(|arr| !arr.is_empty())
+            .unwrap_or(false);
+
+        Ok(AppSummary {
+            id,
+            name: self.parse_string(fields, "name").unwrap_or_default(),
+            description: self.parse_string(fields, "description").unwrap_or_default(),
+            image: self.parse_string(fields, "image").unwrap_or_default(),
+            category: self.parse_string(fields, "category").unwrap_or_else(|| "other".to_string()),
+            author: self.parse_string(fields, "author").unwrap_or_default(),
+            capabilities: self.parse_string_array(fields, "capabilities"),
+            approved: self.parse_bool(fields, "approved").unwrap_or(false),
+            private: self.parse_bool(fields, "private").unwrap_or(false),
+            installs: self.parse_int(fields, "installs").unwrap_or(0),
+            rating_avg: self.parse_float(fields, "rating_avg"),
+            rating_count: self.parse_int(fields, "rating_count").unwrap_or(0),
+            is_paid: self.parse_bool(fields, "is_paid").unwrap_or(false),
+            price: self.parse_float(fields, "price"),
+            enabled: false, // Will be set by caller
+            has_auth_steps,
+            created_at: self.parse_timestamp_optional(fields, "created_at"),
+            translations: self.parse_app_translations(fields),
+        })
+    }
+
+    /// Parse Firestore document to AppReview
+    fn parse_app_review(
+        &self,
+        doc: &Value,
+    ) -> Result<AppReview, FirestoreError> {
+        let fields = doc.get("fields").ok_or("Missing fields")?;
+        let uid = firestore_serde::document_id(doc).unwrap_or_default().to_string();
+
+        Ok(AppReview {
+            uid,
+            score: self.parse_int(fields, "score").unwrap_or(0),
+            review: self.parse_string(fields, "review").unwrap_or_default(),
+            response: self.parse_string(fields, "response"),
+            rated_at: self.parse_timestamp_optional(fields, "rated_at").unwrap_or_else(Utc::now),
+            edited_at: self.parse_timestamp_optional(fields, "edited_at"),
+        })
+    }
+
+    /// Parse string array from Firestore
+    fn parse_string_array(&self, fields: &Value, key: &str) -> Vec<String> {
+        fields
+            .get(key)
+            .and_then(|v| v.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.get("stringValue")?.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Parse a map of string values (e.g. conversation generated_by)
+    fn parse_string_map(&self, fields: &Value, key: &str) -> HashMap<String, String> {
+        self.parse_sub_map(fields, key)
+            .and_then(|m| m.as_object())
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.get("stringValue")?.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Build a Firestore map value of strings
+    fn build_string_map_value(&self, map: &HashMap<String, String>) -> Value {
+        let fields: serde_json::Map<String, Value> = map
+            .iter()
+            .map(|(k, v)| (k.clone(), json!({"stringValue": v})))
+            .collect();
+        self.build_sub_map_value(fields)
+    }
+
+    // =========================================================================
+    // PARSING HELPERS
+    // =========================================================================
+
+    /// Parse Firestore document to Conversation
+    /// Decrypts transcript_segments and photos if data_protection_level is "enhanced"
+    fn parse_conversation(
+        &self,
+        doc: &Value,
+        uid: &str,
+    ) -> Result<Conversation, FirestoreError> {
+        let fields = doc
+            .get("fields")
+            .ok_or("Missing fields in document")?;
+        let id = firestore_serde::document_id(doc).unwrap_or_default().to_string();
+
+        // Use created_at as fallback for missing timestamps
+        let created_at = self.parse_timestamp_optional(fields, "created_at")
+            .unwrap_or_else(Utc::now);
+        let started_at = self.parse_timestamp_optional(fields, "started_at")
+            .unwrap_or(created_at);
+        let finished_at = self.parse_timestamp_optional(fields, "finished_at")
+            .unwrap_or(created_at);
+
+        // Parse apps_results
+        let apps_results = self.parse_apps_results(fields);
+
+        Ok(Conversation {
+            id,
+            created_at,
+            started_at,
+            finished_at,
+            source: self.parse_string(fields, "source")
+                .and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok())
+                .unwrap_or_default(),
+            language: self.parse_string(fields, "language").unwrap_or_default(),
+            status: self.parse_string(fields, "status")
+                .and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok())
+                .unwrap_or_default(),
+            discarded: self.parse_bool(fields, "discarded").unwrap_or(false),
+            discard_reason: self
+                .parse_string(fields, "discard_reason")
+                .and_then(|v| DiscardReason::parse(&v)),
+            deleted: self.parse_bool(fields, "deleted").unwrap_or(false),
+            starred: self.parse_bool(fields, "starred").unwrap_or(false),
+            is_locked: self.parse_bool(fields, "is_locked").unwrap_or(false),
+            folder_id: self.parse_string(fields, "folder_id"),
+            structured: self.parse_structured(fields)?,
+            transcript_segments: self.parse_transcript_segments(fields, uid)?,
+            apps_results,
+            geolocation: self.parse_geolocation(fields),
+            photos: self.parse_photos(fields, uid),
+            input_device_name: self.parse_string(fields, "input_device_name"),
+            memory_extraction_disabled: self
+                .parse_bool(fields, "memory_extraction_disabled")
+                .unwrap_or(false),
+            notion_sync: self.parse_notion_sync(fields),
+            detected_languages: self.parse_string_array(fields, "detected_languages"),
+            analytics: self.parse_conversation_analytics(fields),
+            context_line: self.parse_string(fields, "context_line"),
+            generated_by: self.parse_string_map(fields, "generated_by"),
+            template_id: self.parse_string(fields, "template_id"),
+            transcript_deleted_at: self.parse_timestamp_optional(fields, "transcript_deleted_at"),
+            focus_sessions: self.parse_conversation_focus_sessions(fields),
+            prompt_version: self.parse_string(fields, "prompt_version"),
+            feedback: self.parse_sub_map(fields, "feedback").and_then(|f| {
+                Some(ConversationFeedback {
+                    rating: self.parse_int(f, "rating")?,
+                    comment: self.parse_string(f, "comment"),
+                    rated_at: self.parse_timestamp_optional(f, "rated_at")?,
+                })
+            }),
+        })
+    }
+
+    /// Parse the focus_sessions array attached to a conversation
+    fn parse_conversation_focus_sessions(&self, fields: &Value) -> Vec<ConversationFocusSession> {
+        let Some(values) = fields
+            .get("focus_sessions")
+            .and_then(|a| a.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|a| a.as_array())
+        else {
+            return Vec::new();
+        };
+        values
+            .iter()
+            .filter_map(|v| {
+                let f = v.get("mapValue")?.get("fields")?;
+                Some(ConversationFocusSession {
+                    session_id: self.parse_string(f, "session_id")?,
+                    status: match self.parse_string(f, "status").as_deref() {
+                        Some("focused") => FocusStatus::Focused,
+                        _ => FocusStatus::Distracted,
+                    },
+                    app_or_site: self.parse_string(f, "app_or_site").unwrap_or_default(),
+                    started_at: self.parse_timestamp_optional(f, "started_at")?,
+                    overlap_seconds: self.parse_int(f, "overlap_seconds").unwrap_or(0) as i64,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse apps_results array from Firestore fields
+    fn parse_apps_results(&self, fields: &Value) -> Vec<crate::models::AppResult> {
+        let array = match fields.get("apps_results")
+            .and_then(|a| a.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|a| a.as_array())
+        {
+            Some(arr) => arr,
+            None => return vec![],
+        };
+
+        array.iter().filter_map(|item| {
+            let map_fields = item.get("mapValue")?.get("fields")?;
+            let app_id = self.parse_string(map_fields, "app_id");
+            let content = self.parse_string(map_fields, "content").unwrap_or_default();
+            Some(crate::models::AppResult { app_id, content })
+        }).collect()
+    }
+
+    fn conversation_feedback_to_value(feedback: &ConversationFeedback) -> Value {
+        let mut fields = json!({
+            "rating": {"integerValue": feedback.rating.to_string()},
+            "rated_at": {"timestampValue": feedback.rated_at.to_rfc3339()}
+        });
+        if let Some(comment) = &feedback.comment {
+            fields["comment"] = json!({"stringValue": comment});
+        }
+        json!({"mapValue": {"fields": fields}})
+    }
+
+    fn conversation_analytics_to_value(analytics: &ConversationAnalytics) -> Value {
+        let speakers: Vec<Value> = analytics
+            .speakers
+            .iter()
+            .map(|s| {
+                let mut fields = json!({
+                    "speaker": {"stringValue": s.speaker},
+                    "is_user": {"booleanValue": s.is_user},
+                    "talk_time_seconds": {"doubleValue": s.talk_time_seconds},
+                    "talk_time_ratio": {"doubleValue": s.talk_time_ratio},
+                    "word_count": {"integerValue": s.word_count.to_string()},
+                    "words_per_minute": {"doubleValue": s.words_per_minute},
+                    "longest_monologue_seconds": {"doubleValue": s.longest_monologue_seconds},
+                    "interruptions": {"integerValue": s.interruptions.to_string()}
+                });
+                if let Some(person_id) = &s.person_id {
+                    fields["person_id"] = json!({"stringValue": person_id});
+                }
+                json!({"mapValue": {"fields": fields}})
+            })
+            .collect();
+
+        let mut fields = json!({
+            "total_talk_time_seconds": {"doubleValue": analytics.total_talk_time_seconds},
+            "words_per_minute": {"doubleValue": analytics.words_per_minute},
+            "interruptions": {"integerValue": analytics.interruptions.to_string()},
+            "longest_monologue_seconds": {"doubleValue": analytics.longest_monologue_seconds},
+            "speakers": {"arrayValue": {"values": speakers}},
+            "computed_at": {"timestampValue": analytics.computed_at.to_rfc3339()}
+        });
+        if let Some(speaker) = &analytics.longest_monologue_speaker {
+            fields["longest_monologue_speaker"] = json!({"stringValue": speaker});
+        }
+        json!({"mapValue": {"fields": fields}})
+    }
+
+    fn parse_conversation_analytics(&self, fields: &Value) -> Option<ConversationAnalytics> {
+        let analytics = fields.get("analytics")?.get("mapValue")?.get("fields")?;
+
+        let speakers = analytics
+            .get("speakers")
+            .and_then(|s| s.get("arrayValue"))
+            .and_then(|s| s.get("values"))
+            .and_then(|s| s.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| {
+                        let s = v.get("mapValue")?.get("fields")?;
+                        Some(SpeakerAnalytics {
+                            speaker: self.parse_string(s, "speaker")?,
+                            name: None,
+                            is_user: self.parse_bool(s, "is_user").unwrap_or(false),
+                            person_id: self.parse_string(s, "person_id"),
+                            talk_time_seconds: self.parse_float(s, "talk_time_seconds").unwrap_or(0.0),
+                            talk_time_ratio: self.parse_float(s, "talk_time_ratio").unwrap_or(0.0),
+                            word_count: self.parse_int(s, "word_count").unwrap_or(0),
+                            words_per_minute: self.parse_float(s, "words_per_minute").unwrap_or(0.0),
+                            longest_monologue_seconds: self
+                                .parse_float(s, "longest_monologue_seconds")
+                                .unwrap_or(0.0),
+                            interruptions: self.parse_int(s, "interruptions").unwrap_or(0),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(ConversationAnalytics {
+            total_talk_time_seconds: self.parse_float(analytics, "total_talk_time_seconds").unwrap_or(0.0),
+            words_per_minute: self.parse_float(analytics, "words_per_minute").unwrap_or(0.0),
+            interruptions: self.parse_int(analytics, "interruptions").unwrap_or(0),
+            longest_monologue_seconds: self.parse_float(analytics, "longest_monologue_seconds").unwrap_or(0.0),
+            longest_monologue_speaker: self.parse_string(analytics, "longest_monologue_speaker"),
+            speakers,
+            computed_at: self.parse_timestamp_optional(analytics, "computed_at").unwrap_or_else(Utc::now),
+        })
+    }
+
+    /// Parse Firestore document to ActionItemDB
+    fn parse_action_item(
+        &self,
+        doc: &Value,
+    ) -> Result<ActionItemDB, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
+    }
+
+    /// Parse Firestore document to MemoryDB
+    /// Decrypts content if data_protection_level is "enhanced" and encryption secret is available.
+    fn parse_memory(
+        &self,
+        doc: &Value,
+        uid: &str,
+    ) -> Result<MemoryDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        // Categories this build doesn't know read as the default instead of failing the memory
+        firestore_serde::drop_invalid::<MemoryCategory>(&mut object, "category");
+        // Stored directly for tips; otherwise enriched from the linked conversation
+        let source = object.get("source").and_then(|s| s.as_str()).map(str::to_string);
+        let enhanced = object.get("data_protection_level").and_then(|l| l.as_str()) == Some("enhanced");
+
+        let mut memory: MemoryDB = serde_json::from_value(Value::Object(object))?;
+        memory.source = source;
+
+        if enhanced {
+            if let Some(ref secret) = self.encryption_secret {
+                match encryption::decrypt(&memory.content, uid, secret) {
+                    Ok(decrypted) => memory.content = decrypted,
+                    Err(e) => {
+                        tracing::warn!("Failed to decrypt memory {}: {}", memory.id, e);
+                        memory.content = "[Encrypted content — decryption failed]".to_string();
+                    }
+                }
+            } else {
+                tracing::warn!(
+                    "Memory {} has enhanced protection but no encryption secret configured",
+                    memory.id
+                );
+                memory.content = "[Encrypted content — decryption failed]".to_string();
+            }
+        }
+
+        Ok(memory)
+    }
+
+    /// Parse structured data from conversation
+    fn parse_structured(
+        &self,
+        fields: &Value,
+    ) -> Result<Structured, FirestoreError> {
+        let structured = fields.get("structured").and_then(|s| s.get("mapValue")).and_then(|m| m.get("fields"));
+
+        if let Some(s) = structured {
+            let title = self.parse_string(s, "title").unwrap_or_default();
+            if title.is_empty() {
+                tracing::warn!(
+                    "DEBUG parse_structured: title is empty! structured fields: {}",
+                    serde_json::to_string_pretty(s).unwrap_or_default()
+                );
+            }
+            Ok(Structured {
+                title,
+                overview: self.parse_string(s, "overview").unwrap_or_default(),
+                emoji: self.parse_string(s, "emoji").unwrap_or_else(|| "🧠".to_string()),
+                category: self.parse_string(s, "category")
+                    .and_then(|c| serde_json::from_str(&format!("\"{}\"", c)).ok())
+                    .unwrap_or_default(),
+                action_items: self.parse_action_items_from_structured(s),
+                events: self.parse_events_from_structured(s),
+            })
+        } else {
+            tracing::warn!(
+                "DEBUG parse_structured: no structured field found! fields: {}",
+                serde_json::to_string_pretty(fields).unwrap_or_default()
+            );
+            Ok(Structured::default())
+        }
+    }
+
+    /// Parse action_items array from structured field
+    fn parse_action_items_from_structured(&self, structured_fields: &Value) -> Vec<crate::models::ActionItem> {
+        let array = match structured_fields.get("action_items")
+            .and_then(|a| a.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|a| a.as_array())
+        {
+            Some(arr) => arr,
+            None => return vec![],
+        };
+
+        array.iter().filter_map(|item| {
+            let map_fields = item.get("mapValue")?.get("fields")?;
+            let description = self.parse_string(map_fields, "description").unwrap_or_default();
+            let completed = self.parse_bool(map_fields, "completed").unwrap_or(false);
+            let due_at = self.parse_timestamp_optional(map_fields, "due_at");
+            Some(crate::models::ActionItem {
+                description,
+                completed,
+                due_at,
+                confidence: self.parse_float(map_fields, "confidence"),
+                priority: self.parse_string(map_fields, "priority"),
+                action_item_id: self.parse_string(map_fields, "action_item_id"),
+                due_at_confidence: self.parse_float(map_fields, "due_at_confidence"),
+                due_phrase: self.parse_string(map_fields, "due_phrase"),
+            })
+        }).collect()
+    }
+
+    /// Build the Firestore map value for a structured action item
+    fn structured_action_item_to_value(item: &crate::models::ActionItem) -> Value {
+        let mut fields = serde_json::Map::new();
+        fields.insert("description".to_string(), json!({"stringValue": item.description}));
+        fields.insert("completed".to_string(), json!({"booleanValue": item.completed}));
+        if let Some(due_at) = &item.due_at {
+            fields.insert("due_at".to_string(), json!({"timestampValue": due_at.to_rfc3339()}));
+        }
+        if let Some(confidence) = item.confidence {
+            fields.insert("confidence".to_string(), json!({"doubleValue": confidence}));
+        }
+        if let Some(priority) = &item.priority {
+            fields.insert("priority".to_string(), json!({"stringValue": priority}));
+        }
+        if let Some(action_item_id) = &item.action_item_id {
+            fields.insert("action_item_id".to_string(), json!({"stringValue": action_item_id}));
+        }
+        if let Some(due_at_confidence) = item.due_at_confidence {
+            fields.insert("due_at_confidence".to_string(), json!({"doubleValue": due_at_confidence}));
+        }
+        if let Some(due_phrase) = &item.due_phrase {
+            fields.insert("due_phrase".to_string(), json!({"stringValue": due_phrase}));
+        }
+        json!({"mapValue": {"fields": fields}})
+    }
+
+    /// Parse events array from structured field
+    fn parse_events_from_structured(&self, structured_fields: &Value) -> Vec<crate::models::Event> {
+        let array = match structured_fields.get("events")
+            .and_then(|a| a.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|a| a.as_array())
+        {
+            Some(arr) => arr,
+            None => return vec![],
+        };
+
+        array.iter().filter_map(|item| {
+            let map_fields = item.get("mapValue")?.get("fields")?;
+            let title = self.parse_string(map_fields, "title").unwrap_or_default();
+            let description = self.parse_string(map_fields, "description").unwrap_or_default();
+            let start = self.parse_timestamp_optional(map_fields, "start")?;
+            let duration = self.parse_int(map_fields, "duration").unwrap_or(30);
+            let created = self.parse_bool(map_fields, "created").unwrap_or(false);
+            Some(crate::models::Event { title, description, start, duration, created })
+        }).collect()
+    }
+
+    /// Build the Firestore map value for a structured event
+    fn event_to_value(event: &crate::models::Event) -> Value {
+        json!({
+            "mapValue": {
+                "fields": {
+                    "title": {"stringValue": event.title},
+                    "description": {"stringValue": event.description},
+                    "start": {"timestampValue": event.start.to_rfc3339()},
+                    "duration": {"integerValue": event.duration.to_string()},
+                    "created": {"booleanValue": event.created}
+                }
+            }
+        })
+    }
+
+    /// Parse geolocation from conversation fields
+    fn parse_geolocation(&self, fields: &Value) -> Option<crate::models::Geolocation> {
+        let geo = fields.get("geolocation")?.get("mapValue")?.get("fields")?;
+
+        Some(crate::models::Geolocation {
+            google_place_id: self.parse_string(geo, "google_place_id"),
+            latitude: self.parse_float(geo, "latitude").unwrap_or(0.0),
+            longitude: self.parse_float(geo, "longitude").unwrap_or(0.0),
+            address: self.parse_string(geo, "address"),
+            location_type: self.parse_string(geo, "location_type"),
+        })
+    }
+
+    /// Parse photos array from conversation fields
+    /// Decrypts base64 field if data_protection_level is "enhanced"
+    fn parse_photos(&self, fields: &Value, uid: &str) -> Vec<crate::models::ConversationPhoto> {
+        let array = match fields.get("photos")
+            .and_then(|a| a.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|a| a.as_array())
+        {
+            Some(arr) => arr,
+            None => return vec![],
+        };
+
+        array.iter().filter_map(|item| {
+            let map_fields = item.get("mapValue")?.get("fields")?;
+            let id = self.parse_string(map_fields, "id");
+            let mut base64 = self.parse_string(map_fields, "base64").unwrap_or_default();
+            let description = self.parse_string(map_fields, "description");
+            let created_at = self.parse_timestamp_optional(map_fields, "created_at").unwrap_or_else(Utc::now);
+            let discarded = self.parse_bool(map_fields, "discarded").unwrap_or(false);
+
+            // Check if photo is encrypted (data_protection_level = "enhanced")
+            let data_protection_level = self.parse_string(map_fields, "data_protection_level");
+            if data_protection_level.as_deref() == Some("enhanced") {
+                if let Some(ref secret) = self.encryption_secret {
+                    match encryption::decrypt(&base64, uid, secret) {
+                        Ok(decrypted) => base64 = decrypted,
+                        Err(e) => {
+                            tracing::warn!("Failed to decrypt photo {:?}: {} — skipping", id, e);
+                            return None;
+                        }
+                    }
+                } else {
+                    tracing::warn!("Photo {:?} has enhanced protection but no encryption secret — skipping", id);
+                    return None;
+                }
+            }
+
+            Some(crate::models::ConversationPhoto { id, base64, description, created_at, discarded })
+        }).collect()
+    }
+
+    /// Parse transcript segments
+    /// Handles plain arrays, zlib-compressed bytes (from OMI device), and encrypted segments.
+    /// For encrypted segments (data_protection_level = "enhanced"):
+    ///   - Decrypts the base64 string → hex string
+    ///   - Converts hex to bytes
+    ///   - Decompresses with zlib
+    ///   - Parses JSON array
+    fn parse_transcript_segments(
+        &self,
+        fields: &Value,
+        uid: &str,
+    ) -> Result<Vec<TranscriptSegment>, FirestoreError> {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let transcript_field = fields.get("transcript_segments");
+
+        // Check if transcript is a string (encrypted for enhanced protection)
+        if let Some(string_val) = transcript_field.and_then(|t| t.get("stringValue")).and_then(|s| s.as_str()) {
+            let data_protection_level = self.parse_string(fields, "data_protection_level");
+            if data_protection_level.as_deref() == Some("enhanced") {
+                if let Some(ref secret) = self.encryption_secret {
+                    // Decrypt the encrypted string
+                    let decrypted_payload = match encryption::decrypt(string_val, uid, secret) {
+                        Ok(decrypted) => decrypted,
+                        Err(e) => {
+                            tracing::warn!("Failed to decrypt transcript segments: {}", e);
+                            return Ok(vec![]);
+                        }
+                    };
+
+                    // Check if compression is used (should always be true for enhanced)
+                    let is_compressed = self.parse_bool(fields, "transcript_segments_compressed").unwrap_or(false);
+
+                    if is_compressed {
+                        // Decrypted payload is a hex string, convert to bytes
+                        match hex::decode(&decrypted_payload) {
+                            Ok(compressed_bytes) => {
+                                // Decompress with zlib
+                                let mut decoder = ZlibDecoder::new(&compressed_bytes[..]);
+                                let mut decompressed = String::new();
+                                if let Err(e) = decoder.read_to_string(&mut decompressed) {
+                                    tracing::warn!("Failed to decompress encrypted transcript segments: {}", e);
+                                    return Ok(vec![]);
+                                }
+
+                                // Parse JSON array of segments
+                                match serde_json::from_str::<Vec<serde_json::Value>>(&decompressed) {
+                                    Ok(segments) => {
+                                        let result: Vec<TranscriptSegment> = segments
+                                            .iter()
+                                            .filter_map(|seg| {
+                                                Some(TranscriptSegment {
+                                                    text: seg.get("text")?.as_str()?.to_string(),
+                                                    speaker: seg.get("speaker")
+                                                        .and_then(|s| s.as_str())
+                                                        .unwrap_or("SPEAKER_00")
+                                                        .to_string(),
+                                                    speaker_id: seg.get("speaker_id")
+                                                        .and_then(|s| s.as_i64())
+                                                        .unwrap_or(0) as i32,
+                                                    is_user: seg.get("is_user")
+                                                        .and_then(|s| s.as_bool())
+                                                        .unwrap_or(false),
+                                                    person_id: seg.get("person_id")
+                                                        .and_then(|s| s.as_str())
+                                                        .map(|s| s.to_string()),
+                                                    start: seg.get("start")
+                                                        .and_then(|s| s.as_f64())
+                                                        .unwrap_or(0.0),
+                                                    end: seg.get("end")
+                                                        .and_then(|s| s.as_f64())
+                                                        .unwrap_or(0.0),
+                                                    language: seg.get("language").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                                                    words: seg.get("words").and_then(WordTiming::from_compact),
+                                                })
+                                            })
+                                            .collect();
+                                        tracing::debug!("Decrypted and decompressed {} transcript segments for user {}", result.len(), uid);
+                                        return Ok(result);
+                                    }
+                                    Err(e) => {
+                                        tracing::warn!("Failed to parse decrypted transcript segments JSON: {}", e);
+                                        return Ok(vec![]);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                tracing::warn!("Failed to decode hex from decrypted transcript: {}", e);
+                                return Ok(vec![]);
+                            }
+                        }
+                    } else {
+                        // Old format: decrypted payload is JSON directly (backward compatibility)
+                        match serde_json::from_str::<Vec<serde_json::Value>>(&decrypted_payload) {
+                            Ok(segments) => {
+                                let result: Vec<TranscriptSegment> = segments
+                                    .iter()
+                                    .filter_map(|seg| {
+                                        Some(TranscriptSegment {
+                                            text: seg.get("text")?.as_str()?.to_string(),
+                                            speaker: seg.get("speaker")
+                                                .and_then(|s| s.as_str())
+                                                .unwrap_or("SPEAKER_00")
+                                                .to_string(),
+                                            speaker_id: seg.get("speaker_id")
+                                                .and_then(|s| s.as_i64())
+                                                .unwrap_or(0) as i32,
+                                            is_user: seg.get("is_user")
+                                                .and_then(|s| s.as_bool())
+                                                .unwrap_or(false),
+                                            person_id: seg.get("person_id")
+                                                .and_then(|s| s.as_str())
+                                                .map(|s| s.to_string()),
+                                            start: seg.get("start")
+                                                .and_then(|s| s.as_f64())
+                                                .unwrap_or(0.0),
+                                            end: seg.get("end")
+                                                .and_then(|s| s.as_f64())
+                                                .unwrap_or(0.0),
+                                            language: seg.get("language").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                                            words: seg.get("words").and_then(WordTiming::from_compact),
+                                        })
+                                    })
+                                    .collect();
+                                tracing::debug!("Decrypted {} transcript segments (uncompressed) for user {}", result.len(), uid);
+                                return Ok(result);
+                            }
+                            Err(e) => {
+                                tracing::warn!("Failed to parse decrypted transcript segments JSON: {}", e);
+                                return Ok(vec![]);
+                            }
+                        }
+                    }
+                } else {
+                    tracing::debug!(
+                        "Transcript segments have enhanced protection but no encryption secret configured"
+                    );
+                    return Ok(vec![]);
+                }
+            } else {
+                // String but not enhanced - shouldn't happen, but return empty
+                tracing::debug!("Transcript segments are string format but not enhanced protection");
+                return Ok(vec![]);
+            }
+        }
+
+        // Check if transcript is bytes (zlib compressed) - decompress it
+        if let Some(bytes_val) = transcript_field.and_then(|t| t.get("bytesValue")) {
+            if let Some(b64_str) = bytes_val.as_str() {
+                match self.decompress_transcript_segments(b64_str) {
+                    Ok(segments) => {
+                        tracing::debug!("Decompressed {} transcript segments", segments.len());
+                        return Ok(segments);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to decompress transcript segments: {}", e);
+                        return Ok(vec![]);
+                    }
+                }
+            }
+        }
+
+        // Handle plain array format
+        let segments = transcript_field
+            .and_then(|s| s.get("arrayValue"))
+            .and_then(|a| a.get("values"))
+            .and_then(|v| v.as_array());
+
+        if let Some(segs) = segments {
+            Ok(segs
+                .iter()
+                .filter_map(|seg| {
+                    let seg_fields = seg.get("mapValue")?.get("fields")?;
+                    Some(TranscriptSegment {
+                        text: self.parse_string(seg_fields, "text").unwrap_or_default(),
+                        speaker: self.parse_string(seg_fields, "speaker").unwrap_or_else(|| "SPEAKER_00".to_string()),
+                        speaker_id: self.parse_int(seg_fields, "speaker_id").unwrap_or(0),
+                        is_user: self.parse_bool(seg_fields, "is_user").unwrap_or(false),
+                        person_id: self.parse_string(seg_fields, "person_id"),
+                        start: self.parse_float(seg_fields, "start").unwrap_or(0.0),
+                        end: self.parse_float(seg_fields, "end").unwrap_or(0.0),
+                        language: self.parse_string(seg_fields, "language"),
+                        words: None,
+                    })
+                })
+                .collect())
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Decompress zlib-compressed transcript segments from base64-encoded bytes
+    fn decompress_transcript_segments(
+        &self,
+        b64_str: &str,
+    ) -> Result<Vec<TranscriptSegment>, FirestoreError> {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        // Decode base64 to bytes
+        let compressed_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            b64_str,
+        )?;
+
+        // Decompress with zlib
+        let mut decoder = ZlibDecoder::new(&compressed_bytes[..]);
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed)?;
+
+        // Parse JSON array of segments
+        let segments: Vec<serde_json::Value> = serde_json::from_str(&decompressed)?;
+
+        // Convert to TranscriptSegment
+        Ok(segments
+            .iter()
+            .filter_map(|seg| {
+                Some(TranscriptSegment {
+                    text: seg.get("text")?.as_str()?.to_string(),
+                    speaker: seg
+                        .get("speaker")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("SPEAKER_00")
+                        .to_string(),
+                    speaker_id: seg
+                        .get("speaker_id")
+                        .and_then(|s| s.as_i64())
+                        .unwrap_or(0) as i32,
+                    is_user: seg
+                        .get("is_user")
+                        .and_then(|s| s.as_bool())
+                        .unwrap_or(false),
+                    person_id: seg
+                        .get("person_id")
+                        .and_then(|s| s.as_str())
+                        .map(|s| s.to_string()),
+                    start: seg
+                        .get("start")
+                        .and_then(|s| s.as_f64())
+                        .unwrap_or(0.0),
+                    end: seg
+                        .get("end")
+                        .and_then(|s| s.as_f64())
+                        .unwrap_or(0.0),
+                    language: seg.get("language").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                    words: seg.get("words").and_then(WordTiming::from_compact),
+                })
+            })
+            .collect())
+    }
+
+    /// Encode transcript segments as stored on a conversation: zlib-compressed JSON, encrypted
+    /// when a secret is configured. Returns the document fields and, when the payload is too
+    /// large to keep inline, the chunks to write to transcript_chunks (the fields then hold
+    /// transcript_chunk_count instead of transcript_segments).
+    fn transcript_fields(
+        &self,
+        segments: &[TranscriptSegment],
+        uid: &str,
+    ) -> (serde_json::Map<String, Value>, Vec<TranscriptPayload>) {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // Step 1: Serialize segments to JSON array (matching Python's json.dumps format)
+        let segments_json: Vec<serde_json::Value> = segments.iter().map(|seg| {
+            let mut value = json!({
+                "text": seg.text,
+                "speaker": seg.speaker,
+                "speaker_id": seg.speaker_id,
+                "is_user": seg.is_user,
+                "start": seg.start,
+                "end": seg.end
+            });
+            if let Some(ref person_id) = seg.person_id {
+                value["person_id"] = json!(person_id);
+            }
+            if let Some(ref language) = seg.language {
+                value["language"] = json!(language);
+            }
+            if let Some(ref words) = seg.words {
+                value["words"] = WordTiming::to_compact(words);
+            }
+            value
+        }).collect();
+        let json_str = serde_json::to_string(&segments_json).unwrap_or_else(|_| "[]".to_string());
+
+        // Step 2: Zlib compress
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let _ = encoder.write_all(json_str.as_bytes());
+        let compressed_bytes = encoder.finish().unwrap_or_default();
+
+        // Step 3: Store as compressed bytes or encrypt if secret is available
+        let mut fields = serde_json::Map::new();
+        let payload = match self.encryption_secret {
+            // Enhanced: hex encode compressed bytes → encrypt → store as stringValue
+            Some(ref secret) => match encryption::encrypt(&hex::encode(&compressed_bytes), uid, secret) {
+                Ok(encrypted) => {
+                    fields.insert("data_protection_level".to_string(), json!({"stringValue": "enhanced"}));
+                    TranscriptPayload::Encrypted(encrypted)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to encrypt transcript segments: {}, falling back to compressed bytes", e);
+                    TranscriptPayload::Compressed(compressed_bytes)
+                }
+            },
+            // Standard: store as bytesValue (Firestore REST API expects base64 for bytes)
+            None => TranscriptPayload::Compressed(compressed_bytes),
+        };
+        fields.insert("transcript_segments_compressed".to_string(), json!({"booleanValue": true}));
+
+        if !payload.needs_chunking() {
+            fields.insert("transcript_segments".to_string(), payload.to_field());
+            return (fields, Vec::new());
+        }
+        let chunks = payload.split(TRANSCRIPT_CHUNK_BYTES);
+        fields.insert(
+            "transcript_chunk_count".to_string(),
+            json!({"integerValue": chunks.len().to_string()}),
+        );
+        (fields, chunks)
+    }
+
+    /// Write transcript chunks for a conversation (before the document that counts them)
+    async fn write_transcript_chunks(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        chunks: &[TranscriptPayload],
+    ) -> Result<(), FirestoreError> {
+        let conversation = self.conversation_doc_name(uid, conversation_id);
+        for (index, part) in chunks.iter().enumerate() {
+            let doc_name = format!("{}/{}/{}", conversation, TRANSCRIPT_CHUNKS_SUBCOLLECTION, chunk_doc_id(index));
+            self.set_document_fields(&doc_name, chunk_fields(index, part)).await?;
+        }
+        tracing::info!(
+            "Stored transcript of conversation {} in {} chunks for user {}",
+            conversation_id,
+            chunks.len(),
+            uid
+        );
+        Ok(())
+    }
+
+    /// If a conversation document's transcript lives in chunks, read them and put the
+    /// reassembled transcript_segments back on the document so it parses like any other.
+    /// Failures are logged and leave the transcript empty.
+    async fn load_transcript_chunks(&self, doc: &mut Value) {
+        let Some(fields) = doc.get("fields") else {
+            return;
+        };
+        let count = self.parse_int(fields, "transcript_chunk_count").unwrap_or(0).max(0) as usize;
+        if count == 0 || fields.get("transcript_segments").is_some() {
+            return;
+        }
+        let Some(name) = doc.get("name").and_then(|n| n.as_str()) else {
+            return;
+        };
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": TRANSCRIPT_CHUNKS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "index"}, "direction": "ASCENDING"}],
+                "limit": count
+            }
+        });
+        let parent = format!("https://firestore.googleapis.com/v1/{}", name);
+        let parts = match self.run_user_query(&parent, &query).await {
+            Ok(docs) => docs
+                .iter()
+                .filter_map(|d| TranscriptPayload::from_field(d.get("fields")?.get("data")?))
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                tracing::warn!("Failed to read transcript chunks of {}: {}", name, e);
+                return;
+            }
+        };
+        if parts.len() != count {
+            tracing::warn!("Transcript of {} has {} of {} chunks", name, parts.len(), count);
+            return;
+        }
+        if let (Some(payload), Some(fields)) = (TranscriptPayload::join(parts), doc["fields"].as_object_mut()) {
+            fields.insert("transcript_segments".to_string(), payload.to_field());
+        }
+    }
+
+    /// Delete every transcript chunk of a conversation
+    async fn delete_transcript_chunks(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let parent = format!(
+            "https://firestore.googleapis.com/v1/{}",
+            self.conversation_doc_name(uid, conversation_id)
+        );
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": TRANSCRIPT_CHUNKS_SUBCOLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]}
+            }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        let writes: Vec<Value> = docs
+            .iter()
+            .filter_map(|doc| doc.get("name")?.as_str())
+            .map(|name| json!({"delete": name}))
+            .collect();
+        self.commit_batched_writes(writes).await
+    }
+
+    /// Convert conversation to Firestore document format, plus any transcript chunks to write
+    /// Compresses transcript_segments with zlib to match Python backend format.
+    /// If encryption_secret is available, also encrypts (enhanced protection).
+    fn conversation_to_firestore(&self, conv: &Conversation, uid: &str) -> (Value, Vec<TranscriptPayload>) {
+        // Build action_items array for structured
+        let action_items_values: Vec<Value> = conv.structured.action_items.iter()
+            .map(Self::structured_action_item_to_value)
+            .collect();
+
+        // Build events array for structured
+        let events_values: Vec<Value> = conv.structured.events.iter().map(Self::event_to_value).collect();
+
+        // Build apps_results array
+        let apps_results_values: Vec<Value> = conv.apps_results.iter().map(|result| {
+            let mut fields = serde_json::Map::new();
+            if let Some(app_id) = &result.app_id {
+                fields.insert("app_id".to_string(), json!({"stringValue": app_id}));
+            }
+            fields.insert("content".to_string(), json!({"stringValue": result.content}));
+            json!({"mapValue": {"fields": fields}})
+        }).collect();
+
+        // Build the main document
+        let mut fields = serde_json::Map::new();
+
+        // CRITICAL: Include the id field - Python backend requires this
+        fields.insert("id".to_string(), json!({"stringValue": conv.id}));
+        fields.insert("created_at".to_string(), json!({"timestampValue": conv.created_at.to_rfc3339()}));
+        fields.insert("updated_at".to_string(), json!({"timestampValue": Utc::now().to_rfc3339()}));
+        fields.insert("started_at".to_string(), json!({"timestampValue": conv.started_at.to_rfc3339()}));
+        fields.insert("finished_at".to_string(), json!({"timestampValue": conv.finished_at.to_rfc3339()}));
+        fields.insert("source".to_string(), json!({"stringValue": format!("{:?}", conv.source).to_lowercase()}));
+        fields.insert("language".to_string(), json!({"stringValue": conv.language}));
+        if !conv.detected_languages.is_empty() {
+            let values: Vec<Value> = conv.detected_languages.iter().map(|l| json!({"stringValue": l})).collect();
+            fields.insert("detected_languages".to_string(), json!({"arrayValue": {"values": values}}));
+        }
+        fields.insert("status".to_string(), json!({"stringValue": format!("{:?}", conv.status).to_lowercase()}));
+        fields.insert("discarded".to_string(), json!({"booleanValue": conv.discarded}));
+        if let Some(reason) = conv.discard_reason {
+            fields.insert("discard_reason".to_string(), json!({"stringValue": reason.as_str()}));
+        }
+        fields.insert("deleted".to_string(), json!({"booleanValue": conv.deleted}));
+        fields.insert("starred".to_string(), json!({"booleanValue": conv.starred}));
+        fields.insert("is_locked".to_string(), json!({"booleanValue": conv.is_locked}));
+
+        // Add folder_id if present
+        if let Some(folder_id) = &conv.folder_id {
+            fields.insert("folder_id".to_string(), json!({"stringValue": folder_id}));
+        }
+        if let Some(template_id) = &conv.template_id {
+            fields.insert("template_id".to_string(), json!({"stringValue": template_id}));
+        }
+        if let Some(deleted_at) = conv.transcript_deleted_at {
+            fields.insert("transcript_deleted_at".to_string(), json!({"timestampValue": deleted_at.to_rfc3339()}));
+        }
+
+        // Add geolocation if present
+        if let Some(geo) = &conv.geolocation {
+            let mut geo_fields = serde_json::Map::new();
+            if let Some(place_id) = &geo.google_place_id {
+                geo_fields.insert("google_place_id".to_string(), json!({"stringValue": place_id}));
+            }
+            geo_fields.insert("latitude".to_string(), json!({"doubleValue": geo.latitude}));
+            geo_fields.insert("longitude".to_string(), json!({"doubleValue": geo.longitude}));
+            if let Some(address) = &geo.address {
+                geo_fields.insert("address".to_string(), json!({"stringValue": address}));
+            }
+            if let Some(loc_type) = &geo.location_type {
+                geo_fields.insert("location_type".to_string(), json!({"stringValue": loc_type}));
+            }
+            fields.insert("geolocation".to_string(), json!({"mapValue": {"fields": geo_fields}}));
+        }
+
+        // Add photos array
+        if !conv.photos.is_empty() {
+            let photos_values: Vec<Value> = conv.photos.iter().map(|photo| {
+                let mut photo_fields = serde_json::Map::new();
+                if let Some(id) = &photo.id {
+                    photo_fields.insert("id".to_string(), json!({"stringValue": id}));
+                }
+                photo_fields.insert("base64".to_string(), json!({"stringValue": &photo.base64}));
+                if let Some(desc) = &photo.description {
+                    photo_fields.insert("description".to_string(), json!({"stringValue": desc}));
+                }
+                photo_fields.insert("created_at".to_string(), json!({"timestampValue": photo.created_at.to_rfc3339()}));
+                photo_fields.insert("discarded".to_string(), json!({"booleanValue": photo.discarded}));
+                json!({"mapValue": {"fields": photo_fields}})
+            }).collect();
+            fields.insert("photos".to_string(), json!({"arrayValue": {"values": photos_values}}));
+        }
+
+        // Build structured with action_items and events
+        let mut structured_fields = serde_json::Map::new();
+        structured_fields.insert("title".to_string(), json!({"stringValue": conv.structured.title}));
+        structured_fields.insert("overview".to_string(), json!({"stringValue": conv.structured.overview}));
+        structured_fields.insert("emoji".to_string(), json!({"stringValue": conv.structured.emoji}));
+        structured_fields.insert("category".to_string(), json!({"stringValue": format!("{:?}", conv.structured.category).to_lowercase()}));
+        structured_fields.insert("action_items".to_string(), json!({"arrayValue": {"values": action_items_values}}));
+        structured_fields.insert("events".to_string(), json!({"arrayValue": {"values": events_values}}));
+
+        fields.insert("structured".to_string(), json!({"mapValue": {"fields": structured_fields}}));
+        fields.insert("context_line".to_string(), json!({"stringValue": conv.structured.context_line()}));
+        if !conv.generated_by.is_empty() {
+            fields.insert("generated_by".to_string(), self.build_string_map_value(&conv.generated_by));
+        }
+
+        // Add transcript_segments — compressed (and optionally encrypted) to match Python backend;
+        // oversized transcripts are returned as chunks instead
+        let (transcript_fields, chunks) = self.transcript_fields(&conv.transcript_segments, uid);
+        fields.extend(transcript_fields);
+
+        // Add apps_results
+        fields.insert("apps_results".to_string(), json!({"arrayValue": {"values": apps_results_values}}));
+
+        // Add input_device_name if present
+        if let Some(device_name) = &conv.input_device_name {
+            fields.insert("input_device_name".to_string(), json!({"stringValue": device_name}));
+        }
+
+        if conv.memory_extraction_disabled {
+            fields.insert("memory_extraction_disabled".to_string(), json!({"booleanValue": true}));
+        }
+
+        if let Some(sync) = &conv.notion_sync {
+            fields.insert("notion_sync".to_string(), Self::notion_sync_to_value(sync));
+        }
+
+        if let Some(analytics) = &conv.analytics {
+            fields.insert("analytics".to_string(), Self::conversation_analytics_to_value(analytics));
+        }
+
+        if !conv.focus_sessions.is_empty() {
+            let values: Vec<Value> = conv
+                .focus_sessions
+                .iter()
+                .map(|s| {
+                    json!({"mapValue": {"fields": {
+                        "session_id": {"stringValue": s.session_id},
+                        "status": {"stringValue": s.status.to_string()},
+                        "app_or_site": {"stringValue": s.app_or_site},
+                        "started_at": {"timestampValue": s.started_at.to_rfc3339()},
+                        "overlap_seconds": {"integerValue": s.overlap_seconds.to_string()}
+                    }}})
+                })
+                .collect();
+            fields.insert("focus_sessions".to_string(), json!({"arrayValue": {"values": values}}));
+        }
+
+        if let Some(prompt_version) = &conv.prompt_version {
+            fields.insert("prompt_version".to_string(), json!({"stringValue": prompt_version}));
+        }
+        if let Some(feedback) = &conv.feedback {
+            fields.insert("feedback".to_string(), Self::conversation_feedback_to_value(feedback));
+        }
+
+        (json!({"fields": fields}), chunks)
+    }
+
+    // Field parsing helpers
+    fn parse_string(&self, fields: &Value, key: &str) -> Option<String> {
+        fields.get(key)?.get("stringValue")?.as_str().map(|s| s.to_string())
+    }
+
+    fn parse_bool(&self, fields: &Value, key: &str) -> Result<bool, FirestoreError> {
+        fields
+            .get(key)
+            .and_then(|v| v.get("booleanValue"))
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| format!("Missing or invalid bool field: {}", key).into())
+    }
+
+    fn parse_int(&self, fields: &Value, key: &str) -> Option<i32> {
+        fields
+            .get(key)?
+            .get("integerValue")?
+            .as_str()
+            .and_then(|s| s.parse().ok())
+    }
+
+    fn parse_float(&self, fields: &Value, key: &str) -> Option<f64> {
+        fields.get(key)?.get("doubleValue")?.as_f64()
+    }
+
+    fn parse_timestamp(
+        &self,
+        fields: &Value,
+        key: &str,
+    ) -> Result<DateTime<Utc>, FirestoreError> {
+        let ts = fields
+            .get(key)
+            .and_then(|v| v.get("timestampValue"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("Missing timestamp field: {}", key))?;
+
+        DateTime::parse_from_rfc3339(ts)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| format!("Invalid timestamp {}: {}", key, e).into())
+    }
+
+    fn parse_timestamp_optional(&self, fields: &Value, key: &str) -> Option<DateTime<Utc>> {
+        fields
+            .get(key)
+            .and_then(|v| v.get("timestampValue"))
+            .and_then(|v| v.as_str())
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    // =========================================================================
+    // USER SETTINGS
+    // =========================================================================
+
+    /// Get user document fields
+    async fn get_user_document(
+        &self,
+        uid: &str,
+    ) -> Result<Value, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to get user document", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        Ok(doc)
+    }
+
+    /// Update user document fields (partial update)
+    async fn update_user_fields(
+        &self,
+        uid: &str,
+        fields: Value,
+        update_mask: &[&str],
+    ) -> Result<(), FirestoreError> {
+        let mask_params = update_mask
+            .iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let url = format!(
+            "{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            mask_params
+        );
+
+        let doc = json!({ "fields": fields });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to update user fields", &error_text));
+        }
+
+        Ok(())
+    }
+
+    /// Get daily summary settings for a user
+    pub async fn get_daily_summary_settings(
+        &self,
+        uid: &str,
+    ) -> Result<DailySummarySettings, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(DailySummarySettings {
+            enabled: self.parse_bool(fields, "daily_summary_enabled").unwrap_or(true),
+            hour: self.parse_int(fields, "daily_summary_hour_local").unwrap_or(22),
+        })
+    }
+
+    /// Update daily summary settings for a user
+    pub async fn update_daily_summary_settings(
+        &self,
+        uid: &str,
+        enabled: Option<bool>,
+        hour: Option<i32>,
+    ) -> Result<DailySummarySettings, FirestoreError> {
+        // Get current settings
+        let current = self.get_daily_summary_settings(uid).await?;
+
+        let new_enabled = enabled.unwrap_or(current.enabled);
+        let new_hour = hour.unwrap_or(current.hour);
+
+        let fields = json!({
+            "daily_summary_enabled": {"booleanValue": new_enabled},
+            "daily_summary_hour_local": {"integerValue": new_hour.to_string()}
+        });
+
+        self.update_user_fields(uid, fields, &["daily_summary_enabled", "daily_summary_hour_local"])
+            .await?;
+
+        Ok(DailySummarySettings {
+            enabled: new_enabled,
+            hour: new_hour,
+        })
+    }
+
+    /// Get action item rollover settings for a user
+    pub async fn get_action_item_rollover_settings(
+        &self,
+        uid: &str,
+    ) -> Result<ActionItemRolloverSettings, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+        let defaults = ActionItemRolloverSettings::default();
+
+        Ok(ActionItemRolloverSettings {
+            enabled: self.parse_bool(fields, "action_item_rollover_enabled").unwrap_or(defaults.enabled),
+            hour: self.parse_int(fields, "action_item_rollover_hour_local").unwrap_or(defaults.hour),
+            last_rolled_over_on: self.parse_string(fields, "action_item_rollover_last_date"),
+        })
+    }
+
+    /// Update action item rollover settings for a user
+    pub async fn update_action_item_rollover_settings(
+        &self,
+        uid: &str,
+        enabled: Option<bool>,
+        hour: Option<i32>,
+    ) -> Result<ActionItemRolloverSettings, FirestoreError> {
+        let current = self.get_action_item_rollover_settings(uid).await?;
+
+        let new_enabled = enabled.unwrap_or(current.enabled);
+        let new_hour = hour.unwrap_or(current.hour);
+
+        let fields = json!({
+            "action_item_rollover_enabled": {"booleanValue": new_enabled},
+            "action_item_rollover_hour_local": {"integerValue": new_hour.to_string()}
+        });
+
+        self.update_user_fields(
+            uid,
+            fields,
+            &["action_item_rollover_enabled", "action_item_rollover_hour_local"],
+        )
+        .await?;
+
+        Ok(ActionItemRolloverSettings {
+            enabled: new_enabled,
+            hour: new_hour,
+            last_rolled_over_on: current.last_rolled_over_on,
+        })
+    }
+
+    /// Record the local date of the last rollover run (prevents running twice a day)
+    pub async fn set_action_item_rollover_last_date(
+        &self,
+        uid: &str,
+        local_date: &str,
+    ) -> Result<(), FirestoreError> {
+        let fields = json!({
+            "action_item_rollover_last_date": {"stringValue": local_date}
+        });
+        self.update_user_fields(uid, fields, &["action_item_rollover_last_date"])
+            .await
+    }
+
+    /// Get IDs of users who opted in to the daily action item rollover
+    pub async fn get_users_with_rollover_enabled(
+        &self,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": USERS_COLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "action_item_rollover_enabled"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                }
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let uids = results
+            .iter()
+            .filter_map(|r| firestore_serde::document_id(r.get("document")?))
+            .map(|s| s.to_string())
+            .collect();
+
+        Ok(uids)
+    }
+
+    /// Get weekly review settings for a user
+    pub async fn get_weekly_review_settings(
+        &self,
+        uid: &str,
+    ) -> Result<WeeklyReviewSettings, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+        let defaults = WeeklyReviewSettings::default();
+
+        Ok(WeeklyReviewSettings {
+            enabled: self.parse_bool(fields, "weekly_review_enabled").unwrap_or(defaults.enabled),
+            hour: self.parse_int(fields, "weekly_review_hour_local").unwrap_or(defaults.hour),
+            last_reviewed_week: self.parse_string(fields, "weekly_review_last_week"),
+        })
+    }
+
+    /// Update weekly review settings for a user
+    pub async fn update_weekly_review_settings(
+        &self,
+        uid: &str,
+        enabled: Option<bool>,
+        hour: Option<i32>,
+    ) -> Result<WeeklyReviewSettings, FirestoreError> {
+        let current = self.get_weekly_review_settings(uid).await?;
+
+        let new_enabled = enabled.unwrap_or(current.enabled);
+        let new_hour = hour.unwrap_or(current.hour);
+
+        let fields = json!({
+            "weekly_review_enabled": {"booleanValue": new_enabled},
+            "weekly_review_hour_local": {"integerValue": new_hour.to_string()}
+        });
+
+        self.update_user_fields(uid, fields, &["weekly_review_enabled", "weekly_review_hour_local"])
+            .await?;
+
+        Ok(WeeklyReviewSettings {
+            enabled: new_enabled,
+            hour: new_hour,
+            last_reviewed_week: current.last_reviewed_week,
+        })
+    }
+
+    /// Record the Monday of the last week reviewed by the schedule (prevents duplicates)
+    pub async fn set_weekly_review_last_week(
+        &self,
+        uid: &str,
+        week_start: &str,
+    ) -> Result<(), FirestoreError> {
+        let fields = json!({
+            "weekly_review_last_week": {"stringValue": week_start}
+        });
+        self.update_user_fields(uid, fields, &["weekly_review_last_week"])
+            .await
+    }
+
+    /// Get IDs of users who opted in to automatic weekly reviews
+    pub async fn get_users_with_weekly_review_enabled(
+        &self,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": USERS_COLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "weekly_review_enabled"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                }
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let uids = results
+            .iter()
+            .filter_map(|r| firestore_serde::document_id(r.get("document")?))
+            .map(|s| s.to_string())
+            .collect();
+
+        Ok(uids)
+    }
+
+    /// Get transcription preferences for a user
+    pub async fn get_transcription_preferences(
+        &self,
+        uid: &str,
+    ) -> Result<TranscriptionPreferences, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        // Parse nested transcription_preferences object
+        let prefs = fields
+            .get("transcription_preferences")
+            .and_then(|p| p.get("mapValue"))
+            .and_then(|m| m.get("fields"));
+
+        if let Some(pref_fields) = prefs {
+            Ok(TranscriptionPreferences {
+                single_language_mode: self.parse_bool(pref_fields, "single_language_mode").unwrap_or(false),
+                vocabulary: self.parse_string_array(pref_fields, "vocabulary"),
+            })
+        } else {
+            Ok(TranscriptionPreferences::default())
+        }
+    }
+
+    /// Update transcription preferences for a user
+    pub async fn update_transcription_preferences(
+        &self,
+        uid: &str,
+        single_language_mode: Option<bool>,
+        vocabulary: Option<Vec<String>>,
+    ) -> Result<TranscriptionPreferences, FirestoreError> {
+        // Get current settings
+        let current = self.get_transcription_preferences(uid).await?;
+
+        let new_single_language_mode = single_language_mode.unwrap_or(current.single_language_mode);
+        let new_vocabulary = vocabulary.unwrap_or(current.vocabulary);
+
+        let vocab_values: Vec<Value> = new_vocabulary
+            .iter()
+            .map(|v| json!({"stringValue": v}))
+            .collect();
+
+        let fields = json!({
+            "transcription_preferences": {
+                "mapValue": {
+                    "fields": {
+                        "single_language_mode": {"booleanValue": new_single_language_mode},
+                        "vocabulary": {
+                            "arrayValue": {
+                                "values": vocab_values
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        self.update_user_fields(uid, fields, &["transcription_preferences"])
+            .await?;
+
+        Ok(TranscriptionPreferences {
+            single_language_mode: new_single_language_mode,
+            vocabulary: new_vocabulary,
+        })
+    }
+
+    /// Get the user's custom conversation processing prompt (None if unset)
+    pub async fn get_processing_prompt(
+        &self,
+        uid: &str,
+    ) -> Result<Option<String>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(self
+            .parse_string(fields, "custom_processing_prompt")
+            .filter(|p| !p.trim().is_empty()))
+    }
+
+    /// Get the user's processing preferences (custom prompt and title style).
+    /// `max_length` is left at 0 for the caller to fill.
+    pub async fn get_processing_settings(
+        &self,
+        uid: &str,
+    ) -> Result<ProcessingPromptSettings, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(ProcessingPromptSettings {
+            prompt: self
+                .parse_string(fields, "custom_processing_prompt")
+                .filter(|p| !p.trim().is_empty())
+                .unwrap_or_default(),
+            max_length: 0,
+            plain_titles: self.parse_bool(fields, "plain_titles").unwrap_or(false),
+            auto_discard: self
+                .parse_string(fields, "auto_discard")
+                .and_then(|v| AutoDiscardLevel::parse(&v))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Update the user's processing preferences; None leaves a field unchanged
+    /// (an empty prompt clears it)
+    pub async fn update_processing_settings(
+        &self,
+        uid: &str,
+        prompt: Option<&str>,
+        plain_titles: Option<bool>,
+        auto_discard: Option<AutoDiscardLevel>,
+    ) -> Result<(), FirestoreError> {
+        let mut fields = json!({});
+        let mut mask = Vec::new();
+        if let Some(prompt) = prompt {
+            fields["custom_processing_prompt"] = json!({"stringValue": prompt});
+            mask.push("custom_processing_prompt");
+        }
+        if let Some(plain_titles) = plain_titles {
+            fields["plain_titles"] = json!({"booleanValue": plain_titles});
+            mask.push("plain_titles");
+        }
+        if let Some(level) = auto_discard {
+            fields["auto_discard"] = json!({"stringValue": level.as_str()});
+            mask.push("auto_discard");
+        }
+        if mask.is_empty() {
+            return Ok(());
+        }
+
+        self.update_user_fields(uid, fields, &mask).await
+    }
+
+    /// Titles of the user's most recent conversations (for title disambiguation)
+    pub async fn get_recent_conversation_titles(
+        &self,
+        uid: &str,
+        limit: usize,
+    ) -> Result<Vec<String>, FirestoreError> {
+        Ok(self
+            .get_conversations(uid, limit, 0, false, &[], None, None, None, None)
+            .await?
+            .into_iter()
+            .map(|c| c.structured.title)
+            .filter(|t| !t.trim().is_empty())
+            .collect())
+    }
+
+    // MARK: - Assistant Settings
+
+    /// Helper: parse a sub-map from Firestore fields
+    fn parse_sub_map<'a>(&self, fields: &'a Value, key: &str) -> Option<&'a Value> {
+        fields.get(key)?.get("mapValue")?.get("fields")
+    }
+
+    /// Helper: build a Firestore string array value
+    fn build_string_array_value(&self, items: &[String]) -> Value {
+        let values: Vec<Value> = items.iter().map(|v| json!({"stringValue": v})).collect();
+        json!({"arrayValue": {"values": values}})
+    }
+
+    /// Helper: build a sub-map Firestore value from a serde_json::Map of fields
+    fn build_sub_map_value(&self, map_fields: serde_json::Map<String, Value>) -> Value {
+        json!({"mapValue": {"fields": map_fields}})
+    }
+
+    /// Topics the user never wants memorized (assistant_settings.memory.blocked_topics)
+    pub async fn get_blocked_memory_topics(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let settings = self.get_assistant_settings(uid).await?;
+        Ok(settings.memory.and_then(|m| m.blocked_topics).unwrap_or_default())
+    }
+
+    /// Get assistant settings from user document
+    pub async fn get_assistant_settings(
+        &self,
+        uid: &str,
+    ) -> Result<AssistantSettingsData, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        let settings_fields = self.parse_sub_map(fields, "assistant_settings");
+
+        let Some(sf) = settings_fields else {
+            return Ok(AssistantSettingsData::default());
+        };
+
+        // Parse shared settings
+        let shared = self.parse_sub_map(sf, "shared").map(|f| SharedAssistantSettingsData {
+            cooldown_interval: self.parse_int(f, "cooldown_interval"),
+            glow_overlay_enabled: self.parse_bool(f, "glow_overlay_enabled").ok(),
+            analysis_delay: self.parse_int(f, "analysis_delay"),
+            screen_analysis_enabled: self.parse_bool(f, "screen_analysis_enabled").ok(),
+        });
+
+        // Parse focus settings
+        let focus = self.parse_sub_map(sf, "focus").map(|f| FocusSettingsData {
+            enabled: self.parse_bool(f, "enabled").ok(),
+            analysis_prompt: self.parse_string(f, "analysis_prompt"),
+            cooldown_interval: self.parse_int(f, "cooldown_interval"),
+            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
+            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
+        });
+
+        // Parse task settings
+        let task = self.parse_sub_map(sf, "task").map(|f| TaskSettingsData {
+            enabled: self.parse_bool(f, "enabled").ok(),
+            analysis_prompt: self.parse_string(f, "analysis_prompt"),
+            extraction_interval: self.parse_float(f, "extraction_interval"),
+            min_confidence: self.parse_float(f, "min_confidence"),
+            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
+            allowed_apps: Some(self.parse_string_array(f, "allowed_apps")),
+            browser_keywords: Some(self.parse_string_array(f, "browser_keywords")),
+        });
+
+        // Parse advice settings
+        let advice = self.parse_sub_map(sf, "advice").map(|f| AdviceSettingsData {
+            enabled: self.parse_bool(f, "enabled").ok(),
+            analysis_prompt: self.parse_string(f, "analysis_prompt"),
+            extraction_interval: self.parse_float(f, "extraction_interval"),
+            min_confidence: self.parse_float(f, "min_confidence"),
+            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
+            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
+        });
+
+        // Parse memory settings
+        let memory = self.parse_sub_map(sf, "memory").map(|f| MemorySettingsData {
+            enabled: self.parse_bool(f, "enabled").ok(),
+            analysis_prompt: self.parse_string(f, "analysis_prompt"),
+            extraction_interval: self.parse_float(f, "extraction_interval"),
+            min_confidence: self.parse_float(f, "min_confidence"),
+            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
+            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
+            blocked_topics: Some(self.parse_string_array(f, "blocked_topics")),
+            auto_approve_after_days: self.parse_int(f, "auto_approve_after_days"),
+        });
+
+        // Read top-level update_channel from user doc (not from assistant_settings sub-map)
+        let update_channel = self.parse_string(fields, "update_channel");
+
+        Ok(AssistantSettingsData {
+            shared,
+            focus,
+            task,
+            advice,
+            memory,
+            update_channel,
+        })
+    }
+
+    /// Update assistant settings (merge with existing)
+    pub async fn update_assistant_settings(
+        &self,
+        uid: &str,
+        data: &AssistantSettingsData,
+    ) -> Result<AssistantSettingsData, FirestoreError> {
+        let current = self.get_assistant_settings(uid).await?;
+
+        let mut top_fields = serde_json::Map::new();
+
+        // Build shared sub-map
+        if data.shared.is_some() || current.shared.is_some() {
+            let cur = current.shared.unwrap_or_default();
+            let new = data.shared.clone().unwrap_or_default();
+            let mut m = serde_json::Map::new();
+            let ci = new.cooldown_interval.or(cur.cooldown_interval);
+            if let Some(v) = ci { m.insert("cooldown_interval".into(), json!({"integerValue": v.to_string()})); }
+            let go = new.glow_overlay_enabled.or(cur.glow_overlay_enabled);
+            if let Some(v) = go { m.insert("glow_overlay_enabled".into(), json!({"booleanValue": v})); }
+            let ad = new.analysis_delay.or(cur.analysis_delay);
+            if let Some(v) = ad { m.insert("analysis_delay".into(), json!({"integerValue": v.to_string()})); }
+            let sa = new.screen_analysis_enabled.or(cur.screen_analysis_enabled);
+            if let Some(v) = sa { m.insert("screen_analysis_enabled".into(), json!({"booleanValue": v})); }
+            if !m.is_empty() {
+                top_fields.insert("shared".into(), self.build_sub_map_value(m));
+            }
+        }
+
+        // Build focus sub-map
+        if data.focus.is_some() || current.focus.is_some() {
+            let cur = current.focus.unwrap_or_default();
+            let new = data.focus.clone().unwrap_or_default();
+            let mut m = serde_json::Map::new();
+            let en = new.enabled.or(cur.enabled);
+            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
+            let ap = new.analysis_prompt.or(cur.analysis_prompt);
+            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
+            let ci = new.cooldown_interval.or(cur.cooldown_interval);
+            if let Some(v) = ci { m.insert("cooldown_interval".into(), json!({"integerValue": v.to_string()})); }
+            let ne = new.notifications_enabled.or(cur.notifications_enabled);
+            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
+            let ea = new.excluded_apps.or(cur.excluded_apps);
+            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
+            if !m.is_empty() {
+                top_fields.insert("focus".into(), self.build_sub_map_value(m));
+            }
+        }
+
+        // Build task sub-map
+        if data.task.is_some() || current.task.is_some() {
+            let cur = current.task.unwrap_or_default();
+            let new = data.task.clone().unwrap_or_default();
+            let mut m = serde_json::Map::new();
+            let en = new.enabled.or(cur.enabled);
+            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
+            let ap = new.analysis_prompt.or(cur.analysis_prompt);
+            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
+            let ei = new.extraction_interval.or(cur.extraction_interval);
+            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
+            let mc = new.min_confidence.or(cur.min_confidence);
+            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
+            let aa = new.allowed_apps.or(cur.allowed_apps);
+            if let Some(v) = aa { m.insert("allowed_apps".into(), self.build_string_array_value(&v)); }
+            let ne = new.notifications_enabled.or(cur.notifications_enabled);
+            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
+            let bk = new.browser_keywords.or(cur.browser_keywords);
+            if let Some(v) = bk { m.insert("browser_keywords".into(), self.build_string_array_value(&v)); }
+            if !m.is_empty() {
+                top_fields.insert("task".into(), self.build_sub_map_value(m));
+            }
+        }
+
+        // Build advice sub-map
+        if data.advice.is_some() || current.advice.is_some() {
+            let cur = current.advice.unwrap_or_default();
+            let new = data.advice.clone().unwrap_or_default();
+            let mut m = serde_json::Map::new();
+            let en = new.enabled.or(cur.enabled);
+            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
+            let ap = new.analysis_prompt.or(cur.analysis_prompt);
+            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
+            let ei = new.extraction_interval.or(cur.extraction_interval);
+            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
+            let mc = new.min_confidence.or(cur.min_confidence);
+            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
+            let ne = new.notifications_enabled.or(cur.notifications_enabled);
+            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
+            let ea = new.excluded_apps.or(cur.excluded_apps);
+            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
+            if !m.is_empty() {
+                top_fields.insert("advice".into(), self.build_sub_map_value(m));
+            }
+        }
+
+        // Build memory sub-map
+        if data.memory.is_some() || current.memory.is_some() {
+            let cur = current.memory.unwrap_or_default();
+            let new = data.memory.clone().unwrap_or_default();
+            let mut m = serde_json::Map::new();
+            let en = new.enabled.or(cur.enabled);
+            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
+            let ap = new.analysis_prompt.or(cur.analysis_prompt);
+            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
+            let ei = new.extraction_interval.or(cur.extraction_interval);
+            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
+            let mc = new.min_confidence.or(cur.min_confidence);
+            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
+            let ne = new.notifications_enabled.or(cur.notifications_enabled);
+            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
+            let ea = new.excluded_apps.or(cur.excluded_apps);
+            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
+            let bt = new.blocked_topics.or(cur.blocked_topics);
+            if let Some(v) = bt { m.insert("blocked_topics".into(), self.build_string_array_value(&v)); }
+            let aa = new.auto_approve_after_days.or(cur.auto_approve_after_days);
+            if let Some(v) = aa { m.insert("auto_approve_after_days".into(), json!({"integerValue": v.to_string()})); }
+            if !m.is_empty() {
+                top_fields.insert("memory".into(), self.build_sub_map_value(m));
+            }
+        }
+
+        if !top_fields.is_empty() {
+            let fields = json!({
+                "assistant_settings": {
+                    "mapValue": {
+                        "fields": Value::Object(top_fields)
+                    }
+                }
+            });
+
+            self.update_user_fields(uid, fields, &["assistant_settings"]).await?;
+        }
+
+        // Write update_channel as top-level field on user doc (not inside assistant_settings)
+        if let Some(ref channel) = data.update_channel {
+            let fields = json!({
+                "update_channel": {
+                    "stringValue": channel
+                }
+            });
+            self.update_user_fields(uid, fields, &["update_channel"]).await?;
+        }
+
+        // Return merged state
+        self.get_assistant_settings(uid).await
+    }
+
+    /// Get user email from Firestore profile
+    pub async fn get_user_email(
+        &self,
+        uid: &str,
+    ) -> Result<Option<String>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+        Ok(self.parse_string(fields, "email"))
+    }
+
+    /// Get user language preference
+    pub async fn get_user_language(
+        &self,
+        uid: &str,
+    ) -> Result<String, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(self.parse_string(fields, "language").unwrap_or_else(|| "en".to_string()))
+    }
+
+    /// Update user language preference
+    /// Languages supported by Deepgram Nova-3 multi-language auto-detection.
+    pub(crate) const MULTI_LANGUAGE_SUPPORTED: &[&str] = &[
+        "en", "en-US", "en-AU", "en-GB", "en-IN", "en-NZ",
+        "es", "es-419",
+        "fr", "fr-CA",
+        "de",
+        "hi",
+        "ru",
+        "pt", "pt-BR", "pt-PT",
+        "ja",
+        "it",
+        "nl",
+    ];
+
+    pub async fn update_user_language(
+        &self,
+        uid: &str,
+        language: &str,
+    ) -> Result<(), FirestoreError> {
+        // Set language field
+        let lang_fields = json!({
+            "language": {"stringValue": language}
+        });
+        self.update_user_fields(uid, lang_fields, &["language"]).await?;
+
+        // Auto-set single_language_mode based on whether the language supports multi-language
+        let single_language_mode = !Self::MULTI_LANGUAGE_SUPPORTED.contains(&language);
+        self.update_transcription_preferences(uid, Some(single_language_mode), None).await?;
+
+        Ok(())
+    }
+
+    /// Get recording permission for a user
+    pub async fn get_recording_permission(
+        &self,
+        uid: &str,
+    ) -> Result<bool, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(self.parse_bool(fields, "store_recording_permission").unwrap_or(false))
+    }
+
+    /// Set recording permission for a user
+    pub async fn set_recording_permission(
+        &self,
+        uid: &str,
+        enabled: bool,
+    ) -> Result<(), FirestoreError> {
+        let fields = json!({
+            "store_recording_permission": {"booleanValue": enabled}
+        });
+
+        self.update_user_fields(uid, fields, &["store_recording_permission"]).await
+    }
+
+    /// Get private cloud sync setting for a user
+    pub async fn get_private_cloud_sync(
+        &self,
+        uid: &str,
+    ) -> Result<bool, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        // Default to true if not set
+        Ok(self.parse_bool(fields, "private_cloud_sync_enabled").unwrap_or(true))
+    }
+
+    /// Set private cloud sync setting for a user
+    pub async fn set_private_cloud_sync(
+        &self,
+        uid: &str,
+        enabled: bool,
+    ) -> Result<(), FirestoreError> {
+        let fields = json!({
+            "private_cloud_sync_enabled": {"booleanValue": enabled}
+        });
+
+        self.update_user_fields(uid, fields, &["private_cloud_sync_enabled"]).await
+    }
+
+    /// Get notification settings for a user
+    pub async fn get_notification_settings(
+        &self,
+        uid: &str,
+    ) -> Result<NotificationSettings, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(NotificationSettings {
+            enabled: self.parse_bool(fields, "notifications_enabled").unwrap_or(true),
+            frequency: self.parse_int(fields, "notification_frequency").unwrap_or(3),
+        })
+    }
+
+    /// Update notification settings for a user
+    pub async fn update_notification_settings(
+        &self,
+        uid: &str,
+        enabled: Option<bool>,
+        frequency: Option<i32>,
+    ) -> Result<NotificationSettings, FirestoreError> {
+        // Get current settings
+        let current = self.get_notification_settings(uid).await?;
+
+        let new_enabled = enabled.unwrap_or(current.enabled);
+        let new_frequency = frequency.unwrap_or(current.frequency);
+
+        let fields = json!({
+            "notifications_enabled": {"booleanValue": new_enabled},
+            "notification_frequency": {"integerValue": new_frequency.to_string()}
+        });
+
+        self.update_user_fields(uid, fields, &["notifications_enabled", "notification_frequency"])
+            .await?;
+
+        Ok(NotificationSettings {
+            enabled: new_enabled,
+            frequency: new_frequency,
+        })
+    }
+
+    /// Get user profile
+    pub async fn get_user_profile(
+        &self,
+        uid: &str,
+    ) -> Result<UserProfile, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        Ok(UserProfile {
+            uid: uid.to_string(),
+            email: self.parse_string(fields, "email"),
+            name: self.parse_string(fields, "name"),
+            time_zone: self.parse_string(fields, "time_zone"),
+            created_at: self.parse_timestamp_optional(fields, "created_at")
+                .map(|dt| dt.to_rfc3339()),
+            motivation: self.parse_string(fields, "motivation"),
+            use_case: self.parse_string(fields, "use_case"),
+            job: self.parse_string(fields, "job"),
+            company: self.parse_string(fields, "company"),
+        })
+    }
+
+    /// Update user profile fields (onboarding data)
+    pub async fn update_user_profile(
+        &self,
+        uid: &str,
+        name: Option<String>,
+        motivation: Option<String>,
+        use_case: Option<String>,
+        job: Option<String>,
+        company: Option<String>,
+    ) -> Result<UserProfile, FirestoreError> {
+        let mut fields = json!({});
+        let mut mask: Vec<&str> = Vec::new();
+
+        if let Some(ref v) = name {
+            fields["name"] = json!({"stringValue": v});
+            mask.push("name");
+        }
+        if let Some(ref v) = motivation {
+            fields["motivation"] = json!({"stringValue": v});
+            mask.push("motivation");
+        }
+        if let Some(ref v) = use_case {
+            fields["use_case"] = json!({"stringValue": v});
+            mask.push("use_case");
+        }
+        if let Some(ref v) = job {
+            fields["job"] = json!({"stringValue": v});
+            mask.push("job");
+        }
+        if let Some(ref v) = company {
+            fields["company"] = json!({"stringValue": v});
+            mask.push("company");
+        }
+
+        if !mask.is_empty() {
+            self.update_user_fields(uid, fields, &mask).await?;
+        }
+
+        self.get_user_profile(uid).await
+    }
+
+    // =========================================================================
+    // USER PERSONA
+    // =========================================================================
+
+    /// Get AI-generated user profile from user document
+    pub async fn get_ai_user_profile(
+        &self,
+        uid: &str,
+    ) -> Result<Option<AIUserProfile>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        let profile_fields = fields
+            .get("ai_user_profile")
+            .and_then(|p| p.get("mapValue"))
+            .and_then(|m| m.get("fields"));
+
+        if let Some(pf) = profile_fields {
+            let profile_text = self.parse_string(pf, "profile_text").unwrap_or_default();
+            let generated_at = self.parse_timestamp(pf, "generated_at")?;
+            let data_sources_used = self.parse_int(pf, "data_sources_used").unwrap_or(0);
+
+            Ok(Some(AIUserProfile {
+                profile_text,
+                generated_at,
+                data_sources_used,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Update AI-generated user profile in user document
+    pub async fn update_ai_user_profile(
+        &self,
+        uid: &str,
+        profile_text: &str,
+        generated_at: &str,
+        data_sources_used: i32,
+    ) -> Result<AIUserProfile, FirestoreError> {
+        let generated_at_dt = DateTime::parse_from_rfc3339(generated_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| format!("Invalid generated_at timestamp: {}", e))?;
+
+        let fields = json!({
+            "ai_user_profile": {
+                "mapValue": {
+                    "fields": {
+                        "profile_text": {"stringValue": profile_text},
+                        "generated_at": {"timestampValue": generated_at},
+                        "data_sources_used": {"integerValue": data_sources_used.to_string()}
+                    }
+                }
+            }
+        });
+
+        self.update_user_fields(uid, fields, &["ai_user_profile"]).await?;
+
+        Ok(AIUserProfile {
+            profile_text: profile_text.to_string(),
+            generated_at: generated_at_dt,
+            data_sources_used,
+        })
+    }
+
+    // =========================================================================
+    // FOCUS SESSIONS
+    // =========================================================================
+
+    /// Create a focus session
+    /// Path: users/{uid}/focus_sessions/{session_id}
+    pub async fn create_focus_session(
+        &self,
+        uid: &str,
+        status: &FocusStatus,
+        app_or_site: &str,
+        description: &str,
+        message: Option<&str>,
+    ) -> Result<FocusSessionDB, FirestoreError> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOCUS_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let status_str = match status {
+            FocusStatus::Focused => "focused",
+            FocusStatus::Distracted => "distracted",
+        };
+
+        let mut fields = json!({
+            "status": {"stringValue": status_str},
+            "app_or_site": {"stringValue": app_or_site},
+            "description": {"stringValue": description},
+            "created_at": {"timestampValue": now.to_rfc3339()}
+        });
+
+        if let Some(msg) = message {
+            fields["message"] = json!({"stringValue": msg});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!(
+            "Created focus session {} for user {} with status={}",
+            session_id,
+            uid,
+            status_str
+        );
+
+        Ok(FocusSessionDB {
+            id: session_id,
+            status: status.clone(),
+            app_or_site: app_or_site.to_string(),
+            description: description.to_string(),
+            message: message.map(|s| s.to_string()),
+            created_at: now,
+            duration_seconds: None,
+        })
+    }
+
+    /// Upsert coalesced focus sessions in batched commits.
+    /// Document IDs are derived from (uid, status, app, start time) so re-sent
+    /// batches overwrite the same sessions instead of duplicating them.
+    pub async fn upsert_focus_sessions(
+        &self,
+        uid: &str,
+        sessions: &[CoalescedFocusSession],
+    ) -> Result<Vec<FocusSessionDB>, FirestoreError> {
+        let mut written = Vec::with_capacity(sessions.len());
+
+        for chunk in sessions.chunks(500) {
+            let mut writes: Vec<Value> = Vec::with_capacity(chunk.len());
+            for session in chunk {
+                let session_id = document_id_from_seed(&format!(
+                    "{}-{}-{}-{}",
+                    uid,
+                    session.status,
+                    session.app_or_site,
+                    session.started_at.timestamp_millis()
+                ));
+                let doc_name = format!(
+                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                    self.project_id(), USERS_COLLECTION, uid, FOCUS_SESSIONS_SUBCOLLECTION, session_id
+                );
+                writes.push(json!({
+                    "update": {
+                        "name": doc_name,
+                        "fields": {
+                            "status": {"stringValue": session.status.to_string()},
+                            "app_or_site": {"stringValue": session.app_or_site},
+                            "description": {"stringValue": session.description},
+                            "created_at": {"timestampValue": session.started_at.to_rfc3339()},
+                            "duration_seconds": {"integerValue": session.duration_seconds.to_string()},
+                            "source": {"stringValue": "batch"}
+                        }
+                    }
+                }));
+                written.push(FocusSessionDB {
+                    id: session_id,
+                    status: session.status.clone(),
+                    app_or_site: session.app_or_site.clone(),
+                    description: session.description.clone(),
+                    message: None,
+                    created_at: session.started_at,
+                    duration_seconds: Some(session.duration_seconds),
+                });
+            }
+
+            let commit_url = format!(
+                "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+                self.project_id()
+            );
+
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&json!({ "writes": writes }))
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
+            }
+        }
+
+        tracing::info!("Upserted {} focus sessions for user {}", written.len(), uid);
+        Ok(written)
+    }
+
+    /// Get focus sessions for a user
+    /// Path: users/{uid}/focus_sessions
+    pub async fn get_focus_sessions(
+        &self,
+        uid: &str,
+        limit: usize,
+        offset: usize,
+        date_filter: Option<&str>,
+    ) -> Result<Vec<FocusSessionDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        // If date filter provided, filter by date range
+        if let Some(date) = date_filter {
+            // Parse date and create start/end timestamps
+            if let Ok(parsed_date) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                let start = parsed_date
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                let end = parsed_date
+                    .and_hms_opt(23, 59, 59)
+                    .unwrap()
+                    .and_utc();
+
+                filters.push(json!({
+                    "fieldFilter": {
+                        "field": {"fieldPath": "created_at"},
+                        "op": "GREATER_THAN_OR_EQUAL",
+                        "value": {"timestampValue": start.to_rfc3339()}
+                    }
+                }));
+                filters.push(json!({
+                    "fieldFilter": {
+                        "field": {"fieldPath": "created_at"},
+                        "op": "LESS_THAN_OR_EQUAL",
+                        "value": {"timestampValue": end.to_rfc3339()}
+                    }
+                }));
+            }
+        }
+
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
+
+        // Build structured query
+        let mut structured_query = json!({
+            "from": [{"collectionId": FOCUS_SESSIONS_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit,
+            "offset": offset
+        });
+
+        if let Some(where_filter) = where_clause {
+            structured_query["where"] = where_filter;
+        }
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let sessions = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_focus_session(d).ok())
+            })
+            .collect();
+
+        Ok(sessions)
+    }
+
+    /// Focus sessions that started in [start, end), oldest first
+    pub async fn get_focus_sessions_between(
+        &self,
+        uid: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<FocusSessionDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": FOCUS_SESSIONS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "created_at"},
+                                "op": "GREATER_THAN_OR_EQUAL",
+                                "value": {"timestampValue": start.to_rfc3339()}
+                            }},
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "created_at"},
+                                "op": "LESS_THAN",
+                                "value": {"timestampValue": end.to_rfc3339()}
+                            }}
+                        ]
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "ASCENDING"}],
+                "limit": limit
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_focus_session(d).ok()))
+            .collect())
+    }
+
+    /// The latest focus session that started at or before `at`
+    pub async fn get_focus_session_started_before(
+        &self,
+        uid: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<FocusSessionDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": FOCUS_SESSIONS_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "created_at"},
+                        "op": "LESS_THAN_OR_EQUAL",
+                        "value": {"timestampValue": at.to_rfc3339()}
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": 1
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .find_map(|doc| doc.get("document").and_then(|d| self.parse_focus_session(d).ok())))
+    }
+
+    /// Get a single focus session
+    /// Path: users/{uid}/focus_sessions/{session_id}
+    pub async fn get_focus_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+    ) -> Result<Option<FocusSessionDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOCUS_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        Ok(Some(self.parse_focus_session(&doc)?))
+    }
+
+    /// Delete a focus session
+    pub async fn delete_focus_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOCUS_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted focus session {} for user {}", session_id, uid);
+        Ok(())
+    }
+
+    /// Get focus statistics for a date
+    pub async fn get_focus_stats(
+        &self,
+        uid: &str,
+        date: &str,
+    ) -> Result<FocusStats, FirestoreError> {
+        // Get all sessions for the date
+        let sessions = self.get_focus_sessions(uid, 1000, 0, Some(date)).await?;
+
+        let mut focused_count: i64 = 0;
+        let mut distracted_count: i64 = 0;
+        let mut focused_seconds: i64 = 0;
+        let mut distracted_seconds: i64 = 0;
+        let mut distraction_map: std::collections::HashMap<String, (i64, i64)> =
+            std::collections::HashMap::new();
+
+        for session in &sessions {
+            let seconds = session.duration_seconds.unwrap_or(60); // Default 60s per session
+            match session.status {
+                FocusStatus::Focused => {
+                    focused_count += 1;
+                    focused_seconds += seconds;
+                }
+                FocusStatus::Distracted => {
+                    distracted_count += 1;
+                    distracted_seconds += seconds;
+                    let entry = distraction_map
+                        .entry(session.app_or_site.clone())
+                        .or_insert((0, 0));
+                    entry.0 += seconds;
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        // Build top distractions
+        let mut top_distractions: Vec<DistractionEntry> = distraction_map
+            .into_iter()
+            .map(|(app, (secs, count))| DistractionEntry {
+                app_or_site: app,
+                total_seconds: secs,
+                count,
+            })
+            .collect();
+
+        // Sort by total time descending
+        top_distractions.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds));
+
+        // Take top 5
+        top_distractions.truncate(5);
+
+        // Minutes from recorded durations (each session ~1 minute if no duration)
+        let focused_minutes = focused_seconds / 60;
+        let distracted_minutes = distracted_seconds / 60;
+
+        Ok(FocusStats {
+            date: date.to_string(),
+            focused_minutes,
+            distracted_minutes,
+            session_count: sessions.len() as i64,
+            focused_count,
+            distracted_count,
+            top_distractions,
+        })
+    }
+
+    /// Parse a focus session from Firestore document
+    fn parse_focus_session(
+        &self,
+        doc: &Value,
+    ) -> Result<FocusSessionDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        firestore_serde::drop_invalid::<FocusStatus>(&mut object, "status");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({
+                "status": "distracted",
+                "app_or_site": "",
+                "description": "",
+                "created_at": Utc::now(),
+            }),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    // =========================================================================
+    // CHAT SESSIONS
+    // =========================================================================
+
+    /// Create a chat session
+    /// Path: users/{uid}/chat_sessions/{session_id}
+    pub async fn create_chat_session(
+        &self,
+        uid: &str,
+        title: Option<&str>,
+        app_id: Option<&str>,
+    ) -> Result<ChatSessionDB, FirestoreError> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let mut fields = json!({
+            // CRITICAL: id field required - Python ChatSession model requires it
+            // and chat.py accesses chat_session['id'] directly
+            "id": {"stringValue": &session_id},
+            "title": {"stringValue": title.unwrap_or("New Chat")},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()},
+            "message_count": {"integerValue": "0"},
+            "starred": {"booleanValue": false}
+        });
+
+        // CRITICAL: Always set app_id and plugin_id fields for backward compatibility
+        // Python backend queries chat_sessions.where(plugin_id == null) for main chat
+        if let Some(app) = app_id {
+            fields["app_id"] = json!({"stringValue": app});
+            fields["plugin_id"] = json!({"stringValue": app});
+        } else {
+            // For main chat (no app), explicitly set null values
+            fields["app_id"] = json!({"nullValue": null});
+            fields["plugin_id"] = json!({"nullValue": null});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!(
+            "Created chat session {} for user {} with title={}",
+            session_id,
+            uid,
+            title.unwrap_or("New Chat")
+        );
+
+        Ok(ChatSessionDB {
+            id: session_id,
+            title: title.unwrap_or("New Chat").to_string(),
+            preview: None,
+            created_at: now,
+            updated_at: now,
+            app_id: app_id.map(|s| s.to_string()),
+            message_count: 0,
+            starred: false,
+        })
+    }
+
+    /// Get chat sessions for a user
+    /// Path: users/{uid}/chat_sessions
+    pub async fn get_chat_sessions(
+        &self,
+        uid: &str,
+        app_id: Option<&str>,
+        limit: usize,
+        offset: usize,
+        starred: Option<bool>,
+    ) -> Result<Vec<ChatSessionDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        // Filter by app_id (null = main Omi chat)
+        if let Some(app) = app_id {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "app_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": app}
+                }
+            }));
+        }
+
+        // Filter by starred if specified
+        if let Some(is_starred) = starred {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "starred"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": is_starred}
+                }
+            }));
+        }
+
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
+
+        // Build structured query
+        // NOTE: Use created_at for ordering (not updated_at) for backward compatibility
+        // Old sessions from Flutter app don't have updated_at field
+        let mut structured_query = json!({
+            "from": [{"collectionId": CHAT_SESSIONS_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit,
+            "offset": offset
+        });
+
+        if let Some(where_filter) = where_clause {
+            structured_query["where"] = where_filter;
+        }
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let sessions = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_chat_session(d).ok())
+            })
+            .collect();
+
+        Ok(sessions)
+    }
+
+    /// Get a single chat session
+    /// Path: users/{uid}/chat_sessions/{session_id}
+    pub async fn get_chat_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+    ) -> Result<Option<ChatSessionDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        Ok(Some(self.parse_chat_session(&doc)?))
+    }
+
+    /// Update a chat session (title, starred, preview, message_count)
+    /// Path: users/{uid}/chat_sessions/{session_id}
+    pub async fn update_chat_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+        title: Option<&str>,
+        starred: Option<bool>,
+    ) -> Result<ChatSessionDB, FirestoreError> {
+        // First get the existing session
+        let existing = self.get_chat_session(uid, session_id).await?
+            .ok_or_else(|| FirestoreError::NotFound(format!("Chat session {} not found", session_id)))?;
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let now = Utc::now();
+        let mut fields = json!({
+            "title": {"stringValue": title.unwrap_or(&existing.title)},
+            "starred": {"booleanValue": starred.unwrap_or(existing.starred)},
+            "updated_at": {"timestampValue": now.to_rfc3339()},
+            "created_at": {"timestampValue": existing.created_at.to_rfc3339()},
+            "message_count": {"integerValue": existing.message_count.to_string()}
+        });
+
+        if let Some(preview) = &existing.preview {
+            fields["preview"] = json!({"stringValue": preview});
+        }
+        if let Some(app) = &existing.app_id {
+            fields["app_id"] = json!({"stringValue": app});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        tracing::info!(
+            "Updated chat session {} for user {}",
+            session_id,
+            uid
+        );
+
+        Ok(ChatSessionDB {
+            id: session_id.to_string(),
+            title: title.unwrap_or(&existing.title).to_string(),
+            preview: existing.preview,
+            created_at: existing.created_at,
+            updated_at: now,
+            app_id: existing.app_id,
+            message_count: existing.message_count,
+            starred: starred.unwrap_or(existing.starred),
+        })
+    }
+
+    /// Update chat session preview and message count (called when new message is added)
+    pub async fn update_chat_session_with_message(
+        &self,
+        uid: &str,
+        session_id: &str,
+        preview: &str,
+        title: Option<&str>,
+    ) -> Result<(), FirestoreError> {
+        // First get the existing session
+        let existing = match self.get_chat_session(uid, session_id).await? {
+            Some(s) => s,
+            None => return Ok(()), // Session doesn't exist, skip update
+        };
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let now = Utc::now();
+        let new_count = existing.message_count + 1;
+
+        // Use provided title or keep existing (title is auto-generated from first message)
+        let final_title = title.unwrap_or(&existing.title);
+
+        let mut fields = json!({
+            "title": {"stringValue": final_title},
+            "preview": {"stringValue": preview.chars().take(100).collect::<String>()},
+            "updated_at": {"timestampValue": now.to_rfc3339()},
+            "created_at": {"timestampValue": existing.created_at.to_rfc3339()},
+            "message_count": {"integerValue": new_count.to_string()},
+            "starred": {"booleanValue": existing.starred}
+        });
+
+        if let Some(app) = &existing.app_id {
+            fields["app_id"] = json!({"stringValue": app});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::warn!("Failed to update chat session with message: {}", error_text);
+        }
+
+        Ok(())
+    }
+
+    /// Delete a chat session and its associated messages
+    /// Path: users/{uid}/chat_sessions/{session_id}
+    pub async fn delete_chat_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+    ) -> Result<(), FirestoreError> {
+        // First, delete all messages with this session_id
+        if let Err(e) = self.delete_messages_by_session(uid, session_id).await {
+            tracing::warn!("Failed to delete messages for session {}: {}", session_id, e);
+            // Continue with session deletion
+        }
+
+        // Delete the session document
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted chat session {} for user {}", session_id, uid);
+        Ok(())
+    }
+
+    /// Delete all messages with a specific session_id
+    async fn delete_messages_by_session(
+        &self,
+        uid: &str,
+        session_id: &str,
+    ) -> Result<usize, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Query messages with this session_id
+        let structured_query = json!({
+            "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
+            "where": {
+                "fieldFilter": {
+                    "field": {"fieldPath": "session_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": session_id}
+                }
+            },
+            "limit": 500  // Batch delete limit
+        });
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to query messages", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut deleted_count = 0;
+
+        // Delete each message
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Some(name) = doc.get("name").and_then(|n| n.as_str()) {
+                    // Extract the full document path for deletion
+                    let delete_url = format!(
+                        "https://firestore.googleapis.com/v1/{}",
+                        name
+                    );
+
+                    let delete_response = self
+                        .build_request(reqwest::Method::DELETE, &delete_url)
+                        .await?
+                        .send()
+                        .await?;
+
+                    if delete_response.status().is_success() {
+                        deleted_count += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            "Deleted {} messages for session {} (user {})",
+            deleted_count,
+            session_id,
+            uid
+        );
+
+        Ok(deleted_count)
+    }
+
+    /// Parse a chat session from Firestore document
+    /// Supports both old (Flutter) and new (Desktop) session formats for backward compatibility
+    fn parse_chat_session(
+        &self,
+        doc: &Value,
+    ) -> Result<ChatSessionDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        firestore_serde::fill_missing(&mut object, json!({"created_at": Utc::now()}));
+
+        // Old Flutter sessions have message IDs instead of a count, plugin_id instead of
+        // app_id, and no updated_at
+        let legacy_count = object.get("message_ids").and_then(|ids| ids.as_array()).map(Vec::len);
+        let app_id = object.get("app_id").or_else(|| object.get("plugin_id")).cloned();
+        // Untitled main chats (app_id=null) read as "Omi" so users recognize them
+        let defaults = json!({
+            "title": if app_id.is_none() { "Omi" } else { "New Chat" },
+            "updated_at": object["created_at"].clone(),
+            "message_count": legacy_count.unwrap_or(0),
+            "app_id": app_id,
+        });
+        firestore_serde::fill_missing(&mut object, defaults);
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    // =========================================================================
+    // ADVICE
+    // =========================================================================
+
+    /// Create a new advice entry
+    /// Path: users/{uid}/advice/{advice_id}
+    pub async fn create_advice(
+        &self,
+        uid: &str,
+        content: &str,
+        category: Option<AdviceCategory>,
+        reasoning: Option<&str>,
+        source_app: Option<&str>,
+        confidence: Option<f64>,
+        context_summary: Option<&str>,
+        current_activity: Option<&str>,
+    ) -> Result<AdviceDB, FirestoreError> {
+        let advice_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ADVICE_SUBCOLLECTION,
+            advice_id
+        );
+
+        let category_str = match category.unwrap_or(AdviceCategory::Other) {
+            AdviceCategory::Productivity => "productivity",
+            AdviceCategory::Health => "health",
+            AdviceCategory::Communication => "communication",
+            AdviceCategory::Learning => "learning",
+            AdviceCategory::Other => "other",
+        };
+
+        let mut fields = json!({
+            "content": {"stringValue": content},
+            "category": {"stringValue": category_str},
+            "confidence": {"doubleValue": confidence.unwrap_or(0.5)},
+            "is_read": {"booleanValue": false},
+            "is_dismissed": {"booleanValue": false},
+            "created_at": {"timestampValue": now.to_rfc3339()}
+        });
+
+        if let Some(r) = reasoning {
+            fields["reasoning"] = json!({"stringValue": r});
+        }
+        if let Some(app) = source_app {
+            fields["source_app"] = json!({"stringValue": app});
+        }
+        if let Some(summary) = context_summary {
+            fields["context_summary"] = json!({"stringValue": summary});
+        }
+        if let Some(activity) = current_activity {
+            fields["current_activity"] = json!({"stringValue": activity});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        let created_doc: Value = response.json().await?;
+        let advice = self.parse_advice(&created_doc)?;
+
+        tracing::info!("Created advice {} for user {}", advice_id, uid);
+        Ok(advice)
+    }
+
+    /// Get advice for a user
+    /// Path: users/{uid}/advice
+    pub async fn get_advice(
+        &self,
+        uid: &str,
+        limit: usize,
+        offset: usize,
+        category: Option<&str>,
+        include_dismissed: bool,
+        include_snoozed: bool,
+    ) -> Result<Vec<AdviceDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        // Filter out dismissed unless requested
+        if !include_dismissed {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "is_dismissed"},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": false}
+                }
+            }));
+        }
+
+        // Filter by category if specified
+        if let Some(cat) = category {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "category"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": cat}
+                }
+            }));
+        }
+
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
+
+        // Build structured query
+        let mut structured_query = json!({
+            "from": [{"collectionId": ADVICE_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit,
+            "offset": offset
+        });
+
+        if let Some(where_filter) = where_clause {
+            structured_query["where"] = where_filter;
+        }
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let now = Utc::now();
+        let advice_list = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_advice(d).ok())
+            })
+            .filter(|advice: &AdviceDB| include_snoozed || advice.snoozed_until.is_none_or(|until| until <= now))
+            .collect();
+
+        Ok(advice_list)
+    }
+
+    /// Advice created in [start, end), dismissed included, oldest first
+    pub async fn get_advice_between(
+        &self,
+        uid: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AdviceDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ADVICE_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "created_at"},
+                                "op": "GREATER_THAN_OR_EQUAL",
+                                "value": {"timestampValue": start.to_rfc3339()}
+                            }},
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "created_at"},
+                                "op": "LESS_THAN",
+                                "value": {"timestampValue": end.to_rfc3339()}
+                            }}
+                        ]
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "ASCENDING"}],
+                "limit": limit
+            }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs.iter().filter_map(|doc| self.parse_advice(doc).ok()).collect())
+    }
+
+    /// Update advice (read/dismissed state and delivery tracking)
+    pub async fn update_advice(
+        &self,
+        uid: &str,
+        advice_id: &str,
+        update: &crate::models::UpdateAdviceRequest,
+    ) -> Result<AdviceDB, FirestoreError> {
+        let (fields, field_paths) = advice_update_fields(update, Utc::now());
+
+        let update_mask = field_paths
+            .iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ADVICE_SUBCOLLECTION,
+            advice_id,
+            update_mask
+        );
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        let updated_doc: Value = response.json().await?;
+        let advice = self.parse_advice(&updated_doc)?;
+
+        tracing::info!("Updated advice {} for user {}", advice_id, uid);
+        Ok(advice)
+    }
+
+    /// Snooze advice until `until`, or clear the snooze with None. Fails with a
+    /// NOT_FOUND error if the advice doesn't exist.
+    pub async fn snooze_advice(
+        &self,
+        uid: &str,
+        advice_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            ADVICE_SUBCOLLECTION,
+            advice_id
+        );
+        self.patch_document_fields(&doc_name, snooze_fields(until), &["snoozed_until", "updated_at"])
+            .await
+    }
+
+    /// Delete advice permanently
+    pub async fn delete_advice(
+        &self,
+        uid: &str,
+        advice_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ADVICE_SUBCOLLECTION,
+            advice_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted advice {} for user {}", advice_id, uid);
+        Ok(())
+    }
+
+    /// Record helpful / not helpful feedback (plus optional text) on advice
+    pub async fn set_advice_feedback(
+        &self,
+        uid: &str,
+        advice_id: &str,
+        helpful: bool,
+        text: Option<&str>,
+    ) -> Result<AdviceDB, FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        let mut fields = json!({
+            "helpful": {"booleanValue": helpful},
+            "feedback_at": {"timestampValue": now},
+            "updated_at": {"timestampValue": now}
+        });
+        // Always include feedback_text in the mask so resubmitting without text clears it
+        if let Some(t) = text {
+            fields["feedback_text"] = json!({"stringValue": t});
+        }
+
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=helpful&updateMask.fieldPaths=feedback_text&updateMask.fieldPaths=feedback_at&updateMask.fieldPaths=updated_at&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            ADVICE_SUBCOLLECTION,
+            advice_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        let updated_doc: Value = response.json().await?;
+        let advice = self.parse_advice(&updated_doc)?;
+
+        tracing::info!(
+            "Recorded advice feedback helpful={} for advice {} user {}",
+            helpful,
+            advice_id,
+            uid
+        );
+        Ok(advice)
+    }
+
+    /// Mark all advice as read for a user
+    pub async fn mark_all_advice_read(
+        &self,
+        uid: &str,
+    ) -> Result<usize, FirestoreError> {
+        // Get all unread advice
+        let advice_list = self.get_advice(uid, 1000, 0, None, false, true).await?;
+        let unread: Vec<String> = advice_list
+            .into_iter()
+            .filter(|a| !a.is_read)
+            .map(|a| a.id)
+            .collect();
+
+        let (count, _) = self
+            .bulk_update_advice(uid, &unread, crate::models::AdviceBulkAction::MarkRead)
+            .await?;
+        Ok(count)
+    }
+
+    /// Apply one action to many advice items. Writes are independent (batchWrite), so
+    /// missing IDs don't block the rest. Returns (updated count, IDs not found).
+    pub async fn bulk_update_advice(
+        &self,
+        uid: &str,
+        advice_ids: &[String],
+        action: crate::models::AdviceBulkAction,
+    ) -> Result<(usize, Vec<String>), FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        let url = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:batchWrite",
+            self.project_id()
+        );
+        let mut updated = 0;
+        let mut not_found = Vec::new();
+
+        for chunk in advice_ids.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|advice_id| {
+                    let doc_name = format!(
+                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                        self.project_id(), USERS_COLLECTION, uid, ADVICE_SUBCOLLECTION, advice_id
+                    );
+                    advice_bulk_write(doc_name, action, &now)
+                })
+                .collect();
+
+            let response = self
+                .build_request(reqwest::Method::POST, &url)
+                .await?
+                .json(&json!({ "writes": writes }))
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch write error", &error_text));
+            }
+
+            // One status per write; 5 = NOT_FOUND (precondition on a missing document)
+            let result: Value = response.json().await?;
+            let statuses = result.get("status").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+            for (advice_id, status) in chunk.iter().zip(statuses.iter()) {
+                match status.get("code").and_then(|c| c.as_i64()).unwrap_or(0) {
+                    0 => updated += 1,
+                    5 => not_found.push(advice_id.clone()),
+                    code => {
+                        let message = status.get("message").and_then(|m| m.as_str()).unwrap_or("");
+                        return Err(format!("Firestore batch write failed for advice {} ({}): {}", advice_id, code, message).into());
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Bulk {:?} on {} advice for user {}", action, updated, uid);
+        Ok((updated, not_found))
+    }
+
+    /// Parse Firestore document to AdviceDB
+    fn parse_advice(
+        &self,
+        doc: &Value,
+    ) -> Result<AdviceDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        firestore_serde::drop_invalid::<AdviceCategory>(&mut object, "category");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({"content": "", "confidence": 0.5, "created_at": Utc::now()}),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    // =========================================================================
+    // Desktop Releases (for Sparkle auto-update)
+    // =========================================================================
+
+    /// Get desktop releases for auto-update appcast
+    /// Fetches from desktop_releases collection
+    pub async fn get_desktop_releases(
+        &self,
+    ) -> Result<Vec<crate::routes::updates::ReleaseInfo>, FirestoreError> {
+        let url = format!(
+            "{}/desktop_releases",
+            self.base_url()
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            // If collection doesn't exist, return empty list
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(vec![]);
+            }
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore error", &error_text));
+        }
+
+        let data: Value = response.json().await?;
+        let mut releases = Vec::new();
+
+        if let Some(documents) = data.get("documents").and_then(|d| d.as_array()) {
+            for doc in documents {
+                if let Ok(release) = self.parse_release(doc) {
+                    releases.push(release);
+                }
+            }
+        }
+
+        // Sort by build number descending (newest first)
+        releases.sort_by(|a, b| b.build_number.cmp(&a.build_number));
+
+        Ok(releases)
+    }
+
+    /// Parse Firestore document to ReleaseInfo
+    fn parse_release(
+        &self,
+        doc: &Value,
+    ) -> Result<crate::routes::updates::ReleaseInfo, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        // channel: None = unpromoted (staging), Some("stable") = promoted stable
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({
+                "version": "",
+                "build_number": 0,
+                "download_url": "",
+                "ed_signature": "",
+                "published_at": "",
+                "changelog": [],
+                "is_live": false,
+                "is_critical": false,
+            }),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    /// Create a new desktop release in Firestore
+    pub async fn create_desktop_release(
+        &self,
+        release: &crate::routes::updates::ReleaseInfo,
+    ) -> Result<String, FirestoreError> {
+        let doc_id = format!("v{}+{}", release.version, release.build_number);
+
+        let url = format!(
+            "{}/desktop_releases/{}",
+            self.base_url(),
+            doc_id
+        );
+
+        // Build changelog array
+        let changelog_values: Vec<Value> = release.changelog
+            .iter()
+            .map(|s| json!({"stringValue": s}))
+            .collect();
+
+        // Channel field: always a string. None/empty → "staging" (unpromoted default)
+        let channel_value = match &release.channel {
+            Some(ch) if !ch.is_empty() => json!({"stringValue": ch}),
+            _ => json!({"stringValue": "staging"}),
+        };
+
+        let doc = json!({
+            "fields": {
+                "version": {"stringValue": release.version},
+                "build_number": {"integerValue": release.build_number.to_string()},
+                "download_url": {"stringValue": release.download_url},
+                "ed_signature": {"stringValue": release.ed_signature},
+                "published_at": {"stringValue": release.published_at},
+                "changelog": {"arrayValue": {"values": changelog_values}},
+                "is_live": {"booleanValue": release.is_live},
+                "is_critical": {"booleanValue": release.is_critical},
+                "channel": channel_value
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!("Created desktop release: {}", doc_id);
+        Ok(doc_id)
+    }
+
+    /// Promote a desktop release to the next channel: staging → beta → stable
+    /// Returns (old_channel, new_channel)
+    pub async fn promote_desktop_release(
+        &self,
+        doc_id: &str,
+    ) -> Result<(String, String), FirestoreError> {
+        // Fetch the current document
+        let url = format!(
+            "{}/desktop_releases/{}",
+            self.base_url(),
+            doc_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Release not found", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").ok_or("Missing fields in document")?;
+        let current_channel = self.parse_string(fields, "channel").unwrap_or_default();
+
+        // Determine next channel
+        let (old_channel, new_channel_value) = match current_channel.as_str() {
+            "staging" | "" => ("staging".to_string(), json!({"stringValue": "beta"})),
+            "beta" => ("beta".to_string(), json!({"stringValue": "stable"})),
+            "stable" => return Err("Release is already on stable channel, cannot promote further".into()),
+            other => return Err(format!("Unknown channel '{}', cannot promote", other).into()),
+        };
+
+        let new_channel = new_channel_value.get("stringValue").and_then(|v| v.as_str()).unwrap().to_string();
+
+        // PATCH only the channel field
+        let patch_url = format!(
+            "{}/desktop_releases/{}?updateMask.fieldPaths=channel",
+            self.base_url(),
+            doc_id
+        );
+
+        let patch_doc = json!({
+            "fields": {
+                "channel": new_channel_value
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &patch_url)
+            .await?
+            .json(&patch_doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to update channel", &error_text));
+        }
+
+        tracing::info!("Promoted release {}: {} → {}", doc_id, old_channel, new_channel);
+
+        Ok((old_channel, new_channel))
+    }
+
+    // =========================================================================
+    // MESSAGES (Chat Persistence)
+    // =========================================================================
+
+    /// Get or create a chat session for the given app_id (None = main default chat).
+    /// Mirrors Python's `acquire_chat_session()`:
+    ///   1. List all chat_sessions, find one matching plugin_id
+    ///   2. If none exists, create one with {id, created_at, plugin_id}
+    /// Returns the session ID.
+    pub async fn acquire_chat_session(
+        &self,
+        uid: &str,
+        app_id: Option<&str>,
+    ) -> Result<String, FirestoreError> {
+        // Step 1: Fetch all chat sessions and find a matching one client-side.
+        // We can't use `WHERE plugin_id == null` via REST API — it doesn't match
+        // documents where the field is absent or was set to null by the Python SDK.
+        let list_url = format!(
+            "{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &list_url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let body: Value = response.json().await?;
+            if let Some(documents) = body.get("documents").and_then(|d| d.as_array()) {
+                for doc in documents {
+                    let fields = match doc.get("fields") {
+                        Some(f) => f,
+                        None => continue,
+                    };
+
+                    let matches = match app_id {
+                        Some(target_app) => {
+                            // Looking for a session with this specific plugin_id
+                            fields
+                                .get("plugin_id")
+                                .and_then(|v| v.get("stringValue"))
+                                .and_then(|v| v.as_str())
+                                == Some(target_app)
+                        }
+                        None => {
+                            // Looking for main chat: both plugin_id AND app_id must be
+                            // null, absent, or empty.  Without the app_id check, task-chat
+                            // sessions (plugin_id=null, app_id="task-chat") match falsely.
+                            let plugin_id_null = match fields.get("plugin_id") {
+                                None => true,
+                                Some(val) => {
+                                    val.get("nullValue").is_some()
+                                        || val
+                                            .get("stringValue")
+                                            .and_then(|v| v.as_str())
+                                            .map_or(false, |s| s.is_empty())
+                                }
+                            };
+                            let app_id_null = match fields.get("app_id") {
+                                None => true,
+                                Some(val) => {
+                                    val.get("nullValue").is_some()
+                                        || val
+                                            .get("stringValue")
+                                            .and_then(|v| v.as_str())
+                                            .map_or(false, |s| s.is_empty())
+                                }
+                            };
+                            plugin_id_null && app_id_null
+                        }
+                    };
+
+                    if matches {
+                        if let Some(doc_id) = firestore_serde::document_id(doc) {
+                            tracing::info!(
+                                "Found existing chat session {} for user {} (app_id={:?})",
+                                doc_id,
+                                uid,
+                                app_id
+                            );
+                            return Ok(doc_id.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        // Step 2: No matching session found — create one (mirrors Python's ChatSession model)
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let create_url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CHAT_SESSIONS_SUBCOLLECTION,
+            session_id
+        );
+
+        let mut session_fields = json!({
+            "id": {"stringValue": &session_id},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "message_ids": {"arrayValue": {"values": []}},
+            "file_ids": {"arrayValue": {"values": []}}
+        });
+
+        if let Some(app) = app_id {
+            session_fields["plugin_id"] = json!({"stringValue": app});
+            session_fields["app_id"] = json!({"stringValue": app});
+        } else {
+            session_fields["plugin_id"] = json!({"nullValue": null});
+            session_fields["app_id"] = json!({"nullValue": null});
+        }
+
+        let doc = json!({"fields": session_fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &create_url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to create chat session", &error_text));
+        }
+
+        tracing::info!(
+            "Created new chat session {} for user {} (app_id={:?})",
+            session_id,
+            uid,
+            app_id
+        );
+        Ok(session_id)
+    }
+
+    /// Append a message ID to a chat session's message_ids array.
+    /// Mirrors Python's `add_message_to_chat_session()`.
+    async fn add_message_to_chat_session(
+        &self,
+        uid: &str,
+        chat_session_id: &str,
+        message_id: &str,
+    ) -> Result<(), FirestoreError> {
+        // Firestore REST API: use fieldTransforms with appendMissingElements
+        // to atomically append to the array (equivalent to Python's ArrayUnion)
+        let update = json!({
+            "writes": [{
+                "transform": {
+                    "document": format!(
+                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                        self.project_id(), USERS_COLLECTION, uid,
+                        CHAT_SESSIONS_SUBCOLLECTION, chat_session_id
+                    ),
+                    "fieldTransforms": [{
+                        "fieldPath": "message_ids",
+                        "appendMissingElements": {
+                            "values": [{"stringValue": message_id}]
+                        }
+                    }]
+                }
+            }]
+        });
+
+        let commit_url = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+            self.project_id()
+        );
+
+        let response = self
+            .build_request(reqwest::Method::POST, &commit_url)
+            .await?
+            .json(&update)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::warn!(
+                "Failed to add message {} to chat session {}: {}",
+                message_id,
+                chat_session_id,
+                error_text
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Save a chat message to Firestore
+    /// Used for chat history persistence
+    pub async fn save_message(
+        &self,
+        uid: &str,
+        text: &str,
+        sender: &str,
+        app_id: Option<&str>,
+        session_id: Option<&str>,
+        metadata: Option<&str>,
+    ) -> Result<MessageDB, FirestoreError> {
+        self.save_message_with_attachments(uid, text, sender, app_id, session_id, metadata, &[], &LlmProvenance::default())
+            .await
+    }
+
+    /// Save a chat message with attachments (uploaded beforehand via upload_chat_attachment)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn save_message_with_attachments(
+        &self,
+        uid: &str,
+        text: &str,
+        sender: &str,
+        app_id: Option<&str>,
+        session_id: Option<&str>,
+        metadata: Option<&str>,
+        attachments: &[MessageAttachment],
+        provenance: &LlmProvenance,
+    ) -> Result<MessageDB, FirestoreError> {
+        let message_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            MESSAGES_SUBCOLLECTION,
+            message_id
+        );
+
+        let mut fields = json!({
+            // CRITICAL: id field required - Python queries .where('id', '==', message_id)
+            "id": {"stringValue": &message_id},
+            "text": {"stringValue": text},
+            "sender": {"stringValue": sender},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "reported": {"booleanValue": false},
+            // CRITICAL: type field required - Python Message model requires it (no default)
+            "type": {"stringValue": "text"},
+            // Default empty arrays for memories_id
+            "memories_id": {"arrayValue": {"values": []}},
+            "from_external_integration": {"booleanValue": false},
+            // files/files_id mirror Python's FileChat attachments
+            "files_id": {"arrayValue": {"values": attachments
+                .iter()
+                .map(|a| json!({"stringValue": a.id}))
+                .collect::<Vec<_>>()}},
+            "files": {"arrayValue": {"values": attachments
+                .iter()
+                .map(Self::attachment_to_value)
+                .collect::<Vec<_>>()}}
+        });
+
+        // CRITICAL: Always set app_id and plugin_id fields (even as null) for backward compatibility
+        // Python backend queries .where(plugin_id == null) for main chat
+        // Firestore won't match documents that don't have the field at all
+        if let Some(app) = app_id {
+            fields["app_id"] = json!({"stringValue": app});
+            fields["plugin_id"] = json!({"stringValue": app});
+        } else {
+            // For main chat (no app), explicitly set null values
+            fields["app_id"] = json!({"nullValue": null});
+            fields["plugin_id"] = json!({"nullValue": null});
+        }
+
+        // Acquire (get or create) a chat session — mirrors Python's acquire_chat_session().
+        // This ensures desktop messages have a chat_session_id so they're visible on mobile.
+        let effective_session_id: Option<String> = if let Some(session) = session_id {
+            Some(session.to_string())
+        } else {
+            match self.acquire_chat_session(uid, app_id).await {
+                Ok(session) => Some(session),
+                Err(e) => {
+                    tracing::warn!("Failed to acquire chat session: {}", e);
+                    None
+                }
+            }
+        };
+
+        if let Some(ref session) = effective_session_id {
+            fields["session_id"] = json!({"stringValue": session});
+            fields["chat_session_id"] = json!({"stringValue": session});
+        }
+
+        if let Some(meta) = metadata {
+            fields["metadata"] = json!({"stringValue": meta});
+        }
+        Self::write_provenance(&mut fields, provenance);
+        let tokens: Vec<Value> = message_search::tokenize(text)
+            .into_iter()
+            .map(|t| json!({"stringValue": t}))
+            .collect();
+        fields["search_tokens"] = json!({"arrayValue": {"values": tokens}});
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        // Track message in the chat session's message_ids array
+        // (mirrors Python's add_message_to_chat_session)
+        if let Some(ref session) = effective_session_id {
+            if let Err(e) = self
+                .add_message_to_chat_session(uid, session, &message_id)
+                .await
+            {
+                tracing::warn!("Failed to track message in chat session: {}", e);
+                // Non-fatal — message is already saved
+            }
+        }
+
+        let message = MessageDB {
+            id: message_id.clone(),
+            text: text.to_string(),
+            created_at: now,
+            sender: sender.to_string(),
+            app_id: app_id.map(|s| s.to_string()),
+            session_id: effective_session_id,
+            rating: None,
+            reported: false,
+            metadata: metadata.map(|s| s.to_string()),
+            attachments: attachments.to_vec(),
+            provenance: provenance.clone(),
+        };
+
+        tracing::info!(
+            "Saved {} message {} for user {} (app_id={:?})",
+            sender,
+            message_id,
+            uid,
+            app_id
+        );
+        Ok(message)
+    }
+
+    /// Get chat messages for a user with optional app_id and session_id filter
+    pub async fn get_messages(
+        &self,
+        uid: &str,
+        app_id: Option<&str>,
+        session_id: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<MessageDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        // Build filters
+        let mut filters: Vec<Value> = Vec::new();
+
+        if let Some(app) = app_id {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "app_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": app}
+                }
+            }));
+        }
+
+        if let Some(session) = session_id {
+            // NOTE: Use chat_session_id for backward compatibility
+            // Old messages from Flutter app only have chat_session_id field
+            // New messages have both session_id and chat_session_id
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "chat_session_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": session}
+                }
+            }));
+        }
+
+        // Build the where clause
+        let where_clause = if filters.is_empty() {
+            None
+        } else if filters.len() == 1 {
+            Some(filters.into_iter().next().unwrap())
+        } else {
+            Some(json!({
+                "compositeFilter": {
+                    "op": "AND",
+                    "filters": filters
+                }
+            }))
+        };
+
+        // Build structured query - order by created_at descending to get most recent messages first
+        // The UI will reverse to display in chronological order
+        let mut structured_query = json!({
+            "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit,
+            "offset": offset
+        });
+
+        if let Some(where_filter) = where_clause {
+            structured_query["where"] = where_filter;
+        }
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let messages = results
+            .into_iter()
+            .filter_map(|doc| {
+                doc.get("document")
+                    .and_then(|d| self.parse_message(d, uid).ok())
+            })
+            .collect();
+
+        Ok(messages)
+    }
+
+    /// Newest messages whose search_tokens contain `token`, optionally in one app or session
+    pub async fn search_messages(
+        &self,
+        uid: &str,
+        token: &str,
+        app_id: Option<&str>,
+        session_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<MessageDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let mut filters = vec![json!({
+            "fieldFilter": {
+                "field": {"fieldPath": "search_tokens"},
+                "op": "ARRAY_CONTAINS",
+                "value": {"stringValue": token}
+            }
+        })];
+        if let Some(app) = app_id {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "app_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": app}
+                }
+            }));
+        }
+        if let Some(session) = session_id {
+            filters.push(json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "chat_session_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": session}
+                }
+            }));
+        }
+        let where_clause = if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            json!({"compositeFilter": {"op": "AND", "filters": filters}})
+        };
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
+                "where": where_clause,
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit
+            }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs.iter().filter_map(|doc| self.parse_message(doc, uid).ok()).collect())
+    }
+
+    /// The message just before (or after) `created_at` in a chat session
+    pub async fn get_adjacent_message(
+        &self,
+        uid: &str,
+        session_id: &str,
+        created_at: DateTime<Utc>,
+        before: bool,
+    ) -> Result<Option<MessageDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let (op, direction) = if before {
+            ("LESS_THAN", "DESCENDING")
+        } else {
+            ("GREATER_THAN", "ASCENDING")
+        };
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "chat_session_id"},
+                                "op": "EQUAL",
+                                "value": {"stringValue": session_id}
+                            }},
+                            {"fieldFilter": {
+                                "field": {"fieldPath": "created_at"},
+                                "op": op,
+                                "value": {"timestampValue": created_at.to_rfc3339()}
+                            }}
+                        ]
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": direction}],
+                "limit": 1
+            }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs.first().and_then(|doc| self.parse_message(doc, uid).ok()))
+    }
+
+    /// Delete chat messages for a user with optional app_id filter
+    /// Returns the count of deleted messages
+    pub async fn delete_messages(
+        &self,
+        uid: &str,
+        app_id: Option<&str>,
+    ) -> Result<usize, FirestoreError> {
+        // First, get all message IDs to delete
+        let messages = self.get_messages(uid, app_id, None, 1000, 0).await?;
+        let count = messages.len();
+
+        if count == 0 {
+            return Ok(0);
+        }
+
+        // Delete each message
+        for message in messages {
+            let url = format!(
+                "{}/{}/{}/{}/{}",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                MESSAGES_SUBCOLLECTION,
+                message.id
+            );
+
+            let response = self
+                .build_request(reqwest::Method::DELETE, &url)
+                .await?
+                .send()
+                .await?;
+
+            if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+                let error_text = response.text().await?;
+                tracing::error!("Failed to delete message {}: {}", message.id, error_text);
+            }
+        }
+
+        tracing::info!(
+            "Deleted {} messages for user {} (app_id={:?})",
+            count,
+            uid,
+            app_id
+        );
+        Ok(count)
+    }
+
+    /// Update a message's rating (thumbs up/down) and return the rated message
+    /// rating: 1 = thumbs up, -1 = thumbs down, None = clear rating
+    pub async fn update_message_rating(
+        &self,
+        uid: &str,
+        message_id: &str,
+        rating: Option<i32>,
+    ) -> Result<MessageDB, FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            MESSAGES_SUBCOLLECTION,
+            message_id
+        );
+
+        // Read the message first: it's returned so callers know what was rated
+        let get_response = self
+            .build_request(reqwest::Method::GET, &format!("https://firestore.googleapis.com/v1/{}", doc_name))
+            .await?
+            .send()
+            .await?;
+
+        if get_response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(FirestoreError::NotFound("Message not found".to_string()));
+        }
+
+        if !get_response.status().is_success() {
+            let error_text = get_response.text().await?;
+            return Err(format!("Failed to get message: {}", error_text).into());
+        }
+
+        let doc: Value = get_response.json().await?;
+        let mut message = self.parse_message(&doc, uid)?;
+
+        // Only the rating is written, so the rest of the message is left as it is
+        let fields = json!({
+            "rating": match rating {
+                Some(r) => json!({"integerValue": r.to_string()}),
+                None => json!({"nullValue": null}),
+            }
+        });
+        self.patch_document_fields(&doc_name, fields, &["rating"])
+            .await
+            .map_err(|e| format!("Failed to update message rating: {}", e))?;
+
+        tracing::info!(
+            "Updated rating for message {} (user {}): {:?}",
+            message_id,
+            uid,
+            rating
+        );
+
+        message.rating = rating;
+        Ok(message)
+    }
+
+    /// Parse a Firestore document into a MessageDB
+    /// Decrypts text if data_protection_level is "enhanced" and encryption secret is available.
+    fn parse_message(&self, doc: &Value, uid: &str) -> Result<MessageDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        // Stored as `files`; entries missing an ID or storage path are skipped
+        let attachments: Vec<Value> = match object.remove("files") {
+            Some(Value::Array(files)) => files
+                .into_iter()
+                .filter(|f| serde_json::from_value::<MessageAttachment>(f.clone()).is_ok())
+                .collect(),
+            _ => vec![],
+        };
+        object.insert("attachments".to_string(), Value::Array(attachments));
+        let enhanced = object.get("data_protection_level").and_then(|l| l.as_str()) == Some("enhanced");
+
+        let mut message: MessageDB = serde_json::from_value(Value::Object(object))?;
+
+        if enhanced {
+            if let Some(ref secret) = self.encryption_secret {
+                match encryption::decrypt(&message.text, uid, secret) {
+                    Ok(decrypted) => message.text = decrypted,
+                    Err(e) => {
+                        tracing::warn!("Failed to decrypt message {}: {}", message.id, e);
+                        message.text = "[Encrypted message — decryption failed]".to_string();
+                    }
+                }
+            } else {
+                tracing::warn!(
+                    "Message {} has enhanced protection but no encryption secret configured",
+                    message.id
+                );
+                message.text = "[Encrypted message — decryption failed]".to_string();
+            }
+        }
+
+        Ok(message)
+    }
+
+    fn write_provenance(fields: &mut Value, provenance: &LlmProvenance) {
+        for (key, value) in [
+            ("model", &provenance.model),
+            ("prompt_version", &provenance.prompt_version),
+            ("route", &provenance.route),
+        ] {
+            if let Some(value) = value {
+                fields[key] = json!({"stringValue": value});
+            }
+        }
+    }
+
+    fn parse_provenance(&self, fields: &Value) -> LlmProvenance {
+        LlmProvenance {
+            model: self.parse_string(fields, "model"),
+            prompt_version: self.parse_string(fields, "prompt_version"),
+            route: self.parse_string(fields, "route"),
+        }
+    }
+
+    // =========================================================================
+    // CHAT ATTACHMENTS
+    // =========================================================================
+
+    /// Upload an attachment to GCS and record it under users/{uid}/files/{id}
+    /// `data` may be a streaming body; `size` is its length in bytes.
+    pub async fn upload_chat_attachment(
+        &self,
+        uid: &str,
+        bucket: &str,
+        name: &str,
+        mime_type: &str,
+        data: reqwest::Body,
+        size: i64,
+    ) -> Result<MessageAttachment, FirestoreError> {
+        let attachment_id = uuid::Uuid::new_v4().to_string();
+        // Keep the object name free of path separators from the client
+        let safe_name: String = name
+            .chars()
+            .map(|c| if c == '/' || c == '\\' || c.is_control() { '_' } else { c })
+            .collect();
+        let storage_path = format!("chat_attachments/{}/{}/{}", uid, attachment_id, safe_name);
+
+        let upload_url = format!(
+            "https://storage.googleapis.com/upload/storage/v1/b/{}/o?uploadType=media&name={}",
+            bucket,
+            urlencoding::encode(&storage_path)
+        );
+
+        let response = self
+            .build_request(reqwest::Method::POST, &upload_url)
+            .await?
+            .header("Content-Type", mime_type)
+            .header("Content-Length", size)
+            .body(data)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "GCS upload error", &error_text));
+        }
+
+        let attachment = MessageAttachment {
+            id: attachment_id.clone(),
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            size,
+            storage_path,
+            created_at: Utc::now(),
+        };
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FILES_SUBCOLLECTION,
+            attachment_id
+        );
+
+        let doc = json!({"fields": Self::attachment_to_value(&attachment)["mapValue"]["fields"]});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!(
+            "Uploaded chat attachment {} ({} bytes) for user {}",
+            attachment.id,
+            attachment.size,
+            uid
+        );
+        Ok(attachment)
+    }
+
+    /// Get a chat attachment record by ID
+    pub async fn get_chat_attachment(
+        &self,
+        uid: &str,
+        attachment_id: &str,
+    ) -> Result<Option<MessageAttachment>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FILES_SUBCOLLECTION,
+            attachment_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        Ok(doc
+            .get("fields")
+            .and_then(|f| self.parse_attachment_fields(f)))
+    }
+
+    /// Get several chat attachments; missing IDs are skipped
+    pub async fn get_chat_attachments(
+        &self,
+        uid: &str,
+        attachment_ids: &[String],
+    ) -> Result<Vec<MessageAttachment>, FirestoreError> {
+        let mut attachments = Vec::with_capacity(attachment_ids.len());
+        for id in attachment_ids {
+            if let Some(attachment) = self.get_chat_attachment(uid, id).await? {
+                attachments.push(attachment);
+            }
+        }
+        Ok(attachments)
+    }
+
+    /// Download an attachment's bytes from GCS
+    pub async fn download_chat_attachment(
+        &self,
+        bucket: &str,
+        attachment: &MessageAttachment,
+    ) -> Result<Vec<u8>, FirestoreError> {
+        let url = format!(
+            "https://storage.googleapis.com/storage/v1/b/{}/o/{}?alt=media",
+            bucket,
+            urlencoding::encode(&attachment.storage_path)
+        );
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "GCS download error", &error_text));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    fn attachment_to_value(attachment: &MessageAttachment) -> Value {
+        json!({"mapValue": {"fields": {
+            "id": {"stringValue": attachment.id},
+            "name": {"stringValue": attachment.name},
+            "mime_type": {"stringValue": attachment.mime_type},
+            "size": {"integerValue": attachment.size.to_string()},
+            "storage_path": {"stringValue": attachment.storage_path},
+            "created_at": {"timestampValue": attachment.created_at.to_rfc3339()}
+        }}})
+    }
+
+    fn parse_attachment_fields(&self, fields: &Value) -> Option<MessageAttachment> {
+        Some(MessageAttachment {
+            id: self.parse_string(fields, "id")?,
+            name: self.parse_string(fields, "name").unwrap_or_default(),
+            mime_type: self
+                .parse_string(fields, "mime_type")
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            size: self.parse_int(fields, "size").unwrap_or(0) as i64,
+            storage_path: self.parse_string(fields, "storage_path")?,
+            created_at: self
+                .parse_timestamp_optional(fields, "created_at")
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    // =========================================================================
+    // DATA EXPORTS
+    // =========================================================================
+
+    /// Create or replace an export record
+    pub async fn save_data_export(
+        &self,
+        export: &DataExport,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), DATA_EXPORTS_COLLECTION, export.id);
+        let fields = firestore_serde::to_fields(export, &["id"])?;
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to save data export", &error_text));
+        }
+        Ok(())
+    }
+
+    /// An export record by ID
+    pub async fn get_data_export(
+        &self,
+        export_id: &str,
+    ) -> Result<Option<DataExport>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), DATA_EXPORTS_COLLECTION, export_id);
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to get data export", &error_text));
+        }
+        let doc: Value = response.json().await?;
+        Ok(Some(firestore_serde::from_document(&doc)?))
+    }
+
+    /// A user's exports, newest first
+    pub async fn get_user_data_exports(
+        &self,
+        uid: &str,
+        limit: usize,
+    ) -> Result<Vec<DataExport>, FirestoreError> {
+        let structured_query = json!({
+            "from": [{"collectionId": DATA_EXPORTS_COLLECTION}],
+            "where": {
+                "fieldFilter": {
+                    "field": {"fieldPath": "uid"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": uid}
+                }
+            },
+            "limit": limit
+        });
+
+        let docs = self
+            .run_user_query(&self.base_url(), &json!({"structuredQuery": structured_query}))
+            .await?;
+        let mut exports: Vec<DataExport> = docs
+            .iter()
+            .filter_map(|doc| firestore_serde::from_document(doc).ok())
+            .collect();
+        exports.sort_by_key(|export: &DataExport| std::cmp::Reverse(export.created_at));
+        Ok(exports)
+    }
+
+    /// Exports whose archives are due for deletion
+    pub async fn get_expired_data_exports(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<DataExport>, FirestoreError> {
+        let structured_query = json!({
+            "from": [{"collectionId": DATA_EXPORTS_COLLECTION}],
+            "where": {
+                "fieldFilter": {
+                    "field": {"fieldPath": "expires_at"},
+                    "op": "LESS_THAN",
+                    "value": {"timestampValue": now.to_rfc3339()}
+                }
+            },
+            "limit": limit
+        });
+
+        let docs = self
+            .run_user_query(&self.base_url(), &json!({"structuredQuery": structured_query}))
+            .await?;
+        Ok(docs
+            .iter()
+            .filter_map(|doc| firestore_serde::from_document(doc).ok())
+            .collect())
+    }
+
+    /// Delete an export record
+    pub async fn delete_data_export(&self, export_id: &str) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), DATA_EXPORTS_COLLECTION, export_id);
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to delete data export", &error_text));
+        }
+        Ok(())
+    }
+
+    /// Upload an export archive to GCS; `data` may be a streaming body of `size` bytes
+    pub async fn upload_export_archive(
+        &self,
+        bucket: &str,
+        storage_path: &str,
+        data: reqwest::Body,
+        size: u64,
+    ) -> Result<(), FirestoreError> {
+        let upload_url = format!(
+            "https://storage.googleapis.com/upload/storage/v1/b/{}/o?uploadType=media&name={}",
+            bucket,
+            urlencoding::encode(storage_path)
+        );
+
+        let response = self
+            .build_request(reqwest::Method::POST, &upload_url)
+            .await?
+            .header("Content-Type", "application/gzip")
+            .header("Content-Length", size)
+            .body(data)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "GCS upload error", &error_text));
+        }
+        Ok(())
+    }
+
+    /// Start reading an export archive from GCS. `range` and `if_range` are passed on
+    /// as-is, so the response is GCS's own 200, 206 or 416 with its range headers.
+    pub async fn open_export_archive(
+        &self,
+        bucket: &str,
+        storage_path: &str,
+        range: Option<&str>,
+        if_range: Option<&str>,
+    ) -> Result<reqwest::Response, FirestoreError> {
+        let url = format!(
+            "https://storage.googleapis.com/storage/v1/b/{}/o/{}?alt=media",
+            bucket,
+            urlencoding::encode(storage_path)
+        );
+
+        let mut request = self.build_request(reqwest::Method::GET, &url).await?;
+        if let Some(range) = range {
+            request = request.header(reqwest::header::RANGE, range);
+        }
+        if let Some(if_range) = if_range {
+            request = request.header(reqwest::header::IF_RANGE, if_range);
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "GCS download error", &error_text));
+        }
+        Ok(response)
+    }
+
+    /// Delete an object from GCS; a missing object counts as deleted
+    pub async fn delete_gcs_object(
+        &self,
+        bucket: &str,
+        storage_path: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "https://storage.googleapis.com/storage/v1/b/{}/o/{}",
+            bucket,
+            urlencoding::encode(storage_path)
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "GCS delete error", &error_text));
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // FOLDERS
+    // =========================================================================
+
+    /// Get all folders for a user
+    pub async fn get_folders(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<Folder>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let structured_query = json!({
+            "from": [{"collectionId": FOLDERS_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "order"}, "direction": "ASCENDING"}]
+        });
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(vec![]);
         }
 
-        // Handle plain array format
-        let segments = transcript_field
-            .and_then(|s| s.get("arrayValue"))
-            .and_then(|a| a.get("values"))
-            .and_then(|v| v.as_array());
+        let results: Vec<Value> = response.json().await?;
+        let folders = results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_folder(d).ok()))
+            .collect();
 
-        if let Some(segs) = segments {
-            Ok(segs
-                .iter()
-                .filter_map(|seg| {
-                    let seg_fields = seg.get("mapValue")?.get("fields")?;
-                    Some(TranscriptSegment {
-                        text: self.parse_string(seg_fields, "text").unwrap_or_default(),
-                        speaker: self.parse_string(seg_fields, "speaker").unwrap_or_else(|| "SPEAKER_00".to_string()),
-                        speaker_id: self.parse_int(seg_fields, "speaker_id").unwrap_or(0),
-                        is_user: self.parse_bool(seg_fields, "is_user").unwrap_or(false),
-                        person_id: self.parse_string(seg_fields, "person_id"),
-                        start: self.parse_float(seg_fields, "start").unwrap_or(0.0),
-                        end: self.parse_float(seg_fields, "end").unwrap_or(0.0),
-                    })
-                })
-                .collect())
-        } else {
-            Ok(vec![])
-        }
+        Ok(folders)
     }
 
-    /// Decompress zlib-compressed transcript segments from base64-encoded bytes
-    fn decompress_transcript_segments(
+    /// Create a new folder
+    pub async fn create_folder(
         &self,
-        b64_str: &str,
-    ) -> Result<Vec<TranscriptSegment>, Box<dyn std::error::Error + Send + Sync>> {
-        use flate2::read::ZlibDecoder;
-        use std::io::Read;
+        uid: &str,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<Folder, FirestoreError> {
+        let folder_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
 
-        // Decode base64 to bytes
-        let compressed_bytes = base64::Engine::decode(
-            &base64::engine::general_purpose::STANDARD,
-            b64_str,
-        )?;
+        let existing = self.get_folders(uid).await?;
+        let order = existing.len() as i32;
 
-        // Decompress with zlib
-        let mut decoder = ZlibDecoder::new(&compressed_bytes[..]);
-        let mut decompressed = String::new();
-        decoder.read_to_string(&mut decompressed)?;
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOLDERS_SUBCOLLECTION,
+            folder_id
+        );
 
-        // Parse JSON array of segments
-        let segments: Vec<serde_json::Value> = serde_json::from_str(&decompressed)?;
+        let mut fields = json!({
+            "name": {"stringValue": name},
+            "color": {"stringValue": color.unwrap_or("#6B7280")},
+            "order": {"integerValue": order.to_string()},
+            "is_default": {"booleanValue": false},
+            "is_system": {"booleanValue": false},
+            "conversation_count": {"integerValue": "0"},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-        // Convert to TranscriptSegment
-        Ok(segments
-            .iter()
-            .filter_map(|seg| {
-                Some(TranscriptSegment {
-                    text: seg.get("text")?.as_str()?.to_string(),
-                    speaker: seg
-                        .get("speaker")
-                        .and_then(|s| s.as_str())
-                        .unwrap_or("SPEAKER_00")
-                        .to_string(),
-                    speaker_id: seg
-                        .get("speaker_id")
-                        .and_then(|s| s.as_i64())
-                        .unwrap_or(0) as i32,
-                    is_user: seg
-                        .get("is_user")
-                        .and_then(|s| s.as_bool())
-                        .unwrap_or(false),
-                    person_id: seg
-                        .get("person_id")
-                        .and_then(|s| s.as_str())
-                        .map(|s| s.to_string()),
-                    start: seg
-                        .get("start")
-                        .and_then(|s| s.as_f64())
-                        .unwrap_or(0.0),
-                    end: seg
-                        .get("end")
-                        .and_then(|s| s.as_f64())
-                        .unwrap_or(0.0),
-                })
-            })
-            .collect())
+        if let Some(desc) = description {
+            fields["description"] = json!({"stringValue": desc});
+        }
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!("Created folder {} for user {}", folder_id, uid);
+
+        Ok(Folder {
+            id: folder_id,
+            name: name.to_string(),
+            description: description.map(|s| s.to_string()),
+            color: color.unwrap_or("#6B7280").to_string(),
+            created_at: now,
+            updated_at: now,
+            order,
+            is_default: false,
+            is_system: false,
+            category_mapping: None,
+            conversation_count: 0,
+        })
     }
 
-    /// Convert conversation to Firestore document format
-    /// Compresses transcript_segments with zlib to match Python backend format.
-    /// If encryption_secret is available, also encrypts (enhanced protection).
-    fn conversation_to_firestore(&self, conv: &Conversation, uid: &str) -> Value {
-        // Build action_items array for structured
-        let action_items_values: Vec<Value> = conv.structured.action_items.iter().map(|item| {
-            let mut fields = serde_json::Map::new();
-            fields.insert("description".to_string(), json!({"stringValue": item.description}));
-            fields.insert("completed".to_string(), json!({"booleanValue": item.completed}));
-            if let Some(due_at) = &item.due_at {
-                fields.insert("due_at".to_string(), json!({"timestampValue": due_at.to_rfc3339()}));
-            }
-            json!({"mapValue": {"fields": fields}})
-        }).collect();
+    /// Update a folder
+    pub async fn update_folder(
+        &self,
+        uid: &str,
+        folder_id: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+        color: Option<&str>,
+        order: Option<i32>,
+    ) -> Result<Folder, FirestoreError> {
+        let now = Utc::now();
+        let mut field_paths: Vec<&str> = vec!["updated_at"];
+        let mut fields = json!({
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-        // Build events array for structured
-        let events_values: Vec<Value> = conv.structured.events.iter().map(|event| {
-            json!({
-                "mapValue": {
-                    "fields": {
-                        "title": {"stringValue": event.title},
-                        "description": {"stringValue": event.description},
-                        "start": {"timestampValue": event.start.to_rfc3339()},
-                        "duration": {"integerValue": event.duration.to_string()}
-                    }
-                }
-            })
-        }).collect();
+        if let Some(n) = name {
+            field_paths.push("name");
+            fields["name"] = json!({"stringValue": n});
+        }
 
-        // Build apps_results array
-        let apps_results_values: Vec<Value> = conv.apps_results.iter().map(|result| {
-            let mut fields = serde_json::Map::new();
-            if let Some(app_id) = &result.app_id {
-                fields.insert("app_id".to_string(), json!({"stringValue": app_id}));
-            }
-            fields.insert("content".to_string(), json!({"stringValue": result.content}));
-            json!({"mapValue": {"fields": fields}})
-        }).collect();
+        if let Some(d) = description {
+            field_paths.push("description");
+            fields["description"] = json!({"stringValue": d});
+        }
 
-        // Build the main document
-        let mut fields = serde_json::Map::new();
+        if let Some(c) = color {
+            field_paths.push("color");
+            fields["color"] = json!({"stringValue": c});
+        }
 
-        // CRITICAL: Include the id field - Python backend requires this
-        fields.insert("id".to_string(), json!({"stringValue": conv.id}));
-        fields.insert("created_at".to_string(), json!({"timestampValue": conv.created_at.to_rfc3339()}));
-        fields.insert("started_at".to_string(), json!({"timestampValue": conv.started_at.to_rfc3339()}));
-        fields.insert("finished_at".to_string(), json!({"timestampValue": conv.finished_at.to_rfc3339()}));
-        fields.insert("source".to_string(), json!({"stringValue": format!("{:?}", conv.source).to_lowercase()}));
-        fields.insert("language".to_string(), json!({"stringValue": conv.language}));
-        fields.insert("status".to_string(), json!({"stringValue": format!("{:?}", conv.status).to_lowercase()}));
-        fields.insert("discarded".to_string(), json!({"booleanValue": conv.discarded}));
-        fields.insert("deleted".to_string(), json!({"booleanValue": conv.deleted}));
-        fields.insert("starred".to_string(), json!({"booleanValue": conv.starred}));
-        fields.insert("is_locked".to_string(), json!({"booleanValue": conv.is_locked}));
+        if let Some(o) = order {
+            field_paths.push("order");
+            fields["order"] = json!({"integerValue": o.to_string()});
+        }
 
-        // Add folder_id if present
-        if let Some(folder_id) = &conv.folder_id {
-            fields.insert("folder_id".to_string(), json!({"stringValue": folder_id}));
+        let update_mask = field_paths.iter().map(|f| format!("updateMask.fieldPaths={}", f)).collect::<Vec<_>>().join("&");
+
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOLDERS_SUBCOLLECTION,
+            folder_id,
+            update_mask
+        );
+
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        // Add geolocation if present
-        if let Some(geo) = &conv.geolocation {
-            let mut geo_fields = serde_json::Map::new();
-            if let Some(place_id) = &geo.google_place_id {
-                geo_fields.insert("google_place_id".to_string(), json!({"stringValue": place_id}));
-            }
-            geo_fields.insert("latitude".to_string(), json!({"doubleValue": geo.latitude}));
-            geo_fields.insert("longitude".to_string(), json!({"doubleValue": geo.longitude}));
-            if let Some(address) = &geo.address {
-                geo_fields.insert("address".to_string(), json!({"stringValue": address}));
+        let updated_doc: Value = response.json().await?;
+        let folder = self.parse_folder(&updated_doc)?;
+
+        tracing::info!("Updated folder {} for user {}", folder_id, uid);
+        Ok(folder)
+    }
+
+    /// Delete a folder
+    pub async fn delete_folder(
+        &self,
+        uid: &str,
+        folder_id: &str,
+        move_to_folder_id: Option<&str>,
+    ) -> Result<(), FirestoreError> {
+        if let Some(target_id) = move_to_folder_id {
+            let conversations = self.get_conversations(uid, 100, 0, true, &[], None, Some(folder_id), None, None).await?;
+            for conv in conversations {
+                let _ = self.set_conversation_folder(uid, &conv.id, Some(target_id)).await;
             }
-            if let Some(loc_type) = &geo.location_type {
-                geo_fields.insert("location_type".to_string(), json!({"stringValue": loc_type}));
+        } else {
+            let conversations = self.get_conversations(uid, 100, 0, true, &[], None, Some(folder_id), None, None).await?;
+            for conv in conversations {
+                let _ = self.set_conversation_folder(uid, &conv.id, None).await;
             }
-            fields.insert("geolocation".to_string(), json!({"mapValue": {"fields": geo_fields}}));
         }
 
-        // Add photos array
-        if !conv.photos.is_empty() {
-            let photos_values: Vec<Value> = conv.photos.iter().map(|photo| {
-                let mut photo_fields = serde_json::Map::new();
-                if let Some(id) = &photo.id {
-                    photo_fields.insert("id".to_string(), json!({"stringValue": id}));
-                }
-                photo_fields.insert("base64".to_string(), json!({"stringValue": &photo.base64}));
-                if let Some(desc) = &photo.description {
-                    photo_fields.insert("description".to_string(), json!({"stringValue": desc}));
-                }
-                photo_fields.insert("created_at".to_string(), json!({"timestampValue": photo.created_at.to_rfc3339()}));
-                photo_fields.insert("discarded".to_string(), json!({"booleanValue": photo.discarded}));
-                json!({"mapValue": {"fields": photo_fields}})
-            }).collect();
-            fields.insert("photos".to_string(), json!({"arrayValue": {"values": photos_values}}));
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOLDERS_SUBCOLLECTION,
+            folder_id
+        );
+
+        let response = self.build_request(reqwest::Method::DELETE, &url).await?.send().await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        // Build structured with action_items and events
-        let mut structured_fields = serde_json::Map::new();
-        structured_fields.insert("title".to_string(), json!({"stringValue": conv.structured.title}));
-        structured_fields.insert("overview".to_string(), json!({"stringValue": conv.structured.overview}));
-        structured_fields.insert("emoji".to_string(), json!({"stringValue": conv.structured.emoji}));
-        structured_fields.insert("category".to_string(), json!({"stringValue": format!("{:?}", conv.structured.category).to_lowercase()}));
-        structured_fields.insert("action_items".to_string(), json!({"arrayValue": {"values": action_items_values}}));
-        structured_fields.insert("events".to_string(), json!({"arrayValue": {"values": events_values}}));
+        self.record_deletion(uid, FOLDERS_SUBCOLLECTION, folder_id).await;
+        tracing::info!("Deleted folder {} for user {}", folder_id, uid);
+        Ok(())
+    }
 
-        fields.insert("structured".to_string(), json!({"mapValue": {"fields": structured_fields}}));
+    /// Set conversation folder
+    pub async fn set_conversation_folder(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=folder_id&updateMask.fieldPaths=updated_at",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
+        );
 
-        // Add transcript_segments — compressed (and optionally encrypted) to match Python backend
-        {
-            use flate2::write::ZlibEncoder;
-            use flate2::Compression;
-            use std::io::Write;
+        let folder_value = match folder_id {
+            Some(fid) => json!({"stringValue": fid}),
+            None => json!({"nullValue": null}),
+        };
+        let doc = json!({"fields": {
+            "folder_id": folder_value,
+            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+        }});
 
-            // Step 1: Serialize segments to JSON array (matching Python's json.dumps format)
-            let segments_json: Vec<serde_json::Value> = conv.transcript_segments.iter().map(|seg| {
-                json!({
-                    "text": seg.text,
-                    "speaker": seg.speaker,
-                    "speaker_id": seg.speaker_id,
-                    "is_user": seg.is_user,
-                    "start": seg.start,
-                    "end": seg.end
-                })
-            }).collect();
-            let json_str = serde_json::to_string(&segments_json).unwrap_or_else(|_| "[]".to_string());
+        let response = self.build_request(reqwest::Method::PATCH, &url).await?.json(&doc).send().await?;
 
-            // Step 2: Zlib compress
-            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-            let _ = encoder.write_all(json_str.as_bytes());
-            let compressed_bytes = encoder.finish().unwrap_or_default();
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
 
-            // Step 3: Store as compressed bytes or encrypt if secret is available
-            if let Some(ref secret) = self.encryption_secret {
-                // Enhanced: hex encode compressed bytes → encrypt → store as stringValue
-                let hex_str = hex::encode(&compressed_bytes);
-                match encryption::encrypt(&hex_str, uid, secret) {
-                    Ok(encrypted) => {
-                        fields.insert("transcript_segments".to_string(), json!({"stringValue": encrypted}));
-                        fields.insert("data_protection_level".to_string(), json!({"stringValue": "enhanced"}));
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to encrypt transcript segments: {}, falling back to compressed bytes", e);
-                        let b64 = base64::engine::general_purpose::STANDARD.encode(&compressed_bytes);
-                        fields.insert("transcript_segments".to_string(), json!({"bytesValue": b64}));
-                    }
-                }
-            } else {
-                // Standard: store as bytesValue (Firestore REST API expects base64 for bytes)
-                let b64 = base64::engine::general_purpose::STANDARD.encode(&compressed_bytes);
-                fields.insert("transcript_segments".to_string(), json!({"bytesValue": b64}));
+        tracing::info!("Set conversation {} folder to {:?} for user {}", conversation_id, folder_id, uid);
+        Ok(())
+    }
+
+    /// Bulk move conversations to a folder
+    pub async fn bulk_move_to_folder(
+        &self,
+        uid: &str,
+        folder_id: &str,
+        conversation_ids: &[String],
+    ) -> Result<i32, FirestoreError> {
+        let mut moved_count = 0;
+        for conv_id in conversation_ids {
+            if self.set_conversation_folder(uid, conv_id, Some(folder_id)).await.is_ok() {
+                moved_count += 1;
             }
-            fields.insert("transcript_segments_compressed".to_string(), json!({"booleanValue": true}));
         }
+        tracing::info!("Bulk moved {} conversations to folder {} for user {}", moved_count, folder_id, uid);
+        Ok(moved_count)
+    }
 
-        // Add apps_results
-        fields.insert("apps_results".to_string(), json!({"arrayValue": {"values": apps_results_values}}));
+    /// Reorder folders
+    pub async fn reorder_folders(
+        &self,
+        uid: &str,
+        folder_ids: &[String],
+    ) -> Result<(), FirestoreError> {
+        for (index, folder_id) in folder_ids.iter().enumerate() {
+            let url = format!(
+                "{}/{}/{}/{}/{}?updateMask.fieldPaths=order",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                FOLDERS_SUBCOLLECTION,
+                folder_id
+            );
 
-        // Add input_device_name if present
-        if let Some(device_name) = &conv.input_device_name {
-            fields.insert("input_device_name".to_string(), json!({"stringValue": device_name}));
+            let doc = json!({"fields": {"order": {"integerValue": index.to_string()}}});
+            let _ = self.build_request(reqwest::Method::PATCH, &url).await?.json(&doc).send().await;
         }
-
-        json!({"fields": fields})
+        tracing::info!("Reordered {} folders for user {}", folder_ids.len(), uid);
+        Ok(())
     }
 
-    // Field parsing helpers
-    fn parse_string(&self, fields: &Value, key: &str) -> Option<String> {
-        fields.get(key)?.get("stringValue")?.as_str().map(|s| s.to_string())
-    }
+    /// Create or replace a folder rule
+    pub async fn save_folder_rule(
+        &self,
+        uid: &str,
+        rule: &FolderRule,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOLDER_RULES_SUBCOLLECTION,
+            rule.id
+        );
 
-    fn parse_bool(&self, fields: &Value, key: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        fields
-            .get(key)
-            .and_then(|v| v.get("booleanValue"))
-            .and_then(|v| v.as_bool())
-            .ok_or_else(|| format!("Missing or invalid bool field: {}", key).into())
-    }
+        let fields = firestore_serde::to_fields(rule, &["id"])?;
 
-    fn parse_int(&self, fields: &Value, key: &str) -> Option<i32> {
-        fields
-            .get(key)?
-            .get("integerValue")?
-            .as_str()
-            .and_then(|s| s.parse().ok())
-    }
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
 
-    fn parse_float(&self, fields: &Value, key: &str) -> Option<f64> {
-        fields.get(key)?.get("doubleValue")?.as_f64()
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore save folder rule error", &error_text));
+        }
+        Ok(())
     }
 
-    fn parse_timestamp(
+    /// All of a user's folder rules, in evaluation order
+    pub async fn get_folder_rules(
         &self,
-        fields: &Value,
-        key: &str,
-    ) -> Result<DateTime<Utc>, Box<dyn std::error::Error + Send + Sync>> {
-        let ts = fields
-            .get(key)
-            .and_then(|v| v.get("timestampValue"))
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| format!("Missing timestamp field: {}", key))?;
+        uid: &str,
+    ) -> Result<Vec<FolderRule>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        DateTime::parse_from_rfc3339(ts)
-            .map(|dt| dt.with_timezone(&Utc))
-            .map_err(|e| format!("Invalid timestamp {}: {}", key, e).into())
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": FOLDER_RULES_SUBCOLLECTION}],
+                "limit": crate::models::MAX_FOLDER_RULES
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut rules: Vec<FolderRule> = results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| firestore_serde::from_document(d).ok()))
+            .collect();
+        rules.sort_by_key(|rule: &FolderRule| (rule.order, rule.created_at));
+        Ok(rules)
     }
 
-    fn parse_timestamp_optional(&self, fields: &Value, key: &str) -> Option<DateTime<Utc>> {
-        fields
-            .get(key)
-            .and_then(|v| v.get("timestampValue"))
-            .and_then(|v| v.as_str())
-            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
-            .map(|dt| dt.with_timezone(&Utc))
+    /// Delete a folder rule
+    pub async fn delete_folder_rule(
+        &self,
+        uid: &str,
+        rule_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            FOLDER_RULES_SUBCOLLECTION,
+            rule_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete folder rule error", &error_text));
+        }
+        Ok(())
     }
 
-    // =========================================================================
-    // USER SETTINGS
-    // =========================================================================
+    // ========================================
+    // GOALS METHODS
+    // ========================================
 
-    /// Get user document fields
-    async fn get_user_document(
+    /// Get all active goals for a user (max 3)
+    pub async fn get_user_goals(
         &self,
         uid: &str,
-    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        limit: usize,
+    ) -> Result<Vec<GoalDB>, FirestoreError> {
+        // Query the user's goals subcollection directly
+        let url = format!(
+            "{}/{}/{}:runQuery",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid
+        );
+
+        // Note: Don't use orderBy with where filter on different fields - requires composite index
+        // Instead, we sort in Rust after fetching (like Python backend does)
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": GOALS_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "is_active"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                }
+            }
+        });
 
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::POST, &url)
             .await?
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to get user document: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        Ok(doc)
+        let results: Vec<Value> = response.json().await?;
+        let mut goals = Vec::new();
+
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Ok(goal) = self.parse_goal(doc) {
+                    goals.push(goal);
+                }
+            }
+        }
+
+        // Sort by created_at descending (newest first) and apply limit
+        goals.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        goals.truncate(limit);
+
+        tracing::info!("Found {} active goals for user {}", goals.len(), uid);
+        Ok(goals)
     }
 
-    /// Update user document fields (partial update)
-    async fn update_user_fields(
+    /// Create a new goal for a user
+    /// If user already has 3 active goals, deactivates the oldest one
+    pub async fn create_goal(
         &self,
         uid: &str,
-        fields: Value,
-        update_mask: &[&str],
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mask_params = update_mask
-            .iter()
-            .map(|f| format!("updateMask.fieldPaths={}", f))
-            .collect::<Vec<_>>()
-            .join("&");
+        title: &str,
+        description: Option<&str>,
+        goal_type: GoalType,
+        target_value: f64,
+        current_value: f64,
+        min_value: f64,
+        max_value: f64,
+        unit: Option<&str>,
+        source: Option<&str>,
+    ) -> Result<GoalDB, FirestoreError> {
+        // Check existing active goals
+        let existing_goals = self.get_user_goals(uid, 10).await?;
+
+        // If we have 3 or more active goals, deactivate the oldest one
+        if existing_goals.len() >= 3 {
+            if let Some(oldest) = existing_goals.last() {
+                tracing::info!("Deactivating oldest goal {} to make room for new goal", oldest.id);
+                self.update_goal(uid, &oldest.id, None, None, None, None, None, None, None, Some(false), None).await?;
+            }
+        }
+
+        // Generate a unique ID
+        let now = Utc::now();
+        let goal_id = document_id_from_seed(&format!("{}-{}-{}", uid, title, now.timestamp_millis()));
 
         let url = format!(
-            "{}/{}/{}?{}",
+            "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            mask_params
+            GOALS_SUBCOLLECTION,
+            goal_id
         );
 
-        let doc = json!({ "fields": fields });
+        let mut fields = json!({
+            "title": {"stringValue": title},
+            "goal_type": {"stringValue": match goal_type {
+                GoalType::Boolean => "boolean",
+                GoalType::Scale => "scale",
+                GoalType::Numeric => "numeric",
+            }},
+            "target_value": {"doubleValue": target_value},
+            "current_value": {"doubleValue": current_value},
+            "min_value": {"doubleValue": min_value},
+            "max_value": {"doubleValue": max_value},
+            "is_active": {"booleanValue": true},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
+
+        if let Some(d) = description {
+            fields.as_object_mut().unwrap().insert(
+                "description".to_string(),
+                json!({"stringValue": d}),
+            );
+        }
+
+        if let Some(u) = unit {
+            fields.as_object_mut().unwrap().insert(
+                "unit".to_string(),
+                json!({"stringValue": u}),
+            );
+        }
+
+        if let Some(s) = source {
+            fields.as_object_mut().unwrap().insert(
+                "source".to_string(),
+                json!({"stringValue": s}),
+            );
+        }
+
+        let doc = json!({"fields": fields});
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -4698,680 +11884,951 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to update user fields: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create goal error", &error_text));
         }
 
-        Ok(())
-    }
-
-    /// Get daily summary settings for a user
-    pub async fn get_daily_summary_settings(
-        &self,
-        uid: &str,
-    ) -> Result<DailySummarySettings, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+        let goal = GoalDB {
+            id: goal_id,
+            title: title.to_string(),
+            description: description.map(|s| s.to_string()),
+            goal_type,
+            target_value,
+            current_value,
+            min_value,
+            max_value,
+            unit: unit.map(|s| s.to_string()),
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            source: source.map(|s| s.to_string()),
+        };
 
-        Ok(DailySummarySettings {
-            enabled: self.parse_bool(fields, "daily_summary_enabled").unwrap_or(true),
-            hour: self.parse_int(fields, "daily_summary_hour_local").unwrap_or(22),
-        })
+        tracing::info!("Created goal {} for user {}", goal.id, uid);
+        Ok(goal)
     }
 
-    /// Update daily summary settings for a user
-    pub async fn update_daily_summary_settings(
+    /// Update an existing goal
+    pub async fn update_goal(
         &self,
         uid: &str,
-        enabled: Option<bool>,
-        hour: Option<i32>,
-    ) -> Result<DailySummarySettings, Box<dyn std::error::Error + Send + Sync>> {
-        // Get current settings
-        let current = self.get_daily_summary_settings(uid).await?;
-
-        let new_enabled = enabled.unwrap_or(current.enabled);
-        let new_hour = hour.unwrap_or(current.hour);
-
-        let fields = json!({
-            "daily_summary_enabled": {"booleanValue": new_enabled},
-            "daily_summary_hour_local": {"integerValue": new_hour.to_string()}
-        });
-
-        self.update_user_fields(uid, fields, &["daily_summary_enabled", "daily_summary_hour_local"])
-            .await?;
-
-        Ok(DailySummarySettings {
-            enabled: new_enabled,
-            hour: new_hour,
-        })
-    }
+        goal_id: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        target_value: Option<f64>,
+        current_value: Option<f64>,
+        min_value: Option<f64>,
+        max_value: Option<f64>,
+        unit: Option<&str>,
+        is_active: Option<bool>,
+        completed_at: Option<DateTime<Utc>>,
+    ) -> Result<GoalDB, FirestoreError> {
+        // Build update mask and fields
+        let mut update_fields: Vec<&str> = vec![];
+        let mut fields = serde_json::Map::new();
+        let now = Utc::now();
 
-    /// Get transcription preferences for a user
-    pub async fn get_transcription_preferences(
-        &self,
-        uid: &str,
-    ) -> Result<TranscriptionPreferences, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+        if let Some(t) = title {
+            update_fields.push("title");
+            fields.insert("title".to_string(), json!({"stringValue": t}));
+        }
+        if let Some(d) = description {
+            update_fields.push("description");
+            fields.insert("description".to_string(), json!({"stringValue": d}));
+        }
+        if let Some(v) = target_value {
+            update_fields.push("target_value");
+            fields.insert("target_value".to_string(), json!({"doubleValue": v}));
+        }
+        if let Some(v) = current_value {
+            update_fields.push("current_value");
+            fields.insert("current_value".to_string(), json!({"doubleValue": v}));
+        }
+        if let Some(v) = min_value {
+            update_fields.push("min_value");
+            fields.insert("min_value".to_string(), json!({"doubleValue": v}));
+        }
+        if let Some(v) = max_value {
+            update_fields.push("max_value");
+            fields.insert("max_value".to_string(), json!({"doubleValue": v}));
+        }
+        if let Some(u) = unit {
+            update_fields.push("unit");
+            fields.insert("unit".to_string(), json!({"stringValue": u}));
+        }
+        if let Some(active) = is_active {
+            update_fields.push("is_active");
+            fields.insert("is_active".to_string(), json!({"booleanValue": active}));
+        }
+        if let Some(cat) = completed_at {
+            update_fields.push("completed_at");
+            fields.insert("completed_at".to_string(), json!({"timestampValue": cat.to_rfc3339()}));
+        }
 
-        // Parse nested transcription_preferences object
-        let prefs = fields
-            .get("transcription_preferences")
-            .and_then(|p| p.get("mapValue"))
-            .and_then(|m| m.get("fields"));
+        // Always update updated_at
+        update_fields.push("updated_at");
+        fields.insert("updated_at".to_string(), json!({"timestampValue": now.to_rfc3339()}));
 
-        if let Some(pref_fields) = prefs {
-            Ok(TranscriptionPreferences {
-                single_language_mode: self.parse_bool(pref_fields, "single_language_mode").unwrap_or(false),
-                vocabulary: self.parse_string_array(pref_fields, "vocabulary"),
-            })
-        } else {
-            Ok(TranscriptionPreferences::default())
-        }
-    }
+        let update_mask = update_fields.iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
 
-    /// Update transcription preferences for a user
-    pub async fn update_transcription_preferences(
-        &self,
-        uid: &str,
-        single_language_mode: Option<bool>,
-        vocabulary: Option<Vec<String>>,
-    ) -> Result<TranscriptionPreferences, Box<dyn std::error::Error + Send + Sync>> {
-        // Get current settings
-        let current = self.get_transcription_preferences(uid).await?;
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            GOALS_SUBCOLLECTION,
+            goal_id,
+            update_mask
+        );
 
-        let new_single_language_mode = single_language_mode.unwrap_or(current.single_language_mode);
-        let new_vocabulary = vocabulary.unwrap_or(current.vocabulary);
+        let doc = json!({"fields": fields});
 
-        let vocab_values: Vec<Value> = new_vocabulary
-            .iter()
-            .map(|v| json!({"stringValue": v}))
-            .collect();
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-        let fields = json!({
-            "transcription_preferences": {
-                "mapValue": {
-                    "fields": {
-                        "single_language_mode": {"booleanValue": new_single_language_mode},
-                        "vocabulary": {
-                            "arrayValue": {
-                                "values": vocab_values
-                            }
-                        }
-                    }
-                }
-            }
-        });
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update goal error", &error_text));
+        }
 
-        self.update_user_fields(uid, fields, &["transcription_preferences"])
-            .await?;
+        // Fetch the updated goal
+        let goal = self.get_goal(uid, goal_id).await?
+            .ok_or("Goal not found after update")?;
 
-        Ok(TranscriptionPreferences {
-            single_language_mode: new_single_language_mode,
-            vocabulary: new_vocabulary,
-        })
+        tracing::info!("Updated goal {} for user {}", goal_id, uid);
+        Ok(goal)
     }
 
-    // MARK: - Assistant Settings
+    /// Update goal progress (current_value) and record history
+    pub async fn update_goal_progress(
+        &self,
+        uid: &str,
+        goal_id: &str,
+        current_value: f64,
+    ) -> Result<GoalDB, FirestoreError> {
+        let goal = self.update_goal(uid, goal_id, None, None, None, Some(current_value), None, None, None, None, None).await?;
 
-    /// Helper: parse a sub-map from Firestore fields
-    fn parse_sub_map<'a>(&self, fields: &'a Value, key: &str) -> Option<&'a Value> {
-        fields.get(key)?.get("mapValue")?.get("fields")
-    }
+        // Also save history entry (inline, fast write)
+        if let Err(e) = self.save_goal_progress_history(uid, goal_id, current_value).await {
+            tracing::warn!("Failed to save goal progress history: {}", e);
+        }
 
-    /// Helper: build a Firestore string array value
-    fn build_string_array_value(&self, items: &[String]) -> Value {
-        let values: Vec<Value> = items.iter().map(|v| json!({"stringValue": v})).collect();
-        json!({"arrayValue": {"values": values}})
-    }
+        // Auto-complete if current_value >= target_value
+        if current_value >= goal.target_value && goal.completed_at.is_none() {
+            tracing::info!("Goal {} completed! current_value={} >= target_value={}", goal_id, current_value, goal.target_value);
+            let completed_goal = self.update_goal(uid, goal_id, None, None, None, None, None, None, None, Some(false), Some(Utc::now())).await?;
+            return Ok(completed_goal);
+        }
 
-    /// Helper: build a sub-map Firestore value from a serde_json::Map of fields
-    fn build_sub_map_value(&self, map_fields: serde_json::Map<String, Value>) -> Value {
-        json!({"mapValue": {"fields": map_fields}})
+        Ok(goal)
     }
 
-    /// Get assistant settings from user document
-    pub async fn get_assistant_settings(
+    /// Get inactive goals for a user (is_active == false — both completed and abandoned)
+    pub async fn get_completed_goals(
         &self,
         uid: &str,
-    ) -> Result<AssistantSettingsData, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
-
-        let settings_fields = self.parse_sub_map(fields, "assistant_settings");
-
-        let Some(sf) = settings_fields else {
-            return Ok(AssistantSettingsData::default());
-        };
+        limit: usize,
+    ) -> Result<Vec<GoalDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}:runQuery",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid
+        );
 
-        // Parse shared settings
-        let shared = self.parse_sub_map(sf, "shared").map(|f| SharedAssistantSettingsData {
-            cooldown_interval: self.parse_int(f, "cooldown_interval"),
-            glow_overlay_enabled: self.parse_bool(f, "glow_overlay_enabled").ok(),
-            analysis_delay: self.parse_int(f, "analysis_delay"),
-            screen_analysis_enabled: self.parse_bool(f, "screen_analysis_enabled").ok(),
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": GOALS_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "is_active"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": false}
+                    }
+                }
+            }
         });
 
-        // Parse focus settings
-        let focus = self.parse_sub_map(sf, "focus").map(|f| FocusSettingsData {
-            enabled: self.parse_bool(f, "enabled").ok(),
-            analysis_prompt: self.parse_string(f, "analysis_prompt"),
-            cooldown_interval: self.parse_int(f, "cooldown_interval"),
-            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
-            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
-        });
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-        // Parse task settings
-        let task = self.parse_sub_map(sf, "task").map(|f| TaskSettingsData {
-            enabled: self.parse_bool(f, "enabled").ok(),
-            analysis_prompt: self.parse_string(f, "analysis_prompt"),
-            extraction_interval: self.parse_float(f, "extraction_interval"),
-            min_confidence: self.parse_float(f, "min_confidence"),
-            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
-            allowed_apps: Some(self.parse_string_array(f, "allowed_apps")),
-            browser_keywords: Some(self.parse_string_array(f, "browser_keywords")),
-        });
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query completed goals error", &error_text));
+        }
 
-        // Parse advice settings
-        let advice = self.parse_sub_map(sf, "advice").map(|f| AdviceSettingsData {
-            enabled: self.parse_bool(f, "enabled").ok(),
-            analysis_prompt: self.parse_string(f, "analysis_prompt"),
-            extraction_interval: self.parse_float(f, "extraction_interval"),
-            min_confidence: self.parse_float(f, "min_confidence"),
-            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
-            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
-        });
+        let results: Vec<Value> = response.json().await?;
+        let mut goals = Vec::new();
 
-        // Parse memory settings
-        let memory = self.parse_sub_map(sf, "memory").map(|f| MemorySettingsData {
-            enabled: self.parse_bool(f, "enabled").ok(),
-            analysis_prompt: self.parse_string(f, "analysis_prompt"),
-            extraction_interval: self.parse_float(f, "extraction_interval"),
-            min_confidence: self.parse_float(f, "min_confidence"),
-            notifications_enabled: self.parse_bool(f, "notifications_enabled").ok(),
-            excluded_apps: Some(self.parse_string_array(f, "excluded_apps")),
-        });
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Ok(goal) = self.parse_goal(doc) {
+                    goals.push(goal);
+                }
+            }
+        }
 
-        // Read top-level update_channel from user doc (not from assistant_settings sub-map)
-        let update_channel = self.parse_string(fields, "update_channel");
+        // Sort: completed goals first (by completed_at desc), then abandoned (by updated_at desc)
+        goals.sort_by(|a, b| {
+            let a_time = a.completed_at.unwrap_or(a.updated_at);
+            let b_time = b.completed_at.unwrap_or(b.updated_at);
+            b_time.cmp(&a_time)
+        });
+        goals.truncate(limit);
 
-        Ok(AssistantSettingsData {
-            shared,
-            focus,
-            task,
-            advice,
-            memory,
-            update_channel,
-        })
+        tracing::info!("Found {} completed goals for user {}", goals.len(), uid);
+        Ok(goals)
     }
 
-    /// Update assistant settings (merge with existing)
-    pub async fn update_assistant_settings(
+    /// Save a progress history entry for a goal
+    /// Writes to goals/{goal_id}/goal_history/{YYYY-MM-DD}
+    pub async fn save_goal_progress_history(
         &self,
         uid: &str,
-        data: &AssistantSettingsData,
-    ) -> Result<AssistantSettingsData, Box<dyn std::error::Error + Send + Sync>> {
-        let current = self.get_assistant_settings(uid).await?;
-
-        let mut top_fields = serde_json::Map::new();
-
-        // Build shared sub-map
-        if data.shared.is_some() || current.shared.is_some() {
-            let cur = current.shared.unwrap_or_default();
-            let new = data.shared.clone().unwrap_or_default();
-            let mut m = serde_json::Map::new();
-            let ci = new.cooldown_interval.or(cur.cooldown_interval);
-            if let Some(v) = ci { m.insert("cooldown_interval".into(), json!({"integerValue": v.to_string()})); }
-            let go = new.glow_overlay_enabled.or(cur.glow_overlay_enabled);
-            if let Some(v) = go { m.insert("glow_overlay_enabled".into(), json!({"booleanValue": v})); }
-            let ad = new.analysis_delay.or(cur.analysis_delay);
-            if let Some(v) = ad { m.insert("analysis_delay".into(), json!({"integerValue": v.to_string()})); }
-            let sa = new.screen_analysis_enabled.or(cur.screen_analysis_enabled);
-            if let Some(v) = sa { m.insert("screen_analysis_enabled".into(), json!({"booleanValue": v})); }
-            if !m.is_empty() {
-                top_fields.insert("shared".into(), self.build_sub_map_value(m));
-            }
-        }
-
-        // Build focus sub-map
-        if data.focus.is_some() || current.focus.is_some() {
-            let cur = current.focus.unwrap_or_default();
-            let new = data.focus.clone().unwrap_or_default();
-            let mut m = serde_json::Map::new();
-            let en = new.enabled.or(cur.enabled);
-            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
-            let ap = new.analysis_prompt.or(cur.analysis_prompt);
-            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
-            let ci = new.cooldown_interval.or(cur.cooldown_interval);
-            if let Some(v) = ci { m.insert("cooldown_interval".into(), json!({"integerValue": v.to_string()})); }
-            let ne = new.notifications_enabled.or(cur.notifications_enabled);
-            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
-            let ea = new.excluded_apps.or(cur.excluded_apps);
-            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
-            if !m.is_empty() {
-                top_fields.insert("focus".into(), self.build_sub_map_value(m));
-            }
-        }
-
-        // Build task sub-map
-        if data.task.is_some() || current.task.is_some() {
-            let cur = current.task.unwrap_or_default();
-            let new = data.task.clone().unwrap_or_default();
-            let mut m = serde_json::Map::new();
-            let en = new.enabled.or(cur.enabled);
-            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
-            let ap = new.analysis_prompt.or(cur.analysis_prompt);
-            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
-            let ei = new.extraction_interval.or(cur.extraction_interval);
-            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
-            let mc = new.min_confidence.or(cur.min_confidence);
-            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
-            let aa = new.allowed_apps.or(cur.allowed_apps);
-            if let Some(v) = aa { m.insert("allowed_apps".into(), self.build_string_array_value(&v)); }
-            let ne = new.notifications_enabled.or(cur.notifications_enabled);
-            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
-            let bk = new.browser_keywords.or(cur.browser_keywords);
-            if let Some(v) = bk { m.insert("browser_keywords".into(), self.build_string_array_value(&v)); }
-            if !m.is_empty() {
-                top_fields.insert("task".into(), self.build_sub_map_value(m));
-            }
-        }
+        goal_id: &str,
+        value: f64,
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
+        let date_key = now.format("%Y-%m-%d").to_string();
 
-        // Build advice sub-map
-        if data.advice.is_some() || current.advice.is_some() {
-            let cur = current.advice.unwrap_or_default();
-            let new = data.advice.clone().unwrap_or_default();
-            let mut m = serde_json::Map::new();
-            let en = new.enabled.or(cur.enabled);
-            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
-            let ap = new.analysis_prompt.or(cur.analysis_prompt);
-            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
-            let ei = new.extraction_interval.or(cur.extraction_interval);
-            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
-            let mc = new.min_confidence.or(cur.min_confidence);
-            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
-            let ne = new.notifications_enabled.or(cur.notifications_enabled);
-            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
-            let ea = new.excluded_apps.or(cur.excluded_apps);
-            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
-            if !m.is_empty() {
-                top_fields.insert("advice".into(), self.build_sub_map_value(m));
-            }
-        }
+        let url = format!(
+            "{}/{}/{}/{}/{}/goal_history/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            GOALS_SUBCOLLECTION,
+            goal_id,
+            date_key
+        );
 
-        // Build memory sub-map
-        if data.memory.is_some() || current.memory.is_some() {
-            let cur = current.memory.unwrap_or_default();
-            let new = data.memory.clone().unwrap_or_default();
-            let mut m = serde_json::Map::new();
-            let en = new.enabled.or(cur.enabled);
-            if let Some(v) = en { m.insert("enabled".into(), json!({"booleanValue": v})); }
-            let ap = new.analysis_prompt.or(cur.analysis_prompt);
-            if let Some(v) = ap { m.insert("analysis_prompt".into(), json!({"stringValue": v})); }
-            let ei = new.extraction_interval.or(cur.extraction_interval);
-            if let Some(v) = ei { m.insert("extraction_interval".into(), json!({"doubleValue": v})); }
-            let mc = new.min_confidence.or(cur.min_confidence);
-            if let Some(v) = mc { m.insert("min_confidence".into(), json!({"doubleValue": v})); }
-            let ne = new.notifications_enabled.or(cur.notifications_enabled);
-            if let Some(v) = ne { m.insert("notifications_enabled".into(), json!({"booleanValue": v})); }
-            let ea = new.excluded_apps.or(cur.excluded_apps);
-            if let Some(v) = ea { m.insert("excluded_apps".into(), self.build_string_array_value(&v)); }
-            if !m.is_empty() {
-                top_fields.insert("memory".into(), self.build_sub_map_value(m));
+        let doc = json!({
+            "fields": {
+                "date": {"stringValue": &date_key},
+                "value": {"doubleValue": value},
+                "recorded_at": {"timestampValue": now.to_rfc3339()}
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore save goal history error", &error_text));
         }
 
-        if !top_fields.is_empty() {
-            let fields = json!({
-                "assistant_settings": {
-                    "mapValue": {
-                        "fields": Value::Object(top_fields)
+        tracing::debug!("Saved goal history for {}/{}: {} on {}", uid, goal_id, value, date_key);
+        Ok(())
+    }
+
+    /// Get progress history for a goal
+    pub async fn get_goal_history(
+        &self,
+        uid: &str,
+        goal_id: &str,
+        days: u32,
+    ) -> Result<Vec<GoalHistoryEntry>, FirestoreError> {
+        use crate::models::GoalHistoryEntry;
+
+        // Query the goal_history subcollection
+        let parent = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            GOALS_SUBCOLLECTION,
+            goal_id
+        );
+
+        let cutoff = Utc::now() - chrono::TimeDelta::days(days as i64);
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": "goal_history"}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "recorded_at"},
+                        "op": "GREATER_THAN_OR_EQUAL",
+                        "value": {"timestampValue": cutoff.to_rfc3339()}
                     }
-                }
-            });
+                },
+                "orderBy": [{"field": {"fieldPath": "recorded_at"}, "direction": "DESCENDING"}],
+                "limit": days as i64
+            }
+        });
 
-            self.update_user_fields(uid, fields, &["assistant_settings"]).await?;
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query goal history error", &error_text));
         }
 
-        // Write update_channel as top-level field on user doc (not inside assistant_settings)
-        if let Some(ref channel) = data.update_channel {
-            let fields = json!({
-                "update_channel": {
-                    "stringValue": channel
+        let results: Vec<Value> = response.json().await?;
+        let mut history = Vec::new();
+
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Some(fields) = doc.get("fields") {
+                    let date = self.parse_string(fields, "date").unwrap_or_default();
+                    let value = self.parse_double(fields, "value").unwrap_or(0.0);
+                    let recorded_at = self.parse_timestamp_optional(fields, "recorded_at").unwrap_or_else(Utc::now);
+                    history.push(GoalHistoryEntry { date, value, recorded_at });
                 }
-            });
-            self.update_user_fields(uid, fields, &["update_channel"]).await?;
+            }
         }
 
-        // Return merged state
-        self.get_assistant_settings(uid).await
+        tracing::info!("Found {} history entries for goal {}", history.len(), goal_id);
+        Ok(history)
     }
 
-    /// Get user email from Firestore profile
-    pub async fn get_user_email(
+    /// Delete a goal
+    pub async fn delete_goal(
         &self,
         uid: &str,
-    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
-        Ok(self.parse_string(fields, "email"))
+        goal_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            GOALS_SUBCOLLECTION,
+            goal_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete goal error", &error_text));
+        }
+
+        tracing::info!("Deleted goal {} for user {}", goal_id, uid);
+        Ok(())
     }
 
-    /// Get user language preference
-    pub async fn get_user_language(
+    /// Get a single goal by ID
+    pub async fn get_goal(
         &self,
         uid: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+        goal_id: &str,
+    ) -> Result<Option<GoalDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            GOALS_SUBCOLLECTION,
+            goal_id
+        );
 
-        Ok(self.parse_string(fields, "language").unwrap_or_else(|| "en".to_string()))
-    }
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
 
-    /// Update user language preference
-    /// Languages supported by Deepgram Nova-3 multi-language auto-detection.
-    const MULTI_LANGUAGE_SUPPORTED: &[&str] = &[
-        "en", "en-US", "en-AU", "en-GB", "en-IN", "en-NZ",
-        "es", "es-419",
-        "fr", "fr-CA",
-        "de",
-        "hi",
-        "ru",
-        "pt", "pt-BR", "pt-PT",
-        "ja",
-        "it",
-        "nl",
-    ];
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
 
-    pub async fn update_user_language(
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get goal error", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        let goal = self.parse_goal(&doc)?;
+        Ok(Some(goal))
+    }
+
+    /// Users with at least one active goal (collection group query over goals)
+    pub async fn get_users_with_active_goals(
         &self,
-        uid: &str,
-        language: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Set language field
-        let lang_fields = json!({
-            "language": {"stringValue": language}
+        limit: usize,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": GOALS_SUBCOLLECTION, "allDescendants": true}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "is_active"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                },
+                "limit": limit
+            }
         });
-        self.update_user_fields(uid, lang_fields, &["language"]).await?;
 
-        // Auto-set single_language_mode based on whether the language supports multi-language
-        let single_language_mode = !Self::MULTI_LANGUAGE_SUPPORTED.contains(&language);
-        self.update_transcription_preferences(uid, Some(single_language_mode), None).await?;
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut uids: Vec<String> = Vec::new();
+
+        for result in results {
+            // name: .../documents/users/{uid}/goals/{goal_id}
+            let uid = result
+                .get("document")
+                .and_then(|d| d.get("name"))
+                .and_then(|n| n.as_str())
+                .and_then(|name| name.split('/').rev().nth(2))
+                .map(|s| s.to_string());
+            if let Some(uid) = uid {
+                if !uids.contains(&uid) {
+                    uids.push(uid);
+                }
+            }
+        }
 
-        Ok(())
+        Ok(uids)
     }
 
-    /// Get recording permission for a user
-    pub async fn get_recording_permission(
+    /// UTC activity date goal progress was last evaluated for, if any
+    pub async fn get_goal_progress_evaluated_date(
         &self,
         uid: &str,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<String>, FirestoreError> {
         let doc = self.get_user_document(uid).await?;
         let empty = json!({});
         let fields = doc.get("fields").unwrap_or(&empty);
-
-        Ok(self.parse_bool(fields, "store_recording_permission").unwrap_or(false))
+        Ok(self.parse_string(fields, "goal_progress_evaluated_date"))
     }
 
-    /// Set recording permission for a user
-    pub async fn set_recording_permission(
+    /// Record the activity date goal progress was evaluated for (prevents counting a day twice)
+    pub async fn set_goal_progress_evaluated_date(
         &self,
         uid: &str,
-        enabled: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        date: &str,
+    ) -> Result<(), FirestoreError> {
         let fields = json!({
-            "store_recording_permission": {"booleanValue": enabled}
+            "goal_progress_evaluated_date": {"stringValue": date}
         });
-
-        self.update_user_fields(uid, fields, &["store_recording_permission"]).await
+        self.update_user_fields(uid, fields, &["goal_progress_evaluated_date"])
+            .await
     }
 
-    /// Get private cloud sync setting for a user
-    pub async fn get_private_cloud_sync(
+    /// Record a day's score snapshot
+    /// Writes to daily_scores/{YYYY-MM-DD}
+    pub async fn save_daily_score(
         &self,
         uid: &str,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+        score: &DailyScore,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            DAILY_SCORES_SUBCOLLECTION,
+            score.date
+        );
 
-        // Default to true if not set
-        Ok(self.parse_bool(fields, "private_cloud_sync_enabled").unwrap_or(true))
+        let doc = json!({
+            "fields": {
+                "date": {"stringValue": score.date},
+                "score": {"doubleValue": score.score},
+                "completed_tasks": {"integerValue": score.completed_tasks.to_string()},
+                "total_tasks": {"integerValue": score.total_tasks.to_string()},
+                "recorded_at": {"timestampValue": Utc::now().to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore save daily score error", &error_text));
+        }
+
+        Ok(())
     }
 
-    /// Set private cloud sync setting for a user
-    pub async fn set_private_cloud_sync(
+    /// Get action items for daily score calculation
+    /// Returns (completed_count, total_count) for items due on the given date
+    pub async fn get_action_items_for_daily_score(
         &self,
         uid: &str,
-        enabled: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let fields = json!({
-            "private_cloud_sync_enabled": {"booleanValue": enabled}
+        due_start: &str,
+        due_end: &str,
+    ) -> Result<(i32, i32), FirestoreError> {
+        // Use same URL pattern as working get_action_items method
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let url = format!("{}:runQuery", parent);
+
+        // We need to get all items due today, regardless of completion status
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "due_at"},
+                                    "op": "GREATER_THAN_OR_EQUAL",
+                                    "value": {"timestampValue": due_start}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "due_at"},
+                                    "op": "LESS_THAN",
+                                    "value": {"timestampValue": due_end}
+                                }
+                            }
+                        ]
+                    }
+                },
+                "limit": 100
+            }
         });
 
-        self.update_user_fields(uid, fields, &["private_cloud_sync_enabled"]).await
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut completed = 0;
+        let mut total = 0;
+
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Some(fields) = doc.get("fields") {
+                    total += 1;
+                    if self.parse_bool(fields, "completed").unwrap_or(false) {
+                        completed += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Daily score for user {}: {}/{} tasks completed", uid, completed, total);
+        Ok((completed, total))
     }
 
-    /// Get notification settings for a user
-    pub async fn get_notification_settings(
+    /// Get action items for weekly score calculation (created in date range)
+    pub async fn get_action_items_for_weekly_score(
         &self,
         uid: &str,
-    ) -> Result<NotificationSettings, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<(i32, i32), FirestoreError> {
+        // Use same URL pattern as working get_action_items method
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let url = format!("{}:runQuery", parent);
 
-        Ok(NotificationSettings {
-            enabled: self.parse_bool(fields, "notifications_enabled").unwrap_or(true),
-            frequency: self.parse_int(fields, "notification_frequency").unwrap_or(3),
-        })
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "created_at"},
+                                    "op": "GREATER_THAN_OR_EQUAL",
+                                    "value": {"timestampValue": start_date}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "created_at"},
+                                    "op": "LESS_THAN",
+                                    "value": {"timestampValue": end_date}
+                                }
+                            }
+                        ]
+                    }
+                },
+                "limit": 1000
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        let mut completed = 0;
+        let mut total = 0;
+
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                if let Some(fields) = doc.get("fields") {
+                    total += 1;
+                    if self.parse_bool(fields, "completed").unwrap_or(false) {
+                        completed += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Weekly score for user {}: {}/{} tasks completed", uid, completed, total);
+        Ok((completed, total))
     }
 
-    /// Update notification settings for a user
-    pub async fn update_notification_settings(
+    /// Get all action items for overall score calculation
+    pub async fn get_action_items_for_overall_score(
         &self,
         uid: &str,
-        enabled: Option<bool>,
-        frequency: Option<i32>,
-    ) -> Result<NotificationSettings, Box<dyn std::error::Error + Send + Sync>> {
-        // Get current settings
-        let current = self.get_notification_settings(uid).await?;
+    ) -> Result<(i32, i32), FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let agg_url = format!("{}:runAggregationQuery", parent);
 
-        let new_enabled = enabled.unwrap_or(current.enabled);
-        let new_frequency = frequency.unwrap_or(current.frequency);
+        let structured_query = json!({
+            "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}]
+        });
 
-        let fields = json!({
-            "notifications_enabled": {"booleanValue": new_enabled},
-            "notification_frequency": {"integerValue": new_frequency.to_string()}
+        // Count total and completed in parallel using aggregation queries
+        let total_query = json!({
+            "structuredAggregationQuery": {
+                "structuredQuery": structured_query,
+                "aggregations": [{"alias": "count", "count": {}}]
+            }
+        });
+
+        let completed_query = json!({
+            "structuredAggregationQuery": {
+                "structuredQuery": {
+                    "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                    "where": {
+                        "fieldFilter": {
+                            "field": {"fieldPath": "completed"},
+                            "op": "EQUAL",
+                            "value": {"booleanValue": true}
+                        }
+                    }
+                },
+                "aggregations": [{"alias": "count", "count": {}}]
+            }
         });
 
-        self.update_user_fields(uid, fields, &["notifications_enabled", "notification_frequency"])
-            .await?;
+        let (total_resp, completed_resp) = tokio::join!(
+            async {
+                self.build_request(reqwest::Method::POST, &agg_url)
+                    .await?
+                    .json(&total_query)
+                    .send()
+                    .await
+                    .map_err(FirestoreError::from)
+            },
+            async {
+                self.build_request(reqwest::Method::POST, &agg_url)
+                    .await?
+                    .json(&completed_query)
+                    .send()
+                    .await
+                    .map_err(FirestoreError::from)
+            }
+        );
+
+        let parse_count = |response: reqwest::Response| async move {
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore aggregation query error", &error_text));
+            }
+            let results: Vec<Value> = response.json().await?;
+            let count = results
+                .first()
+                .and_then(|r| r.get("result"))
+                .and_then(|r| r.get("aggregateFields"))
+                .and_then(|f| f.get("count"))
+                .and_then(|c| c.get("integerValue"))
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<i32>().ok())
+                .unwrap_or(0);
+            Ok::<i32, FirestoreError>(count)
+        };
+
+        let total = parse_count(total_resp?).await?;
+        let completed = parse_count(completed_resp?).await?;
 
-        Ok(NotificationSettings {
-            enabled: new_enabled,
-            frequency: new_frequency,
-        })
+        tracing::info!("Overall score for user {}: {}/{} tasks completed", uid, completed, total);
+        Ok((completed, total))
     }
 
-    /// Get user profile
-    pub async fn get_user_profile(
-        &self,
-        uid: &str,
-    ) -> Result<UserProfile, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
-
-        Ok(UserProfile {
-            uid: uid.to_string(),
-            email: self.parse_string(fields, "email"),
-            name: self.parse_string(fields, "name"),
-            time_zone: self.parse_string(fields, "time_zone"),
-            created_at: self.parse_timestamp_optional(fields, "created_at")
-                .map(|dt| dt.to_rfc3339()),
-            motivation: self.parse_string(fields, "motivation"),
-            use_case: self.parse_string(fields, "use_case"),
-            job: self.parse_string(fields, "job"),
-            company: self.parse_string(fields, "company"),
-        })
+    /// Parse a goal from Firestore document
+    fn parse_goal(&self, doc: &Value) -> Result<GoalDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        firestore_serde::drop_invalid::<GoalType>(&mut object, "goal_type");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({
+                "title": "",
+                "target_value": 1.0,
+                "created_at": Utc::now(),
+                "updated_at": Utc::now(),
+            }),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
     }
 
-    /// Update user profile fields (onboarding data)
-    pub async fn update_user_profile(
-        &self,
-        uid: &str,
-        name: Option<String>,
-        motivation: Option<String>,
-        use_case: Option<String>,
-        job: Option<String>,
-        company: Option<String>,
-    ) -> Result<UserProfile, Box<dyn std::error::Error + Send + Sync>> {
-        let mut fields = json!({});
-        let mut mask: Vec<&str> = Vec::new();
-
-        if let Some(ref v) = name {
-            fields["name"] = json!({"stringValue": v});
-            mask.push("name");
-        }
-        if let Some(ref v) = motivation {
-            fields["motivation"] = json!({"stringValue": v});
-            mask.push("motivation");
-        }
-        if let Some(ref v) = use_case {
-            fields["use_case"] = json!({"stringValue": v});
-            mask.push("use_case");
-        }
-        if let Some(ref v) = job {
-            fields["job"] = json!({"stringValue": v});
-            mask.push("job");
-        }
-        if let Some(ref v) = company {
-            fields["company"] = json!({"stringValue": v});
-            mask.push("company");
-        }
-
-        if !mask.is_empty() {
-            self.update_user_fields(uid, fields, &mask).await?;
-        }
-
-        self.get_user_profile(uid).await
+    /// Parse double value from Firestore fields
+    fn parse_double(&self, fields: &Value, key: &str) -> Option<f64> {
+        fields.get(key)
+            .and_then(|v| {
+                // Try doubleValue first
+                if let Some(d) = v.get("doubleValue").and_then(|d| d.as_f64()) {
+                    return Some(d);
+                }
+                // Try integerValue (Firestore sometimes stores numbers as integers)
+                if let Some(i) = v.get("integerValue") {
+                    if let Some(s) = i.as_str() {
+                        return s.parse::<f64>().ok();
+                    }
+                    if let Some(n) = i.as_i64() {
+                        return Some(n as f64);
+                    }
+                }
+                None
+            })
     }
 
     // =========================================================================
-    // USER PERSONA
+    // PERSONAS
     // =========================================================================
 
-    /// Get AI-generated user profile from user document
-    pub async fn get_ai_user_profile(
+    /// Get user's persona (there can be only one per user)
+    pub async fn get_user_persona(
         &self,
         uid: &str,
-    ) -> Result<Option<AIUserProfile>, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
+    ) -> Result<Option<PersonaDB>, FirestoreError> {
+        let parent = self.base_url();
 
-        let profile_fields = fields
-            .get("ai_user_profile")
-            .and_then(|p| p.get("mapValue"))
-            .and_then(|m| m.get("fields"));
+        // Query for persona with matching uid and persona capability
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": APPS_COLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "uid"},
+                                    "op": "EQUAL",
+                                    "value": {"stringValue": uid}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "capabilities"},
+                                    "op": "ARRAY_CONTAINS",
+                                    "value": {"stringValue": "persona"}
+                                }
+                            }
+                        ]
+                    }
+                },
+                "limit": 1
+            }
+        });
 
-        if let Some(pf) = profile_fields {
-            let profile_text = self.parse_string(pf, "profile_text").unwrap_or_default();
-            let generated_at = self.parse_timestamp(pf, "generated_at")?;
-            let data_sources_used = self.parse_int(pf, "data_sources_used").unwrap_or(0);
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-            Ok(Some(AIUserProfile {
-                profile_text,
-                generated_at,
-                data_sources_used,
-            }))
-        } else {
-            Ok(None)
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(None);
         }
+
+        let results: Vec<Value> = response.json().await?;
+
+        // Return first matching persona
+        for result in results {
+            if let Some(doc) = result.get("document") {
+                return Ok(Some(self.parse_persona(doc)?));
+            }
+        }
+
+        Ok(None)
     }
 
-    /// Update AI-generated user profile in user document
-    pub async fn update_ai_user_profile(
+    /// Get the persona with a username, if any
+    pub async fn get_persona_by_username(
         &self,
-        uid: &str,
-        profile_text: &str,
-        generated_at: &str,
-        data_sources_used: i32,
-    ) -> Result<AIUserProfile, Box<dyn std::error::Error + Send + Sync>> {
-        let generated_at_dt = DateTime::parse_from_rfc3339(generated_at)
-            .map(|dt| dt.with_timezone(&Utc))
-            .map_err(|e| format!("Invalid generated_at timestamp: {}", e))?;
+        username: &str,
+    ) -> Result<Option<PersonaDB>, FirestoreError> {
+        let parent = self.base_url();
 
-        let fields = json!({
-            "ai_user_profile": {
-                "mapValue": {
-                    "fields": {
-                        "profile_text": {"stringValue": profile_text},
-                        "generated_at": {"timestampValue": generated_at},
-                        "data_sources_used": {"integerValue": data_sources_used.to_string()}
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": APPS_COLLECTION}],
+                "where": {
+                    "compositeFilter": {
+                        "op": "AND",
+                        "filters": [
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "username"},
+                                    "op": "EQUAL",
+                                    "value": {"stringValue": username}
+                                }
+                            },
+                            {
+                                "fieldFilter": {
+                                    "field": {"fieldPath": "capabilities"},
+                                    "op": "ARRAY_CONTAINS",
+                                    "value": {"stringValue": "persona"}
+                                }
+                            }
+                        ]
                     }
-                }
+                },
+                "limit": 1
             }
         });
 
-        self.update_user_fields(uid, fields, &["ai_user_profile"]).await?;
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-        Ok(AIUserProfile {
-            profile_text: profile_text.to_string(),
-            generated_at: generated_at_dt,
-            data_sources_used,
-        })
-    }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
 
-    // =========================================================================
-    // FOCUS SESSIONS
-    // =========================================================================
+        let results: Vec<Value> = response.json().await?;
+        results
+            .iter()
+            .find_map(|r| r.get("document"))
+            .map(|doc| self.parse_persona(doc))
+            .transpose()
+    }
 
-    /// Create a focus session
-    /// Path: users/{uid}/focus_sessions/{session_id}
-    pub async fn create_focus_session(
+    /// Create a new persona for user
+    pub async fn create_persona(
         &self,
         uid: &str,
-        status: &FocusStatus,
-        app_or_site: &str,
+        name: &str,
+        username: Option<&str>,
         description: &str,
-        message: Option<&str>,
-    ) -> Result<FocusSessionDB, Box<dyn std::error::Error + Send + Sync>> {
-        let session_id = uuid::Uuid::new_v4().to_string();
+        persona_prompt: Option<&str>,
+        author: &str,
+        email: Option<&str>,
+    ) -> Result<PersonaDB, FirestoreError> {
+        // Generate ULID-style ID
+        let persona_id = ulid::Ulid::new().to_string();
         let now = Utc::now();
 
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}",
             self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            FOCUS_SESSIONS_SUBCOLLECTION,
-            session_id
+            APPS_COLLECTION,
+            persona_id
         );
 
-        let status_str = match status {
-            FocusStatus::Focused => "focused",
-            FocusStatus::Distracted => "distracted",
-        };
-
         let mut fields = json!({
-            "status": {"stringValue": status_str},
-            "app_or_site": {"stringValue": app_or_site},
+            "id": {"stringValue": &persona_id},
+            "uid": {"stringValue": uid},
+            "name": {"stringValue": name},
             "description": {"stringValue": description},
-            "created_at": {"timestampValue": now.to_rfc3339()}
+            "image": {"stringValue": ""},
+            "category": {"stringValue": "personality-emulation"},
+            "capabilities": {"arrayValue": {"values": [{"stringValue": "persona"}]}},
+            "approved": {"booleanValue": false},
+            "status": {"stringValue": "under-review"},
+            "private": {"booleanValue": false},
+            "author": {"stringValue": author},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
         });
 
-        if let Some(msg) = message {
-            fields["message"] = json!({"stringValue": msg});
+        if let Some(uname) = username {
+            fields["username"] = json!({"stringValue": uname});
+        }
+        if let Some(prompt) = persona_prompt {
+            fields["persona_prompt"] = json!({"stringValue": prompt});
+        }
+        if let Some(e) = email {
+            fields["email"] = json!({"stringValue": e});
         }
 
-        let doc = json!({"fields": fields});
+        let doc = json!({ "fields": fields });
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -5381,100 +12838,199 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        tracing::info!(
-            "Created focus session {} for user {} with status={}",
-            session_id,
-            uid,
-            status_str
-        );
+        tracing::info!("Created persona {} for user {}", persona_id, uid);
 
-        Ok(FocusSessionDB {
-            id: session_id,
-            status: status.clone(),
-            app_or_site: app_or_site.to_string(),
+        Ok(PersonaDB {
+            id: persona_id,
+            uid: uid.to_string(),
+            name: name.to_string(),
+            username: username.map(|s| s.to_string()),
             description: description.to_string(),
-            message: message.map(|s| s.to_string()),
+            image: String::new(),
+            category: "personality-emulation".to_string(),
+            capabilities: vec!["persona".to_string()],
+            persona_prompt: persona_prompt.map(|s| s.to_string()),
+            approved: false,
+            status: "under-review".to_string(),
+            is_private: false,
+            author: author.to_string(),
+            email: email.map(|s| s.to_string()),
             created_at: now,
-            duration_seconds: None,
+            updated_at: now,
+            noindex: false,
         })
     }
 
-    /// Get focus sessions for a user
-    /// Path: users/{uid}/focus_sessions
-    pub async fn get_focus_sessions(
+    /// Update an existing persona
+    pub async fn update_persona(
         &self,
-        uid: &str,
-        limit: usize,
-        offset: usize,
-        date_filter: Option<&str>,
-    ) -> Result<Vec<FocusSessionDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        persona_id: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+        persona_prompt: Option<&str>,
+        image: Option<&str>,
+        noindex: Option<bool>,
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now();
+        let mut update_fields = vec!["updated_at"];
+        let mut fields = json!({
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
+        if let Some(n) = name {
+            fields["name"] = json!({"stringValue": n});
+            update_fields.push("name");
+        }
+        if let Some(d) = description {
+            fields["description"] = json!({"stringValue": d});
+            update_fields.push("description");
+        }
+        if let Some(p) = persona_prompt {
+            fields["persona_prompt"] = json!({"stringValue": p});
+            update_fields.push("persona_prompt");
+        }
+        if let Some(i) = image {
+            fields["image"] = json!({"stringValue": i});
+            update_fields.push("image");
+        }
+        if let Some(n) = noindex {
+            fields["noindex"] = json!({"booleanValue": n});
+            update_fields.push("noindex");
+        }
 
-        // If date filter provided, filter by date range
-        if let Some(date) = date_filter {
-            // Parse date and create start/end timestamps
-            if let Ok(parsed_date) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
-                let start = parsed_date
-                    .and_hms_opt(0, 0, 0)
-                    .unwrap()
-                    .and_utc();
-                let end = parsed_date
-                    .and_hms_opt(23, 59, 59)
-                    .unwrap()
-                    .and_utc();
+        let update_mask = update_fields
+            .iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
 
-                filters.push(json!({
-                    "fieldFilter": {
-                        "field": {"fieldPath": "created_at"},
-                        "op": "GREATER_THAN_OR_EQUAL",
-                        "value": {"timestampValue": start.to_rfc3339()}
-                    }
-                }));
-                filters.push(json!({
+        let url = format!(
+            "{}/{}/{}?{}",
+            self.base_url(),
+            APPS_COLLECTION,
+            persona_id,
+            update_mask
+        );
+
+        let doc = json!({ "fields": fields });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
+
+        tracing::info!("Updated persona {}", persona_id);
+        Ok(())
+    }
+
+    /// Delete a persona
+    pub async fn delete_persona(
+        &self,
+        persona_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}",
+            self.base_url(),
+            APPS_COLLECTION,
+            persona_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted persona {}", persona_id);
+        Ok(())
+    }
+
+    /// Check if a username is available
+    pub async fn is_username_available(
+        &self,
+        username: &str,
+    ) -> Result<bool, FirestoreError> {
+        let parent = self.base_url();
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": APPS_COLLECTION}],
+                "where": {
                     "fieldFilter": {
-                        "field": {"fieldPath": "created_at"},
-                        "op": "LESS_THAN_OR_EQUAL",
-                        "value": {"timestampValue": end.to_rfc3339()}
+                        "field": {"fieldPath": "username"},
+                        "op": "EQUAL",
+                        "value": {"stringValue": username}
                     }
-                }));
+                },
+                "limit": 1
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            tracing::error!("Firestore query error: {}", error_text);
+            return Ok(false);
         }
 
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
+        let results: Vec<Value> = response.json().await?;
 
-        // Build structured query
-        let mut structured_query = json!({
-            "from": [{"collectionId": FOCUS_SESSIONS_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
-            "limit": limit,
-            "offset": offset
-        });
+        // Username is available if no documents found
+        let has_document = results.iter().any(|r| r.get("document").is_some());
+        Ok(!has_document)
+    }
 
-        if let Some(where_filter) = where_clause {
-            structured_query["where"] = where_filter;
-        }
+    /// Get public memories for a user (for persona generation)
+    pub async fn get_public_memories(
+        &self,
+        uid: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryDB>, FirestoreError> {
+        let parent = format!(
+            "{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid
+        );
 
         let query = json!({
-            "structuredQuery": structured_query
+            "structuredQuery": {
+                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "visibility"},
+                        "op": "EQUAL",
+                        "value": {"stringValue": "public"}
+                    }
+                },
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit
+            }
         });
 
         let response = self
@@ -5491,537 +13047,577 @@ impl FirestoreService {
         }
 
         let results: Vec<Value> = response.json().await?;
-        let sessions = results
+        let memories: Vec<MemoryDB> = results
             .into_iter()
             .filter_map(|doc| {
                 doc.get("document")
-                    .and_then(|d| self.parse_focus_session(d).ok())
+                    .and_then(|d| self.parse_memory(d, uid).ok())
             })
             .collect();
 
-        Ok(sessions)
+        tracing::info!("Found {} public memories for user {}", memories.len(), uid);
+        Ok(memories)
     }
 
-    /// Delete a focus session
-    pub async fn delete_focus_session(
+    /// Count public memories for a user
+    pub async fn count_public_memories(
         &self,
         uid: &str,
-        session_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            FOCUS_SESSIONS_SUBCOLLECTION,
-            session_id
+    ) -> Result<i32, FirestoreError> {
+        let memories = self.get_public_memories(uid, 1000).await?;
+        Ok(memories.len() as i32)
+    }
+
+    /// Parse a persona from Firestore document
+    fn parse_persona(&self, doc: &Value) -> Result<PersonaDB, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        if object.get("capabilities").and_then(|c| c.as_array()).is_none_or(|c| c.is_empty()) {
+            object.insert("capabilities".to_string(), json!(["persona"]));
+        }
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({
+                "uid": "",
+                "name": "",
+                "description": "",
+                "image": "",
+                "category": "personality-emulation",
+                "status": "under-review",
+                "private": false,
+                "author": "",
+                "created_at": Utc::now(),
+                "updated_at": Utc::now(),
+            }),
         );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    /// Parse a folder from Firestore document
+    fn parse_folder(&self, doc: &Value) -> Result<Folder, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
+    }
+
+    // =========================================================================
+    // PEOPLE - Speaker voice profiles for transcript naming
+    // =========================================================================
+
+    /// Get all people for a user
+    pub async fn get_people(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<crate::models::Person>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let structured_query = json!({
+            "from": [{"collectionId": PEOPLE_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "name"}, "direction": "ASCENDING"}]
+        });
+
+        let query = json!({
+            "structuredQuery": structured_query
+        });
 
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
             .await?
+            .json(&query)
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if !response.status().is_success() {
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            tracing::error!("Firestore query error for people: {}", error_text);
+            return Ok(vec![]);
         }
 
-        tracing::info!("Deleted focus session {} for user {}", session_id, uid);
-        Ok(())
+        let results: Vec<Value> = response.json().await?;
+        let people = results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_person(d).ok()))
+            .collect();
+
+        Ok(people)
     }
 
-    /// Get focus statistics for a date
-    pub async fn get_focus_stats(
+    /// Create a new person
+    pub async fn create_person(
         &self,
         uid: &str,
-        date: &str,
-    ) -> Result<FocusStats, Box<dyn std::error::Error + Send + Sync>> {
-        // Get all sessions for the date
-        let sessions = self.get_focus_sessions(uid, 1000, 0, Some(date)).await?;
+        name: &str,
+    ) -> Result<crate::models::Person, FirestoreError> {
+        let person_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
 
-        let mut focused_count: i64 = 0;
-        let mut distracted_count: i64 = 0;
-        let mut distraction_map: std::collections::HashMap<String, (i64, i64)> =
-            std::collections::HashMap::new();
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            PEOPLE_SUBCOLLECTION,
+            person_id
+        );
 
-        for session in &sessions {
-            match session.status {
-                FocusStatus::Focused => focused_count += 1,
-                FocusStatus::Distracted => {
-                    distracted_count += 1;
-                    let entry = distraction_map
-                        .entry(session.app_or_site.clone())
-                        .or_insert((0, 0));
-                    entry.0 += session.duration_seconds.unwrap_or(60); // Default 60s per session
-                    entry.1 += 1;
-                }
-            }
-        }
+        let fields = json!({
+            "name": {"stringValue": name},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
 
-        // Build top distractions
-        let mut top_distractions: Vec<DistractionEntry> = distraction_map
-            .into_iter()
-            .map(|(app, (secs, count))| DistractionEntry {
-                app_or_site: app,
-                total_seconds: secs,
-                count,
-            })
-            .collect();
+        let doc = json!({"fields": fields});
 
-        // Sort by total time descending
-        top_distractions.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds));
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-        // Take top 5
-        top_distractions.truncate(5);
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create person error", &error_text));
+        }
 
-        // Estimate minutes (each session ~1 minute if no duration)
-        let focused_minutes = focused_count;
-        let distracted_minutes = distracted_count;
+        tracing::info!("Created person '{}' ({}) for user {}", name, person_id, uid);
 
-        Ok(FocusStats {
-            date: date.to_string(),
-            focused_minutes,
-            distracted_minutes,
-            session_count: sessions.len() as i64,
-            focused_count,
-            distracted_count,
-            top_distractions,
+        Ok(crate::models::Person {
+            id: person_id,
+            name: name.to_string(),
+            created_at: now,
+            updated_at: now,
+            relationship_notes: None,
         })
     }
 
-    /// Parse a focus session from Firestore document
-    fn parse_focus_session(
+    /// Get a single person
+    pub async fn get_person(
         &self,
-        doc: &Value,
-    ) -> Result<FocusSessionDB, Box<dyn std::error::Error + Send + Sync>> {
-        let name = doc
-            .get("name")
-            .and_then(|n| n.as_str())
-            .ok_or("Missing document name")?;
-
-        let id = name.split('/').last().unwrap_or("unknown").to_string();
+        uid: &str,
+        person_id: &str,
+    ) -> Result<Option<crate::models::Person>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            PEOPLE_SUBCOLLECTION,
+            person_id
+        );
 
-        let fields = doc.get("fields").ok_or("Missing fields")?;
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
 
-        let status_str = self.parse_string(fields, "status").unwrap_or_default();
-        let status = match status_str.as_str() {
-            "focused" => FocusStatus::Focused,
-            _ => FocusStatus::Distracted,
-        };
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get person error", &error_text));
+        }
 
-        Ok(FocusSessionDB {
-            id,
-            status,
-            app_or_site: self.parse_string(fields, "app_or_site").unwrap_or_default(),
-            description: self.parse_string(fields, "description").unwrap_or_default(),
-            message: self.parse_string(fields, "message"),
-            created_at: self
-                .parse_timestamp_optional(fields, "created_at")
-                .unwrap_or_else(Utc::now),
-            duration_seconds: self.parse_int(fields, "duration_seconds").map(|v| v as i64),
-        })
+        let doc: Value = response.json().await?;
+        Ok(Some(self.parse_person(&doc)?))
     }
 
-    // =========================================================================
-    // CHAT SESSIONS
-    // =========================================================================
+    /// Cache relationship notes on a person
+    pub async fn save_relationship_notes(
+        &self,
+        uid: &str,
+        person_id: &str,
+        notes: &crate::models::RelationshipNotes,
+    ) -> Result<(), FirestoreError> {
+        let doc_name = format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            PEOPLE_SUBCOLLECTION,
+            person_id
+        );
+        self.patch_document_fields(
+            &doc_name,
+            json!({
+                "relationship_notes": {"mapValue": {"fields": {
+                    "notes": self.build_string_array_value(&notes.notes),
+                    "latest_conversation_id": {"stringValue": notes.latest_conversation_id},
+                    "generated_at": {"timestampValue": notes.generated_at.to_rfc3339()}
+                }}}
+            }),
+            &["relationship_notes"],
+        )
+        .await
+    }
 
-    /// Create a chat session
-    /// Path: users/{uid}/chat_sessions/{session_id}
-    pub async fn create_chat_session(
+    /// Update a person's name
+    pub async fn update_person_name(
         &self,
         uid: &str,
-        title: Option<&str>,
-        app_id: Option<&str>,
-    ) -> Result<ChatSessionDB, Box<dyn std::error::Error + Send + Sync>> {
-        let session_id = uuid::Uuid::new_v4().to_string();
+        person_id: &str,
+        new_name: &str,
+    ) -> Result<(), FirestoreError> {
         let now = Utc::now();
-
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=name&updateMask.fieldPaths=updated_at",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
+            PEOPLE_SUBCOLLECTION,
+            person_id
         );
 
-        let mut fields = json!({
-            // CRITICAL: id field required - Python ChatSession model requires it
-            // and chat.py accesses chat_session['id'] directly
-            "id": {"stringValue": &session_id},
-            "title": {"stringValue": title.unwrap_or("New Chat")},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()},
-            "message_count": {"integerValue": "0"},
-            "starred": {"booleanValue": false}
+        let fields = json!({
+            "name": {"stringValue": new_name},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
         });
 
-        // CRITICAL: Always set app_id and plugin_id fields for backward compatibility
-        // Python backend queries chat_sessions.where(plugin_id == null) for main chat
-        if let Some(app) = app_id {
-            fields["app_id"] = json!({"stringValue": app});
-            fields["plugin_id"] = json!({"stringValue": app});
-        } else {
-            // For main chat (no app), explicitly set null values
-            fields["app_id"] = json!({"nullValue": null});
-            fields["plugin_id"] = json!({"nullValue": null});
+        let doc = json!({"fields": fields});
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update person error", &error_text));
         }
 
-        let doc = json!({"fields": fields});
+        Ok(())
+    }
+
+    /// Delete a person
+    pub async fn delete_person(
+        &self,
+        uid: &str,
+        person_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            PEOPLE_SUBCOLLECTION,
+            person_id
+        );
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::DELETE, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete person error", &error_text));
         }
 
-        tracing::info!(
-            "Created chat session {} for user {} with title={}",
-            session_id,
-            uid,
-            title.unwrap_or("New Chat")
-        );
-
-        Ok(ChatSessionDB {
-            id: session_id,
-            title: title.unwrap_or("New Chat").to_string(),
-            preview: None,
-            created_at: now,
-            updated_at: now,
-            app_id: app_id.map(|s| s.to_string()),
-            message_count: 0,
-            starred: false,
-        })
+        tracing::info!("Deleted person {} for user {}", person_id, uid);
+        Ok(())
     }
 
-    /// Get chat sessions for a user
-    /// Path: users/{uid}/chat_sessions
-    pub async fn get_chat_sessions(
+    /// Bulk assign segments in a conversation to a person or user, returning the
+    /// segments as they were before
+    pub async fn assign_segments_bulk(
         &self,
         uid: &str,
-        app_id: Option<&str>,
-        limit: usize,
-        offset: usize,
-        starred: Option<bool>,
-    ) -> Result<Vec<ChatSessionDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
+        conversation_id: &str,
+        segment_ids: &[String],
+        assign_type: &str,
+        value: Option<&str>,
+    ) -> Result<Vec<TranscriptSegment>, FirestoreError> {
+        // Get the current conversation to read segments
+        let conv = self.get_conversation(uid, conversation_id).await?
+            .ok_or_else(|| FirestoreError::NotFound("Conversation not found".to_string()))?;
+        let previous = conv.transcript_segments;
+        let mut segments = previous.clone();
 
-        // Filter by app_id (null = main Omi chat)
-        if let Some(app) = app_id {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "app_id"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": app}
-                }
-            }));
-        }
+        // Build a set of target segment IDs for fast lookup
+        let target_ids: std::collections::HashSet<&str> =
+            segment_ids.iter().map(|s| s.as_str()).collect();
 
-        // Filter by starred if specified
-        if let Some(is_starred) = starred {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "starred"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": is_starred}
+        // Update matching segments
+        for (idx, seg) in segments.iter_mut().enumerate() {
+            // Segments may not have explicit IDs in Firestore — match by index as string
+            let seg_id = idx.to_string();
+            if target_ids.contains(seg_id.as_str()) {
+                match assign_type {
+                    "is_user" => {
+                        seg.is_user = value.map(|v| v == "true").unwrap_or(false);
+                        if seg.is_user {
+                            seg.person_id = None;
+                        }
+                    }
+                    "person_id" => {
+                        seg.person_id = value.map(|s| s.to_string());
+                        seg.is_user = false;
+                    }
+                    _ => {}
                 }
-            }));
+            }
         }
 
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
+        self.update_conversation_transcript(uid, conversation_id, &segments).await?;
 
-        // Build structured query
-        // NOTE: Use created_at for ordering (not updated_at) for backward compatibility
-        // Old sessions from Flutter app don't have updated_at field
-        let mut structured_query = json!({
-            "from": [{"collectionId": CHAT_SESSIONS_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
-            "limit": limit,
-            "offset": offset
-        });
+        tracing::info!(
+            "Assigned {} segments in conversation {} for user {}",
+            segment_ids.len(),
+            conversation_id,
+            uid
+        );
+        Ok(previous)
+    }
 
-        if let Some(where_filter) = where_clause {
-            structured_query["where"] = where_filter;
+    /// Replace a conversation's transcript segments
+    pub async fn update_conversation_transcript(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        segments: &[TranscriptSegment],
+    ) -> Result<(), FirestoreError> {
+        let (fields, chunks) = self.transcript_fields(segments, uid);
+        if !chunks.is_empty() {
+            self.write_transcript_chunks(uid, conversation_id, &chunks).await?;
+        }
+
+        // transcript_segments and transcript_chunk_count are always in the mask, so the one
+        // not written is cleared
+        let mut mask = vec![
+            "updateMask.fieldPaths=transcript_segments",
+            "updateMask.fieldPaths=transcript_segments_compressed",
+            "updateMask.fieldPaths=transcript_chunk_count",
+            "updateMask.fieldPaths=updated_at",
+        ];
+        if fields.contains_key("data_protection_level") {
+            mask.push("updateMask.fieldPaths=data_protection_level");
         }
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id,
+            mask.join("&")
+        );
 
-        let query = json!({
-            "structuredQuery": structured_query
-        });
+        let mut fields = fields;
+        fields.insert("updated_at".to_string(), json!({"timestampValue": Utc::now().to_rfc3339()}));
+        let doc = json!({"fields": fields});
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore transcript update error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let sessions = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_chat_session(d).ok())
-            })
-            .collect();
+        Ok(())
+    }
 
-        Ok(sessions)
+    /// Parse a person document from Firestore
+    fn parse_person(
+        &self,
+        doc: &Value,
+    ) -> Result<crate::models::Person, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        // Incomplete notes are regenerated rather than failing the person
+        firestore_serde::drop_invalid::<crate::models::RelationshipNotes>(&mut object, "relationship_notes");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({"name": "", "created_at": Utc::now(), "updated_at": Utc::now()}),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
     }
 
-    /// Get a single chat session
-    /// Path: users/{uid}/chat_sessions/{session_id}
-    pub async fn get_chat_session(
+    // =========================================================================
+    // MEMOS - Quick voice-note style memos
+    // =========================================================================
+
+    /// Save a new memo
+    pub async fn create_memo(
         &self,
         uid: &str,
-        session_id: &str,
-    ) -> Result<Option<ChatSessionDB>, Box<dyn std::error::Error + Send + Sync>> {
+        memo: &Memo,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
+            MEMOS_SUBCOLLECTION,
+            memo.id
         );
 
+        let action_items: Vec<Value> = memo
+            .action_items
+            .iter()
+            .map(|a| json!({"stringValue": a}))
+            .collect();
+        let mut fields = json!({
+            "text": {"stringValue": memo.text},
+            "title": {"stringValue": memo.title},
+            "action_items": {"arrayValue": {"values": action_items}},
+            "created_at": {"timestampValue": memo.created_at.to_rfc3339()}
+        });
+        if let Some(language) = &memo.language {
+            fields["language"] = json!({"stringValue": language});
+        }
+
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Ok(None);
-        }
-
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore get error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create memo error", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        Ok(Some(self.parse_chat_session(&doc)?))
+        tracing::info!("Created memo {} for user {}", memo.id, uid);
+        Ok(())
     }
 
-    /// Update a chat session (title, starred, preview, message_count)
-    /// Path: users/{uid}/chat_sessions/{session_id}
-    pub async fn update_chat_session(
+    /// Get memos for a user, newest first
+    pub async fn get_memos(
         &self,
         uid: &str,
-        session_id: &str,
-        title: Option<&str>,
-        starred: Option<bool>,
-    ) -> Result<ChatSessionDB, Box<dyn std::error::Error + Send + Sync>> {
-        // First get the existing session
-        let existing = self.get_chat_session(uid, session_id).await?
-            .ok_or_else(|| format!("Chat session {} not found", session_id))?;
-
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
-        );
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Memo>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let now = Utc::now();
-        let mut fields = json!({
-            "title": {"stringValue": title.unwrap_or(&existing.title)},
-            "starred": {"booleanValue": starred.unwrap_or(existing.starred)},
-            "updated_at": {"timestampValue": now.to_rfc3339()},
-            "created_at": {"timestampValue": existing.created_at.to_rfc3339()},
-            "message_count": {"integerValue": existing.message_count.to_string()}
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": MEMOS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit,
+                "offset": offset
+            }
         });
 
-        if let Some(preview) = &existing.preview {
-            fields["preview"] = json!({"stringValue": preview});
-        }
-        if let Some(app) = &existing.app_id {
-            fields["app_id"] = json!({"stringValue": app});
-        }
-
-        let doc = json!({"fields": fields});
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        tracing::info!(
-            "Updated chat session {} for user {}",
-            session_id,
-            uid
-        );
-
-        Ok(ChatSessionDB {
-            id: session_id.to_string(),
-            title: title.unwrap_or(&existing.title).to_string(),
-            preview: existing.preview,
-            created_at: existing.created_at,
-            updated_at: now,
-            app_id: existing.app_id,
-            message_count: existing.message_count,
-            starred: starred.unwrap_or(existing.starred),
-        })
-    }
-
-    /// Update chat session preview and message count (called when new message is added)
-    pub async fn update_chat_session_with_message(
-        &self,
-        uid: &str,
-        session_id: &str,
-        preview: &str,
-        title: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // First get the existing session
-        let existing = match self.get_chat_session(uid, session_id).await? {
-            Some(s) => s,
-            None => return Ok(()), // Session doesn't exist, skip update
-        };
+        let results: Vec<Value> = response.json().await?;
+        let memos = results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_memo(d).ok()))
+            .collect();
+
+        Ok(memos)
+    }
 
+    /// Delete a memo
+    pub async fn delete_memo(
+        &self,
+        uid: &str,
+        memo_id: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
+            MEMOS_SUBCOLLECTION,
+            memo_id
         );
 
-        let now = Utc::now();
-        let new_count = existing.message_count + 1;
-
-        // Use provided title or keep existing (title is auto-generated from first message)
-        let final_title = title.unwrap_or(&existing.title);
-
-        let mut fields = json!({
-            "title": {"stringValue": final_title},
-            "preview": {"stringValue": preview.chars().take(100).collect::<String>()},
-            "updated_at": {"timestampValue": now.to_rfc3339()},
-            "created_at": {"timestampValue": existing.created_at.to_rfc3339()},
-            "message_count": {"integerValue": new_count.to_string()},
-            "starred": {"booleanValue": existing.starred}
-        });
-
-        if let Some(app) = &existing.app_id {
-            fields["app_id"] = json!({"stringValue": app});
-        }
-
-        let doc = json!({"fields": fields});
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::DELETE, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::warn!("Failed to update chat session with message: {}", error_text);
+            return Err(FirestoreError::from_response(status, "Firestore delete memo error", &error_text));
         }
 
+        tracing::info!("Deleted memo {} for user {}", memo_id, uid);
         Ok(())
     }
 
-    /// Delete a chat session and its associated messages
-    /// Path: users/{uid}/chat_sessions/{session_id}
-    pub async fn delete_chat_session(
+    fn parse_memo(&self, doc: &Value) -> Result<Memo, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
+    }
+
+    // =========================================================================
+    // CONVERSATION TEMPLATES - Summary structure for recurring meeting types
+    // =========================================================================
+
+    /// Create or replace a conversation template
+    pub async fn save_conversation_template(
         &self,
         uid: &str,
-        session_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // First, delete all messages with this session_id
-        if let Err(e) = self.delete_messages_by_session(uid, session_id).await {
-            tracing::warn!("Failed to delete messages for session {}: {}", session_id, e);
-            // Continue with session deletion
-        }
-
-        // Delete the session document
+        template: &ConversationTemplate,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
+            CONVERSATION_TEMPLATES_SUBCOLLECTION,
+            template.id
         );
 
+        let fields = firestore_serde::to_fields(template, &["id"])?;
+
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore save template error", &error_text));
         }
-
-        tracing::info!("Deleted chat session {} for user {}", session_id, uid);
         Ok(())
     }
 
-    /// Delete all messages with a specific session_id
-    async fn delete_messages_by_session(
+    /// All of a user's conversation templates, oldest first
+    pub async fn get_conversation_templates(
         &self,
         uid: &str,
-        session_id: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Vec<ConversationTemplate>, FirestoreError> {
         let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        // Query messages with this session_id
-        let structured_query = json!({
-            "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
-            "where": {
-                "fieldFilter": {
-                    "field": {"fieldPath": "session_id"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": session_id}
-                }
-            },
-            "limit": 500  // Batch delete limit
-        });
-
         let query = json!({
-            "structuredQuery": structured_query
+            "structuredQuery": {
+                "from": [{"collectionId": CONVERSATION_TEMPLATES_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "ASCENDING"}],
+                "limit": crate::models::MAX_TEMPLATES
+            }
         });
 
         let response = self
@@ -6032,252 +13628,103 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to query messages: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
-        let mut deleted_count = 0;
+        Ok(results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_conversation_template(d).ok()))
+            .collect())
+    }
 
-        // Delete each message
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Some(name) = doc.get("name").and_then(|n| n.as_str()) {
-                    // Extract the full document path for deletion
-                    let delete_url = format!(
-                        "https://firestore.googleapis.com/v1/{}",
-                        name
-                    );
+    /// Delete a conversation template
+    pub async fn delete_conversation_template(
+        &self,
+        uid: &str,
+        template_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATION_TEMPLATES_SUBCOLLECTION,
+            template_id
+        );
 
-                    let delete_response = self
-                        .build_request(reqwest::Method::DELETE, &delete_url)
-                        .await?
-                        .send()
-                        .await?;
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
 
-                    if delete_response.status().is_success() {
-                        deleted_count += 1;
-                    }
-                }
-            }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete template error", &error_text));
         }
-
-        tracing::info!(
-            "Deleted {} messages for session {} (user {})",
-            deleted_count,
-            session_id,
-            uid
-        );
-
-        Ok(deleted_count)
+        Ok(())
     }
 
-    /// Parse a chat session from Firestore document
-    /// Supports both old (Flutter) and new (Desktop) session formats for backward compatibility
-    fn parse_chat_session(
+    fn parse_conversation_template(
         &self,
         doc: &Value,
-    ) -> Result<ChatSessionDB, Box<dyn std::error::Error + Send + Sync>> {
-        let name = doc
-            .get("name")
-            .and_then(|n| n.as_str())
-            .ok_or("Missing document name")?;
-
-        let id = name.split('/').last().unwrap_or("unknown").to_string();
-
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-
-        let created_at = self
-            .parse_timestamp_optional(fields, "created_at")
-            .unwrap_or_else(Utc::now);
-
-        // For message_count: prefer explicit field, fallback to message_ids array length (old format)
-        let message_count = self.parse_int(fields, "message_count").unwrap_or_else(|| {
-            // Old Flutter sessions store message IDs in an array
-            fields
-                .get("message_ids")
-                .and_then(|v| v.get("arrayValue"))
-                .and_then(|a| a.get("values"))
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.len() as i32)
-                .unwrap_or(0)
-        });
-
-        // For app_id: fallback to plugin_id (old Flutter format)
-        let app_id = self
-            .parse_string(fields, "app_id")
-            .or_else(|| self.parse_string(fields, "plugin_id"));
-
-        // For title: use explicit title, or "Omi" for main chat (app_id=null), or "New Chat"
-        // This helps users recognize their main Omi chat from old Flutter sessions
-        let title = self.parse_string(fields, "title").unwrap_or_else(|| {
-            if app_id.is_none() {
-                "Omi".to_string()
-            } else {
-                "New Chat".to_string()
-            }
-        });
-
-        Ok(ChatSessionDB {
-            id,
-            title,
-            preview: self.parse_string(fields, "preview"),
-            created_at,
-            // For updated_at: fallback to created_at (old sessions don't have updated_at)
-            updated_at: self
-                .parse_timestamp_optional(fields, "updated_at")
-                .unwrap_or(created_at),
-            app_id,
-            message_count,
-            starred: self.parse_bool(fields, "starred").unwrap_or(false),
-        })
+    ) -> Result<ConversationTemplate, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
     }
 
     // =========================================================================
-    // ADVICE
+    // WEEKLY REVIEWS - LLM-written look back over a week
     // =========================================================================
 
-    /// Create a new advice entry
-    /// Path: users/{uid}/advice/{advice_id}
-    pub async fn create_advice(
+    /// Create or replace the review for its week
+    pub async fn save_weekly_review(
         &self,
         uid: &str,
-        content: &str,
-        category: Option<AdviceCategory>,
-        reasoning: Option<&str>,
-        source_app: Option<&str>,
-        confidence: Option<f64>,
-        context_summary: Option<&str>,
-        current_activity: Option<&str>,
-    ) -> Result<AdviceDB, Box<dyn std::error::Error + Send + Sync>> {
-        let advice_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
-
+        review: &WeeklyReview,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ADVICE_SUBCOLLECTION,
-            advice_id
+            WEEKLY_REVIEWS_SUBCOLLECTION,
+            review.week_start.format("%Y-%m-%d")
         );
-
-        let category_str = match category.unwrap_or(AdviceCategory::Other) {
-            AdviceCategory::Productivity => "productivity",
-            AdviceCategory::Health => "health",
-            AdviceCategory::Communication => "communication",
-            AdviceCategory::Learning => "learning",
-            AdviceCategory::Other => "other",
-        };
-
-        let mut fields = json!({
-            "content": {"stringValue": content},
-            "category": {"stringValue": category_str},
-            "confidence": {"doubleValue": confidence.unwrap_or(0.5)},
-            "is_read": {"booleanValue": false},
-            "is_dismissed": {"booleanValue": false},
-            "created_at": {"timestampValue": now.to_rfc3339()}
-        });
-
-        if let Some(r) = reasoning {
-            fields["reasoning"] = json!({"stringValue": r});
-        }
-        if let Some(app) = source_app {
-            fields["source_app"] = json!({"stringValue": app});
-        }
-        if let Some(summary) = context_summary {
-            fields["context_summary"] = json!({"stringValue": summary});
-        }
-        if let Some(activity) = current_activity {
-            fields["current_activity"] = json!({"stringValue": activity});
-        }
-
-        let doc = json!({"fields": fields});
+        let fields = firestore_serde::to_fields(review, &[])?;
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore save weekly review error", &error_text));
         }
-
-        let created_doc: Value = response.json().await?;
-        let advice = self.parse_advice(&created_doc)?;
-
-        tracing::info!("Created advice {} for user {}", advice_id, uid);
-        Ok(advice)
+        Ok(())
     }
 
-    /// Get advice for a user
-    /// Path: users/{uid}/advice
-    pub async fn get_advice(
+    /// A user's weekly reviews, most recent week first
+    pub async fn get_weekly_reviews(
         &self,
         uid: &str,
-        limit: usize,
-        offset: usize,
-        category: Option<&str>,
-        include_dismissed: bool,
-    ) -> Result<Vec<AdviceDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
-
-        // Filter out dismissed unless requested
-        if !include_dismissed {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "is_dismissed"},
-                    "op": "EQUAL",
-                    "value": {"booleanValue": false}
-                }
-            }));
-        }
-
-        // Filter by category if specified
-        if let Some(cat) = category {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "category"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": cat}
-                }
-            }));
-        }
-
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
-
-        // Build structured query
-        let mut structured_query = json!({
-            "from": [{"collectionId": ADVICE_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
-            "limit": limit,
-            "offset": offset
-        });
-
-        if let Some(where_filter) = where_clause {
-            structured_query["where"] = where_filter;
-        }
+        limit: usize,
+    ) -> Result<Vec<WeeklyReview>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
         let query = json!({
-            "structuredQuery": structured_query
+            "structuredQuery": {
+                "from": [{"collectionId": WEEKLY_REVIEWS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "week_start"}, "direction": "DESCENDING"}],
+                "limit": limit
+            }
         });
 
         let response = self
@@ -6288,279 +13735,415 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
-        let advice_list = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_advice(d).ok())
-            })
-            .collect();
-
-        Ok(advice_list)
+        Ok(results
+            .iter()
+            .filter_map(|r| r.get("document")?.get("fields"))
+            .filter_map(|fields| self.parse_weekly_review(fields))
+            .collect())
     }
 
-    /// Update advice (mark as read/dismissed)
-    pub async fn update_advice(
+    /// The review for the week starting on `week_start`, if one exists
+    pub async fn get_weekly_review(
         &self,
         uid: &str,
-        advice_id: &str,
-        is_read: Option<bool>,
-        is_dismissed: Option<bool>,
-    ) -> Result<AdviceDB, Box<dyn std::error::Error + Send + Sync>> {
-        let mut field_paths: Vec<&str> = vec!["updated_at"];
-        let mut fields = json!({
-            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
-        });
-
-        if let Some(read) = is_read {
-            field_paths.push("is_read");
-            fields["is_read"] = json!({"booleanValue": read});
-        }
-
-        if let Some(dismissed) = is_dismissed {
-            field_paths.push("is_dismissed");
-            fields["is_dismissed"] = json!({"booleanValue": dismissed});
-        }
-
-        let update_mask = field_paths
-            .iter()
-            .map(|f| format!("updateMask.fieldPaths={}", f))
-            .collect::<Vec<_>>()
-            .join("&");
-
+        week_start: &str,
+    ) -> Result<Option<WeeklyReview>, FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}?{}",
+            "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ADVICE_SUBCOLLECTION,
-            advice_id,
-            update_mask
+            WEEKLY_REVIEWS_SUBCOLLECTION,
+            week_start
         );
 
-        let doc = json!({"fields": fields});
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get failed", &error_text));
         }
 
-        let updated_doc: Value = response.json().await?;
-        let advice = self.parse_advice(&updated_doc)?;
+        let doc: Value = response.json().await?;
+        Ok(doc.get("fields").and_then(|fields| self.parse_weekly_review(fields)))
+    }
 
-        tracing::info!("Updated advice {} for user {}", advice_id, uid);
-        Ok(advice)
+    fn parse_weekly_review(&self, fields: &Value) -> Option<WeeklyReview> {
+        firestore_serde::from_fields(fields).ok()
     }
 
-    /// Delete advice permanently
-    pub async fn delete_advice(
+    // =========================================================================
+    // NOTIFICATIONS - In-app notification inbox
+    // =========================================================================
+
+    /// Store a notification in the user's inbox
+    pub async fn save_notification(
         &self,
         uid: &str,
-        advice_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        notification: &Notification,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            ADVICE_SUBCOLLECTION,
-            advice_id
+            NOTIFICATIONS_SUBCOLLECTION,
+            notification.id
         );
+        let fields = firestore_serde::to_fields(notification, &["id"])?;
 
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore save notification error", &error_text));
         }
-
-        tracing::info!("Deleted advice {} for user {}", advice_id, uid);
         Ok(())
     }
 
-    /// Mark all advice as read for a user
-    pub async fn mark_all_advice_read(
+    /// A page of the user's inbox, newest first
+    pub async fn get_notifications(
         &self,
         uid: &str,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // Get all unread advice
-        let advice_list = self.get_advice(uid, 1000, 0, None, false).await?;
-        let unread: Vec<_> = advice_list.iter().filter(|a| !a.is_read).collect();
-        let count = unread.len();
+        limit: usize,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        // Update each one
-        for advice in unread {
-            let _ = self.update_advice(uid, &advice.id, Some(true), None).await;
+        let mut structured_query = json!({
+            "from": [{"collectionId": NOTIFICATIONS_SUBCOLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit
+        });
+        if unread_only {
+            structured_query["where"] = json!({"fieldFilter": {
+                "field": {"fieldPath": "read"},
+                "op": "EQUAL",
+                "value": {"booleanValue": false}
+            }});
         }
 
-        Ok(count)
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&json!({"structuredQuery": structured_query}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
+
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .iter()
+            .filter_map(|r| r.get("document"))
+            .filter_map(|doc| firestore_serde::from_document(doc).ok())
+            .collect())
     }
 
-    /// Parse Firestore document to AdviceDB
-    fn parse_advice(
+    /// Mark notifications read. Returns how many were updated and the IDs that don't exist.
+    pub async fn mark_notifications_read(
         &self,
-        doc: &Value,
-    ) -> Result<AdviceDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
-
-        let category_str = self.parse_string(fields, "category").unwrap_or_else(|| "other".to_string());
-        let category = match category_str.as_str() {
-            "productivity" => AdviceCategory::Productivity,
-            "health" => AdviceCategory::Health,
-            "communication" => AdviceCategory::Communication,
-            "learning" => AdviceCategory::Learning,
-            _ => AdviceCategory::Other,
-        };
+        uid: &str,
+        notification_ids: &[String],
+    ) -> Result<(usize, Vec<String>), FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        let url = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:batchWrite",
+            self.project_id()
+        );
+        let mut updated = 0;
+        let mut not_found = Vec::new();
 
-        Ok(AdviceDB {
-            id,
-            content: self.parse_string(fields, "content").unwrap_or_default(),
-            category,
-            reasoning: self.parse_string(fields, "reasoning"),
-            source_app: self.parse_string(fields, "source_app"),
-            confidence: self.parse_float(fields, "confidence").unwrap_or(0.5),
-            context_summary: self.parse_string(fields, "context_summary"),
-            current_activity: self.parse_string(fields, "current_activity"),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at"),
-            is_read: self.parse_bool(fields, "is_read").unwrap_or(false),
-            is_dismissed: self.parse_bool(fields, "is_dismissed").unwrap_or(false),
-        })
+        for chunk in notification_ids.chunks(500) {
+            let writes: Vec<Value> = chunk
+                .iter()
+                .map(|id| {
+                    json!({
+                        "update": {
+                            "name": format!(
+                                "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                                self.project_id(), USERS_COLLECTION, uid, NOTIFICATIONS_SUBCOLLECTION, id
+                            ),
+                            "fields": {
+                                "read": {"booleanValue": true},
+                                "read_at": {"timestampValue": now}
+                            }
+                        },
+                        "updateMask": {"fieldPaths": ["read", "read_at"]},
+                        "currentDocument": {"exists": true}
+                    })
+                })
+                .collect();
+
+            let response = self
+                .build_request(reqwest::Method::POST, &url)
+                .await?
+                .json(&json!({ "writes": writes }))
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch write error", &error_text));
+            }
+
+            // One status per write; 5 = NOT_FOUND (precondition on a missing document)
+            let result: Value = response.json().await?;
+            let statuses = result.get("status").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+            for (id, status) in chunk.iter().zip(statuses.iter()) {
+                match status.get("code").and_then(|c| c.as_i64()).unwrap_or(0) {
+                    0 => updated += 1,
+                    5 => not_found.push(id.clone()),
+                    code => {
+                        let message = status.get("message").and_then(|m| m.as_str()).unwrap_or("");
+                        return Err(format!("Firestore batch write failed for notification {} ({}): {}", id, code, message).into());
+                    }
+                }
+            }
+        }
+
+        Ok((updated, not_found))
     }
 
-    // =========================================================================
-    // Desktop Releases (for Sparkle auto-update)
-    // =========================================================================
+    /// Mark the whole inbox read; returns how many notifications changed
+    pub async fn mark_all_notifications_read(
+        &self,
+        uid: &str,
+    ) -> Result<usize, FirestoreError> {
+        let mut updated = 0;
+        loop {
+            let ids: Vec<String> = self
+                .get_notifications(uid, MAX_NOTIFICATION_READ_IDS, true)
+                .await?
+                .into_iter()
+                .map(|n| n.id)
+                .collect();
+            if ids.is_empty() {
+                break;
+            }
+            let page = ids.len();
+            updated += self.mark_notifications_read(uid, &ids).await?.0;
+            if page < MAX_NOTIFICATION_READ_IDS {
+                break;
+            }
+        }
+        tracing::info!("Marked {} notifications read for user {}", updated, uid);
+        Ok(updated)
+    }
 
-    /// Get desktop releases for auto-update appcast
-    /// Fetches from desktop_releases collection
-    pub async fn get_desktop_releases(
+    /// Delete one notification; false when it doesn't exist
+    pub async fn delete_notification(
         &self,
-    ) -> Result<Vec<crate::routes::updates::ReleaseInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        uid: &str,
+        notification_id: &str,
+    ) -> Result<bool, FirestoreError> {
         let url = format!(
-            "{}/desktop_releases",
-            self.base_url()
+            "{}/{}/{}/{}/{}?currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            NOTIFICATIONS_SUBCOLLECTION,
+            notification_id
         );
 
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::DELETE, &url)
             .await?
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
         if !response.status().is_success() {
-            // If collection doesn't exist, return empty list
-            if response.status() == reqwest::StatusCode::NOT_FOUND {
-                return Ok(vec![]);
-            }
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete notification error", &error_text));
         }
+        Ok(true)
+    }
 
-        let data: Value = response.json().await?;
-        let mut releases = Vec::new();
+    /// Delete notifications past the newest `keep`, and any created before `cutoff`.
+    /// Removes at most a few hundred per call; runs after every write so the excess stays small.
+    pub async fn trim_notifications(
+        &self,
+        uid: &str,
+        keep: usize,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let newest_first = json!([{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}]);
+        let queries = [
+            json!({
+                "from": [{"collectionId": NOTIFICATIONS_SUBCOLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "orderBy": newest_first,
+                "offset": keep,
+                "limit": 500
+            }),
+            json!({
+                "from": [{"collectionId": NOTIFICATIONS_SUBCOLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {"fieldFilter": {
+                    "field": {"fieldPath": "created_at"},
+                    "op": "LESS_THAN",
+                    "value": {"timestampValue": cutoff.to_rfc3339()}
+                }},
+                "limit": 500
+            }),
+        ];
 
-        if let Some(documents) = data.get("documents").and_then(|d| d.as_array()) {
-            for doc in documents {
-                if let Ok(release) = self.parse_release(doc) {
-                    releases.push(release);
-                }
+        let mut names = std::collections::BTreeSet::new();
+        for structured_query in queries {
+            let response = self
+                .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+                .await?
+                .json(&json!({"structuredQuery": structured_query}))
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
             }
-        }
 
-        // Sort by build number descending (newest first)
-        releases.sort_by(|a, b| b.build_number.cmp(&a.build_number));
+            let results: Vec<Value> = response.json().await?;
+            names.extend(
+                results
+                    .iter()
+                    .filter_map(|r| r.get("document")?.get("name")?.as_str())
+                    .map(str::to_string),
+            );
+        }
 
-        Ok(releases)
+        let trimmed = names.len();
+        if trimmed > 0 {
+            let writes: Vec<Value> = names.into_iter().map(|name| json!({"delete": name})).collect();
+            self.commit_batched_writes(writes).await?;
+            tracing::info!("Trimmed {} notifications for user {}", trimmed, uid);
+        }
+        Ok(trimmed)
     }
 
-    /// Parse Firestore document to ReleaseInfo
-    fn parse_release(
+    // =========================================================================
+    // KNOWLEDGE GRAPH - Nodes and Edges for 3D Memory Visualization
+    // =========================================================================
+
+    /// Create or update a knowledge graph node
+    pub async fn upsert_kg_node(
         &self,
-        doc: &Value,
-    ) -> Result<crate::routes::updates::ReleaseInfo, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
+        uid: &str,
+        node: &crate::models::KnowledgeGraphNode,
+    ) -> Result<String, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            KG_NODES_SUBCOLLECTION,
+            node.id
+        );
+
+        // Build aliases arrays
+        let aliases_values: Vec<Value> = node.aliases
+            .iter()
+            .map(|a| json!({"stringValue": a}))
+            .collect();
+
+        let aliases_lower_values: Vec<Value> = node.aliases_lower
+            .iter()
+            .map(|a| json!({"stringValue": a}))
+            .collect();
+
+        let memory_ids_values: Vec<Value> = node.memory_ids
+            .iter()
+            .map(|m| json!({"stringValue": m}))
+            .collect();
+
+        let doc = json!({
+            "fields": {
+                "id": {"stringValue": &node.id},
+                "label": {"stringValue": &node.label},
+                "node_type": {"stringValue": node.node_type.to_string()},
+                "aliases": {"arrayValue": {"values": aliases_values}},
+                "aliases_lower": {"arrayValue": {"values": aliases_lower_values}},
+                "memory_ids": {"arrayValue": {"values": memory_ids_values}},
+                "label_lower": {"stringValue": &node.label_lower},
+                "created_at": {"timestampValue": node.created_at.to_rfc3339()},
+                "updated_at": {"timestampValue": node.updated_at.to_rfc3339()}
+            }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&doc)
+            .send()
+            .await?;
 
-        let changelog = if let Some(arr) = fields.get("changelog").and_then(|c| c.get("arrayValue")).and_then(|a| a.get("values")).and_then(|v| v.as_array()) {
-            arr.iter()
-                .filter_map(|v| v.get("stringValue").and_then(|s| s.as_str()))
-                .map(|s| s.to_string())
-                .collect()
-        } else {
-            vec![]
-        };
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to upsert KG node", &error_text));
+        }
 
-        // channel: None = unpromoted (staging), Some("stable") = promoted stable
-        let channel = self.parse_string(fields, "channel");
-
-        Ok(crate::routes::updates::ReleaseInfo {
-            version: self.parse_string(fields, "version").unwrap_or_default(),
-            build_number: self.parse_int(fields, "build_number").unwrap_or(0) as u32,
-            download_url: self.parse_string(fields, "download_url").unwrap_or_default(),
-            ed_signature: self.parse_string(fields, "ed_signature").unwrap_or_default(),
-            published_at: self.parse_string(fields, "published_at").unwrap_or_default(),
-            changelog,
-            is_live: self.parse_bool(fields, "is_live").unwrap_or(false),
-            is_critical: self.parse_bool(fields, "is_critical").unwrap_or(false),
-            channel,
-        })
+        Ok(node.id.clone())
     }
 
-    /// Create a new desktop release in Firestore
-    pub async fn create_desktop_release(
+    /// Create or update a knowledge graph edge
+    pub async fn upsert_kg_edge(
         &self,
-        release: &crate::routes::updates::ReleaseInfo,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let doc_id = format!("v{}+{}", release.version, release.build_number);
-
+        uid: &str,
+        edge: &crate::models::KnowledgeGraphEdge,
+    ) -> Result<String, FirestoreError> {
         let url = format!(
-            "{}/desktop_releases/{}",
+            "{}/{}/{}/{}/{}",
             self.base_url(),
-            doc_id
+            USERS_COLLECTION,
+            uid,
+            KG_EDGES_SUBCOLLECTION,
+            edge.id
         );
 
-        // Build changelog array
-        let changelog_values: Vec<Value> = release.changelog
+        let memory_ids_values: Vec<Value> = edge.memory_ids
             .iter()
-            .map(|s| json!({"stringValue": s}))
+            .map(|m| json!({"stringValue": m}))
             .collect();
 
-        // Channel field: always a string. None/empty → "staging" (unpromoted default)
-        let channel_value = match &release.channel {
-            Some(ch) if !ch.is_empty() => json!({"stringValue": ch}),
-            _ => json!({"stringValue": "staging"}),
-        };
-
         let doc = json!({
             "fields": {
-                "version": {"stringValue": release.version},
-                "build_number": {"integerValue": release.build_number.to_string()},
-                "download_url": {"stringValue": release.download_url},
-                "ed_signature": {"stringValue": release.ed_signature},
-                "published_at": {"stringValue": release.published_at},
-                "changelog": {"arrayValue": {"values": changelog_values}},
-                "is_live": {"booleanValue": release.is_live},
-                "is_critical": {"booleanValue": release.is_critical},
-                "channel": channel_value
+                "id": {"stringValue": &edge.id},
+                "source_id": {"stringValue": &edge.source_id},
+                "target_id": {"stringValue": &edge.target_id},
+                "label": {"stringValue": &edge.label},
+                "memory_ids": {"arrayValue": {"values": memory_ids_values}},
+                "created_at": {"timestampValue": edge.created_at.to_rfc3339()}
             }
         });
 
@@ -6572,25 +14155,25 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to upsert KG edge", &error_text));
         }
 
-        tracing::info!("Created desktop release: {}", doc_id);
-        Ok(doc_id)
+        Ok(edge.id.clone())
     }
 
-    /// Promote a desktop release to the next channel: staging → beta → stable
-    /// Returns (old_channel, new_channel)
-    pub async fn promote_desktop_release(
+    /// Get all knowledge graph nodes for a user
+    pub async fn get_kg_nodes(
         &self,
-        doc_id: &str,
-    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
-        // Fetch the current document
+        uid: &str,
+    ) -> Result<Vec<crate::models::KnowledgeGraphNode>, FirestoreError> {
         let url = format!(
-            "{}/desktop_releases/{}",
+            "{}/{}/{}/{}",
             self.base_url(),
-            doc_id
+            USERS_COLLECTION,
+            uid,
+            KG_NODES_SUBCOLLECTION
         );
 
         let response = self
@@ -6600,711 +14183,845 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Release not found: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to get KG nodes", &error_text));
         }
 
-        let doc: Value = response.json().await?;
-        let fields = doc.get("fields").ok_or("Missing fields in document")?;
-        let current_channel = self.parse_string(fields, "channel").unwrap_or_default();
+        let result: Value = response.json().await?;
+        let documents = result.get("documents").and_then(|d| d.as_array());
 
-        // Determine next channel
-        let (old_channel, new_channel_value) = match current_channel.as_str() {
-            "staging" | "" => ("staging".to_string(), json!({"stringValue": "beta"})),
-            "beta" => ("beta".to_string(), json!({"stringValue": "stable"})),
-            "stable" => return Err("Release is already on stable channel, cannot promote further".into()),
-            other => return Err(format!("Unknown channel '{}', cannot promote", other).into()),
-        };
+        let nodes: Vec<crate::models::KnowledgeGraphNode> = documents
+            .map(|docs| {
+                docs.iter()
+                    .filter_map(|doc| self.parse_kg_node(doc).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
 
-        let new_channel = new_channel_value.get("stringValue").and_then(|v| v.as_str()).unwrap().to_string();
+        tracing::info!("Found {} KG nodes for user {}", nodes.len(), uid);
+        Ok(nodes)
+    }
 
-        // PATCH only the channel field
-        let patch_url = format!(
-            "{}/desktop_releases/{}?updateMask.fieldPaths=channel",
+    /// Get all knowledge graph edges for a user
+    pub async fn get_kg_edges(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<crate::models::KnowledgeGraphEdge>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}",
             self.base_url(),
-            doc_id
+            USERS_COLLECTION,
+            uid,
+            KG_EDGES_SUBCOLLECTION
         );
 
-        let patch_doc = json!({
-            "fields": {
-                "channel": new_channel_value
-            }
-        });
-
         let response = self
-            .build_request(reqwest::Method::PATCH, &patch_url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&patch_doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to update channel: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to get KG edges", &error_text));
         }
 
-        tracing::info!("Promoted release {}: {} → {}", doc_id, old_channel, new_channel);
+        let result: Value = response.json().await?;
+        let documents = result.get("documents").and_then(|d| d.as_array());
 
-        Ok((old_channel, new_channel))
-    }
+        let edges: Vec<crate::models::KnowledgeGraphEdge> = documents
+            .map(|docs| {
+                docs.iter()
+                    .filter_map(|doc| self.parse_kg_edge(doc).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
 
-    // =========================================================================
-    // MESSAGES (Chat Persistence)
-    // =========================================================================
+        tracing::info!("Found {} KG edges for user {}", edges.len(), uid);
+        Ok(edges)
+    }
 
-    /// Get or create a chat session for the given app_id (None = main default chat).
-    /// Mirrors Python's `acquire_chat_session()`:
-    ///   1. List all chat_sessions, find one matching plugin_id
-    ///   2. If none exists, create one with {id, created_at, plugin_id}
-    /// Returns the session ID.
-    pub async fn acquire_chat_session(
+    /// Delete all knowledge graph data for a user
+    pub async fn delete_kg_data(
         &self,
         uid: &str,
-        app_id: Option<&str>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Step 1: Fetch all chat sessions and find a matching one client-side.
-        // We can't use `WHERE plugin_id == null` via REST API — it doesn't match
-        // documents where the field is absent or was set to null by the Python SDK.
-        let list_url = format!(
-            "{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            CHAT_SESSIONS_SUBCOLLECTION
+    ) -> Result<(), FirestoreError> {
+        // Delete all nodes
+        let nodes = self.get_kg_nodes(uid).await?;
+        for node in &nodes {
+            let url = format!(
+                "{}/{}/{}/{}/{}",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                KG_NODES_SUBCOLLECTION,
+                node.id
+            );
+            let _ = self
+                .build_request(reqwest::Method::DELETE, &url)
+                .await?
+                .send()
+                .await;
+        }
+
+        // Delete all edges
+        let edges = self.get_kg_edges(uid).await?;
+        for edge in &edges {
+            let url = format!(
+                "{}/{}/{}/{}/{}",
+                self.base_url(),
+                USERS_COLLECTION,
+                uid,
+                KG_EDGES_SUBCOLLECTION,
+                edge.id
+            );
+            let _ = self
+                .build_request(reqwest::Method::DELETE, &url)
+                .await?
+                .send()
+                .await;
+        }
+
+        tracing::info!("Deleted {} nodes and {} edges for user {}", nodes.len(), edges.len(), uid);
+        Ok(())
+    }
+
+    /// Parse a knowledge graph node from Firestore document
+    fn parse_kg_node(&self, doc: &Value) -> Result<crate::models::KnowledgeGraphNode, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        firestore_serde::drop_invalid::<crate::models::NodeType>(&mut object, "node_type");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({
+                "label": "",
+                "node_type": "concept",
+                "created_at": Utc::now(),
+                "updated_at": Utc::now(),
+            }),
         );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
 
-        let response = self
-            .build_request(reqwest::Method::GET, &list_url)
-            .await?
-            .send()
-            .await?;
+    /// Parse a knowledge graph edge from Firestore document
+    fn parse_kg_edge(&self, doc: &Value) -> Result<crate::models::KnowledgeGraphEdge, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
+    }
 
-        if response.status().is_success() {
-            let body: Value = response.json().await?;
-            if let Some(documents) = body.get("documents").and_then(|d| d.as_array()) {
-                for doc in documents {
-                    let fields = match doc.get("fields") {
-                        Some(f) => f,
-                        None => continue,
-                    };
+    // =========================================================================
+    // AGENT VM
+    // =========================================================================
 
-                    let matches = match app_id {
-                        Some(target_app) => {
-                            // Looking for a session with this specific plugin_id
-                            fields
-                                .get("plugin_id")
-                                .and_then(|v| v.get("stringValue"))
-                                .and_then(|v| v.as_str())
-                                == Some(target_app)
-                        }
-                        None => {
-                            // Looking for main chat: both plugin_id AND app_id must be
-                            // null, absent, or empty.  Without the app_id check, task-chat
-                            // sessions (plugin_id=null, app_id="task-chat") match falsely.
-                            let plugin_id_null = match fields.get("plugin_id") {
-                                None => true,
-                                Some(val) => {
-                                    val.get("nullValue").is_some()
-                                        || val
-                                            .get("stringValue")
-                                            .and_then(|v| v.as_str())
-                                            .map_or(false, |s| s.is_empty())
-                                }
-                            };
-                            let app_id_null = match fields.get("app_id") {
-                                None => true,
-                                Some(val) => {
-                                    val.get("nullValue").is_some()
-                                        || val
-                                            .get("stringValue")
-                                            .and_then(|v| v.as_str())
-                                            .map_or(false, |s| s.is_empty())
-                                }
-                            };
-                            plugin_id_null && app_id_null
-                        }
-                    };
+    /// Get agent VM info for a user from the agentVm field on their user document
+    pub async fn get_agent_vm(
+        &self,
+        uid: &str,
+    ) -> Result<Option<crate::models::agent::AgentVm>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
 
-                    if matches {
-                        if let Some(doc_id) = doc
-                            .get("name")
-                            .and_then(|n| n.as_str())
-                            .and_then(|name| name.split('/').last())
-                        {
-                            tracing::info!(
-                                "Found existing chat session {} for user {} (app_id={:?})",
-                                doc_id,
-                                uid,
-                                app_id
-                            );
-                            return Ok(doc_id.to_string());
-                        }
-                    }
-                }
-            }
+        let agent_vm = fields.get("agentVm");
+        if agent_vm.is_none() {
+            return Ok(None);
         }
 
-        // Step 2: No matching session found — create one (mirrors Python's ChatSession model)
-        let session_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
+        let map_value = agent_vm
+            .and_then(|v| v.get("mapValue"))
+            .and_then(|v| v.get("fields"));
 
-        let create_url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            CHAT_SESSIONS_SUBCOLLECTION,
-            session_id
-        );
+        if map_value.is_none() {
+            return Ok(None);
+        }
 
-        let mut session_fields = json!({
-            "id": {"stringValue": &session_id},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "message_ids": {"arrayValue": {"values": []}},
-            "file_ids": {"arrayValue": {"values": []}}
-        });
+        let f = map_value.unwrap();
 
-        if let Some(app) = app_id {
-            session_fields["plugin_id"] = json!({"stringValue": app});
-            session_fields["app_id"] = json!({"stringValue": app});
-        } else {
-            session_fields["plugin_id"] = json!({"nullValue": null});
-            session_fields["app_id"] = json!({"nullValue": null});
+        let vm_name = f
+            .get("vmName")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        if vm_name.is_empty() {
+            return Ok(None);
         }
 
-        let doc = json!({"fields": session_fields});
+        let zone = f
+            .get("zone")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("us-central1-a")
+            .to_string();
 
-        let response = self
-            .build_request(reqwest::Method::PATCH, &create_url)
-            .await?
-            .json(&doc)
-            .send()
-            .await?;
+        let ip = f
+            .get("ip")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Failed to create chat session: {}", error_text).into());
-        }
+        let status_str = f
+            .get("status")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("provisioning");
 
-        tracing::info!(
-            "Created new chat session {} for user {} (app_id={:?})",
-            session_id,
-            uid,
-            app_id
-        );
-        Ok(session_id)
+        let status = match status_str {
+            "ready" => crate::models::agent::AgentVmStatus::Ready,
+            "stopped" => crate::models::agent::AgentVmStatus::Stopped,
+            "error" => crate::models::agent::AgentVmStatus::Error,
+            _ => crate::models::agent::AgentVmStatus::Provisioning,
+        };
+
+        let auth_token = f
+            .get("authToken")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let created_at = f
+            .get("createdAt")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let last_query_at = f
+            .get("lastQueryAt")
+            .and_then(|v| v.get("stringValue"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        Ok(Some(crate::models::agent::AgentVm {
+            vm_name,
+            zone,
+            ip,
+            status,
+            auth_token,
+            created_at,
+            last_query_at,
+        }))
     }
 
-    /// Append a message ID to a chat session's message_ids array.
-    /// Mirrors Python's `add_message_to_chat_session()`.
-    async fn add_message_to_chat_session(
+    /// Set agent VM info on a user's document
+    pub async fn set_agent_vm(
         &self,
         uid: &str,
-        chat_session_id: &str,
-        message_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Firestore REST API: use fieldTransforms with appendMissingElements
-        // to atomically append to the array (equivalent to Python's ArrayUnion)
-        let update = json!({
-            "writes": [{
-                "transform": {
-                    "document": format!(
-                        "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                        self.project_id, USERS_COLLECTION, uid,
-                        CHAT_SESSIONS_SUBCOLLECTION, chat_session_id
-                    ),
-                    "fieldTransforms": [{
-                        "fieldPath": "message_ids",
-                        "appendMissingElements": {
-                            "values": [{"stringValue": message_id}]
-                        }
-                    }]
-                }
-            }]
+        vm_name: &str,
+        zone: &str,
+        ip: Option<&str>,
+        status: crate::models::agent::AgentVmStatus,
+        auth_token: &str,
+        created_at: &str,
+    ) -> Result<(), FirestoreError> {
+        let mut vm_fields = json!({
+            "vmName": {"stringValue": vm_name},
+            "zone": {"stringValue": zone},
+            "status": {"stringValue": status.to_string()},
+            "authToken": {"stringValue": auth_token},
+            "createdAt": {"stringValue": created_at}
         });
 
-        let commit_url = format!(
-            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-            self.project_id
-        );
-
-        let response = self
-            .build_request(reqwest::Method::POST, &commit_url)
-            .await?
-            .json(&update)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            tracing::warn!(
-                "Failed to add message {} to chat session {}: {}",
-                message_id,
-                chat_session_id,
-                error_text
+        if let Some(ip_val) = ip {
+            vm_fields.as_object_mut().unwrap().insert(
+                "ip".to_string(),
+                json!({"stringValue": ip_val}),
             );
         }
 
-        Ok(())
+        let fields = json!({
+            "agentVm": {
+                "mapValue": {
+                    "fields": vm_fields
+                }
+            }
+        });
+
+        self.update_user_fields(uid, fields, &["agentVm"]).await
     }
 
-    /// Save a chat message to Firestore
-    /// Used for chat history persistence
-    pub async fn save_message(
+    // =========================================================================
+    // DEVICES
+    // =========================================================================
+
+    /// Get all paired devices for a user
+    pub async fn get_devices(
         &self,
         uid: &str,
-        text: &str,
-        sender: &str,
-        app_id: Option<&str>,
-        session_id: Option<&str>,
-        metadata: Option<&str>,
-    ) -> Result<MessageDB, Box<dyn std::error::Error + Send + Sync>> {
-        let message_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
-
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            MESSAGES_SUBCOLLECTION,
-            message_id
-        );
+    ) -> Result<Vec<DeviceDB>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let mut fields = json!({
-            // CRITICAL: id field required - Python queries .where('id', '==', message_id)
-            "id": {"stringValue": &message_id},
-            "text": {"stringValue": text},
-            "sender": {"stringValue": sender},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "reported": {"booleanValue": false},
-            // CRITICAL: type field required - Python Message model requires it (no default)
-            "type": {"stringValue": "text"},
-            // Default empty arrays for memories_id
-            "memories_id": {"arrayValue": {"values": []}},
-            "from_external_integration": {"booleanValue": false}
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": DEVICES_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "paired_at"}, "direction": "DESCENDING"}],
+                "limit": 50
+            }
         });
 
-        // CRITICAL: Always set app_id and plugin_id fields (even as null) for backward compatibility
-        // Python backend queries .where(plugin_id == null) for main chat
-        // Firestore won't match documents that don't have the field at all
-        if let Some(app) = app_id {
-            fields["app_id"] = json!({"stringValue": app});
-            fields["plugin_id"] = json!({"stringValue": app});
-        } else {
-            // For main chat (no app), explicitly set null values
-            fields["app_id"] = json!({"nullValue": null});
-            fields["plugin_id"] = json!({"nullValue": null});
-        }
-
-        // Acquire (get or create) a chat session — mirrors Python's acquire_chat_session().
-        // This ensures desktop messages have a chat_session_id so they're visible on mobile.
-        let effective_session_id: Option<String> = if let Some(session) = session_id {
-            Some(session.to_string())
-        } else {
-            match self.acquire_chat_session(uid, app_id).await {
-                Ok(session) => Some(session),
-                Err(e) => {
-                    tracing::warn!("Failed to acquire chat session: {}", e);
-                    None
-                }
-            }
-        };
+        let response = self
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-        if let Some(ref session) = effective_session_id {
-            fields["session_id"] = json!({"stringValue": session});
-            fields["chat_session_id"] = json!({"stringValue": session});
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        if let Some(meta) = metadata {
-            fields["metadata"] = json!({"stringValue": meta});
-        }
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_device(d).ok()))
+            .collect())
+    }
 
-        let doc = json!({"fields": fields});
+    /// Get a single paired device
+    pub async fn get_device(
+        &self,
+        uid: &str,
+        device_id: &str,
+    ) -> Result<Option<DeviceDB>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            DEVICES_SUBCOLLECTION,
+            device_id
+        );
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&doc)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        // Track message in the chat session's message_ids array
-        // (mirrors Python's add_message_to_chat_session)
-        if let Some(ref session) = effective_session_id {
-            if let Err(e) = self
-                .add_message_to_chat_session(uid, session, &message_id)
-                .await
-            {
-                tracing::warn!("Failed to track message in chat session: {}", e);
-                // Non-fatal — message is already saved
-            }
-        }
+        let doc: Value = response.json().await?;
+        Ok(Some(self.parse_device(&doc)?))
+    }
 
-        let message = MessageDB {
-            id: message_id.clone(),
-            text: text.to_string(),
-            created_at: now,
-            sender: sender.to_string(),
-            app_id: app_id.map(|s| s.to_string()),
-            session_id: effective_session_id,
-            rating: None,
-            reported: false,
-            metadata: metadata.map(|s| s.to_string()),
+    /// Register (pair) a device. The ID is derived from the serial number so
+    /// re-registering updates the existing record and keeps paired_at.
+    pub async fn register_device(
+        &self,
+        uid: &str,
+        serial_number: &str,
+        name: Option<&str>,
+        model: Option<&str>,
+        firmware_version: Option<&str>,
+        hardware_revision: Option<&str>,
+    ) -> Result<DeviceDB, FirestoreError> {
+        let device_id = document_id_from_seed(&format!("omi-device-{}", serial_number));
+        let now = Utc::now();
+        let existing = self.get_device(uid, &device_id).await?;
+
+        let device = DeviceDB {
+            id: device_id.clone(),
+            serial_number: serial_number.to_string(),
+            name: name.map(|s| s.to_string()).or_else(|| existing.as_ref().and_then(|d| d.name.clone())),
+            model: model.map(|s| s.to_string()).or_else(|| existing.as_ref().and_then(|d| d.model.clone())),
+            firmware_version: firmware_version
+                .map(|s| s.to_string())
+                .or_else(|| existing.as_ref().and_then(|d| d.firmware_version.clone())),
+            hardware_revision: hardware_revision
+                .map(|s| s.to_string())
+                .or_else(|| existing.as_ref().and_then(|d| d.hardware_revision.clone())),
+            battery_level: existing.as_ref().and_then(|d| d.battery_level),
+            is_charging: existing.as_ref().is_some_and(|d| d.is_charging),
+            last_seen_at: Some(now),
+            paired_at: existing.as_ref().map_or(now, |d| d.paired_at),
+            updated_at: now,
         };
 
-        tracing::info!(
-            "Saved {} message {} for user {} (app_id={:?})",
-            sender,
-            message_id,
+        let mut fields = json!({
+            "id": {"stringValue": device.id},
+            "serial_number": {"stringValue": device.serial_number},
+            "is_charging": {"booleanValue": device.is_charging},
+            "last_seen_at": {"timestampValue": now.to_rfc3339()},
+            "paired_at": {"timestampValue": device.paired_at.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
+        for (key, value) in [
+            ("name", &device.name),
+            ("model", &device.model),
+            ("firmware_version", &device.firmware_version),
+            ("hardware_revision", &device.hardware_revision),
+        ] {
+            if let Some(v) = value {
+                fields[key] = json!({"stringValue": v});
+            }
+        }
+        if let Some(level) = device.battery_level {
+            fields["battery_level"] = json!({"integerValue": level.to_string()});
+        }
+
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
             uid,
-            app_id
+            DEVICES_SUBCOLLECTION,
+            device_id
         );
-        Ok(message)
+
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!("Registered device {} ({}) for user {}", device_id, serial_number, uid);
+        Ok(device)
     }
 
-    /// Get chat messages for a user with optional app_id and session_id filter
-    pub async fn get_messages(
+    /// Record a status heartbeat for a paired device
+    pub async fn record_device_heartbeat(
         &self,
         uid: &str,
-        app_id: Option<&str>,
-        session_id: Option<&str>,
-        limit: usize,
-        offset: usize,
-    ) -> Result<Vec<MessageDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        // Build filters
-        let mut filters: Vec<Value> = Vec::new();
+        device_id: &str,
+        battery_level: Option<i32>,
+        is_charging: Option<bool>,
+        firmware_version: Option<&str>,
+    ) -> Result<DeviceDB, FirestoreError> {
+        let now = Utc::now();
+        let mut fields = json!({
+            "last_seen_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
+        });
+        let mut mask = vec!["last_seen_at", "updated_at"];
 
-        if let Some(app) = app_id {
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "app_id"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": app}
-                }
-            }));
+        if let Some(level) = battery_level {
+            fields["battery_level"] = json!({"integerValue": level.to_string()});
+            mask.push("battery_level");
         }
-
-        if let Some(session) = session_id {
-            // NOTE: Use chat_session_id for backward compatibility
-            // Old messages from Flutter app only have chat_session_id field
-            // New messages have both session_id and chat_session_id
-            filters.push(json!({
-                "fieldFilter": {
-                    "field": {"fieldPath": "chat_session_id"},
-                    "op": "EQUAL",
-                    "value": {"stringValue": session}
-                }
-            }));
+        if let Some(charging) = is_charging {
+            fields["is_charging"] = json!({"booleanValue": charging});
+            mask.push("is_charging");
+        }
+        if let Some(version) = firmware_version {
+            fields["firmware_version"] = json!({"stringValue": version});
+            mask.push("firmware_version");
         }
 
-        // Build the where clause
-        let where_clause = if filters.is_empty() {
-            None
-        } else if filters.len() == 1 {
-            Some(filters.into_iter().next().unwrap())
-        } else {
-            Some(json!({
-                "compositeFilter": {
-                    "op": "AND",
-                    "filters": filters
-                }
-            }))
-        };
+        let mask_params: String = mask
+            .iter()
+            .map(|f| format!("updateMask.fieldPaths={}", f))
+            .collect::<Vec<_>>()
+            .join("&");
 
-        // Build structured query - order by created_at descending to get most recent messages first
-        // The UI will reverse to display in chronological order
-        let mut structured_query = json!({
-            "from": [{"collectionId": MESSAGES_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
-            "limit": limit,
-            "offset": offset
-        });
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}&currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            DEVICES_SUBCOLLECTION,
+            device_id,
+            mask_params
+        );
 
-        if let Some(where_filter) = where_clause {
-            structured_query["where"] = where_filter;
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let query = json!({
-            "structuredQuery": structured_query
-        });
+        let doc: Value = response.json().await?;
+        self.parse_device(&doc)
+    }
+
+    /// Unpair a device
+    pub async fn delete_device(
+        &self,
+        uid: &str,
+        device_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            DEVICES_SUBCOLLECTION,
+            device_id
+        );
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::DELETE, &url)
             .await?
-            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let messages = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_message(d, uid).ok())
-            })
-            .collect();
+        tracing::info!("Unpaired device {} for user {}", device_id, uid);
+        Ok(())
+    }
 
-        Ok(messages)
+    fn parse_device(&self, doc: &Value) -> Result<DeviceDB, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
     }
 
-    /// Delete chat messages for a user with optional app_id filter
-    /// Returns the count of deleted messages
-    pub async fn delete_messages(
+    // =========================================================================
+    // CONVERSATION RETENTION
+    // =========================================================================
+
+    /// Get the conversation retention policy for a user
+    pub async fn get_retention_policy(
         &self,
         uid: &str,
-        app_id: Option<&str>,
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // First, get all message IDs to delete
-        let messages = self.get_messages(uid, app_id, None, 1000, 0).await?;
-        let count = messages.len();
+    ) -> Result<RetentionPolicy, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
 
-        if count == 0 {
-            return Ok(0);
-        }
+        Ok(RetentionPolicy {
+            archive_after_days: self.parse_int(fields, "retention_archive_after_days").filter(|d| *d > 0),
+            delete_after_days: self.parse_int(fields, "retention_delete_after_days").filter(|d| *d > 0),
+            transcript_delete_after_days: self
+                .parse_int(fields, "retention_transcript_delete_after_days")
+                .filter(|d| *d > 0),
+            last_enforced_on: self.parse_string(fields, "retention_last_enforced_date"),
+        })
+    }
 
-        // Delete each message
-        for message in messages {
-            let url = format!(
-                "{}/{}/{}/{}/{}",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                MESSAGES_SUBCOLLECTION,
-                message.id
-            );
+    /// Save the retention policy. `retention_enabled` lets the enforcement job find opted-in users.
+    pub async fn update_retention_policy(
+        &self,
+        uid: &str,
+        policy: &RetentionPolicy,
+    ) -> Result<(), FirestoreError> {
+        let days_value = |days: Option<i32>| match days {
+            Some(d) => json!({"integerValue": d.to_string()}),
+            None => json!({"nullValue": null}),
+        };
+        let fields = json!({
+            "retention_archive_after_days": days_value(policy.archive_after_days),
+            "retention_delete_after_days": days_value(policy.delete_after_days),
+            "retention_transcript_delete_after_days": days_value(policy.transcript_delete_after_days),
+            "retention_enabled": {"booleanValue": policy.is_enabled()}
+        });
 
-            let response = self
-                .build_request(reqwest::Method::DELETE, &url)
-                .await?
-                .send()
-                .await?;
+        self.update_user_fields(
+            uid,
+            fields,
+            &[
+                "retention_archive_after_days",
+                "retention_delete_after_days",
+                "retention_transcript_delete_after_days",
+                "retention_enabled",
+            ],
+        )
+        .await
+    }
 
-            if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
-                let error_text = response.text().await?;
-                tracing::error!("Failed to delete message {}: {}", message.id, error_text);
+    /// Record the UTC date the retention policy last ran (prevents running twice a day)
+    pub async fn set_retention_last_enforced_date(
+        &self,
+        uid: &str,
+        date: &str,
+    ) -> Result<(), FirestoreError> {
+        let fields = json!({
+            "retention_last_enforced_date": {"stringValue": date}
+        });
+        self.update_user_fields(uid, fields, &["retention_last_enforced_date"])
+            .await
+    }
+
+    /// Get IDs of users with a retention policy
+    pub async fn get_users_with_retention_enabled(
+        &self,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
+
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": USERS_COLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "retention_enabled"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                }
             }
+        });
+
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
-        tracing::info!(
-            "Deleted {} messages for user {} (app_id={:?})",
-            count,
-            uid,
-            app_id
-        );
-        Ok(count)
+        let results: Vec<Value> = response.json().await?;
+        let uids = results
+            .iter()
+            .filter_map(|r| firestore_serde::document_id(r.get("document")?))
+            .map(|s| s.to_string())
+            .collect();
+
+        Ok(uids)
     }
 
-    /// Update a message's rating (thumbs up/down)
-    /// rating: 1 = thumbs up, -1 = thumbs down, None = clear rating
-    pub async fn update_message_rating(
+    /// Create the system Archive folder if the user doesn't have it yet
+    pub async fn ensure_archive_folder(
         &self,
         uid: &str,
-        message_id: &str,
-        rating: Option<i32>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            MESSAGES_SUBCOLLECTION,
-            message_id
+            FOLDERS_SUBCOLLECTION,
+            ARCHIVE_FOLDER_ID
         );
 
-        // First, get the existing message to preserve other fields
-        let get_response = self
+        let response = self
             .build_request(reqwest::Method::GET, &url)
             .await?
             .send()
             .await?;
-
-        if get_response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err("Message not found".into());
+        if response.status().is_success() {
+            return Ok(());
         }
-
-        if !get_response.status().is_success() {
-            let error_text = get_response.text().await?;
-            return Err(format!("Failed to get message: {}", error_text).into());
+        if response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        let doc: Value = get_response.json().await?;
-        let existing_fields = doc.get("fields").ok_or("Missing fields")?;
-
-        // Build updated fields, preserving existing values
-        let mut fields = json!({
-            "text": existing_fields.get("text").cloned().unwrap_or(json!({"stringValue": ""})),
-            "sender": existing_fields.get("sender").cloned().unwrap_or(json!({"stringValue": "human"})),
-            "created_at": existing_fields.get("created_at").cloned().unwrap_or(json!({"timestampValue": Utc::now().to_rfc3339()})),
-            "reported": existing_fields.get("reported").cloned().unwrap_or(json!({"booleanValue": false}))
+        let order = self.get_folders(uid).await?.len() as i32;
+        let now = Utc::now();
+        let fields = json!({
+            "name": {"stringValue": "Archive"},
+            "description": {"stringValue": "Conversations archived by your retention policy"},
+            "color": {"stringValue": "#6B7280"},
+            "order": {"integerValue": order.to_string()},
+            "is_default": {"booleanValue": false},
+            "is_system": {"booleanValue": true},
+            "conversation_count": {"integerValue": "0"},
+            "created_at": {"timestampValue": now.to_rfc3339()},
+            "updated_at": {"timestampValue": now.to_rfc3339()}
         });
 
-        // Preserve optional fields if they exist
-        if let Some(app_id) = existing_fields.get("app_id") {
-            fields["app_id"] = app_id.clone();
-        }
-        if let Some(session_id) = existing_fields.get("session_id") {
-            fields["session_id"] = session_id.clone();
-        }
-
-        // Set the rating (or null to clear)
-        match rating {
-            Some(r) => {
-                fields["rating"] = json!({"integerValue": r.to_string()});
-            }
-            None => {
-                fields["rating"] = json!({"nullValue": null});
-            }
-        }
-
-        let update_doc = json!({"fields": fields});
-
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&update_doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to update message rating: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        tracing::info!(
-            "Updated rating for message {} (user {}): {:?}",
-            message_id,
-            uid,
-            rating
-        );
-
+        tracing::info!("Created archive folder for user {}", uid);
         Ok(())
     }
 
-    /// Parse a Firestore document into a MessageDB
-    /// Decrypts text if data_protection_level is "enhanced" and encryption secret is available.
-    fn parse_message(&self, doc: &Value, uid: &str) -> Result<MessageDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name.split('/').last().unwrap_or("").to_string();
+    /// Move conversations into the Archive folder in batched writes
+    pub async fn batch_archive_conversations(
+        &self,
+        uid: &str,
+        conversation_ids: &[String],
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        let writes: Vec<Value> = conversation_ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "update": {
+                        "name": self.conversation_doc_name(uid, id),
+                        "fields": {
+                            "folder_id": {"stringValue": ARCHIVE_FOLDER_ID},
+                            "archived_at": {"timestampValue": now}
+                        }
+                    },
+                    "updateMask": {"fieldPaths": ["folder_id", "archived_at"]},
+                    "currentDocument": {"exists": true}
+                })
+            })
+            .collect();
 
-        // Get raw text
-        let mut text = fields
-            .get("text")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        self.commit_batched_writes(writes).await?;
+        tracing::info!("Archived {} conversations for user {}", conversation_ids.len(), uid);
+        Ok(())
+    }
 
-        // Check if text is encrypted (data_protection_level = "enhanced")
-        let data_protection_level = self.parse_string(fields, "data_protection_level");
-        if data_protection_level.as_deref() == Some("enhanced") {
-            if let Some(ref secret) = self.encryption_secret {
-                match encryption::decrypt(&text, uid, secret) {
-                    Ok(decrypted) => text = decrypted,
-                    Err(e) => {
-                        tracing::warn!("Failed to decrypt message {}: {}", id, e);
-                        text = "[Encrypted message — decryption failed]".to_string();
-                    }
-                }
-            } else {
-                tracing::warn!(
-                    "Message {} has enhanced protection but no encryption secret configured",
-                    id
-                );
-                text = "[Encrypted message — decryption failed]".to_string();
+    /// Remove the raw transcript of conversations, keeping the summary and other structured
+    /// data. Also deletes transcript chunks and transcript edit history, which hold copies.
+    pub async fn batch_strip_transcripts(
+        &self,
+        uid: &str,
+        conversation_ids: &[String],
+    ) -> Result<(), FirestoreError> {
+        let now = Utc::now().to_rfc3339();
+        // Masked fields missing from "fields" are removed from the document
+        let writes: Vec<Value> = conversation_ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "update": {
+                        "name": self.conversation_doc_name(uid, id),
+                        "fields": {
+                            "transcript_deleted_at": {"timestampValue": now}
+                        }
+                    },
+                    "updateMask": {"fieldPaths": [
+                        "transcript_segments",
+                        "transcript_segments_compressed",
+                        "transcript_chunk_count",
+                        "transcript_deleted_at"
+                    ]},
+                    "currentDocument": {"exists": true}
+                })
+            })
+            .collect();
+
+        self.commit_batched_writes(writes).await?;
+        for id in conversation_ids {
+            if let Err(e) = self.delete_transcript_chunks(uid, id).await {
+                tracing::warn!("Failed to delete transcript chunks of conversation {}: {}", id, e);
+            }
+            if let Err(e) = self
+                .delete_conversation_edits(uid, id, Some(ConversationEditField::Transcript))
+                .await
+            {
+                tracing::warn!("Failed to delete transcript history of conversation {}: {}", id, e);
             }
         }
+        tracing::info!("Stripped transcripts of {} conversations for user {}", conversation_ids.len(), uid);
+        Ok(())
+    }
 
-        let sender = fields
-            .get("sender")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("human")
-            .to_string();
-
-        let created_at = fields
-            .get("created_at")
-            .and_then(|v| v.get("timestampValue"))
-            .and_then(|v| v.as_str())
-            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(Utc::now);
-
-        let app_id = fields
-            .get("app_id")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+    /// Permanently delete conversations in batched writes
+    pub async fn batch_delete_conversations(
+        &self,
+        uid: &str,
+        conversation_ids: &[String],
+    ) -> Result<(), FirestoreError> {
+        // Each delete is paired with its sync tombstone (an even chunk size keeps pairs together)
+        let writes: Vec<Value> = conversation_ids
+            .iter()
+            .flat_map(|id| {
+                [
+                    json!({"delete": self.conversation_doc_name(uid, id)}),
+                    self.sync_tombstone_write(uid, CONVERSATIONS_SUBCOLLECTION, id),
+                ]
+            })
+            .collect();
 
-        let session_id = fields
-            .get("session_id")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        self.commit_batched_writes(writes).await?;
+        for id in conversation_ids {
+            if let Err(e) = self.delete_conversation_edits(uid, id, None).await {
+                tracing::warn!("Failed to delete edit history of conversation {}: {}", id, e);
+            }
+            if let Err(e) = self.delete_transcript_chunks(uid, id).await {
+                tracing::warn!("Failed to delete transcript chunks of conversation {}: {}", id, e);
+            }
+        }
+        tracing::info!("Deleted {} conversations for user {}", conversation_ids.len(), uid);
+        Ok(())
+    }
 
-        let rating = fields
-            .get("rating")
-            .and_then(|v| v.get("integerValue"))
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse::<i32>().ok());
+    fn conversation_doc_name(&self, uid: &str, conversation_id: &str) -> String {
+        format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
+        )
+    }
 
-        let reported = fields
-            .get("reported")
-            .and_then(|v| v.get("booleanValue"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+    /// Commit writes in chunks of 500 (the Firestore per-commit limit)
+    async fn commit_batched_writes(
+        &self,
+        writes: Vec<Value>,
+    ) -> Result<(), FirestoreError> {
+        let commit_url = format!(
+            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
+            self.project_id()
+        );
 
-        let metadata = fields
-            .get("metadata")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        for chunk in writes.chunks(500) {
+            let response = self
+                .build_request(reqwest::Method::POST, &commit_url)
+                .await?
+                .json(&json!({"writes": chunk}))
+                .send()
+                .await?;
 
-        Ok(MessageDB {
-            id,
-            text,
-            created_at,
-            sender,
-            app_id,
-            session_id,
-            rating,
-            reported,
-            metadata,
-        })
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore batch commit error", &error_text));
+            }
+        }
+        Ok(())
     }
 
     // =========================================================================
-    // FOLDERS
+    // USER WEBHOOKS
     // =========================================================================
 
-    /// Get all folders for a user
-    pub async fn get_folders(
+    /// Get the webhooks a user has registered
+    pub async fn get_user_webhooks(
         &self,
         uid: &str,
-    ) -> Result<Vec<Folder>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Vec<UserWebhook>, FirestoreError> {
         let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
 
-        let structured_query = json!({
-            "from": [{"collectionId": FOLDERS_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "order"}, "direction": "ASCENDING"}]
-        });
-
         let query = json!({
-            "structuredQuery": structured_query
+            "structuredQuery": {
+                "from": [{"collectionId": USER_WEBHOOKS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "ASCENDING"}],
+                "limit": 50
+            }
         });
 
         let response = self
@@ -7315,138 +15032,319 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
-        let folders = results
+        Ok(results
             .into_iter()
-            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_folder(d).ok()))
-            .collect();
-
-        Ok(folders)
+            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_user_webhook(d).ok()))
+            .collect())
     }
 
-    /// Create a new folder
-    pub async fn create_folder(
+    /// Register a webhook with a freshly generated signing secret
+    pub async fn create_user_webhook(
         &self,
         uid: &str,
-        name: &str,
-        description: Option<&str>,
-        color: Option<&str>,
-    ) -> Result<Folder, Box<dyn std::error::Error + Send + Sync>> {
-        let folder_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
+        url: &str,
+        events: &[String],
+    ) -> Result<UserWebhook, FirestoreError> {
+        let webhook = UserWebhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            events: events.to_vec(),
+            secret: format!(
+                "whsec_{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+            enabled: true,
+            created_at: Utc::now(),
+        };
 
-        let existing = self.get_folders(uid).await?;
-        let order = existing.len() as i32;
+        let event_values: Vec<Value> = webhook
+            .events
+            .iter()
+            .map(|e| json!({"stringValue": e}))
+            .collect();
+        let fields = json!({
+            "url": {"stringValue": webhook.url},
+            "events": {"arrayValue": {"values": event_values}},
+            "secret": {"stringValue": webhook.secret},
+            "enabled": {"booleanValue": webhook.enabled},
+            "created_at": {"timestampValue": webhook.created_at.to_rfc3339()}
+        });
 
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            FOLDERS_SUBCOLLECTION,
-            folder_id
+            USER_WEBHOOKS_SUBCOLLECTION,
+            webhook.id
         );
 
-        let mut fields = json!({
-            "name": {"stringValue": name},
-            "color": {"stringValue": color.unwrap_or("#6B7280")},
-            "order": {"integerValue": order.to_string()},
-            "is_default": {"booleanValue": false},
-            "is_system": {"booleanValue": false},
-            "conversation_count": {"integerValue": "0"},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
-        });
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
+        }
+
+        tracing::info!("Registered webhook {} for user {}", webhook.id, uid);
+        Ok(webhook)
+    }
+
+    /// Remove a registered webhook
+    pub async fn delete_user_webhook(
+        &self,
+        uid: &str,
+        webhook_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}?currentDocument.exists=true",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            USER_WEBHOOKS_SUBCOLLECTION,
+            webhook_id
+        );
+
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted webhook {} for user {}", webhook_id, uid);
+        Ok(())
+    }
+
+    fn parse_user_webhook(&self, doc: &Value) -> Result<UserWebhook, FirestoreError> {
+        Ok(firestore_serde::from_document(doc)?)
+    }
+
+    // =========================================================================
+    // ADMIN OPERATIONS (used by the omi-admin binary)
+    // =========================================================================
 
-        if let Some(desc) = description {
-            fields["description"] = json!({"stringValue": desc});
+    /// Run a structured query under `parent` page by page, yielding documents as they
+    /// arrive so memory stays bounded to one page however large the result is. Each page
+    /// resumes after the previous page's last document, using a cursor on the query's
+    /// orderBy fields plus `__name__`. A `startAt` already in the query applies to the first
+    /// page; `limit` and `offset` are replaced by the paging.
+    pub fn stream_query(
+        &self,
+        parent: &str,
+        mut structured_query: Value,
+        page_size: usize,
+    ) -> DocumentStream<'_, Value> {
+        let url = format!("{}:runQuery", parent);
+        let order_by = order_by_with_name(structured_query.get("orderBy"));
+        structured_query["orderBy"] = Value::Array(order_by.clone());
+        structured_query["limit"] = json!(page_size);
+        if let Some(query) = structured_query.as_object_mut() {
+            query.remove("offset");
         }
 
-        let doc = json!({"fields": fields});
+        Box::pin(async_stream::try_stream! {
+            loop {
+                let response = self
+                    .build_request(reqwest::Method::POST, &url)
+                    .await?
+                    .json(&json!({"structuredQuery": structured_query}))
+                    .send()
+                    .await?;
+
+                let results: Vec<Value> = if response.status().is_success() {
+                    response.json().await?
+                } else {
+                    let error_text = response.text().await?;
+                    Err(format!("Firestore query error: {}", error_text))?
+                };
+                let docs: Vec<Value> = results
+                    .into_iter()
+                    .filter_map(|mut r| r.get_mut("document").map(Value::take))
+                    .collect();
+                let cursor = (docs.len() == page_size)
+                    .then(|| docs.last().and_then(|doc| cursor_values(&order_by, doc)))
+                    .flatten();
+
+                for doc in docs {
+                    yield doc;
+                }
+
+                let Some(values) = cursor else { break };
+                structured_query["startAt"] = json!({"values": values, "before": false});
+            }
+        })
+    }
+
+    /// Count documents in one of a user's subcollections
+    pub async fn count_user_documents(
+        &self,
+        uid: &str,
+        collection_id: &str,
+    ) -> Result<i64, FirestoreError> {
+        self.count_user_documents_where(uid, collection_id, &[]).await
+    }
+
+    /// Count documents in one of a user's subcollections whose boolean fields have the
+    /// given values (documents missing a field aren't counted)
+    pub async fn count_user_documents_where(
+        &self,
+        uid: &str,
+        collection_id: &str,
+        equals: &[(&str, bool)],
+    ) -> Result<i64, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+
+        let filters: Vec<Value> = equals
+            .iter()
+            .map(|(field, value)| {
+                json!({"fieldFilter": {
+                    "field": {"fieldPath": field},
+                    "op": "EQUAL",
+                    "value": {"booleanValue": value}
+                }})
+            })
+            .collect();
+        let mut structured_query = json!({"from": [{"collectionId": collection_id}]});
+        match filters.len() {
+            0 => {}
+            1 => structured_query["where"] = filters[0].clone(),
+            _ => structured_query["where"] = json!({"compositeFilter": {"op": "AND", "filters": filters}}),
+        }
+        let query = json!({
+            "structuredAggregationQuery": {
+                "structuredQuery": structured_query,
+                "aggregations": [{"alias": "count", "count": {}}]
+            }
+        });
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::POST, &format!("{}:runAggregationQuery", parent))
             .await?
-            .json(&doc)
+            .json(&query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore aggregation query failed", &error_text));
         }
 
-        tracing::info!("Created folder {} for user {}", folder_id, uid);
-
-        Ok(Folder {
-            id: folder_id,
-            name: name.to_string(),
-            description: description.map(|s| s.to_string()),
-            color: color.unwrap_or("#6B7280").to_string(),
-            created_at: now,
-            updated_at: now,
-            order,
-            is_default: false,
-            is_system: false,
-            category_mapping: None,
-            conversation_count: 0,
-        })
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .first()
+            .and_then(|r| r.get("result"))
+            .and_then(|r| r.get("aggregateFields"))
+            .and_then(|f| f.get("count"))
+            .and_then(|c| c.get("integerValue"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or(0))
     }
 
-    /// Update a folder
-    pub async fn update_folder(
+    /// Entitlements granted to a user (users/{uid}.entitlements)
+    pub async fn get_user_entitlements(
         &self,
         uid: &str,
-        folder_id: &str,
-        name: Option<&str>,
-        description: Option<&str>,
-        color: Option<&str>,
-        order: Option<i32>,
-    ) -> Result<Folder, Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-        let mut field_paths: Vec<&str> = vec!["updated_at"];
-        let mut fields = json!({
-            "updated_at": {"timestampValue": now.to_rfc3339()}
-        });
+    ) -> Result<Vec<String>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+        Ok(self.parse_string_array(fields, "entitlements"))
+    }
 
-        if let Some(n) = name {
-            field_paths.push("name");
-            fields["name"] = json!({"stringValue": n});
-        }
+    /// Add an entitlement to a user (no-op if already granted)
+    pub async fn grant_user_entitlement(
+        &self,
+        uid: &str,
+        entitlement: &str,
+    ) -> Result<(), FirestoreError> {
+        let writes = vec![json!({
+            "transform": {
+                "document": format!(
+                    "projects/{}/databases/(default)/documents/{}/{}",
+                    self.project_id(),
+                    USERS_COLLECTION,
+                    uid
+                ),
+                "fieldTransforms": [{
+                    "fieldPath": "entitlements",
+                    "appendMissingElements": {"values": [{"stringValue": entitlement}]}
+                }]
+            },
+            "currentDocument": {"exists": true}
+        })];
+        self.commit_batched_writes(writes).await?;
 
-        if let Some(d) = description {
-            field_paths.push("description");
-            fields["description"] = json!({"stringValue": d});
-        }
+        tracing::info!("Granted entitlement {} to user {}", entitlement, uid);
+        Ok(())
+    }
 
-        if let Some(c) = color {
-            field_paths.push("color");
-            fields["color"] = json!({"stringValue": c});
-        }
+    // =========================================================================
+    // ACTION ITEM NOTES
+    // =========================================================================
 
-        if let Some(o) = order {
-            field_paths.push("order");
-            fields["order"] = json!({"integerValue": o.to_string()});
-        }
+    fn action_item_doc_name(&self, uid: &str, item_id: &str) -> String {
+        format!(
+            "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+            self.project_id(),
+            USERS_COLLECTION,
+            uid,
+            ACTION_ITEMS_SUBCOLLECTION,
+            item_id
+        )
+    }
 
-        let update_mask = field_paths.iter().map(|f| format!("updateMask.fieldPaths={}", f)).collect::<Vec<_>>().join("&");
+    fn action_item_note_doc_name(&self, uid: &str, item_id: &str, note_id: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.action_item_doc_name(uid, item_id),
+            ACTION_ITEM_NOTES_SUBCOLLECTION,
+            note_id
+        )
+    }
 
+    /// Add a progress note and refresh the preview on the action item
+    pub async fn create_action_item_note(
+        &self,
+        uid: &str,
+        item_id: &str,
+        content: &str,
+    ) -> Result<ActionItemNote, FirestoreError> {
+        let note = ActionItemNote {
+            id: uuid::Uuid::new_v4().to_string(),
+            content: content.to_string(),
+            created_at: Utc::now(),
+        };
         let url = format!(
-            "{}/{}/{}/{}/{}?{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            FOLDERS_SUBCOLLECTION,
-            folder_id,
-            update_mask
+            "https://firestore.googleapis.com/v1/{}",
+            self.action_item_note_doc_name(uid, item_id, &note.id)
         );
-
-        let doc = json!({"fields": fields});
+        let doc = json!({
+            "fields": {
+                "content": {"stringValue": note.content},
+                "created_at": {"timestampValue": note.created_at.to_rfc3339()}
+            }
+        });
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -7456,513 +15354,625 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore create error", &error_text));
         }
 
-        let updated_doc: Value = response.json().await?;
-        let folder = self.parse_folder(&updated_doc)?;
-
-        tracing::info!("Updated folder {} for user {}", folder_id, uid);
-        Ok(folder)
+        self.refresh_action_item_note_preview(uid, item_id).await?;
+        tracing::info!("Added note {} to action item {} for user {}", note.id, item_id, uid);
+        Ok(note)
     }
 
-    /// Delete a folder
-    pub async fn delete_folder(
+    /// Notes on an action item, newest first
+    pub async fn get_action_item_notes(
         &self,
         uid: &str,
-        folder_id: &str,
-        move_to_folder_id: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let Some(target_id) = move_to_folder_id {
-            let conversations = self.get_conversations(uid, 100, 0, true, &[], None, Some(folder_id), None, None).await?;
-            for conv in conversations {
-                let _ = self.set_conversation_folder(uid, &conv.id, Some(target_id)).await;
-            }
-        } else {
-            let conversations = self.get_conversations(uid, 100, 0, true, &[], None, Some(folder_id), None, None).await?;
-            for conv in conversations {
-                let _ = self.set_conversation_folder(uid, &conv.id, None).await;
+        item_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ActionItemNote>, FirestoreError> {
+        let parent = format!(
+            "https://firestore.googleapis.com/v1/{}",
+            self.action_item_doc_name(uid, item_id)
+        );
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": ACTION_ITEM_NOTES_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+                "limit": limit
             }
-        }
+        });
+
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(docs
+            .iter()
+            .filter_map(|doc| {
+                let fields = doc.get("fields")?;
+                Some(ActionItemNote {
+                    id: firestore_serde::document_id(doc)?.to_string(),
+                    content: self.parse_string(fields, "content").unwrap_or_default(),
+                    created_at: self.parse_timestamp_optional(fields, "created_at")?,
+                })
+            })
+            .collect())
+    }
 
+    /// Delete one note and refresh the preview on the action item
+    pub async fn delete_action_item_note(
+        &self,
+        uid: &str,
+        item_id: &str,
+        note_id: &str,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            FOLDERS_SUBCOLLECTION,
-            folder_id
+            "https://firestore.googleapis.com/v1/{}",
+            self.action_item_note_doc_name(uid, item_id, note_id)
         );
 
-        let response = self.build_request(reqwest::Method::DELETE, &url).await?.send().await?;
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
 
         if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
         }
 
-        tracing::info!("Deleted folder {} for user {}", folder_id, uid);
+        self.refresh_action_item_note_preview(uid, item_id).await?;
+        tracing::info!("Deleted note {} of action item {} for user {}", note_id, item_id, uid);
         Ok(())
     }
 
-    /// Set conversation folder
-    pub async fn set_conversation_folder(
+    /// Delete every note on an action item (used when the action item is deleted)
+    async fn delete_action_item_notes(
         &self,
         uid: &str,
-        conversation_id: &str,
-        folder_id: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=folder_id",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            CONVERSATIONS_SUBCOLLECTION,
-            conversation_id
-        );
-
-        let doc = if let Some(fid) = folder_id {
-            json!({"fields": {"folder_id": {"stringValue": fid}}})
-        } else {
-            json!({"fields": {"folder_id": {"nullValue": null}}})
-        };
-
-        let response = self.build_request(reqwest::Method::PATCH, &url).await?.json(&doc).send().await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
-        }
+        item_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let notes = self.get_action_item_notes(uid, item_id, MAX_NOTES_PER_ACTION_ITEM).await?;
+        let writes: Vec<Value> = notes
+            .iter()
+            .map(|note| json!({"delete": self.action_item_note_doc_name(uid, item_id, &note.id)}))
+            .collect();
+        self.commit_batched_writes(writes).await
+    }
 
-        tracing::info!("Set conversation {} folder to {:?} for user {}", conversation_id, folder_id, uid);
-        Ok(())
+    /// Snooze an action item until `until`, or clear the snooze with None. Fails with a
+    /// NOT_FOUND error if the item doesn't exist.
+    pub async fn snooze_action_item(
+        &self,
+        uid: &str,
+        item_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), FirestoreError> {
+        self.patch_document_fields(
+            &self.action_item_doc_name(uid, item_id),
+            snooze_fields(until),
+            &["snoozed_until", "updated_at"],
+        )
+        .await
     }
 
-    /// Bulk move conversations to a folder
-    pub async fn bulk_move_to_folder(
+    /// Recompute latest_note, latest_note_at and notes_count on the action item
+    async fn refresh_action_item_note_preview(
         &self,
         uid: &str,
-        folder_id: &str,
-        conversation_ids: &[String],
-    ) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        let mut moved_count = 0;
-        for conv_id in conversation_ids {
-            if self.set_conversation_folder(uid, conv_id, Some(folder_id)).await.is_ok() {
-                moved_count += 1;
+        item_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let notes = self.get_action_item_notes(uid, item_id, MAX_NOTES_PER_ACTION_ITEM).await?;
+        let mut fields = json!({
+            "notes_count": {"integerValue": notes.len().to_string()},
+            "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
+        });
+        match notes.first() {
+            Some(latest) => {
+                fields["latest_note"] = json!({"stringValue": crate::models::note_preview(&latest.content)});
+                fields["latest_note_at"] = json!({"timestampValue": latest.created_at.to_rfc3339()});
+            }
+            None => {
+                fields["latest_note"] = json!({"nullValue": null});
+                fields["latest_note_at"] = json!({"nullValue": null});
             }
         }
-        tracing::info!("Bulk moved {} conversations to folder {} for user {}", moved_count, folder_id, uid);
-        Ok(moved_count)
+
+        self.patch_document_fields(
+            &self.action_item_doc_name(uid, item_id),
+            fields,
+            &["notes_count", "updated_at", "latest_note", "latest_note_at"],
+        )
+        .await
     }
 
-    /// Reorder folders
-    pub async fn reorder_folders(
-        &self,
-        uid: &str,
-        folder_ids: &[String],
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        for (index, folder_id) in folder_ids.iter().enumerate() {
-            let url = format!(
-                "{}/{}/{}/{}/{}?updateMask.fieldPaths=order",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                FOLDERS_SUBCOLLECTION,
-                folder_id
-            );
+    // =========================================================================
+    // SYNC (GET /v2/sync)
+    // =========================================================================
+
+    fn sync_tombstone_write(&self, uid: &str, collection: &str, id: &str) -> Value {
+        json!({
+            "update": {
+                "name": format!(
+                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}_{}",
+                    self.project_id(),
+                    USERS_COLLECTION,
+                    uid,
+                    SYNC_TOMBSTONES_SUBCOLLECTION,
+                    collection,
+                    id
+                ),
+                "fields": {
+                    "collection": {"stringValue": collection},
+                    "item_id": {"stringValue": id},
+                    "deleted_at": {"timestampValue": Utc::now().to_rfc3339()}
+                }
+            }
+        })
+    }
 
-            let doc = json!({"fields": {"order": {"integerValue": index.to_string()}}});
-            let _ = self.build_request(reqwest::Method::PATCH, &url).await?.json(&doc).send().await;
+    /// Record a hard delete for sync clients (best effort; failures are logged)
+    async fn record_deletion(&self, uid: &str, collection: &str, id: &str) {
+        let write = self.sync_tombstone_write(uid, collection, id);
+        if let Err(e) = self.commit_batched_writes(vec![write]).await {
+            tracing::warn!("Failed to record sync tombstone for {} {}: {}", collection, id, e);
         }
-        tracing::info!("Reordered {} folders for user {}", folder_ids.len(), uid);
-        Ok(())
     }
 
-    // ========================================
-    // GOALS METHODS
-    // ========================================
-
-    /// Get all active goals for a user (max 3)
-    pub async fn get_user_goals(
+    /// Hard deletes since `since` (after `after_id` at exactly `since`), as (collection, id)
+    /// pairs in sync order
+    pub async fn get_sync_tombstones(
         &self,
         uid: &str,
+        since: DateTime<Utc>,
+        after_id: Option<&str>,
         limit: usize,
-    ) -> Result<Vec<GoalDB>, Box<dyn std::error::Error + Send + Sync>> {
-        // Query the user's goals subcollection directly
-        let url = format!(
-            "{}/{}/{}:runQuery",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid
-        );
+    ) -> Result<SyncPage<(String, String)>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = self.changed_since_query(uid, SYNC_TOMBSTONES_SUBCOLLECTION, "deleted_at", Some(since), after_id, limit);
+        let docs = self.run_user_query(&parent, &query).await?;
+        Ok(self.sync_page(docs, "deleted_at", limit, |doc| {
+            let fields = doc.get("fields")?;
+            Some((
+                self.parse_string(fields, "collection")?,
+                self.parse_string(fields, "item_id")?,
+            ))
+        }))
+    }
 
-        // Note: Don't use orderBy with where filter on different fields - requires composite index
-        // Instead, we sort in Rust after fetching (like Python backend does)
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": GOALS_SUBCOLLECTION}],
-                "where": {
+    /// Query for documents in a user subcollection with `field` at or after `since` (all
+    /// documents when `since` is None), ordered by `field` then document ID. With
+    /// `after_id`, starts right after that document at exactly `since`.
+    fn changed_since_query(
+        &self,
+        uid: &str,
+        collection: &str,
+        field: &str,
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Value {
+        let mut structured_query = json!({
+            "from": [{"collectionId": collection}],
+            "orderBy": [
+                {"field": {"fieldPath": field}, "direction": "ASCENDING"},
+                {"field": {"fieldPath": "__name__"}, "direction": "ASCENDING"}
+            ],
+            "limit": limit
+        });
+        match (since, after_id) {
+            (Some(since), Some(after_id)) => {
+                let name = format!(
+                    "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
+                    self.project_id(),
+                    USERS_COLLECTION,
+                    uid,
+                    collection,
+                    after_id
+                );
+                structured_query["startAt"] = json!({
+                    "values": [{"timestampValue": since.to_rfc3339()}, {"referenceValue": name}],
+                    "before": false
+                });
+            }
+            (Some(since), None) => {
+                structured_query["where"] = json!({
                     "fieldFilter": {
-                        "field": {"fieldPath": "is_active"},
-                        "op": "EQUAL",
-                        "value": {"booleanValue": true}
+                        "field": {"fieldPath": field},
+                        "op": "GREATER_THAN_OR_EQUAL",
+                        "value": {"timestampValue": since.to_rfc3339()}
                     }
-                }
+                });
             }
-        });
+            (None, _) => {}
+        }
+        json!({"structuredQuery": structured_query})
+    }
+
+    /// Parse query results into a sync page; `end` is set when `limit` documents came back
+    fn sync_page<T>(
+        &self,
+        docs: Vec<Value>,
+        field: &str,
+        limit: usize,
+        parse: impl Fn(&Value) -> Option<T>,
+    ) -> SyncPage<T> {
+        let position = |doc: &Value| {
+            Some(SyncPosition {
+                updated_at: self.parse_timestamp_optional(doc.get("fields")?, field)?,
+                id: firestore_serde::document_id(doc)?.to_string(),
+            })
+        };
+        let end = if docs.len() >= limit { docs.last().and_then(position) } else { None };
+        let docs = docs
+            .iter()
+            .filter_map(|doc| Some((position(doc)?, parse(doc)?)))
+            .collect();
+        SyncPage { docs, end }
+    }
+
+    /// Documents in a user subcollection created or updated since `since`
+    /// (all documents when `since` is None), in sync order
+    async fn get_documents_changed_since(
+        &self,
+        uid: &str,
+        collection: &str,
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Value>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
+        let query = self.changed_since_query(uid, collection, "updated_at", since, after_id, limit);
+        self.run_user_query(&parent, &query).await
+    }
 
+    async fn run_user_query(
+        &self,
+        parent: &str,
+        query: &Value,
+    ) -> Result<Vec<Value>, FirestoreError> {
         let response = self
-            .build_request(reqwest::Method::POST, &url)
+            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
             .await?
-            .json(&query)
+            .json(query)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
-        let mut goals = Vec::new();
+        Ok(results
+            .into_iter()
+            .filter_map(|r| r.get("document").cloned())
+            .collect())
+    }
 
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Ok(goal) = self.parse_goal(doc) {
-                    goals.push(goal);
-                }
-            }
+    pub async fn get_conversations_changed_since(
+        &self,
+        uid: &str,
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<SyncPage<Conversation>, FirestoreError> {
+        let mut docs = self
+            .get_documents_changed_since(uid, CONVERSATIONS_SUBCOLLECTION, since, after_id, limit)
+            .await?;
+        for doc in docs.iter_mut() {
+            self.load_transcript_chunks(doc).await;
         }
+        Ok(self.sync_page(docs, "updated_at", limit, |doc| self.parse_conversation(doc, uid).ok()))
+    }
 
-        // Sort by created_at descending (newest first) and apply limit
-        goals.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        goals.truncate(limit);
-
-        tracing::info!("Found {} active goals for user {}", goals.len(), uid);
-        Ok(goals)
+    pub async fn get_memories_changed_since(
+        &self,
+        uid: &str,
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<SyncPage<MemoryDB>, FirestoreError> {
+        let docs = self
+            .get_documents_changed_since(uid, MEMORIES_SUBCOLLECTION, since, after_id, limit)
+            .await?;
+        Ok(self.sync_page(docs, "updated_at", limit, |doc| self.parse_memory(doc, uid).ok()))
     }
 
-    /// Create a new goal for a user
-    /// If user already has 3 active goals, deactivates the oldest one
-    pub async fn create_goal(
+    pub async fn get_action_items_changed_since(
         &self,
         uid: &str,
-        title: &str,
-        description: Option<&str>,
-        goal_type: GoalType,
-        target_value: f64,
-        current_value: f64,
-        min_value: f64,
-        max_value: f64,
-        unit: Option<&str>,
-        source: Option<&str>,
-    ) -> Result<GoalDB, Box<dyn std::error::Error + Send + Sync>> {
-        // Check existing active goals
-        let existing_goals = self.get_user_goals(uid, 10).await?;
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<SyncPage<ActionItemDB>, FirestoreError> {
+        let docs = self
+            .get_documents_changed_since(uid, ACTION_ITEMS_SUBCOLLECTION, since, after_id, limit)
+            .await?;
+        Ok(self.sync_page(docs, "updated_at", limit, |doc| self.parse_action_item(doc).ok()))
+    }
 
-        // If we have 3 or more active goals, deactivate the oldest one
-        if existing_goals.len() >= 3 {
-            if let Some(oldest) = existing_goals.last() {
-                tracing::info!("Deactivating oldest goal {} to make room for new goal", oldest.id);
-                self.update_goal(uid, &oldest.id, None, None, None, None, None, None, None, Some(false), None).await?;
-            }
-        }
+    pub async fn get_folders_changed_since(
+        &self,
+        uid: &str,
+        since: Option<DateTime<Utc>>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<SyncPage<Folder>, FirestoreError> {
+        let docs = self
+            .get_documents_changed_since(uid, FOLDERS_SUBCOLLECTION, since, after_id, limit)
+            .await?;
+        Ok(self.sync_page(docs, "updated_at", limit, |doc| self.parse_folder(doc).ok()))
+    }
 
-        // Generate a unique ID
-        let now = Utc::now();
-        let goal_id = document_id_from_seed(&format!("{}-{}-{}", uid, title, now.timestamp_millis()));
+    /// Last write to the user document, which holds the user's settings
+    pub async fn get_user_settings_updated_at(
+        &self,
+        uid: &str,
+    ) -> Result<Option<DateTime<Utc>>, FirestoreError> {
+        let doc = self.get_user_document(uid).await?;
+        Ok(doc
+            .get("updateTime")
+            .and_then(|t| t.as_str())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc)))
+    }
 
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id
-        );
+    // =========================================================================
+    // SCHEMA MIGRATIONS
+    // =========================================================================
 
-        let mut fields = json!({
-            "title": {"stringValue": title},
-            "goal_type": {"stringValue": match goal_type {
-                GoalType::Boolean => "boolean",
-                GoalType::Scale => "scale",
-                GoalType::Numeric => "numeric",
-            }},
-            "target_value": {"doubleValue": target_value},
-            "current_value": {"doubleValue": current_value},
-            "min_value": {"doubleValue": min_value},
-            "max_value": {"doubleValue": max_value},
-            "is_active": {"booleanValue": true},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
+    /// Every document of a collection group (e.g. every user's conversations), ordered by
+    /// document name, streamed a page at a time. Pass a document name as `start_after`
+    /// to resume after it.
+    pub fn stream_collection_group(
+        &self,
+        collection_id: &str,
+        page_size: usize,
+        start_after: Option<&str>,
+    ) -> DocumentStream<'_, Value> {
+        let mut structured_query = json!({
+            "from": [{"collectionId": collection_id, "allDescendants": true}],
+            "orderBy": [{"field": {"fieldPath": "__name__"}, "direction": "ASCENDING"}]
         });
-
-        if let Some(d) = description {
-            fields.as_object_mut().unwrap().insert(
-                "description".to_string(),
-                json!({"stringValue": d}),
-            );
-        }
-
-        if let Some(u) = unit {
-            fields.as_object_mut().unwrap().insert(
-                "unit".to_string(),
-                json!({"stringValue": u}),
-            );
-        }
-
-        if let Some(s) = source {
-            fields.as_object_mut().unwrap().insert(
-                "source".to_string(),
-                json!({"stringValue": s}),
-            );
+        if let Some(name) = start_after {
+            structured_query["startAt"] = json!({
+                "values": [{"referenceValue": name}],
+                "before": false
+            });
         }
+        self.stream_query(&self.base_url(), structured_query, page_size)
+    }
 
-        let doc = json!({"fields": fields});
+    /// Create or replace a document by its full name
+    pub async fn set_document_fields(
+        &self,
+        doc_name: &str,
+        fields: Value,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("https://firestore.googleapis.com/v1/{}", doc_name);
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create goal error: {}", error_text).into());
+            return Err(FirestoreError::from_response(
+                status,
+                &format!("Firestore write failed for {}", doc_name),
+                &error_text,
+            ));
         }
-
-        let goal = GoalDB {
-            id: goal_id,
-            title: title.to_string(),
-            description: description.map(|s| s.to_string()),
-            goal_type,
-            target_value,
-            current_value,
-            min_value,
-            max_value,
-            unit: unit.map(|s| s.to_string()),
-            is_active: true,
-            created_at: now,
-            updated_at: now,
-            completed_at: None,
-            source: source.map(|s| s.to_string()),
-        };
-
-        tracing::info!("Created goal {} for user {}", goal.id, uid);
-        Ok(goal)
+        Ok(())
     }
 
-    /// Update an existing goal
-    pub async fn update_goal(
+    /// Patch fields on an existing document by its full name
+    pub async fn patch_document_fields(
         &self,
-        uid: &str,
-        goal_id: &str,
-        title: Option<&str>,
-        description: Option<&str>,
-        target_value: Option<f64>,
-        current_value: Option<f64>,
-        min_value: Option<f64>,
-        max_value: Option<f64>,
-        unit: Option<&str>,
-        is_active: Option<bool>,
-        completed_at: Option<DateTime<Utc>>,
-    ) -> Result<GoalDB, Box<dyn std::error::Error + Send + Sync>> {
-        // Build update mask and fields
-        let mut update_fields: Vec<&str> = vec![];
-        let mut fields = serde_json::Map::new();
-        let now = Utc::now();
-
-        if let Some(t) = title {
-            update_fields.push("title");
-            fields.insert("title".to_string(), json!({"stringValue": t}));
-        }
-        if let Some(d) = description {
-            update_fields.push("description");
-            fields.insert("description".to_string(), json!({"stringValue": d}));
-        }
-        if let Some(v) = target_value {
-            update_fields.push("target_value");
-            fields.insert("target_value".to_string(), json!({"doubleValue": v}));
-        }
-        if let Some(v) = current_value {
-            update_fields.push("current_value");
-            fields.insert("current_value".to_string(), json!({"doubleValue": v}));
-        }
-        if let Some(v) = min_value {
-            update_fields.push("min_value");
-            fields.insert("min_value".to_string(), json!({"doubleValue": v}));
-        }
-        if let Some(v) = max_value {
-            update_fields.push("max_value");
-            fields.insert("max_value".to_string(), json!({"doubleValue": v}));
-        }
-        if let Some(u) = unit {
-            update_fields.push("unit");
-            fields.insert("unit".to_string(), json!({"stringValue": u}));
-        }
-        if let Some(active) = is_active {
-            update_fields.push("is_active");
-            fields.insert("is_active".to_string(), json!({"booleanValue": active}));
-        }
-        if let Some(cat) = completed_at {
-            update_fields.push("completed_at");
-            fields.insert("completed_at".to_string(), json!({"timestampValue": cat.to_rfc3339()}));
-        }
-
-        // Always update updated_at
-        update_fields.push("updated_at");
-        fields.insert("updated_at".to_string(), json!({"timestampValue": now.to_rfc3339()}));
+        doc_name: &str,
+        fields: Value,
+        field_paths: &[&str],
+    ) -> Result<(), FirestoreError> {
+        self.patch_document_fields_if(doc_name, fields, field_paths, &Precondition::Exists(true))
+            .await
+    }
 
-        let update_mask = update_fields.iter()
+    /// Patch fields on a document by its full name, only if the precondition holds.
+    /// Fails with [`FirestoreError::FailedPrecondition`] otherwise.
+    pub async fn patch_document_fields_if(
+        &self,
+        doc_name: &str,
+        fields: Value,
+        field_paths: &[&str],
+        precondition: &Precondition,
+    ) -> Result<(), FirestoreError> {
+        let mask: Vec<String> = field_paths
+            .iter()
             .map(|f| format!("updateMask.fieldPaths={}", f))
-            .collect::<Vec<_>>()
-            .join("&");
-
+            .collect();
         let url = format!(
-            "{}/{}/{}/{}/{}?{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id,
-            update_mask
+            "https://firestore.googleapis.com/v1/{}?{}&{}",
+            doc_name,
+            mask.join("&"),
+            precondition.query_param()
         );
 
-        let doc = json!({"fields": fields});
-
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await?;
-            return Err(format!("Firestore update goal error: {}", error_text).into());
+            if is_precondition_failure(status, &error_text) {
+                return Err(FirestoreError::FailedPrecondition(error_text));
+            }
+            return Err(FirestoreError::from_response(
+                status,
+                &format!("Firestore patch failed for {}", doc_name),
+                &error_text,
+            ));
         }
+        Ok(())
+    }
 
-        // Fetch the updated goal
-        let goal = self.get_goal(uid, goal_id).await?
-            .ok_or("Goal not found after update")?;
+    // =========================================================================
+    // LLM MODEL OVERRIDES
+    // =========================================================================
 
-        tracing::info!("Updated goal {} for user {}", goal_id, uid);
-        Ok(goal)
+    /// Configured task models with admin overrides applied; falls back to the
+    /// configured models if the overrides can't be loaded
+    pub async fn llm_task_models(&self, configured: &TaskModels) -> TaskModels {
+        match self.get_llm_model_overrides().await {
+            Ok(overrides) => configured.with_overrides(&overrides),
+            Err(e) => {
+                tracing::warn!("Failed to load LLM model overrides: {}", e);
+                configured.clone()
+            }
+        }
     }
 
-    /// Update goal progress (current_value) and record history
-    pub async fn update_goal_progress(
+    /// Admin per-task model overrides (cached for a few minutes)
+    pub async fn get_llm_model_overrides(
         &self,
-        uid: &str,
-        goal_id: &str,
-        current_value: f64,
-    ) -> Result<GoalDB, Box<dyn std::error::Error + Send + Sync>> {
-        let goal = self.update_goal(uid, goal_id, None, None, None, Some(current_value), None, None, None, None, None).await?;
-
-        // Also save history entry (inline, fast write)
-        if let Err(e) = self.save_goal_progress_history(uid, goal_id, current_value).await {
-            tracing::warn!("Failed to save goal progress history: {}", e);
+    ) -> Result<HashMap<TaskKind, String>, FirestoreError> {
+        if let Some(overrides) = self.model_overrides.get().await {
+            return Ok(overrides);
         }
+        let overrides = self.load_llm_model_overrides().await?;
+        self.model_overrides.set(overrides.clone()).await;
+        Ok(overrides)
+    }
 
-        // Auto-complete if current_value >= target_value
-        if current_value >= goal.target_value && goal.completed_at.is_none() {
-            tracing::info!("Goal {} completed! current_value={} >= target_value={}", goal_id, current_value, goal.target_value);
-            let completed_goal = self.update_goal(uid, goal_id, None, None, None, None, None, None, None, Some(false), Some(Utc::now())).await?;
-            return Ok(completed_goal);
+    async fn load_llm_model_overrides(
+        &self,
+    ) -> Result<HashMap<TaskKind, String>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), ADMIN_SETTINGS_COLLECTION, LLM_MODELS_DOC);
+
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(HashMap::new());
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get failed", &error_text));
         }
 
-        Ok(goal)
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").cloned().unwrap_or_default();
+        Ok(self
+            .parse_string_map(&fields, "models")
+            .into_iter()
+            .filter_map(|(task, model)| Some((TaskKind::parse(&task)?, model)))
+            .collect())
     }
 
-    /// Get inactive goals for a user (is_active == false — both completed and abandoned)
-    pub async fn get_completed_goals(
+    /// Set (or clear, with `None`) the model override for a task; returns all overrides
+    pub async fn set_llm_model_override(
         &self,
-        uid: &str,
-        limit: usize,
-    ) -> Result<Vec<GoalDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}:runQuery",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid
-        );
+        task: TaskKind,
+        model: Option<&str>,
+    ) -> Result<HashMap<TaskKind, String>, FirestoreError> {
+        let mut overrides = self.load_llm_model_overrides().await?;
+        match model {
+            Some(model) => overrides.insert(task, model.to_string()),
+            None => overrides.remove(&task),
+        };
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": GOALS_SUBCOLLECTION}],
-                "where": {
-                    "fieldFilter": {
-                        "field": {"fieldPath": "is_active"},
-                        "op": "EQUAL",
-                        "value": {"booleanValue": false}
-                    }
-                }
+        let url = format!("{}/{}/{}", self.base_url(), ADMIN_SETTINGS_COLLECTION, LLM_MODELS_DOC);
+        let models: HashMap<String, String> = overrides
+            .iter()
+            .map(|(task, model)| (task.as_str().to_string(), model.clone()))
+            .collect();
+        let doc = json!({
+            "fields": {
+                "models": self.build_string_map_value(&models),
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
             }
         });
 
         let response = self
-            .build_request(reqwest::Method::POST, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&doc)
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore query completed goals error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update failed", &error_text));
         }
 
-        let results: Vec<Value> = response.json().await?;
-        let mut goals = Vec::new();
+        self.model_overrides.set(overrides.clone()).await;
+        Ok(overrides)
+    }
 
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Ok(goal) = self.parse_goal(doc) {
-                    goals.push(goal);
-                }
-            }
-        }
+    /// Admin layout of the marketplace home; the default layout when none is stored
+    pub async fn get_apps_home_layout(
+        &self,
+    ) -> Result<AppsHomeLayout, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), ADMIN_SETTINGS_COLLECTION, APPS_HOME_DOC);
 
-        // Sort: completed goals first (by completed_at desc), then abandoned (by updated_at desc)
-        goals.sort_by(|a, b| {
-            let a_time = a.completed_at.unwrap_or(a.updated_at);
-            let b_time = b.completed_at.unwrap_or(b.updated_at);
-            b_time.cmp(&a_time)
-        });
-        goals.truncate(limit);
+        let response = self
+            .build_request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await?;
 
-        tracing::info!("Found {} completed goals for user {}", goals.len(), uid);
-        Ok(goals)
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(AppsHomeLayout::default());
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get failed", &error_text));
+        }
+
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").cloned().unwrap_or_default();
+        Ok(AppsHomeLayout {
+            // Unknown section kinds (e.g. from a newer deploy) are skipped
+            sections: self
+                .parse_string_array(&fields, "sections")
+                .into_iter()
+                .filter_map(|kind| serde_json::from_value(json!(kind)).ok())
+                .collect(),
+            spotlight_categories: self.parse_string_array(&fields, "spotlight_categories"),
+        })
     }
 
-    /// Save a progress history entry for a goal
-    /// Writes to goals/{goal_id}/goal_history/{YYYY-MM-DD}
-    pub async fn save_goal_progress_history(
+    /// Replace the marketplace home layout
+    pub async fn set_apps_home_layout(
         &self,
-        uid: &str,
-        goal_id: &str,
-        value: f64,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-        let date_key = now.format("%Y-%m-%d").to_string();
-
-        let url = format!(
-            "{}/{}/{}/{}/{}/goal_history/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id,
-            date_key
-        );
-
+        layout: &AppsHomeLayout,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), ADMIN_SETTINGS_COLLECTION, APPS_HOME_DOC);
+        let sections: Vec<String> = layout
+            .sections
+            .iter()
+            .filter_map(|kind| serde_json::to_value(kind).ok()?.as_str().map(str::to_string))
+            .collect();
         let doc = json!({
             "fields": {
-                "date": {"stringValue": &date_key},
-                "value": {"doubleValue": value},
-                "recorded_at": {"timestampValue": now.to_rfc3339()}
+                "sections": self.build_string_array_value(&sections),
+                "spotlight_categories": self.build_string_array_value(&layout.spotlight_categories),
+                "updated_at": {"timestampValue": Utc::now().to_rfc3339()}
             }
         });
 
@@ -7974,124 +15984,178 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore save goal history error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update failed", &error_text));
         }
-
-        tracing::debug!("Saved goal history for {}/{}: {} on {}", uid, goal_id, value, date_key);
         Ok(())
     }
 
-    /// Get progress history for a goal
-    pub async fn get_goal_history(
+    /// Get the tracking record for a migration
+    pub async fn get_migration_record(
         &self,
-        uid: &str,
-        goal_id: &str,
-        days: u32,
-    ) -> Result<Vec<GoalHistoryEntry>, Box<dyn std::error::Error + Send + Sync>> {
-        use crate::models::GoalHistoryEntry;
-
-        // Query the goal_history subcollection
-        let parent = format!(
-            "{}/{}/{}/{}/{}",
+        migration_id: &str,
+    ) -> Result<Option<MigrationRecord>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}",
             self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id
+            SCHEMA_MIGRATIONS_COLLECTION,
+            migration_id
         );
 
-        let cutoff = Utc::now() - chrono::TimeDelta::days(days as i64);
-
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": "goal_history"}],
-                "where": {
-                    "fieldFilter": {
-                        "field": {"fieldPath": "recorded_at"},
-                        "op": "GREATER_THAN_OR_EQUAL",
-                        "value": {"timestampValue": cutoff.to_rfc3339()}
-                    }
-                },
-                "orderBy": [{"field": {"fieldPath": "recorded_at"}, "direction": "DESCENDING"}],
-                "limit": days as i64
-            }
-        });
-
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&query)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore query goal history error: {}", error_text).into());
-        }
-
-        let results: Vec<Value> = response.json().await?;
-        let mut history = Vec::new();
-
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Some(fields) = doc.get("fields") {
-                    let date = self.parse_string(fields, "date").unwrap_or_default();
-                    let value = self.parse_double(fields, "value").unwrap_or(0.0);
-                    let recorded_at = self.parse_timestamp_optional(fields, "recorded_at").unwrap_or_else(Utc::now);
-                    history.push(GoalHistoryEntry { date, value, recorded_at });
-                }
-            }
+            return Err(FirestoreError::from_response(status, "Firestore get failed", &error_text));
         }
 
-        tracing::info!("Found {} history entries for goal {}", history.len(), goal_id);
-        Ok(history)
+        let doc: Value = response.json().await?;
+        Ok(self.parse_migration_record(&doc))
     }
 
-    /// Delete a goal
-    pub async fn delete_goal(
+    /// Create or replace the tracking record for a migration
+    pub async fn save_migration_record(
         &self,
-        uid: &str,
-        goal_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        record: &MigrationRecord,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}",
             self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id
+            SCHEMA_MIGRATIONS_COLLECTION,
+            record.id
         );
 
+        let mut fields = json!({
+            "status": {"stringValue": serde_json::to_value(record.status)?.as_str().unwrap_or("pending")},
+            "scanned": {"integerValue": record.scanned.to_string()},
+            "updated": {"integerValue": record.updated.to_string()},
+            "dry_run": {"booleanValue": record.dry_run},
+            "started_at": {"timestampValue": record.started_at.to_rfc3339()},
+            "updated_at": {"timestampValue": record.updated_at.to_rfc3339()}
+        });
+        if let Some(cursor) = &record.cursor {
+            fields["cursor"] = json!({"stringValue": cursor});
+        }
+        if let Some(error) = &record.error {
+            fields["error"] = json!({"stringValue": error});
+        }
+        if let Some(completed_at) = record.completed_at {
+            fields["completed_at"] = json!({"timestampValue": completed_at.to_rfc3339()});
+        }
+
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete goal error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to save migration record", &error_text));
         }
+        Ok(())
+    }
+
+    fn parse_migration_record(&self, doc: &Value) -> Option<MigrationRecord> {
+        let mut object = firestore_serde::decode_document(doc).ok()?;
+        firestore_serde::drop_invalid::<crate::models::MigrationStatus>(&mut object, "status");
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({"scanned": 0, "updated": 0, "dry_run": false, "started_at": Utc::now()}),
+        );
+        let defaults = json!({
+            "status": crate::models::MigrationStatus::default(),
+            "updated_at": object["started_at"].clone(),
+        });
+        firestore_serde::fill_missing(&mut object, defaults);
+        serde_json::from_value(Value::Object(object)).ok()
+    }
+
+    // =========================================================================
+    // ACCOUNTABILITY GROUPS
+    // =========================================================================
+
+    fn group_doc_name(&self, group_id: &str) -> String {
+        format!(
+            "projects/{}/databases/(default)/documents/{}/{}",
+            self.project_id(),
+            ACCOUNTABILITY_GROUPS_COLLECTION,
+            group_id
+        )
+    }
+
+    fn group_member_doc_name(&self, group_id: &str, uid: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.group_doc_name(group_id),
+            GROUP_MEMBERS_SUBCOLLECTION,
+            uid
+        )
+    }
+
+    fn group_member_fields(member: &GroupMember) -> Value {
+        json!({
+            "display_name": {"stringValue": member.display_name},
+            "share_focus": {"booleanValue": member.share_focus},
+            "joined_at": {"timestampValue": member.joined_at.to_rfc3339()}
+        })
+    }
+
+    /// Create a group with its owner as the first member (one atomic commit)
+    pub async fn create_accountability_group(
+        &self,
+        group: &AccountabilityGroup,
+        owner: &GroupMember,
+    ) -> Result<(), FirestoreError> {
+        let member_uids: Vec<Value> = group
+            .member_uids
+            .iter()
+            .map(|uid| json!({"stringValue": uid}))
+            .collect();
+        let writes = vec![
+            json!({
+                "update": {
+                    "name": self.group_doc_name(&group.id),
+                    "fields": {
+                        "name": {"stringValue": group.name},
+                        "owner_uid": {"stringValue": group.owner_uid},
+                        "invite_code": {"stringValue": group.invite_code},
+                        "member_uids": {"arrayValue": {"values": member_uids}},
+                        "created_at": {"timestampValue": group.created_at.to_rfc3339()}
+                    }
+                },
+                "currentDocument": {"exists": false}
+            }),
+            json!({
+                "update": {
+                    "name": self.group_member_doc_name(&group.id, &owner.uid),
+                    "fields": Self::group_member_fields(owner)
+                }
+            }),
+        ];
+        self.commit_batched_writes(writes).await?;
 
-        tracing::info!("Deleted goal {} for user {}", goal_id, uid);
+        tracing::info!("Created accountability group {} for user {}", group.id, group.owner_uid);
         Ok(())
     }
 
-    /// Get a single goal by ID
-    pub async fn get_goal(
+    /// Get a group by ID
+    pub async fn get_accountability_group(
         &self,
-        uid: &str,
-        goal_id: &str,
-    ) -> Result<Option<GoalDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            GOALS_SUBCOLLECTION,
-            goal_id
-        );
+        group_id: &str,
+    ) -> Result<Option<AccountabilityGroup>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), ACCOUNTABILITY_GROUPS_COLLECTION, group_id);
 
         let response = self
             .build_request(reqwest::Method::GET, &url)
@@ -8102,125 +16166,56 @@ impl FirestoreService {
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Ok(None);
         }
-
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore get goal error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get group error", &error_text));
         }
 
         let doc: Value = response.json().await?;
-        let goal = self.parse_goal(&doc)?;
-        Ok(Some(goal))
+        Ok(Some(self.parse_accountability_group(&doc)?))
     }
 
-    /// Get action items for daily score calculation
-    /// Returns (completed_count, total_count) for items due on the given date
-    pub async fn get_action_items_for_daily_score(
+    /// Find a group by its invite code
+    pub async fn get_accountability_group_by_invite_code(
         &self,
-        uid: &str,
-        due_start: &str,
-        due_end: &str,
-    ) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
-        // Use same URL pattern as working get_action_items method
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-        let url = format!("{}:runQuery", parent);
-
-        // We need to get all items due today, regardless of completion status
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
-                "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": [
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "due_at"},
-                                    "op": "GREATER_THAN_OR_EQUAL",
-                                    "value": {"timestampValue": due_start}
-                                }
-                            },
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "due_at"},
-                                    "op": "LESS_THAN",
-                                    "value": {"timestampValue": due_end}
-                                }
-                            }
-                        ]
-                    }
-                },
-                "limit": 100
-            }
-        });
-
-        let response = self
-            .build_request(reqwest::Method::POST, &url)
-            .await?
-            .json(&query)
-            .send()
+        invite_code: &str,
+    ) -> Result<Option<AccountabilityGroup>, FirestoreError> {
+        let groups = self
+            .query_accountability_groups("invite_code", "EQUAL", json!({"stringValue": invite_code}), 1)
             .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
-        }
-
-        let results: Vec<Value> = response.json().await?;
-        let mut completed = 0;
-        let mut total = 0;
-
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Some(fields) = doc.get("fields") {
-                    total += 1;
-                    if self.parse_bool(fields, "completed").unwrap_or(false) {
-                        completed += 1;
-                    }
-                }
-            }
-        }
-
-        tracing::info!("Daily score for user {}: {}/{} tasks completed", uid, completed, total);
-        Ok((completed, total))
+        Ok(groups.into_iter().next())
     }
 
-    /// Get action items for weekly score calculation (created in date range)
-    pub async fn get_action_items_for_weekly_score(
+    /// Groups the user is a member of
+    pub async fn get_user_accountability_groups(
         &self,
         uid: &str,
-        start_date: &str,
-        end_date: &str,
-    ) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
-        // Use same URL pattern as working get_action_items method
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-        let url = format!("{}:runQuery", parent);
+    ) -> Result<Vec<AccountabilityGroup>, FirestoreError> {
+        self.query_accountability_groups("member_uids", "ARRAY_CONTAINS", json!({"stringValue": uid}), 50)
+            .await
+    }
+
+    async fn query_accountability_groups(
+        &self,
+        field: &str,
+        op: &str,
+        value: Value,
+        limit: usize,
+    ) -> Result<Vec<AccountabilityGroup>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
 
         let query = json!({
             "structuredQuery": {
-                "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
+                "from": [{"collectionId": ACCOUNTABILITY_GROUPS_COLLECTION}],
                 "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": [
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "created_at"},
-                                    "op": "GREATER_THAN_OR_EQUAL",
-                                    "value": {"timestampValue": start_date}
-                                }
-                            },
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "created_at"},
-                                    "op": "LESS_THAN",
-                                    "value": {"timestampValue": end_date}
-                                }
-                            }
-                        ]
+                    "fieldFilter": {
+                        "field": {"fieldPath": field},
+                        "op": op,
+                        "value": value
                     }
                 },
-                "limit": 1000
+                "limit": limit
             }
         });
 
@@ -8232,204 +16227,29 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Firestore query error: {}", error_text).into());
-        }
-
-        let results: Vec<Value> = response.json().await?;
-        let mut completed = 0;
-        let mut total = 0;
-
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                if let Some(fields) = doc.get("fields") {
-                    total += 1;
-                    if self.parse_bool(fields, "completed").unwrap_or(false) {
-                        completed += 1;
-                    }
-                }
-            }
-        }
-
-        tracing::info!("Weekly score for user {}: {}/{} tasks completed", uid, completed, total);
-        Ok((completed, total))
-    }
-
-    /// Get all action items for overall score calculation
-    pub async fn get_action_items_for_overall_score(
-        &self,
-        uid: &str,
-    ) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-        let agg_url = format!("{}:runAggregationQuery", parent);
-
-        let structured_query = json!({
-            "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}]
-        });
-
-        // Count total and completed in parallel using aggregation queries
-        let total_query = json!({
-            "structuredAggregationQuery": {
-                "structuredQuery": structured_query,
-                "aggregations": [{"alias": "count", "count": {}}]
-            }
-        });
-
-        let completed_query = json!({
-            "structuredAggregationQuery": {
-                "structuredQuery": {
-                    "from": [{"collectionId": ACTION_ITEMS_SUBCOLLECTION}],
-                    "where": {
-                        "fieldFilter": {
-                            "field": {"fieldPath": "completed"},
-                            "op": "EQUAL",
-                            "value": {"booleanValue": true}
-                        }
-                    }
-                },
-                "aggregations": [{"alias": "count", "count": {}}]
-            }
-        });
-
-        let (total_resp, completed_resp) = tokio::join!(
-            async {
-                self.build_request(reqwest::Method::POST, &agg_url)
-                    .await?
-                    .json(&total_query)
-                    .send()
-                    .await
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-            },
-            async {
-                self.build_request(reqwest::Method::POST, &agg_url)
-                    .await?
-                    .json(&completed_query)
-                    .send()
-                    .await
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-            }
-        );
-
-        let parse_count = |response: reqwest::Response| async move {
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(format!("Firestore aggregation query error: {}", error_text).into());
-            }
-            let results: Vec<Value> = response.json().await?;
-            let count = results
-                .first()
-                .and_then(|r| r.get("result"))
-                .and_then(|r| r.get("aggregateFields"))
-                .and_then(|f| f.get("count"))
-                .and_then(|c| c.get("integerValue"))
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse::<i32>().ok())
-                .unwrap_or(0);
-            Ok::<i32, Box<dyn std::error::Error + Send + Sync>>(count)
-        };
-
-        let total = parse_count(total_resp?).await?;
-        let completed = parse_count(completed_resp?).await?;
-
-        tracing::info!("Overall score for user {}: {}/{} tasks completed", uid, completed, total);
-        Ok((completed, total))
-    }
-
-    /// Parse a goal from Firestore document
-    fn parse_goal(&self, doc: &Value) -> Result<GoalDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name_path = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name_path.split('/').last().unwrap_or("").to_string();
-
-        let goal_type_str = self.parse_string(fields, "goal_type").unwrap_or_else(|| "boolean".to_string());
-        let goal_type = match goal_type_str.as_str() {
-            "scale" => GoalType::Scale,
-            "numeric" => GoalType::Numeric,
-            _ => GoalType::Boolean,
-        };
-
-        Ok(GoalDB {
-            id,
-            title: self.parse_string(fields, "title").unwrap_or_default(),
-            description: self.parse_string(fields, "description"),
-            goal_type,
-            target_value: self.parse_double(fields, "target_value").unwrap_or(1.0),
-            current_value: self.parse_double(fields, "current_value").unwrap_or(0.0),
-            min_value: self.parse_double(fields, "min_value").unwrap_or(0.0),
-            max_value: self.parse_double(fields, "max_value").unwrap_or(100.0),
-            unit: self.parse_string(fields, "unit"),
-            is_active: self.parse_bool(fields, "is_active").unwrap_or(true),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or_else(Utc::now),
-            completed_at: {
-                if fields.get("completed_at").is_some() {
-                    Some(self.parse_timestamp_optional(fields, "completed_at").unwrap_or_else(Utc::now))
-                } else {
-                    None
-                }
-            },
-            source: self.parse_string(fields, "source"),
-        })
-    }
-
-    /// Parse double value from Firestore fields
-    fn parse_double(&self, fields: &Value, key: &str) -> Option<f64> {
-        fields.get(key)
-            .and_then(|v| {
-                // Try doubleValue first
-                if let Some(d) = v.get("doubleValue").and_then(|d| d.as_f64()) {
-                    return Some(d);
-                }
-                // Try integerValue (Firestore sometimes stores numbers as integers)
-                if let Some(i) = v.get("integerValue") {
-                    if let Some(s) = i.as_str() {
-                        return s.parse::<f64>().ok();
-                    }
-                    if let Some(n) = i.as_i64() {
-                        return Some(n as f64);
-                    }
-                }
-                None
-            })
-    }
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
 
-    // =========================================================================
-    // PERSONAS
-    // =========================================================================
+        let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|r| r.get("document").and_then(|d| self.parse_accountability_group(d).ok()))
+            .collect())
+    }
 
-    /// Get user's persona (there can be only one per user)
-    pub async fn get_user_persona(
+    /// Get all members of a group
+    pub async fn get_group_members(
         &self,
-        uid: &str,
-    ) -> Result<Option<PersonaDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = self.base_url();
+        group_id: &str,
+    ) -> Result<Vec<GroupMember>, FirestoreError> {
+        let parent = format!("{}/{}/{}", self.base_url(), ACCOUNTABILITY_GROUPS_COLLECTION, group_id);
 
-        // Query for persona with matching uid and persona capability
         let query = json!({
             "structuredQuery": {
-                "from": [{"collectionId": APPS_COLLECTION}],
-                "where": {
-                    "compositeFilter": {
-                        "op": "AND",
-                        "filters": [
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "uid"},
-                                    "op": "EQUAL",
-                                    "value": {"stringValue": uid}
-                                }
-                            },
-                            {
-                                "fieldFilter": {
-                                    "field": {"fieldPath": "capabilities"},
-                                    "op": "ARRAY_CONTAINS",
-                                    "value": {"stringValue": "persona"}
-                                }
-                            }
-                        ]
-                    }
-                },
-                "limit": 1
+                "from": [{"collectionId": GROUP_MEMBERS_SUBCOLLECTION}],
+                "orderBy": [{"field": {"fieldPath": "joined_at"}, "direction": "ASCENDING"}]
             }
         });
 
@@ -8441,154 +16261,365 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(None);
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
         }
 
         let results: Vec<Value> = response.json().await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|r| r.get("document").and_then(|d| self.parse_group_member(d).ok()))
+            .collect())
+    }
 
-        // Return first matching persona
-        for result in results {
-            if let Some(doc) = result.get("document") {
-                return Ok(Some(self.parse_persona(doc)?));
-            }
-        }
-
-        Ok(None)
+    /// Add a member doc and their uid on the group in one commit
+    pub async fn add_group_member(
+        &self,
+        group_id: &str,
+        member: &GroupMember,
+    ) -> Result<(), FirestoreError> {
+        let writes = vec![
+            json!({
+                "update": {
+                    "name": self.group_member_doc_name(group_id, &member.uid),
+                    "fields": Self::group_member_fields(member)
+                }
+            }),
+            json!({
+                "transform": {
+                    "document": self.group_doc_name(group_id),
+                    "fieldTransforms": [{
+                        "fieldPath": "member_uids",
+                        "appendMissingElements": {"values": [{"stringValue": member.uid}]}
+                    }]
+                },
+                "currentDocument": {"exists": true}
+            }),
+        ];
+        self.commit_batched_writes(writes).await
     }
 
-    /// Create a new persona for user
-    pub async fn create_persona(
+    /// Remove a member doc and their uid from the group in one commit
+    pub async fn remove_group_member(
         &self,
+        group_id: &str,
         uid: &str,
-        name: &str,
-        username: Option<&str>,
-        description: &str,
-        persona_prompt: Option<&str>,
-        author: &str,
-        email: Option<&str>,
-    ) -> Result<PersonaDB, Box<dyn std::error::Error + Send + Sync>> {
-        // Generate ULID-style ID
-        let persona_id = ulid::Ulid::new().to_string();
-        let now = Utc::now();
-
-        let url = format!(
-            "{}/{}/{}",
-            self.base_url(),
-            APPS_COLLECTION,
-            persona_id
-        );
-
-        let mut fields = json!({
-            "id": {"stringValue": &persona_id},
-            "uid": {"stringValue": uid},
-            "name": {"stringValue": name},
-            "description": {"stringValue": description},
-            "image": {"stringValue": ""},
-            "category": {"stringValue": "personality-emulation"},
-            "capabilities": {"arrayValue": {"values": [{"stringValue": "persona"}]}},
-            "approved": {"booleanValue": false},
-            "status": {"stringValue": "under-review"},
-            "private": {"booleanValue": false},
-            "author": {"stringValue": author},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
-        });
+    ) -> Result<(), FirestoreError> {
+        let writes = vec![
+            json!({"delete": self.group_member_doc_name(group_id, uid)}),
+            json!({
+                "transform": {
+                    "document": self.group_doc_name(group_id),
+                    "fieldTransforms": [{
+                        "fieldPath": "member_uids",
+                        "removeAllFromArray": {"values": [{"stringValue": uid}]}
+                    }]
+                },
+                "currentDocument": {"exists": true}
+            }),
+        ];
+        self.commit_batched_writes(writes).await
+    }
 
-        if let Some(uname) = username {
-            fields["username"] = json!({"stringValue": uname});
+    /// Update a member's sharing flag and/or display name
+    pub async fn update_group_member(
+        &self,
+        group_id: &str,
+        uid: &str,
+        share_focus: Option<bool>,
+        display_name: Option<&str>,
+    ) -> Result<(), FirestoreError> {
+        let mut fields = serde_json::Map::new();
+        let mut mask = vec![];
+        if let Some(share) = share_focus {
+            fields.insert("share_focus".to_string(), json!({"booleanValue": share}));
+            mask.push("updateMask.fieldPaths=share_focus");
         }
-        if let Some(prompt) = persona_prompt {
-            fields["persona_prompt"] = json!({"stringValue": prompt});
+        if let Some(name) = display_name {
+            fields.insert("display_name".to_string(), json!({"stringValue": name}));
+            mask.push("updateMask.fieldPaths=display_name");
         }
-        if let Some(e) = email {
-            fields["email"] = json!({"stringValue": e});
+        if mask.is_empty() {
+            return Ok(());
         }
 
-        let doc = json!({ "fields": fields });
+        let url = format!(
+            "{}/{}/{}/{}/{}?{}&currentDocument.exists=true",
+            self.base_url(),
+            ACCOUNTABILITY_GROUPS_COLLECTION,
+            group_id,
+            GROUP_MEMBERS_SUBCOLLECTION,
+            uid,
+            mask.join("&")
+        );
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update group member error", &error_text));
         }
+        Ok(())
+    }
 
-        tracing::info!("Created persona {} for user {}", persona_id, uid);
+    /// Delete a group and its member docs
+    pub async fn delete_accountability_group(
+        &self,
+        group_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let mut writes: Vec<Value> = self
+            .get_group_members(group_id)
+            .await?
+            .iter()
+            .map(|m| json!({"delete": self.group_member_doc_name(group_id, &m.uid)}))
+            .collect();
+        writes.push(json!({"delete": self.group_doc_name(group_id)}));
+        self.commit_batched_writes(writes).await?;
 
-        Ok(PersonaDB {
-            id: persona_id,
-            uid: uid.to_string(),
-            name: name.to_string(),
-            username: username.map(|s| s.to_string()),
-            description: description.to_string(),
-            image: String::new(),
-            category: "personality-emulation".to_string(),
-            capabilities: vec!["persona".to_string()],
-            persona_prompt: persona_prompt.map(|s| s.to_string()),
-            approved: false,
-            status: "under-review".to_string(),
-            is_private: false,
-            author: author.to_string(),
-            email: email.map(|s| s.to_string()),
-            created_at: now,
-            updated_at: now,
-        })
+        tracing::info!("Deleted accountability group {}", group_id);
+        Ok(())
     }
 
-    /// Update an existing persona
-    pub async fn update_persona(
+    fn parse_accountability_group(
         &self,
-        persona_id: &str,
-        name: Option<&str>,
-        description: Option<&str>,
-        persona_prompt: Option<&str>,
-        image: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-        let mut update_fields = vec!["updated_at"];
+        doc: &Value,
+    ) -> Result<AccountabilityGroup, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        let member_count = object.get("member_uids").and_then(|m| m.as_array()).map_or(0, Vec::len);
+        object.insert("member_count".to_string(), json!(member_count));
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({"name": "", "owner_uid": "", "invite_code": "", "created_at": Utc::now()}),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    fn parse_group_member(&self, doc: &Value) -> Result<GroupMember, FirestoreError> {
+        let mut object = firestore_serde::decode_document(doc)?;
+        // Member documents are keyed by the member's uid
+        if let Some(uid) = object.remove("id") {
+            object.insert("uid".to_string(), uid);
+        }
+        firestore_serde::fill_missing(
+            &mut object,
+            json!({"display_name": "", "share_focus": false, "joined_at": Utc::now()}),
+        );
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    // =========================================================================
+    // INTEGRATION TOKENS
+    // =========================================================================
+
+    /// users/{uid}/integrations/{integration}
+    fn integration_url(&self, uid: &str, integration: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            INTEGRATIONS_SUBCOLLECTION,
+            integration
+        )
+    }
+
+    /// Store a third-party access token, encrypted when an encryption secret is configured
+    fn set_integration_token_fields(
+        &self,
+        fields: &mut Value,
+        uid: &str,
+        access_token: &str,
+    ) -> Result<(), FirestoreError> {
+        let (token, encrypted) = match &self.encryption_secret {
+            Some(secret) => (
+                encryption::encrypt(access_token, uid, secret)
+                    .map_err(|e| format!("Failed to encrypt integration token: {}", e))?,
+                true,
+            ),
+            None => (access_token.to_string(), false),
+        };
+        fields["access_token"] = json!({"stringValue": token});
+        fields["access_token_encrypted"] = json!({"booleanValue": encrypted});
+        Ok(())
+    }
+
+    fn parse_integration_token(
+        &self,
+        fields: &Value,
+        uid: &str,
+    ) -> Result<String, FirestoreError> {
+        let stored = self.parse_string(fields, "access_token").unwrap_or_default();
+        if !self.parse_bool(fields, "access_token_encrypted").unwrap_or(false) {
+            return Ok(stored);
+        }
+        let secret = self
+            .encryption_secret
+            .as_ref()
+            .ok_or("Integration token is encrypted but no encryption secret is configured")?;
+        Ok(encryption::decrypt(&stored, uid, secret)
+            .map_err(|e| format!("Failed to decrypt integration token: {}", e))?)
+    }
+
+    /// Whether secrets can be encrypted at rest (an encryption secret is configured)
+    pub fn can_encrypt(&self) -> bool {
+        self.encryption_secret.is_some()
+    }
+
+    // =========================================================================
+    // LLM QUALITY - Thumbs ratings and the daily reports built from them
+    // =========================================================================
+
+    /// Set or clear the user's feedback on a conversation. Fails with a NOT_FOUND
+    /// error if the conversation doesn't exist.
+    pub async fn set_conversation_feedback(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        feedback: Option<&ConversationFeedback>,
+    ) -> Result<(), FirestoreError> {
+        // A masked field missing from the body is removed
+        let mut fields = json!({"updated_at": {"timestampValue": Utc::now().to_rfc3339()}});
+        if let Some(feedback) = feedback {
+            fields["feedback"] = Self::conversation_feedback_to_value(feedback);
+        }
+        self.patch_document_fields(
+            &self.conversation_doc_name(uid, conversation_id),
+            fields,
+            &["feedback", "updated_at"],
+        )
+        .await
+    }
+
+    /// Record the user's current rating of a message or conversation for quality
+    /// reports, or remove it with None. One document per rated item, so re-rating replaces it.
+    pub async fn set_quality_rating(
+        &self,
+        uid: &str,
+        kind: QualityRatingKind,
+        item_id: &str,
+        rating: Option<&QualityRating>,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}_{}_{}",
+            self.base_url(),
+            LLM_QUALITY_RATINGS_COLLECTION,
+            kind.as_str(),
+            uid,
+            item_id
+        );
+
+        let Some(rating) = rating else {
+            let response = self
+                .build_request(reqwest::Method::DELETE, &url)
+                .await?
+                .send()
+                .await?;
+            if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+            }
+            return Ok(());
+        };
+
         let mut fields = json!({
-            "updated_at": {"timestampValue": now.to_rfc3339()}
+            "kind": {"stringValue": rating.kind.as_str()},
+            "rating": {"integerValue": rating.rating.to_string()},
+            "date": {"stringValue": rating.rated_at.format("%Y-%m-%d").to_string()},
+            "rated_at": {"timestampValue": rating.rated_at.to_rfc3339()}
         });
+        Self::write_provenance(&mut fields, &rating.provenance);
 
-        if let Some(n) = name {
-            fields["name"] = json!({"stringValue": n});
-            update_fields.push("name");
-        }
-        if let Some(d) = description {
-            fields["description"] = json!({"stringValue": d});
-            update_fields.push("description");
-        }
-        if let Some(p) = persona_prompt {
-            fields["persona_prompt"] = json!({"stringValue": p});
-            update_fields.push("persona_prompt");
-        }
-        if let Some(i) = image {
-            fields["image"] = json!({"stringValue": i});
-            update_fields.push("image");
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": fields}))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
+        Ok(())
+    }
 
-        let update_mask = update_fields
-            .iter()
-            .map(|f| format!("updateMask.fieldPaths={}", f))
-            .collect::<Vec<_>>()
-            .join("&");
+    /// Ratings given on a UTC day (YYYY-MM-DD)
+    pub async fn get_quality_ratings_for_date(
+        &self,
+        date: &str,
+        limit: usize,
+    ) -> Result<Vec<QualityRating>, FirestoreError> {
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": LLM_QUALITY_RATINGS_COLLECTION}],
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "date"},
+                        "op": "EQUAL",
+                        "value": {"stringValue": date}
+                    }
+                },
+                "limit": limit
+            }
+        });
 
-        let url = format!(
-            "{}/{}/{}?{}",
-            self.base_url(),
-            APPS_COLLECTION,
-            persona_id,
-            update_mask
-        );
+        let docs = self.run_user_query(&self.base_url(), &query).await?;
+        Ok(docs
+            .iter()
+            .filter_map(|doc| {
+                let fields = doc.get("fields")?;
+                Some(QualityRating {
+                    kind: match self.parse_string(fields, "kind")?.as_str() {
+                        "conversation" => QualityRatingKind::Conversation,
+                        _ => QualityRatingKind::Message,
+                    },
+                    rating: self.parse_int(fields, "rating")?,
+                    provenance: self.parse_provenance(fields),
+                    rated_at: self.parse_timestamp_optional(fields, "rated_at")?,
+                })
+            })
+            .collect())
+    }
 
-        let doc = json!({ "fields": fields });
+    /// Create or replace the report for its date
+    pub async fn save_quality_report(
+        &self,
+        report: &QualityReport,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), LLM_QUALITY_REPORTS_COLLECTION, report.date);
+        let buckets = |buckets: &[QualityBucket]| -> Value {
+            let values: Vec<Value> = buckets
+                .iter()
+                .map(|b| {
+                    json!({"mapValue": {"fields": {
+                        "key": {"stringValue": b.key},
+                        "thumbs_up": {"integerValue": b.thumbs_up.to_string()},
+                        "thumbs_down": {"integerValue": b.thumbs_down.to_string()}
+                    }}})
+                })
+                .collect();
+            json!({"arrayValue": {"values": values}})
+        };
+        let doc = json!({
+            "fields": {
+                "date": {"stringValue": report.date},
+                "thumbs_up": {"integerValue": report.thumbs_up.to_string()},
+                "thumbs_down": {"integerValue": report.thumbs_down.to_string()},
+                "by_model": buckets(&report.by_model),
+                "by_prompt_version": buckets(&report.by_prompt_version),
+                "by_route": buckets(&report.by_route),
+                "generated_at": {"timestampValue": report.generated_at.to_rfc3339()}
+            }
+        });
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -8598,268 +16629,288 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to save quality report", &error_text));
         }
-
-        tracing::info!("Updated persona {}", persona_id);
         Ok(())
     }
 
-    /// Delete a persona
-    pub async fn delete_persona(
+    /// The stored report for a UTC day, if one was generated
+    pub async fn get_quality_report(
         &self,
-        persona_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}",
-            self.base_url(),
-            APPS_COLLECTION,
-            persona_id
-        );
+        date: &str,
+    ) -> Result<Option<QualityReport>, FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), LLM_QUALITY_REPORTS_COLLECTION, date);
 
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::GET, &url)
             .await?
             .send()
             .await?;
 
-        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get failed", &error_text));
         }
 
-        tracing::info!("Deleted persona {}", persona_id);
-        Ok(())
+        let doc: Value = response.json().await?;
+        let Some(fields) = doc.get("fields") else {
+            return Ok(None);
+        };
+        let count = |f: &Value, key: &str| self.parse_int(f, key).unwrap_or(0).max(0) as u32;
+        let buckets = |key: &str| -> Vec<QualityBucket> {
+            Self::array_values(fields, key)
+                .iter()
+                .filter_map(|v| v.get("mapValue")?.get("fields"))
+                .filter_map(|f| {
+                    Some(QualityBucket::new(
+                        &self.parse_string(f, "key")?,
+                        count(f, "thumbs_up"),
+                        count(f, "thumbs_down"),
+                    ))
+                })
+                .collect()
+        };
+        let (thumbs_up, thumbs_down) = (count(fields, "thumbs_up"), count(fields, "thumbs_down"));
+        Ok(Some(QualityReport {
+            date: date.to_string(),
+            thumbs_up,
+            thumbs_down,
+            approval_rate: crate::models::approval_rate(thumbs_up, thumbs_down),
+            by_model: buckets("by_model"),
+            by_prompt_version: buckets("by_prompt_version"),
+            by_route: buckets("by_route"),
+            generated_at: self
+                .parse_timestamp_optional(fields, "generated_at")
+                .unwrap_or_else(Utc::now),
+        }))
     }
 
-    /// Check if a username is available
-    pub async fn is_username_available(
-        &self,
-        username: &str,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = self.base_url();
+    // =========================================================================
+    // FOLLOW-UP EMAILS
+    // =========================================================================
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": APPS_COLLECTION}],
-                "where": {
-                    "fieldFilter": {
-                        "field": {"fieldPath": "username"},
-                        "op": "EQUAL",
-                        "value": {"stringValue": username}
-                    }
-                },
-                "limit": 1
-            }
-        });
+    /// Store a generated follow-up email draft
+    pub async fn save_email_draft(
+        &self,
+        uid: &str,
+        draft: &StoredEmailDraft,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            EMAIL_DRAFTS_SUBCOLLECTION,
+            draft.id
+        );
+        let fields = firestore_serde::to_fields(draft, &["id"])?;
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(false);
+            return Err(FirestoreError::from_response(status, "Failed to save email draft", &error_text));
         }
-
-        let results: Vec<Value> = response.json().await?;
-
-        // Username is available if no documents found
-        let has_document = results.iter().any(|r| r.get("document").is_some());
-        Ok(!has_document)
+        Ok(())
     }
 
-    /// Get public memories for a user (for persona generation)
-    pub async fn get_public_memories(
+    pub async fn get_email_draft(
         &self,
         uid: &str,
-        limit: usize,
-    ) -> Result<Vec<MemoryDB>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!(
-            "{}/{}/{}",
+        draft_id: &str,
+    ) -> Result<Option<StoredEmailDraft>, FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
-            uid
+            uid,
+            EMAIL_DRAFTS_SUBCOLLECTION,
+            draft_id
         );
 
-        let query = json!({
-            "structuredQuery": {
-                "from": [{"collectionId": MEMORIES_SUBCOLLECTION}],
-                "where": {
-                    "fieldFilter": {
-                        "field": {"fieldPath": "visibility"},
-                        "op": "EQUAL",
-                        "value": {"stringValue": "public"}
-                    }
-                },
-                "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
-                "limit": limit
-            }
-        });
-
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::GET, &url)
             .await?
-            .json(&query)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Failed to get email draft", &error_text));
         }
-
-        let results: Vec<Value> = response.json().await?;
-        let memories: Vec<MemoryDB> = results
-            .into_iter()
-            .filter_map(|doc| {
-                doc.get("document")
-                    .and_then(|d| self.parse_memory(d, uid).ok())
-            })
-            .collect();
-
-        tracing::info!("Found {} public memories for user {}", memories.len(), uid);
-        Ok(memories)
+        let doc: Value = response.json().await?;
+        Ok(Some(firestore_serde::from_document(&doc)?))
     }
 
-    /// Count public memories for a user
-    pub async fn count_public_memories(
+    pub async fn delete_email_draft(
         &self,
         uid: &str,
-    ) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        let memories = self.get_public_memories(uid, 1000).await?;
-        Ok(memories.len() as i32)
-    }
-
-    /// Parse a persona from Firestore document
-    fn parse_persona(&self, doc: &Value) -> Result<PersonaDB, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name_path = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name_path.split('/').last().unwrap_or("").to_string();
-
-        let capabilities = {
-            let caps = self.parse_string_array(fields, "capabilities");
-            if caps.is_empty() {
-                vec!["persona".to_string()]
-            } else {
-                caps
-            }
-        };
-
-        Ok(PersonaDB {
-            id,
-            uid: self.parse_string(fields, "uid").unwrap_or_default(),
-            name: self.parse_string(fields, "name").unwrap_or_default(),
-            username: self.parse_string(fields, "username"),
-            description: self.parse_string(fields, "description").unwrap_or_default(),
-            image: self.parse_string(fields, "image").unwrap_or_default(),
-            category: self.parse_string(fields, "category").unwrap_or_else(|| "personality-emulation".to_string()),
-            capabilities,
-            persona_prompt: self.parse_string(fields, "persona_prompt"),
-            approved: self.parse_bool(fields, "approved").unwrap_or(false),
-            status: self.parse_string(fields, "status").unwrap_or_else(|| "under-review".to_string()),
-            is_private: self.parse_bool(fields, "private").unwrap_or(false),
-            author: self.parse_string(fields, "author").unwrap_or_default(),
-            email: self.parse_string(fields, "email"),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or_else(Utc::now),
-        })
-    }
+        draft_id: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            EMAIL_DRAFTS_SUBCOLLECTION,
+            draft_id
+        );
 
-    /// Parse a folder from Firestore document
-    fn parse_folder(&self, doc: &Value) -> Result<Folder, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name_path = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name_path.split('/').last().unwrap_or("").to_string();
+        let response = self
+            .build_request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await?;
 
-        Ok(Folder {
-            id,
-            name: self.parse_string(fields, "name").unwrap_or_default(),
-            description: self.parse_string(fields, "description"),
-            color: self.parse_string(fields, "color").unwrap_or_else(|| "#6B7280".to_string()),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or_else(Utc::now),
-            order: self.parse_int(fields, "order").unwrap_or(0),
-            is_default: self.parse_bool(fields, "is_default").unwrap_or(false),
-            is_system: self.parse_bool(fields, "is_system").unwrap_or(false),
-            category_mapping: self.parse_string(fields, "category_mapping"),
-            conversation_count: self.parse_int(fields, "conversation_count").unwrap_or(0),
-        })
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Failed to delete email draft", &error_text));
+        }
+        Ok(())
     }
 
-    // =========================================================================
-    // PEOPLE - Speaker voice profiles for transcript naming
-    // =========================================================================
-
-    /// Get all people for a user
-    pub async fn get_people(
+    /// Record a sent follow-up email
+    pub async fn add_email_send(
         &self,
         uid: &str,
-    ) -> Result<Vec<crate::models::Person>, Box<dyn std::error::Error + Send + Sync>> {
-        let parent = format!("{}/{}/{}", self.base_url(), USERS_COLLECTION, uid);
-
-        let structured_query = json!({
-            "from": [{"collectionId": PEOPLE_SUBCOLLECTION}],
-            "orderBy": [{"field": {"fieldPath": "name"}, "direction": "ASCENDING"}]
-        });
-
-        let query = json!({
-            "structuredQuery": structured_query
-        });
+        record: &EmailSendRecord,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            self.base_url(),
+            USERS_COLLECTION,
+            uid,
+            EMAIL_SENDS_SUBCOLLECTION,
+            record.id
+        );
+        let fields = firestore_serde::to_fields(record, &["id"])?;
 
         let response = self
-            .build_request(reqwest::Method::POST, &format!("{}:runQuery", parent))
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&query)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            tracing::error!("Firestore query error for people: {}", error_text);
-            return Ok(vec![]);
+            return Err(FirestoreError::from_response(status, "Failed to save email send record", &error_text));
         }
-
-        let results: Vec<Value> = response.json().await?;
-        let people = results
-            .into_iter()
-            .filter_map(|doc| doc.get("document").and_then(|d| self.parse_person(d).ok()))
-            .collect();
-
-        Ok(people)
+        Ok(())
     }
 
-    /// Create a new person
-    pub async fn create_person(
+    /// Reserve `recipients` of the user's rolling 24-hour follow-up email allowance.
+    /// The quota document is rewritten only if nobody else wrote it since it was read
+    /// (or, for the first send, only if it still doesn't exist); a lost race re-reads and
+    /// tries again. Returns false when the send would go over `max_per_day`.
+    pub async fn reserve_email_recipients(
         &self,
         uid: &str,
-        name: &str,
-    ) -> Result<crate::models::Person, Box<dyn std::error::Error + Send + Sync>> {
-        let person_id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now();
-
+        recipients: usize,
+        max_per_day: usize,
+    ) -> Result<bool, FirestoreError> {
         let url = format!(
             "{}/{}/{}/{}/{}",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            PEOPLE_SUBCOLLECTION,
-            person_id
+            EMAIL_SEND_QUOTA_SUBCOLLECTION,
+            EMAIL_SEND_QUOTA_DOC
         );
 
-        let fields = json!({
-            "name": {"stringValue": name},
-            "created_at": {"timestampValue": now.to_rfc3339()},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
-        });
+        for _ in 0..EMAIL_QUOTA_ATTEMPTS {
+            let response = self
+                .build_request(reqwest::Method::GET, &url)
+                .await?
+                .send()
+                .await?;
+
+            let (mut quota, precondition) = if response.status() == reqwest::StatusCode::NOT_FOUND {
+                (EmailSendQuota::default(), Precondition::Exists(false))
+            } else if response.status().is_success() {
+                let doc: Value = response.json().await?;
+                let update_time = doc
+                    .get("updateTime")
+                    .and_then(|t| t.as_str())
+                    .ok_or("Missing updateTime")?
+                    .to_string();
+                (firestore_serde::from_document(&doc)?, Precondition::UpdateTime(update_time))
+            } else {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Failed to get email send quota", &error_text));
+            };
+
+            if !quota.reserve(Utc::now(), recipients, max_per_day) {
+                return Ok(false);
+            }
+
+            let fields = firestore_serde::to_fields(&quota, &[])?;
+            let response = self
+                .build_request(reqwest::Method::PATCH, &format!("{}?{}", url, precondition.query_param()))
+                .await?
+                .json(&json!({"fields": fields}))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                return Ok(true);
+            }
+            let status = response.status();
+            let error_text = response.text().await?;
+            // A first send racing another one fails with ALREADY_EXISTS instead
+            if !is_precondition_failure(status, &error_text) && !error_text.contains("ALREADY_EXISTS") {
+                return Err(FirestoreError::from_response(status, "Failed to save email send quota", &error_text));
+            }
+            tracing::debug!("Email send quota for {} changed while reserving; retrying", uid);
+        }
+
+        Err(FirestoreError::Aborted("Email send quota kept changing; try again".to_string()))
+    }
 
-        let doc = json!({"fields": fields});
+    // =========================================================================
+    // IMPERSONATION AUDIT
+    // =========================================================================
+
+    /// Store one impersonation audit entry
+    pub async fn add_impersonation_audit(
+        &self,
+        entry: &ImpersonationAuditEntry,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), IMPERSONATION_AUDIT_COLLECTION, entry.id);
+        let mut doc = json!({
+            "fields": {
+                "admin_uid": {"stringValue": entry.admin_uid},
+                "target_uid": {"stringValue": entry.target_uid},
+                "reason": {"stringValue": entry.reason},
+                "session_id": {"stringValue": entry.session_id},
+                "method": {"stringValue": entry.method},
+                "path": {"stringValue": entry.path},
+                "status": {"integerValue": entry.status.to_string()},
+                "created_at": {"timestampValue": entry.created_at.to_rfc3339()}
+            }
+        });
+        if let Some(ip) = &entry.ip {
+            doc["fields"]["ip"] = json!({"stringValue": ip});
+        }
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
@@ -8869,300 +16920,400 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore create person error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to save impersonation audit entry", &error_text));
         }
-
-        tracing::info!("Created person '{}' ({}) for user {}", name, person_id, uid);
-
-        Ok(crate::models::Person {
-            id: person_id,
-            name: name.to_string(),
-            created_at: now,
-            updated_at: now,
-        })
+        Ok(())
     }
 
-    /// Update a person's name
-    pub async fn update_person_name(
+    /// Newest audit entries, optionally only those for one impersonated user
+    pub async fn get_impersonation_audit(
         &self,
-        uid: &str,
-        person_id: &str,
-        new_name: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let now = Utc::now();
-        let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=name&updateMask.fieldPaths=updated_at",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            PEOPLE_SUBCOLLECTION,
-            person_id
-        );
-
-        let fields = json!({
-            "name": {"stringValue": new_name},
-            "updated_at": {"timestampValue": now.to_rfc3339()}
+        target_uid: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ImpersonationAuditEntry>, FirestoreError> {
+        let mut structured_query = json!({
+            "from": [{"collectionId": IMPERSONATION_AUDIT_COLLECTION}],
+            "orderBy": [{"field": {"fieldPath": "created_at"}, "direction": "DESCENDING"}],
+            "limit": limit
         });
+        if let Some(target_uid) = target_uid {
+            structured_query["where"] = json!({
+                "fieldFilter": {
+                    "field": {"fieldPath": "target_uid"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": target_uid}
+                }
+            });
+        }
 
-        let doc = json!({"fields": fields});
+        let docs = self
+            .run_user_query(&self.base_url(), &json!({"structuredQuery": structured_query}))
+            .await?;
+        Ok(docs
+            .iter()
+            .filter_map(|doc| {
+                let fields = doc.get("fields")?;
+                Some(ImpersonationAuditEntry {
+                    id: firestore_serde::document_id(doc)?.to_string(),
+                    admin_uid: self.parse_string(fields, "admin_uid")?,
+                    target_uid: self.parse_string(fields, "target_uid")?,
+                    reason: self.parse_string(fields, "reason").unwrap_or_default(),
+                    session_id: self.parse_string(fields, "session_id").unwrap_or_default(),
+                    method: self.parse_string(fields, "method").unwrap_or_default(),
+                    path: self.parse_string(fields, "path").unwrap_or_default(),
+                    status: self.parse_int(fields, "status").unwrap_or(0).clamp(0, u16::MAX as i32) as u16,
+                    ip: self.parse_string(fields, "ip"),
+                    created_at: self.parse_timestamp_optional(fields, "created_at")?,
+                })
+            })
+            .collect())
+    }
+
+    // =========================================================================
+    // SHADOW COMPARISONS
+    // =========================================================================
+
+    /// Store the outputs of one shadow run next to production's
+    pub async fn save_shadow_comparison(
+        &self,
+        comparison: &ShadowComparison,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), SHADOW_COMPARISONS_COLLECTION, comparison.id);
+        let fields = firestore_serde::to_fields(comparison, &["id"])?;
 
         let response = self
             .build_request(reqwest::Method::PATCH, &url)
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore update person error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to save shadow comparison", &error_text));
         }
-
         Ok(())
     }
 
-    /// Delete a person
-    pub async fn delete_person(
+    // =========================================================================
+    // LLM DEBUG LOGS
+    // =========================================================================
+
+    /// Store one redacted LLM call
+    pub async fn save_llm_debug_entry(
         &self,
-        uid: &str,
-        person_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            PEOPLE_SUBCOLLECTION,
-            person_id
-        );
+        entry: &LlmDebugEntry,
+    ) -> Result<(), FirestoreError> {
+        let url = format!("{}/{}/{}", self.base_url(), LLM_DEBUG_LOGS_COLLECTION, entry.id);
+        let fields = firestore_serde::to_fields(entry, &["id"])?;
 
         let response = self
-            .build_request(reqwest::Method::DELETE, &url)
+            .build_request(reqwest::Method::PATCH, &url)
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore delete person error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Failed to save LLM debug entry", &error_text));
         }
-
-        tracing::info!("Deleted person {} for user {}", person_id, uid);
         Ok(())
     }
 
-    /// Bulk assign segments in a conversation to a person or user
-    pub async fn assign_segments_bulk(
+    /// LLM calls made by one request, oldest first. Expired entries the TTL policy hasn't
+    /// deleted yet are left out.
+    pub async fn get_llm_debug_entries(
+        &self,
+        request_id: &str,
+        limit: usize,
+    ) -> Result<Vec<LlmDebugEntry>, FirestoreError> {
+        let structured_query = json!({
+            "from": [{"collectionId": LLM_DEBUG_LOGS_COLLECTION}],
+            "where": {
+                "fieldFilter": {
+                    "field": {"fieldPath": "request_id"},
+                    "op": "EQUAL",
+                    "value": {"stringValue": request_id}
+                }
+            },
+            "limit": limit
+        });
+
+        let docs = self
+            .run_user_query(&self.base_url(), &json!({"structuredQuery": structured_query}))
+            .await?;
+        let now = Utc::now();
+        let mut entries: Vec<LlmDebugEntry> = docs
+            .iter()
+            .filter_map(|doc| firestore_serde::from_document(doc).ok())
+            .filter(|entry: &LlmDebugEntry| entry.expire_at > now)
+            .collect();
+        entries.sort_by_key(|entry| entry.created_at);
+        Ok(entries)
+    }
+
+    // =========================================================================
+    // LLM CREDENTIALS (BYOK)
+    // =========================================================================
+
+    /// The user's own LLM API key, decrypted
+    pub async fn get_llm_credentials(
         &self,
         uid: &str,
-        conversation_id: &str,
-        segment_ids: &[String],
-        assign_type: &str,
-        value: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Get the current conversation to read segments
-        let conv = self.get_conversation(uid, conversation_id).await?
-            .ok_or("Conversation not found")?;
-        let mut segments = conv.transcript_segments;
+    ) -> Result<Option<LlmCredentials>, FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::GET, &self.integration_url(uid, LLM_CREDENTIALS_DOC))
+            .await?
+            .send()
+            .await?;
 
-        // Build a set of target segment IDs for fast lookup
-        let target_ids: std::collections::HashSet<&str> =
-            segment_ids.iter().map(|s| s.as_str()).collect();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
 
-        // Update matching segments
-        for (idx, seg) in segments.iter_mut().enumerate() {
-            // Segments may not have explicit IDs in Firestore — match by index as string
-            let seg_id = idx.to_string();
-            if target_ids.contains(seg_id.as_str()) {
-                match assign_type {
-                    "is_user" => {
-                        seg.is_user = value.map(|v| v == "true").unwrap_or(false);
-                        if seg.is_user {
-                            seg.person_id = None;
-                        }
-                    }
-                    "person_id" => {
-                        seg.person_id = value.map(|s| s.to_string());
-                        seg.is_user = false;
-                    }
-                    _ => {}
-                }
-            }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        // Write updated segments back as array
-        let segment_values: Vec<Value> = segments
-            .iter()
-            .map(|seg| {
-                let mut fields = json!({
-                    "text": {"stringValue": seg.text},
-                    "speaker": {"stringValue": seg.speaker},
-                    "speaker_id": {"integerValue": seg.speaker_id.to_string()},
-                    "is_user": {"booleanValue": seg.is_user},
-                    "start": {"doubleValue": seg.start},
-                    "end": {"doubleValue": seg.end}
-                });
-                if let Some(ref pid) = seg.person_id {
-                    fields["person_id"] = json!({"stringValue": pid});
-                }
-                json!({"mapValue": {"fields": fields}})
-            })
-            .collect();
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").ok_or("Missing fields")?;
+        Ok(Some(self.parse_llm_credentials(fields, uid)?))
+    }
 
-        let url = format!(
-            "{}/{}/{}/{}/{}?updateMask.fieldPaths=transcript_segments",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            CONVERSATIONS_SUBCOLLECTION,
-            conversation_id
-        );
+    /// Decode stored credentials, decrypting the key; fails when it can't be decrypted
+    fn parse_llm_credentials(&self, fields: &Value, uid: &str) -> Result<LlmCredentials, FirestoreError> {
+        let provider = self
+            .parse_string(fields, "provider")
+            .and_then(|p| LlmProvider::parse(&p))
+            .ok_or("Unknown LLM provider")?;
 
-        let doc = json!({
-            "fields": {
-                "transcript_segments": {
-                    "arrayValue": {
-                        "values": segment_values
-                    }
-                }
-            }
+        Ok(LlmCredentials {
+            provider,
+            api_key: self.parse_integration_token(fields, uid)?,
+            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or_else(Utc::now),
+        })
+    }
+
+    /// Fields for stored credentials, with the key encrypted
+    fn llm_credentials_to_fields(&self, uid: &str, credentials: &LlmCredentials) -> Result<Value, FirestoreError> {
+        let mut fields = json!({
+            "provider": {"stringValue": credentials.provider.as_str()},
+            "updated_at": {"timestampValue": credentials.updated_at.to_rfc3339()}
         });
+        self.set_integration_token_fields(&mut fields, uid, &credentials.api_key)?;
+        Ok(fields)
+    }
+
+    /// Store the user's own LLM API key. Refuses to store it unencrypted.
+    pub async fn save_llm_credentials(
+        &self,
+        uid: &str,
+        credentials: &LlmCredentials,
+    ) -> Result<(), FirestoreError> {
+        if !self.can_encrypt() {
+            return Err("No encryption secret configured; refusing to store an LLM API key".into());
+        }
+        let fields = self.llm_credentials_to_fields(uid, credentials)?;
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::PATCH, &self.integration_url(uid, LLM_CREDENTIALS_DOC))
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Firestore assign segments error: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        tracing::info!(
-            "Assigned {} segments in conversation {} for user {}",
-            segment_ids.len(),
-            conversation_id,
-            uid
-        );
+        tracing::info!("Saved LLM credentials for user {}", uid);
         Ok(())
     }
 
-    /// Parse a person document from Firestore
-    fn parse_person(
+    /// Remove the user's own LLM API key
+    pub async fn delete_llm_credentials(
         &self,
-        doc: &Value,
-    ) -> Result<crate::models::Person, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-        let name_path = doc.get("name").and_then(|n| n.as_str()).unwrap_or("");
-        let id = name_path.split('/').last().unwrap_or("").to_string();
+        uid: &str,
+    ) -> Result<(), FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::DELETE, &self.integration_url(uid, LLM_CREDENTIALS_DOC))
+            .await?
+            .send()
+            .await?;
 
-        Ok(crate::models::Person {
-            id,
-            name: self.parse_string(fields, "name").unwrap_or_default(),
-            created_at: self
-                .parse_timestamp_optional(fields, "created_at")
-                .unwrap_or_else(Utc::now),
-            updated_at: self
-                .parse_timestamp_optional(fields, "updated_at")
-                .unwrap_or_else(Utc::now),
-        })
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted LLM credentials for user {}", uid);
+        Ok(())
     }
 
     // =========================================================================
-    // KNOWLEDGE GRAPH - Nodes and Edges for 3D Memory Visualization
+    // NOTION INTEGRATION
     // =========================================================================
 
-    /// Create or update a knowledge graph node
-    pub async fn upsert_kg_node(
+    /// Get the user's Notion connection (access token decrypted)
+    pub async fn get_notion_connection(
         &self,
         uid: &str,
-        node: &crate::models::KnowledgeGraphNode,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            KG_NODES_SUBCOLLECTION,
-            node.id
-        );
+    ) -> Result<Option<NotionConnection>, FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::GET, &self.integration_url(uid, NOTION_INTEGRATION_DOC))
+            .await?
+            .send()
+            .await?;
 
-        // Build aliases arrays
-        let aliases_values: Vec<Value> = node.aliases
-            .iter()
-            .map(|a| json!({"stringValue": a}))
-            .collect();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
 
-        let aliases_lower_values: Vec<Value> = node.aliases_lower
-            .iter()
-            .map(|a| json!({"stringValue": a}))
-            .collect();
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
+        }
 
-        let memory_ids_values: Vec<Value> = node.memory_ids
-            .iter()
-            .map(|m| json!({"stringValue": m}))
-            .collect();
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").ok_or("Missing fields")?;
 
-        let doc = json!({
-            "fields": {
-                "id": {"stringValue": &node.id},
-                "label": {"stringValue": &node.label},
-                "node_type": {"stringValue": node.node_type.to_string()},
-                "aliases": {"arrayValue": {"values": aliases_values}},
-                "aliases_lower": {"arrayValue": {"values": aliases_lower_values}},
-                "memory_ids": {"arrayValue": {"values": memory_ids_values}},
-                "label_lower": {"stringValue": &node.label_lower},
-                "created_at": {"timestampValue": node.created_at.to_rfc3339()},
-                "updated_at": {"timestampValue": node.updated_at.to_rfc3339()}
+        let access_token = self.parse_integration_token(fields, uid)?;
+
+        let mapping = fields
+            .get("property_mapping")
+            .and_then(|m| m.get("mapValue"))
+            .and_then(|m| m.get("fields"));
+        let property_mapping = match mapping {
+            Some(m) => NotionPropertyMapping {
+                title: self.parse_string(m, "title").unwrap_or_else(|| "Name".to_string()),
+                date: self.parse_string(m, "date"),
+                summary: self.parse_string(m, "summary"),
+                category: self.parse_string(m, "category"),
+                open_action_items: self.parse_string(m, "open_action_items"),
+            },
+            None => NotionPropertyMapping::default(),
+        };
+
+        let now = Utc::now();
+        Ok(Some(NotionConnection {
+            access_token,
+            workspace_id: self.parse_string(fields, "workspace_id").unwrap_or_default(),
+            workspace_name: self.parse_string(fields, "workspace_name"),
+            bot_id: self.parse_string(fields, "bot_id").unwrap_or_default(),
+            database_id: self.parse_string(fields, "database_id"),
+            property_mapping,
+            auto_sync: self.parse_bool(fields, "auto_sync").unwrap_or(false),
+            connected_at: self.parse_timestamp_optional(fields, "connected_at").unwrap_or(now),
+            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or(now),
+        }))
+    }
+
+    /// Create or replace the user's Notion connection.
+    /// The access token is encrypted when an encryption secret is configured.
+    pub async fn save_notion_connection(
+        &self,
+        uid: &str,
+        connection: &NotionConnection,
+    ) -> Result<(), FirestoreError> {
+        let mapping = &connection.property_mapping;
+        let mut mapping_fields = json!({
+            "title": {"stringValue": mapping.title}
+        });
+        for (key, value) in [
+            ("date", &mapping.date),
+            ("summary", &mapping.summary),
+            ("category", &mapping.category),
+            ("open_action_items", &mapping.open_action_items),
+        ] {
+            if let Some(v) = value {
+                mapping_fields[key] = json!({"stringValue": v});
             }
+        }
+
+        let mut fields = json!({
+            "workspace_id": {"stringValue": connection.workspace_id},
+            "bot_id": {"stringValue": connection.bot_id},
+            "property_mapping": {"mapValue": {"fields": mapping_fields}},
+            "auto_sync": {"booleanValue": connection.auto_sync},
+            "connected_at": {"timestampValue": connection.connected_at.to_rfc3339()},
+            "updated_at": {"timestampValue": connection.updated_at.to_rfc3339()}
         });
+        self.set_integration_token_fields(&mut fields, uid, &connection.access_token)?;
+        if let Some(name) = &connection.workspace_name {
+            fields["workspace_name"] = json!({"stringValue": name});
+        }
+        if let Some(database_id) = &connection.database_id {
+            fields["database_id"] = json!({"stringValue": database_id});
+        }
 
         let response = self
-            .build_request(reqwest::Method::PATCH, &url)
+            .build_request(reqwest::Method::PATCH, &self.integration_url(uid, NOTION_INTEGRATION_DOC))
             .await?
-            .json(&doc)
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to upsert KG node: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        Ok(node.id.clone())
+        tracing::info!("Saved Notion connection for user {}", uid);
+        Ok(())
     }
 
-    /// Create or update a knowledge graph edge
-    pub async fn upsert_kg_edge(
+    /// Disconnect Notion for a user
+    pub async fn delete_notion_connection(
         &self,
         uid: &str,
-        edge: &crate::models::KnowledgeGraphEdge,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::DELETE, &self.integration_url(uid, NOTION_INTEGRATION_DOC))
+            .await?
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
+
+        tracing::info!("Deleted Notion connection for user {}", uid);
+        Ok(())
+    }
+
+    /// Record the result of pushing a conversation to Notion
+    pub async fn set_conversation_notion_sync(
+        &self,
+        uid: &str,
+        conversation_id: &str,
+        sync: &NotionSyncStatus,
+    ) -> Result<(), FirestoreError> {
         let url = format!(
-            "{}/{}/{}/{}/{}",
+            "{}/{}/{}/{}/{}?updateMask.fieldPaths=notion_sync&currentDocument.exists=true",
             self.base_url(),
             USERS_COLLECTION,
             uid,
-            KG_EDGES_SUBCOLLECTION,
-            edge.id
+            CONVERSATIONS_SUBCOLLECTION,
+            conversation_id
         );
 
-        let memory_ids_values: Vec<Value> = edge.memory_ids
-            .iter()
-            .map(|m| json!({"stringValue": m}))
-            .collect();
-
         let doc = json!({
             "fields": {
-                "id": {"stringValue": &edge.id},
-                "source_id": {"stringValue": &edge.source_id},
-                "target_id": {"stringValue": &edge.target_id},
-                "label": {"stringValue": &edge.label},
-                "memory_ids": {"arrayValue": {"values": memory_ids_values}},
-                "created_at": {"timestampValue": edge.created_at.to_rfc3339()}
+                "notion_sync": Self::notion_sync_to_value(sync)
             }
         });
 
@@ -9174,307 +17325,315 @@ impl FirestoreService {
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to upsert KG edge: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        Ok(edge.id.clone())
+        Ok(())
     }
 
-    /// Get all knowledge graph nodes for a user
-    pub async fn get_kg_nodes(
-        &self,
-        uid: &str,
-    ) -> Result<Vec<crate::models::KnowledgeGraphNode>, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}",
+    fn notion_sync_to_value(sync: &NotionSyncStatus) -> Value {
+        let mut fields = json!({
+            "status": {"stringValue": sync.status},
+            "synced_at": {"timestampValue": sync.synced_at.to_rfc3339()}
+        });
+        for (key, value) in [
+            ("page_id", &sync.page_id),
+            ("page_url", &sync.page_url),
+            ("error", &sync.error),
+        ] {
+            if let Some(v) = value {
+                fields[key] = json!({"stringValue": v});
+            }
+        }
+        json!({"mapValue": {"fields": fields}})
+    }
+
+    fn parse_notion_sync(&self, fields: &Value) -> Option<NotionSyncStatus> {
+        let sync = fields.get("notion_sync")?.get("mapValue")?.get("fields")?;
+
+        Some(NotionSyncStatus {
+            status: self.parse_string(sync, "status")?,
+            page_id: self.parse_string(sync, "page_id"),
+            page_url: self.parse_string(sync, "page_url"),
+            error: self.parse_string(sync, "error"),
+            synced_at: self.parse_timestamp_optional(sync, "synced_at").unwrap_or_else(Utc::now),
+        })
+    }
+
+    // =========================================================================
+    // SLACK INTEGRATION
+    // =========================================================================
+
+    fn slack_user_lookup_url(&self, team_id: &str, slack_user_id: &str) -> String {
+        format!(
+            "{}/{}/{}_{}",
             self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            KG_NODES_SUBCOLLECTION
-        );
+            SLACK_USERS_COLLECTION,
+            team_id,
+            slack_user_id
+        )
+    }
 
+    /// Get the user's Slack connection (access token decrypted)
+    pub async fn get_slack_connection(
+        &self,
+        uid: &str,
+    ) -> Result<Option<SlackConnection>, FirestoreError> {
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::GET, &self.integration_url(uid, SLACK_INTEGRATION_DOC))
             .await?
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to get KG nodes: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        let result: Value = response.json().await?;
-        let documents = result.get("documents").and_then(|d| d.as_array());
-
-        let nodes: Vec<crate::models::KnowledgeGraphNode> = documents
-            .map(|docs| {
-                docs.iter()
-                    .filter_map(|doc| self.parse_kg_node(doc).ok())
-                    .collect()
-            })
-            .unwrap_or_default();
+        let doc: Value = response.json().await?;
+        let fields = doc.get("fields").ok_or("Missing fields")?;
+        let now = Utc::now();
 
-        tracing::info!("Found {} KG nodes for user {}", nodes.len(), uid);
-        Ok(nodes)
+        Ok(Some(SlackConnection {
+            access_token: self.parse_integration_token(fields, uid)?,
+            team_id: self.parse_string(fields, "team_id").unwrap_or_default(),
+            team_name: self.parse_string(fields, "team_name"),
+            bot_user_id: self.parse_string(fields, "bot_user_id"),
+            slack_user_id: self.parse_string(fields, "slack_user_id").unwrap_or_default(),
+            action_item_capture_enabled: self
+                .parse_bool(fields, "action_item_capture_enabled")
+                .unwrap_or(true),
+            digest_enabled: self.parse_bool(fields, "digest_enabled").unwrap_or(false),
+            digest_hour: self
+                .parse_int(fields, "digest_hour_local")
+                .unwrap_or(crate::models::slack::DEFAULT_SLACK_DIGEST_HOUR),
+            digest_channel_id: self.parse_string(fields, "digest_channel_id"),
+            last_digest_date: self.parse_string(fields, "last_digest_date"),
+            connected_at: self.parse_timestamp_optional(fields, "connected_at").unwrap_or(now),
+            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or(now),
+        }))
     }
 
-    /// Get all knowledge graph edges for a user
-    pub async fn get_kg_edges(
+    /// Create or replace the user's Slack connection, keeping the inbound lookup
+    /// and the user-level digest flag (used by the digest scheduler) in sync
+    pub async fn save_slack_connection(
         &self,
         uid: &str,
-    ) -> Result<Vec<crate::models::KnowledgeGraphEdge>, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "{}/{}/{}/{}",
-            self.base_url(),
-            USERS_COLLECTION,
-            uid,
-            KG_EDGES_SUBCOLLECTION
-        );
+        connection: &SlackConnection,
+    ) -> Result<(), FirestoreError> {
+        let mut fields = json!({
+            "team_id": {"stringValue": connection.team_id},
+            "slack_user_id": {"stringValue": connection.slack_user_id},
+            "action_item_capture_enabled": {"booleanValue": connection.action_item_capture_enabled},
+            "digest_enabled": {"booleanValue": connection.digest_enabled},
+            "digest_hour_local": {"integerValue": connection.digest_hour.to_string()},
+            "connected_at": {"timestampValue": connection.connected_at.to_rfc3339()},
+            "updated_at": {"timestampValue": connection.updated_at.to_rfc3339()}
+        });
+        self.set_integration_token_fields(&mut fields, uid, &connection.access_token)?;
+        for (key, value) in [
+            ("team_name", &connection.team_name),
+            ("bot_user_id", &connection.bot_user_id),
+            ("digest_channel_id", &connection.digest_channel_id),
+            ("last_digest_date", &connection.last_digest_date),
+        ] {
+            if let Some(v) = value {
+                fields[key] = json!({"stringValue": v});
+            }
+        }
 
         let response = self
-            .build_request(reqwest::Method::GET, &url)
+            .build_request(reqwest::Method::PATCH, &self.integration_url(uid, SLACK_INTEGRATION_DOC))
             .await?
+            .json(&json!({"fields": fields}))
             .send()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await?;
-            return Err(format!("Failed to get KG edges: {}", error_text).into());
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
         }
 
-        let result: Value = response.json().await?;
-        let documents = result.get("documents").and_then(|d| d.as_array());
+        let lookup = json!({
+            "fields": {
+                "uid": {"stringValue": uid},
+                "updated_at": {"timestampValue": connection.updated_at.to_rfc3339()}
+            }
+        });
+        let response = self
+            .build_request(
+                reqwest::Method::PATCH,
+                &self.slack_user_lookup_url(&connection.team_id, &connection.slack_user_id),
+            )
+            .await?
+            .json(&lookup)
+            .send()
+            .await?;
 
-        let edges: Vec<crate::models::KnowledgeGraphEdge> = documents
-            .map(|docs| {
-                docs.iter()
-                    .filter_map(|doc| self.parse_kg_edge(doc).ok())
-                    .collect()
-            })
-            .unwrap_or_default();
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
 
-        tracing::info!("Found {} KG edges for user {}", edges.len(), uid);
-        Ok(edges)
+        self.update_user_fields(
+            uid,
+            json!({"slack_digest_enabled": {"booleanValue": connection.digest_enabled}}),
+            &["slack_digest_enabled"],
+        )
+        .await?;
+
+        tracing::info!("Saved Slack connection for user {}", uid);
+        Ok(())
     }
 
-    /// Delete all knowledge graph data for a user
-    pub async fn delete_kg_data(
+    /// Disconnect Slack for a user
+    pub async fn delete_slack_connection(
         &self,
         uid: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Delete all nodes
-        let nodes = self.get_kg_nodes(uid).await?;
-        for node in &nodes {
-            let url = format!(
-                "{}/{}/{}/{}/{}",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                KG_NODES_SUBCOLLECTION,
-                node.id
-            );
-            let _ = self
-                .build_request(reqwest::Method::DELETE, &url)
+    ) -> Result<(), FirestoreError> {
+        if let Some(connection) = self.get_slack_connection(uid).await? {
+            let response = self
+                .build_request(
+                    reqwest::Method::DELETE,
+                    &self.slack_user_lookup_url(&connection.team_id, &connection.slack_user_id),
+                )
                 .await?
                 .send()
-                .await;
-        }
+                .await?;
 
-        // Delete all edges
-        let edges = self.get_kg_edges(uid).await?;
-        for edge in &edges {
-            let url = format!(
-                "{}/{}/{}/{}/{}",
-                self.base_url(),
-                USERS_COLLECTION,
-                uid,
-                KG_EDGES_SUBCOLLECTION,
-                edge.id
-            );
-            let _ = self
-                .build_request(reqwest::Method::DELETE, &url)
-                .await?
-                .send()
-                .await;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+            }
         }
 
-        tracing::info!("Deleted {} nodes and {} edges for user {}", nodes.len(), edges.len(), uid);
-        Ok(())
-    }
-
-    /// Parse a knowledge graph node from Firestore document
-    fn parse_kg_node(&self, doc: &Value) -> Result<crate::models::KnowledgeGraphNode, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
-
-        let node_type_str = self.parse_string(fields, "node_type").unwrap_or_else(|| "concept".to_string());
-        let node_type = match node_type_str.as_str() {
-            "person" => crate::models::NodeType::Person,
-            "place" => crate::models::NodeType::Place,
-            "organization" => crate::models::NodeType::Organization,
-            "thing" => crate::models::NodeType::Thing,
-            _ => crate::models::NodeType::Concept,
-        };
+        let response = self
+            .build_request(reqwest::Method::DELETE, &self.integration_url(uid, SLACK_INTEGRATION_DOC))
+            .await?
+            .send()
+            .await?;
 
-        Ok(crate::models::KnowledgeGraphNode {
-            id: self.parse_string(fields, "id").unwrap_or_default(),
-            label: self.parse_string(fields, "label").unwrap_or_default(),
-            node_type,
-            aliases: self.parse_string_array(fields, "aliases"),
-            memory_ids: self.parse_string_array(fields, "memory_ids"),
-            label_lower: self.parse_string(fields, "label_lower").unwrap_or_default(),
-            aliases_lower: self.parse_string_array(fields, "aliases_lower"),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-            updated_at: self.parse_timestamp_optional(fields, "updated_at").unwrap_or_else(Utc::now),
-        })
-    }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore delete error", &error_text));
+        }
 
-    /// Parse a knowledge graph edge from Firestore document
-    fn parse_kg_edge(&self, doc: &Value) -> Result<crate::models::KnowledgeGraphEdge, Box<dyn std::error::Error + Send + Sync>> {
-        let fields = doc.get("fields").ok_or("Missing fields")?;
+        self.update_user_fields(
+            uid,
+            json!({"slack_digest_enabled": {"booleanValue": false}}),
+            &["slack_digest_enabled"],
+        )
+        .await?;
 
-        Ok(crate::models::KnowledgeGraphEdge {
-            id: self.parse_string(fields, "id").unwrap_or_default(),
-            source_id: self.parse_string(fields, "source_id").unwrap_or_default(),
-            target_id: self.parse_string(fields, "target_id").unwrap_or_default(),
-            label: self.parse_string(fields, "label").unwrap_or_default(),
-            memory_ids: self.parse_string_array(fields, "memory_ids"),
-            created_at: self.parse_timestamp_optional(fields, "created_at").unwrap_or_else(Utc::now),
-        })
+        tracing::info!("Deleted Slack connection for user {}", uid);
+        Ok(())
     }
 
-    // =========================================================================
-    // AGENT VM
-    // =========================================================================
-
-    /// Get agent VM info for a user from the agentVm field on their user document
-    pub async fn get_agent_vm(
+    /// Resolve the Omi user behind an inbound Slack request
+    pub async fn find_uid_by_slack_user(
         &self,
-        uid: &str,
-    ) -> Result<Option<crate::models::agent::AgentVm>, Box<dyn std::error::Error + Send + Sync>> {
-        let doc = self.get_user_document(uid).await?;
-        let empty = json!({});
-        let fields = doc.get("fields").unwrap_or(&empty);
-
-        let agent_vm = fields.get("agentVm");
-        if agent_vm.is_none() {
-            return Ok(None);
-        }
-
-        let map_value = agent_vm
-            .and_then(|v| v.get("mapValue"))
-            .and_then(|v| v.get("fields"));
+        team_id: &str,
+        slack_user_id: &str,
+    ) -> Result<Option<String>, FirestoreError> {
+        let response = self
+            .build_request(reqwest::Method::GET, &self.slack_user_lookup_url(team_id, slack_user_id))
+            .await?
+            .send()
+            .await?;
 
-        if map_value.is_none() {
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Ok(None);
         }
 
-        let f = map_value.unwrap();
-
-        let vm_name = f
-            .get("vmName")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        if vm_name.is_empty() {
-            return Ok(None);
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore get error", &error_text));
         }
 
-        let zone = f
-            .get("zone")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("us-central1-a")
-            .to_string();
-
-        let ip = f
-            .get("ip")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        let doc: Value = response.json().await?;
+        Ok(doc.get("fields").and_then(|f| self.parse_string(f, "uid")))
+    }
 
-        let status_str = f
-            .get("status")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("provisioning");
+    /// Get UIDs of users with the Slack digest enabled
+    pub async fn get_users_with_slack_digest_enabled(
+        &self,
+    ) -> Result<Vec<String>, FirestoreError> {
+        let url = format!("{}:runQuery", self.base_url());
 
-        let status = match status_str {
-            "ready" => crate::models::agent::AgentVmStatus::Ready,
-            "stopped" => crate::models::agent::AgentVmStatus::Stopped,
-            "error" => crate::models::agent::AgentVmStatus::Error,
-            _ => crate::models::agent::AgentVmStatus::Provisioning,
-        };
+        let query = json!({
+            "structuredQuery": {
+                "from": [{"collectionId": USERS_COLLECTION}],
+                "select": {"fields": [{"fieldPath": "__name__"}]},
+                "where": {
+                    "fieldFilter": {
+                        "field": {"fieldPath": "slack_digest_enabled"},
+                        "op": "EQUAL",
+                        "value": {"booleanValue": true}
+                    }
+                }
+            }
+        });
 
-        let auth_token = f
-            .get("authToken")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        let response = self
+            .build_request(reqwest::Method::POST, &url)
+            .await?
+            .json(&query)
+            .send()
+            .await?;
 
-        let created_at = f
-            .get("createdAt")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore query error", &error_text));
+        }
 
-        let last_query_at = f
-            .get("lastQueryAt")
-            .and_then(|v| v.get("stringValue"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        let results: Vec<Value> = response.json().await?;
+        let uids = results
+            .iter()
+            .filter_map(|r| firestore_serde::document_id(r.get("document")?))
+            .map(|s| s.to_string())
+            .collect();
 
-        Ok(Some(crate::models::agent::AgentVm {
-            vm_name,
-            zone,
-            ip,
-            status,
-            auth_token,
-            created_at,
-            last_query_at,
-        }))
+        Ok(uids)
     }
 
-    /// Set agent VM info on a user's document
-    pub async fn set_agent_vm(
+    /// Record the local date the Slack digest was last sent
+    pub async fn set_slack_last_digest_date(
         &self,
         uid: &str,
-        vm_name: &str,
-        zone: &str,
-        ip: Option<&str>,
-        status: crate::models::agent::AgentVmStatus,
-        auth_token: &str,
-        created_at: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut vm_fields = json!({
-            "vmName": {"stringValue": vm_name},
-            "zone": {"stringValue": zone},
-            "status": {"stringValue": status.to_string()},
-            "authToken": {"stringValue": auth_token},
-            "createdAt": {"stringValue": created_at}
-        });
+        date: &str,
+    ) -> Result<(), FirestoreError> {
+        let url = format!(
+            "{}?updateMask.fieldPaths=last_digest_date&currentDocument.exists=true",
+            self.integration_url(uid, SLACK_INTEGRATION_DOC)
+        );
 
-        if let Some(ip_val) = ip {
-            vm_fields.as_object_mut().unwrap().insert(
-                "ip".to_string(),
-                json!({"stringValue": ip_val}),
-            );
-        }
+        let response = self
+            .build_request(reqwest::Method::PATCH, &url)
+            .await?
+            .json(&json!({"fields": {"last_digest_date": {"stringValue": date}}}))
+            .send()
+            .await?;
 
-        let fields = json!({
-            "agentVm": {
-                "mapValue": {
-                    "fields": vm_fields
-                }
-            }
-        });
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(FirestoreError::from_response(status, "Firestore update error", &error_text));
+        }
 
-        self.update_user_fields(uid, fields, &["agentVm"]).await
+        Ok(())
     }
 
     // =========================================================================
@@ -9487,7 +17646,7 @@ impl FirestoreService {
         &self,
         uid: &str,
         rows: &[crate::models::screen_activity::ScreenActivityRow],
-    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<usize, FirestoreError> {
         if rows.is_empty() {
             return Ok(0);
         }
@@ -9500,7 +17659,7 @@ impl FirestoreService {
                 .map(|row| {
                     let doc_name = format!(
                         "projects/{}/databases/(default)/documents/{}/{}/{}/{}",
-                        self.project_id,
+                        self.project_id(),
                         USERS_COLLECTION,
                         uid,
                         SCREEN_ACTIVITY_SUBCOLLECTION,
@@ -9526,7 +17685,7 @@ impl FirestoreService {
 
             let commit_url = format!(
                 "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents:commit",
-                self.project_id
+                self.project_id()
             );
 
             let body = json!({ "writes": writes });
@@ -9539,8 +17698,9 @@ impl FirestoreService {
                 .await?;
 
             if !response.status().is_success() {
+                let status = response.status();
                 let error_text = response.text().await?;
-                return Err(format!("Firestore screen_activity batch commit error: {}", error_text).into());
+                return Err(FirestoreError::from_response(status, "Firestore screen_activity batch commit error", &error_text));
             }
 
             written += chunk.len();
@@ -9568,6 +17728,45 @@ impl Default for Structured {
     }
 }
 
+/// orderBy clauses ending with `__name__`, so every document has a unique cursor position
+fn order_by_with_name(order_by: Option<&Value>) -> Vec<Value> {
+    let mut clauses = order_by.and_then(|o| o.as_array()).cloned().unwrap_or_default();
+    let has_name = clauses
+        .iter()
+        .any(|c| c.pointer("/field/fieldPath").and_then(|p| p.as_str()) == Some("__name__"));
+    if !has_name {
+        let direction = clauses
+            .last()
+            .and_then(|c| c.get("direction"))
+            .cloned()
+            .unwrap_or_else(|| json!("ASCENDING"));
+        clauses.push(json!({"field": {"fieldPath": "__name__"}, "direction": direction}));
+    }
+    clauses
+}
+
+/// Cursor values that position a query right after `doc`; None when the document lacks
+/// one of the ordered fields
+fn cursor_values(order_by: &[Value], doc: &Value) -> Option<Vec<Value>> {
+    order_by
+        .iter()
+        .map(|clause| {
+            let path = clause.pointer("/field/fieldPath")?.as_str()?;
+            if path == "__name__" {
+                return Some(json!({"referenceValue": doc.get("name")?}));
+            }
+            let mut value = doc.get("fields")?;
+            for (i, part) in path.split('.').enumerate() {
+                if i > 0 {
+                    value = value.get("mapValue")?.get("fields")?;
+                }
+                value = value.get(part)?;
+            }
+            Some(value.clone())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -9579,4 +17778,707 @@ mod tests {
         assert_eq!(id, document_id_from_seed("test content"));
         assert_ne!(id, document_id_from_seed("different content"));
     }
+
+    #[test]
+    fn test_stream_query_cursor() {
+        let order_by = order_by_with_name(Some(&json!([
+            {"field": {"fieldPath": "analytics.total_talk_time_seconds"}, "direction": "DESCENDING"}
+        ])));
+        assert_eq!(order_by.len(), 2);
+        assert_eq!(order_by[1], json!({"field": {"fieldPath": "__name__"}, "direction": "DESCENDING"}));
+        assert_eq!(order_by_with_name(Some(&json!(order_by))).len(), 2);
+
+        let name = "projects/p/databases/(default)/documents/users/u1/conversations/c1";
+        let doc = json!({
+            "name": name,
+            "fields": {"analytics": {"mapValue": {"fields": {
+                "total_talk_time_seconds": {"doubleValue": 42.5}
+            }}}}
+        });
+        assert_eq!(
+            cursor_values(&order_by, &doc),
+            Some(vec![json!({"doubleValue": 42.5}), json!({"referenceValue": name})])
+        );
+        assert_eq!(cursor_values(&order_by, &json!({"name": name, "fields": {}})), None);
+    }
+
+    #[test]
+    fn test_changed_since_query_resumes_after_cursor() {
+        let service = test_service();
+        let since = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().with_timezone(&Utc);
+
+        let first = service.changed_since_query("u1", "memories", "updated_at", Some(since), None, 100);
+        let query = &first["structuredQuery"];
+        assert_eq!(query["orderBy"][0]["field"]["fieldPath"], "updated_at");
+        assert_eq!(query["orderBy"][1]["field"]["fieldPath"], "__name__");
+        assert_eq!(query["where"]["fieldFilter"]["op"], "GREATER_THAN_OR_EQUAL");
+        assert!(query.get("startAt").is_none());
+
+        let next = service.changed_since_query("u1", "memories", "updated_at", Some(since), Some("m7"), 100);
+        let start = &next["structuredQuery"]["startAt"];
+        assert_eq!(start["before"], false);
+        assert_eq!(
+            start["values"][1]["referenceValue"],
+            "projects/test-project/databases/(default)/documents/users/u1/memories/m7"
+        );
+
+        let docs = vec![
+            json!({"name": "projects/p/databases/(default)/documents/users/u1/memories/m1",
+                   "fields": {"updated_at": {"timestampValue": "2024-05-01T10:00:00Z"}}}),
+            json!({"name": "projects/p/databases/(default)/documents/users/u1/memories/m2",
+                   "fields": {"updated_at": {"timestampValue": "2024-05-01T10:05:00Z"}}}),
+        ];
+        let page = service.sync_page(docs.clone(), "updated_at", 2, |_| Some(()));
+        assert_eq!(page.end.map(|p| p.id), Some("m2".to_string()));
+        assert!(service.sync_page(docs, "updated_at", 3, |_| Some(())).end.is_none());
+    }
+
+    #[test]
+    fn test_precondition_query_param() {
+        assert_eq!(Precondition::Exists(true).query_param(), "currentDocument.exists=true");
+        assert_eq!(
+            Precondition::UpdateTime("2024-05-01T10:00:00.123456Z".to_string()).query_param(),
+            "currentDocument.updateTime=2024-05-01T10:00:00.123456Z"
+        );
+        assert!(is_precondition_failure(
+            reqwest::StatusCode::BAD_REQUEST,
+            r#"{"error": {"status": "FAILED_PRECONDITION"}}"#
+        ));
+        assert!(!is_precondition_failure(reqwest::StatusCode::BAD_REQUEST, "INVALID_ARGUMENT"));
+    }
+
+    fn test_service() -> FirestoreService {
+        FirestoreService {
+            client: Client::new(),
+            project_id: "test-project".to_string(),
+            credentials: None,
+            cached_tokens: Arc::new(RwLock::new(HashMap::new())),
+            encryption_secret: None,
+            apps_cache: AppsCache::new(),
+            model_overrides: ModelOverrides::new(),
+            hedged_reads: None,
+            disabled: false,
+        }
+    }
+
+    /// Captured `plugins_data` document fields for an integration app
+    fn integration_app_fixture() -> Value {
+        json!({
+            "name": "projects/based-hardware/databases/(default)/documents/plugins_data/01JTEST",
+            "fields": {
+                "name": {"stringValue": "Notion Sync"},
+                "capabilities": {"arrayValue": {"values": [
+                    {"stringValue": "external_integration"},
+                    {"stringValue": "proactive_notification"}
+                ]}},
+                "external_integration": {"mapValue": {"fields": {
+                    "triggers_on": {"stringValue": "memory_creation"},
+                    "webhook_url": {"stringValue": "https://example.com/webhook"},
+                    "setup_completed_url": {"stringValue": "https://example.com/setup-check"},
+                    "setup_instructions_file_path": {"stringValue": "https://example.com/instructions"},
+                    "is_instructions_url": {"booleanValue": true},
+                    "app_home_url": {"stringValue": "https://example.com"},
+                    "auth_steps": {"arrayValue": {"values": [
+                        {"mapValue": {"fields": {
+                            "name": {"stringValue": "Connect Notion"},
+                            "url": {"stringValue": "https://example.com/oauth"}
+                        }}}
+                    ]}},
+                    "actions": {"arrayValue": {"values": [
+                        {"mapValue": {"fields": {"action": {"stringValue": "create_conversation"}}}},
+                        {"mapValue": {"fields": {"action": {"stringValue": "read_memories"}}}}
+                    ]}}
+                }}},
+                "proactive_notification": {"mapValue": {"fields": {
+                    "scopes": {"arrayValue": {"values": [
+                        {"stringValue": "user_name"},
+                        {"stringValue": "user_facts"},
+                        {"stringValue": "not_a_scope"}
+                    ]}}
+                }}}
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_app_nested_integration_config() {
+        let service = test_service();
+        let app = service.parse_app(&integration_app_fixture()).unwrap();
+
+        let ei = app.external_integration.expect("external_integration parsed");
+        assert_eq!(ei.triggers_on, TriggerEvent::MemoryCreation);
+        assert_eq!(ei.webhook_url, "https://example.com/webhook");
+        assert_eq!(ei.setup_completed_url.as_deref(), Some("https://example.com/setup-check"));
+        assert!(ei.is_instructions_url);
+        assert_eq!(ei.auth_steps, vec![AuthStep {
+            name: "Connect Notion".to_string(),
+            url: "https://example.com/oauth".to_string(),
+        }]);
+        assert_eq!(ei.actions, vec![ActionType::CreateConversation, ActionType::ReadMemories]);
+
+        let pn = app.proactive_notification.expect("proactive_notification parsed");
+        assert_eq!(pn.scopes, vec![NotificationScope::UserName, NotificationScope::UserFacts]);
+    }
+
+    #[test]
+    fn test_parse_app_unknown_trigger_is_skipped() {
+        let service = test_service();
+        let mut doc = integration_app_fixture();
+        doc["fields"]["external_integration"]["mapValue"]["fields"]["triggers_on"] =
+            json!({"stringValue": "something_new"});
+        let app = service.parse_app(&doc).unwrap();
+        assert!(app.external_integration.is_none());
+    }
+
+    #[test]
+    fn test_external_integration_round_trip() {
+        let service = test_service();
+        let app = service.parse_app(&integration_app_fixture()).unwrap();
+        let ei = app.external_integration.unwrap();
+        let pn = app.proactive_notification.unwrap();
+
+        let doc = json!({
+            "name": "projects/p/databases/(default)/documents/plugins_data/x",
+            "fields": {
+                "external_integration": FirestoreService::external_integration_to_value(&ei),
+                "proactive_notification": FirestoreService::proactive_notification_to_value(&pn)
+            }
+        });
+        let reparsed = service.parse_app(&doc).unwrap();
+        let ei2 = reparsed.external_integration.unwrap();
+        assert_eq!(ei2.webhook_url, ei.webhook_url);
+        assert_eq!(ei2.auth_steps, ei.auth_steps);
+        assert_eq!(ei2.actions, ei.actions);
+        assert_eq!(reparsed.proactive_notification.unwrap().scopes, pn.scopes);
+    }
+
+    /// Wrap typed fields as a REST document with the given ID
+    fn document(collection: &str, id: &str, fields: Value) -> Value {
+        json!({
+            "name": format!("projects/p/databases/(default)/documents/users/u1/{}/{}", collection, id),
+            "fields": fields
+        })
+    }
+
+    #[test]
+    fn test_action_item_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let item: ActionItemDB = serde_json::from_value(json!({
+            "id": "a1", "description": "Send the deck", "completed": true, "created_at": now,
+            "completed_at": now, "due_at": null, "updated_at": null, "conversation_id": "c1",
+            "priority": "high", "relevance_score": 3, "rollover_excluded": true
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&item, &["id"]).unwrap();
+        let back = service.parse_action_item(&document("action_items", "a1", fields)).unwrap();
+        assert_eq!((back.id.as_str(), back.description.as_str(), back.completed), ("a1", "Send the deck", true));
+        assert_eq!((back.completed_at, back.due_at), (Some(now), None));
+        assert_eq!((back.priority.as_deref(), back.relevance_score), (Some("high"), Some(3)));
+        assert_eq!(back.rollover_excluded, Some(true));
+
+        // Python writes explicit nulls, and older items have no created_at
+        let back = service
+            .parse_action_item(&document("action_items", "a2", json!({
+                "description": {"stringValue": "Old"},
+                "completed": {"nullValue": null},
+                "priority": {"nullValue": null}
+            })))
+            .unwrap();
+        assert_eq!((back.completed, back.priority), (false, None));
+    }
+
+    #[test]
+    fn test_memory_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let memory: MemoryDB = serde_json::from_value(json!({
+            "id": "m1", "uid": "u1", "content": "Prefers morning meetings", "category": "interesting",
+            "created_at": now, "updated_at": now, "conversation_id": "c1", "user_review": null,
+            "scoring": "00_998_0000000001", "source_app": null, "context_summary": null,
+            "reasoning": null, "current_activity": null, "window_title": null,
+            "confidence": 0.75, "tags": ["tips"]
+        }))
+        .unwrap();
+        let mut fields = firestore_serde::to_fields(&memory, &["id", "uid", "input_device_name"]).unwrap();
+        fields["source"] = json!({"stringValue": "desktop"});
+        let back = service.parse_memory(&document("memories", "m1", fields), "u1").unwrap();
+        assert_eq!((back.id.as_str(), back.content.as_str()), ("m1", "Prefers morning meetings"));
+        assert_eq!((back.category, back.created_at, back.updated_at), (MemoryCategory::Interesting, now, now));
+        assert_eq!((back.confidence, back.tags), (Some(0.75), vec!["tips".to_string()]));
+        assert_eq!((back.visibility.as_str(), back.source.as_deref()), ("private", Some("desktop")));
+
+        // A category from a newer backend reads as the default
+        let mut fields = firestore_serde::to_fields(&memory, &["id", "uid"]).unwrap();
+        fields["category"] = json!({"stringValue": "something_new"});
+        let back = service.parse_memory(&document("memories", "m1", fields), "u1").unwrap();
+        assert_eq!(back.category, MemoryCategory::System);
+
+        // Without a secret, enhanced content is never returned as ciphertext
+        let mut fields = firestore_serde::to_fields(&memory, &["id", "uid"]).unwrap();
+        fields["data_protection_level"] = json!({"stringValue": "enhanced"});
+        let back = service.parse_memory(&document("memories", "m1", fields), "u1").unwrap();
+        assert_eq!(back.content, "[Encrypted content — decryption failed]");
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let mut message = MessageDB::new("What did I promise Sam?".to_string(), "human".to_string(), None, Some("s1".to_string()));
+        message.rating = Some(1);
+        message.provenance.route = Some("chat".to_string());
+        message.attachments.push(MessageAttachment {
+            id: "f1".to_string(),
+            name: "notes.png".to_string(),
+            mime_type: "image/png".to_string(),
+            size: 2048,
+            storage_path: "u1/f1".to_string(),
+            created_at: now,
+        });
+        let mut fields = firestore_serde::to_fields(&message, &["id"]).unwrap();
+        // Attachments are stored as `files`; one without a storage path is skipped
+        let mut files = fields.as_object_mut().unwrap().remove("attachments").unwrap();
+        files["arrayValue"]["values"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"mapValue": {"fields": {"id": {"stringValue": "f2"}}}}));
+        fields["files"] = files;
+
+        let back = service.parse_message(&document("messages", &message.id, fields), "u1").unwrap();
+        assert_eq!((back.id, back.text), (message.id, message.text));
+        assert_eq!((back.sender.as_str(), back.session_id.as_deref(), back.rating), ("human", Some("s1"), Some(1)));
+        assert_eq!((back.created_at, back.provenance.route.as_deref()), (message.created_at, Some("chat")));
+        assert_eq!(back.attachments.len(), 1);
+        assert_eq!((back.attachments[0].size, back.attachments[0].created_at), (2048, now));
+
+        let back = service
+            .parse_message(&document("messages", "m2", json!({"text": {"stringValue": "hi"}})), "u1")
+            .unwrap();
+        assert_eq!((back.sender.as_str(), back.attachments.len()), ("human", 0));
+    }
+
+    #[test]
+    fn test_focus_session_round_trip() {
+        let service = test_service();
+        let session: FocusSessionDB = serde_json::from_value(json!({
+            "id": "f1", "status": "focused", "app_or_site": "Xcode", "description": "Editing code",
+            "created_at": Utc::now(), "duration_seconds": 300
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&session, &["id"]).unwrap();
+        let back = service.parse_focus_session(&document("focus_sessions", "f1", fields)).unwrap();
+        assert_eq!((back.id.as_str(), back.status, back.app_or_site.as_str()), ("f1", FocusStatus::Focused, "Xcode"));
+        assert_eq!((back.created_at, back.duration_seconds), (session.created_at, Some(300)));
+
+        let back = service
+            .parse_focus_session(&document("focus_sessions", "f2", json!({"status": {"stringValue": "idle"}})))
+            .unwrap();
+        assert_eq!((back.status, back.message), (FocusStatus::Distracted, None));
+    }
+
+    #[test]
+    fn test_chat_session_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let session: ChatSessionDB = serde_json::from_value(json!({
+            "id": "s1", "title": "Trip planning", "created_at": now, "updated_at": now,
+            "app_id": "app1", "message_count": 4, "starred": true
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&session, &["id"]).unwrap();
+        let back = service.parse_chat_session(&document("chat_sessions", "s1", fields)).unwrap();
+        assert_eq!((back.title.as_str(), back.app_id.as_deref()), ("Trip planning", Some("app1")));
+        assert_eq!((back.message_count, back.starred, back.updated_at), (4, true, now));
+
+        // Old Flutter sessions
+        let created = "2024-05-01T10:00:00Z";
+        let legacy = |fields: Value| service.parse_chat_session(&document("chat_sessions", "old", fields)).unwrap();
+        let back = legacy(json!({
+            "created_at": {"timestampValue": created},
+            "message_ids": {"arrayValue": {"values": [{"stringValue": "m1"}, {"stringValue": "m2"}]}}
+        }));
+        assert_eq!((back.title.as_str(), back.message_count, back.app_id), ("Omi", 2, None));
+        assert_eq!(back.updated_at.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+        let back = legacy(json!({"plugin_id": {"stringValue": "app2"}}));
+        assert_eq!((back.title.as_str(), back.app_id.as_deref()), ("New Chat", Some("app2")));
+    }
+
+    #[test]
+    fn test_advice_round_trip() {
+        let service = test_service();
+        let advice: AdviceDB = serde_json::from_value(json!({
+            "id": "a1", "content": "Take a break", "category": "health", "reasoning": null,
+            "source_app": "Slack", "confidence": 0.9, "context_summary": null, "current_activity": null,
+            "created_at": Utc::now(), "updated_at": null, "is_read": true, "helpful": false
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&advice, &["id"]).unwrap();
+        let back = service.parse_advice(&document("advice", "a1", fields)).unwrap();
+        assert_eq!((back.content.as_str(), back.category, back.confidence), ("Take a break", AdviceCategory::Health, 0.9));
+        assert_eq!((back.is_read, back.helpful, back.created_at), (true, Some(false), advice.created_at));
+
+        let back = service
+            .parse_advice(&document("advice", "a2", json!({"category": {"stringValue": "finance"}})))
+            .unwrap();
+        assert_eq!((back.category, back.confidence, back.is_dismissed), (AdviceCategory::Other, 0.5, false));
+    }
+
+    #[test]
+    fn test_release_round_trip() {
+        let service = test_service();
+        let release = crate::routes::updates::ReleaseInfo {
+            version: "1.2.0".to_string(),
+            build_number: 120,
+            download_url: "https://example.com/Omi.zip".to_string(),
+            ed_signature: "sig".to_string(),
+            published_at: "2026-03-02T08:30:00Z".to_string(),
+            changelog: vec!["Faster sync".to_string()],
+            is_live: true,
+            is_critical: false,
+            channel: Some("stable".to_string()),
+        };
+        let fields = firestore_serde::to_fields(&release, &[]).unwrap();
+        let back = service.parse_release(&document("desktop_releases", "v1.2.0+120", fields)).unwrap();
+        assert_eq!((back.version, back.build_number, back.changelog), (release.version, 120, release.changelog));
+        assert_eq!((back.published_at, back.is_live, back.channel), (release.published_at, true, release.channel));
+
+        // Unpromoted releases have no channel
+        let back = service
+            .parse_release(&document("desktop_releases", "v1", json!({"version": {"stringValue": "1.0"}})))
+            .unwrap();
+        assert_eq!((back.build_number, back.channel), (0, None));
+    }
+
+    #[test]
+    fn test_goal_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let goal: GoalDB = serde_json::from_value(json!({
+            "id": "g1", "title": "Read", "goal_type": "numeric", "target_value": 20, "current_value": 5,
+            "unit": "pages", "created_at": now, "updated_at": now, "completed_at": now, "source": "user"
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&goal, &["id"]).unwrap();
+        let back = service.parse_goal(&document("goals", "g1", fields)).unwrap();
+        assert_eq!((back.id.as_str(), back.goal_type, back.target_value, back.current_value), ("g1", GoalType::Numeric, 20.0, 5.0));
+        assert_eq!((back.unit.as_deref(), back.completed_at, back.is_active), (Some("pages"), Some(now), true));
+
+        let back = service
+            .parse_goal(&document("goals", "g2", json!({"goal_type": {"stringValue": "streak"}})))
+            .unwrap();
+        assert_eq!((back.goal_type, back.target_value, back.max_value), (GoalType::Boolean, 1.0, 100.0));
+        assert_eq!((back.is_active, back.completed_at), (true, None));
+    }
+
+    #[test]
+    fn test_persona_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let persona: PersonaDB = serde_json::from_value(json!({
+            "id": "p1", "uid": "u1", "name": "Sam", "username": "sam", "description": "Me",
+            "image": "https://example.com/sam.png", "category": "personality-emulation",
+            "capabilities": ["persona"], "persona_prompt": "Be Sam", "status": "approved",
+            "private": true, "author": "Sam", "email": null, "created_at": now, "updated_at": now
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&persona, &["id"]).unwrap();
+        assert!(fields.get("private").is_some());
+        let back = service.parse_persona(&document("plugins_data", "p1", fields)).unwrap();
+        assert_eq!((back.username.as_deref(), back.status.as_str(), back.is_private), (Some("sam"), "approved", true));
+        assert_eq!((back.persona_prompt.as_deref(), back.created_at), (Some("Be Sam"), now));
+
+        let back = service
+            .parse_persona(&document("plugins_data", "p2", json!({"name": {"stringValue": "Old"}})))
+            .unwrap();
+        assert_eq!((back.capabilities, back.status.as_str()), (vec!["persona".to_string()], "under-review"));
+        assert_eq!((back.category.as_str(), back.is_private), ("personality-emulation", false));
+    }
+
+    #[test]
+    fn test_person_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let person: crate::models::Person = serde_json::from_value(json!({
+            "id": "p1", "name": "Alex", "created_at": now, "updated_at": now,
+            "relationship_notes": {"notes": ["Works on billing"], "latest_conversation_id": "c9", "generated_at": now}
+        }))
+        .unwrap();
+        let fields = firestore_serde::to_fields(&person, &["id"]).unwrap();
+        let back = service.parse_person(&document("people", "p1", fields)).unwrap();
+        let notes = back.relationship_notes.unwrap();
+        assert_eq!((back.name.as_str(), notes.latest_conversation_id.as_str(), notes.generated_at), ("Alex", "c9", now));
+
+        // Notes missing their conversation are dropped, not the person
+        let back = service
+            .parse_person(&document("people", "p2", json!({
+                "name": {"stringValue": "Kim"},
+                "relationship_notes": {"mapValue": {"fields": {"notes": {"arrayValue": {"values": []}}}}}
+            })))
+            .unwrap();
+        assert_eq!((back.name.as_str(), back.relationship_notes.is_none()), ("Kim", true));
+    }
+
+    #[test]
+    fn test_kg_node_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let node: crate::models::KnowledgeGraphNode = serde_json::from_value(json!({
+            "id": "n1", "label": "Berlin", "node_type": "place", "aliases": ["BER"],
+            "memory_ids": ["m1"], "created_at": now, "updated_at": now,
+            "label_lower": "berlin", "aliases_lower": ["ber"]
+        }))
+        .unwrap();
+        // Nodes keep their ID in the fields as well
+        let fields = firestore_serde::to_fields(&node, &[]).unwrap();
+        let back = service.parse_kg_node(&document("kg_nodes", "ignored", fields)).unwrap();
+        assert_eq!((back.id.as_str(), back.node_type, back.aliases), ("n1", crate::models::NodeType::Place, node.aliases));
+        assert_eq!((back.memory_ids, back.created_at), (node.memory_ids, now));
+
+        let back = service
+            .parse_kg_node(&document("kg_nodes", "n2", json!({"node_type": {"stringValue": "event"}})))
+            .unwrap();
+        assert_eq!((back.id.as_str(), back.node_type), ("n2", crate::models::NodeType::Concept));
+    }
+
+    #[test]
+    fn test_migration_record_round_trip() {
+        let service = test_service();
+        let started = Utc::now();
+        let record = MigrationRecord {
+            id: "backfill".to_string(),
+            status: crate::models::MigrationStatus::Running,
+            scanned: 500,
+            updated: 12,
+            cursor: Some("users/u1/memories/m9".to_string()),
+            dry_run: true,
+            error: None,
+            started_at: started,
+            updated_at: started,
+            completed_at: None,
+        };
+        let fields = firestore_serde::to_fields(&record, &["id"]).unwrap();
+        let back = service.parse_migration_record(&document("migrations", "backfill", fields)).unwrap();
+        assert_eq!((back.id, back.status, back.scanned, back.updated), (record.id, record.status, 500, 12));
+        assert_eq!((back.cursor, back.dry_run, back.started_at), (record.cursor, true, started));
+
+        let back = service
+            .parse_migration_record(&document("migrations", "new", json!({
+                "status": {"stringValue": "paused"},
+                "started_at": {"timestampValue": "2026-03-02T08:30:00Z"}
+            })))
+            .unwrap();
+        assert_eq!((back.status, back.scanned), (crate::models::MigrationStatus::Pending, 0));
+        assert_eq!(back.updated_at, back.started_at);
+    }
+
+    #[test]
+    fn test_accountability_group_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let fields = json!({
+            "name": {"stringValue": "Deep work"},
+            "owner_uid": {"stringValue": "u1"},
+            "invite_code": {"stringValue": "ABC123"},
+            "member_uids": {"arrayValue": {"values": [{"stringValue": "u1"}, {"stringValue": "u2"}]}},
+            "created_at": {"timestampValue": now.to_rfc3339()}
+        });
+        let group = service.parse_accountability_group(&document("groups", "g1", fields)).unwrap();
+        assert_eq!((group.id.as_str(), group.invite_code.as_str(), group.member_count), ("g1", "ABC123", 2));
+        assert_eq!((group.member_uids.len(), group.created_at), (2, now));
+
+        let member: GroupMember = serde_json::from_value(json!({
+            "uid": "u2", "display_name": "Kim", "share_focus": true, "joined_at": now
+        }))
+        .unwrap();
+        // The uid is the member document's ID
+        let fields = firestore_serde::to_fields(&member, &[]).unwrap();
+        assert!(fields.get("uid").is_none());
+        let back = service.parse_group_member(&document("members", "u2", fields)).unwrap();
+        assert_eq!((back.uid.as_str(), back.display_name.as_str(), back.share_focus), ("u2", "Kim", true));
+        assert_eq!(back.joined_at, now);
+    }
+
+    #[test]
+    fn test_advice_update_fields() {
+        let now = Utc::now();
+        let update: crate::models::UpdateAdviceRequest =
+            serde_json::from_value(json!({"seen_at": now, "acted_at": now})).unwrap();
+        let (fields, mask) = advice_update_fields(&update, now);
+        // Seeing advice marks it read
+        assert_eq!(mask, vec!["updated_at", "is_read", "seen_at", "acted_at"]);
+        assert_eq!(fields["is_read"], json!({"booleanValue": true}));
+        assert_eq!(fields["seen_at"], json!({"timestampValue": now.to_rfc3339()}));
+
+        // ... unless the client says otherwise
+        let update: crate::models::UpdateAdviceRequest =
+            serde_json::from_value(json!({"seen_at": now, "is_read": false, "is_dismissed": true})).unwrap();
+        let (fields, mask) = advice_update_fields(&update, now);
+        assert_eq!(mask, vec!["updated_at", "is_read", "is_dismissed", "seen_at"]);
+        assert_eq!(fields["is_read"], json!({"booleanValue": false}));
+    }
+
+    #[test]
+    fn test_advice_bulk_writes_require_the_document() {
+        use crate::models::AdviceBulkAction;
+
+        let name = "projects/p/databases/(default)/documents/users/u1/advice/a1";
+        let write = advice_bulk_write(name.to_string(), AdviceBulkAction::Dismiss, "2026-03-02T08:30:00Z");
+        assert_eq!(write["update"]["fields"]["is_dismissed"], json!({"booleanValue": true}));
+        assert_eq!(write["updateMask"]["fieldPaths"], json!(["is_dismissed", "updated_at"]));
+        assert_eq!(write["currentDocument"], json!({"exists": true}));
+
+        let write = advice_bulk_write(name.to_string(), AdviceBulkAction::Delete, "2026-03-02T08:30:00Z");
+        assert_eq!(write, json!({"delete": name, "currentDocument": {"exists": true}}));
+    }
+
+    #[test]
+    fn test_review_edits_and_duplicate_submissions() {
+        let now = Utc::now();
+        let (fields, mask) = review_edit_fields(None, Some("Better now"), now);
+        assert_eq!(mask, vec!["edited_at", "review"]);
+        assert_eq!(fields["edited_at"], json!({"timestampValue": now.to_rfc3339()}));
+        assert!(fields.get("score").is_none());
+
+        // The exists=false precondition failing means the user already reviewed the app
+        let body = r#"{"error": {"code": 400, "status": "FAILED_PRECONDITION"}}"#;
+        assert!(matches!(
+            submit_review_error(reqwest::StatusCode::BAD_REQUEST, body),
+            FirestoreError::AlreadyExists(_)
+        ));
+        let body = r#"{"error": {"code": 503, "status": "UNAVAILABLE"}}"#;
+        assert!(matches!(
+            submit_review_error(reqwest::StatusCode::SERVICE_UNAVAILABLE, body),
+            FirestoreError::Unavailable(_)
+        ));
+    }
+
+    #[test]
+    fn test_conversation_round_trip() {
+        let service = test_service();
+        let now = Utc::now();
+        let conversation: Conversation = serde_json::from_value(json!({
+            "id": "c1",
+            "created_at": now,
+            "started_at": now,
+            "finished_at": now,
+            "status": "completed",
+            "starred": true,
+            "folder_id": "f1",
+            "structured": {
+                "title": "Roadmap sync",
+                "overview": "Agreed on Q3 priorities.",
+                "category": "work",
+                "action_items": [{"description": "Send notes", "due_at": null}],
+                "events": [{
+                    "title": "Q3 kickoff", "description": "With design", "start": now,
+                    "duration": 45, "created": true
+                }]
+            },
+            "apps_results": [{"app_id": "summarizer", "content": "Short summary"}],
+            "detected_languages": ["en", "de"]
+        }))
+        .unwrap();
+        let (doc, chunks) = service.conversation_to_firestore(&conversation, "u1");
+        assert!(chunks.is_empty());
+
+        let back = service
+            .parse_conversation(&document("conversations", "c1", doc["fields"].clone()), "u1")
+            .unwrap();
+        assert_eq!((back.id.as_str(), back.created_at, back.finished_at), ("c1", now, now));
+        assert_eq!((back.status, back.starred, back.folder_id), (conversation.status, true, Some("f1".to_string())));
+        assert_eq!(back.structured.title, "Roadmap sync");
+        assert_eq!(back.structured.action_items[0].description, "Send notes");
+        let event = &back.structured.events[0];
+        assert_eq!((event.title.as_str(), event.description.as_str()), ("Q3 kickoff", "With design"));
+        assert_eq!((event.start, event.duration, event.created), (now, 45, true));
+        assert_eq!(back.apps_results[0].app_id.as_deref(), Some("summarizer"));
+        assert_eq!(back.detected_languages, conversation.detected_languages);
+    }
+
+    #[test]
+    fn test_app_round_trip() {
+        let service = test_service();
+        let mut app = service.parse_app(&integration_app_fixture()).unwrap();
+        app.description = "Sends conversations to Notion".to_string();
+        app.category = "productivity".to_string();
+        app.installs = 42;
+        app.rating_avg = Some(4.5);
+        app.approved = true;
+        app.created_at = Some(Utc::now());
+
+        // Apps are written by the Python backend; the typed mapper produces the same shape
+        let fields = firestore_serde::to_fields(&app, &["id"]).unwrap();
+        let back = service
+            .parse_app(&json!({"name": "projects/p/databases/(default)/documents/plugins_data/01JTEST", "fields": fields}))
+            .unwrap();
+        assert_eq!((back.id.as_str(), back.name.as_str()), ("01JTEST", "Notion Sync"));
+        assert_eq!((back.category, back.installs, back.rating_avg), (app.category, 42, Some(4.5)));
+        assert_eq!((back.approved, back.created_at), (true, app.created_at));
+        assert_eq!(back.capabilities, app.capabilities);
+        assert_eq!(back.external_integration.unwrap().actions, app.external_integration.unwrap().actions);
+        assert_eq!(back.proactive_notification.unwrap().scopes, app.proactive_notification.unwrap().scopes);
+    }
+
+    #[test]
+    fn test_credentials_selected_by_type() {
+        let external: GoogleCredentials = serde_json::from_value(json!({
+            "type": "external_account",
+            "audience": "//iam.googleapis.com/projects/1/locations/global/workloadIdentityPools/p/providers/q",
+            "subject_token_type": "urn:ietf:params:oauth:token-type:jwt",
+            "token_url": "https://sts.googleapis.com/v1/token",
+            "credential_source": {"file": "/var/run/token", "format": {"type": "text"}}
+        }))
+        .unwrap();
+        assert!(matches!(external, GoogleCredentials::ExternalAccount(_)));
+
+        let impersonated: GoogleCredentials = serde_json::from_value(json!({
+            "type": "impersonated_service_account",
+            "service_account_impersonation_url": "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/sa@p.iam.gserviceaccount.com:generateAccessToken",
+            "source_credentials": {
+                "type": "authorized_user",
+                "client_id": "id",
+                "client_secret": "secret",
+                "refresh_token": "token"
+            }
+        }))
+        .unwrap();
+        match impersonated {
+            GoogleCredentials::ImpersonatedServiceAccount(c) => {
+                assert!(matches!(*c.source_credentials, GoogleCredentials::AuthorizedUser(_)));
+            }
+            other => panic!("unexpected credentials: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_llm_credentials_encryption_round_trip() {
+        let service = FirestoreService { encryption_secret: Some(b"test-secret".to_vec()), ..test_service() };
+        let stored = LlmCredentials {
+            provider: LlmProvider::Gemini,
+            api_key: "AIzaSyExample1234".to_string(),
+            updated_at: Utc::now(),
+        };
+        let fields = service.llm_credentials_to_fields("user-1", &stored).unwrap();
+        assert_ne!(fields["access_token"]["stringValue"], "AIzaSyExample1234");
+        assert_eq!(fields["access_token_encrypted"]["booleanValue"], true);
+
+        let loaded = service.parse_llm_credentials(&fields, "user-1").unwrap();
+        assert_eq!(loaded.api_key, "AIzaSyExample1234");
+        assert_eq!(loaded.provider, LlmProvider::Gemini);
+
+        // Another user's id or another secret can't decrypt the key
+        assert!(service.parse_llm_credentials(&fields, "user-2").is_err());
+        let other = FirestoreService { encryption_secret: Some(b"other-secret".to_vec()), ..test_service() };
+        assert!(other.parse_llm_credentials(&fields, "user-1").is_err());
+        assert!(test_service().parse_llm_credentials(&fields, "user-1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_service_fails_without_reaching_firestore() {
+        let service = FirestoreService::disabled(None);
+        assert!(service.is_disabled());
+        let result = service.get_conversation("user-1", "conv-1").await;
+        assert!(matches!(result, Err(FirestoreError::Unavailable(_))));
+    }
 }
@@ -0,0 +1,222 @@
+// Firestore errors - What went wrong with a Firestore (or GCS) call, classified so handlers
+// can answer with a matching HTTP status instead of a blanket 500.
+// Classification uses the `error.status` of the REST error body (the gRPC code name), and
+// the HTTP status when the body has none.
+
+use axum::http::StatusCode;
+
+#[derive(Debug)]
+pub enum FirestoreError {
+    /// The document (or object) doesn't exist
+    NotFound(String),
+    /// A create hit an existing document
+    AlreadyExists(String),
+    /// A write's precondition (update time, existence) didn't hold
+    FailedPrecondition(String),
+    /// Aborted because of contention with another write
+    Aborted(String),
+    /// The service account may not access the resource
+    PermissionDenied(String),
+    /// The access token was rejected
+    Unauthenticated(String),
+    /// Quota or rate limit hit
+    ResourceExhausted(String),
+    /// The request was malformed (a bug in the request this backend built)
+    InvalidArgument(String),
+    /// Firestore unreachable, overloaded or too slow; worth retrying
+    Unavailable(String),
+    /// Anything else, including responses that couldn't be decoded
+    Internal(String),
+}
+
+impl FirestoreError {
+    /// Classify a failed response from its status and body; `context` prefixes the message
+    pub fn from_response(status: reqwest::StatusCode, context: &str, error_text: &str) -> Self {
+        let message = format!("{}: {}", context, error_text);
+        let code = serde_json::from_str::<serde_json::Value>(error_text)
+            .ok()
+            .and_then(|body| body["error"]["status"].as_str().map(str::to_string));
+
+        match code.as_deref() {
+            Some("NOT_FOUND") => return Self::NotFound(message),
+            Some("ALREADY_EXISTS") => return Self::AlreadyExists(message),
+            Some("FAILED_PRECONDITION") => return Self::FailedPrecondition(message),
+            Some("ABORTED") => return Self::Aborted(message),
+            Some("PERMISSION_DENIED") => return Self::PermissionDenied(message),
+            Some("UNAUTHENTICATED") => return Self::Unauthenticated(message),
+            Some("RESOURCE_EXHAUSTED") => return Self::ResourceExhausted(message),
+            Some("INVALID_ARGUMENT") | Some("OUT_OF_RANGE") => return Self::InvalidArgument(message),
+            Some("UNAVAILABLE") | Some("DEADLINE_EXCEEDED") => return Self::Unavailable(message),
+            _ => {}
+        }
+
+        match status.as_u16() {
+            404 => Self::NotFound(message),
+            409 => Self::Aborted(message),
+            412 => Self::FailedPrecondition(message),
+            403 => Self::PermissionDenied(message),
+            401 => Self::Unauthenticated(message),
+            429 => Self::ResourceExhausted(message),
+            400 => Self::InvalidArgument(message),
+            503 | 504 => Self::Unavailable(message),
+            _ => Self::Internal(message),
+        }
+    }
+
+    /// The status a handler should answer with
+    pub fn http_status(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AlreadyExists(_) | Self::FailedPrecondition(_) | Self::Aborted(_) => StatusCode::CONFLICT,
+            Self::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            Self::ResourceExhausted(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            // Our credentials or request, not the client's
+            Self::Unauthenticated(_) | Self::InvalidArgument(_) | Self::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// Handler error: the classified status with a fixed message for it. The error's own
+    /// message names documents and quotes Firestore responses, so it only goes to the logs.
+    pub fn public_error(&self) -> (StatusCode, String) {
+        let status = self.http_status();
+        (status, public_message(status).to_string())
+    }
+
+    /// Whether the same call may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Aborted(_) | Self::ResourceExhausted(_) | Self::Unavailable(_))
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::NotFound(m)
+            | Self::AlreadyExists(m)
+            | Self::FailedPrecondition(m)
+            | Self::Aborted(m)
+            | Self::PermissionDenied(m)
+            | Self::Unauthenticated(m)
+            | Self::ResourceExhausted(m)
+            | Self::InvalidArgument(m)
+            | Self::Unavailable(m)
+            | Self::Internal(m) => m,
+        }
+    }
+}
+
+impl std::fmt::Display for FirestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for FirestoreError {}
+
+/// What clients are told for each status `http_status` can return
+fn public_message(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::NOT_FOUND => "Not found",
+        StatusCode::CONFLICT => "Conflicting update, please retry",
+        StatusCode::FORBIDDEN => "Access denied",
+        StatusCode::TOO_MANY_REQUESTS => "Too many requests, please retry later",
+        StatusCode::SERVICE_UNAVAILABLE => "Service temporarily unavailable, please retry",
+        _ => "Internal server error",
+    }
+}
+
+/// Handler error: logs the details, answers with `public_error`
+impl From<FirestoreError> for (StatusCode, String) {
+    fn from(e: FirestoreError) -> Self {
+        tracing::error!("Firestore call failed: {}", e);
+        e.public_error()
+    }
+}
+
+impl From<reqwest::Error> for FirestoreError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() || e.is_connect() {
+            Self::Unavailable(e.to_string())
+        } else {
+            Self::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for FirestoreError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for FirestoreError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for FirestoreError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<String> for FirestoreError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+impl From<&str> for FirestoreError {
+    fn from(message: &str) -> Self {
+        Self::Internal(message.to_string())
+    }
+}
+
+/// Errors of helpers shared with non-Firestore code (token signing, document mapping)
+impl From<Box<dyn std::error::Error + Send + Sync>> for FirestoreError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match e.downcast::<FirestoreError>() {
+            Ok(e) => *e,
+            Err(e) => Self::Internal(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification_and_status() {
+        let body = r#"{"error": {"code": 400, "message": "precondition", "status": "FAILED_PRECONDITION"}}"#;
+        let e = FirestoreError::from_response(reqwest::StatusCode::BAD_REQUEST, "Update failed", body);
+        assert!(matches!(e, FirestoreError::FailedPrecondition(_)));
+        assert_eq!(e.http_status(), StatusCode::CONFLICT);
+        assert!(e.to_string().starts_with("Update failed: "));
+
+        let e = FirestoreError::from_response(reqwest::StatusCode::TOO_MANY_REQUESTS, "Query", "quota");
+        assert_eq!(e.http_status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(e.is_retryable());
+
+        let e = FirestoreError::from_response(reqwest::StatusCode::NOT_FOUND, "Get", "");
+        assert_eq!(<(StatusCode, String)>::from(e).0, StatusCode::NOT_FOUND);
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(FirestoreError::Unavailable("down".into()));
+        assert!(matches!(FirestoreError::from(boxed), FirestoreError::Unavailable(_)));
+    }
+
+    #[test]
+    fn test_public_error_hides_details() {
+        let body = r#"{"error": {"status": "PERMISSION_DENIED", "message": "users/u1/memories/m1 denied"}}"#;
+        let e = FirestoreError::from_response(reqwest::StatusCode::FORBIDDEN, "projects/p/documents", body);
+        let (status, message) = <(StatusCode, String)>::from(e);
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(message, "Access denied");
+
+        let (status, message) = FirestoreError::Internal("decode users/u1: bad field".into()).public_error();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(message, "Internal server error");
+    }
+}
@@ -0,0 +1,155 @@
+// Firestore HTTP - Connection tuning and hedged reads for the Firestore REST client
+// Connections are kept warm (pooled, TCP keepalive, HTTP/2 pings) so requests skip the
+// handshake. With hedging on, a critical read still unanswered after its recent p95 latency
+// is sent a second time and the first successful response wins.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::select_ok;
+use reqwest::{Client, RequestBuilder, Response};
+
+use crate::config::Config;
+
+/// Latencies kept per read path
+const LATENCY_WINDOW: usize = 200;
+/// Samples needed before a read path is hedged; until then there is no p95 to go by
+const MIN_HEDGE_SAMPLES: usize = 20;
+/// HTTP/2 ping interval, so idle connections aren't silently dropped by middleboxes
+const HTTP2_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// A connection whose ping isn't answered within this is closed
+const HTTP2_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connection settings for the Firestore client
+#[derive(Debug, Clone)]
+pub struct HttpTuning {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub tcp_keepalive: Duration,
+    pub hedged_reads: bool,
+    pub hedge_min_delay: Duration,
+}
+
+impl HttpTuning {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            pool_max_idle_per_host: config.firestore_pool_max_idle_per_host,
+            pool_idle_timeout: Duration::from_secs(config.firestore_pool_idle_timeout_secs),
+            tcp_keepalive: Duration::from_secs(config.firestore_tcp_keepalive_secs),
+            hedged_reads: config.firestore_hedged_reads,
+            hedge_min_delay: Duration::from_millis(config.firestore_hedge_min_delay_ms),
+        }
+    }
+
+    /// Client with the tuned pool and keepalives; HTTP/2 is negotiated via ALPN
+    pub fn build_client(&self) -> reqwest::Result<Client> {
+        Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(HTTP2_KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_timeout(HTTP2_KEEP_ALIVE_TIMEOUT)
+            .http2_keep_alive_while_idle(true)
+            .build()
+    }
+
+    /// Hedging state, when enabled
+    pub fn hedged_reads(&self) -> Option<HedgedReads> {
+        self.hedged_reads.then(|| HedgedReads::new(self.hedge_min_delay))
+    }
+}
+
+/// Recent latencies per read path, which decide when a read is hedged
+#[derive(Debug, Clone)]
+pub struct HedgedReads {
+    min_delay: Duration,
+    latencies: Arc<Mutex<HashMap<&'static str, VecDeque<Duration>>>>,
+}
+
+impl HedgedReads {
+    pub fn new(min_delay: Duration) -> Self {
+        Self {
+            min_delay,
+            latencies: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn record(&self, path: &'static str, latency: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        let window = latencies.entry(path).or_default();
+        if window.len() == LATENCY_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(latency);
+    }
+
+    /// How long to wait before sending the backup: the path's p95, never under the
+    /// minimum delay. None until the path has enough samples.
+    pub fn hedge_delay(&self, path: &str) -> Option<Duration> {
+        let latencies = self.latencies.lock().unwrap();
+        let window = latencies.get(path).filter(|w| w.len() >= MIN_HEDGE_SAMPLES)?;
+        let mut sorted: Vec<Duration> = window.iter().copied().collect();
+        sorted.sort();
+        let p95 = sorted[(sorted.len() * 95).div_ceil(100) - 1];
+        Some(p95.max(self.min_delay))
+    }
+
+    /// Send `request`, and a copy of it if no response arrived within the hedge delay.
+    /// The first successful response is returned; the other request is dropped.
+    pub async fn send(&self, path: &'static str, request: RequestBuilder) -> reqwest::Result<Response> {
+        let started = Instant::now();
+        let backup = self
+            .hedge_delay(path)
+            .and_then(|delay| request.try_clone().map(|backup| (delay, backup)));
+        let mut primary = Box::pin(request.send());
+
+        let result = match backup {
+            Some((delay, backup)) => {
+                tokio::select! {
+                    result = &mut primary => result,
+                    _ = tokio::time::sleep(delay) => {
+                        tracing::debug!("Hedging Firestore {} read after {:?}", path, delay);
+                        select_ok([primary, Box::pin(backup.send())])
+                            .await
+                            .map(|(response, _)| response)
+                    }
+                }
+            }
+            None => primary.await,
+        };
+
+        if result.is_ok() {
+            self.record(path, started.elapsed());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hedge_delay_tracks_p95() {
+        let hedging = HedgedReads::new(Duration::from_millis(20));
+        for ms in 1..MIN_HEDGE_SAMPLES as u64 {
+            hedging.record("get_conversation", Duration::from_millis(ms * 10));
+        }
+        assert_eq!(hedging.hedge_delay("get_conversation"), None);
+
+        // 1..=100 x 10ms: p95 is the 95th sample
+        for ms in MIN_HEDGE_SAMPLES as u64..=100 {
+            hedging.record("get_conversation", Duration::from_millis(ms * 10));
+        }
+        assert_eq!(hedging.hedge_delay("get_conversation"), Some(Duration::from_millis(950)));
+        assert_eq!(hedging.hedge_delay("get_memories"), None);
+
+        // Fast paths are floored at the minimum delay, and old samples roll off
+        for _ in 0..LATENCY_WINDOW {
+            hedging.record("get_conversation", Duration::from_millis(1));
+        }
+        assert_eq!(hedging.hedge_delay("get_conversation"), Some(Duration::from_millis(20)));
+    }
+}
@@ -0,0 +1,324 @@
+// Firestore serde - Maps model structs to and from Firestore REST documents
+// Typed values ({"stringValue": ..}, {"mapValue": ..}) are converted to plain JSON and back,
+// so parsers can lean on the models' serde attributes instead of digging through `fields`.
+// Conversations and apps keep field-by-field parsers: they skip bad nested entries one at a
+// time and decompress/decrypt transcripts and photos, which a derive can't express; their
+// round-trip tests pin them to the shape this mapper writes. Encrypted tokens and LLM
+// credentials are read field by field as well, since they are decrypted before use.
+
+use chrono::DateTime;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+type MapperResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Decode a document's typed `fields` into `T`. The document ID (last segment of
+/// `name`) is added as `id` unless the fields already have one.
+pub fn from_document<T: DeserializeOwned>(doc: &Value) -> MapperResult<T> {
+    Ok(serde_json::from_value(Value::Object(decode_document(doc)?))?)
+}
+
+/// Plain JSON object for a document, with `id` added as in `from_document`. For parsers
+/// that adjust a few fields (legacy names, encrypted values) before deserializing.
+pub fn decode_document(doc: &Value) -> MapperResult<Map<String, Value>> {
+    let fields = doc.get("fields").ok_or("Missing fields")?;
+    let mut object = match decode_map(fields) {
+        Value::Object(object) => object,
+        _ => Map::new(),
+    };
+    if !object.contains_key("id") {
+        if let Some(id) = document_id(doc) {
+            object.insert("id".to_string(), Value::String(id.to_string()));
+        }
+    }
+    Ok(object)
+}
+
+/// Document ID: the last segment of the document's full `name`
+pub fn document_id(doc: &Value) -> Option<&str> {
+    doc.get("name")?.as_str()?.rsplit('/').next()
+}
+
+/// Remove `key` if it doesn't decode as `T`, so an unknown enum value or a malformed
+/// nested map falls back to the model's default instead of failing the whole document
+pub fn drop_invalid<T: DeserializeOwned>(object: &mut Map<String, Value>, key: &str) {
+    if object
+        .get(key)
+        .is_some_and(|value| serde_json::from_value::<T>(value.clone()).is_err())
+    {
+        object.remove(key);
+    }
+}
+
+/// Add each field of `defaults` the document doesn't have. For fields older documents
+/// lack but the model requires.
+pub fn fill_missing(object: &mut Map<String, Value>, defaults: Value) {
+    if let Value::Object(defaults) = defaults {
+        for (key, value) in defaults {
+            object.entry(key).or_insert(value);
+        }
+    }
+}
+
+/// Decode typed `fields` (a document's or a nested map's) into `T`
+pub fn from_fields<T: DeserializeOwned>(fields: &Value) -> MapperResult<T> {
+    Ok(serde_json::from_value(decode_map(fields))?)
+}
+
+/// Encode `value` as typed document fields, leaving out `skip` (e.g. an `id` that is the
+/// document ID). None fields are omitted rather than written as nulls.
+pub fn to_fields<T: Serialize>(value: &T, skip: &[&str]) -> MapperResult<Value> {
+    let Value::Object(mut object) = serde_json::to_value(value)? else {
+        return Err("Only structs and maps can be written as document fields".into());
+    };
+    for key in skip {
+        object.remove(*key);
+    }
+    Ok(encode_map(&object))
+}
+
+/// Plain JSON for one typed value
+pub fn decode_value(value: &Value) -> Value {
+    let Some((kind, inner)) = value.as_object().and_then(|o| o.iter().next()) else {
+        return Value::Null;
+    };
+    match kind.as_str() {
+        "integerValue" => inner
+            .as_str()
+            .and_then(|s| s.parse::<i64>().ok())
+            .or_else(|| inner.as_i64())
+            .map_or(Value::Null, Value::from),
+        // NaN and infinities arrive as strings and have no JSON form
+        "doubleValue" => inner.as_f64().map_or(Value::Null, Value::from),
+        "stringValue" | "timestampValue" | "referenceValue" | "bytesValue" | "booleanValue" => inner.clone(),
+        "mapValue" => decode_map(inner.get("fields").unwrap_or(&Value::Null)),
+        "arrayValue" => Value::Array(
+            inner
+                .get("values")
+                .and_then(|v| v.as_array())
+                .map(|values| values.iter().map(decode_value).collect())
+                .unwrap_or_default(),
+        ),
+        "geoPointValue" => json!({
+            "latitude": inner.get("latitude").cloned().unwrap_or(Value::Null),
+            "longitude": inner.get("longitude").cloned().unwrap_or(Value::Null),
+        }),
+        _ => Value::Null,
+    }
+}
+
+/// Null fields are left out, so a stored null reads like a missing field and falls back
+/// to the model's serde default
+fn decode_map(fields: &Value) -> Value {
+    let object = fields
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), decode_value(value)))
+                .filter(|(_, value)| !value.is_null())
+                .collect()
+        })
+        .unwrap_or_default();
+    Value::Object(object)
+}
+
+/// Typed value for plain JSON. RFC 3339 date-times (how chrono serializes `DateTime`)
+/// become `timestampValue` so they sort and filter as timestamps.
+pub fn encode_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({"nullValue": null}),
+        Value::Bool(b) => json!({"booleanValue": b}),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({"integerValue": n.to_string()}),
+        Value::Number(n) => json!({"doubleValue": n.as_f64()}),
+        Value::String(s) if is_timestamp(s) => json!({"timestampValue": s}),
+        Value::String(s) => json!({"stringValue": s}),
+        Value::Array(values) => {
+            json!({"arrayValue": {"values": values.iter().map(encode_value).collect::<Vec<_>>()}})
+        }
+        Value::Object(object) => json!({"mapValue": {"fields": encode_map(object)}}),
+    }
+}
+
+fn encode_map(object: &Map<String, Value>) -> Value {
+    Value::Object(
+        object
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key.clone(), encode_value(value)))
+            .collect(),
+    )
+}
+
+fn is_timestamp(s: &str) -> bool {
+    s.len() >= 20 && s.as_bytes()[10] == b'T' && DateTime::parse_from_rfc3339(s).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ConversationTemplate, DeviceDB, Folder, KnowledgeGraphEdge, Memo, ReviewItem, UserWebhook,
+        WeeklyReview,
+    };
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde::Deserialize;
+
+    /// Encode, wrap as a REST document and decode again
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T, id: &str) -> T {
+        let fields = to_fields(value, &["id"]).unwrap();
+        let doc = json!({"name": format!("projects/p/databases/(default)/documents/c/{}", id), "fields": fields});
+        from_document(&doc).unwrap()
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let at = "2026-03-02T08:00:00.123456Z";
+        let fields = to_fields(
+            &json!({"n": 3, "x": 2.5, "at": at, "text": "2026-03-02", "tags": ["a"], "gone": null}),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(fields["n"], json!({"integerValue": "3"}));
+        assert_eq!(fields["x"], json!({"doubleValue": 2.5}));
+        assert_eq!(fields["at"], json!({"timestampValue": at}));
+        assert_eq!(fields["text"], json!({"stringValue": "2026-03-02"}));
+        assert!(fields.get("gone").is_none());
+
+        #[derive(Deserialize)]
+        struct Point {
+            lat: f64,
+            count: i64,
+            missing: Option<String>,
+        }
+        let point: Point = from_fields(&json!({
+            "lat": {"integerValue": "4"},
+            "count": {"integerValue": "7"},
+            "missing": {"nullValue": null}
+        }))
+        .unwrap();
+        assert_eq!((point.lat, point.count, point.missing), (4.0, 7, None));
+    }
+
+    fn now() -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 8, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_memo_round_trip() {
+        let memo = Memo {
+            id: "m1".to_string(),
+            text: "Call the bank".to_string(),
+            title: "Bank".to_string(),
+            action_items: vec!["Call the bank".to_string()],
+            language: None,
+            created_at: now(),
+        };
+        let back = round_trip(&memo, "m1");
+        assert_eq!((back.id, back.action_items, back.created_at), (memo.id, memo.action_items, now()));
+    }
+
+    #[test]
+    fn test_folder_round_trip() {
+        let folder: Folder = serde_json::from_value(json!({
+            "id": "f1", "name": "Work", "created_at": now(), "updated_at": now(), "order": 2
+        }))
+        .unwrap();
+        let back = round_trip(&folder, "f1");
+        assert_eq!((back.name, back.color, back.order), ("Work".to_string(), folder.color, 2));
+    }
+
+    #[test]
+    fn test_device_round_trip() {
+        let device: DeviceDB = serde_json::from_value(json!({
+            "id": "d1", "serial_number": "SN1", "battery_level": 80,
+            "last_seen_at": now(), "paired_at": now(), "updated_at": now()
+        }))
+        .unwrap();
+        let back = round_trip(&device, "d1");
+        assert_eq!((back.battery_level, back.last_seen_at, back.name), (Some(80), Some(now()), None));
+    }
+
+    #[test]
+    fn test_kg_edge_round_trip() {
+        // Edges keep their ID in the fields, which wins over the document name
+        let edge = KnowledgeGraphEdge::new("a".to_string(), "b".to_string(), "works with".to_string());
+        let fields = to_fields(&edge, &[]).unwrap();
+        let back: KnowledgeGraphEdge = from_document(&json!({"name": "x/ignored", "fields": fields})).unwrap();
+        assert_eq!((back.id, back.label), (edge.id, edge.label));
+    }
+
+    #[test]
+    fn test_conversation_template_round_trip() {
+        let template: ConversationTemplate = serde_json::from_value(json!({
+            "id": "t1", "name": "Standup", "summary_sections": ["Updates", "Blockers"],
+            "created_at": now(), "updated_at": now()
+        }))
+        .unwrap();
+        let back = round_trip(&template, "t1");
+        assert_eq!(back.summary_sections, template.summary_sections);
+    }
+
+    #[test]
+    fn test_user_webhook_decode() {
+        // The secret isn't serialized, so webhooks are only decoded with the mapper
+        let webhook: UserWebhook = from_document(&json!({
+            "name": "users/u1/webhooks/w1",
+            "fields": {
+                "url": {"stringValue": "https://example.com/hook"},
+                "events": {"arrayValue": {"values": [{"stringValue": "action_item.completed"}]}},
+                "secret": {"stringValue": "s3cret"},
+                "created_at": {"timestampValue": "2026-03-02T08:30:00Z"}
+            }
+        }))
+        .unwrap();
+        assert_eq!((webhook.id.as_str(), webhook.secret.as_str(), webhook.enabled), ("w1", "s3cret", true));
+    }
+
+    #[test]
+    fn test_weekly_review_round_trip() {
+        let review = WeeklyReview {
+            week_start: NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            timezone: "UTC".to_string(),
+            accomplished: vec![ReviewItem {
+                action_item_id: "a1".to_string(),
+                description: "Ship it".to_string(),
+                due_at: None,
+                completed_at: Some(now()),
+            }],
+            slipped: vec![],
+            focused_minutes: 90,
+            distracted_minutes: 10,
+            focus_summary: "Solid week".to_string(),
+            suggested_priorities: vec!["Rest".to_string()],
+            scheduled: true,
+            generated_at: now(),
+        };
+        let back = round_trip(&review, "2026-03-02");
+        assert_eq!(back.week_start, review.week_start);
+        assert_eq!(back.accomplished[0].completed_at, Some(now()));
+        assert_eq!((back.focused_minutes, back.scheduled), (90, true));
+    }
+
+    #[test]
+    fn test_document_id() {
+        let doc = json!({"name": "projects/p/databases/(default)/documents/users/u1/memories/m1"});
+        assert_eq!(document_id(&doc), Some("m1"));
+        assert_eq!(document_id(&json!({"name": "m2"})), Some("m2"));
+        assert_eq!(document_id(&json!({"fields": {}})), None);
+    }
+
+    #[test]
+    fn test_drop_invalid_and_fill_missing() {
+        let mut object = decode_document(&json!({
+            "name": "users/u1/focus_sessions/f1",
+            "fields": {"status": {"stringValue": "idle"}, "app_or_site": {"stringValue": "Xcode"}}
+        }))
+        .unwrap();
+        drop_invalid::<crate::models::FocusStatus>(&mut object, "status");
+        assert!(object.get("status").is_none());
+        fill_missing(&mut object, json!({"status": "focused", "app_or_site": ""}));
+        assert_eq!((object["status"].as_str(), object["app_or_site"].as_str()), (Some("focused"), Some("Xcode")));
+    }
+}
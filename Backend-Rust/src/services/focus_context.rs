@@ -0,0 +1,159 @@
+// Focus context - Correlates focus sessions with the conversations they overlapped
+// Sessions are attached to a conversation when it's processed; the reverse lookup runs on demand.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{
+    Conversation, ConversationFocusSession, FocusConversationSnippet, FocusSessionDB, TranscriptSegment,
+};
+use crate::services::storage::Storage;
+use crate::services::FirestoreService;
+
+/// Length credited to a session without a recorded duration (as in focus stats)
+const DEFAULT_SESSION_SECONDS: i64 = 60;
+/// Longest session or conversation considered; bounds how far back overlap queries look
+const OVERLAP_LOOKBACK_HOURS: i64 = 8;
+/// Focus sessions read per conversation
+const MAX_OVERLAP_SESSIONS: usize = 500;
+/// Conversations read per focus session
+const MAX_OVERLAP_CONVERSATIONS: usize = 20;
+/// Longest transcript snippet returned per conversation, in characters
+const MAX_SNIPPET_CHARS: usize = 1000;
+
+fn session_end(session: &FocusSessionDB) -> DateTime<Utc> {
+    session.created_at + Duration::seconds(session.duration_seconds.unwrap_or(DEFAULT_SESSION_SECONDS))
+}
+
+fn overlap_seconds(start: DateTime<Utc>, end: DateTime<Utc>, other_start: DateTime<Utc>, other_end: DateTime<Utc>) -> i64 {
+    (end.min(other_end) - start.max(other_start)).num_seconds().max(0)
+}
+
+/// Sessions overlapping [start, end), oldest first
+pub fn overlapping_sessions(
+    sessions: &[FocusSessionDB],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<ConversationFocusSession> {
+    let mut overlapping: Vec<ConversationFocusSession> = sessions
+        .iter()
+        .filter_map(|s| {
+            let overlap = overlap_seconds(s.created_at, session_end(s), start, end);
+            (overlap > 0).then(|| ConversationFocusSession {
+                session_id: s.id.clone(),
+                status: s.status.clone(),
+                app_or_site: s.app_or_site.clone(),
+                started_at: s.created_at,
+                overlap_seconds: overlap,
+            })
+        })
+        .collect();
+    overlapping.sort_by_key(|s| s.started_at);
+    overlapping
+}
+
+/// Transcript lines spoken between `start` and `end`, capped at MAX_SNIPPET_CHARS
+pub fn transcript_snippet(conversation: &Conversation, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let at = |offset: f64| conversation.started_at + Duration::milliseconds((offset * 1000.0) as i64);
+    let spoken: Vec<TranscriptSegment> = conversation
+        .transcript_segments
+        .iter()
+        .filter(|s| s.end > s.start && at(s.start) < end && at(s.end) > start)
+        .cloned()
+        .collect();
+    TranscriptSegment::to_transcript_text(&spoken)
+        .chars()
+        .take(MAX_SNIPPET_CHARS)
+        .collect()
+}
+
+/// Focus sessions overlapping a conversation; empty (with a warning) if they can't be read
+pub async fn sessions_for_conversation(
+    storage: &dyn Storage,
+    uid: &str,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+) -> Vec<ConversationFocusSession> {
+    let since = started_at - Duration::hours(OVERLAP_LOOKBACK_HOURS);
+    match storage
+        .get_focus_sessions_between(uid, since, finished_at, MAX_OVERLAP_SESSIONS)
+        .await
+    {
+        Ok(sessions) => overlapping_sessions(&sessions, started_at, finished_at),
+        Err(e) => {
+            tracing::warn!("Failed to load focus sessions for user {}: {}", uid, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Conversations overlapping a focus session, each with what was said during it
+pub async fn conversations_for_session(
+    firestore: &FirestoreService,
+    uid: &str,
+    session: &FocusSessionDB,
+) -> Result<Vec<FocusConversationSnippet>, Box<dyn std::error::Error + Send + Sync>> {
+    let start = session.created_at;
+    let end = session_end(session);
+    let since = (start - Duration::hours(OVERLAP_LOOKBACK_HOURS)).to_rfc3339();
+    let conversations = firestore
+        .get_conversations(
+            uid,
+            MAX_OVERLAP_CONVERSATIONS,
+            0,
+            false,
+            &[],
+            None,
+            None,
+            Some(&since),
+            Some(&end.to_rfc3339()),
+        )
+        .await?;
+
+    let mut snippets: Vec<FocusConversationSnippet> = conversations
+        .iter()
+        .filter(|c| !c.deleted && overlap_seconds(c.started_at, c.finished_at, start, end) > 0)
+        .map(|c| {
+            let snippet = transcript_snippet(c, start, end);
+            FocusConversationSnippet {
+                conversation_id: c.id.clone(),
+                title: c.structured.title.clone(),
+                started_at: c.started_at,
+                finished_at: c.finished_at,
+                snippet: if snippet.is_empty() { c.structured.overview.clone() } else { snippet },
+            }
+        })
+        .collect();
+    snippets.sort_by_key(|s| s.started_at);
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FocusStatus;
+
+    #[test]
+    fn test_overlapping_sessions() {
+        let utc = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let session = |id: &str, at: &str, duration: Option<i64>| FocusSessionDB {
+            id: id.to_string(),
+            status: FocusStatus::Focused,
+            app_or_site: "Xcode".to_string(),
+            description: String::new(),
+            message: None,
+            created_at: utc(at),
+            duration_seconds: duration,
+        };
+        let sessions = [
+            session("before", "2026-10-14T09:00:00Z", Some(600)),
+            session("spans-start", "2026-10-14T09:50:00Z", Some(1200)),
+            session("inside", "2026-10-14T10:20:00Z", None),
+            session("after", "2026-10-14T10:30:00Z", Some(600)),
+        ];
+
+        let overlapping = overlapping_sessions(&sessions, utc("2026-10-14T10:00:00Z"), utc("2026-10-14T10:30:00Z"));
+        let summary: Vec<(&str, i64)> =
+            overlapping.iter().map(|s| (s.session_id.as_str(), s.overlap_seconds)).collect();
+        assert_eq!(summary, vec![("spans-start", 600), ("inside", 60)]);
+    }
+}
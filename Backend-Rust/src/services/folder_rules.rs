@@ -0,0 +1,251 @@
+// Folder rules - Files conversations into folders from user-defined rules
+// Rules are tried in order and the first enabled rule whose conditions all match wins, so a
+// narrow rule ("title contains standup" → Work/Standups) goes above a broad one
+// ("category = work" → Work). Rules pointing at a deleted folder are skipped. New
+// conversations are filed when they're saved without a folder; POST /v1/folder-rules/backfill
+// applies the rules to existing ones.
+
+use crate::models::{
+    Conversation, Folder, FolderRule, FolderRuleBackfillRequest, FolderRuleBackfillResponse,
+    FolderRuleCondition, FolderRuleField, FolderRuleMove, FolderRuleOperator, SaveFolderRuleRequest,
+    MAX_BACKFILL_CONVERSATIONS, MAX_RULE_CONDITIONS, MAX_RULE_LABEL_CHARS,
+};
+use crate::services::conversation_templates::title_matches;
+use crate::services::storage::Storage;
+use crate::services::FirestoreService;
+
+/// Conversations read per page during a backfill
+const BACKFILL_PAGE_SIZE: usize = 100;
+
+/// Serialized (snake_case) name of an enum value, e.g. "apple_watch"
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn condition_matches(condition: &FolderRuleCondition, conversation: &Conversation) -> bool {
+    let actual = match condition.field {
+        FolderRuleField::Title => conversation.structured.title.clone(),
+        FolderRuleField::Overview => conversation.structured.overview.clone(),
+        FolderRuleField::Category => serde_name(&conversation.structured.category),
+        FolderRuleField::Source => serde_name(&conversation.source),
+    };
+    match condition.operator {
+        FolderRuleOperator::Contains => title_matches(&condition.value, &actual),
+        FolderRuleOperator::Equals => actual.trim().eq_ignore_ascii_case(condition.value.trim()),
+    }
+}
+
+/// Whether every condition of an enabled rule matches. A rule without conditions matches nothing.
+pub fn rule_matches(rule: &FolderRule, conversation: &Conversation) -> bool {
+    rule.enabled
+        && !rule.conditions.is_empty()
+        && rule.conditions.iter().all(|c| condition_matches(c, conversation))
+}
+
+/// The first matching rule whose folder still exists
+pub fn first_match<'a>(
+    rules: &'a [FolderRule],
+    folders: &[Folder],
+    conversation: &Conversation,
+) -> Option<&'a FolderRule> {
+    rules
+        .iter()
+        .filter(|rule| folders.iter().any(|f| f.id == rule.folder_id))
+        .find(|rule| rule_matches(rule, conversation))
+}
+
+/// Folder a new conversation should be filed in, if a rule matches. Failures are
+/// logged and leave the conversation unfiled.
+pub async fn folder_for_new_conversation(
+    storage: &dyn Storage,
+    uid: &str,
+    conversation: &Conversation,
+) -> Option<String> {
+    let rules = match storage.get_folder_rules(uid).await {
+        Ok(rules) => rules,
+        Err(e) => {
+            tracing::warn!("Failed to get folder rules for user {}: {}", uid, e);
+            return None;
+        }
+    };
+    if !rules.iter().any(|rule| rule_matches(rule, conversation)) {
+        return None;
+    }
+    let folders = match storage.get_folders(uid).await {
+        Ok(folders) => folders,
+        Err(e) => {
+            tracing::warn!("Failed to get folders for user {}: {}", uid, e);
+            return None;
+        }
+    };
+    let rule = first_match(&rules, &folders, conversation)?;
+    tracing::info!("Folder rule {} files conversation {} into {}", rule.id, conversation.id, rule.folder_id);
+    Some(rule.folder_id.clone())
+}
+
+/// Apply the rules to the user's most recent conversations
+pub async fn backfill(
+    firestore: &FirestoreService,
+    uid: &str,
+    request: &FolderRuleBackfillRequest,
+) -> Result<FolderRuleBackfillResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let limit = request
+        .limit
+        .unwrap_or(MAX_BACKFILL_CONVERSATIONS)
+        .min(MAX_BACKFILL_CONVERSATIONS);
+    let (rules, folders) = tokio::try_join!(firestore.get_folder_rules(uid), firestore.get_folders(uid))?;
+
+    let mut scanned = 0;
+    let mut moved = Vec::new();
+    while scanned < limit {
+        let page_size = BACKFILL_PAGE_SIZE.min(limit - scanned);
+        let page = firestore
+            .get_conversations(uid, page_size, scanned, false, &[], None, None, None, None)
+            .await?;
+        scanned += page.len();
+
+        for conversation in &page {
+            if conversation.folder_id.is_some() && !request.overwrite {
+                continue;
+            }
+            let Some(rule) = first_match(&rules, &folders, conversation) else {
+                continue;
+            };
+            if conversation.folder_id.as_deref() == Some(rule.folder_id.as_str()) {
+                continue;
+            }
+            if !request.dry_run {
+                firestore
+                    .set_conversation_folder(uid, &conversation.id, Some(&rule.folder_id))
+                    .await?;
+            }
+            moved.push(FolderRuleMove {
+                conversation_id: conversation.id.clone(),
+                rule_id: rule.id.clone(),
+                folder_id: rule.folder_id.clone(),
+            });
+        }
+        if page.len() < page_size {
+            break;
+        }
+    }
+
+    tracing::info!(
+        "Folder rule backfill for user {}: {} scanned, {} {}",
+        uid,
+        scanned,
+        moved.len(),
+        if request.dry_run { "would move" } else { "moved" }
+    );
+    Ok(FolderRuleBackfillResponse {
+        dry_run: request.dry_run,
+        scanned,
+        moved,
+    })
+}
+
+/// Trim and validate a save request
+pub fn clean_request(request: SaveFolderRuleRequest) -> Result<SaveFolderRuleRequest, String> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_RULE_LABEL_CHARS {
+        return Err(format!("Name must be 1-{} characters", MAX_RULE_LABEL_CHARS));
+    }
+    if request.conditions.is_empty() || request.conditions.len() > MAX_RULE_CONDITIONS {
+        return Err(format!("A rule needs 1-{} conditions", MAX_RULE_CONDITIONS));
+    }
+    let mut conditions = Vec::with_capacity(request.conditions.len());
+    for condition in request.conditions {
+        let value = condition.value.trim().to_string();
+        if value.is_empty() || value.chars().count() > MAX_RULE_LABEL_CHARS {
+            return Err(format!("Condition values must be 1-{} characters", MAX_RULE_LABEL_CHARS));
+        }
+        conditions.push(FolderRuleCondition { value, ..condition });
+    }
+    let folder_id = request.folder_id.trim().to_string();
+    if folder_id.is_empty() {
+        return Err("folder_id is required".to_string());
+    }
+    Ok(SaveFolderRuleRequest {
+        name,
+        conditions,
+        folder_id,
+        enabled: request.enabled,
+    })
+}
+
+/// Rule IDs in their new order: the listed ones first, then the rest as they were
+pub fn reordered(rules: &[FolderRule], rule_ids: &[String]) -> Vec<String> {
+    let mut order: Vec<String> = rule_ids
+        .iter()
+        .filter(|id| rules.iter().any(|r| &r.id == *id))
+        .cloned()
+        .collect();
+    order.dedup();
+    for rule in rules {
+        if !order.contains(&rule.id) {
+            order.push(rule.id.clone());
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(title: &str, category: &str, source: &str) -> Conversation {
+        serde_json::from_value(serde_json::json!({
+            "id": "c1",
+            "created_at": "2024-03-10T10:00:00Z",
+            "started_at": "2024-03-10T10:00:00Z",
+            "finished_at": "2024-03-10T10:30:00Z",
+            "source": source,
+            "structured": {"title": title, "category": category},
+        }))
+        .unwrap()
+    }
+
+    fn rule(id: &str, order: i32, conditions: serde_json::Value) -> FolderRule {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "conditions": conditions,
+            "folder_id": format!("folder-{}", id),
+            "order": order,
+        }))
+        .unwrap()
+    }
+
+    fn folder(id: &str) -> Folder {
+        serde_json::from_value(serde_json::json!({"id": id})).unwrap()
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let standup = rule("standup", 0, serde_json::json!([
+            {"field": "title", "operator": "contains", "value": "Standup"},
+        ]));
+        let work = rule("work", 1, serde_json::json!([
+            {"field": "category", "operator": "equals", "value": "work"},
+            {"field": "source", "operator": "equals", "value": "desktop"},
+        ]));
+        let rules = vec![standup, work];
+        let folders = vec![folder("folder-standup"), folder("folder-work")];
+
+        let daily = conversation("Daily standup", "work", "desktop");
+        assert_eq!(first_match(&rules, &folders, &daily).unwrap().id, "standup");
+
+        let planning = conversation("Sprint planning", "work", "desktop");
+        assert_eq!(first_match(&rules, &folders, &planning).unwrap().id, "work");
+        assert!(first_match(&rules, &folders, &conversation("Sprint planning", "work", "phone")).is_none());
+
+        // The standup folder was deleted: fall through to the next rule
+        assert_eq!(first_match(&rules, &folders[1..], &daily).unwrap().id, "work");
+
+        let ids = vec!["work".to_string(), "missing".to_string()];
+        assert_eq!(reordered(&rules, &ids), vec!["work", "standup"]);
+    }
+}
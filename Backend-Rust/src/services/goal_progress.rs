@@ -0,0 +1,279 @@
+// Goal progress service - daily LLM-assisted goal updates from the previous day's activity
+// Reads completed tasks, focus stats and conversations; updates goals, daily scores and milestones
+
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::job_queue::JobQueue;
+use crate::llm::client::GoalProgressEstimate;
+use crate::llm::{LlmClient, TaskModels};
+use crate::models::{DailyScore, GoalDB, GoalType, NotificationKind};
+use crate::services::{llm_keys, notifications, FirestoreService};
+
+/// How often to check which users are due for evaluation
+const GOAL_PROGRESS_CHECK_INTERVAL_MINUTES: u64 = 60;
+/// Upper bound on users evaluated per run
+const MAX_USERS_PER_RUN: usize = 500;
+/// Progress percentages that trigger a notification when crossed
+const MILESTONES: &[u32] = &[25, 50, 75, 100];
+
+/// Spawn the daily goal progress job. No-op without a Gemini key.
+pub fn spawn_goal_progress_updater(firestore: Arc<FirestoreService>, config: Arc<Config>, jobs: JobQueue) {
+    let Some(api_key) = config.gemini_api_key.clone() else {
+        tracing::warn!("GEMINI_API_KEY not set - goal progress automation disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            GOAL_PROGRESS_CHECK_INTERVAL_MINUTES * 60,
+        ));
+        loop {
+            interval.tick().await;
+            let models = firestore.llm_task_models(&config.llm_models).await;
+            let llm = LlmClient::new(api_key.clone()).with_task_models(models.clone());
+            run_goal_progress_pass(&firestore, &llm, &models, &jobs).await;
+        }
+    });
+
+    tracing::info!(
+        "Goal progress automation scheduled every {} minutes",
+        GOAL_PROGRESS_CHECK_INTERVAL_MINUTES
+    );
+}
+
+/// Queue an evaluation of yesterday (UTC) for every user with active goals that hasn't been
+/// evaluated yet. Users who brought their own LLM key are evaluated with it.
+async fn run_goal_progress_pass(firestore: &Arc<FirestoreService>, llm: &LlmClient, models: &TaskModels, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_active_goals(MAX_USERS_PER_RUN).await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Goal progress: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    let date = (Utc::now() - Duration::days(1)).format("%Y-%m-%d").to_string();
+    for uid in uids {
+        match firestore.get_goal_progress_evaluated_date(&uid).await {
+            Ok(Some(last)) if last == date => continue,
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Goal progress: failed to read state for user {}: {}", uid, e);
+                continue;
+            }
+        }
+        let (firestore, llm, models, date) = (firestore.clone(), llm.clone(), models.clone(), date.clone());
+        jobs.submit_for_user(&uid, "goal-progress", |uid| async move {
+            let user_llm = llm_keys::user_client(&firestore, &uid, &models).await.map_err(|e| e.0)?;
+            let llm = user_llm.as_ref().unwrap_or(&llm);
+            evaluate_user_goals(&firestore, llm, &uid, &date).await
+        });
+    }
+}
+
+/// Evaluate one user's active goals against a day's activity
+async fn evaluate_user_goals(
+    firestore: &FirestoreService,
+    llm: &LlmClient,
+    uid: &str,
+    date: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let day_start = format!("{}T00:00:00Z", date);
+    let day_end = format!("{}T23:59:59.999Z", date);
+
+    // Snapshot the day's score first; it doesn't depend on the LLM
+    let (completed_tasks, total_tasks) = firestore
+        .get_action_items_for_daily_score(uid, &day_start, &day_end)
+        .await?;
+    let score = if total_tasks > 0 {
+        (completed_tasks as f64 / total_tasks as f64) * 100.0
+    } else {
+        0.0
+    };
+    firestore
+        .save_daily_score(
+            uid,
+            &DailyScore {
+                score,
+                completed_tasks,
+                total_tasks,
+                date: date.to_string(),
+            },
+        )
+        .await?;
+
+    let goals = firestore.get_user_goals(uid, 10).await?;
+    if !goals.is_empty() {
+        let completed: Vec<String> = firestore
+            .get_action_items(uid, 100, 0, Some(true), None, None, None, None, None, None, None, true)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| {
+                item.completed_at
+                    .is_some_and(|at| at.format("%Y-%m-%d").to_string() == date)
+            })
+            .map(|item| item.description)
+            .collect();
+
+        let focus = match firestore.get_focus_stats(uid, date).await {
+            Ok(stats) if stats.session_count > 0 => {
+                let top: Vec<&str> = stats
+                    .top_distractions
+                    .iter()
+                    .take(3)
+                    .map(|d| d.app_or_site.as_str())
+                    .collect();
+                format!(
+                    "{} focused minutes, {} distracted minutes over {} sessions (top distractions: {})",
+                    stats.focused_minutes,
+                    stats.distracted_minutes,
+                    stats.session_count,
+                    if top.is_empty() { "none".to_string() } else { top.join(", ") }
+                )
+            }
+            _ => "(No focus data)".to_string(),
+        };
+
+        let conversations: Vec<String> = firestore
+            .get_conversations(uid, 30, 0, false, &[], None, None, Some(&day_start), Some(&day_end))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| !c.structured.title.is_empty())
+            .map(|c| format!("{}: {}", c.structured.title, c.structured.overview))
+            .collect();
+
+        let estimates = llm
+            .evaluate_goal_progress(date, &goals, &completed, &focus, &conversations)
+            .await?;
+        apply_estimates(firestore, uid, &goals, &estimates).await;
+    }
+
+    firestore.set_goal_progress_evaluated_date(uid, date).await?;
+    Ok(())
+}
+
+/// Write accepted estimates and announce newly reached milestones
+async fn apply_estimates(
+    firestore: &FirestoreService,
+    uid: &str,
+    goals: &[GoalDB],
+    estimates: &[GoalProgressEstimate],
+) {
+    for estimate in estimates {
+        let Some(goal) = goals.iter().find(|g| g.id == estimate.id) else {
+            continue;
+        };
+        let Some(value) = accepted_value(goal, estimate.new_value) else {
+            continue;
+        };
+
+        if let Err(e) = firestore.update_goal_progress(uid, &goal.id, value).await {
+            tracing::error!("Failed to update progress for goal {}: {}", goal.id, e);
+            continue;
+        }
+        tracing::info!(
+            "Goal {} for user {}: {} -> {} ({})",
+            goal.id,
+            uid,
+            goal.current_value,
+            value,
+            estimate.reason
+        );
+
+        if let Some(milestone) = crossed_milestone(goal, goal.current_value, value) {
+            let text = if milestone >= 100 {
+                format!("You reached your goal \"{}\"! {}", goal.title, estimate.reason)
+            } else {
+                format!(
+                    "You're {}% of the way to \"{}\". {}",
+                    milestone, goal.title, estimate.reason
+                )
+            };
+            if let Err(e) = firestore.save_message(uid, text.trim(), "ai", None, None, None).await {
+                tracing::error!("Failed to notify user about goal {}: {}", goal.id, e);
+            }
+            let title = if milestone >= 100 { "Goal reached" } else { "Goal progress" };
+            let link = Some(format!("/goals/{}", goal.id));
+            if let Err(e) = notifications::notify(firestore, uid, NotificationKind::Nudge, title, &text, link).await {
+                tracing::error!("Failed to add goal {} notification to inbox: {}", goal.id, e);
+            }
+        }
+    }
+}
+
+/// Sanitize an LLM estimate. Automatic updates never move numeric or boolean goals
+/// backwards; scale goals stay within their range. None when nothing changes.
+pub fn accepted_value(goal: &GoalDB, proposed: f64) -> Option<f64> {
+    if !proposed.is_finite() {
+        return None;
+    }
+    let value = match goal.goal_type {
+        GoalType::Boolean if proposed >= 1.0 => goal.target_value.max(1.0),
+        GoalType::Boolean => return None,
+        GoalType::Numeric => proposed.max(goal.current_value),
+        GoalType::Scale => proposed.clamp(goal.min_value, goal.max_value),
+    };
+    ((value - goal.current_value).abs() > f64::EPSILON).then_some(value)
+}
+
+/// Highest milestone percentage crossed moving from `old` to `new`
+pub fn crossed_milestone(goal: &GoalDB, old: f64, new: f64) -> Option<u32> {
+    let span = goal.target_value - goal.min_value;
+    if span <= 0.0 {
+        return None;
+    }
+    let percent = |v: f64| (v - goal.min_value) / span * 100.0;
+    let (old, new) = (percent(old), percent(new));
+    MILESTONES
+        .iter()
+        .rev()
+        .find(|&&m| old < m as f64 && new >= m as f64)
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(goal_type: GoalType, current: f64, target: f64) -> GoalDB {
+        serde_json::from_value(serde_json::json!({
+            "id": "g1",
+            "title": "Read books",
+            "goal_type": goal_type,
+            "current_value": current,
+            "target_value": target,
+            "unit": null,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accepted_value_by_goal_type() {
+        let numeric = goal(GoalType::Numeric, 10.0, 20.0);
+        assert_eq!(accepted_value(&numeric, 12.5), Some(12.5));
+        assert_eq!(accepted_value(&numeric, 8.0), None);
+
+        let boolean = goal(GoalType::Boolean, 0.0, 1.0);
+        assert_eq!(accepted_value(&boolean, 1.0), Some(1.0));
+        assert_eq!(accepted_value(&boolean, 0.4), None);
+
+        let scale = goal(GoalType::Scale, 50.0, 80.0);
+        assert_eq!(accepted_value(&scale, 140.0), Some(100.0));
+        assert_eq!(accepted_value(&scale, f64::NAN), None);
+    }
+
+    #[test]
+    fn test_crossed_milestone() {
+        let g = goal(GoalType::Numeric, 0.0, 20.0);
+        assert_eq!(crossed_milestone(&g, 4.0, 6.0), Some(25));
+        assert_eq!(crossed_milestone(&g, 4.0, 16.0), Some(75));
+        assert_eq!(crossed_milestone(&g, 6.0, 9.0), None);
+        assert_eq!(crossed_milestone(&g, 19.0, 25.0), Some(100));
+    }
+}
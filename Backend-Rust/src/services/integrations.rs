@@ -1,12 +1,21 @@
 // App integrations service - External webhook triggers
 // Port of Python backend utils/app_integrations.py
 
+use chrono::Utc;
+use hmac::{Hmac, Mac};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::Semaphore;
 
-use crate::models::{App, Conversation, TriggerEvent};
+use crate::config::Config;
+use crate::models::{ActionItemDB, App, AppScope, Conversation, TriggerEvent, UserWebhook, ACTION_ITEM_COMPLETED_EVENT};
+use crate::services::events::{self, AppEvent, EventBus};
+use crate::services::FirestoreService;
 
 /// Truncate a string to at most `max_bytes` bytes at a valid UTF-8 character boundary.
 fn truncate_str(s: &str, max_bytes: usize) -> &str {
@@ -35,22 +44,196 @@ pub struct IntegrationResult {
     pub success: bool,
     pub message: Option<String>,
     pub error: Option<String>,
+    /// Set when this failure tripped the circuit breaker for the app
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub delivery_disabled: bool,
+}
+
+impl IntegrationResult {
+    fn failure(app: &App, error: String) -> Self {
+        Self {
+            app_id: app.id.clone(),
+            app_name: app.name.clone(),
+            success: false,
+            message: None,
+            error: Some(error),
+            delivery_disabled: false,
+        }
+    }
+}
+
+/// Limits applied to outbound webhook delivery
+#[derive(Debug, Clone)]
+pub struct WebhookPolicy {
+    /// Timeout for a single attempt
+    pub timeout: Duration,
+    /// Retries after the first attempt (5xx and network errors only)
+    pub max_retries: u32,
+    /// Base delay for exponential backoff between retries
+    pub retry_base_delay: Duration,
+    /// Max in-flight calls per app across all users
+    pub max_concurrency_per_app: usize,
+    /// Consecutive failures for a user/app pair before delivery is paused
+    pub failure_threshold: u32,
+}
+
+impl Default for WebhookPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_base_delay: Duration::from_millis(500),
+            max_concurrency_per_app: 4,
+            failure_threshold: 10,
+        }
+    }
+}
+
+impl WebhookPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            timeout: Duration::from_secs(config.integration_webhook_timeout_secs),
+            max_retries: config.integration_webhook_max_retries,
+            max_concurrency_per_app: config.integration_webhook_max_concurrency.max(1),
+            failure_threshold: config.integration_webhook_failure_threshold,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based): base * 2^(attempt-1), capped at 30s
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.retry_base_delay
+            .saturating_mul(factor)
+            .min(Duration::from_secs(30))
+    }
+}
+
+/// Shared delivery state: per-app concurrency limits and failure counters
+#[derive(Clone)]
+struct DeliveryGuard {
+    policy: Arc<WebhookPolicy>,
+    semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    /// Consecutive failures keyed by "uid:app_id"
+    failures: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl DeliveryGuard {
+    fn new(policy: WebhookPolicy) -> Self {
+        Self {
+            policy: Arc::new(policy),
+            semaphores: Arc::new(Mutex::new(HashMap::new())),
+            failures: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn semaphore(&self, app_id: &str) -> Arc<Semaphore> {
+        let mut map = self.semaphores.lock().unwrap();
+        map.entry(app_id.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.policy.max_concurrency_per_app)))
+            .clone()
+    }
+
+    /// Record the outcome of a delivery. Returns true when the failure threshold
+    /// was just reached and delivery for this user/app should be paused.
+    fn record_outcome(&self, uid: &str, app_id: &str, success: bool) -> bool {
+        let key = format!("{}:{}", uid, app_id);
+        let mut failures = self.failures.lock().unwrap();
+        if success {
+            failures.remove(&key);
+            return false;
+        }
+        let count = failures.entry(key.clone()).or_insert(0);
+        *count += 1;
+        if self.policy.failure_threshold > 0 && *count >= self.policy.failure_threshold {
+            failures.remove(&key);
+            return true;
+        }
+        false
+    }
+
+    /// POST a payload, retrying on 5xx and network errors with exponential backoff
+    async fn post_with_retry(
+        &self,
+        client: &Client,
+        url: &str,
+        payload: &Value,
+        headers: &[(&str, String)],
+        timeout: Duration,
+    ) -> Result<reqwest::Response, String> {
+        let mut attempt = 0;
+        loop {
+            let mut request = client.post(url).json(payload).timeout(timeout);
+            for (name, value) in headers {
+                request = request.header(*name, value);
+            }
+            let outcome = request.send().await;
+            let retryable = match &outcome {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            };
+
+            if !retryable || attempt >= self.policy.max_retries {
+                return outcome.map_err(|e| format!("Request failed: {}", e));
+            }
+
+            attempt += 1;
+            let delay = self.policy.backoff(attempt);
+            tracing::debug!(
+                "Retrying webhook {} (attempt {}) in {:?}",
+                truncate_str(url, 100),
+                attempt,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Run a delivery under the app's concurrency limit and track its outcome
+    async fn deliver<F, Fut>(&self, uid: &str, app: &App, call: F) -> IntegrationResult
+    where
+        F: FnOnce(DeliveryGuard) -> Fut,
+        Fut: std::future::Future<Output = IntegrationResult>,
+    {
+        let semaphore = self.semaphore(&app.id);
+        let _permit = semaphore.acquire_owned().await;
+        let mut result = call(self.clone()).await;
+        if self.record_outcome(uid, &app.id, result.success) {
+            tracing::warn!(
+                "App {} reached {} consecutive webhook failures for user {}, pausing delivery",
+                app.id,
+                self.policy.failure_threshold,
+                uid
+            );
+            result.delivery_disabled = true;
+        }
+        result
+    }
 }
 
 /// Integration service for triggering external app webhooks
 pub struct IntegrationService {
     client: Client,
+    guard: DeliveryGuard,
 }
 
 impl IntegrationService {
-    /// Create a new integration service
+    /// Create a new integration service with the default delivery policy
     pub fn new() -> Self {
+        Self::with_policy(WebhookPolicy::default())
+    }
+
+    /// Create a new integration service with a custom delivery policy
+    pub fn with_policy(policy: WebhookPolicy) -> Self {
         let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+            .timeout(policy.timeout)
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client }
+        Self {
+            client,
+            guard: DeliveryGuard::new(policy),
+        }
     }
 
     /// Trigger external integrations for a newly created conversation
@@ -74,9 +257,18 @@ impl IntegrationService {
         // 2. Are enabled by the user
         // 3. Have triggers_on = memory_creation
         // 4. Have a webhook_url configured
+        // 5. Haven't had delivery paused after repeated failures
+        // 6. Were granted read:conversations
+        // 7. Have finished their setup flow, if they need one
         let triggered_apps: Vec<&App> = enabled_apps
             .iter()
             .filter(|app| {
+                if app.integration_delivery_disabled
+                    || app.awaiting_setup()
+                    || !app.has_scope(AppScope::ReadConversations)
+                {
+                    return false;
+                }
                 if let Some(ref integration) = app.external_integration {
                     integration.triggers_on == TriggerEvent::MemoryCreation
                         && !integration.webhook_url.is_empty()
@@ -102,12 +294,17 @@ impl IntegrationService {
 
         for app in triggered_apps {
             let client = self.client.clone();
+            let guard = self.guard.clone();
             let uid = uid.to_string();
             let conversation = conversation.clone();
             let app = app.clone();
 
             let handle = tokio::spawn(async move {
-                Self::call_webhook(&client, &uid, &conversation, &app).await
+                guard
+                    .deliver(&uid, &app, |guard| {
+                        Self::call_webhook(guard, &client, &uid, &conversation, &app)
+                    })
+                    .await
             });
 
             handles.push(handle);
@@ -129,6 +326,7 @@ impl IntegrationService {
 
     /// Call a single app's webhook with conversation data
     async fn call_webhook(
+        guard: DeliveryGuard,
         client: &Client,
         uid: &str,
         conversation: &Conversation,
@@ -143,6 +341,7 @@ impl IntegrationService {
                     success: false,
                     message: None,
                     error: Some("No integration config".to_string()),
+                    delivery_disabled: false,
                 };
             }
         };
@@ -171,12 +370,14 @@ impl IntegrationService {
                     success: false,
                     message: None,
                     error: Some(format!("Failed to serialize conversation: {}", e)),
+                    delivery_disabled: false,
                 };
             }
         };
 
         // Make the webhook call
-        match client.post(&url).json(&payload).send().await {
+        let timeout = guard.policy.timeout;
+        match guard.post_with_retry(client, &url, &payload, &[], timeout).await {
             Ok(response) => {
                 let status = response.status();
 
@@ -199,6 +400,7 @@ impl IntegrationService {
                         success: false,
                         message: None,
                         error: Some(format!("HTTP {}: {}", status, truncated)),
+                        delivery_disabled: false,
                     };
                 }
 
@@ -220,6 +422,7 @@ impl IntegrationService {
                     success: true,
                     message,
                     error: None,
+                    delivery_disabled: false,
                 }
             }
             Err(e) => {
@@ -230,7 +433,8 @@ impl IntegrationService {
                     app_name: app.name.clone(),
                     success: false,
                     message: None,
-                    error: Some(format!("Request failed: {}", e)),
+                    error: Some(e),
+                    delivery_disabled: false,
                 }
             }
         }
@@ -245,10 +449,16 @@ impl IntegrationService {
         conversation_id: Option<&str>,
         enabled_apps: &[App],
     ) -> Vec<IntegrationResult> {
-        // Filter to apps that trigger on transcript_processed
+        // Filter to set-up apps that trigger on transcript_processed and may read conversations
         let triggered_apps: Vec<&App> = enabled_apps
             .iter()
             .filter(|app| {
+                if app.integration_delivery_disabled
+                    || app.awaiting_setup()
+                    || !app.has_scope(AppScope::ReadConversations)
+                {
+                    return false;
+                }
                 if let Some(ref integration) = app.external_integration {
                     integration.triggers_on == TriggerEvent::TranscriptProcessed
                         && !integration.webhook_url.is_empty()
@@ -272,13 +482,25 @@ impl IntegrationService {
 
         for app in triggered_apps {
             let client = self.client.clone();
+            let guard = self.guard.clone();
             let uid = uid.to_string();
             let segments = segments.to_vec();
             let conversation_id = conversation_id.map(|s| s.to_string());
             let app = app.clone();
 
             let handle = tokio::spawn(async move {
-                Self::call_realtime_webhook(&client, &uid, &segments, conversation_id.as_deref(), &app).await
+                guard
+                    .deliver(&uid, &app, |guard| {
+                        Self::call_realtime_webhook(
+                            guard,
+                            &client,
+                            &uid,
+                            &segments,
+                            conversation_id.as_deref(),
+                            &app,
+                        )
+                    })
+                    .await
             });
 
             handles.push(handle);
@@ -299,6 +521,7 @@ impl IntegrationService {
 
     /// Call webhook for realtime transcript processing
     async fn call_realtime_webhook(
+        guard: DeliveryGuard,
         client: &Client,
         uid: &str,
         segments: &[Value],
@@ -314,6 +537,7 @@ impl IntegrationService {
                     success: false,
                     message: None,
                     error: Some("No integration config".to_string()),
+                    delivery_disabled: false,
                 };
             }
         };
@@ -331,13 +555,9 @@ impl IntegrationService {
             "conversation_id": conversation_id,
         });
 
-        match client
-            .post(&url)
-            .json(&payload)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await
-        {
+        // Realtime calls stay short so they don't back up the transcript stream
+        let timeout = guard.policy.timeout.min(Duration::from_secs(10));
+        match guard.post_with_retry(client, &url, &payload, &[], timeout).await {
             Ok(response) => {
                 let status = response.status();
 
@@ -348,6 +568,7 @@ impl IntegrationService {
                         success: false,
                         message: None,
                         error: Some(format!("HTTP {}", status)),
+                        delivery_disabled: false,
                     };
                 }
 
@@ -363,17 +584,185 @@ impl IntegrationService {
                     success: true,
                     message,
                     error: None,
+                    delivery_disabled: false,
+                }
+            }
+            Err(e) => IntegrationResult::failure(app, e),
+        }
+    }
+
+    /// Fire `action_item.completed` to the user's webhooks and to enabled apps
+    /// subscribed to the trigger. Returns results for app deliveries only;
+    /// user webhook failures are logged.
+    pub async fn trigger_action_item_completed(
+        &self,
+        uid: &str,
+        item: &ActionItemDB,
+        user_webhooks: &[UserWebhook],
+        enabled_apps: &[App],
+    ) -> Vec<IntegrationResult> {
+        let payload = action_item_completed_payload(uid, item);
+
+        let webhooks: Vec<&UserWebhook> = user_webhooks
+            .iter()
+            .filter(|w| w.subscribes_to(ACTION_ITEM_COMPLETED_EVENT))
+            .collect();
+        let triggered_apps: Vec<&App> = enabled_apps
+            .iter()
+            .filter(|app| {
+                !app.integration_delivery_disabled
+                    && !app.awaiting_setup()
+                    && app.has_scope(AppScope::WriteActionItems)
+                    && app.external_integration.as_ref().is_some_and(|integration| {
+                        integration.triggers_on == TriggerEvent::ActionItemCompleted
+                            && !integration.webhook_url.is_empty()
+                    })
+            })
+            .collect();
+
+        if webhooks.is_empty() && triggered_apps.is_empty() {
+            return vec![];
+        }
+
+        tracing::info!(
+            "Triggering {} webhooks and {} app integrations for completed action item {}",
+            webhooks.len(),
+            triggered_apps.len(),
+            item.id
+        );
+
+        for webhook in webhooks {
+            let client = self.client.clone();
+            let guard = self.guard.clone();
+            let webhook = webhook.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                Self::call_user_webhook(guard, &client, &webhook, &payload).await;
+            });
+        }
+
+        let mut handles = vec![];
+        for app in triggered_apps {
+            let client = self.client.clone();
+            let guard = self.guard.clone();
+            let uid = uid.to_string();
+            let payload = payload.clone();
+            let app = app.clone();
+
+            handles.push(tokio::spawn(async move {
+                guard
+                    .deliver(&uid, &app, |guard| {
+                        Self::call_event_webhook(guard, &client, &uid, &payload, &app)
+                    })
+                    .await
+            }));
+        }
+
+        let mut results = vec![];
+        for handle in handles {
+            match handle.await {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    tracing::error!("Task join error: {}", e);
                 }
             }
-            Err(e) => IntegrationResult {
-                app_id: app.id.clone(),
-                app_name: app.name.clone(),
-                success: false,
-                message: None,
-                error: Some(format!("Request failed: {}", e)),
-            },
         }
+
+        results
     }
+
+    /// POST an event payload to an app's webhook
+    async fn call_event_webhook(
+        guard: DeliveryGuard,
+        client: &Client,
+        uid: &str,
+        payload: &Value,
+        app: &App,
+    ) -> IntegrationResult {
+        let Some(integration) = &app.external_integration else {
+            return IntegrationResult::failure(app, "No integration config".to_string());
+        };
+
+        let mut url = integration.webhook_url.clone();
+        if url.contains('?') {
+            url.push_str(&format!("&uid={}", uid));
+        } else {
+            url.push_str(&format!("?uid={}", uid));
+        }
+
+        let timeout = guard.policy.timeout;
+        match guard.post_with_retry(client, &url, payload, &[], timeout).await {
+            Ok(response) if response.status().is_success() => {
+                let message = response
+                    .json::<WebhookResponse>()
+                    .await
+                    .ok()
+                    .and_then(|r| r.message);
+
+                IntegrationResult {
+                    app_id: app.id.clone(),
+                    app_name: app.name.clone(),
+                    success: true,
+                    message,
+                    error: None,
+                    delivery_disabled: false,
+                }
+            }
+            Ok(response) => IntegrationResult::failure(app, format!("HTTP {}", response.status())),
+            Err(e) => IntegrationResult::failure(app, e),
+        }
+    }
+
+    /// POST a signed event payload to a user-registered webhook
+    async fn call_user_webhook(guard: DeliveryGuard, client: &Client, webhook: &UserWebhook, payload: &Value) {
+        let timestamp = Utc::now().timestamp().to_string();
+        let body = payload.to_string();
+        let headers = [
+            ("X-Omi-Event", ACTION_ITEM_COMPLETED_EVENT.to_string()),
+            ("X-Omi-Timestamp", timestamp.clone()),
+            (
+                "X-Omi-Signature",
+                format!("sha256={}", sign_webhook_payload(&webhook.secret, &timestamp, &body)),
+            ),
+        ];
+
+        let timeout = guard.policy.timeout;
+        match guard
+            .post_with_retry(client, &webhook.url, payload, &headers, timeout)
+            .await
+        {
+            Ok(response) if response.status().is_success() => {
+                tracing::info!("Delivered {} to webhook {}", ACTION_ITEM_COMPLETED_EVENT, webhook.id);
+            }
+            Ok(response) => {
+                tracing::warn!("Webhook {} returned HTTP {}", webhook.id, response.status());
+            }
+            Err(e) => {
+                tracing::warn!("Webhook {} delivery failed: {}", webhook.id, e);
+            }
+        }
+    }
+}
+
+/// Event body for a completed action item, referencing its originating conversation
+pub fn action_item_completed_payload(uid: &str, item: &ActionItemDB) -> Value {
+    json!({
+        "event": ACTION_ITEM_COMPLETED_EVENT,
+        "uid": uid,
+        "occurred_at": item.completed_at.unwrap_or_else(Utc::now).to_rfc3339(),
+        "action_item": item,
+        "conversation": item.conversation_id.as_ref().map(|id| json!({"id": id})),
+    })
+}
+
+/// Hex HMAC-SHA256 of "{timestamp}.{body}" with the webhook's secret
+pub fn sign_webhook_payload(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
 }
 
 impl Default for IntegrationService {
@@ -382,6 +771,150 @@ impl Default for IntegrationService {
     }
 }
 
+/// Persist paused delivery for apps whose circuit tripped and let the user know
+pub async fn handle_disabled_deliveries(
+    firestore: &FirestoreService,
+    uid: &str,
+    results: &[IntegrationResult],
+) {
+    for result in results.iter().filter(|r| r.delivery_disabled) {
+        let reason = result.error.as_deref().unwrap_or("Repeated webhook failures");
+        if let Err(e) = firestore
+            .disable_integration_delivery(uid, &result.app_id, reason)
+            .await
+        {
+            tracing::error!(
+                "Failed to disable integration delivery for app {}: {}",
+                result.app_id,
+                e
+            );
+            continue;
+        }
+
+        let text = format!(
+            "{} kept failing to receive your conversations, so it has been paused. Re-enable the app to resume.",
+            result.app_name
+        );
+        if let Err(e) = firestore
+            .save_message(uid, &text, "ai", Some(&result.app_id), None, None)
+            .await
+        {
+            tracing::error!("Failed to notify user about paused app {}: {}", result.app_id, e);
+        }
+    }
+}
+
+/// Webhook replies the user should see, as (app ID, message): successful deliveries
+/// that returned a message, from apps the user allowed to send notifications
+fn app_messages<'a>(results: &'a [IntegrationResult], apps: &[App]) -> Vec<(&'a str, &'a str)> {
+    results
+        .iter()
+        .filter(|r| r.success)
+        .filter_map(|r| {
+            let message = r.message.as_deref().map(str::trim).filter(|m| !m.is_empty())?;
+            let permitted = apps
+                .iter()
+                .any(|app| app.id == r.app_id && app.has_scope(AppScope::Notifications));
+            if !permitted {
+                tracing::info!("Dropping message from app {} without the notifications scope", r.app_id);
+                return None;
+            }
+            Some((r.app_id.as_str(), message))
+        })
+        .collect()
+}
+
+/// Pass webhook replies on to the user as messages from the app
+pub async fn deliver_app_messages(
+    firestore: &FirestoreService,
+    uid: &str,
+    results: &[IntegrationResult],
+    apps: &[App],
+) {
+    for (app_id, message) in app_messages(results, apps) {
+        if let Err(e) = firestore.save_message(uid, message, "ai", Some(app_id), None, None).await {
+            tracing::error!("Failed to deliver message from app {}: {}", app_id, e);
+        }
+    }
+}
+
+/// Load the user's webhooks and enabled apps and fire `action_item.completed`
+pub async fn dispatch_action_item_completed(
+    firestore: &FirestoreService,
+    integrations: &IntegrationService,
+    uid: &str,
+    item: &ActionItemDB,
+) {
+    let user_webhooks = firestore.get_user_webhooks(uid).await.unwrap_or_else(|e| {
+        tracing::error!("Failed to load webhooks for user {}: {}", uid, e);
+        vec![]
+    });
+    let enabled_apps = firestore.get_enabled_apps_full(uid).await.unwrap_or_else(|e| {
+        tracing::error!("Failed to get enabled apps for integrations: {}", e);
+        vec![]
+    });
+
+    let results = integrations
+        .trigger_action_item_completed(uid, item, &user_webhooks, &enabled_apps)
+        .await;
+    handle_disabled_deliveries(firestore, uid, &results).await;
+    deliver_app_messages(firestore, uid, &results, &enabled_apps).await;
+}
+
+/// Load the user's enabled apps and fire conversation webhooks
+pub async fn dispatch_conversation_created(
+    firestore: &FirestoreService,
+    integrations: &IntegrationService,
+    uid: &str,
+    conversation: &Conversation,
+) {
+    let enabled_apps = match firestore.get_enabled_apps_full(uid).await {
+        Ok(apps) => apps,
+        Err(e) => {
+            tracing::error!("Failed to get enabled apps for integration triggers: {}", e);
+            return;
+        }
+    };
+
+    let results = integrations
+        .trigger_conversation_created(uid, conversation, &enabled_apps)
+        .await;
+    handle_disabled_deliveries(firestore, uid, &results).await;
+    deliver_app_messages(firestore, uid, &results, &enabled_apps).await;
+
+    if !results.is_empty() {
+        let successful = results.iter().filter(|r| r.success).count();
+        tracing::info!(
+            "Integration triggers completed: {} successful, {} failed",
+            successful,
+            results.len() - successful
+        );
+    }
+}
+
+/// Subscribe the integration dispatcher to conversation and action item events
+pub fn spawn_integration_dispatcher(
+    bus: &EventBus,
+    firestore: Arc<FirestoreService>,
+    integrations: Arc<IntegrationService>,
+) {
+    events::spawn_consumer(bus, "integrations", move |event| {
+        let firestore = firestore.clone();
+        let integrations = integrations.clone();
+        async move {
+            match event {
+                AppEvent::ConversationCreated { uid, conversation } => {
+                    dispatch_conversation_created(&firestore, &integrations, &uid, &conversation).await;
+                }
+                AppEvent::ActionItemCompleted { uid, item } => {
+                    dispatch_action_item_completed(&firestore, &integrations, &uid, &item).await;
+                }
+                AppEvent::FocusSessionCreated { .. } | AppEvent::AppResultCompleted { .. } => {}
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,4 +925,92 @@ mod tests {
         // Just verify it creates successfully
         assert!(true);
     }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = WebhookPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(20), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_circuit_trips_after_consecutive_failures() {
+        let guard = DeliveryGuard::new(WebhookPolicy {
+            failure_threshold: 3,
+            ..WebhookPolicy::default()
+        });
+        assert!(!guard.record_outcome("u1", "app", false));
+        assert!(!guard.record_outcome("u1", "app", false));
+        // A success resets the streak
+        assert!(!guard.record_outcome("u1", "app", true));
+        assert!(!guard.record_outcome("u1", "app", false));
+        assert!(!guard.record_outcome("u1", "app", false));
+        // Other users are tracked separately
+        assert!(!guard.record_outcome("u2", "app", false));
+        assert!(guard.record_outcome("u1", "app", false));
+    }
+
+    #[test]
+    fn test_action_item_completed_payload_references_conversation() {
+        let item: ActionItemDB = serde_json::from_value(json!({
+            "id": "item-1",
+            "description": "Send the deck",
+            "completed": true,
+            "created_at": "2026-01-05T10:00:00Z",
+            "completed_at": "2026-01-06T09:30:00Z",
+            "conversation_id": "conv-9"
+        }))
+        .unwrap();
+        let payload = action_item_completed_payload("u1", &item);
+        assert_eq!(payload["event"], "action_item.completed");
+        assert_eq!(payload["action_item"]["id"], "item-1");
+        assert_eq!(payload["conversation"]["id"], "conv-9");
+        assert_eq!(payload["occurred_at"], "2026-01-06T09:30:00+00:00");
+    }
+
+    #[test]
+    fn test_app_messages_need_the_notifications_scope() {
+        let app = |id: &str, scopes: Value| -> App {
+            serde_json::from_value(json!({
+                "id": id,
+                "name": id,
+                "description": "",
+                "image": "",
+                "category": "productivity",
+                "author": "omi",
+                "granted_scopes": scopes
+            }))
+            .unwrap()
+        };
+        let apps = [
+            app("granted", json!(["read:conversations", "notifications"])),
+            app("silent", json!(["read:conversations"])),
+        ];
+        let result = |app_id: &str, message: Option<&str>| IntegrationResult {
+            app_id: app_id.to_string(),
+            app_name: app_id.to_string(),
+            success: true,
+            message: message.map(str::to_string),
+            error: None,
+            delivery_disabled: false,
+        };
+        let results = [
+            result("granted", Some(" Your notes are ready ")),
+            result("silent", Some("Buy our premium plan")),
+            result("granted", Some("  ")),
+            result("unknown", Some("Hello")),
+        ];
+        assert_eq!(app_messages(&results, &apps), vec![("granted", "Your notes are ready")]);
+    }
+
+    #[test]
+    fn test_webhook_signature_covers_timestamp_and_body() {
+        let sig = sign_webhook_payload("whsec_test", "1700000000", "{}");
+        assert_eq!(sig.len(), 64);
+        assert_eq!(sig, sign_webhook_payload("whsec_test", "1700000000", "{}"));
+        assert_ne!(sig, sign_webhook_payload("whsec_test", "1700000001", "{}"));
+        assert_ne!(sig, sign_webhook_payload("whsec_other", "1700000000", "{}"));
+    }
 }
@@ -0,0 +1,185 @@
+// Language detection - Tag transcript segments and conversations with spoken languages
+// Uses whatlang (offline trigram models); codes are ISO 639-1 to match `Conversation.language`
+
+use std::collections::HashMap;
+
+use whatlang::Lang;
+
+use crate::models::TranscriptSegment;
+
+/// Shorter text is too ambiguous to classify on its own
+const MIN_DETECTION_CHARS: usize = 12;
+/// Minimum whatlang confidence to accept a detection
+const MIN_CONFIDENCE: f64 = 0.5;
+/// Share of classified text a language needs to be listed for the conversation
+const MIN_LANGUAGE_SHARE: f64 = 0.15;
+
+/// Detect the language of a piece of text, if it is long and unambiguous enough
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let text = text.trim();
+    if text.chars().count() < MIN_DETECTION_CHARS {
+        return None;
+    }
+    let info = whatlang::detect(text)?;
+    if info.confidence() < MIN_CONFIDENCE {
+        return None;
+    }
+    Some(iso_639_1(info.lang()))
+}
+
+/// Fill in `language` on segments that don't have one yet. Segments too ambiguous to
+/// classify alone fall back to the language of the transcript as a whole.
+pub fn tag_segment_languages(segments: &mut [TranscriptSegment]) {
+    let full_text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let dominant = detect_language(&full_text);
+
+    for segment in segments.iter_mut().filter(|s| s.language.is_none()) {
+        let long_enough = segment.text.trim().chars().count() >= MIN_DETECTION_CHARS;
+        segment.language = detect_language(&segment.text)
+            .or(dominant.filter(|_| long_enough))
+            .map(|l| l.to_string());
+    }
+}
+
+/// Languages spoken in a conversation, most-used first (by characters of tagged text)
+pub fn conversation_languages(segments: &[TranscriptSegment]) -> Vec<String> {
+    let mut chars_by_language: HashMap<&str, usize> = HashMap::new();
+    for segment in segments {
+        if let Some(language) = segment.language.as_deref() {
+            *chars_by_language.entry(language).or_default() += segment.text.chars().count();
+        }
+    }
+
+    let total: usize = chars_by_language.values().sum();
+    let mut languages: Vec<(&str, usize)> = chars_by_language
+        .into_iter()
+        .filter(|(_, chars)| *chars as f64 >= total as f64 * MIN_LANGUAGE_SHARE)
+        .collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    languages.into_iter().map(|(l, _)| l.to_string()).collect()
+}
+
+/// Language to write summaries in: the requested one when it was actually spoken,
+/// otherwise the dominant detected language
+pub fn summary_language(requested: &str, detected: &[String]) -> String {
+    let requested = requested.trim();
+    let requested_spoken = detected.iter().any(|l| l.eq_ignore_ascii_case(requested));
+    match detected.first() {
+        Some(dominant) if !requested_spoken => dominant.clone(),
+        _ if requested.is_empty() || requested == "multi" => "en".to_string(),
+        _ => requested.to_string(),
+    }
+}
+
+fn iso_639_1(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Epo => "eo",
+        Lang::Eng => "en",
+        Lang::Rus => "ru",
+        Lang::Cmn => "zh",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ben => "bn",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ukr => "uk",
+        Lang::Kat => "ka",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Jpn => "ja",
+        Lang::Heb => "he",
+        Lang::Yid => "yi",
+        Lang::Pol => "pl",
+        Lang::Amh => "am",
+        Lang::Jav => "jv",
+        Lang::Kor => "ko",
+        Lang::Nob => "nb",
+        Lang::Dan => "da",
+        Lang::Swe => "sv",
+        Lang::Fin => "fi",
+        Lang::Tur => "tr",
+        Lang::Nld => "nl",
+        Lang::Hun => "hu",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Bul => "bg",
+        Lang::Bel => "be",
+        Lang::Mar => "mr",
+        Lang::Kan => "kn",
+        Lang::Ron => "ro",
+        Lang::Slv => "sl",
+        Lang::Hrv => "hr",
+        Lang::Srp => "sr",
+        Lang::Mkd => "mk",
+        Lang::Lit => "lt",
+        Lang::Lav => "lv",
+        Lang::Est => "et",
+        Lang::Tam => "ta",
+        Lang::Vie => "vi",
+        Lang::Urd => "ur",
+        Lang::Tha => "th",
+        Lang::Guj => "gu",
+        Lang::Uzb => "uz",
+        Lang::Pan => "pa",
+        Lang::Aze => "az",
+        Lang::Ind => "id",
+        Lang::Tel => "te",
+        Lang::Pes => "fa",
+        Lang::Mal => "ml",
+        Lang::Ori => "or",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Sin => "si",
+        Lang::Khm => "km",
+        Lang::Tuk => "tk",
+        Lang::Aka => "ak",
+        Lang::Zul => "zu",
+        Lang::Sna => "sn",
+        Lang::Afr => "af",
+        Lang::Lat => "la",
+        Lang::Slk => "sk",
+        Lang::Cat => "ca",
+        Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str) -> TranscriptSegment {
+        serde_json::from_value(serde_json::json!({"text": text})).unwrap()
+    }
+
+    #[test]
+    fn test_mixed_conversation_languages() {
+        let mut segments = vec![
+            segment("We should ship the new onboarding flow before the end of the month."),
+            segment("I agree, and we also need to update the pricing page with the new plans."),
+            segment("Perfecto, mañana le envío el resumen al equipo de ventas en Madrid."),
+            segment("ok"),
+        ];
+        tag_segment_languages(&mut segments);
+
+        assert_eq!(segments[0].language.as_deref(), Some("en"));
+        assert_eq!(segments[2].language.as_deref(), Some("es"));
+        assert_eq!(segments[3].language, None);
+        assert_eq!(conversation_languages(&segments), vec!["en", "es"]);
+    }
+
+    #[test]
+    fn test_summary_language_prefers_spoken_request() {
+        let detected = vec!["en".to_string(), "es".to_string()];
+        assert_eq!(summary_language("es", &detected), "es");
+        assert_eq!(summary_language("de", &detected), "en");
+        assert_eq!(summary_language("multi", &detected), "en");
+        assert_eq!(summary_language("fr", &[]), "fr");
+        assert_eq!(summary_language("", &[]), "en");
+    }
+}
@@ -0,0 +1,116 @@
+// LLM keys - Which API key LLM calls on a user's data run with
+// A user's own key (BYOK) when configured, otherwise the server key. A saved key
+// that can't be read is an error: it never silently falls back to the server key.
+
+use axum::http::StatusCode;
+
+use crate::llm::{LlmClient, TaskModels};
+use crate::models::LlmCredentials;
+use crate::services::firestore::FirestoreError;
+use crate::services::FirestoreService;
+
+/// A user's saved API key couldn't be loaded or decrypted
+#[derive(Debug)]
+pub struct UserKeyError(pub FirestoreError);
+
+impl std::fmt::Display for UserKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Your LLM API key couldn't be read; save it again in settings")
+    }
+}
+
+impl std::error::Error for UserKeyError {}
+
+impl From<UserKeyError> for (StatusCode, String) {
+    fn from(e: UserKeyError) -> Self {
+        (StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+    }
+}
+
+impl From<UserKeyError> for StatusCode {
+    fn from(_: UserKeyError) -> Self {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+/// The user's own API key; None when they haven't saved one
+pub async fn user_api_key(firestore: &FirestoreService, uid: &str) -> Result<Option<String>, UserKeyError> {
+    let loaded = firestore.get_llm_credentials(uid).await;
+    if let Err(e) = &loaded {
+        tracing::error!("Failed to load LLM credentials for user {}: {}", uid, e);
+    }
+    choose_key(loaded, None)
+}
+
+/// Key for LLM calls on a user's data; None when neither key is available
+pub async fn api_key_for_user(
+    firestore: &FirestoreService,
+    uid: &str,
+    server_key: Option<&str>,
+) -> Result<Option<String>, UserKeyError> {
+    let loaded = firestore.get_llm_credentials(uid).await;
+    if let Err(e) = &loaded {
+        tracing::error!("Failed to load LLM credentials for user {}: {}", uid, e);
+    }
+    choose_key(loaded, server_key)
+}
+
+/// Client for a user in a job that otherwise shares one server-key client:
+/// a dedicated client when the user brought their own key
+pub async fn user_client(
+    firestore: &FirestoreService,
+    uid: &str,
+    models: &TaskModels,
+) -> Result<Option<LlmClient>, UserKeyError> {
+    let api_key = user_api_key(firestore, uid).await?;
+    Ok(api_key.map(|key| LlmClient::new(key).with_task_models(models.clone())))
+}
+
+/// The user's key when saved, the server key when not, and an error when a saved
+/// key couldn't be loaded
+fn choose_key(
+    loaded: Result<Option<LlmCredentials>, FirestoreError>,
+    server_key: Option<&str>,
+) -> Result<Option<String>, UserKeyError> {
+    match loaded.map_err(UserKeyError)? {
+        Some(credentials) => Ok(Some(credentials.api_key)),
+        None => Ok(server_key.map(str::to_string)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LlmProvider;
+    use chrono::Utc;
+
+    fn credentials(api_key: &str) -> LlmCredentials {
+        LlmCredentials {
+            provider: LlmProvider::Gemini,
+            api_key: api_key.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_user_key_wins_over_server_key() {
+        let key = choose_key(Ok(Some(credentials("user-key"))), Some("server-key")).unwrap();
+        assert_eq!(key.as_deref(), Some("user-key"));
+    }
+
+    #[test]
+    fn test_server_key_used_when_no_user_key() {
+        let key = choose_key(Ok(None), Some("server-key")).unwrap();
+        assert_eq!(key.as_deref(), Some("server-key"));
+        assert_eq!(choose_key(Ok(None), None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unreadable_user_key_does_not_fall_back() {
+        let loaded = Err(FirestoreError::from("Failed to decrypt integration token"));
+        let err = choose_key(loaded, Some("server-key")).unwrap_err();
+        let (status, message) = <(StatusCode, String)>::from(err);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!message.contains("decrypt"));
+    }
+}
@@ -0,0 +1,156 @@
+// LLM quality - Daily reports of thumbs ratings on chat messages and conversation summaries
+// Broken down by model, prompt version and route so prompt changes can be judged on data.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::models::{LlmProvenance, QualityBucket, QualityRating, QualityRatingKind, QualityReport, approval_rate};
+use crate::services::FirestoreService;
+
+/// How often to check whether yesterday's report exists
+const QUALITY_REPORT_CHECK_INTERVAL_MINUTES: u64 = 60;
+/// Upper bound on ratings read into one day's report
+const MAX_RATINGS_PER_REPORT: usize = 20_000;
+/// Bucket for ratings that don't record a dimension
+const UNKNOWN: &str = "unknown";
+
+fn buckets(ratings: &[QualityRating], key: impl Fn(&LlmProvenance) -> Option<&String>) -> Vec<QualityBucket> {
+    let mut counts: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    for rating in ratings {
+        let entry = counts
+            .entry(key(&rating.provenance).map(|k| k.as_str()).unwrap_or(UNKNOWN))
+            .or_default();
+        if rating.rating > 0 {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+    }
+    let mut buckets: Vec<QualityBucket> =
+        counts.into_iter().map(|(key, (up, down))| QualityBucket::new(key, up, down)).collect();
+    // Most rated first; ties stay in key order
+    buckets.sort_by_key(|b| std::cmp::Reverse(b.thumbs_up + b.thumbs_down));
+    buckets
+}
+
+/// Aggregate one day's ratings
+pub fn build_report(date: &str, ratings: &[QualityRating], now: DateTime<Utc>) -> QualityReport {
+    let thumbs_up = ratings.iter().filter(|r| r.rating > 0).count() as u32;
+    let thumbs_down = ratings.len() as u32 - thumbs_up;
+    QualityReport {
+        date: date.to_string(),
+        thumbs_up,
+        thumbs_down,
+        approval_rate: approval_rate(thumbs_up, thumbs_down),
+        by_model: buckets(ratings, |p| p.model.as_ref()),
+        by_prompt_version: buckets(ratings, |p| p.prompt_version.as_ref()),
+        by_route: buckets(ratings, |p| p.route.as_ref()),
+        generated_at: now,
+    }
+}
+
+/// Build and store the report for a UTC day (YYYY-MM-DD)
+pub async fn generate_report(
+    firestore: &FirestoreService,
+    date: &str,
+) -> Result<QualityReport, Box<dyn std::error::Error + Send + Sync>> {
+    let ratings = firestore.get_quality_ratings_for_date(date, MAX_RATINGS_PER_REPORT).await?;
+    let report = build_report(date, &ratings, Utc::now());
+    firestore.save_quality_report(&report).await?;
+    tracing::info!(
+        "LLM quality report for {}: {} up, {} down",
+        date,
+        report.thumbs_up,
+        report.thumbs_down
+    );
+    Ok(report)
+}
+
+/// Store (or with None, remove) a rating for reports; failures are logged, not returned,
+/// so they never fail the rating itself
+pub async fn record_rating(
+    firestore: &FirestoreService,
+    uid: &str,
+    kind: QualityRatingKind,
+    item_id: &str,
+    rating: Option<i32>,
+    provenance: LlmProvenance,
+) {
+    let rating = rating.map(|rating| QualityRating { kind, rating, provenance, rated_at: Utc::now() });
+    if let Err(e) = firestore.set_quality_rating(uid, kind, item_id, rating.as_ref()).await {
+        tracing::warn!("Failed to record quality rating for {} {}: {}", kind.as_str(), item_id, e);
+    }
+}
+
+/// Spawn the job that writes yesterday's report once the day is over
+pub fn spawn_quality_report_job(firestore: Arc<FirestoreService>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            QUALITY_REPORT_CHECK_INTERVAL_MINUTES * 60,
+        ));
+        loop {
+            interval.tick().await;
+            let date = (Utc::now() - Duration::days(1)).format("%Y-%m-%d").to_string();
+            match firestore.get_quality_report(&date).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    if let Err(e) = generate_report(&firestore, &date).await {
+                        tracing::error!("LLM quality report for {} failed: {}", date, e);
+                    }
+                }
+                Err(e) => tracing::error!("LLM quality: failed to read report for {}: {}", date, e),
+            }
+        }
+    });
+
+    tracing::info!(
+        "LLM quality reports scheduled every {} minutes",
+        QUALITY_REPORT_CHECK_INTERVAL_MINUTES
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report() {
+        let rating = |rating: i32, model: Option<&str>, route: &str| QualityRating {
+            kind: QualityRatingKind::Message,
+            rating,
+            provenance: LlmProvenance {
+                model: model.map(str::to_string),
+                prompt_version: Some("a1b2c3".to_string()),
+                route: Some(route.to_string()),
+            },
+            rated_at: Utc::now(),
+        };
+        let ratings = [
+            rating(1, Some("gemini-2.5-flash"), "chat"),
+            rating(-1, Some("gemini-2.5-flash"), "chat"),
+            rating(1, Some("gemini-2.5-flash"), "conversation_processing"),
+            rating(1, Some("gemini-2.5-pro"), "chat"),
+            rating(-1, None, "chat"),
+        ];
+
+        let report = build_report("2026-10-15", &ratings, Utc::now());
+        assert_eq!((report.thumbs_up, report.thumbs_down), (3, 2));
+        assert_eq!(report.approval_rate, 0.6);
+        assert_eq!(
+            report.by_model,
+            vec![
+                QualityBucket::new("gemini-2.5-flash", 2, 1),
+                QualityBucket::new("gemini-2.5-pro", 1, 0),
+                QualityBucket::new("unknown", 0, 1),
+            ]
+        );
+        assert_eq!(report.by_model[0].approval_rate, 0.667);
+        assert_eq!(report.by_prompt_version, vec![QualityBucket::new("a1b2c3", 3, 2)]);
+        assert_eq!(report.by_route[0], QualityBucket::new("chat", 2, 2));
+
+        let empty = build_report("2026-10-15", &[], Utc::now());
+        assert_eq!(empty.approval_rate, 0.0);
+        assert!(empty.by_route.is_empty());
+    }
+}
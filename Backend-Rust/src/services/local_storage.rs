@@ -0,0 +1,1309 @@
+// Local storage - The Storage trait over a single SQLite file (STORAGE=local)
+// Each document is stored as the JSON of its model in one `documents` table keyed by
+// (uid, collection, id); filters and sorting run in Rust, which is fine for the
+// single-user deployments this mode is for. Settings live in one document per user.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::models::{
+    ActionItem, ActionItemDB, AutoDiscardLevel, Conversation, ConversationSource, ConversationTemplate, Event,
+    FocusSessionDB, Folder, FolderRule, Memory, MemoryCategory, MemoryDB, MessageDB, NotificationSettings,
+    ProcessingPromptSettings,
+};
+use crate::services::firestore::{
+    document_id_from_seed, FirestoreError, Precondition, ACTION_ITEMS_SUBCOLLECTION, CONVERSATIONS_SUBCOLLECTION,
+    MEMORIES_SUBCOLLECTION, MESSAGES_SUBCOLLECTION, STAGED_TASKS_SUBCOLLECTION, USERS_COLLECTION,
+};
+use crate::services::storage::{Storage, StorageFuture};
+use crate::services::FirestoreService;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS documents (
+    uid TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (uid, collection, id)
+);
+CREATE INDEX IF NOT EXISTS documents_by_created_at ON documents (uid, collection, created_at);
+";
+
+/// Storage in a local SQLite file
+#[derive(Clone)]
+pub struct LocalStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+/// A stored document: its JSON and a version bumped on every write
+struct Stored {
+    data: Value,
+    version: i64,
+}
+
+fn storage_error(e: rusqlite::Error) -> FirestoreError {
+    FirestoreError::Internal(format!("Local storage error: {}", e))
+}
+
+/// Sortable timestamp; fixed precision so text order is time order
+fn sort_key(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn now_value() -> Value {
+    json!(Utc::now())
+}
+
+fn parse_bound(value: Option<&str>) -> Result<Option<DateTime<Utc>>, FirestoreError> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| FirestoreError::InvalidArgument(format!("Invalid timestamp {}: {}", v, e)))
+        })
+        .transpose()
+}
+
+fn timestamp(data: &Value, field: &str) -> Option<DateTime<Utc>> {
+    serde_json::from_value(data.get(field)?.clone()).ok()
+}
+
+fn decode<T: DeserializeOwned>(data: Value) -> Result<T, FirestoreError> {
+    Ok(serde_json::from_value(data)?)
+}
+
+fn load(conn: &Connection, uid: &str, collection: &str, id: &str) -> Result<Option<Stored>, FirestoreError> {
+    let row = conn
+        .query_row(
+            "SELECT data, version FROM documents WHERE uid = ?1 AND collection = ?2 AND id = ?3",
+            params![uid, collection, id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .optional()
+        .map_err(storage_error)?;
+    row.map(|(data, version)| Ok(Stored { data: serde_json::from_str(&data)?, version }))
+        .transpose()
+}
+
+/// Every document of a collection, newest first
+fn load_all(conn: &Connection, uid: &str, collection: &str) -> Result<Vec<Value>, FirestoreError> {
+    let mut statement = conn
+        .prepare("SELECT data FROM documents WHERE uid = ?1 AND collection = ?2 ORDER BY created_at DESC")
+        .map_err(storage_error)?;
+    let rows = statement
+        .query_map(params![uid, collection], |row| row.get::<_, String>(0))
+        .map_err(storage_error)?;
+    let mut documents = Vec::new();
+    for row in rows {
+        documents.push(serde_json::from_str(&row.map_err(storage_error)?)?);
+    }
+    Ok(documents)
+}
+
+/// Create or replace a document, returning its new version
+fn store(
+    conn: &Connection,
+    uid: &str,
+    collection: &str,
+    id: &str,
+    created_at: &DateTime<Utc>,
+    data: &Value,
+) -> Result<i64, FirestoreError> {
+    conn.query_row(
+        "INSERT INTO documents (uid, collection, id, created_at, version, data) VALUES (?1, ?2, ?3, ?4, 1, ?5)
+         ON CONFLICT (uid, collection, id) DO UPDATE SET
+             created_at = excluded.created_at, version = documents.version + 1, data = excluded.data
+         RETURNING version",
+        params![uid, collection, id, sort_key(created_at), data.to_string()],
+        |row| row.get(0),
+    )
+    .map_err(storage_error)
+}
+
+fn remove(conn: &Connection, uid: &str, collection: &str, id: &str) -> Result<(), FirestoreError> {
+    conn.execute(
+        "DELETE FROM documents WHERE uid = ?1 AND collection = ?2 AND id = ?3",
+        params![uid, collection, id],
+    )
+    .map_err(storage_error)?;
+    Ok(())
+}
+
+/// Change fields of an existing document; NotFound when it doesn't exist
+fn update(
+    conn: &Connection,
+    uid: &str,
+    collection: &str,
+    id: &str,
+    change: impl FnOnce(&mut Map<String, Value>),
+) -> Result<Value, FirestoreError> {
+    let stored = load(conn, uid, collection, id)?
+        .ok_or_else(|| FirestoreError::NotFound(format!("No {} document {}", collection, id)))?;
+    let mut data = stored.data;
+    let fields = data.as_object_mut().ok_or("Stored document is not an object")?;
+    change(fields);
+    let created_at = timestamp(&data, "created_at").unwrap_or_else(Utc::now);
+    store(conn, uid, collection, id, &created_at, &data)?;
+    Ok(data)
+}
+
+/// The user's settings document (empty when nothing has been saved)
+fn user_settings(conn: &Connection, uid: &str) -> Result<Value, FirestoreError> {
+    Ok(load(conn, uid, USERS_COLLECTION, uid)?.map(|s| s.data).unwrap_or_else(|| json!({})))
+}
+
+fn update_user_settings(conn: &Connection, uid: &str, fields: Map<String, Value>) -> Result<(), FirestoreError> {
+    let mut settings = user_settings(conn, uid)?;
+    let current = settings.as_object_mut().ok_or("Stored settings are not an object")?;
+    current.extend(fields);
+    store(conn, uid, USERS_COLLECTION, uid, &DateTime::<Utc>::UNIX_EPOCH, &settings)?;
+    Ok(())
+}
+
+/// Set a field of the conversation's `structured` map
+fn set_structured(fields: &mut Map<String, Value>, key: &str, value: Value) {
+    if let Some(structured) = fields.get_mut("structured").and_then(Value::as_object_mut) {
+        structured.insert(key.to_string(), value);
+    }
+    fields.insert("updated_at".to_string(), now_value());
+}
+
+/// Open staged tasks, best ranked (lowest relevance_score) first. Unlike the Firestore
+/// query, unscored tasks are kept (last), since nothing ranks them locally.
+fn staged_tasks(conn: &Connection, uid: &str) -> Result<Vec<ActionItemDB>, FirestoreError> {
+    let mut tasks = Vec::new();
+    for data in load_all(conn, uid, STAGED_TASKS_SUBCOLLECTION)? {
+        let task: ActionItemDB = decode(data)?;
+        if !task.completed && task.deleted != Some(true) {
+            tasks.push(task);
+        }
+    }
+    tasks.sort_by_key(|t| (t.relevance_score.is_none(), t.relevance_score));
+    Ok(tasks)
+}
+
+fn notification_settings(settings: &Value) -> NotificationSettings {
+    NotificationSettings {
+        enabled: settings["notifications_enabled"].as_bool().unwrap_or(true),
+        frequency: settings["notification_frequency"].as_i64().unwrap_or(3) as i32,
+    }
+}
+
+/// A conversation's source as stored, for enriching memories and action items
+fn conversation_source(conn: &Connection, uid: &str, conversation_id: &str) -> Option<(ConversationSource, Value)> {
+    let stored = load(conn, uid, CONVERSATIONS_SUBCOLLECTION, conversation_id).ok()??;
+    let source = serde_json::from_value(stored.data.get("source")?.clone()).ok()?;
+    Some((source, stored.data["input_device_name"].clone()))
+}
+
+/// Memory with the fields serde skips on read (source, device name) restored and
+/// enriched from its conversation, as FirestoreService does
+fn memory_from_value(conn: &Connection, uid: &str, data: Value) -> Result<MemoryDB, FirestoreError> {
+    let stored_source = data["source"].as_str().map(str::to_string);
+    let mut memory: MemoryDB = decode(data)?;
+    memory.uid = uid.to_string();
+    memory.source = stored_source;
+    if let Some((source, device)) = memory
+        .conversation_id
+        .as_deref()
+        .and_then(|id| conversation_source(conn, uid, id))
+    {
+        memory.source = Some(format!("{:?}", source).to_lowercase());
+        memory.input_device_name = device.as_str().map(str::to_string);
+    }
+    Ok(memory)
+}
+
+/// Action item, with its source derived from its conversation when it has none
+fn action_item_from_value(conn: &Connection, uid: &str, data: Value) -> Result<ActionItemDB, FirestoreError> {
+    let mut item: ActionItemDB = decode(data)?;
+    if item.source.is_none() {
+        if let Some((source, _)) = item.conversation_id.as_deref().and_then(|id| conversation_source(conn, uid, id)) {
+            item.source = Some(format!("transcription:{:?}", source).to_lowercase());
+        }
+    }
+    Ok(item)
+}
+
+impl LocalStorage {
+    /// Open (creating if needed) the database file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FirestoreError> {
+        let conn = Connection::open(path).map_err(storage_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// A throwaway in-memory database
+    pub fn in_memory() -> Result<Self, FirestoreError> {
+        Self::with_connection(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, FirestoreError> {
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Run `f` on the connection off the async runtime
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, FirestoreError> + Send + 'static,
+    ) -> Result<T, FirestoreError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+            f(&conn)
+        })
+        .await
+        .map_err(|e| FirestoreError::Internal(format!("Local storage task failed: {}", e)))?
+    }
+}
+
+fn owned(value: Option<&str>) -> Option<String> {
+    value.map(str::to_string)
+}
+
+impl Storage for LocalStorage {
+    fn get_conversations<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        include_discarded: bool,
+        statuses: &'a [String],
+        starred: Option<bool>,
+        folder_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> StorageFuture<'a, Vec<Conversation>> {
+        let (uid, statuses, folder_id) = (uid.to_string(), statuses.to_vec(), owned(folder_id));
+        Box::pin(async move {
+            let (start, end) = (parse_bound(start_date)?, parse_bound(end_date)?);
+            self.run(move |conn| {
+                let mut conversations = Vec::new();
+                for data in load_all(conn, &uid, CONVERSATIONS_SUBCOLLECTION)? {
+                    let conversation: Conversation = decode(data)?;
+                    let status = serde_json::to_value(&conversation.status)?;
+                    let keep = (include_discarded || !conversation.discarded)
+                        && (statuses.is_empty() || statuses.iter().any(|s| status == s.as_str()))
+                        && starred.is_none_or(|starred| conversation.starred == starred)
+                        && folder_id.as_ref().is_none_or(|f| conversation.folder_id.as_ref() == Some(f))
+                        && start.is_none_or(|start| conversation.created_at >= start)
+                        && end.is_none_or(|end| conversation.created_at < end);
+                    if keep {
+                        conversations.push(conversation);
+                    }
+                }
+                Ok(conversations.into_iter().skip(offset).take(limit).collect())
+            })
+            .await
+        })
+    }
+
+    fn get_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, Option<Conversation>> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| {
+            load(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id)?
+                .map(|stored| decode(stored.data))
+                .transpose()
+        }))
+    }
+
+    fn save_conversation<'a>(&'a self, uid: &'a str, conversation: &'a Conversation) -> StorageFuture<'a, ()> {
+        let (uid, conversation) = (uid.to_string(), conversation.clone());
+        Box::pin(self.run(move |conn| {
+            let data = serde_json::to_value(&conversation)?;
+            store(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &conversation.id, &conversation.created_at, &data)?;
+            Ok(())
+        }))
+    }
+
+    fn delete_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| remove(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id)))
+    }
+
+    fn update_conversation_title<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        title: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id, title) = (uid.to_string(), conversation_id.to_string(), title.to_string());
+        Box::pin(self.run(move |conn| {
+            update(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id, |fields| {
+                if let Some(structured) = fields.get_mut("structured").and_then(Value::as_object_mut) {
+                    structured.insert("title".to_string(), json!(title));
+                }
+                // Rebuilt from `structured` by chat context until the next full write
+                fields.remove("context_line");
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn set_conversation_starred<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        starred: bool,
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| {
+            update(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id, |fields| {
+                fields.insert("starred".to_string(), json!(starred));
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn get_memories_filtered<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        category: Option<&'a str>,
+        tags: Option<&'a [String]>,
+        include_dismissed: bool,
+    ) -> StorageFuture<'a, Vec<MemoryDB>> {
+        let (uid, category, tags) = (uid.to_string(), owned(category), tags.map(<[String]>::to_vec));
+        Box::pin(self.run(move |conn| {
+            let mut memories = Vec::new();
+            for data in load_all(conn, &uid, MEMORIES_SUBCOLLECTION)? {
+                if category.as_deref().is_some_and(|c| data["category"].as_str() != Some(c)) {
+                    continue;
+                }
+                let memory = memory_from_value(conn, &uid, data)?;
+                // Rejected memories are never listed
+                let keep = memory.user_review != Some(false)
+                    && (include_dismissed || !memory.is_dismissed)
+                    && tags.as_ref().is_none_or(|tags| tags.iter().all(|t| memory.tags.contains(t)));
+                if keep {
+                    memories.push(memory);
+                }
+            }
+            // Same order as Firestore: scoring, then newest first
+            memories.sort_by(|a, b| b.scoring.cmp(&a.scoring).then_with(|| b.created_at.cmp(&a.created_at)));
+            Ok(memories.into_iter().skip(offset).take(limit).collect())
+        }))
+    }
+
+    fn create_memory<'a>(
+        &'a self,
+        uid: &'a str,
+        content: &'a str,
+        visibility: &'a str,
+        category: Option<MemoryCategory>,
+        confidence: Option<f64>,
+        source_app: Option<&'a str>,
+        context_summary: Option<&'a str>,
+        tags: &'a [String],
+        reasoning: Option<&'a str>,
+        current_activity: Option<&'a str>,
+        source: Option<&'a str>,
+        window_title: Option<&'a str>,
+    ) -> StorageFuture<'a, String> {
+        let memory_id = document_id_from_seed(content);
+        let now = Utc::now();
+        let is_manual = category.is_none() || matches!(category, Some(MemoryCategory::Manual));
+        let category = category.unwrap_or(MemoryCategory::Manual);
+        let data = json!({
+            "id": memory_id,
+            "uid": uid,
+            "content": content,
+            "category": category,
+            "created_at": now,
+            "updated_at": now,
+            "reviewed": is_manual,
+            "user_review": is_manual,
+            "visibility": visibility,
+            "manually_added": is_manual,
+            "scoring": MemoryDB::calculate_scoring(&category, &now, is_manual),
+            "is_read": false,
+            "is_dismissed": false,
+            "tags": tags,
+            "confidence": confidence,
+            "source_app": source_app,
+            "context_summary": context_summary,
+            "reasoning": reasoning,
+            "current_activity": current_activity,
+            "source": source,
+            "window_title": window_title,
+        });
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            store(conn, &uid, MEMORIES_SUBCOLLECTION, &memory_id, &now, &data)?;
+            Ok(memory_id)
+        }))
+    }
+
+    fn delete_memory<'a>(&'a self, uid: &'a str, memory_id: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), memory_id.to_string());
+        Box::pin(self.run(move |conn| remove(conn, &uid, MEMORIES_SUBCOLLECTION, &id)))
+    }
+
+    fn update_memory_content<'a>(&'a self, uid: &'a str, memory_id: &'a str, content: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, id, content) = (uid.to_string(), memory_id.to_string(), content.to_string());
+        Box::pin(self.run(move |conn| {
+            update(conn, &uid, MEMORIES_SUBCOLLECTION, &id, |fields| {
+                fields.insert("content".to_string(), json!(content));
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn update_memory_visibility<'a>(
+        &'a self,
+        uid: &'a str,
+        memory_id: &'a str,
+        visibility: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id, visibility) = (uid.to_string(), memory_id.to_string(), visibility.to_string());
+        Box::pin(self.run(move |conn| {
+            update(conn, &uid, MEMORIES_SUBCOLLECTION, &id, |fields| {
+                fields.insert("visibility".to_string(), json!(visibility));
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn get_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        completed_filter: Option<bool>,
+        conversation_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+        due_start_date: Option<&'a str>,
+        due_end_date: Option<&'a str>,
+        sort_by: Option<&'a str>,
+        include_deleted: Option<bool>,
+        include_snoozed: bool,
+    ) -> StorageFuture<'a, Vec<ActionItemDB>> {
+        let (uid, conversation_id, sort_by) = (uid.to_string(), owned(conversation_id), owned(sort_by));
+        Box::pin(async move {
+            let (start, end) = (parse_bound(start_date)?, parse_bound(end_date)?);
+            let (due_start, due_end) = (parse_bound(due_start_date)?, parse_bound(due_end_date)?);
+            self.run(move |conn| {
+                let now = Utc::now();
+                let mut items = Vec::new();
+                for data in load_all(conn, &uid, ACTION_ITEMS_SUBCOLLECTION)? {
+                    let item = action_item_from_value(conn, &uid, data)?;
+                    // Like a Firestore range filter or order, a missing due_at/priority excludes the item
+                    let due_in_range = match item.due_at {
+                        Some(due) => due_start.is_none_or(|s| due >= s) && due_end.is_none_or(|e| due <= e),
+                        None => due_start.is_none() && due_end.is_none() && sort_by.as_deref() != Some("due_at"),
+                    };
+                    let keep = completed_filter.is_none_or(|c| item.completed == c)
+                        && conversation_id.as_ref().is_none_or(|id| item.conversation_id.as_ref() == Some(id))
+                        && start.is_none_or(|s| item.created_at >= s)
+                        && end.is_none_or(|e| item.created_at <= e)
+                        && due_in_range
+                        && (sort_by.as_deref() != Some("priority") || item.priority.is_some())
+                        && if include_deleted == Some(true) {
+                            item.deleted == Some(true)
+                        } else {
+                            item.deleted != Some(true)
+                        }
+                        // Snoozed items stay hidden until their resurface time
+                        && (include_snoozed || item.snoozed_until.is_none_or(|until| until <= now));
+                    if keep {
+                        items.push(item);
+                    }
+                }
+                if sort_by.as_deref() == Some("priority") {
+                    items.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| b.created_at.cmp(&a.created_at)));
+                }
+                let mut items: Vec<ActionItemDB> = items.into_iter().skip(offset).take(limit).collect();
+                // Same final order as FirestoreService: due items first by due date, then newest
+                items.sort_by(|a, b| match (&a.due_at, &b.due_at) {
+                    (Some(due_a), Some(due_b)) => due_a.cmp(due_b).then_with(|| b.created_at.cmp(&a.created_at)),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => b.created_at.cmp(&a.created_at),
+                });
+                Ok(items)
+            })
+            .await
+        })
+    }
+
+    fn get_action_item_by_id<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, Option<ActionItemDB>> {
+        Box::pin(async move { Ok(self.get_action_item_versioned(uid, item_id).await?.map(|(item, _)| item)) })
+    }
+
+    fn get_action_item_versioned<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+    ) -> StorageFuture<'a, Option<(ActionItemDB, String)>> {
+        let (uid, id) = (uid.to_string(), item_id.to_string());
+        Box::pin(self.run(move |conn| {
+            let Some(stored) = load(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &id)? else {
+                return Ok(None);
+            };
+            let item = action_item_from_value(conn, &uid, stored.data)?;
+            Ok(Some((item, stored.version.to_string())))
+        }))
+    }
+
+    fn create_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+        from_staged: Option<bool>,
+        recurrence_rule: Option<&'a str>,
+        recurrence_parent_id: Option<&'a str>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        let item_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let data = json!({
+            "id": item_id,
+            "description": description,
+            "completed": false,
+            "created_at": now,
+            "updated_at": now,
+            "due_at": due_at,
+            "source": source,
+            "priority": priority,
+            "metadata": metadata,
+            "category": category,
+            "relevance_score": relevance_score,
+            "from_staged": from_staged,
+            "recurrence_rule": recurrence_rule,
+            "recurrence_parent_id": recurrence_parent_id,
+        });
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            store(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &item_id, &now, &data)?;
+            decode(data)
+        }))
+    }
+
+    fn update_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        completed: Option<bool>,
+        description: Option<&'a str>,
+        due_at: Option<DateTime<Utc>>,
+        priority: Option<&'a str>,
+        category: Option<&'a str>,
+        goal_id: Option<&'a str>,
+        relevance_score: Option<i32>,
+        sort_order: Option<i32>,
+        indent_level: Option<i32>,
+        recurrence_rule: Option<&'a str>,
+        rollover_excluded: Option<bool>,
+        precondition: Option<&'a Precondition>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        let now = Utc::now();
+        let mut changes = Map::new();
+        changes.insert("updated_at".to_string(), json!(now));
+        if let Some(completed) = completed {
+            changes.insert("completed".to_string(), json!(completed));
+            // Cleared when marked incomplete
+            changes.insert("completed_at".to_string(), if completed { json!(now) } else { Value::Null });
+        }
+        let optional = [
+            ("description", description.map(|v| json!(v))),
+            ("due_at", due_at.map(|v| json!(v))),
+            ("priority", priority.map(|v| json!(v))),
+            ("category", category.map(|v| json!(v))),
+            ("goal_id", goal_id.map(|v| json!(v))),
+            ("relevance_score", relevance_score.map(|v| json!(v))),
+            ("sort_order", sort_order.map(|v| json!(v))),
+            ("indent_level", indent_level.map(|v| json!(v))),
+            // An empty rule clears recurrence
+            ("recurrence_rule", recurrence_rule.map(|v| if v.is_empty() { Value::Null } else { json!(v) })),
+            ("rollover_excluded", rollover_excluded.map(|v| json!(v))),
+        ];
+        for (field, value) in optional {
+            if let Some(value) = value {
+                changes.insert(field.to_string(), value);
+            }
+        }
+        let (uid, id, precondition) = (uid.to_string(), item_id.to_string(), precondition.cloned());
+        Box::pin(self.run(move |conn| {
+            let stored = load(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &id)?;
+            let holds = match (&precondition, &stored) {
+                (None, _) => true,
+                (Some(Precondition::Exists(exists)), stored) => stored.is_some() == *exists,
+                (Some(Precondition::UpdateTime(version)), Some(stored)) => stored.version.to_string() == *version,
+                (Some(Precondition::UpdateTime(_)), None) => false,
+            };
+            if !holds {
+                return Err(FirestoreError::FailedPrecondition(format!("Action item {} changed", id)));
+            }
+            let data = update(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &id, |fields| fields.extend(changes))?;
+            action_item_from_value(conn, &uid, data)
+        }))
+    }
+
+    fn delete_action_item<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), item_id.to_string());
+        Box::pin(self.run(move |conn| remove(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &id)))
+    }
+
+    fn get_messages<'a>(
+        &'a self,
+        uid: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        limit: usize,
+        offset: usize,
+    ) -> StorageFuture<'a, Vec<MessageDB>> {
+        let (uid, app_id, session_id) = (uid.to_string(), owned(app_id), owned(session_id));
+        Box::pin(self.run(move |conn| {
+            let mut messages = Vec::new();
+            for data in load_all(conn, &uid, MESSAGES_SUBCOLLECTION)? {
+                let message: MessageDB = decode(data)?;
+                if app_id.is_some() && message.app_id != app_id {
+                    continue;
+                }
+                if session_id.is_some() && message.session_id != session_id {
+                    continue;
+                }
+                messages.push(message);
+            }
+            Ok(messages.into_iter().skip(offset).take(limit).collect())
+        }))
+    }
+
+    fn save_message<'a>(
+        &'a self,
+        uid: &'a str,
+        text: &'a str,
+        sender: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        metadata: Option<&'a str>,
+    ) -> StorageFuture<'a, MessageDB> {
+        let message = MessageDB {
+            id: uuid::Uuid::new_v4().to_string(),
+            text: text.to_string(),
+            created_at: Utc::now(),
+            sender: sender.to_string(),
+            app_id: owned(app_id),
+            session_id: owned(session_id),
+            rating: None,
+            reported: false,
+            metadata: owned(metadata),
+            attachments: Vec::new(),
+            provenance: Default::default(),
+        };
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let data = serde_json::to_value(&message)?;
+            store(conn, &uid, MESSAGES_SUBCOLLECTION, &message.id, &message.created_at, &data)?;
+            Ok(message)
+        }))
+    }
+
+    fn delete_messages<'a>(&'a self, uid: &'a str, app_id: Option<&'a str>) -> StorageFuture<'a, usize> {
+        let (uid, app_id) = (uid.to_string(), owned(app_id));
+        Box::pin(self.run(move |conn| {
+            let mut deleted = 0;
+            for data in load_all(conn, &uid, MESSAGES_SUBCOLLECTION)? {
+                let message: MessageDB = decode(data)?;
+                if app_id.is_none() || message.app_id == app_id {
+                    remove(conn, &uid, MESSAGES_SUBCOLLECTION, &message.id)?;
+                    deleted += 1;
+                }
+            }
+            Ok(deleted)
+        }))
+    }
+
+    fn get_user_language<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, String> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let settings = user_settings(conn, &uid)?;
+            Ok(settings["language"].as_str().unwrap_or("en").to_string())
+        }))
+    }
+
+    fn update_user_language<'a>(&'a self, uid: &'a str, language: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, language) = (uid.to_string(), language.to_string());
+        Box::pin(self.run(move |conn| {
+            let single_language_mode = !FirestoreService::MULTI_LANGUAGE_SUPPORTED.contains(&language.as_str());
+            let mut fields = Map::new();
+            fields.insert("language".to_string(), json!(language));
+            fields.insert("single_language_mode".to_string(), json!(single_language_mode));
+            update_user_settings(conn, &uid, fields)
+        }))
+    }
+
+    fn get_processing_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, ProcessingPromptSettings> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let settings = user_settings(conn, &uid)?;
+            Ok(ProcessingPromptSettings {
+                prompt: settings["custom_processing_prompt"]
+                    .as_str()
+                    .filter(|p| !p.trim().is_empty())
+                    .unwrap_or_default()
+                    .to_string(),
+                max_length: 0,
+                plain_titles: settings["plain_titles"].as_bool().unwrap_or(false),
+                auto_discard: settings["auto_discard"]
+                    .as_str()
+                    .and_then(AutoDiscardLevel::parse)
+                    .unwrap_or_default(),
+            })
+        }))
+    }
+
+    fn update_processing_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        prompt: Option<&'a str>,
+        plain_titles: Option<bool>,
+        auto_discard: Option<AutoDiscardLevel>,
+    ) -> StorageFuture<'a, ()> {
+        let mut fields = Map::new();
+        if let Some(prompt) = prompt {
+            fields.insert("custom_processing_prompt".to_string(), json!(prompt));
+        }
+        if let Some(plain_titles) = plain_titles {
+            fields.insert("plain_titles".to_string(), json!(plain_titles));
+        }
+        if let Some(level) = auto_discard {
+            fields.insert("auto_discard".to_string(), json!(level.as_str()));
+        }
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            if fields.is_empty() {
+                return Ok(());
+            }
+            update_user_settings(conn, &uid, fields)
+        }))
+    }
+
+    fn get_notification_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, NotificationSettings> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| Ok(notification_settings(&user_settings(conn, &uid)?))))
+    }
+
+    fn update_notification_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        enabled: Option<bool>,
+        frequency: Option<i32>,
+    ) -> StorageFuture<'a, NotificationSettings> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let current = notification_settings(&user_settings(conn, &uid)?);
+            let updated = NotificationSettings {
+                enabled: enabled.unwrap_or(current.enabled),
+                frequency: frequency.unwrap_or(current.frequency),
+            };
+            let mut fields = Map::new();
+            fields.insert("notifications_enabled".to_string(), json!(updated.enabled));
+            fields.insert("notification_frequency".to_string(), json!(updated.frequency));
+            update_user_settings(conn, &uid, fields)?;
+            Ok(updated)
+        }))
+    }
+
+    fn get_conversations_count<'a>(
+        &'a self,
+        uid: &'a str,
+        include_discarded: bool,
+        statuses: &'a [String],
+    ) -> StorageFuture<'a, i64> {
+        let (uid, statuses) = (uid.to_string(), statuses.to_vec());
+        Box::pin(self.run(move |conn| {
+            let mut count = 0;
+            for data in load_all(conn, &uid, CONVERSATIONS_SUBCOLLECTION)? {
+                let discarded = data["discarded"].as_bool().unwrap_or(false);
+                let status = data["status"].as_str().unwrap_or_default();
+                if (include_discarded || !discarded) && (statuses.is_empty() || statuses.iter().any(|s| s == status)) {
+                    count += 1;
+                }
+            }
+            Ok(count)
+        }))
+    }
+
+    fn get_auto_discarded_conversations<'a>(&'a self, uid: &'a str, limit: usize) -> StorageFuture<'a, Vec<Conversation>> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let mut conversations = Vec::new();
+            for data in load_all(conn, &uid, CONVERSATIONS_SUBCOLLECTION)? {
+                if data["discarded"].as_bool() == Some(true) {
+                    conversations.push(decode::<Conversation>(data)?);
+                }
+            }
+            // Like the Firestore query: newest discarded first, then only automatic discards
+            Ok(conversations
+                .into_iter()
+                .take(limit)
+                .filter(|c| c.discard_reason.is_some())
+                .collect())
+        }))
+    }
+
+    fn set_conversation_memory_extraction_disabled<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        disabled: bool,
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| {
+            update(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id, |fields| {
+                fields.insert("memory_extraction_disabled".to_string(), json!(disabled));
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn update_conversation_events<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        events: &'a [Event],
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        let events = serde_json::to_value(events);
+        Box::pin(self.run(move |conn| {
+            let events = events?;
+            update(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id, |fields| set_structured(fields, "events", events))?;
+            Ok(())
+        }))
+    }
+
+    fn update_conversation_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        action_items: &'a [ActionItem],
+    ) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), conversation_id.to_string());
+        let action_items = serde_json::to_value(action_items);
+        Box::pin(self.run(move |conn| {
+            let action_items = action_items?;
+            update(conn, &uid, CONVERSATIONS_SUBCOLLECTION, &id, |fields| {
+                set_structured(fields, "action_items", action_items)
+            })?;
+            Ok(())
+        }))
+    }
+
+    fn save_memories<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        memories: &'a [Memory],
+    ) -> StorageFuture<'a, Vec<String>> {
+        let now = Utc::now();
+        let documents: Vec<(String, Value)> = memories
+            .iter()
+            .map(|memory| {
+                let memory_id = document_id_from_seed(&memory.content);
+                let data = json!({
+                    "id": memory_id,
+                    "uid": uid,
+                    "content": memory.content,
+                    "category": memory.category,
+                    "created_at": now,
+                    "updated_at": now,
+                    "conversation_id": conversation_id,
+                    "reviewed": false,
+                    // Null (not yet reviewed) rather than false (rejected)
+                    "user_review": null,
+                    "visibility": "private",
+                    "manually_added": false,
+                    "scoring": MemoryDB::calculate_scoring(&memory.category, &now, false),
+                    "is_read": false,
+                    "is_dismissed": false,
+                    "tags": [],
+                });
+                (memory_id, data)
+            })
+            .collect();
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let mut saved_ids = Vec::with_capacity(documents.len());
+            for (memory_id, data) in documents {
+                store(conn, &uid, MEMORIES_SUBCOLLECTION, &memory_id, &now, &data)?;
+                saved_ids.push(memory_id);
+            }
+            Ok(saved_ids)
+        }))
+    }
+
+    fn delete_memories_for_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, usize> {
+        let (uid, conversation_id) = (uid.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| {
+            let mut deleted = 0;
+            for data in load_all(conn, &uid, MEMORIES_SUBCOLLECTION)? {
+                let extracted = data["conversation_id"].as_str() == Some(conversation_id.as_str())
+                    && data["manually_added"].as_bool() != Some(true);
+                if let (true, Some(id)) = (extracted, data["id"].as_str()) {
+                    remove(conn, &uid, MEMORIES_SUBCOLLECTION, id)?;
+                    deleted += 1;
+                }
+            }
+            Ok(deleted)
+        }))
+    }
+
+    fn link_action_item_to_conversation<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        conversation_id: &'a str,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        let (uid, id, conversation_id) = (uid.to_string(), item_id.to_string(), conversation_id.to_string());
+        Box::pin(self.run(move |conn| {
+            let data = update(conn, &uid, ACTION_ITEMS_SUBCOLLECTION, &id, |fields| {
+                fields.insert("conversation_id".to_string(), json!(conversation_id));
+                fields.insert("updated_at".to_string(), now_value());
+            })?;
+            action_item_from_value(conn, &uid, data)
+        }))
+    }
+
+    fn create_staged_task<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        let description = description.trim().to_string();
+        let item_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let data = json!({
+            "id": item_id,
+            "description": description,
+            "completed": false,
+            "created_at": now,
+            "updated_at": now,
+            "due_at": due_at,
+            "source": source,
+            "priority": priority,
+            "metadata": metadata,
+            "category": category,
+            "relevance_score": relevance_score,
+        });
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            if description.is_empty() {
+                return Err("Cannot create staged task with empty description".into());
+            }
+            // An exact (case-insensitive) duplicate returns the existing task
+            let wanted = description.to_lowercase();
+            if let Some(existing) = staged_tasks(conn, &uid)?
+                .into_iter()
+                .find(|t| t.description.trim().to_lowercase() == wanted)
+            {
+                return Ok(existing);
+            }
+            store(conn, &uid, STAGED_TASKS_SUBCOLLECTION, &item_id, &now, &data)?;
+            decode(data)
+        }))
+    }
+
+    fn get_staged_tasks<'a>(&'a self, uid: &'a str, limit: usize, offset: usize) -> StorageFuture<'a, Vec<ActionItemDB>> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| Ok(staged_tasks(conn, &uid)?.into_iter().skip(offset).take(limit).collect())))
+    }
+
+    fn delete_staged_task<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()> {
+        let (uid, id) = (uid.to_string(), item_id.to_string());
+        Box::pin(self.run(move |conn| remove(conn, &uid, STAGED_TASKS_SUBCOLLECTION, &id)))
+    }
+
+    fn get_blocked_memory_topics<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<String>> {
+        let uid = uid.to_string();
+        Box::pin(self.run(move |conn| {
+            let settings = user_settings(conn, &uid)?;
+            let topics = &settings["assistant_settings"]["memory"]["blocked_topics"];
+            Ok(serde_json::from_value(topics.clone()).unwrap_or_default())
+        }))
+    }
+
+    // Folders, folder rules, templates and focus sessions aren't kept locally, so
+    // processing runs without them
+
+    fn get_conversation_templates<'a>(&'a self, _uid: &'a str) -> StorageFuture<'a, Vec<ConversationTemplate>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn get_folders<'a>(&'a self, _uid: &'a str) -> StorageFuture<'a, Vec<Folder>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn get_folder_rules<'a>(&'a self, _uid: &'a str) -> StorageFuture<'a, Vec<FolderRule>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn get_focus_sessions_between<'a>(
+        &'a self,
+        _uid: &'a str,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+        _limit: usize,
+    ) -> StorageFuture<'a, Vec<FocusSessionDB>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn conversation(id: &str, created_at: DateTime<Utc>) -> Conversation {
+        serde_json::from_value(json!({
+            "id": id,
+            "created_at": created_at,
+            "started_at": created_at,
+            "finished_at": created_at,
+            "structured": {"title": id, "overview": ""}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_conversations_round_trip_and_filters() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let now = Utc::now();
+        let mut discarded = conversation("discarded", now - Duration::hours(1));
+        discarded.discarded = true;
+        storage.save_conversation("u1", &conversation("old", now - Duration::days(2))).await.unwrap();
+        storage.save_conversation("u1", &conversation("new", now)).await.unwrap();
+        storage.save_conversation("u1", &discarded).await.unwrap();
+        storage.save_conversation("u2", &conversation("other-user", now)).await.unwrap();
+
+        let ids = |conversations: Vec<Conversation>| conversations.into_iter().map(|c| c.id).collect::<Vec<_>>();
+        let listed = storage.get_conversations("u1", 10, 0, false, &[], None, None, None, None).await.unwrap();
+        assert_eq!(ids(listed), vec!["new", "old"]);
+        let listed = storage.get_conversations("u1", 10, 0, true, &[], None, None, None, None).await.unwrap();
+        assert_eq!(ids(listed), vec!["new", "discarded", "old"]);
+        let since = (now - Duration::days(1)).to_rfc3339();
+        let listed = storage
+            .get_conversations("u1", 10, 0, false, &[], None, None, Some(&since), None)
+            .await
+            .unwrap();
+        assert_eq!(ids(listed), vec!["new"]);
+
+        storage.update_conversation_title("u1", "old", "Renamed").await.unwrap();
+        storage.set_conversation_starred("u1", "old", true).await.unwrap();
+        let old = storage.get_conversation("u1", "old").await.unwrap().unwrap();
+        assert_eq!(old.structured.title, "Renamed");
+        assert!(old.starred);
+        let listed = storage.get_conversations("u1", 10, 0, false, &[], Some(true), None, None, None).await.unwrap();
+        assert_eq!(ids(listed), vec!["old"]);
+
+        storage.delete_conversation("u1", "old").await.unwrap();
+        assert!(storage.get_conversation("u1", "old").await.unwrap().is_none());
+        assert!(matches!(
+            storage.set_conversation_starred("u1", "old", true).await,
+            Err(FirestoreError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_memories_create_filter_and_update() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let tags = vec!["work".to_string()];
+        let id = storage
+            .create_memory("u1", "Likes tea", "private", None, None, None, None, &tags, None, None, Some("desktop"), None)
+            .await
+            .unwrap();
+        storage
+            .create_memory("u1", "Works at Acme", "private", Some(MemoryCategory::System), None, None, None, &[], None, None, None, None)
+            .await
+            .unwrap();
+
+        // Extracted memories start unreviewed and, as in Firestore, aren't listed
+        let memories = storage.get_memories_filtered("u1", 10, 0, None, None, false).await.unwrap();
+        assert_eq!(memories.len(), 1);
+        let tagged = storage.get_memories_filtered("u1", 10, 0, None, Some(&tags), false).await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].id, id);
+        assert!(tagged[0].manually_added);
+        assert_eq!(tagged[0].source.as_deref(), Some("desktop"));
+        assert!(storage.get_memories_filtered("u1", 10, 0, Some("system"), None, false).await.unwrap().is_empty());
+        assert_eq!(storage.get_memories_filtered("u1", 10, 0, Some("manual"), None, false).await.unwrap().len(), 1);
+
+        storage.update_memory_content("u1", &id, "Likes green tea").await.unwrap();
+        storage.update_memory_visibility("u1", &id, "public").await.unwrap();
+        let tagged = storage.get_memories_filtered("u1", 10, 0, None, Some(&tags), false).await.unwrap();
+        assert_eq!(tagged[0].content, "Likes green tea");
+        assert_eq!(tagged[0].visibility, "public");
+
+        storage.delete_memory("u1", &id).await.unwrap();
+        assert!(storage.get_memories_filtered("u1", 10, 0, None, None, false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conversation_learning_opt_out() {
+        let storage = LocalStorage::in_memory().unwrap();
+        storage.save_conversation("u1", &conversation("c1", Utc::now())).await.unwrap();
+        storage.set_conversation_memory_extraction_disabled("u1", "c1", true).await.unwrap();
+        assert!(storage.get_conversation("u1", "c1").await.unwrap().unwrap().memory_extraction_disabled);
+
+        let memories: Vec<Memory> = serde_json::from_value(json!([
+            {"content": "Likes tea", "category": "interesting"},
+            {"content": "Works at Acme", "category": "system"}
+        ]))
+        .unwrap();
+        let ids = storage.save_memories("u1", "c1", &memories).await.unwrap();
+        let kept = ids[0].clone();
+        storage
+            .run(move |conn| {
+                update(conn, "u1", MEMORIES_SUBCOLLECTION, &kept, |fields| {
+                    fields.insert("manually_added".to_string(), json!(true));
+                })
+                .map(|_| ())
+            })
+            .await
+            .unwrap();
+
+        // Memories the user added by hand survive the opt-out
+        assert_eq!(storage.delete_memories_for_conversation("u1", "c1").await.unwrap(), 1);
+        let remaining = storage.run(|conn| load_all(conn, "u1", MEMORIES_SUBCOLLECTION)).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0]["id"], json!(ids[0]));
+
+        assert!(matches!(
+            storage.set_conversation_memory_extraction_disabled("u1", "missing", true).await,
+            Err(FirestoreError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_action_item_updates_check_the_version() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let item = storage
+            .create_action_item("u1", "Send the report", None, None, None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let (_, version) = storage.get_action_item_versioned("u1", &item.id).await.unwrap().unwrap();
+
+        let updated = storage
+            .update_action_item(
+                "u1", &item.id, Some(true), None, None, None, None, None, None, None, None, None, None,
+                Some(&Precondition::UpdateTime(version.clone())),
+            )
+            .await
+            .unwrap();
+        assert!(updated.completed);
+        assert!(updated.completed_at.is_some());
+
+        // The first update moved the version on
+        let stale = storage
+            .update_action_item(
+                "u1", &item.id, Some(false), None, None, None, None, None, None, None, None, None, None,
+                Some(&Precondition::UpdateTime(version)),
+            )
+            .await;
+        assert!(matches!(stale, Err(FirestoreError::FailedPrecondition(_))));
+
+        let missing = storage
+            .update_action_item("u1", "missing", Some(true), None, None, None, None, None, None, None, None, None, None, None)
+            .await;
+        assert!(matches!(missing, Err(FirestoreError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_action_items_list_due_items_first() {
+        let storage = LocalStorage::in_memory().unwrap();
+        let now = Utc::now();
+        let later = storage
+            .create_action_item("u1", "Later", Some(now + Duration::days(2)), None, None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let undated = storage
+            .create_action_item("u1", "Someday", None, None, None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let soon = storage
+            .create_action_item("u1", "Soon", Some(now + Duration::days(1)), None, None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+
+        let items = storage
+            .get_action_items("u1", 10, 0, None, None, None, None, None, None, None, None, false)
+            .await
+            .unwrap();
+        let ids: Vec<_> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec![soon.id.as_str(), later.id.as_str(), undated.id.as_str()]);
+
+        let due_end = (now + Duration::days(1) + Duration::hours(1)).to_rfc3339();
+        let items = storage
+            .get_action_items("u1", 10, 0, None, None, None, None, None, Some(&due_end), None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, soon.id);
+    }
+
+    #[tokio::test]
+    async fn test_messages_by_app() {
+        let storage = LocalStorage::in_memory().unwrap();
+        storage.save_message("u1", "hi", "human", None, Some("s1"), None).await.unwrap();
+        storage.save_message("u1", "hello", "ai", None, Some("s1"), None).await.unwrap();
+        storage.save_message("u1", "app chat", "human", Some("app"), None, None).await.unwrap();
+
+        let messages = storage.get_messages("u1", None, Some("s1"), 10, 0).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        let app = storage.get_messages("u1", Some("app"), None, 10, 0).await.unwrap();
+        assert_eq!(app.len(), 1);
+        assert_eq!(app[0].text, "app chat");
+
+        assert_eq!(storage.delete_messages("u1", Some("app")).await.unwrap(), 1);
+        assert_eq!(storage.get_messages("u1", None, None, 10, 0).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_settings_defaults_and_updates() {
+        let storage = LocalStorage::in_memory().unwrap();
+        assert_eq!(storage.get_user_language("u1").await.unwrap(), "en");
+        let notifications = storage.get_notification_settings("u1").await.unwrap();
+        assert!(notifications.enabled);
+        assert_eq!(notifications.frequency, 3);
+
+        storage.update_user_language("u1", "de").await.unwrap();
+        let updated = storage.update_notification_settings("u1", None, Some(1)).await.unwrap();
+        assert!(updated.enabled);
+        assert_eq!(updated.frequency, 1);
+        storage
+            .update_processing_settings("u1", Some("Be brief"), Some(true), Some(AutoDiscardLevel::Off))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.get_user_language("u1").await.unwrap(), "de");
+        assert_eq!(storage.get_notification_settings("u1").await.unwrap().frequency, 1);
+        let processing = storage.get_processing_settings("u1").await.unwrap();
+        assert_eq!(processing.prompt, "Be brief");
+        assert!(processing.plain_titles);
+        assert_eq!(storage.get_user_language("u2").await.unwrap(), "en");
+    }
+}
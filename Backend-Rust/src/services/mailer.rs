@@ -0,0 +1,99 @@
+// Mailer service - Outbound email via the Resend HTTP API
+// Used for user-confirmed sends (e.g. conversation follow-up emails)
+
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::config::Config;
+
+const RESEND_API_URL: &str = "https://api.resend.com/emails";
+
+#[derive(Clone)]
+pub struct Mailer {
+    client: Client,
+    api_key: String,
+    from_address: String,
+}
+
+impl Mailer {
+    /// Build a mailer when an API key is configured
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let api_key = config.mailer_api_key.clone()?;
+        let from_address = config.mailer_from_address.clone();
+        Some(Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .expect("Failed to create HTTP client"),
+            api_key,
+            from_address,
+        })
+    }
+
+    /// Send a plain-text email. `reply_to` lets recipients answer the user directly.
+    pub async fn send(
+        &self,
+        to: &[String],
+        subject: &str,
+        text: &str,
+        reply_to: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut body = json!({
+            "from": self.from_address,
+            "to": to,
+            "subject": subject,
+            "text": text,
+        });
+        if let Some(reply_to) = reply_to {
+            body["reply_to"] = json!(reply_to);
+        }
+
+        let response = self
+            .client
+            .post(RESEND_API_URL)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error = response.text().await.unwrap_or_default();
+            return Err(format!("Mailer error {}: {}", status, error).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Minimal sanity check for recipient addresses
+pub fn is_valid_email(address: &str) -> bool {
+    let address = address.trim();
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !address.chars().any(|c| c.is_whitespace() || c == ',' || c == '<' || c == '>')
+                && !domain.contains('@')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_email() {
+        assert!(is_valid_email("ana@example.com"));
+        assert!(is_valid_email(" bob.smith+omi@mail.example.org "));
+        assert!(!is_valid_email("ana"));
+        assert!(!is_valid_email("ana@localhost"));
+        assert!(!is_valid_email("a@b@example.com"));
+        assert!(!is_valid_email("Ana <ana@example.com>"));
+    }
+}
@@ -0,0 +1,142 @@
+// Memory review - The queue of extracted memories awaiting the user's approval
+// Users can opt into auto-approval (assistant_settings.memory.auto_approve_after_days);
+// a periodic job approves whatever has waited longer than that.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::job_queue::JobQueue;
+use crate::models::{MemoryDB, PendingReviewResponse};
+use crate::services::FirestoreService;
+
+/// How often the auto-approve job runs
+const AUTO_APPROVE_INTERVAL_HOURS: u64 = 6;
+/// Most pending memories read per user (the queue and each auto-approve pass)
+pub const MAX_PENDING_SCAN: usize = 1000;
+
+/// Queue page with counts over every scanned pending memory
+pub fn pending_review(pending: Vec<MemoryDB>, limit: usize) -> PendingReviewResponse {
+    let mut by_category: HashMap<String, usize> = HashMap::new();
+    for memory in &pending {
+        let category = serde_json::to_value(&memory.category)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        *by_category.entry(category).or_default() += 1;
+    }
+    PendingReviewResponse {
+        pending: pending.len(),
+        by_category,
+        oldest_pending_at: pending.iter().map(|m| m.created_at).min(),
+        memories: pending.into_iter().take(limit).collect(),
+    }
+}
+
+/// IDs of pending memories created before `cutoff`
+pub fn due_for_auto_approve(pending: &[MemoryDB], cutoff: DateTime<Utc>) -> Vec<String> {
+    pending
+        .iter()
+        .filter(|m| m.created_at < cutoff)
+        .map(|m| m.id.clone())
+        .collect()
+}
+
+/// Spawn the periodic auto-approve job
+pub fn spawn_memory_auto_approve(firestore: Arc<FirestoreService>, jobs: JobQueue) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(AUTO_APPROVE_INTERVAL_HOURS * 3600));
+        loop {
+            interval.tick().await;
+            run_auto_approve_pass(&firestore, &jobs).await;
+        }
+    });
+
+    tracing::info!("Memory auto-approve scheduled every {} hours", AUTO_APPROVE_INTERVAL_HOURS);
+}
+
+/// Queue approval of overdue pending memories for every user who opted in
+async fn run_auto_approve_pass(firestore: &Arc<FirestoreService>, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_memory_auto_approve().await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Memory auto-approve: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    for uid in uids {
+        let firestore = firestore.clone();
+        jobs.submit_for_user(&uid, "memory-auto-approve", |uid| async move {
+            auto_approve_user(&firestore, &uid).await
+        });
+    }
+}
+
+async fn auto_approve_user(
+    firestore: &FirestoreService,
+    uid: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let settings = firestore.get_assistant_settings(uid).await?;
+    let days = settings.memory.and_then(|m| m.auto_approve_after_days).unwrap_or(0);
+    if days <= 0 {
+        return Ok(());
+    }
+
+    let pending = firestore.get_pending_review_memories(uid, MAX_PENDING_SCAN).await?;
+    let due = due_for_auto_approve(&pending, Utc::now() - Duration::days(days as i64));
+    if due.is_empty() {
+        return Ok(());
+    }
+    let (approved, _) = firestore.review_memories(uid, &due, true).await?;
+    tracing::info!("Auto-approved {} memories pending over {} days for user {}", approved, days, uid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::category::MemoryCategory;
+
+    fn memory(id: &str, category: MemoryCategory, age_days: i64) -> MemoryDB {
+        let created_at = Utc::now() - Duration::days(age_days);
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "uid": "u1",
+            "content": "Prefers morning meetings",
+            "category": category,
+            "created_at": created_at,
+            "updated_at": created_at,
+            "conversation_id": null,
+            "reviewed": false,
+            "user_review": null,
+            "visibility": "private",
+            "manually_added": false,
+            "scoring": null,
+            "is_read": false,
+            "is_dismissed": false,
+            "tags": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pending_counts_and_auto_approve() {
+        let pending = vec![
+            memory("new", MemoryCategory::System, 1),
+            memory("old", MemoryCategory::Manual, 10),
+            memory("older", MemoryCategory::System, 20),
+        ];
+
+        let due = due_for_auto_approve(&pending, Utc::now() - Duration::days(7));
+        assert_eq!(due, vec!["old".to_string(), "older".to_string()]);
+
+        let oldest = pending[2].created_at;
+        let response = pending_review(pending, 1);
+        assert_eq!(response.pending, 3);
+        assert_eq!(response.memories.len(), 1);
+        assert_eq!(response.by_category["system"], 2);
+        assert_eq!(response.oldest_pending_at, Some(oldest));
+    }
+}
@@ -0,0 +1,136 @@
+// Message search - Finds chat messages by the words they contain
+// Messages store their lowercased words in search_tokens; a search queries the longest term
+// with array-contains and checks the remaining terms against the decrypted text.
+
+use std::collections::HashSet;
+
+use crate::models::{MessageContext, MessageDB, MessageSearchResult};
+use crate::services::FirestoreService;
+
+/// Most distinct words indexed per message
+pub const MAX_SEARCH_TOKENS: usize = 256;
+/// Candidates read per requested result, to leave room for multi-word filtering
+const CANDIDATES_PER_RESULT: usize = 5;
+/// Characters of a snippet (and of context messages)
+const SNIPPET_CHARS: usize = 160;
+
+/// Distinct lowercased words of `text` (2+ characters), in order of first appearance
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .filter(|w| seen.insert(w.clone()))
+        .take(MAX_SEARCH_TOKENS)
+        .collect()
+}
+
+/// Whether every term is one of the text's words
+pub fn matches_all(text: &str, terms: &[String]) -> bool {
+    let words: HashSet<String> = tokenize(text).into_iter().collect();
+    terms.iter().all(|t| words.contains(t))
+}
+
+/// Up to SNIPPET_CHARS of `text` starting a little before the first term
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let lower = text.to_lowercase();
+    // Only use the match position when lowercasing kept byte offsets aligned
+    let start = if lower.len() == text.len() {
+        terms
+            .iter()
+            .filter_map(|t| lower.find(t.as_str()))
+            .min()
+            .map(|pos| text[..pos].chars().count().saturating_sub(SNIPPET_CHARS / 4))
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let mut snippet: String = text.chars().skip(start).take(SNIPPET_CHARS).collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if text.chars().count() > start + SNIPPET_CHARS {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn context(message: MessageDB) -> MessageContext {
+    MessageContext {
+        id: message.id,
+        sender: message.sender,
+        text: message.text.chars().take(SNIPPET_CHARS).collect(),
+        created_at: message.created_at,
+    }
+}
+
+/// Newest messages containing every word of `query`, with the messages around them
+pub async fn search(
+    firestore: &FirestoreService,
+    uid: &str,
+    query: &str,
+    app_id: Option<&str>,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<MessageSearchResult>, Box<dyn std::error::Error + Send + Sync>> {
+    let terms = tokenize(query);
+    // The longest term is usually the rarest, so it narrows the candidates most
+    let Some(index_term) = terms.iter().max_by_key(|t| t.chars().count()) else {
+        return Ok(Vec::new());
+    };
+
+    let candidates = firestore
+        .search_messages(uid, index_term, app_id, session_id, limit * CANDIDATES_PER_RESULT)
+        .await?;
+    let hits: Vec<MessageDB> = candidates
+        .into_iter()
+        .filter(|m| matches_all(&m.text, &terms))
+        .take(limit)
+        .collect();
+
+    let mut results = Vec::with_capacity(hits.len());
+    for message in hits {
+        let (previous, next) = match message.session_id.as_deref() {
+            Some(session) => {
+                let (previous, next) = tokio::join!(
+                    firestore.get_adjacent_message(uid, session, message.created_at, true),
+                    firestore.get_adjacent_message(uid, session, message.created_at, false),
+                );
+                (previous?.map(context), next?.map(context))
+            }
+            None => (None, None),
+        };
+        results.push(MessageSearchResult {
+            snippet: snippet(&message.text, &terms),
+            session_id: message.session_id.clone(),
+            message,
+            previous,
+            next,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_and_match() {
+        assert_eq!(
+            tokenize("Deploy the API? The api's v2 is live, a/b test."),
+            ["deploy", "the", "api", "v2", "is", "live", "test"]
+        );
+        let terms = tokenize("API deploy");
+        assert!(matches_all("Deploy the API today", &terms));
+        assert!(!matches_all("Deploy the app today", &terms));
+        // Whole words only
+        assert!(!matches_all("Deployment of the API", &terms));
+
+        let text = format!("{} the budget spreadsheet is ready", "word ".repeat(60));
+        let s = snippet(&text, &tokenize("budget"));
+        assert!(s.starts_with('…'));
+        assert!(s.contains("budget spreadsheet"));
+        assert_eq!(snippet("short answer", &tokenize("answer")), "short answer");
+    }
+}
@@ -0,0 +1,510 @@
+// Schema migrations - Versioned backfills over Firestore collection groups
+// Progress is tracked in schema_migrations/{id}; interrupted runs resume from the last document.
+// Run via POST /v1/admin/migrations/:id/run or `omi-admin migrate run <id>`.
+
+use base64::Engine;
+use chrono::Utc;
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+
+use crate::models::{conversation_context_line, MigrationRecord, MigrationStatus};
+use crate::services::firestore::{
+    FirestoreError, ACTION_ITEMS_SUBCOLLECTION, CONVERSATIONS_SUBCOLLECTION, MESSAGES_SUBCOLLECTION,
+    TRANSCRIPT_CHUNKS_SUBCOLLECTION,
+};
+use crate::services::message_search;
+use crate::services::transcript_chunks::{chunk_doc_id, chunk_fields, TranscriptPayload, TRANSCRIPT_CHUNK_BYTES};
+use crate::services::FirestoreService;
+
+/// Documents fetched per page
+const MIGRATION_PAGE_SIZE: usize = 200;
+
+/// Fields to write on one document
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPatch {
+    pub fields: Value,
+    pub field_paths: Vec<&'static str>,
+    /// Documents to create or replace under the migrated document before it's patched,
+    /// as (path relative to the document, fields)
+    pub children: Vec<(String, Value)>,
+}
+
+/// A versioned migration. `transform` receives a document's Firestore `fields` and returns
+/// the patch to apply, or None when the document is already up to date. Transforms must be
+/// idempotent so a resumed or repeated run never changes a migrated document again.
+pub struct Migration {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub collection_id: &'static str,
+    pub transform: fn(&Value) -> Option<FieldPatch>,
+}
+
+/// Registered migrations, oldest first. Never renumber or remove entries.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        id: "0001_backfill_conversation_starred",
+        description: "Set starred=false on conversations without the field so starred filters match them",
+        collection_id: CONVERSATIONS_SUBCOLLECTION,
+        transform: backfill_starred,
+    },
+    Migration {
+        id: "0002_backfill_action_item_completed",
+        description: "Set completed=false on action items without the field so completion filters match them",
+        collection_id: ACTION_ITEMS_SUBCOLLECTION,
+        transform: backfill_completed,
+    },
+    Migration {
+        id: "0003_compress_legacy_transcripts",
+        description: "Rewrite plain transcript_segments arrays as zlib-compressed bytes",
+        collection_id: CONVERSATIONS_SUBCOLLECTION,
+        transform: compress_legacy_transcript,
+    },
+    Migration {
+        id: "0004_backfill_conversation_context_line",
+        description: "Precompute context_line on conversations so chat context doesn't rebuild it per request",
+        collection_id: CONVERSATIONS_SUBCOLLECTION,
+        transform: backfill_context_line,
+    },
+    Migration {
+        id: "0005_chunk_oversized_transcripts",
+        description: "Move transcripts too large for the conversation document into transcript_chunks (run 0003 first)",
+        collection_id: CONVERSATIONS_SUBCOLLECTION,
+        transform: chunk_oversized_transcript,
+    },
+    Migration {
+        id: "0006_backfill_message_search_tokens",
+        description: "Index the words of chat messages in search_tokens for GET /v2/messages/search",
+        collection_id: MESSAGES_SUBCOLLECTION,
+        transform: backfill_message_search_tokens,
+    },
+];
+
+pub fn find_migration(id: &str) -> Option<&'static Migration> {
+    MIGRATIONS.iter().find(|m| m.id == id)
+}
+
+/// Options for a single run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Count what would change without writing anything (including the tracking record)
+    pub dry_run: bool,
+    /// Stop after scanning this many documents; the run stays resumable
+    pub limit: Option<u64>,
+    /// Ignore previous progress and start from the first document
+    pub restart: bool,
+}
+
+// =========================================================================
+// Transforms
+// =========================================================================
+
+fn backfill_bool(fields: &Value, key: &'static str) -> Option<FieldPatch> {
+    if fields.get(key).is_some() {
+        return None;
+    }
+    Some(FieldPatch {
+        fields: json!({ key: {"booleanValue": false} }),
+        field_paths: vec![key],
+        children: Vec::new(),
+    })
+}
+
+fn backfill_starred(fields: &Value) -> Option<FieldPatch> {
+    backfill_bool(fields, "starred")
+}
+
+fn backfill_completed(fields: &Value) -> Option<FieldPatch> {
+    backfill_bool(fields, "completed")
+}
+
+/// Numeric Firestore value (doubleValue or integerValue)
+fn firestore_number(value: Option<&Value>) -> Option<f64> {
+    let value = value?;
+    value
+        .get("doubleValue")
+        .and_then(|v| v.as_f64())
+        .or_else(|| {
+            value
+                .get("integerValue")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse().ok())
+        })
+}
+
+fn firestore_string(fields: &Value, key: &str) -> Option<String> {
+    fields
+        .get(key)
+        .and_then(|v| v.get("stringValue"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
+/// Convert a legacy `arrayValue` transcript into the compressed `bytesValue` format
+/// written by conversation_to_firestore. Encrypted and already-compressed transcripts
+/// are left alone.
+fn compress_legacy_transcript(fields: &Value) -> Option<FieldPatch> {
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let array = fields.get("transcript_segments")?.get("arrayValue")?;
+    let segments: Vec<Value> = array
+        .get("values")
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|seg| {
+                    let f = seg.get("mapValue")?.get("fields")?;
+                    let mut value = json!({
+                        "text": firestore_string(f, "text").unwrap_or_default(),
+                        "speaker": firestore_string(f, "speaker").unwrap_or_else(|| "SPEAKER_00".to_string()),
+                        "speaker_id": firestore_number(f.get("speaker_id")).unwrap_or(0.0) as i64,
+                        "is_user": f.get("is_user").and_then(|v| v.get("booleanValue")).and_then(|v| v.as_bool()).unwrap_or(false),
+                        "start": firestore_number(f.get("start")).unwrap_or(0.0),
+                        "end": firestore_number(f.get("end")).unwrap_or(0.0)
+                    });
+                    if let Some(person_id) = firestore_string(f, "person_id") {
+                        value["person_id"] = json!(person_id);
+                    }
+                    if let Some(language) = firestore_string(f, "language") {
+                        value["language"] = json!(language);
+                    }
+                    Some(value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let json_str = serde_json::to_string(&segments).ok()?;
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json_str.as_bytes()).ok()?;
+    let compressed = encoder.finish().ok()?;
+
+    Some(FieldPatch {
+        fields: json!({
+            "transcript_segments": {
+                "bytesValue": base64::engine::general_purpose::STANDARD.encode(compressed)
+            },
+            "transcript_segments_compressed": {"booleanValue": true}
+        }),
+        field_paths: vec!["transcript_segments", "transcript_segments_compressed"],
+        children: Vec::new(),
+    })
+}
+
+/// Build context_line from structured.title/overview/emoji when it's missing
+fn backfill_context_line(fields: &Value) -> Option<FieldPatch> {
+    if fields.get("context_line").is_some() {
+        return None;
+    }
+    let structured = fields.get("structured")?.get("mapValue")?.get("fields")?;
+    let title = firestore_string(structured, "title").unwrap_or_default();
+    let overview = firestore_string(structured, "overview").unwrap_or_default();
+    if title.trim().is_empty() && overview.trim().is_empty() {
+        return None;
+    }
+    let emoji = firestore_string(structured, "emoji").unwrap_or_else(|| "🧠".to_string());
+
+    Some(FieldPatch {
+        fields: json!({
+            "context_line": {"stringValue": conversation_context_line(&emoji, &title, &overview)}
+        }),
+        field_paths: vec!["context_line"],
+        children: Vec::new(),
+    })
+}
+
+/// Move a compressed or encrypted transcript too large to keep inline into
+/// transcript_chunks, leaving transcript_chunk_count on the conversation
+fn chunk_oversized_transcript(fields: &Value) -> Option<FieldPatch> {
+    let payload = TranscriptPayload::from_field(fields.get("transcript_segments")?)?;
+    if !payload.needs_chunking() {
+        return None;
+    }
+    let chunks = payload.split(TRANSCRIPT_CHUNK_BYTES);
+
+    Some(FieldPatch {
+        fields: json!({
+            "transcript_chunk_count": {"integerValue": chunks.len().to_string()}
+        }),
+        // transcript_segments is masked but not written, which removes it
+        field_paths: vec!["transcript_segments", "transcript_chunk_count"],
+        children: chunks
+            .iter()
+            .enumerate()
+            .map(|(index, part)| {
+                (
+                    format!("{}/{}", TRANSCRIPT_CHUNKS_SUBCOLLECTION, chunk_doc_id(index)),
+                    chunk_fields(index, part),
+                )
+            })
+            .collect(),
+    })
+}
+
+/// Add search_tokens to messages without them. Encrypted messages are skipped: their words
+/// can't be read here, and storing them in the clear would undo the encryption.
+fn backfill_message_search_tokens(fields: &Value) -> Option<FieldPatch> {
+    if fields.get("search_tokens").is_some()
+        || firestore_string(fields, "data_protection_level").as_deref() == Some("enhanced")
+    {
+        return None;
+    }
+    let text = firestore_string(fields, "text")?;
+    let tokens: Vec<Value> = message_search::tokenize(&text)
+        .into_iter()
+        .map(|t| json!({"stringValue": t}))
+        .collect();
+
+    Some(FieldPatch {
+        fields: json!({"search_tokens": {"arrayValue": {"values": tokens}}}),
+        field_paths: vec!["search_tokens"],
+        children: Vec::new(),
+    })
+}
+
+// =========================================================================
+// Runner
+// =========================================================================
+
+/// Run (or resume) a migration. `on_progress` is called after every page and at the end.
+/// A run stopped by `limit` is left `pending` with its cursor so the next run continues.
+pub async fn run_migration(
+    firestore: &FirestoreService,
+    migration: &Migration,
+    options: RunOptions,
+    mut on_progress: impl FnMut(&MigrationRecord),
+) -> Result<MigrationRecord, Box<dyn std::error::Error + Send + Sync>> {
+    let existing = firestore.get_migration_record(migration.id).await?;
+    let now = Utc::now();
+
+    let mut record = match existing {
+        Some(r) if !options.dry_run && !options.restart && !r.dry_run => {
+            if r.status == MigrationStatus::Completed {
+                return Ok(r);
+            }
+            MigrationRecord {
+                status: MigrationStatus::Running,
+                error: None,
+                updated_at: now,
+                ..r
+            }
+        }
+        _ => MigrationRecord {
+            id: migration.id.to_string(),
+            status: MigrationStatus::Running,
+            scanned: 0,
+            updated: 0,
+            cursor: None,
+            dry_run: options.dry_run,
+            error: None,
+            started_at: now,
+            updated_at: now,
+            completed_at: None,
+        },
+    };
+
+    tracing::info!(
+        "Migration {}: starting{} at {} scanned",
+        migration.id,
+        if options.dry_run { " (dry run)" } else { "" },
+        record.scanned
+    );
+    if !options.dry_run {
+        firestore.save_migration_record(&record).await?;
+    }
+
+    // Documents are streamed a page at a time; progress is checkpointed after every page
+    let page_size = options
+        .limit
+        .map_or(MIGRATION_PAGE_SIZE, |l| (l as usize).clamp(1, MIGRATION_PAGE_SIZE));
+    let start_after = record.cursor.clone();
+    let mut docs = firestore.stream_collection_group(migration.collection_id, page_size, start_after.as_deref());
+
+    let mut scanned_this_run = 0;
+    loop {
+        if options.limit.is_some_and(|l| scanned_this_run >= l) {
+            record.status = MigrationStatus::Pending;
+            break;
+        }
+        let doc = match docs.try_next().await {
+            Ok(Some(doc)) => doc,
+            Ok(None) => {
+                record.status = MigrationStatus::Completed;
+                record.completed_at = Some(Utc::now());
+                break;
+            }
+            Err(e) => return Err(fail(firestore, record, options, e).await),
+        };
+
+        let Some(name) = doc.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let empty = json!({});
+        let fields = doc.get("fields").unwrap_or(&empty);
+
+        if let Some(patch) = (migration.transform)(fields) {
+            if !options.dry_run {
+                for (path, child) in &patch.children {
+                    let child_name = format!("{}/{}", name, path);
+                    if let Err(e) = firestore.set_document_fields(&child_name, child.clone()).await {
+                        return Err(fail(firestore, record, options, e).await);
+                    }
+                }
+                if let Err(e) = firestore
+                    .patch_document_fields(name, patch.fields, &patch.field_paths)
+                    .await
+                {
+                    return Err(fail(firestore, record, options, e).await);
+                }
+            }
+            record.updated += 1;
+        }
+        record.scanned += 1;
+        scanned_this_run += 1;
+        record.cursor = Some(name.to_string());
+
+        if scanned_this_run % page_size as u64 == 0 {
+            record.updated_at = Utc::now();
+            if !options.dry_run {
+                firestore.save_migration_record(&record).await?;
+            }
+            on_progress(&record);
+        }
+    }
+
+    record.updated_at = Utc::now();
+    if !options.dry_run {
+        firestore.save_migration_record(&record).await?;
+    }
+    on_progress(&record);
+    tracing::info!(
+        "Migration {}: {:?} - scanned {}, updated {}",
+        migration.id,
+        record.status,
+        record.scanned,
+        record.updated
+    );
+    Ok(record)
+}
+
+/// Record a failed run (keeping its cursor for a retry) and pass the error on
+async fn fail(
+    firestore: &FirestoreService,
+    mut record: MigrationRecord,
+    options: RunOptions,
+    error: FirestoreError,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    tracing::error!("Migration {} failed: {}", record.id, error);
+    if !options.dry_run {
+        record.status = MigrationStatus::Failed;
+        record.error = Some(error.to_string());
+        record.updated_at = Utc::now();
+        if let Err(e) = firestore.save_migration_record(&record).await {
+            tracing::error!("Failed to record migration failure: {}", e);
+        }
+    }
+    Box::new(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backfill_only_touches_missing_fields() {
+        let missing = json!({"discarded": {"booleanValue": false}});
+        let patch = backfill_starred(&missing).unwrap();
+        assert_eq!(patch.field_paths, vec!["starred"]);
+        assert_eq!(patch.fields["starred"]["booleanValue"], json!(false));
+
+        let present = json!({"starred": {"booleanValue": true}});
+        assert_eq!(backfill_starred(&present), None);
+
+        let ids: Vec<&str> = MIGRATIONS.iter().map(|m| m.id).collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(ids, sorted, "migration ids must be unique and ordered");
+    }
+
+    #[test]
+    fn test_compress_legacy_transcript() {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let legacy = json!({
+            "transcript_segments": {"arrayValue": {"values": [{"mapValue": {"fields": {
+                "text": {"stringValue": "Hello there"},
+                "speaker": {"stringValue": "SPEAKER_01"},
+                "speaker_id": {"integerValue": "1"},
+                "is_user": {"booleanValue": true},
+                "start": {"integerValue": "2"},
+                "end": {"doubleValue": 3.5}
+            }}}]}}
+        });
+        let patch = compress_legacy_transcript(&legacy).unwrap();
+        let b64 = patch.fields["transcript_segments"]["bytesValue"].as_str().unwrap();
+        let bytes = base64::engine::general_purpose::STANDARD.decode(b64).unwrap();
+        let mut decompressed = String::new();
+        ZlibDecoder::new(&bytes[..]).read_to_string(&mut decompressed).unwrap();
+        let segments: Vec<Value> = serde_json::from_str(&decompressed).unwrap();
+        assert_eq!(segments[0]["text"], json!("Hello there"));
+        assert_eq!(segments[0]["speaker_id"], json!(1));
+        assert_eq!(segments[0]["start"], json!(2.0));
+        assert_eq!(segments[0]["end"], json!(3.5));
+
+        // Already compressed or encrypted transcripts are skipped
+        let compressed = json!({"transcript_segments": {"bytesValue": b64}});
+        assert_eq!(compress_legacy_transcript(&compressed), None);
+        let encrypted = json!({"transcript_segments": {"stringValue": "abc"}});
+        assert_eq!(compress_legacy_transcript(&encrypted), None);
+    }
+
+    #[test]
+    fn test_chunk_oversized_transcript() {
+        let small = TranscriptPayload::Compressed(vec![1; 1024]);
+        assert_eq!(chunk_oversized_transcript(&json!({"transcript_segments": small.to_field()})), None);
+
+        let large = TranscriptPayload::Encrypted("a".repeat(TRANSCRIPT_CHUNK_BYTES * 2 + 10));
+        let patch = chunk_oversized_transcript(&json!({"transcript_segments": large.to_field()})).unwrap();
+        assert_eq!(patch.fields["transcript_chunk_count"]["integerValue"], json!("3"));
+        assert_eq!(patch.field_paths, vec!["transcript_segments", "transcript_chunk_count"]);
+        let paths: Vec<&str> = patch.children.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["transcript_chunks/0000", "transcript_chunks/0001", "transcript_chunks/0002"]
+        );
+        let parts: Vec<TranscriptPayload> = patch
+            .children
+            .iter()
+            .map(|(_, child)| TranscriptPayload::from_field(&child["data"]).unwrap())
+            .collect();
+        assert_eq!(TranscriptPayload::join(parts), Some(large));
+
+        // Already chunked: nothing left inline
+        assert_eq!(chunk_oversized_transcript(&patch.fields), None);
+    }
+
+    #[test]
+    fn test_backfill_context_line() {
+        let conversation = json!({
+            "structured": {"mapValue": {"fields": {
+                "title": {"stringValue": "Roadmap sync"},
+                "overview": {"stringValue": "Agreed to ship search first. Pricing moves to Q3."},
+                "emoji": {"stringValue": "🗺️"}
+            }}}
+        });
+        let patch = backfill_context_line(&conversation).unwrap();
+        assert_eq!(
+            patch.fields["context_line"]["stringValue"],
+            json!("🗺️ Roadmap sync - Agreed to ship search first.")
+        );
+
+        let done = json!({"context_line": {"stringValue": "x"}});
+        assert_eq!(backfill_context_line(&done), None);
+        let empty = json!({"structured": {"mapValue": {"fields": {}}}});
+        assert_eq!(backfill_context_line(&empty), None);
+    }
+}
@@ -1,8 +1,64 @@
 // Services module
 
+pub mod accountability;
+pub mod action_item_export;
+pub mod app_moderation;
+pub mod apps_cache;
+pub mod apps_home;
+pub mod auto_discard;
+pub mod badges;
+pub mod conversation_analytics;
+pub mod conversation_calendar;
+pub mod conversation_export;
+pub mod conversation_history;
+pub mod conversation_processing;
+pub mod conversation_templates;
+pub mod data_export;
+pub mod due_dates;
+pub mod events;
 pub mod firestore;
+pub mod firestore_error;
+pub mod firestore_http;
+pub mod firestore_serde;
+pub mod focus_context;
+pub mod folder_rules;
+pub mod goal_progress;
 pub mod integrations;
+pub mod language;
+pub mod llm_keys;
+pub mod llm_quality;
+pub mod local_storage;
+pub mod mailer;
+pub mod memory_review;
+pub mod message_search;
+pub mod migrations;
+pub mod next_action;
+pub mod notifications;
+pub mod notion;
+pub mod people_overview;
+pub mod persona_pages;
+pub mod presence;
+pub mod prioritization;
+pub mod prompt_sandbox;
 pub mod redis;
+pub mod reprocess_all;
+pub mod response_guardrails;
+pub mod retention;
+pub mod rollover;
+pub mod screen_context;
+pub mod self_check;
+pub mod shadow;
+pub mod slack;
+pub mod snooze;
+pub mod storage;
+pub mod timeline;
+pub mod token_refresh;
+pub mod transcript_chunks;
+pub mod transcript_limits;
+pub mod transcript_search;
+pub mod universal_search;
+pub mod uploads;
+pub mod weekly_review;
 
 pub use firestore::FirestoreService;
 pub use integrations::IntegrationService;
@@ -0,0 +1,282 @@
+// Next action - Picks the single open task to do now, with the reasons for the pick
+// Each open task is scored on its due date, priority, relevance rank (from the background
+// scorer), a rough effort estimate and how well it matches what the user is doing now
+// (latest screen snapshot and focus session).
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+use crate::models::screen_activity::ScreenContextSnapshot;
+use crate::models::{ActionItemDB, FocusSessionDB, FocusStatus, NextActionItemResponse};
+use crate::services::FirestoreService;
+
+/// Open tasks considered
+const MAX_CANDIDATES: usize = 300;
+/// Screen snapshots and focus sessions older than this don't describe "now"
+const CURRENT_CONTEXT_MINUTES: i64 = 30;
+/// Context words shorter than this are too common to match on
+const MIN_KEYWORD_CHARS: usize = 4;
+
+/// Words that suggest a task takes a few minutes
+const QUICK_VERBS: &[&str] = &["call", "email", "reply", "send", "text", "book", "pay", "confirm", "remind", "check"];
+/// Words that suggest a task needs a long stretch of focus
+const DEEP_VERBS: &[&str] = &["write", "prepare", "build", "design", "research", "plan", "implement", "review", "draft"];
+
+/// Rough size of a task, from its wording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effort {
+    Quick,
+    Medium,
+    Deep,
+}
+
+/// Estimate effort from the description: quick verbs and short descriptions are quick wins
+pub fn estimate_effort(description: &str) -> Effort {
+    let lower = description.to_lowercase();
+    let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    let first = words.first().copied().unwrap_or_default();
+    if DEEP_VERBS.contains(&first) || words.len() > 15 {
+        Effort::Deep
+    } else if QUICK_VERBS.contains(&first) || words.len() <= 4 {
+        Effort::Quick
+    } else {
+        Effort::Medium
+    }
+}
+
+/// What the user is doing now
+#[derive(Debug, Clone, Default)]
+pub struct CurrentContext {
+    /// Human-readable, e.g. "Xcode — ContentView.swift"
+    pub summary: Option<String>,
+    /// Lowercased words from the app, window title and focus description
+    pub keywords: HashSet<String>,
+    /// The latest focus session says the user is distracted
+    pub distracted: bool,
+}
+
+impl CurrentContext {
+    /// From the latest screen snapshot and focus session, ignoring stale ones
+    pub fn new(screen: Option<&ScreenContextSnapshot>, focus: Option<&FocusSessionDB>, now: DateTime<Utc>) -> Self {
+        let fresh = |at: DateTime<Utc>| now - at <= Duration::minutes(CURRENT_CONTEXT_MINUTES);
+        let screen = screen.filter(|s| fresh(s.captured_at));
+        let focus = focus.filter(|f| fresh(f.created_at));
+
+        let mut text = String::new();
+        if let Some(screen) = screen {
+            text.push_str(&format!("{} {} ", screen.app_name, screen.window_title));
+        }
+        if let Some(focus) = focus {
+            text.push_str(&format!("{} {}", focus.app_or_site, focus.description));
+        }
+        let summary = screen
+            .map(|s| s.summary())
+            .filter(|s| !s.is_empty())
+            .or_else(|| focus.map(|f| f.app_or_site.clone()).filter(|s| !s.is_empty()));
+
+        Self {
+            summary,
+            keywords: keywords(&text),
+            distracted: focus.is_some_and(|f| f.status == FocusStatus::Distracted),
+        }
+    }
+}
+
+fn keywords(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_KEYWORD_CHARS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// A task's score and the reasons that contributed most
+#[derive(Debug, Clone)]
+pub struct Scored {
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+/// Score one open task; higher is more pressing
+pub fn score(item: &ActionItemDB, context: &CurrentContext, now: DateTime<Utc>) -> Scored {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+
+    if let Some(due_at) = item.due_at {
+        let hours = (due_at - now).num_minutes() as f64 / 60.0;
+        let due = if hours < 0.0 {
+            let days = (-hours / 24.0).ceil().max(1.0);
+            reasons.push(format!("overdue by {} day{}", days, if days == 1.0 { "" } else { "s" }));
+            40.0 + days.min(10.0)
+        } else if hours <= 24.0 {
+            reasons.push("due within a day".to_string());
+            30.0
+        } else if hours <= 72.0 {
+            reasons.push("due in the next 3 days".to_string());
+            15.0
+        } else if hours <= 168.0 {
+            5.0
+        } else {
+            0.0
+        };
+        score += due;
+    }
+
+    match item.priority.as_deref() {
+        Some("high") => {
+            score += 25.0;
+            reasons.push("high priority".to_string());
+        }
+        Some("medium") => score += 12.0,
+        Some("low") => {}
+        _ => score += 6.0,
+    }
+
+    // relevance_score is a rank: 1 is the most relevant
+    if let Some(rank) = item.relevance_score.filter(|rank| *rank > 0) {
+        let relevance = (20.0 - (rank - 1) as f64 * 2.0).max(0.0);
+        if rank <= 3 {
+            reasons.push("ranked among your most relevant tasks".to_string());
+        }
+        score += relevance;
+    }
+
+    let effort = estimate_effort(&item.description);
+    match effort {
+        Effort::Quick => {
+            // Quick wins are the way back in when the user is drifting
+            score += if context.distracted { 15.0 } else { 8.0 };
+            reasons.push("looks quick".to_string());
+        }
+        Effort::Medium => {}
+        Effort::Deep if context.distracted => score -= 10.0,
+        Effort::Deep => score -= 3.0,
+    }
+
+    let matches = context.keywords.intersection(&keywords(&item.description)).count();
+    if matches > 0 {
+        score += (matches as f64 * 15.0).min(30.0);
+        let summary = context.summary.as_deref().unwrap_or("what you're working on");
+        reasons.push(format!("matches {}", summary));
+    }
+
+    if item.goal_id.is_some() {
+        score += 5.0;
+        reasons.push("moves a goal forward".to_string());
+    }
+
+    Scored { score, reasons }
+}
+
+/// Best open task, with the reasoning shown to the user. Ties go to the older task.
+pub fn pick_next(items: Vec<ActionItemDB>, context: &CurrentContext, now: DateTime<Utc>) -> NextActionItemResponse {
+    let open: Vec<ActionItemDB> = items
+        .into_iter()
+        .filter(|item| !item.completed && !item.deleted.unwrap_or(false))
+        .filter(|item| item.snoozed_until.is_none_or(|until| until <= now))
+        .collect();
+    let candidates = open.len();
+
+    let best = open
+        .into_iter()
+        .map(|item| {
+            let scored = score(&item, context, now);
+            (item, scored)
+        })
+        .max_by(|(a, a_score), (b, b_score)| {
+            a_score
+                .score
+                .total_cmp(&b_score.score)
+                .then(b.created_at.cmp(&a.created_at))
+        });
+
+    let Some((item, scored)) = best else {
+        return NextActionItemResponse {
+            action_item: None,
+            score: 0.0,
+            reasoning: "No open tasks".to_string(),
+            candidates,
+            current_context: context.summary.clone(),
+        };
+    };
+
+    let reasoning = if scored.reasons.is_empty() {
+        "Best of your open tasks".to_string()
+    } else {
+        let mut reasoning = scored.reasons.join(", ");
+        if let Some(first) = reasoning.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        reasoning
+    };
+    NextActionItemResponse {
+        action_item: Some(item),
+        score: scored.score,
+        reasoning,
+        candidates,
+        current_context: context.summary.clone(),
+    }
+}
+
+/// The task to do now for `uid`, given their latest screen snapshot
+pub async fn recommend_next(
+    firestore: &FirestoreService,
+    uid: &str,
+    screen: Option<ScreenContextSnapshot>,
+) -> Result<NextActionItemResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now();
+    let (items, sessions) = tokio::try_join!(
+        firestore.get_action_items(uid, MAX_CANDIDATES, 0, Some(false), None, None, None, None, None, None, None, false),
+        firestore.get_focus_sessions_between(uid, now - Duration::minutes(CURRENT_CONTEXT_MINUTES), now, 20),
+    )?;
+    let latest_focus = sessions.iter().max_by_key(|s| s.created_at);
+    let context = CurrentContext::new(screen.as_ref(), latest_focus, now);
+    Ok(pick_next(items, &context, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, description: &str, due_in_hours: Option<i64>, priority: Option<&str>) -> ActionItemDB {
+        let now = Utc::now();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "description": description,
+            "completed": false,
+            "created_at": now - Duration::days(1),
+            "due_at": due_in_hours.map(|h| now + Duration::hours(h)),
+            "priority": priority,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pick_prefers_overdue_then_context() {
+        let now = Utc::now();
+        let screen = ScreenContextSnapshot {
+            app_name: "Xcode".to_string(),
+            window_title: "Onboarding.swift".to_string(),
+            ocr_text: String::new(),
+            captured_at: now,
+        };
+        let context = CurrentContext::new(Some(&screen), None, now);
+
+        let items = vec![
+            item("later", "Write the quarterly planning document for the team", Some(200), Some("medium")),
+            item("onboarding", "Fix onboarding crash on launch", None, Some("medium")),
+            item("overdue", "Email Sam the invoice", Some(-30), Some("low")),
+        ];
+        let next = pick_next(items.clone(), &context, now);
+        assert_eq!(next.action_item.unwrap().id, "overdue");
+        assert_eq!(next.reasoning, "Overdue by 2 days, looks quick");
+        assert_eq!(next.candidates, 3);
+
+        let next = pick_next(items[..2].to_vec(), &context, now);
+        assert_eq!(next.action_item.unwrap().id, "onboarding");
+        assert_eq!(next.reasoning, "Matches Xcode — Onboarding.swift");
+
+        assert!(pick_next(vec![], &context, now).action_item.is_none());
+        assert_eq!(estimate_effort("Research vendors"), Effort::Deep);
+    }
+}
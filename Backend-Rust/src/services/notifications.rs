@@ -0,0 +1,78 @@
+// Notifications service - Persists nudges, summaries and integration alerts to the in-app inbox
+// Pushes are ephemeral; the inbox keeps the history the Swift app's notification center shows.
+
+use chrono::{Duration, Utc};
+
+use crate::models::{Notification, NotificationKind};
+use crate::services::FirestoreService;
+
+/// Most notifications kept per user; older ones are trimmed on write
+pub const MAX_STORED_NOTIFICATIONS: usize = 200;
+/// Notifications older than this are trimmed on write
+pub const NOTIFICATION_RETENTION_DAYS: i64 = 90;
+
+/// A new unread notification
+pub fn new_notification(kind: NotificationKind, title: &str, body: &str, link: Option<String>) -> Notification {
+    Notification {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        title: title.trim().to_string(),
+        body: body.trim().to_string(),
+        link,
+        read: false,
+        read_at: None,
+        created_at: Utc::now(),
+    }
+}
+
+/// Add a notification to the user's inbox, then trim the inbox to its retention limits.
+/// Trimming failures are logged; the notification is already stored.
+pub async fn notify(
+    firestore: &FirestoreService,
+    uid: &str,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+    link: Option<String>,
+) -> Result<Notification, Box<dyn std::error::Error + Send + Sync>> {
+    let notification = new_notification(kind, title, body, link);
+    firestore.save_notification(uid, &notification).await?;
+
+    let cutoff = Utc::now() - Duration::days(NOTIFICATION_RETENTION_DAYS);
+    if let Err(e) = firestore
+        .trim_notifications(uid, MAX_STORED_NOTIFICATIONS, cutoff)
+        .await
+    {
+        tracing::warn!("Failed to trim notifications for user {}: {}", uid, e);
+    }
+    Ok(notification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::firestore_serde;
+    use serde_json::json;
+
+    #[test]
+    fn test_notification_document_round_trip() {
+        let notification = new_notification(
+            NotificationKind::IntegrationAlert,
+            " Notion sync failed ",
+            "Reconnect Notion to keep syncing.",
+            Some("/settings/integrations/notion".to_string()),
+        );
+        assert_eq!(notification.title, "Notion sync failed");
+        assert!(!notification.read);
+
+        let fields = firestore_serde::to_fields(&notification, &["id"]).unwrap();
+        assert_eq!(fields["kind"], json!({"stringValue": "integration_alert"}));
+        assert!(fields.get("read_at").is_none());
+
+        let name = format!("projects/p/databases/(default)/documents/users/u1/notifications/{}", notification.id);
+        let back: Notification = firestore_serde::from_document(&json!({"name": name, "fields": fields})).unwrap();
+        assert_eq!(back.id, notification.id);
+        assert_eq!(back.kind, NotificationKind::IntegrationAlert);
+        assert_eq!(back.created_at, notification.created_at);
+    }
+}
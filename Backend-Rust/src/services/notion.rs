@@ -0,0 +1,459 @@
+// Notion integration - OAuth connection and pushing conversations to a database
+// API reference: https://developers.notion.com/reference
+
+use chrono::Utc;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::models::{
+    Conversation, NotionConnection, NotionDatabase, NotionDatabaseProperty, NotionPropertyMapping,
+    NotificationKind, NotionSyncStatus,
+};
+use crate::services::events::{self, AppEvent, EventBus};
+use crate::services::{notifications, FirestoreService};
+
+const NOTION_API_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+/// Notion rejects rich text objects longer than this
+const MAX_RICH_TEXT_CHARS: usize = 2000;
+
+/// Token response from the Notion OAuth exchange
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotionOAuthToken {
+    pub access_token: String,
+    pub workspace_id: String,
+    #[serde(default)]
+    pub workspace_name: Option<String>,
+    pub bot_id: String,
+}
+
+pub struct NotionService {
+    client: Client,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    redirect_uri: Option<String>,
+}
+
+impl NotionService {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("Failed to create HTTP client"),
+            client_id: config.notion_client_id.clone(),
+            client_secret: config.notion_client_secret.clone(),
+            redirect_uri: config.notion_redirect_uri.clone(),
+        }
+    }
+
+    /// Whether OAuth credentials are configured
+    pub fn is_configured(&self) -> bool {
+        self.client_id.is_some() && self.client_secret.is_some()
+    }
+
+    /// Exchange an OAuth authorization code for an access token
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        redirect_uri: Option<&str>,
+    ) -> Result<NotionOAuthToken, Box<dyn std::error::Error + Send + Sync>> {
+        let (client_id, client_secret) = match (&self.client_id, &self.client_secret) {
+            (Some(id), Some(secret)) => (id, secret),
+            _ => return Err("Notion OAuth is not configured".into()),
+        };
+
+        let mut body = json!({
+            "grant_type": "authorization_code",
+            "code": code,
+        });
+        if let Some(uri) = redirect_uri.or(self.redirect_uri.as_deref()) {
+            body["redirect_uri"] = json!(uri);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/oauth/token", NOTION_API_URL))
+            .basic_auth(client_id, Some(client_secret))
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error = response.text().await.unwrap_or_default();
+            return Err(format!("Notion OAuth error: {}", error).into());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Databases shared with the integration
+    pub async fn list_databases(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<NotionDatabase>, Box<dyn std::error::Error + Send + Sync>> {
+        let body = json!({
+            "filter": {"property": "object", "value": "database"},
+            "page_size": 100
+        });
+
+        let result = self.request(reqwest::Method::POST, "search", access_token, Some(body)).await?;
+
+        let databases = result
+            .get("results")
+            .and_then(|r| r.as_array())
+            .map(|results| results.iter().filter_map(parse_database).collect())
+            .unwrap_or_default();
+
+        Ok(databases)
+    }
+
+    /// Create a page for the conversation in the connection's database.
+    /// A previously synced page is archived so re-syncing doesn't duplicate entries.
+    /// Returns (page_id, page_url).
+    pub async fn push_conversation(
+        &self,
+        connection: &NotionConnection,
+        database_id: &str,
+        conversation: &Conversation,
+    ) -> Result<(String, Option<String>), Box<dyn std::error::Error + Send + Sync>> {
+        let body = json!({
+            "parent": {"database_id": database_id},
+            "properties": page_properties(&connection.property_mapping, conversation),
+            "children": page_children(conversation),
+        });
+
+        let page = self
+            .request(reqwest::Method::POST, "pages", &connection.access_token, Some(body))
+            .await?;
+        let page_id = page
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or("Notion response missing page id")?
+            .to_string();
+        let page_url = page.get("url").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        let previous_page = conversation
+            .notion_sync
+            .as_ref()
+            .and_then(|s| s.page_id.as_deref())
+            .filter(|id| *id != page_id);
+        if let Some(previous_page) = previous_page {
+            if let Err(e) = self
+                .request(
+                    reqwest::Method::PATCH,
+                    &format!("pages/{}", previous_page),
+                    &connection.access_token,
+                    Some(json!({"archived": true})),
+                )
+                .await
+            {
+                tracing::warn!("Failed to archive previous Notion page {}: {}", previous_page, e);
+            }
+        }
+
+        Ok((page_id, page_url))
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        access_token: &str,
+        body: Option<Value>,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut request = self
+            .client
+            .request(method, format!("{}/{}", NOTION_API_URL, path))
+            .bearer_auth(access_token)
+            .header("Notion-Version", NOTION_VERSION);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error = response.text().await.unwrap_or_default();
+            return Err(format!("Notion API error {}: {}", status, error).into());
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Push a conversation to the user's Notion database and record the result on the
+/// conversation. Push failures are recorded and returned as a "failed" status;
+/// Err means no push was attempted (not connected, no database, Firestore error).
+pub async fn sync_conversation(
+    firestore: &FirestoreService,
+    notion: &NotionService,
+    uid: &str,
+    conversation: &Conversation,
+) -> Result<NotionSyncStatus, Box<dyn std::error::Error + Send + Sync>> {
+    let connection = firestore
+        .get_notion_connection(uid)
+        .await?
+        .ok_or("Notion is not connected")?;
+    let database_id = connection
+        .database_id
+        .clone()
+        .ok_or("No Notion database selected")?;
+
+    let status = match notion.push_conversation(&connection, &database_id, conversation).await {
+        Ok((page_id, page_url)) => NotionSyncStatus::synced(page_id, page_url),
+        Err(e) => {
+            tracing::error!("Failed to push conversation {} to Notion: {}", conversation.id, e);
+            NotionSyncStatus::failed(e.to_string(), conversation.notion_sync.as_ref())
+        }
+    };
+
+    firestore
+        .set_conversation_notion_sync(uid, &conversation.id, &status)
+        .await?;
+
+    tracing::info!(
+        "Notion sync for conversation {} (user {}): {}",
+        conversation.id,
+        uid,
+        status.status
+    );
+    Ok(status)
+}
+
+/// Push a newly processed conversation if the user enabled automatic sync
+pub async fn auto_sync_conversation(
+    firestore: &FirestoreService,
+    notion: &NotionService,
+    uid: &str,
+    conversation: &Conversation,
+) {
+    match firestore.get_notion_connection(uid).await {
+        Ok(Some(connection)) if connection.auto_sync && connection.database_id.is_some() => {}
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!("Failed to get Notion connection for user {}: {}", uid, e);
+            return;
+        }
+    }
+
+    match sync_conversation(firestore, notion, uid, conversation).await {
+        Ok(status) if status.status == "failed" => {
+            let body = format!(
+                "\"{}\" couldn't be pushed to Notion: {}",
+                conversation.structured.title,
+                status.error.as_deref().unwrap_or("unknown error")
+            );
+            let link = Some(format!("/conversations/{}", conversation.id));
+            if let Err(e) =
+                notifications::notify(firestore, uid, NotificationKind::IntegrationAlert, "Notion sync failed", &body, link).await
+            {
+                tracing::error!("Failed to add Notion alert for user {}: {}", uid, e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!("Notion auto-sync failed for conversation {}: {}", conversation.id, e),
+    }
+}
+
+/// Subscribe automatic Notion sync to new conversations
+pub fn spawn_notion_auto_sync(
+    bus: &EventBus,
+    firestore: Arc<FirestoreService>,
+    notion: Arc<NotionService>,
+) {
+    events::spawn_consumer(bus, "notion", move |event| {
+        let firestore = firestore.clone();
+        let notion = notion.clone();
+        async move {
+            if let AppEvent::ConversationCreated { uid, conversation } = event {
+                auto_sync_conversation(&firestore, &notion, &uid, &conversation).await;
+            }
+        }
+    });
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+fn rich_text(text: &str) -> Value {
+    json!([{"type": "text", "text": {"content": truncate_chars(text, MAX_RICH_TEXT_CHARS)}}])
+}
+
+/// Database property values for a conversation page, per the user's mapping
+fn page_properties(mapping: &NotionPropertyMapping, conversation: &Conversation) -> Value {
+    let s = &conversation.structured;
+    let title = if s.title.trim().is_empty() {
+        "Untitled conversation"
+    } else {
+        s.title.trim()
+    };
+
+    let mut properties = json!({});
+    properties[&mapping.title] = json!({"title": rich_text(title)});
+
+    if let Some(date) = &mapping.date {
+        properties[date] = json!({
+            "date": {
+                "start": conversation.started_at.to_rfc3339(),
+                "end": conversation.finished_at.to_rfc3339()
+            }
+        });
+    }
+    if let Some(summary) = &mapping.summary {
+        properties[summary] = json!({"rich_text": rich_text(s.overview.trim())});
+    }
+    if let Some(category) = &mapping.category {
+        if let Some(name) = serde_json::to_value(&s.category).ok().and_then(|v| v.as_str().map(|s| s.to_string())) {
+            properties[category] = json!({"select": {"name": name}});
+        }
+    }
+    if let Some(open_items) = &mapping.open_action_items {
+        let count = s.action_items.iter().filter(|i| !i.completed).count();
+        properties[open_items] = json!({"number": count});
+    }
+
+    properties
+}
+
+fn heading(text: &str) -> Value {
+    json!({"object": "block", "type": "heading_2", "heading_2": {"rich_text": rich_text(text)}})
+}
+
+/// Page body: overview, action items as to-dos, and a sync footer
+fn page_children(conversation: &Conversation) -> Vec<Value> {
+    let s = &conversation.structured;
+    let mut blocks = vec![];
+
+    if !s.overview.trim().is_empty() {
+        blocks.push(heading("Summary"));
+        blocks.push(json!({
+            "object": "block",
+            "type": "paragraph",
+            "paragraph": {"rich_text": rich_text(s.overview.trim())}
+        }));
+    }
+
+    if !s.action_items.is_empty() {
+        blocks.push(heading("Action Items"));
+        for item in &s.action_items {
+            let text = match item.due_at {
+                Some(due) => format!("{} (due {})", item.description.trim(), due.format("%Y-%m-%d")),
+                None => item.description.trim().to_string(),
+            };
+            blocks.push(json!({
+                "object": "block",
+                "type": "to_do",
+                "to_do": {"rich_text": rich_text(&text), "checked": item.completed}
+            }));
+        }
+    }
+
+    blocks.push(json!({
+        "object": "block",
+        "type": "paragraph",
+        "paragraph": {"rich_text": rich_text(&format!(
+            "Synced from Omi on {}",
+            Utc::now().format("%Y-%m-%d %H:%M UTC")
+        ))}
+    }));
+
+    // Notion accepts at most 100 blocks per request
+    blocks.truncate(100);
+    blocks
+}
+
+fn parse_database(value: &Value) -> Option<NotionDatabase> {
+    let id = value.get("id")?.as_str()?.to_string();
+    let title = value
+        .get("title")
+        .and_then(|t| t.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|p| p.get("plain_text").and_then(|t| t.as_str()))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    let mut properties: Vec<NotionDatabaseProperty> = value
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .map(|props| {
+            props
+                .iter()
+                .map(|(name, prop)| NotionDatabaseProperty {
+                    name: name.clone(),
+                    property_type: prop
+                        .get("type")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Some(NotionDatabase {
+        id,
+        title,
+        url: value.get("url").and_then(|u| u.as_str()).map(|s| s.to_string()),
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Conversation {
+        let now = Utc::now();
+        serde_json::from_value(json!({
+            "id": "c1",
+            "created_at": now,
+            "started_at": now,
+            "finished_at": now,
+            "structured": {
+                "title": "Roadmap sync",
+                "overview": "Agreed on Q3 priorities.",
+                "category": "work",
+                "action_items": [
+                    {"description": "Send notes", "due_at": null},
+                    {"description": "Book room", "completed": true, "due_at": null}
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_page_properties_follow_mapping() {
+        let mapping = NotionPropertyMapping {
+            title: "Meeting".to_string(),
+            summary: Some("Notes".to_string()),
+            category: Some("Type".to_string()),
+            open_action_items: Some("Open".to_string()),
+            date: None,
+        };
+        let props = page_properties(&mapping, &conversation());
+        assert_eq!(props["Meeting"]["title"][0]["text"]["content"], "Roadmap sync");
+        assert_eq!(props["Notes"]["rich_text"][0]["text"]["content"], "Agreed on Q3 priorities.");
+        assert_eq!(props["Type"]["select"]["name"], "work");
+        assert_eq!(props["Open"]["number"], 1);
+        assert!(props.get("Date").is_none());
+    }
+
+    #[test]
+    fn test_page_children_include_action_items_as_todos() {
+        let blocks = page_children(&conversation());
+        let todos: Vec<&Value> = blocks.iter().filter(|b| b["type"] == "to_do").collect();
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[1]["to_do"]["checked"], true);
+    }
+}
@@ -0,0 +1,91 @@
+// People overview - Aggregates a person's conversations and shared tasks
+// Conversations are matched by segments assigned to the person; tasks by conversation or by name.
+
+use std::collections::HashSet;
+
+use crate::models::{ActionItemDB, Conversation, Person, PersonConversation, PersonOverview};
+
+/// Conversations the person spoke in, newest first, with their talk time
+pub fn person_conversations(person_id: &str, conversations: &[Conversation]) -> Vec<PersonConversation> {
+    let mut matched: Vec<PersonConversation> = conversations
+        .iter()
+        .filter_map(|c| {
+            let segments: Vec<_> = c
+                .transcript_segments
+                .iter()
+                .filter(|s| s.person_id.as_deref() == Some(person_id))
+                .collect();
+            if segments.is_empty() {
+                return None;
+            }
+            Some(PersonConversation {
+                id: c.id.clone(),
+                title: c.structured.title.clone(),
+                emoji: c.structured.emoji.clone(),
+                overview: c.structured.overview.clone(),
+                started_at: c.started_at,
+                talk_time_seconds: segments.iter().map(|s| (s.end - s.start).max(0.0)).sum(),
+            })
+        })
+        .collect();
+    matched.sort_by_key(|c| std::cmp::Reverse(c.started_at));
+    matched
+}
+
+/// Whether the description mentions the name as a whole word, case-insensitively
+fn mentions_name(description: &str, name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+    let description = description.to_lowercase();
+    description.match_indices(&name).any(|(start, _)| {
+        let before = description[..start].chars().next_back();
+        let after = description[start + name.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Build the overview from the person's conversations and the user's action items
+pub fn build_overview(
+    person: Person,
+    conversations: Vec<PersonConversation>,
+    action_items: Vec<ActionItemDB>,
+    relationship_notes: Vec<String>,
+) -> PersonOverview {
+    let conversation_ids: HashSet<&str> = conversations.iter().map(|c| c.id.as_str()).collect();
+    let action_items: Vec<ActionItemDB> = action_items
+        .into_iter()
+        .filter(|item| item.deleted != Some(true))
+        .filter(|item| {
+            item.conversation_id
+                .as_deref()
+                .is_some_and(|id| conversation_ids.contains(id))
+                || mentions_name(&item.description, &person.name)
+        })
+        .collect();
+
+    PersonOverview {
+        conversation_count: conversations.len(),
+        first_seen_at: conversations.last().map(|c| c.started_at),
+        last_seen_at: conversations.first().map(|c| c.started_at),
+        total_talk_time_seconds: conversations.iter().map(|c| c.talk_time_seconds).sum(),
+        person,
+        conversations,
+        action_items,
+        relationship_notes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentions_name() {
+        assert!(mentions_name("Send Ana the Q3 roadmap", "Ana"));
+        assert!(mentions_name("Follow up with ana.", "Ana"));
+        assert!(!mentions_name("Review the analytics dashboard", "Ana"));
+        assert!(!mentions_name("Call the bank", " "));
+    }
+}
@@ -0,0 +1,154 @@
+// Persona pages - Public profiles served at /u/:username
+// Built from the persona document and the owner's public memories only; the HTML is minimal
+// and fully escaped.
+
+use crate::models::{MemoryDB, PersonaDB, PublicPersonaMemory, PublicPersonaProfile, MAX_PUBLIC_PERSONA_MEMORIES};
+use crate::services::{FirestoreService, RedisService};
+
+/// Public profile for a persona; None for private personas or ones without a username
+pub fn build_profile(persona: PersonaDB, memories: Vec<MemoryDB>) -> Option<PublicPersonaProfile> {
+    if persona.is_private {
+        return None;
+    }
+    Some(PublicPersonaProfile {
+        username: persona.username?,
+        name: persona.name,
+        description: persona.description,
+        image: persona.image,
+        author: persona.author,
+        memories: memories
+            .into_iter()
+            .filter(|m| m.visibility == "public")
+            .take(MAX_PUBLIC_PERSONA_MEMORIES)
+            .map(|m| PublicPersonaMemory {
+                content: m.content,
+                category: m.category,
+                created_at: m.created_at,
+            })
+            .collect(),
+        noindex: persona.noindex,
+        updated_at: persona.updated_at,
+    })
+}
+
+/// Robots directive for a profile, sent both as the `x-robots-tag` header and the meta tag
+pub fn robots_directive(profile: &PublicPersonaProfile) -> &'static str {
+    if profile.noindex {
+        "noindex, nofollow"
+    } else {
+        "index, follow"
+    }
+}
+
+/// Drop the cached public page of a persona after it changes
+pub async fn invalidate(redis: Option<&RedisService>, persona: &PersonaDB) {
+    let (Some(redis), Some(username)) = (redis, &persona.username) else { return };
+    if let Err(e) = redis.delete_public_persona(username).await {
+        tracing::warn!("Failed to invalidate public persona {}: {}", username, e);
+    }
+}
+
+/// Drop the cached public page of a user's persona after their memories change, so a
+/// memory made private or deleted stops showing right away
+pub async fn invalidate_for_user(firestore: &FirestoreService, redis: Option<&RedisService>, uid: &str) {
+    if redis.is_none() {
+        return;
+    }
+    match firestore.get_user_persona(uid).await {
+        Ok(Some(persona)) => invalidate(redis, &persona).await,
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load persona of user {} to invalidate its page: {}", uid, e),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Minimal standalone HTML page for a profile
+pub fn render_html(profile: &PublicPersonaProfile) -> String {
+    let name = escape_html(&profile.name);
+    let description = escape_html(&profile.description);
+    let robots = robots_directive(profile);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<meta name=\"robots\" content=\"{}\">\n", robots));
+    html.push_str(&format!("<title>{} (@{}) on Omi</title>\n", name, escape_html(&profile.username)));
+    html.push_str(&format!("<meta property=\"og:title\" content=\"{}\">\n", name));
+    html.push_str(&format!("<meta property=\"og:description\" content=\"{}\">\n", description));
+    if profile.image.starts_with("https://") {
+        html.push_str(&format!("<meta property=\"og:image\" content=\"{}\">\n", escape_html(&profile.image)));
+    }
+    html.push_str("</head>\n<body>\n");
+    if profile.image.starts_with("https://") {
+        html.push_str(&format!(
+            "<img src=\"{}\" alt=\"{}\" width=\"96\" height=\"96\">\n",
+            escape_html(&profile.image),
+            name
+        ));
+    }
+    html.push_str(&format!("<h1>{}</h1>\n", name));
+    html.push_str(&format!("<p>@{}</p>\n", escape_html(&profile.username)));
+    if !profile.description.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", description));
+    }
+    if !profile.memories.is_empty() {
+        html.push_str("<h2>About</h2>\n<ul>\n");
+        for memory in &profile.memories {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(&memory.content)));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn profile(noindex: bool) -> PublicPersonaProfile {
+        PublicPersonaProfile {
+            username: "ada".to_string(),
+            name: "Ada <script>".to_string(),
+            description: "Builds \"engines\" & more".to_string(),
+            image: "javascript:alert(1)".to_string(),
+            author: "Ada".to_string(),
+            memories: vec![PublicPersonaMemory {
+                content: "Likes <b>tea</b>".to_string(),
+                category: Default::default(),
+                created_at: Utc::now(),
+            }],
+            noindex,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_html_escapes_and_respects_noindex() {
+        let html = render_html(&profile(true));
+        assert!(html.contains("<h1>Ada &lt;script&gt;</h1>"));
+        assert!(html.contains("Builds &quot;engines&quot; &amp; more"));
+        assert!(html.contains("<li>Likes &lt;b&gt;tea&lt;/b&gt;</li>"));
+        assert!(html.contains("content=\"noindex, nofollow\""));
+        assert!(!html.contains("javascript:"));
+
+        assert!(render_html(&profile(false)).contains("content=\"index, follow\""));
+        // The header and the meta tag carry the same directive
+        assert_eq!(robots_directive(&profile(false)), "index, follow");
+    }
+}
@@ -0,0 +1,136 @@
+// Presence store - Short-lived per-device state, fed by POST /v1/presence heartbeats
+// In memory like the screen context buffer; a device disappears when its heartbeats stop.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::{DevicePresence, PresenceState};
+
+/// A device without a heartbeat for this long is considered gone
+pub const PRESENCE_TTL_SECS: i64 = 90;
+/// Max devices tracked per user; the least recently seen is dropped
+const MAX_DEVICES_PER_USER: usize = 20;
+
+/// In-memory device presence per user
+#[derive(Clone, Default)]
+pub struct PresenceStore {
+    inner: Arc<RwLock<HashMap<String, HashMap<String, DevicePresence>>>>,
+}
+
+impl PresenceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a heartbeat, replacing the device's previous state
+    pub async fn heartbeat(
+        &self,
+        uid: &str,
+        device_id: &str,
+        state: PresenceState,
+        device_name: Option<String>,
+        platform: Option<String>,
+        ip_address: Option<String>,
+    ) -> DevicePresence {
+        let now = Utc::now();
+        let presence = DevicePresence {
+            device_id: device_id.to_string(),
+            state,
+            device_name,
+            platform,
+            ip_address,
+            updated_at: now,
+            expires_at: now + Duration::seconds(PRESENCE_TTL_SECS),
+        };
+        let mut map = self.inner.write().await;
+        record(map.entry(uid.to_string()).or_default(), presence.clone(), now);
+        presence
+    }
+
+    /// Devices with an unexpired heartbeat, most recently seen first
+    pub async fn devices(&self, uid: &str) -> Vec<DevicePresence> {
+        let now = Utc::now();
+        let map = self.inner.read().await;
+        let mut devices: Vec<DevicePresence> = map
+            .get(uid)
+            .map(|devices| devices.values().filter(|d| d.expires_at > now).cloned().collect())
+            .unwrap_or_default();
+        devices.sort_by_key(|d| std::cmp::Reverse(d.updated_at));
+        devices
+    }
+
+    /// Drop expired devices and users with nothing left
+    pub async fn prune(&self) {
+        let now = Utc::now();
+        let mut map = self.inner.write().await;
+        map.retain(|_, devices| {
+            devices.retain(|_, d| d.expires_at > now);
+            !devices.is_empty()
+        });
+    }
+}
+
+/// Store a device's presence, dropping expired devices and capping the count
+fn record(devices: &mut HashMap<String, DevicePresence>, presence: DevicePresence, now: DateTime<Utc>) {
+    devices.retain(|_, d| d.expires_at > now);
+    devices.insert(presence.device_id.clone(), presence);
+    while devices.len() > MAX_DEVICES_PER_USER {
+        let Some(oldest) = devices
+            .values()
+            .min_by_key(|d| d.updated_at)
+            .map(|d| d.device_id.clone())
+        else {
+            break;
+        };
+        devices.remove(&oldest);
+    }
+}
+
+/// Periodically drop expired devices so idle users don't hold memory
+pub fn spawn_presence_pruner(store: PresenceStore) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(PRESENCE_TTL_SECS as u64));
+        loop {
+            interval.tick().await;
+            store.prune().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presence(device_id: &str, updated_at: DateTime<Utc>) -> DevicePresence {
+        DevicePresence {
+            device_id: device_id.to_string(),
+            state: PresenceState::Recording,
+            device_name: None,
+            platform: None,
+            ip_address: None,
+            updated_at,
+            expires_at: updated_at + Duration::seconds(PRESENCE_TTL_SECS),
+        }
+    }
+
+    #[test]
+    fn test_record_replaces_expires_and_caps() {
+        let now = Utc::now();
+        let mut devices = HashMap::new();
+        record(&mut devices, presence("stale", now - Duration::seconds(PRESENCE_TTL_SECS + 1)), now);
+        record(&mut devices, presence("mac", now - Duration::seconds(10)), now);
+        assert_eq!(devices.len(), 1);
+        assert!(devices.contains_key("mac"));
+
+        record(&mut devices, presence("mac", now), now);
+        assert_eq!(devices["mac"].updated_at, now);
+
+        for i in 0..MAX_DEVICES_PER_USER {
+            record(&mut devices, presence(&format!("phone-{}", i), now), now);
+        }
+        assert_eq!(devices.len(), MAX_DEVICES_PER_USER);
+    }
+}
@@ -0,0 +1,180 @@
+// Prioritization service - background LLM scoring of pending action items
+// Fills relevance_score (rank, lower = more relevant) and missing priorities
+
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::job_queue::JobQueue;
+use crate::llm::client::{ActionItemScore, ActionItemScoringInput};
+use crate::llm::LlmClient;
+use crate::services::{llm_keys, FirestoreService};
+
+/// Only users who created action items in this window are scored
+const ACTIVE_USER_WINDOW_DAYS: i64 = 7;
+/// Upper bound on users scored per run
+const MAX_USERS_PER_RUN: usize = 500;
+/// Pending action items sent to the LLM per user (one call per user)
+const MAX_ITEMS_PER_USER: usize = 100;
+
+/// Spawn the periodic action item scorer. No-op when disabled or without a Gemini key.
+pub fn spawn_action_item_scorer(firestore: Arc<FirestoreService>, config: Arc<Config>, jobs: JobQueue) {
+    let interval_minutes = config.action_item_scoring_interval_minutes;
+    let Some(api_key) = config.gemini_api_key.clone() else {
+        tracing::warn!("GEMINI_API_KEY not set - action item scoring disabled");
+        return;
+    };
+    if interval_minutes == 0 {
+        tracing::info!("Action item scoring disabled (ACTION_ITEM_SCORING_INTERVAL_MINUTES=0)");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_minutes * 60));
+        // Don't hammer the LLM on startup; first run after one full interval
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            run_scoring_pass(&firestore, &config, &jobs, &api_key).await;
+        }
+    });
+
+    tracing::info!("Action item scorer scheduled every {} minutes", interval_minutes);
+}
+
+/// Queue a background scoring job for every recently active user. Users who brought
+/// their own LLM key are scored with it.
+async fn run_scoring_pass(firestore: &Arc<FirestoreService>, config: &Config, jobs: &JobQueue, api_key: &str) {
+    let since = Utc::now() - Duration::days(ACTIVE_USER_WINDOW_DAYS);
+    let uids = match firestore.get_users_with_recent_action_items(since, MAX_USERS_PER_RUN).await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Action item scoring: failed to list active users: {}", e);
+            return;
+        }
+    };
+
+    tracing::info!("Action item scoring: {} active users", uids.len());
+    let models = firestore.llm_task_models(&config.llm_models).await;
+    let llm = LlmClient::new(api_key.to_string()).with_task_models(models.clone());
+
+    for uid in uids {
+        let (firestore, llm, models) = (firestore.clone(), llm.clone(), models.clone());
+        jobs.submit_for_user(&uid, "action-item-scoring", |uid| async move {
+            let user_llm = llm_keys::user_client(&firestore, &uid, &models).await.map_err(|e| e.0)?;
+            let llm = user_llm.as_ref().unwrap_or(&llm);
+            score_user_action_items(&firestore, llm, &uid).await
+        });
+    }
+}
+
+/// Score one user's pending action items with a single batched LLM call
+pub async fn score_user_action_items(
+    firestore: &FirestoreService,
+    llm: &LlmClient,
+    uid: &str,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let items: Vec<_> = firestore
+        .get_action_items(uid, MAX_ITEMS_PER_USER, 0, Some(false), None, None, None, None, None, None, None, true)
+        .await?
+        .into_iter()
+        .filter(|item| !item.deleted.unwrap_or(false))
+        .collect();
+
+    if items.is_empty() {
+        return Ok(0);
+    }
+
+    let goals: Vec<String> = firestore
+        .get_user_goals(uid, 10)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|g| match g.description {
+            Some(desc) if !desc.is_empty() => format!("{} ({})", g.title, desc),
+            _ => g.title,
+        })
+        .collect();
+
+    let recent_activity: Vec<String> = firestore
+        .get_conversations(uid, 10, 0, false, &[], None, None, None, None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| c.structured.title)
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let inputs: Vec<_> = items
+        .iter()
+        .map(|item| ActionItemScoringInput {
+            id: item.id.clone(),
+            description: item.description.clone(),
+            due_at: item.due_at,
+            latest_note: item.latest_note.clone(),
+        })
+        .collect();
+
+    let scores = llm.score_action_items(&goals, &recent_activity, &inputs).await?;
+    if scores.is_empty() {
+        return Ok(0);
+    }
+
+    firestore.batch_update_scores(uid, &rank_by_relevance(&scores)).await?;
+
+    // Only fill priorities the user (or extraction) hasn't set
+    let priorities: Vec<(String, String)> = scores
+        .iter()
+        .filter(|s| matches!(s.priority.as_str(), "high" | "medium" | "low"))
+        .filter(|s| {
+            items
+                .iter()
+                .any(|item| item.id == s.id && item.priority.as_deref().is_none_or(str::is_empty))
+        })
+        .map(|s| (s.id.clone(), s.priority.clone()))
+        .collect();
+    if !priorities.is_empty() {
+        firestore.batch_update_priorities(uid, &priorities).await?;
+    }
+
+    Ok(scores.len())
+}
+
+/// Convert 0-100 relevance into relevance_score ranks (1 = most relevant).
+/// Ties keep the model's order.
+pub fn rank_by_relevance(scores: &[ActionItemScore]) -> Vec<(String, i32)> {
+    let mut sorted: Vec<&ActionItemScore> = scores.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.relevance));
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.clone(), i as i32 + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(id: &str, relevance: i32) -> ActionItemScore {
+        ActionItemScore {
+            id: id.to_string(),
+            relevance,
+            priority: "medium".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rank_by_relevance_orders_highest_first() {
+        let scores = vec![score("a", 20), score("b", 90), score("c", 20), score("d", 55)];
+        assert_eq!(
+            rank_by_relevance(&scores),
+            vec![
+                ("b".to_string(), 1),
+                ("d".to_string(), 2),
+                ("a".to_string(), 3),
+                ("c".to_string(), 4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Prompt sandbox - Lets app developers try a memory or chat prompt before submitting
+// Prompts only ever run against a built-in synthetic transcript or one the developer sends;
+// no stored conversation is read.
+
+use crate::llm::client::{memory_prompt_input, TokenUsage};
+use crate::llm::prompts::delimit_untrusted;
+use crate::llm::{LlmClient, TaskKind};
+use crate::models::{Category, PromptTestKind, Structured, TestPromptRequest};
+
+/// Synthetic conversation used when the developer doesn't provide one
+pub const SAMPLE_TRANSCRIPT: &str = "\
+User: Morning! Did you get a chance to look at the launch checklist?
+Speaker 1: Yes, most of it is done. The pricing page still needs final copy.
+User: Okay. I'll write the copy today and send it to Priya for review by Thursday.
+Speaker 1: Great. Also, the beta users asked for a dark mode again.
+User: Let's add it to the roadmap for next quarter. Can you book a call with the design team next week?
+Speaker 1: Sure, I'll set it up for Tuesday at 2pm.
+User: Perfect. And remind me to renew the domain before the end of the month.";
+/// Question used for chat prompts when the developer doesn't provide one
+pub const SAMPLE_QUESTION: &str = "What should I focus on this week?";
+
+/// The model input for a sandbox run: (task, prompt, whether the sample is synthetic)
+pub fn build_input(request: &TestPromptRequest) -> (TaskKind, String, bool) {
+    let provided = request.transcript.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let transcript = provided.unwrap_or(SAMPLE_TRANSCRIPT);
+
+    let input = match request.kind {
+        PromptTestKind::Memory => {
+            let structured = Structured {
+                title: "Sample conversation".to_string(),
+                overview: String::new(),
+                emoji: "🧪".to_string(),
+                category: Category::Work,
+                action_items: vec![],
+                events: vec![],
+            };
+            (TaskKind::Summary, memory_prompt_input(&request.prompt, transcript, &structured))
+        }
+        PromptTestKind::Chat => {
+            let question = request.question.as_deref().map(str::trim).filter(|q| !q.is_empty());
+            let prompt = format!(
+                "<app_context>\nInstructions: {}\n</app_context>\n\nRecent conversation:\n{}\n\nUser question: {}",
+                request.prompt,
+                delimit_untrusted(transcript),
+                question.unwrap_or(SAMPLE_QUESTION)
+            );
+            (TaskKind::Chat, prompt)
+        }
+    };
+    (input.0, input.1, provided.is_none())
+}
+
+/// Run the prompt; returns (result, model, usage)
+pub async fn run(
+    llm: &LlmClient,
+    task: TaskKind,
+    input: &str,
+) -> Result<(String, String, TokenUsage), Box<dyn std::error::Error + Send + Sync>> {
+    let (result, usage) = llm.call_text_with_usage(task, input, Some(0.7), Some(2000)).await?;
+    Ok((result, llm.model_for(task).to_string(), usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_input() {
+        let request = |kind, transcript: Option<&str>| TestPromptRequest {
+            kind,
+            prompt: "List every deadline mentioned.".to_string(),
+            transcript: transcript.map(str::to_string),
+            question: None,
+        };
+
+        let (task, input, synthetic) = build_input(&request(PromptTestKind::Memory, None));
+        assert_eq!(task, TaskKind::Summary);
+        assert!(synthetic);
+        assert!(input.starts_with("List every deadline mentioned."));
+        assert!(input.contains("renew the domain"));
+
+        let (task, input, synthetic) =
+            build_input(&request(PromptTestKind::Chat, Some("User: Ship the beta on Friday.")));
+        assert_eq!(task, TaskKind::Chat);
+        assert!(!synthetic);
+        assert!(input.contains("Ship the beta on Friday."));
+        assert!(!input.contains("renew the domain"));
+        assert!(input.ends_with(SAMPLE_QUESTION));
+
+        // A blank transcript falls back to the synthetic one
+        assert!(build_input(&request(PromptTestKind::Memory, Some("  "))).2);
+    }
+}
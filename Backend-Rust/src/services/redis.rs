@@ -5,6 +5,10 @@ use redis::{AsyncCommands, Client, ConnectionAddr, ConnectionInfo, RedisConnecti
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::llm::chunking::ProcessingProgress;
+use crate::models::{BadgeCounts, PublicPersonaProfile};
+use crate::services::reprocess_all::ReprocessAllProgress;
+
 /// Redis service for conversation visibility and sharing
 pub struct RedisService {
     client: Client,
@@ -146,6 +150,145 @@ impl RedisService {
         Ok(pong == "PONG")
     }
 
+    // ============================================================================
+    // CONVERSATION PROCESSING PROGRESS
+    // ============================================================================
+
+    /// Store the user's in-flight long-transcript processing progress (10-minute TTL)
+    /// Key format: conversation-processing:{uid}
+    pub async fn store_processing_progress(
+        &self,
+        uid: &str,
+        progress: &ProcessingProgress,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("conversation-processing:{}", uid);
+        let value = serde_json::to_string(progress).unwrap_or_default();
+        let _: () = conn.set_ex(&key, value, 10 * 60).await?;
+        Ok(())
+    }
+
+    /// Get the user's in-flight processing progress (None when nothing is running)
+    pub async fn get_processing_progress(
+        &self,
+        uid: &str,
+    ) -> Result<Option<ProcessingProgress>, redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("conversation-processing:{}", uid);
+        let raw: Option<String> = conn.get(&key).await?;
+        Ok(raw.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    /// Clear the user's processing progress once processing finishes
+    pub async fn clear_processing_progress(&self, uid: &str) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("conversation-processing:{}", uid);
+        let _: () = conn.del(&key).await?;
+        Ok(())
+    }
+
+    /// Store a reprocess-all run's progress, one hash field per conversation (1-hour TTL
+    /// on the whole hash, refreshed on each update)
+    /// Key format: conversation-reprocess:{uid}
+    pub async fn store_reprocess_progress(
+        &self,
+        uid: &str,
+        progress: &ReprocessAllProgress,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("conversation-reprocess:{}", uid);
+        let value = serde_json::to_string(progress).unwrap_or_default();
+        let _: () = conn.hset(&key, &progress.conversation_id, value).await?;
+        let _: () = conn.expire(&key, 60 * 60).await?;
+        Ok(())
+    }
+
+    /// The user's recent reprocess-all runs, newest first
+    pub async fn get_reprocess_progress(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<ReprocessAllProgress>, redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("conversation-reprocess:{}", uid);
+        let raw: Vec<String> = conn.hvals(&key).await?;
+        let mut runs: Vec<ReprocessAllProgress> =
+            raw.iter().filter_map(|data| serde_json::from_str(data).ok()).collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        Ok(runs)
+    }
+
+    // ============================================================================
+    // PUBLIC PERSONA PAGES
+    // ============================================================================
+
+    /// Cache a public persona profile (5-minute TTL)
+    /// Key format: public_persona:{username}
+    pub async fn store_public_persona(&self, profile: &PublicPersonaProfile) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("public_persona:{}", profile.username);
+        let value = serde_json::to_string(profile).unwrap_or_default();
+        let _: () = conn.set_ex(&key, value, 300).await?;
+        Ok(())
+    }
+
+    /// Cached public persona profile, if still fresh
+    pub async fn get_public_persona(&self, username: &str) -> Result<Option<PublicPersonaProfile>, redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("public_persona:{}", username);
+        let raw: Option<String> = conn.get(&key).await?;
+        Ok(raw.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    /// Drop a cached public persona profile after the persona changes
+    pub async fn delete_public_persona(&self, username: &str) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let _: () = conn.del(format!("public_persona:{}", username)).await?;
+        Ok(())
+    }
+
+    // ============================================================================
+    // BADGES
+    // ============================================================================
+
+    /// Cache the user's badge counts for one timezone (30-second TTL)
+    /// Key format: badges:{uid}:{timezone}
+    pub async fn store_badges(
+        &self,
+        uid: &str,
+        timezone: &str,
+        badges: &BadgeCounts,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("badges:{}:{}", uid, timezone);
+        let value = serde_json::to_string(badges).unwrap_or_default();
+        let _: () = conn.set_ex(&key, value, 30).await?;
+        Ok(())
+    }
+
+    /// Cached badge counts, if still fresh
+    pub async fn get_badges(&self, uid: &str, timezone: &str) -> Result<Option<BadgeCounts>, redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("badges:{}:{}", uid, timezone);
+        let raw: Option<String> = conn.get(&key).await?;
+        Ok(raw.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    // ============================================================================
+    // PROMPT SANDBOX
+    // ============================================================================
+
+    /// Count a prompt sandbox run and return the developer's runs in the current hour
+    /// Key format: prompt_tests:{uid}:{YYYYMMDDHH}
+    pub async fn increment_prompt_tests(&self, uid: &str, hour: &str) -> Result<u32, redis::RedisError> {
+        let mut conn = self.get_connection().await?;
+        let key = format!("prompt_tests:{}:{}", uid, hour);
+        let count: u32 = conn.incr(&key, 1).await?;
+        if count == 1 {
+            let _: () = conn.expire(&key, 60 * 60).await?;
+        }
+        Ok(count)
+    }
+
     // ============================================================================
     // TASK SHARING
     // ============================================================================
@@ -0,0 +1,237 @@
+// Reprocess all - Runs every enabled memories app over a conversation in the background
+// Per-app status is kept in Redis and reported by GET /v1/conversations/processing-status.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{App, AppResult, AppScope, Conversation};
+use crate::services::events::AppEvent;
+use crate::AppState;
+
+/// Prompt used for apps without a memory prompt (as in single-app reprocessing)
+pub const DEFAULT_MEMORY_PROMPT: &str = "Analyze this conversation and provide insights.";
+/// Characters of an app result sent in its completion event
+const RESULT_PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppReprocessState {
+    Queued,
+    Running,
+    Completed,
+    /// Not run: the conversation already has this app's result, or the app is blocked
+    /// or wasn't granted read:conversations
+    Skipped,
+    Failed,
+}
+
+/// Where one app is in a reprocess-all run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppReprocessStatus {
+    pub app_id: String,
+    pub app_name: String,
+    pub state: AppReprocessState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Progress of reprocessing one conversation with all of the user's apps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReprocessAllProgress {
+    pub conversation_id: String,
+    pub apps: Vec<AppReprocessStatus>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReprocessAllProgress {
+    /// Whether every app has completed, failed or been skipped
+    pub fn is_finished(&self) -> bool {
+        self.apps
+            .iter()
+            .all(|a| !matches!(a.state, AppReprocessState::Queued | AppReprocessState::Running))
+    }
+}
+
+/// Statuses for a new run: one per distinct memories-capable app, already skipped
+/// when the conversation has that app's result
+pub fn plan(apps: &[App], existing: &[AppResult]) -> Vec<AppReprocessStatus> {
+    let mut planned: Vec<AppReprocessStatus> = Vec::new();
+    for app in apps.iter().filter(|a| a.capabilities.iter().any(|c| c == "memories")) {
+        if planned.iter().any(|p| p.app_id == app.id) {
+            continue;
+        }
+        let has_result = existing.iter().any(|r| r.app_id.as_deref() == Some(app.id.as_str()));
+        planned.push(AppReprocessStatus {
+            app_id: app.id.clone(),
+            app_name: app.name.clone(),
+            state: if has_result { AppReprocessState::Skipped } else { AppReprocessState::Queued },
+            detail: has_result.then(|| "Already has a result".to_string()),
+        });
+    }
+    planned
+}
+
+/// Transcript handed to app memory prompts, with the user's own lines under their name
+pub fn memory_prompt_transcript(conversation: &Conversation, user_name: Option<&str>) -> String {
+    conversation
+        .transcript_segments
+        .iter()
+        .map(|s| {
+            let speaker = if s.is_user {
+                user_name.unwrap_or("User").to_string()
+            } else {
+                format!("Speaker {}", s.speaker_id)
+            };
+            format!("{}: {}", speaker, s.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Announce a saved app result on the event bus, so clients can show it before the
+/// other apps finish
+pub fn publish_result(state: &AppState, uid: &str, conversation_id: &str, app_id: &str, content: &str) {
+    let mut preview: String = content.trim().chars().take(RESULT_PREVIEW_CHARS).collect();
+    if content.trim().chars().count() > RESULT_PREVIEW_CHARS {
+        preview.push_str("...");
+    }
+    state.events.publish(AppEvent::AppResultCompleted {
+        uid: uid.to_string(),
+        conversation_id: conversation_id.to_string(),
+        app_id: app_id.to_string(),
+        preview,
+    });
+}
+
+async fn store(state: &AppState, uid: &str, progress: &mut ReprocessAllProgress) {
+    progress.updated_at = Utc::now();
+    if let Some(redis) = &state.redis {
+        if let Err(e) = redis.store_reprocess_progress(uid, progress).await {
+            tracing::warn!("Failed to store reprocess progress: {}", e);
+        }
+    }
+}
+
+/// Run the queued apps one after another, saving each result and the progress as it goes
+pub async fn run(
+    state: AppState,
+    uid: String,
+    user_name: Option<String>,
+    conversation: Conversation,
+    apps: Vec<App>,
+    mut progress: ReprocessAllProgress,
+) {
+    let detail = match state.llm_client(&uid).await {
+        Ok(Some(llm)) => Ok(llm),
+        Ok(None) => Err("No LLM key configured".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let llm = match detail {
+        Ok(llm) => llm,
+        Err(detail) => {
+            for status in progress.apps.iter_mut().filter(|a| a.state == AppReprocessState::Queued) {
+                status.state = AppReprocessState::Failed;
+                status.detail = Some(detail.clone());
+            }
+            store(&state, &uid, &mut progress).await;
+            return;
+        }
+    };
+    let transcript = memory_prompt_transcript(&conversation, user_name.as_deref());
+
+    for i in 0..progress.apps.len() {
+        if progress.apps[i].state != AppReprocessState::Queued {
+            continue;
+        }
+        let Some(app) = apps.iter().find(|a| a.id == progress.apps[i].app_id) else {
+            continue;
+        };
+        if state.app_blocked(app).await {
+            progress.apps[i].state = AppReprocessState::Skipped;
+            progress.apps[i].detail = Some("Blocked by moderation".to_string());
+            store(&state, &uid, &mut progress).await;
+            continue;
+        }
+        if !app.has_scope(AppScope::ReadConversations) {
+            progress.apps[i].state = AppReprocessState::Skipped;
+            progress.apps[i].detail = Some("Not granted read:conversations".to_string());
+            store(&state, &uid, &mut progress).await;
+            continue;
+        }
+        progress.apps[i].state = AppReprocessState::Running;
+        store(&state, &uid, &mut progress).await;
+
+        let prompt = app.memory_prompt.as_deref().unwrap_or(DEFAULT_MEMORY_PROMPT);
+        let outcome = match llm.run_memory_prompt(prompt, &transcript, &conversation.structured).await {
+            Ok(result) => state
+                .firestore
+                .add_app_result(&uid, &conversation.id, &app.id, &result)
+                .await
+                .map(|()| publish_result(&state, &uid, &conversation.id, &app.id, &result))
+                .map_err(|e| format!("Failed to save result: {}", e)),
+            Err(e) => Err(format!("Failed to process: {}", e)),
+        };
+        match outcome {
+            Ok(()) => progress.apps[i].state = AppReprocessState::Completed,
+            Err(e) => {
+                tracing::error!("Reprocessing conversation {} with app {} failed: {}", conversation.id, app.id, e);
+                progress.apps[i].state = AppReprocessState::Failed;
+                progress.apps[i].detail = Some(e);
+            }
+        }
+        store(&state, &uid, &mut progress).await;
+    }
+
+    tracing::info!(
+        "Reprocessed conversation {} with {} apps for user {}",
+        conversation.id,
+        progress.apps.iter().filter(|a| a.state == AppReprocessState::Completed).count(),
+        uid
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_dedupes_apps() {
+        let app = |id: &str, capabilities: &[&str]| -> App {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "name": format!("App {}", id),
+                "description": "",
+                "image": "",
+                "category": "productivity",
+                "author": "omi",
+                "capabilities": capabilities,
+            }))
+            .unwrap()
+        };
+        let apps = [
+            app("notes", &["memories"]),
+            app("chat-only", &["chat"]),
+            app("coach", &["chat", "memories"]),
+            app("notes", &["memories"]),
+        ];
+        let existing = [AppResult { app_id: Some("coach".to_string()), content: "Earlier result".to_string() }];
+
+        let planned = plan(&apps, &existing);
+        let states: Vec<(&str, AppReprocessState)> = planned.iter().map(|p| (p.app_id.as_str(), p.state)).collect();
+        assert_eq!(
+            states,
+            vec![("notes", AppReprocessState::Queued), ("coach", AppReprocessState::Skipped)]
+        );
+
+        let mut progress = ReprocessAllProgress {
+            conversation_id: "c1".to_string(),
+            apps: planned,
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert!(!progress.is_finished());
+        progress.apps[0].state = AppReprocessState::Failed;
+        assert!(progress.is_finished());
+    }
+}
@@ -0,0 +1,140 @@
+// Response guardrails - Post-processing for chat responses before they reach the client
+// A response detected in a language other than the user's is translated back, and markdown
+// is flattened to plain text for clients that say they can't render it. Both are toggled
+// in config (CHAT_ENFORCE_LANGUAGE, CHAT_STRIP_MARKDOWN).
+
+use crate::config::Config;
+use crate::services::language::detect_language;
+use crate::AppState;
+
+/// Which guardrails run
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseGuardrails {
+    pub enforce_language: bool,
+    pub strip_markdown: bool,
+}
+
+impl ResponseGuardrails {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enforce_language: config.chat_enforce_language,
+            strip_markdown: config.chat_strip_markdown,
+        }
+    }
+
+    /// `text` in the user's language and in a format the client renders. The user's LLM
+    /// client is only loaded when a translation is needed; the original language is kept
+    /// when translating fails.
+    pub async fn apply(&self, state: &AppState, uid: &str, text: &str, client_supports_markdown: bool) -> String {
+        let mut text = text.to_string();
+
+        if self.enforce_language {
+            let language = state.storage.get_user_language(uid).await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to get language of user {}: {}", uid, e);
+                String::new()
+            });
+            if let Some(detected) = language_mismatch(&text, &language) {
+                let expected = primary_subtag(&language);
+                match state.llm_client(uid).await {
+                    Ok(Some(llm)) => match llm.translate_chat_response(&text, &expected).await {
+                        Ok(translated) if !translated.is_empty() => {
+                            tracing::info!("Translated chat response from {} to {}", detected, expected);
+                            text = translated;
+                        }
+                        Ok(_) => tracing::warn!("Empty translation of chat response to {}", expected),
+                        Err(e) => tracing::warn!("Failed to translate chat response to {}: {}", expected, e),
+                    },
+                    Ok(None) => tracing::warn!("No LLM key to translate chat response to {}", expected),
+                    Err(e) => tracing::warn!("Can't translate chat response to {}: {}", expected, e),
+                }
+            }
+        }
+
+        if self.strip_markdown && !client_supports_markdown {
+            text = markdown_to_plain_text(&text);
+        }
+        text
+    }
+}
+
+/// "en" for "en-US"
+fn primary_subtag(language: &str) -> String {
+    language.split(['-', '_']).next().unwrap_or_default().trim().to_lowercase()
+}
+
+/// The language `text` was detected in, when that's clearly not `expected`. Nothing for
+/// text too short to classify, or when the user has no single language ("multi").
+pub fn language_mismatch(text: &str, expected: &str) -> Option<&'static str> {
+    let expected = primary_subtag(expected);
+    if expected.is_empty() || expected == "multi" {
+        return None;
+    }
+    detect_language(text).filter(|detected| *detected != expected)
+}
+
+/// Plain-text rendering of a markdown response: headings, emphasis, code fences and quote
+/// markers are dropped, bullets become "•" and links keep their URL in parentheses
+pub fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            continue;
+        }
+        let indent = &line[..line.len() - trimmed.len()];
+
+        let mut content = trimmed.trim_start_matches('>').trim_start();
+        if content.starts_with('#') {
+            content = content.trim_start_matches('#').trim_start();
+        }
+        let (bullet, content) = match content.strip_prefix("- ").or_else(|| content.strip_prefix("* ")) {
+            Some(rest) => ("• ", rest),
+            None => ("", content),
+        };
+        lines.push(format!("{}{}{}", indent, bullet, strip_inline(content)));
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Drop inline emphasis and code markers, and rewrite [text](url) as "text (url)"
+fn strip_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let link = rest[open..]
+            .find("](")
+            .map(|mid| open + mid)
+            .and_then(|mid| rest[mid..].find(')').map(|close| (mid, mid + close)));
+        let Some((mid, close)) = link else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        out.push_str(&format!("{} ({})", &rest[open + 1..mid], &rest[mid + 2..close]));
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+
+    ["**", "__", "~~", "`"]
+        .iter()
+        .fold(out, |text, marker| text.replace(marker, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_mismatch_and_markdown() {
+        let spanish = "Mañana tienes una reunión con el equipo de ventas a las diez en la oficina.";
+        assert_eq!(language_mismatch(spanish, "en-US"), Some("es"));
+        assert_eq!(language_mismatch(spanish, "es"), None);
+        assert_eq!(language_mismatch(spanish, "multi"), None);
+        assert_eq!(language_mismatch("ok", "de"), None);
+
+        let markdown = "## Plan\nSee **this** [doc](https://example.com/a) and `run it`.\n\n- one\n  * two\n> quoted\n```\nlet x = 1;\n```";
+        assert_eq!(
+            markdown_to_plain_text(markdown),
+            "Plan\nSee this doc (https://example.com/a) and run it.\n\n• one\n  • two\nquoted\nlet x = 1;"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+// Retention service - opt-in conversation auto-archival and deletion
+// Once a day, conversations past the user's thresholds are archived, deleted, or have their
+// raw transcript removed (keeping the summary). Each user's run is a background job on the
+// shared job queue.
+
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use std::sync::Arc;
+
+use crate::job_queue::JobQueue;
+use crate::models::{Conversation, RetentionCandidate, RetentionPolicy, RetentionPreview};
+use crate::services::firestore::ARCHIVE_FOLDER_ID;
+use crate::services::FirestoreService;
+
+/// How often to check which users are due for enforcement
+const RETENTION_CHECK_INTERVAL_MINUTES: u64 = 60;
+/// Max conversations acted on per user per run. Conversations are scanned oldest first and
+/// processed ones stop qualifying, so the remainder is picked up the next day.
+const MAX_RETENTION_CANDIDATES: usize = 500;
+/// Conversations read per page while scanning
+const RETENTION_PAGE_SIZE: usize = 100;
+
+/// Spawn the periodic retention enforcement job
+pub fn spawn_retention_enforcement(firestore: Arc<FirestoreService>, jobs: JobQueue) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            RETENTION_CHECK_INTERVAL_MINUTES * 60,
+        ));
+        loop {
+            interval.tick().await;
+            run_retention_pass(&firestore, &jobs).await;
+        }
+    });
+
+    tracing::info!(
+        "Conversation retention enforcement scheduled every {} minutes",
+        RETENTION_CHECK_INTERVAL_MINUTES
+    );
+}
+
+/// Queue enforcement for every opted-in user; users already done today are skipped by the job
+async fn run_retention_pass(firestore: &Arc<FirestoreService>, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_retention_enabled().await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Retention: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    for uid in uids {
+        let (firestore, today) = (firestore.clone(), today.clone());
+        jobs.submit_for_user(&uid, "retention", |uid| async move {
+            enforce_user_if_due(&firestore, &uid, &today).await
+        });
+    }
+}
+
+async fn enforce_user_if_due(
+    firestore: &FirestoreService,
+    uid: &str,
+    today: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let policy = firestore.get_retention_policy(uid).await?;
+    if !policy.is_enabled() || policy.last_enforced_on.as_deref() == Some(today) {
+        return Ok(());
+    }
+
+    let preview = preview_retention(firestore, uid, &policy).await?;
+
+    if !preview.delete.is_empty() {
+        let ids: Vec<String> = preview.delete.iter().map(|c| c.id.clone()).collect();
+        firestore.batch_delete_conversations(uid, &ids).await?;
+    }
+    if !preview.strip_transcripts.is_empty() {
+        let ids: Vec<String> = preview.strip_transcripts.iter().map(|c| c.id.clone()).collect();
+        firestore.batch_strip_transcripts(uid, &ids).await?;
+    }
+    if !preview.archive.is_empty() {
+        firestore.ensure_archive_folder(uid).await?;
+        let ids: Vec<String> = preview.archive.iter().map(|c| c.id.clone()).collect();
+        firestore.batch_archive_conversations(uid, &ids).await?;
+    }
+
+    firestore.set_retention_last_enforced_date(uid, today).await?;
+
+    if !preview.archive.is_empty() || !preview.delete.is_empty() || !preview.strip_transcripts.is_empty() {
+        tracing::info!(
+            "Retention for user {}: archived {}, deleted {}, stripped {} transcripts",
+            uid,
+            preview.archive.len(),
+            preview.delete.len(),
+            preview.strip_transcripts.len()
+        );
+    }
+    Ok(())
+}
+
+/// What enforcing `policy` now would do, without writing anything
+pub async fn preview_retention(
+    firestore: &FirestoreService,
+    uid: &str,
+    policy: &RetentionPolicy,
+) -> Result<RetentionPreview, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now();
+    let oldest_threshold = [
+        policy.archive_after_days,
+        policy.delete_after_days,
+        policy.transcript_delete_after_days,
+    ]
+    .into_iter()
+        .flatten()
+        .min();
+
+    let mut archive = vec![];
+    let mut delete = vec![];
+    let mut strip_transcripts = vec![];
+    let mut truncated = false;
+    if let Some(days) = oldest_threshold {
+        let cutoff = (now - Duration::days(days as i64)).to_rfc3339();
+        let mut conversations = firestore.stream_conversations_before(uid, &cutoff, RETENTION_PAGE_SIZE);
+        while let Some(conversation) = conversations.try_next().await? {
+            if archive.len() + delete.len() + strip_transcripts.len() >= MAX_RETENTION_CANDIDATES {
+                truncated = true;
+                break;
+            }
+            let (a, d, s) = plan_retention(std::slice::from_ref(&conversation), policy, now);
+            archive.extend(a);
+            delete.extend(d);
+            strip_transcripts.extend(s);
+        }
+    }
+
+    Ok(RetentionPreview {
+        archive_after_days: policy.archive_after_days,
+        delete_after_days: policy.delete_after_days,
+        transcript_delete_after_days: policy.transcript_delete_after_days,
+        archive,
+        delete,
+        strip_transcripts,
+        truncated,
+    })
+}
+
+/// Split conversations into (archive, delete, strip transcripts). Starred conversations are
+/// never archived or deleted, but do lose their transcript when transcript removal is on.
+/// Deletion wins over the other steps, and already-archived or already-stripped ones aren't
+/// processed again. Archiving and transcript removal can apply together.
+pub fn plan_retention(
+    conversations: &[Conversation],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> (Vec<RetentionCandidate>, Vec<RetentionCandidate>, Vec<RetentionCandidate>) {
+    let older_than = |conv: &Conversation, days: Option<i32>| {
+        days.is_some_and(|d| conv.created_at < now - Duration::days(d as i64))
+    };
+
+    let mut archive = vec![];
+    let mut delete = vec![];
+    let mut strip_transcripts = vec![];
+    for conv in conversations {
+        let candidate = || RetentionCandidate {
+            id: conv.id.clone(),
+            title: conv.structured.title.clone(),
+            created_at: conv.created_at,
+        };
+        if !conv.starred && older_than(conv, policy.delete_after_days) {
+            delete.push(candidate());
+            continue;
+        }
+        if !conv.starred
+            && older_than(conv, policy.archive_after_days)
+            && conv.folder_id.as_deref() != Some(ARCHIVE_FOLDER_ID)
+        {
+            archive.push(candidate());
+        }
+        if older_than(conv, policy.transcript_delete_after_days)
+            && conv.transcript_deleted_at.is_none()
+            && !conv.transcript_segments.is_empty()
+        {
+            strip_transcripts.push(candidate());
+        }
+    }
+    (archive, delete, strip_transcripts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(id: &str, age_days: i64, now: DateTime<Utc>) -> Conversation {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "created_at": now - Duration::days(age_days),
+            "started_at": now - Duration::days(age_days),
+            "finished_at": now - Duration::days(age_days),
+            "structured": {"title": id, "overview": ""}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_retention_thresholds() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            archive_after_days: Some(90),
+            delete_after_days: Some(365),
+            transcript_delete_after_days: None,
+            last_enforced_on: None,
+        };
+        let mut archived = conversation("already-archived", 100, now);
+        archived.folder_id = Some(ARCHIVE_FOLDER_ID.to_string());
+        let mut starred = conversation("starred", 400, now);
+        starred.starred = true;
+        let conversations = vec![
+            conversation("recent", 10, now),
+            conversation("old", 100, now),
+            conversation("ancient", 400, now),
+            archived,
+            starred,
+        ];
+
+        let (archive, delete, _) = plan_retention(&conversations, &policy, now);
+        let ids = |c: &[RetentionCandidate]| c.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&archive), vec!["old"]);
+        assert_eq!(ids(&delete), vec!["ancient"]);
+    }
+
+    #[test]
+    fn test_plan_retention_delete_only() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            archive_after_days: None,
+            delete_after_days: Some(30),
+            transcript_delete_after_days: None,
+            last_enforced_on: None,
+        };
+        let conversations = vec![conversation("a", 10, now), conversation("b", 40, now)];
+        let (archive, delete, _) = plan_retention(&conversations, &policy, now);
+        assert!(archive.is_empty());
+        assert_eq!(delete.len(), 1);
+        assert_eq!(delete[0].id, "b");
+    }
+
+    #[test]
+    fn test_plan_retention_strip_transcripts() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            archive_after_days: None,
+            delete_after_days: Some(365),
+            transcript_delete_after_days: Some(30),
+            last_enforced_on: None,
+        };
+        let with_transcript = |id: &str, age_days: i64| {
+            let mut conv = conversation(id, age_days, now);
+            conv.transcript_segments = serde_json::from_value(serde_json::json!([
+                {"text": "hello", "speaker": "SPEAKER_00", "is_user": true, "start": 0.0, "end": 1.0}
+            ]))
+            .unwrap();
+            conv
+        };
+        let mut stripped = with_transcript("stripped", 60);
+        stripped.transcript_deleted_at = Some(now - Duration::days(5));
+        let mut starred = with_transcript("starred", 400);
+        starred.starred = true;
+        let conversations = vec![
+            with_transcript("recent", 10),
+            with_transcript("old", 60),
+            with_transcript("ancient", 400),
+            conversation("no-transcript", 60, now),
+            stripped,
+            starred,
+        ];
+
+        let (archive, delete, strip) = plan_retention(&conversations, &policy, now);
+        assert!(archive.is_empty());
+        assert_eq!(delete.len(), 1);
+        assert_eq!(delete[0].id, "ancient");
+        assert_eq!(strip.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["old", "starred"]);
+    }
+
+    #[test]
+    fn test_plan_retention_starred_keeps_conversation_but_not_transcript() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            archive_after_days: Some(60),
+            delete_after_days: Some(90),
+            transcript_delete_after_days: Some(30),
+            last_enforced_on: None,
+        };
+        let mut starred = conversation("starred", 400, now);
+        starred.starred = true;
+        starred.transcript_segments = serde_json::from_value(serde_json::json!([
+            {"text": "hello", "speaker": "SPEAKER_00", "is_user": true, "start": 0.0, "end": 1.0}
+        ]))
+        .unwrap();
+
+        let (archive, delete, strip) = plan_retention(&[starred], &policy, now);
+        assert!(archive.is_empty());
+        assert!(delete.is_empty());
+        assert_eq!(strip.len(), 1);
+        assert_eq!(strip[0].id, "starred");
+    }
+}
@@ -0,0 +1,152 @@
+// Rollover service - opt-in daily rollover of overdue action items
+// Each morning (user's local time) overdue, incomplete items are moved to today. Each user's
+// rollover runs as a background job on the shared job queue.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
+
+use crate::job_queue::JobQueue;
+use crate::services::FirestoreService;
+
+/// How often to check which users are due for their morning rollover
+const ROLLOVER_CHECK_INTERVAL_MINUTES: u64 = 15;
+/// Max overdue items moved per user per day
+const MAX_ROLLOVER_ITEMS: usize = 500;
+
+/// Spawn the periodic rollover checker
+pub fn spawn_action_item_rollover(firestore: Arc<FirestoreService>, jobs: JobQueue) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(ROLLOVER_CHECK_INTERVAL_MINUTES * 60));
+        loop {
+            interval.tick().await;
+            run_rollover_pass(&firestore, &jobs).await;
+        }
+    });
+
+    tracing::info!(
+        "Action item rollover checker scheduled every {} minutes",
+        ROLLOVER_CHECK_INTERVAL_MINUTES
+    );
+}
+
+/// Queue a rollover job for every opted-in user; each runs only once the user's local
+/// rollover hour has passed today
+async fn run_rollover_pass(firestore: &Arc<FirestoreService>, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_rollover_enabled().await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Action item rollover: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    for uid in uids {
+        let firestore = firestore.clone();
+        jobs.submit_for_user(&uid, "action-item-rollover", |uid| async move {
+            rollover_user_if_due(&firestore, &uid).await
+        });
+    }
+}
+
+/// Run today's rollover for one user if it's past their hour and hasn't run yet
+async fn rollover_user_if_due(
+    firestore: &FirestoreService,
+    uid: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let settings = firestore.get_action_item_rollover_settings(uid).await?;
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let tz: Tz = firestore
+        .get_user_profile(uid)
+        .await?
+        .time_zone
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(Tz::UTC);
+
+    let local_now = Utc::now().with_timezone(&tz);
+    let today = local_now.date_naive();
+    let today_str = today.format("%Y-%m-%d").to_string();
+
+    if (local_now.hour() as i32) < settings.hour
+        || settings.last_rolled_over_on.as_deref() == Some(today_str.as_str())
+    {
+        return Ok(());
+    }
+
+    let moved = rollover_overdue_action_items(firestore, uid, tz, today).await?;
+    firestore.set_action_item_rollover_last_date(uid, &today_str).await?;
+
+    if moved > 0 {
+        tracing::info!("Rolled over {} overdue action items for user {} to {}", moved, uid, today_str);
+    }
+    Ok(())
+}
+
+/// Move overdue, incomplete, non-excluded items to `today`, bumping their rollover count
+async fn rollover_overdue_action_items(
+    firestore: &FirestoreService,
+    uid: &str,
+    tz: Tz,
+    today: NaiveDate,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let start_of_today = local_to_utc(tz, today, chrono::NaiveTime::MIN);
+    let start_str = start_of_today.to_rfc3339();
+
+    let items = firestore
+        .get_action_items(uid, MAX_ROLLOVER_ITEMS, 0, Some(false), None, None, None, None, Some(&start_str), None, None, true)
+        .await?;
+
+    let updates: Vec<(String, DateTime<Utc>, i32)> = items
+        .into_iter()
+        .filter(|item| !item.deleted.unwrap_or(false) && !item.rollover_excluded.unwrap_or(false))
+        .filter_map(|item| {
+            let due_at = item.due_at.filter(|d| *d < start_of_today)?;
+            Some((
+                item.id,
+                rolled_over_due_at(due_at, tz, today),
+                item.rollover_count.unwrap_or(0) + 1,
+            ))
+        })
+        .collect();
+
+    if !updates.is_empty() {
+        firestore.batch_rollover_action_items(uid, &updates).await?;
+    }
+    Ok(updates.len())
+}
+
+/// New due date for a rolled-over item: same local time of day, on `today`
+pub fn rolled_over_due_at(due_at: DateTime<Utc>, tz: Tz, today: NaiveDate) -> DateTime<Utc> {
+    local_to_utc(tz, today, due_at.with_timezone(&tz).time())
+}
+
+/// Resolve a local date/time to UTC (earliest match across DST transitions)
+pub fn local_to_utc(tz: Tz, date: NaiveDate, time: chrono::NaiveTime) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolled_over_due_at_keeps_local_time_of_day() {
+        let tz: Tz = "America/Los_Angeles".parse().unwrap();
+        // 2026-03-02 17:30 PST
+        let due = Utc.with_ymd_and_hms(2026, 3, 3, 1, 30, 0).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        // 2026-03-10 17:30 PDT (after DST change)
+        assert_eq!(
+            rolled_over_due_at(due, tz, today),
+            Utc.with_ymd_and_hms(2026, 3, 11, 0, 30, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+// Screen context buffer - Rolling per-user buffer of recent screen snapshots
+// Fed by POST /v1/context/screen; read by chat context and advice
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::screen_activity::ScreenContextSnapshot;
+
+/// Snapshots older than this are dropped
+const SNAPSHOT_TTL_SECS: i64 = 300;
+/// Max snapshots kept per user
+const MAX_SNAPSHOTS_PER_USER: usize = 20;
+/// Minimum spacing between snapshots of the same window
+const MIN_SNAPSHOT_INTERVAL_SECS: i64 = 10;
+/// OCR text is capped to keep prompts small
+pub const MAX_OCR_CHARS: usize = 4000;
+
+/// In-memory, short-lived screen context per user
+#[derive(Clone, Default)]
+pub struct ScreenContextBuffer {
+    inner: Arc<RwLock<HashMap<String, VecDeque<ScreenContextSnapshot>>>>,
+}
+
+impl ScreenContextBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a snapshot. Returns (accepted, buffered count); snapshots of an
+    /// unchanged window arriving faster than the minimum interval are dropped.
+    pub async fn push(&self, uid: &str, snapshot: ScreenContextSnapshot) -> (bool, usize) {
+        let mut map = self.inner.write().await;
+        let buffer = map.entry(uid.to_string()).or_default();
+        let accepted = push_snapshot(buffer, snapshot, Utc::now());
+        (accepted, buffer.len())
+    }
+
+    /// Most recent snapshot that hasn't expired
+    pub async fn latest(&self, uid: &str) -> Option<ScreenContextSnapshot> {
+        let map = self.inner.read().await;
+        let snapshot = map.get(uid)?.back()?;
+        if is_expired(snapshot, Utc::now()) {
+            return None;
+        }
+        Some(snapshot.clone())
+    }
+
+    /// Drop expired snapshots and users with nothing left
+    pub async fn prune(&self) {
+        let now = Utc::now();
+        let mut map = self.inner.write().await;
+        map.retain(|_, buffer| {
+            prune_expired(buffer, now);
+            !buffer.is_empty()
+        });
+    }
+}
+
+fn is_expired(snapshot: &ScreenContextSnapshot, now: DateTime<Utc>) -> bool {
+    now - snapshot.captured_at > Duration::seconds(SNAPSHOT_TTL_SECS)
+}
+
+fn prune_expired(buffer: &mut VecDeque<ScreenContextSnapshot>, now: DateTime<Utc>) {
+    while buffer.front().is_some_and(|s| is_expired(s, now)) {
+        buffer.pop_front();
+    }
+}
+
+/// Apply TTL, throttling and capacity rules to a user's buffer
+fn push_snapshot(
+    buffer: &mut VecDeque<ScreenContextSnapshot>,
+    snapshot: ScreenContextSnapshot,
+    now: DateTime<Utc>,
+) -> bool {
+    prune_expired(buffer, now);
+
+    if is_expired(&snapshot, now) {
+        return false;
+    }
+
+    if let Some(last) = buffer.back() {
+        let same_window =
+            last.app_name == snapshot.app_name && last.window_title == snapshot.window_title;
+        let too_soon =
+            snapshot.captured_at - last.captured_at < Duration::seconds(MIN_SNAPSHOT_INTERVAL_SECS);
+        // Out-of-order or throttled snapshots of the same window are dropped
+        if snapshot.captured_at < last.captured_at || (same_window && too_soon) {
+            return false;
+        }
+    }
+
+    buffer.push_back(snapshot);
+    while buffer.len() > MAX_SNAPSHOTS_PER_USER {
+        buffer.pop_front();
+    }
+    true
+}
+
+/// Periodically drop expired snapshots so idle users don't hold memory
+pub fn spawn_screen_context_pruner(buffer: ScreenContextBuffer) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(SNAPSHOT_TTL_SECS as u64));
+        loop {
+            interval.tick().await;
+            buffer.prune().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(window: &str, captured_at: DateTime<Utc>) -> ScreenContextSnapshot {
+        ScreenContextSnapshot {
+            app_name: "Xcode".to_string(),
+            window_title: window.to_string(),
+            ocr_text: String::new(),
+            captured_at,
+        }
+    }
+
+    #[test]
+    fn test_push_throttles_same_window_but_not_switches() {
+        let now = Utc::now();
+        let mut buffer = VecDeque::new();
+        assert!(push_snapshot(&mut buffer, snapshot("a.swift", now - Duration::seconds(20)), now));
+        // Same window 5s later is throttled
+        assert!(!push_snapshot(&mut buffer, snapshot("a.swift", now - Duration::seconds(15)), now));
+        // Switching windows is always recorded
+        assert!(push_snapshot(&mut buffer, snapshot("b.swift", now - Duration::seconds(14)), now));
+        assert!(push_snapshot(&mut buffer, snapshot("b.swift", now), now));
+        assert_eq!(buffer.len(), 3);
+    }
+
+    #[test]
+    fn test_push_drops_expired_snapshots() {
+        let now = Utc::now();
+        let mut buffer = VecDeque::new();
+        let old = now - Duration::seconds(SNAPSHOT_TTL_SECS + 10);
+        assert!(!push_snapshot(&mut buffer, snapshot("a.swift", old), now));
+
+        buffer.push_back(snapshot("a.swift", old));
+        assert!(push_snapshot(&mut buffer, snapshot("b.swift", now), now));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer[0].window_title, "b.swift");
+    }
+}
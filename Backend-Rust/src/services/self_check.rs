@@ -0,0 +1,319 @@
+// Self-check - Startup validation behind `omi-desktop-backend --check`
+// Verifies configuration, credentials, Firestore, the Gemini key and composite indexes,
+// so a deploy can refuse to roll out a pod that would fail on its first request.
+
+use crate::client_info::{ForwardedHeader, TrustedProxies};
+use crate::config::Config;
+use crate::llm::LlmClient;
+use crate::services::firestore::{
+    CompositeIndex, ADVICE_SUBCOLLECTION, CONVERSATIONS_SUBCOLLECTION, IMPERSONATION_AUDIT_COLLECTION,
+    MEMORIES_SUBCOLLECTION, MESSAGES_SUBCOLLECTION, NOTIFICATIONS_SUBCOLLECTION,
+};
+use crate::services::FirestoreService;
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Degraded but able to serve
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// All check results, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct SelfCheckReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl SelfCheckReport {
+    fn add(&mut self, name: &str, status: CheckStatus, detail: impl Into<String>) {
+        self.results.push(CheckResult {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// No check failed (warnings are allowed)
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.status != CheckStatus::Fail)
+    }
+
+    /// Human-readable report, one line per check plus a summary
+    pub fn render(&self) -> String {
+        let mut out = String::from("omi-desktop-backend self-check\n\n");
+        for result in &self.results {
+            out.push_str(&format!("  [{}] {:<14} {}\n", result.status.label(), result.name, result.detail));
+        }
+        let count = |status| self.results.iter().filter(|r| r.status == status).count();
+        out.push_str(&format!(
+            "\n{} passed, {} warnings, {} failed - {}",
+            count(CheckStatus::Pass),
+            count(CheckStatus::Warn),
+            count(CheckStatus::Fail),
+            if self.passed() { "OK" } else { "NOT READY" }
+        ));
+        out
+    }
+}
+
+/// A composite index some query needs
+struct RequiredIndex {
+    collection_group: &'static str,
+    fields: &'static [(&'static str, &'static str)],
+    /// Query that fails without it
+    used_by: &'static str,
+}
+
+/// Composite indexes the API's filtered, ordered queries rely on
+const REQUIRED_INDEXES: &[RequiredIndex] = &[
+    RequiredIndex {
+        collection_group: CONVERSATIONS_SUBCOLLECTION,
+        fields: &[("discarded", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "auto-discarded conversations",
+    },
+    RequiredIndex {
+        collection_group: MEMORIES_SUBCOLLECTION,
+        fields: &[("visibility", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "public memories",
+    },
+    RequiredIndex {
+        collection_group: ADVICE_SUBCOLLECTION,
+        fields: &[("is_dismissed", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "advice list",
+    },
+    RequiredIndex {
+        collection_group: MESSAGES_SUBCOLLECTION,
+        fields: &[("chat_session_id", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "chat session messages",
+    },
+    RequiredIndex {
+        collection_group: MESSAGES_SUBCOLLECTION,
+        fields: &[("app_id", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "app chat messages",
+    },
+    RequiredIndex {
+        collection_group: NOTIFICATIONS_SUBCOLLECTION,
+        fields: &[("read", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "unread notifications",
+    },
+    RequiredIndex {
+        collection_group: IMPERSONATION_AUDIT_COLLECTION,
+        fields: &[("target_uid", "ASCENDING"), ("created_at", "DESCENDING")],
+        used_by: "impersonation audit by user",
+    },
+];
+
+/// Whether `index` can serve queries needing `required`. Firestore appends `__name__`
+/// to every index, so only the leading fields are compared.
+fn index_matches(index: &CompositeIndex, required: &RequiredIndex) -> bool {
+    index.collection_group == required.collection_group
+        && index.query_scope == "COLLECTION"
+        && index
+            .fields
+            .iter()
+            .filter(|(path, _)| path != "__name__")
+            .map(|(path, mode)| (path.as_str(), mode.as_str()))
+            .eq(required.fields.iter().copied())
+}
+
+/// Configuration problems that would break requests, and ones that only degrade features
+fn check_config(config: &Config, report: &mut SelfCheckReport) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    if config.gemini_api_key.is_none() {
+        errors.push("GEMINI_API_KEY not set".to_string());
+    }
+    if config.encryption_secret.is_none() {
+        errors.push("ENCRYPTION_SECRET not set".to_string());
+    }
+    if config.firebase_project_id.is_none() {
+        warnings.push("FIREBASE_PROJECT_ID not set, using based-hardware".to_string());
+    }
+    if config.redis_host.is_none() {
+        warnings.push("REDIS_DB_HOST not set".to_string());
+    }
+    if !config.cors_dev_mode && config.cors_allowed_origins.is_empty() {
+        warnings.push("CORS_ALLOWED_ORIGINS not set".to_string());
+    }
+    if let Ok(header) = std::env::var("FORWARDED_HEADER") {
+        if let Err(e) = header.parse::<ForwardedHeader>() {
+            errors.push(format!("invalid FORWARDED_HEADER: {}", e));
+        }
+    }
+    for entry in &config.trusted_proxies {
+        if TrustedProxies::parse_entry(entry).is_none() {
+            errors.push(format!("invalid TRUSTED_PROXIES entry '{}'", entry));
+        }
+    }
+    if let Err(e) = config.validate() {
+        errors.push(e);
+    }
+
+    let (status, issues) = if !errors.is_empty() {
+        errors.extend(warnings);
+        (CheckStatus::Fail, errors)
+    } else if !warnings.is_empty() {
+        (CheckStatus::Warn, warnings)
+    } else {
+        (CheckStatus::Pass, vec!["all required settings present".to_string()])
+    };
+    report.add("configuration", status, issues.join("; "));
+}
+
+/// Run every check. Later checks are skipped when what they depend on failed.
+pub async fn run(config: &Config) -> SelfCheckReport {
+    let mut report = SelfCheckReport::default();
+    check_config(config, &mut report);
+
+    let credentials_ok = match FirestoreService::check_credentials() {
+        Ok(Some(description)) => {
+            report.add("credentials", CheckStatus::Pass, description);
+            true
+        }
+        Ok(None) => {
+            report.add("credentials", CheckStatus::Warn, "none configured, using the metadata server");
+            true
+        }
+        Err(e) => {
+            report.add("credentials", CheckStatus::Fail, e.to_string());
+            false
+        }
+    };
+
+    let project_id = config.firebase_project_id.clone().unwrap_or_else(|| "based-hardware".to_string());
+    let firestore = if credentials_ok {
+        match FirestoreService::new(project_id.clone(), config.encryption_secret.clone()).await {
+            Ok(firestore) => Some(firestore),
+            Err(e) => {
+                report.add("firestore", CheckStatus::Fail, e.to_string());
+                None
+            }
+        }
+    } else {
+        report.add("firestore", CheckStatus::Fail, "skipped: no usable credentials");
+        None
+    };
+    let firestore_ok = match &firestore {
+        Some(firestore) => match firestore.ping().await {
+            Ok(()) => {
+                report.add("firestore", CheckStatus::Pass, format!("connected to project {}", project_id));
+                true
+            }
+            Err(e) => {
+                report.add("firestore", CheckStatus::Fail, e.to_string());
+                false
+            }
+        },
+        None => false,
+    };
+
+    match &config.gemini_api_key {
+        Some(key) => {
+            let client = LlmClient::new(key.clone()).with_task_models(config.llm_models.clone());
+            match client.check_api_key().await {
+                Ok(()) => report.add("llm key", CheckStatus::Pass, "Gemini accepted the key"),
+                Err(reason) => report.add("llm key", CheckStatus::Fail, reason),
+            }
+        }
+        None => report.add("llm key", CheckStatus::Fail, "skipped: GEMINI_API_KEY not set"),
+    }
+
+    match (&firestore, firestore_ok) {
+        (Some(firestore), true) => match firestore.list_composite_indexes().await {
+            Ok(indexes) => check_indexes(&indexes, &mut report),
+            Err(e) => report.add("indexes", CheckStatus::Fail, e.to_string()),
+        },
+        _ => report.add("indexes", CheckStatus::Fail, "skipped: Firestore unreachable"),
+    }
+
+    report
+}
+
+/// Missing indexes fail; ones still building only warn
+fn check_indexes(indexes: &[CompositeIndex], report: &mut SelfCheckReport) {
+    let mut missing = Vec::new();
+    let mut building = Vec::new();
+    for required in REQUIRED_INDEXES {
+        let describe = || {
+            let fields: Vec<String> = required.fields.iter().map(|(path, mode)| format!("{} {}", path, mode)).collect();
+            format!("{}({}) for {}", required.collection_group, fields.join(", "), required.used_by)
+        };
+        match indexes.iter().find(|index| index_matches(index, required)) {
+            Some(index) if index.state == "READY" => {}
+            Some(_) => building.push(describe()),
+            None => missing.push(describe()),
+        }
+    }
+
+    if !missing.is_empty() {
+        report.add("indexes", CheckStatus::Fail, format!("missing: {}", missing.join("; ")));
+    } else if !building.is_empty() {
+        report.add("indexes", CheckStatus::Warn, format!("not ready: {}", building.join("; ")));
+    } else {
+        report.add("indexes", CheckStatus::Pass, format!("{} required indexes ready", REQUIRED_INDEXES.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(group: &str, fields: &[(&str, &str)], state: &str) -> CompositeIndex {
+        CompositeIndex {
+            collection_group: group.to_string(),
+            query_scope: "COLLECTION".to_string(),
+            fields: fields.iter().map(|(p, m)| (p.to_string(), m.to_string())).collect(),
+            state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_index_report() {
+        let mut indexes: Vec<CompositeIndex> = REQUIRED_INDEXES
+            .iter()
+            .map(|r| {
+                let mut fields = r.fields.to_vec();
+                fields.push(("__name__", "DESCENDING"));
+                index(r.collection_group, &fields, "READY")
+            })
+            .collect();
+
+        let mut report = SelfCheckReport::default();
+        check_indexes(&indexes, &mut report);
+        assert_eq!(report.results[0].status, CheckStatus::Pass);
+
+        indexes[0].state = "CREATING".to_string();
+        let mut report = SelfCheckReport::default();
+        check_indexes(&indexes, &mut report);
+        assert_eq!(report.results[0].status, CheckStatus::Warn);
+        assert!(report.passed());
+
+        // Same fields in another order don't serve the query
+        indexes[1] = index(MEMORIES_SUBCOLLECTION, &[("created_at", "DESCENDING"), ("visibility", "ASCENDING")], "READY");
+        let mut report = SelfCheckReport::default();
+        check_indexes(&indexes, &mut report);
+        assert_eq!(report.results[0].status, CheckStatus::Fail);
+        assert!(report.results[0].detail.contains("public memories"));
+        assert!(!report.passed());
+        assert!(report.render().ends_with("0 passed, 0 warnings, 1 failed - NOT READY"));
+    }
+}
@@ -0,0 +1,173 @@
+// Shadow traffic - Runs a candidate processing prompt/model next to production
+// A sampled share of from-segments requests is processed again in the background with the
+// candidate; both outputs go to shadow_comparisons for offline review. The user only ever
+// sees production's output, and a failing candidate never affects the request.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::llm::client::ProcessedConversation;
+use crate::llm::prompts;
+use crate::llm::{LlmClient, TaskKind};
+use crate::models::{ActionItem, MemoryDB, ShadowComparison, ShadowOutput, TranscriptSegment};
+use crate::services::firestore::carry_project_override;
+use crate::services::FirestoreService;
+
+/// The configured candidate; None when shadow traffic is off
+#[derive(Debug, Clone)]
+pub struct ShadowCandidate {
+    pub percent: f64,
+    pub model: Option<String>,
+    pub structure_prompt: Option<String>,
+}
+
+impl ShadowCandidate {
+    /// Needs a sample rate and something that differs from production
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if config.shadow_traffic_percent <= 0.0
+            || (config.shadow_model.is_none() && config.shadow_structure_prompt.is_none())
+        {
+            return None;
+        }
+        Some(Self {
+            percent: config.shadow_traffic_percent,
+            model: config.shadow_model.clone(),
+            structure_prompt: config.shadow_structure_prompt.clone(),
+        })
+    }
+
+    /// Whether this conversation is in the sample. Keyed on the ID so the decision is
+    /// stable for a conversation.
+    pub fn sampled(&self, conversation_id: &str) -> bool {
+        let hash = Sha256::digest(conversation_id.as_bytes());
+        let bucket = u16::from_be_bytes([hash[0], hash[1]]) % 10_000;
+        f64::from(bucket) < self.percent * 100.0
+    }
+
+    /// Production's client with the candidate's model and prompt swapped in
+    pub fn client(&self, production: &LlmClient) -> LlmClient {
+        let client = production.clone().with_structure_prompt(self.structure_prompt.clone());
+        match &self.model {
+            Some(model) => client.with_model(model),
+            None => client,
+        }
+    }
+
+    pub fn prompt_version(&self) -> String {
+        match &self.structure_prompt {
+            Some(prompt) => prompts::prompt_version_with(prompt),
+            None => prompts::prompt_version().to_string(),
+        }
+    }
+}
+
+/// What production was given, so the candidate processes the same thing
+pub struct ShadowInput {
+    pub uid: String,
+    pub conversation_id: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub started_at: String,
+    pub timezone: String,
+    pub language: String,
+    pub user_name: String,
+    pub existing_action_items: Vec<ActionItem>,
+    pub existing_memories: Vec<MemoryDB>,
+}
+
+/// Summary of one processing run for the comparison document
+pub fn output(
+    processed: &ProcessedConversation,
+    model: &str,
+    prompt_version: String,
+    started: Instant,
+) -> ShadowOutput {
+    let structured = &processed.structured;
+    ShadowOutput {
+        model: model.to_string(),
+        prompt_version,
+        title: structured.title.clone(),
+        overview: structured.overview.clone(),
+        emoji: structured.emoji.clone(),
+        category: format!("{:?}", structured.category).to_lowercase(),
+        action_items: processed.action_items.iter().map(|a| a.description.clone()).collect(),
+        memories: processed.memories.iter().map(|m| m.content.clone()).collect(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Process `input` with the candidate in the background and store both outputs
+pub fn spawn_shadow_run(
+    firestore: Arc<FirestoreService>,
+    candidate: ShadowCandidate,
+    client: LlmClient,
+    input: ShadowInput,
+    baseline: ShadowOutput,
+) {
+    tokio::spawn(carry_project_override(async move {
+        let started = Instant::now();
+        let result = client
+            .process_conversation(
+                &input.segments,
+                &input.started_at,
+                &input.timezone,
+                &input.language,
+                &input.user_name,
+                &input.existing_action_items,
+                &input.existing_memories,
+            )
+            .await;
+
+        let model = client.model_for(TaskKind::Summary);
+        let (candidate_output, candidate_error) = match result {
+            Ok(processed) => (Some(output(&processed, model, candidate.prompt_version(), started)), None),
+            Err(e) => {
+                tracing::warn!("Shadow run failed for conversation {}: {}", input.conversation_id, e);
+                (None, Some(e.to_string()))
+            }
+        };
+
+        let comparison = ShadowComparison {
+            id: input.conversation_id,
+            uid: input.uid,
+            baseline,
+            candidate: candidate_output,
+            candidate_error,
+            created_at: Utc::now(),
+        };
+        match firestore.save_shadow_comparison(&comparison).await {
+            Ok(()) => tracing::info!("Stored shadow comparison for conversation {}", comparison.id),
+            Err(e) => tracing::warn!("Failed to store shadow comparison {}: {}", comparison.id, e),
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_and_candidate_client() {
+        let candidate = |percent| ShadowCandidate {
+            percent,
+            model: Some("gemini-candidate".to_string()),
+            structure_prompt: Some("Summarize {transcript_text}".to_string()),
+        };
+
+        let ids: Vec<String> = (0..2000).map(|i| format!("conversation-{}", i)).collect();
+        let sampled = ids.iter().filter(|id| candidate(10.0).sampled(id)).count();
+        assert!((120..280).contains(&sampled), "sampled {}", sampled);
+        assert!(ids.iter().all(|id| !candidate(0.0).sampled(id)));
+        assert!(ids.iter().all(|id| candidate(100.0).sampled(id)));
+        assert_eq!(candidate(10.0).sampled(&ids[7]), candidate(10.0).sampled(&ids[7]));
+
+        let production = LlmClient::new("key".to_string());
+        let shadow = candidate(10.0).client(&production);
+        assert_eq!(shadow.model_for(TaskKind::Summary), "gemini-candidate");
+        assert_ne!(production.model_for(TaskKind::Summary), "gemini-candidate");
+        assert_ne!(candidate(10.0).prompt_version(), prompts::prompt_version());
+    }
+}
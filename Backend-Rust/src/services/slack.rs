@@ -0,0 +1,427 @@
+// Slack integration - OAuth, request verification, messaging and the daily digest DM
+// API reference: https://api.slack.com/methods
+
+use chrono::{Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::job_queue::JobQueue;
+use crate::models::{ActionItemDB, Conversation, SlackConnection};
+use crate::services::FirestoreService;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SLACK_API_URL: &str = "https://slack.com/api";
+/// Reject signed requests older than this (replay protection)
+const MAX_REQUEST_AGE_SECS: i64 = 300;
+/// How often to check which users are due for their digest
+const DIGEST_CHECK_INTERVAL_MINUTES: u64 = 15;
+/// Max items of each kind listed in a digest
+const MAX_DIGEST_ENTRIES: usize = 10;
+
+/// Token response from the Slack OAuth v2 exchange
+#[derive(Debug, Clone)]
+pub struct SlackOAuthToken {
+    pub access_token: String,
+    pub team_id: String,
+    pub team_name: Option<String>,
+    pub bot_user_id: Option<String>,
+    pub slack_user_id: String,
+}
+
+pub struct SlackService {
+    client: Client,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    signing_secret: Option<String>,
+    redirect_uri: Option<String>,
+}
+
+impl SlackService {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(15))
+                .build()
+                .expect("Failed to create HTTP client"),
+            client_id: config.slack_client_id.clone(),
+            client_secret: config.slack_client_secret.clone(),
+            signing_secret: config.slack_signing_secret.clone(),
+            redirect_uri: config.slack_redirect_uri.clone(),
+        }
+    }
+
+    /// Whether OAuth credentials are configured
+    pub fn is_configured(&self) -> bool {
+        self.client_id.is_some() && self.client_secret.is_some()
+    }
+
+    /// Verify a request signed with the app's signing secret.
+    /// Returns false when no signing secret is configured.
+    pub fn verify_signature(&self, timestamp: &str, signature: &str, body: &[u8]) -> bool {
+        let Some(secret) = &self.signing_secret else {
+            return false;
+        };
+        verify_slack_signature(secret, timestamp, signature, body, Utc::now().timestamp())
+    }
+
+    /// Exchange an OAuth authorization code for a bot token
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        redirect_uri: Option<&str>,
+    ) -> Result<SlackOAuthToken, Box<dyn std::error::Error + Send + Sync>> {
+        let (client_id, client_secret) = match (&self.client_id, &self.client_secret) {
+            (Some(id), Some(secret)) => (id, secret),
+            _ => return Err("Slack OAuth is not configured".into()),
+        };
+
+        let mut form = vec![
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("code", code),
+        ];
+        if let Some(uri) = redirect_uri.or(self.redirect_uri.as_deref()) {
+            form.push(("redirect_uri", uri));
+        }
+
+        let response: Value = self
+            .client
+            .post(format!("{}/oauth.v2.access", SLACK_API_URL))
+            .form(&form)
+            .send()
+            .await?
+            .json()
+            .await?;
+        let response = check_ok(response)?;
+
+        #[derive(Deserialize)]
+        struct Team {
+            id: String,
+            name: Option<String>,
+        }
+        #[derive(Deserialize)]
+        struct AuthedUser {
+            id: String,
+        }
+        #[derive(Deserialize)]
+        struct OAuthResponse {
+            access_token: String,
+            bot_user_id: Option<String>,
+            team: Team,
+            authed_user: AuthedUser,
+        }
+
+        let parsed: OAuthResponse = serde_json::from_value(response)?;
+        Ok(SlackOAuthToken {
+            access_token: parsed.access_token,
+            team_id: parsed.team.id,
+            team_name: parsed.team.name,
+            bot_user_id: parsed.bot_user_id,
+            slack_user_id: parsed.authed_user.id,
+        })
+    }
+
+    /// Post a message to a channel (or DM channel)
+    pub async fn post_message(
+        &self,
+        access_token: &str,
+        channel: &str,
+        text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.call(access_token, "chat.postMessage", json!({"channel": channel, "text": text}))
+            .await?;
+        Ok(())
+    }
+
+    /// Open (or reuse) the bot's DM channel with a user
+    pub async fn open_dm(
+        &self,
+        access_token: &str,
+        slack_user_id: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .call(access_token, "conversations.open", json!({"users": slack_user_id}))
+            .await?;
+        response
+            .get("channel")
+            .and_then(|c| c.get("id"))
+            .and_then(|id| id.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "Slack response missing channel id".into())
+    }
+
+    async fn call(
+        &self,
+        access_token: &str,
+        method: &str,
+        body: Value,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let response: Value = self
+            .client
+            .post(format!("{}/{}", SLACK_API_URL, method))
+            .bearer_auth(access_token)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        check_ok(response)
+    }
+}
+
+/// Slack returns HTTP 200 with {"ok": false, "error": "..."} on failure
+fn check_ok(response: Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    if response.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+        Ok(response)
+    } else {
+        let error = response
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("unknown_error");
+        Err(format!("Slack API error: {}", error).into())
+    }
+}
+
+/// Slack request signing: v0=hex(hmac_sha256(secret, "v0:{timestamp}:{body}"))
+fn verify_slack_signature(secret: &str, timestamp: &str, signature: &str, body: &[u8], now: i64) -> bool {
+    let Ok(ts) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - ts).abs() > MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+    let Some(signature) = signature.strip_prefix("v0=").and_then(|s| hex::decode(s).ok()) else {
+        return false;
+    };
+
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    // Constant-time comparison
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Metadata stored on action items captured from Slack
+pub fn action_item_metadata(team_id: &str, channel_id: Option<&str>, via: &str) -> String {
+    json!({
+        "source_app": "Slack",
+        "slack_team_id": team_id,
+        "slack_channel_id": channel_id,
+        "via": via,
+    })
+    .to_string()
+}
+
+// =========================================================================
+// DAILY DIGEST
+// =========================================================================
+
+/// Spawn the periodic digest checker
+pub fn spawn_slack_digest(firestore: Arc<FirestoreService>, slack: Arc<SlackService>, jobs: JobQueue) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(DIGEST_CHECK_INTERVAL_MINUTES * 60));
+        loop {
+            interval.tick().await;
+            run_digest_pass(&firestore, &slack, &jobs).await;
+        }
+    });
+
+    tracing::info!(
+        "Slack digest checker scheduled every {} minutes",
+        DIGEST_CHECK_INTERVAL_MINUTES
+    );
+}
+
+/// Queue a digest job for every opted-in user; each sends only once the user's hour has passed
+async fn run_digest_pass(firestore: &Arc<FirestoreService>, slack: &Arc<SlackService>, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_slack_digest_enabled().await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Slack digest: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    for uid in uids {
+        let (firestore, slack) = (firestore.clone(), slack.clone());
+        jobs.submit_for_user(&uid, "slack-digest", |uid| async move {
+            send_digest_if_due(&firestore, &slack, &uid).await
+        });
+    }
+}
+
+/// Send today's digest for one user if it's past their hour and hasn't been sent yet
+async fn send_digest_if_due(
+    firestore: &FirestoreService,
+    slack: &SlackService,
+    uid: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(connection) = firestore.get_slack_connection(uid).await? else {
+        return Ok(());
+    };
+    if !connection.digest_enabled {
+        return Ok(());
+    }
+
+    let tz: Tz = firestore
+        .get_user_profile(uid)
+        .await?
+        .time_zone
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(Tz::UTC);
+
+    let local_now = Utc::now().with_timezone(&tz);
+    let today = local_now.date_naive();
+    let today_str = today.format("%Y-%m-%d").to_string();
+
+    if (local_now.hour() as i32) < connection.digest_hour
+        || connection.last_digest_date.as_deref() == Some(today_str.as_str())
+    {
+        return Ok(());
+    }
+
+    send_digest(firestore, slack, uid, &connection, tz, today).await?;
+    firestore.set_slack_last_digest_date(uid, &today_str).await?;
+    Ok(())
+}
+
+/// Build and deliver the digest for `date` (user's local day)
+pub async fn send_digest(
+    firestore: &FirestoreService,
+    slack: &SlackService,
+    uid: &str,
+    connection: &SlackConnection,
+    tz: Tz,
+    date: NaiveDate,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let start = tz
+        .from_local_datetime(&date.and_time(chrono::NaiveTime::MIN))
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let end = start + Duration::days(1);
+    let (start_str, end_str) = (start.to_rfc3339(), end.to_rfc3339());
+
+    let conversations = firestore
+        .get_conversations(
+            uid,
+            MAX_DIGEST_ENTRIES * 2,
+            0,
+            false,
+            &["completed".to_string()],
+            None,
+            None,
+            Some(&start_str),
+            Some(&end_str),
+        )
+        .await?;
+    let open_items = firestore
+        .get_action_items(uid, 100, 0, Some(false), None, None, None, None, Some(&end_str), None, None, false)
+        .await?;
+
+    let text = build_digest(date, &conversations, &open_items);
+
+    let channel = match &connection.digest_channel_id {
+        Some(channel) => channel.clone(),
+        None => slack.open_dm(&connection.access_token, &connection.slack_user_id).await?,
+    };
+    slack.post_message(&connection.access_token, &channel, &text).await?;
+
+    tracing::info!("Sent Slack digest for {} to user {}", date, uid);
+    Ok(())
+}
+
+/// Digest text (Slack mrkdwn): today's conversations and open action items due by today
+fn build_digest(date: NaiveDate, conversations: &[Conversation], open_items: &[ActionItemDB]) -> String {
+    let mut out = format!("*Your Omi daily digest — {}*\n", date.format("%A, %B %-d"));
+
+    let conversations: Vec<&Conversation> = conversations
+        .iter()
+        .filter(|c| !c.discarded && !c.structured.title.trim().is_empty())
+        .collect();
+    if conversations.is_empty() {
+        out.push_str("\nNo conversations recorded today.\n");
+    } else {
+        out.push_str(&format!("\n*Conversations ({})*\n", conversations.len()));
+        for c in conversations.iter().take(MAX_DIGEST_ENTRIES) {
+            out.push_str(&format!("{} *{}*", c.structured.emoji, c.structured.title.trim()));
+            let overview = c.structured.overview.trim();
+            if !overview.is_empty() {
+                let short: String = overview.chars().take(200).collect();
+                let ellipsis = if overview.chars().count() > 200 { "…" } else { "" };
+                out.push_str(&format!(" — {}{}", short, ellipsis));
+            }
+            out.push('\n');
+        }
+        if conversations.len() > MAX_DIGEST_ENTRIES {
+            out.push_str(&format!("_…and {} more_\n", conversations.len() - MAX_DIGEST_ENTRIES));
+        }
+    }
+
+    let open_items: Vec<&ActionItemDB> = open_items
+        .iter()
+        .filter(|i| !i.completed && !i.deleted.unwrap_or(false))
+        .collect();
+    if !open_items.is_empty() {
+        out.push_str(&format!("\n*Open action items ({})*\n", open_items.len()));
+        for item in open_items.iter().take(MAX_DIGEST_ENTRIES) {
+            out.push_str(&format!("• {}\n", item.description.trim()));
+        }
+        if open_items.len() > MAX_DIGEST_ENTRIES {
+            out.push_str(&format!("_…and {} more_\n", open_items.len() - MAX_DIGEST_ENTRIES));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", timestamp).as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_slack_signature() {
+        let body = b"token=x&team_id=T1&user_id=U1&text=buy+milk";
+        let now = 1_700_000_000;
+        let ts = now.to_string();
+        let signature = sign("secret", &ts, body);
+
+        assert!(verify_slack_signature("secret", &ts, &signature, body, now));
+        assert!(!verify_slack_signature("other", &ts, &signature, body, now));
+        assert!(!verify_slack_signature("secret", &ts, &signature, b"tampered", now));
+        // Replayed request
+        assert!(!verify_slack_signature("secret", &ts, &signature, body, now + MAX_REQUEST_AGE_SECS + 1));
+    }
+
+    #[test]
+    fn test_build_digest_lists_open_items_only() {
+        let now = Utc::now();
+        let items: Vec<ActionItemDB> = serde_json::from_value(json!([
+            {"id": "a", "description": "Send invoice", "completed": false, "created_at": now, "updated_at": null, "due_at": null, "completed_at": null, "conversation_id": null},
+            {"id": "b", "description": "Old task", "completed": true, "created_at": now, "updated_at": null, "due_at": null, "completed_at": null, "conversation_id": null}
+        ]))
+        .unwrap();
+        let digest = build_digest(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(), &[], &items);
+        assert!(digest.starts_with("*Your Omi daily digest — Monday, March 2*"));
+        assert!(digest.contains("No conversations recorded today."));
+        assert!(digest.contains("*Open action items (1)*\n• Send invoice\n"));
+        assert!(!digest.contains("Old task"));
+    }
+}
@@ -0,0 +1,184 @@
+// Snooze - Picks when a smart-snoozed action item or advice resurfaces
+// Ahead of the due date or the next morning, then moved out of nights, meetings and focus hours.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{
+    BusyBlock, FocusSessionDB, FocusStatus, SnoozeBasis, SnoozeRequest, MAX_SNOOZE_BUSY_BLOCKS,
+    MAX_SNOOZE_DAYS,
+};
+use crate::services::rollover::local_to_utc;
+use crate::services::FirestoreService;
+
+/// Local hour a "next morning" snooze resurfaces at; nothing resurfaces before it
+const MORNING_HOUR: u32 = 9;
+/// Local hour from which nothing resurfaces until the next morning
+const NIGHT_START_HOUR: u32 = 21;
+/// Shortest smart snooze
+const MIN_SMART_SNOOZE_MINUTES: i64 = 30;
+/// Focus sessions from this many days back make up the focus schedule
+pub const FOCUS_HISTORY_DAYS: i64 = 14;
+/// A local hour is focus time with at least this many focused sessions in it...
+const MIN_FOCUSED_SESSIONS_PER_HOUR: usize = 5;
+/// ...making up at least this share of the hour's sessions
+const MIN_FOCUSED_SHARE: f64 = 0.7;
+/// Adjustment rounds before settling for the current candidate
+const MAX_ADJUSTMENTS: usize = 48;
+/// Focus sessions read for the focus schedule
+const MAX_FOCUS_SESSIONS: usize = 2000;
+
+/// Local hours the user is usually focused in, from recent focus sessions
+pub fn focus_hours(sessions: &[FocusSessionDB], tz: Tz) -> [bool; 24] {
+    let mut focused = [0usize; 24];
+    let mut total = [0usize; 24];
+    for session in sessions {
+        let hour = session.created_at.with_timezone(&tz).hour() as usize;
+        total[hour] += 1;
+        if session.status == FocusStatus::Focused {
+            focused[hour] += 1;
+        }
+    }
+    std::array::from_fn(|h| {
+        focused[h] >= MIN_FOCUSED_SESSIONS_PER_HOUR && focused[h] as f64 >= total[h] as f64 * MIN_FOCUSED_SHARE
+    })
+}
+
+fn at_hour(tz: Tz, date: NaiveDate, hour: u32) -> DateTime<Utc> {
+    local_to_utc(tz, date, NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN))
+}
+
+/// MORNING_HOUR today if it's still ahead, else tomorrow
+fn next_morning(local: DateTime<Tz>) -> DateTime<Utc> {
+    let date = local.date_naive();
+    let date = if local.hour() < MORNING_HOUR { date } else { date + Duration::days(1) };
+    at_hour(local.timezone(), date, MORNING_HOUR)
+}
+
+/// When a smart snooze resurfaces. With an upcoming due date: a day ahead of it, or
+/// halfway there when it's due within two days. Otherwise the next morning. The time
+/// is then moved past nights, busy calendar blocks and usual focus hours, unless that
+/// would push it beyond the due date.
+pub fn smart_snooze_until(
+    now: DateTime<Tz>,
+    due_at: Option<DateTime<Utc>>,
+    busy: &[BusyBlock],
+    focus_hours: &[bool; 24],
+) -> (DateTime<Utc>, SnoozeBasis) {
+    let tz = now.timezone();
+    let earliest = now.with_timezone(&Utc) + Duration::minutes(MIN_SMART_SNOOZE_MINUTES);
+    let upcoming_due = due_at.filter(|due| *due > earliest);
+
+    let (initial, basis) = match upcoming_due {
+        Some(due) => {
+            let remaining = due - now.with_timezone(&Utc);
+            let lead = if remaining > Duration::days(2) { Duration::days(1) } else { remaining / 2 };
+            ((due - lead).max(earliest), SnoozeBasis::DueDate)
+        }
+        None => (next_morning(now).max(earliest), SnoozeBasis::NextMorning),
+    };
+
+    let mut candidate = initial;
+    for _ in 0..MAX_ADJUSTMENTS {
+        let local = candidate.with_timezone(&tz);
+        if local.hour() >= NIGHT_START_HOUR || local.hour() < MORNING_HOUR {
+            candidate = next_morning(local);
+        } else if let Some(block) = busy.iter().find(|b| b.start <= candidate && candidate < b.end) {
+            candidate = block.end;
+        } else if focus_hours[local.hour() as usize] {
+            candidate = at_hour(tz, local.date_naive(), local.hour() + 1);
+        } else {
+            break;
+        }
+    }
+
+    if upcoming_due.is_some_and(|due| candidate > due) {
+        candidate = initial;
+    }
+    (candidate, basis)
+}
+
+/// Resolve a snooze request for an item with the given due date. Err is a message
+/// for a 400. A smart snooze without a readable profile or focus history still
+/// works, in UTC and without focus hours.
+pub async fn resolve(
+    firestore: &FirestoreService,
+    uid: &str,
+    request: &SnoozeRequest,
+    due_at: Option<DateTime<Utc>>,
+) -> Result<(DateTime<Utc>, SnoozeBasis), String> {
+    let modes = [request.minutes.is_some(), request.until.is_some(), request.smart];
+    if modes.iter().filter(|set| **set).count() != 1 {
+        return Err("Send exactly one of minutes, until or smart".to_string());
+    }
+    let now = Utc::now();
+    let latest = now + Duration::days(MAX_SNOOZE_DAYS);
+
+    if let Some(minutes) = request.minutes {
+        if !(1..=MAX_SNOOZE_DAYS * 24 * 60).contains(&minutes) {
+            return Err(format!("minutes must be between 1 and {} days' worth", MAX_SNOOZE_DAYS));
+        }
+        return Ok((now + Duration::minutes(minutes), SnoozeBasis::Requested));
+    }
+    if let Some(until) = request.until {
+        if until <= now || until > latest {
+            return Err(format!("until must be in the next {} days", MAX_SNOOZE_DAYS));
+        }
+        return Ok((until, SnoozeBasis::Requested));
+    }
+    if request.busy.len() > MAX_SNOOZE_BUSY_BLOCKS {
+        return Err(format!("At most {} busy blocks are allowed", MAX_SNOOZE_BUSY_BLOCKS));
+    }
+
+    let timezone = match &request.timezone {
+        Some(tz) => Some(tz.clone()),
+        None => firestore.get_user_profile(uid).await.ok().and_then(|p| p.time_zone),
+    };
+    let tz: Tz = timezone.and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC);
+    let history_start = now - Duration::days(FOCUS_HISTORY_DAYS);
+    let sessions: Vec<FocusSessionDB> = match firestore.get_focus_sessions(uid, MAX_FOCUS_SESSIONS, 0, None).await {
+        Ok(sessions) => sessions.into_iter().filter(|s| s.created_at >= history_start).collect(),
+        Err(e) => {
+            tracing::warn!("Smart snooze: failed to load focus sessions for user {}: {}", uid, e);
+            Vec::new()
+        }
+    };
+
+    let (until, basis) = smart_snooze_until(now.with_timezone(&tz), due_at, &request.busy, &focus_hours(&sessions, tz));
+    Ok((until.min(latest), basis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_smart_snooze_until() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        // Wednesday 2026-10-14 10:00 EDT
+        let now = tz.with_ymd_and_hms(2026, 10, 14, 10, 0, 0).unwrap();
+        let utc = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let no_focus = [false; 24];
+
+        // No due date: tomorrow 09:00
+        assert_eq!(
+            smart_snooze_until(now, None, &[], &no_focus),
+            (utc("2026-10-15T13:00:00Z"), SnoozeBasis::NextMorning)
+        );
+        // Due in three hours: halfway there
+        assert_eq!(
+            smart_snooze_until(now, Some(utc("2026-10-14T17:00:00Z")), &[], &no_focus).0,
+            utc("2026-10-14T15:30:00Z")
+        );
+
+        // Due Friday 17:00: a day ahead, after the 16:30-18:00 meeting, skipping the 18:00 focus hour
+        let busy = [BusyBlock { start: utc("2026-10-15T20:30:00Z"), end: utc("2026-10-15T22:00:00Z") }];
+        let mut focus = [false; 24];
+        focus[18] = true;
+        assert_eq!(
+            smart_snooze_until(now, Some(utc("2026-10-16T21:00:00Z")), &busy, &focus),
+            (utc("2026-10-15T23:00:00Z"), SnoozeBasis::DueDate)
+        );
+    }
+}
@@ -0,0 +1,754 @@
+// Storage - The user-data surface handlers use for conversations, memories, action items,
+// messages and settings, so it can be served by Firestore or by a local SQLite file.
+// STORAGE=local (LOCAL_STORAGE_PATH) selects the SQLite store; everything outside this
+// trait still goes through FirestoreService.
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+
+use crate::models::{
+    ActionItem, ActionItemDB, AutoDiscardLevel, Conversation, ConversationTemplate, Event, FocusSessionDB, Folder,
+    FolderRule, Memory, MemoryCategory, MemoryDB, MessageDB, NotificationSettings, ProcessingPromptSettings,
+};
+use crate::services::firestore::{FirestoreError, Precondition};
+use crate::services::FirestoreService;
+
+/// Result of a storage call
+pub type StorageFuture<'a, T> = BoxFuture<'a, Result<T, FirestoreError>>;
+
+/// Where user data lives (STORAGE)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageKind {
+    /// Firestore (the default)
+    #[default]
+    Firestore,
+    /// A local SQLite file, for deployments without Firestore
+    Local,
+}
+
+impl std::str::FromStr for StorageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "firestore" => Ok(Self::Firestore),
+            "local" | "sqlite" => Ok(Self::Local),
+            other => Err(format!("unknown storage '{}'", other)),
+        }
+    }
+}
+
+/// User data operations with the same semantics as the FirestoreService methods of the
+/// same name. Not-found reads are Ok(None); local writes to a missing document are NotFound.
+#[allow(clippy::too_many_arguments)]
+pub trait Storage: Send + Sync {
+    // Conversations
+
+    fn get_conversations<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        include_discarded: bool,
+        statuses: &'a [String],
+        starred: Option<bool>,
+        folder_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> StorageFuture<'a, Vec<Conversation>>;
+
+    fn get_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, Option<Conversation>>;
+
+    fn save_conversation<'a>(&'a self, uid: &'a str, conversation: &'a Conversation) -> StorageFuture<'a, ()>;
+
+    fn delete_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, ()>;
+
+    fn update_conversation_title<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        title: &'a str,
+    ) -> StorageFuture<'a, ()>;
+
+    fn set_conversation_starred<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        starred: bool,
+    ) -> StorageFuture<'a, ()>;
+
+    fn get_conversations_count<'a>(
+        &'a self,
+        uid: &'a str,
+        include_discarded: bool,
+        statuses: &'a [String],
+    ) -> StorageFuture<'a, i64>;
+
+    fn get_auto_discarded_conversations<'a>(&'a self, uid: &'a str, limit: usize) -> StorageFuture<'a, Vec<Conversation>>;
+
+    fn set_conversation_memory_extraction_disabled<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        disabled: bool,
+    ) -> StorageFuture<'a, ()>;
+
+    fn update_conversation_events<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        events: &'a [Event],
+    ) -> StorageFuture<'a, ()>;
+
+    fn update_conversation_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        action_items: &'a [ActionItem],
+    ) -> StorageFuture<'a, ()>;
+
+    /// Non-empty titles of the most recent conversations
+    fn get_recent_conversation_titles<'a>(&'a self, uid: &'a str, limit: usize) -> StorageFuture<'a, Vec<String>> {
+        Box::pin(async move {
+            Ok(self
+                .get_conversations(uid, limit, 0, false, &[], None, None, None, None)
+                .await?
+                .into_iter()
+                .map(|c| c.structured.title)
+                .filter(|t| !t.trim().is_empty())
+                .collect())
+        })
+    }
+
+    // Memories
+
+    fn get_memories_filtered<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        category: Option<&'a str>,
+        tags: Option<&'a [String]>,
+        include_dismissed: bool,
+    ) -> StorageFuture<'a, Vec<MemoryDB>>;
+
+    fn create_memory<'a>(
+        &'a self,
+        uid: &'a str,
+        content: &'a str,
+        visibility: &'a str,
+        category: Option<MemoryCategory>,
+        confidence: Option<f64>,
+        source_app: Option<&'a str>,
+        context_summary: Option<&'a str>,
+        tags: &'a [String],
+        reasoning: Option<&'a str>,
+        current_activity: Option<&'a str>,
+        source: Option<&'a str>,
+        window_title: Option<&'a str>,
+    ) -> StorageFuture<'a, String>;
+
+    fn delete_memory<'a>(&'a self, uid: &'a str, memory_id: &'a str) -> StorageFuture<'a, ()>;
+
+    fn update_memory_content<'a>(&'a self, uid: &'a str, memory_id: &'a str, content: &'a str) -> StorageFuture<'a, ()>;
+
+    fn update_memory_visibility<'a>(
+        &'a self,
+        uid: &'a str,
+        memory_id: &'a str,
+        visibility: &'a str,
+    ) -> StorageFuture<'a, ()>;
+
+    /// Listed memories (not rejected or dismissed), best scored first
+    fn get_memories<'a>(&'a self, uid: &'a str, limit: usize) -> StorageFuture<'a, Vec<MemoryDB>> {
+        self.get_memories_filtered(uid, limit, 0, None, None, false)
+    }
+
+    /// Save memories extracted from a conversation, pending review; returns the saved IDs
+    fn save_memories<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        memories: &'a [Memory],
+    ) -> StorageFuture<'a, Vec<String>>;
+
+    /// Delete the extracted (not manually added) memories of a conversation
+    fn delete_memories_for_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, usize>;
+
+    // Action items
+
+    fn get_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        completed_filter: Option<bool>,
+        conversation_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+        due_start_date: Option<&'a str>,
+        due_end_date: Option<&'a str>,
+        sort_by: Option<&'a str>,
+        include_deleted: Option<bool>,
+        include_snoozed: bool,
+    ) -> StorageFuture<'a, Vec<ActionItemDB>>;
+
+    fn get_action_item_by_id<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, Option<ActionItemDB>>;
+
+    /// An action item with an opaque version, for a [`Precondition::UpdateTime`] on a
+    /// following write
+    fn get_action_item_versioned<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+    ) -> StorageFuture<'a, Option<(ActionItemDB, String)>>;
+
+    fn create_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+        from_staged: Option<bool>,
+        recurrence_rule: Option<&'a str>,
+        recurrence_parent_id: Option<&'a str>,
+    ) -> StorageFuture<'a, ActionItemDB>;
+
+    fn update_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        completed: Option<bool>,
+        description: Option<&'a str>,
+        due_at: Option<DateTime<Utc>>,
+        priority: Option<&'a str>,
+        category: Option<&'a str>,
+        goal_id: Option<&'a str>,
+        relevance_score: Option<i32>,
+        sort_order: Option<i32>,
+        indent_level: Option<i32>,
+        recurrence_rule: Option<&'a str>,
+        rollover_excluded: Option<bool>,
+        precondition: Option<&'a Precondition>,
+    ) -> StorageFuture<'a, ActionItemDB>;
+
+    fn delete_action_item<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()>;
+
+    fn link_action_item_to_conversation<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        conversation_id: &'a str,
+    ) -> StorageFuture<'a, ActionItemDB>;
+
+    // Staged tasks (extracted action items awaiting promotion)
+
+    fn create_staged_task<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+    ) -> StorageFuture<'a, ActionItemDB>;
+
+    fn get_staged_tasks<'a>(&'a self, uid: &'a str, limit: usize, offset: usize) -> StorageFuture<'a, Vec<ActionItemDB>>;
+
+    fn delete_staged_task<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()>;
+
+    // Messages
+
+    fn get_messages<'a>(
+        &'a self,
+        uid: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        limit: usize,
+        offset: usize,
+    ) -> StorageFuture<'a, Vec<MessageDB>>;
+
+    fn save_message<'a>(
+        &'a self,
+        uid: &'a str,
+        text: &'a str,
+        sender: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        metadata: Option<&'a str>,
+    ) -> StorageFuture<'a, MessageDB>;
+
+    fn delete_messages<'a>(&'a self, uid: &'a str, app_id: Option<&'a str>) -> StorageFuture<'a, usize>;
+
+    // Settings
+
+    fn get_user_language<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, String>;
+
+    fn update_user_language<'a>(&'a self, uid: &'a str, language: &'a str) -> StorageFuture<'a, ()>;
+
+    fn get_processing_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, ProcessingPromptSettings>;
+
+    fn update_processing_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        prompt: Option<&'a str>,
+        plain_titles: Option<bool>,
+        auto_discard: Option<AutoDiscardLevel>,
+    ) -> StorageFuture<'a, ()>;
+
+    fn get_notification_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, NotificationSettings>;
+
+    fn update_notification_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        enabled: Option<bool>,
+        frequency: Option<i32>,
+    ) -> StorageFuture<'a, NotificationSettings>;
+
+    /// The custom processing prompt, None when unset
+    fn get_processing_prompt<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let prompt = self.get_processing_settings(uid).await?.prompt;
+            Ok(Some(prompt).filter(|p| !p.is_empty()))
+        })
+    }
+
+    fn get_blocked_memory_topics<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<String>>;
+
+    // Processing context: optional inputs to conversation processing
+
+    fn get_conversation_templates<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<ConversationTemplate>>;
+
+    fn get_folders<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<Folder>>;
+
+    fn get_folder_rules<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<FolderRule>>;
+
+    /// Focus sessions that started in [start, end), oldest first
+    fn get_focus_sessions_between<'a>(
+        &'a self,
+        uid: &'a str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+    ) -> StorageFuture<'a, Vec<FocusSessionDB>>;
+}
+
+// Inherent methods take precedence, so each call below goes to FirestoreService itself
+impl Storage for FirestoreService {
+    fn get_conversations<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        include_discarded: bool,
+        statuses: &'a [String],
+        starred: Option<bool>,
+        folder_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> StorageFuture<'a, Vec<Conversation>> {
+        Box::pin(self.get_conversations(
+            uid,
+            limit,
+            offset,
+            include_discarded,
+            statuses,
+            starred,
+            folder_id,
+            start_date,
+            end_date,
+        ))
+    }
+
+    fn get_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, Option<Conversation>> {
+        Box::pin(self.get_conversation(uid, conversation_id))
+    }
+
+    fn save_conversation<'a>(&'a self, uid: &'a str, conversation: &'a Conversation) -> StorageFuture<'a, ()> {
+        Box::pin(self.save_conversation(uid, conversation))
+    }
+
+    fn delete_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.delete_conversation(uid, conversation_id))
+    }
+
+    fn update_conversation_title<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        title: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_conversation_title(uid, conversation_id, title))
+    }
+
+    fn set_conversation_starred<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        starred: bool,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.set_conversation_starred(uid, conversation_id, starred))
+    }
+
+    fn get_memories_filtered<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        category: Option<&'a str>,
+        tags: Option<&'a [String]>,
+        include_dismissed: bool,
+    ) -> StorageFuture<'a, Vec<MemoryDB>> {
+        Box::pin(self.get_memories_filtered(uid, limit, offset, category, tags, include_dismissed))
+    }
+
+    fn create_memory<'a>(
+        &'a self,
+        uid: &'a str,
+        content: &'a str,
+        visibility: &'a str,
+        category: Option<MemoryCategory>,
+        confidence: Option<f64>,
+        source_app: Option<&'a str>,
+        context_summary: Option<&'a str>,
+        tags: &'a [String],
+        reasoning: Option<&'a str>,
+        current_activity: Option<&'a str>,
+        source: Option<&'a str>,
+        window_title: Option<&'a str>,
+    ) -> StorageFuture<'a, String> {
+        Box::pin(self.create_memory(
+            uid,
+            content,
+            visibility,
+            category,
+            confidence,
+            source_app,
+            context_summary,
+            tags,
+            reasoning,
+            current_activity,
+            source,
+            window_title,
+        ))
+    }
+
+    fn delete_memory<'a>(&'a self, uid: &'a str, memory_id: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.delete_memory(uid, memory_id))
+    }
+
+    fn update_memory_content<'a>(&'a self, uid: &'a str, memory_id: &'a str, content: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_memory_content(uid, memory_id, content))
+    }
+
+    fn update_memory_visibility<'a>(
+        &'a self,
+        uid: &'a str,
+        memory_id: &'a str,
+        visibility: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_memory_visibility(uid, memory_id, visibility))
+    }
+
+    fn get_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        limit: usize,
+        offset: usize,
+        completed_filter: Option<bool>,
+        conversation_id: Option<&'a str>,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+        due_start_date: Option<&'a str>,
+        due_end_date: Option<&'a str>,
+        sort_by: Option<&'a str>,
+        include_deleted: Option<bool>,
+        include_snoozed: bool,
+    ) -> StorageFuture<'a, Vec<ActionItemDB>> {
+        Box::pin(self.get_action_items(
+            uid,
+            limit,
+            offset,
+            completed_filter,
+            conversation_id,
+            start_date,
+            end_date,
+            due_start_date,
+            due_end_date,
+            sort_by,
+            include_deleted,
+            include_snoozed,
+        ))
+    }
+
+    fn get_action_item_by_id<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, Option<ActionItemDB>> {
+        Box::pin(self.get_action_item_by_id(uid, item_id))
+    }
+
+    fn get_action_item_versioned<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+    ) -> StorageFuture<'a, Option<(ActionItemDB, String)>> {
+        Box::pin(self.get_action_item_versioned(uid, item_id))
+    }
+
+    fn create_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+        from_staged: Option<bool>,
+        recurrence_rule: Option<&'a str>,
+        recurrence_parent_id: Option<&'a str>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        Box::pin(self.create_action_item(
+            uid,
+            description,
+            due_at,
+            source,
+            priority,
+            metadata,
+            category,
+            relevance_score,
+            from_staged,
+            recurrence_rule,
+            recurrence_parent_id,
+        ))
+    }
+
+    fn update_action_item<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        completed: Option<bool>,
+        description: Option<&'a str>,
+        due_at: Option<DateTime<Utc>>,
+        priority: Option<&'a str>,
+        category: Option<&'a str>,
+        goal_id: Option<&'a str>,
+        relevance_score: Option<i32>,
+        sort_order: Option<i32>,
+        indent_level: Option<i32>,
+        recurrence_rule: Option<&'a str>,
+        rollover_excluded: Option<bool>,
+        precondition: Option<&'a Precondition>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        Box::pin(self.update_action_item(
+            uid,
+            item_id,
+            completed,
+            description,
+            due_at,
+            priority,
+            category,
+            goal_id,
+            relevance_score,
+            sort_order,
+            indent_level,
+            recurrence_rule,
+            rollover_excluded,
+            precondition,
+        ))
+    }
+
+    fn delete_action_item<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.delete_action_item(uid, item_id))
+    }
+
+    fn get_messages<'a>(
+        &'a self,
+        uid: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        limit: usize,
+        offset: usize,
+    ) -> StorageFuture<'a, Vec<MessageDB>> {
+        Box::pin(self.get_messages(uid, app_id, session_id, limit, offset))
+    }
+
+    fn save_message<'a>(
+        &'a self,
+        uid: &'a str,
+        text: &'a str,
+        sender: &'a str,
+        app_id: Option<&'a str>,
+        session_id: Option<&'a str>,
+        metadata: Option<&'a str>,
+    ) -> StorageFuture<'a, MessageDB> {
+        Box::pin(self.save_message(uid, text, sender, app_id, session_id, metadata))
+    }
+
+    fn delete_messages<'a>(&'a self, uid: &'a str, app_id: Option<&'a str>) -> StorageFuture<'a, usize> {
+        Box::pin(self.delete_messages(uid, app_id))
+    }
+
+    fn get_user_language<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, String> {
+        Box::pin(self.get_user_language(uid))
+    }
+
+    fn update_user_language<'a>(&'a self, uid: &'a str, language: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_user_language(uid, language))
+    }
+
+    fn get_processing_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, ProcessingPromptSettings> {
+        Box::pin(self.get_processing_settings(uid))
+    }
+
+    fn update_processing_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        prompt: Option<&'a str>,
+        plain_titles: Option<bool>,
+        auto_discard: Option<AutoDiscardLevel>,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_processing_settings(uid, prompt, plain_titles, auto_discard))
+    }
+
+    fn get_notification_settings<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, NotificationSettings> {
+        Box::pin(self.get_notification_settings(uid))
+    }
+
+    fn update_notification_settings<'a>(
+        &'a self,
+        uid: &'a str,
+        enabled: Option<bool>,
+        frequency: Option<i32>,
+    ) -> StorageFuture<'a, NotificationSettings> {
+        Box::pin(self.update_notification_settings(uid, enabled, frequency))
+    }
+
+    fn get_conversations_count<'a>(
+        &'a self,
+        uid: &'a str,
+        include_discarded: bool,
+        statuses: &'a [String],
+    ) -> StorageFuture<'a, i64> {
+        Box::pin(self.get_conversations_count(uid, include_discarded, statuses))
+    }
+
+    fn get_auto_discarded_conversations<'a>(&'a self, uid: &'a str, limit: usize) -> StorageFuture<'a, Vec<Conversation>> {
+        Box::pin(self.get_auto_discarded_conversations(uid, limit))
+    }
+
+    fn set_conversation_memory_extraction_disabled<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        disabled: bool,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.set_conversation_memory_extraction_disabled(uid, conversation_id, disabled))
+    }
+
+    fn update_conversation_events<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        events: &'a [Event],
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_conversation_events(uid, conversation_id, events))
+    }
+
+    fn update_conversation_action_items<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        action_items: &'a [ActionItem],
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(self.update_conversation_action_items(uid, conversation_id, action_items))
+    }
+
+    fn save_memories<'a>(
+        &'a self,
+        uid: &'a str,
+        conversation_id: &'a str,
+        memories: &'a [Memory],
+    ) -> StorageFuture<'a, Vec<String>> {
+        Box::pin(self.save_memories(uid, conversation_id, memories))
+    }
+
+    fn delete_memories_for_conversation<'a>(&'a self, uid: &'a str, conversation_id: &'a str) -> StorageFuture<'a, usize> {
+        Box::pin(self.delete_memories_for_conversation(uid, conversation_id))
+    }
+
+    fn link_action_item_to_conversation<'a>(
+        &'a self,
+        uid: &'a str,
+        item_id: &'a str,
+        conversation_id: &'a str,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        Box::pin(self.link_action_item_to_conversation(uid, item_id, conversation_id))
+    }
+
+    fn create_staged_task<'a>(
+        &'a self,
+        uid: &'a str,
+        description: &'a str,
+        due_at: Option<DateTime<Utc>>,
+        source: Option<&'a str>,
+        priority: Option<&'a str>,
+        metadata: Option<&'a str>,
+        category: Option<&'a str>,
+        relevance_score: Option<i32>,
+    ) -> StorageFuture<'a, ActionItemDB> {
+        Box::pin(self.create_staged_task(uid, description, due_at, source, priority, metadata, category, relevance_score))
+    }
+
+    fn get_staged_tasks<'a>(&'a self, uid: &'a str, limit: usize, offset: usize) -> StorageFuture<'a, Vec<ActionItemDB>> {
+        Box::pin(self.get_staged_tasks(uid, limit, offset))
+    }
+
+    fn delete_staged_task<'a>(&'a self, uid: &'a str, item_id: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.delete_staged_task(uid, item_id))
+    }
+
+    fn get_blocked_memory_topics<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<String>> {
+        Box::pin(self.get_blocked_memory_topics(uid))
+    }
+
+    fn get_conversation_templates<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<ConversationTemplate>> {
+        Box::pin(self.get_conversation_templates(uid))
+    }
+
+    fn get_folders<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<Folder>> {
+        Box::pin(self.get_folders(uid))
+    }
+
+    fn get_folder_rules<'a>(&'a self, uid: &'a str) -> StorageFuture<'a, Vec<FolderRule>> {
+        Box::pin(self.get_folder_rules(uid))
+    }
+
+    fn get_focus_sessions_between<'a>(
+        &'a self,
+        uid: &'a str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+    ) -> StorageFuture<'a, Vec<FocusSessionDB>> {
+        Box::pin(self.get_focus_sessions_between(uid, start, end, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_kind_parse() {
+        assert_eq!("local".parse::<StorageKind>(), Ok(StorageKind::Local));
+        assert_eq!(" SQLite ".parse::<StorageKind>(), Ok(StorageKind::Local));
+        assert_eq!("firestore".parse::<StorageKind>(), Ok(StorageKind::Firestore));
+        assert!("postgres".parse::<StorageKind>().is_err());
+    }
+}
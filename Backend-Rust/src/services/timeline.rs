@@ -0,0 +1,212 @@
+// Timeline - A day's conversations, focus sessions, advice and action items as one feed
+// Backs the Swift "Today" view. Received emails aren't stored by this backend, so they
+// aren't part of the feed.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{
+    ActionItemDB, AdviceDB, Conversation, FocusSessionDB, TimelineCounts, TimelineEntry, TimelineItem,
+    TimelineResponse,
+};
+use crate::services::rollover::local_to_utc;
+use crate::services::FirestoreService;
+
+/// Most entries of each kind read for one day
+const MAX_ENTRIES_PER_KIND: usize = 200;
+/// Completed action items scanned for ones completed in the range
+const MAX_COMPLETED_SCAN: usize = 500;
+
+/// UTC bounds [start, end) of `date` in `tz`, or of the last 24 hours when no date is given
+pub fn timeline_bounds(tz: Tz, date: Option<NaiveDate>, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    match date {
+        Some(date) => (
+            local_to_utc(tz, date, NaiveTime::MIN),
+            local_to_utc(tz, date + Duration::days(1), NaiveTime::MIN),
+        ),
+        None => (now - Duration::hours(24), now),
+    }
+}
+
+/// Everything that happened on `date` in `tz` (or in the last 24 hours)
+pub async fn build_timeline(
+    firestore: &FirestoreService,
+    uid: &str,
+    tz: Tz,
+    date: Option<NaiveDate>,
+) -> Result<TimelineResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let (start, end) = timeline_bounds(tz, date, Utc::now());
+    let (start_str, end_str) = (start.to_rfc3339(), end.to_rfc3339());
+    let statuses = ["completed".to_string()];
+
+    let (conversations, focus_sessions, advice, created, completed) = tokio::try_join!(
+        firestore.get_conversations(
+            uid,
+            MAX_ENTRIES_PER_KIND,
+            0,
+            false,
+            &statuses,
+            None,
+            None,
+            Some(&start_str),
+            Some(&end_str),
+        ),
+        firestore.get_focus_sessions_between(uid, start, end, MAX_ENTRIES_PER_KIND),
+        firestore.get_advice_between(uid, start, end, MAX_ENTRIES_PER_KIND),
+        firestore.get_action_items(
+            uid,
+            MAX_ENTRIES_PER_KIND,
+            0,
+            None,
+            None,
+            Some(&start_str),
+            Some(&end_str),
+            None,
+            None,
+            None,
+            None,
+            true,
+        ),
+        firestore.get_action_items(uid, MAX_COMPLETED_SCAN, 0, Some(true), None, None, None, None, None, None, None, true),
+    )?;
+
+    let (counts, entries) = assemble(start, end, conversations, focus_sessions, advice, created, completed);
+    Ok(TimelineResponse {
+        date,
+        timezone: tz.name().to_string(),
+        start,
+        end,
+        counts,
+        entries,
+    })
+}
+
+/// Merge the sources into entries in [start, end), oldest first
+fn assemble(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conversations: Vec<Conversation>,
+    focus_sessions: Vec<FocusSessionDB>,
+    advice: Vec<AdviceDB>,
+    created: Vec<ActionItemDB>,
+    completed: Vec<ActionItemDB>,
+) -> (TimelineCounts, Vec<TimelineEntry>) {
+    let in_range = |at: DateTime<Utc>| at >= start && at < end;
+    let mut counts = TimelineCounts::default();
+    let mut entries = Vec::new();
+
+    for conversation in conversations.into_iter().filter(|c| in_range(c.created_at)) {
+        counts.conversations += 1;
+        entries.push(TimelineEntry {
+            at: conversation.started_at,
+            item: TimelineItem::Conversation {
+                id: conversation.id,
+                title: conversation.structured.title,
+                overview: conversation.structured.overview,
+                emoji: conversation.structured.emoji,
+                category: conversation.structured.category,
+                finished_at: conversation.finished_at,
+            },
+        });
+    }
+    for session in focus_sessions.into_iter().filter(|s| in_range(s.created_at)) {
+        counts.focus_sessions += 1;
+        entries.push(TimelineEntry {
+            at: session.created_at,
+            item: TimelineItem::FocusSession {
+                id: session.id,
+                status: session.status,
+                app_or_site: session.app_or_site,
+                description: session.description,
+                duration_seconds: session.duration_seconds,
+            },
+        });
+    }
+    for advice in advice.into_iter().filter(|a| in_range(a.created_at)) {
+        counts.advice += 1;
+        entries.push(TimelineEntry {
+            at: advice.created_at,
+            item: TimelineItem::Advice {
+                id: advice.id,
+                content: advice.content,
+                category: advice.category,
+                source_app: advice.source_app,
+            },
+        });
+    }
+    for item in created.into_iter().filter(|i| in_range(i.created_at)) {
+        counts.action_items_created += 1;
+        entries.push(TimelineEntry {
+            at: item.created_at,
+            item: TimelineItem::ActionItemCreated {
+                id: item.id,
+                description: item.description,
+                conversation_id: item.conversation_id,
+            },
+        });
+    }
+    for item in completed {
+        let Some(completed_at) = item.completed_at.filter(|at| in_range(*at)) else {
+            continue;
+        };
+        if item.deleted.unwrap_or(false) {
+            continue;
+        }
+        counts.action_items_completed += 1;
+        entries.push(TimelineEntry {
+            at: completed_at,
+            item: TimelineItem::ActionItemCompleted {
+                id: item.id,
+                description: item.description,
+            },
+        });
+    }
+
+    // Stable, so same-instant entries keep the source order above
+    entries.sort_by_key(|entry| entry.at);
+    (counts, entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn action_item(id: &str, created_at: DateTime<Utc>, completed_at: Option<DateTime<Utc>>) -> ActionItemDB {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "description": format!("Task {}", id),
+            "completed": completed_at.is_some(),
+            "created_at": created_at,
+            "completed_at": completed_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_day_bounds_and_ordering() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let (start, end) = timeline_bounds(tz, Some(date), Utc::now());
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 10, 5, 0, 0).unwrap());
+        // DST starts that day: 23 hours long
+        assert_eq!(end - start, Duration::hours(23));
+
+        let at = |hour| start + Duration::hours(hour);
+        let created = vec![action_item("late", at(9), None), action_item("yesterday", at(-2), None)];
+        let completed = vec![
+            action_item("done", at(-30), Some(at(3))),
+            action_item("done-earlier", at(-30), Some(at(-1))),
+        ];
+        let (counts, entries) = assemble(start, end, vec![], vec![], vec![], created, completed);
+
+        assert_eq!(counts.action_items_created, 1);
+        assert_eq!(counts.action_items_completed, 1);
+        let order: Vec<DateTime<Utc>> = entries.iter().map(|e| e.at).collect();
+        assert_eq!(order, vec![at(3), at(9)]);
+        assert!(matches!(
+            &entries[0].item,
+            TimelineItem::ActionItemCompleted { id, .. } if id == "done"
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+// Token refresh service - proactive renewal of credentials in the background
+// Keeps the Firestore access token and Firebase signing keys fresh so requests
+// never pay refresh latency and key rotation doesn't break auth
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::FirebaseAuth;
+use crate::services::FirestoreService;
+
+/// Renew Firestore tokens this long before they expire
+const FIRESTORE_REFRESH_MARGIN_SECS: i64 = 300;
+/// Never wait longer than this between Firestore token checks
+const FIRESTORE_MAX_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Re-fetch Firebase keys at this fraction of their Cache-Control max-age
+const KEY_REFRESH_FRACTION: f64 = 0.9;
+/// Cap for exponential retry backoff
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Spawn a task that renews the Firestore access token before it expires
+pub fn spawn_firestore_token_refresh(firestore: Arc<FirestoreService>) {
+    tokio::spawn(async move {
+        let mut failures: u32 = 0;
+        loop {
+            let delay = match firestore.refresh_expiring_tokens(FIRESTORE_REFRESH_MARGIN_SECS).await {
+                Ok(next_in) => {
+                    failures = 0;
+                    Duration::from_secs(next_in as u64).min(FIRESTORE_MAX_CHECK_INTERVAL)
+                }
+                Err(e) => {
+                    failures += 1;
+                    let delay = retry_delay(failures);
+                    tracing::warn!(
+                        "Firestore token refresh failed (attempt {}): {} - retrying in {}s",
+                        failures,
+                        e,
+                        delay.as_secs()
+                    );
+                    delay
+                }
+            };
+            tokio::time::sleep(with_jitter(delay)).await;
+        }
+    });
+}
+
+/// Spawn a task that re-fetches Firebase public keys on their Cache-Control schedule.
+/// `first_refresh_in` is the max-age from the startup fetch (or a short delay if it failed).
+pub fn spawn_firebase_key_refresh(auth: Arc<FirebaseAuth>, first_refresh_in: Duration) {
+    tokio::spawn(async move {
+        let mut delay = first_refresh_in.mul_f64(KEY_REFRESH_FRACTION);
+        let mut failures: u32 = 0;
+        loop {
+            tokio::time::sleep(with_jitter(delay)).await;
+            delay = match auth.refresh_keys().await {
+                Ok(max_age) => {
+                    failures = 0;
+                    max_age.mul_f64(KEY_REFRESH_FRACTION)
+                }
+                Err(e) => {
+                    failures += 1;
+                    let delay = retry_delay(failures);
+                    tracing::warn!(
+                        "Firebase key refresh failed (attempt {}): {} - retrying in {}s",
+                        failures,
+                        e,
+                        delay.as_secs()
+                    );
+                    delay
+                }
+            };
+        }
+    });
+}
+
+/// Exponential backoff: 2s, 4s, 8s, ... capped at MAX_RETRY_DELAY
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.clamp(1, 16)).min(MAX_RETRY_DELAY)
+}
+
+/// Randomize a delay by ±10% so replicas don't refresh in lockstep
+fn with_jitter(delay: Duration) -> Duration {
+    let unit = (uuid::Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0; // [0, 1)
+    delay.mul_f64(0.9 + 0.2 * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert_eq!(retry_delay(30), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        for _ in 0..100 {
+            let d = with_jitter(Duration::from_secs(100));
+            assert!(d >= Duration::from_secs(90) && d <= Duration::from_secs(110));
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Transcript chunks - Splits oversized transcript payloads across documents
+// Firestore caps documents at 1 MiB, so a compressed (or encrypted) transcript larger than
+// TRANSCRIPT_INLINE_MAX_BYTES is stored in users/{uid}/conversations/{id}/transcript_chunks/{index}
+// and the conversation keeps only transcript_chunk_count.
+
+use base64::Engine;
+use serde_json::{json, Value};
+
+/// Largest transcript payload kept on the conversation document itself; leaves room
+/// for the summary, photos and app results
+pub const TRANSCRIPT_INLINE_MAX_BYTES: usize = 512 * 1024;
+/// Payload bytes per chunk document
+pub const TRANSCRIPT_CHUNK_BYTES: usize = 768 * 1024;
+
+/// An encoded `transcript_segments` value, as stored in Firestore
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptPayload {
+    /// Encrypted hex of the compressed JSON (enhanced protection), a stringValue
+    Encrypted(String),
+    /// zlib-compressed JSON, a bytesValue
+    Compressed(Vec<u8>),
+}
+
+impl TranscriptPayload {
+    /// Read a stringValue or bytesValue field; None for plain arrays and other values
+    pub fn from_field(value: &Value) -> Option<Self> {
+        if let Some(s) = value.get("stringValue").and_then(|v| v.as_str()) {
+            return Some(TranscriptPayload::Encrypted(s.to_string()));
+        }
+        let b64 = value.get("bytesValue")?.as_str()?;
+        base64::engine::general_purpose::STANDARD
+            .decode(b64)
+            .ok()
+            .map(TranscriptPayload::Compressed)
+    }
+
+    /// The Firestore field value
+    pub fn to_field(&self) -> Value {
+        match self {
+            TranscriptPayload::Encrypted(s) => json!({"stringValue": s}),
+            TranscriptPayload::Compressed(bytes) => {
+                json!({"bytesValue": base64::engine::general_purpose::STANDARD.encode(bytes)})
+            }
+        }
+    }
+
+    /// Stored size in bytes
+    pub fn len(&self) -> usize {
+        match self {
+            TranscriptPayload::Encrypted(s) => s.len(),
+            TranscriptPayload::Compressed(bytes) => bytes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the payload has to be moved to chunk documents
+    pub fn needs_chunking(&self) -> bool {
+        self.len() > TRANSCRIPT_INLINE_MAX_BYTES
+    }
+
+    /// Split into parts of at most `chunk_bytes` (strings split on char boundaries)
+    pub fn split(&self, chunk_bytes: usize) -> Vec<TranscriptPayload> {
+        match self {
+            TranscriptPayload::Encrypted(s) => {
+                let mut parts = Vec::new();
+                let mut rest = s.as_str();
+                while !rest.is_empty() {
+                    let mut end = chunk_bytes.min(rest.len());
+                    while !rest.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    let (part, tail) = rest.split_at(end);
+                    parts.push(TranscriptPayload::Encrypted(part.to_string()));
+                    rest = tail;
+                }
+                parts
+            }
+            TranscriptPayload::Compressed(bytes) => bytes
+                .chunks(chunk_bytes)
+                .map(|part| TranscriptPayload::Compressed(part.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Reassemble parts in order; None if they're empty or of mixed kinds
+    pub fn join(parts: Vec<TranscriptPayload>) -> Option<Self> {
+        let mut parts = parts.into_iter();
+        let mut joined = parts.next()?;
+        for part in parts {
+            match (&mut joined, part) {
+                (TranscriptPayload::Encrypted(s), TranscriptPayload::Encrypted(p)) => s.push_str(&p),
+                (TranscriptPayload::Compressed(b), TranscriptPayload::Compressed(p)) => b.extend(p),
+                _ => return None,
+            }
+        }
+        Some(joined)
+    }
+}
+
+/// Document id of a chunk; zero-padded so ids sort in chunk order
+pub fn chunk_doc_id(index: usize) -> String {
+    format!("{:04}", index)
+}
+
+/// Fields of a chunk document
+pub fn chunk_fields(index: usize, part: &TranscriptPayload) -> Value {
+    json!({
+        "index": {"integerValue": index.to_string()},
+        "data": part.to_field()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_join() {
+        let bytes: Vec<u8> = (0..2500u32).map(|i| (i % 251) as u8).collect();
+        let compressed = TranscriptPayload::Compressed(bytes);
+        let parts = compressed.split(1000);
+        assert_eq!(parts.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![1000, 1000, 500]);
+        let round_trip: Vec<TranscriptPayload> = parts
+            .iter()
+            .map(|p| TranscriptPayload::from_field(&p.to_field()).unwrap())
+            .collect();
+        assert_eq!(TranscriptPayload::join(round_trip), Some(compressed));
+
+        // Multi-byte characters never straddle two chunks
+        let encrypted = TranscriptPayload::Encrypted("ab€cd€".to_string());
+        let parts = encrypted.split(4);
+        assert!(parts.iter().all(|p| p.len() <= 4));
+        assert_eq!(TranscriptPayload::join(parts), Some(encrypted));
+
+        let mixed = vec![
+            TranscriptPayload::Encrypted("ab".to_string()),
+            TranscriptPayload::Compressed(vec![1]),
+        ];
+        assert_eq!(TranscriptPayload::join(mixed), None);
+        assert_eq!(TranscriptPayload::join(vec![]), None);
+        assert!(!TranscriptPayload::Compressed(vec![0; TRANSCRIPT_INLINE_MAX_BYTES]).needs_chunking());
+        assert_eq!(chunk_doc_id(7), "0007");
+    }
+}
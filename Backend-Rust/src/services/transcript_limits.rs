@@ -0,0 +1,134 @@
+// Transcript limits - Bounds on uploaded transcripts and what happens past them
+// Limits come from config; each upload may pick its own strategy for going over.
+
+use crate::llm::estimate::estimate_tokens;
+use crate::models::{TranscriptSegment, TranscriptTruncation, TruncationStrategy};
+
+/// Size limits for one uploaded transcript
+#[derive(Debug, Clone, Copy)]
+pub struct TranscriptLimits {
+    pub max_segments: usize,
+    pub max_tokens: usize,
+}
+
+/// Transcript that couldn't be brought within the limits
+#[derive(Debug)]
+pub struct TranscriptTooLong {
+    pub segments: usize,
+    pub tokens: usize,
+}
+
+impl std::fmt::Display for TranscriptTooLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transcript too long ({} segments, ~{} tokens)", self.segments, self.tokens)
+    }
+}
+
+impl TranscriptLimits {
+    /// Bring `segments` within the limits using `strategy`. Returns the segments to keep
+    /// and, when the transcript was over the limits, how it was handled.
+    /// `Summarize` keeps everything; the caller condenses it for processing.
+    pub fn apply(
+        &self,
+        segments: Vec<TranscriptSegment>,
+        strategy: TruncationStrategy,
+    ) -> Result<(Vec<TranscriptSegment>, Option<TranscriptTruncation>), TranscriptTooLong> {
+        let tokens: Vec<usize> = segments.iter().map(|s| estimate_tokens(&s.text)).collect();
+        let original_tokens: usize = tokens.iter().sum();
+        if segments.len() <= self.max_segments && original_tokens <= self.max_tokens {
+            return Ok((segments, None));
+        }
+        let too_long = || TranscriptTooLong {
+            segments: segments.len(),
+            tokens: original_tokens,
+        };
+
+        let keep = match strategy {
+            TruncationStrategy::Reject => return Err(too_long()),
+            TruncationStrategy::Summarize => 0..segments.len(),
+            TruncationStrategy::Head => 0..self.fitting(tokens.iter()),
+            TruncationStrategy::Tail => segments.len() - self.fitting(tokens.iter().rev())..segments.len(),
+        };
+        if keep.is_empty() {
+            // Not even one segment fits
+            return Err(too_long());
+        }
+
+        let truncation = TranscriptTruncation {
+            strategy,
+            original_segments: segments.len(),
+            kept_segments: keep.len(),
+            original_tokens,
+            kept_tokens: tokens[keep.clone()].iter().sum(),
+        };
+        let kept = segments.into_iter().skip(keep.start).take(keep.len()).collect();
+        Ok((kept, Some(truncation)))
+    }
+
+    /// How many segments, taken in order, fit both limits
+    fn fitting<'a>(&self, tokens: impl Iterator<Item = &'a usize>) -> usize {
+        let mut total = 0;
+        tokens
+            .take(self.max_segments)
+            .take_while(|t| {
+                total += **t;
+                total <= self.max_tokens
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(words: &[usize]) -> Vec<TranscriptSegment> {
+        words
+            .iter()
+            .map(|w| TranscriptSegment {
+                text: "word ".repeat(*w),
+                speaker: "SPEAKER_00".to_string(),
+                speaker_id: 0,
+                is_user: false,
+                person_id: None,
+                start: 0.0,
+                end: 0.0,
+                language: None,
+                words: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_truncation_strategies() {
+        let tokens = |words: usize| estimate_tokens(&"word ".repeat(words));
+        let limits = TranscriptLimits {
+            max_segments: 3,
+            max_tokens: tokens(10) * 2,
+        };
+
+        let (kept, truncation) = limits.apply(segments(&[10, 10]), TruncationStrategy::Reject).unwrap();
+        assert_eq!((kept.len(), truncation.is_none()), (2, true));
+
+        let long = segments(&[10, 5, 10, 5]);
+        assert!(limits.apply(long.clone(), TruncationStrategy::Reject).is_err());
+
+        let (kept, truncation) = limits.apply(long.clone(), TruncationStrategy::Head).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(truncation.unwrap().kept_tokens, tokens(10) + tokens(5));
+
+        let (kept, _) = limits.apply(long.clone(), TruncationStrategy::Tail).unwrap();
+        assert_eq!(kept.iter().map(|s| s.text.len()).collect::<Vec<_>>(), vec![25, 50, 25]);
+
+        let (kept, truncation) = limits.apply(long, TruncationStrategy::Summarize).unwrap();
+        let truncation = truncation.unwrap();
+        assert_eq!((kept.len(), truncation.kept_segments, truncation.original_segments), (4, 4, 4));
+
+        // The segment count limit applies on its own too
+        let (kept, _) = limits.apply(segments(&[1, 1, 1, 1, 1]), TruncationStrategy::Tail).unwrap();
+        assert_eq!(kept.len(), 3);
+
+        // A single oversized segment can't be truncated to fit
+        assert!(limits.apply(segments(&[50]), TruncationStrategy::Head).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// Transcript search - Map text matches to audio timestamps
+// Uses word-level timing when the segment has it, otherwise interpolates within the segment.
+
+use serde::Serialize;
+
+use crate::models::TranscriptSegment;
+
+/// One match of the query in a transcript
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptHit {
+    pub segment_index: usize,
+    pub speaker_id: i32,
+    pub is_user: bool,
+    /// Full text of the matching segment
+    pub text: String,
+    /// Audio offset of the match, in seconds
+    pub start: f64,
+    pub end: f64,
+    /// True when the times come from word-level timestamps rather than interpolation
+    pub word_level: bool,
+}
+
+fn normalize_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Find `query` in the transcript, in order, up to `limit` hits
+pub fn search_transcript(segments: &[TranscriptSegment], query: &str, limit: usize) -> Vec<TranscriptHit> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(normalize_word)
+        .filter(|t| !t.is_empty())
+        .collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let hit = |start: f64, end: f64, word_level: bool| TranscriptHit {
+            segment_index: index,
+            speaker_id: segment.speaker_id,
+            is_user: segment.is_user,
+            text: segment.text.clone(),
+            start,
+            end,
+            word_level,
+        };
+
+        match segment.words.as_deref() {
+            Some(words) if !words.is_empty() => {
+                let normalized: Vec<String> = words.iter().map(|w| normalize_word(&w.word)).collect();
+                for k in 0..normalized.len().saturating_sub(terms.len() - 1) {
+                    if normalized[k..k + terms.len()] == terms[..] {
+                        hits.push(hit(words[k].start, words[k + terms.len() - 1].end, true));
+                    }
+                }
+            }
+            _ => {
+                let text = segment.text.to_lowercase();
+                let phrase = terms.join(" ");
+                let total_chars = text.chars().count().max(1) as f64;
+                let duration = (segment.end - segment.start).max(0.0);
+                for (byte_offset, _) in text.match_indices(&phrase) {
+                    let char_start = text[..byte_offset].chars().count() as f64;
+                    let char_end = char_start + phrase.chars().count() as f64;
+                    hits.push(hit(
+                        segment.start + duration * char_start / total_chars,
+                        segment.start + duration * (char_end / total_chars).min(1.0),
+                        false,
+                    ));
+                }
+            }
+        }
+        if hits.len() >= limit {
+            hits.truncate(limit);
+            break;
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WordTiming;
+
+    fn segment(text: &str, start: f64, end: f64, words: Option<Vec<WordTiming>>) -> TranscriptSegment {
+        let mut segment: TranscriptSegment = serde_json::from_value(serde_json::json!({
+            "text": text,
+            "start": start,
+            "end": end
+        }))
+        .unwrap();
+        segment.words = words;
+        segment
+    }
+
+    #[test]
+    fn test_word_level_hits() {
+        let words = ["Let's", "ship", "the", "beta,", "Friday."]
+            .iter()
+            .enumerate()
+            .map(|(i, w)| WordTiming {
+                word: w.to_string(),
+                start: 10.0 + i as f64,
+                end: 10.5 + i as f64,
+            })
+            .collect();
+        let segments = vec![segment("Let's ship the beta, Friday.", 10.0, 15.0, Some(words))];
+
+        let hits = search_transcript(&segments, "the Beta", 10);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].word_level);
+        assert_eq!((hits[0].start, hits[0].end), (12.0, 13.5));
+        assert!(search_transcript(&segments, "beta friday monday", 10).is_empty());
+    }
+
+    #[test]
+    fn test_interpolated_hits() {
+        let segments = vec![
+            segment("nothing here", 0.0, 4.0, None),
+            segment("abcd budget efgh", 20.0, 36.0, None),
+        ];
+        let hits = search_transcript(&segments, "budget", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].segment_index, 1);
+        assert!(!hits[0].word_level);
+        assert_eq!((hits[0].start, hits[0].end), (25.0, 31.0));
+        assert!(search_transcript(&segments, "  ", 10).is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+// Universal search - Ranked command palette search over a per-user in-memory index
+// The index is built from parallel Firestore reads and refreshed in the background, so
+// keystroke queries never wait on Firestore once it's warm. Apps come from the apps cache.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::models::{AppSummary, SearchResultKind, UniversalSearchResult};
+use crate::services::firestore::{carry_project_override, FirestoreError};
+use crate::services::FirestoreService;
+
+/// Indexes younger than this are served as they are
+const INDEX_FRESH_FOR: Duration = Duration::from_secs(30);
+/// Older indexes are rebuilt before answering; in between they're served while a rebuild runs
+const INDEX_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+/// Items of each kind read into an index
+const MAX_INDEXED_PER_KIND: usize = 500;
+/// Longest subtitle kept per entry, in characters
+const MAX_SUBTITLE_CHARS: usize = 140;
+
+/// One searchable item
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    pub kind: SearchResultKind,
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    title_lower: String,
+    body_lower: String,
+}
+
+impl SearchEntry {
+    pub fn new(
+        kind: SearchResultKind,
+        id: &str,
+        title: &str,
+        body: &str,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Self {
+        let subtitle: String = body.trim().chars().take(MAX_SUBTITLE_CHARS).collect();
+        Self {
+            kind,
+            id: id.to_string(),
+            title: title.to_string(),
+            subtitle: (!subtitle.is_empty()).then_some(subtitle),
+            timestamp,
+            title_lower: title.to_lowercase(),
+            body_lower: body.to_lowercase(),
+        }
+    }
+
+    fn from_app(app: &AppSummary) -> Self {
+        Self::new(SearchResultKind::App, &app.id, &app.name, &app.description, None)
+    }
+}
+
+/// Relevance of an entry, or None unless every term matches. Title word prefixes
+/// (what someone typing into a palette hits first) beat title substrings, which beat
+/// body matches; a title starting with the whole query and recent items get a boost.
+pub fn score_entry(entry: &SearchEntry, query: &str, terms: &[&str], now: DateTime<Utc>) -> Option<f64> {
+    let mut score = 0.0;
+    for term in terms {
+        score += if entry.title_lower.split_whitespace().any(|w| w.starts_with(term)) {
+            3.0
+        } else if entry.title_lower.contains(term) {
+            2.0
+        } else if entry.body_lower.contains(term) {
+            1.0
+        } else {
+            return None;
+        };
+    }
+    if entry.title_lower.starts_with(query) {
+        score += 2.0;
+    }
+    if let Some(timestamp) = entry.timestamp {
+        let age_days = (now - timestamp).num_days().max(0) as f64;
+        score += 1.0 / (1.0 + age_days / 30.0);
+    }
+    Some(score)
+}
+
+/// Best matches among `entries`, highest score first (newest first on ties)
+pub fn rank<'a>(
+    entries: impl Iterator<Item = &'a SearchEntry>,
+    query: &str,
+    kinds: &[SearchResultKind],
+    limit: usize,
+    now: DateTime<Utc>,
+) -> Vec<UniversalSearchResult> {
+    let query = query.trim().to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut scored: Vec<(f64, &SearchEntry)> = entries
+        .filter(|e| kinds.is_empty() || kinds.contains(&e.kind))
+        .filter_map(|e| score_entry(e, &query, &terms, now).map(|score| (score, e)))
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.total_cmp(a_score).then_with(|| b.timestamp.cmp(&a.timestamp))
+    });
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(score, e)| UniversalSearchResult {
+            kind: e.kind,
+            id: e.id.clone(),
+            title: e.title.clone(),
+            subtitle: e.subtitle.clone(),
+            deep_link: e.kind.deep_link(&e.id),
+            timestamp: e.timestamp,
+            score: (score * 100.0).round() / 100.0,
+        })
+        .collect()
+}
+
+struct SearchIndex {
+    entries: Vec<SearchEntry>,
+    built_at: Instant,
+}
+
+/// Per-user search indexes, kept in memory on this instance
+#[derive(Clone, Default)]
+pub struct SearchIndexCache {
+    inner: Arc<RwLock<HashMap<String, Arc<SearchIndex>>>>,
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SearchIndexCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Search the user's index (building it on a miss) plus the cached apps
+    pub async fn search(
+        &self,
+        firestore: &Arc<FirestoreService>,
+        uid: &str,
+        query: &str,
+        kinds: &[SearchResultKind],
+        limit: usize,
+    ) -> Vec<UniversalSearchResult> {
+        let want_apps = kinds.is_empty() || kinds.contains(&SearchResultKind::App);
+        let (index, apps) = tokio::join!(self.index(firestore, uid), async {
+            if !want_apps {
+                return Vec::new();
+            }
+            match firestore.approved_apps_snapshot().await {
+                Ok(apps) => apps.iter().map(SearchEntry::from_app).collect(),
+                Err(e) => {
+                    tracing::warn!("Universal search: failed to load apps: {}", e);
+                    Vec::new()
+                }
+            }
+        });
+        rank(index.entries.iter().chain(apps.iter()), query, kinds, limit, Utc::now())
+    }
+
+    /// Drop a user's index so the next search rebuilds it
+    pub async fn invalidate(&self, uid: &str) {
+        self.inner.write().await.remove(uid);
+    }
+
+    async fn index(&self, firestore: &Arc<FirestoreService>, uid: &str) -> Arc<SearchIndex> {
+        let cached = self.inner.read().await.get(uid).cloned();
+        match cached {
+            Some(index) if index.built_at.elapsed() < INDEX_FRESH_FOR => index,
+            Some(index) if index.built_at.elapsed() < INDEX_MAX_AGE => {
+                self.spawn_rebuild(firestore.clone(), uid);
+                index
+            }
+            _ => self.rebuild(firestore, uid).await,
+        }
+    }
+
+    fn spawn_rebuild(&self, firestore: Arc<FirestoreService>, uid: &str) {
+        if !self.refreshing.lock().unwrap().insert(uid.to_string()) {
+            return;
+        }
+        let cache = self.clone();
+        let uid = uid.to_string();
+        tokio::spawn(carry_project_override(async move {
+            cache.rebuild(&firestore, &uid).await;
+            cache.refreshing.lock().unwrap().remove(&uid);
+        }));
+    }
+
+    async fn rebuild(&self, firestore: &FirestoreService, uid: &str) -> Arc<SearchIndex> {
+        let index = Arc::new(SearchIndex {
+            entries: build_entries(firestore, uid).await,
+            built_at: Instant::now(),
+        });
+        let mut map = self.inner.write().await;
+        map.retain(|_, i| i.built_at.elapsed() < INDEX_MAX_AGE);
+        map.insert(uid.to_string(), index.clone());
+        index
+    }
+}
+
+/// Read the user's conversations, memories, action items and folders in parallel.
+/// A source that fails to load is left out (with a warning) rather than failing the search.
+async fn build_entries(firestore: &FirestoreService, uid: &str) -> Vec<SearchEntry> {
+    let started = Instant::now();
+    let completed = ["completed".to_string()];
+    let (conversations, memories, action_items, folders) = tokio::join!(
+        firestore.get_conversations(uid, MAX_INDEXED_PER_KIND, 0, false, &completed, None, None, None, None),
+        firestore.get_memories(uid, MAX_INDEXED_PER_KIND),
+        firestore.get_action_items(uid, MAX_INDEXED_PER_KIND, 0, None, None, None, None, None, None, None, None, true),
+        firestore.get_folders(uid),
+    );
+    fn loaded<T>(uid: &str, source: &str, result: Result<Vec<T>, FirestoreError>) -> Vec<T> {
+        result.unwrap_or_else(|e| {
+            tracing::warn!("Universal search: failed to load {} for user {}: {}", source, uid, e);
+            Vec::new()
+        })
+    }
+
+    let mut entries = Vec::new();
+    for c in loaded(uid, "conversations", conversations).iter().filter(|c| !c.deleted) {
+        entries.push(SearchEntry::new(
+            SearchResultKind::Conversation,
+            &c.id,
+            &c.structured.title,
+            &c.structured.overview,
+            Some(c.started_at),
+        ));
+    }
+    for m in loaded(uid, "memories", memories).iter().filter(|m| !m.is_dismissed) {
+        entries.push(SearchEntry::new(SearchResultKind::Memory, &m.id, &m.content, "", Some(m.created_at)));
+    }
+    for item in loaded(uid, "action items", action_items) {
+        let status = if item.completed { "Completed" } else { "Open" };
+        entries.push(SearchEntry::new(
+            SearchResultKind::ActionItem,
+            &item.id,
+            &item.description,
+            status,
+            Some(item.created_at),
+        ));
+    }
+    for f in loaded(uid, "folders", folders) {
+        entries.push(SearchEntry::new(
+            SearchResultKind::Folder,
+            &f.id,
+            &f.name,
+            f.description.as_deref().unwrap_or_default(),
+            None,
+        ));
+    }
+    tracing::debug!(
+        "Built search index for user {} with {} entries in {}ms",
+        uid,
+        entries.len(),
+        started.elapsed().as_millis()
+    );
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_rank() {
+        let now = Utc::now();
+        let entries = [
+            SearchEntry::new(SearchResultKind::Conversation, "c1", "Budget review with Dana", "Went over Q3 numbers", Some(now - Duration::days(2))),
+            SearchEntry::new(SearchResultKind::ActionItem, "a1", "Send the budget to finance", "Open", Some(now)),
+            SearchEntry::new(SearchResultKind::Memory, "m1", "Dana prefers written agendas", "", Some(now - Duration::days(300))),
+            SearchEntry::new(SearchResultKind::Folder, "f1", "Finance", "", None),
+        ];
+
+        // Title starting with the query beats a mid-title word, which beats a body match
+        let ids = |results: Vec<UniversalSearchResult>| results.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(rank(entries.iter(), "bud", &[], 10, now)), vec!["c1", "a1"]);
+        assert_eq!(ids(rank(entries.iter(), "fin", &[], 10, now)), vec!["f1", "a1"]);
+        assert_eq!(ids(rank(entries.iter(), "dana", &[], 10, now)), vec!["m1", "c1"]);
+        // Every term has to match somewhere
+        assert_eq!(ids(rank(entries.iter(), "dana q3", &[], 10, now)), vec!["c1"]);
+        assert_eq!(ids(rank(entries.iter(), "budget", &[SearchResultKind::ActionItem], 10, now)), vec!["a1"]);
+        assert!(rank(entries.iter(), "  ", &[], 10, now).is_empty());
+
+        let result = &rank(entries.iter(), "budget", &[], 1, now)[0];
+        assert_eq!(result.deep_link, "omi-computer://conversation/c1");
+    }
+}
@@ -0,0 +1,94 @@
+// Upload spooling - Stream multipart file fields to temp files instead of memory
+// Size limits are enforced while streaming; the file is then streamed on to GCS.
+
+use axum::body::Bytes;
+use axum::extract::multipart::Field;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Read buffer when streaming a spooled file out
+const SPOOL_READ_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum SpoolError {
+    /// The field exceeded the size limit
+    TooLarge,
+    /// The client sent a malformed or truncated body
+    Read(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SpoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpoolError::TooLarge => write!(f, "upload exceeds the size limit"),
+            SpoolError::Read(e) => write!(f, "failed to read upload: {}", e),
+            SpoolError::Io(e) => write!(f, "failed to spool upload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpoolError {}
+
+/// An upload written to a temp file; the file is removed on drop
+pub struct SpooledFile {
+    path: PathBuf,
+    pub size: u64,
+}
+
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove spooled upload {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl SpooledFile {
+    /// Request body streaming the file contents (keep `self` alive until sent)
+    pub async fn body(&self) -> Result<reqwest::Body, SpoolError> {
+        let file = tokio::fs::File::open(&self.path).await.map_err(SpoolError::Io)?;
+        let stream = futures::stream::try_unfold(file, |mut file| async move {
+            let mut buf = vec![0u8; SPOOL_READ_CHUNK_BYTES];
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                return Ok::<_, std::io::Error>(None);
+            }
+            buf.truncate(n);
+            Ok(Some((Bytes::from(buf), file)))
+        });
+        Ok(reqwest::Body::wrap_stream(stream))
+    }
+}
+
+/// A new, empty temp file and a handle for writing it (for files the server builds itself)
+pub async fn spool_writer(prefix: &str) -> Result<(SpooledFile, tokio::fs::File), SpoolError> {
+    let path = std::env::temp_dir().join(format!("{}-{}", prefix, uuid::Uuid::new_v4()));
+    let file = tokio::fs::File::create(&path).await.map_err(SpoolError::Io)?;
+    Ok((SpooledFile { path, size: 0 }, file))
+}
+
+/// Stream a multipart field to a temp file, failing as soon as it passes `max_bytes`
+pub async fn spool_field(field: &mut Field<'_>, max_bytes: u64) -> Result<SpooledFile, SpoolError> {
+    let path = std::env::temp_dir().join(format!("omi-upload-{}", uuid::Uuid::new_v4()));
+    let mut file = tokio::fs::File::create(&path).await.map_err(SpoolError::Io)?;
+    // Created first so the temp file is cleaned up on every error path
+    let mut spooled = SpooledFile { path, size: 0 };
+
+    while let Some(chunk) = field.chunk().await.map_err(|e| {
+        if e.status() == axum::http::StatusCode::PAYLOAD_TOO_LARGE {
+            SpoolError::TooLarge
+        } else {
+            SpoolError::Read(e.body_text())
+        }
+    })? {
+        spooled.size += chunk.len() as u64;
+        if spooled.size > max_bytes {
+            return Err(SpoolError::TooLarge);
+        }
+        file.write_all(&chunk).await.map_err(SpoolError::Io)?;
+    }
+    file.flush().await.map_err(SpoolError::Io)?;
+
+    Ok(spooled)
+}
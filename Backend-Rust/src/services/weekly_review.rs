@@ -0,0 +1,248 @@
+// Weekly review service - LLM-written look back over a week of tasks, goals and focus
+// Generated on request, and on Sunday evening (user's local time) for users who opt in
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::job_queue::JobQueue;
+use crate::llm::{LlmClient, TaskModels};
+use crate::models::{ActionItemDB, NotificationKind, ReviewItem, WeeklyReview};
+use crate::services::rollover::local_to_utc;
+use crate::services::{llm_keys, notifications, FirestoreService};
+
+/// How often to check which users are due for their weekly review
+const WEEKLY_REVIEW_CHECK_INTERVAL_MINUTES: u64 = 30;
+/// Max action items read for each side of the review
+const MAX_REVIEW_ITEMS: usize = 500;
+/// Items listed per side in the prompt; the stored review keeps them all
+const MAX_PROMPT_ITEMS: usize = 40;
+
+/// Monday of the week containing `date`
+pub fn week_start_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Week reviewed when none is requested: the current week on Sundays, otherwise the
+/// previous one
+pub fn default_week_start(today: NaiveDate) -> NaiveDate {
+    if today.weekday() == Weekday::Sun {
+        week_start_of(today)
+    } else {
+        week_start_of(today) - Duration::days(7)
+    }
+}
+
+/// UTC bounds [start, end) of the week starting on `week_start` in `tz`
+pub fn week_bounds(tz: Tz, week_start: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        local_to_utc(tz, week_start, NaiveTime::MIN),
+        local_to_utc(tz, week_start + Duration::days(7), NaiveTime::MIN),
+    )
+}
+
+/// Build and store the review for one week. `tz` sets the week boundaries.
+pub async fn generate_weekly_review(
+    firestore: &FirestoreService,
+    llm: &LlmClient,
+    uid: &str,
+    week_start: NaiveDate,
+    tz: Tz,
+    scheduled: bool,
+) -> Result<WeeklyReview, Box<dyn std::error::Error + Send + Sync>> {
+    let (start, end) = week_bounds(tz, week_start);
+    // Items still due later this week haven't slipped yet
+    let slip_cutoff = end.min(Utc::now());
+
+    let accomplished: Vec<ReviewItem> = firestore
+        .get_action_items(uid, MAX_REVIEW_ITEMS, 0, Some(true), None, None, None, None, None, None, None, true)
+        .await?
+        .into_iter()
+        .filter(|item| item.completed_at.is_some_and(|at| at >= start && at < end))
+        .map(review_item)
+        .collect();
+
+    let (start_str, cutoff_str) = (start.to_rfc3339(), slip_cutoff.to_rfc3339());
+    let slipped: Vec<ReviewItem> = firestore
+        .get_action_items(uid, MAX_REVIEW_ITEMS, 0, Some(false), None, None, None, Some(&start_str), Some(&cutoff_str), None, None, true)
+        .await?
+        .into_iter()
+        .filter(|item| !item.deleted.unwrap_or(false))
+        .filter(|item| item.due_at.is_some_and(|due| due >= start && due < slip_cutoff))
+        .map(review_item)
+        .collect();
+
+    let days: Vec<String> = (0..7)
+        .map(|d| (week_start + Duration::days(d)).format("%Y-%m-%d").to_string())
+        .collect();
+    let (mut focused_minutes, mut distracted_minutes) = (0i64, 0i64);
+    for stats in futures::future::join_all(days.iter().map(|day| firestore.get_focus_stats(uid, day)))
+        .await
+        .into_iter()
+        .flatten()
+    {
+        focused_minutes += stats.focused_minutes;
+        distracted_minutes += stats.distracted_minutes;
+    }
+    let focus = if focused_minutes + distracted_minutes > 0 {
+        format!("{} focused minutes, {} distracted minutes", focused_minutes, distracted_minutes)
+    } else {
+        "(No focus data)".to_string()
+    };
+
+    let goals = firestore.get_user_goals(uid, 10).await.unwrap_or_default();
+    let descriptions = |items: &[ReviewItem]| -> Vec<String> {
+        items
+            .iter()
+            .take(MAX_PROMPT_ITEMS)
+            .map(|item| item.description.clone())
+            .collect()
+    };
+    let week = format!(
+        "{} to {}",
+        week_start.format("%b %-d"),
+        (week_start + Duration::days(6)).format("%b %-d, %Y")
+    );
+    let narrative = llm
+        .generate_weekly_review(&week, &goals, &descriptions(&accomplished), &descriptions(&slipped), &focus)
+        .await?;
+
+    let review = WeeklyReview {
+        week_start,
+        timezone: tz.name().to_string(),
+        accomplished,
+        slipped,
+        focused_minutes,
+        distracted_minutes,
+        focus_summary: narrative.focus_summary,
+        suggested_priorities: narrative.suggested_priorities,
+        scheduled,
+        generated_at: Utc::now(),
+    };
+    firestore.save_weekly_review(uid, &review).await?;
+    Ok(review)
+}
+
+fn review_item(item: ActionItemDB) -> ReviewItem {
+    ReviewItem {
+        action_item_id: item.id,
+        description: item.description,
+        due_at: item.due_at,
+        completed_at: item.completed_at,
+    }
+}
+
+/// Spawn the weekly review scheduler. No-op without a Gemini key.
+pub fn spawn_weekly_review_job(firestore: Arc<FirestoreService>, config: Arc<Config>, jobs: JobQueue) {
+    let Some(api_key) = config.gemini_api_key.clone() else {
+        tracing::warn!("GEMINI_API_KEY not set - scheduled weekly reviews disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            WEEKLY_REVIEW_CHECK_INTERVAL_MINUTES * 60,
+        ));
+        loop {
+            interval.tick().await;
+            let models = firestore.llm_task_models(&config.llm_models).await;
+            let llm = LlmClient::new(api_key.clone()).with_task_models(models.clone());
+            run_weekly_review_pass(&firestore, &llm, &models, &jobs).await;
+        }
+    });
+
+    tracing::info!(
+        "Weekly review scheduler checking every {} minutes",
+        WEEKLY_REVIEW_CHECK_INTERVAL_MINUTES
+    );
+}
+
+/// Queue a review of the current week for every opted-in user; each runs only once the
+/// user's Sunday hour has passed
+async fn run_weekly_review_pass(firestore: &Arc<FirestoreService>, llm: &LlmClient, models: &TaskModels, jobs: &JobQueue) {
+    let uids = match firestore.get_users_with_weekly_review_enabled().await {
+        Ok(uids) => uids,
+        Err(e) => {
+            tracing::error!("Weekly review: failed to list users: {}", e);
+            return;
+        }
+    };
+
+    for uid in uids {
+        let (firestore, llm, models) = (firestore.clone(), llm.clone(), models.clone());
+        jobs.submit_for_user(&uid, "weekly-review", |uid| async move {
+            review_user_if_due(&firestore, &llm, &models, &uid).await
+        });
+    }
+}
+
+/// Generate this week's review for one user if it's Sunday past their hour and not done yet
+async fn review_user_if_due(
+    firestore: &FirestoreService,
+    llm: &LlmClient,
+    models: &TaskModels,
+    uid: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let settings = firestore.get_weekly_review_settings(uid).await?;
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let tz: Tz = firestore
+        .get_user_profile(uid)
+        .await?
+        .time_zone
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(Tz::UTC);
+
+    let local_now = Utc::now().with_timezone(&tz);
+    let today = local_now.date_naive();
+    let week_start = week_start_of(today);
+    let week_str = week_start.format("%Y-%m-%d").to_string();
+
+    if today.weekday() != Weekday::Sun
+        || (local_now.hour() as i32) < settings.hour
+        || settings.last_reviewed_week.as_deref() == Some(week_str.as_str())
+    {
+        return Ok(());
+    }
+
+    // A saved key that can't be read fails this user's review instead of using the server key
+    let user_llm = llm_keys::user_client(firestore, uid, models).await?;
+    generate_weekly_review(firestore, user_llm.as_ref().unwrap_or(llm), uid, week_start, tz, true).await?;
+    firestore.set_weekly_review_last_week(uid, &week_str).await?;
+
+    let text = format!("Your weekly review for the week of {} is ready.", week_start.format("%b %-d"));
+    if let Err(e) = firestore.save_message(uid, &text, "ai", None, None, None).await {
+        tracing::error!("Failed to notify user {} about weekly review: {}", uid, e);
+    }
+    let link = Some(format!("/reviews/weekly/{}", week_str));
+    if let Err(e) = notifications::notify(firestore, uid, NotificationKind::Summary, "Weekly review", &text, link).await {
+        tracing::error!("Failed to add weekly review notification for user {}: {}", uid, e);
+    }
+    tracing::info!("Generated scheduled weekly review for user {} ({})", uid, week_str);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_week_boundaries() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        // 2026-03-04 is a Wednesday, 2026-03-08 a Sunday
+        assert_eq!(week_start_of(date(4)), date(2));
+        assert_eq!(week_start_of(date(2)), date(2));
+        assert_eq!(default_week_start(date(4)), NaiveDate::from_ymd_opt(2026, 2, 23).unwrap());
+        assert_eq!(default_week_start(date(8)), date(2));
+
+        // The week spans the DST change on 2026-03-08
+        let tz: Tz = "America/Los_Angeles".parse().unwrap();
+        let (start, end) = week_bounds(tz, date(2));
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2026, 3, 9, 7, 0, 0).unwrap());
+    }
+}